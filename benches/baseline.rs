@@ -0,0 +1,230 @@
+//! # YaoXiang 基准对照
+//!
+//! 用其他运行时执行等价程序，报告 YaoXiang 在每个基准上的相对倍数，
+//! 让虚拟机的性能目标有一个具体的参照。
+//!
+//! 测量指标：单次完整运行耗时（wall time，含编译/加载）
+//! 基准线：
+//! - 内嵌 Lua 5.4（`bench-lua` feature，现场测量）
+//! - `benches/baseline/timings.toml` 中预录制的耗时（如 Python，由 `record.py` 录制）
+//!
+//! ## 使用方法
+//! ```bash
+//! cargo bench --features bench-baseline --bench baseline  # YaoXiang vs 预录制耗时
+//! cargo bench --features bench-lua --bench baseline       # 额外内嵌 Lua 现场对比
+//! ```
+//!
+//! 倍数 = YaoXiang 耗时 / 基准线耗时，大于 1 表示 YaoXiang 更慢。
+
+use std::collections::BTreeMap;
+use std::hint::black_box;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use criterion::{criterion_group, Criterion};
+
+/// 对照程序：(基准名, YaoXiang 源码, Lua 源码)
+///
+/// 各语言版本位于 `benches/baseline/`，与 `benches/yx_benchmarks/` 一一对应。
+const PROGRAMS: &[(&str, &str, &str)] = &[
+    (
+        "fibonacci",
+        include_str!("yx_benchmarks/fibonacci.yx"),
+        include_str!("baseline/fibonacci.lua"),
+    ),
+    (
+        "matrix",
+        include_str!("yx_benchmarks/matrix.yx"),
+        include_str!("baseline/matrix.lua"),
+    ),
+    (
+        "list_ops",
+        include_str!("yx_benchmarks/list_ops.yx"),
+        include_str!("baseline/list_ops.lua"),
+    ),
+    (
+        "string_concat",
+        include_str!("yx_benchmarks/string_concat.yx"),
+        include_str!("baseline/string_concat.lua"),
+    ),
+];
+
+/// 预录制的其他运行时耗时（微秒）
+const RECORDED_TIMINGS: &str = include_str!("baseline/timings.toml");
+
+/// 测量结果：基准名 -> 运行时 -> 累计 (耗时, 迭代次数)
+type Measurements = BTreeMap<&'static str, BTreeMap<&'static str, (Duration, u64)>>;
+
+/// 本次运行的测量结果，供最后的汇总表使用
+static MEASURED: Mutex<Measurements> = Mutex::new(BTreeMap::new());
+
+/// 累计一次 `iter_custom` 的测量结果，用于最后计算平均耗时
+fn record(
+    bench: &'static str,
+    runtime: &'static str,
+    elapsed: Duration,
+    iters: u64,
+) {
+    let mut measured = MEASURED.lock().unwrap();
+    let entry = measured
+        .entry(bench)
+        .or_default()
+        .entry(runtime)
+        .or_default();
+    entry.0 += elapsed;
+    entry.1 += iters;
+}
+
+/// 计时运行 `iters` 次，并记录到对照表
+fn timed(
+    bench: &'static str,
+    runtime: &'static str,
+    iters: u64,
+    mut run: impl FnMut(),
+) -> Duration {
+    let start = Instant::now();
+    for _ in 0..iters {
+        run();
+    }
+    let elapsed = start.elapsed();
+    record(bench, runtime, elapsed, iters);
+    elapsed
+}
+
+// ============================================================================
+// Baseline Benchmarks - 对照测量
+// ============================================================================
+
+fn bench_baseline(c: &mut Criterion) {
+    let mut group = c.benchmark_group("baseline");
+    for &(name, yx_source, _lua_source) in PROGRAMS {
+        group.bench_function(format!("{}/yaoxiang", name), |b| {
+            b.iter_custom(|iters| {
+                timed(name, "yaoxiang", iters, || {
+                    yaoxiang::run(black_box(yx_source)).expect("YaoXiang execution failed");
+                })
+            })
+        });
+
+        #[cfg(feature = "bench-lua")]
+        group.bench_function(format!("{}/lua", name), |b| {
+            b.iter_custom(|iters| {
+                timed(name, "lua", iters, || {
+                    // 每次新建 Lua 状态，与 yaoxiang::run 的完整运行口径一致
+                    let lua = mlua::Lua::new();
+                    lua.load(black_box(_lua_source))
+                        .set_name(name)
+                        .exec()
+                        .expect("Lua execution failed");
+                })
+            })
+        });
+    }
+    group.finish();
+}
+
+// ============================================================================
+// Report - 相对倍数汇总
+// ============================================================================
+
+/// 读取预录制耗时：运行时 -> (描述, 基准名 -> 微秒)
+fn recorded_timings() -> BTreeMap<String, (String, BTreeMap<String, f64>)> {
+    let table: toml::Table = RECORDED_TIMINGS
+        .parse()
+        .expect("benches/baseline/timings.toml is not valid TOML");
+    table
+        .into_iter()
+        .filter_map(|(runtime, section)| {
+            let section = section.as_table()?.clone();
+            let label = section
+                .get("runtime")
+                .and_then(|v| v.as_str())
+                .unwrap_or(&runtime)
+                .to_string();
+            let timings = section
+                .iter()
+                .filter_map(|(bench, v)| {
+                    let micros = v.as_float().or_else(|| v.as_integer().map(|i| i as f64))?;
+                    Some((bench.clone(), micros))
+                })
+                .collect();
+            Some((runtime, (label, timings)))
+        })
+        .collect()
+}
+
+/// 打印每个基准相对于各基准线的倍数
+fn report() {
+    let measured = MEASURED.lock().unwrap();
+    if measured.is_empty() {
+        return;
+    }
+    let recorded = recorded_timings();
+
+    println!();
+    println!("Baseline comparison (mean per run, slowdown = yaoxiang / baseline)");
+    println!(
+        "{:<16} {:>14} {:<26} {:>14} {:>10}",
+        "benchmark", "yaoxiang", "baseline", "time", "slowdown"
+    );
+    for &(name, _, _) in PROGRAMS {
+        let Some(runs) = measured.get(name) else {
+            continue;
+        };
+        let Some(yx) = runs.get("yaoxiang").and_then(|&m| mean_micros(m)) else {
+            continue;
+        };
+
+        let mut baselines: Vec<(String, f64)> = runs
+            .iter()
+            .filter(|(runtime, _)| **runtime != "yaoxiang")
+            .filter_map(|(runtime, &m)| Some((format!("{} (measured)", runtime), mean_micros(m)?)))
+            .collect();
+        baselines.extend(recorded.values().filter_map(|(label, timings)| {
+            Some((format!("{} (recorded)", label), *timings.get(name)?))
+        }));
+
+        if baselines.is_empty() {
+            println!("{:<16} {:>14}", name, format_micros(yx));
+        }
+        for (i, (label, base)) in baselines.iter().enumerate() {
+            let (bench, yx_time) = if i == 0 {
+                (name, format_micros(yx))
+            } else {
+                ("", String::new())
+            };
+            println!(
+                "{:<16} {:>14} {:<26} {:>14} {:>9.2}x",
+                bench,
+                yx_time,
+                label,
+                format_micros(*base),
+                yx / base
+            );
+        }
+    }
+}
+
+fn mean_micros((total, iters): (Duration, u64)) -> Option<f64> {
+    (iters > 0).then(|| total.as_secs_f64() * 1e6 / iters as f64)
+}
+
+fn format_micros(micros: f64) -> String {
+    if micros >= 1000.0 {
+        format!("{:.2} ms", micros / 1000.0)
+    } else {
+        format!("{:.1} µs", micros)
+    }
+}
+
+criterion_group!(
+    name = baseline;
+    config = Criterion::default().sample_size(10);
+    targets = bench_baseline
+);
+
+fn main() {
+    baseline();
+    Criterion::default().configure_from_args().final_summary();
+    report();
+}
@@ -0,0 +1,16 @@
+-- fibonacci_iterative - 迭代斐波那契（对应 benches/yx_benchmarks/fibonacci.yx）
+
+local function fibonacci(n)
+    if n <= 1 then
+        return n
+    end
+    local a, b, result = 0, 1, 0
+    for _ = 2, n do
+        result = a + b
+        a = b
+        b = result
+    end
+    return result
+end
+
+fibonacci(20)
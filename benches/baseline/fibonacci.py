@@ -0,0 +1,15 @@
+"""fibonacci_iterative - 迭代斐波那契（对应 benches/yx_benchmarks/fibonacci.yx）"""
+
+
+def fibonacci(n):
+    if n <= 1:
+        return n
+    a, b, result = 0, 1, 0
+    for _ in range(2, n + 1):
+        result = a + b
+        a = b
+        b = result
+    return result
+
+
+fibonacci(20)
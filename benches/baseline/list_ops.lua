@@ -0,0 +1,28 @@
+-- list_operations - 列表操作（对应 benches/yx_benchmarks/list_ops.yx）
+
+local function is_even(n)
+    return n % 2 == 0
+end
+
+local function double(n)
+    return n * 2
+end
+
+local result = 0
+for _ = 1, 100 do
+    local nums = {}
+    for j = 0, 999 do
+        nums[#nums + 1] = j
+    end
+    local filtered = {}
+    for _, n in ipairs(nums) do
+        if is_even(n) then
+            filtered[#filtered + 1] = n
+        end
+    end
+    local doubled = {}
+    for _, n in ipairs(filtered) do
+        doubled[#doubled + 1] = double(n)
+    end
+    result = result + doubled[1]
+end
@@ -0,0 +1,19 @@
+"""list_operations - 列表操作（对应 benches/yx_benchmarks/list_ops.yx）"""
+
+
+def is_even(n):
+    return n % 2 == 0
+
+
+def double(n):
+    return n * 2
+
+
+result = 0
+for _ in range(100):
+    nums = []
+    for j in range(1000):
+        nums.append(j)
+    filtered = [n for n in nums if is_even(n)]
+    doubled = [double(n) for n in filtered]
+    result = result + doubled[0]
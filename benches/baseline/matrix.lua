@@ -0,0 +1,33 @@
+-- matrix_multiply - 矩阵乘法（对应 benches/yx_benchmarks/matrix.yx）
+
+local function create_matrix(size)
+    local matrix = {}
+    for i = 0, size - 1 do
+        local row = {}
+        for j = 0, size - 1 do
+            row[#row + 1] = i * j
+        end
+        matrix[#matrix + 1] = row
+    end
+    return matrix
+end
+
+local function multiply(a, b, size)
+    local result = {}
+    for i = 1, size do
+        local row = {}
+        for j = 1, size do
+            local sum = 0
+            for k = 1, size do
+                sum = sum + a[i][k] * b[k][j]
+            end
+            row[#row + 1] = sum
+        end
+        result[#result + 1] = row
+    end
+    return result
+end
+
+local size = 20
+local c = multiply(create_matrix(size), create_matrix(size), size)
+return c[1][1]
@@ -0,0 +1,29 @@
+"""matrix_multiply - 矩阵乘法（对应 benches/yx_benchmarks/matrix.yx）"""
+
+
+def create_matrix(size):
+    matrix = []
+    for i in range(size):
+        row = []
+        for j in range(size):
+            row.append(i * j)
+        matrix.append(row)
+    return matrix
+
+
+def multiply(a, b, size):
+    result = []
+    for i in range(size):
+        row = []
+        for j in range(size):
+            s = 0
+            for k in range(size):
+                s = s + a[i][k] * b[k][j]
+            row.append(s)
+        result.append(row)
+    return result
+
+
+size = 20
+c = multiply(create_matrix(size), create_matrix(size), size)
+c[0][0]
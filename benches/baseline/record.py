@@ -0,0 +1,39 @@
+#!/usr/bin/env python3
+"""录制 Python 参照耗时，输出 timings.toml 中的 [python3] 段
+
+每次运行都重新编译并执行整个脚本，与 YaoXiang 基准（`yaoxiang::run` 含编译）口径一致。
+
+用法: python3 benches/baseline/record.py，输出替换 timings.toml 中的 [python3] 段
+"""
+
+import platform
+import sys
+import timeit
+from pathlib import Path
+
+BENCHMARKS = ["fibonacci", "matrix", "list_ops", "string_concat"]
+
+
+def mean_micros(path: Path) -> float:
+    source = path.read_text(encoding="utf-8")
+
+    def run():
+        exec(compile(source, str(path), "exec"), {"__name__": "__bench__"})
+
+    timer = timeit.Timer(run)
+    number, _ = timer.autorange()
+    best = min(timer.repeat(repeat=5, number=number))
+    return best / number * 1e6
+
+
+def main() -> None:
+    here = Path(__file__).parent
+    print("[python3]")
+    print(f'runtime = "Python {platform.python_version()}"')
+    print(f'machine = "{platform.processor() or platform.machine()}"')
+    for name in BENCHMARKS:
+        print(f"{name} = {mean_micros(here / f'{name}.py'):.1f}")
+
+
+if __name__ == "__main__":
+    sys.exit(main())
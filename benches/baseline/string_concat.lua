@@ -0,0 +1,9 @@
+-- string_concat - 字符串拼接（对应 benches/yx_benchmarks/string_concat.yx）
+
+local result = ""
+for _ = 1, 500 do
+    result = result .. "Hello"
+    result = result .. " "
+    result = result .. "World"
+    result = result .. "!"
+end
@@ -0,0 +1,8 @@
+"""string_concat - 字符串拼接（对应 benches/yx_benchmarks/string_concat.yx）"""
+
+result = ""
+for _ in range(500):
+    result = result + "Hello"
+    result = result + " "
+    result = result + "World"
+    result = result + "!"
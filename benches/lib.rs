@@ -6,17 +6,99 @@
 //! - `micro`: Rust 微基准测试（CPU 运算）
 //! - `yaoxiang`: YaoXiang 解释器性能测试
 //! - `interpreter`: 解释器性能测试
-//! - `codegen`: 编译器效率测试
+//! - `codegen`: 编译器效率测试（大模块另外报告一次编译的分配次数与内存峰值）
+//! - `dispatch`: 解释器指令分派方式对比（逐帧直接循环 vs 单步路径）
+//! - `parallel`: 互不依赖的任务在不同线程数的工作窃取执行器上的耗时
 //!
 //! ## 使用方法
 //! ```bash
 //! cargo bench          # 运行所有
 //! cargo bench micro    # 只运行微基准
 //! cargo bench yaoxiang # 只运行 YaoXiang 测试
+//! cargo bench compile  # 只运行编译期基准
+//! cargo bench dispatch # 只运行分派方式对比
+//! cargo bench parallel # 只运行多线程执行器对比
 //! ```
+//!
+//! 与其他运行时（内嵌 Lua、预录制的 Python 耗时）的对照见 `benches/baseline.rs`，
+//! 需显式开启 `bench-baseline` feature。
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 use criterion::{criterion_group, criterion_main, Criterion};
 
+// ============================================================================
+// 分配统计 - 编译期基准据此报告分配次数与内存峰值
+// ============================================================================
+
+struct CountingAlloc;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+static LIVE_BYTES: AtomicUsize = AtomicUsize::new(0);
+static PEAK_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+impl CountingAlloc {
+    fn record(size: usize) {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(size, Ordering::Relaxed);
+        let live = LIVE_BYTES.fetch_add(size, Ordering::Relaxed) + size;
+        PEAK_BYTES.fetch_max(live, Ordering::Relaxed);
+    }
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(
+        &self,
+        layout: Layout,
+    ) -> *mut u8 {
+        Self::record(layout.size());
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(
+        &self,
+        ptr: *mut u8,
+        layout: Layout,
+    ) {
+        LIVE_BYTES.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(
+        &self,
+        ptr: *mut u8,
+        layout: Layout,
+        new_size: usize,
+    ) -> *mut u8 {
+        LIVE_BYTES.fetch_sub(layout.size(), Ordering::Relaxed);
+        Self::record(new_size);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+/// 运行一次 `f`，打印期间的分配次数、分配总量与相对起点的内存峰值
+fn report_allocations<T>(
+    name: &str,
+    f: impl FnOnce() -> T,
+) {
+    let base = LIVE_BYTES.load(Ordering::Relaxed);
+    PEAK_BYTES.store(base, Ordering::Relaxed);
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let bytes = ALLOCATED_BYTES.load(Ordering::Relaxed);
+    drop(f());
+    println!(
+        "{name}: {} allocations, {} KiB allocated, {} KiB peak",
+        ALLOCATIONS.load(Ordering::Relaxed) - allocations,
+        (ALLOCATED_BYTES.load(Ordering::Relaxed) - bytes) / 1024,
+        (PEAK_BYTES.load(Ordering::Relaxed) - base) / 1024,
+    );
+}
+
 // ============================================================================
 // Micro Benchmarks - Rust 底层运算基准
 // ============================================================================
@@ -166,6 +248,166 @@ fn bench_yaoxiang_string_concat(c: &mut Criterion) {
     });
 }
 
+// ============================================================================
+// Codegen Benchmarks - 编译器效率
+// ============================================================================
+
+fn bench_compile_generics(c: &mut Criterion) {
+    let source = std::fs::read_to_string("benches/yx_benchmarks/generics.yx")
+        .expect("Cannot read generics.yx");
+
+    let _ = tracing_subscriber::fmt::Subscriber::builder()
+        .with_max_level(tracing::Level::ERROR)
+        .try_init();
+
+    c.bench_function("compile_generic_specialization", |b| {
+        b.iter(|| {
+            yaoxiang::frontend::Compiler::new()
+                .compile("generics.yx", &source)
+                .expect("YaoXiang compilation failed")
+        })
+    });
+}
+
+/// 生成含 `functions` 个函数的模块：每个函数带循环、分支、结构体、列表与泛型调用，
+/// 用于观察中端随函数数量增长的耗时
+fn large_module_source(functions: usize) -> String {
+    let mut source = String::from(
+        "use std.io.{print}\n\n\
+         Box: (T: Type) -> Type = { value: T }\n\
+         Point: Type = { x: Int, y: Int }\n\
+         identity: (T: Type) -> ((x: T) -> T) = (x) => x\n\
+         pick: (A: Type, B: Type) -> ((a: A, b: B) -> B) = (a, b) => b\n\n\
+         f_0: (n: Int) -> Int = (n) => {\n    return n\n}\n\n",
+    );
+    for i in 1..functions {
+        source.push_str(&format!(
+            "f_{i}: (n: Int) -> Int = (n) => {{\n    mut t = 0\n    mut k = 0\n    \
+             p: Point = Point({i}, n)\n    xs = [{i}, {}, {}]\n    while k < n {{\n        \
+             if k % 3 == 0 {{\n            t = t + k * {i} + p.x\n        \
+             }} elif k % 3 == 1 {{\n            t = t - xs[1] + identity(k)\n        \
+             }} else {{\n            t = t + pick(\"s\", p.y)\n        }}\n        k = k + 1\n    }}\n    \
+             b: Box(Int) = Box(t)\n    return identity(b.value) + f_{}(1) % 7\n}}\n\n",
+            i + 1,
+            i + 2,
+            i - 1,
+        ));
+    }
+    source.push_str(&format!(
+        "main = {{\n    print(f_{}(5))\n}}\n",
+        functions - 1
+    ));
+    source
+}
+
+fn bench_compile_large_module(c: &mut Criterion) {
+    let source = large_module_source(200);
+
+    let _ = tracing_subscriber::fmt::Subscriber::builder()
+        .with_max_level(tracing::Level::ERROR)
+        .try_init();
+
+    report_allocations("compile_large_module", || {
+        yaoxiang::frontend::Compiler::new()
+            .compile("large.yx", &source)
+            .expect("YaoXiang compilation failed")
+    });
+    c.bench_function("compile_large_module", |b| {
+        b.iter(|| {
+            yaoxiang::frontend::Compiler::new()
+                .compile("large.yx", &source)
+                .expect("YaoXiang compilation failed")
+        })
+    });
+}
+
+// ============================================================================
+// Dispatch Benchmarks - 指令分派方式
+// ============================================================================
+
+/// 循环与调用密集的程序：每条指令的分派开销占主导
+const DISPATCH_SOURCE: &str = r#"
+fib: (n: Int) -> Int = {
+    if n <= 1 {
+        return n
+    }
+    return fib(n - 1) + fib(n - 2)
+};
+
+main: () -> Int = {
+    mut total = 0;
+    for i in 0..20000 {
+        total = total + i % 7;
+    }
+    total = total + fib(18);
+    return 0
+}
+"#;
+
+fn bench_dispatch(c: &mut Criterion) {
+    use yaoxiang::backends::{DispatchMode, ExecutorConfig};
+
+    let _ = tracing_subscriber::fmt::Subscriber::builder()
+        .with_max_level(tracing::Level::ERROR)
+        .try_init();
+
+    let mut group = c.benchmark_group("dispatch");
+    for (name, dispatch) in [
+        ("direct", DispatchMode::Direct),
+        ("step", DispatchMode::Step),
+    ] {
+        // 关闭 JIT，只比较解释器本身
+        let engine = yaoxiang::Engine::builder()
+            .executor_config(ExecutorConfig {
+                dispatch,
+                native_code: false,
+                ..ExecutorConfig::default()
+            })
+            .build();
+        let program = engine
+            .compile("dispatch.yx", DISPATCH_SOURCE)
+            .expect("YaoXiang compilation failed");
+        group.bench_function(name, |b| {
+            b.iter(|| engine.run(&program).expect("YaoXiang execution failed"))
+        });
+    }
+    group.finish();
+}
+
+// ============================================================================
+// Parallel Benchmarks - 多线程执行器
+// ============================================================================
+
+fn bench_parallel(c: &mut Criterion) {
+    use yaoxiang::backends::ExecutorConfig;
+
+    let source = std::fs::read_to_string("benches/yx_benchmarks/parallel.yx")
+        .expect("Cannot read parallel.yx");
+
+    let _ = tracing_subscriber::fmt::Subscriber::builder()
+        .with_max_level(tracing::Level::ERROR)
+        .try_init();
+
+    let mut group = c.benchmark_group("parallel");
+    // 1 个线程即串行执行同样的任务，作为对照
+    for threads in [1, 2, 4, 8] {
+        let engine = yaoxiang::Engine::builder()
+            .executor_config(ExecutorConfig {
+                native_code: false,
+                ..ExecutorConfig::default()
+            })
+            .threads(threads)
+            .build();
+        let program = engine
+            .compile("parallel.yx", &source)
+            .expect("YaoXiang compilation failed");
+        group.bench_function(format!("threads_{threads}"), |b| {
+            b.iter(|| engine.run(&program).expect("YaoXiang execution failed"))
+        });
+    }
+    group.finish();
+}
+
 // ============================================================================
 // Criterion Groups
 // ============================================================================
@@ -188,6 +430,24 @@ criterion_group!(
     targets = bench_fibonacci_rust, bench_matrix_rust
 );
 
-criterion_main!(micro, yaoxiang, interpreter);
+criterion_group!(
+    name = codegen;
+    config = Criterion::default().sample_size(20);
+    targets = bench_compile_generics, bench_compile_large_module
+);
+
+criterion_group!(
+    name = dispatch;
+    config = Criterion::default().sample_size(20);
+    targets = bench_dispatch
+);
+
+criterion_group!(
+    name = parallel;
+    config = Criterion::default().sample_size(10);
+    targets = bench_parallel
+);
+
+criterion_main!(micro, yaoxiang, interpreter, codegen, dispatch, parallel);
 
 // TODO: 添加更多基准测试，例如编译器效率测试、内存使用基准等。修复语言原始问题等。
@@ -0,0 +1,993 @@
+//! SQLite native extension package for YaoXiang
+//!
+//! Builds a cdylib that exports `yaoxiang_extension` (ABI version 3, see
+//! `backends/interpreter/extension.rs` in the runtime). Vendoring this package
+//! into a project makes the functions below available after `use sqlite`:
+//!
+//! ```text
+//! open(path: String) -> Int
+//! close(conn: Int) -> Void
+//! execute(conn: Int, sql: String, params: List) -> Int
+//! query(conn: Int, sql: String, params: List) -> List(List((String, Any)))
+//! begin(conn: Int) -> Void
+//! commit(conn: Int) -> Void
+//! rollback(conn: Int) -> Void
+//! last_insert_id(conn: Int) -> Int
+//! cached_statements(conn: Int) -> Int
+//! transaction(conn: Int, body: () -> Void) -> Void
+//! ```
+//!
+//! Connections are opaque `Int` handles that belong to the VM that opened
+//! them: another VM cannot use them, and whatever a program leaves open is
+//! closed when its VM is dropped. Each connection keeps a small cache of
+//! prepared statements keyed by SQL text, so running the same query in a loop
+//! compiles it only once.
+//!
+//! `transaction` runs `body` between `BEGIN` and `COMMIT`; if `body` fails,
+//! the transaction is rolled back and the error is passed on.
+//!
+//! `params` are bound to the `?` placeholders in order. Each row of a query
+//! result is a list of `(column, value)` tuples in the order of the `SELECT`
+//! list, with values converted by storage class:
+//!
+//! | SQLite storage class | YaoXiang value |
+//! |----------------------|----------------|
+//! | INTEGER              | `Int`          |
+//! | REAL                 | `Float`        |
+//! | TEXT                 | `String`       |
+//! | BLOB                 | `Bytes`        |
+//! | NULL                 | `()`           |
+
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::ffi::{c_char, c_int, c_void, CStr, CString};
+use std::sync::{Mutex, MutexGuard};
+
+// ============================================================================
+// Extension ABI
+// ============================================================================
+
+const ABI_VERSION: u32 = 3;
+
+const YX_UNIT: u32 = 0;
+const YX_BOOL: u32 = 1;
+const YX_INT: u32 = 2;
+const YX_FLOAT: u32 = 3;
+const YX_STRING: u32 = 4;
+const YX_BYTES: u32 = 5;
+const YX_LIST: u32 = 6;
+const YX_TUPLE: u32 = 7;
+const YX_FUNCTION: u32 = 8;
+
+/// A value crossing the extension boundary.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct YxValue {
+    tag: u32,
+    int: i64,
+    float: f64,
+    ptr: *const u8,
+    len: usize,
+}
+
+impl YxValue {
+    const UNIT: Self = Self {
+        tag: YX_UNIT,
+        int: 0,
+        float: 0.0,
+        ptr: std::ptr::null(),
+        len: 0,
+    };
+
+    fn int(n: i64) -> Self {
+        Self {
+            tag: YX_INT,
+            int: n,
+            ..Self::UNIT
+        }
+    }
+
+    fn float(x: f64) -> Self {
+        Self {
+            tag: YX_FLOAT,
+            float: x,
+            ..Self::UNIT
+        }
+    }
+
+    /// Bytes of a string or bytes value.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be null or point to `len` readable bytes.
+    unsafe fn bytes(&self) -> &[u8] {
+        if self.ptr.is_null() {
+            &[]
+        } else {
+            unsafe { std::slice::from_raw_parts(self.ptr, self.len) }
+        }
+    }
+
+    /// Elements of a list or tuple value.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be null or point to `len` readable `YxValue`s.
+    unsafe fn elements(&self) -> &[YxValue] {
+        if self.ptr.is_null() {
+            &[]
+        } else {
+            unsafe { std::slice::from_raw_parts(self.ptr.cast(), self.len) }
+        }
+    }
+}
+
+type YxInvokeFn =
+    unsafe extern "C" fn(*mut c_void, *const YxValue, *const YxValue, usize, *mut YxValue) -> i32;
+
+/// What the runtime passes to every call.
+#[repr(C)]
+pub struct YxCall {
+    /// The calling VM's `Connections`, from `state_new`
+    state: *mut c_void,
+    host: *mut c_void,
+    invoke: YxInvokeFn,
+}
+
+type YxNativeFn = unsafe extern "C" fn(*const YxCall, *const YxValue, usize, *mut YxValue) -> i32;
+
+#[repr(C)]
+pub struct YxFunctionDecl {
+    name: *const c_char,
+    signature: *const c_char,
+    func: YxNativeFn,
+}
+
+#[repr(C)]
+pub struct YxExtension {
+    abi_version: u32,
+    name: *const c_char,
+    functions: *const YxFunctionDecl,
+    function_count: usize,
+    state_new: unsafe extern "C" fn() -> *mut c_void,
+    state_drop: unsafe extern "C" fn(*mut c_void),
+}
+
+// SAFETY: the descriptor only points at static strings and functions.
+unsafe impl Sync for YxFunctionDecl {}
+unsafe impl Sync for YxExtension {}
+
+macro_rules! decl {
+    ($name:literal, $signature:literal, $func:ident) => {
+        YxFunctionDecl {
+            name: concat!($name, "\0").as_ptr().cast(),
+            signature: concat!($signature, "\0").as_ptr().cast(),
+            func: $func,
+        }
+    };
+}
+
+static FUNCTIONS: [YxFunctionDecl; 10] = [
+    decl!("open", "(path: String) -> Int", ext_open),
+    decl!("close", "(conn: Int) -> Void", ext_close),
+    decl!(
+        "execute",
+        "(conn: Int, sql: String, params: List) -> Int",
+        ext_execute
+    ),
+    decl!(
+        "query",
+        "(conn: Int, sql: String, params: List) -> List(List((String, Any)))",
+        ext_query
+    ),
+    decl!("begin", "(conn: Int) -> Void", ext_begin),
+    decl!("commit", "(conn: Int) -> Void", ext_commit),
+    decl!("rollback", "(conn: Int) -> Void", ext_rollback),
+    decl!("last_insert_id", "(conn: Int) -> Int", ext_last_insert_id),
+    decl!(
+        "cached_statements",
+        "(conn: Int) -> Int",
+        ext_cached_statements
+    ),
+    decl!(
+        "transaction",
+        "(conn: Int, body: () -> Void) -> Void",
+        ext_transaction
+    ),
+];
+
+static DESCRIPTOR: YxExtension = YxExtension {
+    abi_version: ABI_VERSION,
+    name: c"sqlite".as_ptr(),
+    functions: FUNCTIONS.as_ptr(),
+    function_count: FUNCTIONS.len(),
+    state_new: connections_new,
+    state_drop: connections_drop,
+};
+
+/// Extension entry point looked up by the YaoXiang runtime.
+#[no_mangle]
+pub extern "C" fn yaoxiang_extension() -> *const YxExtension {
+    &DESCRIPTOR
+}
+
+// ============================================================================
+// Returned Values
+// ============================================================================
+
+/// Backing storage for the value of the last call on this thread.
+///
+/// The runtime copies a returned value before making the next call, so each
+/// call may reuse the buffers of the previous one.
+#[derive(Default)]
+struct Output {
+    texts: Vec<Vec<u8>>,
+    arrays: Vec<Vec<YxValue>>,
+}
+
+impl Output {
+    fn bytes(
+        &mut self,
+        tag: u32,
+        bytes: &[u8],
+    ) -> YxValue {
+        let owned = bytes.to_vec();
+        let value = YxValue {
+            tag,
+            ptr: owned.as_ptr(),
+            len: owned.len(),
+            ..YxValue::UNIT
+        };
+        self.texts.push(owned);
+        value
+    }
+
+    fn text(
+        &mut self,
+        text: &str,
+    ) -> YxValue {
+        self.bytes(YX_STRING, text.as_bytes())
+    }
+
+    fn array(
+        &mut self,
+        tag: u32,
+        items: Vec<YxValue>,
+    ) -> YxValue {
+        let value = YxValue {
+            tag,
+            ptr: items.as_ptr().cast(),
+            len: items.len(),
+            ..YxValue::UNIT
+        };
+        self.arrays.push(items);
+        value
+    }
+}
+
+thread_local! {
+    static OUTPUT: RefCell<Output> = RefCell::default();
+}
+
+/// Error raised by an extension function; becomes the runtime error message.
+struct Error(String);
+
+impl Error {
+    fn new(message: impl Into<String>) -> Self {
+        Self(message.into())
+    }
+}
+
+/// Runs an extension function body, writing its value or error to `out`.
+///
+/// # Safety
+///
+/// `call` must be the runtime's call, `args` must point to `argc` values and
+/// `out` must be writable, as the ABI guarantees.
+unsafe fn export(
+    call: *const YxCall,
+    args: *const YxValue,
+    argc: usize,
+    out: *mut YxValue,
+    body: impl FnOnce(&Connections, &[YxValue], &mut Output) -> Result<YxValue, Error>,
+) -> i32 {
+    let args = unsafe { arg_slice(args, argc) };
+    unsafe {
+        finish(out, |output| {
+            vm_connections(call).and_then(|conns| body(conns, args, output))
+        })
+    }
+}
+
+/// Writes the value or error `body` produces to `out`, returning the status.
+///
+/// # Safety
+///
+/// `out` must be writable.
+unsafe fn finish(
+    out: *mut YxValue,
+    body: impl FnOnce(&mut Output) -> Result<YxValue, Error>,
+) -> i32 {
+    OUTPUT.with_borrow_mut(|output| {
+        *output = Output::default();
+        let (status, value) = match body(output) {
+            Ok(value) => (0, value),
+            Err(Error(message)) => (1, output.text(&message)),
+        };
+        unsafe { *out = value };
+        status
+    })
+}
+
+/// # Safety
+///
+/// `args` must be null or point to `argc` values.
+unsafe fn arg_slice<'a>(
+    args: *const YxValue,
+    argc: usize,
+) -> &'a [YxValue] {
+    if args.is_null() {
+        &[]
+    } else {
+        unsafe { std::slice::from_raw_parts(args, argc) }
+    }
+}
+
+// ============================================================================
+// SQLite C API
+// ============================================================================
+
+const SQLITE_OK: c_int = 0;
+const SQLITE_ROW: c_int = 100;
+const SQLITE_DONE: c_int = 101;
+
+const SQLITE_INTEGER: c_int = 1;
+const SQLITE_FLOAT: c_int = 2;
+const SQLITE_TEXT: c_int = 3;
+const SQLITE_BLOB: c_int = 4;
+
+const SQLITE_OPEN_READWRITE: c_int = 0x0000_0002;
+const SQLITE_OPEN_CREATE: c_int = 0x0000_0004;
+const SQLITE_OPEN_URI: c_int = 0x0000_0040;
+const SQLITE_OPEN_FULLMUTEX: c_int = 0x0001_0000;
+
+/// `SQLITE_TRANSIENT`: SQLite copies bound text/blob data before returning.
+const SQLITE_TRANSIENT: isize = -1;
+
+/// Maximum number of prepared statements cached per connection.
+const STATEMENT_CACHE_CAPACITY: usize = 32;
+
+type Db = *mut c_void;
+type Stmt = *mut c_void;
+
+#[link(name = "sqlite3")]
+extern "C" {
+    fn sqlite3_open_v2(
+        filename: *const c_char,
+        db: *mut Db,
+        flags: c_int,
+        vfs: *const c_char,
+    ) -> c_int;
+    fn sqlite3_close_v2(db: Db) -> c_int;
+    fn sqlite3_errmsg(db: Db) -> *const c_char;
+    fn sqlite3_prepare_v2(
+        db: Db,
+        sql: *const c_char,
+        len: c_int,
+        stmt: *mut Stmt,
+        tail: *mut *const c_char,
+    ) -> c_int;
+    fn sqlite3_step(stmt: Stmt) -> c_int;
+    fn sqlite3_reset(stmt: Stmt) -> c_int;
+    fn sqlite3_clear_bindings(stmt: Stmt) -> c_int;
+    fn sqlite3_finalize(stmt: Stmt) -> c_int;
+    fn sqlite3_bind_parameter_count(stmt: Stmt) -> c_int;
+    fn sqlite3_bind_null(
+        stmt: Stmt,
+        index: c_int,
+    ) -> c_int;
+    fn sqlite3_bind_int64(
+        stmt: Stmt,
+        index: c_int,
+        value: i64,
+    ) -> c_int;
+    fn sqlite3_bind_double(
+        stmt: Stmt,
+        index: c_int,
+        value: f64,
+    ) -> c_int;
+    fn sqlite3_bind_text(
+        stmt: Stmt,
+        index: c_int,
+        text: *const c_char,
+        len: c_int,
+        destructor: isize,
+    ) -> c_int;
+    fn sqlite3_bind_blob(
+        stmt: Stmt,
+        index: c_int,
+        blob: *const c_void,
+        len: c_int,
+        destructor: isize,
+    ) -> c_int;
+    fn sqlite3_column_count(stmt: Stmt) -> c_int;
+    fn sqlite3_column_name(
+        stmt: Stmt,
+        index: c_int,
+    ) -> *const c_char;
+    fn sqlite3_column_type(
+        stmt: Stmt,
+        index: c_int,
+    ) -> c_int;
+    fn sqlite3_column_int64(
+        stmt: Stmt,
+        index: c_int,
+    ) -> i64;
+    fn sqlite3_column_double(
+        stmt: Stmt,
+        index: c_int,
+    ) -> f64;
+    fn sqlite3_column_text(
+        stmt: Stmt,
+        index: c_int,
+    ) -> *const u8;
+    fn sqlite3_column_blob(
+        stmt: Stmt,
+        index: c_int,
+    ) -> *const c_void;
+    fn sqlite3_column_bytes(
+        stmt: Stmt,
+        index: c_int,
+    ) -> c_int;
+    fn sqlite3_changes(db: Db) -> c_int;
+    fn sqlite3_last_insert_rowid(db: Db) -> i64;
+}
+
+/// Last error message reported for `db`.
+fn error_message(db: Db) -> String {
+    let msg = unsafe { sqlite3_errmsg(db) };
+    if msg.is_null() {
+        return "unknown SQLite error".to_string();
+    }
+    unsafe { CStr::from_ptr(msg) }
+        .to_string_lossy()
+        .into_owned()
+}
+
+// ============================================================================
+// Connections
+// ============================================================================
+
+/// An open database connection and its prepared-statement cache.
+struct Connection {
+    db: Db,
+    /// Cached statements keyed by SQL text
+    statements: HashMap<String, Stmt>,
+    /// Cache keys from least to most recently used
+    recent: VecDeque<String>,
+}
+
+// SAFETY: connections are opened with SQLITE_OPEN_FULLMUTEX and only touched
+// while holding the lock of their VM's `Connections`.
+unsafe impl Send for Connection {}
+
+impl Connection {
+    /// Returns a reset statement for `sql`, preparing and caching it if needed.
+    fn statement(
+        &mut self,
+        sql: &str,
+    ) -> Result<Stmt, Error> {
+        if let Some(&stmt) = self.statements.get(sql) {
+            self.recent.retain(|key| key != sql);
+            self.recent.push_back(sql.to_string());
+            return Ok(stmt);
+        }
+
+        let c_sql =
+            CString::new(sql).map_err(|_| Error::new("SQL text must not contain NUL bytes"))?;
+        let mut stmt: Stmt = std::ptr::null_mut();
+        let mut tail: *const c_char = std::ptr::null();
+        let rc = unsafe { sqlite3_prepare_v2(self.db, c_sql.as_ptr(), -1, &mut stmt, &mut tail) };
+        if rc != SQLITE_OK {
+            return Err(Error::new(format!(
+                "SQLite prepare failed: {}",
+                error_message(self.db)
+            )));
+        }
+        if stmt.is_null() {
+            return Err(Error::new("SQLite prepare failed: empty statement"));
+        }
+        let rest = unsafe { CStr::from_ptr(tail) }.to_string_lossy();
+        if !rest.trim().trim_start_matches(';').trim().is_empty() {
+            unsafe { sqlite3_finalize(stmt) };
+            return Err(Error::new("SQLite statements must be run one at a time"));
+        }
+
+        if self.statements.len() >= STATEMENT_CACHE_CAPACITY {
+            if let Some(oldest) = self.recent.pop_front() {
+                if let Some(old) = self.statements.remove(&oldest) {
+                    unsafe { sqlite3_finalize(old) };
+                }
+            }
+        }
+        self.statements.insert(sql.to_string(), stmt);
+        self.recent.push_back(sql.to_string());
+        Ok(stmt)
+    }
+
+    /// Finalizes every cached statement and closes the database.
+    fn close(self) {
+        for stmt in self.statements.into_values() {
+            unsafe { sqlite3_finalize(stmt) };
+        }
+        unsafe { sqlite3_close_v2(self.db) };
+    }
+}
+
+/// The connections one VM has opened, closed when the VM is dropped.
+#[derive(Default)]
+struct Connections {
+    table: Mutex<Table>,
+}
+
+#[derive(Default)]
+struct Table {
+    /// Open connections by handle
+    open: HashMap<i64, Connection>,
+    /// Last handle given out
+    last_handle: i64,
+}
+
+impl Connections {
+    fn table(&self) -> MutexGuard<'_, Table> {
+        self.table.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Runs `f` with the connection whose handle is the first argument.
+    fn with<R>(
+        &self,
+        args: &[YxValue],
+        f: impl FnOnce(&mut Connection) -> Result<R, Error>,
+    ) -> Result<R, Error> {
+        let handle = int_arg(args, 0)?;
+        let mut table = self.table();
+        let conn = table
+            .open
+            .get_mut(&handle)
+            .ok_or_else(|| Error::new(format!("Invalid SQLite connection: {handle}")))?;
+        f(conn)
+    }
+}
+
+impl Drop for Connections {
+    fn drop(&mut self) {
+        let table = std::mem::take(self.table.get_mut().unwrap_or_else(|e| e.into_inner()));
+        for conn in table.open.into_values() {
+            conn.close();
+        }
+    }
+}
+
+/// `state_new`: an empty connection table for a new VM.
+unsafe extern "C" fn connections_new() -> *mut c_void {
+    Box::into_raw(Box::<Connections>::default()).cast()
+}
+
+/// `state_drop`: closes everything the VM left open.
+unsafe extern "C" fn connections_drop(state: *mut c_void) {
+    if !state.is_null() {
+        drop(unsafe { Box::from_raw(state.cast::<Connections>()) });
+    }
+}
+
+/// The calling VM's connections.
+///
+/// # Safety
+///
+/// `call` must be the runtime's call, whose state came from `connections_new`.
+unsafe fn vm_connections<'a>(call: *const YxCall) -> Result<&'a Connections, Error> {
+    let state = unsafe { (*call).state };
+    if state.is_null() {
+        return Err(Error::new("SQLite functions must be called from a VM"));
+    }
+    Ok(unsafe { &*state.cast::<Connections>() })
+}
+
+// ============================================================================
+// Statement Execution
+// ============================================================================
+
+/// Binds `params` to the positional placeholders of `stmt`.
+fn bind_params(
+    stmt: Stmt,
+    params: &[YxValue],
+) -> Result<(), Error> {
+    let expected = unsafe { sqlite3_bind_parameter_count(stmt) } as usize;
+    if expected != params.len() {
+        return Err(Error::new(format!(
+            "SQL statement expects {expected} parameter(s), got {}",
+            params.len()
+        )));
+    }
+
+    for (i, param) in params.iter().enumerate() {
+        let index = (i + 1) as c_int;
+        let rc = unsafe {
+            match param.tag {
+                YX_UNIT => sqlite3_bind_null(stmt, index),
+                YX_BOOL | YX_INT => sqlite3_bind_int64(stmt, index, param.int),
+                YX_FLOAT => sqlite3_bind_double(stmt, index, param.float),
+                YX_STRING => sqlite3_bind_text(
+                    stmt,
+                    index,
+                    param.ptr.cast(),
+                    param.len as c_int,
+                    SQLITE_TRANSIENT,
+                ),
+                YX_BYTES => sqlite3_bind_blob(
+                    stmt,
+                    index,
+                    param.ptr.cast(),
+                    param.len as c_int,
+                    SQLITE_TRANSIENT,
+                ),
+                _ => {
+                    return Err(Error::new(format!(
+                        "Cannot bind parameter {index}: lists and tuples are not SQL values"
+                    )))
+                }
+            }
+        };
+        if rc != SQLITE_OK {
+            return Err(Error::new(format!("Failed to bind SQL parameter {index}")));
+        }
+    }
+    Ok(())
+}
+
+/// Reads column `index` of the current row.
+fn column_value(
+    stmt: Stmt,
+    index: c_int,
+    output: &mut Output,
+) -> YxValue {
+    unsafe {
+        let (tag, ptr) = match sqlite3_column_type(stmt, index) {
+            SQLITE_INTEGER => return YxValue::int(sqlite3_column_int64(stmt, index)),
+            SQLITE_FLOAT => return YxValue::float(sqlite3_column_double(stmt, index)),
+            SQLITE_TEXT => (YX_STRING, sqlite3_column_text(stmt, index)),
+            SQLITE_BLOB => (YX_BYTES, sqlite3_column_blob(stmt, index).cast()),
+            _ => return YxValue::UNIT,
+        };
+        let len = sqlite3_column_bytes(stmt, index) as usize;
+        let bytes = if ptr.is_null() {
+            &[][..]
+        } else {
+            std::slice::from_raw_parts(ptr, len)
+        };
+        output.bytes(tag, bytes)
+    }
+}
+
+/// Runs `sql` with `params` on `conn`, turning each result row into a list of
+/// `(column, value)` tuples.
+fn run_statement(
+    conn: &mut Connection,
+    sql: &str,
+    params: &[YxValue],
+    output: &mut Output,
+) -> Result<Vec<YxValue>, Error> {
+    let stmt = conn.statement(sql)?;
+    let result = (|| {
+        bind_params(stmt, params)?;
+        let columns = unsafe { sqlite3_column_count(stmt) };
+        let names: Vec<String> = (0..columns)
+            .map(|i| {
+                let name = unsafe { sqlite3_column_name(stmt, i) };
+                if name.is_null() {
+                    format!("column{i}")
+                } else {
+                    unsafe { CStr::from_ptr(name) }
+                        .to_string_lossy()
+                        .into_owned()
+                }
+            })
+            .collect();
+
+        let mut rows = Vec::new();
+        loop {
+            match unsafe { sqlite3_step(stmt) } {
+                SQLITE_ROW => {
+                    let row = names
+                        .iter()
+                        .enumerate()
+                        .map(|(i, name)| {
+                            let pair =
+                                vec![output.text(name), column_value(stmt, i as c_int, output)];
+                            output.array(YX_TUPLE, pair)
+                        })
+                        .collect();
+                    rows.push(output.array(YX_LIST, row));
+                }
+                SQLITE_DONE => return Ok(rows),
+                _ => {
+                    return Err(Error::new(format!(
+                        "SQLite error: {}",
+                        error_message(conn.db)
+                    )))
+                }
+            }
+        }
+    })();
+    unsafe {
+        sqlite3_reset(stmt);
+        sqlite3_clear_bindings(stmt);
+    }
+    result
+}
+
+// ============================================================================
+// Argument Helpers
+// ============================================================================
+
+fn int_arg(
+    args: &[YxValue],
+    index: usize,
+) -> Result<i64, Error> {
+    match args.get(index) {
+        Some(arg) if arg.tag == YX_INT => Ok(arg.int),
+        _ => Err(Error::new(format!("argument {} must be an Int", index + 1))),
+    }
+}
+
+fn string_arg(
+    args: &[YxValue],
+    index: usize,
+) -> Result<String, Error> {
+    match args.get(index) {
+        Some(arg) if arg.tag == YX_STRING => {
+            Ok(String::from_utf8_lossy(unsafe { arg.bytes() }).into_owned())
+        }
+        _ => Err(Error::new(format!(
+            "argument {} must be a String",
+            index + 1
+        ))),
+    }
+}
+
+fn list_arg(
+    args: &[YxValue],
+    index: usize,
+) -> Result<&[YxValue], Error> {
+    match args.get(index) {
+        Some(arg) if arg.tag == YX_LIST => Ok(unsafe { arg.elements() }),
+        _ => Err(Error::new(format!("argument {} must be a List", index + 1))),
+    }
+}
+
+fn function_arg(
+    args: &[YxValue],
+    index: usize,
+) -> Result<&YxValue, Error> {
+    match args.get(index) {
+        Some(arg) if arg.tag == YX_FUNCTION => Ok(arg),
+        _ => Err(Error::new(format!(
+            "argument {} must be a function",
+            index + 1
+        ))),
+    }
+}
+
+/// Runs a statement without parameters or results.
+fn run_control(
+    conns: &Connections,
+    args: &[YxValue],
+    sql: &str,
+    output: &mut Output,
+) -> Result<YxValue, Error> {
+    conns.with(args, |conn| run_statement(conn, sql, &[], output))?;
+    Ok(YxValue::UNIT)
+}
+
+// ============================================================================
+// Exported Functions
+// ============================================================================
+
+unsafe extern "C" fn ext_open(
+    call: *const YxCall,
+    args: *const YxValue,
+    argc: usize,
+    out: *mut YxValue,
+) -> i32 {
+    unsafe {
+        export(call, args, argc, out, |conns, args, _| {
+            let path = string_arg(args, 0)?;
+            let c_path = CString::new(path)
+                .map_err(|_| Error::new("Database path must not contain NUL bytes"))?;
+            let mut db: Db = std::ptr::null_mut();
+            let flags = SQLITE_OPEN_READWRITE
+                | SQLITE_OPEN_CREATE
+                | SQLITE_OPEN_URI
+                | SQLITE_OPEN_FULLMUTEX;
+            let rc = sqlite3_open_v2(c_path.as_ptr(), &mut db, flags, std::ptr::null());
+            if rc != SQLITE_OK {
+                let message = if db.is_null() {
+                    format!("SQLite open failed with code {rc}")
+                } else {
+                    format!("SQLite open failed: {}", error_message(db))
+                };
+                sqlite3_close_v2(db);
+                return Err(Error::new(message));
+            }
+
+            let mut table = conns.table();
+            table.last_handle += 1;
+            let handle = table.last_handle;
+            table.open.insert(
+                handle,
+                Connection {
+                    db,
+                    statements: HashMap::new(),
+                    recent: VecDeque::new(),
+                },
+            );
+            Ok(YxValue::int(handle))
+        })
+    }
+}
+
+unsafe extern "C" fn ext_close(
+    call: *const YxCall,
+    args: *const YxValue,
+    argc: usize,
+    out: *mut YxValue,
+) -> i32 {
+    unsafe {
+        export(call, args, argc, out, |conns, args, _| {
+            let handle = int_arg(args, 0)?;
+            let conn = conns
+                .table()
+                .open
+                .remove(&handle)
+                .ok_or_else(|| Error::new(format!("Invalid SQLite connection: {handle}")))?;
+            conn.close();
+            Ok(YxValue::UNIT)
+        })
+    }
+}
+
+unsafe extern "C" fn ext_execute(
+    call: *const YxCall,
+    args: *const YxValue,
+    argc: usize,
+    out: *mut YxValue,
+) -> i32 {
+    unsafe {
+        export(call, args, argc, out, |conns, args, output| {
+            let sql = string_arg(args, 1)?;
+            let params = list_arg(args, 2)?;
+            conns.with(args, |conn| {
+                run_statement(conn, &sql, params, output)?;
+                Ok(YxValue::int(i64::from(sqlite3_changes(conn.db))))
+            })
+        })
+    }
+}
+
+unsafe extern "C" fn ext_query(
+    call: *const YxCall,
+    args: *const YxValue,
+    argc: usize,
+    out: *mut YxValue,
+) -> i32 {
+    unsafe {
+        export(call, args, argc, out, |conns, args, output| {
+            let sql = string_arg(args, 1)?;
+            let params = list_arg(args, 2)?;
+            let rows = conns.with(args, |conn| run_statement(conn, &sql, params, output))?;
+            Ok(output.array(YX_LIST, rows))
+        })
+    }
+}
+
+unsafe extern "C" fn ext_begin(
+    call: *const YxCall,
+    args: *const YxValue,
+    argc: usize,
+    out: *mut YxValue,
+) -> i32 {
+    unsafe {
+        export(call, args, argc, out, |conns, args, output| {
+            run_control(conns, args, "BEGIN", output)
+        })
+    }
+}
+
+unsafe extern "C" fn ext_commit(
+    call: *const YxCall,
+    args: *const YxValue,
+    argc: usize,
+    out: *mut YxValue,
+) -> i32 {
+    unsafe {
+        export(call, args, argc, out, |conns, args, output| {
+            run_control(conns, args, "COMMIT", output)
+        })
+    }
+}
+
+unsafe extern "C" fn ext_rollback(
+    call: *const YxCall,
+    args: *const YxValue,
+    argc: usize,
+    out: *mut YxValue,
+) -> i32 {
+    unsafe {
+        export(call, args, argc, out, |conns, args, output| {
+            run_control(conns, args, "ROLLBACK", output)
+        })
+    }
+}
+
+unsafe extern "C" fn ext_last_insert_id(
+    call: *const YxCall,
+    args: *const YxValue,
+    argc: usize,
+    out: *mut YxValue,
+) -> i32 {
+    unsafe {
+        export(call, args, argc, out, |conns, args, _| {
+            conns.with(args, |conn| {
+                Ok(YxValue::int(sqlite3_last_insert_rowid(conn.db)))
+            })
+        })
+    }
+}
+
+unsafe extern "C" fn ext_cached_statements(
+    call: *const YxCall,
+    args: *const YxValue,
+    argc: usize,
+    out: *mut YxValue,
+) -> i32 {
+    unsafe {
+        export(call, args, argc, out, |conns, args, _| {
+            conns.with(args, |conn| Ok(YxValue::int(conn.statements.len() as i64)))
+        })
+    }
+}
+
+unsafe extern "C" fn ext_transaction(
+    call: *const YxCall,
+    args: *const YxValue,
+    argc: usize,
+    out: *mut YxValue,
+) -> i32 {
+    let args = unsafe { arg_slice(args, argc) };
+    // 事务体可能再次调用本扩展，调用它时不能持有 OUTPUT 或连接锁
+    let result = (|| {
+        let conns = unsafe { vm_connections(call) }?;
+        let body = function_arg(args, 1)?;
+        let control = |sql| run_control(conns, args, sql, &mut Output::default());
+        control("BEGIN")?;
+
+        let mut failure = YxValue::UNIT;
+        let status =
+            unsafe { ((*call).invoke)((*call).host, body, std::ptr::null(), 0, &mut failure) };
+        if status != 0 {
+            let _ = control("ROLLBACK");
+            let message = if failure.tag == YX_STRING {
+                String::from_utf8_lossy(unsafe { failure.bytes() }).into_owned()
+            } else {
+                format!("transaction body failed with status {status}")
+            };
+            return Err(Error::new(message));
+        }
+        if let Err(error) = control("COMMIT") {
+            let _ = control("ROLLBACK");
+            return Err(error);
+        }
+        Ok(YxValue::UNIT)
+    })();
+    unsafe { finish(out, |_| result) }
+}
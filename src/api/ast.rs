@@ -0,0 +1,20 @@
+//! 语法树
+//!
+//! 解析结果只做语法检查，不含类型信息。
+
+pub use crate::frontend::core::lexer::tokens::Literal;
+pub use crate::frontend::core::parser::ast::{
+    BinOp, Block, Expr, MatchArm, Module, Param, Pattern, Stmt, StmtKind, Type, UnOp,
+};
+pub use crate::util::span::{Position, Span};
+
+use crate::frontend::CompileError;
+
+/// 解析源码为语法树
+///
+/// 词法或语法错误时返回第一个错误的诊断。
+pub fn parse(source: &str) -> Result<Module, CompileError> {
+    let mut compiler = crate::frontend::Compiler::new();
+    let tokens = compiler.lex(source)?;
+    compiler.parse(&tokens)
+}
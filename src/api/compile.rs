@@ -0,0 +1,34 @@
+//! 编译入口
+//!
+//! ```no_run
+//! let program = yaoxiang::compile::compile("hello.yx", "main = { print(\"hi\") }")?;
+//! yaoxiang::vm::run(&program)?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+pub use crate::frontend::{CompileConfig, CompileError, Compiler, OptLevel};
+
+use super::engine::Engine;
+use super::vm::Program;
+
+/// 编译源码为可执行程序
+///
+/// 依次完成解析、类型检查、IR 生成与字节码生成。
+pub fn compile(
+    source_name: &str,
+    source: &str,
+) -> Result<Program, CompileError> {
+    compile_with_config(source_name, source, CompileConfig::default())
+}
+
+/// 使用指定配置编译源码
+pub fn compile_with_config(
+    source_name: &str,
+    source: &str,
+    config: CompileConfig,
+) -> Result<Program, CompileError> {
+    Engine::builder()
+        .compile_config(config)
+        .build()
+        .compile(source_name, source)
+}
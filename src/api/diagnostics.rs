@@ -0,0 +1,19 @@
+//! 诊断信息
+//!
+//! 编译错误通过 [`CompileError::diagnostic`](crate::compile::CompileError::diagnostic)
+//! 取得结构化诊断，再用 [`render`] 渲染为带源码高亮的文本。
+
+pub use crate::util::diagnostic::{
+    Diagnostic, EmitterConfig, ErrorCodeDefinition, JsonEmitter, Severity, TextEmitter,
+};
+pub use crate::util::span::{SourceFile, SourceMap, Span};
+
+/// 以默认文本格式渲染诊断
+pub fn render(
+    diagnostic: &Diagnostic,
+    source_name: &str,
+    source: &str,
+) -> String {
+    let file = SourceFile::new(source_name.to_string(), source.to_string());
+    TextEmitter::new().render_with_source(diagnostic, Some(&file))
+}
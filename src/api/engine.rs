@@ -0,0 +1,428 @@
+//! 嵌入式引擎
+//!
+//! [`Engine`] 把编译选项、虚拟机限制、能力策略、标准库选择、宿主函数与界面语言收拢为
+//! 一个配置好的对象，嵌入方只需构造一次，之后用它编译、执行与调用：
+//!
+//! ```no_run
+//! use yaoxiang::{CapabilityPolicy, Engine, RuntimeValue};
+//!
+//! let engine = Engine::builder()
+//!     .max_stack_depth(256)
+//!     .capabilities(CapabilityPolicy::deny_all())
+//!     .std_modules(["io", "math"])
+//!     .build();
+//!
+//! let program = engine.compile("lib.yx", "add: (a: Int, b: Int) -> Int = (a, b) => a + b")?;
+//! let sum = engine.call(&program, "add", &[RuntimeValue::Int(1), RuntimeValue::Int(2)])?;
+//! assert_eq!(sum, RuntimeValue::Int(3));
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! `run`、`eval_code` 等自由函数即默认配置的引擎（[`Engine::default`]）。
+
+use crate::backends::common::value::FunctionId;
+use crate::backends::common::RuntimeValue;
+use crate::backends::interpreter::host::{FromValue, HostFunction, IntoArgs, IntoHostFunction};
+use crate::backends::interpreter::runtime::InterpreterRuntimeConfig;
+use crate::backends::interpreter::Interpreter;
+use crate::backends::{CapabilityPolicy, Executor, ExecutorConfig, ExecutorError, ExecutorResult};
+use crate::frontend::core::parser::ast::StmtKind;
+use crate::frontend::{CompileConfig, CompileError, Compiler, OptLevel};
+use crate::middle::passes::codegen::bytecode::{BytecodeFile, DebugSection};
+use crate::middle::passes::codegen::CodegenContext;
+use crate::std::NativeHandler;
+use crate::util::diagnostic::ErrorCodeDefinition;
+use crate::util::span::SourceMap;
+
+use super::vm::Program;
+
+/// 配置好的编译与执行引擎
+///
+/// 由 [`Engine::builder`] 构造。引擎本身不持有虚拟机状态，每次执行都创建新的解释器。
+#[derive(Debug, Clone, Default)]
+pub struct Engine {
+    compile: CompileConfig,
+    executor: ExecutorConfig,
+    runtime: InterpreterRuntimeConfig,
+    std_modules: Option<Vec<String>>,
+    host_functions: Vec<(String, HostFunction)>,
+}
+
+impl Engine {
+    /// 以默认配置开始构造引擎
+    pub fn builder() -> EngineBuilder {
+        EngineBuilder::default()
+    }
+
+    /// 编译选项
+    pub fn compile_config(&self) -> &CompileConfig {
+        &self.compile
+    }
+
+    /// 虚拟机配置
+    pub fn executor_config(&self) -> &ExecutorConfig {
+        &self.executor
+    }
+
+    /// 运行时配置（spawn 出的任务在哪种运行时、多少线程上执行）
+    pub fn runtime_config(&self) -> &InterpreterRuntimeConfig {
+        &self.runtime
+    }
+
+    /// 编译源码为可执行程序
+    ///
+    /// 限定了标准库模块时，导入未选择的 `std` 模块报 E5002。
+    pub fn compile(
+        &self,
+        source_name: &str,
+        source: &str,
+    ) -> Result<Program, CompileError> {
+        self.compile_bytecode(source_name, source)
+            .map(Program::from)
+    }
+
+    /// 执行程序，返回退出码（`main` 返回的 Int、`std.process.exit` 的参数或 0）
+    pub fn run(
+        &self,
+        program: &Program,
+    ) -> ExecutorResult<i32> {
+        let mut interpreter = self.interpreter();
+        interpreter.execute_module(program)?;
+        Ok(interpreter.state().exit_code)
+    }
+
+    /// 编译并执行一段代码，没有 `main` 时自动包装为 `main = { ... }`
+    pub fn eval(
+        &self,
+        source: &str,
+    ) -> crate::Result<i32> {
+        let (compile_source, _) = crate::eval_source(source)?;
+        let program = self.compile("<eval>", &compile_source)?;
+        Ok(self.run(&program)?)
+    }
+
+    /// 调用程序中名为 `name` 的函数
+    ///
+    /// 只加载程序而不执行 `main`；参数与返回值中的堆对象只在本次调用内有效。
+    pub fn call(
+        &self,
+        program: &Program,
+        name: &str,
+        args: &[RuntimeValue],
+    ) -> ExecutorResult<RuntimeValue> {
+        let index = program
+            .functions
+            .iter()
+            .position(|f| f.name == name)
+            .ok_or_else(|| {
+                ExecutorError::FunctionNotFound(format!("Function not found: {}", name), None)
+            })?;
+        let mut interpreter = self.load(program)?;
+        interpreter.call_function_by_id(FunctionId(index as u32), args)
+    }
+
+    /// 以 Rust 类型调用程序中名为 `name` 的函数
+    ///
+    /// `engine.call_typed::<_, f64>(&program, "area", (2.0, 3))`：参数元组经
+    /// [`IntoArgs`](crate::vm::IntoArgs)、返回值经 [`FromValue`](crate::vm::FromValue) 转换。
+    /// 参数个数或基本类型不符时在调用前报类型错误，返回值无法转换时同样报类型错误。
+    pub fn call_typed<Args: IntoArgs, R: FromValue>(
+        &self,
+        program: &Program,
+        name: &str,
+        args: Args,
+    ) -> ExecutorResult<R> {
+        self.load(program)?.call(name, args)
+    }
+
+    /// 按引擎配置创建解释器：虚拟机限制、能力策略、运行时、标准库选择与宿主函数
+    pub fn interpreter(&self) -> Interpreter {
+        let mut interpreter = Interpreter::with_config(self.executor.clone());
+        interpreter.set_runtime_config(self.runtime.clone());
+        let registry = interpreter.ffi_registry_mut();
+        if let Some(modules) = &self.std_modules {
+            registry.retain(|name| match name.strip_prefix("std.") {
+                Some(rest) => {
+                    let module = rest.split('.').next().unwrap_or(rest);
+                    modules.iter().any(|m| m == module)
+                }
+                None => true,
+            });
+        }
+        for (name, function) in &self.host_functions {
+            registry.register_host(name, function.clone());
+        }
+        interpreter
+    }
+
+    /// 创建解释器并加载程序，不执行 `main`
+    fn load(
+        &self,
+        program: &Program,
+    ) -> ExecutorResult<Interpreter> {
+        let mut loaded = program.clone();
+        loaded.entry_point = None;
+        let mut interpreter = self.interpreter();
+        interpreter.execute_module(&loaded)?;
+        Ok(interpreter)
+    }
+
+    /// 编译源码为字节码文件（供字节码缓存使用）
+    ///
+    /// 带行号表与源码，运行时错误可以用 [`RuntimePanic`](super::vm::RuntimePanic) 定位到源码。
+    pub(crate) fn compile_bytecode(
+        &self,
+        source_name: &str,
+        source: &str,
+    ) -> Result<BytecodeFile, CompileError> {
+        self.check_std_imports(source)?;
+        // `call` 可以按名调用程序中的任意函数，不做死函数消除
+        let config = self.compile.clone().with_tree_shaking(false);
+        let module = Compiler::with_config(config).compile_with_source(source_name, source)?;
+        let mut ctx = CodegenContext::new(module);
+        ctx.set_generate_debug_info(true);
+        let mut file = ctx
+            .generate()
+            .map_err(|d| CompileError::IRError(d.message))?;
+        let mut sources = SourceMap::new();
+        sources.add_file(source_name.to_string(), source.to_string());
+        file.debug_section = Some(DebugSection::from_sources_and_functions(
+            sources,
+            &file.code_section.functions,
+        ));
+        Ok(file)
+    }
+
+    /// 检查导入的标准库模块都在选择范围内
+    pub(crate) fn check_std_imports(
+        &self,
+        source: &str,
+    ) -> Result<(), CompileError> {
+        let Some(modules) = &self.std_modules else {
+            return Ok(());
+        };
+        // 词法与语法错误留给编译器报告
+        let Ok(tokens) = crate::frontend::core::tokenize(source) else {
+            return Ok(());
+        };
+        let parse_result = crate::frontend::core::parser::parse(&tokens);
+        for stmt in &parse_result.module.items {
+            let StmtKind::Use {
+                path, path_span, ..
+            } = &stmt.kind
+            else {
+                continue;
+            };
+            let module = match path.strip_prefix("std") {
+                Some("") => None,
+                Some(rest) => match rest.strip_prefix('.') {
+                    Some(rest) => Some(rest.split('.').next().unwrap_or(rest)),
+                    None => continue,
+                },
+                None => continue,
+            };
+            if module.is_some_and(|module| modules.iter().any(|m| m == module)) {
+                continue;
+            }
+            let diagnostic = ErrorCodeDefinition::import_error(
+                path,
+                "standard library module is not enabled for this engine",
+            )
+            .at(*path_span)
+            .build();
+            return Err(CompileError::TypeError(
+                diagnostic.message.clone(),
+                Some(Box::new(diagnostic)),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// [`Engine`] 的构造器
+#[derive(Debug, Clone, Default)]
+pub struct EngineBuilder {
+    engine: Engine,
+    language: Option<String>,
+}
+
+impl EngineBuilder {
+    /// 编译选项（优化级别、内联阈值等）
+    pub fn compile_config(
+        mut self,
+        config: CompileConfig,
+    ) -> Self {
+        self.engine.compile = config;
+        self
+    }
+
+    /// 优化级别
+    pub fn opt_level(
+        mut self,
+        level: OptLevel,
+    ) -> Self {
+        self.engine.compile = self.engine.compile.with_opt_level(level);
+        self
+    }
+
+    /// 虚拟机配置（整体替换，之前设置的限制与能力策略随之覆盖）
+    pub fn executor_config(
+        mut self,
+        config: ExecutorConfig,
+    ) -> Self {
+        self.engine.executor = config;
+        self
+    }
+
+    /// 最大调用栈深度
+    pub fn max_stack_depth(
+        mut self,
+        depth: usize,
+    ) -> Self {
+        self.engine.executor.max_stack_depth = depth;
+        self
+    }
+
+    /// 深递归时每次扩展的宿主栈段大小（字节）
+    pub fn stack_segment_size(
+        mut self,
+        bytes: usize,
+    ) -> Self {
+        self.engine.executor.stack_segment_size = bytes;
+        self
+    }
+
+    /// 复用缓冲区的栈帧数
+    pub fn frame_pool_size(
+        mut self,
+        frames: usize,
+    ) -> Self {
+        self.engine.executor.frame_pool_size = frames;
+        self
+    }
+
+    /// 最大堆大小（字节）：超出时分配以 [`ExecutorError::HeapExhausted`] 失败
+    pub fn max_heap_size(
+        mut self,
+        size: usize,
+    ) -> Self {
+        self.engine.executor.max_heap_size = size;
+        self
+    }
+
+    /// 指令预算：执行满 `instructions` 条指令后以 [`ExecutorError::FuelExhausted`] 结束
+    ///
+    /// 设置预算后所有函数都解释执行（机器码无法计数）。
+    pub fn fuel(
+        mut self,
+        instructions: u64,
+    ) -> Self {
+        self.engine.executor.fuel = Some(instructions);
+        self
+    }
+
+    /// 确定性执行：spawn 出的任务按创建顺序就地执行，不使用机器码
+    ///
+    /// 同一程序与输入每次执行相同的指令序列；与 [`fuel`](Self::fuel) 配合可限制整个程序。
+    /// 覆盖 [`threads`](Self::threads) 选择的运行时。
+    pub fn deterministic(
+        mut self,
+        enabled: bool,
+    ) -> Self {
+        self.engine.executor.deterministic = enabled;
+        self
+    }
+
+    /// 在 `threads` 个线程上运行 spawn 出的任务，空闲线程窃取其他线程的任务
+    ///
+    /// 任务间只能通过 `ref` 共享不含堆对象的值，其余参数与结果按值复制。
+    pub fn threads(
+        mut self,
+        threads: usize,
+    ) -> Self {
+        self.engine.runtime = InterpreterRuntimeConfig::threads(threads);
+        self
+    }
+
+    /// 授予程序的能力；默认拒绝所有可选能力
+    pub fn capabilities(
+        mut self,
+        policy: CapabilityPolicy,
+    ) -> Self {
+        self.engine.executor.capabilities = policy;
+        self
+    }
+
+    /// 只启用这些标准库模块（如 `"io"`、`"math"`）；默认启用全部
+    pub fn std_modules<I, S>(
+        mut self,
+        modules: I,
+    ) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.engine.std_modules = Some(modules.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// 注册宿主函数，程序通过 `Native.rs("name")` 绑定调用
+    ///
+    /// 与标准库函数同名时覆盖标准库实现。
+    pub fn host_function(
+        mut self,
+        name: impl Into<String>,
+        handler: NativeHandler,
+    ) -> Self {
+        self.engine
+            .host_functions
+            .push((name.into(), HostFunction::from(handler)));
+        self
+    }
+
+    /// 注册捕获状态的宿主闭包，参数与返回值为原始的 [`RuntimeValue`]
+    pub fn register_native(
+        mut self,
+        name: impl Into<String>,
+        f: impl Fn(&[RuntimeValue]) -> ExecutorResult<RuntimeValue> + Send + Sync + 'static,
+    ) -> Self {
+        self.engine
+            .host_functions
+            .push((name.into(), HostFunction::new(f)));
+        self
+    }
+
+    /// 注册以 Rust 类型为参数与返回值的宿主闭包（如 `|a: i64, b: f64| a as f64 * b`）
+    ///
+    /// 参数经 [`FromValue`](crate::vm::FromValue)、返回值经 [`IntoValue`](crate::vm::IntoValue)
+    /// 转换；参数个数或类型不符时以类型错误结束调用，不进入闭包。
+    pub fn register_fn<Args>(
+        mut self,
+        name: impl Into<String>,
+        f: impl IntoHostFunction<Args>,
+    ) -> Self {
+        let name = name.into();
+        let function = HostFunction::typed(&name, f);
+        self.engine.host_functions.push((name, function));
+        self
+    }
+
+    /// 诊断与日志的语言（如 `"zh"`、`"en"`）
+    ///
+    /// 语言是进程级设置，构造引擎时生效，同样影响其它引擎与命令行输出。
+    pub fn language(
+        mut self,
+        lang: impl Into<String>,
+    ) -> Self {
+        self.language = Some(lang.into());
+        self
+    }
+
+    /// 构造引擎
+    pub fn build(self) -> Engine {
+        if let Some(lang) = self.language {
+            crate::util::i18n::set_lang_from_string(lang);
+        }
+        self.engine
+    }
+}
@@ -0,0 +1,20 @@
+//! 稳定公共 API
+//!
+//! 外部工具（编辑器插件、构建脚本、嵌入式宿主）应只依赖这里导出的接口：
+//!
+//! - [`compile`] - 源码 → 可执行程序
+//! - [`ast`] - 语法树与解析入口
+//! - [`diagnostics`] - 诊断信息与渲染
+//! - [`vm`] - 程序加载与执行
+//! - [`engine`] - 汇总全部配置的嵌入式引擎
+//! - [`session`] - 跨多次求值保留定义与变量的会话
+//!
+//! 这些路径遵循 semver：次版本号内不删除、不改名、不改变签名。
+//! `frontend`、`middle`、`backends`、`util` 等内部模块随重构变化，不在保证范围内。
+
+pub mod ast;
+pub mod compile;
+pub mod diagnostics;
+pub mod engine;
+pub mod session;
+pub mod vm;
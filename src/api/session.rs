@@ -0,0 +1,191 @@
+//! 跨多次求值保留状态的会话
+//!
+//! [`Session`] 由 [`Engine::session`] 创建，持有一个解释器与编译环境。每次
+//! [`eval`](Session::eval) 编译一段输入并在同一个解释器中执行：输入中定义的函数与类型、
+//! 导入的模块以及顶层赋值的变量在之后的输入中仍然可用。
+//!
+//! ```no_run
+//! use yaoxiang::{Engine, RuntimeValue};
+//!
+//! let mut session = Engine::default().session();
+//! session.eval("double: (n: Int) -> Int = (n) => n * 2")?;
+//! session.eval("x = double(20)")?;
+//! assert_eq!(session.eval("x + 2")?, RuntimeValue::Int(42));
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! 输入编译或执行失败时会话不变：失败输入中的变量与定义不会被之后的输入看到。
+
+use crate::backends::common::heap::HeapValue;
+use crate::backends::common::{RuntimeValue, Value};
+use crate::backends::interpreter::Interpreter;
+use crate::backends::Executor;
+use crate::frontend::core::types::{MonoType, PolyType};
+use crate::frontend::snippet::SNIPPET_FUNCTION;
+use crate::frontend::{CompileError, Compiler, SessionInput, SnippetContext};
+use crate::middle::bytecode::BytecodeModule;
+use crate::middle::passes::codegen::bytecode::BytecodeFile;
+use crate::middle::passes::codegen::CodegenContext;
+use crate::util::artifact_cache::ArtifactCache;
+
+use super::engine::Engine;
+
+/// 保留定义与变量的求值会话
+#[derive(Debug)]
+pub struct Session {
+    engine: Engine,
+    compiler: Compiler,
+    context: SnippetContext,
+    interpreter: Interpreter,
+    /// 会话中定义的函数及其类型，按首次定义的顺序
+    functions: Vec<(String, PolyType)>,
+    /// 编译结果缓存（`None` 时不缓存）
+    cache: Option<ArtifactCache>,
+}
+
+impl Engine {
+    /// 按引擎配置创建空会话
+    pub fn session(&self) -> Session {
+        Session {
+            engine: self.clone(),
+            compiler: Compiler::with_config(self.compile_config().clone()),
+            context: SnippetContext::default(),
+            interpreter: self.interpreter(),
+            functions: Vec::new(),
+            cache: None,
+        }
+    }
+}
+
+impl Session {
+    /// 编译并执行一段输入，返回最后一个表达式的值
+    ///
+    /// 最后一条语句不是表达式时返回 `Unit`。限定了标准库模块时，导入未选择的 `std`
+    /// 模块报 E5002。
+    pub fn eval(
+        &mut self,
+        source: &str,
+    ) -> crate::Result<RuntimeValue> {
+        self.engine.check_std_imports(source)?;
+        let input = self.compiler.compile_session(&self.context, source)?;
+        let file = self.generate(&input)?;
+        self.interpreter.load_module(BytecodeModule::from(file))?;
+
+        let args: Vec<RuntimeValue> = self
+            .context
+            .locals()
+            .iter()
+            .map(|(name, _)| {
+                self.interpreter
+                    .global(name)
+                    .cloned()
+                    .unwrap_or(RuntimeValue::Unit)
+            })
+            .collect();
+        let result: RuntimeValue = self.interpreter.call(SNIPPET_FUNCTION, args.as_slice())?;
+
+        let has_value = input.result_type != MonoType::Void;
+        let value = if input.variables.is_empty() {
+            result
+        } else {
+            let mut items = match result {
+                RuntimeValue::List(handle) => match self.interpreter.heap().get(handle) {
+                    Some(HeapValue::List(items)) => items.iter().map(Value::get).collect(),
+                    _ => Vec::new(),
+                },
+                _ => Vec::new(),
+            }
+            .into_iter();
+            let value = if has_value { items.next() } else { None };
+            for ((name, _), item) in input.variables.iter().zip(items) {
+                self.interpreter.set_global(name.clone(), item);
+            }
+            value.unwrap_or(RuntimeValue::Unit)
+        };
+        self.context.commit(&input);
+        for function in &input.module.functions {
+            let Some(ty) = self.context.binding(&function.name) else {
+                continue;
+            };
+            match self
+                .functions
+                .iter_mut()
+                .find(|(name, _)| *name == function.name)
+            {
+                Some(defined) => defined.1 = ty.clone(),
+                None => self.functions.push((function.name.clone(), ty.clone())),
+            }
+        }
+        Ok(if has_value { value } else { RuntimeValue::Unit })
+    }
+
+    /// 会话变量 `name` 的当前值
+    pub fn get(
+        &self,
+        name: &str,
+    ) -> Option<&RuntimeValue> {
+        self.interpreter.global(name)
+    }
+
+    /// 会话变量及其类型，按首次赋值的顺序
+    pub fn variables(&self) -> &[(String, MonoType)] {
+        self.context.locals()
+    }
+
+    /// 会话中定义的函数及其类型，按首次定义的顺序
+    pub fn functions(&self) -> &[(String, PolyType)] {
+        &self.functions
+    }
+
+    /// 按 `show(value)` 的方式格式化值
+    pub fn show(
+        &mut self,
+        value: &RuntimeValue,
+    ) -> String {
+        self.interpreter
+            .show_value(value)
+            .unwrap_or_else(|_| value.to_string())
+    }
+
+    /// 会话的解释器
+    pub fn interpreter(&self) -> &Interpreter {
+        &self.interpreter
+    }
+
+    /// 会话的解释器（可注册宿主函数、读写全局变量）
+    pub fn interpreter_mut(&mut self) -> &mut Interpreter {
+        &mut self.interpreter
+    }
+
+    /// 经 `cache` 查找与保存编译结果
+    pub(crate) fn with_cache(
+        mut self,
+        cache: ArtifactCache,
+    ) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// 生成输入的字节码，启用缓存时以 IR 文本为键
+    fn generate(
+        &self,
+        input: &SessionInput,
+    ) -> Result<BytecodeFile, CompileError> {
+        let key = self
+            .cache
+            .as_ref()
+            .map(|_| ArtifactCache::key(&["repl", &input.module.to_string()]));
+        if let (Some(cache), Some(key)) = (&self.cache, &key) {
+            if let Some(file) = cache.load_bytecode(key) {
+                return Ok(file);
+            }
+        }
+        let file = CodegenContext::new(input.module.clone())
+            .generate()
+            .map_err(|d| CompileError::IRError(d.message))?;
+        if let (Some(cache), Some(key)) = (&self.cache, &key) {
+            let _ = cache.store_bytecode(key, &file);
+        }
+        Ok(file)
+    }
+}
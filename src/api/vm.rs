@@ -0,0 +1,48 @@
+//! 程序执行
+//!
+//! [`Program`] 由 [`compile`](crate::compile::compile) 生成或从 `.42` 字节码文件加载。
+//! 执行出错时，[`RuntimePanic::new`] 把错误与程序的行号表结合为带源码调用栈的错误对象。
+
+pub use crate::backends::common::serialize::{from_value, to_value, SerdeError, ValueRef};
+pub use crate::backends::common::{RuntimeValue, SendValue};
+#[cfg(feature = "hooks")]
+pub use crate::backends::interpreter::hooks::{ExecutionHook, TaskSwitch, VmState};
+pub use crate::backends::interpreter::host::{
+    FromValue, HostFunction, HostReturn, IntoArgs, IntoHostFunction, IntoValue, Serde,
+};
+pub use crate::backends::interpreter::Interpreter;
+pub use crate::backends::{BuildMode, Executor, ExecutorConfig, ExecutorError, ExecutorResult};
+pub use crate::middle::bytecode::BytecodeModule as Program;
+pub use crate::std::{NativeContext, NativeHandler};
+pub use crate::util::diagnostic::panic::{PanicKind, RuntimePanic, SourceLocation, TraceFrame};
+
+/// 断点、暂停与单步执行（需要 `hooks` 特性）
+///
+/// [`Debugger`](debug::Debugger) 作为执行钩子挂到解释器上，CLI 调试器与 DAP 服务器都基于它。
+#[cfg(feature = "hooks")]
+pub mod debug {
+    pub use crate::backends::interpreter::debugger::{
+        Breakpoint, BreakpointId, DebugEvent, Debugger, StackFrame, Step, StepUnit, Stop,
+        StopReason,
+    };
+}
+
+/// 以默认配置执行程序
+pub fn run(program: &Program) -> ExecutorResult<()> {
+    run_with_config(program, ExecutorConfig::default())
+}
+
+/// 以指定配置执行程序
+pub fn run_with_config(
+    program: &Program,
+    config: ExecutorConfig,
+) -> ExecutorResult<()> {
+    Interpreter::with_config(config).execute_module(program)
+}
+
+/// 加载 `.42` / `.yxbc` 字节码文件（校验文件长度与校验和）
+#[cfg(not(target_arch = "wasm32"))]
+pub fn load(path: &std::path::Path) -> std::io::Result<Program> {
+    let file = crate::middle::passes::codegen::LazyBytecodeFile::open(path)?;
+    Program::try_from(file)
+}
@@ -0,0 +1,104 @@
+//! Tracing collector for the handle heap
+//!
+//! Heap objects are never freed by their users: a list stays allocated as long
+//! as the heap does, however many handles to it are dropped. [`collect`] marks
+//! every object reachable from a set of roots and frees the rest. Objects that
+//! only reference each other (a list holding a closure that captures the list,
+//! a struct field pointing back at its owner through an `Arc`) are unreachable
+//! as a group and are freed together.
+//!
+//! The caller supplies the roots; anything a root reaches through closures,
+//! method tables, `Arc`s, enum payloads and resolved async values is kept.
+//! Weak references are not followed.
+
+use std::borrow::Cow;
+use std::collections::HashSet;
+
+use super::heap::{Handle, Heap, HeapValue};
+use super::value::{AsyncState, RuntimeValue};
+
+/// Free every object on `heap` that `roots` and `pinned` do not reach
+///
+/// `pinned` are objects kept alive by handle alone (e.g. frame stack slots).
+/// Returns the number of objects freed.
+pub fn collect<'a>(
+    heap: &mut Heap,
+    roots: impl IntoIterator<Item = Cow<'a, RuntimeValue>>,
+    pinned: impl IntoIterator<Item = Handle>,
+) -> usize {
+    let mut pending: Vec<Handle> = pinned.into_iter().collect();
+    for root in roots {
+        value_handles(&root, &mut pending);
+    }
+
+    let mut marked = HashSet::new();
+    while let Some(handle) = pending.pop() {
+        if !marked.insert(handle) {
+            continue;
+        }
+        if let Some(object) = heap.get(handle) {
+            object_handles(object, &mut pending);
+        }
+    }
+
+    let garbage: Vec<Handle> = heap
+        .iter()
+        .map(|(handle, _)| handle)
+        .filter(|handle| !marked.contains(handle))
+        .collect();
+    for &handle in &garbage {
+        heap.deallocate(handle);
+    }
+    heap.recount();
+    garbage.len()
+}
+
+/// Handles stored directly in a heap object (dict keys included)
+fn object_handles(
+    object: &HeapValue,
+    out: &mut Vec<Handle>,
+) {
+    match object {
+        HeapValue::Tuple(items)
+        | HeapValue::Array(items)
+        | HeapValue::List(items)
+        | HeapValue::Struct(items) => items
+            .iter()
+            .for_each(|item| value_handles(&item.view(), out)),
+        HeapValue::Dict(map) => {
+            for (key, value) in map {
+                value_handles(key, out);
+                value_handles(value, out);
+            }
+        }
+    }
+}
+
+/// Handles a value references without going through another heap object
+pub(super) fn value_handles(
+    value: &RuntimeValue,
+    out: &mut Vec<Handle>,
+) {
+    match value {
+        RuntimeValue::Tuple(handle)
+        | RuntimeValue::Array(handle)
+        | RuntimeValue::List(handle)
+        | RuntimeValue::Dict(handle) => out.push(*handle),
+        RuntimeValue::Struct { fields, vtable, .. } => {
+            out.push(*fields);
+            for (_, method) in vtable {
+                method.env.iter().for_each(|v| value_handles(v, out));
+            }
+        }
+        RuntimeValue::Function(func) => func.env.iter().for_each(|v| value_handles(v, out)),
+        RuntimeValue::Enum { payload, .. } => value_handles(payload, out),
+        RuntimeValue::Arc(inner) => value_handles(inner, out),
+        RuntimeValue::Dyn { value, .. } => value_handles(value, out),
+        RuntimeValue::Async(value) => {
+            if let AsyncState::Ready(inner) | AsyncState::Error(inner) = value.state.as_ref() {
+                value_handles(inner, out);
+            }
+        }
+        _ => {}
+    }
+}
@@ -6,6 +6,9 @@
 use std::collections::HashMap;
 use std::fmt;
 
+use super::nanbox::Value;
+use super::value::RuntimeValue;
+
 /// Handle to a value stored in the heap
 ///
 /// Handles are opaque references that allow mutation of heap-allocated
@@ -41,6 +44,8 @@ pub enum HeapError {
     InvalidHandle(Handle),
     /// Handle allocation failed (out of handles)
     OutOfHandles,
+    /// The allocation would take the heap past its byte limit
+    LimitExceeded(usize),
 }
 
 impl fmt::Display for HeapError {
@@ -51,28 +56,46 @@ impl fmt::Display for HeapError {
         match self {
             HeapError::InvalidHandle(h) => write!(f, "invalid handle: {}", h),
             HeapError::OutOfHandles => write!(f, "out of handle space"),
+            HeapError::LimitExceeded(limit) => write!(f, "heap limit of {} bytes exceeded", limit),
         }
     }
 }
 
 /// Heap value - storage for collection types
 ///
-/// This enum holds the actual collection data stored on the heap.
+/// This enum holds the actual collection data stored on the heap. Sequences
+/// hold packed eight-byte [`Value`]s; dictionaries keep unpacked keys so they
+/// can hash and compare them.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum HeapValue {
     /// Tuple storage
-    Tuple(Vec<super::value::RuntimeValue>),
+    Tuple(Vec<Value>),
     /// Array storage
-    Array(Vec<super::value::RuntimeValue>),
+    Array(Vec<Value>),
     /// List storage
-    List(Vec<super::value::RuntimeValue>),
+    List(Vec<Value>),
     /// Dictionary storage
-    Dict(HashMap<super::value::RuntimeValue, super::value::RuntimeValue>),
+    Dict(HashMap<RuntimeValue, RuntimeValue>),
     /// Struct storage (field values)
-    Struct(Vec<super::value::RuntimeValue>),
+    Struct(Vec<Value>),
 }
 
 impl HeapValue {
+    /// Tuple storage packed from unpacked values
+    pub fn tuple(items: impl IntoIterator<Item = RuntimeValue>) -> Self {
+        HeapValue::Tuple(items.into_iter().map(Value::new).collect())
+    }
+
+    /// Array storage packed from unpacked values
+    pub fn array(items: impl IntoIterator<Item = RuntimeValue>) -> Self {
+        HeapValue::Array(items.into_iter().map(Value::new).collect())
+    }
+
+    /// List storage packed from unpacked values
+    pub fn list(items: impl IntoIterator<Item = RuntimeValue>) -> Self {
+        HeapValue::List(items.into_iter().map(Value::new).collect())
+    }
+
     /// Get the number of elements in this collection
     pub fn len(&self) -> usize {
         match self {
@@ -88,6 +111,39 @@ impl HeapValue {
     pub fn is_empty(&self) -> bool {
         self.len() == 0
     }
+
+    /// Estimated bytes this value holds: one slot per element (two per dict
+    /// entry) plus the text of the strings stored directly in it
+    pub fn size_bytes(&self) -> usize {
+        let elements: usize = match self {
+            HeapValue::Tuple(v)
+            | HeapValue::Array(v)
+            | HeapValue::List(v)
+            | HeapValue::Struct(v) => v.iter().map(|v| Self::element_bytes(&v.view())).sum(),
+            HeapValue::Dict(m) => m.iter().map(|(k, v)| Self::entry_bytes(k, v)).sum(),
+        };
+        std::mem::size_of::<HeapValue>() + elements
+    }
+
+    /// Estimated bytes one element adds to a tuple, array, list or struct
+    pub fn element_bytes(value: &RuntimeValue) -> usize {
+        std::mem::size_of::<Value>() + Self::text_bytes(value)
+    }
+
+    /// Estimated bytes one entry adds to a dictionary
+    pub fn entry_bytes(
+        key: &RuntimeValue,
+        value: &RuntimeValue,
+    ) -> usize {
+        2 * std::mem::size_of::<RuntimeValue>() + Self::text_bytes(key) + Self::text_bytes(value)
+    }
+
+    fn text_bytes(value: &RuntimeValue) -> usize {
+        match value {
+            RuntimeValue::String(s) => s.len(),
+            _ => 0,
+        }
+    }
 }
 
 /// Heap storage for runtime values
@@ -96,7 +152,8 @@ impl HeapValue {
 /// using handles. This enables:
 /// - Efficient in-place modification of collections
 /// - Shared references via handle copying
-/// - Potential for future garbage collection
+/// - Collection of unreachable objects (see `super::gc`)
+/// - Accounting of the bytes held and allocated, with an optional limit
 #[derive(Debug, Clone)]
 pub struct Heap {
     /// Handle generator for allocation
@@ -105,6 +162,13 @@ pub struct Heap {
     values: HashMap<Handle, HeapValue>,
     /// Free list for handle reuse
     free_list: Vec<Handle>,
+    /// Estimated bytes held by live values (see [`HeapValue::size_bytes`])
+    bytes: usize,
+    /// Bytes allocated over the heap's lifetime, freed or not
+    allocated: u64,
+    /// Bytes that [`try_allocate`](Self::try_allocate) and
+    /// [`reserve`](Self::reserve) may not take the heap past
+    limit: Option<usize>,
 }
 
 impl Default for Heap {
@@ -120,13 +184,99 @@ impl Heap {
             next_handle: 0usize,
             values: HashMap::new(),
             free_list: Vec::new(),
+            bytes: 0,
+            allocated: 0,
+            limit: None,
         }
     }
 
     /// Allocate a heap value and return a handle
+    ///
+    /// The value is counted but never refused; allocations the program
+    /// asks for go through [`try_allocate`](Self::try_allocate).
     pub fn allocate(
         &mut self,
         value: HeapValue,
+    ) -> Handle {
+        self.charge(value.size_bytes());
+        self.insert(value)
+    }
+
+    /// Allocate a heap value unless it would take the heap past its limit
+    pub fn try_allocate(
+        &mut self,
+        value: HeapValue,
+    ) -> Result<Handle, HeapError> {
+        self.reserve(value.size_bytes())?;
+        Ok(self.insert(value))
+    }
+
+    /// Count `bytes` a live value is about to grow by in place, unless they
+    /// would take the heap past its limit
+    pub fn reserve(
+        &mut self,
+        bytes: usize,
+    ) -> Result<(), HeapError> {
+        self.check(bytes)?;
+        self.charge(bytes);
+        Ok(())
+    }
+
+    /// Fail if `bytes` more would take the heap past its limit, without
+    /// counting them (for values kept outside the heap, such as strings)
+    pub fn check(
+        &self,
+        bytes: usize,
+    ) -> Result<(), HeapError> {
+        match self.limit {
+            Some(limit) if self.bytes.saturating_add(bytes) > limit => {
+                Err(HeapError::LimitExceeded(limit))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Limit [`try_allocate`](Self::try_allocate) and [`reserve`](Self::reserve)
+    /// to `limit` bytes; `None` removes the limit
+    pub fn set_limit(
+        &mut self,
+        limit: Option<usize>,
+    ) {
+        self.limit = limit;
+    }
+
+    /// The byte limit, if any
+    pub fn limit(&self) -> Option<usize> {
+        self.limit
+    }
+
+    /// Estimated bytes held by live values
+    pub fn bytes(&self) -> usize {
+        self.bytes
+    }
+
+    /// Bytes allocated over the heap's lifetime, including freed values
+    pub fn allocated_bytes(&self) -> u64 {
+        self.allocated
+    }
+
+    /// Re-measure the live values, correcting the drift of in-place updates
+    /// that were not reserved (e.g. an element overwritten by a longer string)
+    pub fn recount(&mut self) {
+        self.bytes = self.values.values().map(HeapValue::size_bytes).sum();
+    }
+
+    fn charge(
+        &mut self,
+        bytes: usize,
+    ) {
+        self.bytes = self.bytes.saturating_add(bytes);
+        self.allocated = self.allocated.saturating_add(bytes as u64);
+    }
+
+    fn insert(
+        &mut self,
+        value: HeapValue,
     ) -> Handle {
         let handle = if let Some(h) = self.free_list.pop() {
             h
@@ -162,7 +312,10 @@ impl Heap {
         value: HeapValue,
     ) -> Result<(), HeapError> {
         if let std::collections::hash_map::Entry::Occupied(mut e) = self.values.entry(handle) {
-            e.insert(value);
+            let size = value.size_bytes();
+            let old = e.insert(value);
+            self.bytes = self.bytes.saturating_sub(old.size_bytes());
+            self.charge(size);
             Ok(())
         } else {
             Err(HeapError::InvalidHandle(handle))
@@ -174,7 +327,8 @@ impl Heap {
         &mut self,
         handle: Handle,
     ) -> Option<HeapValue> {
-        if self.values.remove(&handle).is_some() {
+        if let Some(value) = self.values.remove(&handle) {
+            self.bytes = self.bytes.saturating_sub(value.size_bytes());
             self.free_list.push(handle);
             Some(HeapValue::List(vec![]))
         } else {
@@ -200,9 +354,15 @@ impl Heap {
         self.values.is_empty()
     }
 
-    /// Clear all allocated values
+    /// Iterate over all live values (in unspecified order)
+    pub fn iter(&self) -> impl Iterator<Item = (Handle, &HeapValue)> {
+        self.values.iter().map(|(handle, value)| (*handle, value))
+    }
+
+    /// Clear all allocated values (the limit and lifetime total are kept)
     pub fn clear(&mut self) {
         self.values.clear();
         self.free_list.clear();
+        self.bytes = 0;
     }
 }
@@ -0,0 +1,525 @@
+//! Heap dumps for leak hunting
+//!
+//! A [`HeapDump`] is a JSON snapshot of the object graph on the [`Heap`]:
+//! every live object with its type, approximate size, outgoing references and
+//! the shortest retaining path from a root (a frame slot or the return value).
+//! Objects no root reaches are still listed, with a path from the outermost
+//! object that holds them, so leaked containers show what they keep alive.
+//!
+//! [`HeapDiff`] compares two dumps per type, which is how growth in a
+//! long-running script is tracked down (`yaoxiang heap-diff a.dump b.dump`).
+
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::io;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::heap::{Handle, Heap, HeapValue};
+use super::nanbox::Value;
+use super::struct_types::StructTypes;
+use super::value::{AsyncState, RuntimeValue, TypeId};
+
+/// Format version of heap dump files
+pub const HEAP_DUMP_VERSION: u32 = 1;
+
+/// Number of retaining paths sampled per growing type in a diff
+const DIFF_SAMPLES: usize = 3;
+
+/// Snapshot of the heap object graph
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HeapDump {
+    /// Format version ([`HEAP_DUMP_VERSION`])
+    pub version: u32,
+    /// Labels of the roots the graph was traced from
+    pub roots: Vec<String>,
+    /// All live objects, ordered by id
+    pub objects: Vec<HeapObject>,
+}
+
+/// One heap object in a [`HeapDump`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HeapObject {
+    /// Heap handle
+    pub id: usize,
+    /// `Tuple`, `Array`, `List`, `Dict`, or the struct type name
+    pub type_name: String,
+    /// Number of elements (fields for structs)
+    pub len: usize,
+    /// Approximate shallow size in bytes
+    pub size: usize,
+    /// Ids of the objects this one references directly
+    pub references: Vec<usize>,
+    /// Whether a root reaches this object
+    pub reachable: bool,
+    /// Shortest retaining path, e.g. `main:local0["users"][3]`
+    pub retaining_path: String,
+}
+
+/// An outgoing reference: path segment and target handle
+struct Edge {
+    label: String,
+    target: Handle,
+}
+
+impl HeapDump {
+    /// Capture the heap, tracing retaining paths from the labelled roots
+    pub fn capture(
+        heap: &Heap,
+        struct_types: &StructTypes,
+        roots: &[(String, &RuntimeValue)],
+    ) -> Self {
+        // 堆上的结构体字段不记得自己的类型，先从所有引用处收集 handle → TypeId
+        let mut struct_ids = HashMap::new();
+        for (_, value) in roots {
+            collect_struct_ids(value, &mut struct_ids);
+        }
+        for (_, object) in heap.iter() {
+            for value in object_values(object) {
+                collect_struct_ids(&value, &mut struct_ids);
+            }
+        }
+
+        let mut handles: Vec<Handle> = heap.iter().map(|(handle, _)| handle).collect();
+        handles.sort_by_key(Handle::raw);
+
+        let edges: HashMap<Handle, Vec<Edge>> = handles
+            .iter()
+            .filter_map(|&handle| {
+                let object = heap.get(handle)?;
+                let field_names = struct_ids
+                    .get(&handle)
+                    .and_then(|id| struct_types.get(*id))
+                    .map(|info| info.fields.as_slice());
+                Some((handle, object_edges(object, field_names)))
+            })
+            .collect();
+
+        let mut paths: HashMap<Handle, String> = HashMap::new();
+        let mut queue = VecDeque::new();
+        for (label, value) in roots {
+            let mut root_edges = Vec::new();
+            value_edges(value, label.clone(), &mut root_edges);
+            for edge in root_edges {
+                if heap.is_valid(edge.target) && !paths.contains_key(&edge.target) {
+                    paths.insert(edge.target, edge.label);
+                    queue.push_back(edge.target);
+                }
+            }
+        }
+        trace(&edges, &mut paths, &mut queue);
+        let reachable: HashSet<Handle> = paths.keys().copied().collect();
+
+        // 根不可达的对象：从无人引用的最外层对象出发，剩下的只可能在环里
+        let referenced: HashSet<Handle> =
+            edges.values().flatten().map(|edge| edge.target).collect();
+        for pass in ["unreferenced", "cycle"] {
+            for &handle in &handles {
+                if paths.contains_key(&handle)
+                    || (pass == "unreferenced" && referenced.contains(&handle))
+                {
+                    continue;
+                }
+                let type_name = type_name_of(heap, handle, &struct_ids, struct_types);
+                paths.insert(handle, format!("<{} {}#{}>", pass, type_name, handle.raw()));
+                queue.push_back(handle);
+                trace(&edges, &mut paths, &mut queue);
+            }
+        }
+
+        let objects = handles
+            .iter()
+            .filter_map(|&handle| {
+                let object = heap.get(handle)?;
+                let mut references: Vec<usize> = edges[&handle]
+                    .iter()
+                    .map(|edge| edge.target.raw())
+                    .collect();
+                references.sort_unstable();
+                references.dedup();
+                Some(HeapObject {
+                    id: handle.raw(),
+                    type_name: type_name_of(heap, handle, &struct_ids, struct_types),
+                    len: object.len(),
+                    size: shallow_size(object),
+                    references,
+                    reachable: reachable.contains(&handle),
+                    retaining_path: paths.remove(&handle).unwrap_or_default(),
+                })
+            })
+            .collect();
+
+        Self {
+            version: HEAP_DUMP_VERSION,
+            roots: roots.iter().map(|(label, _)| label.clone()).collect(),
+            objects,
+        }
+    }
+
+    /// Total approximate size of all objects in bytes
+    pub fn total_size(&self) -> usize {
+        self.objects.iter().map(|object| object.size).sum()
+    }
+
+    /// Number of objects no root reaches
+    pub fn unreachable_count(&self) -> usize {
+        self.objects
+            .iter()
+            .filter(|object| !object.reachable)
+            .count()
+    }
+
+    /// Write the dump as JSON
+    pub fn write(
+        &self,
+        path: &Path,
+    ) -> io::Result<()> {
+        let mut writer = io::BufWriter::new(std::fs::File::create(path)?);
+        serde_json::to_writer(&mut writer, self).map_err(io::Error::other)?;
+        io::Write::flush(&mut writer)
+    }
+
+    /// Read a dump written by [`HeapDump::write`]
+    pub fn read(path: &Path) -> io::Result<Self> {
+        let file = std::fs::File::open(path)?;
+        let dump: Self = serde_json::from_reader(io::BufReader::new(file))
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        if dump.version != HEAP_DUMP_VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "unsupported heap dump version {} (expected {})",
+                    dump.version, HEAP_DUMP_VERSION
+                ),
+            ));
+        }
+        Ok(dump)
+    }
+}
+
+/// Breadth-first walk from the queued objects, recording the first path to each
+fn trace(
+    edges: &HashMap<Handle, Vec<Edge>>,
+    paths: &mut HashMap<Handle, String>,
+    queue: &mut VecDeque<Handle>,
+) {
+    while let Some(handle) = queue.pop_front() {
+        let Some(out) = edges.get(&handle) else {
+            continue;
+        };
+        for edge in out {
+            if edges.contains_key(&edge.target) && !paths.contains_key(&edge.target) {
+                let path = format!("{}{}", paths[&handle], edge.label);
+                paths.insert(edge.target, path);
+                queue.push_back(edge.target);
+            }
+        }
+    }
+}
+
+/// Values stored directly in a heap object (dict keys included)
+fn object_values(object: &HeapValue) -> Box<dyn Iterator<Item = Cow<'_, RuntimeValue>> + '_> {
+    match object {
+        HeapValue::Tuple(items)
+        | HeapValue::Array(items)
+        | HeapValue::List(items)
+        | HeapValue::Struct(items) => Box::new(items.iter().map(Value::view)),
+        HeapValue::Dict(map) => Box::new(
+            map.iter()
+                .flat_map(|(k, v)| [Cow::Borrowed(k), Cow::Borrowed(v)]),
+        ),
+    }
+}
+
+/// Outgoing references of a heap object, labelled by index, key or field
+fn object_edges(
+    object: &HeapValue,
+    field_names: Option<&[String]>,
+) -> Vec<Edge> {
+    let mut edges = Vec::new();
+    match object {
+        HeapValue::Tuple(items) | HeapValue::Array(items) | HeapValue::List(items) => {
+            for (i, item) in items.iter().enumerate() {
+                value_edges(&item.view(), format!("[{}]", i), &mut edges);
+            }
+        }
+        HeapValue::Struct(fields) => {
+            for (i, field) in fields.iter().enumerate() {
+                let label = match field_names.and_then(|names| names.get(i)) {
+                    Some(name) => format!(".{}", name),
+                    None => format!(".{}", i),
+                };
+                value_edges(&field.view(), label, &mut edges);
+            }
+        }
+        HeapValue::Dict(map) => {
+            // 按键排序，保证同一个堆得到相同的路径
+            let mut entries: Vec<_> = map.iter().map(|(k, v)| (key_label(k), k, v)).collect();
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+            for (label, key, value) in entries {
+                value_edges(key, format!("<key {}>", label), &mut edges);
+                value_edges(value, label, &mut edges);
+            }
+        }
+    }
+    edges
+}
+
+/// Path segment for a dict entry
+fn key_label(key: &RuntimeValue) -> String {
+    match key {
+        RuntimeValue::String(s) => format!("[{:?}]", s.as_ref()),
+        other => format!("[{}]", other),
+    }
+}
+
+/// Heap handles reachable from a value without going through another heap object
+fn value_edges(
+    value: &RuntimeValue,
+    label: String,
+    out: &mut Vec<Edge>,
+) {
+    match value {
+        RuntimeValue::Tuple(handle)
+        | RuntimeValue::Array(handle)
+        | RuntimeValue::List(handle)
+        | RuntimeValue::Dict(handle) => out.push(Edge {
+            label,
+            target: *handle,
+        }),
+        RuntimeValue::Struct { fields, vtable, .. } => {
+            for (method, func) in vtable {
+                for (i, captured) in func.env.iter().enumerate() {
+                    value_edges(captured, format!("{}.{}.env[{}]", label, method, i), out);
+                }
+            }
+            out.push(Edge {
+                label,
+                target: *fields,
+            });
+        }
+        RuntimeValue::Function(func) => {
+            for (i, captured) in func.env.iter().enumerate() {
+                value_edges(captured, format!("{}.env[{}]", label, i), out);
+            }
+        }
+        RuntimeValue::Enum { payload, .. } => value_edges(payload, label, out),
+        RuntimeValue::Arc(inner) => value_edges(inner, label, out),
+        RuntimeValue::Dyn { value, .. } => value_edges(value, label, out),
+        RuntimeValue::Async(value) => match value.state.as_ref() {
+            AsyncState::Ready(inner) | AsyncState::Error(inner) => value_edges(inner, label, out),
+            AsyncState::Pending(_) => {}
+        },
+        _ => {}
+    }
+}
+
+/// Record the struct type of every struct field handle found in a value
+fn collect_struct_ids(
+    value: &RuntimeValue,
+    out: &mut HashMap<Handle, TypeId>,
+) {
+    match value {
+        RuntimeValue::Struct {
+            type_id,
+            fields,
+            vtable,
+        } => {
+            out.insert(*fields, *type_id);
+            for (_, func) in vtable {
+                func.env.iter().for_each(|v| collect_struct_ids(v, out));
+            }
+        }
+        RuntimeValue::Function(func) => func.env.iter().for_each(|v| collect_struct_ids(v, out)),
+        RuntimeValue::Enum { payload, .. } => collect_struct_ids(payload, out),
+        RuntimeValue::Arc(inner) => collect_struct_ids(inner, out),
+        RuntimeValue::Dyn { value, .. } => collect_struct_ids(value, out),
+        RuntimeValue::Async(value) => {
+            if let AsyncState::Ready(inner) | AsyncState::Error(inner) = value.state.as_ref() {
+                collect_struct_ids(inner, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn type_name_of(
+    heap: &Heap,
+    handle: Handle,
+    struct_ids: &HashMap<Handle, TypeId>,
+    struct_types: &StructTypes,
+) -> String {
+    match heap.get(handle) {
+        Some(HeapValue::Tuple(_)) => "Tuple".to_string(),
+        Some(HeapValue::Array(_)) => "Array".to_string(),
+        Some(HeapValue::List(_)) => "List".to_string(),
+        Some(HeapValue::Dict(_)) => "Dict".to_string(),
+        Some(HeapValue::Struct(_)) | None => struct_ids
+            .get(&handle)
+            .and_then(|id| struct_types.get(*id))
+            .map_or_else(|| "Struct".to_string(), |info| info.name.clone()),
+    }
+}
+
+/// Object header plus element slots plus inline string/bytes payloads
+fn shallow_size(object: &HeapValue) -> usize {
+    let slots = match object {
+        HeapValue::Dict(map) => map.len() * 2 * std::mem::size_of::<RuntimeValue>(),
+        other => other.len() * std::mem::size_of::<Value>(),
+    };
+    let payload: usize = object_values(object)
+        .map(|value| match &*value {
+            RuntimeValue::String(s) => s.len(),
+            RuntimeValue::Bytes(b) => b.len(),
+            _ => 0,
+        })
+        .sum();
+    std::mem::size_of::<HeapValue>() + slots + payload
+}
+
+/// Per-type change between two heap dumps
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeDelta {
+    /// Object type name
+    pub type_name: String,
+    /// Object count in the first dump
+    pub before_count: usize,
+    /// Object count in the second dump
+    pub after_count: usize,
+    /// Total size in the first dump
+    pub before_size: usize,
+    /// Total size in the second dump
+    pub after_size: usize,
+    /// Retaining paths of objects only the second dump has (sampled)
+    pub new_paths: Vec<String>,
+}
+
+impl TypeDelta {
+    /// Change in object count
+    pub fn count_delta(&self) -> i64 {
+        self.after_count as i64 - self.before_count as i64
+    }
+
+    /// Change in total size
+    pub fn size_delta(&self) -> i64 {
+        self.after_size as i64 - self.before_size as i64
+    }
+}
+
+/// Comparison of two heap dumps, largest growth first
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeapDiff {
+    /// Types whose count or size changed
+    pub types: Vec<TypeDelta>,
+    /// Total size in the first dump
+    pub before_size: usize,
+    /// Total size in the second dump
+    pub after_size: usize,
+    /// Unreachable objects in the first dump
+    pub before_unreachable: usize,
+    /// Unreachable objects in the second dump
+    pub after_unreachable: usize,
+}
+
+impl HeapDiff {
+    /// Compare `before` against `after`
+    pub fn between(
+        before: &HeapDump,
+        after: &HeapDump,
+    ) -> Self {
+        let mut types: BTreeMap<&str, TypeDelta> = BTreeMap::new();
+        let entry = |name: &str| TypeDelta {
+            type_name: name.to_string(),
+            before_count: 0,
+            after_count: 0,
+            before_size: 0,
+            after_size: 0,
+            new_paths: Vec::new(),
+        };
+        for object in &before.objects {
+            let delta = types
+                .entry(&object.type_name)
+                .or_insert_with(|| entry(&object.type_name));
+            delta.before_count += 1;
+            delta.before_size += object.size;
+        }
+        let known_paths: HashSet<&str> = before
+            .objects
+            .iter()
+            .map(|object| object.retaining_path.as_str())
+            .collect();
+        for object in &after.objects {
+            let delta = types
+                .entry(&object.type_name)
+                .or_insert_with(|| entry(&object.type_name));
+            delta.after_count += 1;
+            delta.after_size += object.size;
+            if delta.new_paths.len() < DIFF_SAMPLES
+                && !known_paths.contains(object.retaining_path.as_str())
+            {
+                delta.new_paths.push(object.retaining_path.clone());
+            }
+        }
+
+        let mut types: Vec<TypeDelta> = types
+            .into_values()
+            .filter(|delta| delta.count_delta() != 0 || delta.size_delta() != 0)
+            .map(|mut delta| {
+                // 只有增长的类型才需要指出新对象挂在哪里
+                if delta.count_delta() <= 0 {
+                    delta.new_paths.clear();
+                }
+                delta
+            })
+            .collect();
+        types.sort_by(|a, b| {
+            b.size_delta()
+                .cmp(&a.size_delta())
+                .then(b.count_delta().cmp(&a.count_delta()))
+                .then(a.type_name.cmp(&b.type_name))
+        });
+
+        Self {
+            types,
+            before_size: before.total_size(),
+            after_size: after.total_size(),
+            before_unreachable: before.unreachable_count(),
+            after_unreachable: after.unreachable_count(),
+        }
+    }
+
+    /// Human-readable report
+    pub fn render(&self) -> String {
+        let mut out = format!(
+            "heap size: {} -> {} bytes ({:+})\nunreachable objects: {} -> {}\n",
+            self.before_size,
+            self.after_size,
+            self.after_size as i64 - self.before_size as i64,
+            self.before_unreachable,
+            self.after_unreachable,
+        );
+        if self.types.is_empty() {
+            out.push_str("no changes\n");
+            return out;
+        }
+        out.push_str(&format!(
+            "\n{:<24} {:>16} {:>8} {:>24} {:>10}\n",
+            "TYPE", "COUNT", "DELTA", "SIZE", "DELTA"
+        ));
+        for delta in &self.types {
+            out.push_str(&format!(
+                "{:<24} {:>16} {:>+8} {:>24} {:>+10}\n",
+                delta.type_name,
+                format!("{} -> {}", delta.before_count, delta.after_count),
+                delta.count_delta(),
+                format!("{} -> {}", delta.before_size, delta.after_size),
+                delta.size_delta(),
+            ));
+            for path in &delta.new_paths {
+                out.push_str(&format!("    new: {}\n", path));
+            }
+        }
+        out
+    }
+}
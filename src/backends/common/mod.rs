@@ -3,16 +3,31 @@
 //! This module provides shared components used across all backends:
 //! - Opcode definitions
 //! - Runtime value types
+//! - The eight-byte NaN-boxed value stored in registers and collections
 //! - Heap storage
+//! - Tracing collection of unreachable heap objects
 //! - Memory allocators
+//! - Struct type registry
+//! - Moving values between threads
+//! - serde support for runtime values
 
 pub mod allocator;
+pub mod gc;
 pub mod heap;
+pub mod heap_dump;
+pub mod nanbox;
 pub mod opcode;
+pub mod serialize;
+pub mod struct_types;
+pub mod transfer;
 pub mod value;
 
 // Re-exports for convenience
 pub use opcode::Opcode;
 pub use value::RuntimeValue;
+pub use nanbox::Value;
 pub use heap::{Handle, Heap, HeapValue};
+pub use struct_types::{StructInfo, StructTypes};
+pub use transfer::{SendValue, TransferError};
+pub use serialize::{from_value, to_value, SerdeError, ValueRef};
 pub use allocator::{Allocator, BumpAllocator, MemoryLayout, AllocError};
@@ -0,0 +1,422 @@
+//! NaN-boxed value representation
+//!
+//! `Value` is the eight-byte form in which the interpreter stores values:
+//! registers, locals, upvalues and the elements of heap collections.
+//! `RuntimeValue` stays the unpacked form that instructions and the standard
+//! library match on; a `Value` converts to and from it at those boundaries.
+//!
+//! # Layout
+//!
+//! A `Value` is a `u64`. Every bit pattern below `0xFFF8_0000_0000_0000` is
+//! an `f64`; NaNs are canonicalised to `0x7FF8_0000_0000_0000`, which frees
+//! the negative quiet-NaN space for tagged values:
+//!
+//! ```text
+//! 1111 1111 1111 1ttt  pppp ... pppp   (t: 3-bit tag, p: 48-bit payload)
+//! ```
+//!
+//! | tag | payload |
+//! |-----|---------|
+//! | 0   | `Int` in `-2^47 .. 2^47`, sign-extended |
+//! | 1   | `Unit`, `Bool` or `Char` (kind in bits 32..34, value below) |
+//! | 2-5 | `Tuple`, `Array`, `List`, `Dict` heap handle |
+//! | 6   | pointer from `Arc::into_raw` to any other `RuntimeValue` |
+//! | 7   | slot in a side table holding such a pointer when it needs more than 48 bits |
+//!
+//! Integers outside the 48-bit range, strings, structs, enums, closures and
+//! the remaining variants are boxed. Boxed values are shared, so copying a
+//! `Value` with tag 6 never allocates. Tag 7 covers heap pointers above 48 bits
+//! (tagged pointers on aarch64, 5-level paging on x86-64); such a copy takes a
+//! slot of its own, so only those targets pay for the table.
+
+use std::borrow::Cow;
+use std::fmt;
+use std::marker::PhantomData;
+use std::sync::{Arc, Mutex};
+
+use super::heap::Handle;
+use super::value::RuntimeValue;
+
+/// First bit pattern that is not an `f64`
+const TAGGED: u64 = 0xFFF8_0000_0000_0000;
+/// The only NaN stored as a float
+const CANONICAL_NAN: u64 = 0x7FF8_0000_0000_0000;
+const TAG_SHIFT: u32 = 48;
+const PAYLOAD: u64 = (1 << TAG_SHIFT) - 1;
+
+const TAG_INT: u64 = 0;
+const TAG_SCALAR: u64 = 1;
+const TAG_TUPLE: u64 = 2;
+const TAG_ARRAY: u64 = 3;
+const TAG_LIST: u64 = 4;
+const TAG_DICT: u64 = 5;
+const TAG_BOXED: u64 = 6;
+const TAG_WIDE: u64 = 7;
+
+const SCALAR_SHIFT: u32 = 32;
+const SCALAR_UNIT: u64 = 0;
+const SCALAR_BOOL: u64 = 1;
+const SCALAR_CHAR: u64 = 2;
+
+/// Smallest integer stored inline
+pub const SMALL_INT_MIN: i64 = -(1 << 47);
+/// Largest integer stored inline
+pub const SMALL_INT_MAX: i64 = (1 << 47) - 1;
+
+const fn tagged(
+    tag: u64,
+    payload: u64,
+) -> u64 {
+    TAGGED | (tag << TAG_SHIFT) | payload
+}
+
+const fn scalar(
+    kind: u64,
+    value: u64,
+) -> u64 {
+    tagged(TAG_SCALAR, (kind << SCALAR_SHIFT) | value)
+}
+
+/// Boxed pointers that do not fit the payload; each tag-7 `Value` owns one slot
+static WIDE_POINTERS: Mutex<WidePointers> = Mutex::new(WidePointers {
+    slots: Vec::new(),
+    free: Vec::new(),
+});
+
+struct WidePointers {
+    slots: Vec<usize>,
+    free: Vec<usize>,
+}
+
+fn wide_pointers() -> std::sync::MutexGuard<'static, WidePointers> {
+    // The table is consistent after every operation, so a poisoned lock is usable
+    WIDE_POINTERS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+impl WidePointers {
+    fn insert(
+        &mut self,
+        ptr: usize,
+    ) -> u64 {
+        let slot = match self.free.pop() {
+            Some(slot) => {
+                self.slots[slot] = ptr;
+                slot
+            }
+            None => {
+                self.slots.push(ptr);
+                self.slots.len() - 1
+            }
+        };
+        slot as u64
+    }
+
+    fn get(
+        &self,
+        slot: u64,
+    ) -> usize {
+        self.slots[slot as usize]
+    }
+
+    fn remove(
+        &mut self,
+        slot: u64,
+    ) -> usize {
+        self.free.push(slot as usize);
+        self.slots[slot as usize]
+    }
+}
+
+/// A runtime value packed into eight bytes (see the module docs for the layout)
+pub struct Value {
+    bits: u64,
+    /// Tag 6 owns one strong count of an `Arc<RuntimeValue>`
+    _boxed: PhantomData<Arc<RuntimeValue>>,
+}
+
+impl Value {
+    /// The unit value
+    pub const UNIT: Value = Value::from_bits(scalar(SCALAR_UNIT, 0));
+
+    const fn from_bits(bits: u64) -> Self {
+        Self {
+            bits,
+            _boxed: PhantomData,
+        }
+    }
+
+    /// Pack a value
+    pub fn new(value: RuntimeValue) -> Self {
+        match value {
+            RuntimeValue::Unit => Self::UNIT,
+            RuntimeValue::Bool(b) => Self::bool(b),
+            RuntimeValue::Int(n) => Self::int(n),
+            RuntimeValue::Float(f) => Self::float(f),
+            RuntimeValue::Char(c) => Self::from_bits(scalar(SCALAR_CHAR, u64::from(c))),
+            RuntimeValue::Tuple(handle) => Self::handle(TAG_TUPLE, handle, value),
+            RuntimeValue::Array(handle) => Self::handle(TAG_ARRAY, handle, value),
+            RuntimeValue::List(handle) => Self::handle(TAG_LIST, handle, value),
+            RuntimeValue::Dict(handle) => Self::handle(TAG_DICT, handle, value),
+            value => Self::boxed(value),
+        }
+    }
+
+    /// An integer; inline when it fits in 48 bits
+    #[inline]
+    pub fn int(n: i64) -> Self {
+        if (SMALL_INT_MIN..=SMALL_INT_MAX).contains(&n) {
+            Self::from_bits(tagged(TAG_INT, n as u64 & PAYLOAD))
+        } else {
+            Self::boxed(RuntimeValue::Int(n))
+        }
+    }
+
+    /// A float; every NaN becomes the canonical quiet NaN
+    #[inline]
+    pub fn float(f: f64) -> Self {
+        Self::from_bits(if f.is_nan() {
+            CANONICAL_NAN
+        } else {
+            f.to_bits()
+        })
+    }
+
+    /// A boolean
+    #[inline]
+    pub fn bool(b: bool) -> Self {
+        Self::from_bits(scalar(SCALAR_BOOL, u64::from(b)))
+    }
+
+    fn handle(
+        tag: u64,
+        handle: Handle,
+        value: RuntimeValue,
+    ) -> Self {
+        if handle.0 as u64 <= PAYLOAD {
+            Self::from_bits(tagged(tag, handle.0 as u64))
+        } else {
+            Self::boxed(value)
+        }
+    }
+
+    fn boxed(value: RuntimeValue) -> Self {
+        Self::from_box(Arc::into_raw(Arc::new(value)) as usize)
+    }
+
+    /// Take over one strong count of the `Arc` at `ptr`
+    fn from_box(ptr: usize) -> Self {
+        if ptr as u64 <= PAYLOAD {
+            Self::from_bits(tagged(TAG_BOXED, ptr as u64))
+        } else {
+            Self::from_bits(tagged(TAG_WIDE, wide_pointers().insert(ptr)))
+        }
+    }
+
+    /// Box `value` through the side table, as for a pointer above 48 bits
+    #[cfg(test)]
+    pub(crate) fn boxed_wide(value: RuntimeValue) -> Self {
+        let ptr = Arc::into_raw(Arc::new(value)) as usize;
+        Self::from_bits(tagged(TAG_WIDE, wide_pointers().insert(ptr)))
+    }
+
+    #[inline]
+    fn is_boxed(&self) -> bool {
+        matches!(self.tag(), Some(TAG_BOXED | TAG_WIDE))
+    }
+
+    /// Pointer from `Arc::into_raw` of a boxed value
+    #[inline]
+    fn box_ptr(&self) -> Option<*const RuntimeValue> {
+        match self.tag() {
+            Some(TAG_BOXED) => Some(self.payload() as usize as *const RuntimeValue),
+            Some(TAG_WIDE) => Some(wide_pointers().get(self.payload()) as *const RuntimeValue),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    fn tag(&self) -> Option<u64> {
+        (self.bits >= TAGGED).then_some((self.bits >> TAG_SHIFT) & 0b111)
+    }
+
+    #[inline]
+    fn payload(&self) -> u64 {
+        self.bits & PAYLOAD
+    }
+
+    /// The integer, if it is stored inline
+    #[inline]
+    pub fn small_int(&self) -> Option<i64> {
+        (self.tag() == Some(TAG_INT)).then_some(((self.bits << 16) as i64) >> 16)
+    }
+
+    /// The integer, inline or boxed
+    #[inline]
+    pub fn as_int(&self) -> Option<i64> {
+        match self.boxed_ref() {
+            Some(RuntimeValue::Int(n)) => Some(*n),
+            _ => self.small_int(),
+        }
+    }
+
+    /// The float
+    #[inline]
+    pub fn as_float(&self) -> Option<f64> {
+        (self.bits < TAGGED).then(|| f64::from_bits(self.bits))
+    }
+
+    /// The boolean
+    #[inline]
+    pub fn as_bool(&self) -> Option<bool> {
+        (self.tag() == Some(TAG_SCALAR) && self.payload() >> SCALAR_SHIFT == SCALAR_BOOL)
+            .then(|| self.payload() & 1 == 1)
+    }
+
+    /// Whether this is the unit value
+    #[inline]
+    pub fn is_unit(&self) -> bool {
+        self.bits == Self::UNIT.bits
+    }
+
+    /// The boxed value, if this value is boxed
+    #[inline]
+    pub fn boxed_ref(&self) -> Option<&RuntimeValue> {
+        // SAFETY: the pointer came from `Arc::into_raw` and this value owns one
+        // of its strong counts, so it stays valid while `self` is borrowed
+        self.box_ptr().map(|ptr| unsafe { &*ptr })
+    }
+
+    /// Unpacked view: borrowed when boxed, built in place otherwise
+    pub fn view(&self) -> Cow<'_, RuntimeValue> {
+        match self.boxed_ref() {
+            Some(value) => Cow::Borrowed(value),
+            None => Cow::Owned(self.unpack_inline()),
+        }
+    }
+
+    /// Unpack a copy of the value
+    pub fn get(&self) -> RuntimeValue {
+        self.view().into_owned()
+    }
+
+    /// Unpack the value, moving it out of its box when this was the last copy
+    pub fn into_inner(self) -> RuntimeValue {
+        let Some(ptr) = self.box_ptr() else {
+            return self.unpack_inline();
+        };
+        if self.tag() == Some(TAG_WIDE) {
+            wide_pointers().remove(self.payload());
+        }
+        std::mem::forget(self);
+        // SAFETY: takes over the strong count `self` owned
+        let arc = unsafe { Arc::from_raw(ptr) };
+        Arc::try_unwrap(arc).unwrap_or_else(|arc| (*arc).clone())
+    }
+
+    fn unpack_inline(&self) -> RuntimeValue {
+        let payload = self.payload();
+        match self.tag() {
+            None => RuntimeValue::Float(f64::from_bits(self.bits)),
+            Some(TAG_INT) => RuntimeValue::Int(((self.bits << 16) as i64) >> 16),
+            Some(TAG_SCALAR) => match payload >> SCALAR_SHIFT {
+                SCALAR_BOOL => RuntimeValue::Bool(payload & 1 == 1),
+                SCALAR_CHAR => RuntimeValue::Char(payload as u32),
+                _ => RuntimeValue::Unit,
+            },
+            Some(TAG_TUPLE) => RuntimeValue::Tuple(Handle(payload as usize)),
+            Some(TAG_ARRAY) => RuntimeValue::Array(Handle(payload as usize)),
+            Some(TAG_LIST) => RuntimeValue::List(Handle(payload as usize)),
+            Some(TAG_DICT) => RuntimeValue::Dict(Handle(payload as usize)),
+            Some(tag) => unreachable!("value tag {tag} is not inline"),
+        }
+    }
+}
+
+impl Clone for Value {
+    #[inline]
+    fn clone(&self) -> Self {
+        match self.box_ptr() {
+            Some(ptr) => {
+                // SAFETY: the pointer came from `Arc::into_raw` and is still live
+                unsafe { Arc::increment_strong_count(ptr) };
+                Self::from_box(ptr as usize)
+            }
+            None => Self::from_bits(self.bits),
+        }
+    }
+}
+
+impl Drop for Value {
+    #[inline]
+    fn drop(&mut self) {
+        let ptr = match self.tag() {
+            Some(TAG_BOXED) => self.payload() as usize,
+            Some(TAG_WIDE) => wide_pointers().remove(self.payload()),
+            _ => return,
+        };
+        // SAFETY: releases the strong count this value owns
+        unsafe { Arc::decrement_strong_count(ptr as *const RuntimeValue) };
+    }
+}
+
+impl Default for Value {
+    fn default() -> Self {
+        Self::UNIT
+    }
+}
+
+impl From<RuntimeValue> for Value {
+    fn from(value: RuntimeValue) -> Self {
+        Self::new(value)
+    }
+}
+
+impl From<&RuntimeValue> for Value {
+    fn from(value: &RuntimeValue) -> Self {
+        Self::new(value.clone())
+    }
+}
+
+impl From<Value> for RuntimeValue {
+    fn from(value: Value) -> Self {
+        value.into_inner()
+    }
+}
+
+impl PartialEq for Value {
+    fn eq(
+        &self,
+        other: &Self,
+    ) -> bool {
+        (self.bits == other.bits && !self.is_boxed()) || self.view() == other.view()
+    }
+}
+
+impl Eq for Value {}
+
+impl PartialEq<RuntimeValue> for Value {
+    fn eq(
+        &self,
+        other: &RuntimeValue,
+    ) -> bool {
+        *self.view() == *other
+    }
+}
+
+impl fmt::Debug for Value {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        fmt::Debug::fmt(&*self.view(), f)
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        fmt::Display::fmt(&*self.view(), f)
+    }
+}
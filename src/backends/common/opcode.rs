@@ -52,6 +52,9 @@ pub enum Opcode {
     /// Label definition
     Label = 0x0B,
 
+    /// Jump table dispatch on a dense integer or char range, or on an enum variant id
+    TableSwitch = 0x0C,
+
     /// Spawn a new concurrent task (dynamic call)
     Spawn = 0x0E,
 
@@ -244,6 +247,12 @@ pub enum Opcode {
     /// Create Rc (non-atomic reference count)
     RcNew = 0x89,
 
+    /// Interface method call through an existential value's vtable
+    InvokeVirtual = 0x8A,
+
+    /// Pack a value with an interface vtable into an existential value
+    MakeDyn = 0x8B,
+
     // =====================
     // String Operations (0x90-0x9F)
     // =====================
@@ -253,6 +262,12 @@ pub enum Opcode {
     StringGetChar = 0x93,
     StringFromInt = 0x94,
     StringFromFloat = 0x95,
+    /// 新建字符串构建器（循环内反复拼接改写而来）
+    StringBuilderNew = 0x96,
+    /// 原地追加到字符串构建器
+    StringBuilderAppend = 0x97,
+    /// 构建器拼成字符串
+    StringBuilderFinish = 0x98,
 
     // =====================
     // Exception Handling (0xA0-0xAF)
@@ -272,11 +287,19 @@ pub enum Opcode {
     // =====================
     TypeCheck = 0xC0,
     Cast = 0xC1,
+    /// 定宽算术结果收窄（目标类型编号同 `Cast`）
+    Narrow = 0xC2,
+    /// `as?` 向下转换：按运行时类型标签检查，不符时得到 void
+    TypeTest = 0xC3,
+    /// 整数转浮点（隐式 Int → Float）
+    I64ToF64 = 0xC4,
 
     // =====================
     // Reflection (0xD0-0xDF)
     // =====================
     TypeOf = 0xD0,
+    /// Read a struct field by name through the value's layout (anonymous records)
+    GetRecordField = 0xD1,
 
     // =====================
     // Reserved (0xE0-0xFF)
@@ -304,6 +327,7 @@ impl Opcode {
             Opcode::TailCall => "TailCall",
             Opcode::Yield => "Yield",
             Opcode::Label => "Label",
+            Opcode::TableSwitch => "TableSwitch",
             Opcode::Spawn => "Spawn",
             Opcode::SpawnFromList => "SpawnFromList",
             Opcode::Mov => "Mov",
@@ -390,6 +414,8 @@ impl Opcode {
             Opcode::WeakUpgrade => "WeakUpgrade",
             Opcode::CallStatic => "CallStatic",
             Opcode::CallVirt => "CallVirt",
+            Opcode::InvokeVirtual => "InvokeVirtual",
+            Opcode::MakeDyn => "MakeDyn",
             Opcode::CallDyn => "CallDyn",
             Opcode::MakeClosure => "MakeClosure",
             Opcode::LoadUpvalue => "LoadUpvalue",
@@ -403,6 +429,9 @@ impl Opcode {
             Opcode::StringGetChar => "StringGetChar",
             Opcode::StringFromInt => "StringFromInt",
             Opcode::StringFromFloat => "StringFromFloat",
+            Opcode::StringBuilderNew => "StringBuilderNew",
+            Opcode::StringBuilderAppend => "StringBuilderAppend",
+            Opcode::StringBuilderFinish => "StringBuilderFinish",
             Opcode::TryBegin => "TryBegin",
             Opcode::TryEnd => "TryEnd",
             Opcode::Throw => "Throw",
@@ -410,7 +439,11 @@ impl Opcode {
             Opcode::BoundsCheck => "BoundsCheck",
             Opcode::TypeCheck => "TypeCheck",
             Opcode::Cast => "Cast",
+            Opcode::Narrow => "Narrow",
+            Opcode::TypeTest => "TypeTest",
+            Opcode::I64ToF64 => "I64ToF64",
             Opcode::TypeOf => "TypeOf",
+            Opcode::GetRecordField => "GetRecordField",
             Opcode::Custom0 => "Custom0",
             Opcode::Custom1 => "Custom1",
             Opcode::Custom2 => "Custom2",
@@ -466,7 +499,11 @@ impl Opcode {
     pub fn is_call_op(&self) -> bool {
         matches!(
             self,
-            Opcode::CallStatic | Opcode::CallVirt | Opcode::CallDyn | Opcode::CallNative
+            Opcode::CallStatic
+                | Opcode::CallVirt
+                | Opcode::CallDyn
+                | Opcode::CallNative
+                | Opcode::InvokeVirtual
         )
     }
 
@@ -486,6 +523,7 @@ impl Opcode {
                 | Opcode::JmpIf
                 | Opcode::JmpIfNot
                 | Opcode::Switch
+                | Opcode::TableSwitch
                 | Opcode::LoopStart
                 | Opcode::LoopInc
         )
@@ -504,6 +542,41 @@ impl Opcode {
         matches!(self, Opcode::StoreLocal | Opcode::StoreElement)
     }
 
+    /// 操作数中常量池索引的位置：`(起始字节, 宽度)`，宽度为 2 或 4 字节（小端）
+    ///
+    /// 与字节码解码器读取操作数的布局一致，合并常量池时据此改写索引。
+    pub fn const_operands(
+        &self,
+        operands: &[u8],
+    ) -> Vec<(usize, usize)> {
+        match self {
+            // dst(1) + const_idx(2)
+            Opcode::LoadConst => vec![(1, 2)],
+            // dst(1) + obj(1) + name_idx(2) + ...
+            Opcode::CallVirt => vec![(2, 2)],
+            // dst(1) + obj(1) + slot(2) + name_idx(2) + ...
+            Opcode::InvokeVirtual => vec![(4, 2)],
+            // func_name_idx(4) + base(1) + count(1) + args(2*count)
+            Opcode::TailCall => vec![(0, 4)],
+            // dst(1) + func_name_idx(4) + base(1) + count(1) + args(2*count)
+            Opcode::CallStatic => vec![(1, 4)],
+            // 同 CallStatic；FFI 函数在 func_name_idx 后多出 mechanism/lib/symbol(4*3)
+            Opcode::CallNative => {
+                let arg_count = operands.get(6).copied().unwrap_or(0) as usize;
+                if operands.len() > 7 && operands.len() != 7 + 2 * arg_count {
+                    vec![(1, 4), (5, 4), (9, 4), (13, 4)]
+                } else {
+                    vec![(1, 4)]
+                }
+            }
+            // dst(1) + src(1) + name_idx(4)
+            Opcode::GetRecordField | Opcode::TypeTest => vec![(2, 4)],
+            // dst(1) + type_name_idx(4) + field_count(1) + fields(2*count)
+            Opcode::CreateStruct => vec![(1, 4)],
+            _ => Vec::new(),
+        }
+    }
+
     /// Get the number of operands for this opcode
     pub fn operand_count(&self) -> u8 {
         match self {
@@ -519,7 +592,6 @@ impl Opcode {
             | Opcode::Rethrow
             | Opcode::BoundsCheck
             | Opcode::TypeCheck
-            | Opcode::StackAlloc
             | Opcode::ArcDrop
             | Opcode::TryBegin
             | Opcode::TypeOf
@@ -541,6 +613,7 @@ impl Opcode {
             | Opcode::I32Neg
             | Opcode::F64Neg
             | Opcode::F32Neg
+            | Opcode::StackAlloc
             | Opcode::HeapAlloc
             | Opcode::ArcNew
             | Opcode::ArcClone
@@ -549,13 +622,18 @@ impl Opcode {
             | Opcode::StringLength
             | Opcode::StringFromInt
             | Opcode::StringFromFloat
+            | Opcode::StringBuilderNew
+            | Opcode::StringBuilderFinish
             | Opcode::Cast
+            | Opcode::Narrow
+            | Opcode::I64ToF64
             | Opcode::LoadUpvalue
             | Opcode::StoreUpvalue
             | Opcode::Borrow => 2,
 
             // 3 operands
             Opcode::Switch
+            | Opcode::TableSwitch
             | Opcode::LoopInc
             | Opcode::I64Add
             | Opcode::I64Sub
@@ -608,24 +686,32 @@ impl Opcode {
             | Opcode::F32Gt
             | Opcode::F32Ge
             | Opcode::GetField
+            | Opcode::GetRecordField
+            | Opcode::TypeTest
             | Opcode::SetField
-            | Opcode::NewListWithCap => 3,
+            | Opcode::NewListWithCap
+            | Opcode::MakeDyn => 3,
 
             // Variable operands (like calls)
             Opcode::CreateStruct | Opcode::NewDict | Opcode::Spawn | Opcode::SpawnFromList => 5,
 
             // 4 operands
             Opcode::LoopStart
-            | Opcode::TailCall
             | Opcode::MakeClosure
             | Opcode::LoadElement
             | Opcode::StoreElement
             | Opcode::StringConcat
+            | Opcode::StringBuilderAppend
             | Opcode::StringEqual
             | Opcode::StringGetChar => 4,
 
             // 5 operands (function calls)
-            Opcode::CallStatic | Opcode::CallVirt | Opcode::CallDyn | Opcode::CallNative => 5,
+            Opcode::CallStatic
+            | Opcode::TailCall
+            | Opcode::CallVirt
+            | Opcode::CallDyn
+            | Opcode::CallNative
+            | Opcode::InvokeVirtual => 5,
 
             // Default
             _ => 0,
@@ -660,6 +746,7 @@ impl TryFrom<u8> for Opcode {
             0x09 => Ok(Opcode::TailCall),
             0x0A => Ok(Opcode::Yield),
             0x0B => Ok(Opcode::Label),
+            0x0C => Ok(Opcode::TableSwitch),
             0x0E => Ok(Opcode::Spawn),
             0x0F => Ok(Opcode::SpawnFromList),
             0x10 => Ok(Opcode::Mov),
@@ -698,12 +785,17 @@ impl TryFrom<u8> for Opcode {
             0x87 => Ok(Opcode::CallNative),
             0x88 => Ok(Opcode::NewDict),
             0x89 => Ok(Opcode::RcNew),
+            0x8A => Ok(Opcode::InvokeVirtual),
+            0x8B => Ok(Opcode::MakeDyn),
             0x90 => Ok(Opcode::StringLength),
             0x91 => Ok(Opcode::StringConcat),
             0x92 => Ok(Opcode::StringEqual),
             0x93 => Ok(Opcode::StringGetChar),
             0x94 => Ok(Opcode::StringFromInt),
             0x95 => Ok(Opcode::StringFromFloat),
+            0x96 => Ok(Opcode::StringBuilderNew),
+            0x97 => Ok(Opcode::StringBuilderAppend),
+            0x98 => Ok(Opcode::StringBuilderFinish),
             0xA0 => Ok(Opcode::TryBegin),
             0xA1 => Ok(Opcode::TryEnd),
             0xA2 => Ok(Opcode::Throw),
@@ -711,7 +803,11 @@ impl TryFrom<u8> for Opcode {
             0xB0 => Ok(Opcode::BoundsCheck),
             0xC0 => Ok(Opcode::TypeCheck),
             0xC1 => Ok(Opcode::Cast),
+            0xC2 => Ok(Opcode::Narrow),
+            0xC3 => Ok(Opcode::TypeTest),
+            0xC4 => Ok(Opcode::I64ToF64),
             0xD0 => Ok(Opcode::TypeOf),
+            0xD1 => Ok(Opcode::GetRecordField),
             0xE0 => Ok(Opcode::Custom0),
             0xE1 => Ok(Opcode::Custom1),
             0xE2 => Ok(Opcode::Custom2),
@@ -0,0 +1,1128 @@
+//! serde support for runtime values
+//!
+//! A [`RuntimeValue`] is only meaningful next to the heap its handles point
+//! into, so serde works on pairs:
+//!
+//! - [`ValueRef`] (a value and its heap) implements `Serialize`, so a script
+//!   value can be written as JSON, RON or any other format, and `Deserializer`,
+//!   so a Rust type can be read out of it ([`from_value`])
+//! - [`ValueSerializer`] builds a value on a heap from any `Serialize` type
+//!   ([`to_value`]); [`ValueSeed`] does the same for any `Deserializer`
+//! - [`SendValue`], which carries its own heap, implements both traits and is
+//!   the owned form for snapshots
+//!
+//! Collections map to sequences, dicts to maps, and structs to maps keyed by
+//! field name when the struct layouts are known ([`ValueRef::with_struct_types`])
+//! or sequences otherwise. A script enum value becomes `{"variant": id,
+//! "value": payload}`; Rust enums use serde's external tagging (`"Unit"` or
+//! `{"Variant": payload}`). Functions, references, tasks and pointers cannot be
+//! serialized, and neither can a value that contains itself.
+
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use serde::de::value::{MapDeserializer, SeqDeserializer};
+use serde::de::{
+    self, DeserializeOwned, DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess, SeqAccess,
+    VariantAccess, Visitor,
+};
+use serde::ser::{self, Serialize, SerializeMap, SerializeSeq, Serializer};
+use serde::Deserialize;
+
+use super::heap::{Handle, Heap, HeapValue};
+use super::nanbox::Value;
+use super::struct_types::StructTypes;
+use super::transfer::SendValue;
+use super::value::RuntimeValue;
+
+/// Why a value could not be serialized or deserialized
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SerdeError(String);
+
+impl fmt::Display for SerdeError {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for SerdeError {}
+
+impl ser::Error for SerdeError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        SerdeError(msg.to_string())
+    }
+}
+
+impl de::Error for SerdeError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        SerdeError(msg.to_string())
+    }
+}
+
+impl From<SerdeError> for crate::backends::ExecutorError {
+    fn from(error: SerdeError) -> Self {
+        crate::backends::ExecutorError::type_only(error.0)
+    }
+}
+
+/// Build a value on `heap` from any serializable Rust value
+pub fn to_value<T: Serialize + ?Sized>(
+    value: &T,
+    heap: &mut Heap,
+) -> Result<RuntimeValue, SerdeError> {
+    value.serialize(ValueSerializer { heap })
+}
+
+/// Read a Rust value out of `value`, following its handles on `heap`
+pub fn from_value<T: DeserializeOwned>(
+    value: &RuntimeValue,
+    heap: &Heap,
+) -> Result<T, SerdeError> {
+    T::deserialize(ValueRef::new(value, heap))
+}
+
+// ── Serializing script values ───────────────────────────────
+
+/// A value together with the heap its handles point into
+#[derive(Clone)]
+pub struct ValueRef<'a> {
+    value: Cow<'a, RuntimeValue>,
+    heap: &'a Heap,
+    struct_types: Option<&'a StructTypes>,
+}
+
+impl<'a> ValueRef<'a> {
+    /// Pair `value` with `heap`
+    pub fn new(
+        value: &'a RuntimeValue,
+        heap: &'a Heap,
+    ) -> Self {
+        Self {
+            value: Cow::Borrowed(value),
+            heap,
+            struct_types: None,
+        }
+    }
+
+    /// Write struct values as maps keyed by the field names in `struct_types`
+    pub fn with_struct_types(
+        mut self,
+        struct_types: &'a StructTypes,
+    ) -> Self {
+        self.struct_types = Some(struct_types);
+        self
+    }
+
+    fn child(
+        &self,
+        value: Cow<'a, RuntimeValue>,
+    ) -> Self {
+        Self {
+            value,
+            heap: self.heap,
+            struct_types: self.struct_types,
+        }
+    }
+
+    fn object(
+        &self,
+        handle: Handle,
+    ) -> Result<&'a HeapValue, SerdeError> {
+        self.heap
+            .get(handle)
+            .ok_or_else(|| SerdeError(format!("invalid handle: {}", handle)))
+    }
+
+    /// Field names of a struct value, if its layout is known
+    fn field_names(&self) -> Option<&'a [String]> {
+        match &*self.value {
+            RuntimeValue::Struct { type_id, .. } => self
+                .struct_types?
+                .get(*type_id)
+                .map(|info| info.fields.as_slice()),
+            _ => None,
+        }
+    }
+
+    fn unsupported(&self) -> SerdeError {
+        SerdeError(format!(
+            "cannot serialize a value of type {}",
+            self.value.value_type(Some(self.heap)).name()
+        ))
+    }
+}
+
+impl Serialize for ValueRef<'_> {
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        // Depth-first with the handles on the current path, so a cycle is
+        // reported instead of recursing forever
+        Guarded {
+            value: self.clone(),
+            path: &RefCell::new(HashSet::new()),
+        }
+        .serialize(serializer)
+    }
+}
+
+/// A value being serialized, with the handles of its enclosing collections
+struct Guarded<'a, 'p> {
+    value: ValueRef<'a>,
+    path: &'p RefCell<HashSet<Handle>>,
+}
+
+impl<'a, 'p> Guarded<'a, 'p> {
+    fn child(
+        &self,
+        value: Cow<'a, RuntimeValue>,
+    ) -> Self {
+        Self {
+            value: self.value.child(value),
+            path: self.path,
+        }
+    }
+
+    fn serialize_object<S: Serializer>(
+        &self,
+        handle: Handle,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let object = self.value.object(handle).map_err(ser::Error::custom)?;
+        match (object, self.value.field_names()) {
+            (HeapValue::Dict(map), _) => {
+                let mut out = serializer.serialize_map(Some(map.len()))?;
+                for (k, v) in map {
+                    out.serialize_entry(
+                        &self.child(Cow::Borrowed(k)),
+                        &self.child(Cow::Borrowed(v)),
+                    )?;
+                }
+                out.end()
+            }
+            (HeapValue::Tuple(items) | HeapValue::Struct(items), Some(names)) => {
+                let mut out = serializer.serialize_map(Some(items.len()))?;
+                for (name, v) in names.iter().zip(items) {
+                    out.serialize_entry(name, &self.child(v.view()))?;
+                }
+                out.end()
+            }
+            (
+                HeapValue::Tuple(items)
+                | HeapValue::Array(items)
+                | HeapValue::List(items)
+                | HeapValue::Struct(items),
+                _,
+            ) => {
+                let mut out = serializer.serialize_seq(Some(items.len()))?;
+                for v in items {
+                    out.serialize_element(&self.child(v.view()))?;
+                }
+                out.end()
+            }
+        }
+    }
+}
+
+impl Serialize for Guarded<'_, '_> {
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let value = &self.value;
+        match &*value.value {
+            RuntimeValue::Unit => serializer.serialize_unit(),
+            RuntimeValue::Bool(b) => serializer.serialize_bool(*b),
+            RuntimeValue::Int(n) => serializer.serialize_i64(*n),
+            RuntimeValue::Float(f) => serializer.serialize_f64(*f),
+            RuntimeValue::Char(c) => match char::from_u32(*c) {
+                Some(c) => serializer.serialize_char(c),
+                None => Err(ser::Error::custom(format!("invalid char: {:#x}", c))),
+            },
+            RuntimeValue::String(s) => serializer.serialize_str(s),
+            RuntimeValue::Bytes(b) => serializer.serialize_bytes(b),
+            RuntimeValue::Tuple(h)
+            | RuntimeValue::Array(h)
+            | RuntimeValue::List(h)
+            | RuntimeValue::Dict(h)
+            | RuntimeValue::Struct { fields: h, .. } => {
+                if !self.path.borrow_mut().insert(*h) {
+                    return Err(ser::Error::custom(
+                        "cannot serialize a value that contains itself",
+                    ));
+                }
+                let result = self.serialize_object(*h, serializer);
+                self.path.borrow_mut().remove(h);
+                result
+            }
+            RuntimeValue::Enum {
+                variant_id,
+                payload,
+                ..
+            } => {
+                let mut out = serializer.serialize_map(Some(2))?;
+                out.serialize_entry("variant", variant_id)?;
+                out.serialize_entry("value", &self.child(Cow::Owned(payload.as_ref().clone())))?;
+                out.end()
+            }
+            RuntimeValue::Dyn { value: inner, .. } => self
+                .child(Cow::Owned(inner.as_ref().clone()))
+                .serialize(serializer),
+            _ => Err(ser::Error::custom(value.unsupported())),
+        }
+    }
+}
+
+// ── Reading Rust values out of script values ────────────────
+
+impl<'de> IntoDeserializer<'de, SerdeError> for ValueRef<'_> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+impl<'de> de::Deserializer<'de> for ValueRef<'_> {
+    type Error = SerdeError;
+
+    fn deserialize_any<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        match &*self.value {
+            RuntimeValue::Unit => visitor.visit_unit(),
+            RuntimeValue::Bool(b) => visitor.visit_bool(*b),
+            RuntimeValue::Int(n) => visitor.visit_i64(*n),
+            RuntimeValue::Float(f) => visitor.visit_f64(*f),
+            RuntimeValue::Char(c) => match char::from_u32(*c) {
+                Some(c) => visitor.visit_char(c),
+                None => Err(SerdeError(format!("invalid char: {:#x}", c))),
+            },
+            RuntimeValue::String(s) => visitor.visit_str(s),
+            RuntimeValue::Bytes(b) => visitor.visit_bytes(b),
+            RuntimeValue::Tuple(h)
+            | RuntimeValue::Array(h)
+            | RuntimeValue::List(h)
+            | RuntimeValue::Dict(h)
+            | RuntimeValue::Struct { fields: h, .. } => {
+                match (self.object(*h)?, self.field_names()) {
+                    (HeapValue::Dict(map), _) => {
+                        let entries = map.iter().map(|(k, v)| {
+                            (self.child(Cow::Borrowed(k)), self.child(Cow::Borrowed(v)))
+                        });
+                        let mut access = MapDeserializer::new(entries);
+                        let value = visitor.visit_map(&mut access)?;
+                        access.end()?;
+                        Ok(value)
+                    }
+                    (HeapValue::Tuple(items), Some(names))
+                    | (HeapValue::Struct(items), Some(names)) => {
+                        let entries = names
+                            .iter()
+                            .map(|name| name.as_str())
+                            .zip(items.iter().map(|v| self.child(v.view())));
+                        let mut access = MapDeserializer::new(entries);
+                        let value = visitor.visit_map(&mut access)?;
+                        access.end()?;
+                        Ok(value)
+                    }
+                    (
+                        HeapValue::Tuple(items)
+                        | HeapValue::Array(items)
+                        | HeapValue::List(items)
+                        | HeapValue::Struct(items),
+                        _,
+                    ) => {
+                        let mut access =
+                            SeqDeserializer::new(items.iter().map(|v| self.child(v.view())));
+                        let value = visitor.visit_seq(&mut access)?;
+                        access.end()?;
+                        Ok(value)
+                    }
+                }
+            }
+            RuntimeValue::Enum {
+                variant_id,
+                payload,
+                ..
+            } => {
+                let entries = [
+                    ("variant", ValueOrId::Id(*variant_id)),
+                    (
+                        "value",
+                        ValueOrId::Value(self.child(Cow::Owned(payload.as_ref().clone()))),
+                    ),
+                ];
+                let mut access = MapDeserializer::new(entries.into_iter());
+                let value = visitor.visit_map(&mut access)?;
+                access.end()?;
+                Ok(value)
+            }
+            RuntimeValue::Dyn { value, .. } => self
+                .child(Cow::Owned(value.as_ref().clone()))
+                .deserialize_any(visitor),
+            _ => Err(SerdeError(format!(
+                "cannot deserialize from a value of type {}",
+                self.value.value_type(Some(self.heap)).name()
+            ))),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        match &*self.value {
+            RuntimeValue::Unit => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        match &*self.value {
+            RuntimeValue::String(_) => visitor.visit_enum(Variant {
+                tag: self,
+                value: None,
+            }),
+            RuntimeValue::Dict(h) => match self.object(*h)? {
+                HeapValue::Dict(map) if map.len() == 1 => {
+                    let (tag, value) = map.iter().next().unwrap();
+                    visitor.visit_enum(Variant {
+                        tag: self.child(Cow::Borrowed(tag)),
+                        value: Some(self.child(Cow::Borrowed(value))),
+                    })
+                }
+                _ => Err(SerdeError(
+                    "expected a dict with a single variant entry".to_string(),
+                )),
+            },
+            _ => Err(SerdeError(format!(
+                "expected an enum variant, found {}",
+                self.value.value_type(Some(self.heap)).name()
+            ))),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct
+        identifier ignored_any
+    }
+}
+
+/// The `variant` id or `value` payload of a script enum value
+enum ValueOrId<'a> {
+    Id(u32),
+    Value(ValueRef<'a>),
+}
+
+impl<'de> IntoDeserializer<'de, SerdeError> for ValueOrId<'_> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+impl<'de> de::Deserializer<'de> for ValueOrId<'_> {
+    type Error = SerdeError;
+
+    fn deserialize_any<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        match self {
+            ValueOrId::Id(id) => visitor.visit_u32(id),
+            ValueOrId::Value(value) => value.deserialize_any(visitor),
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+/// An externally tagged Rust enum variant read from a string or single-entry dict
+struct Variant<'a> {
+    tag: ValueRef<'a>,
+    value: Option<ValueRef<'a>>,
+}
+
+impl<'de, 'a> EnumAccess<'de> for Variant<'a> {
+    type Error = SerdeError;
+    type Variant = Payload<'a>;
+
+    fn variant_seed<T: DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<(T::Value, Self::Variant), SerdeError> {
+        Ok((seed.deserialize(self.tag)?, Payload(self.value)))
+    }
+}
+
+/// The payload of a variant; unit variants have none
+struct Payload<'a>(Option<ValueRef<'a>>);
+
+impl<'de> VariantAccess<'de> for Payload<'_> {
+    type Error = SerdeError;
+
+    fn unit_variant(self) -> Result<(), SerdeError> {
+        match self.0 {
+            None => Ok(()),
+            Some(value) => de::Deserialize::deserialize(value),
+        }
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<T::Value, SerdeError> {
+        match self.0 {
+            Some(value) => seed.deserialize(value),
+            None => Err(SerdeError("expected a variant payload".to_string())),
+        }
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        match self.0 {
+            Some(value) => de::Deserializer::deserialize_seq(value, visitor),
+            None => Err(SerdeError("expected a variant payload".to_string())),
+        }
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        match self.0 {
+            Some(value) => de::Deserializer::deserialize_map(value, visitor),
+            None => Err(SerdeError("expected a variant payload".to_string())),
+        }
+    }
+}
+
+// ── Building script values ──────────────────────────────────
+
+/// Serializer that builds a script value on a heap
+///
+/// Sequences become lists, maps and structs become dicts, `None` and `()`
+/// become `Unit`; Rust enum variants are tagged externally like serde_json.
+pub struct ValueSerializer<'h> {
+    /// Heap the built collections are allocated on
+    pub heap: &'h mut Heap,
+}
+
+/// Collects the elements of a sequence, or the entries of a map, being built
+pub struct Collect<'h> {
+    heap: &'h mut Heap,
+    items: Vec<Value>,
+    entries: HashMap<RuntimeValue, RuntimeValue>,
+    next_key: Option<RuntimeValue>,
+    /// Variant tag to wrap the result in (`{tag: value}`)
+    variant: Option<&'static str>,
+}
+
+impl<'h> Collect<'h> {
+    fn new(
+        heap: &'h mut Heap,
+        variant: Option<&'static str>,
+    ) -> Self {
+        Self {
+            heap,
+            items: Vec::new(),
+            entries: HashMap::new(),
+            next_key: None,
+            variant,
+        }
+    }
+
+    fn push<T: Serialize + ?Sized>(
+        &mut self,
+        value: &T,
+    ) -> Result<(), SerdeError> {
+        let value = to_value(value, self.heap)?;
+        self.items.push(value.into());
+        Ok(())
+    }
+
+    fn insert<T: Serialize + ?Sized>(
+        &mut self,
+        key: RuntimeValue,
+        value: &T,
+    ) -> Result<(), SerdeError> {
+        let value = to_value(value, self.heap)?;
+        self.entries.insert(key, value);
+        Ok(())
+    }
+
+    fn finish(
+        self,
+        object: HeapValue,
+    ) -> Result<RuntimeValue, SerdeError> {
+        let value = match object {
+            HeapValue::Dict(_) => RuntimeValue::Dict(allocate(self.heap, object)?),
+            _ => RuntimeValue::List(allocate(self.heap, object)?),
+        };
+        match self.variant {
+            Some(tag) => tagged(self.heap, tag, value),
+            None => Ok(value),
+        }
+    }
+
+    fn finish_list(mut self) -> Result<RuntimeValue, SerdeError> {
+        let items = std::mem::take(&mut self.items);
+        self.finish(HeapValue::List(items))
+    }
+
+    fn finish_dict(mut self) -> Result<RuntimeValue, SerdeError> {
+        let entries = std::mem::take(&mut self.entries);
+        self.finish(HeapValue::Dict(entries))
+    }
+}
+
+/// Allocate within the heap's limit
+fn allocate(
+    heap: &mut Heap,
+    object: HeapValue,
+) -> Result<Handle, SerdeError> {
+    heap.try_allocate(object)
+        .map_err(|e| SerdeError(e.to_string()))
+}
+
+/// `{tag: value}`
+fn tagged(
+    heap: &mut Heap,
+    tag: &str,
+    value: RuntimeValue,
+) -> Result<RuntimeValue, SerdeError> {
+    let mut map = HashMap::new();
+    map.insert(RuntimeValue::String(tag.into()), value);
+    Ok(RuntimeValue::Dict(allocate(heap, HeapValue::Dict(map))?))
+}
+
+impl<'h> Serializer for ValueSerializer<'h> {
+    type Ok = RuntimeValue;
+    type Error = SerdeError;
+    type SerializeSeq = Collect<'h>;
+    type SerializeTuple = Collect<'h>;
+    type SerializeTupleStruct = Collect<'h>;
+    type SerializeTupleVariant = Collect<'h>;
+    type SerializeMap = Collect<'h>;
+    type SerializeStruct = Collect<'h>;
+    type SerializeStructVariant = Collect<'h>;
+
+    fn serialize_bool(
+        self,
+        v: bool,
+    ) -> Result<RuntimeValue, SerdeError> {
+        Ok(RuntimeValue::Bool(v))
+    }
+
+    fn serialize_i8(
+        self,
+        v: i8,
+    ) -> Result<RuntimeValue, SerdeError> {
+        Ok(RuntimeValue::Int(v.into()))
+    }
+
+    fn serialize_i16(
+        self,
+        v: i16,
+    ) -> Result<RuntimeValue, SerdeError> {
+        Ok(RuntimeValue::Int(v.into()))
+    }
+
+    fn serialize_i32(
+        self,
+        v: i32,
+    ) -> Result<RuntimeValue, SerdeError> {
+        Ok(RuntimeValue::Int(v.into()))
+    }
+
+    fn serialize_i64(
+        self,
+        v: i64,
+    ) -> Result<RuntimeValue, SerdeError> {
+        Ok(RuntimeValue::Int(v))
+    }
+
+    fn serialize_u8(
+        self,
+        v: u8,
+    ) -> Result<RuntimeValue, SerdeError> {
+        Ok(RuntimeValue::Int(v.into()))
+    }
+
+    fn serialize_u16(
+        self,
+        v: u16,
+    ) -> Result<RuntimeValue, SerdeError> {
+        Ok(RuntimeValue::Int(v.into()))
+    }
+
+    fn serialize_u32(
+        self,
+        v: u32,
+    ) -> Result<RuntimeValue, SerdeError> {
+        Ok(RuntimeValue::Int(v.into()))
+    }
+
+    fn serialize_u64(
+        self,
+        v: u64,
+    ) -> Result<RuntimeValue, SerdeError> {
+        i64::try_from(v)
+            .map(RuntimeValue::Int)
+            .map_err(|_| SerdeError(format!("{} does not fit in Int", v)))
+    }
+
+    fn serialize_f32(
+        self,
+        v: f32,
+    ) -> Result<RuntimeValue, SerdeError> {
+        Ok(RuntimeValue::Float(v.into()))
+    }
+
+    fn serialize_f64(
+        self,
+        v: f64,
+    ) -> Result<RuntimeValue, SerdeError> {
+        Ok(RuntimeValue::Float(v))
+    }
+
+    fn serialize_char(
+        self,
+        v: char,
+    ) -> Result<RuntimeValue, SerdeError> {
+        Ok(RuntimeValue::Char(v as u32))
+    }
+
+    fn serialize_str(
+        self,
+        v: &str,
+    ) -> Result<RuntimeValue, SerdeError> {
+        Ok(RuntimeValue::String(v.into()))
+    }
+
+    fn serialize_bytes(
+        self,
+        v: &[u8],
+    ) -> Result<RuntimeValue, SerdeError> {
+        Ok(RuntimeValue::Bytes(v.into()))
+    }
+
+    fn serialize_none(self) -> Result<RuntimeValue, SerdeError> {
+        Ok(RuntimeValue::Unit)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(
+        self,
+        value: &T,
+    ) -> Result<RuntimeValue, SerdeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<RuntimeValue, SerdeError> {
+        Ok(RuntimeValue::Unit)
+    }
+
+    fn serialize_unit_struct(
+        self,
+        _name: &'static str,
+    ) -> Result<RuntimeValue, SerdeError> {
+        Ok(RuntimeValue::Unit)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<RuntimeValue, SerdeError> {
+        Ok(RuntimeValue::String(variant.into()))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<RuntimeValue, SerdeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<RuntimeValue, SerdeError> {
+        let value = to_value(value, self.heap)?;
+        tagged(self.heap, variant, value)
+    }
+
+    fn serialize_seq(
+        self,
+        _len: Option<usize>,
+    ) -> Result<Collect<'h>, SerdeError> {
+        Ok(Collect::new(self.heap, None))
+    }
+
+    fn serialize_tuple(
+        self,
+        _len: usize,
+    ) -> Result<Collect<'h>, SerdeError> {
+        Ok(Collect::new(self.heap, None))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Collect<'h>, SerdeError> {
+        Ok(Collect::new(self.heap, None))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Collect<'h>, SerdeError> {
+        Ok(Collect::new(self.heap, Some(variant)))
+    }
+
+    fn serialize_map(
+        self,
+        _len: Option<usize>,
+    ) -> Result<Collect<'h>, SerdeError> {
+        Ok(Collect::new(self.heap, None))
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Collect<'h>, SerdeError> {
+        Ok(Collect::new(self.heap, None))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Collect<'h>, SerdeError> {
+        Ok(Collect::new(self.heap, Some(variant)))
+    }
+}
+
+impl SerializeSeq for Collect<'_> {
+    type Ok = RuntimeValue;
+    type Error = SerdeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(
+        &mut self,
+        value: &T,
+    ) -> Result<(), SerdeError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<RuntimeValue, SerdeError> {
+        self.finish_list()
+    }
+}
+
+impl ser::SerializeTuple for Collect<'_> {
+    type Ok = RuntimeValue;
+    type Error = SerdeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(
+        &mut self,
+        value: &T,
+    ) -> Result<(), SerdeError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<RuntimeValue, SerdeError> {
+        self.finish_list()
+    }
+}
+
+impl ser::SerializeTupleStruct for Collect<'_> {
+    type Ok = RuntimeValue;
+    type Error = SerdeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        value: &T,
+    ) -> Result<(), SerdeError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<RuntimeValue, SerdeError> {
+        self.finish_list()
+    }
+}
+
+impl ser::SerializeTupleVariant for Collect<'_> {
+    type Ok = RuntimeValue;
+    type Error = SerdeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        value: &T,
+    ) -> Result<(), SerdeError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<RuntimeValue, SerdeError> {
+        self.finish_list()
+    }
+}
+
+impl SerializeMap for Collect<'_> {
+    type Ok = RuntimeValue;
+    type Error = SerdeError;
+
+    fn serialize_key<T: Serialize + ?Sized>(
+        &mut self,
+        key: &T,
+    ) -> Result<(), SerdeError> {
+        self.next_key = Some(to_value(key, self.heap)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(
+        &mut self,
+        value: &T,
+    ) -> Result<(), SerdeError> {
+        let key = self
+            .next_key
+            .take()
+            .ok_or_else(|| SerdeError("map value without a key".to_string()))?;
+        self.insert(key, value)
+    }
+
+    fn end(self) -> Result<RuntimeValue, SerdeError> {
+        self.finish_dict()
+    }
+}
+
+impl ser::SerializeStruct for Collect<'_> {
+    type Ok = RuntimeValue;
+    type Error = SerdeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), SerdeError> {
+        self.insert(RuntimeValue::String(key.into()), value)
+    }
+
+    fn end(self) -> Result<RuntimeValue, SerdeError> {
+        self.finish_dict()
+    }
+}
+
+impl ser::SerializeStructVariant for Collect<'_> {
+    type Ok = RuntimeValue;
+    type Error = SerdeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), SerdeError> {
+        self.insert(RuntimeValue::String(key.into()), value)
+    }
+
+    fn end(self) -> Result<RuntimeValue, SerdeError> {
+        self.finish_dict()
+    }
+}
+
+// ── Deserializing script values ─────────────────────────────
+
+/// Builds a script value on a heap from any self-describing format
+pub struct ValueSeed<'h> {
+    /// Heap the built collections are allocated on
+    pub heap: &'h mut Heap,
+}
+
+impl<'de> DeserializeSeed<'de> for ValueSeed<'_> {
+    type Value = RuntimeValue;
+
+    fn deserialize<D: de::Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<RuntimeValue, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for ValueSeed<'_> {
+    type Value = RuntimeValue;
+
+    fn expecting(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        f.write_str("a YaoXiang value")
+    }
+
+    fn visit_bool<E: de::Error>(
+        self,
+        v: bool,
+    ) -> Result<RuntimeValue, E> {
+        Ok(RuntimeValue::Bool(v))
+    }
+
+    fn visit_i64<E: de::Error>(
+        self,
+        v: i64,
+    ) -> Result<RuntimeValue, E> {
+        Ok(RuntimeValue::Int(v))
+    }
+
+    fn visit_u64<E: de::Error>(
+        self,
+        v: u64,
+    ) -> Result<RuntimeValue, E> {
+        i64::try_from(v)
+            .map(RuntimeValue::Int)
+            .map_err(|_| E::custom(format!("{} does not fit in Int", v)))
+    }
+
+    fn visit_f64<E: de::Error>(
+        self,
+        v: f64,
+    ) -> Result<RuntimeValue, E> {
+        Ok(RuntimeValue::Float(v))
+    }
+
+    fn visit_char<E: de::Error>(
+        self,
+        v: char,
+    ) -> Result<RuntimeValue, E> {
+        Ok(RuntimeValue::Char(v as u32))
+    }
+
+    fn visit_str<E: de::Error>(
+        self,
+        v: &str,
+    ) -> Result<RuntimeValue, E> {
+        Ok(RuntimeValue::String(v.into()))
+    }
+
+    fn visit_bytes<E: de::Error>(
+        self,
+        v: &[u8],
+    ) -> Result<RuntimeValue, E> {
+        Ok(RuntimeValue::Bytes(v.into()))
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<RuntimeValue, E> {
+        Ok(RuntimeValue::Unit)
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<RuntimeValue, E> {
+        Ok(RuntimeValue::Unit)
+    }
+
+    fn visit_some<D: de::Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<RuntimeValue, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+
+    fn visit_newtype_struct<D: de::Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<RuntimeValue, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(
+        self,
+        mut seq: A,
+    ) -> Result<RuntimeValue, A::Error> {
+        let mut items = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(item) = seq.next_element_seed(ValueSeed {
+            heap: &mut *self.heap,
+        })? {
+            items.push(Value::from(item));
+        }
+        allocate(self.heap, HeapValue::List(items))
+            .map(RuntimeValue::List)
+            .map_err(de::Error::custom)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(
+        self,
+        mut map: A,
+    ) -> Result<RuntimeValue, A::Error> {
+        let mut entries = HashMap::with_capacity(map.size_hint().unwrap_or(0));
+        while let Some(key) = map.next_key_seed(ValueSeed {
+            heap: &mut *self.heap,
+        })? {
+            let value = map.next_value_seed(ValueSeed {
+                heap: &mut *self.heap,
+            })?;
+            entries.insert(key, value);
+        }
+        allocate(self.heap, HeapValue::Dict(entries))
+            .map(RuntimeValue::Dict)
+            .map_err(de::Error::custom)
+    }
+}
+
+// ── Owned values ────────────────────────────────────────────
+
+impl Serialize for SendValue {
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        ValueRef::new(self.value(), self.objects()).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for SendValue {
+    fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut objects = Heap::new();
+        let value = ValueSeed { heap: &mut objects }.deserialize(deserializer)?;
+        Ok(SendValue::from_parts(value, objects))
+    }
+}
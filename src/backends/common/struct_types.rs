@@ -0,0 +1,146 @@
+//! Runtime struct type registry
+//!
+//! Struct values only carry a `TypeId` and a handle to their positional fields.
+//! The registry maps type names from `CreateStruct` to stable ids and keeps the
+//! declared field names, so values can be printed as `Point(x: 1.0, y: 2.0)`.
+//! Each layout also gets a field-name → slot map, used by `GetRecordField`
+//! when a value is read through an anonymous record type.
+//!
+//! `GetField`/`SetField` address fields by the byte offsets the compiler's
+//! layout pass assigned; the registry keeps those offsets and maps them back
+//! to the slot holding the field. Every registration bumps a generation counter,
+//! so slots cached by the interpreter can tell that a layout changed under them.
+
+use std::collections::HashMap;
+
+use super::value::TypeId;
+
+/// First id handed out to registered struct types (below are the builtin type ids)
+const FIRST_STRUCT_ID: u32 = 0x1000;
+
+/// Declared layout of a struct type
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StructInfo {
+    /// Type name
+    pub name: String,
+    /// Field names in declaration order
+    pub fields: Vec<String>,
+    /// Byte offset of each field, parallel to `fields` and strictly increasing
+    pub offsets: Vec<u32>,
+}
+
+/// Registry of struct layouts, indexed by runtime `TypeId`
+#[derive(Debug, Clone, Default)]
+pub struct StructTypes {
+    types: Vec<StructInfo>,
+    /// Field name → slot per type, parallel to `types`
+    slots: Vec<HashMap<String, usize>>,
+    by_name: HashMap<String, usize>,
+    generation: u64,
+}
+
+impl StructTypes {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a struct layout; re-registering a name replaces its fields but keeps its id
+    pub fn register(
+        &mut self,
+        name: impl Into<String>,
+        fields: Vec<String>,
+        offsets: Vec<u32>,
+    ) -> TypeId {
+        let name = name.into();
+        self.generation += 1;
+        let slots = fields
+            .iter()
+            .enumerate()
+            .map(|(slot, field)| (field.clone(), slot))
+            .collect();
+        let index = match self.by_name.get(&name) {
+            Some(&index) => {
+                self.types[index].fields = fields;
+                self.types[index].offsets = offsets;
+                self.slots[index] = slots;
+                index
+            }
+            None => {
+                self.by_name.insert(name.clone(), self.types.len());
+                self.types.push(StructInfo {
+                    name,
+                    fields,
+                    offsets,
+                });
+                self.slots.push(slots);
+                self.types.len() - 1
+            }
+        };
+        TypeId(FIRST_STRUCT_ID + index as u32)
+    }
+
+    /// Runtime id for a `CreateStruct` type name
+    ///
+    /// Module-qualified (`geo.Point`) and instantiated (`Box[Int]`) names fall back
+    /// to their base name. Unknown names map to `TypeId(0)`.
+    pub fn id_of(
+        &self,
+        type_name: &str,
+    ) -> TypeId {
+        let base = type_name.split(['[', '(']).next().unwrap_or(type_name);
+        let short = base.rsplit('.').next().unwrap_or(base);
+        [type_name, base, short]
+            .iter()
+            .find_map(|name| self.by_name.get(*name))
+            .map_or(TypeId(0), |&index| TypeId(FIRST_STRUCT_ID + index as u32))
+    }
+
+    /// Layout of a registered struct type
+    pub fn get(
+        &self,
+        id: TypeId,
+    ) -> Option<&StructInfo> {
+        let index = id.0.checked_sub(FIRST_STRUCT_ID)?;
+        self.types.get(index as usize)
+    }
+
+    /// Slot of a named field in a registered struct type
+    pub fn field_slot(
+        &self,
+        id: TypeId,
+        field: &str,
+    ) -> Option<usize> {
+        let index = id.0.checked_sub(FIRST_STRUCT_ID)?;
+        self.slots.get(index as usize)?.get(field).copied()
+    }
+
+    /// Slot of the field at a byte offset
+    ///
+    /// Values of unregistered types have no layout; their offsets are taken as slots.
+    pub fn slot_at(
+        &self,
+        id: TypeId,
+        offset: u16,
+    ) -> Option<usize> {
+        match self.get(id) {
+            Some(info) => info.offsets.binary_search(&u32::from(offset)).ok(),
+            None => Some(offset as usize),
+        }
+    }
+
+    /// Changes whenever a layout is registered or replaced
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Number of registered struct types
+    pub fn len(&self) -> usize {
+        self.types.len()
+    }
+
+    /// Whether no struct type is registered
+    pub fn is_empty(&self) -> bool {
+        self.types.is_empty()
+    }
+}
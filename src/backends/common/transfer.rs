@@ -0,0 +1,242 @@
+//! Moving values between interpreters on different threads
+//!
+//! Every interpreter owns its heap, so a handle means nothing to another
+//! thread. A value handed to a task (its arguments, its result) is detached
+//! from the sending heap into a [`SendValue`] that carries a private copy of
+//! every heap object the value reaches, and is attached to the receiving heap
+//! on arrival. Sharing inside the copy (two fields holding the same list, a
+//! list that contains itself) survives the trip.
+//!
+//! `ref` values (`Arc`) are the only values shared across threads instead of
+//! copied, so what a `ref` points to must not live on a heap: neither side
+//! would see the other's writes. Such values are rejected with
+//! [`TransferError::SharedHeapObject`].
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+use super::heap::{Handle, Heap, HeapValue};
+use super::value::{AsyncState, MethodTable, RuntimeValue};
+
+/// Why a value cannot cross to another thread
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransferError {
+    /// A `ref` reaches objects on the sending thread's heap
+    SharedHeapObject,
+    /// A handle does not name a live object on the sending heap
+    InvalidHandle(Handle),
+}
+
+impl fmt::Display for TransferError {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        match self {
+            TransferError::SharedHeapObject => write!(
+                f,
+                "a `ref` shared with another task cannot hold lists, tuples, dicts or structs; \
+                 pass the value itself so the task gets its own copy"
+            ),
+            TransferError::InvalidHandle(h) => {
+                write!(
+                    f,
+                    "cannot send value to another task: invalid handle: {}",
+                    h
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for TransferError {}
+
+/// A value detached from its heap, ready to cross to another thread
+#[derive(Debug, Clone)]
+pub struct SendValue {
+    value: RuntimeValue,
+    /// Copies of the heap objects `value` reaches
+    objects: Heap,
+}
+
+impl SendValue {
+    /// Copy `value` and everything it reaches on `heap`
+    pub fn detach(
+        value: &RuntimeValue,
+        heap: &Heap,
+    ) -> Result<Self, TransferError> {
+        let mut copier = Copier::default();
+        let mut value = value.clone();
+        remap_value(&mut value, &mut |h| copier.translate(h))?;
+        while let Some((from, to)) = copier.pending.pop() {
+            let mut object = heap
+                .get(from)
+                .cloned()
+                .ok_or(TransferError::InvalidHandle(from))?;
+            remap_object(&mut object, &mut |h| copier.translate(h))?;
+            copier
+                .objects
+                .write(to, object)
+                .map_err(|_| TransferError::InvalidHandle(to))?;
+        }
+        Ok(Self {
+            value,
+            objects: copier.objects,
+        })
+    }
+
+    /// Allocate the carried objects on `heap` and return the value pointing at them
+    pub fn attach(
+        self,
+        heap: &mut Heap,
+    ) -> RuntimeValue {
+        let SendValue { mut value, objects } = self;
+        let moved: HashMap<Handle, Handle> = objects
+            .iter()
+            .map(|(h, _)| (h, heap.allocate(HeapValue::Tuple(Vec::new()))))
+            .collect();
+        let mut translate = |h: Handle| moved.get(&h).copied().unwrap_or(h);
+        for (h, object) in objects.iter() {
+            let mut object = object.clone();
+            // `detach` already rejected everything that could fail here
+            let _ = remap_object(&mut object, &mut translate);
+            let _ = heap.write(moved[&h], object);
+        }
+        let _ = remap_value(&mut value, &mut translate);
+        value
+    }
+
+    /// The detached value; its handles refer to the carried objects
+    pub fn value(&self) -> &RuntimeValue {
+        &self.value
+    }
+
+    /// The carried objects `value` points into
+    pub(super) fn objects(&self) -> &Heap {
+        &self.objects
+    }
+
+    /// Wrap a value whose handles refer only to `objects`
+    pub(super) fn from_parts(
+        value: RuntimeValue,
+        objects: Heap,
+    ) -> Self {
+        Self { value, objects }
+    }
+}
+
+/// Copies objects from the sending heap on first sight
+#[derive(Default)]
+struct Copier {
+    objects: Heap,
+    copied: HashMap<Handle, Handle>,
+    /// Objects allocated in `objects` whose contents are not copied yet
+    pending: Vec<(Handle, Handle)>,
+}
+
+impl Copier {
+    fn translate(
+        &mut self,
+        handle: Handle,
+    ) -> Handle {
+        if let Some(&copy) = self.copied.get(&handle) {
+            return copy;
+        }
+        let copy = self.objects.allocate(HeapValue::Tuple(Vec::new()));
+        self.copied.insert(handle, copy);
+        self.pending.push((handle, copy));
+        copy
+    }
+}
+
+/// Rewrite the handles in a heap object's contents
+fn remap_object(
+    object: &mut HeapValue,
+    f: &mut impl FnMut(Handle) -> Handle,
+) -> Result<(), TransferError> {
+    match object {
+        HeapValue::Tuple(items)
+        | HeapValue::Array(items)
+        | HeapValue::List(items)
+        | HeapValue::Struct(items) => {
+            for item in items {
+                let mut value = std::mem::take(item).into_inner();
+                remap_value(&mut value, f)?;
+                *item = value.into();
+            }
+        }
+        HeapValue::Dict(map) => {
+            // Keys may hold handles too, so the map is rebuilt
+            for (mut key, mut value) in std::mem::take(map) {
+                remap_value(&mut key, f)?;
+                remap_value(&mut value, f)?;
+                map.insert(key, value);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Rewrite the handles a value references without going through another heap object
+fn remap_value(
+    value: &mut RuntimeValue,
+    f: &mut impl FnMut(Handle) -> Handle,
+) -> Result<(), TransferError> {
+    match value {
+        RuntimeValue::Tuple(handle)
+        | RuntimeValue::Array(handle)
+        | RuntimeValue::List(handle)
+        | RuntimeValue::Dict(handle) => *handle = f(*handle),
+        RuntimeValue::Struct { fields, vtable, .. } => {
+            *fields = f(*fields);
+            remap_methods(vtable, f)?;
+        }
+        RuntimeValue::Function(func) if !func.env.is_empty() => {
+            for captured in &mut Arc::make_mut(func).env {
+                remap_value(captured, f)?;
+            }
+        }
+        RuntimeValue::Enum { payload, .. } => remap_value(payload, f)?,
+        RuntimeValue::Dyn { value, .. } => remap_value(value, f)?,
+        RuntimeValue::Async(value) => {
+            if let AsyncState::Ready(inner) | AsyncState::Error(inner) = value.state.as_mut() {
+                remap_value(inner, f)?;
+            }
+        }
+        RuntimeValue::Arc(inner) if reaches_heap(inner) => {
+            return Err(TransferError::SharedHeapObject);
+        }
+        RuntimeValue::Weak(weak) if weak.upgrade().is_some_and(|inner| reaches_heap(&inner)) => {
+            return Err(TransferError::SharedHeapObject);
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn remap_methods(
+    vtable: &mut MethodTable,
+    f: &mut impl FnMut(Handle) -> Handle,
+) -> Result<(), TransferError> {
+    if vtable.iter().all(|(_, method)| method.env.is_empty()) {
+        return Ok(());
+    }
+    let mut methods: Vec<_> = vtable
+        .iter()
+        .map(|(name, method)| (name.clone(), (**method).clone()))
+        .collect();
+    for (_, method) in &mut methods {
+        for captured in &mut method.env {
+            remap_value(captured, f)?;
+        }
+    }
+    *vtable = MethodTable::new(methods);
+    Ok(())
+}
+
+fn reaches_heap(value: &RuntimeValue) -> bool {
+    let mut handles = Vec::new();
+    super::gc::value_handles(value, &mut handles);
+    !handles.is_empty()
+}
@@ -9,6 +9,8 @@ use std::fmt;
 use std::alloc;
 use std::hash::{Hash, Hasher};
 
+use super::nanbox::Value;
+
 /// Wrapper for `*mut c_void` that implements Send + Sync.
 ///
 /// # Safety
@@ -128,6 +130,62 @@ pub enum ValueType {
     Ptr(PtrKind),
     /// FFI opaque handle type
     OpaqueHandle,
+    /// Type value (the meta-type `Type`)
+    Type,
+}
+
+impl ValueType {
+    /// Source-level type name (`Int`, `List`, ...)
+    ///
+    /// Struct and enum types only carry an id here; their declared names come from
+    /// the program's struct layouts (see `StructTypes`).
+    pub fn name(&self) -> String {
+        let name = match self {
+            ValueType::Unit => "Void",
+            ValueType::Bool => "Bool",
+            ValueType::Int(width) => match width {
+                IntWidth::I8 => "Int8",
+                IntWidth::I16 => "Int16",
+                IntWidth::I32 => "Int32",
+                IntWidth::I64 => "Int",
+                IntWidth::I128 => "Int128",
+                IntWidth::ISize => "ISize",
+                IntWidth::U8 => "UInt8",
+                IntWidth::U16 => "UInt16",
+                IntWidth::U32 => "UInt32",
+                IntWidth::U64 => "UInt64",
+                IntWidth::U128 => "UInt128",
+                IntWidth::USize => "USize",
+            },
+            ValueType::Float(FloatWidth::F32) => "Float32",
+            ValueType::Float(FloatWidth::F64) => "Float",
+            ValueType::Char => "Char",
+            ValueType::String => "String",
+            ValueType::Bytes => "Bytes",
+            ValueType::Tuple(items) => {
+                let items: Vec<String> = items.iter().map(ValueType::name).collect();
+                return format!("({})", items.join(", "));
+            }
+            ValueType::Array { element } => return format!("Array({})", element.name()),
+            ValueType::List => "List",
+            ValueType::Dict => "Dict",
+            ValueType::Struct(id) if *id == TypeId::STRUCT => "Error",
+            ValueType::Struct(_) => "Struct",
+            ValueType::Enum(id) if *id == TypeId::ENUM => "Result",
+            ValueType::Enum(_) => "Enum",
+            ValueType::Function(_) => "Function",
+            ValueType::Ref(inner) | ValueType::Arc(inner) => {
+                return format!("ref {}", inner.name())
+            }
+            ValueType::Weak(inner) => return format!("Weak({})", inner.name()),
+            ValueType::Async(inner) => return format!("Async({})", inner.name()),
+            ValueType::Ptr(PtrKind::Const) => "*const",
+            ValueType::Ptr(PtrKind::Mut) => "*mut",
+            ValueType::OpaqueHandle => "Opaque",
+            ValueType::Type => "Type",
+        };
+        name.to_string()
+    }
 }
 
 /// Type ID for runtime type identification
@@ -178,12 +236,72 @@ pub struct FunctionValue {
     pub env: Vec<RuntimeValue>,
 }
 
+/// A struct method: its name and function
+pub type Method = (String, Arc<FunctionValue>);
+
+/// Methods of a struct value (method name -> function)
+///
+/// Shared by every copy of the value; a struct without methods holds no table.
+/// Each method is its own `Arc`, so calling one hands out a function value
+/// without allocating.
+#[derive(Debug, Clone, Default, PartialEq, Hash)]
+pub struct MethodTable(Option<Arc<Vec<Method>>>);
+
+impl MethodTable {
+    pub fn new(methods: Vec<(String, FunctionValue)>) -> Self {
+        Self((!methods.is_empty()).then(|| {
+            Arc::new(
+                methods
+                    .into_iter()
+                    .map(|(name, method)| (name, Arc::new(method)))
+                    .collect(),
+            )
+        }))
+    }
+}
+
+impl From<Vec<(String, FunctionValue)>> for MethodTable {
+    fn from(methods: Vec<(String, FunctionValue)>) -> Self {
+        Self::new(methods)
+    }
+}
+
+impl std::ops::Deref for MethodTable {
+    type Target = [Method];
+
+    fn deref(&self) -> &Self::Target {
+        self.0.as_deref().map_or(&[], Vec::as_slice)
+    }
+}
+
+impl<'a> IntoIterator for &'a MethodTable {
+    type Item = &'a Method;
+    type IntoIter = std::slice::Iter<'a, Method>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Foreign pointer held by `RuntimeValue::OpaqueHandle`
+#[derive(Debug, Clone, PartialEq, Hash)]
+pub struct Opaque {
+    /// Name of the foreign type
+    pub type_name: String,
+    /// The pointer, never dereferenced by YaoXiang
+    pub ptr: OpaquePtr,
+}
+
 /// Runtime value - unified representation of all YaoXiang values
 ///
 /// # Design Principles
 /// - Uses `enum` for easy pattern matching
 /// - `Arc` for shared ownership
 /// - Handle-based storage for collections
+/// - Three words wide: payloads that do not fit in two words (closures,
+///   method tables, opaque handles, type values) live behind a pointer
+/// - The unpacked form: registers, locals and sequence elements store the
+///   eight-byte [`Value`] and unpack it where instructions need the variant
 #[derive(Debug, Clone, Default)]
 pub enum RuntimeValue {
     /// Empty value
@@ -227,7 +345,7 @@ pub enum RuntimeValue {
         /// Field values handle (stored on heap for efficient cloning)
         fields: super::heap::Handle,
         /// Virtual method table (method name -> function)
-        vtable: Vec<(String, FunctionValue)>,
+        vtable: MethodTable,
     },
 
     /// Enum variant
@@ -241,7 +359,7 @@ pub enum RuntimeValue {
     },
 
     /// Function closure (captures environment)
-    Function(FunctionValue),
+    Function(Arc<FunctionValue>),
 
     /// Thread-safe reference count (ref T keyword runtime representation)
     Arc(Arc<RuntimeValue>),
@@ -264,7 +382,19 @@ pub enum RuntimeValue {
 
     /// FFI opaque handle — pointer-sized value owned by external library
     /// YaoXiang only holds the pointer without dereferencing
-    OpaqueHandle { type_name: String, ptr: OpaquePtr },
+    OpaqueHandle(Box<Opaque>),
+
+    /// Interface existential — a concrete value paired with the vtable of
+    /// its interface implementation (index into the module's vtable table)
+    Dyn {
+        /// Wrapped concrete value
+        value: Box<RuntimeValue>,
+        /// Vtable index
+        vtable: u32,
+    },
+
+    /// Type value — the runtime type of another value (`typeof(x)`)
+    Type(Box<ValueType>),
 }
 
 // ============================================================================
@@ -289,7 +419,7 @@ impl RuntimeValue {
                 if let Some(h) = heap {
                     if let Some(super::heap::HeapValue::Tuple(items)) = h.get(*handle) {
                         return ValueType::Tuple(
-                            items.iter().map(|v| v.value_type(heap)).collect(),
+                            items.iter().map(|v| v.view().value_type(heap)).collect(),
                         );
                     }
                 }
@@ -302,7 +432,7 @@ impl RuntimeValue {
                             element: Box::new(
                                 items
                                     .first()
-                                    .map(|v| v.value_type(heap))
+                                    .map(|v| v.view().value_type(heap))
                                     .unwrap_or(ValueType::Unit),
                             ),
                         };
@@ -321,7 +451,9 @@ impl RuntimeValue {
             RuntimeValue::Weak(_) => ValueType::Weak(Box::new(ValueType::Unit)),
             RuntimeValue::Async(v) => ValueType::Async(Box::new(v.value_type.clone())),
             RuntimeValue::Ptr { kind, .. } => ValueType::Ptr(*kind),
-            RuntimeValue::OpaqueHandle { .. } => ValueType::OpaqueHandle,
+            RuntimeValue::OpaqueHandle(_) => ValueType::OpaqueHandle,
+            RuntimeValue::Dyn { value, .. } => value.value_type(heap),
+            RuntimeValue::Type(_) => ValueType::Type,
         }
     }
 
@@ -355,15 +487,15 @@ impl RuntimeValue {
     }
 
     /// Get struct field by index with heap access
-    pub fn struct_field_with_heap<'a>(
+    pub fn struct_field_with_heap(
         &self,
         index: usize,
-        heap: &'a super::heap::Heap,
-    ) -> Option<&'a RuntimeValue> {
+        heap: &super::heap::Heap,
+    ) -> Option<RuntimeValue> {
         match self {
             RuntimeValue::Struct { fields, .. } => {
                 if let Some(super::heap::HeapValue::Tuple(items)) = heap.get(*fields) {
-                    items.get(index)
+                    items.get(index).map(Value::get)
                 } else {
                     None
                 }
@@ -416,16 +548,17 @@ impl RuntimeValue {
         name: &str,
     ) -> Option<&FunctionValue> {
         match self {
-            RuntimeValue::Struct { vtable, .. } => {
-                vtable.iter().find(|(n, _)| n == name).map(|(_, f)| f)
-            }
-            RuntimeValue::Function(f) => Some(f),
+            RuntimeValue::Struct { vtable, .. } => vtable
+                .iter()
+                .find(|(n, _)| n == name)
+                .map(|(_, f)| f.as_ref()),
+            RuntimeValue::Function(f) => Some(f.as_ref()),
             _ => None,
         }
     }
 
     /// Get vtable reference for a struct
-    pub fn vtable(&self) -> Option<&Vec<(String, FunctionValue)>> {
+    pub fn vtable(&self) -> Option<&[Method]> {
         match self {
             RuntimeValue::Struct { vtable, .. } => Some(vtable),
             _ => None,
@@ -488,10 +621,12 @@ impl RuntimeValue {
                 address: *address,
                 type_id: *type_id,
             },
-            RuntimeValue::OpaqueHandle { type_name, ptr } => RuntimeValue::OpaqueHandle {
-                type_name: type_name.clone(),
-                ptr: *ptr,
+            RuntimeValue::OpaqueHandle(opaque) => RuntimeValue::OpaqueHandle(opaque.clone()),
+            RuntimeValue::Dyn { value, vtable } => RuntimeValue::Dyn {
+                value: Box::new(value.explicit_clone()),
+                vtable: *vtable,
             },
+            RuntimeValue::Type(ty) => RuntimeValue::Type(ty.clone()),
         }
     }
 
@@ -509,7 +644,7 @@ impl RuntimeValue {
             RuntimeValue::String(s) => RuntimeValue::String(s.clone()),
             RuntimeValue::Bytes(b) => RuntimeValue::Bytes(b.clone()),
             RuntimeValue::Tuple(handle) => {
-                let items_copy: Vec<RuntimeValue> =
+                let items_copy: Vec<Value> =
                     if let Some(super::heap::HeapValue::Tuple(items)) = heap.get(*handle) {
                         items.clone()
                     } else {
@@ -517,12 +652,12 @@ impl RuntimeValue {
                     };
                 let cloned = items_copy
                     .into_iter()
-                    .map(|v| v.explicit_clone_with_heap(heap))
+                    .map(|v| v.into_inner().explicit_clone_with_heap(heap).into())
                     .collect();
                 RuntimeValue::Tuple(heap.allocate(super::heap::HeapValue::Tuple(cloned)))
             }
             RuntimeValue::Array(handle) => {
-                let items_copy: Vec<RuntimeValue> =
+                let items_copy: Vec<Value> =
                     if let Some(super::heap::HeapValue::Array(items)) = heap.get(*handle) {
                         items.clone()
                     } else {
@@ -530,12 +665,12 @@ impl RuntimeValue {
                     };
                 let cloned = items_copy
                     .into_iter()
-                    .map(|v| v.explicit_clone_with_heap(heap))
+                    .map(|v| v.into_inner().explicit_clone_with_heap(heap).into())
                     .collect();
                 RuntimeValue::Array(heap.allocate(super::heap::HeapValue::Array(cloned)))
             }
             RuntimeValue::List(handle) => {
-                let items_copy: Vec<RuntimeValue> =
+                let items_copy: Vec<Value> =
                     if let Some(super::heap::HeapValue::List(items)) = heap.get(*handle) {
                         items.clone()
                     } else {
@@ -543,7 +678,7 @@ impl RuntimeValue {
                     };
                 let cloned = items_copy
                     .into_iter()
-                    .map(|v| v.explicit_clone_with_heap(heap))
+                    .map(|v| v.into_inner().explicit_clone_with_heap(heap).into())
                     .collect();
                 RuntimeValue::List(heap.allocate(super::heap::HeapValue::List(cloned)))
             }
@@ -570,7 +705,7 @@ impl RuntimeValue {
                 fields,
                 vtable,
             } => {
-                let items_copy: Vec<RuntimeValue> =
+                let items_copy: Vec<Value> =
                     if let Some(super::heap::HeapValue::Tuple(items)) = heap.get(*fields) {
                         items.clone()
                     } else {
@@ -578,7 +713,7 @@ impl RuntimeValue {
                     };
                 let cloned = items_copy
                     .into_iter()
-                    .map(|v| v.explicit_clone_with_heap(heap))
+                    .map(|v| v.into_inner().explicit_clone_with_heap(heap).into())
                     .collect();
                 RuntimeValue::Struct {
                     type_id: *type_id,
@@ -608,10 +743,12 @@ impl RuntimeValue {
                 address: *address,
                 type_id: *type_id,
             },
-            RuntimeValue::OpaqueHandle { type_name, ptr } => RuntimeValue::OpaqueHandle {
-                type_name: type_name.clone(),
-                ptr: *ptr,
+            RuntimeValue::OpaqueHandle(opaque) => RuntimeValue::OpaqueHandle(opaque.clone()),
+            RuntimeValue::Dyn { value, vtable } => RuntimeValue::Dyn {
+                value: Box::new(value.explicit_clone_with_heap(heap)),
+                vtable: *vtable,
             },
+            RuntimeValue::Type(ty) => RuntimeValue::Type(ty.clone()),
         }
     }
 
@@ -668,10 +805,10 @@ impl RuntimeValue {
             RuntimeValue::Weak(_) => alloc::Layout::new::<std::sync::Weak<RuntimeValue>>(),
             RuntimeValue::Async(_) => alloc::Layout::new::<AsyncState>(),
             RuntimeValue::Ptr { .. } => alloc::Layout::new::<usize>(),
-            RuntimeValue::Function(_) => alloc::Layout::new::<FunctionValue>(),
-            RuntimeValue::OpaqueHandle { .. } => {
-                alloc::Layout::new::<(*const std::ffi::c_void, String)>()
-            }
+            RuntimeValue::Function(_) => alloc::Layout::new::<Arc<FunctionValue>>(),
+            RuntimeValue::OpaqueHandle(_) => alloc::Layout::new::<Box<Opaque>>(),
+            RuntimeValue::Dyn { .. } => alloc::Layout::new::<(Box<RuntimeValue>, u32)>(),
+            RuntimeValue::Type(_) => alloc::Layout::new::<Box<ValueType>>(),
         }
     }
 }
@@ -730,7 +867,9 @@ impl fmt::Display for RuntimeValue {
             RuntimeValue::Weak(_) => write!(f, "weak(...)"),
             RuntimeValue::Async(_) => write!(f, "async"),
             RuntimeValue::Ptr { kind, address, .. } => write!(f, "ptr({:?}, {:#x})", kind, address),
-            RuntimeValue::OpaqueHandle { type_name, .. } => write!(f, "opaque<{}>", type_name),
+            RuntimeValue::OpaqueHandle(opaque) => write!(f, "opaque<{}>", opaque.type_name),
+            RuntimeValue::Dyn { value, .. } => write!(f, "{}", value),
+            RuntimeValue::Type(ty) => write!(f, "<type {}>", ty.name()),
         }
     }
 }
@@ -797,10 +936,18 @@ impl PartialEq for RuntimeValue {
                     type_id: t2,
                 },
             ) => k1 == k2 && a1 == a2 && t1 == t2,
+            (RuntimeValue::OpaqueHandle(a), RuntimeValue::OpaqueHandle(b)) => a.ptr == b.ptr,
             (
-                RuntimeValue::OpaqueHandle { ptr: p1, .. },
-                RuntimeValue::OpaqueHandle { ptr: p2, .. },
-            ) => p1 == p2,
+                RuntimeValue::Dyn {
+                    value: v1,
+                    vtable: t1,
+                },
+                RuntimeValue::Dyn {
+                    value: v2,
+                    vtable: t2,
+                },
+            ) => t1 == t2 && v1 == v2,
+            (RuntimeValue::Type(a), RuntimeValue::Type(b)) => a == b,
             _ => false,
         }
     }
@@ -885,10 +1032,12 @@ impl Hash for RuntimeValue {
                 address.hash(state);
                 type_id.hash(state);
             }
-            RuntimeValue::OpaqueHandle { type_name, ptr } => {
-                type_name.hash(state);
-                ptr.hash(state);
+            RuntimeValue::OpaqueHandle(opaque) => opaque.hash(state),
+            RuntimeValue::Dyn { value, vtable } => {
+                value.hash(state);
+                vtable.hash(state);
             }
+            RuntimeValue::Type(ty) => ty.hash(state),
         }
     }
 }
@@ -0,0 +1,610 @@
+//! Breakpoints and stepping on top of execution hooks
+//!
+//! A [`Debugger`] is an [`ExecutionHook`] that blocks the interpreter thread in
+//! `on_step` while execution is paused. The interpreter runs on its own thread;
+//! the thread driving the debugger sets breakpoints, waits for the program to
+//! stop and resumes it:
+//!
+//! ```no_run
+//! use yaoxiang::vm::debug::{Breakpoint, DebugEvent, Debugger, Step, StepUnit};
+//! use yaoxiang::Engine;
+//!
+//! let program = Engine::default().compile("main.yx", "main = () => print(1)")?;
+//! let debugger = Debugger::new(&program);
+//! debugger.set_breakpoint(Breakpoint::Line { file: "main.yx".into(), line: 1 });
+//!
+//! let vm = debugger.clone();
+//! let runner = std::thread::spawn(move || {
+//!     let mut interpreter = Engine::default().interpreter();
+//!     vm.attach(&mut interpreter);
+//!     let result = yaoxiang::Executor::execute_module(&mut interpreter, &program);
+//!     vm.finish();
+//!     result.is_ok()
+//! });
+//! while let DebugEvent::Stopped(stop) = debugger.wait() {
+//!     println!("stopped in {}", stop.frames[0].function);
+//!     debugger.step(Step::Over, StepUnit::Line);
+//! }
+//! runner.join().unwrap();
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! Stopping is all-stop: while one thread is paused, every other task thread
+//! of the program waits at its next instruction. Only the paused thread can be
+//! looked into, with [`Debugger::inspect`]. Call stacks are tracked per OS
+//! thread from the hook's enter and exit events.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread::{self, ThreadId};
+
+use crate::backends::common::RuntimeValue;
+use crate::middle::bytecode::{BytecodeFunction, BytecodeModule};
+use crate::util::diagnostic::panic::SourceLocation;
+use crate::util::span::{DebugSpan, FileId, SourceMap};
+
+use super::hooks::{ExecutionHook, VmState};
+use super::Interpreter;
+
+/// Identifier of a breakpoint set with [`Debugger::set_breakpoint`]
+pub type BreakpointId = u32;
+
+/// Where a breakpoint stops execution
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Breakpoint {
+    /// Instruction `offset` of the function named `function`
+    Instruction { function: String, offset: usize },
+    /// The first instruction of source line `line` (1-based) in `file`
+    Line { file: String, line: usize },
+}
+
+/// How far [`Debugger::step`] runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+    /// To the next instruction or line, following calls
+    Into,
+    /// To the next instruction or line of the current function, or its caller
+    Over,
+    /// Until the current function returns
+    Out,
+}
+
+/// Unit of a step
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StepUnit {
+    /// A source line (instructions without a source location are skipped)
+    #[default]
+    Line,
+    /// A single bytecode instruction
+    Instruction,
+}
+
+/// Why execution stopped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopReason {
+    /// A breakpoint was hit
+    Breakpoint(BreakpointId),
+    /// A step finished
+    Step,
+    /// [`Debugger::pause`] was requested
+    Pause,
+}
+
+/// One function call of a stopped thread
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StackFrame {
+    /// Function name
+    pub function: String,
+    /// Index of the instruction being executed (the call, for callers)
+    pub ip: usize,
+    /// Source location of the instruction, when the function has a line table
+    pub location: Option<SourceLocation>,
+}
+
+/// A paused thread
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stop {
+    /// Why it stopped
+    pub reason: StopReason,
+    /// The thread running the interpreter
+    pub thread: ThreadId,
+    /// Its call stack, innermost first
+    pub frames: Vec<StackFrame>,
+}
+
+/// What [`Debugger::wait`] reports
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DebugEvent {
+    /// A thread paused
+    Stopped(Stop),
+    /// The program finished ([`Debugger::finish`])
+    Exited,
+}
+
+/// Controls the interpreters it is attached to: breakpoints, pausing and stepping
+///
+/// Clones share their state, so one clone can be attached to the interpreter
+/// while another drives it from a different thread.
+#[derive(Clone)]
+pub struct Debugger {
+    shared: Arc<Shared>,
+}
+
+struct Shared {
+    state: Mutex<State>,
+    /// Signalled on every change of `state`
+    changed: Condvar,
+    sources: Option<SourceMap>,
+    functions: Vec<FunctionLines>,
+}
+
+/// Instruction count and source lines of one of the program's functions
+struct FunctionLines {
+    name: String,
+    len: usize,
+    lines: Vec<(FileId, usize)>,
+}
+
+/// A closure run by the paused thread on behalf of [`Debugger::inspect`]
+type Inspection = Box<dyn FnOnce(&VmState<'_>) + Send>;
+
+#[derive(Default)]
+struct State {
+    breakpoints: Vec<(BreakpointId, Breakpoint, Option<FileId>)>,
+    next_breakpoint: BreakpointId,
+    pause_requested: bool,
+    stepping: Option<Stepping>,
+    paused: Option<Paused>,
+    inspection: Option<Inspection>,
+    stacks: HashMap<ThreadId, Vec<ShadowFrame>>,
+    exited: bool,
+    detached: bool,
+}
+
+struct Paused {
+    stop: Stop,
+    depth: usize,
+    line: Option<(FileId, usize)>,
+    reported: bool,
+}
+
+struct Stepping {
+    thread: ThreadId,
+    step: Step,
+    unit: StepUnit,
+    depth: usize,
+    line: Option<(FileId, usize)>,
+}
+
+struct ShadowFrame {
+    function: Arc<BytecodeFunction>,
+    ip: usize,
+    line: Option<(FileId, usize)>,
+}
+
+fn line_of(span: DebugSpan) -> (FileId, usize) {
+    (span.file_id, span.span.start.line)
+}
+
+impl Debugger {
+    /// Create a debugger for `program`, which resolves line breakpoints
+    /// against the program's source files
+    pub fn new(program: &BytecodeModule) -> Self {
+        let functions = program
+            .functions
+            .iter()
+            .map(|function| {
+                let lines = function
+                    .line_table
+                    .rows()
+                    .iter()
+                    .filter(|(_, span)| !span.is_dummy())
+                    .map(|(_, span)| line_of(*span))
+                    .collect();
+                FunctionLines {
+                    name: function.name.clone(),
+                    len: function.instructions.len(),
+                    lines,
+                }
+            })
+            .collect();
+        Self {
+            shared: Arc::new(Shared {
+                state: Mutex::new(State::default()),
+                changed: Condvar::new(),
+                sources: program.sources.clone(),
+                functions,
+            }),
+        }
+    }
+
+    /// Install the debugger as `interpreter`'s hook
+    pub fn attach(
+        &self,
+        interpreter: &mut Interpreter,
+    ) {
+        interpreter.set_hook(Arc::new(self.clone()));
+    }
+
+    /// Set a breakpoint
+    ///
+    /// A line without instructions moves to the next line that has some; the
+    /// breakpoint actually set is returned by [`breakpoint`](Self::breakpoint).
+    /// Returns `None` for an unknown function, offset or file.
+    pub fn set_breakpoint(
+        &self,
+        at: Breakpoint,
+    ) -> Option<BreakpointId> {
+        let (at, file_id) = match at {
+            Breakpoint::Instruction { function, offset } => {
+                let known = self
+                    .shared
+                    .functions
+                    .iter()
+                    .any(|f| f.name == function && (offset < f.len || f.len == 0));
+                if !known {
+                    return None;
+                }
+                (Breakpoint::Instruction { function, offset }, None)
+            }
+            Breakpoint::Line { file, line } => {
+                let file_id = self.file_id(&file)?;
+                let line = self
+                    .shared
+                    .functions
+                    .iter()
+                    .flat_map(|f| &f.lines)
+                    .filter(|(id, l)| *id == file_id && *l >= line)
+                    .map(|(_, l)| *l)
+                    .min()
+                    .unwrap_or(line);
+                (Breakpoint::Line { file, line }, Some(file_id))
+            }
+        };
+        let mut state = self.lock();
+        let id = state.next_breakpoint;
+        state.next_breakpoint += 1;
+        state.breakpoints.push((id, at, file_id));
+        Some(id)
+    }
+
+    /// Remove a breakpoint; `false` if it was not set
+    pub fn remove_breakpoint(
+        &self,
+        id: BreakpointId,
+    ) -> bool {
+        let mut state = self.lock();
+        let before = state.breakpoints.len();
+        state.breakpoints.retain(|(bp, _, _)| *bp != id);
+        state.breakpoints.len() != before
+    }
+
+    /// Remove every breakpoint
+    pub fn clear_breakpoints(&self) {
+        self.lock().breakpoints.clear();
+    }
+
+    /// Where breakpoint `id` stops
+    pub fn breakpoint(
+        &self,
+        id: BreakpointId,
+    ) -> Option<Breakpoint> {
+        self.lock()
+            .breakpoints
+            .iter()
+            .find(|(bp, _, _)| *bp == id)
+            .map(|(_, at, _)| at.clone())
+    }
+
+    /// All breakpoints, in the order they were set
+    pub fn breakpoints(&self) -> Vec<(BreakpointId, Breakpoint)> {
+        self.lock()
+            .breakpoints
+            .iter()
+            .map(|(id, at, _)| (*id, at.clone()))
+            .collect()
+    }
+
+    /// Stop at the next instruction any thread executes
+    pub fn pause(&self) {
+        self.lock().pause_requested = true;
+    }
+
+    /// Let the paused thread run until the next breakpoint
+    pub fn resume(&self) {
+        let mut state = self.lock();
+        state.paused = None;
+        state.stepping = None;
+        self.shared.changed.notify_all();
+    }
+
+    /// Let the paused thread run for one step
+    pub fn step(
+        &self,
+        step: Step,
+        unit: StepUnit,
+    ) {
+        let mut state = self.lock();
+        let Some(paused) = state.paused.take() else {
+            return;
+        };
+        state.stepping = Some(Stepping {
+            thread: paused.stop.thread,
+            step,
+            unit,
+            depth: paused.depth,
+            line: paused.line,
+        });
+        self.shared.changed.notify_all();
+    }
+
+    /// Block until a thread stops or the program finishes
+    ///
+    /// Each stop is reported once; a stop that was already reported is
+    /// available from [`stopped`](Self::stopped) until execution resumes.
+    pub fn wait(&self) -> DebugEvent {
+        let mut state = self.lock();
+        loop {
+            if let Some(paused) = &mut state.paused {
+                if !paused.reported {
+                    paused.reported = true;
+                    return DebugEvent::Stopped(paused.stop.clone());
+                }
+            }
+            if state.exited {
+                return DebugEvent::Exited;
+            }
+            state = self.wait_change(state);
+        }
+    }
+
+    /// The current stop, while a thread is paused
+    pub fn stopped(&self) -> Option<Stop> {
+        self.lock()
+            .paused
+            .as_ref()
+            .map(|paused| paused.stop.clone())
+    }
+
+    /// Run `f` on the paused thread, with a view of the interpreter at the
+    /// stop; `None` if no thread is paused
+    pub fn inspect<T: Send + 'static>(
+        &self,
+        f: impl FnOnce(&VmState<'_>) -> T + Send + 'static,
+    ) -> Option<T> {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        {
+            let mut state = self.lock();
+            state.paused.as_ref()?;
+            state.inspection = Some(Box::new(move |vm| {
+                let _ = sender.send(f(vm));
+            }));
+            self.shared.changed.notify_all();
+        }
+        // The paused thread drops the closure unrun when it resumes first
+        receiver.recv().ok()
+    }
+
+    /// Record that the program finished, waking [`wait`](Self::wait)
+    pub fn finish(&self) {
+        let mut state = self.lock();
+        state.exited = true;
+        state.paused = None;
+        self.shared.changed.notify_all();
+    }
+
+    /// Stop debugging: remove every breakpoint and let the program run to its
+    /// end without stopping again
+    pub fn detach(&self) {
+        let mut state = self.lock();
+        state.detached = true;
+        state.breakpoints.clear();
+        state.pause_requested = false;
+        state.stepping = None;
+        state.paused = None;
+        state.stacks.clear();
+        self.shared.changed.notify_all();
+    }
+
+    /// Id of the source file named `file` (also matched as a path suffix)
+    fn file_id(
+        &self,
+        file: &str,
+    ) -> Option<FileId> {
+        let files = self.shared.sources.as_ref()?.files();
+        let path = std::path::Path::new(file);
+        files
+            .iter()
+            .position(|f| f.name == file)
+            .or_else(|| {
+                files.iter().position(|f| {
+                    let name = std::path::Path::new(&f.name);
+                    path.ends_with(name) || name.ends_with(path)
+                })
+            })
+            .map(|index| index as FileId)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.shared
+            .state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn wait_change<'a>(
+        &self,
+        state: MutexGuard<'a, State>,
+    ) -> MutexGuard<'a, State> {
+        self.shared
+            .changed
+            .wait(state)
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn location(
+        &self,
+        span: DebugSpan,
+    ) -> SourceLocation {
+        SourceLocation {
+            file: self
+                .shared
+                .sources
+                .as_ref()
+                .and_then(|sources| sources.get(span.file_id))
+                .map(|file| file.name.clone()),
+            line: span.span.start.line,
+            column: span.span.start.column,
+        }
+    }
+
+    /// Why the instruction about to execute stops the thread, if it does
+    fn stop_reason(
+        state: &State,
+        thread: ThreadId,
+        vm: &VmState<'_>,
+        line: Option<(FileId, usize)>,
+        new_line: bool,
+    ) -> Option<StopReason> {
+        if state.pause_requested {
+            return Some(StopReason::Pause);
+        }
+        let hit = state.breakpoints.iter().find(|(_, at, file_id)| match at {
+            Breakpoint::Instruction { function, offset } => {
+                vm.ip() == *offset && vm.function().name == *function
+            }
+            Breakpoint::Line { line: at, .. } => {
+                new_line && line == file_id.map(|file_id| (file_id, *at))
+            }
+        });
+        if let Some((id, _, _)) = hit {
+            return Some(StopReason::Breakpoint(*id));
+        }
+        let stepping = state.stepping.as_ref().filter(|s| s.thread == thread)?;
+        let depth = vm.depth();
+        let done = match (stepping.step, stepping.unit) {
+            (Step::Out, _) => depth < stepping.depth,
+            (Step::Into, StepUnit::Instruction) => true,
+            (Step::Over, StepUnit::Instruction) => depth <= stepping.depth,
+            (Step::Into, StepUnit::Line) => {
+                line.is_some() && (depth != stepping.depth || line != stepping.line)
+            }
+            (Step::Over, StepUnit::Line) => {
+                line.is_some()
+                    && (depth < stepping.depth
+                        || (depth == stepping.depth && line != stepping.line))
+            }
+        };
+        done.then_some(StopReason::Step)
+    }
+}
+
+impl ExecutionHook for Debugger {
+    fn on_step(
+        &self,
+        vm: &VmState<'_>,
+    ) {
+        let thread = thread::current().id();
+        let line = vm.location().filter(|span| !span.is_dummy()).map(line_of);
+        let mut state = self.lock();
+        if state.detached {
+            return;
+        }
+
+        let stack = state.stacks.entry(thread).or_default();
+        if stack.is_empty() {
+            stack.push(ShadowFrame {
+                function: Arc::clone(vm.function()),
+                ip: 0,
+                line: None,
+            });
+        }
+        let top = stack.last_mut().expect("stack has a frame");
+        let new_line = line.is_some() && top.line != line;
+        top.ip = vm.ip();
+        if line.is_some() {
+            top.line = line;
+        }
+
+        // All-stop: wait while another thread is paused
+        while state
+            .paused
+            .as_ref()
+            .is_some_and(|paused| paused.stop.thread != thread)
+        {
+            state = self.wait_change(state);
+        }
+
+        let Some(reason) = Self::stop_reason(&state, thread, vm, line, new_line) else {
+            return;
+        };
+        let frames = state.stacks[&thread]
+            .iter()
+            .rev()
+            .map(|frame| StackFrame {
+                function: frame.function.name.clone(),
+                ip: frame.ip,
+                location: frame
+                    .function
+                    .line_table
+                    .lookup(frame.ip)
+                    .filter(|span| !span.is_dummy())
+                    .map(|span| self.location(span)),
+            })
+            .collect();
+        state.pause_requested = false;
+        state.stepping = None;
+        state.paused = Some(Paused {
+            stop: Stop {
+                reason,
+                thread,
+                frames,
+            },
+            depth: vm.depth(),
+            line,
+            reported: false,
+        });
+        self.shared.changed.notify_all();
+
+        while state.paused.is_some() {
+            if let Some(inspection) = state.inspection.take() {
+                drop(state);
+                inspection(vm);
+                state = self.lock();
+                continue;
+            }
+            state = self.wait_change(state);
+        }
+        state.inspection = None;
+    }
+
+    fn on_enter(
+        &self,
+        vm: &VmState<'_>,
+    ) {
+        let mut state = self.lock();
+        if state.detached {
+            return;
+        }
+        state
+            .stacks
+            .entry(thread::current().id())
+            .or_default()
+            .push(ShadowFrame {
+                function: Arc::clone(vm.function()),
+                ip: 0,
+                line: None,
+            });
+    }
+
+    fn on_exit(
+        &self,
+        _function: &BytecodeFunction,
+        _result: Option<&RuntimeValue>,
+    ) {
+        let mut state = self.lock();
+        if let Some(stack) = state.stacks.get_mut(&thread::current().id()) {
+            stack.pop();
+        }
+    }
+}
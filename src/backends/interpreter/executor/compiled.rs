@@ -0,0 +1,358 @@
+//! Tiered JIT
+//!
+//! Every call of a function that is not compiled yet is counted; once a function
+//! has been interpreted `ExecutorConfig::jit_threshold` times, it is compiled to
+//! template code together with the functions it calls. With the `native`
+//! feature, a function whose template code has then been called
+//! `OPTIMIZE_AFTER` times as often is recompiled, again with its callees, by the
+//! Cranelift backend. Calls to compiled functions run the machine code unless a
+//! debugger is attached; when the machine code gives up, the call is run again by
+//! the interpreter, with the machine code switched off until it returns.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::backends::common::RuntimeValue;
+#[cfg(feature = "native")]
+use crate::backends::native::NativeCode;
+use crate::backends::template::{Outcome, Program, TemplateCode};
+use crate::middle::bytecode::{BytecodeFunction, BytecodeInstr};
+use super::executor::Interpreter;
+
+/// Template-code calls, in multiples of `jit_threshold`, before a function is optimized
+#[cfg(feature = "native")]
+const OPTIMIZE_AFTER: u32 = 10;
+
+/// Machine code a function runs as; a hot function shares its code with the
+/// callees compiled along with it
+#[derive(Clone)]
+enum MachineCode {
+    /// Pasted together from stencils
+    Template(Arc<TemplateCode>),
+    /// Optimized by Cranelift
+    #[cfg(feature = "native")]
+    Optimized(Arc<NativeCode>),
+}
+
+impl MachineCode {
+    fn call(
+        &self,
+        name: &str,
+        args: &[RuntimeValue],
+        depth: usize,
+    ) -> Option<Outcome> {
+        match self {
+            MachineCode::Template(code) => code.call(name, args, depth),
+            #[cfg(feature = "native")]
+            MachineCode::Optimized(code) => code.call(name, args, depth),
+        }
+    }
+}
+
+/// JIT state of one interpreter
+#[derive(Default)]
+pub(super) struct Jit {
+    /// Calls so far of each function that is not compiled yet
+    counts: HashMap<String, u32>,
+    /// Machine code of each compiled function
+    code: HashMap<String, MachineCode>,
+    /// Calls so far of each template-compiled function not optimized yet
+    #[cfg(feature = "native")]
+    warm: HashMap<String, u32>,
+    /// Hot functions that could not be compiled
+    rejected: HashSet<String>,
+    /// Call depth of the frame re-running a call the machine code gave up on;
+    /// until it returns, everything is interpreted
+    pub(super) suspended_at: Option<usize>,
+    native_calls: u64,
+    deopts: u64,
+    compile_time: Duration,
+    native_time: Duration,
+}
+
+/// What the JIT did during a run
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct JitStats {
+    /// Functions that run as machine code, sorted by name
+    pub compiled: Vec<String>,
+    /// Compiled functions recompiled by Cranelift (`native` feature), sorted by name
+    pub optimized: Vec<String>,
+    /// Hot functions that stayed interpreted, sorted by name
+    pub rejected: Vec<String>,
+    /// Calls that ran as machine code
+    pub native_calls: u64,
+    /// Calls the machine code gave up on and the interpreter ran again
+    pub deopts: u64,
+    /// Time spent compiling hot functions
+    pub compile_time: Duration,
+    /// Time spent in machine code
+    pub native_time: Duration,
+    /// Time spent running the entry point
+    pub total_time: Duration,
+}
+
+impl JitStats {
+    /// Time spent interpreting: whatever was not compiling or machine code
+    pub fn interpreted_time(&self) -> Duration {
+        self.total_time
+            .saturating_sub(self.compile_time)
+            .saturating_sub(self.native_time)
+    }
+}
+
+impl fmt::Display for JitStats {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        let ms = |d: Duration| d.as_secs_f64() * 1000.0;
+        writeln!(
+            f,
+            "jit: {} compiled, {} optimized, {} rejected",
+            self.compiled.len(),
+            self.optimized.len(),
+            self.rejected.len()
+        )?;
+        if !self.compiled.is_empty() {
+            writeln!(f, "  compiled: {}", self.compiled.join(", "))?;
+        }
+        if !self.optimized.is_empty() {
+            writeln!(f, "  optimized: {}", self.optimized.join(", "))?;
+        }
+        if !self.rejected.is_empty() {
+            writeln!(f, "  rejected: {}", self.rejected.join(", "))?;
+        }
+        writeln!(
+            f,
+            "machine code  {:>10.3}ms  ({} calls, {} deopts)",
+            ms(self.native_time),
+            self.native_calls,
+            self.deopts
+        )?;
+        writeln!(f, "compiling     {:>10.3}ms", ms(self.compile_time))?;
+        writeln!(f, "interpreter   {:>10.3}ms", ms(self.interpreted_time()))?;
+        writeln!(f, "total         {:>10.3}ms", ms(self.total_time))
+    }
+}
+
+impl Interpreter {
+    /// What the JIT has done so far (`total_time` is left zero)
+    pub fn jit_stats(&self) -> JitStats {
+        let mut compiled: Vec<String> = self.jit.code.keys().cloned().collect();
+        compiled.sort();
+        let mut optimized: Vec<String> = self
+            .jit
+            .code
+            .iter()
+            .filter(|(_, code)| !matches!(code, MachineCode::Template(_)))
+            .map(|(name, _)| name.clone())
+            .collect();
+        optimized.sort();
+        let mut rejected: Vec<String> = self.jit.rejected.iter().cloned().collect();
+        rejected.sort();
+        JitStats {
+            compiled,
+            optimized,
+            rejected,
+            native_calls: self.jit.native_calls,
+            deopts: self.jit.deopts,
+            compile_time: self.jit.compile_time,
+            native_time: self.jit.native_time,
+            total_time: Duration::ZERO,
+        }
+    }
+
+    /// Print the JIT statistics requested by `ExecutorConfig::jit_stats` (VM shutdown)
+    pub(super) fn report_jit_stats(
+        &self,
+        total_time: Duration,
+    ) {
+        if self.config.jit_stats {
+            let stats = JitStats {
+                total_time,
+                ..self.jit_stats()
+            };
+            // Keep the program's pending output ahead of the report
+            let _ = std::io::Write::flush(&mut std::io::stdout());
+            eprint!("{}", stats);
+        }
+    }
+
+    /// Run `name` as machine code in a frame at call depth `depth`
+    ///
+    /// Counts the call if `name` is not compiled yet, and compiles it once it
+    /// is hot (or optimizes it once it stays hot). `None` means the interpreter has to run the call: the function
+    /// is not compiled, or the machine code gave up, in which case the machine
+    /// code stays off until the frame at `depth` returns.
+    pub(super) fn call_native_code(
+        &mut self,
+        name: &str,
+        args: &[RuntimeValue],
+        depth: usize,
+    ) -> Option<RuntimeValue> {
+        if !self.config.native_code
+            || self.jit.suspended_at.is_some()
+            || !self.breakpoints.is_empty()
+        {
+            return None;
+        }
+        #[cfg(feature = "native")]
+        self.count_warm_call(name);
+        let code = match self.jit.code.get(name) {
+            Some(code) => code.clone(),
+            None => {
+                if !self.count_call(name) {
+                    return None;
+                }
+                self.compile_hot_function(name);
+                self.jit.code.get(name)?.clone()
+            }
+        };
+
+        let levels = self.config.max_stack_depth + 1 - depth;
+        let started = Instant::now();
+        let outcome = code.call(name, args, levels);
+        self.jit.native_time += started.elapsed();
+        match outcome? {
+            Outcome::Returned(value) => {
+                self.jit.native_calls += 1;
+                Some(value)
+            }
+            Outcome::Deopt => {
+                self.jit.deopts += 1;
+                self.jit.suspended_at = Some(depth);
+                None
+            }
+        }
+    }
+
+    /// Switch the machine code back on once the frame that re-ran a call returns
+    pub(super) fn resume_native_code(&mut self) {
+        if self.jit.suspended_at == Some(self.call_depth + 1) {
+            self.jit.suspended_at = None;
+        }
+    }
+
+    /// Count an interpreted call of `name`; `true` once it is hot
+    fn count_call(
+        &mut self,
+        name: &str,
+    ) -> bool {
+        if self.jit.rejected.contains(name) {
+            return false;
+        }
+        let count = match self.jit.counts.get_mut(name) {
+            Some(count) => count,
+            None => self.jit.counts.entry(name.to_string()).or_insert(0),
+        };
+        *count += 1;
+        if *count <= self.config.jit_threshold {
+            return false;
+        }
+        self.jit.counts.remove(name);
+        true
+    }
+
+    /// Compile `name` to template code together with the functions it can reach
+    fn compile_hot_function(
+        &mut self,
+        name: &str,
+    ) {
+        let started = Instant::now();
+        let functions = self.reachable_functions(name);
+        let callee_name = |func: &_| self.static_callee_name(func);
+        let code = Arc::new(TemplateCode::compile(&Program {
+            functions,
+            constants: &self.constants,
+            callee_name: &callee_name,
+            traps_overflow: self.config.build_mode.traps_overflow(),
+        }));
+        tracing::debug!("jit: compiled {} as {:?}", name, code);
+
+        for compiled in code.names() {
+            self.jit.counts.remove(compiled);
+            if !self.jit.code.contains_key(compiled) {
+                self.jit
+                    .code
+                    .insert(compiled.to_string(), MachineCode::Template(code.clone()));
+                #[cfg(feature = "native")]
+                self.jit.warm.insert(compiled.to_string(), 0);
+            }
+        }
+        if !code.is_compiled(name) {
+            self.jit.rejected.insert(name.to_string());
+        }
+        self.jit.compile_time += started.elapsed();
+    }
+
+    /// Count a template-code call of `name`, optimizing it once it stays hot
+    #[cfg(feature = "native")]
+    fn count_warm_call(
+        &mut self,
+        name: &str,
+    ) {
+        let Some(count) = self.jit.warm.get_mut(name) else {
+            return;
+        };
+        *count += 1;
+        if *count <= self.config.jit_threshold.saturating_mul(OPTIMIZE_AFTER) {
+            return;
+        }
+        self.jit.warm.remove(name);
+
+        let started = Instant::now();
+        let functions = self.reachable_functions(name);
+        let callee_name = |func: &_| self.static_callee_name(func);
+        let code = Arc::new(NativeCode::compile(&Program {
+            functions,
+            constants: &self.constants,
+            callee_name: &callee_name,
+            traps_overflow: self.config.build_mode.traps_overflow(),
+        }));
+        tracing::debug!("jit: optimized {} as {:?}", name, code);
+        for optimized in code.names() {
+            self.jit.counts.remove(optimized);
+            self.jit.warm.remove(optimized);
+            self.jit
+                .code
+                .insert(optimized.to_string(), MachineCode::Optimized(code.clone()));
+        }
+        self.jit.compile_time += started.elapsed();
+    }
+
+    /// `name` and the bytecode functions it can reach through static calls
+    ///
+    /// A name the FFI registry knows calls the host function, not the bytecode;
+    /// callees that are not loaded yet keep their callers interpreted.
+    fn reachable_functions(
+        &self,
+        name: &str,
+    ) -> Vec<&BytecodeFunction> {
+        let mut names = vec![name.to_string()];
+        let mut next = 0;
+        while let Some(current) = names.get(next) {
+            next += 1;
+            let Some(func) = self.functions.get(current) else {
+                continue;
+            };
+            for instr in &func.instructions {
+                if let BytecodeInstr::CallStatic { func, .. }
+                | BytecodeInstr::TailCall { func, .. } = instr
+                {
+                    let callee = self.static_callee_name(func);
+                    if !names.contains(&callee) {
+                        names.push(callee);
+                    }
+                }
+            }
+        }
+        names
+            .iter()
+            .filter(|name| !self.ffi.has(name))
+            .filter_map(|name| self.functions.get(name))
+            .map(|func| func.as_ref())
+            .collect()
+    }
+}
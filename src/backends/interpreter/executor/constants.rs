@@ -0,0 +1,194 @@
+//! Composite constants built once per pool slot
+//!
+//! A list, dict or struct constant is converted to runtime values when its
+//! pool is loaded: scalar and string elements become `RuntimeValue`s (strings
+//! share one `Arc`), nested composites are prebuilt the same way. `LoadConst`
+//! then copies the outer collection in one go and only allocates fresh objects
+//! for the nested composites, so loads never share mutable state.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::backends::common::{HeapValue, RuntimeValue, Value};
+use crate::middle::core::ir::ConstValue;
+use super::executor::Interpreter;
+
+/// A constant pool slot converted ahead of `LoadConst`
+#[derive(Debug, Clone)]
+pub(super) enum PrebuiltConst {
+    String(Arc<str>),
+    Composite(Arc<CompositeConst>),
+}
+
+impl PrebuiltConst {
+    /// `None` for constants converted on every load (scalars, FFI references,
+    /// dicts keyed by composites)
+    pub(super) fn build(
+        constant: &ConstValue,
+        strings: &mut HashMap<String, Arc<str>>,
+    ) -> Option<Self> {
+        match constant {
+            ConstValue::String(s) => Some(PrebuiltConst::String(intern(s, strings))),
+            _ => CompositeConst::build(constant, strings)
+                .map(|composite| PrebuiltConst::Composite(Arc::new(composite))),
+        }
+    }
+}
+
+fn intern(
+    s: &str,
+    strings: &mut HashMap<String, Arc<str>>,
+) -> Arc<str> {
+    strings
+        .entry(s.to_string())
+        .or_insert_with(|| Arc::from(s))
+        .clone()
+}
+
+/// Runtime form of a composite constant
+#[derive(Debug)]
+pub(super) struct CompositeConst {
+    kind: CompositeKind,
+    /// Outer collection; nested composites hold `Unit` until instantiated
+    outer: HeapValue,
+    /// Positions of the nested composites in `outer`
+    nested: Vec<(Slot, CompositeConst)>,
+}
+
+#[derive(Debug)]
+enum CompositeKind {
+    List,
+    Dict,
+    Struct(String),
+}
+
+#[derive(Debug)]
+enum Slot {
+    Index(usize),
+    Key(RuntimeValue),
+}
+
+impl CompositeConst {
+    /// Prebuild `constant`; `None` for scalars, and for dicts keyed by
+    /// composites (those keys are heap objects, built on every load instead)
+    fn build(
+        constant: &ConstValue,
+        strings: &mut HashMap<String, Arc<str>>,
+    ) -> Option<Self> {
+        let (kind, items) = match constant {
+            ConstValue::List(items) => (CompositeKind::List, items),
+            ConstValue::Struct { type_name, fields } => {
+                (CompositeKind::Struct(type_name.clone()), fields)
+            }
+            ConstValue::Dict(entries) => return Self::build_dict(entries, strings),
+            _ => return None,
+        };
+        let mut values = Vec::with_capacity(items.len());
+        let mut nested = Vec::new();
+        for (index, item) in items.iter().enumerate() {
+            match Element::build(item, strings)? {
+                Element::Value(value) => values.push(value.into()),
+                Element::Composite(composite) => {
+                    values.push(Value::UNIT);
+                    nested.push((Slot::Index(index), composite));
+                }
+            }
+        }
+        let outer = match kind {
+            CompositeKind::List => HeapValue::List(values),
+            _ => HeapValue::Tuple(values),
+        };
+        Some(Self {
+            kind,
+            outer,
+            nested,
+        })
+    }
+
+    fn build_dict(
+        entries: &[(ConstValue, ConstValue)],
+        strings: &mut HashMap<String, Arc<str>>,
+    ) -> Option<Self> {
+        let mut map = HashMap::with_capacity(entries.len());
+        let mut nested = Vec::new();
+        for (key, value) in entries {
+            let Element::Value(key) = Element::build(key, strings)? else {
+                return None;
+            };
+            match Element::build(value, strings)? {
+                Element::Value(value) => {
+                    map.insert(key, value);
+                }
+                Element::Composite(composite) => {
+                    map.insert(key.clone(), RuntimeValue::Unit);
+                    nested.push((Slot::Key(key), composite));
+                }
+            }
+        }
+        Some(Self {
+            kind: CompositeKind::Dict,
+            outer: HeapValue::Dict(map),
+            nested,
+        })
+    }
+}
+
+/// One element of a composite constant
+enum Element {
+    Value(RuntimeValue),
+    Composite(CompositeConst),
+}
+
+impl Element {
+    fn build(
+        constant: &ConstValue,
+        strings: &mut HashMap<String, Arc<str>>,
+    ) -> Option<Self> {
+        Some(match constant {
+            ConstValue::Void => Element::Value(RuntimeValue::Unit),
+            ConstValue::Bool(b) => Element::Value(RuntimeValue::Bool(*b)),
+            ConstValue::Int(i) => Element::Value(RuntimeValue::Int(*i as i64)),
+            ConstValue::Float(f) => Element::Value(RuntimeValue::Float(*f)),
+            ConstValue::Char(c) => Element::Value(RuntimeValue::Char(*c as u32)),
+            ConstValue::String(s) => Element::Value(RuntimeValue::String(intern(s, strings))),
+            ConstValue::Bytes(b) => Element::Value(RuntimeValue::Bytes(b.as_slice().into())),
+            ConstValue::List(_) | ConstValue::Dict(_) | ConstValue::Struct { .. } => {
+                Element::Composite(CompositeConst::build(constant, strings)?)
+            }
+            ConstValue::LibraryRef { .. } | ConstValue::ExternRef { .. } => return None,
+        })
+    }
+}
+
+impl Interpreter {
+    /// Instantiate a prebuilt composite: the outer collection is copied, nested
+    /// composites get fresh objects
+    pub(super) fn instantiate_composite(
+        &mut self,
+        composite: &CompositeConst,
+    ) -> RuntimeValue {
+        let mut outer = composite.outer.clone();
+        for (slot, nested) in &composite.nested {
+            let value = self.instantiate_composite(nested);
+            match (slot, &mut outer) {
+                (Slot::Index(index), HeapValue::List(items) | HeapValue::Tuple(items)) => {
+                    items[*index] = value.into();
+                }
+                (Slot::Key(key), HeapValue::Dict(map)) => {
+                    map.insert(key.clone(), value);
+                }
+                _ => unreachable!("nested slot does not match its collection"),
+            }
+        }
+        let handle = self.heap.allocate(outer);
+        match &composite.kind {
+            CompositeKind::List => RuntimeValue::List(handle),
+            CompositeKind::Dict => RuntimeValue::Dict(handle),
+            CompositeKind::Struct(type_name) => RuntimeValue::Struct {
+                type_id: self.struct_types.id_of(type_name),
+                fields: handle,
+                vtable: self.build_vtable(type_name).into(),
+            },
+        }
+    }
+}
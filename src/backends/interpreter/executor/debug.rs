@@ -3,11 +3,17 @@
 //! This module contains the DebuggableExecutor trait implementation and the
 //! core stepping engine (step_one / execute_instr / run_until_stop).
 
+use std::sync::Arc;
+
 use crate::backends::{DebuggableExecutor, ExecutorError, ExecutorResult};
-use crate::backends::common::RuntimeValue;
-use crate::middle::bytecode::{BytecodeInstr, FunctionRef, ConstValue, Label, Reg};
-use super::executor::Interpreter;
+use crate::backends::common::{RuntimeValue, Value};
+use crate::middle::bytecode::{FunctionRef, ConstValue, NumericTarget, Reg};
+use crate::middle::decoded::{DecodedCode, Instr, Op};
+use super::executor::{as_float, Interpreter};
 use crate::backends::interpreter::Frame;
+#[cfg(feature = "hooks")]
+use crate::backends::interpreter::hooks::VmState;
+use crate::backends::interpreter::frames::MAX_LOCALS;
 
 /// Outcome of a single instruction execution.
 pub(super) enum StepOutcome {
@@ -25,14 +31,35 @@ pub(super) enum StopReason {
 }
 
 impl Interpreter {
-    /// Decode a Label into a signed offset for relative jumps.
-    fn decode_label_offset(label: Label) -> i32 {
-        i32::from_le_bytes([
-            label.0 as u8,
-            (label.0 >> 8) as u8,
-            (label.0 >> 16) as u8,
-            (label.0 >> 24) as u8,
-        ])
+    /// Jump to the absolute instruction index `target`
+    fn jump(
+        &mut self,
+        frame: &mut Frame,
+        target: usize,
+    ) -> ExecutorResult<StepOutcome> {
+        if target <= frame.ip {
+            // 循环回边是安全点
+            self.safepoint(Some(&*frame), &[])?;
+        }
+        frame.ip = target;
+        Ok(StepOutcome::Continue)
+    }
+
+    /// Name of a statically called function (constant-pool index or qualified name).
+    pub(super) fn static_callee_name(
+        &self,
+        func_ref: &FunctionRef,
+    ) -> String {
+        match func_ref {
+            FunctionRef::Static { name, .. } => name.clone(),
+            FunctionRef::Index(idx) => {
+                if let Some(ConstValue::String(s)) = self.constants.get(*idx as usize) {
+                    s.clone()
+                } else {
+                    format!("fn_{}", idx)
+                }
+            }
+        }
     }
 
     /// Execute a single instruction. The core of the stepping engine.
@@ -59,14 +86,22 @@ impl Interpreter {
         }
 
         let depth_before = self.call_stack.len();
-        let instr = frame.function.instructions[frame.ip].clone();
-        let outcome = self.execute_instr(&mut frame, &instr)?;
+        let function = Arc::clone(&frame.function);
+        let code = function.decoded();
+        #[cfg(feature = "hooks")]
+        if let Some(hook) = &self.hook {
+            hook.on_step(&VmState::new(self, &frame, self.call_depth));
+        }
+        let instr = code.instrs[frame.ip];
+        let outcome = self.execute_instr(&mut frame, code, instr)?;
 
         // Detect if a function call was executed (depth increased then restored)
         self.called_func = self.call_stack.len() > depth_before;
 
         // Don't push back on Return — frame is already consumed
-        if !matches!(outcome, StepOutcome::Returned) {
+        if matches!(outcome, StepOutcome::Returned) {
+            self.frame_pool.release(frame);
+        } else {
             self.push_frame(frame)?;
         }
 
@@ -74,6 +109,57 @@ impl Interpreter {
         Ok(outcome)
     }
 
+    /// Run the frame on top of the call stack until it returns.
+    ///
+    /// Same effect as calling `step_one` until it reports `Returned`, but the
+    /// frame stays off the call stack between instructions.
+    pub(super) fn run_frame(&mut self) -> ExecutorResult<RuntimeValue> {
+        let Some(mut frame) = self.pop_frame() else {
+            return Ok(std::mem::replace(
+                &mut self.last_return_value,
+                RuntimeValue::Unit,
+            ));
+        };
+
+        'function: loop {
+            let function = Arc::clone(&frame.function);
+            let code = function.decoded();
+            loop {
+                let Some(&instr) = code.instrs.get(frame.ip) else {
+                    // Ran off the end: left on the stack, as step_one does
+                    self.current_frame_info = None;
+                    self.push_frame(frame)?;
+                    return Ok(std::mem::replace(
+                        &mut self.last_return_value,
+                        RuntimeValue::Unit,
+                    ));
+                };
+                match &mut self.current_frame_info {
+                    Some((name, ip)) if *name == function.name => *ip = frame.ip,
+                    info => *info = Some((function.name.clone(), frame.ip)),
+                }
+                #[cfg(feature = "hooks")]
+                if let Some(hook) = &self.hook {
+                    hook.on_step(&VmState::new(self, &frame, self.call_depth));
+                }
+                if let StepOutcome::Returned = self.execute_instr(&mut frame, code, instr)? {
+                    break 'function;
+                }
+                // A tail call replaces the frame's function
+                if !Arc::ptr_eq(&function, &frame.function) {
+                    continue 'function;
+                }
+            }
+        }
+
+        self.frame_pool.release(frame);
+        self.current_frame_info = None;
+        Ok(std::mem::replace(
+            &mut self.last_return_value,
+            RuntimeValue::Unit,
+        ))
+    }
+
     /// Execute until a stop condition (breakpoint, return, or completion).
     pub(super) fn run_until_stop(&mut self) -> ExecutorResult<StopReason> {
         loop {
@@ -92,6 +178,14 @@ impl Interpreter {
         }
     }
 
+    #[cold]
+    fn fuel_exhausted(&self) -> ExecutorError {
+        ExecutorError::FuelExhausted(
+            self.config.fuel.unwrap_or(u64::MAX),
+            Some(self.capture_stack()),
+        )
+    }
+
     /// Execute a single instruction on the given frame.
     ///
     /// This is the instruction dispatcher — all instruction logic lives here.
@@ -100,194 +194,265 @@ impl Interpreter {
     fn execute_instr(
         &mut self,
         frame: &mut Frame,
-        instr: &BytecodeInstr,
+        code: &DecodedCode,
+        instr: Instr,
     ) -> ExecutorResult<StepOutcome> {
-        match instr {
+        // 每条指令消耗一份燃料；未设置预算时从 u64::MAX 开始，不会耗尽
+        if self.fuel == 0 {
+            return Err(self.fuel_exhausted());
+        }
+        self.fuel -= 1;
+        match instr.op {
             // ── No-ops ──────────────────────────────────────────
-            BytecodeInstr::Nop
-            | BytecodeInstr::Yield
-            | BytecodeInstr::Drop { .. }
-            | BytecodeInstr::Release { .. }
-            | BytecodeInstr::StackAlloc { .. }
-            | BytecodeInstr::TryBegin { .. }
-            | BytecodeInstr::TryEnd
-            | BytecodeInstr::ArcDrop { .. }
-            | BytecodeInstr::CloseUpvalue { .. } => {
+            Op::Nop
+            | Op::Drop
+            | Op::Release
+            | Op::TryBegin
+            | Op::TryEnd
+            | Op::ArcDrop
+            | Op::CloseUpvalue => {
+                frame.advance();
+                Ok(StepOutcome::Continue)
+            }
+
+            // 绿色线程让出执行权；不在绿色线程中时什么也不做
+            Op::Yield => {
+                #[cfg(not(target_arch = "wasm32"))]
+                if let Some(green) = crate::backends::runtime::green::current() {
+                    green.yield_now();
+                }
                 frame.advance();
                 Ok(StepOutcome::Continue)
             }
 
             // ── Return ──────────────────────────────────────────
-            BytecodeInstr::Return => {
+            Op::Return => {
                 for task_id in frame.take_all_spawned_tasks() {
                     let mut v = self.make_async_pending(task_id);
                     self.force_value_in_place(&mut v)?;
                 }
+                self.release_stack_slots(frame);
                 self.last_return_value = RuntimeValue::Unit;
                 // Frame is NOT pushed back — caller handles this
                 Ok(StepOutcome::Returned)
             }
-            BytecodeInstr::ReturnValue { value } => {
-                let result = frame
-                    .registers
-                    .get(value.0 as usize)
-                    .cloned()
-                    .unwrap_or(RuntimeValue::Unit);
+            Op::ReturnValue => {
+                let value = instr.a();
+                let result = frame.register(value.0 as usize);
                 for task_id in frame.take_all_spawned_tasks() {
                     let mut v = self.make_async_pending(task_id);
                     self.force_value_in_place(&mut v)?;
                 }
+                self.release_stack_slots(frame);
                 self.last_return_value = result;
                 Ok(StepOutcome::Returned)
             }
 
             // ── Jumps ───────────────────────────────────────────
-            BytecodeInstr::Jmp { target } => {
-                let offset = Self::decode_label_offset(*target);
-                frame.ip = ((frame.ip as i32) + offset) as usize;
-                Ok(StepOutcome::Continue)
-            }
-            BytecodeInstr::JmpIf { cond, target } => {
+            Op::Jmp => self.jump(frame, instr.target()),
+            Op::JmpIf | Op::JmpIfNot => {
                 let c = self
-                    .force_register(frame, *cond)?
+                    .force_register(frame, instr.a())?
                     .to_bool()
                     .unwrap_or(false);
-                if c {
-                    let offset = Self::decode_label_offset(*target);
-                    frame.ip = ((frame.ip as i32) + offset) as usize;
+                if c == (instr.op == Op::JmpIf) {
+                    self.jump(frame, instr.target())
                 } else {
                     frame.advance();
+                    Ok(StepOutcome::Continue)
                 }
-                Ok(StepOutcome::Continue)
             }
-            BytecodeInstr::JmpIfNot { cond, target } => {
-                let c = self
-                    .force_register(frame, *cond)?
-                    .to_bool()
-                    .unwrap_or(false);
-                if !c {
-                    let offset = Self::decode_label_offset(*target);
-                    frame.ip = ((frame.ip as i32) + offset) as usize;
-                } else {
-                    frame.advance();
+            Op::Switch => {
+                let switch = &code.switches[instr.index()];
+                let val = self.force_register(frame, instr.a())?;
+                let matched = code
+                    .cases(switch.cases)
+                    .iter()
+                    .find(|(case, _)| match &val {
+                        RuntimeValue::Int(n) => *n == i64::from(*case),
+                        RuntimeValue::Bool(b) => *b == (*case != 0),
+                        RuntimeValue::Enum { variant_id, .. } => *variant_id == *case as u32,
+                        _ => false,
+                    });
+                match matched.map(|(_, target)| *target).or(switch.default) {
+                    Some(target) => frame.ip = target as usize,
+                    None => frame.advance(),
                 }
                 Ok(StepOutcome::Continue)
             }
-            BytecodeInstr::Switch { value, targets } => {
-                let val = self.force_register(frame, *value)?;
-                let mut jumped = false;
-                for (case_val, target) in targets {
-                    if let Some(case_label) = case_val {
-                        let case_offset = Self::decode_label_offset(*case_label);
-                        let matches = match &val {
-                            RuntimeValue::Int(n) => *n == case_offset as i64,
-                            RuntimeValue::Bool(b) => *b == (case_offset != 0),
-                            RuntimeValue::Enum { variant_id, .. } => {
-                                *variant_id == case_offset as u32
-                            }
-                            _ => false,
-                        };
-                        if matches {
-                            let offset = Self::decode_label_offset(*target);
-                            frame.ip = ((frame.ip as i32) + offset) as usize;
-                            jumped = true;
-                            break;
-                        }
-                    }
-                }
-                if !jumped {
-                    if let Some((None, default_target)) = targets.last() {
-                        let offset = Self::decode_label_offset(*default_target);
-                        frame.ip = ((frame.ip as i32) + offset) as usize;
-                    } else {
-                        frame.advance();
-                    }
-                }
-                Ok(StepOutcome::Continue)
+
+            Op::TableSwitch => {
+                let table = &code.tables[instr.index()];
+                let key = match self.force_register(frame, instr.a())? {
+                    RuntimeValue::Int(n) => Some(n),
+                    RuntimeValue::Char(c) => Some(i64::from(c)),
+                    RuntimeValue::Enum { variant_id, .. } => Some(i64::from(variant_id)),
+                    _ => None,
+                };
+                let target = key
+                    .and_then(|key| key.checked_sub(table.low))
+                    .and_then(|index| usize::try_from(index).ok())
+                    .and_then(|index| code.targets(table.targets).get(index))
+                    .copied()
+                    .unwrap_or(table.default);
+                self.jump(frame, target as usize)
             }
 
             // ── Register operations ─────────────────────────────
-            BytecodeInstr::Mov { dst, src } => {
+            Op::Mov => {
+                let (dst, src) = (instr.a(), instr.b());
                 let val = frame
                     .registers
                     .get(src.0 as usize)
                     .cloned()
-                    .unwrap_or(RuntimeValue::Unit);
+                    .unwrap_or_default();
                 frame.set_register(dst.0 as usize, val);
                 frame.advance();
                 Ok(StepOutcome::Continue)
             }
-            BytecodeInstr::LoadConst { dst, const_idx } => {
-                let val = self.load_constant(*const_idx);
+            Op::LoadConst => {
+                let (dst, const_idx) = (instr.a(), instr.b);
+                let val = self.load_constant(const_idx);
                 frame.set_register(dst.0 as usize, val);
                 frame.advance();
                 Ok(StepOutcome::Continue)
             }
-            BytecodeInstr::LoadLocal { dst, local_idx } => {
+            Op::LoadLocal => {
+                let (dst, local_idx) = (instr.a(), instr.b);
                 let val = frame
-                    .get_local(*local_idx as usize)
+                    .locals()
+                    .get(local_idx as usize)
                     .cloned()
-                    .unwrap_or(RuntimeValue::Unit);
+                    .unwrap_or_default();
                 frame.set_register(dst.0 as usize, val);
                 frame.advance();
                 Ok(StepOutcome::Continue)
             }
-            BytecodeInstr::StoreLocal { local_idx, src } => {
+            Op::StoreLocal => {
+                let (local_idx, src) = (instr.a, instr.b());
                 let val = frame
                     .registers
                     .get(src.0 as usize)
                     .cloned()
-                    .unwrap_or(RuntimeValue::Unit);
-                frame.set_local(*local_idx as usize, val);
+                    .unwrap_or_default();
+                frame.set_local(local_idx as usize, val);
                 frame.advance();
                 Ok(StepOutcome::Continue)
             }
-            BytecodeInstr::LoadArg { dst, arg_idx } => {
+            Op::LoadArg => {
+                let (dst, arg_idx) = (instr.a(), instr.b);
                 // Args are stored in locals by Frame::with_args
                 let val = frame
-                    .get_local(*arg_idx as usize)
+                    .locals()
+                    .get(arg_idx as usize)
                     .cloned()
-                    .unwrap_or(RuntimeValue::Unit);
+                    .unwrap_or_default();
                 frame.set_register(dst.0 as usize, val);
                 frame.advance();
                 Ok(StepOutcome::Continue)
             }
-            BytecodeInstr::LoadUpvalue { dst, upvalue_idx } => {
+            Op::LoadUpvalue => {
+                let (dst, upvalue_idx) = (instr.a(), instr.b);
                 let val = frame
-                    .get_upvalue(*upvalue_idx as usize)
+                    .upvalues()
+                    .get(upvalue_idx as usize)
                     .cloned()
-                    .unwrap_or(RuntimeValue::Unit);
+                    .unwrap_or_default();
                 frame.set_register(dst.0 as usize, val);
                 frame.advance();
                 Ok(StepOutcome::Continue)
             }
-            BytecodeInstr::StoreUpvalue { src, upvalue_idx } => {
+            Op::StoreUpvalue => {
+                let (src, upvalue_idx) = (instr.a(), instr.b);
                 let val = frame
                     .registers
                     .get(src.0 as usize)
                     .cloned()
                     .expect("register index out of bounds");
-                frame.set_upvalue(*upvalue_idx as usize, val);
+                frame.set_upvalue(upvalue_idx as usize, val);
                 frame.advance();
                 Ok(StepOutcome::Continue)
             }
 
             // ── Arithmetic / comparison ─────────────────────────
-            BytecodeInstr::BinaryOp { dst, lhs, rhs, op } => {
-                self.exec_binary_op(*dst, *lhs, *rhs, *op, frame)?;
+            Op::BinaryOp => {
+                let (dst, lhs, rhs, op) = (instr.a(), instr.b(), instr.c(), instr.binary_op());
+                self.exec_binary_op(dst, lhs, rhs, op, frame)?;
+                frame.advance();
+                Ok(StepOutcome::Continue)
+            }
+            Op::Int32Op => {
+                let (dst, lhs, rhs, op) = (instr.a(), instr.b(), instr.c(), instr.binary_op());
+                self.exec_int32_op(dst, lhs, rhs, op, frame)?;
+                frame.advance();
+                Ok(StepOutcome::Continue)
+            }
+            Op::Int32Neg => {
+                let (dst, src) = (instr.a(), instr.b());
+                let val = self.force_register(frame, src)?;
+                let RuntimeValue::Int(n) = val else {
+                    let stack = self.capture_stack();
+                    return Err(ExecutorError::type_error(
+                        format!("expected an integer, found {:?}", val),
+                        stack,
+                    ));
+                };
+                let result =
+                    self.narrow_value(RuntimeValue::Int(n.wrapping_neg()), NumericTarget::Int(32))?;
+                frame.set_register(dst.0 as usize, result);
+                frame.advance();
+                Ok(StepOutcome::Continue)
+            }
+            Op::Compare => {
+                let (dst, lhs, rhs, cmp) = (instr.a(), instr.b(), instr.c(), instr.compare_op());
+                self.exec_compare(dst, lhs, rhs, cmp, frame)?;
                 frame.advance();
                 Ok(StepOutcome::Continue)
             }
-            BytecodeInstr::Compare { dst, lhs, rhs, cmp } => {
-                self.exec_compare(*dst, *lhs, *rhs, *cmp, frame)?;
+            Op::FloatOp => {
+                let (dst, lhs, rhs, op) = (instr.a(), instr.b(), instr.c(), instr.binary_op());
+                self.exec_float_op(dst, lhs, rhs, op, frame)?;
+                frame.advance();
+                Ok(StepOutcome::Continue)
+            }
+            Op::FloatCompare => {
+                let (dst, lhs, rhs, cmp) = (instr.a(), instr.b(), instr.c(), instr.compare_op());
+                self.exec_float_compare(dst, lhs, rhs, cmp, frame)?;
+                frame.advance();
+                Ok(StepOutcome::Continue)
+            }
+            Op::FloatNeg | Op::IntToFloat => {
+                let (dst, src) = (instr.a(), instr.b());
+                let val = self.force_register(frame, src)?;
+                let Some(f) = as_float(&val) else {
+                    let stack = self.capture_stack();
+                    return Err(ExecutorError::type_error(
+                        format!("expected a number, found {:?}", val),
+                        stack,
+                    ));
+                };
+                let negate = instr.op == Op::FloatNeg;
+                frame.set_register(
+                    dst.0 as usize,
+                    RuntimeValue::Float(if negate { -f } else { f }),
+                );
                 frame.advance();
                 Ok(StepOutcome::Continue)
             }
-            BytecodeInstr::UnaryOp { dst, src, op } => {
-                let val = self.force_register(frame, *src)?;
+            Op::UnaryOp => {
+                let (dst, src, op) = (instr.a(), instr.b(), instr.unary_op());
+                let val = self.force_register(frame, src)?;
                 let result = match (op, val) {
                     (crate::middle::bytecode::UnaryOp::Neg, RuntimeValue::Int(n)) => {
-                        RuntimeValue::Int(-n)
+                        let (value, overflowed) = n.overflowing_neg();
+                        if overflowed && self.config.build_mode.traps_overflow() {
+                            let stack = self.capture_stack();
+                            return Err(ExecutorError::integer_overflow(
+                                format!("-({})", n),
+                                stack,
+                            ));
+                        }
+                        RuntimeValue::Int(value)
                     }
                     (crate::middle::bytecode::UnaryOp::Neg, RuntimeValue::Float(f)) => {
                         RuntimeValue::Float(-f)
@@ -312,167 +477,122 @@ impl Interpreter {
             }
 
             // ── Function calls ──────────────────────────────────
-            BytecodeInstr::CallStatic {
-                dst,
-                func: func_ref,
-                args: arg_regs,
-            } => {
-                let func_name = match func_ref {
-                    FunctionRef::Static { name, .. } => name.clone(),
-                    FunctionRef::Index(idx) => {
-                        if let Some(ConstValue::String(s)) = self.constants.get(*idx as usize) {
-                            s.clone()
-                        } else {
-                            format!("fn_{}", idx)
-                        }
-                    }
-                };
+            Op::CallStatic => {
+                let call = &code.calls[instr.index()];
+                let (dst, func_ref, arg_regs) = (instr.dst(), &call.func, code.regs(call.args));
+                let func_name = self.static_callee_name(func_ref);
 
                 let call_args: Vec<RuntimeValue> = arg_regs
                     .iter()
-                    .map(|r| {
-                        frame
-                            .registers
-                            .get(r.0 as usize)
-                            .cloned()
-                            .unwrap_or(RuntimeValue::Unit)
-                    })
+                    .map(|r| frame.register(r.0 as usize))
                     .collect();
 
-                let runtime = self.runtime_config.runtime;
-
-                if matches!(runtime, crate::backends::runtime::RuntimeMode::Embedded) {
-                    let result = self.call_static_by_name(&func_name, &call_args)?;
-                    if let Some(dst_reg) = dst {
-                        frame.set_register(dst_reg.index() as usize, result);
-                    }
-                    frame.advance();
-                    return Ok(StepOutcome::Continue);
-                }
-
-                use crate::backends::runtime::engine::{ResourceKey, TaskMeta};
-                use std::sync::Arc;
-
-                let is_ffi = self.ffi.has(&func_name);
-                let deps = self.deps_from_args(&call_args);
-                let resources = if is_ffi {
-                    vec![ResourceKey::from("ffi")]
-                } else {
-                    Vec::new()
-                };
-
-                let task_id = self.schedule_task(
-                    if is_ffi {
-                        super::executor::InterpreterTask::Native {
-                            func_name: func_name.clone(),
-                            args: call_args.clone(),
-                        }
-                    } else {
-                        super::executor::InterpreterTask::Static {
-                            func_name: func_name.clone(),
-                            args: call_args.clone(),
-                        }
-                    },
-                    TaskMeta {
-                        deps,
-                        resources,
-                        label: Some(Arc::<str>::from(func_name.as_str())),
-                    },
-                )?;
-
-                self.drive_dag_until(Some(task_id))?;
-                let mut v = self.make_async_pending(task_id);
-                self.force_value_in_place(&mut v)?;
+                // 普通调用立即等待结果，在所有运行时中都就地执行；只有 spawn 产生任务
+                let result = self.with_frame_suspended(frame, |this, _| {
+                    this.call_static_by_name(&func_name, &call_args)
+                })?;
                 if let Some(dst_reg) = dst {
-                    frame.set_register(dst_reg.index() as usize, v);
+                    frame.set_register(dst_reg.index() as usize, result);
                 }
-
                 frame.advance();
                 Ok(StepOutcome::Continue)
             }
-            BytecodeInstr::CallNative {
-                dst,
-                func_name,
-                mechanism,
-                lib,
-                symbol,
-                args: arg_regs,
-            } => {
-                let call_args: Vec<RuntimeValue> = arg_regs
-                    .iter()
-                    .map(|r| {
-                        frame
-                            .registers
-                            .get(r.0 as usize)
-                            .cloned()
-                            .unwrap_or(RuntimeValue::Unit)
-                    })
-                    .collect();
-
-                let runtime = self.runtime_config.runtime;
+            Op::TailCall => {
+                let call = &code.calls[instr.index()];
+                let (func_ref, arg_regs) = (&call.func, code.regs(call.args));
+                let func_name = self.static_callee_name(func_ref);
+                let mut call_args = Vec::with_capacity(arg_regs.len());
+                for r in arg_regs {
+                    let arg = frame.register(r.0 as usize);
+                    call_args.push(self.force_value_clone(&arg)?);
+                }
+                // The frame is about to be discarded, same as on Return
+                for task_id in frame.take_all_spawned_tasks() {
+                    let mut v = self.make_async_pending(task_id);
+                    self.force_value_in_place(&mut v)?;
+                }
+                self.release_stack_slots(frame);
 
-                if matches!(runtime, crate::backends::runtime::RuntimeMode::Embedded) {
-                    let result = self
-                        .call_native_with_ffi_meta(func_name, mechanism, lib, symbol, &call_args)?;
-                    if let Some(dst_reg) = dst {
-                        frame.set_register(dst_reg.index() as usize, result);
-                    }
-                    frame.advance();
-                    return Ok(StepOutcome::Continue);
+                if self.ffi.has(&func_name) {
+                    self.last_return_value = self.call_native_by_name(&func_name, &call_args)?;
+                    return Ok(StepOutcome::Returned);
                 }
 
-                use crate::backends::runtime::engine::{ResourceKey, TaskMeta};
-                use std::sync::Arc;
+                let target = self.lookup_static_function(&func_name)?;
+                if target.local_count > MAX_LOCALS {
+                    let stack = self.capture_stack();
+                    return Err(ExecutorError::runtime(
+                        format!(
+                            "Too many locals in function '{}': {}",
+                            target.name, target.local_count
+                        ),
+                        stack,
+                    ));
+                }
+                // Tail recursion has no loop back-edge, so the call is the safepoint
+                self.safepoint(Some(&*frame), &call_args)?;
+
+                // The callee takes over this frame's call depth
+                if let Some(value) =
+                    self.call_native_code(&target.name, &call_args, self.call_depth)
+                {
+                    self.last_return_value = value;
+                    return Ok(StepOutcome::Returned);
+                }
 
-                let deps = self.deps_from_args(&call_args);
-                let task_id = self.schedule_task(
-                    super::executor::InterpreterTask::Native {
-                        func_name: func_name.clone(),
-                        args: call_args.clone(),
-                    },
-                    TaskMeta {
-                        deps,
-                        resources: vec![ResourceKey::from("ffi")],
-                        label: Some(Arc::<str>::from(func_name.as_str())),
-                    },
-                )?;
+                // Reuse the frame: the call depth stays the same
+                let caller = std::mem::replace(frame, self.frame_pool.frame(target, &call_args));
+                self.frame_pool.release(caller);
+                frame.set_entry_ip(0);
+                Ok(StepOutcome::Continue)
+            }
+            Op::CallNative => {
+                let native = &code.natives[instr.index()];
+                let (dst, arg_regs) = (instr.dst(), code.regs(native.args));
+                let mut call_args = Vec::with_capacity(arg_regs.len());
+                for r in arg_regs {
+                    // 原生函数不认识 Async：先等待 spawn 出的参数
+                    call_args.push(self.force_register(frame, *r)?);
+                }
 
-                self.drive_dag_until(Some(task_id))?;
-                let mut v = self.make_async_pending(task_id);
-                self.force_value_in_place(&mut v)?;
+                let result = self.with_frame_suspended(frame, |this, _| {
+                    this.call_native_with_ffi_meta(
+                        &native.func_name,
+                        &native.mechanism,
+                        &native.lib,
+                        &native.symbol,
+                        &call_args,
+                    )
+                })?;
                 if let Some(dst_reg) = dst {
-                    frame.set_register(dst_reg.index() as usize, v);
+                    frame.set_register(dst_reg.index() as usize, result);
                 }
-
                 frame.advance();
                 Ok(StepOutcome::Continue)
             }
-            BytecodeInstr::CallVirt {
-                dst,
-                obj,
-                method_idx,
-                args,
-            } => {
-                let obj_val = self.force_register(frame, *obj)?;
-
-                let method_name = self
-                    .constants
-                    .get(*method_idx as usize)
-                    .and_then(|c| {
-                        if let ConstValue::String(s) = c {
-                            Some(s.clone())
-                        } else {
-                            None
-                        }
-                    })
-                    .unwrap_or_default();
+            Op::CallVirt => {
+                let method = &code.methods[instr.index()];
+                let (dst, obj, method_idx, args) = (
+                    instr.dst(),
+                    instr.b(),
+                    method.name_idx,
+                    code.regs(method.args),
+                );
+                let obj_val = self.force_register(frame, obj)?;
 
-                if let Some(func_value) = obj_val.get_method(&method_name).cloned() {
+                let method_name = match self.constants.get(method_idx as usize) {
+                    Some(ConstValue::String(s)) => s.as_str(),
+                    _ => "",
+                };
+
+                if let Some(func_id) = obj_val.get_method(method_name).map(|f| f.func_id) {
                     let mut call_args = Vec::with_capacity(args.len());
                     for r in args {
                         call_args.push(self.force_register(frame, *r)?);
                     }
-                    let result = self.call_function_by_id(func_value.func_id, &call_args)?;
+                    let result = self.with_frame_suspended(frame, |this, frame| {
+                        this.call_method_cached(frame, func_id, &call_args)
+                    })?;
                     if let Some(dst_reg) = dst {
                         frame.set_register(dst_reg.index() as usize, result);
                     }
@@ -484,13 +604,70 @@ impl Interpreter {
                 frame.advance();
                 Ok(StepOutcome::Continue)
             }
-            BytecodeInstr::CallDyn {
-                dst,
-                obj,
-                name_idx: _,
-                args,
-            } => {
-                let closure_val = self.force_register(frame, *obj)?;
+            Op::MakeDyn => {
+                let (dst, src, vtable) = (instr.a(), instr.b(), instr.imm);
+                let value = self.force_register(frame, src)?;
+                frame.set_register(
+                    dst.index() as usize,
+                    RuntimeValue::Dyn {
+                        value: Box::new(value),
+                        vtable,
+                    },
+                );
+                frame.advance();
+                Ok(StepOutcome::Continue)
+            }
+            Op::InvokeVirtual => {
+                let method = &code.methods[instr.index()];
+                let (dst, obj, slot, name_idx, args) = (
+                    instr.dst(),
+                    instr.b(),
+                    instr.c,
+                    method.name_idx,
+                    code.regs(method.args),
+                );
+                let obj_val = self.force_register(frame, obj)?;
+                let mut call_args = Vec::with_capacity(args.len());
+                for r in args.iter().skip(1) {
+                    call_args.push(self.force_register(frame, *r)?);
+                }
+
+                // 存在类型值按虚表槽位分派；未打包的具体值退回按方法名查找
+                let result = match obj_val {
+                    RuntimeValue::Dyn { value, vtable } => {
+                        call_args.insert(0, *value);
+                        self.with_frame_suspended(frame, |this, frame| {
+                            this.call_vtable_slot_cached(frame, vtable, slot, &call_args)
+                        })?
+                    }
+                    obj_val => {
+                        let method_name = match self.constants.get(name_idx as usize) {
+                            Some(ConstValue::String(s)) => s.as_str(),
+                            _ => "",
+                        };
+                        let Some(func_id) = obj_val.get_method(method_name).map(|f| f.func_id)
+                        else {
+                            return Err(ExecutorError::function_not_found(
+                                format!("Method not found: '{}'", method_name),
+                                self.capture_stack(),
+                            ));
+                        };
+                        call_args.insert(0, obj_val);
+                        self.with_frame_suspended(frame, |this, frame| {
+                            this.call_method_cached(frame, func_id, &call_args)
+                        })?
+                    }
+                };
+                if let Some(dst_reg) = dst {
+                    frame.set_register(dst_reg.index() as usize, result);
+                }
+                frame.advance();
+                Ok(StepOutcome::Continue)
+            }
+            Op::CallDyn => {
+                let method = &code.methods[instr.index()];
+                let (dst, obj, args) = (instr.dst(), instr.b(), code.regs(method.args));
+                let closure_val = self.force_register(frame, obj)?;
 
                 if let RuntimeValue::Function(func_value) = closure_val {
                     let env_args: Vec<RuntimeValue> = func_value.env.clone();
@@ -500,7 +677,9 @@ impl Interpreter {
                     }
                     let mut final_args = env_args;
                     final_args.extend(call_args);
-                    let result = self.call_function_by_id(func_value.func_id, &final_args)?;
+                    let result = self.with_frame_suspended(frame, |this, _| {
+                        this.call_function_by_id(func_value.func_id, &final_args)
+                    })?;
                     if let Some(dst_reg) = dst {
                         frame.set_register(dst_reg.index() as usize, result);
                     }
@@ -514,18 +693,15 @@ impl Interpreter {
             }
 
             // ── Concurrency ─────────────────────────────────────
-            BytecodeInstr::Spawn {
-                dst: _,
-                closures,
-                task_deps,
-                task_resources,
-            } => {
-                let closures = closures.clone();
-                let task_deps = task_deps.clone();
-                let task_resources = task_resources.clone();
-                let runtime = self.runtime_config.runtime;
-
-                if matches!(runtime, crate::backends::runtime::RuntimeMode::Embedded) {
+            Op::Spawn => {
+                let spawn = &code.spawns[instr.index()];
+                let (closures, task_deps, task_resources) = (
+                    code.regs(spawn.closures),
+                    &spawn.task_deps,
+                    &spawn.task_resources,
+                );
+
+                if self.spawns_inline() {
                     for func_reg in closures.iter() {
                         let closure_val = self.force_register(frame, *func_reg)?;
                         let RuntimeValue::Function(func_value) = closure_val else {
@@ -535,8 +711,9 @@ impl Interpreter {
                                 stack,
                             ));
                         };
-                        let _result =
-                            self.call_function_by_id(func_value.func_id, &func_value.env)?;
+                        let _result = self.with_frame_suspended(frame, |this, _| {
+                            this.call_function_by_id(func_value.func_id, &func_value.env)
+                        })?;
                         frame.set_register(func_reg.0 as usize, _result);
                     }
                 } else {
@@ -598,20 +775,17 @@ impl Interpreter {
                 frame.advance();
                 Ok(StepOutcome::Continue)
             }
-            BytecodeInstr::SpawnFromList {
-                dst: _,
-                closures_list,
-                task_deps,
-                task_resources,
-            } => {
-                let closures_list = *closures_list;
-                let task_deps = task_deps.clone();
-                let task_resources = task_resources.clone();
+            Op::SpawnFromList => {
+                let spawn = &code.spawns[instr.index()];
+                let (closures_list, task_deps, task_resources) =
+                    (instr.b(), &spawn.task_deps, &spawn.task_resources);
 
                 let list_val = self.force_register(frame, closures_list)?;
                 let closures: Vec<RuntimeValue> = match list_val {
                     RuntimeValue::List(handle) => match self.heap.get(handle) {
-                        Some(crate::backends::common::HeapValue::List(items)) => items.clone(),
+                        Some(crate::backends::common::HeapValue::List(items)) => {
+                            items.iter().map(Value::get).collect()
+                        }
                         _ => {
                             let stack = self.capture_stack();
                             return Err(ExecutorError::type_error(
@@ -629,9 +803,7 @@ impl Interpreter {
                     }
                 };
 
-                let runtime = self.runtime_config.runtime;
-
-                if matches!(runtime, crate::backends::runtime::RuntimeMode::Embedded) {
+                if self.spawns_inline() {
                     for closure_val in closures.iter() {
                         let RuntimeValue::Function(func_value) = closure_val else {
                             let stack = self.capture_stack();
@@ -701,47 +873,60 @@ impl Interpreter {
             }
 
             // ── Heap / collection operations ─────────────────────
-            BytecodeInstr::HeapAlloc { dst, type_id: _ } => {
-                let handle = self
-                    .heap
-                    .allocate(crate::backends::common::HeapValue::Tuple(Vec::new()));
+            Op::HeapAlloc => {
+                let dst = instr.a();
+                let handle =
+                    self.allocate_in(frame, crate::backends::common::HeapValue::Tuple(Vec::new()))?;
                 frame.set_register(dst.0 as usize, RuntimeValue::Tuple(handle));
                 frame.advance();
                 Ok(StepOutcome::Continue)
             }
-            BytecodeInstr::NewListWithCap { dst, capacity } => {
-                let handle = self.heap.allocate(crate::backends::common::HeapValue::List(
-                    Vec::with_capacity(*capacity as usize),
-                ));
+            Op::StackAlloc => {
+                let (dst, src) = (instr.a(), instr.b());
+                let value = frame.register(src.0 as usize);
+                if let RuntimeValue::List(handle) | RuntimeValue::Struct { fields: handle, .. } =
+                    &value
+                {
+                    // The object from this site's previous run is no longer reachable
+                    if let Some(old) = frame.replace_stack_slot(frame.ip, *handle) {
+                        if old != *handle {
+                            self.heap.deallocate(old);
+                        }
+                    }
+                }
+                frame.set_register(dst.0 as usize, value);
+                frame.advance();
+                Ok(StepOutcome::Continue)
+            }
+            Op::NewListWithCap => {
+                let (dst, capacity) = (instr.a(), instr.b);
+                let handle = self.allocate_in(
+                    frame,
+                    crate::backends::common::HeapValue::List(Vec::with_capacity(capacity as usize)),
+                )?;
                 frame.set_register(dst.0 as usize, RuntimeValue::List(handle));
                 frame.advance();
                 Ok(StepOutcome::Continue)
             }
-            BytecodeInstr::NewDict { dst, keys, values } => {
+            Op::NewDict => {
+                let dict = &code.dicts[instr.index()];
+                let (dst, keys, values) = (instr.a(), code.regs(dict.keys), code.regs(dict.values));
                 let mut map = std::collections::HashMap::new();
                 for (key_reg, val_reg) in keys.iter().zip(values.iter()) {
-                    let key = frame
-                        .registers
-                        .get(key_reg.0 as usize)
-                        .cloned()
-                        .unwrap_or(RuntimeValue::Unit);
-                    let val = frame
-                        .registers
-                        .get(val_reg.0 as usize)
-                        .cloned()
-                        .unwrap_or(RuntimeValue::Unit);
+                    let key = frame.register(key_reg.0 as usize);
+                    let val = frame.register(val_reg.0 as usize);
                     map.insert(key, val);
                 }
-                let handle = self
-                    .heap
-                    .allocate(crate::backends::common::HeapValue::Dict(map));
+                let handle =
+                    self.allocate_in(frame, crate::backends::common::HeapValue::Dict(map))?;
                 frame.set_register(dst.0 as usize, RuntimeValue::Dict(handle));
                 frame.advance();
                 Ok(StepOutcome::Continue)
             }
-            BytecodeInstr::LoadElement { dst, array, index } => {
-                let arr = self.force_register(frame, *array)?;
-                let idx_value = self.force_register(frame, *index)?;
+            Op::LoadElement => {
+                let (dst, array, index) = (instr.a(), instr.b(), instr.c());
+                let arr = self.force_register(frame, array)?;
+                let idx_value = self.force_register(frame, index)?;
 
                 match arr {
                     RuntimeValue::List(handle) => {
@@ -788,25 +973,31 @@ impl Interpreter {
                 frame.advance();
                 Ok(StepOutcome::Continue)
             }
-            BytecodeInstr::StoreElement {
-                array,
-                index,
-                value,
-            } => {
-                let arr = self.force_register(frame, *array)?;
-                let idx_value = self.force_register(frame, *index)?;
-                let val = self.force_register(frame, *value)?;
+            Op::StoreElement => {
+                let (array, index, value) = (instr.a(), instr.b(), instr.c());
+                let arr = self.force_register(frame, array)?;
+                let idx_value = self.force_register(frame, index)?;
+                let val = self.force_register(frame, value)?;
 
+                use crate::backends::common::HeapValue;
                 match arr {
                     RuntimeValue::List(handle) => {
                         let idx = idx_value.to_int().unwrap_or(0) as usize;
+                        if matches!(self.heap.get(handle), Some(HeapValue::List(items)) if idx == items.len())
+                        {
+                            self.reserve_in(
+                                frame,
+                                HeapValue::element_bytes(&val),
+                                std::slice::from_ref(&val),
+                            )?;
+                        }
                         if let Some(crate::backends::common::HeapValue::List(items)) =
                             self.heap.get_mut(handle)
                         {
                             if idx < items.len() {
-                                items[idx] = val;
+                                items[idx] = val.into();
                             } else if idx == items.len() {
-                                items.push(val);
+                                items.push(val.into());
                             }
                         }
                     }
@@ -816,11 +1007,16 @@ impl Interpreter {
                             self.heap.get_mut(handle)
                         {
                             if idx < items.len() {
-                                items[idx] = val;
+                                items[idx] = val.into();
                             }
                         }
                     }
                     RuntimeValue::Dict(handle) => {
+                        if matches!(self.heap.get(handle), Some(HeapValue::Dict(map)) if !map.contains_key(&idx_value))
+                        {
+                            let bytes = HeapValue::entry_bytes(&idx_value, &val);
+                            self.reserve_in(frame, bytes, &[idx_value.clone(), val.clone()])?;
+                        }
                         if let Some(crate::backends::common::HeapValue::Dict(map)) =
                             self.heap.get_mut(handle)
                         {
@@ -832,64 +1028,82 @@ impl Interpreter {
                 frame.advance();
                 Ok(StepOutcome::Continue)
             }
-            BytecodeInstr::GetField {
-                dst,
-                src,
-                field_idx,
-            } => {
-                let obj = self.force_register(frame, *src)?;
-                if let RuntimeValue::Struct { fields, .. } = obj {
-                    if let Some(crate::backends::common::HeapValue::Tuple(items)) =
-                        self.heap.get(fields)
+            Op::GetField => {
+                let (dst, src, offset) = (instr.a(), instr.b(), instr.c);
+                if let Some((type_id, fields)) = self.struct_in_register(frame, src)? {
+                    let slot = self
+                        .cached_field_slot(frame, type_id, |types| types.slot_at(type_id, offset));
+                    if let (Some(slot), Some(crate::backends::common::HeapValue::Tuple(items))) =
+                        (slot, self.heap.get(fields))
                     {
-                        if (*field_idx as usize) < items.len() {
-                            frame.set_register(dst.0 as usize, items[*field_idx as usize].clone());
+                        if let Some(value) = items.get(slot) {
+                            frame.set_register(dst.0 as usize, value.clone());
                         }
                     }
                 }
                 frame.advance();
                 Ok(StepOutcome::Continue)
             }
-            BytecodeInstr::SetField {
-                src,
-                field_idx,
-                value,
-            } => {
-                let obj = self.force_register(frame, *src)?;
-                let val = self.force_register(frame, *value)?;
-                if let RuntimeValue::Struct { fields, .. } = obj {
-                    if let Some(crate::backends::common::HeapValue::Tuple(items)) =
-                        self.heap.get_mut(fields)
+            Op::GetRecordField => {
+                let (dst, src, field) = (instr.a(), instr.b(), &code.names[instr.index()]);
+                let Some((type_id, fields)) = self.struct_in_register(frame, src)? else {
+                    let stack = self.capture_stack();
+                    return Err(ExecutorError::type_error(
+                        format!("cannot read field '{}' of a non-struct value", field),
+                        stack,
+                    ));
+                };
+                let value = self
+                    .cached_field_slot(frame, type_id, |types| types.field_slot(type_id, field))
+                    .and_then(|slot| match self.heap.get(fields) {
+                        Some(crate::backends::common::HeapValue::Tuple(items)) => {
+                            items.get(slot).cloned()
+                        }
+                        _ => None,
+                    });
+                let Some(value) = value else {
+                    let stack = self.capture_stack();
+                    return Err(ExecutorError::runtime(
+                        format!("struct has no field '{}'", field),
+                        stack,
+                    ));
+                };
+                frame.set_register(dst.0 as usize, value);
+                frame.advance();
+                Ok(StepOutcome::Continue)
+            }
+            Op::SetField => {
+                let (src, offset, value) = (instr.a(), instr.b, instr.c());
+                let obj = self.struct_in_register(frame, src)?;
+                let val = self.force_register(frame, value)?;
+                if let Some((type_id, fields)) = obj {
+                    let slot = self
+                        .cached_field_slot(frame, type_id, |types| types.slot_at(type_id, offset));
+                    if let (Some(slot), Some(crate::backends::common::HeapValue::Tuple(items))) =
+                        (slot, self.heap.get_mut(fields))
                     {
-                        if (*field_idx as usize) < items.len() {
-                            items[*field_idx as usize] = val;
+                        if let Some(item) = items.get_mut(slot) {
+                            *item = val.into();
                         }
                     }
                 }
                 frame.advance();
                 Ok(StepOutcome::Continue)
             }
-            BytecodeInstr::CreateStruct {
-                dst,
-                type_name,
-                fields,
-            } => {
+            Op::CreateStruct => {
+                let init = &code.structs[instr.index()];
+                let (dst, type_name, fields) = (instr.a(), &init.type_name, code.regs(init.fields));
                 let field_values: Vec<RuntimeValue> = fields
                     .iter()
-                    .map(|reg| {
-                        frame
-                            .registers
-                            .get(reg.0 as usize)
-                            .cloned()
-                            .unwrap_or(RuntimeValue::Unit)
-                    })
+                    .map(|reg| frame.register(reg.0 as usize))
                     .collect();
-                let handle = self
-                    .heap
-                    .allocate(crate::backends::common::HeapValue::Tuple(field_values));
-                let vtable = self.build_vtable(type_name);
+                let handle = self.allocate_in(
+                    frame,
+                    crate::backends::common::HeapValue::tuple(field_values),
+                )?;
+                let vtable = self.build_vtable(type_name).into();
                 let struct_val = RuntimeValue::Struct {
-                    type_id: crate::backends::common::value::TypeId(0),
+                    type_id: self.struct_types.id_of(type_name),
                     fields: handle,
                     vtable,
                 };
@@ -897,9 +1111,10 @@ impl Interpreter {
                 frame.advance();
                 Ok(StepOutcome::Continue)
             }
-            BytecodeInstr::BoundsCheck { array, index } => {
-                let arr = self.force_register(frame, *array)?;
-                let idx = self.force_register(frame, *index)?.to_int().unwrap_or(-1);
+            Op::BoundsCheck => {
+                let (array, index) = (instr.a(), instr.b());
+                let arr = self.force_register(frame, array)?;
+                let idx = self.force_register(frame, index)?.to_int().unwrap_or(-1);
                 let len = match &arr {
                     RuntimeValue::List(h) | RuntimeValue::Tuple(h) | RuntimeValue::Array(h) => {
                         match self.heap.get(*h) {
@@ -924,12 +1139,13 @@ impl Interpreter {
             }
 
             // ── String operations ────────────────────────────────
-            BytecodeInstr::StringConcat { dst, str1, str2 } => {
-                let s1: String = match self.force_register(frame, *str1)? {
+            Op::StringConcat => {
+                let (dst, str1, str2) = (instr.a(), instr.b(), instr.c());
+                let s1: String = match self.force_register(frame, str1)? {
                     RuntimeValue::String(s) => s.as_ref().to_string(),
                     _ => String::new(),
                 };
-                let s2: String = match self.force_register(frame, *str2)? {
+                let s2: String = match self.force_register(frame, str2)? {
                     RuntimeValue::String(s) => s.as_ref().to_string(),
                     _ => String::new(),
                 };
@@ -940,8 +1156,9 @@ impl Interpreter {
                 frame.advance();
                 Ok(StepOutcome::Continue)
             }
-            BytecodeInstr::StringLength { dst, src } => {
-                let s: String = match self.force_register(frame, *src)? {
+            Op::StringLength => {
+                let (dst, src) = (instr.a(), instr.b());
+                let s: String = match self.force_register(frame, src)? {
                     RuntimeValue::String(s) => s.as_ref().to_string(),
                     _ => String::new(),
                 };
@@ -949,12 +1166,13 @@ impl Interpreter {
                 frame.advance();
                 Ok(StepOutcome::Continue)
             }
-            BytecodeInstr::StringEqual { dst, str1, str2 } => {
-                let s1: String = match self.force_register(frame, *str1)? {
+            Op::StringEqual => {
+                let (dst, str1, str2) = (instr.a(), instr.b(), instr.c());
+                let s1: String = match self.force_register(frame, str1)? {
                     RuntimeValue::String(s) => s.as_ref().to_string(),
                     _ => String::new(),
                 };
-                let s2: String = match self.force_register(frame, *str2)? {
+                let s2: String = match self.force_register(frame, str2)? {
                     RuntimeValue::String(s) => s.as_ref().to_string(),
                     _ => String::new(),
                 };
@@ -965,8 +1183,9 @@ impl Interpreter {
                 frame.advance();
                 Ok(StepOutcome::Continue)
             }
-            BytecodeInstr::StringGetChar { dst, src, index } => {
-                let s: String = match self.force_register(frame, *src)? {
+            Op::StringGetChar => {
+                let (dst, src, index) = (instr.a(), instr.b(), instr.c());
+                let s: String = match self.force_register(frame, src)? {
                     RuntimeValue::String(s) => s.as_ref().to_string(),
                     _ => String::new(),
                 };
@@ -979,58 +1198,119 @@ impl Interpreter {
                 frame.advance();
                 Ok(StepOutcome::Continue)
             }
-            BytecodeInstr::StringFromInt { dst, src } => {
-                let val = self.force_register(frame, *src)?.to_int().unwrap_or(0);
+            Op::StringFromInt => {
+                let (dst, src) = (instr.a(), instr.b());
+                let val = self.force_register(frame, src)?.to_int().unwrap_or(0);
                 frame.set_register(dst.0 as usize, RuntimeValue::String(val.to_string().into()));
                 frame.advance();
                 Ok(StepOutcome::Continue)
             }
-            BytecodeInstr::StringFromFloat { dst, src } => {
-                let val = self.force_register(frame, *src)?.to_float().unwrap_or(0.0);
+            Op::StringFromFloat => {
+                let (dst, src) = (instr.a(), instr.b());
+                let val = self.force_register(frame, src)?.to_float().unwrap_or(0.0);
                 frame.set_register(dst.0 as usize, RuntimeValue::String(val.to_string().into()));
                 frame.advance();
                 Ok(StepOutcome::Continue)
             }
+            Op::StringBuilderNew => {
+                let (dst, src) = (instr.a(), instr.b());
+                let val = self.force_register(frame, src)?;
+                let handle =
+                    self.allocate_in(frame, crate::backends::common::HeapValue::list([val]))?;
+                frame.set_register(dst.0 as usize, RuntimeValue::List(handle));
+                frame.advance();
+                Ok(StepOutcome::Continue)
+            }
+            Op::StringBuilderAppend => {
+                let (dst, lhs, rhs) = (instr.a(), instr.b(), instr.c());
+                let builder = self.force_register(frame, lhs)?;
+                let val = self.force_register(frame, rhs)?;
+                match &builder {
+                    RuntimeValue::List(handle) => {
+                        self.reserve_in(
+                            frame,
+                            crate::backends::common::HeapValue::element_bytes(&val),
+                            std::slice::from_ref(&val),
+                        )?;
+                        if let Some(crate::backends::common::HeapValue::List(parts)) =
+                            self.heap.get_mut(*handle)
+                        {
+                            parts.push(val.into());
+                        }
+                    }
+                    _ => {
+                        let stack = self.capture_stack();
+                        return Err(ExecutorError::type_error(
+                            format!("expected a string builder, found {:?}", builder),
+                            stack,
+                        ));
+                    }
+                }
+                frame.set_register(dst.0 as usize, builder);
+                frame.advance();
+                Ok(StepOutcome::Continue)
+            }
+            Op::StringBuilderFinish => {
+                let (dst, src) = (instr.a(), instr.b());
+                let val = match self.force_register(frame, src)? {
+                    RuntimeValue::List(handle) => {
+                        let parts = match self.heap.get(handle) {
+                            Some(crate::backends::common::HeapValue::List(parts)) => {
+                                parts.as_slice()
+                            }
+                            _ => &[],
+                        };
+                        let len = parts
+                            .iter()
+                            .map(|part| match part.boxed_ref() {
+                                Some(RuntimeValue::String(s)) => s.len(),
+                                _ => 0,
+                            })
+                            .sum();
+                        // 字符串不在堆上，只检查放不放得下
+                        self.heap.check(len).map_err(|e| self.heap_error(e))?;
+                        let mut joined = String::with_capacity(len);
+                        for part in parts {
+                            if let Some(RuntimeValue::String(s)) = part.boxed_ref() {
+                                joined.push_str(s);
+                            }
+                        }
+                        RuntimeValue::String(joined.into())
+                    }
+                    other => other,
+                };
+                frame.set_register(dst.0 as usize, val);
+                frame.advance();
+                Ok(StepOutcome::Continue)
+            }
 
             // ── Reference counting ──────────────────────────────
-            BytecodeInstr::ArcNew { dst, src } => {
-                let val = frame
-                    .registers
-                    .get(src.0 as usize)
-                    .cloned()
-                    .unwrap_or(RuntimeValue::Unit);
+            Op::ArcNew => {
+                let (dst, src) = (instr.a(), instr.b());
+                let val = frame.register(src.0 as usize);
                 frame.set_register(dst.0 as usize, val.into_arc());
                 frame.advance();
                 Ok(StepOutcome::Continue)
             }
-            BytecodeInstr::RcNew { dst, src } => {
-                let val = frame
-                    .registers
-                    .get(src.0 as usize)
-                    .cloned()
-                    .unwrap_or(RuntimeValue::Unit);
+            Op::RcNew => {
+                let (dst, src) = (instr.a(), instr.b());
+                let val = frame.register(src.0 as usize);
                 frame.set_register(dst.0 as usize, val.into_arc());
                 frame.advance();
                 Ok(StepOutcome::Continue)
             }
-            BytecodeInstr::ArcClone { dst, src } => {
-                let val = frame
-                    .registers
-                    .get(src.0 as usize)
-                    .cloned()
-                    .unwrap_or(RuntimeValue::Unit);
+            Op::ArcClone => {
+                let (dst, src) = (instr.a(), instr.b());
+                let val = frame.register(src.0 as usize);
                 if let RuntimeValue::Arc(inner) = val {
                     frame.set_register(dst.0 as usize, RuntimeValue::Arc(inner));
                 }
                 frame.advance();
                 Ok(StepOutcome::Continue)
             }
-            BytecodeInstr::WeakNew { dst, src } => {
-                let val = frame
-                    .registers
-                    .get(src.0 as usize)
-                    .cloned()
-                    .unwrap_or(RuntimeValue::Unit);
+            Op::WeakNew => {
+                let (dst, src) = (instr.a(), instr.b());
+                let val = frame.register(src.0 as usize);
                 if let RuntimeValue::Arc(arc) = val {
                     frame.set_register(
                         dst.0 as usize,
@@ -1042,12 +1322,9 @@ impl Interpreter {
                 frame.advance();
                 Ok(StepOutcome::Continue)
             }
-            BytecodeInstr::WeakUpgrade { dst, src } => {
-                let val = frame
-                    .registers
-                    .get(src.0 as usize)
-                    .cloned()
-                    .unwrap_or(RuntimeValue::Unit);
+            Op::WeakUpgrade => {
+                let (dst, src) = (instr.a(), instr.b());
+                let val = frame.register(src.0 as usize);
                 if let RuntimeValue::Weak(weak) = val {
                     if let Some(arc) = weak.upgrade() {
                         frame.set_register(dst.0 as usize, RuntimeValue::Arc(arc));
@@ -1062,23 +1339,18 @@ impl Interpreter {
             }
 
             // ── Borrow (ZST, runtime equivalent to Mov) ─────────
-            BytecodeInstr::Borrow { dst, src, .. } => {
-                let val = frame
-                    .registers
-                    .get(src.0 as usize)
-                    .cloned()
-                    .unwrap_or(RuntimeValue::Unit);
+            Op::Borrow => {
+                let (dst, src) = (instr.a(), instr.b());
+                let val = frame.register(src.0 as usize);
                 frame.set_register(dst.0 as usize, val);
                 frame.advance();
                 Ok(StepOutcome::Continue)
             }
 
             // ── Closures ────────────────────────────────────────
-            BytecodeInstr::MakeClosure {
-                dst,
-                func: func_ref,
-                env,
-            } => {
+            Op::MakeClosure => {
+                let call = &code.calls[instr.index()];
+                let (dst, func_ref, env) = (instr.a(), &call.func, code.regs(call.args));
                 let func_id = match func_ref {
                     FunctionRef::Static { name, .. } => {
                         if let Some((idx, _)) = self
@@ -1088,9 +1360,12 @@ impl Interpreter {
                             .find(|(_, f)| f.name == *name)
                         {
                             crate::backends::common::value::FunctionId(idx as u32)
-                        } else if let Some(func) = self.functions.get(name.as_str()) {
+                        } else if let Some(func) = match self.functions.get(name.as_str()) {
+                            Some(func) => Some(func.clone()),
+                            None => self.load_lazy_function(name)?,
+                        } {
                             let idx = self.functions_by_id.len();
-                            self.functions_by_id.push(func.clone());
+                            self.functions_by_id.push(func);
                             crate::backends::common::value::FunctionId(idx as u32)
                         } else {
                             eprintln!(
@@ -1115,68 +1390,61 @@ impl Interpreter {
                 };
                 let captured_env: Vec<RuntimeValue> = env
                     .iter()
-                    .map(|r| frame.registers[r.0 as usize].clone())
+                    .map(|r| frame.registers[r.0 as usize].get())
                     .collect();
-                let closure =
-                    RuntimeValue::Function(crate::backends::common::value::FunctionValue {
+                let closure = RuntimeValue::Function(Arc::new(
+                    crate::backends::common::value::FunctionValue {
                         func_id,
                         env: captured_env,
-                    });
+                    },
+                ));
                 frame.set_register(dst.0 as usize, closure);
                 frame.advance();
                 Ok(StepOutcome::Continue)
             }
 
             // ── Type operations ──────────────────────────────────
-            BytecodeInstr::TypeOf { dst, src } => {
-                let val = self.force_register(frame, *src)?;
-                let type_name: &str = match &val {
-                    RuntimeValue::Unit => "Void",
-                    RuntimeValue::Bool(_) => "Bool",
-                    RuntimeValue::Int(_) => "Int",
-                    RuntimeValue::Float(_) => "Float",
-                    RuntimeValue::Char(_) => "Char",
-                    RuntimeValue::String(_) => "String",
-                    RuntimeValue::Bytes(_) => "Bytes",
-                    RuntimeValue::Tuple(_) => "Tuple",
-                    RuntimeValue::Array(_) => "Array",
-                    RuntimeValue::List(_) => "List",
-                    RuntimeValue::Dict(_) => "Dict",
-                    RuntimeValue::Struct { .. } => "Struct",
-                    RuntimeValue::Enum { .. } => "Enum",
-                    RuntimeValue::Function(_) => "Function",
-                    RuntimeValue::Arc(_) => "Arc",
-                    RuntimeValue::Weak(_) => "Weak",
-                    RuntimeValue::Async(_) => "Async",
-                    RuntimeValue::Ptr { .. } => "Ptr",
-                    RuntimeValue::OpaqueHandle { .. } => "OpaqueHandle",
+            Op::TypeOf => {
+                let (dst, src) = (instr.a(), instr.b());
+                let val = self.force_register(frame, src)?;
+                let ty = val.value_type(Some(&self.heap));
+                frame.set_register(dst.0 as usize, RuntimeValue::Type(Box::new(ty)));
+                frame.advance();
+                Ok(StepOutcome::Continue)
+            }
+            Op::Cast => {
+                let (dst, src, target_type_id) = (instr.a(), instr.b(), instr.c);
+                let val = self.force_register(frame, src)?;
+                let result = match NumericTarget::from_id(target_type_id) {
+                    Some(target) => Self::cast_value(val, target),
+                    None => val,
                 };
-                frame.set_register(
-                    dst.0 as usize,
-                    RuntimeValue::String(std::sync::Arc::from(type_name)),
-                );
+                frame.set_register(dst.0 as usize, result);
                 frame.advance();
                 Ok(StepOutcome::Continue)
             }
-            BytecodeInstr::Cast {
-                dst,
-                src,
-                target_type_id,
-            } => {
-                let val = self.force_register(frame, *src)?;
-                let result = match (val, *target_type_id) {
-                    (RuntimeValue::Int(n), 1) => RuntimeValue::Float(n as f64),
-                    (RuntimeValue::Float(f), 0) => RuntimeValue::Int(f as i64),
-                    (RuntimeValue::Int(n), 2) => RuntimeValue::Bool(n != 0),
-                    (RuntimeValue::Bool(b), 0) => RuntimeValue::Int(if b { 1 } else { 0 }),
-                    (v, _) => v,
+            Op::Narrow => {
+                let (dst, src, target_type_id) = (instr.a(), instr.b(), instr.c);
+                let val = self.force_register(frame, src)?;
+                let result = match NumericTarget::from_id(target_type_id) {
+                    Some(target) => self.narrow_value(val, target)?,
+                    None => val,
                 };
                 frame.set_register(dst.0 as usize, result);
                 frame.advance();
                 Ok(StepOutcome::Continue)
             }
-            BytecodeInstr::TypeCheck { value, type_id } => {
-                let val = self.force_register(frame, *value)?;
+            Op::TypeTest => {
+                let (dst, src, target) = (instr.a(), instr.b(), &code.names[instr.index()]);
+                let val = self.force_register(frame, src)?;
+                let result = self.type_test(val, target);
+                frame.set_register(dst.0 as usize, result);
+                frame.advance();
+                Ok(StepOutcome::Continue)
+            }
+            Op::TypeCheck => {
+                let (value, type_id) = (instr.a(), instr.b);
+                let val = self.force_register(frame, value)?;
                 let actual_id: u16 = match val {
                     RuntimeValue::Int(_) => 0,
                     RuntimeValue::Float(_) => 1,
@@ -1186,7 +1454,7 @@ impl Interpreter {
                     RuntimeValue::Unit => 5,
                     _ => u16::MAX,
                 };
-                if actual_id != *type_id && *type_id != u16::MAX {
+                if actual_id != type_id && type_id != u16::MAX {
                     let stack = self.capture_stack();
                     return Err(ExecutorError::runtime(
                         format!(
@@ -1201,7 +1469,7 @@ impl Interpreter {
             }
 
             // ── Error handling ───────────────────────────────────
-            BytecodeInstr::Throw { error: _ } => {
+            Op::Throw => {
                 let stack = self.capture_stack();
                 Err(ExecutorError::runtime(
                     "User thrown error".to_string(),
@@ -1289,6 +1557,12 @@ impl DebuggableExecutor for Interpreter {
         self.call_stack.last().map(|f| f.function.name.as_str())
     }
 
+    fn current_location(&self) -> Option<crate::util::span::DebugSpan> {
+        self.call_stack
+            .last()
+            .and_then(|f| f.function.line_table.lookup(f.ip))
+    }
+
     fn breakpoints(&self) -> Vec<usize> {
         self.breakpoints.keys().copied().collect()
     }
@@ -2,30 +2,50 @@
 //!
 //! This module contains the Executor trait implementation with the main bytecode execution loop.
 
-use crate::backends::{Executor, ExecutorResult, ExecutorError, ExecutionState};
+use std::sync::Arc;
+
+use crate::backends::{DispatchMode, Executor, ExecutorResult, ExecutorError, ExecutionState};
 use crate::backends::common::{RuntimeValue, Heap};
 use crate::middle::bytecode::{BytecodeModule, BytecodeFunction};
-use crate::backends::interpreter::Frame;
 use crate::backends::interpreter::frames::MAX_LOCALS;
+#[cfg(feature = "hooks")]
+use crate::backends::interpreter::hooks::VmState;
 use crate::backends::runtime::Runtime;
 use crate::backends::runtime::facade::RuntimeConfig;
 use crate::util::i18n::MSG;
 use crate::tlog;
-use super::executor::{Interpreter, SharedState};
+use super::executor::{install, Interpreter, SharedState};
+
+/// Host stack that must be left before a nested call starts a new segment
+/// (one interpreted call takes a few kilobytes of it)
+const HOST_STACK_RED_ZONE: usize = 256 * 1024;
 
 impl Executor for Interpreter {
     fn execute_module(
         &mut self,
         module: &BytecodeModule,
     ) -> ExecutorResult<()> {
+        // 新模块可能替换同名函数与虚表，先前解析的调用目标作废
+        self.inline_caches.invalidate();
+
         // Add constants
-        self.constants.extend(module.constants.clone());
+        self.add_constants(&module.constants);
 
         // Add functions
+        // 按需加载的模块中，未解码的函数只占据 functions_by_id 的下标，首次调用时再加载
+        let func_base = self.functions_by_id.len();
+        if module.lazy_functions.is_some() {
+            self.lazy_functions = module.lazy_functions.clone();
+            self.lazy_id_base = func_base;
+        }
         for func in &module.functions {
+            let func = install(func.clone());
+            self.functions_by_id.push(Arc::clone(&func));
+            if module.lazy_functions.is_some() && func.instructions.is_empty() {
+                continue;
+            }
             tlog!(debug, MSG::DebugLoadingFunction, &func.name);
-            self.functions.insert(func.name.clone(), func.clone());
-            self.functions_by_id.push(func.clone());
+            self.functions.insert(func.name.clone(), func);
         }
         tlog!(debug, MSG::DebugTotalFunctions, &self.functions.len());
         tlog!(
@@ -36,26 +56,43 @@ impl Executor for Interpreter {
 
         // Add types
         self.type_table.extend(module.type_table.clone());
+        self.vtables.extend(module.vtables.iter().cloned());
+        for layout in &module.struct_layouts {
+            self.struct_types.register(
+                layout.name.clone(),
+                layout.fields.clone(),
+                layout.offsets.clone(),
+            );
+        }
 
-        // Create shared state for parallel task execution
-        let shared = Box::new(SharedState {
-            functions: self.functions.clone(),
-            functions_by_id: self.functions_by_id.clone(),
-            constants: self.constants.clone(),
-            type_table: self.type_table.clone(),
-            ffi: self.ffi.clone(),
-        });
-        self.shared = Box::into_raw(shared);
+        self.share_state();
 
         // Execute entry point
         if let Some(entry_idx) = module.entry_point {
             if entry_idx < module.functions.len() {
-                let entry_func = &module.functions[entry_idx];
-                let result = self.execute_function(entry_func, &[])?;
-                // Print result if not unit
-                if !matches!(result, RuntimeValue::Unit) {
-                    tracing::info!("{}", result);
-                }
+                let entry_func = Arc::clone(&self.functions_by_id[func_base + entry_idx]);
+                let started = std::time::Instant::now();
+                // main 返回的 Int 或 exit(n) 决定退出码
+                let outcome = self.call_function(entry_func, &[]);
+                self.report_jit_stats(started.elapsed());
+                // 关停前转储堆（出错时保留调用栈，便于看到仍被引用的对象）
+                self.write_heap_dump_on_exit();
+                self.state.exit_code = match outcome {
+                    Ok(RuntimeValue::Int(code)) => code as i32,
+                    Ok(result) => {
+                        // Print result if not unit
+                        if !matches!(result, RuntimeValue::Unit) {
+                            tracing::info!("{}", result);
+                        }
+                        0
+                    }
+                    Err(ExecutorError::Exit(code)) => {
+                        // exit 会跳过尚未返回的帧
+                        self.call_stack.clear();
+                        code
+                    }
+                    Err(e) => return Err(e),
+                };
             }
         }
 
@@ -67,38 +104,20 @@ impl Executor for Interpreter {
         func: &BytecodeFunction,
         args: &[RuntimeValue],
     ) -> ExecutorResult<RuntimeValue> {
-        if func.local_count > MAX_LOCALS {
-            let stack = self.capture_stack();
-            return Err(ExecutorError::runtime(
-                format!(
-                    "Too many locals in function '{}': {}",
-                    func.name, func.local_count
-                ),
-                stack,
-            ));
-        }
-        // Create new frame and push onto call stack
-        let mut frame = Frame::with_args(func.clone(), args);
-        frame.set_entry_ip(0);
-        self.push_frame(frame)?;
-
-        // Execute via step_one loop — all instruction logic is in debug.rs
-        loop {
-            match self.step_one()? {
-                super::debug::StepOutcome::Continue => {}
-                super::debug::StepOutcome::Returned => {
-                    return Ok(std::mem::replace(
-                        &mut self.last_return_value,
-                        RuntimeValue::Unit,
-                    ))
-                }
-            }
+        if let Some(value) = self.enter_function(func, args)? {
+            return Ok(value);
         }
+        self.run_function(install(func.clone()), args)
     }
 
     fn reset(&mut self) {
         self.heap.clear();
+        self.globals.clear();
         self.call_stack.clear();
+        self.call_depth = 0;
+        self.fuel = self.config.fuel.unwrap_or(u64::MAX);
+        self.gc = Default::default();
+        self.jit.suspended_at = None;
         self.state = ExecutionState::default();
         self.breakpoints.clear();
         self.current_frame_info = None;
@@ -119,3 +138,145 @@ impl Executor for Interpreter {
         &self.heap
     }
 }
+
+impl Interpreter {
+    /// Publish the loaded functions, constants and types to spawned tasks
+    pub(super) fn share_state(&mut self) {
+        let shared = Box::new(SharedState {
+            functions: self.functions.clone(),
+            functions_by_id: self.functions_by_id.clone(),
+            constants: self.constants.clone(),
+            prebuilt_constants: self.prebuilt_constants.clone(),
+            type_table: self.type_table.clone(),
+            ffi: self.ffi.clone(),
+            lazy_functions: self.lazy_functions.clone(),
+            lazy_id_base: self.lazy_id_base,
+            vtables: self.vtables.clone(),
+            struct_types: self.struct_types.clone(),
+            max_heap_size: self.config.max_heap_size,
+            stdout: self.stdout.clone(),
+            resources: Arc::clone(&self.resources),
+            #[cfg(feature = "hooks")]
+            hook: self.hook.clone(),
+        });
+        self.shared = Box::into_raw(shared);
+    }
+
+    /// Call `func` without copying it (call sites that already share the function)
+    pub(super) fn call_function(
+        &mut self,
+        func: Arc<BytecodeFunction>,
+        args: &[RuntimeValue],
+    ) -> ExecutorResult<RuntimeValue> {
+        if let Some(value) = self.enter_function(&func, args)? {
+            return Ok(value);
+        }
+        // The caller's frame is off the call stack while the callee runs;
+        // an error unwinding out of the callee records it in the trace
+        let caller = self.current_frame_info.take();
+        let mut result = self.run_nested(func, args);
+        if let (Err(e), Some((name, ip))) = (&mut result, &caller) {
+            e.push_caller(crate::backends::StackFrame {
+                function_name: name.clone(),
+                ip: *ip,
+            });
+        }
+        self.current_frame_info = caller;
+        result
+    }
+
+    /// Nested calls recurse on the host stack; give them a fresh segment
+    /// when it runs low so only `max_stack_depth` bounds the recursion
+    fn run_nested(
+        &mut self,
+        func: Arc<BytecodeFunction>,
+        args: &[RuntimeValue],
+    ) -> ExecutorResult<RuntimeValue> {
+        // A green thread's stack has a fixed size and cannot grow
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(task) = crate::backends::runtime::green::current() {
+            if task.remaining_stack() < HOST_STACK_RED_ZONE {
+                let stack = self.capture_stack();
+                return Err(ExecutorError::stack_overflow(self.call_depth, stack));
+            }
+            return self.run_function(func, args);
+        }
+        stacker::maybe_grow(HOST_STACK_RED_ZONE, self.config.stack_segment_size, || {
+            self.run_function(func, args)
+        })
+    }
+
+    /// 每次调用进入函数前的检查；机器码已经完成这次调用时返回 `Some`
+    fn enter_function(
+        &mut self,
+        func: &BytecodeFunction,
+        args: &[RuntimeValue],
+    ) -> ExecutorResult<Option<RuntimeValue>> {
+        if func.local_count > MAX_LOCALS {
+            let stack = self.capture_stack();
+            return Err(ExecutorError::runtime(
+                format!(
+                    "Too many locals in function '{}': {}",
+                    func.name, func.local_count
+                ),
+                stack,
+            ));
+        }
+        if self.call_depth >= self.config.max_stack_depth {
+            let stack = self.capture_stack();
+            return Err(ExecutorError::stack_overflow(
+                self.config.max_stack_depth,
+                stack,
+            ));
+        }
+        // 函数入口是安全点（递归没有循环回边）
+        self.safepoint(None, args)?;
+
+        if let Some(value) = self.call_native_code(&func.name, args, self.call_depth + 1) {
+            return Ok(Some(value));
+        }
+        Ok(None)
+    }
+
+    /// 在新栈帧中解释执行 `func`
+    fn run_function(
+        &mut self,
+        func: Arc<BytecodeFunction>,
+        args: &[RuntimeValue],
+    ) -> ExecutorResult<RuntimeValue> {
+        #[cfg(feature = "hooks")]
+        let function = self.hook.is_some().then(|| Arc::clone(&func));
+        let mut frame = self.frame_pool.frame(func, args);
+        frame.set_entry_ip(0);
+        #[cfg(feature = "hooks")]
+        if let Some(hook) = &self.hook {
+            hook.on_enter(&VmState::new(self, &frame, self.call_depth + 1));
+        }
+        self.push_frame(frame)?;
+
+        // 指令逻辑都在 debug.rs
+        self.call_depth += 1;
+        let result = match self.config.dispatch {
+            DispatchMode::Direct => self.run_frame(),
+            DispatchMode::Step => loop {
+                match self.step_one() {
+                    Ok(super::debug::StepOutcome::Continue) => {}
+                    Ok(super::debug::StepOutcome::Returned) => {
+                        break Ok(std::mem::replace(
+                            &mut self.last_return_value,
+                            RuntimeValue::Unit,
+                        ))
+                    }
+                    Err(e) => break Err(e),
+                }
+            },
+        };
+        self.call_depth -= 1;
+        self.resume_native_code();
+        #[cfg(feature = "hooks")]
+        if let (Some(hook), Some(function)) = (&self.hook, function) {
+            hook.on_exit(&function, result.as_ref().ok());
+        }
+        result
+    }
+}
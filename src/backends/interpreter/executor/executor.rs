@@ -6,37 +6,74 @@
 use std::collections::HashMap;
 use std::fmt;
 use std::sync::Arc;
-use crate::backends::{Executor, ExecutorResult, ExecutorError, ExecutionState, ExecutorConfig};
-use crate::backends::common::{RuntimeValue, Heap, HeapValue};
+use crate::backends::{ExecutorResult, ExecutorError, ExecutionState, ExecutorConfig};
+use crate::backends::common::{RuntimeValue, Heap, HeapValue, SendValue, StructTypes, Value};
+use crate::backends::common::heap_dump::HeapDump;
 use crate::backends::common::value::{
     AsyncState, AsyncValue, FunctionValue, FunctionId, TaskId, ValueType,
 };
-use crate::middle::bytecode::{BytecodeFunction, Reg, Label, BinaryOp, CompareOp, ConstValue};
+use crate::middle::core::ir::VTable;
+use crate::middle::bytecode::{
+    BytecodeFunction, Reg, Label, BinaryOp, CompareOp, ConstValue, LazyFunctions, NumericTarget,
+};
 use crate::backends::interpreter::Frame;
+use crate::backends::interpreter::frames::FramePool;
 use crate::backends::interpreter::ffi::FfiRegistry;
+#[cfg(feature = "hooks")]
+use crate::backends::interpreter::hooks::{ExecutionHook, TaskSwitch};
+use crate::backends::interpreter::host::{self, FromValue, HostFunction, IntoArgs, IntoHostFunction};
 use crate::backends::interpreter::runtime::InterpreterRuntimeConfig;
 use crate::backends::runtime::Runtime;
 use crate::backends::runtime::facade::RuntimeConfig;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::backends::runtime::green;
 use crate::backends::runtime::engine::{
     SyncValue, TaskCancelReason, TaskMeta, TaskOutcome, TaskResult, sv,
 };
 use crate::util::i18n::MSG;
 use crate::tlog;
-use crate::std::NativeContext;
+use crate::std::{NativeContext, NativeResources};
+use super::constants::PrebuiltConst;
+use super::import::ImportedModules;
 
 /// Maximum call stack depth
 const DEFAULT_MAX_STACK_DEPTH: usize = 1024;
 
+/// Id of the task running on this thread: a green thread knows its own,
+/// other runtimes hand it over through `spawned`
+#[cfg(feature = "hooks")]
+fn running_task(spawned: &std::sync::OnceLock<TaskId>) -> Option<TaskId> {
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(green) = green::current() {
+        return Some(green.id());
+    }
+    spawned.get().copied()
+}
+
 /// Read-only shared state, shared across threads via raw pointer.
 ///
 /// Safety: `drive_until` blocks until all tasks complete, so the data outlives all tasks.
 /// Data is read-only after creation, so no data races.
 pub(super) struct SharedState {
-    pub functions: HashMap<String, BytecodeFunction>,
-    pub functions_by_id: Vec<BytecodeFunction>,
+    pub functions: HashMap<String, Arc<BytecodeFunction>>,
+    pub functions_by_id: Vec<Arc<BytecodeFunction>>,
     pub constants: Vec<ConstValue>,
+    pub prebuilt_constants: Vec<Option<PrebuiltConst>>,
     pub type_table: Vec<crate::middle::core::ir::Type>,
     pub ffi: FfiRegistry,
+    pub lazy_functions: Option<LazyFunctions>,
+    pub lazy_id_base: usize,
+    pub vtables: Vec<VTable>,
+    pub struct_types: StructTypes,
+    /// Byte limit of each task interpreter's heap
+    pub max_heap_size: usize,
+    /// Output redirect inherited by task interpreters
+    pub stdout: Option<Arc<std::sync::Mutex<dyn std::io::Write + Send>>>,
+    /// Native module state shared with task interpreters
+    pub resources: Arc<NativeResources>,
+    /// Hook inherited by task interpreters
+    #[cfg(feature = "hooks")]
+    pub hook: Option<Arc<dyn ExecutionHook>>,
 }
 
 /// Wrapper around a raw pointer to make it `Send`.
@@ -75,11 +112,29 @@ pub enum InterpreterTask {
         args: Vec<RuntimeValue>,
     },
     Dyn {
-        func: FunctionValue,
+        func: Arc<FunctionValue>,
         args: Vec<RuntimeValue>,
     },
 }
 
+/// [`InterpreterTask`] detached from the spawning interpreter's heap, so it
+/// can run on another thread with a heap of its own
+enum SendTask {
+    Static {
+        func_name: String,
+        args: Vec<SendValue>,
+    },
+    Native {
+        func_name: String,
+        args: Vec<SendValue>,
+    },
+    /// `func` is a detached `RuntimeValue::Function`
+    Dyn {
+        func: SendValue,
+        args: Vec<SendValue>,
+    },
+}
+
 /// The YaoXiang bytecode interpreter
 ///
 /// The interpreter loads bytecode modules and executes them instruction by instruction.
@@ -94,12 +149,31 @@ pub struct Interpreter {
     pub(super) call_stack: Vec<Frame>,
     /// Constant pool (shared across modules)
     pub(super) constants: Vec<ConstValue>,
-    /// Function table (name -> function)
-    pub(super) functions: HashMap<String, BytecodeFunction>,
+    /// String and composite constants built once per pool slot; `LoadConst`
+    /// clones the `Arc` of a string instead of copying the text, and copies a
+    /// prebuilt composite instead of converting it again
+    pub(super) prebuilt_constants: Vec<Option<PrebuiltConst>>,
+    /// Function table (name -> function); functions are decoded once when
+    /// loaded and shared by every call, frame and task interpreter
+    pub(super) functions: HashMap<String, Arc<BytecodeFunction>>,
     /// Function table by index (for closure calls via func_id)
-    pub(super) functions_by_id: Vec<BytecodeFunction>,
+    pub(super) functions_by_id: Vec<Arc<BytecodeFunction>>,
+    /// On-demand function source of a lazily loaded module
+    pub(super) lazy_functions: Option<LazyFunctions>,
+    /// Index in `functions_by_id` where the lazily loaded module's functions start
+    pub(super) lazy_id_base: usize,
+    /// Modules linked in by `std.module.import`
+    pub(super) imported_modules: ImportedModules,
+    /// Values kept across evaluations of a session; roots of every collection
+    pub(super) globals: HashMap<String, RuntimeValue>,
+    /// Directory that relative import paths are resolved against
+    pub(super) import_base_dir: Option<std::path::PathBuf>,
     /// Type table
     pub(super) type_table: Vec<crate::middle::core::ir::Type>,
+    /// Interface vtables (indexed by `RuntimeValue::Dyn::vtable`)
+    pub(super) vtables: Vec<VTable>,
+    /// Struct layouts (indexed by `RuntimeValue::Struct::type_id`)
+    pub(super) struct_types: StructTypes,
     /// Current execution state
     pub(super) state: ExecutionState,
     /// Configuration
@@ -108,9 +182,10 @@ pub struct Interpreter {
     pub(super) breakpoints: HashMap<usize, ()>,
     /// FFI Registry for native function calls
     pub(super) ffi: FfiRegistry,
-    /// Standard output
-    #[allow(dead_code)] // Might be unused if only accessed via write!
-    stdout: Option<std::sync::Arc<std::sync::Mutex<dyn std::io::Write + Send>>>,
+    /// Where `print` writes (`None`: the process's standard output)
+    pub(super) stdout: Option<std::sync::Arc<std::sync::Mutex<dyn std::io::Write + Send>>>,
+    /// Native module state (open connections and the like), released with the VM
+    pub(super) resources: Arc<NativeResources>,
     /// Interpreter-side runtime configuration (defaults to current behavior).
     pub(super) runtime_config: InterpreterRuntimeConfig,
     /// Runtime facade used for task scheduling (Embedded / Standard / Full).
@@ -118,6 +193,9 @@ pub struct Interpreter {
     /// Read-only shared state, shared across threads via raw pointer.
     /// Set in `execute_module`; null when not yet initialized.
     pub(super) shared: *const SharedState,
+    /// Task interpreters: the scheduling interpreter's shared state (not owned),
+    /// handed on to the tasks they spawn.
+    pub(super) parent_shared: *const SharedState,
     /// Cached stack-trace info for the frame currently being executed.
     /// Populated in `step_one` before popping the frame, so `capture_stack()`
     /// can include it even though the frame is temporarily off `call_stack`.
@@ -126,6 +204,23 @@ pub struct Interpreter {
     pub(super) called_func: bool,
     /// Return value from the last Return/ReturnValue instruction.
     pub(super) last_return_value: RuntimeValue,
+    /// Nesting depth of `execute_function` calls.
+    /// The caller's frame is off `call_stack` while a nested call runs, so the
+    /// stack depth limit is checked against this count as well.
+    pub(super) call_depth: usize,
+    /// Buffers of returned frames, reused by the next calls
+    pub(super) frame_pool: FramePool,
+    /// Field slots and call targets resolved per instruction
+    pub(super) inline_caches: super::inline_cache::InlineCaches,
+    /// Frames handed over for collections during nested calls
+    pub(super) gc: super::gc::Collector,
+    /// Instructions left in the budget (`u64::MAX` when `config.fuel` is unset)
+    pub(super) fuel: u64,
+    /// Call counts and machine code of the JIT
+    pub(super) jit: super::compiled::Jit,
+    /// Observer of execution (debuggers, profilers, coverage)
+    #[cfg(feature = "hooks")]
+    pub(super) hook: Option<Arc<dyn ExecutionHook>>,
 }
 
 impl fmt::Debug for Interpreter {
@@ -139,6 +234,9 @@ impl fmt::Debug for Interpreter {
             .field("constants", &self.constants)
             .field("functions", &self.functions)
             .field("functions_by_id", &self.functions_by_id)
+            .field("lazy_functions", &self.lazy_functions)
+            .field("imported_modules", &self.imported_modules)
+            .field("import_base_dir", &self.import_base_dir)
             .field("type_table", &self.type_table)
             .field("state", &self.state)
             .field("config", &self.config)
@@ -173,7 +271,11 @@ impl Interpreter {
     }
 
     /// Create an interpreter with custom configuration
-    pub fn with_config(config: ExecutorConfig) -> Self {
+    pub fn with_config(mut config: ExecutorConfig) -> Self {
+        // 机器码不计指令数，也不保证与解释执行相同的执行顺序
+        if config.fuel.is_some() || config.deterministic {
+            config.native_code = false;
+        }
         let runtime_config = InterpreterRuntimeConfig::default();
         let rt = Runtime::new(RuntimeConfig {
             mode: runtime_config.runtime,
@@ -181,25 +283,46 @@ impl Interpreter {
             work_stealing: runtime_config.work_stealing,
         })
         .unwrap_or_else(|_| Runtime::new(RuntimeConfig::default()).unwrap());
+        let frame_pool = FramePool::new(config.frame_pool_size);
+        let mut heap = Heap::new();
+        heap.set_limit(Some(config.max_heap_size));
 
         Self {
-            heap: Heap::new(),
+            heap,
             call_stack: Vec::with_capacity(DEFAULT_MAX_STACK_DEPTH),
             constants: Vec::new(),
+            prebuilt_constants: Vec::new(),
             functions: HashMap::new(),
             functions_by_id: Vec::new(),
+            lazy_functions: None,
+            lazy_id_base: 0,
+            imported_modules: ImportedModules::new(),
+            globals: HashMap::new(),
+            import_base_dir: None,
             type_table: Vec::new(),
+            vtables: Vec::new(),
+            struct_types: StructTypes::new(),
             state: ExecutionState::default(),
+            fuel: config.fuel.unwrap_or(u64::MAX),
             config,
             breakpoints: HashMap::new(),
             ffi: FfiRegistry::with_std(),
             stdout: None, // Default to stdout (handled by None check)
+            resources: Arc::default(),
             runtime_config,
             rt,
             shared: std::ptr::null(),
+            parent_shared: std::ptr::null(),
             current_frame_info: None,
             called_func: false,
             last_return_value: RuntimeValue::Unit,
+            call_depth: 0,
+            frame_pool,
+            inline_caches: Default::default(),
+            gc: Default::default(),
+            jit: Default::default(),
+            #[cfg(feature = "hooks")]
+            hook: None,
         }
     }
 
@@ -207,6 +330,11 @@ impl Interpreter {
         &self.runtime_config
     }
 
+    /// Instructions left in the budget set by `ExecutorConfig::fuel`
+    pub fn remaining_fuel(&self) -> Option<u64> {
+        self.config.fuel.map(|_| self.fuel)
+    }
+
     /// Create an interpreter that shares read-only state via a raw pointer.
     ///
     /// The caller must ensure that the `SharedState` outlives this interpreter.
@@ -218,52 +346,110 @@ impl Interpreter {
         // 主解释器通过 drive_until 阻塞直到所有任务完成，保证数据在任务期间有效。
         // 数据在创建后只读，无数据竞争。
         // 如果 shared 为空（例如 execute_module 未调用），使用空数据。
-        let (constants, functions, functions_by_id, type_table, ffi) = if shared.is_null() {
+        let (
+            constants,
+            prebuilt_constants,
+            functions,
+            functions_by_id,
+            type_table,
+            ffi,
+            lazy_functions,
+            lazy_id_base,
+            vtables,
+            struct_types,
+            max_heap_size,
+        ) = if shared.is_null() {
             (
+                Vec::new(),
                 Vec::new(),
                 HashMap::new(),
                 Vec::new(),
                 Vec::new(),
                 FfiRegistry::new(),
+                None,
+                0,
+                Vec::new(),
+                StructTypes::new(),
+                ExecutorConfig::default().max_heap_size,
             )
         } else {
             let shared_ref = unsafe { &*shared };
             (
                 shared_ref.constants.clone(),
+                shared_ref.prebuilt_constants.clone(),
                 shared_ref.functions.clone(),
                 shared_ref.functions_by_id.clone(),
                 shared_ref.type_table.clone(),
                 shared_ref.ffi.clone(),
+                shared_ref.lazy_functions.clone(),
+                shared_ref.lazy_id_base,
+                shared_ref.vtables.clone(),
+                shared_ref.struct_types.clone(),
+                shared_ref.max_heap_size,
             )
         };
-
+        let mut heap = Heap::new();
+        heap.set_limit(Some(max_heap_size));
         Self {
-            heap: Heap::new(),
+            heap,
             call_stack: Vec::with_capacity(DEFAULT_MAX_STACK_DEPTH),
             constants,
+            prebuilt_constants,
             functions,
             functions_by_id,
+            lazy_functions,
+            lazy_id_base,
+            imported_modules: ImportedModules::new(),
+            globals: HashMap::new(),
+            import_base_dir: None,
             type_table,
+            vtables,
+            struct_types,
             state: ExecutionState::default(),
             config: ExecutorConfig::default(),
             breakpoints: HashMap::new(),
             ffi,
-            stdout: None,
+            stdout: (!shared.is_null())
+                .then(|| unsafe { &*shared }.stdout.clone())
+                .flatten(),
+            resources: if shared.is_null() {
+                Arc::default()
+            } else {
+                Arc::clone(&unsafe { &*shared }.resources)
+            },
             runtime_config: InterpreterRuntimeConfig::default(),
             rt,
             // 不设置 shared 字段，避免 Drop 时双重释放。
             // 共享数据已拷贝到上方的字段中。
             shared: std::ptr::null(),
+            parent_shared: shared,
             current_frame_info: None,
             called_func: false,
             last_return_value: RuntimeValue::Unit,
+            call_depth: 0,
+            frame_pool: FramePool::new(ExecutorConfig::default().frame_pool_size),
+            inline_caches: Default::default(),
+            gc: Default::default(),
+            fuel: u64::MAX,
+            jit: Default::default(),
+            #[cfg(feature = "hooks")]
+            hook: (!shared.is_null())
+                .then(|| unsafe { &*shared }.hook.clone())
+                .flatten(),
         }
     }
 
+    /// Select the runtime that spawned tasks run on
+    ///
+    /// A deterministic interpreter (`ExecutorConfig::deterministic`) keeps
+    /// running spawned tasks inline whatever runtime is requested.
     pub fn set_runtime_config(
         &mut self,
-        runtime_config: InterpreterRuntimeConfig,
+        mut runtime_config: InterpreterRuntimeConfig,
     ) {
+        if self.config.deterministic {
+            runtime_config.runtime = crate::backends::runtime::RuntimeMode::Embedded;
+        }
         self.runtime_config = runtime_config;
         // Rebuild Runtime facade to match new config
         self.rt = Runtime::new(RuntimeConfig {
@@ -274,7 +460,8 @@ impl Interpreter {
         .unwrap_or_else(|_| Runtime::new(RuntimeConfig::default()).unwrap());
     }
 
-    /// Set standard output redirect
+    /// Send the output of `print` and `println` to `stdout` instead of the
+    /// process's standard output
     pub fn set_stdout(
         &mut self,
         stdout: std::sync::Arc<std::sync::Mutex<dyn std::io::Write + Send>>,
@@ -282,6 +469,24 @@ impl Interpreter {
         self.stdout = Some(stdout);
     }
 
+    /// Report execution to `hook` (see [`hooks`](crate::backends::interpreter::hooks))
+    ///
+    /// Functions run interpreted from then on, even after the hook is removed.
+    #[cfg(feature = "hooks")]
+    pub fn set_hook(
+        &mut self,
+        hook: Arc<dyn ExecutionHook>,
+    ) {
+        self.config.native_code = false;
+        self.hook = Some(hook);
+    }
+
+    /// Stop reporting execution, returning the hook
+    #[cfg(feature = "hooks")]
+    pub fn take_hook(&mut self) -> Option<Arc<dyn ExecutionHook>> {
+        self.hook.take()
+    }
+
     /// Get mutable reference to the FFI registry for registering native functions
     pub fn ffi_registry_mut(&mut self) -> &mut FfiRegistry {
         &mut self.ffi
@@ -292,6 +497,29 @@ impl Interpreter {
         &self.ffi
     }
 
+    /// Expose a host closure to scripts, bound with `Native.rs("name")`
+    ///
+    /// Overrides a standard library function of the same name.
+    pub fn register_native(
+        &mut self,
+        name: &str,
+        f: impl Fn(&[RuntimeValue]) -> ExecutorResult<RuntimeValue> + Send + Sync + 'static,
+    ) {
+        self.ffi.register_host(name, HostFunction::new(f));
+    }
+
+    /// Expose a host closure over Rust types (e.g. `|a: i64, b: f64| a as f64 * b`)
+    ///
+    /// Arguments are converted with [`FromValue`](crate::backends::interpreter::host::FromValue),
+    /// the result with [`IntoValue`](crate::backends::interpreter::host::IntoValue).
+    pub fn register_fn<Args>(
+        &mut self,
+        name: &str,
+        f: impl IntoHostFunction<Args>,
+    ) {
+        self.ffi.register_host(name, HostFunction::typed(name, f));
+    }
+
     /// Build vtable for a struct type at runtime
     ///
     /// This method looks up methods in the function table by matching the type name prefix.
@@ -303,6 +531,20 @@ impl Interpreter {
         let mut vtable = Vec::new();
         let method_prefix = format!("{}.", type_name);
 
+        // 按需加载的模块中尚未解码的方法也要进入 vtable；
+        // 加载失败的方法留到实际调用时再报告
+        if self.lazy_functions.is_some() {
+            let pending: Vec<String> = self.functions_by_id[self.lazy_id_base..]
+                .iter()
+                .filter(|f| f.name.starts_with(&method_prefix))
+                .filter(|f| !self.functions.contains_key(&f.name))
+                .map(|f| f.name.clone())
+                .collect();
+            for name in pending {
+                let _ = self.load_lazy_function(&name);
+            }
+        }
+
         // Find all functions that match the type name prefix
         for (func_name, bytecode_func) in &self.functions {
             if func_name.starts_with(&method_prefix) {
@@ -328,6 +570,56 @@ impl Interpreter {
         vtable
     }
 
+    /// Canonical representation of a value (`std.io.show`): struct field names,
+    /// cycle detection, truncation and per-type `show` overrides.
+    pub fn show_value(
+        &mut self,
+        value: &RuntimeValue,
+    ) -> ExecutorResult<String> {
+        match self.call_native_by_name("std.io.show", std::slice::from_ref(value))? {
+            RuntimeValue::String(s) => Ok(s.to_string()),
+            other => Ok(other.to_string()),
+        }
+    }
+
+    /// Type and field names of the program's structs
+    pub(crate) fn struct_types(&self) -> &StructTypes {
+        &self.struct_types
+    }
+
+    /// Snapshot of the heap object graph, rooted at the live frames and the last return value
+    pub fn heap_dump(&self) -> HeapDump {
+        let mut values = Vec::new();
+        for frame in &self.call_stack {
+            let name = frame.function_name();
+            for (i, value) in frame.registers.iter().enumerate() {
+                values.push((format!("{}:r{}", name, i), value.get()));
+            }
+            for (i, value) in frame.locals().iter().enumerate() {
+                values.push((format!("{}:local{}", name, i), value.get()));
+            }
+            for (i, value) in frame.upvalues().iter().enumerate() {
+                values.push((format!("{}:upvalue{}", name, i), value.get()));
+            }
+        }
+        values.push(("<return>".to_string(), self.last_return_value.clone()));
+        let roots: Vec<_> = values
+            .iter()
+            .map(|(label, value)| (label.clone(), value))
+            .collect();
+        HeapDump::capture(&self.heap, &self.struct_types, &roots)
+    }
+
+    /// Write the heap dump requested by `ExecutorConfig::heap_dump` (VM shutdown)
+    pub(super) fn write_heap_dump_on_exit(&self) {
+        let Some(path) = &self.config.heap_dump else {
+            return;
+        };
+        if let Err(e) = self.heap_dump().write(path) {
+            tracing::warn!("failed to write heap dump to {}: {}", path.display(), e);
+        }
+    }
+
     /// Call a YaoXiang function by its FunctionId.
     /// This is used by native functions (like map/filter/reduce) to invoke closures.
     pub fn call_function_by_id(
@@ -335,8 +627,58 @@ impl Interpreter {
         func_id: crate::backends::common::value::FunctionId,
         args: &[RuntimeValue],
     ) -> Result<RuntimeValue, ExecutorError> {
+        let func = self.function_by_id(func_id)?;
+        self.call_function(func, args)
+    }
+
+    /// Call the loaded function `name` with Rust arguments and convert its result
+    ///
+    /// `interp.call::<_, f64>("area", (2.0, 3))`: the arguments go through
+    /// [`IntoArgs`], the result through [`FromValue`]. A wrong argument count or
+    /// an argument of the wrong primitive type fails with a type error before
+    /// the function runs.
+    pub fn call<Args: IntoArgs, R: FromValue>(
+        &mut self,
+        name: &str,
+        args: Args,
+    ) -> ExecutorResult<R> {
+        let func = match self.functions.get(name) {
+            Some(func) if !func.instructions.is_empty() => Arc::clone(func),
+            _ => self.load_lazy_function(name)?.ok_or_else(|| {
+                ExecutorError::FunctionNotFound(format!("Function not found: {}", name), None)
+            })?,
+        };
+        let args = args.into_args(&mut self.heap)?;
+        if args.len() != func.params.len() {
+            return Err(ExecutorError::type_only(format!(
+                "{} expects {} argument(s), got {}",
+                name,
+                func.params.len(),
+                args.len()
+            )));
+        }
+        for (index, (param, arg)) in func.params.iter().zip(&args).enumerate() {
+            if let Some(expected) = primitive_mismatch(param, arg) {
+                let error = host::mismatch(expected, arg, &self.heap);
+                return Err(host::in_argument(name, index, error));
+            }
+        }
+        let value = self.call_function(func, &args)?;
+        R::from_value(&value, &self.heap).map_err(|e| match e {
+            ExecutorError::Type(msg, stack) => {
+                ExecutorError::Type(format!("result of {}: {}", name, msg), stack)
+            }
+            other => other,
+        })
+    }
+
+    /// The function with id `func_id`, decoding it first if it is not loaded yet
+    pub(super) fn function_by_id(
+        &mut self,
+        func_id: crate::backends::common::value::FunctionId,
+    ) -> ExecutorResult<Arc<BytecodeFunction>> {
         let idx = func_id.0 as usize;
-        if idx >= self.functions_by_id.len() {
+        let Some(func) = self.functions_by_id.get(idx) else {
             let stack = self.capture_stack();
             return Err(ExecutorError::function_not_found(
                 format!(
@@ -346,10 +688,42 @@ impl Interpreter {
                 ),
                 stack,
             ));
+        };
+        let mut func = Arc::clone(func);
+        if func.instructions.is_empty() && idx >= self.lazy_id_base {
+            if let Some(loaded) = self.load_lazy_function(&func.name)? {
+                func = loaded;
+            }
         }
-        // Clone the function to avoid borrow issues
-        let func = self.functions_by_id[idx].clone();
-        self.execute_function(&func, args)
+        Ok(func)
+    }
+
+    /// Decode a not-yet-loaded function from the lazy function source
+    /// and register it in the function tables.
+    ///
+    /// Returns `Ok(None)` when there is no lazy source or it has no such function.
+    pub(super) fn load_lazy_function(
+        &mut self,
+        name: &str,
+    ) -> ExecutorResult<Option<Arc<BytecodeFunction>>> {
+        let Some(lazy) = self.lazy_functions.clone() else {
+            return Ok(None);
+        };
+        let Some(index) = lazy.index_of(name) else {
+            return Ok(None);
+        };
+        let func = install(lazy.load(index).map_err(|e| {
+            ExecutorError::runtime(
+                format!("Failed to load function '{}': {}", name, e),
+                self.capture_stack(),
+            )
+        })?);
+        tlog!(debug, MSG::DebugLoadingFunction, &func.name);
+        self.functions.insert(func.name.clone(), func.clone());
+        if let Some(slot) = self.functions_by_id.get_mut(self.lazy_id_base + index) {
+            *slot = func.clone();
+        }
+        Ok(Some(func))
     }
 
     /// Push a frame onto the call stack
@@ -359,7 +733,10 @@ impl Interpreter {
     ) -> ExecutorResult<()> {
         if self.call_stack.len() >= self.config.max_stack_depth {
             let stack = self.capture_stack();
-            return Err(ExecutorError::stack_overflow(stack));
+            return Err(ExecutorError::stack_overflow(
+                self.config.max_stack_depth,
+                stack,
+            ));
         }
         self.call_stack.push(frame);
         Ok(())
@@ -370,6 +747,16 @@ impl Interpreter {
         self.call_stack.pop()
     }
 
+    /// Free the objects an exiting frame placed in its stack region
+    pub(super) fn release_stack_slots(
+        &mut self,
+        frame: &mut Frame,
+    ) {
+        for handle in frame.take_stack_slots() {
+            self.heap.deallocate(handle);
+        }
+    }
+
     /// Get the current frame
     pub fn current_frame(&mut self) -> Option<&mut Frame> {
         self.call_stack.last_mut()
@@ -377,10 +764,11 @@ impl Interpreter {
 
     /// Get the current function
     pub fn current_function(&self) -> Option<&BytecodeFunction> {
-        self.call_stack.last().map(|f| &f.function)
+        self.call_stack.last().map(|f| f.function.as_ref())
     }
 
     /// Capture the current call stack as a vector of StackFrame
+    #[cold]
     pub fn capture_stack(&self) -> Vec<crate::backends::StackFrame> {
         let mut stack: Vec<crate::backends::StackFrame> = self
             .call_stack
@@ -401,6 +789,60 @@ impl Interpreter {
         stack
     }
 
+    /// An allocation the heap refused, with the current stack
+    #[cold]
+    pub(super) fn heap_error(
+        &self,
+        error: crate::backends::common::heap::HeapError,
+    ) -> ExecutorError {
+        ExecutorError::from(error).with_stack(self.capture_stack())
+    }
+
+    /// 安全点：处理宿主收到的 SIGINT/SIGTERM，堆增长到阈值时回收不可达对象
+    ///
+    /// 已通过 `signal.on` 注册处理函数时，以调度任务的形式运行它并等待完成；
+    /// 否则以 `Interrupted` 展开，携带当前调用栈。`frame` 是正在执行的帧，
+    /// `args` 是正要进入的调用的参数，二者都是回收的根。
+    pub(super) fn safepoint(
+        &mut self,
+        frame: Option<&Frame>,
+        args: &[RuntimeValue],
+    ) -> ExecutorResult<()> {
+        while let Some(signal) = crate::std::signal::take_pending() {
+            let Some(handler) = crate::std::signal::handler(signal) else {
+                return Err(ExecutorError::Interrupted(
+                    signal.name().to_string(),
+                    Some(self.capture_stack()),
+                ));
+            };
+            let args = vec![RuntimeValue::String(signal.name().into())];
+            if matches!(
+                self.runtime_config.runtime,
+                crate::backends::runtime::RuntimeMode::Embedded
+            ) {
+                let mut final_args = handler.env.clone();
+                final_args.extend(args);
+                self.call_function_by_id(handler.func_id, &final_args)?;
+                continue;
+            }
+            let task_id = self.schedule_task(
+                InterpreterTask::Dyn {
+                    func: handler,
+                    args,
+                },
+                TaskMeta {
+                    deps: Vec::new(),
+                    resources: Vec::new(),
+                    label: Some(Arc::<str>::from("signal")),
+                },
+            )?;
+            let mut pending = self.make_async_pending(task_id);
+            self.force_value_in_place(&mut pending)?;
+        }
+        self.maybe_collect(frame, args);
+        Ok(())
+    }
+
     /// Resolve a label to an instruction offset
     pub fn resolve_label(
         &mut self,
@@ -410,24 +852,88 @@ impl Interpreter {
             .and_then(|f| f.function.labels.get(&label).copied())
     }
 
+    /// Append a module's constant pool, prebuilding its strings and composites
+    pub(super) fn add_constants(
+        &mut self,
+        constants: &[ConstValue],
+    ) {
+        // 对齐到常量池（直接写入 `constants` 的常量不预构建）
+        self.prebuilt_constants.resize(self.constants.len(), None);
+        self.constants.extend(constants.iter().cloned());
+        let mut strings = HashMap::new();
+        self.prebuilt_constants.extend(
+            constants
+                .iter()
+                .map(|constant| PrebuiltConst::build(constant, &mut strings)),
+        );
+    }
+
     /// Load a constant by index
     pub(super) fn load_constant(
-        &self,
+        &mut self,
         idx: u16,
     ) -> RuntimeValue {
-        self.constants
+        match self.prebuilt_constants.get(idx as usize) {
+            Some(Some(PrebuiltConst::String(s))) => return RuntimeValue::String(Arc::clone(s)),
+            Some(Some(PrebuiltConst::Composite(composite))) => {
+                let composite = Arc::clone(composite);
+                return self.instantiate_composite(&composite);
+            }
+            _ => {}
+        }
+        let constant = self
+            .constants
             .get(idx as usize)
-            .map(|c| match c {
-                ConstValue::Void => RuntimeValue::Unit,
-                ConstValue::Bool(b) => RuntimeValue::Bool(*b),
-                ConstValue::Int(i) => RuntimeValue::Int((*i) as i64),
-                ConstValue::Float(f) => RuntimeValue::Float(*f),
-                ConstValue::Char(c) => RuntimeValue::Char((*c) as u32),
-                ConstValue::String(s) => RuntimeValue::String(s.as_str().into()),
-                ConstValue::Bytes(b) => RuntimeValue::Bytes(b.as_slice().into()),
-                ConstValue::LibraryRef { .. } | ConstValue::ExternRef { .. } => todo!(),
-            })
             .expect("constant index out of bounds")
+            .clone();
+        self.materialize_constant(&constant)
+    }
+
+    /// 将常量转换为运行时值
+    ///
+    /// 只用于未预构建的常量（例如以复合值为键的字典）；复合常量每次加载
+    /// 都分配新的堆对象，因此修改加载结果不会影响常量池。
+    fn materialize_constant(
+        &mut self,
+        constant: &ConstValue,
+    ) -> RuntimeValue {
+        match constant {
+            ConstValue::Void => RuntimeValue::Unit,
+            ConstValue::Bool(b) => RuntimeValue::Bool(*b),
+            ConstValue::Int(i) => RuntimeValue::Int((*i) as i64),
+            ConstValue::Float(f) => RuntimeValue::Float(*f),
+            ConstValue::Char(c) => RuntimeValue::Char((*c) as u32),
+            ConstValue::String(s) => RuntimeValue::String(s.as_str().into()),
+            ConstValue::Bytes(b) => RuntimeValue::Bytes(b.as_slice().into()),
+            ConstValue::List(items) => {
+                let values = items
+                    .iter()
+                    .map(|item| self.materialize_constant(item).into())
+                    .collect();
+                RuntimeValue::List(self.heap.allocate(HeapValue::List(values)))
+            }
+            ConstValue::Dict(entries) => {
+                let mut map = HashMap::with_capacity(entries.len());
+                for (key, value) in entries {
+                    let key = self.materialize_constant(key);
+                    let value = self.materialize_constant(value);
+                    map.insert(key, value);
+                }
+                RuntimeValue::Dict(self.heap.allocate(HeapValue::Dict(map)))
+            }
+            ConstValue::Struct { type_name, fields } => {
+                let values = fields
+                    .iter()
+                    .map(|field| self.materialize_constant(field).into())
+                    .collect();
+                RuntimeValue::Struct {
+                    type_id: self.struct_types.id_of(type_name),
+                    fields: self.heap.allocate(HeapValue::Tuple(values)),
+                    vtable: self.build_vtable(type_name).into(),
+                }
+            }
+            ConstValue::LibraryRef { .. } | ConstValue::ExternRef { .. } => todo!(),
+        }
     }
 
     pub(super) fn make_async_pending(
@@ -461,19 +967,92 @@ impl Interpreter {
         task: InterpreterTask,
         meta: TaskMeta,
     ) -> ExecutorResult<TaskId> {
-        let sp = SendPtr(self.shared);
+        let sp = SendPtr(if self.shared.is_null() {
+            self.parent_shared
+        } else {
+            self.shared
+        });
+        let task = self.detach_task(task)?;
+        // Tasks start once the runtime drives them, after `spawn` has returned the id
+        #[cfg(feature = "hooks")]
+        let spawned = Arc::new(std::sync::OnceLock::new());
+        #[cfg(feature = "hooks")]
+        let task_id = Arc::clone(&spawned);
         let task_fn: crate::backends::runtime::TaskFn = Box::new(move |_spawn_handle| {
             let mut task_interp = Interpreter::from_shared(unsafe { sp.get() });
-            task_interp.execute_scheduled_task_from_data(task)
+            #[cfg(feature = "hooks")]
+            let switch = task_interp.hook.clone().zip(running_task(&task_id));
+            #[cfg(feature = "hooks")]
+            if let Some((hook, id)) = &switch {
+                hook.on_task_switch(TaskSwitch::Start(*id));
+            }
+            let result = task_interp.execute_scheduled_task_from_data(task);
+            #[cfg(feature = "hooks")]
+            if let Some((hook, id)) = &switch {
+                hook.on_task_switch(TaskSwitch::Finish(*id));
+            }
+            result
         });
 
+        // 绿色线程中 spawn 出的任务与当前任务同属一个运行时
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(green) = green::current() {
+            return green.spawn(meta, task_fn).map_err(|e| {
+                let stack = self.capture_stack();
+                ExecutorError::runtime(format!("{e}"), stack)
+            });
+        }
         let id = self.rt.spawn(meta, task_fn).map_err(|e| {
             let stack = self.capture_stack();
             ExecutorError::runtime(format!("{e}"), stack)
         })?;
+        #[cfg(feature = "hooks")]
+        let _ = spawned.set(id);
         Ok(id)
     }
 
+    /// `spawn` 是否就地顺序执行：Embedded 运行时且不在绿色线程中
+    pub(super) fn spawns_inline(&self) -> bool {
+        #[cfg(not(target_arch = "wasm32"))]
+        if green::current().is_some() {
+            return false;
+        }
+        matches!(
+            self.runtime_config.runtime,
+            crate::backends::runtime::RuntimeMode::Embedded
+        )
+    }
+
+    /// 等待任务结束并取回结果；绿色线程挂起自己，其他线程驱动运行时
+    fn await_task(
+        &mut self,
+        task_id: TaskId,
+    ) -> ExecutorResult<TaskOutcome> {
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(green) = green::current() {
+            return green.join(task_id).map_err(|e| {
+                let stack = self.capture_stack();
+                ExecutorError::runtime(format!("{e}"), stack)
+            });
+        }
+        self.drive_dag_until(Some(task_id))?;
+        self.rt.outcome(task_id).ok_or_else(|| {
+            let stack = self.capture_stack();
+            ExecutorError::runtime(format!("Task has no outcome: {task_id:?}"), stack)
+        })
+    }
+
+    fn task_outcome(
+        &self,
+        task_id: TaskId,
+    ) -> Option<TaskOutcome> {
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(green) = green::current() {
+            return green.outcome(task_id);
+        }
+        self.rt.outcome(task_id)
+    }
+
     pub(super) fn drive_dag_until(
         &mut self,
         target: Option<TaskId>,
@@ -484,10 +1063,62 @@ impl Interpreter {
         })
     }
 
-    pub(super) fn execute_scheduled_task_from_data(
-        &mut self,
+    /// 任务可能在其他线程上运行：参数连同它们引用的堆对象一起复制出去
+    fn detach_task(
+        &self,
         task: InterpreterTask,
+    ) -> ExecutorResult<SendTask> {
+        let detach = |value: &RuntimeValue| {
+            SendValue::detach(value, &self.heap).map_err(|e| {
+                let stack = self.capture_stack();
+                ExecutorError::type_error(e.to_string(), stack)
+            })
+        };
+        let detach_all = |args: &[RuntimeValue]| -> ExecutorResult<Vec<SendValue>> {
+            args.iter().map(detach).collect()
+        };
+        Ok(match task {
+            InterpreterTask::Static { func_name, args } => SendTask::Static {
+                args: detach_all(&args)?,
+                func_name,
+            },
+            InterpreterTask::Native { func_name, args } => SendTask::Native {
+                args: detach_all(&args)?,
+                func_name,
+            },
+            InterpreterTask::Dyn { func, args } => SendTask::Dyn {
+                func: detach(&RuntimeValue::Function(func))?,
+                args: detach_all(&args)?,
+            },
+        })
+    }
+
+    fn execute_scheduled_task_from_data(
+        &mut self,
+        task: SendTask,
     ) -> TaskResult {
+        let mut attach_all = |args: Vec<SendValue>| -> Vec<RuntimeValue> {
+            args.into_iter().map(|v| v.attach(&mut self.heap)).collect()
+        };
+        let task = match task {
+            SendTask::Static { func_name, args } => InterpreterTask::Static {
+                args: attach_all(args),
+                func_name,
+            },
+            SendTask::Native { func_name, args } => InterpreterTask::Native {
+                args: attach_all(args),
+                func_name,
+            },
+            SendTask::Dyn { func, args } => {
+                let args = attach_all(args);
+                let RuntimeValue::Function(func) = func.attach(&mut self.heap) else {
+                    return Err(sv(RuntimeValue::String(
+                        "spawned value is not a function".into(),
+                    )));
+                };
+                InterpreterTask::Dyn { func, args }
+            }
+        };
         let exec_result = match task {
             InterpreterTask::Static { func_name, args } => {
                 self.call_static_by_name(&func_name, &args)
@@ -510,7 +1141,16 @@ impl Interpreter {
         };
 
         match exec_result {
-            Ok(v) => Ok(sv(v)),
+            Ok(v) => match SendValue::detach(&v, &self.heap) {
+                Ok(v) => Ok(sv(v)),
+                Err(e) => Err(sv(RuntimeValue::String(e.to_string().into()))),
+            },
+            // 保留 exit/中断请求，等待该任务的一方据此继续展开
+            Err(
+                e @ (ExecutorError::Exit(_)
+                | ExecutorError::Interrupted(..)
+                | ExecutorError::FuelExhausted(..)),
+            ) => Err(sv(e)),
             Err(e) => Err(sv(RuntimeValue::String(format!("{e}").into()))),
         }
     }
@@ -568,7 +1208,7 @@ impl Interpreter {
         task_id: TaskId,
     ) -> String {
         let task = self.format_task_id(task_id);
-        match self.rt.outcome(task_id) {
+        match self.task_outcome(task_id) {
             Some(TaskOutcome::Err(payload)) => {
                 format!("{task}: {}", self.format_sync_value(&payload))
             }
@@ -616,6 +1256,9 @@ impl Interpreter {
         if let Some(rv) = payload.downcast_ref::<RuntimeValue>() {
             return rv.to_string();
         }
+        if let Some(v) = payload.downcast_ref::<SendValue>() {
+            return v.value().to_string();
+        }
         if let Some(s) = payload.downcast_ref::<String>() {
             return s.clone();
         }
@@ -625,6 +1268,9 @@ impl Interpreter {
         if let Some(s) = payload.downcast_ref::<&'static str>() {
             return s.to_string();
         }
+        if let Some(e) = payload.downcast_ref::<ExecutorError>() {
+            return e.to_string();
+        }
         "Unknown task payload".to_string()
     }
 
@@ -649,22 +1295,27 @@ impl Interpreter {
                 ))
             }
             AsyncState::Pending(task_id) => {
-                self.drive_dag_until(Some(*task_id))?;
-                let outcome = self.rt.outcome(*task_id).ok_or_else(|| {
-                    let stack = self.capture_stack();
-                    ExecutorError::runtime(format!("Task has no outcome: {task_id:?}"), stack)
-                })?;
-
-                match outcome {
+                let task_id = *task_id;
+                match self.await_task(task_id)? {
                     TaskOutcome::Ok(payload) => {
-                        let rv = payload
-                            .downcast_ref::<RuntimeValue>()
+                        // 结果在任务自己的堆上构造，复制到本解释器的堆
+                        *value = payload
+                            .downcast_ref::<SendValue>()
                             .cloned()
-                            .unwrap_or(RuntimeValue::Unit);
-                        *value = rv;
+                            .map_or(RuntimeValue::Unit, |v| v.attach(&mut self.heap));
                         Ok(())
                     }
                     TaskOutcome::Err(payload) => {
+                        match payload.downcast_ref::<ExecutorError>() {
+                            Some(ExecutorError::Exit(code)) => {
+                                return Err(ExecutorError::Exit(*code));
+                            }
+                            Some(
+                                e @ (ExecutorError::Interrupted(..)
+                                | ExecutorError::FuelExhausted(..)),
+                            ) => return Err(e.clone()),
+                            _ => {}
+                        }
                         let stack = self.capture_stack();
                         Err(ExecutorError::runtime(
                             self.format_sync_value(&payload),
@@ -674,7 +1325,7 @@ impl Interpreter {
                     TaskOutcome::Cancelled(reason) => {
                         let stack = self.capture_stack();
                         Err(ExecutorError::runtime(
-                            self.format_cancel_reason(*task_id, &reason),
+                            self.format_cancel_reason(task_id, &reason),
                             stack,
                         ))
                     }
@@ -688,12 +1339,16 @@ impl Interpreter {
         frame: &mut Frame,
         reg: Reg,
     ) -> ExecutorResult<RuntimeValue> {
-        if let Some(v) = frame.registers.get_mut(reg.0 as usize) {
-            self.force_value_in_place(v)?;
-            Ok(v.clone())
-        } else {
-            Ok(RuntimeValue::Unit)
+        let Some(slot) = frame.registers.get_mut(reg.0 as usize) else {
+            return Ok(RuntimeValue::Unit);
+        };
+        if !matches!(slot.boxed_ref(), Some(RuntimeValue::Async(_))) {
+            return Ok(slot.get());
         }
+        let mut value = slot.get();
+        self.force_value_in_place(&mut value)?;
+        *slot = Value::from(&value);
+        Ok(value)
     }
 
     pub(super) fn force_value_clone(
@@ -723,7 +1378,10 @@ impl Interpreter {
             if let RuntimeValue::Function(fv) = func {
                 // SAFETY: The interpreter lives as long as the callback.
                 let interpreter = unsafe { &mut *interp_ptr };
-                interpreter.call_function_by_id(fv.func_id, args)
+                // The closure's environment precedes the call arguments
+                let mut final_args = fv.env.clone();
+                final_args.extend_from_slice(args);
+                interpreter.call_function_by_id(fv.func_id, &final_args)
             } else {
                 Err(ExecutorError::type_error(
                     "Expected function value".to_string(),
@@ -731,10 +1389,26 @@ impl Interpreter {
                 ))
             }
         };
-        let mut ctx = NativeContext::with_call_fn(&mut self.heap, &mut call_fn);
-        self.ffi
-            .call(func_name, &resolved, &mut ctx)
-            .map_err(|e| e.with_stack(stack))
+        let mut import_fn = move |path: &str| -> Result<RuntimeValue, ExecutorError> {
+            // SAFETY: The interpreter lives as long as the callback.
+            let interpreter = unsafe { &mut *interp_ptr };
+            interpreter.import_module(path)
+        };
+        let mut collect_fn = move || -> usize {
+            // SAFETY: The interpreter lives as long as the callback.
+            let interpreter = unsafe { &mut *interp_ptr };
+            interpreter.collect_for_native()
+        };
+        self.gc.native_depth += 1;
+        let mut ctx = NativeContext::with_call_fn(&mut self.heap, &mut call_fn)
+            .with_import_fn(&mut import_fn)
+            .with_collect_fn(&mut collect_fn)
+            .with_struct_types(&self.struct_types)
+            .with_stdout(self.stdout.as_deref())
+            .with_resources(&self.resources);
+        let result = self.ffi.call(func_name, &resolved, &mut ctx);
+        self.gc.native_depth -= 1;
+        result.map_err(|e| e.with_stack(stack))
     }
 
     pub(super) fn call_native_with_ffi_meta(
@@ -763,7 +1437,10 @@ impl Interpreter {
             if let RuntimeValue::Function(fv) = func {
                 // SAFETY: The interpreter lives as long as the callback.
                 let interpreter = unsafe { &mut *interp_ptr };
-                interpreter.call_function_by_id(fv.func_id, args)
+                // The closure's environment precedes the call arguments
+                let mut final_args = fv.env.clone();
+                final_args.extend_from_slice(args);
+                interpreter.call_function_by_id(fv.func_id, &final_args)
             } else {
                 Err(ExecutorError::type_error(
                     "Expected function value".to_string(),
@@ -771,10 +1448,16 @@ impl Interpreter {
                 ))
             }
         };
-        let mut ctx = NativeContext::with_call_fn(&mut self.heap, &mut call_fn);
-        self.ffi
-            .call_with_mechanism(mechanism, lib, symbol, func_name, &resolved, &mut ctx)
-            .map_err(|e| e.with_stack(stack))
+        self.gc.native_depth += 1;
+        let mut ctx = NativeContext::with_call_fn(&mut self.heap, &mut call_fn)
+            .with_struct_types(&self.struct_types)
+            .with_stdout(self.stdout.as_deref())
+            .with_resources(&self.resources);
+        let result = self
+            .ffi
+            .call_with_mechanism(mechanism, lib, symbol, func_name, &resolved, &mut ctx);
+        self.gc.native_depth -= 1;
+        result.map_err(|e| e.with_stack(stack))
     }
 
     pub(super) fn call_static_by_name(
@@ -791,23 +1474,32 @@ impl Interpreter {
             return self.call_native_by_name(func_name, &resolved);
         }
 
-        let mut lookup_name = func_name.to_string();
-        if !self.functions.contains_key(func_name) {
-            let constructor_name = format!("{}_constructor", func_name);
-            if self.functions.contains_key(&constructor_name) {
-                lookup_name = constructor_name;
-            }
+        let target_func = self.lookup_static_function(func_name)?;
+        self.call_function(target_func, &resolved)
+    }
+
+    /// Resolve a statically called function by name: a loaded function, its
+    /// `_constructor`, or either of them from the lazy function source.
+    pub(super) fn lookup_static_function(
+        &mut self,
+        func_name: &str,
+    ) -> ExecutorResult<Arc<BytecodeFunction>> {
+        if let Some(func) = self.functions.get(func_name) {
+            return Ok(Arc::clone(func));
+        }
+        let constructor_name = format!("{}_constructor", func_name);
+        let mut target = self.functions.get(&constructor_name).cloned();
+        if target.is_none() {
+            target = match self.load_lazy_function(func_name)? {
+                Some(func) => Some(func),
+                None => self.load_lazy_function(&constructor_name)?,
+            };
         }
 
-        if let Some(target_func) = self.functions.get(&lookup_name).cloned() {
-            self.execute_function(&target_func, &resolved)
-        } else {
+        target.ok_or_else(|| {
             let stack = self.capture_stack();
-            Err(ExecutorError::function_not_found(
-                func_name.to_string(),
-                stack,
-            ))
-        }
+            ExecutorError::function_not_found(func_name.to_string(), stack)
+        })
     }
 
     /// Execute a binary operation
@@ -819,6 +1511,20 @@ impl Interpreter {
         op: BinaryOp,
         frame: &mut Frame,
     ) -> ExecutorResult<()> {
+        if let Some((l, r)) = small_int_operands(frame, lhs, rhs) {
+            let value = match op {
+                BinaryOp::And => l & r,
+                BinaryOp::Or => l | r,
+                BinaryOp::Xor => l ^ r,
+                BinaryOp::Div | BinaryOp::Rem if r == 0 => {
+                    let stack = self.capture_stack();
+                    return Err(ExecutorError::division_by_zero(stack));
+                }
+                _ => self.int_arith(op, l, r)?,
+            };
+            frame.set_register(dst.0 as usize, Value::int(value));
+            return Ok(());
+        }
         tlog!(
             debug,
             MSG::DebugRegisters,
@@ -841,36 +1547,26 @@ impl Interpreter {
             (BinaryOp::Add, RuntimeValue::Int(l), RuntimeValue::Int(r)) => {
                 tlog!(debug, MSG::DebugAddingNumbers, &l, &r);
                 tlog!(debug, MSG::VmI64Add, &l, &r);
-                RuntimeValue::Int(l + r)
-            }
-            (BinaryOp::Sub, RuntimeValue::Int(l), RuntimeValue::Int(r)) => RuntimeValue::Int(l - r),
-            (BinaryOp::Mul, RuntimeValue::Int(l), RuntimeValue::Int(r)) => RuntimeValue::Int(l * r),
-            (BinaryOp::Div, RuntimeValue::Int(l), RuntimeValue::Int(r)) => {
-                if r == 0 {
-                    let stack = self.capture_stack();
-                    return Err(ExecutorError::division_by_zero(stack));
-                }
-                RuntimeValue::Int(l / r)
+                RuntimeValue::Int(self.int_arith(op, l, r)?)
             }
-            (BinaryOp::Rem, RuntimeValue::Int(l), RuntimeValue::Int(r)) => {
-                if r == 0 {
-                    let stack = self.capture_stack();
-                    return Err(ExecutorError::division_by_zero(stack));
-                }
-                RuntimeValue::Int(l % r)
+            (BinaryOp::Div | BinaryOp::Rem, RuntimeValue::Int(_), RuntimeValue::Int(0)) => {
+                let stack = self.capture_stack();
+                return Err(ExecutorError::division_by_zero(stack));
             }
+            (
+                BinaryOp::Sub
+                | BinaryOp::Mul
+                | BinaryOp::Div
+                | BinaryOp::Rem
+                | BinaryOp::Shl
+                | BinaryOp::Sar
+                | BinaryOp::Shr,
+                RuntimeValue::Int(l),
+                RuntimeValue::Int(r),
+            ) => RuntimeValue::Int(self.int_arith(op, l, r)?),
             (BinaryOp::And, RuntimeValue::Int(l), RuntimeValue::Int(r)) => RuntimeValue::Int(l & r),
             (BinaryOp::Or, RuntimeValue::Int(l), RuntimeValue::Int(r)) => RuntimeValue::Int(l | r),
             (BinaryOp::Xor, RuntimeValue::Int(l), RuntimeValue::Int(r)) => RuntimeValue::Int(l ^ r),
-            (BinaryOp::Shl, RuntimeValue::Int(l), RuntimeValue::Int(r)) => {
-                RuntimeValue::Int(l << r)
-            }
-            (BinaryOp::Sar, RuntimeValue::Int(l), RuntimeValue::Int(r)) => {
-                RuntimeValue::Int(l >> r)
-            }
-            (BinaryOp::Shr, RuntimeValue::Int(l), RuntimeValue::Int(r)) => {
-                RuntimeValue::Int(l >> r)
-            }
             (BinaryOp::Add, RuntimeValue::Float(l), RuntimeValue::Float(r)) => {
                 RuntimeValue::Float(l + r)
             }
@@ -887,6 +1583,9 @@ impl Interpreter {
                 RuntimeValue::Float(l % r)
             }
             (BinaryOp::Add, RuntimeValue::String(l), RuntimeValue::String(r)) => {
+                self.heap
+                    .check(l.len() + r.len())
+                    .map_err(|e| self.heap_error(e))?;
                 let mut result = (*l).to_string();
                 result.push_str(&r);
                 RuntimeValue::String(result.into())
@@ -901,7 +1600,10 @@ impl Interpreter {
                     merged.extend(items.iter().cloned());
                 }
 
-                let handle = self.heap.allocate(HeapValue::List(merged));
+                let handle = self
+                    .heap
+                    .try_allocate(HeapValue::List(merged))
+                    .map_err(|e| self.heap_error(e))?;
                 RuntimeValue::List(handle)
             }
             _ => {
@@ -917,6 +1619,237 @@ impl Interpreter {
         Ok(())
     }
 
+    /// Execute a float operation (`F64Add`..`F64Rem`); integer operands widen to `Float`
+    pub(super) fn exec_float_op(
+        &mut self,
+        dst: Reg,
+        lhs: Reg,
+        rhs: Reg,
+        op: BinaryOp,
+        frame: &mut Frame,
+    ) -> ExecutorResult<()> {
+        let a = self.force_register(frame, lhs)?;
+        let b = self.force_register(frame, rhs)?;
+        let (Some(l), Some(r)) = (as_float(&a), as_float(&b)) else {
+            let stack = self.capture_stack();
+            return Err(ExecutorError::type_error(
+                format!("type mismatch in float operation {:?}", op),
+                stack,
+            ));
+        };
+        let value = match op {
+            BinaryOp::Add => l + r,
+            BinaryOp::Sub => l - r,
+            BinaryOp::Mul => l * r,
+            BinaryOp::Div => l / r,
+            _ => l % r,
+        };
+        frame.set_register(dst.0 as usize, RuntimeValue::Float(value));
+        Ok(())
+    }
+
+    /// Execute a float comparison (`F64Eq`..`F64Ge`); integer operands widen to `Float`
+    pub(super) fn exec_float_compare(
+        &mut self,
+        dst: Reg,
+        lhs: Reg,
+        rhs: Reg,
+        cmp: CompareOp,
+        frame: &mut Frame,
+    ) -> ExecutorResult<()> {
+        let a = self.force_register(frame, lhs)?;
+        let b = self.force_register(frame, rhs)?;
+        let (Some(l), Some(r)) = (as_float(&a), as_float(&b)) else {
+            let stack = self.capture_stack();
+            return Err(ExecutorError::type_error(
+                format!("type mismatch in float comparison {:?}", cmp),
+                stack,
+            ));
+        };
+        frame.set_register(
+            dst.0 as usize,
+            RuntimeValue::Bool(compare_floats(cmp, l, r)),
+        );
+        Ok(())
+    }
+
+    /// Execute a 32-bit integer operation (`I32Add`..`I32Shr`)
+    ///
+    /// Same result as the 64-bit operation followed by `Narrow` to `Int32`,
+    /// in a single dispatch.
+    pub(super) fn exec_int32_op(
+        &mut self,
+        dst: Reg,
+        lhs: Reg,
+        rhs: Reg,
+        op: BinaryOp,
+        frame: &mut Frame,
+    ) -> ExecutorResult<()> {
+        let (l, r) = match small_int_operands(frame, lhs, rhs) {
+            Some(operands) => operands,
+            None => {
+                let a = self.force_register(frame, lhs)?;
+                let b = self.force_register(frame, rhs)?;
+                let (RuntimeValue::Int(l), RuntimeValue::Int(r)) = (a, b) else {
+                    let stack = self.capture_stack();
+                    return Err(ExecutorError::type_error(
+                        format!("type mismatch in int32 operation {:?}", op),
+                        stack,
+                    ));
+                };
+                (l, r)
+            }
+        };
+        let value = match op {
+            BinaryOp::And => l & r,
+            BinaryOp::Or => l | r,
+            BinaryOp::Xor => l ^ r,
+            BinaryOp::Div | BinaryOp::Rem if r == 0 => {
+                let stack = self.capture_stack();
+                return Err(ExecutorError::division_by_zero(stack));
+            }
+            _ => self.int_arith(op, l, r)?,
+        };
+        let result = self.narrow_value(RuntimeValue::Int(value), NumericTarget::Int(32))?;
+        frame.set_register(dst.0 as usize, result);
+        Ok(())
+    }
+
+    /// 整数算术
+    ///
+    /// 溢出时（含 `Int.MIN / -1` 与超出位宽的移位）调试构建报运行时错误，
+    /// 发布构建按二进制补码回绕。
+    pub(super) fn int_arith(
+        &self,
+        op: BinaryOp,
+        l: i64,
+        r: i64,
+    ) -> ExecutorResult<i64> {
+        let (value, overflowed) = match op {
+            BinaryOp::Add => l.overflowing_add(r),
+            BinaryOp::Sub => l.overflowing_sub(r),
+            BinaryOp::Mul => l.overflowing_mul(r),
+            BinaryOp::Div => l.overflowing_div(r),
+            BinaryOp::Rem => l.overflowing_rem(r),
+            BinaryOp::Shl => match u32::try_from(r) {
+                Ok(shift) => l.overflowing_shl(shift),
+                Err(_) => (l.wrapping_shl(r as u32), true),
+            },
+            BinaryOp::Sar | BinaryOp::Shr => match u32::try_from(r) {
+                Ok(shift) => l.overflowing_shr(shift),
+                Err(_) => (l.wrapping_shr(r as u32), true),
+            },
+            _ => unreachable!("{:?} is not an arithmetic operation", op),
+        };
+        if overflowed && self.config.build_mode.traps_overflow() {
+            let stack = self.capture_stack();
+            let symbol = match op {
+                BinaryOp::Add => "+",
+                BinaryOp::Sub => "-",
+                BinaryOp::Mul => "*",
+                BinaryOp::Div => "/",
+                BinaryOp::Rem => "%",
+                BinaryOp::Shl => "<<",
+                _ => ">>",
+            };
+            return Err(ExecutorError::integer_overflow(
+                format!("{} {} {}", l, symbol, r),
+                stack,
+            ));
+        }
+        Ok(value)
+    }
+
+    /// `Cast`：显式转换，语义同 Rust 的 `as`（整数回绕，浮点转整数饱和）
+    pub(super) fn cast_value(
+        value: RuntimeValue,
+        target: NumericTarget,
+    ) -> RuntimeValue {
+        match (value, target) {
+            (RuntimeValue::Int(n), NumericTarget::Int(bits)) => {
+                RuntimeValue::Int(wrap_to_width(n, bits))
+            }
+            (RuntimeValue::Float(f), NumericTarget::Int(bits)) => RuntimeValue::Int(match bits {
+                8 => f as i8 as i64,
+                16 => f as i16 as i64,
+                32 => f as i32 as i64,
+                _ => f as i64,
+            }),
+            (RuntimeValue::Bool(b), NumericTarget::Int(_)) => RuntimeValue::Int(b as i64),
+            (RuntimeValue::Char(c), NumericTarget::Int(bits)) => {
+                RuntimeValue::Int(wrap_to_width(c as i64, bits))
+            }
+            (RuntimeValue::Int(n), NumericTarget::Float(bits)) => {
+                RuntimeValue::Float(round_to_width(n as f64, bits))
+            }
+            (RuntimeValue::Float(f), NumericTarget::Float(bits)) => {
+                RuntimeValue::Float(round_to_width(f, bits))
+            }
+            (RuntimeValue::Bool(b), NumericTarget::Float(_)) => {
+                RuntimeValue::Float(if b { 1.0 } else { 0.0 })
+            }
+            (RuntimeValue::Int(n), NumericTarget::Bool) => RuntimeValue::Bool(n != 0),
+            (v, _) => v,
+        }
+    }
+
+    /// `Narrow`：定宽算术结果收窄；超出位宽时调试构建报错，发布构建回绕
+    pub(super) fn narrow_value(
+        &self,
+        value: RuntimeValue,
+        target: NumericTarget,
+    ) -> ExecutorResult<RuntimeValue> {
+        match (value, target) {
+            (RuntimeValue::Int(n), NumericTarget::Int(bits)) => {
+                let wrapped = wrap_to_width(n, bits);
+                if wrapped != n && self.config.build_mode.traps_overflow() {
+                    let stack = self.capture_stack();
+                    return Err(ExecutorError::integer_overflow(
+                        format!("{} (out of range for int{})", n, bits),
+                        stack,
+                    ));
+                }
+                Ok(RuntimeValue::Int(wrapped))
+            }
+            (RuntimeValue::Float(f), NumericTarget::Float(bits)) => {
+                Ok(RuntimeValue::Float(round_to_width(f, bits)))
+            }
+            (v, _) => Ok(v),
+        }
+    }
+
+    /// `TypeTest`（`as?`）：运行时类型标签为 `target` 时原样返回，否则返回 void
+    ///
+    /// 定宽整数与 `Int` 共用运行时表示，按位宽检查取值范围。
+    pub(super) fn type_test(
+        &self,
+        value: RuntimeValue,
+        target: &str,
+    ) -> RuntimeValue {
+        let matches = match &value {
+            _ if target == "Any" => true,
+            RuntimeValue::Int(n) => match target.strip_prefix("Int") {
+                Some("") => true,
+                Some(bits) => bits
+                    .parse::<u8>()
+                    .is_ok_and(|bits| bits < 64 && wrap_to_width(*n, bits) == *n),
+                None => false,
+            },
+            RuntimeValue::Float(_) => target == "Float" || target == "Float32",
+            _ => {
+                crate::std::reflect::type_name(
+                    &value.value_type(Some(&self.heap)),
+                    Some(&self.struct_types),
+                ) == target
+            }
+        };
+        if matches {
+            value
+        } else {
+            RuntimeValue::Unit
+        }
+    }
+
     /// Execute a comparison
     pub(super) fn exec_compare(
         &mut self,
@@ -926,6 +1859,10 @@ impl Interpreter {
         cmp: CompareOp,
         frame: &mut Frame,
     ) -> ExecutorResult<()> {
+        if let Some((l, r)) = small_int_operands(frame, lhs, rhs) {
+            frame.set_register(dst.0 as usize, Value::bool(compare_ints(cmp, l, r)));
+            return Ok(());
+        }
         let a = self.force_register(frame, lhs)?;
         let b = self.force_register(frame, rhs)?;
 
@@ -949,6 +1886,14 @@ impl Interpreter {
             (CompareOp::Ge, RuntimeValue::Int(l), RuntimeValue::Int(r)) => {
                 RuntimeValue::Bool(l >= r)
             }
+            // Float comparison (an integer on either side widens to Float)
+            (_, RuntimeValue::Float(_), RuntimeValue::Float(_) | RuntimeValue::Int(_))
+            | (_, RuntimeValue::Int(_), RuntimeValue::Float(_)) => {
+                match (as_float(&a), as_float(&b)) {
+                    (Some(l), Some(r)) => RuntimeValue::Bool(compare_floats(cmp, l, r)),
+                    _ => RuntimeValue::Bool(false),
+                }
+            }
             // String comparison
             (CompareOp::Eq, RuntimeValue::String(l), RuntimeValue::String(r)) => {
                 RuntimeValue::Bool(l == r)
@@ -968,6 +1913,22 @@ impl Interpreter {
             (CompareOp::Ge, RuntimeValue::String(l), RuntimeValue::String(r)) => {
                 RuntimeValue::Bool(l >= r)
             }
+            // 类型值按结构比较（`typeof(a) == typeof(b)`）
+            (CompareOp::Eq, RuntimeValue::Type(l), RuntimeValue::Type(r)) => {
+                RuntimeValue::Bool(l == r)
+            }
+            (CompareOp::Ne, RuntimeValue::Type(l), RuntimeValue::Type(r)) => {
+                RuntimeValue::Bool(l != r)
+            }
+            // void 只等于 void（`x != void` 判空）
+            (CompareOp::Eq, RuntimeValue::Unit, other)
+            | (CompareOp::Eq, other, RuntimeValue::Unit) => {
+                RuntimeValue::Bool(matches!(other, RuntimeValue::Unit))
+            }
+            (CompareOp::Ne, RuntimeValue::Unit, other)
+            | (CompareOp::Ne, other, RuntimeValue::Unit) => {
+                RuntimeValue::Bool(!matches!(other, RuntimeValue::Unit))
+            }
             _ => RuntimeValue::Bool(false),
         };
 
@@ -985,3 +1946,116 @@ impl Drop for Interpreter {
         }
     }
 }
+
+/// 两个操作数都是内联整数时直接从寄存器读出，跳过解包与 Async 检查
+#[inline]
+fn small_int_operands(
+    frame: &Frame,
+    lhs: Reg,
+    rhs: Reg,
+) -> Option<(i64, i64)> {
+    let l = frame.registers.get(lhs.0 as usize)?.small_int()?;
+    let r = frame.registers.get(rhs.0 as usize)?.small_int()?;
+    Some((l, r))
+}
+
+/// 按位宽回绕有符号整数
+fn wrap_to_width(
+    n: i64,
+    bits: u8,
+) -> i64 {
+    match bits {
+        8 => n as i8 as i64,
+        16 => n as i16 as i64,
+        32 => n as i32 as i64,
+        _ => n,
+    }
+}
+
+/// 按位宽舍入浮点数（`Float32` 经 `f32` 往返）
+fn round_to_width(
+    f: f64,
+    bits: u8,
+) -> f64 {
+    if bits == 32 {
+        f as f32 as f64
+    } else {
+        f
+    }
+}
+
+/// 装载函数：此时把指令解码成定长形式，调用时不再解码
+pub(super) fn install(mut func: BytecodeFunction) -> Arc<BytecodeFunction> {
+    // 导入时指令可能已被重定位，先前的解码结果作废
+    func.decoded = Default::default();
+    func.decoded();
+    Arc::new(func)
+}
+
+/// 数值按浮点取值（整数拓宽），其他值为 `None`
+pub(super) fn as_float(value: &RuntimeValue) -> Option<f64> {
+    match value {
+        RuntimeValue::Float(f) => Some(*f),
+        RuntimeValue::Int(n) => Some(*n as f64),
+        _ => None,
+    }
+}
+
+/// 按 IEEE 754 比较浮点数（与 NaN 比较时只有 `!=` 成立）
+fn compare_floats(
+    cmp: CompareOp,
+    l: f64,
+    r: f64,
+) -> bool {
+    match cmp {
+        CompareOp::Eq => l == r,
+        CompareOp::Ne => l != r,
+        CompareOp::Lt => l < r,
+        CompareOp::Le => l <= r,
+        CompareOp::Gt => l > r,
+        CompareOp::Ge => l >= r,
+    }
+}
+
+fn compare_ints(
+    cmp: CompareOp,
+    l: i64,
+    r: i64,
+) -> bool {
+    match cmp {
+        CompareOp::Eq => l == r,
+        CompareOp::Ne => l != r,
+        CompareOp::Lt => l < r,
+        CompareOp::Le => l <= r,
+        CompareOp::Gt => l > r,
+        CompareOp::Ge => l >= r,
+    }
+}
+
+/// 参数声明为基本类型而实参不是该类型时，返回声明的类型名；其他类型不检查
+fn primitive_mismatch(
+    param: &crate::middle::core::ir::Type,
+    arg: &RuntimeValue,
+) -> Option<&'static str> {
+    use crate::middle::core::ir::Type;
+    let name = match param {
+        Type::Int(_) => "Int",
+        Type::Float(_) => "Float",
+        Type::Bool => "Bool",
+        Type::Char => "Char",
+        Type::String => "String",
+        Type::Name { name, .. } => name.as_str(),
+        _ => return None,
+    };
+    let (expected, ok) = match name {
+        "Int" | "Int8" | "Int16" | "Int32" | "Int64" => {
+            ("Int", matches!(arg, RuntimeValue::Int(_)))
+        }
+        "Float" | "Float32" | "Float64" => ("Float", matches!(arg, RuntimeValue::Float(_))),
+        "Bool" => ("Bool", matches!(arg, RuntimeValue::Bool(_))),
+        "Char" => ("Char", matches!(arg, RuntimeValue::Char(_))),
+        "String" => ("String", matches!(arg, RuntimeValue::String(_))),
+        _ => return None,
+    };
+    (!ok).then_some(expected)
+}
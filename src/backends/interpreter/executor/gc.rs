@@ -0,0 +1,219 @@
+//! Collection of unreachable heap objects
+//!
+//! The collector (`backends::common::gc`) runs at safepoints once the heap holds
+//! `ExecutorConfig::gc_threshold` objects, and again whenever the heap has doubled
+//! since the last collection; `gc.collect()` runs it on demand. A heap limited
+//! to `ExecutorConfig::max_heap_size` bytes is also collected once half of it
+//! is in use, so garbage alone does not exhaust it, and an instruction whose
+//! allocation would go past the limit collects before giving up.
+//!
+//! A collection must see every live value. The frame being executed is passed
+//! in; a frame waiting for a nested call hands its values to the collector for
+//! the duration of the call. Anything else that holds values on the Rust stack
+//! (a native function, a signal handler, a call that did not hand its frame
+//! over) makes the frame count disagree with the call depth, and the collection
+//! is skipped.
+
+use std::borrow::Cow;
+
+use crate::backends::common::gc;
+use crate::backends::common::{Handle, HeapValue, RuntimeValue, Value};
+use crate::backends::ExecutorResult;
+use crate::backends::interpreter::frames::FrameValues;
+use crate::backends::interpreter::Frame;
+use crate::backends::runtime::RuntimeMode;
+use crate::std::signal::{self, Signal};
+use super::executor::Interpreter;
+
+/// Collector state of one interpreter
+#[derive(Default)]
+pub(super) struct Collector {
+    /// Values of the frames waiting for a nested call, outermost first
+    suspended: Vec<FrameValues>,
+    /// Native functions currently running
+    pub(super) native_depth: usize,
+    /// Heap size that triggers the next automatic collection
+    next_collection: usize,
+    /// Live bytes that trigger the next automatic collection
+    next_bytes: usize,
+}
+
+impl Interpreter {
+    /// Run `call` with `frame`'s values visible to collections in the meantime
+    pub(super) fn with_frame_suspended<R>(
+        &mut self,
+        frame: &mut Frame,
+        call: impl FnOnce(&mut Self, &mut Frame) -> R,
+    ) -> R {
+        self.gc.suspended.push(frame.take_values());
+        let result = call(self, frame);
+        if let Some(values) = self.gc.suspended.pop() {
+            frame.restore_values(values);
+        }
+        result
+    }
+
+    /// Values of the frames waiting for a nested call, outermost first
+    pub(crate) fn suspended_frames(&self) -> &[FrameValues] {
+        &self.gc.suspended
+    }
+
+    /// Collect if the heap has grown past the trigger (safepoints)
+    ///
+    /// `frame` is the frame being executed, `args` the arguments of the call
+    /// being entered.
+    pub(super) fn maybe_collect(
+        &mut self,
+        frame: Option<&Frame>,
+        args: &[RuntimeValue],
+    ) {
+        // Values handed to the host between calls are not roots, so the
+        // outermost call leaves the heap alone on entry
+        let threshold = self.config.gc_threshold;
+        // A heap with a byte limit also collects once half of it is in use
+        let near_limit = self
+            .heap
+            .limit()
+            .is_some_and(|limit| self.heap.bytes() >= self.gc.next_bytes.max(limit / 2));
+        if threshold != 0
+            && self.call_depth > 0
+            && (self.heap.len() >= self.gc.next_collection.max(threshold) || near_limit)
+        {
+            self.collect(frame, args);
+        }
+    }
+
+    /// Allocate `value` for the instruction running in `frame`, collecting
+    /// first when it would take the heap past its limit
+    pub(super) fn allocate_in(
+        &mut self,
+        frame: &Frame,
+        value: HeapValue,
+    ) -> ExecutorResult<Handle> {
+        let bytes = value.size_bytes();
+        if self.heap.check(bytes).is_err() {
+            // The new object's elements are not in the heap yet
+            let pending: Vec<RuntimeValue> = match &value {
+                HeapValue::Tuple(items)
+                | HeapValue::Array(items)
+                | HeapValue::List(items)
+                | HeapValue::Struct(items) => items.iter().map(Value::get).collect(),
+                HeapValue::Dict(map) => map
+                    .iter()
+                    .flat_map(|(key, value)| [key.clone(), value.clone()])
+                    .collect(),
+            };
+            self.make_room(frame, bytes, &pending)?;
+        }
+        #[cfg(feature = "hooks")]
+        if let Some(hook) = &self.hook {
+            hook.on_alloc(bytes);
+        }
+        Ok(self.heap.allocate(value))
+    }
+
+    /// Count `bytes` a live object grows by when the instruction running in
+    /// `frame` stores `pending` into it, collecting first when they would take
+    /// the heap past its limit
+    pub(super) fn reserve_in(
+        &mut self,
+        frame: &Frame,
+        bytes: usize,
+        pending: &[RuntimeValue],
+    ) -> ExecutorResult<()> {
+        if self.heap.check(bytes).is_err() {
+            self.make_room(frame, bytes, pending)?;
+        }
+        self.heap.reserve(bytes).map_err(|e| self.heap_error(e))?;
+        #[cfg(feature = "hooks")]
+        if let Some(hook) = &self.hook {
+            hook.on_alloc(bytes);
+        }
+        Ok(())
+    }
+
+    /// Collect so `bytes` more fit under the heap limit, or fail with `HeapExhausted`
+    fn make_room(
+        &mut self,
+        frame: &Frame,
+        bytes: usize,
+        pending: &[RuntimeValue],
+    ) -> ExecutorResult<()> {
+        if self.config.gc_threshold != 0 {
+            self.collect(Some(frame), pending);
+        }
+        self.heap.check(bytes).map_err(|e| self.heap_error(e))
+    }
+
+    /// Collect on behalf of the native function that is running (`gc.collect()`)
+    pub(super) fn collect_for_native(&mut self) -> usize {
+        // Only the caller of `gc.collect` may be on the stack, and it is suspended
+        if self.gc.native_depth != 1 {
+            return 0;
+        }
+        self.gc.native_depth = 0;
+        let freed = self.collect(None, &[]);
+        self.gc.native_depth = 1;
+        freed.unwrap_or(0)
+    }
+
+    /// Free the heap objects no live frame reaches, returning how many were freed
+    ///
+    /// Between calls every object not held by a frame left on the call stack is
+    /// freed; values returned to the host earlier must not be used afterwards.
+    pub fn collect_garbage(&mut self) -> usize {
+        self.collect(None, &[]).unwrap_or(0)
+    }
+
+    /// Collect with the suspended frames, `frame` and `args` as roots;
+    /// `None` when some live value may be out of sight
+    fn collect(
+        &mut self,
+        frame: Option<&Frame>,
+        args: &[RuntimeValue],
+    ) -> Option<usize> {
+        // Other runtime modes move values between per-task heaps
+        let active = self.gc.suspended.len() + usize::from(frame.is_some());
+        if active != self.call_depth
+            || self.gc.native_depth != 0
+            || !matches!(self.runtime_config.runtime, RuntimeMode::Embedded)
+        {
+            return None;
+        }
+
+        let handlers: Vec<_> = Signal::ALL
+            .into_iter()
+            .filter_map(signal::handler)
+            .collect();
+        let roots = self
+            .call_stack
+            .iter()
+            .flat_map(Frame::values)
+            .chain(self.gc.suspended.iter().flat_map(FrameValues::values))
+            .chain(frame.into_iter().flat_map(Frame::values))
+            .map(Value::view)
+            .chain(
+                args.iter()
+                    .chain(std::iter::once(&self.last_return_value))
+                    .chain(self.globals.values())
+                    .chain(handlers.iter().flat_map(|handler| &handler.env))
+                    .map(Cow::Borrowed),
+            );
+        let pinned = self
+            .call_stack
+            .iter()
+            .flat_map(Frame::stack_handles)
+            .chain(
+                self.gc
+                    .suspended
+                    .iter()
+                    .flat_map(FrameValues::stack_handles),
+            )
+            .chain(frame.into_iter().flat_map(Frame::stack_handles));
+        let freed = gc::collect(&mut self.heap, roots, pinned);
+        self.gc.next_collection = self.heap.len() * 2;
+        self.gc.next_bytes = self.heap.bytes() * 2;
+        tracing::debug!("gc: freed {} objects, {} live", freed, self.heap.len());
+        Some(freed)
+    }
+}
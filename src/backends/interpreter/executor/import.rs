@@ -0,0 +1,434 @@
+//! Runtime module import for the YaoXiang interpreter
+//!
+//! `std.module.import(path)` compiles another source file and links its
+//! functions into the running interpreter. The imported functions are
+//! renamed to `<module path>::<name>` so they never clash with functions of
+//! the importing program, and the module's constant indices are rebased onto
+//! the interpreter's shared constant pool.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::backends::common::value::{FunctionId, FunctionValue};
+use crate::backends::common::{HeapValue, RuntimeValue};
+use crate::backends::{ExecutorError, ExecutorResult};
+use crate::frontend::module::loader::ModuleLoader;
+use crate::frontend::module::{ExportKind, ModuleSource};
+use crate::middle::bytecode::{BytecodeInstr, BytecodeModule, ConstValue, FunctionRef};
+use crate::tlog;
+use crate::util::i18n::MSG;
+use super::executor::{install, Interpreter};
+
+/// Exported function ids of imported modules, keyed by canonical file path
+pub(super) type ImportedModules = HashMap<PathBuf, Vec<(String, FunctionId)>>;
+
+impl Interpreter {
+    /// Set the directory that relative `import` paths are resolved against
+    pub fn set_import_base_dir(
+        &mut self,
+        dir: impl Into<PathBuf>,
+    ) {
+        self.import_base_dir = Some(dir.into());
+    }
+
+    /// Compile and link the module at `path`, returning its public namespace.
+    ///
+    /// The namespace is a dict from each `pub` function name to its function
+    /// value. A module is compiled only once; later imports of the same file
+    /// return a fresh namespace over the already linked functions.
+    pub(super) fn import_module(
+        &mut self,
+        path: &str,
+    ) -> ExecutorResult<RuntimeValue> {
+        if !self.config.capabilities.dynamic_import {
+            return Err(ExecutorError::runtime(
+                format!(
+                    "Cannot import '{path}': dynamic import is disabled by the capability policy"
+                ),
+                self.capture_stack(),
+            ));
+        }
+
+        let resolved = self.resolve_import_path(path)?;
+        let exports = match self.imported_modules.get(&resolved) {
+            Some(exports) => exports.clone(),
+            None => {
+                let exports = self.link_module_file(path, &resolved)?;
+                self.imported_modules
+                    .insert(resolved.clone(), exports.clone());
+                exports
+            }
+        };
+
+        let namespace = exports
+            .into_iter()
+            .map(|(name, func_id)| {
+                (
+                    RuntimeValue::String(name.into()),
+                    RuntimeValue::Function(Arc::new(FunctionValue {
+                        func_id,
+                        env: Vec::new(),
+                    })),
+                )
+            })
+            .collect();
+        let handle = self.heap.allocate(HeapValue::Dict(namespace));
+        Ok(RuntimeValue::Dict(handle))
+    }
+
+    /// Resolve an import path and check it against the allowed import roots
+    fn resolve_import_path(
+        &self,
+        path: &str,
+    ) -> ExecutorResult<PathBuf> {
+        let requested = Path::new(path);
+        let joined = match &self.import_base_dir {
+            Some(base) if requested.is_relative() => base.join(requested),
+            _ => requested.to_path_buf(),
+        };
+        let resolved = joined.canonicalize().map_err(|e| {
+            ExecutorError::runtime(format!("Cannot import '{path}': {e}"), self.capture_stack())
+        })?;
+
+        let roots = &self.config.capabilities.import_roots;
+        let allowed = roots.is_empty()
+            || roots.iter().any(|root| {
+                root.canonicalize()
+                    .map(|root| resolved.starts_with(root))
+                    .unwrap_or(false)
+            });
+        if !allowed {
+            return Err(ExecutorError::runtime(
+                format!("Cannot import '{path}': file is outside the allowed import roots"),
+                self.capture_stack(),
+            ));
+        }
+        Ok(resolved)
+    }
+
+    /// Compile a module file and link its functions into this interpreter.
+    ///
+    /// Returns the exported `(name, function id)` pairs.
+    fn link_module_file(
+        &mut self,
+        path: &str,
+        resolved: &Path,
+    ) -> ExecutorResult<Vec<(String, FunctionId)>> {
+        let import_error = |msg: String| {
+            ExecutorError::runtime(
+                format!("Cannot import '{path}': {msg}"),
+                self.capture_stack(),
+            )
+        };
+
+        let source = std::fs::read_to_string(resolved).map_err(|e| import_error(e.to_string()))?;
+        let module_name = resolved.display().to_string();
+
+        let tokens =
+            crate::frontend::core::tokenize(&source).map_err(|e| import_error(format!("{e:?}")))?;
+        let ast = crate::frontend::core::parser::parse(&tokens).module;
+        let info = ModuleLoader::extract_exports(&module_name, &ast, &ModuleSource::User);
+
+        let ir = crate::frontend::Compiler::new()
+            .compile_with_source(&module_name, &source)
+            .map_err(|e| import_error(e.to_string()))?;
+        let file = crate::middle::passes::codegen::CodegenContext::new(ir)
+            .generate()
+            .map_err(|e| import_error(format!("codegen failed: {e:?}")))?;
+        let module = BytecodeModule::from(file);
+
+        let exported: Vec<String> = info
+            .exports
+            .into_values()
+            .filter(|export| export.kind == ExportKind::Function && !export.name.contains('.'))
+            .map(|export| export.name)
+            .collect();
+        self.link_module(Some(&module_name), module, &exported)
+            .map_err(|msg| {
+                ExecutorError::runtime(
+                    format!("Cannot import '{module_name}': {msg}"),
+                    self.capture_stack(),
+                )
+            })
+    }
+
+    /// Append a compiled module's constants and functions to this interpreter,
+    /// returning the function ids of the `exported` names it defines
+    ///
+    /// With a `module_name` the module's functions and structs are renamed to
+    /// `<module_name>::<name>`; without one they keep their names and replace
+    /// the functions already loaded under them.
+    pub(super) fn link_module(
+        &mut self,
+        module_name: Option<&str>,
+        module: BytecodeModule,
+        exported: &[String],
+    ) -> Result<Vec<(String, FunctionId)>, String> {
+        let const_base = self.constants.len();
+        let func_base = self.functions_by_id.len();
+        let vtable_base = self.vtables.len();
+        let local_names: HashSet<String> =
+            module.functions.iter().map(|f| f.name.clone()).collect();
+        let relocator = Relocator {
+            module_name,
+            constants: &module.constants,
+            local_names: &local_names,
+            const_base,
+            func_base,
+            vtable_base,
+        };
+
+        let export_ids = exported
+            .iter()
+            .filter_map(|name| {
+                module
+                    .functions
+                    .iter()
+                    .position(|f| f.name == *name)
+                    .map(|idx| (name.clone(), FunctionId((func_base + idx) as u32)))
+            })
+            .collect();
+
+        let mut linked = Vec::with_capacity(module.functions.len());
+        for mut func in module.functions {
+            func.name = relocator.qualify(&func.name);
+            for instr in &mut func.instructions {
+                relocator.relocate(instr)?;
+            }
+            linked.push(func);
+        }
+
+        let constants: Vec<ConstValue> = module
+            .constants
+            .iter()
+            .map(|constant| relocator.relocate_const(constant))
+            .collect();
+        self.add_constants(&constants);
+        self.vtables
+            .extend(module.vtables.into_iter().map(|mut vtable| {
+                vtable.methods = vtable
+                    .methods
+                    .iter()
+                    .map(|method| relocator.qualify(method))
+                    .collect();
+                vtable
+            }));
+        // 导入模块的结构体按限定名注册；短名未被占用时也可按短名查找
+        for layout in &module.struct_layouts {
+            let qualified = relocator.qualify(&layout.name);
+            self.struct_types
+                .register(qualified, layout.fields.clone(), layout.offsets.clone());
+            if self.struct_types.id_of(&layout.name).0 == 0 {
+                self.struct_types.register(
+                    layout.name.clone(),
+                    layout.fields.clone(),
+                    layout.offsets.clone(),
+                );
+            }
+        }
+        for func in linked.into_iter().map(install) {
+            tlog!(debug, MSG::DebugLoadingFunction, &func.name);
+            self.functions.insert(func.name.clone(), func.clone());
+            self.functions_by_id.push(func);
+        }
+        // 导入可能替换同名函数，先前解析的调用目标作废
+        self.inline_caches.invalidate();
+        Ok(export_ids)
+    }
+}
+
+/// Rewrites a module's instructions for its position in the interpreter
+struct Relocator<'a> {
+    module_name: Option<&'a str>,
+    constants: &'a [ConstValue],
+    local_names: &'a HashSet<String>,
+    const_base: usize,
+    func_base: usize,
+    vtable_base: usize,
+}
+
+impl Relocator<'_> {
+    fn qualify(
+        &self,
+        name: &str,
+    ) -> String {
+        match self.module_name {
+            Some(module_name) => format!("{}::{}", module_name, name),
+            None => name.to_string(),
+        }
+    }
+
+    /// Whether `name` (or its constructor) is defined by the imported module
+    fn is_local(
+        &self,
+        name: &str,
+    ) -> bool {
+        self.local_names.contains(name) || self.local_names.contains(&format!("{name}_constructor"))
+    }
+
+    /// Whether `type_name` is a struct the imported module defines methods for
+    fn is_local_struct(
+        &self,
+        type_name: &str,
+    ) -> bool {
+        let method_prefix = format!("{type_name}.");
+        self.local_names
+            .iter()
+            .any(|name| name.starts_with(&method_prefix))
+    }
+
+    /// Struct constants name their type like `CreateStruct` does
+    fn relocate_const(
+        &self,
+        constant: &ConstValue,
+    ) -> ConstValue {
+        match constant {
+            ConstValue::List(items) => {
+                ConstValue::List(items.iter().map(|item| self.relocate_const(item)).collect())
+            }
+            ConstValue::Dict(entries) => ConstValue::Dict(
+                entries
+                    .iter()
+                    .map(|(key, value)| (self.relocate_const(key), self.relocate_const(value)))
+                    .collect(),
+            ),
+            ConstValue::Struct { type_name, fields } => ConstValue::Struct {
+                type_name: if self.is_local_struct(type_name) {
+                    self.qualify(type_name)
+                } else {
+                    type_name.clone()
+                },
+                fields: fields
+                    .iter()
+                    .map(|field| self.relocate_const(field))
+                    .collect(),
+            },
+            other => other.clone(),
+        }
+    }
+
+    fn rebase_const(
+        &self,
+        idx: &mut u16,
+    ) -> Result<(), String> {
+        *idx = u16::try_from(self.const_base + *idx as usize)
+            .map_err(|_| "constant pool overflow".to_string())?;
+        Ok(())
+    }
+
+    fn relocate(
+        &self,
+        instr: &mut BytecodeInstr,
+    ) -> Result<(), String> {
+        match instr {
+            BytecodeInstr::LoadConst { const_idx, .. } => self.rebase_const(const_idx)?,
+            BytecodeInstr::CallVirt { method_idx, .. } => self.rebase_const(method_idx)?,
+            BytecodeInstr::CallDyn { name_idx, .. } => self.rebase_const(name_idx)?,
+            BytecodeInstr::InvokeVirtual { name_idx, .. } => self.rebase_const(name_idx)?,
+            BytecodeInstr::MakeDyn { vtable, .. } => *vtable += self.vtable_base as u32,
+            BytecodeInstr::CallStatic { func, .. } | BytecodeInstr::TailCall { func, .. } => {
+                if let FunctionRef::Index(idx) = func {
+                    // 调用模块自身的函数时改用限定名，其它（标准库等）保持全局名称
+                    let name = match self.constants.get(*idx as usize) {
+                        Some(ConstValue::String(name)) => name.clone(),
+                        _ => format!("fn_{}", idx),
+                    };
+                    *func = FunctionRef::Static {
+                        module: self.module_name.unwrap_or_default().to_string(),
+                        name: if self.is_local(&name) {
+                            self.qualify(&name)
+                        } else {
+                            name
+                        },
+                    };
+                }
+            }
+            BytecodeInstr::MakeClosure { func, .. } => match func {
+                FunctionRef::Index(idx) => *idx += self.func_base as u32,
+                FunctionRef::Static { name, .. } => {
+                    if self.is_local(name) {
+                        *name = self.qualify(name);
+                    }
+                }
+            },
+            BytecodeInstr::CreateStruct { type_name, .. } => {
+                if self.is_local_struct(type_name) {
+                    *type_name = self.qualify(type_name);
+                }
+            }
+            // `as?` 的目标须与上面 CreateStruct 的限定名一致
+            BytecodeInstr::TypeTest { target, .. } => {
+                if self.is_local_struct(target) {
+                    *target = self.qualify(target);
+                }
+            }
+            // 以下指令不引用常量池、函数表、虚表或模块内的类型名。
+            // 这里逐一列出而不用 `_`：新增指令时编译器会要求决定它是否需要重定位。
+            BytecodeInstr::Nop
+            | BytecodeInstr::Return
+            | BytecodeInstr::ReturnValue { .. }
+            | BytecodeInstr::Yield
+            | BytecodeInstr::Spawn { .. }
+            | BytecodeInstr::SpawnFromList { .. }
+            | BytecodeInstr::Jmp { .. }
+            | BytecodeInstr::JmpIf { .. }
+            | BytecodeInstr::JmpIfNot { .. }
+            | BytecodeInstr::Switch { .. }
+            | BytecodeInstr::TableSwitch { .. }
+            | BytecodeInstr::Mov { .. }
+            | BytecodeInstr::LoadLocal { .. }
+            | BytecodeInstr::StoreLocal { .. }
+            | BytecodeInstr::LoadArg { .. }
+            | BytecodeInstr::BinaryOp { .. }
+            | BytecodeInstr::UnaryOp { .. }
+            | BytecodeInstr::Int32Op { .. }
+            | BytecodeInstr::Int32Neg { .. }
+            | BytecodeInstr::Compare { .. }
+            | BytecodeInstr::FloatOp { .. }
+            | BytecodeInstr::FloatNeg { .. }
+            | BytecodeInstr::FloatCompare { .. }
+            | BytecodeInstr::IntToFloat { .. }
+            | BytecodeInstr::StackAlloc { .. }
+            | BytecodeInstr::HeapAlloc { .. }
+            | BytecodeInstr::Drop { .. }
+            | BytecodeInstr::GetField { .. }
+            | BytecodeInstr::SetField { .. }
+            | BytecodeInstr::LoadElement { .. }
+            | BytecodeInstr::StoreElement { .. }
+            | BytecodeInstr::NewListWithCap { .. }
+            | BytecodeInstr::NewDict { .. }
+            | BytecodeInstr::ArcNew { .. }
+            | BytecodeInstr::RcNew { .. }
+            | BytecodeInstr::ArcClone { .. }
+            | BytecodeInstr::ArcDrop { .. }
+            | BytecodeInstr::WeakNew { .. }
+            | BytecodeInstr::WeakUpgrade { .. }
+            | BytecodeInstr::Borrow { .. }
+            | BytecodeInstr::Release { .. }
+            | BytecodeInstr::CallNative { .. }
+            | BytecodeInstr::LoadUpvalue { .. }
+            | BytecodeInstr::StoreUpvalue { .. }
+            | BytecodeInstr::CloseUpvalue { .. }
+            | BytecodeInstr::StringLength { .. }
+            | BytecodeInstr::StringConcat { .. }
+            | BytecodeInstr::StringEqual { .. }
+            | BytecodeInstr::StringGetChar { .. }
+            | BytecodeInstr::StringFromInt { .. }
+            | BytecodeInstr::StringFromFloat { .. }
+            | BytecodeInstr::StringBuilderNew { .. }
+            | BytecodeInstr::StringBuilderAppend { .. }
+            | BytecodeInstr::StringBuilderFinish { .. }
+            | BytecodeInstr::TryBegin { .. }
+            | BytecodeInstr::TryEnd
+            | BytecodeInstr::Throw { .. }
+            | BytecodeInstr::BoundsCheck { .. }
+            | BytecodeInstr::TypeCheck { .. }
+            | BytecodeInstr::Cast { .. }
+            | BytecodeInstr::Narrow { .. }
+            | BytecodeInstr::TypeOf { .. }
+            | BytecodeInstr::GetRecordField { .. } => {}
+        }
+        Ok(())
+    }
+}
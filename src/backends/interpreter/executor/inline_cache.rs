@@ -0,0 +1,247 @@
+//! Inline caches for field access and virtual calls
+//!
+//! Every field read or write remembers the slot it found for the last struct type
+//! it saw, and every virtual call the function its last receiver resolved to.
+//! Caches belong to an instruction of a function (a call site); a frame finds its
+//! function's table the first time it needs one. Field entries record the
+//! generation of the struct registry they were filled in, call entries the epoch;
+//! registering a layout starts a new generation and loading or importing a module
+//! a new epoch, so stale entries just miss.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::backends::common::value::{FunctionId, TypeId};
+use crate::backends::common::{Handle, RuntimeValue, StructTypes};
+use crate::backends::interpreter::Frame;
+use crate::backends::{ExecutorError, ExecutorResult};
+use crate::middle::bytecode::{BytecodeFunction, Reg};
+use super::executor::Interpreter;
+
+/// Inline cache tables of one interpreter
+#[derive(Default)]
+pub(super) struct InlineCaches {
+    /// Table index per function name
+    tables: HashMap<String, usize>,
+    /// One entry per instruction of each function
+    sites: Vec<Vec<Site>>,
+    epoch: u64,
+    pub(super) hits: u64,
+    pub(super) misses: u64,
+}
+
+/// What a call site resolved last time
+#[derive(Clone, Default)]
+enum Site {
+    #[default]
+    Empty,
+    Field {
+        generation: u64,
+        type_id: TypeId,
+        slot: usize,
+    },
+    Call {
+        epoch: u64,
+        receiver: Receiver,
+        target: Arc<BytecodeFunction>,
+    },
+}
+
+/// What a virtual call dispatched on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Receiver {
+    /// An existential value, by vtable index
+    Vtable(u32),
+    /// A struct method, by the function it names
+    Method(FunctionId),
+}
+
+impl InlineCaches {
+    /// Forget the call targets resolved so far (functions or vtables changed)
+    pub(super) fn invalidate(&mut self) {
+        self.epoch += 1;
+    }
+}
+
+impl Interpreter {
+    /// Cache entry of the instruction `frame` is executing
+    fn inline_cache_site(
+        &mut self,
+        frame: &mut Frame,
+    ) -> Option<&mut Site> {
+        if !self.config.inline_cache {
+            return None;
+        }
+        let caches = &mut self.inline_caches;
+        let table = match frame.inline_cache {
+            Some(table) => table,
+            None => {
+                let next = caches.sites.len();
+                let table = *caches
+                    .tables
+                    .entry(frame.function.name.clone())
+                    .or_insert(next);
+                if table == next {
+                    caches.sites.push(Vec::new());
+                }
+                frame.inline_cache = Some(table);
+                table
+            }
+        };
+        let sites = &mut caches.sites[table];
+        if sites.len() < frame.function.instructions.len() {
+            sites.resize(frame.function.instructions.len(), Site::Empty);
+        }
+        sites.get_mut(frame.ip)
+    }
+
+    /// Type and field storage of the struct in `reg`, without copying the struct
+    pub(super) fn struct_in_register(
+        &mut self,
+        frame: &mut Frame,
+        reg: Reg,
+    ) -> ExecutorResult<Option<(TypeId, Handle)>> {
+        let Some(value) = frame.registers.get(reg.0 as usize) else {
+            return Ok(None);
+        };
+        if let Some(RuntimeValue::Struct {
+            type_id, fields, ..
+        }) = value.boxed_ref()
+        {
+            return Ok(Some((*type_id, *fields)));
+        }
+        Ok(match self.force_register(frame, reg)? {
+            RuntimeValue::Struct {
+                type_id, fields, ..
+            } => Some((type_id, fields)),
+            _ => None,
+        })
+    }
+
+    /// Slot of the field the current instruction accesses on a `type_id` struct
+    ///
+    /// `resolve` looks the slot up when the cache misses.
+    pub(super) fn cached_field_slot(
+        &mut self,
+        frame: &mut Frame,
+        type_id: TypeId,
+        resolve: impl FnOnce(&StructTypes) -> Option<usize>,
+    ) -> Option<usize> {
+        let generation = self.struct_types.generation();
+        if let Some(Site::Field {
+            generation: g,
+            type_id: t,
+            slot,
+        }) = self.inline_cache_site(frame)
+        {
+            if *g == generation && *t == type_id {
+                let slot = *slot;
+                self.inline_caches.hits += 1;
+                return Some(slot);
+            }
+        }
+        let slot = resolve(&self.struct_types)?;
+        self.inline_caches.misses += 1;
+        if let Some(site) = self.inline_cache_site(frame) {
+            *site = Site::Field {
+                generation,
+                type_id,
+                slot,
+            };
+        }
+        Some(slot)
+    }
+
+    /// Function the current virtual call resolved to for `receiver` last time
+    fn cached_call_target(
+        &mut self,
+        frame: &mut Frame,
+        receiver: Receiver,
+    ) -> Option<Arc<BytecodeFunction>> {
+        let epoch = self.inline_caches.epoch;
+        match self.inline_cache_site(frame) {
+            Some(Site::Call {
+                epoch: e,
+                receiver: r,
+                target,
+            }) if *e == epoch && *r == receiver => {
+                let target = target.clone();
+                self.inline_caches.hits += 1;
+                Some(target)
+            }
+            _ => {
+                self.inline_caches.misses += 1;
+                None
+            }
+        }
+    }
+
+    /// Call the method `func_id` of a struct, through the call site's cache
+    pub(super) fn call_method_cached(
+        &mut self,
+        frame: &mut Frame,
+        func_id: FunctionId,
+        args: &[RuntimeValue],
+    ) -> ExecutorResult<RuntimeValue> {
+        let receiver = Receiver::Method(func_id);
+        let target = match self.cached_call_target(frame, receiver) {
+            Some(target) => target,
+            None => {
+                let target = self.function_by_id(func_id)?;
+                self.cache_call_target(frame, receiver, target.clone());
+                target
+            }
+        };
+        self.call_function(target, args)
+    }
+
+    /// Call slot `slot` of vtable `vtable` with `args` (receiver first), through
+    /// the call site's cache
+    pub(super) fn call_vtable_slot_cached(
+        &mut self,
+        frame: &mut Frame,
+        vtable: u32,
+        slot: u16,
+        args: &[RuntimeValue],
+    ) -> ExecutorResult<RuntimeValue> {
+        let receiver = Receiver::Vtable(vtable);
+        if let Some(target) = self.cached_call_target(frame, receiver) {
+            return self.call_function(target, args);
+        }
+        let method = self
+            .vtables
+            .get(vtable as usize)
+            .and_then(|table| table.methods.get(slot as usize))
+            .cloned()
+            .ok_or_else(|| {
+                ExecutorError::function_not_found(
+                    format!("Vtable {} has no slot {}", vtable, slot),
+                    self.capture_stack(),
+                )
+            })?;
+        // Host functions are looked up by name on every call
+        if self.ffi.has(&method) {
+            return self.call_static_by_name(&method, args);
+        }
+        let target = self.lookup_static_function(&method)?;
+        self.cache_call_target(frame, receiver, target.clone());
+        self.call_function(target, args)
+    }
+
+    /// Remember that the current virtual call resolved `receiver` to `target`
+    fn cache_call_target(
+        &mut self,
+        frame: &mut Frame,
+        receiver: Receiver,
+        target: Arc<BytecodeFunction>,
+    ) {
+        let epoch = self.inline_caches.epoch;
+        if let Some(site) = self.inline_cache_site(frame) {
+            *site = Site::Call {
+                epoch,
+                receiver,
+                target,
+            };
+        }
+    }
+}
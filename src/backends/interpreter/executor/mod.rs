@@ -5,13 +5,26 @@
 //! This module provides the main interpreter implementation split into:
 //! - `executor.rs`: Interpreter struct and core functionality
 //! - `execute.rs`: Executor trait implementation with bytecode execution
+//! - `constants.rs`: Constant pool slots prebuilt for `LoadConst`
 //! - `debug.rs`: DebuggableExecutor trait and tests
+//! - `import.rs`: Runtime module import (`std.module.import`)
+//! - `inline_cache.rs`: Inline caches for field access and virtual calls
+//! - `gc.rs`: Collection of unreachable heap objects
+//! - `session.rs`: Modules and globals kept across evaluations
+//! - `compiled.rs`: Tiered JIT (template code, then Cranelift with the `native` feature)
 
+mod compiled;
+mod constants;
 mod debug;
 mod execute;
 mod executor;
+mod gc;
+mod import;
+mod inline_cache;
+mod session;
 
 #[cfg(test)]
 mod tests;
 
 pub use executor::Interpreter;
+pub use compiled::JitStats;
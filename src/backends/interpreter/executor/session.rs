@@ -0,0 +1,62 @@
+//! State kept across evaluations
+//!
+//! A REPL or an embedder evaluates one input at a time against the same
+//! interpreter. [`Interpreter::load_module`] links each compiled input into
+//! the running interpreter without executing it: its functions join (or
+//! replace) the ones already loaded, its constants and vtables are appended,
+//! and its struct layouts are registered. Globals set with
+//! [`Interpreter::set_global`] keep their values between evaluations and are
+//! roots of every collection, so heap objects they reach stay alive.
+
+use crate::backends::common::RuntimeValue;
+use crate::backends::{ExecutorError, ExecutorResult};
+use crate::middle::bytecode::BytecodeModule;
+use super::executor::Interpreter;
+
+impl Interpreter {
+    /// Link `module` into the running interpreter without executing it
+    ///
+    /// Functions keep their names; a function with the name of one loaded
+    /// earlier replaces it for all later calls.
+    pub fn load_module(
+        &mut self,
+        module: BytecodeModule,
+    ) -> ExecutorResult<()> {
+        self.link_module(None, module, &[])
+            .map_err(|msg| ExecutorError::runtime_only(format!("Cannot load module: {msg}")))?;
+        self.share_state();
+        Ok(())
+    }
+
+    /// Keep `value` under `name` across evaluations
+    pub fn set_global(
+        &mut self,
+        name: impl Into<String>,
+        value: RuntimeValue,
+    ) {
+        self.globals.insert(name.into(), value);
+    }
+
+    /// The global named `name`
+    pub fn global(
+        &self,
+        name: &str,
+    ) -> Option<&RuntimeValue> {
+        self.globals.get(name)
+    }
+
+    /// Stop keeping the global named `name`, returning its value
+    pub fn remove_global(
+        &mut self,
+        name: &str,
+    ) -> Option<RuntimeValue> {
+        self.globals.remove(name)
+    }
+
+    /// All globals, in no particular order
+    pub fn globals(&self) -> impl Iterator<Item = (&str, &RuntimeValue)> {
+        self.globals
+            .iter()
+            .map(|(name, value)| (name.as_str(), value))
+    }
+}
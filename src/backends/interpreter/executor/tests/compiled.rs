@@ -0,0 +1,153 @@
+//! 分层 JIT 测试
+//!
+//! 测试覆盖内容：
+//! - 调用次数超过阈值后函数被编译为模板代码，结果与解释执行一致
+//! - 模板代码持续被调用后由 Cranelift 重新编译（native feature）
+//! - 未达阈值、关闭 JIT 时保持解释执行
+//! - 无法编译的热点函数记为 rejected，不再重试
+//! - 机器码放弃执行时由解释器重跑，错误与解释执行一致
+
+use crate::backends::{BuildMode, Executor, ExecutorConfig, ExecutorError};
+use crate::backends::common::RuntimeValue;
+use crate::backends::interpreter::executor::Interpreter;
+use crate::frontend::{CompileConfig, Compiler};
+use crate::middle::bytecode::BytecodeModule;
+use crate::middle::passes::codegen::CodegenContext;
+
+const SOURCE: &str = r#"
+fib: (n: Int) -> Int = {
+    if n < 2 {
+        return n
+    }
+    return fib(n - 1) + fib(n - 2)
+}
+
+mul: (a: Int, b: Int) -> Int = {
+    return a * b
+}
+
+name: (n: Int) -> String = {
+    return "fib"
+}
+
+main: () -> Void = {
+    print(fib(1))
+    print(mul(1, 1))
+    print(name(1))
+}
+"#;
+
+fn bytecode() -> BytecodeModule {
+    let module = Compiler::with_config(CompileConfig::new().with_tree_shaking(false))
+        .compile("jit.yx", SOURCE)
+        .expect("source should compile");
+    let mut ctx = CodegenContext::new(module);
+    BytecodeModule::from(ctx.generate().expect("codegen should succeed"))
+}
+
+fn run(
+    config: ExecutorConfig,
+    name: &str,
+    args: &[RuntimeValue],
+    times: usize,
+) -> (Interpreter, Vec<Result<RuntimeValue, ExecutorError>>) {
+    let module = bytecode();
+    let mut interp = Interpreter::with_config(config);
+    interp.execute_module(&module).expect("module should run");
+    let func = module
+        .functions
+        .iter()
+        .find(|f| f.name == name)
+        .expect("function in module")
+        .clone();
+    let results = (0..times)
+        .map(|_| interp.execute_function(&func, args))
+        .collect();
+    (interp, results)
+}
+
+fn config(threshold: u32) -> ExecutorConfig {
+    ExecutorConfig {
+        native_code: true,
+        jit_threshold: threshold,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_hot_function_compiled_after_threshold() {
+    let (interp, results) = run(config(10), "fib", &[RuntimeValue::Int(15)], 1);
+    assert_eq!(results[0].as_ref().unwrap(), &RuntimeValue::Int(610));
+    let stats = interp.jit_stats();
+    assert_eq!(stats.compiled, vec!["fib".to_string()]);
+    assert!(stats.native_calls > 0);
+    assert_eq!(stats.deopts, 0);
+}
+
+#[test]
+fn test_cold_function_stays_interpreted() {
+    let (interp, results) = run(config(1000), "fib", &[RuntimeValue::Int(10)], 1);
+    assert_eq!(results[0].as_ref().unwrap(), &RuntimeValue::Int(55));
+    let stats = interp.jit_stats();
+    assert!(stats.compiled.is_empty());
+    assert_eq!(stats.native_calls, 0);
+}
+
+#[test]
+fn test_disabled_jit_interprets_everything() {
+    let config = ExecutorConfig {
+        native_code: false,
+        ..config(0)
+    };
+    let (interp, results) = run(config, "fib", &[RuntimeValue::Int(15)], 1);
+    assert_eq!(results[0].as_ref().unwrap(), &RuntimeValue::Int(610));
+    assert_eq!(interp.jit_stats(), Default::default());
+}
+
+#[test]
+fn test_unsupported_hot_function_rejected() {
+    let (interp, results) = run(config(2), "name", &[RuntimeValue::Int(1)], 5);
+    assert!(results.iter().all(|r| r.is_ok()));
+    let stats = interp.jit_stats();
+    assert!(stats.compiled.is_empty());
+    assert_eq!(stats.rejected, vec!["name".to_string()]);
+}
+
+#[test]
+fn test_deopt_reruns_call_in_interpreter() {
+    let args = [RuntimeValue::Int(i64::MAX), RuntimeValue::Int(2)];
+    let (interp, results) = run(config(0), "mul", &args, 2);
+    for result in &results {
+        assert!(matches!(result, Err(ExecutorError::IntegerOverflow(..))));
+    }
+    let stats = interp.jit_stats();
+    assert!(stats.compiled.contains(&"mul".to_string()));
+    assert_eq!(stats.deopts, 2);
+
+    let release = ExecutorConfig {
+        build_mode: BuildMode::Release,
+        ..config(0)
+    };
+    let (interp, results) = run(release, "mul", &args, 1);
+    assert_eq!(
+        results[0].as_ref().unwrap(),
+        &RuntimeValue::Int(i64::MAX.wrapping_mul(2))
+    );
+    // 发布构建回绕，机器码不会放弃
+    assert_eq!(interp.jit_stats().deopts, 0);
+}
+
+#[cfg(feature = "native")]
+#[test]
+fn test_hot_template_code_optimized() {
+    let args = [RuntimeValue::Int(6), RuntimeValue::Int(7)];
+    let (interp, results) = run(config(1), "mul", &args, 5);
+    assert!(results.iter().all(|r| r == &Ok(RuntimeValue::Int(42))));
+    let stats = interp.jit_stats();
+    assert_eq!(stats.compiled, vec!["mul".to_string()]);
+    assert!(stats.optimized.is_empty());
+
+    let (interp, results) = run(config(1), "mul", &args, 20);
+    assert!(results.iter().all(|r| r == &Ok(RuntimeValue::Int(42))));
+    assert_eq!(interp.jit_stats().optimized, vec!["mul".to_string()]);
+}
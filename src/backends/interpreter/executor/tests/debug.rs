@@ -9,6 +9,7 @@
 //! - 栈追踪: capture_stack 在 step_one 期间的正确性
 
 use std::collections::HashMap;
+use std::sync::Arc;
 use crate::backends::DebuggableExecutor;
 use crate::middle::bytecode::{
     BytecodeModule, BytecodeFunction, BytecodeInstr, Reg, FunctionRef, ConstValue,
@@ -34,7 +35,8 @@ fn make_module(
         instructions,
         labels: HashMap::new(),
         exception_handlers: vec![],
-        debug_map: HashMap::new(),
+        line_table: crate::util::span::LineTable::new(),
+        decoded: Default::default(),
     });
     module.constants = constants;
     module.entry_point = Some(func_idx);
@@ -61,8 +63,9 @@ fn load_module_for_stepping(module: &BytecodeModule) -> Interpreter {
 
     // 加载函数
     for func in &module.functions {
+        let func = Arc::new(func.clone());
         interp.functions.insert(func.name.clone(), func.clone());
-        interp.functions_by_id.push(func.clone());
+        interp.functions_by_id.push(func);
     }
 
     // 加载类型
@@ -157,7 +160,8 @@ fn test_step_over_advances_past_call() {
         ],
         labels: HashMap::new(),
         exception_handlers: vec![],
-        debug_map: HashMap::new(),
+        line_table: crate::util::span::LineTable::new(),
+        decoded: Default::default(),
     };
 
     let mut module = BytecodeModule::new("test".to_string());
@@ -181,7 +185,8 @@ fn test_step_over_advances_past_call() {
         ],
         labels: HashMap::new(),
         exception_handlers: vec![],
-        debug_map: HashMap::new(),
+        line_table: crate::util::span::LineTable::new(),
+        decoded: Default::default(),
     });
     module.entry_point = Some(main_idx);
     let mut interp = load_module_for_stepping(&module);
@@ -237,7 +242,8 @@ fn test_step_out_returns_to_caller() {
         ],
         labels: HashMap::new(),
         exception_handlers: vec![],
-        debug_map: HashMap::new(),
+        line_table: crate::util::span::LineTable::new(),
+        decoded: Default::default(),
     };
 
     let mut module = BytecodeModule::new("test".to_string());
@@ -262,7 +268,8 @@ fn test_step_out_returns_to_caller() {
         ],
         labels: HashMap::new(),
         exception_handlers: vec![],
-        debug_map: HashMap::new(),
+        line_table: crate::util::span::LineTable::new(),
+        decoded: Default::default(),
     });
     module.entry_point = Some(main_idx);
 
@@ -270,8 +277,9 @@ fn test_step_out_returns_to_caller() {
     let mut interp = embedded_interpreter();
     interp.constants.extend(module.constants.clone());
     for func in &module.functions {
+        let func = Arc::new(func.clone());
         interp.functions.insert(func.name.clone(), func.clone());
-        interp.functions_by_id.push(func.clone());
+        interp.functions_by_id.push(func);
     }
     interp.type_table.extend(module.type_table.clone());
 
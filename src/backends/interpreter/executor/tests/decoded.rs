@@ -0,0 +1,340 @@
+//! 定长解码指令测试
+//!
+//! 测试覆盖内容：
+//! - 解码后的指令为 12 字节
+//! - 跳转标签解析为绝对下标，越过函数开头的标签解析为 NO_TARGET
+//! - 参数列表、FFI 元数据、方法名、分支表等变长操作数放入旁表
+//! - BinaryOp / CompareOp / UnaryOp 经立即数往返不变
+//! - 装载函数时即完成解码；克隆的函数需重新解码
+//! - 解释器按解码结果执行回跳循环与跳转表
+
+use std::collections::HashMap;
+
+use crate::backends::common::RuntimeValue;
+use crate::backends::interpreter::executor::Interpreter;
+use crate::backends::Executor;
+use crate::middle::bytecode::{
+    BinaryOp, BytecodeFunction, BytecodeInstr, BytecodeModule, CompareOp, ConstValue, FunctionRef,
+    Label, Reg, UnaryOp,
+};
+use crate::middle::decoded::{DecodedCode, Instr, Op, NO_REG, NO_TARGET};
+
+fn function(
+    name: &str,
+    instructions: Vec<BytecodeInstr>,
+) -> BytecodeFunction {
+    BytecodeFunction {
+        name: name.to_string(),
+        params: vec![],
+        return_type: crate::middle::core::ir::Type::Int(64),
+        local_count: 0,
+        upvalue_count: 0,
+        instructions,
+        labels: HashMap::new(),
+        exception_handlers: vec![],
+        line_table: crate::util::span::LineTable::new(),
+        decoded: Default::default(),
+    }
+}
+
+/// 相对标签（偏移量按 i32 存放）
+fn label(offset: i32) -> Label {
+    Label(offset as u32)
+}
+
+#[test]
+fn test_instr_is_twelve_bytes() {
+    assert_eq!(std::mem::size_of::<Instr>(), 12);
+}
+
+#[test]
+fn test_jump_labels_resolve_to_absolute_targets() {
+    let code = DecodedCode::decode(&[
+        BytecodeInstr::Nop,
+        BytecodeInstr::JmpIf {
+            cond: Reg(3),
+            target: label(2),
+        },
+        BytecodeInstr::Jmp { target: label(-2) },
+        BytecodeInstr::Jmp { target: label(-10) },
+        BytecodeInstr::TryBegin {
+            catch_target: label(1),
+        },
+    ]);
+    assert_eq!(code.instrs.len(), 5);
+    assert_eq!((code.instrs[1].op, code.instrs[1].a), (Op::JmpIf, 3));
+    assert_eq!(code.instrs[1].target(), 3);
+    assert_eq!(code.instrs[2].target(), 0);
+    assert_eq!(code.instrs[3].imm, NO_TARGET);
+    assert_eq!(code.instrs[4].target(), 5);
+}
+
+#[test]
+fn test_variable_length_operands_go_to_side_tables() {
+    let code = DecodedCode::decode(&[
+        BytecodeInstr::CallStatic {
+            dst: None,
+            func: FunctionRef::Index(7),
+            args: vec![Reg(1), Reg(2)],
+        },
+        BytecodeInstr::CallNative {
+            dst: Some(Reg(4)),
+            func_name: "open".to_string(),
+            mechanism: "c".to_string(),
+            lib: "libsqlite3".to_string(),
+            symbol: "sqlite3_open".to_string(),
+            args: vec![Reg(5)],
+        },
+        BytecodeInstr::InvokeVirtual {
+            dst: Some(Reg(0)),
+            obj: Reg(1),
+            slot: 2,
+            name_idx: 9,
+            args: vec![Reg(1), Reg(6)],
+        },
+        BytecodeInstr::NewDict {
+            dst: Reg(0),
+            keys: vec![Reg(1), Reg(2)],
+            values: vec![Reg(3), Reg(4)],
+        },
+        BytecodeInstr::GetRecordField {
+            dst: Reg(0),
+            src: Reg(1),
+            field: "x".to_string(),
+        },
+    ]);
+
+    let call = code.instrs[0];
+    assert_eq!(
+        (call.op, call.a, call.dst()),
+        (Op::CallStatic, NO_REG, None)
+    );
+    let side = &code.calls[call.index()];
+    assert!(matches!(side.func, FunctionRef::Index(7)));
+    assert_eq!(code.regs(side.args), &[Reg(1), Reg(2)]);
+
+    let native = code.instrs[1];
+    assert_eq!(native.dst(), Some(Reg(4)));
+    let side = &code.natives[native.index()];
+    assert_eq!(
+        (side.lib.as_str(), side.symbol.as_str()),
+        ("libsqlite3", "sqlite3_open")
+    );
+    assert_eq!(code.regs(side.args), &[Reg(5)]);
+
+    let invoke = code.instrs[2];
+    assert_eq!((invoke.b(), invoke.c), (Reg(1), 2));
+    let side = &code.methods[invoke.index()];
+    assert_eq!(side.name_idx, 9);
+    assert_eq!(code.regs(side.args), &[Reg(1), Reg(6)]);
+
+    let dict = &code.dicts[code.instrs[3].index()];
+    assert_eq!(code.regs(dict.keys), &[Reg(1), Reg(2)]);
+    assert_eq!(code.regs(dict.values), &[Reg(3), Reg(4)]);
+
+    assert_eq!(code.names[code.instrs[4].index()], "x");
+}
+
+#[test]
+fn test_switch_tables_are_decoded() {
+    let code = DecodedCode::decode(&[
+        BytecodeInstr::Nop,
+        BytecodeInstr::Switch {
+            value: Reg(0),
+            targets: vec![(Some(label(5)), label(2)), (None, label(3))],
+        },
+        BytecodeInstr::Switch {
+            value: Reg(0),
+            // 不在末尾的无值项不是默认分支
+            targets: vec![(None, label(1)), (Some(label(-1)), label(1))],
+        },
+        BytecodeInstr::TableSwitch {
+            value: Reg(0),
+            low: 10,
+            targets: vec![label(1), label(-3)],
+            default: label(2),
+        },
+    ]);
+
+    let switch = &code.switches[code.instrs[1].index()];
+    assert_eq!(code.cases(switch.cases), &[(5, 3)]);
+    assert_eq!(switch.default, Some(4));
+    let switch = &code.switches[code.instrs[2].index()];
+    assert_eq!(code.cases(switch.cases), &[(-1, 3)]);
+    assert_eq!(switch.default, None);
+
+    let table = &code.tables[code.instrs[3].index()];
+    assert_eq!(table.low, 10);
+    assert_eq!(code.targets(table.targets), &[4, 0]);
+    assert_eq!(table.default, 5);
+}
+
+#[test]
+fn test_sub_operations_round_trip() {
+    let binary = [
+        BinaryOp::Add,
+        BinaryOp::Sub,
+        BinaryOp::Mul,
+        BinaryOp::Div,
+        BinaryOp::Rem,
+        BinaryOp::And,
+        BinaryOp::Or,
+        BinaryOp::Xor,
+        BinaryOp::Shl,
+        BinaryOp::Sar,
+        BinaryOp::Shr,
+    ];
+    let compare = [
+        CompareOp::Eq,
+        CompareOp::Ne,
+        CompareOp::Lt,
+        CompareOp::Le,
+        CompareOp::Gt,
+        CompareOp::Ge,
+    ];
+    let mut instructions = Vec::new();
+    for op in binary {
+        instructions.push(BytecodeInstr::BinaryOp {
+            dst: Reg(0),
+            lhs: Reg(1),
+            rhs: Reg(2),
+            op,
+        });
+    }
+    for cmp in compare {
+        instructions.push(BytecodeInstr::FloatCompare {
+            dst: Reg(0),
+            lhs: Reg(1),
+            rhs: Reg(2),
+            cmp,
+        });
+    }
+    for op in [UnaryOp::Neg, UnaryOp::Not] {
+        instructions.push(BytecodeInstr::UnaryOp {
+            dst: Reg(0),
+            src: Reg(1),
+            op,
+        });
+    }
+
+    let code = DecodedCode::decode(&instructions);
+    let mut instrs = code.instrs.iter();
+    for op in binary {
+        let instr = instrs.next().unwrap();
+        assert_eq!((instr.a, instr.b, instr.c), (0, 1, 2));
+        assert_eq!(instr.binary_op(), op);
+    }
+    for cmp in compare {
+        assert_eq!(instrs.next().unwrap().compare_op(), cmp);
+    }
+    for op in [UnaryOp::Neg, UnaryOp::Not] {
+        assert_eq!(instrs.next().unwrap().unary_op(), op);
+    }
+}
+
+#[test]
+fn test_functions_are_decoded_when_installed() {
+    let mut module = BytecodeModule::new("test".to_string());
+    module.add_function(function(
+        "helper",
+        vec![BytecodeInstr::Nop, BytecodeInstr::Return],
+    ));
+    let entry = module.add_function(function("main", vec![BytecodeInstr::Return]));
+    module.entry_point = Some(entry);
+
+    let mut interp = Interpreter::new();
+    interp.execute_module(&module).unwrap();
+    // helper 从未被调用，装载时也已解码
+    let helper = &interp.functions["helper"];
+    assert!(helper.decoded.is_decoded());
+    assert_eq!(helper.decoded().instrs.len(), 2);
+
+    // 克隆可能被改写指令，需重新解码
+    assert!(!(**helper).clone().decoded.is_decoded());
+}
+
+#[test]
+fn test_interpreter_runs_decoded_loops_and_jump_tables() {
+    // r0 = 0; r1 = 0; while r1 < 5 { r0 = r0 + r1; r1 = r1 + 1 }
+    // 再按 r1 - 5 查表：0 => r0 + 100
+    let instructions = vec![
+        BytecodeInstr::LoadConst {
+            dst: Reg(0),
+            const_idx: 0,
+        },
+        BytecodeInstr::LoadConst {
+            dst: Reg(1),
+            const_idx: 0,
+        },
+        BytecodeInstr::LoadConst {
+            dst: Reg(2),
+            const_idx: 1,
+        },
+        BytecodeInstr::LoadConst {
+            dst: Reg(3),
+            const_idx: 2,
+        },
+        // 4: 循环头
+        BytecodeInstr::Compare {
+            dst: Reg(4),
+            lhs: Reg(1),
+            rhs: Reg(2),
+            cmp: CompareOp::Lt,
+        },
+        BytecodeInstr::JmpIfNot {
+            cond: Reg(4),
+            target: label(4),
+        },
+        BytecodeInstr::BinaryOp {
+            dst: Reg(0),
+            lhs: Reg(0),
+            rhs: Reg(1),
+            op: BinaryOp::Add,
+        },
+        BytecodeInstr::BinaryOp {
+            dst: Reg(1),
+            lhs: Reg(1),
+            rhs: Reg(3),
+            op: BinaryOp::Add,
+        },
+        BytecodeInstr::Jmp { target: label(-4) },
+        // 9: 循环出口
+        BytecodeInstr::BinaryOp {
+            dst: Reg(5),
+            lhs: Reg(1),
+            rhs: Reg(2),
+            op: BinaryOp::Sub,
+        },
+        BytecodeInstr::TableSwitch {
+            value: Reg(5),
+            low: 0,
+            targets: vec![label(2)],
+            default: label(1),
+        },
+        BytecodeInstr::ReturnValue { value: Reg(5) },
+        BytecodeInstr::LoadConst {
+            dst: Reg(6),
+            const_idx: 3,
+        },
+        BytecodeInstr::BinaryOp {
+            dst: Reg(0),
+            lhs: Reg(0),
+            rhs: Reg(6),
+            op: BinaryOp::Add,
+        },
+        BytecodeInstr::ReturnValue { value: Reg(0) },
+    ];
+    let mut module = BytecodeModule::new("test".to_string());
+    module.constants = vec![
+        ConstValue::Int(0),
+        ConstValue::Int(5),
+        ConstValue::Int(1),
+        ConstValue::Int(100),
+    ];
+    module.add_function(function("sum", instructions));
+
+    let mut interp = Interpreter::new();
+    interp.execute_module(&module).unwrap();
+    let result = interp.execute_function(&module.functions[0], &[]).unwrap();
+    assert_eq!(result, RuntimeValue::Int(110));
+}
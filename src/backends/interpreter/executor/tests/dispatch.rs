@@ -0,0 +1,160 @@
+//! 指令分派方式测试
+//!
+//! 测试覆盖内容：
+//! - 逐帧直接循环与单步路径的执行结果一致（循环、递归、尾调用）
+//! - 两种分派方式报告相同的运行时错误与栈帧
+//! - 栈溢出在两种分派方式下都被检测到，错误带有调用深度上限
+//! - 深递归在宿主栈很小的线程中按段扩展宿主栈，不会使宿主崩溃
+
+use crate::backends::common::RuntimeValue;
+use crate::backends::{DispatchMode, ExecutorConfig};
+use crate::vm::Program;
+use crate::Engine;
+
+const SOURCE: &str = r#"
+loop_sum: (n: Int) -> Int = {
+    mut total = 0;
+    for i in 0..n {
+        total = total + i;
+    }
+    return total
+};
+
+fib: (n: Int) -> Int = {
+    if n <= 1 {
+        return n
+    }
+    return fib(n - 1) + fib(n - 2)
+};
+
+count_down: (n: Int, acc: Int) -> Int = {
+    if n == 0 {
+        return acc
+    }
+    return count_down(n - 1, acc + n)
+};
+
+deep: (n: Int) -> Int = {
+    return deep(n + 1) + 1
+};
+
+depth: (n: Int) -> Int = {
+    if n == 0 {
+        return 0
+    }
+    return depth(n - 1) + 1
+};
+
+div: (a: Int, b: Int) -> Int = {
+    return a / b
+};
+
+main: () -> Int = {
+    return 0
+}
+"#;
+
+fn engine(dispatch: DispatchMode) -> Engine {
+    Engine::builder()
+        .executor_config(ExecutorConfig {
+            dispatch,
+            native_code: false,
+            ..ExecutorConfig::default()
+        })
+        .build()
+}
+
+fn compile() -> Program {
+    engine(DispatchMode::Direct)
+        .compile("dispatch.yx", SOURCE)
+        .expect("compile")
+}
+
+#[test]
+fn test_dispatch_modes_agree_on_results() {
+    let program = compile();
+    let calls: [(&str, Vec<RuntimeValue>, i64); 3] = [
+        ("loop_sum", vec![RuntimeValue::Int(100)], 4950),
+        ("fib", vec![RuntimeValue::Int(15)], 610),
+        (
+            "count_down",
+            vec![RuntimeValue::Int(500), RuntimeValue::Int(0)],
+            125250,
+        ),
+    ];
+    for dispatch in [DispatchMode::Direct, DispatchMode::Step] {
+        let engine = engine(dispatch);
+        for (name, args, expected) in &calls {
+            let value = engine.call(&program, name, args).expect(name);
+            assert!(
+                matches!(value, RuntimeValue::Int(v) if v == *expected),
+                "{:?} {}: {:?}",
+                dispatch,
+                name,
+                value
+            );
+        }
+    }
+}
+
+#[test]
+fn test_dispatch_modes_report_same_error() {
+    let program = compile();
+    let args = [RuntimeValue::Int(1), RuntimeValue::Int(0)];
+    let direct = engine(DispatchMode::Direct)
+        .call(&program, "div", &args)
+        .unwrap_err();
+    let step = engine(DispatchMode::Step)
+        .call(&program, "div", &args)
+        .unwrap_err();
+    assert_eq!(format!("{:?}", direct), format!("{:?}", step));
+    assert!(direct.stack_trace().is_some_and(|s| !s.is_empty()));
+}
+
+#[test]
+fn test_dispatch_modes_detect_stack_overflow() {
+    let program = compile();
+    for dispatch in [DispatchMode::Direct, DispatchMode::Step] {
+        // 测试线程的栈较小，调用深度上限也取小
+        let err = Engine::builder()
+            .executor_config(ExecutorConfig {
+                dispatch,
+                native_code: false,
+                max_stack_depth: 64,
+                ..ExecutorConfig::default()
+            })
+            .build()
+            .call(&program, "deep", &[RuntimeValue::Int(0)])
+            .unwrap_err();
+        assert!(
+            matches!(err, crate::backends::ExecutorError::StackOverflow(64, _)),
+            "{:?}: {:?}",
+            dispatch,
+            err
+        );
+    }
+}
+
+#[test]
+fn test_deep_recursion_grows_host_stack() {
+    // 512 KiB 的线程栈放不下两万层解释器调用
+    let worker = std::thread::Builder::new()
+        .stack_size(512 * 1024)
+        .spawn(|| {
+            let program = compile();
+            for dispatch in [DispatchMode::Direct, DispatchMode::Step] {
+                let result = Engine::builder()
+                    .executor_config(ExecutorConfig {
+                        dispatch,
+                        native_code: false,
+                        max_stack_depth: 50_000,
+                        ..ExecutorConfig::default()
+                    })
+                    .build()
+                    .call(&program, "depth", &[RuntimeValue::Int(20_000)]);
+                assert_eq!(result, Ok(RuntimeValue::Int(20_000)), "{:?}", dispatch);
+            }
+        })
+        .unwrap();
+    worker.join().unwrap();
+}
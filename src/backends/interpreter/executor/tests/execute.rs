@@ -3,11 +3,18 @@
 //! 测试覆盖内容：
 //! - Borrow/Release 字节码指令的执行
 //! - 借用令牌（ZST）的拷贝、释放及边界行为
+//! - MakeDyn/InvokeVirtual 存在类型值的虚表分派
+//! - 整数溢出：调试构建报错、发布构建回绕
+//! - 定宽数值：`as` 转换与算术结果收窄
+//! - 32 位算术指令：结果按 `Int32` 收窄
+//! - 字符串常量驻留：多次加载共享同一份文本
+//! - 函数只在加载时解码一次，按名、按下标调用共享同一份
 
 use crate::backends::Executor;
-use crate::backends::common::RuntimeValue;
+use crate::backends::common::{HeapValue, RuntimeValue, Value};
 use crate::middle::bytecode::{BytecodeFunction, BytecodeInstr, Reg, ConstValue};
 use std::collections::HashMap;
+use std::sync::Arc;
 use crate::backends::interpreter::executor::Interpreter;
 
 fn make_function(instrs: Vec<BytecodeInstr>) -> BytecodeFunction {
@@ -20,7 +27,8 @@ fn make_function(instrs: Vec<BytecodeInstr>) -> BytecodeFunction {
         instructions: instrs,
         labels: HashMap::new(),
         exception_handlers: vec![],
-        debug_map: HashMap::new(),
+        line_table: crate::util::span::LineTable::new(),
+        decoded: Default::default(),
     }
 }
 
@@ -31,6 +39,55 @@ fn make_interp_with_const(val: ConstValue) -> Interpreter {
     interp
 }
 
+/// 以虚表方法名调用，方法收到解包后的具体值作为 self
+#[test]
+fn test_invoke_virtual_dispatches_through_vtable() {
+    let mut method = make_function(vec![
+        BytecodeInstr::LoadArg {
+            dst: Reg(0),
+            arg_idx: 0,
+        },
+        BytecodeInstr::ReturnValue { value: Reg(0) },
+    ]);
+    method.name = "Meter.value".to_string();
+    method.params = vec![crate::middle::core::ir::Type::Int(64)];
+    let func = make_function(vec![
+        BytecodeInstr::LoadConst {
+            dst: Reg(0),
+            const_idx: 0,
+        },
+        BytecodeInstr::MakeDyn {
+            dst: Reg(1),
+            src: Reg(0),
+            vtable: 0,
+        },
+        BytecodeInstr::InvokeVirtual {
+            dst: Some(Reg(2)),
+            obj: Reg(1),
+            slot: 0,
+            name_idx: 1,
+            args: vec![Reg(1)],
+        },
+        BytecodeInstr::ReturnValue { value: Reg(2) },
+    ]);
+
+    let mut interp = make_interp_with_const(ConstValue::Int(41));
+    interp
+        .constants
+        .push(ConstValue::String("value".to_string()));
+    let method = Arc::new(method);
+    interp.functions.insert(method.name.clone(), method.clone());
+    interp.functions_by_id.push(method);
+    interp.vtables.push(crate::middle::core::ir::VTable {
+        interface: "Gauge".to_string(),
+        type_name: "Meter".to_string(),
+        methods: vec!["Meter.value".to_string()],
+    });
+
+    let result = interp.execute_function(&func, &[]).unwrap();
+    assert_eq!(result, RuntimeValue::Int(41));
+}
+
 /// Borrow copies value from src register to dst register (immutable)
 #[test]
 fn test_borrow_copies_value_immutable() {
@@ -238,7 +295,8 @@ fn spawn_concurrent_standard_mode() {
         ],
         labels: HashMap::new(),
         exception_handlers: vec![],
-        debug_map: HashMap::new(),
+        line_table: crate::util::span::LineTable::new(),
+        decoded: Default::default(),
     };
 
     // task_b: 返回 Int(20)
@@ -257,7 +315,8 @@ fn spawn_concurrent_standard_mode() {
         ],
         labels: HashMap::new(),
         exception_handlers: vec![],
-        debug_map: HashMap::new(),
+        line_table: crate::util::span::LineTable::new(),
+        decoded: Default::default(),
     };
 
     // main: 创建两个闭包，spawn 并发执行，读取结果并相加
@@ -300,7 +359,8 @@ fn spawn_concurrent_standard_mode() {
         ],
         labels: HashMap::new(),
         exception_handlers: vec![],
-        debug_map: HashMap::new(),
+        line_table: crate::util::span::LineTable::new(),
+        decoded: Default::default(),
     };
 
     let module = BytecodeModule {
@@ -310,6 +370,10 @@ fn spawn_concurrent_standard_mode() {
         type_table: vec![],
         globals: vec![],
         entry_point: Some(2), // main 函数
+        lazy_functions: None,
+        vtables: vec![],
+        struct_layouts: vec![],
+        sources: None,
     };
 
     // 配置 Standard 模式 + 1 worker（避免多线程并发问题）
@@ -348,3 +412,341 @@ fn spawn_concurrent_standard_mode() {
     );
     assert_eq!(interp.runtime_config().workers, 1, "workers 应为 1");
 }
+
+/// 调试构建中整数溢出报 IntegerOverflow
+#[test]
+fn test_int_overflow_traps_in_debug() {
+    use crate::backends::ExecutorError;
+    use crate::middle::bytecode::BinaryOp;
+
+    let interp = Interpreter::new();
+    assert!(matches!(
+        interp.int_arith(BinaryOp::Add, i64::MAX, 1),
+        Err(ExecutorError::IntegerOverflow(..))
+    ));
+    assert!(matches!(
+        interp.int_arith(BinaryOp::Mul, i64::MIN, -1),
+        Err(ExecutorError::IntegerOverflow(..))
+    ));
+    assert!(matches!(
+        interp.int_arith(BinaryOp::Div, i64::MIN, -1),
+        Err(ExecutorError::IntegerOverflow(..))
+    ));
+    assert!(matches!(
+        interp.int_arith(BinaryOp::Shl, 1, 64),
+        Err(ExecutorError::IntegerOverflow(..))
+    ));
+    assert_eq!(interp.int_arith(BinaryOp::Sub, 10, 3).unwrap(), 7);
+}
+
+/// 发布构建中整数溢出按补码回绕
+#[test]
+fn test_int_overflow_wraps_in_release() {
+    use crate::backends::{BuildMode, ExecutorConfig};
+    use crate::middle::bytecode::BinaryOp;
+
+    let interp = Interpreter::with_config(ExecutorConfig {
+        build_mode: BuildMode::Release,
+        ..Default::default()
+    });
+    assert_eq!(
+        interp.int_arith(BinaryOp::Add, i64::MAX, 1).unwrap(),
+        i64::MIN
+    );
+    assert_eq!(
+        interp.int_arith(BinaryOp::Sub, i64::MIN, 1).unwrap(),
+        i64::MAX
+    );
+    assert_eq!(
+        interp.int_arith(BinaryOp::Div, i64::MIN, -1).unwrap(),
+        i64::MIN
+    );
+}
+
+/// `as` 转换：整数按位宽回绕，浮点转整数向零截断并饱和
+#[test]
+fn test_cast_value_follows_as_semantics() {
+    use crate::middle::bytecode::NumericTarget;
+
+    assert_eq!(
+        Interpreter::cast_value(RuntimeValue::Int(1000), NumericTarget::Int(8)),
+        RuntimeValue::Int(-24)
+    );
+    assert_eq!(
+        Interpreter::cast_value(RuntimeValue::Float(-3.9), NumericTarget::Int(32)),
+        RuntimeValue::Int(-3)
+    );
+    assert_eq!(
+        Interpreter::cast_value(RuntimeValue::Float(1e10), NumericTarget::Int(16)),
+        RuntimeValue::Int(i16::MAX as i64)
+    );
+    assert_eq!(
+        Interpreter::cast_value(RuntimeValue::Bool(true), NumericTarget::Int(64)),
+        RuntimeValue::Int(1)
+    );
+    assert_eq!(
+        Interpreter::cast_value(RuntimeValue::Float(0.1), NumericTarget::Float(32)),
+        RuntimeValue::Float(0.1f32 as f64)
+    );
+}
+
+/// 定宽算术结果超出位宽：调试构建报错，发布构建回绕
+#[test]
+fn test_narrow_traps_in_debug_and_wraps_in_release() {
+    use crate::backends::{BuildMode, ExecutorConfig, ExecutorError};
+    use crate::middle::bytecode::NumericTarget;
+
+    let debug = Interpreter::new();
+    assert!(matches!(
+        debug.narrow_value(RuntimeValue::Int(128), NumericTarget::Int(8)),
+        Err(ExecutorError::IntegerOverflow(..))
+    ));
+    assert_eq!(
+        debug
+            .narrow_value(RuntimeValue::Int(127), NumericTarget::Int(8))
+            .unwrap(),
+        RuntimeValue::Int(127)
+    );
+
+    let release = Interpreter::with_config(ExecutorConfig {
+        build_mode: BuildMode::Release,
+        ..Default::default()
+    });
+    assert_eq!(
+        release
+            .narrow_value(RuntimeValue::Int(128), NumericTarget::Int(8))
+            .unwrap(),
+        RuntimeValue::Int(-128)
+    );
+}
+
+/// `I32*` 指令等价于 64 位运算后收窄到 `Int32`
+#[test]
+fn test_int32_op_narrows_result() {
+    use crate::backends::{BuildMode, ExecutorConfig, ExecutorError};
+    use crate::middle::bytecode::BinaryOp;
+
+    let func = make_function(vec![
+        BytecodeInstr::LoadConst {
+            dst: Reg(0),
+            const_idx: 0,
+        },
+        BytecodeInstr::LoadConst {
+            dst: Reg(1),
+            const_idx: 1,
+        },
+        BytecodeInstr::Int32Op {
+            dst: Reg(2),
+            lhs: Reg(0),
+            rhs: Reg(1),
+            op: BinaryOp::Mul,
+        },
+        BytecodeInstr::Int32Neg {
+            dst: Reg(2),
+            src: Reg(2),
+        },
+        BytecodeInstr::ReturnValue { value: Reg(2) },
+    ]);
+    let run = |config: ExecutorConfig, factor: i128| {
+        let mut interp = Interpreter::with_config(config);
+        interp.constants.push(ConstValue::Int(40000));
+        interp.constants.push(ConstValue::Int(factor));
+        interp.execute_function(&func, &[])
+    };
+
+    assert_eq!(
+        run(ExecutorConfig::default(), 3).unwrap(),
+        RuntimeValue::Int(-120000)
+    );
+    assert!(matches!(
+        run(ExecutorConfig::default(), 65536),
+        Err(ExecutorError::IntegerOverflow(..))
+    ));
+    let release = ExecutorConfig {
+        build_mode: BuildMode::Release,
+        ..Default::default()
+    };
+    assert_eq!(run(release, 65536).unwrap(), RuntimeValue::Int(1673527296));
+}
+
+/// `as?`：类型标签相符时原样返回，否则得到 void；定宽整数按范围判断
+#[test]
+fn test_type_test_matches_runtime_tag() {
+    let interp = Interpreter::new();
+    let text = RuntimeValue::String("hi".into());
+    assert_eq!(interp.type_test(text.clone(), "String"), text);
+    assert_eq!(interp.type_test(text.clone(), "Any"), text);
+    assert_eq!(interp.type_test(text, "Int"), RuntimeValue::Unit);
+    assert_eq!(
+        interp.type_test(RuntimeValue::Int(100), "Int8"),
+        RuntimeValue::Int(100)
+    );
+    assert_eq!(
+        interp.type_test(RuntimeValue::Int(300), "Int8"),
+        RuntimeValue::Unit
+    );
+    assert_eq!(
+        interp.type_test(RuntimeValue::Bool(true), "Bool"),
+        RuntimeValue::Bool(true)
+    );
+}
+
+/// `ExecutorConfig::heap_dump` 在 VM 关停时写出堆转储
+#[test]
+fn test_heap_dump_written_on_exit() {
+    use crate::backends::common::heap_dump::HeapDump;
+
+    let dir = tempfile::TempDir::new().unwrap();
+    let source = dir.path().join("leak.yx");
+    let dump_path = dir.path().join("exit.dump");
+    std::fs::write(
+        &source,
+        "main = () => {\n    cache = {\"k\": [1, 2, 3]}\n    print(\"ok\")\n}\n",
+    )
+    .unwrap();
+
+    let code = crate::util::diagnostic::run_file_with_diagnostics(
+        &source,
+        crate::util::diagnostic::RunOptions {
+            executor: crate::backends::ExecutorConfig {
+                heap_dump: Some(dump_path.clone()),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    assert_eq!(code, 0);
+
+    let dump = HeapDump::read(&dump_path).unwrap();
+    let dict = dump
+        .objects
+        .iter()
+        .find(|o| o.type_name == "Dict")
+        .expect("dict in dump");
+    assert_eq!(dict.len, 1);
+    assert_eq!(dict.references.len(), 1);
+    // main 已返回，缓存不再被任何根引用
+    assert!(!dict.reachable);
+    assert!(dump
+        .objects
+        .iter()
+        .any(|o| o.type_name == "List" && o.retaining_path.ends_with(r#"["k"]"#)));
+}
+
+#[test]
+fn test_string_constants_are_interned() {
+    // 直接写入的常量不驻留，之后加入的常量池仍按下标对齐
+    let mut interp = make_interp_with_const(ConstValue::Int(1));
+    interp.add_constants(&[
+        ConstValue::Bool(true),
+        ConstValue::String("hello".to_string()),
+    ]);
+    match (interp.load_constant(2), interp.load_constant(2)) {
+        (RuntimeValue::String(a), RuntimeValue::String(b)) => {
+            assert_eq!(&*a, "hello");
+            assert!(std::sync::Arc::ptr_eq(&a, &b));
+        }
+        other => panic!("expected strings, got {:?}", other),
+    }
+    assert!(matches!(interp.load_constant(0), RuntimeValue::Int(1)));
+    assert!(matches!(interp.load_constant(1), RuntimeValue::Bool(true)));
+}
+
+#[test]
+fn test_composite_constants_are_prebuilt_per_slot() {
+    let mut interp = Interpreter::new();
+    interp.add_constants(&[ConstValue::List(vec![
+        ConstValue::String("a".to_string()),
+        ConstValue::Struct {
+            type_name: "Point".to_string(),
+            fields: vec![ConstValue::Int(1), ConstValue::Int(2)],
+        },
+    ])]);
+    let (RuntimeValue::List(first), RuntimeValue::List(second)) =
+        (interp.load_constant(0), interp.load_constant(0))
+    else {
+        panic!("expected lists");
+    };
+    // 每次加载都是新对象（含嵌套的结构体），字符串元素共用同一份
+    assert_ne!(first, second);
+    let (Some(HeapValue::List(a)), Some(HeapValue::List(b))) = (
+        interp.heap.get(first).cloned(),
+        interp.heap.get(second).cloned(),
+    ) else {
+        panic!("expected list objects");
+    };
+    match (&*a[0].view(), &*b[0].view()) {
+        (RuntimeValue::String(x), RuntimeValue::String(y)) => assert!(Arc::ptr_eq(x, y)),
+        other => panic!("expected strings, got {:?}", other),
+    }
+    match (&*a[1].view(), &*b[1].view()) {
+        (RuntimeValue::Struct { fields: x, .. }, RuntimeValue::Struct { fields: y, .. }) => {
+            assert_ne!(x, y);
+            assert!(matches!(
+                interp.heap.get(*x),
+                Some(HeapValue::Tuple(values))
+                    if values[..] == [Value::int(1), Value::int(2)]
+            ));
+        }
+        other => panic!("expected structs, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_struct_literals_in_constant_list() {
+    let program = crate::Engine::default()
+        .compile(
+            "points.yx",
+            "Point: Type = { x: Int, y: Int = 9 }\nsecond_x: () -> Int = {\n    points = [Point(1, 2), Point(y = 4, x = 3), Point(5)]\n    return points[1].x + points[2].y\n}\nmain: () -> Int = { return second_x() }",
+        )
+        .expect("compile");
+    assert!(program.constants.iter().any(|c| matches!(
+        c,
+        ConstValue::List(items) if matches!(items[..], [ConstValue::Struct { .. }, ..])
+    )));
+    let mut interp = Interpreter::new();
+    interp.execute_module(&program).unwrap();
+    let id = program
+        .functions
+        .iter()
+        .position(|f| f.name == "second_x")
+        .unwrap();
+    assert!(matches!(
+        interp.call_function_by_id(crate::backends::common::value::FunctionId(id as u32), &[]),
+        Ok(RuntimeValue::Int(12))
+    ));
+}
+
+#[test]
+fn test_loaded_functions_are_shared_by_calls() {
+    let program = crate::Engine::default()
+        .compile(
+            "shared.yx",
+            "twice: (n: Int) -> Int = { return n * 2 };\nmain: () -> Int = { return twice(0) }",
+        )
+        .expect("compile");
+    let mut interp = Interpreter::new();
+    interp.execute_module(&program).unwrap();
+
+    // 按名与按下标查找拿到的都是加载时解码的那一份
+    let loaded = interp.functions["twice"].clone();
+    let by_name = interp.lookup_static_function("twice").unwrap();
+    assert!(Arc::ptr_eq(&loaded, &by_name));
+    let id = program
+        .functions
+        .iter()
+        .position(|f| f.name == "twice")
+        .unwrap();
+    let by_id = interp
+        .function_by_id(crate::backends::common::value::FunctionId(id as u32))
+        .unwrap();
+    assert!(Arc::ptr_eq(&loaded, &by_id));
+    assert!(matches!(
+        interp.call_function_by_id(
+            crate::backends::common::value::FunctionId(id as u32),
+            &[RuntimeValue::Int(21)]
+        ),
+        Ok(RuntimeValue::Int(42))
+    ));
+}
@@ -0,0 +1,148 @@
+//! 指令预算与确定性执行测试
+//!
+//! 测试覆盖内容：
+//! - 无限递归在预算用完时以 `FuelExhausted` 结束，带预算与调用栈，且不可恢复
+//! - 预算恰好够用时成功，少一条指令时失败；两种分派方式消耗相同
+//! - 确定性模式忽略请求的运行时，spawn 就地执行，每次运行消耗相同的燃料
+
+use crate::backends::common::value::FunctionId;
+use crate::backends::common::RuntimeValue;
+use crate::backends::runtime::RuntimeMode;
+use crate::backends::{DispatchMode, Executor, ExecutorConfig, ExecutorError};
+use crate::Engine;
+
+const SOURCE: &str = r#"
+spin: (n: Int) -> Int = {
+    return spin(n + 1)
+}
+
+loop_sum: (n: Int) -> Int = {
+    mut total = 0
+    for i in 0..n {
+        total = total + i
+    }
+    return total
+}
+
+main: () -> Int = {
+    return 0
+}
+"#;
+
+const SPAWNS: &str = r#"
+double: (n: Int) -> Int = {
+    return n * 2
+}
+
+main: () -> Int = {
+    items = [1, 2, 3, 4, 5]
+    results = spawn for item in items {
+        return double(item)
+    }
+    a = spawn double(3)
+    b = spawn double(2)
+    return a + b
+}
+"#;
+
+fn engine(
+    fuel: u64,
+    dispatch: DispatchMode,
+) -> Engine {
+    Engine::builder()
+        .executor_config(ExecutorConfig {
+            dispatch,
+            ..ExecutorConfig::default()
+        })
+        .fuel(fuel)
+        .build()
+}
+
+/// `loop_sum(100)` 在 `dispatch` 下消耗的燃料
+fn fuel_used(dispatch: DispatchMode) -> u64 {
+    let engine = engine(1_000_000, dispatch);
+    let program = engine.compile("fuel.yx", SOURCE).expect("compile");
+    let mut interp = engine.interpreter();
+    interp.execute_module(&program).expect("load");
+    let before = interp.remaining_fuel().unwrap();
+    let sum = interp
+        .call_function_by_id(
+            FunctionId(
+                program
+                    .functions
+                    .iter()
+                    .position(|f| f.name == "loop_sum")
+                    .unwrap() as u32,
+            ),
+            &[RuntimeValue::Int(100)],
+        )
+        .expect("loop_sum");
+    assert_eq!(sum, RuntimeValue::Int(4950));
+    before - interp.remaining_fuel().unwrap()
+}
+
+#[test]
+fn test_infinite_loop_runs_out_of_fuel() {
+    // 调用深度达到上限之前燃料先用完
+    let engine = engine(1_000, DispatchMode::Direct);
+    let program = engine.compile("fuel.yx", SOURCE).expect("compile");
+    let err = engine
+        .call(&program, "spin", &[RuntimeValue::Int(0)])
+        .unwrap_err();
+    assert!(
+        matches!(&err, ExecutorError::FuelExhausted(1_000, Some(stack))
+            if stack.iter().any(|f| f.function_name.ends_with("spin"))),
+        "{:?}",
+        err
+    );
+    assert!(!err.is_panic());
+}
+
+#[test]
+fn test_budget_is_exact_and_dispatch_independent() {
+    let used = fuel_used(DispatchMode::Direct);
+    assert!(used > 100, "every iteration costs fuel: {}", used);
+    assert_eq!(fuel_used(DispatchMode::Step), used);
+
+    let program = engine(used, DispatchMode::Direct)
+        .compile("fuel.yx", SOURCE)
+        .expect("compile");
+    let args = [RuntimeValue::Int(100)];
+    // `call` 加载程序本身不执行指令，预算全部留给 loop_sum
+    for dispatch in [DispatchMode::Direct, DispatchMode::Step] {
+        assert_eq!(
+            engine(used, dispatch).call(&program, "loop_sum", &args),
+            Ok(RuntimeValue::Int(4950)),
+            "{:?}",
+            dispatch
+        );
+        assert!(matches!(
+            engine(used - 1, dispatch).call(&program, "loop_sum", &args),
+            Err(ExecutorError::FuelExhausted(..))
+        ));
+    }
+}
+
+#[test]
+fn test_deterministic_mode_runs_spawns_inline() {
+    let engine = Engine::builder()
+        .threads(4)
+        .deterministic(true)
+        .fuel(1_000_000)
+        .build();
+    let program = engine.compile("spawns.yx", SPAWNS).expect("compile");
+    let mut remaining = Vec::new();
+    for _ in 0..3 {
+        let mut interp = engine.interpreter();
+        assert_eq!(interp.runtime_config().runtime, RuntimeMode::Embedded);
+        interp.execute_module(&program).expect("run");
+        assert_eq!(interp.state().exit_code, 10);
+        remaining.push(interp.remaining_fuel().unwrap());
+    }
+    assert!(remaining[0] < 1_000_000);
+    assert!(
+        remaining.iter().all(|&r| r == remaining[0]),
+        "{:?}",
+        remaining
+    );
+}
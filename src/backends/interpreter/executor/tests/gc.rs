@@ -0,0 +1,151 @@
+//! 运行期回收测试
+//!
+//! 测试覆盖内容：
+//! - `gc.collect()` 回收循环中遗留的引用环，返回回收的对象数
+//! - 堆增长到 `gc_threshold` 时在安全点自动回收；阈值为 0 时不自动回收
+//! - 等待嵌套调用的帧中的值在回收中保留（两种分派方式）
+//! - 原生函数回调中的 `gc.collect()` 不回收（原生函数持有的值不可见）
+//! - 程序自己定义的 `collect` 不被解析为 `std.gc.collect`
+
+use crate::backends::{DispatchMode, Executor, ExecutorConfig};
+use crate::backends::interpreter::executor::Interpreter;
+use crate::Engine;
+
+const CYCLES: &str = r#"
+use std.{list, gc}
+
+make: (n: Int) -> Int = {
+    items = [n]
+    f = () => items
+    list.push(items, f)
+    return n
+}
+
+main: () -> Int = {
+    mut i = 0
+    while i < 1000 {
+        make(i)
+        i = i + 1
+    }
+    return gc.collect()
+}
+"#;
+
+fn run(
+    source: &str,
+    config: ExecutorConfig,
+) -> Interpreter {
+    let engine = Engine::builder()
+        .executor_config(ExecutorConfig {
+            native_code: false,
+            ..config
+        })
+        .build();
+    let program = engine.compile("gc.yx", source).expect("compile");
+    let mut interp = engine.interpreter();
+    interp.execute_module(&program).expect("run");
+    interp
+}
+
+fn threshold(gc_threshold: usize) -> ExecutorConfig {
+    ExecutorConfig {
+        gc_threshold,
+        ..ExecutorConfig::default()
+    }
+}
+
+#[test]
+fn test_gc_collect_frees_cycles() {
+    let interp = run(CYCLES, threshold(0));
+    assert!(
+        interp.state().exit_code >= 1000,
+        "{}",
+        interp.state().exit_code
+    );
+    assert!(interp.heap.len() < 10, "{}", interp.heap.len());
+}
+
+#[test]
+fn test_heap_is_collected_past_threshold() {
+    let source = CYCLES.replace("return gc.collect()", "return 0");
+    let collected = run(&source, threshold(64));
+    assert!(collected.heap.len() < 200, "{}", collected.heap.len());
+
+    let uncollected = run(&source, threshold(0));
+    assert!(uncollected.heap.len() >= 1000, "{}", uncollected.heap.len());
+}
+
+#[test]
+fn test_collection_keeps_values_of_waiting_frames() {
+    let source = r#"
+use std.list
+
+churn: (n: Int) -> Int = {
+    mut i = 0
+    mut total = 0
+    while i < n {
+        tmp = [i, i + 1]
+        total = total + tmp[0]
+        i = i + 1
+    }
+    return total
+}
+
+main: () -> Int = {
+    kept = [40, 2]
+    inner = churn(100)
+    shifted = list.map([1, 2], x => x + churn(3))
+    return kept[0] + kept[1] + inner - 4950 + shifted[1] - 5
+}
+"#;
+    for dispatch in [DispatchMode::Direct, DispatchMode::Step] {
+        let interp = run(
+            source,
+            ExecutorConfig {
+                dispatch,
+                gc_threshold: 1,
+                ..ExecutorConfig::default()
+            },
+        );
+        assert_eq!(interp.state().exit_code, 42, "{:?}", dispatch);
+    }
+}
+
+#[test]
+fn test_gc_collect_in_native_callback_frees_nothing() {
+    let source = r#"
+use std.{list, gc}
+
+make: () -> Int = {
+    items = [0]
+    f = () => items
+    list.push(items, f)
+    return 0
+}
+
+main: () -> Int = {
+    make()
+    in_callback = list.map([0], x => gc.collect())
+    return in_callback[0] * 100 + gc.collect()
+}
+"#;
+    let interp = run(source, threshold(0));
+    let code = interp.state().exit_code;
+    assert!(code > 0 && code < 100, "{}", code);
+}
+
+#[test]
+fn test_user_function_named_collect_is_not_gc_collect() {
+    let source = r#"
+collect: (n: Int) -> List(Int) = (n) => {
+    return [n, n + 1]
+}
+
+main: () -> Int = {
+    nums = collect(41)
+    return nums[1]
+}
+"#;
+    let interp = run(source, threshold(0));
+    assert_eq!(interp.state().exit_code, 42);
+}
@@ -0,0 +1,229 @@
+//! 运行期模块导入测试
+//!
+//! 测试覆盖内容：
+//! - 导入的公开函数可通过命名空间调用，私有函数不导出
+//! - 导入模块的函数与宿主函数同名时互不干扰
+//! - 同一文件只编译链接一次
+//! - 默认能力策略禁止导入；策略可限制导入目录
+//! - 导入模块中对自身结构体的 `as?` 与构造使用同一限定名
+
+use crate::backends::common::{HeapValue, RuntimeValue};
+use crate::backends::{CapabilityPolicy, ExecutorConfig};
+use crate::backends::interpreter::executor::Interpreter;
+use crate::middle::bytecode::{BytecodeFunction, BytecodeInstr};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+const GREETER: &str = r#"
+pub greet: (name: String) -> String = (name) => {
+    return "hello, " + name
+}
+
+shout: (name: String) -> String = (name) => {
+    return name + "!"
+}
+
+pub loud: (name: String) -> String = (name) => {
+    return shout(greet(name))
+}
+"#;
+
+/// 授予动态导入能力的解释器（默认策略拒绝导入）
+fn importer() -> Interpreter {
+    Interpreter::with_config(ExecutorConfig {
+        capabilities: CapabilityPolicy::allow_all(),
+        ..ExecutorConfig::default()
+    })
+}
+
+fn write_greeter(dir: &tempfile::TempDir) {
+    std::fs::write(dir.path().join("greeter.yx"), GREETER).expect("write module");
+}
+
+/// 从命名空间中取出导出函数并调用
+fn call_export(
+    interp: &mut Interpreter,
+    namespace: &RuntimeValue,
+    name: &str,
+    arg: &str,
+) -> RuntimeValue {
+    let RuntimeValue::Dict(handle) = namespace else {
+        panic!("namespace should be a dict, got {namespace:?}");
+    };
+    let Some(HeapValue::Dict(map)) = interp.heap.get(*handle) else {
+        panic!("namespace handle should point to a dict");
+    };
+    let Some(RuntimeValue::Function(func)) = map.get(&RuntimeValue::String(name.into())) else {
+        panic!("'{name}' should be exported");
+    };
+    let func_id = func.func_id;
+    interp
+        .call_function_by_id(func_id, &[RuntimeValue::String(arg.into())])
+        .expect("call exported function")
+}
+
+fn namespace_len(
+    interp: &Interpreter,
+    namespace: &RuntimeValue,
+) -> usize {
+    match namespace {
+        RuntimeValue::Dict(handle) => interp.heap.get(*handle).map_or(0, |v| v.len()),
+        _ => 0,
+    }
+}
+
+#[test]
+fn test_import_exposes_public_functions() {
+    // Arrange
+    let dir = tempfile::TempDir::new().expect("create temp dir");
+    write_greeter(&dir);
+    let mut interp = importer();
+    interp.set_import_base_dir(dir.path());
+
+    // Act
+    let namespace = interp.import_module("greeter.yx").expect("import greeter");
+
+    // Assert
+    assert_eq!(namespace_len(&interp, &namespace), 2);
+    assert_eq!(
+        call_export(&mut interp, &namespace, "greet", "bob"),
+        RuntimeValue::String("hello, bob".into())
+    );
+    assert_eq!(
+        call_export(&mut interp, &namespace, "loud", "amy"),
+        RuntimeValue::String("hello, amy!".into())
+    );
+}
+
+#[test]
+fn test_imported_functions_do_not_clash_with_host() {
+    // Arrange: 宿主已有同名函数 shout
+    let dir = tempfile::TempDir::new().expect("create temp dir");
+    write_greeter(&dir);
+    let mut interp = importer();
+    interp.set_import_base_dir(dir.path());
+    let host_shout = BytecodeFunction {
+        name: "shout".to_string(),
+        params: vec![],
+        return_type: crate::middle::core::ir::Type::Void,
+        local_count: 1,
+        upvalue_count: 0,
+        instructions: vec![BytecodeInstr::Return],
+        labels: HashMap::new(),
+        exception_handlers: vec![],
+        line_table: crate::util::span::LineTable::new(),
+        decoded: Default::default(),
+    };
+    let host_shout = Arc::new(host_shout);
+    interp
+        .functions
+        .insert("shout".to_string(), host_shout.clone());
+    interp.functions_by_id.push(host_shout);
+
+    // Act
+    let namespace = interp.import_module("greeter.yx").expect("import greeter");
+
+    // Assert: 模块内对 shout 的调用仍指向模块自己的实现
+    assert_eq!(
+        call_export(&mut interp, &namespace, "loud", "cat"),
+        RuntimeValue::String("hello, cat!".into())
+    );
+    assert_eq!(interp.functions["shout"].instructions.len(), 1);
+}
+
+#[test]
+fn test_import_links_each_file_once() {
+    // Arrange
+    let dir = tempfile::TempDir::new().expect("create temp dir");
+    write_greeter(&dir);
+    let mut interp = importer();
+    interp.set_import_base_dir(dir.path());
+
+    // Act
+    let first = interp.import_module("greeter.yx").expect("first import");
+    let linked = interp.functions_by_id.len();
+    let second = interp.import_module("./greeter.yx").expect("second import");
+
+    // Assert
+    assert_eq!(interp.functions_by_id.len(), linked);
+    assert_eq!(namespace_len(&interp, &second), 2);
+    assert_ne!(first, second, "each import returns a fresh namespace");
+}
+
+#[test]
+fn test_import_denied_by_default_policy() {
+    // Arrange
+    let dir = tempfile::TempDir::new().expect("create temp dir");
+    write_greeter(&dir);
+    let mut interp = Interpreter::new();
+    interp.set_import_base_dir(dir.path());
+
+    // Act
+    let err = interp.import_module("greeter.yx").unwrap_err();
+
+    // Assert
+    assert!(err.to_string().contains("capability policy"), "{err}");
+    assert!(interp.functions_by_id.is_empty());
+}
+
+#[test]
+fn test_import_outside_allowed_roots_is_rejected() {
+    // Arrange
+    let allowed = tempfile::TempDir::new().expect("create allowed dir");
+    let other = tempfile::TempDir::new().expect("create other dir");
+    write_greeter(&other);
+    let mut interp = Interpreter::with_config(ExecutorConfig {
+        capabilities: CapabilityPolicy {
+            dynamic_import: true,
+            import_roots: vec![allowed.path().to_path_buf()],
+            native_extensions: false,
+        },
+        ..ExecutorConfig::default()
+    });
+
+    // Act
+    let path = other.path().join("greeter.yx");
+    let err = interp
+        .import_module(&path.display().to_string())
+        .unwrap_err();
+
+    // Assert
+    assert!(err.to_string().contains("allowed import roots"), "{err}");
+}
+
+#[test]
+fn test_imported_struct_downcast_uses_qualified_name() {
+    // Arrange: Point 有方法，导入后以限定名创建
+    let dir = tempfile::TempDir::new().expect("create temp dir");
+    std::fs::write(
+        dir.path().join("shapes.yx"),
+        r#"
+Point: Type = { x: Int, y: Int }
+
+Point.sum: (self: Point) -> Int = (self) => {
+    return self.x + self.y
+}
+
+pub describe: (tag: String) -> String = (tag) => {
+    v: Any = Point(1, 2)
+    p = v as? Point
+    if p != void {
+        return tag + ": point"
+    }
+    return tag + ": other"
+}
+"#,
+    )
+    .expect("write module");
+    let mut interp = importer();
+    interp.set_import_base_dir(dir.path());
+
+    // Act
+    let namespace = interp.import_module("shapes.yx").expect("import shapes");
+
+    // Assert
+    assert_eq!(
+        call_export(&mut interp, &namespace, "describe", "v"),
+        RuntimeValue::String("v: point".into())
+    );
+}
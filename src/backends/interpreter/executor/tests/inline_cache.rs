@@ -0,0 +1,184 @@
+//! 内联缓存测试
+//!
+//! 测试覆盖内容：
+//! - 按名读取字段：同一类型再次访问命中缓存的槽位
+//! - 结构体布局重新注册后缓存失效，读取新布局的槽位
+//! - 虚表分派：同一虚表再次调用命中缓存的目标函数
+//! - 关闭 `inline_cache` 时不缓存
+
+use crate::backends::{Executor, ExecutorConfig};
+use crate::backends::common::{HeapValue, RuntimeValue};
+use crate::middle::bytecode::{BytecodeFunction, BytecodeInstr, ConstValue, Reg};
+use std::collections::HashMap;
+use std::sync::Arc;
+use crate::backends::interpreter::executor::Interpreter;
+
+fn make_function(
+    name: &str,
+    instrs: Vec<BytecodeInstr>,
+) -> BytecodeFunction {
+    BytecodeFunction {
+        name: name.to_string(),
+        params: vec![],
+        return_type: crate::middle::core::ir::Type::Void,
+        local_count: 4,
+        upvalue_count: 0,
+        instructions: instrs,
+        labels: HashMap::new(),
+        exception_handlers: vec![],
+        line_table: crate::util::span::LineTable::new(),
+        decoded: Default::default(),
+    }
+}
+
+/// 读取参数的 `y` 字段
+fn read_y() -> BytecodeFunction {
+    make_function(
+        "read_y",
+        vec![
+            BytecodeInstr::LoadArg {
+                dst: Reg(0),
+                arg_idx: 0,
+            },
+            BytecodeInstr::GetRecordField {
+                dst: Reg(1),
+                src: Reg(0),
+                field: "y".to_string(),
+            },
+            BytecodeInstr::ReturnValue { value: Reg(1) },
+        ],
+    )
+}
+
+/// 注册 `Point { x, y }` 并构造 `Point(1, 2)`
+fn make_point(interp: &mut Interpreter) -> RuntimeValue {
+    let type_id =
+        interp
+            .struct_types
+            .register("Point", vec!["x".to_string(), "y".to_string()], vec![0, 8]);
+    let fields = interp.heap.allocate(HeapValue::tuple([
+        RuntimeValue::Int(1),
+        RuntimeValue::Int(2),
+    ]));
+    RuntimeValue::Struct {
+        type_id,
+        fields,
+        vtable: Default::default(),
+    }
+}
+
+#[test]
+fn test_record_field_slot_cached() {
+    let mut interp = Interpreter::new();
+    let point = make_point(&mut interp);
+    let func = read_y();
+    for _ in 0..3 {
+        let result = interp
+            .execute_function(&func, std::slice::from_ref(&point))
+            .unwrap();
+        assert_eq!(result, RuntimeValue::Int(2));
+    }
+    assert_eq!(interp.inline_caches.misses, 1);
+    assert_eq!(interp.inline_caches.hits, 2);
+}
+
+#[test]
+fn test_layout_change_invalidates_field_slot() {
+    let mut interp = Interpreter::new();
+    let point = make_point(&mut interp);
+    let func = read_y();
+    let result = interp
+        .execute_function(&func, std::slice::from_ref(&point))
+        .unwrap();
+    assert_eq!(result, RuntimeValue::Int(2));
+
+    // 同名重新注册保留类型 id，但 `y` 换到了第一个槽位
+    interp
+        .struct_types
+        .register("Point", vec!["y".to_string(), "x".to_string()], vec![0, 8]);
+    let result = interp
+        .execute_function(&func, std::slice::from_ref(&point))
+        .unwrap();
+    assert_eq!(result, RuntimeValue::Int(1));
+    assert_eq!(interp.inline_caches.hits, 0);
+}
+
+#[test]
+fn test_vtable_call_target_cached() {
+    let mut method = make_function(
+        "Meter.value",
+        vec![
+            BytecodeInstr::LoadArg {
+                dst: Reg(0),
+                arg_idx: 0,
+            },
+            BytecodeInstr::ReturnValue { value: Reg(0) },
+        ],
+    );
+    method.params = vec![crate::middle::core::ir::Type::Int(64)];
+    let func = make_function(
+        "invoke",
+        vec![
+            BytecodeInstr::LoadConst {
+                dst: Reg(0),
+                const_idx: 0,
+            },
+            BytecodeInstr::MakeDyn {
+                dst: Reg(1),
+                src: Reg(0),
+                vtable: 0,
+            },
+            BytecodeInstr::InvokeVirtual {
+                dst: Some(Reg(2)),
+                obj: Reg(1),
+                slot: 0,
+                name_idx: 1,
+                args: vec![Reg(1)],
+            },
+            BytecodeInstr::ReturnValue { value: Reg(2) },
+        ],
+    );
+
+    let mut interp = Interpreter::new();
+    interp.constants.push(ConstValue::Int(41));
+    interp
+        .constants
+        .push(ConstValue::String("value".to_string()));
+    let method = Arc::new(method);
+    interp.functions.insert(method.name.clone(), method.clone());
+    interp.functions_by_id.push(method);
+    interp.vtables.push(crate::middle::core::ir::VTable {
+        interface: "Gauge".to_string(),
+        type_name: "Meter".to_string(),
+        methods: vec!["Meter.value".to_string()],
+    });
+
+    for _ in 0..2 {
+        let result = interp.execute_function(&func, &[]).unwrap();
+        assert_eq!(result, RuntimeValue::Int(41));
+    }
+    assert_eq!(interp.inline_caches.misses, 1);
+    assert_eq!(interp.inline_caches.hits, 1);
+
+    // 函数表变化后重新解析
+    interp.inline_caches.invalidate();
+    interp.execute_function(&func, &[]).unwrap();
+    assert_eq!(interp.inline_caches.misses, 2);
+}
+
+#[test]
+fn test_disabled_inline_cache_resolves_every_time() {
+    let mut interp = Interpreter::with_config(ExecutorConfig {
+        inline_cache: false,
+        ..Default::default()
+    });
+    let point = make_point(&mut interp);
+    let func = read_y();
+    for _ in 0..3 {
+        let result = interp
+            .execute_function(&func, std::slice::from_ref(&point))
+            .unwrap();
+        assert_eq!(result, RuntimeValue::Int(2));
+    }
+    assert_eq!(interp.inline_caches.hits, 0);
+}
@@ -0,0 +1,89 @@
+//! 堆内存上限测试
+//!
+//! 测试覆盖内容：
+//! - 不断增长的列表在超出 `max_heap_size` 时以 `HeapExhausted` 结束，带上限与调用栈
+//! - `HeapExhausted` 属于运行时 panic，`recover` 可以捕获，之后程序继续执行
+//! - 只产生垃圾的循环不会耗尽堆：接近上限时回收
+//! - 堆统计存活字节与累计分配字节
+
+use crate::backends::common::value::FunctionId;
+use crate::backends::common::RuntimeValue;
+use crate::backends::{Executor, ExecutorError};
+use crate::Engine;
+
+const LIMIT: usize = 64 * 1024;
+
+const SOURCE: &str = r#"
+use std.result
+
+grow: (n: Int) -> List(Int) = {
+    mut xs: List(Int) = [0]
+    for i in 0..n {
+        xs = xs + [i]
+    }
+    return xs
+}
+
+churn: (n: Int) -> Int = {
+    mut total = 0
+    for i in 0..n {
+        xs: List(Int) = [i, i, i, i, i, i, i, i]
+        total = total + xs[0]
+    }
+    return total
+}
+
+main: () -> Int = {
+    if is_err(recover(() => grow(1000000))) {
+        return 7
+    }
+    return 0
+}
+"#;
+
+fn engine() -> Engine {
+    Engine::builder().max_heap_size(LIMIT).build()
+}
+
+#[test]
+fn test_growing_list_exhausts_heap() {
+    let engine = engine();
+    let program = engine.compile("memory.yx", SOURCE).expect("compile");
+    let err = engine
+        .call(&program, "grow", &[RuntimeValue::Int(1_000_000)])
+        .unwrap_err();
+    assert!(
+        matches!(&err, ExecutorError::HeapExhausted(LIMIT, Some(stack))
+            if stack.iter().any(|f| f.function_name.ends_with("grow"))),
+        "{:?}",
+        err
+    );
+    assert!(err.is_panic());
+}
+
+#[test]
+fn test_heap_exhaustion_is_recoverable() {
+    let engine = engine();
+    let program = engine.compile("memory.yx", SOURCE).expect("compile");
+    let mut interp = engine.interpreter();
+    interp.execute_module(&program).expect("run");
+    assert_eq!(interp.state().exit_code, 7);
+}
+
+#[test]
+fn test_garbage_does_not_exhaust_heap() {
+    let engine = engine();
+    let program = engine.compile("memory.yx", SOURCE).expect("compile");
+    let mut interp = engine.interpreter();
+    interp.execute_module(&program).expect("load");
+    let id = program
+        .functions
+        .iter()
+        .position(|f| f.name == "churn")
+        .unwrap();
+    let result = interp.call_function_by_id(FunctionId(id as u32), &[RuntimeValue::Int(10_000)]);
+    assert_eq!(result, Ok(RuntimeValue::Int(49_995_000)));
+    let heap = interp.heap();
+    assert!(heap.bytes() <= LIMIT);
+    assert!(heap.allocated_bytes() > LIMIT as u64);
+}
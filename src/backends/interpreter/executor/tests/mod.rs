@@ -1,6 +1,15 @@
 //! 解释器执行器测试入口
 //!
-//! 包含 debug 和 execute 的测试模块。
+//! 包含 compiled、debug、decoded、dispatch、execute、fuel、gc、import、inline_cache 和 memory 的测试模块。
 
+#[cfg(all(target_arch = "x86_64", unix))]
+mod compiled;
 mod debug;
+mod decoded;
+mod dispatch;
 mod execute;
+mod fuel;
+mod gc;
+mod import;
+mod inline_cache;
+mod memory;
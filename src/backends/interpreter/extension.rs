@@ -0,0 +1,863 @@
+//! Native extension packages for YaoXiang
+//!
+//! A package can ship a companion cdylib that exposes typed native functions.
+//! When the package is vendored into a project, the runtime loads the library
+//! (subject to [`CapabilityPolicy::native_extensions`]) and registers every
+//! function under the package name, so `use sqlite` followed by
+//! `sqlite.open(path)` type-checks against the declared signature and
+//! dispatches straight into the extension.
+//!
+//! # ABI (version 3)
+//!
+//! The library exports one symbol:
+//!
+//! ```c
+//! const YxExtension *yaoxiang_extension(void);
+//! ```
+//!
+//! ```text
+//! YxExtension { abi_version, name, functions, function_count, state_new, state_drop }
+//!       │
+//!       └── YxFunctionDecl { name, signature, func }
+//!                 │
+//!                 └── int32_t func(const YxCall *call, const YxValue *args, size_t argc, YxValue *out)
+//! ```
+//!
+//! Signatures use YaoXiang syntax (e.g. `"(a: Int, b: String) -> Int"`) and
+//! may only mention `Int`, `Float`, `Bool`, `String`, `List` and `Void`; a
+//! `List` may carry an element type (`List(Int)`), which only the type
+//! checker looks at. Parameters may also be functions (`f: (x: Int) -> Int`).
+//! A function returns `0` on success; any other value is an error, and if
+//! `out` holds a string it is used as the error message.
+//!
+//! Lists and tuples cross the boundary as `ptr` pointing at `len` consecutive
+//! `YxValue`s; their elements may be any value, including `()` and `Bytes`.
+//! A function argument is an opaque `ptr` the extension passes to
+//! `call->invoke` to run it.
+//!
+//! # Per-VM State
+//!
+//! Each VM that calls into an extension gets its own state from `state_new`,
+//! handed to every call as `call->state` and released with `state_drop` when
+//! the VM is dropped. An extension keeps what a program opens there, so
+//! handles are private to one VM and nothing outlives it. Both hooks may be
+//! null, in which case `call->state` is null.
+//!
+//! Version 3 added `YxCall`, the state hooks and function arguments; version 2
+//! added lists, tuples and bytes. The layout of `YxValue` is unchanged from
+//! version 1.
+//!
+//! # Ownership
+//!
+//! Arguments, including the elements of list arguments, are borrowed for the
+//! duration of the call. Values returned through `out` stay owned by the
+//! extension and only need to remain valid until the function returns to the
+//! runtime, which copies them immediately (a thread-local buffer is
+//! sufficient). Likewise, the result `invoke` writes is owned by the runtime
+//! and valid until the extension function returns.
+
+use std::collections::HashMap;
+use std::ffi::{c_char, c_void, CStr};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+
+use libloading::Library;
+
+use crate::backends::common::{Heap, HeapValue, RuntimeValue};
+use crate::backends::interpreter::ffi::FfiRegistry;
+use crate::backends::{CapabilityPolicy, ExecutorError};
+use crate::frontend::module::{Export, ExportKind, ModuleInfo, ModuleSource};
+use crate::std::NativeContext;
+
+/// ABI version understood by this runtime
+pub const EXTENSION_ABI_VERSION: u32 = 3;
+
+/// Symbol every extension library must export
+pub const EXTENSION_ENTRY_SYMBOL: &str = "yaoxiang_extension";
+
+/// `YxValue.tag` for `Void`
+pub const YX_UNIT: u32 = 0;
+/// `YxValue.tag` for `Bool` (stored in `int` as 0/1)
+pub const YX_BOOL: u32 = 1;
+/// `YxValue.tag` for `Int`
+pub const YX_INT: u32 = 2;
+/// `YxValue.tag` for `Float`
+pub const YX_FLOAT: u32 = 3;
+/// `YxValue.tag` for `String` (UTF-8 bytes in `ptr`/`len`)
+pub const YX_STRING: u32 = 4;
+/// `YxValue.tag` for `Bytes` (raw bytes in `ptr`/`len`)
+pub const YX_BYTES: u32 = 5;
+/// `YxValue.tag` for a list (`len` elements of type `YxValue` at `ptr`)
+pub const YX_LIST: u32 = 6;
+/// `YxValue.tag` for a tuple (`len` elements of type `YxValue` at `ptr`)
+pub const YX_TUPLE: u32 = 7;
+/// `YxValue.tag` for a function argument (opaque `ptr`, run with `YxCall.invoke`)
+pub const YX_FUNCTION: u32 = 8;
+
+/// A value crossing the extension boundary.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct YxValue {
+    pub tag: u32,
+    pub int: i64,
+    pub float: f64,
+    pub ptr: *const u8,
+    pub len: usize,
+}
+
+impl YxValue {
+    fn unit() -> Self {
+        Self {
+            tag: YX_UNIT,
+            int: 0,
+            float: 0.0,
+            ptr: std::ptr::null(),
+            len: 0,
+        }
+    }
+}
+
+/// Native function entry point: `(call, args, argc, out) -> status`.
+pub type YxNativeFn =
+    unsafe extern "C" fn(*const YxCall, *const YxValue, usize, *mut YxValue) -> i32;
+
+/// Runs a function argument: `(host, function, args, argc, out) -> status`.
+///
+/// On failure `out` holds the error message as a string.
+pub type YxInvokeFn =
+    unsafe extern "C" fn(*mut c_void, *const YxValue, *const YxValue, usize, *mut YxValue) -> i32;
+
+/// Creates the extension's state for one VM.
+pub type YxStateNewFn = unsafe extern "C" fn() -> *mut c_void;
+
+/// Releases a VM's state.
+pub type YxStateDropFn = unsafe extern "C" fn(*mut c_void);
+
+/// What the runtime passes to every call of an extension function.
+#[repr(C)]
+pub struct YxCall {
+    /// The calling VM's state from `state_new` (null without state hooks)
+    pub state: *mut c_void,
+    /// Opaque runtime pointer, the first argument of `invoke`
+    pub host: *mut c_void,
+    /// Runs a function argument
+    pub invoke: YxInvokeFn,
+}
+
+/// One exported function in an extension descriptor.
+#[repr(C)]
+pub struct YxFunctionDecl {
+    pub name: *const c_char,
+    pub signature: *const c_char,
+    pub func: YxNativeFn,
+}
+
+/// Descriptor returned by `yaoxiang_extension()`.
+#[repr(C)]
+pub struct YxExtension {
+    pub abi_version: u32,
+    pub name: *const c_char,
+    pub functions: *const YxFunctionDecl,
+    pub function_count: usize,
+    pub state_new: Option<YxStateNewFn>,
+    pub state_drop: Option<YxStateDropFn>,
+}
+
+/// Value kinds allowed in extension signatures.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueKind {
+    Void,
+    Bool,
+    Int,
+    Float,
+    String,
+    List,
+    Function,
+}
+
+impl ValueKind {
+    fn parse(name: &str) -> Option<Self> {
+        if name.starts_with('(') && name.contains("->") {
+            return parse_signature(name).map(|_| Self::Function);
+        }
+        match name {
+            "Void" => Some(Self::Void),
+            "Bool" => Some(Self::Bool),
+            "Int" => Some(Self::Int),
+            "Float" => Some(Self::Float),
+            "String" => Some(Self::String),
+            "List" => Some(Self::List),
+            _ => {
+                let element = name.strip_prefix("List(")?.strip_suffix(')')?;
+                (!element.trim().is_empty()).then_some(Self::List)
+            }
+        }
+    }
+}
+
+/// Split `text` at `separator` where it is not nested inside parentheses.
+fn split_top_level(
+    text: &str,
+    separator: char,
+) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, c) in text.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            c if c == separator && depth == 0 => {
+                parts.push(&text[start..i]);
+                start = i + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    parts.push(&text[start..]);
+    parts
+}
+
+/// Parse `"(a: Int, b: String) -> Int"` into parameter and return kinds.
+fn parse_signature(signature: &str) -> Option<(Vec<ValueKind>, ValueKind)> {
+    let rest = signature.trim().strip_prefix('(')?;
+    // 参数表在与开头括号配对的 `)` 处结束，参数类型里可以嵌套括号
+    let mut depth = 1usize;
+    let close = rest.char_indices().find_map(|(i, c)| {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            _ => {}
+        }
+        (depth == 0).then_some(i)
+    })?;
+    let (params, ret) = (&rest[..close], &rest[close + 1..]);
+    let ret = ret.trim().strip_prefix("->")?.trim();
+    let mut kinds = Vec::new();
+    for param in split_top_level(params, ',')
+        .into_iter()
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+    {
+        let (_, ty) = param.split_once(':')?;
+        let kind = ValueKind::parse(ty.trim())?;
+        if kind == ValueKind::Void {
+            return None;
+        }
+        kinds.push(kind);
+    }
+    let ret = ValueKind::parse(ret)?;
+    (ret != ValueKind::Function).then_some((kinds, ret))
+}
+
+/// A function exported by a loaded extension.
+#[derive(Clone)]
+pub struct ExtensionFunction {
+    /// Short name (e.g. `"open"`)
+    pub name: String,
+    /// Qualified name (e.g. `"sqlite.open"`)
+    pub qualified_name: String,
+    /// Declared signature (e.g. `"(path: String) -> Int"`)
+    pub signature: String,
+    params: Vec<ValueKind>,
+    ret: ValueKind,
+    func: YxNativeFn,
+    /// State hooks of the extension, if it keeps per-VM state
+    state: Option<StateHooks>,
+    /// Keeps the library mapped while the function is reachable
+    library: Arc<Library>,
+}
+
+#[derive(Clone, Copy)]
+struct StateHooks {
+    new: Option<YxStateNewFn>,
+    drop: Option<YxStateDropFn>,
+}
+
+/// The states extensions keep for one VM, released when the VM is dropped.
+///
+/// Lives in the VM's [`NativeResources`](crate::std::NativeResources).
+#[derive(Default)]
+pub struct ExtensionStates {
+    /// State per extension library
+    states: Mutex<HashMap<usize, VmState>>,
+}
+
+struct VmState {
+    ptr: *mut c_void,
+    drop: Option<YxStateDropFn>,
+    /// The library must stay mapped until `drop` has run
+    _library: Arc<Library>,
+}
+
+// SAFETY: the state is only used by the extension's own functions, which the
+// ABI requires to be callable from any thread
+unsafe impl Send for VmState {}
+
+impl Drop for VmState {
+    fn drop(&mut self) {
+        if let Some(drop) = self.drop {
+            // SAFETY: `ptr` came from this library's `state_new` and is released once
+            unsafe { drop(self.ptr) };
+        }
+    }
+}
+
+impl ExtensionStates {
+    /// This VM's state for the extension of `function`, created on first use.
+    fn get(
+        &self,
+        function: &ExtensionFunction,
+        hooks: StateHooks,
+    ) -> *mut c_void {
+        let mut states = self.states.lock().unwrap_or_else(|e| e.into_inner());
+        let key = Arc::as_ptr(&function.library) as usize;
+        states
+            .entry(key)
+            .or_insert_with(|| VmState {
+                // SAFETY: the hook takes no arguments; its result is owned by this entry
+                ptr: hooks
+                    .new
+                    .map_or(std::ptr::null_mut(), |new| unsafe { new() }),
+                drop: hooks.drop,
+                _library: function.library.clone(),
+            })
+            .ptr
+    }
+}
+
+impl std::fmt::Debug for ExtensionFunction {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        f.debug_struct("ExtensionFunction")
+            .field("qualified_name", &self.qualified_name)
+            .field("signature", &self.signature)
+            .finish()
+    }
+}
+
+impl ExtensionFunction {
+    /// Marshal `args`, invoke the native function and convert its result.
+    ///
+    /// List arguments are read from the heap, and lists and tuples in the
+    /// result are allocated on it. Function arguments are run through `ctx`,
+    /// and the extension's state is the one of the VM `ctx` belongs to.
+    pub fn call(
+        &self,
+        args: &[RuntimeValue],
+        ctx: &mut NativeContext<'_>,
+    ) -> Result<RuntimeValue, ExecutorError> {
+        if args.len() != self.params.len() {
+            return Err(ExecutorError::runtime_only(format!(
+                "{} expects {} argument(s), got {}",
+                self.qualified_name,
+                self.params.len(),
+                args.len()
+            )));
+        }
+
+        // 参数字符串与列表元素在调用期间由 args 和 lowered 借出，YxValue 只持有指针
+        let mut lowered = Lowered::default();
+        let mut raw_args = Vec::with_capacity(args.len());
+        for (arg, kind) in args.iter().zip(&self.params) {
+            let raw = match (kind, arg) {
+                // 函数值不复制，扩展拿到的是指向 args 中该值的不透明指针
+                (ValueKind::Function, RuntimeValue::Function(_)) => Some(YxValue {
+                    tag: YX_FUNCTION,
+                    ptr: (arg as *const RuntimeValue).cast(),
+                    ..YxValue::unit()
+                }),
+                (ValueKind::Bool, RuntimeValue::Bool(_))
+                | (ValueKind::Int, RuntimeValue::Int(_))
+                | (ValueKind::Float, RuntimeValue::Float(_))
+                | (ValueKind::String, RuntimeValue::String(_))
+                | (ValueKind::List, RuntimeValue::List(_)) => {
+                    lower_value(arg, ctx.heap, &mut lowered)
+                }
+                _ => None,
+            };
+            let raw = raw.ok_or_else(|| {
+                ExecutorError::runtime_only(format!(
+                    "{}: argument {:?} does not match signature {}",
+                    self.qualified_name, arg, self.signature
+                ))
+            })?;
+            raw_args.push(raw);
+        }
+
+        let state = match self.state {
+            Some(hooks) => ctx.resource::<ExtensionStates>()?.get(self, hooks),
+            None => std::ptr::null_mut(),
+        };
+        let mut host = Host {
+            ctx,
+            kept: Lowered::default(),
+            error: None,
+        };
+        let call = YxCall {
+            state,
+            host: (&mut host as *mut Host<'_, '_>).cast(),
+            invoke,
+        };
+        let mut out = YxValue::unit();
+        let status = unsafe { (self.func)(&call, raw_args.as_ptr(), raw_args.len(), &mut out) };
+        let result = unsafe { read_value(&out, host.ctx.heap) }?;
+
+        if status != 0 {
+            // 函数参数出错时报告原始错误，而不是扩展转述后的消息
+            if let Some(error) = host.error {
+                return Err(error);
+            }
+            let detail = match result {
+                Some(RuntimeValue::String(msg)) => msg.to_string(),
+                _ => format!("status {status}"),
+            };
+            return Err(ExecutorError::runtime_only(format!(
+                "{} failed: {detail}",
+                self.qualified_name
+            )));
+        }
+
+        match (self.ret, result) {
+            (ValueKind::Void, _) => Ok(RuntimeValue::Unit),
+            (ValueKind::Bool, Some(v @ RuntimeValue::Bool(_)))
+            | (ValueKind::Int, Some(v @ RuntimeValue::Int(_)))
+            | (ValueKind::Float, Some(v @ RuntimeValue::Float(_)))
+            | (ValueKind::String, Some(v @ RuntimeValue::String(_)))
+            | (ValueKind::List, Some(v @ RuntimeValue::List(_))) => Ok(v),
+            (_, other) => Err(ExecutorError::runtime_only(format!(
+                "{} returned {:?}, expected {:?}",
+                self.qualified_name, other, self.ret
+            ))),
+        }
+    }
+}
+
+/// Runtime side of one extension call, reached from `invoke` through `YxCall.host`
+struct Host<'c, 'a> {
+    ctx: &'c mut NativeContext<'a>,
+    /// Results of function arguments, kept until the extension returns
+    kept: Lowered,
+    /// First error raised by a function argument
+    error: Option<ExecutorError>,
+}
+
+/// `YxCall.invoke`: run a function argument with `args`, writing its result to `out`.
+unsafe extern "C" fn invoke(
+    host: *mut c_void,
+    function: *const YxValue,
+    args: *const YxValue,
+    argc: usize,
+    out: *mut YxValue,
+) -> i32 {
+    // SAFETY: `host` is the `Host` of the call in progress, and the extension
+    // passes the pointers the ABI describes
+    let host = unsafe { &mut *host.cast::<Host<'_, '_>>() };
+    let function = unsafe { &*function };
+    let args: &[YxValue] = if args.is_null() {
+        &[]
+    } else {
+        unsafe { std::slice::from_raw_parts(args, argc) }
+    };
+    let result = (|| {
+        if function.tag != YX_FUNCTION || function.ptr.is_null() {
+            return Err(ExecutorError::runtime_only(
+                "invoke: the callee is not a function argument".to_string(),
+            ));
+        }
+        // SAFETY: function arguments point at a value in the arguments of the call
+        let callee = unsafe { &*function.ptr.cast::<RuntimeValue>() };
+        let mut values = Vec::with_capacity(args.len());
+        for arg in args {
+            match unsafe { read_value(arg, host.ctx.heap) }? {
+                Some(value) => values.push(value),
+                None => {
+                    return Err(ExecutorError::runtime_only(format!(
+                        "invoke: unsupported value tag {}",
+                        arg.tag
+                    )))
+                }
+            }
+        }
+        let value = host.ctx.call_function(callee, &values)?;
+        let raw = lower_value(&value, host.ctx.heap, &mut host.kept).ok_or_else(|| {
+            ExecutorError::runtime_only(format!(
+                "invoke: {value:?} cannot be returned to a native extension"
+            ))
+        })?;
+        host.kept.values.push(value);
+        Ok(raw)
+    })();
+    match result {
+        Ok(raw) => {
+            unsafe { *out = raw };
+            0
+        }
+        Err(error) => {
+            let message = RuntimeValue::String(Arc::from(error.to_string().as_str()));
+            let raw = lower_value(&message, host.ctx.heap, &mut host.kept);
+            unsafe { *out = raw.unwrap_or_else(YxValue::unit) };
+            host.kept.values.push(message);
+            host.error.get_or_insert(error);
+            1
+        }
+    }
+}
+
+/// Storage backing lowered values until the extension call returns
+#[derive(Default)]
+struct Lowered {
+    /// Arrays of list and tuple elements
+    arrays: Vec<Vec<YxValue>>,
+    /// Values whose strings and bytes the lowered values point into
+    values: Vec<RuntimeValue>,
+}
+
+/// Describe a runtime value as a `YxValue` borrowing its strings and elements.
+///
+/// The arrays backing lists and tuples, and the list elements the strings
+/// point into, are kept in `lowered`, which must outlive the returned value.
+/// Returns `None` for values that cannot cross the extension boundary.
+fn lower_value(
+    value: &RuntimeValue,
+    heap: &Heap,
+    lowered: &mut Lowered,
+) -> Option<YxValue> {
+    let mut raw = YxValue::unit();
+    match value {
+        RuntimeValue::Unit => {}
+        RuntimeValue::Bool(b) => {
+            raw.tag = YX_BOOL;
+            raw.int = *b as i64;
+        }
+        RuntimeValue::Int(n) => {
+            raw.tag = YX_INT;
+            raw.int = *n;
+        }
+        RuntimeValue::Float(x) => {
+            raw.tag = YX_FLOAT;
+            raw.float = *x;
+        }
+        RuntimeValue::String(s) => {
+            raw.tag = YX_STRING;
+            raw.ptr = s.as_ptr();
+            raw.len = s.len();
+        }
+        RuntimeValue::Bytes(b) => {
+            raw.tag = YX_BYTES;
+            raw.ptr = b.as_ptr();
+            raw.len = b.len();
+        }
+        RuntimeValue::List(handle) | RuntimeValue::Tuple(handle) => {
+            let (tag, items) = match heap.get(*handle)? {
+                HeapValue::List(items) => (YX_LIST, items),
+                HeapValue::Tuple(items) => (YX_TUPLE, items),
+                _ => return None,
+            };
+            let mut elements = Vec::with_capacity(items.len());
+            for item in items {
+                let item = item.view();
+                elements.push(lower_value(&item, heap, lowered)?);
+                // 元素的字符串可能随列表被修改而释放，这里保留一份引用
+                lowered.values.push(item.into_owned());
+            }
+            raw.tag = tag;
+            // 移动 Vec 不会移动其缓冲区，指针在 lowered 释放前一直有效
+            raw.ptr = elements.as_ptr().cast();
+            raw.len = elements.len();
+            lowered.arrays.push(elements);
+        }
+        _ => return None,
+    }
+    Some(raw)
+}
+
+/// Convert a `YxValue` written by an extension into a runtime value,
+/// allocating its lists and tuples on `heap`.
+///
+/// Returns `Ok(None)` for an unknown tag.
+///
+/// # Safety
+///
+/// String and bytes values must point to `len` readable bytes, lists and
+/// tuples to `len` readable `YxValue`s.
+unsafe fn read_value(
+    value: &YxValue,
+    heap: &mut Heap,
+) -> Result<Option<RuntimeValue>, ExecutorError> {
+    let bytes = || {
+        if value.ptr.is_null() {
+            &[][..]
+        } else {
+            unsafe { std::slice::from_raw_parts(value.ptr, value.len) }
+        }
+    };
+    Ok(match value.tag {
+        YX_UNIT => Some(RuntimeValue::Unit),
+        YX_BOOL => Some(RuntimeValue::Bool(value.int != 0)),
+        YX_INT => Some(RuntimeValue::Int(value.int)),
+        YX_FLOAT => Some(RuntimeValue::Float(value.float)),
+        YX_STRING => Some(RuntimeValue::String(Arc::from(
+            String::from_utf8_lossy(bytes()).as_ref(),
+        ))),
+        YX_BYTES => Some(RuntimeValue::Bytes(Arc::from(bytes()))),
+        YX_LIST | YX_TUPLE => {
+            let raw_items: &[YxValue] = if value.ptr.is_null() {
+                &[]
+            } else {
+                unsafe { std::slice::from_raw_parts(value.ptr.cast(), value.len) }
+            };
+            let mut items = Vec::with_capacity(raw_items.len());
+            for item in raw_items {
+                match unsafe { read_value(item, heap) }? {
+                    Some(item) => items.push(item.into()),
+                    None => return Ok(None),
+                }
+            }
+            Some(if value.tag == YX_LIST {
+                RuntimeValue::List(heap.try_allocate(HeapValue::List(items))?)
+            } else {
+                RuntimeValue::Tuple(heap.try_allocate(HeapValue::Tuple(items))?)
+            })
+        }
+        _ => None,
+    })
+}
+
+/// Read a NUL-terminated string from an extension descriptor.
+///
+/// # Safety
+///
+/// `ptr` must be null or point to a NUL-terminated string.
+unsafe fn read_cstr(
+    ptr: *const c_char,
+    what: &str,
+    path: &Path,
+) -> Result<String, ExecutorError> {
+    if ptr.is_null() {
+        return Err(ExecutorError::runtime_only(format!(
+            "native extension {}: missing {what}",
+            path.display()
+        )));
+    }
+    unsafe { CStr::from_ptr(ptr) }
+        .to_str()
+        .map(str::to_string)
+        .map_err(|_| {
+            ExecutorError::runtime_only(format!(
+                "native extension {}: {what} is not valid UTF-8",
+                path.display()
+            ))
+        })
+}
+
+/// A loaded native extension library.
+#[derive(Debug)]
+pub struct NativeExtension {
+    name: String,
+    path: PathBuf,
+    functions: Vec<ExtensionFunction>,
+}
+
+impl NativeExtension {
+    /// Load an extension library and validate its descriptor.
+    ///
+    /// Fails if the entry symbol is missing, the ABI version differs from
+    /// [`EXTENSION_ABI_VERSION`], or a signature uses an unsupported type.
+    pub fn load(path: &Path) -> Result<Self, ExecutorError> {
+        let library = Arc::new(unsafe { Library::new(path) }.map_err(|e| {
+            ExecutorError::runtime_only(format!(
+                "failed to load native extension {}: {e}",
+                path.display()
+            ))
+        })?);
+
+        type EntryFn = unsafe extern "C" fn() -> *const YxExtension;
+        let descriptor = unsafe {
+            let entry: libloading::Symbol<'_, EntryFn> = library
+                .get(EXTENSION_ENTRY_SYMBOL.as_bytes())
+                .map_err(|e| {
+                    ExecutorError::runtime_only(format!(
+                        "native extension {} does not export {EXTENSION_ENTRY_SYMBOL}: {e}",
+                        path.display()
+                    ))
+                })?;
+            entry()
+        };
+        let descriptor = unsafe { descriptor.as_ref() }.ok_or_else(|| {
+            ExecutorError::runtime_only(format!(
+                "native extension {} returned a null descriptor",
+                path.display()
+            ))
+        })?;
+
+        if descriptor.abi_version != EXTENSION_ABI_VERSION {
+            return Err(ExecutorError::runtime_only(format!(
+                "native extension {} targets ABI version {}, runtime supports {}",
+                path.display(),
+                descriptor.abi_version,
+                EXTENSION_ABI_VERSION
+            )));
+        }
+
+        let name = unsafe { read_cstr(descriptor.name, "name", path) }?;
+        let decls: &[YxFunctionDecl] = if descriptor.functions.is_null()
+            || descriptor.function_count == 0
+        {
+            &[]
+        } else {
+            unsafe { std::slice::from_raw_parts(descriptor.functions, descriptor.function_count) }
+        };
+
+        let state = (descriptor.state_new.is_some() || descriptor.state_drop.is_some()).then_some(
+            StateHooks {
+                new: descriptor.state_new,
+                drop: descriptor.state_drop,
+            },
+        );
+        let mut functions = Vec::with_capacity(decls.len());
+        for decl in decls {
+            let fn_name = unsafe { read_cstr(decl.name, "function name", path) }?;
+            let signature = unsafe { read_cstr(decl.signature, "function signature", path) }?;
+            let (params, ret) = parse_signature(&signature).ok_or_else(|| {
+                ExecutorError::runtime_only(format!(
+                    "native extension {name}: unsupported signature for {fn_name}: {signature}"
+                ))
+            })?;
+            functions.push(ExtensionFunction {
+                qualified_name: format!("{name}.{fn_name}"),
+                name: fn_name,
+                signature,
+                params,
+                ret,
+                func: decl.func,
+                state,
+                library: library.clone(),
+            });
+        }
+
+        Ok(Self {
+            name,
+            path: path.to_path_buf(),
+            functions,
+        })
+    }
+
+    /// Extension (module) name declared by the library.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Path the library was loaded from.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Functions exported by the extension.
+    pub fn functions(&self) -> &[ExtensionFunction] {
+        &self.functions
+    }
+
+    /// Module description for the frontend module system.
+    pub fn module_info(&self) -> ModuleInfo {
+        let mut module = ModuleInfo::new(self.name.clone(), ModuleSource::Native);
+        for function in &self.functions {
+            module.add_export(Export {
+                name: function.name.clone(),
+                full_path: function.qualified_name.clone(),
+                kind: ExportKind::Function,
+                signature: function.signature.clone(),
+            });
+        }
+        module
+    }
+}
+
+/// Extensions installed into this process.
+///
+/// The compiler and every interpreter consult this store, so extensions must
+/// be installed before compiling the program that uses them.
+static INSTALLED: RwLock<Vec<Arc<NativeExtension>>> = RwLock::new(Vec::new());
+
+/// Install a loaded extension, replacing any previous one with the same name.
+pub fn install(extension: NativeExtension) {
+    let mut installed = INSTALLED.write().unwrap_or_else(|e| e.into_inner());
+    installed.retain(|ext| ext.name != extension.name);
+    installed.push(Arc::new(extension));
+}
+
+/// All installed extensions.
+pub fn installed() -> Vec<Arc<NativeExtension>> {
+    INSTALLED.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Module descriptions of all installed extensions.
+pub fn module_infos() -> Vec<ModuleInfo> {
+    installed().iter().map(|ext| ext.module_info()).collect()
+}
+
+/// Register the functions of all installed extensions into `registry`.
+pub fn register_installed(registry: &mut FfiRegistry) {
+    for ext in installed() {
+        for function in ext.functions() {
+            registry.register_extension(function.clone());
+        }
+    }
+}
+
+/// Load and install the native extensions of every vendored dependency of
+/// the project at `project_dir`.
+///
+/// Returns the names of the installed extensions. Fails if a dependency
+/// declares a native library while the policy denies native extensions, or
+/// if the library's declared name differs from its package name.
+pub fn load_project_extensions(
+    project_dir: &Path,
+    policy: &CapabilityPolicy,
+) -> Result<Vec<String>, ExecutorError> {
+    let vendor = crate::package::vendor::VendorManager::new(project_dir);
+    let libraries = vendor
+        .native_libraries()
+        .map_err(|e| ExecutorError::runtime_only(e.to_string()))?;
+
+    let mut names = Vec::with_capacity(libraries.len());
+    for (package, library) in libraries {
+        if !policy.native_extensions {
+            return Err(ExecutorError::runtime_only(format!(
+                "Cannot load native extension of package '{package}': native extensions are disabled by the capability policy"
+            )));
+        }
+        let extension = NativeExtension::load(&library)?;
+        if extension.name() != package {
+            return Err(ExecutorError::runtime_only(format!(
+                "native extension {} declares name '{}', expected package name '{package}'",
+                library.display(),
+                extension.name()
+            )));
+        }
+        names.push(package);
+        install(extension);
+    }
+    Ok(names)
+}
+
+/// Load the native extensions of the project that contains `file`.
+///
+/// Walks up from the file's directory to the nearest `yaoxiang.toml`; files
+/// outside a project load nothing.
+pub fn load_for_file(
+    file: &Path,
+    policy: &CapabilityPolicy,
+) -> Result<Vec<String>, ExecutorError> {
+    let file = std::path::absolute(file).unwrap_or_else(|_| file.to_path_buf());
+    let project_dir = file
+        .ancestors()
+        .skip(1)
+        .find(|dir| dir.join(crate::package::manifest::MANIFEST_FILE).exists());
+    match project_dir {
+        Some(dir) => load_project_extensions(dir, policy),
+        None => Ok(Vec::new()),
+    }
+}
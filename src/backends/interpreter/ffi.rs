@@ -13,6 +13,10 @@
 //!       └── "c"  → FfiRegistry.call_c() → libloading → transmute → call
 //! ```
 //!
+//! Functions from native extension packages (see [`super::extension`]) and
+//! host functions registered by embedders (see [`super::host`]) are
+//! registered by qualified name and dispatched through `call()` as well.
+//!
 //! # Safety
 //!
 //! C ABI calls involve transmuting function pointer addresses obtained from
@@ -30,6 +34,9 @@ use std::collections::HashSet;
 #[cfg(not(target_arch = "wasm32"))]
 use std::sync::Arc;
 
+#[cfg(not(target_arch = "wasm32"))]
+use super::extension::ExtensionFunction;
+use super::host::HostFunction;
 use crate::backends::common::RuntimeValue;
 use crate::backends::ExecutorError;
 use crate::std::{NativeContext, NativeHandler};
@@ -52,9 +59,14 @@ use crate::std::{NativeContext, NativeHandler};
 pub struct FfiRegistry {
     /// Function handler table: name -> handler
     handlers: HashMap<String, NativeHandler>,
+    /// Host functions registered by the embedder: name -> function
+    hosts: HashMap<String, HostFunction>,
     /// Cached loaded libraries (lib_name -> Library)
     #[cfg(not(target_arch = "wasm32"))]
     loaded_libs: HashMap<String, Arc<Library>>,
+    /// Functions exported by native extension packages (qualified name -> function)
+    #[cfg(not(target_arch = "wasm32"))]
+    extensions: HashMap<String, ExtensionFunction>,
     /// Registered opaque type names
     opaque_types: HashSet<String>,
 }
@@ -70,6 +82,7 @@ impl std::fmt::Debug for FfiRegistry {
                 "registered_functions",
                 &self.handlers.keys().collect::<Vec<_>>(),
             )
+            .field("host_functions", &self.hosts.keys().collect::<Vec<_>>())
             .finish()
     }
 }
@@ -85,18 +98,24 @@ impl FfiRegistry {
     pub fn new() -> Self {
         Self {
             handlers: HashMap::new(),
+            hosts: HashMap::new(),
             #[cfg(not(target_arch = "wasm32"))]
             loaded_libs: HashMap::new(),
+            #[cfg(not(target_arch = "wasm32"))]
+            extensions: HashMap::new(),
             opaque_types: HashSet::new(),
         }
     }
 
     /// Create a new FFI registry pre-populated with standard library functions.
     ///
-    /// This registers all `std.*` native functions that are available by default.
+    /// This registers all `std.*` native functions that are available by default,
+    /// plus the functions of every installed native extension.
     pub fn with_std() -> Self {
         let mut registry = Self::new();
         crate::std::register_all(&mut registry);
+        #[cfg(not(target_arch = "wasm32"))]
+        super::extension::register_installed(&mut registry);
         registry
     }
 
@@ -113,9 +132,41 @@ impl FfiRegistry {
         name: &str,
         handler: NativeHandler,
     ) {
+        self.hosts.remove(name);
         self.handlers.insert(name.to_string(), handler);
     }
 
+    /// Register a host function supplied by the embedder.
+    ///
+    /// Overwrites any handler or host function registered under `name`.
+    pub fn register_host(
+        &mut self,
+        name: &str,
+        function: HostFunction,
+    ) {
+        self.handlers.remove(name);
+        self.hosts.insert(name.to_string(), function);
+    }
+
+    /// Keep only the handlers whose name satisfies `keep`.
+    pub fn retain(
+        &mut self,
+        mut keep: impl FnMut(&str) -> bool,
+    ) {
+        self.handlers.retain(|name, _| keep(name));
+        self.hosts.retain(|name, _| keep(name));
+    }
+
+    /// Register a function exported by a native extension under its qualified name.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn register_extension(
+        &mut self,
+        function: ExtensionFunction,
+    ) {
+        self.extensions
+            .insert(function.qualified_name.clone(), function);
+    }
+
     /// Call a registered native function by name.
     ///
     /// # Arguments
@@ -136,6 +187,9 @@ impl FfiRegistry {
     ) -> Result<RuntimeValue, ExecutorError> {
         match self.handlers.get(name) {
             Some(handler) => handler(args, ctx),
+            None if self.hosts.contains_key(name) => self.hosts[name].call(args, ctx),
+            #[cfg(not(target_arch = "wasm32"))]
+            None if self.extensions.contains_key(name) => self.extensions[name].call(args, ctx),
             None => Err(ExecutorError::FunctionNotFound(
                 format!("Native function not found: {}", name),
                 None,
@@ -148,40 +202,49 @@ impl FfiRegistry {
         &self,
         name: &str,
     ) -> bool {
-        self.handlers.contains_key(name)
+        #[cfg(not(target_arch = "wasm32"))]
+        if self.extensions.contains_key(name) {
+            return true;
+        }
+        self.handlers.contains_key(name) || self.hosts.contains_key(name)
     }
 
     /// Get the number of registered handlers.
     pub fn len(&self) -> usize {
-        self.handlers.len()
+        self.handlers.len() + self.hosts.len()
     }
 
     /// Check if the registry is empty.
     pub fn is_empty(&self) -> bool {
-        self.handlers.is_empty()
+        self.handlers.is_empty() && self.hosts.is_empty()
     }
 
     /// Get a list of all registered function names.
     pub fn registered_functions(&self) -> Vec<&str> {
-        self.handlers.keys().map(|s| s.as_str()).collect()
+        self.handlers
+            .keys()
+            .chain(self.hosts.keys())
+            .map(|s| s.as_str())
+            .collect()
     }
 
     /// Call a native function by mechanism and name.
     ///
     /// Dispatches to either:
-    /// - `"rs"` — calls a registered Rust handler via `call()`
+    /// - `"rs"` — calls the Rust handler registered as `symbol` (or `func_name`) via `call()`
     /// - `"c"` — calls a C function from a dynamically loaded library via `call_c()`
     pub fn call_with_mechanism(
         &self,
         mechanism: &str,
         #[cfg_attr(target_arch = "wasm32", allow(unused_variables))] lib: &str,
-        #[cfg_attr(target_arch = "wasm32", allow(unused_variables))] symbol: &str,
+        symbol: &str,
         func_name: &str,
         args: &[RuntimeValue],
         ctx: &mut NativeContext<'_>,
     ) -> Result<RuntimeValue, ExecutorError> {
         match mechanism {
-            "rs" => self.call(func_name, args, ctx),
+            "rs" if symbol.is_empty() => self.call(func_name, args, ctx),
+            "rs" => self.call(symbol, args, ctx),
             #[cfg(not(target_arch = "wasm32"))]
             "c" => self.call_c(lib, symbol, args, ctx),
             #[cfg(target_arch = "wasm32")]
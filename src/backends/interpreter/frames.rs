@@ -2,12 +2,106 @@
 //!
 //! This module provides the call frame structure used for function calls.
 
-use crate::backends::common::RuntimeValue;
+use std::sync::Arc;
+
+use crate::backends::common::{Handle, RuntimeValue, Value};
 use crate::backends::common::value::TaskId;
 use crate::middle::bytecode::{BytecodeFunction, Label};
 
-/// Maximum number of local variables
-pub const MAX_LOCALS: usize = 256;
+/// Maximum number of local variable slots (slot indices are encoded as u16)
+pub const MAX_LOCALS: usize = u16::MAX as usize + 1;
+
+/// What a frame keeps alive: its value slots and the objects in its stack region
+///
+/// Moved out of a frame while it waits for a nested call, so that a collection
+/// during the call can see them (see `Frame::take_values`).
+#[derive(Debug, Default)]
+pub struct FrameValues {
+    registers: Vec<Value>,
+    locals: Vec<Value>,
+    upvalues: Vec<Value>,
+    stack_slots: Vec<(usize, Handle)>,
+}
+
+impl FrameValues {
+    /// Registers of the waiting frame
+    pub fn registers(&self) -> &[Value] {
+        &self.registers
+    }
+
+    /// Registers, locals and upvalues
+    pub fn values(&self) -> impl Iterator<Item = &Value> {
+        self.registers
+            .iter()
+            .chain(&self.locals)
+            .chain(&self.upvalues)
+    }
+
+    /// Objects placed in the frame's stack region
+    pub fn stack_handles(&self) -> impl Iterator<Item = Handle> + '_ {
+        self.stack_slots.iter().map(|(_, handle)| *handle)
+    }
+}
+
+/// Register and local buffers of returned frames, reused by new frames
+///
+/// Holds at most `capacity` buffer pairs; frames returning while the pool is
+/// full drop theirs.
+#[derive(Debug, Default)]
+pub struct FramePool {
+    buffers: Vec<(Vec<Value>, Vec<Value>)>,
+    capacity: usize,
+}
+
+impl FramePool {
+    /// Create a pool that keeps up to `capacity` frames' buffers
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            buffers: Vec::new(),
+            capacity,
+        }
+    }
+
+    /// Create a frame like `Frame::with_args`, on pooled buffers if any
+    pub fn frame(
+        &mut self,
+        function: impl Into<Arc<BytecodeFunction>>,
+        args: &[RuntimeValue],
+    ) -> Frame {
+        let (registers, locals) = self
+            .buffers
+            .pop()
+            .unwrap_or_else(|| (Vec::with_capacity(32), Vec::new()));
+        Frame::with_buffers(function.into(), registers, locals).with_call_args(args)
+    }
+
+    /// Keep a returned frame's buffers for the next call
+    pub fn release(
+        &mut self,
+        frame: Frame,
+    ) {
+        if self.buffers.len() < self.capacity {
+            let Frame {
+                mut registers,
+                mut locals,
+                ..
+            } = frame;
+            registers.clear();
+            locals.clear();
+            self.buffers.push((registers, locals));
+        }
+    }
+
+    /// Number of buffer pairs waiting to be reused
+    pub fn len(&self) -> usize {
+        self.buffers.len()
+    }
+
+    /// Whether no buffers are waiting to be reused
+    pub fn is_empty(&self) -> bool {
+        self.buffers.is_empty()
+    }
+}
 
 /// Call frame for function execution
 ///
@@ -16,48 +110,76 @@ pub const MAX_LOCALS: usize = 256;
 #[derive(Debug, Clone)]
 pub struct Frame {
     /// The function being executed
-    pub function: BytecodeFunction,
+    pub function: Arc<BytecodeFunction>,
     /// Instruction pointer (index into instructions)
     pub ip: usize,
     /// Register file for this frame
-    pub registers: Vec<RuntimeValue>,
+    pub registers: Vec<Value>,
     /// Local variable values (flat array)
-    locals: Vec<RuntimeValue>,
+    locals: Vec<Value>,
     /// Upvalue capture values
-    upvalues: Vec<RuntimeValue>,
+    upvalues: Vec<Value>,
     /// Entry IP (for stack unwinding)
     entry_ip: usize,
     /// Spawn task groups (RFC-024: only meaningful inside spawn scopes).
     spawn_groups: Vec<Vec<TaskId>>,
+    /// Objects placed in this frame's stack region, keyed by the index of
+    /// the `StackAlloc` that placed them
+    stack_slots: Vec<(usize, Handle)>,
+    /// Inline cache table of `function`, found the first time an instruction needs it
+    pub inline_cache: Option<usize>,
 }
 
 impl Frame {
     /// Create a new frame for a function
-    pub fn new(function: BytecodeFunction) -> Self {
-        let local_count = function.local_count.max(1);
+    pub fn new(function: impl Into<Arc<BytecodeFunction>>) -> Self {
+        Self::with_buffers(function.into(), Vec::with_capacity(32), Vec::new())
+    }
+
+    /// Create a frame on top of (empty) register and local buffers
+    fn with_buffers(
+        function: Arc<BytecodeFunction>,
+        registers: Vec<Value>,
+        mut locals: Vec<Value>,
+    ) -> Self {
+        locals.resize(function.local_count.max(1), Value::UNIT);
         Self {
             function,
             ip: 0,
-            registers: Vec::with_capacity(32),
-            locals: vec![RuntimeValue::Unit; local_count],
+            registers,
+            locals,
             upvalues: Vec::new(),
             entry_ip: 0,
             spawn_groups: Vec::new(),
+            stack_slots: Vec::new(),
+            inline_cache: None,
         }
     }
 
     /// Create a new frame with arguments
+    ///
+    /// A closure's captured environment is prepended to its call arguments:
+    /// the first `upvalue_count` values become upvalues, the rest fill the
+    /// parameter slots.
     pub fn with_args(
-        function: BytecodeFunction,
+        function: impl Into<Arc<BytecodeFunction>>,
         args: &[RuntimeValue],
     ) -> Self {
-        let mut frame = Self::new(function);
-        for (i, arg) in args.iter().enumerate() {
-            if i < frame.locals.len() {
-                frame.locals[i] = arg.clone();
-            }
+        Self::new(function).with_call_args(args)
+    }
+
+    /// Fill upvalues and parameter slots from call arguments (see `with_args`)
+    fn with_call_args(
+        mut self,
+        args: &[RuntimeValue],
+    ) -> Self {
+        let env_len = self.function.upvalue_count.min(args.len());
+        let (env, args) = args.split_at(env_len);
+        self.upvalues = env.iter().map(Value::from).collect();
+        for (slot, arg) in self.locals.iter_mut().zip(args) {
+            *slot = Value::from(arg);
         }
-        frame
+        self
     }
 
     /// Get the current instruction
@@ -89,32 +211,43 @@ impl Frame {
     pub fn get_local(
         &self,
         index: usize,
-    ) -> Option<&RuntimeValue> {
-        self.locals.get(index)
+    ) -> Option<RuntimeValue> {
+        self.locals.get(index).map(Value::get)
     }
 
     /// Set a local variable
     pub fn set_local(
         &mut self,
         index: usize,
-        value: RuntimeValue,
+        value: impl Into<Value>,
     ) {
         if index >= self.locals.len() {
-            self.locals.resize(index + 1, RuntimeValue::Unit);
+            self.locals.resize(index + 1, Value::UNIT);
         }
-        self.locals[index] = value;
+        self.locals[index] = value.into();
+    }
+
+    /// Get a register value, unit if the register was never written
+    pub fn register(
+        &self,
+        index: usize,
+    ) -> RuntimeValue {
+        self.registers
+            .get(index)
+            .map_or(RuntimeValue::Unit, Value::get)
     }
 
     /// Set a register value, extending the register file if necessary
+    #[inline]
     pub fn set_register(
         &mut self,
         index: usize,
-        value: RuntimeValue,
+        value: impl Into<Value>,
     ) {
         if index >= self.registers.len() {
-            self.registers.resize(index + 1, RuntimeValue::Unit);
+            self.registers.resize(index + 1, Value::UNIT);
         }
-        self.registers[index] = value;
+        self.registers[index] = value.into();
     }
 
     pub fn push_spawn_group(&mut self) {
@@ -142,24 +275,82 @@ impl Frame {
         out
     }
 
+    /// Place `handle` in the stack slot of the `StackAlloc` at `ip`,
+    /// returning the object it displaces
+    pub fn replace_stack_slot(
+        &mut self,
+        ip: usize,
+        handle: Handle,
+    ) -> Option<Handle> {
+        match self.stack_slots.iter_mut().find(|(slot, _)| *slot == ip) {
+            Some((_, old)) => Some(std::mem::replace(old, handle)),
+            None => {
+                self.stack_slots.push((ip, handle));
+                None
+            }
+        }
+    }
+
+    /// Take every object in the stack region, for release when the frame exits
+    pub fn take_stack_slots(&mut self) -> Vec<Handle> {
+        self.stack_slots
+            .drain(..)
+            .map(|(_, handle)| handle)
+            .collect()
+    }
+
+    /// Move the frame's values out while it waits for a nested call
+    pub fn take_values(&mut self) -> FrameValues {
+        FrameValues {
+            registers: std::mem::take(&mut self.registers),
+            locals: std::mem::take(&mut self.locals),
+            upvalues: std::mem::take(&mut self.upvalues),
+            stack_slots: std::mem::take(&mut self.stack_slots),
+        }
+    }
+
+    /// Put back the values taken by `take_values`
+    pub fn restore_values(
+        &mut self,
+        values: FrameValues,
+    ) {
+        self.registers = values.registers;
+        self.locals = values.locals;
+        self.upvalues = values.upvalues;
+        self.stack_slots = values.stack_slots;
+    }
+
+    /// Registers, locals and upvalues
+    pub fn values(&self) -> impl Iterator<Item = &Value> {
+        self.registers
+            .iter()
+            .chain(&self.locals)
+            .chain(&self.upvalues)
+    }
+
+    /// Objects placed in the frame's stack region
+    pub fn stack_handles(&self) -> impl Iterator<Item = Handle> + '_ {
+        self.stack_slots.iter().map(|(_, handle)| *handle)
+    }
+
     /// Get an upvalue
     pub fn get_upvalue(
         &self,
         index: usize,
-    ) -> Option<&RuntimeValue> {
-        self.upvalues.get(index)
+    ) -> Option<RuntimeValue> {
+        self.upvalues.get(index).map(Value::get)
     }
 
     /// Set an upvalue
     pub fn set_upvalue(
         &mut self,
         index: usize,
-        value: RuntimeValue,
+        value: impl Into<Value>,
     ) {
         if index >= self.upvalues.len() {
-            self.upvalues.resize(index + 1, RuntimeValue::Unit);
+            self.upvalues.resize(index + 1, Value::UNIT);
         }
-        self.upvalues[index] = value;
+        self.upvalues[index] = value.into();
     }
 
     /// Get the function name
@@ -190,8 +381,18 @@ impl Frame {
         self.upvalues.len()
     }
 
+    /// All local variable slots
+    pub fn locals(&self) -> &[Value] {
+        &self.locals
+    }
+
+    /// All captured upvalues
+    pub fn upvalues(&self) -> &[Value] {
+        &self.upvalues
+    }
+
     /// Get mutable access to upvalues (for closure capture)
-    pub fn upvalues_mut(&mut self) -> &mut Vec<RuntimeValue> {
+    pub fn upvalues_mut(&mut self) -> &mut Vec<Value> {
         &mut self.upvalues
     }
 }
@@ -0,0 +1,180 @@
+//! Execution hooks: callbacks observing a running interpreter
+//!
+//! Debuggers, profilers and coverage tools install an [`ExecutionHook`] with
+//! [`Interpreter::set_hook`](super::Interpreter::set_hook). The interpreter
+//! then reports every instruction it is about to execute, every interpreted
+//! function it enters and leaves, the heap bytes its instructions allocate and
+//! the spawned tasks it starts and finishes. Task interpreters inherit the hook
+//! of the interpreter that spawned them, so a hook may be called from several
+//! threads at once.
+//!
+//! ```no_run
+//! use std::sync::atomic::{AtomicU64, Ordering};
+//! use std::sync::Arc;
+//! use yaoxiang::vm::{ExecutionHook, Interpreter, VmState};
+//!
+//! #[derive(Default)]
+//! struct Counter(AtomicU64);
+//!
+//! impl ExecutionHook for Counter {
+//!     fn on_step(&self, _vm: &VmState<'_>) {
+//!         self.0.fetch_add(1, Ordering::Relaxed);
+//!     }
+//! }
+//!
+//! let counter = Arc::new(Counter::default());
+//! let mut interpreter = Interpreter::new();
+//! interpreter.set_hook(counter.clone());
+//! ```
+//!
+//! Hooks only exist with the `hooks` feature; without it the interpreter
+//! carries no hook and the dispatch loop has no extra check. An interpreter
+//! with a hook never runs functions as machine code, since those would skip
+//! the callbacks.
+
+use std::sync::Arc;
+
+use crate::backends::common::serialize::ValueRef;
+use crate::backends::common::{Heap, RuntimeValue, Value};
+use crate::backends::common::value::TaskId;
+use crate::backends::Executor;
+use crate::middle::bytecode::{BytecodeFunction, BytecodeInstr};
+use crate::util::span::DebugSpan;
+
+use super::{Frame, Interpreter};
+
+/// Observer of a running interpreter
+///
+/// Every method has an empty default, so an implementation only overrides
+/// the events it needs.
+pub trait ExecutionHook: Send + Sync {
+    /// The instruction at `vm.ip()` of `vm.function()` is about to execute
+    fn on_step(
+        &self,
+        _vm: &VmState<'_>,
+    ) {
+    }
+
+    /// An interpreted function is entered; `vm.ip()` is its first instruction
+    fn on_enter(
+        &self,
+        _vm: &VmState<'_>,
+    ) {
+    }
+
+    /// An interpreted function returned (`result` is `None` when it failed)
+    fn on_exit(
+        &self,
+        _function: &BytecodeFunction,
+        _result: Option<&RuntimeValue>,
+    ) {
+    }
+
+    /// An instruction allocated `bytes` on the heap
+    fn on_alloc(
+        &self,
+        _bytes: usize,
+    ) {
+    }
+
+    /// A task interpreter started or finished running a spawned task
+    fn on_task_switch(
+        &self,
+        _switch: TaskSwitch,
+    ) {
+    }
+}
+
+/// A task interpreter taking over or giving back the thread it runs on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskSwitch {
+    /// The task starts running
+    Start(TaskId),
+    /// The task finished, successfully or not
+    Finish(TaskId),
+}
+
+/// Read-only view of the interpreter at the point a hook is called
+pub struct VmState<'a> {
+    interpreter: &'a Interpreter,
+    frame: &'a Frame,
+    depth: usize,
+}
+
+impl<'a> VmState<'a> {
+    pub(crate) fn new(
+        interpreter: &'a Interpreter,
+        frame: &'a Frame,
+        depth: usize,
+    ) -> Self {
+        Self {
+            interpreter,
+            frame,
+            depth,
+        }
+    }
+
+    /// The function being executed
+    pub fn function(&self) -> &'a Arc<BytecodeFunction> {
+        &self.frame.function
+    }
+
+    /// Index of the instruction about to execute
+    pub fn ip(&self) -> usize {
+        self.frame.ip
+    }
+
+    /// The instruction about to execute (`None` past the end of the function)
+    pub fn instruction(&self) -> Option<&'a BytecodeInstr> {
+        self.frame.function.instructions.get(self.frame.ip)
+    }
+
+    /// Source location of the instruction, from the function's line table
+    pub fn location(&self) -> Option<DebugSpan> {
+        self.frame.function.line_table.lookup(self.frame.ip)
+    }
+
+    /// Number of interpreted calls in progress, this one included
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Registers of the current frame
+    pub fn registers(&self) -> &'a [Value] {
+        &self.frame.registers
+    }
+
+    /// Registers of the frame `level` calls up (`1` is the caller), as they
+    /// were when it made its call; `None` past the outermost waiting frame
+    pub fn caller_registers(
+        &self,
+        level: usize,
+    ) -> Option<&'a [Value]> {
+        let suspended = self.interpreter.suspended_frames();
+        let index = suspended.len().checked_sub(level)?;
+        (level > 0).then(|| suspended[index].registers())
+    }
+
+    /// The frame being executed
+    pub fn frame(&self) -> &'a Frame {
+        self.frame
+    }
+
+    /// The interpreter's heap, to look into values in registers
+    pub fn heap(&self) -> &'a Heap {
+        self.interpreter.heap()
+    }
+
+    /// `value` with the heap and struct field names, to serialize it
+    pub fn value(
+        &self,
+        value: &'a RuntimeValue,
+    ) -> ValueRef<'a> {
+        ValueRef::new(value, self.heap()).with_struct_types(self.interpreter.struct_types())
+    }
+
+    /// The interpreter
+    pub fn interpreter(&self) -> &'a Interpreter {
+        self.interpreter
+    }
+}
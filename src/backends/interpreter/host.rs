@@ -0,0 +1,575 @@
+//! Host functions registered by embedders, and typed calls into scripts
+//!
+//! A Rust application exposes its own API to scripts by registering host
+//! functions on an [`Interpreter`](super::Interpreter) or
+//! [`EngineBuilder`](crate::EngineBuilder). Scripts bind them with
+//! `Native.rs("name")` exactly like standard library functions:
+//!
+//! ```no_run
+//! use yaoxiang::{Engine, RuntimeValue};
+//!
+//! let engine = Engine::builder()
+//!     .register_native("app.version", |_args| Ok(RuntimeValue::String("1.2".into())))
+//!     .register_fn("app.scale", |x: f64, factor: i64| x * factor as f64)
+//!     .build();
+//!
+//! let source = "scale: (x: Float, factor: Int) -> Float = Native.rs(\"app.scale\")\n\
+//!               half: () -> Float = () => scale(0.25, 2)\n";
+//! let program = engine.compile("app.yx", source)?;
+//! assert_eq!(engine.call(&program, "half", &[])?, RuntimeValue::Float(0.5));
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! Unlike a [`NativeHandler`], a [`HostFunction`] may capture state. The typed
+//! form converts arguments with [`FromValue`] and the result with
+//! [`IntoValue`]; a call with the wrong number or types of arguments fails
+//! with a type error naming the function instead of reaching the closure.
+//!
+//! The same conversions run the other way for
+//! [`Interpreter::call`](super::Interpreter::call): the argument tuple goes
+//! through [`IntoArgs`] and the result through [`FromValue`].
+//!
+//! Any serde type crosses the boundary wrapped in [`Serde`], which converts
+//! through [`to_value`] and [`from_value`](crate::backends::common::from_value).
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::Arc;
+
+use crate::backends::common::serialize::{from_value, to_value};
+use crate::backends::common::{Heap, HeapValue, RuntimeValue, Value};
+use crate::backends::{ExecutorError, ExecutorResult};
+use crate::std::{NativeContext, NativeHandler};
+
+/// Signature shared by every host function
+type HostFn =
+    dyn Fn(&[RuntimeValue], &mut NativeContext<'_>) -> ExecutorResult<RuntimeValue> + Send + Sync;
+
+/// A native function supplied by the embedding application
+#[derive(Clone)]
+pub struct HostFunction(Arc<HostFn>);
+
+impl HostFunction {
+    /// Wrap a closure over the raw argument values
+    pub fn new(
+        f: impl Fn(&[RuntimeValue]) -> ExecutorResult<RuntimeValue> + Send + Sync + 'static
+    ) -> Self {
+        Self(Arc::new(move |args, _ctx| f(args)))
+    }
+
+    /// Wrap a closure that also needs the native context (heap access,
+    /// calling back into script functions)
+    pub fn with_context(
+        f: impl Fn(&[RuntimeValue], &mut NativeContext<'_>) -> ExecutorResult<RuntimeValue>
+            + Send
+            + Sync
+            + 'static
+    ) -> Self {
+        Self(Arc::new(f))
+    }
+
+    /// Wrap a closure over Rust types; `name` is used in conversion errors
+    pub fn typed<Args>(
+        name: &str,
+        f: impl IntoHostFunction<Args>,
+    ) -> Self {
+        f.into_host_function(name)
+    }
+
+    /// Call the function
+    pub fn call(
+        &self,
+        args: &[RuntimeValue],
+        ctx: &mut NativeContext<'_>,
+    ) -> ExecutorResult<RuntimeValue> {
+        (self.0)(args, ctx)
+    }
+}
+
+impl From<NativeHandler> for HostFunction {
+    fn from(handler: NativeHandler) -> Self {
+        Self(Arc::new(handler))
+    }
+}
+
+impl std::fmt::Debug for HostFunction {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        f.write_str("HostFunction(..)")
+    }
+}
+
+/// Conversion from a script value into a Rust type
+pub trait FromValue: Sized {
+    /// Convert `value`, reading heap objects (lists, dicts) from `heap`
+    fn from_value(
+        value: &RuntimeValue,
+        heap: &Heap,
+    ) -> ExecutorResult<Self>;
+}
+
+/// Conversion from a Rust type into a script value
+pub trait IntoValue {
+    /// Convert `self`, allocating heap objects (lists, dicts) on `heap`
+    fn into_value(
+        self,
+        heap: &mut Heap,
+    ) -> ExecutorResult<RuntimeValue>;
+}
+
+/// What a typed host function may return: a value, or a `Result` whose error
+/// becomes the script's error
+pub trait HostReturn {
+    /// Convert the return value
+    fn into_result(
+        self,
+        heap: &mut Heap,
+    ) -> ExecutorResult<RuntimeValue>;
+}
+
+impl<T: IntoValue> HostReturn for T {
+    fn into_result(
+        self,
+        heap: &mut Heap,
+    ) -> ExecutorResult<RuntimeValue> {
+        self.into_value(heap)
+    }
+}
+
+impl<T: IntoValue> HostReturn for ExecutorResult<T> {
+    fn into_result(
+        self,
+        heap: &mut Heap,
+    ) -> ExecutorResult<RuntimeValue> {
+        self?.into_value(heap)
+    }
+}
+
+/// Closures that can be registered as typed host functions
+///
+/// Implemented for `Fn` closures of up to six [`FromValue`] arguments
+/// returning a [`HostReturn`]; `Args` is the tuple of argument types.
+pub trait IntoHostFunction<Args> {
+    /// Wrap the closure; `name` is used in conversion errors
+    fn into_host_function(
+        self,
+        name: &str,
+    ) -> HostFunction;
+}
+
+/// Argument lists for calling into scripts: tuples of up to six [`IntoValue`]s
+pub trait IntoArgs {
+    /// Convert each element, allocating heap objects on `heap`
+    fn into_args(
+        self,
+        heap: &mut Heap,
+    ) -> ExecutorResult<Vec<RuntimeValue>>;
+}
+
+impl IntoArgs for &[RuntimeValue] {
+    fn into_args(
+        self,
+        _heap: &mut Heap,
+    ) -> ExecutorResult<Vec<RuntimeValue>> {
+        Ok(self.to_vec())
+    }
+}
+
+macro_rules! impl_into_args {
+    ($($ty:ident $arg:ident),*) => {
+        impl<$($ty: IntoValue),*> IntoArgs for ($($ty,)*) {
+            #[allow(unused_variables)]
+            fn into_args(
+                self,
+                heap: &mut Heap,
+            ) -> ExecutorResult<Vec<RuntimeValue>> {
+                let ($($arg,)*) = self;
+                Ok(vec![$($arg.into_value(heap)?),*])
+            }
+        }
+    };
+}
+
+impl_into_args!();
+impl_into_args!(A1 a1);
+impl_into_args!(A1 a1, A2 a2);
+impl_into_args!(A1 a1, A2 a2, A3 a3);
+impl_into_args!(A1 a1, A2 a2, A3 a3, A4 a4);
+impl_into_args!(A1 a1, A2 a2, A3 a3, A4 a4, A5 a5);
+impl_into_args!(A1 a1, A2 a2, A3 a3, A4 a4, A5 a5, A6 a6);
+
+/// The type error for a value of the wrong type
+pub(crate) fn mismatch(
+    expected: &str,
+    value: &RuntimeValue,
+    heap: &Heap,
+) -> ExecutorError {
+    ExecutorError::type_only(format!(
+        "expected {}, found {}",
+        expected,
+        value.value_type(Some(heap)).name()
+    ))
+}
+
+/// Name the argument a conversion type error came from
+pub(crate) fn in_argument(
+    name: &str,
+    index: usize,
+    error: ExecutorError,
+) -> ExecutorError {
+    match error {
+        ExecutorError::Type(msg, stack) => ExecutorError::Type(
+            format!("argument {} of {}: {}", index + 1, name, msg),
+            stack,
+        ),
+        other => other,
+    }
+}
+
+macro_rules! impl_into_host_function {
+    ($($ty:ident $arg:ident),*) => {
+        impl<F, R, $($ty),*> IntoHostFunction<($($ty,)*)> for F
+        where
+            F: Fn($($ty),*) -> R + Send + Sync + 'static,
+            R: HostReturn,
+            $($ty: FromValue,)*
+        {
+            #[allow(unused_variables, unused_mut)]
+            fn into_host_function(
+                self,
+                name: &str,
+            ) -> HostFunction {
+                const ARITY: usize = <[&str]>::len(&[$(stringify!($arg)),*]);
+                let name = name.to_string();
+                HostFunction::with_context(move |args, ctx| {
+                    if args.len() != ARITY {
+                        return Err(ExecutorError::type_only(format!(
+                            "{} expects {} argument(s), got {}",
+                            name,
+                            ARITY,
+                            args.len()
+                        )));
+                    }
+                    let mut args = args.iter().enumerate();
+                    $(
+                        let (index, value) = args.next().unwrap();
+                        let $arg = $ty::from_value(value, ctx.heap)
+                            .map_err(|e| in_argument(&name, index, e))?;
+                    )*
+                    (self)($($arg),*).into_result(ctx.heap)
+                })
+            }
+        }
+    };
+}
+
+impl_into_host_function!();
+impl_into_host_function!(A1 a1);
+impl_into_host_function!(A1 a1, A2 a2);
+impl_into_host_function!(A1 a1, A2 a2, A3 a3);
+impl_into_host_function!(A1 a1, A2 a2, A3 a3, A4 a4);
+impl_into_host_function!(A1 a1, A2 a2, A3 a3, A4 a4, A5 a5);
+impl_into_host_function!(A1 a1, A2 a2, A3 a3, A4 a4, A5 a5, A6 a6);
+
+// ── Conversions ─────────────────────────────────────────────
+
+impl FromValue for RuntimeValue {
+    fn from_value(
+        value: &RuntimeValue,
+        _heap: &Heap,
+    ) -> ExecutorResult<Self> {
+        Ok(value.clone())
+    }
+}
+
+impl IntoValue for RuntimeValue {
+    fn into_value(
+        self,
+        _heap: &mut Heap,
+    ) -> ExecutorResult<RuntimeValue> {
+        Ok(self)
+    }
+}
+
+impl FromValue for () {
+    fn from_value(
+        value: &RuntimeValue,
+        heap: &Heap,
+    ) -> ExecutorResult<Self> {
+        match value {
+            RuntimeValue::Unit => Ok(()),
+            other => Err(mismatch("Void", other, heap)),
+        }
+    }
+}
+
+impl IntoValue for () {
+    fn into_value(
+        self,
+        _heap: &mut Heap,
+    ) -> ExecutorResult<RuntimeValue> {
+        Ok(RuntimeValue::Unit)
+    }
+}
+
+impl FromValue for bool {
+    fn from_value(
+        value: &RuntimeValue,
+        heap: &Heap,
+    ) -> ExecutorResult<Self> {
+        match value {
+            RuntimeValue::Bool(b) => Ok(*b),
+            other => Err(mismatch("Bool", other, heap)),
+        }
+    }
+}
+
+impl IntoValue for bool {
+    fn into_value(
+        self,
+        _heap: &mut Heap,
+    ) -> ExecutorResult<RuntimeValue> {
+        Ok(RuntimeValue::Bool(self))
+    }
+}
+
+impl FromValue for i64 {
+    fn from_value(
+        value: &RuntimeValue,
+        heap: &Heap,
+    ) -> ExecutorResult<Self> {
+        match value {
+            RuntimeValue::Int(n) => Ok(*n),
+            other => Err(mismatch("Int", other, heap)),
+        }
+    }
+}
+
+impl IntoValue for i64 {
+    fn into_value(
+        self,
+        _heap: &mut Heap,
+    ) -> ExecutorResult<RuntimeValue> {
+        Ok(RuntimeValue::Int(self))
+    }
+}
+
+macro_rules! impl_narrow_int {
+    ($($ty:ty),*) => {
+        $(
+            impl FromValue for $ty {
+                fn from_value(
+                    value: &RuntimeValue,
+                    heap: &Heap,
+                ) -> ExecutorResult<Self> {
+                    let n = i64::from_value(value, heap)?;
+                    <$ty>::try_from(n).map_err(|_| {
+                        ExecutorError::type_only(format!(
+                            "{} does not fit in {}",
+                            n,
+                            stringify!($ty)
+                        ))
+                    })
+                }
+            }
+
+            impl IntoValue for $ty {
+                fn into_value(
+                    self,
+                    _heap: &mut Heap,
+                ) -> ExecutorResult<RuntimeValue> {
+                    i64::try_from(self).map(RuntimeValue::Int).map_err(|_| {
+                        ExecutorError::type_only(format!("{} does not fit in Int", self))
+                    })
+                }
+            }
+        )*
+    };
+}
+
+impl_narrow_int!(i32, u32, u64, usize);
+
+impl FromValue for f64 {
+    fn from_value(
+        value: &RuntimeValue,
+        heap: &Heap,
+    ) -> ExecutorResult<Self> {
+        match value {
+            RuntimeValue::Float(f) => Ok(*f),
+            other => Err(mismatch("Float", other, heap)),
+        }
+    }
+}
+
+impl IntoValue for f64 {
+    fn into_value(
+        self,
+        _heap: &mut Heap,
+    ) -> ExecutorResult<RuntimeValue> {
+        Ok(RuntimeValue::Float(self))
+    }
+}
+
+impl FromValue for char {
+    fn from_value(
+        value: &RuntimeValue,
+        heap: &Heap,
+    ) -> ExecutorResult<Self> {
+        match value {
+            RuntimeValue::Char(c) => char::from_u32(*c)
+                .ok_or_else(|| ExecutorError::type_only(format!("invalid char: {:#x}", c))),
+            other => Err(mismatch("Char", other, heap)),
+        }
+    }
+}
+
+impl IntoValue for char {
+    fn into_value(
+        self,
+        _heap: &mut Heap,
+    ) -> ExecutorResult<RuntimeValue> {
+        Ok(RuntimeValue::Char(self as u32))
+    }
+}
+
+impl FromValue for String {
+    fn from_value(
+        value: &RuntimeValue,
+        heap: &Heap,
+    ) -> ExecutorResult<Self> {
+        match value {
+            RuntimeValue::String(s) => Ok(s.to_string()),
+            other => Err(mismatch("String", other, heap)),
+        }
+    }
+}
+
+impl FromValue for Arc<str> {
+    fn from_value(
+        value: &RuntimeValue,
+        heap: &Heap,
+    ) -> ExecutorResult<Self> {
+        match value {
+            RuntimeValue::String(s) => Ok(s.clone()),
+            other => Err(mismatch("String", other, heap)),
+        }
+    }
+}
+
+impl IntoValue for String {
+    fn into_value(
+        self,
+        _heap: &mut Heap,
+    ) -> ExecutorResult<RuntimeValue> {
+        Ok(RuntimeValue::String(self.into()))
+    }
+}
+
+impl IntoValue for &str {
+    fn into_value(
+        self,
+        _heap: &mut Heap,
+    ) -> ExecutorResult<RuntimeValue> {
+        Ok(RuntimeValue::String(self.into()))
+    }
+}
+
+impl IntoValue for Arc<str> {
+    fn into_value(
+        self,
+        _heap: &mut Heap,
+    ) -> ExecutorResult<RuntimeValue> {
+        Ok(RuntimeValue::String(self))
+    }
+}
+
+impl<T: FromValue> FromValue for Vec<T> {
+    fn from_value(
+        value: &RuntimeValue,
+        heap: &Heap,
+    ) -> ExecutorResult<Self> {
+        let items = match value {
+            RuntimeValue::List(handle) | RuntimeValue::Array(handle) => match heap.get(*handle) {
+                Some(HeapValue::List(items)) | Some(HeapValue::Array(items)) => items,
+                _ => return Err(ExecutorError::InvalidHandle(*handle)),
+            },
+            other => return Err(mismatch("List", other, heap)),
+        };
+        items
+            .iter()
+            .map(|item| T::from_value(&item.view(), heap))
+            .collect()
+    }
+}
+
+impl<T: IntoValue> IntoValue for Vec<T> {
+    fn into_value(
+        self,
+        heap: &mut Heap,
+    ) -> ExecutorResult<RuntimeValue> {
+        let items = self
+            .into_iter()
+            .map(|item| item.into_value(heap).map(Value::from))
+            .collect::<ExecutorResult<Vec<_>>>()?;
+        Ok(RuntimeValue::List(
+            heap.try_allocate(HeapValue::List(items))?,
+        ))
+    }
+}
+
+impl<K: FromValue + Eq + Hash, V: FromValue> FromValue for HashMap<K, V> {
+    fn from_value(
+        value: &RuntimeValue,
+        heap: &Heap,
+    ) -> ExecutorResult<Self> {
+        let map = match value {
+            RuntimeValue::Dict(handle) => match heap.get(*handle) {
+                Some(HeapValue::Dict(map)) => map,
+                _ => return Err(ExecutorError::InvalidHandle(*handle)),
+            },
+            other => return Err(mismatch("Dict", other, heap)),
+        };
+        map.iter()
+            .map(|(k, v)| Ok((K::from_value(k, heap)?, V::from_value(v, heap)?)))
+            .collect()
+    }
+}
+
+impl<K: IntoValue, V: IntoValue> IntoValue for HashMap<K, V> {
+    fn into_value(
+        self,
+        heap: &mut Heap,
+    ) -> ExecutorResult<RuntimeValue> {
+        let map = self
+            .into_iter()
+            .map(|(k, v)| Ok((k.into_value(heap)?, v.into_value(heap)?)))
+            .collect::<ExecutorResult<HashMap<_, _>>>()?;
+        Ok(RuntimeValue::Dict(heap.try_allocate(HeapValue::Dict(map))?))
+    }
+}
+
+/// A serde type passed to or returned from a host function
+///
+/// Structs arrive as dicts keyed by field name and leave the same way.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Serde<T>(pub T);
+
+impl<T: serde::de::DeserializeOwned> FromValue for Serde<T> {
+    fn from_value(
+        value: &RuntimeValue,
+        heap: &Heap,
+    ) -> ExecutorResult<Self> {
+        Ok(Serde(from_value(value, heap)?))
+    }
+}
+
+impl<T: serde::Serialize> IntoValue for Serde<T> {
+    fn into_value(
+        self,
+        heap: &mut Heap,
+    ) -> ExecutorResult<RuntimeValue> {
+        Ok(to_value(&self.0, heap)?)
+    }
+}
@@ -3,9 +3,16 @@
 //! This module implements the interpreter-based execution backend.
 //! It reads bytecode instructions and executes them directly.
 
+#[cfg(feature = "hooks")]
+pub mod debugger;
 pub mod executor;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod extension;
 pub mod ffi;
 pub mod frames;
+#[cfg(feature = "hooks")]
+pub mod hooks;
+pub mod host;
 pub mod registers;
 pub mod runtime;
 
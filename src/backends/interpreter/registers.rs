@@ -2,7 +2,7 @@
 //!
 //! Provides a flat register array for the bytecode interpreter.
 
-use crate::backends::common::RuntimeValue;
+use crate::backends::common::Value;
 
 /// Number of general purpose registers
 pub const GENERAL_PURPOSE_REGS: usize = 32;
@@ -14,7 +14,7 @@ pub const GENERAL_PURPOSE_REGS: usize = 32;
 #[derive(Debug, Clone)]
 pub struct RegisterFile {
     /// Register values
-    registers: Vec<RuntimeValue>,
+    registers: Vec<Value>,
     /// Number of valid registers
     count: usize,
 }
@@ -34,7 +34,7 @@ impl RegisterFile {
     /// Create a register file with specified size
     pub fn with_size(size: usize) -> Self {
         Self {
-            registers: vec![RuntimeValue::default(); size],
+            registers: vec![Value::UNIT; size],
             count: size,
         }
     }
@@ -53,7 +53,7 @@ impl RegisterFile {
     pub fn get(
         &self,
         index: usize,
-    ) -> Option<&RuntimeValue> {
+    ) -> Option<&Value> {
         self.registers.get(index)
     }
 
@@ -61,7 +61,7 @@ impl RegisterFile {
     pub fn get_mut(
         &mut self,
         index: usize,
-    ) -> Option<&mut RuntimeValue> {
+    ) -> Option<&mut Value> {
         self.registers.get_mut(index)
     }
 
@@ -72,7 +72,7 @@ impl RegisterFile {
     pub fn at(
         &self,
         index: usize,
-    ) -> &RuntimeValue {
+    ) -> &Value {
         &self.registers[index]
     }
 
@@ -80,7 +80,7 @@ impl RegisterFile {
     pub fn at_mut(
         &mut self,
         index: usize,
-    ) -> &mut RuntimeValue {
+    ) -> &mut Value {
         &mut self.registers[index]
     }
 
@@ -88,12 +88,12 @@ impl RegisterFile {
     pub fn set(
         &mut self,
         index: usize,
-        value: RuntimeValue,
+        value: impl Into<Value>,
     ) {
         if index >= self.registers.len() {
-            self.registers.resize(index + 1, RuntimeValue::default());
+            self.registers.resize(index + 1, Value::UNIT);
         }
-        self.registers[index] = value;
+        self.registers[index] = value.into();
         self.count = self.count.max(index + 1);
     }
 
@@ -109,18 +109,18 @@ impl RegisterFile {
     /// Clear all registers
     pub fn clear(&mut self) {
         for reg in &mut self.registers {
-            *reg = RuntimeValue::default();
+            *reg = Value::UNIT;
         }
         self.count = 0;
     }
 
     /// Get all registers as a slice
-    pub fn as_slice(&self) -> &[RuntimeValue] {
+    pub fn as_slice(&self) -> &[Value] {
         &self.registers[..self.count]
     }
 
     /// Get mutable access to all registers
-    pub fn as_mut_slice(&mut self) -> &mut [RuntimeValue] {
+    pub fn as_mut_slice(&mut self) -> &mut [Value] {
         &mut self.registers[..self.count]
     }
 }
@@ -29,3 +29,15 @@ impl Default for InterpreterRuntimeConfig {
         }
     }
 }
+
+impl InterpreterRuntimeConfig {
+    /// Run spawned tasks on `threads` worker threads with work stealing
+    /// (Full runtime).
+    pub fn threads(threads: usize) -> Self {
+        Self {
+            runtime: RuntimeMode::Full,
+            workers: threads.max(1),
+            work_stealing: true,
+        }
+    }
+}
@@ -21,8 +21,13 @@ fn test_run_bytecode_file_roundtrip() {
     .expect("write source file");
 
     // Act
-    crate::build_bytecode_with_options(&source_path, &bytecode_path, false)
-        .expect("build bytecode");
+    crate::build_bytecode_with_options(
+        &source_path,
+        &bytecode_path,
+        false,
+        crate::frontend::CompileConfig::new(),
+    )
+    .expect("build bytecode");
     let bytecode_file = crate::middle::passes::codegen::BytecodeFile::load(&bytecode_path)
         .expect("load bytecode file");
     let bytecode_module = crate::middle::bytecode::BytecodeModule::from(bytecode_file);
@@ -35,6 +40,85 @@ fn test_run_bytecode_file_roundtrip() {
         .expect("execute bytecode module — roundtrip should succeed");
 }
 
+/// 按需加载模式：只解码入口函数，其余函数在首次调用时从文件加载。
+#[test]
+fn test_run_bytecode_file_lazy_loading() {
+    // Arrange
+    let dir = tempfile::TempDir::new().expect("create temp dir");
+    let source_path = dir.path().join("lazy.yx");
+    let bytecode_path = dir.path().join("lazy.42");
+    std::fs::write(
+        &source_path,
+        "double: (x: Int) -> Int = (x) => x * 2\n\
+         unused: () -> Int = () => 0\n\
+         main = () => { print(double(21)) }",
+    )
+    .expect("write source file");
+    crate::build_bytecode_with_options(
+        &source_path,
+        &bytecode_path,
+        false,
+        crate::frontend::CompileConfig::new(),
+    )
+    .expect("build bytecode");
+
+    // Act
+    let lazy_file = crate::middle::passes::codegen::LazyBytecodeFile::open(&bytecode_path)
+        .expect("open bytecode file lazily");
+    let bytecode_module = crate::middle::bytecode::BytecodeModule::try_from(lazy_file)
+        .expect("decode entry function");
+    let loaded: Vec<&str> = bytecode_module
+        .functions
+        .iter()
+        .filter(|f| !f.instructions.is_empty())
+        .map(|f| f.name.as_str())
+        .collect();
+    let interp = crate::backends::interpreter::Interpreter::new();
+    let mut executor: Box<dyn crate::backends::Executor> = Box::new(interp);
+
+    // Assert
+    assert_eq!(
+        loaded,
+        vec!["main"],
+        "only the entry function is decoded up front"
+    );
+    executor
+        .execute_module(&bytecode_module)
+        .expect("execute lazily loaded module");
+}
+
+/// 入口函数读取失败时加载报错，而不是以空函数体“成功”运行。
+#[test]
+fn test_lazy_loading_fails_on_unreadable_entry() {
+    // Arrange
+    let dir = tempfile::TempDir::new().expect("create temp dir");
+    let source_path = dir.path().join("entry.yx");
+    let bytecode_path = dir.path().join("entry.42");
+    std::fs::write(&source_path, "main = () => { print(\"unreachable\") }")
+        .expect("write source file");
+    crate::build_bytecode_with_options(
+        &source_path,
+        &bytecode_path,
+        false,
+        crate::frontend::CompileConfig::new(),
+    )
+    .expect("build bytecode");
+    let mut lazy_file = crate::middle::passes::codegen::LazyBytecodeFile::open(&bytecode_path)
+        .expect("open bytecode file lazily");
+    // 让入口函数的函数体位于文件末尾之外
+    let entry = lazy_file.header.entry_point as usize;
+    lazy_file.functions[entry].offset = u32::MAX;
+
+    // Act
+    let result = crate::middle::bytecode::BytecodeModule::try_from(lazy_file);
+
+    // Assert
+    assert!(
+        result.is_err(),
+        "an unreadable entry function must fail the load"
+    );
+}
+
 /// 无效魔数的 .42 文件应产生清晰的错误信息。
 #[test]
 fn test_run_bytecode_file_invalid_magic() {
@@ -88,8 +172,11 @@ fn test_run_yx_file_uses_source_compile_path() {
     let path = PathBuf::from("/nonexistent/path/file.yx");
 
     // Act
-    let err = crate::util::diagnostic::run_file_with_diagnostics(&path, false, "embedded", 0)
-        .expect_err("expected error for nonexistent .yx file");
+    let err = crate::util::diagnostic::run_file_with_diagnostics(
+        &path,
+        crate::util::diagnostic::RunOptions::default(),
+    )
+    .expect_err("expected error for nonexistent .yx file");
 
     // Assert
     let msg = format!("{}", err);
@@ -106,8 +193,11 @@ fn test_run_42_file_uses_bytecode_load_path() {
     let path = PathBuf::from("/nonexistent/path/file.42");
 
     // Act
-    let err = crate::util::diagnostic::run_file_with_diagnostics(&path, false, "embedded", 0)
-        .expect_err("expected error for nonexistent .42 file");
+    let err = crate::util::diagnostic::run_file_with_diagnostics(
+        &path,
+        crate::util::diagnostic::RunOptions::default(),
+    )
+    .expect_err("expected error for nonexistent .42 file");
 
     // Assert
     let msg = format!("{}", err);
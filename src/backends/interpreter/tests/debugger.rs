@@ -0,0 +1,269 @@
+//! 调试器测试（`backends::interpreter::debugger`）
+//!
+//! 测试覆盖内容：
+//! - 行断点与指令断点：在递归调用中逐次命中，报告调用栈与源码位置
+//! - 没有指令的行移到下一行，未知文件与函数不能设断点
+//! - 按行单步：进入、越过与跳出函数
+//! - 暂停时在被暂停的线程上查看寄存器
+//! - 暂停请求与分离后程序运行到结束
+
+use std::thread::JoinHandle;
+
+use crate::backends::common::Value;
+use crate::backends::interpreter::debugger::{
+    Breakpoint, DebugEvent, Debugger, Step, StepUnit, Stop, StopReason,
+};
+use crate::middle::bytecode::BytecodeModule;
+use crate::Engine;
+
+const SOURCE: &str = "\
+fact: (n: Int) -> Int = (n) => {
+    if n <= 1 {
+        return 1
+    }
+    return n * fact(n - 1)
+}
+
+compute: () -> Int = () => {
+    a = fact(3)
+
+    b = a + 1
+    return b
+}
+";
+
+fn compile() -> BytecodeModule {
+    let mut program = Engine::default().compile("debug.yx", SOURCE).unwrap();
+    program.entry_point = None;
+    program
+}
+
+/// 在新线程中调用 `compute`，结束后通知调试器
+fn start(
+    debugger: &Debugger,
+    program: BytecodeModule,
+) -> JoinHandle<i64> {
+    let debugger = debugger.clone();
+    std::thread::spawn(move || {
+        let mut interpreter = Engine::default().interpreter();
+        crate::backends::Executor::execute_module(&mut interpreter, &program).unwrap();
+        debugger.attach(&mut interpreter);
+        let value = interpreter.call("compute", ());
+        debugger.finish();
+        value.unwrap()
+    })
+}
+
+fn stopped(debugger: &Debugger) -> Stop {
+    match debugger.wait() {
+        DebugEvent::Stopped(stop) => stop,
+        DebugEvent::Exited => panic!("program exited instead of stopping"),
+    }
+}
+
+fn line(stop: &Stop) -> usize {
+    stop.frames[0].location.as_ref().unwrap().line
+}
+
+fn functions(stop: &Stop) -> Vec<&str> {
+    stop.frames.iter().map(|f| f.function.as_str()).collect()
+}
+
+#[test]
+fn test_line_breakpoint_hits_recursive_calls() {
+    let program = compile();
+    let debugger = Debugger::new(&program);
+    let id = debugger
+        .set_breakpoint(Breakpoint::Line {
+            file: "debug.yx".to_string(),
+            line: 5,
+        })
+        .unwrap();
+    let runner = start(&debugger, program);
+
+    // fact(1) 在第 3 行返回，不经过第 5 行
+    for depth in 1..=2 {
+        let stop = stopped(&debugger);
+        assert_eq!(stop.reason, StopReason::Breakpoint(id));
+        assert_eq!(line(&stop), 5);
+        assert_eq!(stop.frames.len(), depth + 1);
+        assert_eq!(stop.frames.last().unwrap().function, "compute");
+        let location = stop.frames[0].location.as_ref().unwrap();
+        assert_eq!(location.file.as_deref(), Some("debug.yx"));
+        debugger.resume();
+    }
+    assert_eq!(debugger.wait(), DebugEvent::Exited);
+    assert_eq!(runner.join().unwrap(), 7);
+}
+
+#[test]
+fn test_instruction_breakpoint_and_removal() {
+    let program = compile();
+    let debugger = Debugger::new(&program);
+    let id = debugger
+        .set_breakpoint(Breakpoint::Instruction {
+            function: "fact".to_string(),
+            offset: 0,
+        })
+        .unwrap();
+    let runner = start(&debugger, program);
+
+    let stop = stopped(&debugger);
+    assert_eq!(stop.reason, StopReason::Breakpoint(id));
+    assert_eq!(functions(&stop), ["fact", "compute"]);
+    assert_eq!(stop.frames[0].ip, 0);
+    assert!(debugger.remove_breakpoint(id));
+    assert!(!debugger.remove_breakpoint(id));
+    debugger.resume();
+
+    assert_eq!(debugger.wait(), DebugEvent::Exited);
+    assert_eq!(runner.join().unwrap(), 7);
+}
+
+#[test]
+fn test_breakpoint_resolution() {
+    let debugger = Debugger::new(&compile());
+    // 空行上的断点移到下一条带行号的语句
+    let id = debugger
+        .set_breakpoint(Breakpoint::Line {
+            file: "/work/debug.yx".to_string(),
+            line: 10,
+        })
+        .unwrap();
+    assert_eq!(
+        debugger.breakpoint(id),
+        Some(Breakpoint::Line {
+            file: "/work/debug.yx".to_string(),
+            line: 11,
+        })
+    );
+    // `if` 条件不带行号，断点落在第 5 行
+    let id = debugger
+        .set_breakpoint(Breakpoint::Line {
+            file: "debug.yx".to_string(),
+            line: 2,
+        })
+        .unwrap();
+    assert_eq!(
+        debugger.breakpoint(id),
+        Some(Breakpoint::Line {
+            file: "debug.yx".to_string(),
+            line: 5,
+        })
+    );
+    assert_eq!(
+        debugger.set_breakpoint(Breakpoint::Line {
+            file: "other.yx".to_string(),
+            line: 1,
+        }),
+        None
+    );
+    assert_eq!(
+        debugger.set_breakpoint(Breakpoint::Instruction {
+            function: "missing".to_string(),
+            offset: 0,
+        }),
+        None
+    );
+    assert_eq!(debugger.breakpoints().len(), 2);
+}
+
+#[test]
+fn test_stepping_by_line() {
+    let program = compile();
+    let debugger = Debugger::new(&program);
+    debugger.set_breakpoint(Breakpoint::Line {
+        file: "debug.yx".to_string(),
+        line: 9,
+    });
+    let runner = start(&debugger, program);
+
+    let stop = stopped(&debugger);
+    assert_eq!((functions(&stop), line(&stop)), (vec!["compute"], 9));
+
+    // 进入 fact 的第一条带行号的语句
+    debugger.step(Step::Into, StepUnit::Line);
+    let stop = stopped(&debugger);
+    assert_eq!(stop.reason, StopReason::Step);
+    assert_eq!(
+        (functions(&stop), line(&stop)),
+        (vec!["fact", "compute"], 5)
+    );
+
+    // 进入递归调用
+    debugger.step(Step::Into, StepUnit::Line);
+    let stop = stopped(&debugger);
+    assert_eq!(functions(&stop), ["fact", "fact", "compute"]);
+
+    // 跳出内层 fact，回到调用所在的行
+    debugger.step(Step::Out, StepUnit::Line);
+    let stop = stopped(&debugger);
+    assert_eq!(
+        (functions(&stop), line(&stop)),
+        (vec!["fact", "compute"], 5)
+    );
+
+    // 越过本行剩余的指令，回到 compute
+    debugger.step(Step::Over, StepUnit::Line);
+    let stop = stopped(&debugger);
+    assert_eq!((functions(&stop), line(&stop)), (vec!["compute"], 9));
+
+    debugger.step(Step::Over, StepUnit::Line);
+    let stop = stopped(&debugger);
+    assert_eq!((functions(&stop), line(&stop)), (vec!["compute"], 11));
+
+    debugger.resume();
+    assert_eq!(debugger.wait(), DebugEvent::Exited);
+    assert_eq!(runner.join().unwrap(), 7);
+}
+
+#[test]
+fn test_inspect_paused_thread() {
+    let program = compile();
+    let debugger = Debugger::new(&program);
+    // 第 1 条指令之后参数已载入寄存器
+    debugger.set_breakpoint(Breakpoint::Instruction {
+        function: "fact".to_string(),
+        offset: 1,
+    });
+    assert!(debugger.inspect(|vm| vm.ip()).is_none());
+    let runner = start(&debugger, program);
+
+    stopped(&debugger);
+    let (name, registers) = debugger
+        .inspect(|vm| (vm.function().name.clone(), vm.registers().to_vec()))
+        .unwrap();
+    assert_eq!(name, "fact");
+    assert!(registers.contains(&Value::int(3)));
+    debugger.resume();
+
+    // 第二次进入 fact 时调用方仍在等待，参数为 2
+    stopped(&debugger);
+    let caller = debugger
+        .inspect(|vm| vm.caller_registers(1).map(<[_]>::to_vec))
+        .unwrap();
+    assert!(caller.is_some_and(|registers| registers.contains(&Value::int(3))));
+    debugger.detach();
+
+    assert_eq!(debugger.wait(), DebugEvent::Exited);
+    assert_eq!(runner.join().unwrap(), 7);
+}
+
+#[test]
+fn test_pause_stops_at_next_instruction() {
+    let program = compile();
+    let debugger = Debugger::new(&program);
+    debugger.pause();
+    let runner = start(&debugger, program);
+
+    let stop = stopped(&debugger);
+    assert_eq!(stop.reason, StopReason::Pause);
+    assert_eq!(stop.frames[0].ip, 0);
+    assert_eq!(debugger.stopped(), Some(stop));
+    debugger.step(Step::Into, StepUnit::Instruction);
+    assert_eq!(stopped(&debugger).frames[0].ip, 1);
+    debugger.resume();
+
+    assert_eq!(debugger.wait(), DebugEvent::Exited);
+    assert_eq!(runner.join().unwrap(), 7);
+}
@@ -0,0 +1,564 @@
+//! 原生扩展包测试
+//!
+//! 用系统 C 编译器现场构建一个扩展 cdylib；找不到 `cc` 时跳过。
+//!
+//! 覆盖场景：
+//! - 描述符加载、签名校验与 Int/String 编组
+//! - 列表参数按元素借出，返回的列表与元组复制到堆上
+//! - 非零状态码携带错误消息
+//! - 函数参数经 `invoke` 回调执行，其错误原样传出
+//! - 每个 VM 拥有独立的扩展状态，VM 释放时调用 `state_drop`
+//! - ABI 版本不匹配时拒绝加载
+//! - vendor 依赖通过 `[native]` 自动安装，`use demo` 后可直接调用
+//! - 能力策略禁用原生扩展时拒绝加载
+//! - sqlite 连接句柄属于打开它的 VM，VM 释放时关闭连接
+//! - sqlite 事务体失败时回滚
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::backends::common::{Heap, HeapValue, RuntimeValue};
+use crate::backends::interpreter::Interpreter;
+use crate::backends::interpreter::extension::{
+    self, ExtensionFunction, NativeExtension, EXTENSION_ABI_VERSION,
+};
+use crate::backends::{CapabilityPolicy, Executor, ExecutorError};
+use crate::std::{NativeContext, NativeResources};
+
+const EXTENSION_SOURCE: &str = r#"
+#include <stdint.h>
+#include <stddef.h>
+#include <stdio.h>
+#include <stdlib.h>
+
+typedef struct { uint32_t tag; int64_t i; double f; const uint8_t *ptr; size_t len; } YxValue;
+typedef int32_t (*YxInvokeFn)(void *, const YxValue *, const YxValue *, size_t, YxValue *);
+typedef struct { void *state; void *host; YxInvokeFn invoke; } YxCall;
+typedef int32_t (*YxNativeFn)(const YxCall *, const YxValue *, size_t, YxValue *);
+typedef struct { const char *name; const char *signature; YxNativeFn func; } YxFunctionDecl;
+typedef struct {
+    uint32_t abi_version; const char *name; const YxFunctionDecl *functions; size_t function_count;
+    void *(*state_new)(void); void (*state_drop)(void *);
+} YxExtension;
+
+static int32_t add(const YxCall *call, const YxValue *args, size_t argc, YxValue *out) {
+    out->tag = 2;
+    out->i = args[0].i + args[1].i;
+    return 0;
+}
+
+static char greeting[256];
+static int32_t greet(const YxCall *call, const YxValue *args, size_t argc, YxValue *out) {
+    int len = snprintf(greeting, sizeof greeting, "hello, %.*s", (int)args[0].len, (const char *)args[0].ptr);
+    out->tag = 4;
+    out->ptr = (const uint8_t *)greeting;
+    out->len = (size_t)len;
+    return 0;
+}
+
+/* 把字符串列表编号为 (序号, 元素) 元组的列表 */
+static YxValue pair_items[64];
+static YxValue pair_list[32];
+static int32_t number(const YxCall *call, const YxValue *args, size_t argc, YxValue *out) {
+    const YxValue *xs = (const YxValue *)args[0].ptr;
+    size_t n = args[0].len < 32 ? args[0].len : 32;
+    for (size_t k = 0; k < n; k++) {
+        pair_items[2 * k] = (YxValue){ 2, (int64_t)k, 0.0, NULL, 0 };
+        pair_items[2 * k + 1] = xs[k];
+        pair_list[k] = (YxValue){ 7, 0, 0.0, (const uint8_t *)&pair_items[2 * k], 2 };
+    }
+    out->tag = 6;
+    out->ptr = (const uint8_t *)pair_list;
+    out->len = n;
+    return 0;
+}
+
+static int32_t fail(const YxCall *call, const YxValue *args, size_t argc, YxValue *out) {
+    static const char message[] = "boom";
+    out->tag = 4;
+    out->ptr = (const uint8_t *)message;
+    out->len = sizeof message - 1;
+    return 1;
+}
+
+/* 把函数参数作用两次：twice(f, x) = f(f(x)) */
+static int32_t twice(const YxCall *call, const YxValue *args, size_t argc, YxValue *out) {
+    YxValue once;
+    int32_t status = call->invoke(call->host, &args[0], &args[1], 1, &once);
+    if (status != 0) {
+        *out = once;
+        return status;
+    }
+    return call->invoke(call->host, &args[0], &once, 1, out);
+}
+
+/* 每个 VM 一个计数器，释放时记入 dropped */
+static int64_t dropped;
+static void *counter_new(void) { return calloc(1, sizeof(int64_t)); }
+static void counter_drop(void *state) { free(state); dropped++; }
+
+static int32_t count(const YxCall *call, const YxValue *args, size_t argc, YxValue *out) {
+    int64_t *counter = call->state;
+    out->tag = 2;
+    out->i = ++*counter;
+    return 0;
+}
+
+static int32_t drops(const YxCall *call, const YxValue *args, size_t argc, YxValue *out) {
+    out->tag = 2;
+    out->i = dropped;
+    return 0;
+}
+
+static const YxFunctionDecl functions[] = {
+    { "add", "(a: Int, b: Int) -> Int", add },
+    { "greet", "(name: String) -> String", greet },
+    { "fail", "() -> Int", fail },
+    { "number", "(xs: List(String)) -> List((Int, String))", number },
+    { "twice", "(f: (x: Int) -> Int, x: Int) -> Int", twice },
+    { "count", "() -> Int", count },
+    { "drops", "() -> Int", drops },
+};
+
+static const YxExtension descriptor = { EXT_ABI, EXT_NAME, functions, 7, counter_new, counter_drop };
+
+const YxExtension *yaoxiang_extension(void) { return &descriptor; }
+"#;
+
+/// 在挂有 `resources` 的上下文中调用扩展函数
+fn call(
+    function: &ExtensionFunction,
+    args: &[RuntimeValue],
+    heap: &mut Heap,
+    resources: &NativeResources,
+) -> Result<RuntimeValue, ExecutorError> {
+    function.call(
+        args,
+        &mut NativeContext::new(heap).with_resources(resources),
+    )
+}
+
+/// 编译扩展到 `dir/lib<name>.so`，没有 C 编译器时返回 None
+fn build_extension(
+    dir: &Path,
+    name: &str,
+    abi_version: u32,
+) -> Option<PathBuf> {
+    let source = dir.join(format!("{name}.c"));
+    let library = dir.join(format!("lib{name}.so"));
+    std::fs::write(&source, EXTENSION_SOURCE).unwrap();
+    let status = Command::new("cc")
+        .arg("-shared")
+        .arg("-fPIC")
+        .arg(format!("-DEXT_ABI={abi_version}"))
+        .arg(format!("-DEXT_NAME=\"{name}\""))
+        .arg("-o")
+        .arg(&library)
+        .arg(&source)
+        .status()
+        .ok()?;
+    status.success().then_some(library)
+}
+
+#[test]
+fn test_load_and_call_extension() {
+    let tmp = tempfile::TempDir::new().unwrap();
+    let Some(library) = build_extension(tmp.path(), "ext_call", EXTENSION_ABI_VERSION) else {
+        return;
+    };
+
+    let ext = NativeExtension::load(&library).unwrap();
+    let mut heap = Heap::new();
+    let resources = NativeResources::default();
+    assert_eq!(ext.name(), "ext_call");
+    let info = ext.module_info();
+    assert_eq!(info.exports["add"].full_path, "ext_call.add");
+    assert_eq!(info.exports["greet"].signature, "(name: String) -> String");
+
+    let add = &ext.functions()[0];
+    let sum = call(
+        add,
+        &[RuntimeValue::Int(2), RuntimeValue::Int(40)],
+        &mut heap,
+        &resources,
+    )
+    .unwrap();
+    assert!(matches!(sum, RuntimeValue::Int(42)));
+
+    let greet = &ext.functions()[1];
+    let greeting = call(
+        greet,
+        &[RuntimeValue::String("yx".into())],
+        &mut heap,
+        &resources,
+    )
+    .unwrap();
+    assert!(matches!(greeting, RuntimeValue::String(s) if &*s == "hello, yx"));
+
+    // 参数类型与声明签名不符
+    assert!(call(
+        add,
+        &[RuntimeValue::Int(1), RuntimeValue::Bool(true)],
+        &mut heap,
+        &resources,
+    )
+    .is_err());
+
+    let err = call(&ext.functions()[2], &[], &mut heap, &resources).unwrap_err();
+    assert!(format!("{err}").contains("boom"), "{err}");
+}
+
+#[test]
+fn test_lists_and_tuples_cross_the_boundary() {
+    let tmp = tempfile::TempDir::new().unwrap();
+    let Some(library) = build_extension(tmp.path(), "ext_list", EXTENSION_ABI_VERSION) else {
+        return;
+    };
+
+    let ext = NativeExtension::load(&library).unwrap();
+    let number = &ext.functions()[3];
+    assert_eq!(
+        number.signature,
+        "(xs: List(String)) -> List((Int, String))"
+    );
+
+    let mut heap = Heap::new();
+    let resources = NativeResources::default();
+    let xs = heap.allocate(HeapValue::list([
+        RuntimeValue::String("a".into()),
+        RuntimeValue::String("b".into()),
+    ]));
+    let RuntimeValue::List(result) =
+        call(number, &[RuntimeValue::List(xs)], &mut heap, &resources).unwrap()
+    else {
+        panic!("expected a list");
+    };
+    let Some(HeapValue::List(pairs)) = heap.get(result) else {
+        panic!("expected a list on the heap");
+    };
+    let pairs: Vec<(i64, String)> = pairs
+        .iter()
+        .map(|pair| {
+            let RuntimeValue::Tuple(pair) = pair.get() else {
+                panic!("expected a tuple, got {pair:?}");
+            };
+            match heap.get(pair) {
+                Some(HeapValue::Tuple(items)) => match (items[0].get(), items[1].get()) {
+                    (RuntimeValue::Int(i), RuntimeValue::String(s)) => (i, s.to_string()),
+                    other => panic!("unexpected tuple {other:?}"),
+                },
+                other => panic!("unexpected heap value {other:?}"),
+            }
+        })
+        .collect();
+    assert_eq!(pairs, vec![(0, "a".to_string()), (1, "b".to_string())]);
+
+    // 列表元素无法跨越边界时拒绝调用
+    let dict = RuntimeValue::Dict(heap.allocate(HeapValue::Dict(Default::default())));
+    let bad = heap.allocate(HeapValue::list([dict]));
+    assert!(call(number, &[RuntimeValue::List(bad)], &mut heap, &resources).is_err());
+}
+
+#[test]
+fn test_function_arguments_are_invoked() {
+    let tmp = tempfile::TempDir::new().unwrap();
+    let Some(library) = build_extension(tmp.path(), "ext_invoke", EXTENSION_ABI_VERSION) else {
+        return;
+    };
+    extension::install(NativeExtension::load(&library).unwrap());
+
+    let engine = crate::Engine::default();
+    let program = engine
+        .compile(
+            "main.yx",
+            "use ext_invoke\n\nmain = {\n    assert_eq(ext_invoke.twice(x => x * 3, 2), 18)\n}\n",
+        )
+        .unwrap();
+    assert_eq!(engine.run(&program).unwrap(), 0);
+
+    // 函数参数出错时报告原始错误
+    let program = engine
+        .compile(
+            "main.yx",
+            "use ext_invoke\n\nmain = {\n    ext_invoke.twice(x => x / 0, 2)\n}\n",
+        )
+        .unwrap();
+    let err = engine.run(&program).unwrap_err();
+    assert!(format!("{err}").contains("zero"), "{err}");
+}
+
+#[test]
+fn test_extension_state_is_per_vm() {
+    let tmp = tempfile::TempDir::new().unwrap();
+    let Some(library) = build_extension(tmp.path(), "ext_state", EXTENSION_ABI_VERSION) else {
+        return;
+    };
+    let ext = NativeExtension::load(&library).unwrap();
+    let (count, drops) = (&ext.functions()[5], &ext.functions()[6]);
+    let mut heap = Heap::new();
+    let first = NativeResources::default();
+    let second = NativeResources::default();
+
+    for expected in 1..=3 {
+        assert_eq!(
+            call(count, &[], &mut heap, &first).unwrap(),
+            RuntimeValue::Int(expected)
+        );
+    }
+    assert_eq!(
+        call(count, &[], &mut heap, &second).unwrap(),
+        RuntimeValue::Int(1)
+    );
+    // 状态函数只能在 VM 中调用
+    assert!(count.call(&[], &mut NativeContext::new(&mut heap)).is_err());
+
+    drop(first);
+    assert_eq!(
+        call(drops, &[], &mut heap, &second).unwrap(),
+        RuntimeValue::Int(1)
+    );
+    drop(second);
+    assert_eq!(
+        call(drops, &[], &mut heap, &NativeResources::default()).unwrap(),
+        RuntimeValue::Int(2)
+    );
+}
+
+#[test]
+fn test_abi_version_mismatch_is_rejected() {
+    let tmp = tempfile::TempDir::new().unwrap();
+    let Some(library) = build_extension(tmp.path(), "ext_abi", EXTENSION_ABI_VERSION + 1) else {
+        return;
+    };
+
+    let err = NativeExtension::load(&library).unwrap_err();
+    assert!(format!("{err}").contains("ABI version"), "{err}");
+}
+
+/// 在 `project` 下创建带 `[native]` 的 vendor 依赖
+fn vendor_extension(
+    project: &Path,
+    name: &str,
+) -> bool {
+    let dep_dir = project
+        .join(".yaoxiang/vendor")
+        .join(format!("{name}-0.1.0"));
+    std::fs::create_dir_all(&dep_dir).unwrap();
+    if build_extension(&dep_dir, name, EXTENSION_ABI_VERSION).is_none() {
+        return false;
+    }
+    std::fs::write(
+        dep_dir.join("yaoxiang.toml"),
+        format!(
+            "[package]\nname = \"{name}\"\nversion = \"0.1.0\"\n\n[native]\nlibrary = \"lib{name}.so\"\n"
+        ),
+    )
+    .unwrap();
+    std::fs::write(
+        project.join("yaoxiang.toml"),
+        "[package]\nname = \"app\"\nversion = \"0.1.0\"\n",
+    )
+    .unwrap();
+    true
+}
+
+#[test]
+fn test_vendored_extension_is_callable_from_yaoxiang() {
+    let tmp = tempfile::TempDir::new().unwrap();
+    if !vendor_extension(tmp.path(), "ext_demo") {
+        return;
+    }
+    let main = tmp.path().join("main.yx");
+    std::fs::write(
+        &main,
+        "use ext_demo\n\nmain = {\n    assert_eq(ext_demo.add(2, 3), 5)\n    assert_eq(ext_demo.greet(\"native\"), \"hello, native\")\n}\n",
+    )
+    .unwrap();
+
+    // 默认能力策略拒绝原生扩展，须由宿主显式授予
+    assert!(crate::run_file(&main).is_err());
+    extension::load_for_file(&main, &CapabilityPolicy::allow_all()).unwrap();
+    let engine = crate::Engine::default();
+    let source = std::fs::read_to_string(&main).unwrap();
+    let program = engine.compile("main.yx", &source).unwrap();
+    assert_eq!(engine.run(&program).unwrap(), 0);
+    assert!(extension::installed()
+        .iter()
+        .any(|ext| ext.name() == "ext_demo"));
+}
+
+#[test]
+fn test_policy_denies_native_extensions() {
+    let tmp = tempfile::TempDir::new().unwrap();
+    if !vendor_extension(tmp.path(), "ext_denied") {
+        return;
+    }
+
+    let err =
+        extension::load_project_extensions(tmp.path(), &CapabilityPolicy::deny_all()).unwrap_err();
+    assert!(format!("{err}").contains("capability policy"), "{err}");
+    assert!(!extension::installed()
+        .iter()
+        .any(|ext| ext.name() == "ext_denied"));
+}
+
+/// 构建 `extensions/sqlite` 并按其 `yaoxiang.toml` 放入 `project` 的 vendor 目录；
+/// 构建失败（如缺少 libsqlite3）时返回 false
+fn vendor_sqlite_package(project: &Path) -> bool {
+    let package = Path::new(env!("CARGO_MANIFEST_DIR")).join("extensions/sqlite");
+    let target = project.join("sqlite-target");
+    let built = Command::new(std::env::var("CARGO").unwrap_or_else(|_| "cargo".to_string()))
+        .arg("build")
+        .arg("--release")
+        .arg("--manifest-path")
+        .arg(package.join("Cargo.toml"))
+        .arg("--target-dir")
+        .arg(&target)
+        .stderr(std::process::Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success());
+    if !built {
+        return false;
+    }
+
+    let dep_dir = project.join(".yaoxiang/vendor/sqlite-0.1.0");
+    let release = dep_dir.join("target/release");
+    std::fs::create_dir_all(&release).unwrap();
+    std::fs::copy(
+        target.join("release/libyaoxiang_sqlite.so"),
+        release.join("libyaoxiang_sqlite.so"),
+    )
+    .unwrap();
+    std::fs::copy(package.join("yaoxiang.toml"), dep_dir.join("yaoxiang.toml")).unwrap();
+    std::fs::write(
+        project.join("yaoxiang.toml"),
+        "[package]\nname = \"app\"\nversion = \"0.1.0\"\n",
+    )
+    .unwrap();
+    true
+}
+
+/// 在新的 VM 中运行 `source`，返回 VM 以便测试控制其释放时机
+fn vm_run(source: &str) -> (Interpreter, Result<(), String>) {
+    let engine = crate::Engine::default();
+    let program = engine.compile("main.yx", source).expect("compile");
+    let mut interpreter = engine.interpreter();
+    let result = interpreter
+        .execute_module(&program)
+        .map_err(|e| e.to_string());
+    (interpreter, result)
+}
+
+/// 安装 vendor 进 `project` 的 sqlite 包；构建失败时返回 false
+fn install_sqlite(project: &Path) -> bool {
+    if !vendor_sqlite_package(project) {
+        return false;
+    }
+    let main = project.join("main.yx");
+    std::fs::write(&main, "").unwrap();
+    assert_eq!(
+        extension::load_for_file(&main, &CapabilityPolicy::allow_all()).unwrap(),
+        vec!["sqlite".to_string()]
+    );
+    true
+}
+
+#[test]
+fn test_sqlite_package_runs_queries() {
+    let tmp = tempfile::TempDir::new().unwrap();
+    if !install_sqlite(tmp.path()) {
+        return;
+    }
+    let script = Path::new(env!("CARGO_MANIFEST_DIR")).join("extensions/sqlite/tests/sqlite.yx");
+    let source = std::fs::read_to_string(script).unwrap();
+    let (_, result) = vm_run(&source);
+    result.unwrap();
+}
+
+#[test]
+fn test_sqlite_connections_belong_to_their_vm() {
+    let tmp = tempfile::TempDir::new().unwrap();
+    if !install_sqlite(tmp.path()) {
+        return;
+    }
+    let (_owner, opened) = vm_run(
+        "use sqlite\n\nmain = {\n    db = sqlite.open(\":memory:\")\n    assert_eq(db, 1)\n}\n",
+    );
+    opened.unwrap();
+
+    // 连接句柄只在打开它的 VM 中有效
+    let (_other, result) = vm_run("use sqlite\n\nmain = {\n    sqlite.close(1)\n}\n");
+    let err = result.unwrap_err();
+    assert!(err.contains("Invalid SQLite connection"), "{err}");
+
+    // 句柄关闭后失效，而不是访问已关闭的连接
+    let (_, result) = vm_run(
+        "use sqlite\n\nmain = {\n    db = sqlite.open(\":memory:\")\n    sqlite.close(db)\n    sqlite.close(db)\n}\n",
+    );
+    let err = result.unwrap_err();
+    assert!(err.contains("Invalid SQLite connection"), "{err}");
+}
+
+#[test]
+fn test_dropping_the_vm_closes_sqlite_connections() {
+    let tmp = tempfile::TempDir::new().unwrap();
+    if !install_sqlite(tmp.path()) {
+        return;
+    }
+    let path = tmp.path().join("locked.db");
+    // 独占锁定模式下，首次写入后连接一直持有文件锁，直到连接关闭
+    let holder = format!(
+        "use sqlite\n\nmain = {{\n    db = sqlite.open(\"{}\")\n    \
+         sqlite.execute(db, \"PRAGMA locking_mode=EXCLUSIVE\", [])\n    \
+         sqlite.execute(db, \"CREATE TABLE t (x INTEGER)\", [])\n}}\n",
+        path.display()
+    );
+    let writer = format!(
+        "use sqlite\n\nmain = {{\n    db = sqlite.open(\"{}\")\n    \
+         sqlite.execute(db, \"INSERT INTO t VALUES (1)\", [])\n    sqlite.close(db)\n}}\n",
+        path.display()
+    );
+
+    let (holder_vm, held) = vm_run(&holder);
+    held.unwrap();
+    let (_, blocked) = vm_run(&writer);
+    let err = blocked.unwrap_err();
+    assert!(err.contains("locked"), "{err}");
+
+    drop(holder_vm);
+    let (_, written) = vm_run(&writer);
+    written.unwrap();
+}
+
+#[test]
+fn test_sqlite_transaction_rolls_back_on_failure() {
+    let tmp = tempfile::TempDir::new().unwrap();
+    if !install_sqlite(tmp.path()) {
+        return;
+    }
+    let setup = "db = sqlite.open(\":memory:\")\n    \
+                 sqlite.execute(db, \"CREATE TABLE t (x INTEGER)\", [])\n    \
+                 sqlite.transaction(db, () => {\n        \
+                 sqlite.execute(db, \"INSERT INTO t VALUES (1)\", [])\n    })";
+    let failing = "sqlite.transaction(db, () => {\n        \
+                   sqlite.execute(db, \"INSERT INTO t VALUES (2)\", [])\n        \
+                   sqlite.execute(db, \"INSERT INTO missing VALUES (3)\", [])\n    })";
+
+    // 事务体中途失败：已执行的插入被回滚，之后可以开始新事务
+    let (_, result) = vm_run(&format!(
+        "use std.list\nuse std.result\nuse sqlite\n\nmain = {{\n    {setup}\n    \
+         assert_eq(is_err(recover(() => {failing})), true)\n    \
+         rows = sqlite.query(db, \"SELECT x FROM t\", [])\n    \
+         assert_eq(list.len(rows), 1)\n    \
+         sqlite.transaction(db, () => {{\n        \
+         sqlite.execute(db, \"INSERT INTO t VALUES (4)\", [])\n    }})\n}}\n"
+    ));
+    result.unwrap();
+
+    // 错误原样传出
+    let (_, failed) = vm_run(&format!(
+        "use sqlite\n\nmain = {{\n    {setup}\n    {failing}\n}}\n"
+    ));
+    let err = failed.unwrap_err();
+    assert!(err.contains("no such table"), "{err}");
+}
@@ -12,6 +12,10 @@
 //! - 错误处理
 //! - std.result 模块（is_ok, is_err, unwrap, unwrap_or）
 //! - std.string.parse_int / parse_float
+//! - std.string.format 数字格式说明符（千分位、精度、进制）
+//! - std.locale 区域化数字格式
+//! - std.env.args 程序参数（argv[0] 为脚本路径）
+//! - std.process.exit 退出码
 
 use crate::backends::common::RuntimeValue;
 use crate::backends::common::Heap;
@@ -459,3 +463,288 @@ fn test_parse_float_returns_err_for_invalid_input() {
         "parse_float('not_a_number') should return an Err"
     );
 }
+
+// ============================================================================
+// std.string.format 数字格式 / std.locale
+// ============================================================================
+
+/// 通过注册表调用 std.string.format
+fn format_with(
+    fmt: &str,
+    values: &[RuntimeValue],
+) -> String {
+    let registry = FfiRegistry::with_std();
+    let mut heap = Heap::new();
+    let mut ctx = test_ctx(&mut heap);
+    let mut args = vec![RuntimeValue::String(fmt.into())];
+    args.extend_from_slice(values);
+    match registry.call("std.string.format", &args, &mut ctx).unwrap() {
+        RuntimeValue::String(s) => s.to_string(),
+        other => panic!("Expected String, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_format_float_precision() {
+    assert_eq!(
+        format_with("{0:.2f}", &[RuntimeValue::Float(1.23456)]),
+        "1.23"
+    );
+    assert_eq!(format_with("{0:.0f}", &[RuntimeValue::Float(2.5)]), "2");
+    assert_eq!(
+        format_with("{0:f}", &[RuntimeValue::Float(1.5)]),
+        "1.500000"
+    );
+    assert_eq!(
+        format_with("{0:.1%}", &[RuntimeValue::Float(0.256)]),
+        "25.6%"
+    );
+    assert_eq!(
+        format_with("{0:.2e}", &[RuntimeValue::Float(1234.5)]),
+        "1.23e3"
+    );
+    // 整数配合精度按浮点输出
+    assert_eq!(format_with("{0:.2f}", &[RuntimeValue::Int(3)]), "3.00");
+}
+
+#[test]
+fn test_format_default_float_is_locale_independent() {
+    assert_eq!(format_with("{0}", &[RuntimeValue::Float(2.0)]), "2.0");
+    assert_eq!(format_with("{0}", &[RuntimeValue::Float(-0.5)]), "-0.5");
+}
+
+#[test]
+fn test_format_thousands_separators() {
+    assert_eq!(
+        format_with("{0:,}", &[RuntimeValue::Int(1234567)]),
+        "1,234,567"
+    );
+    assert_eq!(
+        format_with("{0:_}", &[RuntimeValue::Int(-1234567)]),
+        "-1_234_567"
+    );
+    assert_eq!(format_with("{0:,}", &[RuntimeValue::Int(999)]), "999");
+    assert_eq!(
+        format_with("{0:,.2f}", &[RuntimeValue::Float(1234567.891)]),
+        "1,234,567.89"
+    );
+}
+
+#[test]
+fn test_format_width_and_zero_padding() {
+    assert_eq!(
+        format_with("{0:08.3f}", &[RuntimeValue::Float(1.23456)]),
+        "0001.235"
+    );
+    assert_eq!(format_with("{0:05}", &[RuntimeValue::Int(-42)]), "-0042");
+    assert_eq!(format_with("{0:>6}", &[RuntimeValue::Int(42)]), "    42");
+    assert_eq!(format_with("{0:*<6}", &[RuntimeValue::Int(42)]), "42****");
+    assert_eq!(
+        format_with("{0:^7}", &[RuntimeValue::String("ab".into())]),
+        "  ab   "
+    );
+    assert_eq!(
+        format_with("{0:.3}", &[RuntimeValue::String("abcdef".into())]),
+        "abc"
+    );
+}
+
+#[test]
+fn test_format_integer_radix() {
+    assert_eq!(format_with("{0:x}", &[RuntimeValue::Int(255)]), "ff");
+    assert_eq!(format_with("{0:X}", &[RuntimeValue::Int(255)]), "FF");
+    assert_eq!(format_with("{0:08b}", &[RuntimeValue::Int(5)]), "00000101");
+    assert_eq!(format_with("{0:o}", &[RuntimeValue::Int(8)]), "10");
+}
+
+fn call_locale(
+    name: &str,
+    args: &[RuntimeValue],
+) -> String {
+    let registry = FfiRegistry::with_std();
+    let mut heap = Heap::new();
+    let mut ctx = test_ctx(&mut heap);
+    match registry.call(name, args, &mut ctx).unwrap() {
+        RuntimeValue::String(s) => s.to_string(),
+        other => panic!("Expected String, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_locale_format_int_uses_locale_group_separator() {
+    let n = RuntimeValue::Int(1234567);
+    assert_eq!(
+        call_locale(
+            "std.locale.format_int",
+            &[n.clone(), RuntimeValue::String("en-US".into())]
+        ),
+        "1,234,567"
+    );
+    assert_eq!(
+        call_locale(
+            "std.locale.format_int",
+            &[n.clone(), RuntimeValue::String("de_DE.UTF-8".into())]
+        ),
+        "1.234.567"
+    );
+    assert_eq!(
+        call_locale(
+            "std.locale.format_int",
+            &[n, RuntimeValue::String("C".into())]
+        ),
+        "1234567"
+    );
+}
+
+#[test]
+fn test_locale_format_float_uses_locale_decimal_separator() {
+    let x = RuntimeValue::Float(-1234.5);
+    assert_eq!(
+        call_locale(
+            "std.locale.format_float",
+            &[
+                x.clone(),
+                RuntimeValue::Int(2),
+                RuntimeValue::String("de-DE".into())
+            ]
+        ),
+        "-1.234,50"
+    );
+    assert_eq!(
+        call_locale(
+            "std.locale.format_float",
+            &[x, RuntimeValue::Int(-1), RuntimeValue::String("fr".into())]
+        ),
+        "-1\u{202F}234,5"
+    );
+}
+
+#[test]
+fn test_locale_separators() {
+    let de = RuntimeValue::String("de-CH".into());
+    assert_eq!(
+        call_locale("std.locale.decimal_separator", std::slice::from_ref(&de)),
+        "."
+    );
+    assert_eq!(call_locale("std.locale.group_separator", &[de]), "\u{2019}");
+    // 未知语言回退到英文习惯
+    let unknown = RuntimeValue::String("xx".into());
+    assert_eq!(
+        call_locale(
+            "std.locale.decimal_separator",
+            std::slice::from_ref(&unknown)
+        ),
+        "."
+    );
+    assert_eq!(call_locale("std.locale.group_separator", &[unknown]), ",");
+}
+
+// ============================================================================
+// std.env 程序参数
+// ============================================================================
+
+#[test]
+fn test_env_args_returns_program_args_as_list() {
+    use crate::backends::common::HeapValue;
+
+    crate::std::env::set_program_args(vec![
+        "tool.yx".to_string(),
+        "--verbose".to_string(),
+        "input.txt".to_string(),
+    ]);
+
+    let registry = FfiRegistry::with_std();
+    let mut heap = Heap::new();
+    let mut ctx = test_ctx(&mut heap);
+    let handle = match registry.call("std.env.args", &[], &mut ctx).unwrap() {
+        RuntimeValue::List(handle) => handle,
+        other => panic!("Expected List, got {:?}", other),
+    };
+    let items: Vec<String> = match heap.get(handle) {
+        Some(HeapValue::List(items)) => items
+            .iter()
+            .map(|item| match item.get() {
+                RuntimeValue::String(s) => s.to_string(),
+                other => panic!("Expected String, got {:?}", other),
+            })
+            .collect(),
+        other => panic!("Expected list on heap, got {:?}", other),
+    };
+    assert_eq!(items, ["tool.yx", "--verbose", "input.txt"]);
+
+    // std.os.args 与 std.env.args 看到同一份参数
+    let mut ctx = test_ctx(&mut heap);
+    match registry.call("std.os.args", &[], &mut ctx).unwrap() {
+        RuntimeValue::String(s) => assert_eq!(&*s, "tool.yx\n--verbose\ninput.txt"),
+        other => panic!("Expected String, got {:?}", other),
+    }
+}
+
+// ============================================================================
+// std.process 退出码
+// ============================================================================
+
+#[test]
+fn test_process_exit_returns_exit_error() {
+    let registry = FfiRegistry::with_std();
+    let mut heap = Heap::new();
+    let mut ctx = test_ctx(&mut heap);
+    let result = registry.call("std.process.exit", &[RuntimeValue::Int(42)], &mut ctx);
+    assert_eq!(result, Err(ExecutorError::Exit(42)));
+}
+
+#[test]
+fn test_process_exit_rejects_non_int_code() {
+    let registry = FfiRegistry::with_std();
+    let mut heap = Heap::new();
+    let mut ctx = test_ctx(&mut heap);
+    let result = registry.call(
+        "std.process.exit",
+        &[RuntimeValue::String("1".into())],
+        &mut ctx,
+    );
+    assert!(matches!(result, Err(ExecutorError::Type(..))));
+}
+
+// ============================================================================
+// std.signal 信号处理
+// ============================================================================
+
+#[test]
+fn test_signal_on_rejects_unknown_signal_name() {
+    let registry = FfiRegistry::with_std();
+    let mut heap = Heap::new();
+    let mut ctx = test_ctx(&mut heap);
+    let result = registry.call(
+        "std.signal.on",
+        &[RuntimeValue::String("SIGHUP".into()), RuntimeValue::Unit],
+        &mut ctx,
+    );
+    match result {
+        Err(ExecutorError::Runtime(msg, _)) => assert!(msg.contains("SIGHUP"), "{}", msg),
+        other => panic!("Expected runtime error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_signal_on_rejects_non_function_handler() {
+    let registry = FfiRegistry::with_std();
+    let mut heap = Heap::new();
+    let mut ctx = test_ctx(&mut heap);
+    let result = registry.call(
+        "std.signal.on",
+        &[RuntimeValue::String("SIGINT".into()), RuntimeValue::Int(1)],
+        &mut ctx,
+    );
+    assert!(matches!(result, Err(ExecutorError::Type(..))));
+}
+
+#[test]
+fn test_signal_names_and_exit_codes() {
+    use crate::std::signal::Signal;
+    assert_eq!(Signal::from_name("SIGINT"), Some(Signal::Interrupt));
+    assert_eq!(Signal::from_name("SIGTERM"), Some(Signal::Terminate));
+    assert_eq!(Signal::from_name("sigint"), None);
+    assert_eq!(Signal::Interrupt.exit_code(), 130);
+    assert_eq!(Signal::Terminate.exit_code(), 143);
+}
@@ -3,9 +3,10 @@
 //! 测试覆盖内容：
 //! - Frame 的创建和初始化
 //! - 局部变量的访问和修改
+//! - FramePool 复用返回帧的缓冲区，且最多保留 capacity 份
 
 use crate::backends::common::RuntimeValue;
-use crate::backends::interpreter::frames::Frame;
+use crate::backends::interpreter::frames::{Frame, FramePool};
 use crate::middle::bytecode::BytecodeFunction;
 use std::collections::HashMap;
 
@@ -19,7 +20,8 @@ fn make_test_function() -> BytecodeFunction {
         instructions: vec![],
         labels: HashMap::new(),
         exception_handlers: vec![],
-        debug_map: HashMap::new(),
+        line_table: crate::util::span::LineTable::new(),
+        decoded: Default::default(),
     }
 }
 
@@ -38,3 +40,23 @@ fn test_frame_local_access() {
     frame.set_local(0, RuntimeValue::Int(42));
     assert_eq!(frame.get_local(0).unwrap().to_int(), Some(42));
 }
+
+#[test]
+fn test_frame_pool_reuses_buffers() {
+    let mut pool = FramePool::new(1);
+    let mut frame = pool.frame(make_test_function(), &[RuntimeValue::Int(7)]);
+    assert_eq!(frame.get_local(0), Some(RuntimeValue::Int(7)));
+    frame.set_register(40, RuntimeValue::Int(1));
+    let registers = frame.registers.as_ptr();
+    pool.release(frame);
+    pool.release(Frame::new(make_test_function()));
+    assert_eq!(pool.len(), 1);
+
+    // 复用的缓冲区不带上一帧的值
+    let frame = pool.frame(make_test_function(), &[]);
+    assert!(pool.is_empty());
+    assert_eq!(frame.registers.as_ptr(), registers);
+    assert!(frame.registers.is_empty());
+    assert_eq!(frame.local_count(), 2);
+    assert_eq!(frame.get_local(0), Some(RuntimeValue::Unit));
+}
@@ -0,0 +1,103 @@
+//! 堆回收测试（`backends::common::gc`）
+//!
+//! 测试覆盖内容：
+//! - 根不可达的引用环（列表与捕获它的闭包、经 Arc 的结构体字段）被整体回收
+//! - 根可达的对象及其引用的环保留
+//! - 只按 handle 固定的对象（栈区对象）保留
+//! - 弱引用不保留对象
+
+use std::borrow::Cow;
+use std::sync::Arc;
+
+use crate::backends::common::gc;
+use crate::backends::common::value::{FunctionId, FunctionValue, TypeId};
+use crate::backends::common::{Heap, HeapValue, RuntimeValue};
+
+fn closure(env: Vec<RuntimeValue>) -> RuntimeValue {
+    RuntimeValue::Function(Arc::new(FunctionValue {
+        func_id: FunctionId(0),
+        env,
+    }))
+}
+
+/// 列表里放着捕获该列表的闭包
+fn list_with_capturing_closure(heap: &mut Heap) -> RuntimeValue {
+    let list = heap.allocate(HeapValue::List(Vec::new()));
+    heap.write(
+        list,
+        HeapValue::list([closure(vec![RuntimeValue::List(list)])]),
+    )
+    .unwrap();
+    RuntimeValue::List(list)
+}
+
+#[test]
+fn test_collect_frees_unreachable_cycles() {
+    let mut heap = Heap::new();
+    list_with_capturing_closure(&mut heap);
+    // 结构体字段经 Arc 指回自身
+    let fields = heap.allocate(HeapValue::Struct(Vec::new()));
+    let node = RuntimeValue::Struct {
+        type_id: TypeId(0),
+        fields,
+        vtable: Default::default(),
+    };
+    heap.write(
+        fields,
+        HeapValue::Struct(vec![RuntimeValue::Arc(Arc::new(node)).into()]),
+    )
+    .unwrap();
+
+    let freed = gc::collect(&mut heap, [], []);
+
+    assert_eq!(freed, 2);
+    assert!(heap.is_empty());
+}
+
+#[test]
+fn test_collect_keeps_what_roots_reach() {
+    let mut heap = Heap::new();
+    let kept = list_with_capturing_closure(&mut heap);
+    let inner = heap.allocate(HeapValue::tuple([RuntimeValue::Int(1)]));
+    let mut entries = std::collections::HashMap::new();
+    entries.insert(RuntimeValue::Tuple(inner), RuntimeValue::Unit);
+    let dict = heap.allocate(HeapValue::Dict(entries));
+    let root = closure(vec![kept.clone(), RuntimeValue::Dict(dict)]);
+    list_with_capturing_closure(&mut heap);
+
+    let freed = gc::collect(&mut heap, [Cow::Borrowed(&root)], []);
+
+    assert_eq!(freed, 1);
+    assert_eq!(heap.len(), 3);
+    let RuntimeValue::List(list) = kept else {
+        unreachable!()
+    };
+    assert!(heap.is_valid(list) && heap.is_valid(inner) && heap.is_valid(dict));
+}
+
+#[test]
+fn test_collect_keeps_pinned_objects() {
+    let mut heap = Heap::new();
+    let element = heap.allocate(HeapValue::List(Vec::new()));
+    let slot = heap.allocate(HeapValue::array([RuntimeValue::List(element)]));
+
+    let freed = gc::collect(&mut heap, [], [slot]);
+
+    assert_eq!(freed, 0);
+    assert!(heap.is_valid(slot) && heap.is_valid(element));
+}
+
+#[test]
+fn test_collect_does_not_follow_weak_references() {
+    let mut heap = Heap::new();
+    let list = heap.allocate(HeapValue::List(Vec::new()));
+    let strong = Arc::new(RuntimeValue::List(list));
+    let weak = RuntimeValue::Weak(Arc::downgrade(&strong));
+    heap.write(list, HeapValue::list([RuntimeValue::Arc(strong)]))
+        .unwrap();
+
+    let freed = gc::collect(&mut heap, [Cow::Borrowed(&weak)], []);
+
+    assert_eq!(freed, 1);
+    assert!(weak.upgrade().is_none());
+}
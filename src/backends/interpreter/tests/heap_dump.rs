@@ -0,0 +1,184 @@
+//! 堆转储测试（`backends::common::heap_dump`）
+//!
+//! 测试覆盖内容：
+//! - 对象类型、大小与引用
+//! - 从根出发的保留路径，以及根不可达对象的路径
+//! - 两次转储的按类型对比
+//! - 转储文件的读写与版本校验
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::backends::common::heap_dump::{HeapDiff, HeapDump, HeapObject, HEAP_DUMP_VERSION};
+use crate::backends::common::{Heap, HeapValue, RuntimeValue, StructTypes};
+
+fn string(s: &str) -> RuntimeValue {
+    RuntimeValue::String(Arc::from(s))
+}
+
+fn object(
+    dump: &HeapDump,
+    id: usize,
+) -> &HeapObject {
+    dump.objects.iter().find(|o| o.id == id).expect("object")
+}
+
+#[test]
+fn test_capture_traces_retaining_paths_from_roots() {
+    let mut heap = Heap::new();
+    let mut types = StructTypes::new();
+    let user_id = types.register(
+        "User",
+        vec!["name".to_string(), "tags".to_string()],
+        vec![0, 8],
+    );
+    let tags = heap.allocate(HeapValue::list([string("admin")]));
+    let fields = heap.allocate(HeapValue::Struct(vec![
+        string("ada").into(),
+        RuntimeValue::List(tags).into(),
+    ]));
+    let user = RuntimeValue::Struct {
+        type_id: user_id,
+        fields,
+        vtable: Default::default(),
+    };
+    let mut users = HashMap::new();
+    users.insert(string("ada"), user);
+    let dict = heap.allocate(HeapValue::Dict(users));
+
+    let root = RuntimeValue::Dict(dict);
+    let dump = HeapDump::capture(&heap, &types, &[("main:local0".to_string(), &root)]);
+
+    assert_eq!(dump.version, HEAP_DUMP_VERSION);
+    assert_eq!(dump.roots, vec!["main:local0".to_string()]);
+    assert_eq!(dump.objects.len(), 3);
+    assert!(dump.objects.iter().all(|o| o.reachable));
+
+    let user = object(&dump, fields.raw());
+    assert_eq!(user.type_name, "User");
+    assert_eq!(user.len, 2);
+    assert_eq!(user.references, vec![tags.raw()]);
+    assert_eq!(user.retaining_path, r#"main:local0["ada"]"#);
+    assert_eq!(
+        object(&dump, tags.raw()).retaining_path,
+        r#"main:local0["ada"].tags"#
+    );
+    assert_eq!(object(&dump, dict.raw()).type_name, "Dict");
+    assert!(object(&dump, tags.raw()).size > object(&dump, tags.raw()).len);
+}
+
+#[test]
+fn test_capture_reports_unreachable_objects_and_cycles() {
+    let mut heap = Heap::new();
+    let inner = heap.allocate(HeapValue::list([RuntimeValue::Int(1)]));
+    let outer = heap.allocate(HeapValue::list([RuntimeValue::List(inner)]));
+    // 两个列表互相引用，且没有任何根
+    let a = heap.allocate(HeapValue::List(Vec::new()));
+    let b = heap.allocate(HeapValue::list([RuntimeValue::List(a)]));
+    heap.write(a, HeapValue::list([RuntimeValue::List(b)]))
+        .unwrap();
+
+    let dump = HeapDump::capture(&heap, &StructTypes::new(), &[]);
+
+    assert_eq!(dump.unreachable_count(), 4);
+    assert_eq!(
+        object(&dump, outer.raw()).retaining_path,
+        format!("<unreferenced List#{}>", outer.raw())
+    );
+    assert_eq!(
+        object(&dump, inner.raw()).retaining_path,
+        format!("<unreferenced List#{}>[0]", outer.raw())
+    );
+    assert_eq!(
+        object(&dump, a.raw()).retaining_path,
+        format!("<cycle List#{}>", a.raw())
+    );
+    assert_eq!(
+        object(&dump, b.raw()).retaining_path,
+        format!("<cycle List#{}>[0]", a.raw())
+    );
+}
+
+#[test]
+fn test_capture_follows_closure_environments() {
+    let mut heap = Heap::new();
+    let captured = heap.allocate(HeapValue::List(Vec::new()));
+    let closure = RuntimeValue::Function(std::sync::Arc::new(
+        crate::backends::common::value::FunctionValue {
+            func_id: crate::backends::common::value::FunctionId(0),
+            env: vec![RuntimeValue::Int(0), RuntimeValue::List(captured)],
+        },
+    ));
+
+    let dump = HeapDump::capture(
+        &heap,
+        &StructTypes::new(),
+        &[("f:r0".to_string(), &closure)],
+    );
+
+    assert!(dump.objects[0].reachable);
+    assert_eq!(dump.objects[0].retaining_path, "f:r0.env[1]");
+}
+
+#[test]
+fn test_diff_reports_growth_per_type() {
+    let mut heap = Heap::new();
+    let cache = heap.allocate(HeapValue::List(Vec::new()));
+    let root = RuntimeValue::List(cache);
+    let types = StructTypes::new();
+    let roots = [("main:local0".to_string(), &root)];
+    let before = HeapDump::capture(&heap, &types, &roots);
+
+    let entries: Vec<RuntimeValue> = (0..3)
+        .map(|i| RuntimeValue::Tuple(heap.allocate(HeapValue::tuple([RuntimeValue::Int(i)]))))
+        .collect();
+    heap.write(cache, HeapValue::list(entries)).unwrap();
+    let after = HeapDump::capture(&heap, &types, &roots);
+
+    let diff = HeapDiff::between(&before, &after);
+    assert_eq!(diff.types.len(), 2);
+    let tuples = &diff.types[0];
+    assert_eq!(tuples.type_name, "Tuple");
+    assert_eq!((tuples.before_count, tuples.after_count), (0, 3));
+    assert_eq!(tuples.count_delta(), 3);
+    assert_eq!(
+        tuples.new_paths,
+        vec!["main:local0[0]", "main:local0[1]", "main:local0[2]"]
+    );
+    // 缓存列表本身没有增加，只是变大了
+    let lists = &diff.types[1];
+    assert_eq!(lists.type_name, "List");
+    assert_eq!(lists.count_delta(), 0);
+    assert!(lists.size_delta() > 0);
+    assert!(lists.new_paths.is_empty());
+
+    let report = diff.render();
+    assert!(report.contains("Tuple"), "{report}");
+    assert!(report.contains("new: main:local0[0]"), "{report}");
+    assert!(HeapDiff::between(&after, &after)
+        .render()
+        .contains("no changes"));
+}
+
+#[test]
+fn test_dump_roundtrips_through_file() {
+    let mut heap = Heap::new();
+    heap.allocate(HeapValue::list([string("x")]));
+    let dump = HeapDump::capture(&heap, &StructTypes::new(), &[]);
+    let dir = tempfile::TempDir::new().unwrap();
+    let path = dir.path().join("a.dump");
+
+    dump.write(&path).unwrap();
+    assert_eq!(HeapDump::read(&path).unwrap(), dump);
+
+    let stale = std::fs::read_to_string(&path).unwrap().replace(
+        &format!("\"version\":{}", HEAP_DUMP_VERSION),
+        "\"version\":0",
+    );
+    std::fs::write(&path, stale).unwrap();
+    let err = HeapDump::read(&path).unwrap_err();
+    assert!(
+        err.to_string().contains("unsupported heap dump version"),
+        "{err}"
+    );
+}
@@ -0,0 +1,137 @@
+//! 执行钩子测试（`backends::interpreter::hooks`）
+//!
+//! 测试覆盖内容：
+//! - 每条执行的指令都报告 on_step，附带函数、位置与调用深度
+//! - 解释执行的函数成对报告进入与返回，返回值随 on_exit 给出
+//! - 指令分配堆对象时报告字节数
+//! - 取下钩子后不再报告
+
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::backends::common::RuntimeValue;
+use crate::backends::interpreter::hooks::{ExecutionHook, VmState};
+use crate::backends::interpreter::Interpreter;
+use crate::middle::bytecode::BytecodeFunction;
+use crate::Engine;
+
+const SOURCE: &str = "\
+square: (n: Int) -> Int = (n) => n * n
+
+fact: (n: Int) -> Int = (n) => {
+    if n <= 1 {
+        return 1
+    }
+    return n * fact(n - 1)
+}
+
+make_list: (n: Int) -> List(Int) = (n) => [n, n + 1, n + 2]
+";
+
+#[derive(Default)]
+struct Recorder {
+    steps: AtomicU64,
+    max_depth: AtomicUsize,
+    located: AtomicU64,
+    calls: Mutex<Vec<String>>,
+    allocated: AtomicUsize,
+}
+
+impl ExecutionHook for Recorder {
+    fn on_step(
+        &self,
+        vm: &VmState<'_>,
+    ) {
+        assert!(vm.instruction().is_some());
+        self.steps.fetch_add(1, Ordering::Relaxed);
+        self.max_depth.fetch_max(vm.depth(), Ordering::Relaxed);
+        if vm.location().is_some() {
+            self.located.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    fn on_enter(
+        &self,
+        vm: &VmState<'_>,
+    ) {
+        assert_eq!(vm.ip(), 0);
+        let mut calls = self.calls.lock().unwrap();
+        calls.push(format!("> {}", vm.function().name));
+    }
+
+    fn on_exit(
+        &self,
+        function: &BytecodeFunction,
+        result: Option<&RuntimeValue>,
+    ) {
+        let mut calls = self.calls.lock().unwrap();
+        calls.push(format!("< {} {:?}", function.name, result));
+    }
+
+    fn on_alloc(
+        &self,
+        bytes: usize,
+    ) {
+        self.allocated.fetch_add(bytes, Ordering::Relaxed);
+    }
+}
+
+fn loaded() -> Interpreter {
+    let engine = Engine::default();
+    let program = engine.compile("hooks.yx", SOURCE).unwrap();
+    let mut interpreter = engine.interpreter();
+    let mut program = program.clone();
+    program.entry_point = None;
+    crate::backends::Executor::execute_module(&mut interpreter, &program).unwrap();
+    interpreter
+}
+
+#[test]
+fn test_steps_and_calls_are_reported() {
+    let recorder = Arc::new(Recorder::default());
+    let mut interpreter = loaded();
+    interpreter.set_hook(recorder.clone());
+
+    let value: i64 = interpreter.call("fact", (3,)).unwrap();
+    assert_eq!(value, 6);
+
+    assert!(recorder.steps.load(Ordering::Relaxed) > 0);
+    assert_eq!(recorder.max_depth.load(Ordering::Relaxed), 3);
+    // 带行号表编译的函数，语句的指令能定位到源码
+    assert!(recorder.located.load(Ordering::Relaxed) > 0);
+    let calls = recorder.calls.lock().unwrap();
+    assert_eq!(
+        *calls,
+        [
+            "> fact",
+            "> fact",
+            "> fact",
+            "< fact Some(Int(1))",
+            "< fact Some(Int(2))",
+            "< fact Some(Int(6))",
+        ]
+    );
+}
+
+#[test]
+fn test_allocations_are_reported() {
+    let recorder = Arc::new(Recorder::default());
+    let mut interpreter = loaded();
+    interpreter.set_hook(recorder.clone());
+
+    let list: Vec<i64> = interpreter.call("make_list", (1,)).unwrap();
+    assert_eq!(list, [1, 2, 3]);
+    assert!(recorder.allocated.load(Ordering::Relaxed) > 0);
+}
+
+#[test]
+fn test_removed_hook_is_not_called() {
+    let recorder = Arc::new(Recorder::default());
+    let mut interpreter = loaded();
+    interpreter.set_hook(recorder.clone());
+    assert!(interpreter.take_hook().is_some());
+
+    let _: i64 = interpreter.call("square", (4,)).unwrap();
+    assert_eq!(recorder.steps.load(Ordering::Relaxed), 0);
+    assert!(recorder.calls.lock().unwrap().is_empty());
+}
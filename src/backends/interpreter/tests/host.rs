@@ -0,0 +1,184 @@
+//! 宿主函数测试
+//!
+//! 测试覆盖内容：
+//! - 捕获状态的宿主闭包经 FfiRegistry 调用
+//! - 带类型的宿主闭包：参数与返回值转换（Int、Float、String、List、Dict）
+//! - 参数个数或类型不符时报类型错误，指明函数与参数位置
+//! - 宿主闭包返回 Err 时原样传给脚本
+//! - 同名注册互相覆盖
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+
+use crate::backends::common::{Heap, HeapValue, RuntimeValue};
+use crate::backends::interpreter::ffi::FfiRegistry;
+use crate::backends::interpreter::host::{FromValue, HostFunction, IntoValue};
+use crate::backends::ExecutorError;
+use crate::std::NativeContext;
+
+fn call(
+    registry: &FfiRegistry,
+    heap: &mut Heap,
+    name: &str,
+    args: &[RuntimeValue],
+) -> Result<RuntimeValue, ExecutorError> {
+    registry.call(name, args, &mut NativeContext::new(heap))
+}
+
+#[test]
+fn test_closure_keeps_state_between_calls() {
+    let counter = Arc::new(AtomicI64::new(0));
+    let seen = counter.clone();
+    let mut registry = FfiRegistry::new();
+    registry.register_host(
+        "host.count",
+        HostFunction::new(move |args| {
+            let step = args.first().and_then(RuntimeValue::to_int).unwrap_or(1);
+            Ok(RuntimeValue::Int(
+                seen.fetch_add(step, Ordering::SeqCst) + step,
+            ))
+        }),
+    );
+    let mut heap = Heap::new();
+
+    assert!(registry.has("host.count"));
+    assert_eq!(registry.len(), 1);
+    assert_eq!(
+        call(&registry, &mut heap, "host.count", &[RuntimeValue::Int(2)]),
+        Ok(RuntimeValue::Int(2))
+    );
+    assert_eq!(
+        call(&registry, &mut heap, "host.count", &[]),
+        Ok(RuntimeValue::Int(3))
+    );
+    assert_eq!(counter.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+fn test_typed_function_converts_arguments_and_result() {
+    let mut registry = FfiRegistry::new();
+    registry.register_host(
+        "host.scale",
+        HostFunction::typed("host.scale", |x: f64, n: i64| x * n as f64),
+    );
+    registry.register_host(
+        "host.words",
+        HostFunction::typed("host.words", |s: String| -> Vec<String> {
+            s.split_whitespace().map(str::to_string).collect()
+        }),
+    );
+    let mut heap = Heap::new();
+
+    assert_eq!(
+        call(
+            &registry,
+            &mut heap,
+            "host.scale",
+            &[RuntimeValue::Float(1.5), RuntimeValue::Int(4)]
+        ),
+        Ok(RuntimeValue::Float(6.0))
+    );
+    let words = call(
+        &registry,
+        &mut heap,
+        "host.words",
+        &[RuntimeValue::String("a bb ccc".into())],
+    )
+    .unwrap();
+    assert_eq!(
+        Vec::<String>::from_value(&words, &heap).unwrap(),
+        ["a", "bb", "ccc"]
+    );
+}
+
+#[test]
+fn test_typed_function_rejects_wrong_arguments() {
+    let mut registry = FfiRegistry::new();
+    registry.register_host(
+        "host.add",
+        HostFunction::typed("host.add", |a: i64, b: i64| a + b),
+    );
+    let mut heap = Heap::new();
+
+    let err = call(&registry, &mut heap, "host.add", &[RuntimeValue::Int(1)]).unwrap_err();
+    assert!(
+        matches!(&err, ExecutorError::Type(msg, _) if msg == "host.add expects 2 argument(s), got 1"),
+        "{:?}",
+        err
+    );
+    let err = call(
+        &registry,
+        &mut heap,
+        "host.add",
+        &[RuntimeValue::Int(1), RuntimeValue::String("2".into())],
+    )
+    .unwrap_err();
+    assert!(
+        matches!(&err, ExecutorError::Type(msg, _) if msg == "argument 2 of host.add: expected Int, found String"),
+        "{:?}",
+        err
+    );
+}
+
+#[test]
+fn test_typed_function_error_reaches_script() {
+    let mut registry = FfiRegistry::new();
+    registry.register_host(
+        "host.checked_div",
+        HostFunction::typed("host.checked_div", |a: i64, b: i64| {
+            a.checked_div(b)
+                .ok_or_else(|| ExecutorError::runtime_only("division by zero in host"))
+        }),
+    );
+    let mut heap = Heap::new();
+    let args = [RuntimeValue::Int(7), RuntimeValue::Int(0)];
+    assert_eq!(
+        call(&registry, &mut heap, "host.checked_div", &args),
+        Err(ExecutorError::runtime_only("division by zero in host"))
+    );
+}
+
+#[test]
+fn test_dict_roundtrip() {
+    let mut heap = Heap::new();
+    let scores: HashMap<String, i64> = [("a".to_string(), 1), ("b".to_string(), 2)].into();
+    let value = scores.clone().into_value(&mut heap).unwrap();
+    assert!(matches!(
+        value,
+        RuntimeValue::Dict(handle) if matches!(heap.get(handle), Some(HeapValue::Dict(map)) if map.len() == 2)
+    ));
+    assert_eq!(
+        HashMap::<String, i64>::from_value(&value, &heap).unwrap(),
+        scores
+    );
+    // 超出 i32 范围的整数不能静默截断
+    assert!(i32::from_value(&RuntimeValue::Int(i64::MAX), &heap).is_err());
+}
+
+#[test]
+fn test_registration_overrides_same_name() {
+    fn std_version(
+        _args: &[RuntimeValue],
+        _ctx: &mut NativeContext<'_>,
+    ) -> Result<RuntimeValue, ExecutorError> {
+        Ok(RuntimeValue::Int(1))
+    }
+    let mut registry = FfiRegistry::new();
+    registry.register("app.version", std_version);
+    registry.register_host(
+        "app.version",
+        HostFunction::new(|_| Ok(RuntimeValue::Int(2))),
+    );
+    let mut heap = Heap::new();
+    assert_eq!(registry.len(), 1);
+    assert_eq!(
+        call(&registry, &mut heap, "app.version", &[]),
+        Ok(RuntimeValue::Int(2))
+    );
+    registry.register("app.version", std_version);
+    assert_eq!(
+        call(&registry, &mut heap, "app.version", &[]),
+        Ok(RuntimeValue::Int(1))
+    );
+}
@@ -1,10 +1,24 @@
 //! 解释器测试入口
 //!
-//! 包含 ffi、frames、registers 和 weak 的测试模块。
+//! 包含 debugger、extension、ffi、frames、gc、heap_dump、hooks、host、nanbox、reflect、registers、serialize、show、transfer 和 weak 的测试模块。
 
 mod bytecode_load;
+#[cfg(feature = "hooks")]
+mod debugger;
+#[cfg(unix)]
+mod extension;
 mod ffi;
 mod ffi_c_integration;
 mod frames;
+mod gc;
+mod heap_dump;
+#[cfg(feature = "hooks")]
+mod hooks;
+mod host;
+mod nanbox;
+mod reflect;
 mod registers;
+mod serialize;
+mod show;
+mod transfer;
 mod weak;
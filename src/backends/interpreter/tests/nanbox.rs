@@ -0,0 +1,142 @@
+//! NaN-boxed Value 测试
+//!
+//! 测试覆盖内容：
+//! - 48 位内联整数的边界与装箱回退
+//! - 浮点数（含 NaN、负零、无穷）的往返
+//! - 标量与堆句柄的内联存储
+//! - 装箱值的共享、释放与取出
+//! - 超出 48 位的指针经旁表装箱
+
+use std::sync::Arc;
+
+use crate::backends::common::nanbox::{SMALL_INT_MAX, SMALL_INT_MIN};
+use crate::backends::common::{Handle, RuntimeValue, Value};
+
+fn round_trip(value: RuntimeValue) -> RuntimeValue {
+    Value::new(value).get()
+}
+
+#[test]
+fn test_small_int_edges_are_inline() {
+    for n in [0, 1, -1, SMALL_INT_MIN, SMALL_INT_MAX] {
+        let value = Value::int(n);
+        assert_eq!(value.small_int(), Some(n));
+        assert!(value.boxed_ref().is_none());
+        assert_eq!(value.get(), RuntimeValue::Int(n));
+    }
+}
+
+#[test]
+fn test_large_int_is_boxed() {
+    for n in [SMALL_INT_MIN - 1, SMALL_INT_MAX + 1, i64::MIN, i64::MAX] {
+        let value = Value::int(n);
+        assert_eq!(value.small_int(), None);
+        assert_eq!(value.as_int(), Some(n));
+        assert_eq!(value.get(), RuntimeValue::Int(n));
+    }
+}
+
+#[test]
+fn test_float_round_trip() {
+    for f in [
+        0.0,
+        -0.0,
+        1.5,
+        f64::MIN,
+        f64::MAX,
+        f64::INFINITY,
+        f64::NEG_INFINITY,
+    ] {
+        let value = Value::float(f);
+        assert_eq!(value.as_float().map(f64::to_bits), Some(f.to_bits()));
+        assert_eq!(value.small_int(), None);
+    }
+    // 负号 NaN 与带载荷的 NaN 都会落进标签区，必须规范化
+    let nan = f64::from_bits(0xFFF8_0000_0000_1234);
+    let value = Value::float(nan);
+    assert!(value.as_float().is_some_and(f64::is_nan));
+    assert!(matches!(value.get(), RuntimeValue::Float(f) if f.is_nan()));
+}
+
+#[test]
+fn test_scalars_round_trip() {
+    for value in [
+        RuntimeValue::Unit,
+        RuntimeValue::Bool(true),
+        RuntimeValue::Bool(false),
+        RuntimeValue::Char('a' as u32),
+        RuntimeValue::Char(char::MAX as u32),
+    ] {
+        assert_eq!(round_trip(value.clone()), value);
+    }
+    assert!(Value::UNIT.is_unit());
+    assert_eq!(Value::bool(true).as_bool(), Some(true));
+    assert_eq!(Value::int(1).as_bool(), None);
+}
+
+#[test]
+fn test_handles_are_inline() {
+    for value in [
+        RuntimeValue::Tuple(Handle(0)),
+        RuntimeValue::Array(Handle(7)),
+        RuntimeValue::List(Handle(1 << 40)),
+        RuntimeValue::Dict(Handle(3)),
+    ] {
+        assert!(Value::new(value.clone()).boxed_ref().is_none());
+        assert_eq!(round_trip(value.clone()), value);
+    }
+}
+
+#[test]
+fn test_boxed_clone_shares_allocation() {
+    let text: Arc<str> = Arc::from("shared");
+    let value = Value::new(RuntimeValue::String(text.clone()));
+    let copy = value.clone();
+    let (Some(a), Some(b)) = (value.boxed_ref(), copy.boxed_ref()) else {
+        panic!("strings are boxed");
+    };
+    assert!(std::ptr::eq(a, b));
+    assert_eq!(copy, RuntimeValue::String(text.clone()));
+
+    drop(value);
+    drop(copy);
+    // 两份拷贝都释放后只剩测试自己持有的字符串
+    assert_eq!(Arc::strong_count(&text), 1);
+}
+
+#[test]
+fn test_into_inner_moves_out_of_last_copy() {
+    let text: Arc<str> = Arc::from("moved");
+    let value = Value::new(RuntimeValue::String(text.clone()));
+    let copy = value.clone();
+    assert_eq!(copy.into_inner(), RuntimeValue::String(text.clone()));
+    assert_eq!(value.into_inner(), RuntimeValue::String(text.clone()));
+    assert_eq!(Arc::strong_count(&text), 1);
+}
+
+#[test]
+fn test_equality_matches_runtime_value() {
+    assert_eq!(Value::int(3), Value::new(RuntimeValue::Int(3)));
+    assert_ne!(Value::int(3), Value::float(3.0));
+    assert_eq!(
+        Value::new(RuntimeValue::String("a".into())),
+        Value::new(RuntimeValue::String("a".into()))
+    );
+}
+
+#[test]
+fn test_wide_pointer_goes_through_side_table() {
+    let text: Arc<str> = Arc::from("wide");
+    let value = Value::boxed_wide(RuntimeValue::String(text.clone()));
+    let copy = value.clone();
+    let (Some(a), Some(b)) = (value.boxed_ref(), copy.boxed_ref()) else {
+        panic!("strings are boxed");
+    };
+    assert!(std::ptr::eq(a, b));
+    assert_eq!(copy, Value::new(RuntimeValue::String("wide".into())));
+    assert_eq!(value.get(), RuntimeValue::String(text.clone()));
+
+    drop(copy);
+    assert_eq!(value.into_inner(), RuntimeValue::String(text.clone()));
+    assert_eq!(Arc::strong_count(&text), 1);
+}
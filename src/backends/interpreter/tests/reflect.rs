@@ -0,0 +1,117 @@
+//! 运行时类型反射测试
+//!
+//! 对应规范章节：
+//! - `docs/src/reference/language-spec/stdlib.md` §1.6: std.reflect
+//!
+//! 测试覆盖内容：
+//! - typeof 返回类型值，类型值按结构比较
+//! - type_name 通过结构体布局解析结构体名称
+//! - type_fields 枚举结构体字段，非结构体为空
+//! - 非类型参数报类型错误
+
+use crate::backends::common::value::ValueType;
+use crate::backends::common::{Heap, HeapValue, RuntimeValue, StructTypes};
+use crate::backends::interpreter::ffi::FfiRegistry;
+use crate::std::NativeContext;
+
+fn call(
+    registry: &FfiRegistry,
+    ctx: &mut NativeContext<'_>,
+    name: &str,
+    arg: RuntimeValue,
+) -> RuntimeValue {
+    registry.call(name, &[arg], ctx).unwrap()
+}
+
+#[test]
+fn test_typeof_returns_comparable_type_values() {
+    let registry = FfiRegistry::with_std();
+    let mut heap = Heap::new();
+    let mut ctx = NativeContext::new(&mut heap);
+
+    let int_a = call(
+        &registry,
+        &mut ctx,
+        "std.reflect.typeof",
+        RuntimeValue::Int(1),
+    );
+    let int_b = call(
+        &registry,
+        &mut ctx,
+        "std.reflect.typeof",
+        RuntimeValue::Int(2),
+    );
+    let text = call(
+        &registry,
+        &mut ctx,
+        "std.reflect.typeof",
+        RuntimeValue::String("a".into()),
+    );
+    assert_eq!(int_a, int_b);
+    assert_ne!(int_a, text);
+    assert_eq!(text.value_type(None), ValueType::Type);
+
+    let name = call(&registry, &mut ctx, "std.reflect.type_name", int_a);
+    assert_eq!(name, RuntimeValue::String("Int".into()));
+}
+
+#[test]
+fn test_struct_type_name_and_fields() {
+    let registry = FfiRegistry::with_std();
+    let mut heap = Heap::new();
+    let mut types = StructTypes::new();
+    let id = types.register("Point", vec!["x".to_string(), "y".to_string()], vec![0, 8]);
+    let fields = heap.allocate(HeapValue::tuple([
+        RuntimeValue::Float(1.0),
+        RuntimeValue::Float(2.0),
+    ]));
+    let point = RuntimeValue::Struct {
+        type_id: id,
+        fields,
+        vtable: Default::default(),
+    };
+    let mut ctx = NativeContext::new(&mut heap).with_struct_types(&types);
+
+    let ty = call(&registry, &mut ctx, "std.reflect.typeof", point);
+    assert_eq!(ty, RuntimeValue::Type(Box::new(ValueType::Struct(id))));
+    let name = call(&registry, &mut ctx, "std.reflect.type_name", ty.clone());
+    assert_eq!(name, RuntimeValue::String("Point".into()));
+
+    let RuntimeValue::List(handle) = call(&registry, &mut ctx, "std.reflect.type_fields", ty)
+    else {
+        panic!("type_fields should return a list");
+    };
+    assert_eq!(
+        ctx.heap.get(handle),
+        Some(&HeapValue::list([
+            RuntimeValue::String("x".into()),
+            RuntimeValue::String("y".into()),
+        ]))
+    );
+}
+
+#[test]
+fn test_type_fields_of_non_struct_is_empty() {
+    let registry = FfiRegistry::with_std();
+    let mut heap = Heap::new();
+    let mut ctx = NativeContext::new(&mut heap);
+
+    let ty = RuntimeValue::Type(Box::new(ValueType::List));
+    let RuntimeValue::List(handle) = call(&registry, &mut ctx, "std.reflect.type_fields", ty)
+    else {
+        panic!("type_fields should return a list");
+    };
+    assert_eq!(ctx.heap.get(handle), Some(&HeapValue::list([])));
+}
+
+#[test]
+fn test_type_name_rejects_non_type_argument() {
+    let registry = FfiRegistry::with_std();
+    let mut heap = Heap::new();
+    let mut ctx = NativeContext::new(&mut heap);
+
+    let err = registry
+        .call("std.reflect.type_name", &[RuntimeValue::Int(1)], &mut ctx)
+        .unwrap_err();
+    assert!(err.to_string().contains("expects a Type value"), "{}", err);
+}
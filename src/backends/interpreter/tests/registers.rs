@@ -18,7 +18,7 @@ fn test_register_file_new() {
 fn test_register_set_get() {
     let mut rf = RegisterFile::new();
     rf.set(0, RuntimeValue::Int(42));
-    assert_eq!(rf.at(0).to_int(), Some(42));
+    assert_eq!(rf.at(0).as_int(), Some(42));
 }
 
 #[test]
@@ -26,5 +26,5 @@ fn test_register_copy() {
     let mut rf = RegisterFile::new();
     rf.set(0, RuntimeValue::Int(42));
     rf.copy(1, 0);
-    assert_eq!(rf.at(1).to_int(), Some(42));
+    assert_eq!(rf.at(1).as_int(), Some(42));
 }
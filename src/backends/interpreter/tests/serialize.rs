@@ -0,0 +1,169 @@
+//! 运行时值的 serde 支持测试（`backends::common::serialize`）
+//!
+//! 测试覆盖内容：
+//! - Rust 结构体经 to_value 成为以字段名为键的字典，再经 from_value 读回
+//! - Rust 枚举按外部标签表示（单元变体为字符串，其余为单键字典）
+//! - 已知布局的结构体值序列化为带字段名的 JSON 对象，未知时为数组
+//! - SendValue 与 JSON 往返，嵌套对象保持不变
+//! - 函数值与自引用的值拒绝序列化
+//! - 宿主函数通过 Serde 包装收发 serde 类型
+
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::backends::common::serialize::{from_value, to_value, ValueRef};
+use crate::backends::common::value::{FunctionId, FunctionValue};
+use crate::backends::common::{Heap, HeapValue, RuntimeValue, SendValue, StructTypes};
+use crate::backends::interpreter::ffi::FfiRegistry;
+use crate::backends::interpreter::host::{HostFunction, Serde};
+use crate::std::NativeContext;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Player {
+    name: String,
+    level: u32,
+    tags: Vec<String>,
+    guild: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+enum Event {
+    Quit,
+    Move(i64, i64),
+    Say { text: String },
+}
+
+fn json(
+    value: &RuntimeValue,
+    heap: &Heap,
+) -> serde_json::Value {
+    serde_json::to_value(ValueRef::new(value, heap)).unwrap()
+}
+
+#[test]
+fn test_struct_roundtrip_through_dict() {
+    let mut heap = Heap::new();
+    let player = Player {
+        name: "Ada".to_string(),
+        level: 7,
+        tags: vec!["mage".to_string()],
+        guild: None,
+    };
+    let value = to_value(&player, &mut heap).unwrap();
+    assert!(matches!(
+        value,
+        RuntimeValue::Dict(h) if matches!(heap.get(h), Some(HeapValue::Dict(map)) if map.len() == 4)
+    ));
+    assert_eq!(
+        json(&value, &heap),
+        serde_json::json!({"name": "Ada", "level": 7, "tags": ["mage"], "guild": null})
+    );
+    assert_eq!(from_value::<Player>(&value, &heap).unwrap(), player);
+}
+
+#[test]
+fn test_enum_uses_external_tagging() {
+    let mut heap = Heap::new();
+    let events = vec![
+        Event::Quit,
+        Event::Move(1, -2),
+        Event::Say {
+            text: "hi".to_string(),
+        },
+    ];
+    let value = to_value(&events, &mut heap).unwrap();
+    assert_eq!(
+        json(&value, &heap),
+        serde_json::json!(["Quit", {"Move": [1, -2]}, {"Say": {"text": "hi"}}])
+    );
+    assert_eq!(from_value::<Vec<Event>>(&value, &heap).unwrap(), events);
+    // u64 超出 Int 范围时报错而非截断
+    assert!(to_value(&u64::MAX, &mut heap).is_err());
+}
+
+#[test]
+fn test_struct_value_uses_field_names_when_known() {
+    let mut types = StructTypes::new();
+    let point = types.register("Point", vec!["x".to_string(), "y".to_string()], vec![0, 1]);
+    let mut heap = Heap::new();
+    let fields = heap.allocate(HeapValue::tuple([
+        RuntimeValue::Int(3),
+        RuntimeValue::Float(0.5),
+    ]));
+    let value = RuntimeValue::Struct {
+        type_id: point,
+        fields,
+        vtable: Default::default(),
+    };
+
+    assert_eq!(json(&value, &heap), serde_json::json!([3, 0.5]));
+    let named = ValueRef::new(&value, &heap).with_struct_types(&types);
+    assert_eq!(
+        serde_json::to_value(named).unwrap(),
+        serde_json::json!({"x": 3, "y": 0.5})
+    );
+}
+
+#[test]
+fn test_send_value_json_roundtrip() {
+    let mut heap = Heap::new();
+    let inner = heap.allocate(HeapValue::list([RuntimeValue::String("a".into())]));
+    let list = heap.allocate(HeapValue::list([
+        RuntimeValue::Int(1),
+        RuntimeValue::List(inner),
+        RuntimeValue::Bool(true),
+    ]));
+    let sent = SendValue::detach(&RuntimeValue::List(list), &heap).unwrap();
+
+    let text = serde_json::to_string(&sent).unwrap();
+    assert_eq!(text, r#"[1,["a"],true]"#);
+    let restored: SendValue = serde_json::from_str(&text).unwrap();
+    let mut other = Heap::new();
+    let value = restored.attach(&mut other);
+    assert_eq!(json(&value, &other), serde_json::json!([1, ["a"], true]));
+}
+
+#[test]
+fn test_unserializable_values_are_rejected() {
+    let mut heap = Heap::new();
+    let function = RuntimeValue::Function(Arc::new(FunctionValue {
+        func_id: FunctionId(0),
+        env: Vec::new(),
+    }));
+    let err = serde_json::to_string(&ValueRef::new(&function, &heap)).unwrap_err();
+    assert!(err.to_string().contains("cannot serialize"), "{err}");
+
+    let list = heap.allocate(HeapValue::List(Vec::new()));
+    heap.write(list, HeapValue::list([RuntimeValue::List(list)]))
+        .unwrap();
+    let err = serde_json::to_string(&ValueRef::new(&RuntimeValue::List(list), &heap)).unwrap_err();
+    assert!(err.to_string().contains("contains itself"), "{err}");
+}
+
+#[test]
+fn test_host_function_takes_serde_types() {
+    let mut registry = FfiRegistry::new();
+    registry.register_host(
+        "game.promote",
+        HostFunction::typed("game.promote", |Serde(mut player): Serde<Player>| {
+            player.level += 1;
+            Serde(player)
+        }),
+    );
+    let mut heap = Heap::new();
+    let player = Player {
+        name: "Bo".to_string(),
+        level: 1,
+        tags: Vec::new(),
+        guild: Some("North".to_string()),
+    };
+    let arg = to_value(&player, &mut heap).unwrap();
+    let result = registry
+        .call("game.promote", &[arg], &mut NativeContext::new(&mut heap))
+        .unwrap();
+    assert_eq!(
+        from_value::<Player>(&result, &heap).unwrap(),
+        Player { level: 2, ..player }
+    );
+}
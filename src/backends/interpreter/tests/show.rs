@@ -0,0 +1,267 @@
+//! 规范值打印测试
+//!
+//! 测试覆盖内容：
+//! - 结构体按声明的字段名打印
+//! - 嵌套字符串加引号，字典按键排序
+//! - 超长集合截断、超深嵌套省略、自引用集合标记为 `<cycle>`
+//! - 超出行宽时换行
+//! - assert_eq 失败信息与多行差异
+//! - assert_approx_eq / assert_all_close 的容差比较与不匹配位置
+
+use crate::backends::common::value::TypeId;
+use crate::backends::common::{Heap, HeapValue, RuntimeValue, StructTypes};
+use crate::backends::interpreter::ffi::FfiRegistry;
+use crate::std::show::{show, show_display, show_with, ShowOptions};
+use crate::std::NativeContext;
+
+fn list(
+    heap: &mut Heap,
+    items: Vec<RuntimeValue>,
+) -> RuntimeValue {
+    RuntimeValue::List(heap.allocate(HeapValue::list(items)))
+}
+
+fn shown(
+    heap: &mut Heap,
+    value: &RuntimeValue,
+) -> String {
+    show(value, &mut NativeContext::new(heap)).unwrap()
+}
+
+#[test]
+fn test_show_struct_uses_field_names() {
+    let mut heap = Heap::new();
+    let mut types = StructTypes::new();
+    let id = types.register("Point", vec!["x".to_string(), "y".to_string()], vec![0, 8]);
+    let fields = heap.allocate(HeapValue::tuple([
+        RuntimeValue::Float(1.0),
+        RuntimeValue::Float(2.5),
+    ]));
+    let point = RuntimeValue::Struct {
+        type_id: id,
+        fields,
+        vtable: Default::default(),
+    };
+
+    let mut ctx = NativeContext::new(&mut heap).with_struct_types(&types);
+    assert_eq!(show(&point, &mut ctx).unwrap(), "Point(x: 1.0, y: 2.5)");
+    assert_eq!(types.id_of("geo.Point"), id);
+    assert_eq!(types.id_of("Missing"), TypeId(0));
+}
+
+#[test]
+fn test_show_quotes_nested_strings_only() {
+    let mut heap = Heap::new();
+    let words = list(
+        &mut heap,
+        vec![
+            RuntimeValue::String("hi".into()),
+            RuntimeValue::Char('a' as u32),
+        ],
+    );
+    assert_eq!(shown(&mut heap, &words), r#"["hi", 'a']"#);
+
+    let text = RuntimeValue::String("hi".into());
+    assert_eq!(shown(&mut heap, &text), r#""hi""#);
+    let mut ctx = NativeContext::new(&mut heap);
+    assert_eq!(show_display(&text, &mut ctx).unwrap(), "hi");
+}
+
+#[test]
+fn test_show_dict_sorted_by_key() {
+    let mut heap = Heap::new();
+    let entries = [("b", 2), ("a", 1), ("c", 3)]
+        .into_iter()
+        .map(|(k, v)| (RuntimeValue::String(k.into()), RuntimeValue::Int(v)))
+        .collect();
+    let dict = RuntimeValue::Dict(heap.allocate(HeapValue::Dict(entries)));
+    assert_eq!(shown(&mut heap, &dict), r#"{"a": 1, "b": 2, "c": 3}"#);
+}
+
+#[test]
+fn test_show_truncates_long_collections() {
+    let mut heap = Heap::new();
+    let items = (0..10).map(RuntimeValue::Int).collect();
+    let value = list(&mut heap, items);
+    let options = ShowOptions {
+        max_items: 3,
+        ..ShowOptions::default()
+    };
+    let mut ctx = NativeContext::new(&mut heap);
+    assert_eq!(
+        show_with(&value, &mut ctx, &options).unwrap(),
+        "[0, 1, 2, ... 7 more]"
+    );
+}
+
+#[test]
+fn test_show_elides_deep_nesting() {
+    let mut heap = Heap::new();
+    let mut value = RuntimeValue::Int(1);
+    for _ in 0..4 {
+        value = list(&mut heap, vec![value]);
+    }
+    let options = ShowOptions {
+        max_depth: 2,
+        ..ShowOptions::default()
+    };
+    let mut ctx = NativeContext::new(&mut heap);
+    assert_eq!(show_with(&value, &mut ctx, &options).unwrap(), "[[[...]]]");
+}
+
+#[test]
+fn test_show_detects_cycles() {
+    let mut heap = Heap::new();
+    let handle = heap.allocate(HeapValue::list([RuntimeValue::Int(1)]));
+    if let Some(HeapValue::List(items)) = heap.get_mut(handle) {
+        items.push(RuntimeValue::List(handle).into());
+    }
+    assert_eq!(
+        shown(&mut heap, &RuntimeValue::List(handle)),
+        "[1, <cycle>]"
+    );
+}
+
+#[test]
+fn test_show_breaks_wide_values() {
+    let mut heap = Heap::new();
+    let rows: Vec<RuntimeValue> = (0..3)
+        .map(|row| {
+            let cells = (0..8)
+                .map(|col| RuntimeValue::Int(row * 1000 + col))
+                .collect();
+            list(&mut heap, cells)
+        })
+        .collect();
+    let grid = list(&mut heap, rows);
+    assert_eq!(
+        shown(&mut heap, &grid),
+        "[\n  [0, 1, 2, 3, 4, 5, 6, 7],\n  [1000, 1001, 1002, 1003, 1004, 1005, 1006, 1007],\n  [2000, 2001, 2002, 2003, 2004, 2005, 2006, 2007],\n]"
+    );
+}
+
+#[test]
+fn test_assert_eq_reports_both_sides() {
+    let registry = FfiRegistry::with_std();
+    let mut heap = Heap::new();
+    let left = list(&mut heap, vec![RuntimeValue::Int(1), RuntimeValue::Int(2)]);
+    let same = list(&mut heap, vec![RuntimeValue::Int(1), RuntimeValue::Int(2)]);
+    let right = list(&mut heap, vec![RuntimeValue::Int(1), RuntimeValue::Int(3)]);
+    let mut ctx = NativeContext::new(&mut heap);
+
+    assert!(registry
+        .call("std.assert.assert_eq", &[left.clone(), same], &mut ctx)
+        .is_ok());
+    let err = registry
+        .call(
+            "std.assert.assert_eq",
+            &[left.clone(), right.clone()],
+            &mut ctx,
+        )
+        .unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("assertion failed: left == right\n  left: [1, 2]\n right: [1, 3]"));
+    assert!(registry
+        .call("std.assert.assert_ne", &[left, right], &mut ctx)
+        .is_ok());
+}
+
+#[test]
+fn test_assert_eq_diffs_multiline_values() {
+    let registry = FfiRegistry::with_std();
+    let mut heap = Heap::new();
+    let word = |n: i64| RuntimeValue::String(format!("{}-{}", "x".repeat(40), n).into());
+    let left = list(&mut heap, vec![word(1), word(2), word(3)]);
+    let right = list(&mut heap, vec![word(1), word(4), word(3)]);
+    let mut ctx = NativeContext::new(&mut heap);
+
+    let err = registry
+        .call("std.assert.assert_eq", &[left, right], &mut ctx)
+        .unwrap_err()
+        .to_string();
+    let x = "x".repeat(40);
+    assert!(err.contains("diff (- left, + right):"), "{}", err);
+    assert!(err.contains(&format!("-   \"{}-2\",", x)), "{}", err);
+    assert!(err.contains(&format!("+   \"{}-4\",", x)), "{}", err);
+    assert!(err.contains(&format!("    \"{}-1\",", x)), "{}", err);
+}
+
+#[test]
+fn test_assert_approx_eq_uses_tolerance() {
+    let registry = FfiRegistry::with_std();
+    let mut heap = Heap::new();
+    let mut ctx = NativeContext::new(&mut heap);
+    let call = |ctx: &mut NativeContext<'_>, a: f64, b: f64, eps: f64| {
+        registry.call(
+            "std.assert.assert_approx_eq",
+            &[
+                RuntimeValue::Float(a),
+                RuntimeValue::Float(b),
+                RuntimeValue::Float(eps),
+            ],
+            ctx,
+        )
+    };
+
+    assert!(call(&mut ctx, 0.1 + 0.2, 0.3, 1e-12).is_ok());
+    assert!(call(&mut ctx, f64::INFINITY, f64::INFINITY, 0.0).is_ok());
+    assert!(call(&mut ctx, f64::NAN, f64::NAN, 1.0).is_err());
+    let err = call(&mut ctx, 1.0, 1.5, 0.25).unwrap_err().to_string();
+    assert!(
+        err.contains("left: 1.0\n right: 1.5\n  diff: 0.5\n   eps: 0.25"),
+        "{}",
+        err
+    );
+    let err = call(&mut ctx, 1.0, 1.0, -1.0).unwrap_err().to_string();
+    assert!(err.contains("eps must be non-negative"), "{}", err);
+}
+
+#[test]
+fn test_assert_all_close_reports_index_path() {
+    let registry = FfiRegistry::with_std();
+    let mut heap = Heap::new();
+    let row = |heap: &mut Heap, items: &[f64]| {
+        list(
+            heap,
+            items.iter().map(|f| RuntimeValue::Float(*f)).collect(),
+        )
+    };
+    let left_rows = vec![row(&mut heap, &[1.0, 2.0]), row(&mut heap, &[3.0, 4.0])];
+    let close_rows = vec![
+        row(&mut heap, &[1.0 + 1e-10, 2.0]),
+        list(
+            &mut heap,
+            vec![RuntimeValue::Int(3), RuntimeValue::Float(4.0)],
+        ),
+    ];
+    let far_rows = vec![row(&mut heap, &[1.0, 2.0]), row(&mut heap, &[3.0, 4.5])];
+    let short_rows = vec![row(&mut heap, &[1.0, 2.0]), row(&mut heap, &[3.0])];
+    let left = list(&mut heap, left_rows);
+    let near = list(&mut heap, close_rows);
+    let far = list(&mut heap, far_rows);
+    let short = list(&mut heap, short_rows);
+    let eps = RuntimeValue::Float(1e-6);
+    let mut ctx = NativeContext::new(&mut heap);
+    let mut call = |right: &RuntimeValue| {
+        registry.call(
+            "std.assert.assert_all_close",
+            &[left.clone(), right.clone(), eps.clone()],
+            &mut ctx,
+        )
+    };
+
+    assert!(call(&near).is_ok());
+    let err = call(&far).unwrap_err().to_string();
+    assert!(
+        err.contains("left[1][1] ≈ right[1][1]\n  left: 4.0\n right: 4.5"),
+        "{}",
+        err
+    );
+    let err = call(&short).unwrap_err().to_string();
+    assert!(
+        err.contains("len(left[1]) == len(right[1])\n  left: 2 elements\n right: 1 elements"),
+        "{}",
+        err
+    );
+}
@@ -0,0 +1,126 @@
+//! 跨线程传值测试（`backends::common::transfer`）
+//!
+//! 测试覆盖内容：
+//! - 列表连同嵌套对象复制到另一个堆，原堆的修改不影响副本
+//! - 对象间的共享与引用环在副本中保留
+//! - 闭包捕获的堆对象随闭包一起复制
+//! - 指向堆对象的 ref 拒绝跨线程，指向标量的 ref 原样共享
+
+use std::sync::Arc;
+
+use crate::backends::common::value::{FunctionId, FunctionValue};
+use crate::backends::common::{Heap, HeapValue, RuntimeValue, SendValue, TransferError, Value};
+
+fn items(
+    heap: &Heap,
+    value: &RuntimeValue,
+) -> Vec<RuntimeValue> {
+    let (RuntimeValue::List(h) | RuntimeValue::Tuple(h)) = value else {
+        panic!("expected a list or tuple, got {value:?}");
+    };
+    match heap.get(*h) {
+        Some(HeapValue::List(items) | HeapValue::Tuple(items)) => {
+            items.iter().map(Value::get).collect()
+        }
+        other => panic!("expected list storage, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_detached_list_is_rebuilt_on_another_heap() {
+    let mut sender = Heap::new();
+    let inner = sender.allocate(HeapValue::tuple([RuntimeValue::Int(2)]));
+    let list = sender.allocate(HeapValue::list([
+        RuntimeValue::Int(1),
+        RuntimeValue::Tuple(inner),
+    ]));
+
+    let sent = SendValue::detach(&RuntimeValue::List(list), &sender).unwrap();
+    sender
+        .write(inner, HeapValue::tuple([RuntimeValue::Int(99)]))
+        .unwrap();
+
+    // 接收方的堆上已有别的对象，句柄不能与发送方对齐
+    let mut receiver = Heap::new();
+    receiver.allocate(HeapValue::list([RuntimeValue::Int(-1)]));
+    let value = sent.attach(&mut receiver);
+
+    let received = items(&receiver, &value);
+    assert_eq!(received[0], RuntimeValue::Int(1));
+    assert_eq!(items(&receiver, &received[1]), vec![RuntimeValue::Int(2)]);
+    assert_eq!(receiver.len(), 3);
+}
+
+#[test]
+fn test_sharing_and_cycles_survive_the_copy() {
+    let mut sender = Heap::new();
+    let shared = sender.allocate(HeapValue::tuple([RuntimeValue::Int(7)]));
+    let list = sender.allocate(HeapValue::List(Vec::new()));
+    sender
+        .write(
+            list,
+            HeapValue::list([
+                RuntimeValue::Tuple(shared),
+                RuntimeValue::Tuple(shared),
+                RuntimeValue::List(list),
+            ]),
+        )
+        .unwrap();
+
+    let sent = SendValue::detach(&RuntimeValue::List(list), &sender).unwrap();
+    let mut receiver = Heap::new();
+    let value = sent.attach(&mut receiver);
+
+    let received = items(&receiver, &value);
+    assert_eq!(received[0], received[1]);
+    assert_eq!(received[2], value);
+    assert_eq!(receiver.len(), 2);
+}
+
+#[test]
+fn test_closure_captures_are_copied() {
+    let mut sender = Heap::new();
+    let captured = sender.allocate(HeapValue::list([RuntimeValue::Int(3)]));
+    let func = RuntimeValue::Function(Arc::new(FunctionValue {
+        func_id: FunctionId(0),
+        env: vec![RuntimeValue::List(captured)],
+    }));
+
+    let sent = SendValue::detach(&func, &sender).unwrap();
+    let mut receiver = Heap::new();
+    receiver.allocate(HeapValue::List(Vec::new()));
+    let RuntimeValue::Function(received) = sent.attach(&mut receiver) else {
+        panic!("expected a closure");
+    };
+    assert_eq!(
+        items(&receiver, &received.env[0]),
+        vec![RuntimeValue::Int(3)]
+    );
+}
+
+#[test]
+fn test_ref_to_heap_object_is_rejected() {
+    let mut sender = Heap::new();
+    let list = sender.allocate(HeapValue::list([RuntimeValue::Int(1)]));
+    let shared = RuntimeValue::Arc(Arc::new(RuntimeValue::List(list)));
+    let wrapped = sender.allocate(HeapValue::tuple([shared.clone()]));
+
+    assert_eq!(
+        SendValue::detach(&shared, &sender).unwrap_err(),
+        TransferError::SharedHeapObject
+    );
+    assert_eq!(
+        SendValue::detach(&RuntimeValue::Tuple(wrapped), &sender).unwrap_err(),
+        TransferError::SharedHeapObject
+    );
+}
+
+#[test]
+fn test_ref_to_scalar_is_shared() {
+    let shared = Arc::new(RuntimeValue::Int(42));
+    let sent = SendValue::detach(&RuntimeValue::Arc(Arc::clone(&shared)), &Heap::new()).unwrap();
+    let RuntimeValue::Arc(received) = sent.attach(&mut Heap::new()) else {
+        panic!("expected a ref");
+    };
+    assert!(Arc::ptr_eq(&received, &shared));
+}
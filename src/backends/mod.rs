@@ -2,8 +2,9 @@
 //!
 //! This module provides a unified interface for different execution backends:
 //! - Interpreter: Fast bytecode interpretation
-//! - AOT: Ahead-of-time compilation (future)
-//! - JIT: Just-in-time compilation (future)
+//! - WebAssembly: IR lowered to a wasm module (`wasm` feature)
+//! - Template: machine code pasted from per-opcode stencils for hot numeric functions, run by the interpreter
+//! - Native: Cranelift-optimized machine code for the functions that stay hot (`native` feature)
 //!
 //! # Architecture
 //!
@@ -23,7 +24,12 @@
 
 pub mod common;
 pub mod interpreter;
+#[cfg(feature = "native")]
+pub mod native;
 pub mod runtime;
+pub mod template;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
 use crate::middle::bytecode::{BytecodeModule, BytecodeFunction};
 use crate::backends::common::{RuntimeValue, Heap, Handle};
@@ -56,10 +62,11 @@ pub enum ExecutorError {
     Runtime(String, Option<Vec<StackFrame>>),
     /// Type error with optional stack trace
     Type(String, Option<Vec<StackFrame>>),
-    /// Stack overflow
-    StackOverflow(Option<Vec<StackFrame>>),
-    /// Heap exhaustion
-    HeapExhausted,
+    /// Stack overflow: the call depth limit that was hit, and the stack trace
+    StackOverflow(usize, Option<Vec<StackFrame>>),
+    /// Heap exhaustion: an allocation would take the heap past its byte
+    /// limit ([`ExecutorConfig::max_heap_size`], carried)
+    HeapExhausted(usize, Option<Vec<StackFrame>>),
     /// Invalid opcode
     InvalidOpcode(u8),
     /// Invalid handle access
@@ -72,6 +79,21 @@ pub enum ExecutorError {
     FieldNotFound(String, Option<Vec<StackFrame>>),
     /// Function not found
     FunctionNotFound(String, Option<Vec<StackFrame>>),
+    /// Integer overflow (debug builds only; release builds wrap)
+    IntegerOverflow(String, Option<Vec<StackFrame>>),
+    /// Program requested termination via `std.process.exit(code)`
+    ///
+    /// Not a failure: it unwinds the VM and `execute_module` turns it into
+    /// [`ExecutionState::exit_code`].
+    Exit(i32),
+    /// Interrupted by a host signal (`SIGINT`/`SIGTERM`) with no registered handler
+    ///
+    /// Raised at a safepoint, so the stack trace shows where the program stopped.
+    Interrupted(String, Option<Vec<StackFrame>>),
+    /// The program executed its whole instruction budget ([`ExecutorConfig::fuel`])
+    ///
+    /// Carries the budget that ran out; like `Interrupted` it ends the run.
+    FuelExhausted(u64, Option<Vec<StackFrame>>),
 }
 
 impl ExecutorError {
@@ -90,14 +112,92 @@ impl ExecutorError {
         match self {
             ExecutorError::Runtime(_, stack) => stack.as_ref(),
             ExecutorError::Type(_, stack) => stack.as_ref(),
-            ExecutorError::StackOverflow(stack) => stack.as_ref(),
+            ExecutorError::StackOverflow(_, stack) => stack.as_ref(),
             ExecutorError::DivisionByZero(stack) => stack.as_ref(),
             ExecutorError::IndexOutOfBounds(stack) => stack.as_ref(),
             ExecutorError::FieldNotFound(_, stack) => stack.as_ref(),
             ExecutorError::FunctionNotFound(_, stack) => stack.as_ref(),
-            ExecutorError::HeapExhausted => None,
+            ExecutorError::IntegerOverflow(_, stack) => stack.as_ref(),
+            ExecutorError::Interrupted(_, stack) => stack.as_ref(),
+            ExecutorError::FuelExhausted(_, stack) => stack.as_ref(),
+            ExecutorError::HeapExhausted(_, stack) => stack.as_ref(),
             ExecutorError::InvalidOpcode(_) => None,
             ExecutorError::InvalidHandle(_) => None,
+            ExecutorError::Exit(_) => None,
+        }
+    }
+
+    /// The error message without the stack trace
+    pub fn message(&self) -> String {
+        match self {
+            ExecutorError::Runtime(msg, _) => format!("Runtime error: {}", msg),
+            ExecutorError::Type(msg, _) => format!("Type error: {}", msg),
+            ExecutorError::StackOverflow(limit, _) => {
+                format!("Stack overflow: call depth exceeds {}", limit)
+            }
+            ExecutorError::HeapExhausted(limit, _) => {
+                format!(
+                    "Heap exhausted: allocation exceeds the {} byte limit",
+                    limit
+                )
+            }
+            ExecutorError::InvalidOpcode(op) => format!("Invalid opcode: {:#x}", op),
+            ExecutorError::InvalidHandle(h) => format!("Invalid handle: {}", h),
+            ExecutorError::Exit(code) => format!("Program exited with code {}", code),
+            ExecutorError::DivisionByZero(_) => "Division by zero".to_string(),
+            ExecutorError::IndexOutOfBounds(_) => "Index out of bounds".to_string(),
+            ExecutorError::FieldNotFound(name, _) => format!("Field not found: {}", name),
+            ExecutorError::FunctionNotFound(name, _) => format!("Function not found: {}", name),
+            ExecutorError::IntegerOverflow(operation, _) => {
+                format!("Integer overflow: {}", operation)
+            }
+            ExecutorError::Interrupted(signal, _) => format!("Interrupted by {}", signal),
+            ExecutorError::FuelExhausted(budget, _) => {
+                format!("Fuel exhausted: executed {} instructions", budget)
+            }
+        }
+    }
+
+    /// Whether the error is a panic the program can recover from
+    ///
+    /// Exit requests, host interrupts, an exhausted instruction budget and
+    /// corrupt bytecode always end the run.
+    pub fn is_panic(&self) -> bool {
+        !matches!(
+            self,
+            ExecutorError::Exit(_)
+                | ExecutorError::Interrupted(..)
+                | ExecutorError::FuelExhausted(..)
+                | ExecutorError::InvalidOpcode(_)
+                | ExecutorError::InvalidHandle(_)
+        )
+    }
+
+    /// Append the frame of the caller an error unwinds into (outermost last)
+    ///
+    /// Errors without a stack trace are left unchanged.
+    pub fn push_caller(
+        &mut self,
+        frame: StackFrame,
+    ) {
+        let stack = match self {
+            ExecutorError::Runtime(_, stack)
+            | ExecutorError::Type(_, stack)
+            | ExecutorError::StackOverflow(_, stack)
+            | ExecutorError::DivisionByZero(stack)
+            | ExecutorError::IndexOutOfBounds(stack)
+            | ExecutorError::FieldNotFound(_, stack)
+            | ExecutorError::FunctionNotFound(_, stack)
+            | ExecutorError::IntegerOverflow(_, stack)
+            | ExecutorError::Interrupted(_, stack)
+            | ExecutorError::FuelExhausted(_, stack)
+            | ExecutorError::HeapExhausted(_, stack) => stack,
+            ExecutorError::InvalidOpcode(_)
+            | ExecutorError::InvalidHandle(_)
+            | ExecutorError::Exit(_) => return,
+        };
+        if let Some(stack) = stack {
+            stack.push(frame);
         }
     }
 
@@ -133,9 +233,12 @@ impl ExecutorError {
         ExecutorError::FieldNotFound(name.into(), Some(stack))
     }
 
-    /// Create a stack overflow error with stack trace
-    pub fn stack_overflow(stack: Vec<StackFrame>) -> Self {
-        ExecutorError::StackOverflow(Some(stack))
+    /// Create a stack overflow error for the depth `limit` with stack trace
+    pub fn stack_overflow(
+        limit: usize,
+        stack: Vec<StackFrame>,
+    ) -> Self {
+        ExecutorError::StackOverflow(limit, Some(stack))
     }
 
     /// Create a division by zero error with stack trace
@@ -143,6 +246,14 @@ impl ExecutorError {
         ExecutorError::DivisionByZero(Some(stack))
     }
 
+    /// Create an integer overflow error with stack trace
+    pub fn integer_overflow(
+        operation: impl Into<String>,
+        stack: Vec<StackFrame>,
+    ) -> Self {
+        ExecutorError::IntegerOverflow(operation.into(), Some(stack))
+    }
+
     /// Create an index out of bounds error with stack trace
     pub fn index_out_of_bounds(stack: Vec<StackFrame>) -> Self {
         ExecutorError::IndexOutOfBounds(Some(stack))
@@ -157,15 +268,21 @@ impl ExecutorError {
             // Already has stack trace
             ExecutorError::Runtime(_, Some(_)) => self,
             ExecutorError::Type(_, Some(_)) => self,
-            ExecutorError::StackOverflow(Some(_)) => self,
+            ExecutorError::StackOverflow(_, Some(_)) => self,
             ExecutorError::DivisionByZero(Some(_)) => self,
             ExecutorError::IndexOutOfBounds(Some(_)) => self,
             ExecutorError::FieldNotFound(_, Some(_)) => self,
             ExecutorError::FunctionNotFound(_, Some(_)) => self,
+            ExecutorError::IntegerOverflow(_, Some(_)) => self,
+            ExecutorError::Interrupted(_, Some(_)) => self,
+            ExecutorError::FuelExhausted(_, Some(_)) => self,
+            ExecutorError::HeapExhausted(_, Some(_)) => self,
             // Add stack trace
             ExecutorError::Runtime(msg, None) => ExecutorError::Runtime(msg, Some(stack)),
             ExecutorError::Type(msg, None) => ExecutorError::Type(msg, Some(stack)),
-            ExecutorError::StackOverflow(None) => ExecutorError::StackOverflow(Some(stack)),
+            ExecutorError::StackOverflow(limit, None) => {
+                ExecutorError::StackOverflow(limit, Some(stack))
+            }
             ExecutorError::DivisionByZero(None) => ExecutorError::DivisionByZero(Some(stack)),
             ExecutorError::IndexOutOfBounds(None) => ExecutorError::IndexOutOfBounds(Some(stack)),
             ExecutorError::FieldNotFound(name, None) => {
@@ -174,10 +291,22 @@ impl ExecutorError {
             ExecutorError::FunctionNotFound(name, None) => {
                 ExecutorError::FunctionNotFound(name, Some(stack))
             }
+            ExecutorError::IntegerOverflow(operation, None) => {
+                ExecutorError::IntegerOverflow(operation, Some(stack))
+            }
+            ExecutorError::Interrupted(signal, None) => {
+                ExecutorError::Interrupted(signal, Some(stack))
+            }
+            ExecutorError::FuelExhausted(budget, None) => {
+                ExecutorError::FuelExhausted(budget, Some(stack))
+            }
+            ExecutorError::HeapExhausted(limit, None) => {
+                ExecutorError::HeapExhausted(limit, Some(stack))
+            }
             // These don't support stack trace
-            ExecutorError::HeapExhausted => self,
             ExecutorError::InvalidOpcode(op) => ExecutorError::InvalidOpcode(op),
             ExecutorError::InvalidHandle(h) => ExecutorError::InvalidHandle(h),
+            ExecutorError::Exit(code) => ExecutorError::Exit(code),
         }
     }
 }
@@ -187,79 +316,29 @@ impl std::fmt::Display for ExecutorError {
         &self,
         f: &mut std::fmt::Formatter<'_>,
     ) -> std::fmt::Result {
-        match self {
-            ExecutorError::Runtime(msg, stack) => {
-                write!(f, "Runtime error: {}", msg)?;
-                if let Some(frames) = stack {
-                    for frame in frames {
-                        writeln!(f, "{}", frame)?;
-                    }
-                }
-                Ok(())
-            }
-            ExecutorError::Type(msg, stack) => {
-                write!(f, "Type error: {}", msg)?;
-                if let Some(frames) = stack {
-                    for frame in frames {
-                        writeln!(f, "{}", frame)?;
-                    }
-                }
-                Ok(())
-            }
-            ExecutorError::StackOverflow(stack) => {
-                write!(f, "Stack overflow")?;
-                if let Some(frames) = stack {
-                    for frame in frames {
-                        writeln!(f, "{}", frame)?;
-                    }
-                }
-                Ok(())
-            }
-            ExecutorError::HeapExhausted => write!(f, "Heap exhausted"),
-            ExecutorError::InvalidOpcode(op) => write!(f, "Invalid opcode: {:#x}", op),
-            ExecutorError::InvalidHandle(h) => write!(f, "Invalid handle: {}", h),
-            ExecutorError::DivisionByZero(stack) => {
-                write!(f, "Division by zero")?;
-                if let Some(frames) = stack {
-                    for frame in frames {
-                        writeln!(f, "{}", frame)?;
-                    }
-                }
-                Ok(())
-            }
-            ExecutorError::IndexOutOfBounds(stack) => {
-                write!(f, "Index out of bounds")?;
-                if let Some(frames) = stack {
-                    for frame in frames {
-                        writeln!(f, "{}", frame)?;
-                    }
-                }
-                Ok(())
-            }
-            ExecutorError::FieldNotFound(name, stack) => {
-                write!(f, "Field not found: {}", name)?;
-                if let Some(frames) = stack {
-                    for frame in frames {
-                        writeln!(f, "{}", frame)?;
-                    }
-                }
-                Ok(())
-            }
-            ExecutorError::FunctionNotFound(name, stack) => {
-                write!(f, "Function not found: {}", name)?;
-                if let Some(frames) = stack {
-                    for frame in frames {
-                        writeln!(f, "{}", frame)?;
-                    }
-                }
-                Ok(())
+        write!(f, "{}", self.message())?;
+        if let Some(frames) = self.stack_trace() {
+            for frame in frames {
+                writeln!(f, "{}", frame)?;
             }
         }
+        Ok(())
     }
 }
 
 impl std::error::Error for ExecutorError {}
 
+impl From<crate::backends::common::heap::HeapError> for ExecutorError {
+    fn from(error: crate::backends::common::heap::HeapError) -> Self {
+        use crate::backends::common::heap::HeapError;
+        match error {
+            HeapError::InvalidHandle(handle) => ExecutorError::InvalidHandle(handle),
+            HeapError::LimitExceeded(limit) => ExecutorError::HeapExhausted(limit, None),
+            HeapError::OutOfHandles => ExecutorError::runtime_only(error.to_string()),
+        }
+    }
+}
+
 /// Execution state for a running program
 #[derive(Debug, Clone, Default)]
 pub struct ExecutionState {
@@ -271,6 +350,8 @@ pub struct ExecutionState {
     pub call_depth: usize,
     /// Whether execution is complete
     pub is_complete: bool,
+    /// Exit code requested by the program: `main`'s Int return value or `exit(n)`
+    pub exit_code: i32,
 }
 
 /// Executor trait - all backends must implement this
@@ -336,6 +417,10 @@ pub trait DebuggableExecutor: Executor {
     /// Get the current function name
     fn current_function(&self) -> Option<&str>;
 
+    /// Source location of the current instruction, from the function's line table
+    /// (`None` when the bytecode carries no debug info)
+    fn current_location(&self) -> Option<crate::util::span::DebugSpan>;
+
     /// Get all breakpoints
     fn breakpoints(&self) -> Vec<usize>;
 }
@@ -394,14 +479,38 @@ pub enum BuildMode {
     Profile,
 }
 
+impl BuildMode {
+    /// Whether integer overflow traps (debug/profile) instead of wrapping (release)
+    pub fn traps_overflow(self) -> bool {
+        !matches!(self, BuildMode::Release)
+    }
+}
+
+/// How the interpreter runs the instructions of a call
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DispatchMode {
+    /// Run each frame in a tight loop that keeps it off the call stack
+    #[default]
+    Direct,
+    /// Go through the single-step path the debugger uses, one instruction at a time
+    Step,
+}
+
 /// Configuration for an executor
 #[derive(Debug, Clone)]
 pub struct ExecutorConfig {
     /// Maximum call stack depth
     pub max_stack_depth: usize,
+    /// Bytes of each host stack segment allocated when nested calls run low
+    /// on stack, so deep recursion stops at `max_stack_depth` instead of
+    /// overflowing the host thread
+    pub stack_segment_size: usize,
+    /// Returned frames whose register and local buffers are kept for reuse
+    pub frame_pool_size: usize,
     /// Initial heap capacity
     pub initial_heap_size: usize,
-    /// Maximum heap size
+    /// Bytes the heap may hold; an allocation past it fails with
+    /// [`ExecutorError::HeapExhausted`]
     pub max_heap_size: usize,
     /// Build mode
     pub build_mode: BuildMode,
@@ -409,17 +518,98 @@ pub struct ExecutorConfig {
     pub enable_checks: bool,
     /// Enable debugging features
     pub enable_debug: bool,
+    /// Capabilities granted to the running program
+    pub capabilities: CapabilityPolicy,
+    /// Write a heap dump to this path when the VM shuts down
+    pub heap_dump: Option<std::path::PathBuf>,
+    /// Cache resolved field slots and virtual call targets per instruction
+    pub inline_cache: bool,
+    /// How instructions are dispatched
+    pub dispatch: DispatchMode,
+    /// Run eligible functions as machine code once they are hot
+    pub native_code: bool,
+    /// Interpreted calls a function gets before it is compiled to machine code
+    pub jit_threshold: u32,
+    /// Print what the JIT compiled and where the time went when the VM shuts down
+    pub jit_stats: bool,
+    /// Free unreachable heap objects (reference cycles included) once the heap
+    /// holds this many, and again whenever it has doubled since; 0 turns
+    /// automatic collection off (`gc.collect()` still works)
+    pub gc_threshold: usize,
+    /// Instructions the program may execute before it stops with
+    /// [`ExecutorError::FuelExhausted`]; `None` = unlimited
+    ///
+    /// Machine code cannot be metered, so a budget keeps every function
+    /// interpreted. Tasks run on other threads (Standard / Full runtime) are
+    /// not counted; combine with `deterministic` to meter the whole program.
+    pub fuel: Option<u64>,
+    /// Run spawned tasks inline, in the order they are spawned, and never as
+    /// machine code, so the same program and input execute the same
+    /// instructions every time
+    pub deterministic: bool,
+}
+
+/// Capabilities granted to the running program
+///
+/// 宿主通过该策略限制程序在运行期可以做的事情（例如动态加载其它模块）。
+/// 默认拒绝所有可选能力；命令行工具运行的是用户自己的代码，使用 [`CapabilityPolicy::allow_all`]。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CapabilityPolicy {
+    /// Allow `std.module.import` to compile and load modules at runtime
+    pub dynamic_import: bool,
+    /// Directories that dynamically imported files must live under (empty = unrestricted)
+    pub import_roots: Vec<std::path::PathBuf>,
+    /// Allow vendored packages to load their companion native extension library
+    pub native_extensions: bool,
+}
+
+impl Default for CapabilityPolicy {
+    fn default() -> Self {
+        Self::deny_all()
+    }
+}
+
+impl CapabilityPolicy {
+    /// A policy that denies every optional capability
+    pub fn deny_all() -> Self {
+        Self {
+            dynamic_import: false,
+            import_roots: Vec::new(),
+            native_extensions: false,
+        }
+    }
+
+    /// A policy that grants every optional capability, with imports unrestricted
+    pub fn allow_all() -> Self {
+        Self {
+            dynamic_import: true,
+            import_roots: Vec::new(),
+            native_extensions: true,
+        }
+    }
 }
 
 impl Default for ExecutorConfig {
     fn default() -> Self {
         Self {
             max_stack_depth: 1024,
+            stack_segment_size: 1024 * 1024,
+            frame_pool_size: 64,
             initial_heap_size: 64 * 1024,
             max_heap_size: 64 * 1024 * 1024,
             build_mode: BuildMode::Debug,
             enable_checks: true,
             enable_debug: true,
+            capabilities: CapabilityPolicy::default(),
+            heap_dump: None,
+            inline_cache: true,
+            dispatch: DispatchMode::Direct,
+            native_code: true,
+            jit_threshold: 1000,
+            jit_stats: false,
+            gc_threshold: 10_000,
+            fuel: None,
+            deterministic: false,
         }
     }
 }
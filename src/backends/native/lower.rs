@@ -0,0 +1,642 @@
+//! Bytecode to Cranelift IR
+//!
+//! Every slot (register or local) gets one Cranelift variable per type it can
+//! hold; the analysis says which one is live at each instruction. Operations the
+//! interpreter would report an error for — overflow in debug builds, division by
+//! zero, a void return — branch to a single deopt block that flags the context
+//! and returns, so the interpreter can run the call again and report it.
+
+use std::collections::HashMap;
+
+use cranelift_codegen::ir::condcodes::{FloatCC, IntCC};
+use cranelift_codegen::ir::{
+    types, AbiParam, Block, BlockCall, FuncRef, InstBuilder, JumpTableData, MemFlags, Type, Value,
+};
+use cranelift_frontend::{FunctionBuilder, Variable};
+
+use crate::backends::template::analysis::{jump_target, successors, Analysis, Ty};
+use crate::middle::bytecode::{
+    BinaryOp, BytecodeFunction, BytecodeInstr, CompareOp, ConstValue, Reg, UnaryOp,
+};
+
+/// Cranelift type holding values of `ty`
+pub fn clif_type(ty: Ty) -> Type {
+    match ty {
+        Ty::Int => types::I64,
+        Ty::Float => types::F64,
+        Ty::Bool => types::I8,
+    }
+}
+
+/// Append the parameters and result of a compiled function to `sig`:
+/// `(status: *mut u8, depth: i64, params...) -> ret`
+pub fn signature(
+    sig: &mut cranelift_codegen::ir::Signature,
+    params: &[Ty],
+    ret: Ty,
+) {
+    sig.params.push(AbiParam::new(types::I64));
+    sig.params.push(AbiParam::new(types::I64));
+    sig.params
+        .extend(params.iter().map(|ty| AbiParam::new(clif_type(*ty))));
+    sig.returns.push(AbiParam::new(clif_type(ret)));
+}
+
+/// Functions referenced from the function being lowered
+pub struct Callees<'a> {
+    /// Compiled functions by name, with their result types
+    pub functions: &'a HashMap<String, (FuncRef, Ty)>,
+    /// `fmod`, for `Float % Float`
+    pub float_rem: FuncRef,
+}
+
+/// Lowers one analyzed function into `builder`
+pub struct Lowering<'a, 'b> {
+    builder: FunctionBuilder<'b>,
+    func: &'a BytecodeFunction,
+    analysis: &'a Analysis,
+    constants: &'a [ConstValue],
+    callees: Callees<'a>,
+    callee_name: &'a dyn Fn(&crate::middle::bytecode::FunctionRef) -> String,
+    traps_overflow: bool,
+    /// Block of each basic-block leader
+    blocks: HashMap<usize, Block>,
+    entry: Block,
+    deopt: Block,
+    status: Value,
+    depth: Value,
+}
+
+impl<'a, 'b> Lowering<'a, 'b> {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        mut builder: FunctionBuilder<'b>,
+        func: &'a BytecodeFunction,
+        analysis: &'a Analysis,
+        constants: &'a [ConstValue],
+        callees: Callees<'a>,
+        callee_name: &'a dyn Fn(&crate::middle::bytecode::FunctionRef) -> String,
+        traps_overflow: bool,
+    ) -> Self {
+        let slots = analysis
+            .states
+            .iter()
+            .flatten()
+            .map(Vec::len)
+            .max()
+            .unwrap_or(0);
+        for slot in 0..slots {
+            for ty in [Ty::Int, Ty::Float, Ty::Bool] {
+                builder.declare_var(variable(slot, ty), clif_type(ty));
+            }
+        }
+        let entry = builder.create_block();
+        builder.append_block_params_for_function_params(entry);
+        let deopt = builder.create_block();
+        let status = builder.block_params(entry)[0];
+        let depth = builder.block_params(entry)[1];
+        Self {
+            builder,
+            func,
+            analysis,
+            constants,
+            callees,
+            callee_name,
+            traps_overflow,
+            blocks: HashMap::new(),
+            entry,
+            deopt,
+            status,
+            depth,
+        }
+    }
+
+    /// Emit the whole function
+    pub fn lower(mut self) {
+        let entry = self.entry;
+        self.builder.switch_to_block(entry);
+        self.create_blocks();
+
+        // Entry: safepoint, call depth, then the arguments become locals 0..n
+        self.poll_signals();
+        let exhausted = self.builder.ins().icmp_imm(IntCC::Equal, self.depth, 0);
+        self.deopt_if(exhausted);
+        let params = self.builder.block_params(entry)[2..].to_vec();
+        for (i, (value, ty)) in params
+            .iter()
+            .zip(&self.analysis.signature.params)
+            .enumerate()
+        {
+            let slot = self.analysis.registers + i;
+            self.builder.def_var(variable(slot, *ty), *value);
+        }
+        let first = self.blocks[&0];
+        self.builder.ins().jump(first, &[]);
+
+        let len = self.func.instructions.len();
+        let loop_headers = self.loop_headers();
+        let mut open = false;
+        for ip in 0..len {
+            if let Some(&block) = self.blocks.get(&ip) {
+                if open {
+                    self.builder.ins().jump(block, &[]);
+                }
+                self.builder.switch_to_block(block);
+                open = true;
+                if loop_headers.contains(&ip) {
+                    self.poll_signals();
+                }
+            }
+            if !open || self.analysis.states[ip].is_none() {
+                open = false;
+                continue;
+            }
+            let instr = &self.func.instructions[ip];
+            self.instruction(ip, instr);
+            open = !is_terminator(instr);
+            if open && ip + 1 == len {
+                // Running off the end returns void
+                self.builder.ins().jump(self.deopt, &[]);
+                open = false;
+            }
+        }
+
+        self.builder.switch_to_block(self.deopt);
+        let one = self.builder.ins().iconst(types::I8, 1);
+        self.builder
+            .ins()
+            .store(MemFlags::trusted(), one, self.status, 0);
+        let zero = self.zero(self.analysis.signature.ret);
+        self.builder.ins().return_(&[zero]);
+
+        self.builder.seal_all_blocks();
+        self.builder.finalize();
+    }
+
+    /// A block for every reachable basic-block leader
+    fn create_blocks(&mut self) {
+        let instructions = &self.func.instructions;
+        let len = instructions.len();
+        let mut leaders = vec![0];
+        for (ip, instr) in instructions.iter().enumerate() {
+            let nexts = successors(ip, instr, len);
+            if nexts.len() != 1 || nexts[0] != Some(ip + 1) {
+                leaders.extend(nexts.into_iter().flatten());
+                leaders.push(ip + 1);
+            }
+        }
+        for ip in leaders {
+            if ip < len && self.analysis.states[ip].is_some() && !self.blocks.contains_key(&ip) {
+                let block = self.builder.create_block();
+                self.blocks.insert(ip, block);
+            }
+        }
+    }
+
+    /// Targets of backward jumps, where the interpreter has its loop safepoints
+    fn loop_headers(&self) -> Vec<usize> {
+        let len = self.func.instructions.len();
+        let mut headers = Vec::new();
+        for (ip, instr) in self.func.instructions.iter().enumerate() {
+            for next in successors(ip, instr, len).into_iter().flatten() {
+                if next <= ip {
+                    headers.push(next);
+                }
+            }
+        }
+        headers
+    }
+
+    /// Deopt when a signal is pending (without taking it: the interpreter handles it)
+    fn poll_signals(&mut self) {
+        let flags = crate::std::signal::pending_flags().as_ptr() as i64;
+        let base = self.builder.ins().iconst(types::I64, flags);
+        let interrupt = self
+            .builder
+            .ins()
+            .load(types::I8, MemFlags::trusted(), base, 0);
+        let terminate = self
+            .builder
+            .ins()
+            .load(types::I8, MemFlags::trusted(), base, 1);
+        let pending = self.builder.ins().bor(interrupt, terminate);
+        self.deopt_if(pending);
+    }
+
+    /// Branch to the deopt block when `cond` is non-zero, continuing in a new block
+    fn deopt_if(
+        &mut self,
+        cond: Value,
+    ) {
+        let next = self.builder.create_block();
+        self.builder.ins().brif(cond, self.deopt, &[], next, &[]);
+        self.builder.switch_to_block(next);
+    }
+
+    fn zero(
+        &mut self,
+        ty: Ty,
+    ) -> Value {
+        match ty {
+            Ty::Float => self.builder.ins().f64const(0.0),
+            _ => self.builder.ins().iconst(clif_type(ty), 0),
+        }
+    }
+
+    /// Block that jumping to the instruction at `target` enters
+    fn target(
+        &self,
+        ip: usize,
+        label: crate::middle::bytecode::Label,
+    ) -> Block {
+        jump_target(ip, label, self.func.instructions.len())
+            .map_or(self.deopt, |target| self.blocks[&target])
+    }
+
+    fn read(
+        &mut self,
+        ip: usize,
+        reg: Reg,
+    ) -> (Value, Ty) {
+        let ty = self.analysis.reg(ip, reg);
+        (self.builder.use_var(variable(reg.0 as usize, ty)), ty)
+    }
+
+    /// Read a number as a float (ints widen)
+    fn read_float(
+        &mut self,
+        ip: usize,
+        reg: Reg,
+    ) -> Value {
+        let (value, ty) = self.read(ip, reg);
+        self.widen(value, ty)
+    }
+
+    fn widen(
+        &mut self,
+        value: Value,
+        ty: Ty,
+    ) -> Value {
+        match ty {
+            Ty::Int => self.builder.ins().fcvt_from_sint(types::F64, value),
+            _ => value,
+        }
+    }
+
+    fn write(
+        &mut self,
+        reg: Reg,
+        ty: Ty,
+        value: Value,
+    ) {
+        self.builder.def_var(variable(reg.0 as usize, ty), value);
+    }
+
+    fn instruction(
+        &mut self,
+        ip: usize,
+        instr: &BytecodeInstr,
+    ) {
+        match instr {
+            BytecodeInstr::Nop
+            | BytecodeInstr::Yield
+            | BytecodeInstr::Drop { .. }
+            | BytecodeInstr::Release { .. }
+            | BytecodeInstr::ArcDrop { .. } => {}
+            BytecodeInstr::Return => {
+                self.builder.ins().jump(self.deopt, &[]);
+            }
+            BytecodeInstr::ReturnValue { value } => {
+                let (value, _) = self.read(ip, *value);
+                self.builder.ins().return_(&[value]);
+            }
+            BytecodeInstr::Jmp { target } => {
+                let target = self.target(ip, *target);
+                self.builder.ins().jump(target, &[]);
+            }
+            BytecodeInstr::JmpIf { cond, target } | BytecodeInstr::JmpIfNot { cond, target } => {
+                let (cond, _) = self.read(ip, *cond);
+                let taken = self.target(ip, *target);
+                let next = self.blocks.get(&(ip + 1)).copied().unwrap_or(self.deopt);
+                if matches!(instr, BytecodeInstr::JmpIf { .. }) {
+                    self.builder.ins().brif(cond, taken, &[], next, &[]);
+                } else {
+                    self.builder.ins().brif(cond, next, &[], taken, &[]);
+                }
+            }
+            BytecodeInstr::TableSwitch {
+                value,
+                low,
+                targets,
+                default,
+            } => {
+                let (key, _) = self.read(ip, *value);
+                let default = self.target(ip, *default);
+                let (index, overflowed) = {
+                    let low = self.builder.ins().iconst(types::I64, *low);
+                    self.builder.ins().ssub_overflow(key, low)
+                };
+                let checked = self.builder.create_block();
+                self.builder
+                    .ins()
+                    .brif(overflowed, default, &[], checked, &[]);
+                self.builder.switch_to_block(checked);
+                let in_range = self.builder.create_block();
+                let outside = self.builder.ins().icmp_imm(
+                    IntCC::UnsignedGreaterThanOrEqual,
+                    index,
+                    targets.len() as i64,
+                );
+                self.builder
+                    .ins()
+                    .brif(outside, default, &[], in_range, &[]);
+                self.builder.switch_to_block(in_range);
+                let index = self.builder.ins().ireduce(types::I32, index);
+                let default_call = self.block_call(default);
+                let table: Vec<BlockCall> = targets
+                    .iter()
+                    .map(|target| {
+                        let block = self.target(ip, *target);
+                        self.block_call(block)
+                    })
+                    .collect();
+                let table = self
+                    .builder
+                    .create_jump_table(JumpTableData::new(default_call, &table));
+                self.builder.ins().br_table(index, table);
+            }
+            BytecodeInstr::Mov { dst, src } => {
+                let (value, ty) = self.read(ip, *src);
+                self.write(*dst, ty, value);
+            }
+            BytecodeInstr::LoadConst { dst, const_idx } => {
+                let (value, ty) = match &self.constants[*const_idx as usize] {
+                    ConstValue::Int(n) => {
+                        (self.builder.ins().iconst(types::I64, *n as i64), Ty::Int)
+                    }
+                    ConstValue::Float(f) => (self.builder.ins().f64const(*f), Ty::Float),
+                    ConstValue::Bool(b) => {
+                        (self.builder.ins().iconst(types::I8, *b as i64), Ty::Bool)
+                    }
+                    other => unreachable!("constant {:?} is not native", other),
+                };
+                self.write(*dst, ty, value);
+            }
+            BytecodeInstr::LoadLocal { dst, local_idx }
+            | BytecodeInstr::LoadArg {
+                dst,
+                arg_idx: local_idx,
+            } => {
+                let ty = self.analysis.local(ip, *local_idx);
+                let slot = self.analysis.registers + *local_idx as usize;
+                let value = self.builder.use_var(variable(slot, ty));
+                self.write(*dst, ty, value);
+            }
+            BytecodeInstr::StoreLocal { local_idx, src } => {
+                let (value, ty) = self.read(ip, *src);
+                let slot = self.analysis.registers + *local_idx as usize;
+                self.builder.def_var(variable(slot, ty), value);
+            }
+            BytecodeInstr::BinaryOp { dst, lhs, rhs, op } => {
+                let (l, ty) = self.read(ip, *lhs);
+                let (r, _) = self.read(ip, *rhs);
+                let value = match ty {
+                    Ty::Int => self.int_op(*op, l, r),
+                    _ => self.float_op(*op, l, r),
+                };
+                self.write(*dst, ty, value);
+            }
+            BytecodeInstr::UnaryOp { dst, src, op } => {
+                let (value, ty) = self.read(ip, *src);
+                let result = match (op, ty) {
+                    (UnaryOp::Neg, Ty::Int) => {
+                        if self.traps_overflow {
+                            let min = self.builder.ins().icmp_imm(IntCC::Equal, value, i64::MIN);
+                            self.deopt_if(min);
+                        }
+                        self.builder.ins().ineg(value)
+                    }
+                    (UnaryOp::Neg, _) => self.builder.ins().fneg(value),
+                    (UnaryOp::Not, Ty::Int) => self.builder.ins().bnot(value),
+                    (UnaryOp::Not, _) => self.builder.ins().bxor_imm(value, 1),
+                };
+                self.write(*dst, ty, result);
+            }
+            BytecodeInstr::Compare { dst, lhs, rhs, cmp } => {
+                let (l, lty) = self.read(ip, *lhs);
+                let (r, rty) = self.read(ip, *rhs);
+                let value = if (lty, rty) == (Ty::Int, Ty::Int) {
+                    self.builder.ins().icmp(int_cc(*cmp), l, r)
+                } else {
+                    let l = self.widen(l, lty);
+                    let r = self.widen(r, rty);
+                    self.builder.ins().fcmp(float_cc(*cmp), l, r)
+                };
+                self.write(*dst, Ty::Bool, value);
+            }
+            BytecodeInstr::FloatOp { dst, lhs, rhs, op } => {
+                let l = self.read_float(ip, *lhs);
+                let r = self.read_float(ip, *rhs);
+                let value = self.float_op(*op, l, r);
+                self.write(*dst, Ty::Float, value);
+            }
+            BytecodeInstr::FloatCompare { dst, lhs, rhs, cmp } => {
+                let l = self.read_float(ip, *lhs);
+                let r = self.read_float(ip, *rhs);
+                let value = self.builder.ins().fcmp(float_cc(*cmp), l, r);
+                self.write(*dst, Ty::Bool, value);
+            }
+            BytecodeInstr::FloatNeg { dst, src } => {
+                let value = self.read_float(ip, *src);
+                let value = self.builder.ins().fneg(value);
+                self.write(*dst, Ty::Float, value);
+            }
+            BytecodeInstr::IntToFloat { dst, src } => {
+                let value = self.read_float(ip, *src);
+                self.write(*dst, Ty::Float, value);
+            }
+            BytecodeInstr::CallStatic { dst, func, args } => {
+                let (callee, ty) = self.callee(func);
+                let args = self.call_args(ip, args, true);
+                let call = self.builder.ins().call(callee, &args);
+                let result = self.builder.inst_results(call)[0];
+                // A deopt anywhere below unwinds the whole native call
+                let status =
+                    self.builder
+                        .ins()
+                        .load(types::I8, MemFlags::trusted(), self.status, 0);
+                self.deopt_if(status);
+                if let Some(dst) = dst {
+                    self.write(*dst, ty, result);
+                }
+            }
+            BytecodeInstr::TailCall { func, args } => {
+                let (callee, _) = self.callee(func);
+                let args = self.call_args(ip, args, false);
+                self.builder.ins().return_call(callee, &args);
+            }
+            other => unreachable!("{:?} is not native", other),
+        }
+    }
+
+    fn block_call(
+        &mut self,
+        block: Block,
+    ) -> BlockCall {
+        self.builder.func.dfg.block_call(block, &[])
+    }
+
+    fn callee(
+        &self,
+        func: &crate::middle::bytecode::FunctionRef,
+    ) -> (FuncRef, Ty) {
+        self.callees.functions[&(self.callee_name)(func)]
+    }
+
+    /// Context, depth and argument values of a call; a tail call reuses the
+    /// caller's depth as it replaces the caller's frame
+    fn call_args(
+        &mut self,
+        ip: usize,
+        args: &[Reg],
+        nested: bool,
+    ) -> Vec<Value> {
+        let depth = if nested {
+            self.builder.ins().iadd_imm(self.depth, -1)
+        } else {
+            self.depth
+        };
+        let mut values = vec![self.status, depth];
+        for arg in args {
+            values.push(self.read(ip, *arg).0);
+        }
+        values
+    }
+
+    /// Integer arithmetic with the interpreter's overflow and division rules
+    fn int_op(
+        &mut self,
+        op: BinaryOp,
+        l: Value,
+        r: Value,
+    ) -> Value {
+        match op {
+            BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul => {
+                if !self.traps_overflow {
+                    return match op {
+                        BinaryOp::Add => self.builder.ins().iadd(l, r),
+                        BinaryOp::Sub => self.builder.ins().isub(l, r),
+                        _ => self.builder.ins().imul(l, r),
+                    };
+                }
+                let (value, overflowed) = match op {
+                    BinaryOp::Add => self.builder.ins().sadd_overflow(l, r),
+                    BinaryOp::Sub => self.builder.ins().ssub_overflow(l, r),
+                    _ => self.builder.ins().smul_overflow(l, r),
+                };
+                self.deopt_if(overflowed);
+                value
+            }
+            BinaryOp::Div | BinaryOp::Rem => {
+                let zero = self.builder.ins().icmp_imm(IntCC::Equal, r, 0);
+                self.deopt_if(zero);
+                // `Int.MIN / -1` overflows; wrapping gives `Int.MIN` and remainder 0
+                let min = self.builder.ins().icmp_imm(IntCC::Equal, l, i64::MIN);
+                let minus_one = self.builder.ins().icmp_imm(IntCC::Equal, r, -1);
+                let overflowed = self.builder.ins().band(min, minus_one);
+                let r = if self.traps_overflow {
+                    self.deopt_if(overflowed);
+                    r
+                } else {
+                    let one = self.builder.ins().iconst(types::I64, 1);
+                    self.builder.ins().select(overflowed, one, r)
+                };
+                if op == BinaryOp::Div {
+                    self.builder.ins().sdiv(l, r)
+                } else {
+                    self.builder.ins().srem(l, r)
+                }
+            }
+            BinaryOp::Shl | BinaryOp::Sar | BinaryOp::Shr => {
+                if self.traps_overflow {
+                    let overflowed =
+                        self.builder
+                            .ins()
+                            .icmp_imm(IntCC::UnsignedGreaterThanOrEqual, r, 64);
+                    self.deopt_if(overflowed);
+                }
+                // Both right shifts are arithmetic in the interpreter
+                match op {
+                    BinaryOp::Shl => self.builder.ins().ishl(l, r),
+                    _ => self.builder.ins().sshr(l, r),
+                }
+            }
+            BinaryOp::And => self.builder.ins().band(l, r),
+            BinaryOp::Or => self.builder.ins().bor(l, r),
+            BinaryOp::Xor => self.builder.ins().bxor(l, r),
+        }
+    }
+
+    fn float_op(
+        &mut self,
+        op: BinaryOp,
+        l: Value,
+        r: Value,
+    ) -> Value {
+        match op {
+            BinaryOp::Add => self.builder.ins().fadd(l, r),
+            BinaryOp::Sub => self.builder.ins().fsub(l, r),
+            BinaryOp::Mul => self.builder.ins().fmul(l, r),
+            BinaryOp::Div => self.builder.ins().fdiv(l, r),
+            _ => {
+                let call = self.builder.ins().call(self.callees.float_rem, &[l, r]);
+                self.builder.inst_results(call)[0]
+            }
+        }
+    }
+}
+
+/// Whether control never continues with the next instruction
+fn is_terminator(instr: &BytecodeInstr) -> bool {
+    matches!(
+        instr,
+        BytecodeInstr::Return
+            | BytecodeInstr::ReturnValue { .. }
+            | BytecodeInstr::Jmp { .. }
+            | BytecodeInstr::JmpIf { .. }
+            | BytecodeInstr::JmpIfNot { .. }
+            | BytecodeInstr::TableSwitch { .. }
+            | BytecodeInstr::TailCall { .. }
+    )
+}
+
+/// Variable holding values of `ty` in `slot`
+fn variable(
+    slot: usize,
+    ty: Ty,
+) -> Variable {
+    Variable::from_u32((slot * 3 + ty.index()) as u32)
+}
+
+fn int_cc(cmp: CompareOp) -> IntCC {
+    match cmp {
+        CompareOp::Eq => IntCC::Equal,
+        CompareOp::Ne => IntCC::NotEqual,
+        CompareOp::Lt => IntCC::SignedLessThan,
+        CompareOp::Le => IntCC::SignedLessThanOrEqual,
+        CompareOp::Gt => IntCC::SignedGreaterThan,
+        CompareOp::Ge => IntCC::SignedGreaterThanOrEqual,
+    }
+}
+
+/// IEEE comparisons: only `!=` holds for NaN
+fn float_cc(cmp: CompareOp) -> FloatCC {
+    match cmp {
+        CompareOp::Eq => FloatCC::Equal,
+        CompareOp::Ne => FloatCC::NotEqual,
+        CompareOp::Lt => FloatCC::LessThan,
+        CompareOp::Le => FloatCC::LessThanOrEqual,
+        CompareOp::Gt => FloatCC::GreaterThan,
+        CompareOp::Ge => FloatCC::GreaterThanOrEqual,
+    }
+}
@@ -0,0 +1,338 @@
+//! Native backend
+//!
+//! Compiles type-stable functions to machine code with Cranelift, lowering each
+//! bytecode instruction to Cranelift IR and optimizing the function as a whole.
+//! It is the interpreter's second tier: functions start out as template code
+//! (see [`crate::backends::template`]) and are recompiled here once they stay hot.
+//!
+//! # Coverage
+//!
+//! The same functions as the template JIT, whose analysis it shares.
+//!
+//! # Deoptimization
+//!
+//! Compiled functions are pure, so whenever the interpreter would do something
+//! the machine code does not — report an overflow in a debug build, a division by
+//! zero or a stack overflow, return void, or handle a pending signal — the
+//! machine code gives up and the interpreter runs the whole call again. Errors and
+//! stack traces are therefore exactly the interpreter's.
+
+mod lower;
+
+#[cfg(test)]
+mod tests;
+
+use std::collections::HashMap;
+
+use cranelift_codegen::ir::{types, AbiParam, InstBuilder, MemFlags, UserFuncName};
+use cranelift_codegen::isa::CallConv;
+use cranelift_codegen::settings::{self, Configurable};
+use cranelift_frontend::{FunctionBuilder, FunctionBuilderContext};
+use cranelift_jit::{JITBuilder, JITModule};
+use cranelift_module::{FuncId, Linkage, Module};
+
+use lower::{Callees, Lowering};
+
+use crate::backends::common::RuntimeValue;
+use crate::backends::template::analysis::{Signature, Ty};
+use crate::backends::template::analyze;
+pub use crate::backends::template::{Outcome, Program};
+
+/// `extern "C" fn(status, depth, args, ret)` entry of a compiled function
+type Entry = unsafe extern "C" fn(*mut u8, i64, *const u64, *mut u64);
+
+struct NativeFunction {
+    signature: Signature,
+    entry: Entry,
+}
+
+/// Machine code for the compilable functions of a program
+///
+/// # Safety
+/// The module is finalized before a `NativeCode` is handed out and only freed on
+/// drop; after that it is never touched, and the compiled functions keep no
+/// state of their own, so calls from several threads at once cannot race.
+pub struct NativeCode {
+    module: Option<JITModule>,
+    functions: HashMap<String, NativeFunction>,
+}
+unsafe impl Send for NativeCode {}
+unsafe impl Sync for NativeCode {}
+
+impl std::fmt::Debug for NativeCode {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        f.debug_struct("NativeCode")
+            .field("functions", &self.functions.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl NativeCode {
+    /// Compile every function of `program` that qualifies
+    ///
+    /// Compilation never fails the program: if Cranelift rejects the code (or
+    /// does not support the host), nothing is compiled and everything is
+    /// interpreted.
+    pub fn compile(program: &Program<'_>) -> NativeCode {
+        match Self::try_compile(program) {
+            Ok(code) => code,
+            Err(e) => {
+                tracing::debug!("native compilation failed: {}", e);
+                NativeCode {
+                    module: None,
+                    functions: HashMap::new(),
+                }
+            }
+        }
+    }
+
+    /// Whether `name` runs as machine code
+    pub fn is_compiled(
+        &self,
+        name: &str,
+    ) -> bool {
+        self.functions.contains_key(name)
+    }
+
+    /// Names of the compiled functions
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.functions.keys().map(String::as_str)
+    }
+
+    /// Number of compiled functions
+    pub fn len(&self) -> usize {
+        self.functions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.functions.is_empty()
+    }
+
+    /// Call the compiled `name` with `depth` call levels left (counting its own)
+    ///
+    /// Returns `None` when `name` is not compiled or `args` do not have the
+    /// types it was compiled for.
+    pub fn call(
+        &self,
+        name: &str,
+        args: &[RuntimeValue],
+        depth: usize,
+    ) -> Option<Outcome> {
+        let function = self.functions.get(name)?;
+        if args.len() != function.signature.params.len() {
+            return None;
+        }
+        let mut raw = Vec::with_capacity(args.len());
+        for (arg, ty) in args.iter().zip(&function.signature.params) {
+            raw.push(match (arg, ty) {
+                (RuntimeValue::Int(n), Ty::Int) => *n as u64,
+                (RuntimeValue::Float(f), Ty::Float) => f.to_bits(),
+                (RuntimeValue::Bool(b), Ty::Bool) => *b as u64,
+                _ => return None,
+            });
+        }
+
+        let mut status = 0u8;
+        let mut ret = 0u64;
+        let depth = i64::try_from(depth).unwrap_or(i64::MAX);
+        // SAFETY: the entry was compiled for exactly these argument types, and the
+        // code stays mapped as long as `self` (which owns the module) is alive.
+        unsafe { (function.entry)(&mut status, depth, raw.as_ptr(), &mut ret) };
+        if status != 0 {
+            return Some(Outcome::Deopt);
+        }
+        Some(Outcome::Returned(match function.signature.ret {
+            Ty::Int => RuntimeValue::Int(ret as i64),
+            Ty::Float => RuntimeValue::Float(f64::from_bits(ret)),
+            Ty::Bool => RuntimeValue::Bool(ret != 0),
+        }))
+    }
+
+    fn try_compile(program: &Program<'_>) -> Result<NativeCode, String> {
+        let analyses = analyze(program);
+        if analyses.is_empty() {
+            return Ok(NativeCode {
+                module: None,
+                functions: HashMap::new(),
+            });
+        }
+
+        let mut flags = settings::builder();
+        flags.set("opt_level", "speed").map_err(|e| e.to_string())?;
+        // Tail calls (`TailCall`) rely on frame pointers
+        flags
+            .set("preserve_frame_pointers", "true")
+            .map_err(|e| e.to_string())?;
+        let isa = cranelift_native::builder()?
+            .finish(settings::Flags::new(flags))
+            .map_err(|e| e.to_string())?;
+        let mut builder = JITBuilder::with_isa(isa, cranelift_module::default_libcall_names());
+        builder.symbol("yx_float_rem", float_rem as *const u8);
+        let mut module = JITModule::new(builder);
+
+        // Declare everything first: functions call each other in any order
+        let mut float_rem_sig = module.make_signature();
+        float_rem_sig.params.push(AbiParam::new(types::F64));
+        float_rem_sig.params.push(AbiParam::new(types::F64));
+        float_rem_sig.returns.push(AbiParam::new(types::F64));
+        let float_rem_id = module
+            .declare_function("yx_float_rem", Linkage::Import, &float_rem_sig)
+            .map_err(|e| e.to_string())?;
+        let mut ids: HashMap<&str, (FuncId, FuncId)> = HashMap::new();
+        for (index, (func, analysis)) in analyses.iter().enumerate() {
+            let mut sig = module.make_signature();
+            sig.call_conv = CallConv::Tail;
+            lower::signature(&mut sig, &analysis.signature.params, analysis.signature.ret);
+            let body = module
+                .declare_function(&format!("yx_fn{}", index), Linkage::Local, &sig)
+                .map_err(|e| e.to_string())?;
+            let mut entry_sig = module.make_signature();
+            entry_sig.params.extend([AbiParam::new(types::I64); 4]);
+            let entry = module
+                .declare_function(&format!("yx_entry{}", index), Linkage::Local, &entry_sig)
+                .map_err(|e| e.to_string())?;
+            ids.insert(func.name.as_str(), (body, entry));
+        }
+
+        let mut ctx = module.make_context();
+        let mut builder_ctx = FunctionBuilderContext::new();
+        for (func, analysis) in &analyses {
+            let (body, entry) = ids[func.name.as_str()];
+
+            ctx.func.signature = module
+                .declarations()
+                .get_function_decl(body)
+                .signature
+                .clone();
+            ctx.func.name = UserFuncName::user(0, body.as_u32());
+            let mut functions = HashMap::new();
+            for callee in &analysis.callees {
+                let (id, _) = ids[callee.as_str()];
+                let func_ref = module.declare_func_in_func(id, &mut ctx.func);
+                let ret = analyses
+                    .iter()
+                    .find(|(f, _)| &f.name == callee)
+                    .map(|(_, a)| a.signature.ret)
+                    .expect("callees are compiled");
+                functions.insert(callee.clone(), (func_ref, ret));
+            }
+            let float_rem = module.declare_func_in_func(float_rem_id, &mut ctx.func);
+            Lowering::new(
+                FunctionBuilder::new(&mut ctx.func, &mut builder_ctx),
+                func,
+                analysis,
+                program.constants,
+                Callees {
+                    functions: &functions,
+                    float_rem,
+                },
+                program.callee_name,
+                program.traps_overflow,
+            )
+            .lower();
+            module
+                .define_function(body, &mut ctx)
+                .map_err(|e| format!("{}: {:?}", func.name, e))?;
+            module.clear_context(&mut ctx);
+
+            ctx.func.signature = module
+                .declarations()
+                .get_function_decl(entry)
+                .signature
+                .clone();
+            ctx.func.name = UserFuncName::user(0, entry.as_u32());
+            let body_ref = module.declare_func_in_func(body, &mut ctx.func);
+            emit_entry(
+                FunctionBuilder::new(&mut ctx.func, &mut builder_ctx),
+                body_ref,
+                &analysis.signature,
+            );
+            module
+                .define_function(entry, &mut ctx)
+                .map_err(|e| format!("{}: {:?}", func.name, e))?;
+            module.clear_context(&mut ctx);
+        }
+        module.finalize_definitions().map_err(|e| e.to_string())?;
+
+        let functions = analyses
+            .iter()
+            .map(|(func, analysis)| {
+                let (_, entry) = ids[func.name.as_str()];
+                let code = module.get_finalized_function(entry);
+                // SAFETY: `entry` was defined with the `Entry` signature above
+                let entry = unsafe { std::mem::transmute::<*const u8, Entry>(code) };
+                let function = NativeFunction {
+                    signature: analysis.signature.clone(),
+                    entry,
+                };
+                (func.name.clone(), function)
+            })
+            .collect();
+        Ok(NativeCode {
+            module: Some(module),
+            functions,
+        })
+    }
+}
+
+impl Drop for NativeCode {
+    fn drop(&mut self) {
+        if let Some(module) = self.module.take() {
+            // SAFETY: the entries die with `self`, nothing else points into the code
+            unsafe { module.free_memory() };
+        }
+    }
+}
+
+/// Emit the `extern "C"` entry: unpack the arguments, call the body, store the result
+fn emit_entry(
+    mut builder: FunctionBuilder<'_>,
+    body: cranelift_codegen::ir::FuncRef,
+    signature: &Signature,
+) {
+    let block = builder.create_block();
+    builder.append_block_params_for_function_params(block);
+    builder.switch_to_block(block);
+    builder.seal_block(block);
+    let [status, depth, args, ret] = builder.block_params(block).try_into().expect("4 params");
+
+    let mut values = vec![status, depth];
+    for (i, ty) in signature.params.iter().enumerate() {
+        let offset = (i * 8) as i32;
+        let value = match ty {
+            Ty::Float => builder
+                .ins()
+                .load(types::F64, MemFlags::trusted(), args, offset),
+            _ => {
+                let raw = builder
+                    .ins()
+                    .load(types::I64, MemFlags::trusted(), args, offset);
+                match ty {
+                    Ty::Bool => builder.ins().ireduce(types::I8, raw),
+                    _ => raw,
+                }
+            }
+        };
+        values.push(value);
+    }
+    let call = builder.ins().call(body, &values);
+    let result = builder.inst_results(call)[0];
+    let result = match signature.ret {
+        Ty::Bool => builder.ins().uextend(types::I64, result),
+        _ => result,
+    };
+    builder.ins().store(MemFlags::trusted(), result, ret, 0);
+    builder.ins().return_(&[]);
+    builder.finalize();
+}
+
+/// `Float % Float`, which Cranelift has no instruction for
+extern "C" fn float_rem(
+    l: f64,
+    r: f64,
+) -> f64 {
+    l % r
+}
@@ -0,0 +1,160 @@
+//! 原生后端测试
+//!
+//! 测试覆盖内容：
+//! - 递归整数函数编译为机器码，结果与解释器一致
+//! - 调试模式下整数溢出、除零退回解释器，发布模式下溢出回绕
+//! - 不支持的函数及调用它们的函数不被编译
+//! - 参数类型不符时不调用机器码
+
+use super::{NativeCode, Outcome, Program};
+use crate::backends::common::RuntimeValue;
+use crate::backends::Executor;
+use crate::frontend::{CompileConfig, Compiler};
+use crate::middle::bytecode::{BytecodeModule, ConstValue, FunctionRef};
+use crate::middle::passes::codegen::CodegenContext;
+
+const SOURCE: &str = r#"
+fib: (n: Int) -> Int = {
+    if n < 2 {
+        return n
+    }
+    return fib(n - 1) + fib(n - 2)
+}
+
+mul: (a: Int, b: Int) -> Int = {
+    return a * b
+}
+
+div: (a: Int, b: Int) -> Int = {
+    return a / b
+}
+
+name: () -> String = {
+    return "fib"
+}
+
+greet: (n: Int) -> Int = {
+    print(name())
+    return n
+}
+
+main: () -> Void = {
+    print(fib(10))
+    print(mul(2, 3))
+    print(div(6, 3))
+    print(greet(1))
+}
+"#;
+
+fn bytecode() -> BytecodeModule {
+    let module = Compiler::with_config(CompileConfig::new().with_tree_shaking(false))
+        .compile("native.yx", SOURCE)
+        .expect("source should compile");
+    let mut ctx = CodegenContext::new(module);
+    BytecodeModule::from(ctx.generate().expect("codegen should succeed"))
+}
+
+fn compile(
+    module: &BytecodeModule,
+    traps_overflow: bool,
+) -> NativeCode {
+    let callee_name = |func: &FunctionRef| match func {
+        FunctionRef::Static { name, .. } => name.clone(),
+        FunctionRef::Index(idx) => match module.constants.get(*idx as usize) {
+            Some(ConstValue::String(s)) => s.clone(),
+            _ => format!("fn_{}", idx),
+        },
+    };
+    NativeCode::compile(&Program {
+        functions: module.functions.iter().collect(),
+        constants: &module.constants,
+        callee_name: &callee_name,
+        traps_overflow,
+    })
+}
+
+#[test]
+fn test_recursive_function_matches_interpreter() {
+    let module = bytecode();
+    let code = compile(&module, true);
+    assert!(code.is_compiled("fib"));
+
+    let mut interpreter = crate::backends::interpreter::Interpreter::new();
+    interpreter
+        .execute_module(&module)
+        .expect("module should run");
+    let fib = module
+        .functions
+        .iter()
+        .find(|f| f.name == "fib")
+        .expect("fib compiled");
+    let expected = interpreter
+        .execute_function(fib, &[RuntimeValue::Int(20)])
+        .expect("fib should run");
+
+    match code.call("fib", &[RuntimeValue::Int(20)], 1000) {
+        Some(Outcome::Returned(value)) => assert_eq!(value, expected),
+        other => panic!("expected a return value, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_overflow_deopts_when_trapping() {
+    let module = bytecode();
+    let args = [RuntimeValue::Int(i64::MAX), RuntimeValue::Int(2)];
+
+    let trapping = compile(&module, true);
+    assert!(matches!(
+        trapping.call("mul", &args, 10),
+        Some(Outcome::Deopt)
+    ));
+
+    let wrapping = compile(&module, false);
+    match wrapping.call("mul", &args, 10) {
+        Some(Outcome::Returned(value)) => {
+            assert_eq!(value, RuntimeValue::Int(i64::MAX.wrapping_mul(2)))
+        }
+        other => panic!("expected a return value, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_division_by_zero_deopts() {
+    let module = bytecode();
+    let code = compile(&module, false);
+    let zero = [RuntimeValue::Int(1), RuntimeValue::Int(0)];
+    assert!(matches!(code.call("div", &zero, 10), Some(Outcome::Deopt)));
+    let ok = [RuntimeValue::Int(7), RuntimeValue::Int(2)];
+    assert!(matches!(
+        code.call("div", &ok, 10),
+        Some(Outcome::Returned(RuntimeValue::Int(3)))
+    ));
+}
+
+#[test]
+fn test_recursion_depth_deopts() {
+    let module = bytecode();
+    let code = compile(&module, true);
+    assert!(matches!(
+        code.call("fib", &[RuntimeValue::Int(20)], 5),
+        Some(Outcome::Deopt)
+    ));
+}
+
+#[test]
+fn test_unsupported_functions_not_compiled() {
+    let module = bytecode();
+    let code = compile(&module, true);
+    assert!(!code.is_compiled("name"));
+    assert!(!code.is_compiled("greet"));
+    assert!(!code.is_compiled("main"));
+    assert!(code.call("greet", &[RuntimeValue::Int(1)], 10).is_none());
+}
+
+#[test]
+fn test_argument_mismatch_not_called() {
+    let module = bytecode();
+    let code = compile(&module, true);
+    assert!(code.call("fib", &[RuntimeValue::Float(1.0)], 10).is_none());
+    assert!(code.call("fib", &[], 10).is_none());
+}
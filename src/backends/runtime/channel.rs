@@ -0,0 +1,197 @@
+//! Channels for passing values between tasks.
+//!
+//! A [`Channel`] is a multi-producer queue, either unbounded or bounded to a
+//! fixed capacity. Blocking operations (`recv` on an empty channel, `send` on
+//! a full bounded one) wait differently depending on the caller:
+//! - inside a green thread (Full runtime) the task parks, and its worker keeps
+//!   running other tasks until a peer wakes it;
+//! - on any other thread (the main thread, Standard workers) the OS thread
+//!   blocks on a condition variable.
+
+use std::collections::VecDeque;
+use std::fmt;
+
+use parking_lot::{Condvar, Mutex, MutexGuard};
+
+use super::green::{self, Unparker};
+
+/// The channel was closed: nothing more can be sent, and a receiver gets this
+/// once every queued value has been taken.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChannelClosed;
+
+impl fmt::Display for ChannelClosed {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        f.write_str("channel is closed")
+    }
+}
+
+impl std::error::Error for ChannelClosed {}
+
+/// A multi-producer channel shared by reference (usually behind an `Arc`).
+pub struct Channel<T> {
+    state: Mutex<ChannelState<T>>,
+    /// Signalled for callers blocked outside green threads.
+    changed: Condvar,
+}
+
+struct ChannelState<T> {
+    queue: VecDeque<T>,
+    /// `None` for unbounded channels.
+    capacity: Option<usize>,
+    closed: bool,
+    /// Green threads parked in `recv`.
+    receivers: VecDeque<Unparker>,
+    /// Green threads parked in `send` on a full channel.
+    senders: VecDeque<Unparker>,
+}
+
+impl<T> ChannelState<T> {
+    fn is_full(&self) -> bool {
+        self.capacity.is_some_and(|cap| self.queue.len() >= cap)
+    }
+}
+
+impl<T> Channel<T> {
+    /// A channel that never makes senders wait.
+    pub fn unbounded() -> Self {
+        Self::with_capacity(None)
+    }
+
+    /// A channel holding at most `capacity` values (at least 1); further sends
+    /// wait for a receiver.
+    pub fn bounded(capacity: usize) -> Self {
+        Self::with_capacity(Some(capacity.max(1)))
+    }
+
+    fn with_capacity(capacity: Option<usize>) -> Self {
+        Self {
+            state: Mutex::new(ChannelState {
+                queue: VecDeque::new(),
+                capacity,
+                closed: false,
+                receivers: VecDeque::new(),
+                senders: VecDeque::new(),
+            }),
+            changed: Condvar::new(),
+        }
+    }
+
+    /// Queue `value`, waiting while a bounded channel is full.
+    pub fn send(
+        &self,
+        value: T,
+    ) -> Result<(), ChannelClosed> {
+        let mut state = self.state.lock();
+        loop {
+            if state.closed {
+                return Err(ChannelClosed);
+            }
+            if !state.is_full() {
+                break;
+            }
+            state = self.wait(state, |s| &mut s.senders);
+        }
+        state.queue.push_back(value);
+        if let Some(receiver) = state.receivers.pop_front() {
+            receiver.unpark();
+        }
+        drop(state);
+        self.changed.notify_all();
+        Ok(())
+    }
+
+    /// Take the oldest value, waiting while the channel is empty.
+    pub fn recv(&self) -> Result<T, ChannelClosed> {
+        let mut state = self.state.lock();
+        loop {
+            if let Some(value) = state.queue.pop_front() {
+                if let Some(sender) = state.senders.pop_front() {
+                    sender.unpark();
+                }
+                drop(state);
+                self.changed.notify_all();
+                return Ok(value);
+            }
+            if state.closed {
+                return Err(ChannelClosed);
+            }
+            state = self.wait(state, |s| &mut s.receivers);
+        }
+    }
+
+    /// Take the oldest value if there is one, without waiting.
+    pub fn try_recv(&self) -> Option<T> {
+        let mut state = self.state.lock();
+        let value = state.queue.pop_front()?;
+        if let Some(sender) = state.senders.pop_front() {
+            sender.unpark();
+        }
+        drop(state);
+        self.changed.notify_all();
+        Some(value)
+    }
+
+    /// Refuse further sends and wake everyone waiting. Values already queued
+    /// can still be received.
+    pub fn close(&self) {
+        let mut state = self.state.lock();
+        state.closed = true;
+        let state = &mut *state;
+        for waiter in state.receivers.drain(..).chain(state.senders.drain(..)) {
+            waiter.unpark();
+        }
+        self.changed.notify_all();
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.state.lock().closed
+    }
+
+    /// Number of queued values.
+    pub fn len(&self) -> usize {
+        self.state.lock().queue.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Wait until the channel changes. Green threads register in `waiters`
+    /// and park; other threads block on the condition variable.
+    fn wait<'a>(
+        &'a self,
+        mut state: MutexGuard<'a, ChannelState<T>>,
+        waiters: impl FnOnce(&mut ChannelState<T>) -> &mut VecDeque<Unparker>,
+    ) -> MutexGuard<'a, ChannelState<T>> {
+        match green::current() {
+            Some(task) => {
+                waiters(&mut state).push_back(task.unparker());
+                drop(state);
+                task.park();
+                self.state.lock()
+            }
+            None => {
+                self.changed.wait(&mut state);
+                state
+            }
+        }
+    }
+}
+
+impl<T> fmt::Debug for Channel<T> {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        let state = self.state.lock();
+        f.debug_struct("Channel")
+            .field("len", &state.queue.len())
+            .field("capacity", &state.capacity)
+            .field("closed", &state.closed)
+            .finish()
+    }
+}
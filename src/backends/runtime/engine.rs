@@ -147,6 +147,9 @@ pub struct RuntimeStats {
     pub cancelled_count: usize,
     pub total_spawned: usize,
     pub avg_execution_time: Duration,
+    /// Tasks resumed by a worker other than the one that started them
+    /// (work-stealing Full runtime only).
+    pub stolen_count: usize,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -502,6 +505,13 @@ impl LocalRuntime {
         None
     }
 
+    /// Returns true if some task can be popped with [`next_ready`](Self::next_ready).
+    pub fn has_ready(&self) -> bool {
+        self.ready
+            .iter()
+            .any(|id| self.tasks.get(id).is_some_and(TaskNode::is_runnable))
+    }
+
     /// Pop the next runnable task id that is required to finish `target`.
     ///
     /// This prioritizes the main dependency chain and prevents unrelated "island"
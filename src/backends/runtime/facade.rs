@@ -9,6 +9,9 @@ use std::thread::JoinHandle;
 use std::time::Duration;
 use crate::util::time_compat::Instant;
 
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::Arc;
+
 #[cfg(not(target_arch = "wasm32"))]
 use crossbeam::channel::{Receiver, Sender};
 
@@ -17,6 +20,8 @@ use crate::backends::common::value::TaskId;
 use super::engine::{
     sv, LocalRuntime, RuntimeError, RuntimeStats, TaskMeta, TaskOutcome, TaskPoll, TaskResult,
 };
+#[cfg(not(target_arch = "wasm32"))]
+use super::green::{GreenRuntime, Shared as GreenShared};
 
 /// Runtime mode (three-tier per RFC-008).
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -25,7 +30,7 @@ pub enum RuntimeMode {
     Embedded,
     /// Single-thread DAG scheduling.
     Standard,
-    /// Multi-thread execution + (optional) work-stealing.
+    /// M:N green threads on worker threads + (optional) work-stealing.
     Full,
 }
 
@@ -49,6 +54,17 @@ impl Default for RuntimeConfig {
     }
 }
 
+impl RuntimeConfig {
+    /// Full runtime on `threads` worker threads that steal each other's tasks.
+    pub fn threads(threads: usize) -> Self {
+        Self {
+            mode: RuntimeMode::Full,
+            workers: threads.max(1),
+            work_stealing: true,
+        }
+    }
+}
+
 /// A generic runnable task for Standard/Full runtimes.
 ///
 /// The `SpawnHandle` parameter allows nested spawning from within tasks.
@@ -98,7 +114,15 @@ enum WorkerMessage {
 ///
 /// Tasks can use this to spawn child tasks that become part of the runtime's DAG.
 pub struct SpawnHandle {
-    tx: Sender<WorkerMessage>,
+    target: SpawnTarget,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+enum SpawnTarget {
+    /// Standard workers ask the driving thread to spawn.
+    Channel(Sender<WorkerMessage>),
+    /// Green threads spawn straight into the shared graph.
+    Green(Arc<GreenShared>),
 }
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -108,14 +132,17 @@ impl SpawnHandle {
         meta: TaskMeta,
         task: TaskFn,
     ) -> Result<TaskId, RuntimeError> {
+        let tx = match &self.target {
+            SpawnTarget::Channel(tx) => tx,
+            SpawnTarget::Green(shared) => return shared.spawn(meta, task),
+        };
         let (respond_tx, respond_rx) = crossbeam::channel::bounded(1);
-        self.tx
-            .send(WorkerMessage::SpawnRequest {
-                meta,
-                task,
-                respond: respond_tx,
-            })
-            .map_err(|_| RuntimeError::DeadlockOrCycle(TaskId(0)))?;
+        tx.send(WorkerMessage::SpawnRequest {
+            meta,
+            task,
+            respond: respond_tx,
+        })
+        .map_err(|_| RuntimeError::DeadlockOrCycle(TaskId(0)))?;
         respond_rx
             .recv()
             .map_err(|_| RuntimeError::DeadlockOrCycle(TaskId(0)))
@@ -123,7 +150,19 @@ impl SpawnHandle {
 
     pub fn noop() -> Self {
         let (tx, _rx) = crossbeam::channel::unbounded();
-        Self { tx }
+        Self::channel(tx)
+    }
+
+    fn channel(tx: Sender<WorkerMessage>) -> Self {
+        Self {
+            target: SpawnTarget::Channel(tx),
+        }
+    }
+
+    pub(super) fn green(shared: Arc<GreenShared>) -> Self {
+        Self {
+            target: SpawnTarget::Green(shared),
+        }
     }
 }
 
@@ -146,7 +185,7 @@ pub struct Runtime {
 enum RuntimeInner {
     Embedded(EmbeddedRuntime),
     #[cfg(not(target_arch = "wasm32"))]
-    Standard(StandardRuntime),
+    Standard(Box<StandardRuntime>),
     #[cfg(not(target_arch = "wasm32"))]
     Full(FullRuntime),
 }
@@ -160,7 +199,7 @@ impl Runtime {
                 if config.workers == 0 {
                     return Err(RuntimeFacadeError::InvalidConfig("workers must be >= 1"));
                 }
-                RuntimeInner::Standard(StandardRuntime::new(config.workers)?)
+                RuntimeInner::Standard(Box::new(StandardRuntime::new(config.workers)?))
             }
             #[cfg(not(target_arch = "wasm32"))]
             RuntimeMode::Full => {
@@ -231,7 +270,7 @@ impl Runtime {
             #[cfg(not(target_arch = "wasm32"))]
             RuntimeInner::Standard(rt) => rt.outcome(task_id).cloned(),
             #[cfg(not(target_arch = "wasm32"))]
-            RuntimeInner::Full(rt) => rt.outcome(task_id).cloned(),
+            RuntimeInner::Full(rt) => rt.outcome(task_id),
         }
     }
 
@@ -401,6 +440,9 @@ struct StandardRuntime {
     msg_rx: Receiver<WorkerMessage>,
     threads: Vec<JoinHandle<()>>,
     workers: usize,
+    /// Tasks sent to the pool whose completion has not been received yet;
+    /// kept across `drive_until` calls, which may return while siblings still run.
+    in_flight: usize,
 }
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -418,6 +460,7 @@ impl StandardRuntime {
             msg_rx,
             threads,
             workers,
+            in_flight: 0,
         })
     }
 
@@ -482,8 +525,6 @@ impl StandardRuntime {
         &mut self,
         target: Option<TaskId>,
     ) -> Result<(), RuntimeError> {
-        let mut in_flight = 0usize;
-
         loop {
             if let Some(t) = target {
                 if self.graph.is_complete(t) {
@@ -493,7 +534,7 @@ impl StandardRuntime {
             }
 
             // Dispatch ready tasks to the thread pool.
-            while in_flight < self.workers {
+            while self.in_flight < self.workers {
                 let Some(next) = (match target {
                     Some(t) => self
                         .graph
@@ -536,9 +577,7 @@ impl StandardRuntime {
                     }
                 };
 
-                let spawn_handle = SpawnHandle {
-                    tx: self.msg_tx.clone(),
-                };
+                let spawn_handle = SpawnHandle::channel(self.msg_tx.clone());
                 self.work_tx
                     .send(WorkItem {
                         id: next,
@@ -546,10 +585,10 @@ impl StandardRuntime {
                         spawn_handle,
                     })
                     .map_err(|_| RuntimeError::DeadlockOrCycle(next))?;
-                in_flight += 1;
+                self.in_flight += 1;
             }
 
-            if in_flight == 0 {
+            if self.in_flight == 0 {
                 if let Some(t) = target {
                     if !self.graph.is_complete(t) {
                         return Err(RuntimeError::DeadlockOrCycle(t));
@@ -571,7 +610,7 @@ impl StandardRuntime {
                     result,
                     exec_time,
                 } => {
-                    in_flight = in_flight.saturating_sub(1);
+                    self.in_flight = self.in_flight.saturating_sub(1);
                     match result {
                         Ok(v) => self.graph.complete(id, TaskOutcome::Ok(v), exec_time)?,
                         Err(e) => self.graph.complete(id, TaskOutcome::Err(e), exec_time)?,
@@ -637,23 +676,22 @@ impl Drop for StandardRuntime {
 }
 
 // ============================================================================
-// Full Runtime (delegates to StandardRuntime) — not available in wasm
+// Full Runtime (M:N green threads) — not available in wasm
 // ============================================================================
 
 #[cfg(not(target_arch = "wasm32"))]
 struct FullRuntime {
-    standard: StandardRuntime,
-    // TODO: WorkStealer for load balancing
+    green: GreenRuntime,
 }
 
 #[cfg(not(target_arch = "wasm32"))]
 impl FullRuntime {
     fn new(
         workers: usize,
-        _work_stealing: bool,
+        work_stealing: bool,
     ) -> Result<Self, RuntimeFacadeError> {
         Ok(Self {
-            standard: StandardRuntime::new(workers)?,
+            green: GreenRuntime::new(workers, work_stealing),
         })
     }
 
@@ -662,47 +700,60 @@ impl FullRuntime {
         meta: TaskMeta,
         task: TaskFn,
     ) -> Result<TaskId, RuntimeError> {
-        self.standard.spawn(meta, task)
+        self.green.spawn(meta, task)
     }
 
     fn cancel(
         &mut self,
         task_id: TaskId,
     ) -> Result<(), RuntimeError> {
-        self.standard.cancel(task_id)
+        self.green.cancel(task_id)
     }
 
     fn outcome(
         &self,
         task_id: TaskId,
-    ) -> Option<&TaskOutcome> {
-        self.standard.outcome(task_id)
+    ) -> Option<TaskOutcome> {
+        self.green.outcome(task_id)
     }
 
     fn is_complete(
         &self,
         task_id: TaskId,
     ) -> bool {
-        self.standard.is_complete(task_id)
+        self.green.is_complete(task_id)
     }
 
     fn stats(&self) -> RuntimeStats {
-        self.standard.stats()
+        self.green.stats()
     }
 
     fn drive_until(
         &mut self,
         target: Option<TaskId>,
     ) -> Result<(), RuntimeError> {
-        self.standard.drive_until(target)
+        self.green.drive_until(target)
     }
 
+    /// A cooperative task becomes a green thread that yields on `Pending`.
     fn spawn_coop(
         &mut self,
         meta: TaskMeta,
-        task: CoopTaskFn,
+        mut task: CoopTaskFn,
     ) -> Result<TaskId, RuntimeError> {
-        self.standard.spawn_coop(meta, task)
+        self.green.spawn(
+            meta,
+            Box::new(move |_handle| loop {
+                match task(true) {
+                    TaskPoll::Ready(result) => return result,
+                    TaskPoll::Pending => {
+                        if let Some(green) = super::green::current() {
+                            green.yield_now();
+                        }
+                    }
+                }
+            }),
+        )
     }
 }
 
@@ -0,0 +1,648 @@
+//! Green threads (M:N) backing the Full runtime.
+//!
+//! Every task runs as a stackful coroutine with its own stack, and N worker
+//! threads multiplex any number of them. A task can suspend at any call depth:
+//! - [`GreenTask::yield_now`] re-queues it behind the other runnable tasks;
+//! - [`GreenTask::join`] parks it until another task finishes, and the worker
+//!   that finishes that task wakes it;
+//! - [`GreenTask::park`] sleeps until someone calls [`Unparker::unpark`], which
+//!   is how blocking primitives such as [`channel`](super::channel) and the
+//!   I/O [`reactor`](super::reactor) wait.
+//!
+//! Readiness (dependencies, resource serialization, cancellation) still comes
+//! from the [`LocalRuntime`] graph. By default a started task always resumes on
+//! the worker that first ran it, so its stack may hold thread-bound data. With
+//! work stealing, an idle worker takes suspended tasks queued on a busy one and
+//! resumes them itself; task bodies must then only keep `Send` data across a
+//! suspension (interpreter tasks copy their values off other threads' heaps,
+//! see [`transfer`](crate::backends::common::transfer)).
+
+use std::cell::Cell;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::marker::PhantomData;
+use std::panic::AssertUnwindSafe;
+use std::ptr::NonNull;
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use corosensei::stack::{DefaultStack, Stack};
+use corosensei::{Coroutine, CoroutineResult, Yielder};
+use parking_lot::{Condvar, Mutex};
+
+use crate::backends::common::value::TaskId;
+use crate::util::time_compat::Instant;
+
+use super::engine::{sv, LocalRuntime, RuntimeError, RuntimeStats, TaskMeta, TaskOutcome};
+use super::facade::{SpawnHandle, TaskFn};
+
+/// Stack reserved for each green thread. Pages are committed on first touch,
+/// so idle tasks only cost the part of the stack they actually used.
+pub const GREEN_STACK_SIZE: usize = 16 * 1024 * 1024;
+
+/// Why a task handed control back to its worker.
+enum Suspend {
+    /// Run again after the other runnable tasks.
+    Yield,
+    /// Resume once the given task has finished.
+    Join(TaskId),
+    /// Resume once unparked.
+    Park,
+}
+
+type GreenCoroutine = Coroutine<(), Suspend, super::engine::TaskResult, DefaultStack>;
+
+/// A suspended coroutine waiting in the shared state.
+struct Parked(GreenCoroutine);
+
+// SAFETY: without work stealing a parked coroutine is only ever resumed by the
+// worker that started it. With work stealing, task bodies only keep `Send`
+// data across suspensions (see the module docs), and the thread-local context
+// is reset around every suspension.
+unsafe impl Send for Parked {}
+
+thread_local! {
+    /// Context of the green thread running on this worker, if any.
+    static CURRENT: Cell<Option<NonNull<TaskContext>>> = const { Cell::new(None) };
+}
+
+// A stolen task resumes on another thread: the thread-local must be looked up
+// afresh after every suspension, never through an address cached before it.
+#[inline(never)]
+fn current_context() -> Option<NonNull<TaskContext>> {
+    CURRENT.with(|c| c.get())
+}
+
+#[inline(never)]
+fn set_current_context(ctx: Option<NonNull<TaskContext>>) {
+    CURRENT.with(|c| c.set(ctx));
+}
+
+/// Per-task state living on the task's own stack.
+struct TaskContext {
+    id: TaskId,
+    yielder: NonNull<Yielder<(), Suspend>>,
+    shared: Arc<Shared>,
+    /// Lowest usable address of the task's stack.
+    stack_limit: usize,
+}
+
+impl TaskContext {
+    fn suspend(
+        &self,
+        reason: Suspend,
+    ) {
+        set_current_context(None);
+        // SAFETY: the yielder belongs to the coroutine running this code and
+        // outlives every suspension point inside it.
+        unsafe { self.yielder.as_ref() }.suspend(reason);
+        set_current_context(Some(NonNull::from(self)));
+    }
+}
+
+/// Clears [`CURRENT`] when a task's body returns or unwinds.
+struct CurrentGuard;
+
+impl Drop for CurrentGuard {
+    fn drop(&mut self) {
+        set_current_context(None);
+    }
+}
+
+/// The green thread running on the current OS thread.
+///
+/// Returns `None` outside green tasks (the main thread, Standard workers).
+pub fn current() -> Option<GreenTask> {
+    current_context().map(|ctx| GreenTask {
+        // SAFETY: CURRENT only holds the context of the coroutine running right now.
+        id: unsafe { ctx.as_ref() }.id,
+        _not_send: PhantomData,
+    })
+}
+
+/// Handle to the running green thread. Only usable on the thread that
+/// returned it, while that task is still running.
+#[derive(Debug, Clone, Copy)]
+pub struct GreenTask {
+    id: TaskId,
+    _not_send: PhantomData<*const ()>,
+}
+
+impl GreenTask {
+    fn with_context<R>(
+        &self,
+        f: impl FnOnce(&TaskContext) -> R,
+    ) -> R {
+        let ctx = current_context().expect("GreenTask used outside its green thread");
+        // SAFETY: the context lives on the running coroutine's stack.
+        f(unsafe { ctx.as_ref() })
+    }
+
+    /// Id of this task in the runtime graph.
+    pub fn id(&self) -> TaskId {
+        self.id
+    }
+
+    /// Let the other runnable tasks run before continuing.
+    pub fn yield_now(&self) {
+        self.with_context(|ctx| ctx.suspend(Suspend::Yield));
+    }
+
+    /// Park until `task` finishes and return its outcome.
+    pub fn join(
+        &self,
+        task: TaskId,
+    ) -> Result<TaskOutcome, RuntimeError> {
+        self.with_context(|ctx| {
+            if task == ctx.id {
+                return Err(RuntimeError::DeadlockOrCycle(task));
+            }
+            {
+                let state = ctx.shared.state.lock();
+                if let Some(outcome) = state.graph.outcome(task) {
+                    return Ok(outcome.clone());
+                }
+                if state.graph.meta(task).is_none() {
+                    return Err(RuntimeError::TaskNotFound(task));
+                }
+            }
+            ctx.suspend(Suspend::Join(task));
+            ctx.shared
+                .state
+                .lock()
+                .graph
+                .outcome(task)
+                .cloned()
+                .ok_or(RuntimeError::DeadlockOrCycle(task))
+        })
+    }
+
+    /// Sleep until an [`Unparker`] of this task is used. Wake-ups are not lost:
+    /// an unpark that arrives before the task parks makes this return at once.
+    /// Callers must re-check their condition, as in a condition-variable loop.
+    pub fn park(&self) {
+        self.with_context(|ctx| ctx.suspend(Suspend::Park));
+    }
+
+    /// [`park`](Self::park) for an event the reactor or a helper thread is
+    /// going to deliver. Until then the runtime does not report the task as
+    /// deadlocked, even when no other task can run.
+    pub fn park_for_io(&self) {
+        self.with_context(|ctx| {
+            ctx.shared.state.lock().io_waiting += 1;
+            ctx.suspend(Suspend::Park);
+            ctx.shared.state.lock().io_waiting -= 1;
+        });
+    }
+
+    /// A handle other tasks or threads can use to wake this task from [`park`](Self::park).
+    pub fn unparker(&self) -> Unparker {
+        self.with_context(|ctx| Unparker {
+            id: ctx.id,
+            shared: Arc::clone(&ctx.shared),
+        })
+    }
+
+    /// Spawn a sibling task into the same runtime.
+    pub fn spawn(
+        &self,
+        meta: TaskMeta,
+        task: TaskFn,
+    ) -> Result<TaskId, RuntimeError> {
+        self.with_context(|ctx| ctx.shared.spawn(meta, task))
+    }
+
+    /// Outcome of a finished task, `None` while it is still pending.
+    pub fn outcome(
+        &self,
+        task: TaskId,
+    ) -> Option<TaskOutcome> {
+        self.with_context(|ctx| ctx.shared.state.lock().graph.outcome(task).cloned())
+    }
+
+    /// Bytes left on this task's stack. Green stacks have a fixed size, so deep
+    /// recursion must check this instead of growing the stack.
+    pub fn remaining_stack(&self) -> usize {
+        let marker = 0u8;
+        let sp = std::ptr::addr_of!(marker) as usize;
+        self.with_context(|ctx| sp.saturating_sub(ctx.stack_limit))
+    }
+}
+
+/// Wakes a task parked with [`GreenTask::park`]. Can be sent to any thread.
+#[derive(Clone)]
+pub struct Unparker {
+    id: TaskId,
+    shared: Arc<Shared>,
+}
+
+impl Unparker {
+    pub fn unpark(&self) {
+        let mut state = self.shared.state.lock();
+        if state.sleeping.remove(&self.id) {
+            let owner = state.owner[&self.id];
+            state.resumable[owner].push_back(self.id);
+            drop(state);
+            self.shared.work.notify_all();
+        } else if !state.graph.is_complete(self.id) {
+            // Still running: the task has not suspended yet
+            state.unparked.insert(self.id);
+        }
+    }
+}
+
+impl std::fmt::Debug for Unparker {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        f.debug_struct("Unparker").field("id", &self.id).finish()
+    }
+}
+
+/// State shared by the runtime handle, its workers and the running tasks.
+pub(super) struct Shared {
+    state: Mutex<State>,
+    /// Signalled when workers may have something to run.
+    work: Condvar,
+    /// Signalled when a task finished or the runtime went idle.
+    done: Condvar,
+}
+
+struct State {
+    graph: LocalRuntime,
+    /// Bodies of tasks that have not started yet.
+    bodies: HashMap<TaskId, TaskFn>,
+    /// Suspended coroutines of started tasks.
+    parked: HashMap<TaskId, Parked>,
+    /// Worker that owns each started task's coroutine.
+    owner: HashMap<TaskId, usize>,
+    /// Started tasks ready to resume, per worker.
+    resumable: Vec<VecDeque<TaskId>>,
+    /// Idle workers may take resumable tasks from other workers.
+    stealing: bool,
+    /// Tasks resumed by a worker other than their owner.
+    steals: usize,
+    /// Parked tasks, keyed by the task they are waiting for.
+    waiters: HashMap<TaskId, Vec<TaskId>>,
+    /// Tasks suspended in [`GreenTask::park`].
+    sleeping: HashSet<TaskId>,
+    /// Tasks unparked while still running; their next park returns at once.
+    unparked: HashSet<TaskId>,
+    /// Tasks in [`GreenTask::park_for_io`]: something outside the runtime
+    /// will wake them, so the runtime is not idle while any wait.
+    io_waiting: usize,
+    /// Tasks currently running on a worker.
+    executing: usize,
+    shutdown: bool,
+}
+
+enum Job {
+    Start(TaskId, TaskFn),
+    Resume(TaskId, Parked),
+}
+
+impl State {
+    fn next_job(
+        &mut self,
+        worker: usize,
+        work: &Condvar,
+    ) -> Option<Job> {
+        if let Some(id) = self.resumable[worker].pop_front() {
+            return self.resume(id, worker);
+        }
+        while let Some(id) = self.graph.next_ready() {
+            if self.graph.mark_running(id).is_err() {
+                continue;
+            }
+            if let Some(body) = self.bodies.remove(&id) {
+                self.owner.insert(id, worker);
+                return Some(Job::Start(id, body));
+            }
+            match self.owner.get(&id) {
+                // A yielded task: resume it here or hand it back to its owner
+                Some(&owner) if owner == worker || self.stealing => {
+                    return self.resume(id, worker);
+                }
+                Some(&owner) => {
+                    self.resumable[owner].push_back(id);
+                    work.notify_all();
+                }
+                None => {
+                    let _ = self.graph.complete(
+                        id,
+                        TaskOutcome::Err(sv("task payload missing")),
+                        Duration::ZERO,
+                    );
+                }
+            }
+        }
+        if self.stealing {
+            return self.steal(worker);
+        }
+        None
+    }
+
+    /// Take the oldest resumable task of the worker with the longest queue.
+    fn steal(
+        &mut self,
+        worker: usize,
+    ) -> Option<Job> {
+        let victim = (0..self.resumable.len())
+            .filter(|&other| other != worker)
+            .max_by_key(|&other| self.resumable[other].len())?;
+        let id = self.resumable[victim].pop_front()?;
+        self.resume(id, worker)
+    }
+
+    fn resume(
+        &mut self,
+        id: TaskId,
+        worker: usize,
+    ) -> Option<Job> {
+        let co = self.parked.remove(&id)?;
+        if let Some(owner) = self.owner.insert(id, worker) {
+            if owner != worker {
+                self.steals += 1;
+            }
+        }
+        Some(Job::Resume(id, co))
+    }
+
+    /// Move tasks parked on finished (or cancelled) tasks back to their workers.
+    fn wake_waiters(&mut self) {
+        let finished: Vec<TaskId> = self
+            .waiters
+            .keys()
+            .copied()
+            .filter(|id| self.graph.is_complete(*id))
+            .collect();
+        for id in finished {
+            for waiter in self.waiters.remove(&id).unwrap_or_default() {
+                let owner = self.owner[&waiter];
+                self.resumable[owner].push_back(waiter);
+            }
+        }
+        self.bodies.retain(|id, _| !self.graph.is_complete(*id));
+    }
+
+    /// Nothing is running and nothing can start: only parked tasks remain.
+    fn is_idle(&self) -> bool {
+        self.executing == 0
+            && self.io_waiting == 0
+            && !self.graph.has_ready()
+            && self.resumable.iter().all(VecDeque::is_empty)
+    }
+}
+
+impl Shared {
+    pub(super) fn spawn(
+        &self,
+        meta: TaskMeta,
+        task: TaskFn,
+    ) -> Result<TaskId, RuntimeError> {
+        let mut state = self.state.lock();
+        let id = state.graph.spawn(meta)?;
+        if state.graph.is_complete(id) {
+            // Pre-cancelled due to failed/cancelled deps.
+            state.wake_waiters();
+            self.done.notify_all();
+        } else {
+            state.bodies.insert(id, task);
+            self.work.notify_all();
+        }
+        Ok(id)
+    }
+}
+
+/// M:N green-thread runtime.
+pub(super) struct GreenRuntime {
+    shared: Arc<Shared>,
+    threads: Vec<JoinHandle<()>>,
+}
+
+impl GreenRuntime {
+    pub(super) fn new(
+        workers: usize,
+        work_stealing: bool,
+    ) -> Self {
+        let shared = Arc::new(Shared {
+            state: Mutex::new(State {
+                graph: LocalRuntime::new(),
+                bodies: HashMap::new(),
+                parked: HashMap::new(),
+                owner: HashMap::new(),
+                resumable: vec![VecDeque::new(); workers],
+                stealing: work_stealing,
+                steals: 0,
+                waiters: HashMap::new(),
+                sleeping: HashSet::new(),
+                unparked: HashSet::new(),
+                io_waiting: 0,
+                executing: 0,
+                shutdown: false,
+            }),
+            work: Condvar::new(),
+            done: Condvar::new(),
+        });
+        let threads = (0..workers)
+            .map(|worker| {
+                let shared = Arc::clone(&shared);
+                std::thread::spawn(move || worker_loop(shared, worker))
+            })
+            .collect();
+        Self { shared, threads }
+    }
+
+    pub(super) fn spawn(
+        &mut self,
+        meta: TaskMeta,
+        task: TaskFn,
+    ) -> Result<TaskId, RuntimeError> {
+        self.shared.spawn(meta, task)
+    }
+
+    pub(super) fn cancel(
+        &mut self,
+        task_id: TaskId,
+    ) -> Result<(), RuntimeError> {
+        let mut state = self.shared.state.lock();
+        state.graph.cancel(task_id)?;
+        state.wake_waiters();
+        self.shared.work.notify_all();
+        self.shared.done.notify_all();
+        Ok(())
+    }
+
+    pub(super) fn outcome(
+        &self,
+        task_id: TaskId,
+    ) -> Option<TaskOutcome> {
+        self.shared.state.lock().graph.outcome(task_id).cloned()
+    }
+
+    pub(super) fn is_complete(
+        &self,
+        task_id: TaskId,
+    ) -> bool {
+        self.shared.state.lock().graph.is_complete(task_id)
+    }
+
+    pub(super) fn stats(&self) -> RuntimeStats {
+        let state = self.shared.state.lock();
+        RuntimeStats {
+            stolen_count: state.steals,
+            ..state.graph.stats()
+        }
+    }
+
+    /// Block the calling (non-green) thread until `target` finishes, or until
+    /// no task can make progress if `target` is `None`.
+    pub(super) fn drive_until(
+        &mut self,
+        target: Option<TaskId>,
+    ) -> Result<(), RuntimeError> {
+        let mut state = self.shared.state.lock();
+        loop {
+            match target {
+                Some(t) if state.graph.is_complete(t) => return Ok(()),
+                Some(t) if state.is_idle() => return Err(RuntimeError::DeadlockOrCycle(t)),
+                None if state.is_idle() => return Ok(()),
+                _ => self.shared.done.wait(&mut state),
+            }
+        }
+    }
+}
+
+impl Drop for GreenRuntime {
+    fn drop(&mut self) {
+        self.shared.state.lock().shutdown = true;
+        self.shared.work.notify_all();
+        for t in self.threads.drain(..) {
+            let _ = t.join();
+        }
+        // Dropping a suspended coroutine unwinds its stack, which must not
+        // happen while holding the lock it may take
+        let parked = std::mem::take(&mut self.shared.state.lock().parked);
+        drop(parked);
+    }
+}
+
+fn worker_loop(
+    shared: Arc<Shared>,
+    worker: usize,
+) {
+    loop {
+        let job = {
+            let mut state = shared.state.lock();
+            loop {
+                if state.shutdown {
+                    return;
+                }
+                if let Some(job) = state.next_job(worker, &shared.work) {
+                    state.executing += 1;
+                    break job;
+                }
+                shared.work.wait(&mut state);
+            }
+        };
+
+        let (id, mut co) = match job {
+            Job::Start(id, body) => match start_coroutine(&shared, id, body) {
+                Ok(co) => (id, co),
+                Err(e) => {
+                    let mut state = shared.state.lock();
+                    state.executing -= 1;
+                    let outcome =
+                        TaskOutcome::Err(sv(format!("failed to allocate task stack: {e}")));
+                    let _ = state.graph.complete(id, outcome, Duration::ZERO);
+                    state.wake_waiters();
+                    shared.work.notify_all();
+                    shared.done.notify_all();
+                    continue;
+                }
+            },
+            Job::Resume(id, Parked(co)) => (id, co),
+        };
+
+        let start = Instant::now();
+        let resumed = std::panic::catch_unwind(AssertUnwindSafe(|| co.resume(())));
+        let exec_time = start.elapsed();
+
+        let mut state = shared.state.lock();
+        state.executing -= 1;
+        match resumed {
+            Ok(CoroutineResult::Yield(Suspend::Yield)) => {
+                state.parked.insert(id, Parked(co));
+                let _ = state.graph.yield_now(id, exec_time);
+            }
+            Ok(CoroutineResult::Yield(Suspend::Join(target))) => {
+                state.parked.insert(id, Parked(co));
+                // The target may have finished after the task checked it
+                if state.graph.is_complete(target) {
+                    state.resumable[worker].push_back(id);
+                } else {
+                    state.waiters.entry(target).or_default().push(id);
+                }
+            }
+            Ok(CoroutineResult::Yield(Suspend::Park)) => {
+                state.parked.insert(id, Parked(co));
+                if state.unparked.remove(&id) {
+                    state.resumable[worker].push_back(id);
+                } else {
+                    state.sleeping.insert(id);
+                }
+            }
+            Ok(CoroutineResult::Return(result)) => {
+                let outcome = match result {
+                    Ok(v) => TaskOutcome::Ok(v),
+                    Err(e) => TaskOutcome::Err(e),
+                };
+                let _ = state.graph.complete(id, outcome, exec_time);
+            }
+            Err(panic) => {
+                let message = panic
+                    .downcast_ref::<&str>()
+                    .map(|s| s.to_string())
+                    .or_else(|| panic.downcast_ref::<String>().cloned())
+                    .unwrap_or_else(|| "task panicked".to_string());
+                let _ = state
+                    .graph
+                    .complete(id, TaskOutcome::Err(sv(message)), exec_time);
+            }
+        }
+        state.wake_waiters();
+        if state.graph.is_complete(id) {
+            state.owner.remove(&id);
+            state.unparked.remove(&id);
+        }
+        drop(state);
+        shared.work.notify_all();
+        shared.done.notify_all();
+    }
+}
+
+fn start_coroutine(
+    shared: &Arc<Shared>,
+    id: TaskId,
+    body: TaskFn,
+) -> std::io::Result<GreenCoroutine> {
+    let stack = DefaultStack::new(GREEN_STACK_SIZE)?;
+    let stack_limit = stack.limit().get();
+    let shared = Arc::clone(shared);
+    Ok(Coroutine::with_stack(
+        stack,
+        move |yielder: &Yielder<(), Suspend>, ()| {
+            let handle = SpawnHandle::green(Arc::clone(&shared));
+            let ctx = TaskContext {
+                id,
+                yielder: NonNull::from(yielder),
+                shared,
+                stack_limit,
+            };
+            set_current_context(Some(NonNull::from(&ctx)));
+            let _guard = CurrentGuard;
+            body(&handle)
+        },
+    ))
+}
@@ -3,14 +3,20 @@
 //! Per RFC-008: Three-tier runtime architecture
 //! - Embedded Runtime: Immediate executor, no DAG, sync execution
 //! - Standard Runtime: DAG scheduler, lazy evaluation, async/concurrent
-//! - Full Runtime: + WorkStealer, parallel optimization
+//! - Full Runtime: M:N green threads on a worker pool (+ WorkStealer)
 //!
 //! Per RFC-009: Memory management uses Arc (ref keyword in YaoXiang)
 //! - ❌ No GC - reference counting via Arc
 //! - Task boundary is the leak boundary
 
+#[cfg(not(target_arch = "wasm32"))]
+pub mod channel;
 pub mod engine;
 pub mod facade;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod green;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod reactor;
 pub mod task;
 
 #[cfg(test)]
@@ -20,6 +26,8 @@ pub use engine::TaskPoll;
 pub use facade::{Runtime, RuntimeConfig, RuntimeFacadeError, RuntimeMode, SpawnHandle, TaskFn};
 #[cfg(not(target_arch = "wasm32"))]
 pub use facade::CoopTaskFn;
+#[cfg(not(target_arch = "wasm32"))]
+pub use green::{GreenTask, Unparker};
 
 pub use task::{
     Task, TaskId, TaskContext, TaskPriority, TaskConfig, TaskSpawner, TaskState, Scheduler,
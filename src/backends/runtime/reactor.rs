@@ -0,0 +1,329 @@
+//! I/O reactor: lets blocking I/O suspend only the task that performs it.
+//!
+//! One background thread polls the OS (epoll / kqueue / IOCP through `mio`)
+//! for every registered socket. An operation that would block waits for the
+//! socket's next readiness event instead:
+//! - inside a green thread (Full runtime) the task parks, and its worker goes
+//!   on running other tasks until the reactor wakes it;
+//! - on any other thread the OS thread blocks until the event arrives.
+//!
+//! Regular files are always "ready" to the OS, so file operations cannot be
+//! polled; [`blocking`] runs them on a bounded pool of helper threads instead
+//! and parks the calling task until they finish.
+
+use std::collections::{HashMap, VecDeque};
+use std::io;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, OnceLock, Weak};
+use std::time::Duration;
+
+use mio::event::Source;
+use mio::{Events, Interest, Poll, Registry, Token};
+use parking_lot::{Condvar, Mutex};
+
+use super::green::{self, Unparker};
+
+/// The process-wide reactor and its polling thread.
+pub struct Reactor {
+    registry: Registry,
+    /// Readiness slots of the live registrations, by token.
+    slots: Mutex<HashMap<Token, Weak<Slot>>>,
+    next_token: AtomicUsize,
+}
+
+/// Readiness of one registered source.
+struct Slot {
+    state: Mutex<SlotState>,
+    /// Signalled for callers waiting outside green threads.
+    changed: Condvar,
+}
+
+#[derive(Default)]
+struct SlotState {
+    readable: bool,
+    writable: bool,
+    /// Green threads waiting for the next event.
+    waiters: Vec<Unparker>,
+}
+
+/// Which readiness an operation waits for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Read,
+    Write,
+}
+
+impl SlotState {
+    fn ready(
+        &mut self,
+        direction: Direction,
+    ) -> &mut bool {
+        match direction {
+            Direction::Read => &mut self.readable,
+            Direction::Write => &mut self.writable,
+        }
+    }
+}
+
+static REACTOR: OnceLock<Reactor> = OnceLock::new();
+
+impl Reactor {
+    /// The reactor, started on first use.
+    pub fn global() -> io::Result<&'static Reactor> {
+        if let Some(reactor) = REACTOR.get() {
+            return Ok(reactor);
+        }
+        let poll = Poll::new()?;
+        let reactor = Reactor {
+            registry: poll.registry().try_clone()?,
+            slots: Mutex::new(HashMap::new()),
+            next_token: AtomicUsize::new(0),
+        };
+        // Two threads may race to start it; only the winner's poller runs
+        if REACTOR.set(reactor).is_ok() {
+            std::thread::Builder::new()
+                .name("yaoxiang-reactor".to_string())
+                .spawn(move || poll_loop(poll))?;
+        }
+        Ok(REACTOR.get().expect("reactor initialized above"))
+    }
+
+    /// Watch `source` for readability and writability. The returned
+    /// registration stops tracking it when dropped.
+    pub fn register<S: Source + ?Sized>(
+        &'static self,
+        source: &mut S,
+    ) -> io::Result<Registration> {
+        let token = Token(self.next_token.fetch_add(1, Ordering::Relaxed));
+        let slot = Arc::new(Slot {
+            state: Mutex::new(SlotState::default()),
+            changed: Condvar::new(),
+        });
+        self.slots.lock().insert(token, Arc::downgrade(&slot));
+        if let Err(e) =
+            self.registry
+                .register(source, token, Interest::READABLE | Interest::WRITABLE)
+        {
+            self.slots.lock().remove(&token);
+            return Err(e);
+        }
+        Ok(Registration {
+            reactor: self,
+            token,
+            slot,
+        })
+    }
+
+    fn dispatch(
+        &self,
+        events: &Events,
+    ) {
+        let slots = self.slots.lock();
+        for event in events {
+            let Some(slot) = slots.get(&event.token()).and_then(Weak::upgrade) else {
+                continue;
+            };
+            let mut state = slot.state.lock();
+            // Errors and hang-ups wake both sides: the retried operation reports them
+            if event.is_readable() || event.is_read_closed() || event.is_error() {
+                state.readable = true;
+            }
+            if event.is_writable() || event.is_write_closed() || event.is_error() {
+                state.writable = true;
+            }
+            for waiter in state.waiters.drain(..) {
+                waiter.unpark();
+            }
+            drop(state);
+            slot.changed.notify_all();
+        }
+    }
+}
+
+fn poll_loop(mut poll: Poll) {
+    let mut events = Events::with_capacity(256);
+    loop {
+        if let Err(e) = poll.poll(&mut events, None) {
+            if e.kind() == io::ErrorKind::Interrupted {
+                continue;
+            }
+            tracing::error!("I/O reactor stopped: {e}");
+            return;
+        }
+        if let Some(reactor) = REACTOR.get() {
+            reactor.dispatch(&events);
+        }
+    }
+}
+
+/// A source registered with the [`Reactor`].
+pub struct Registration {
+    reactor: &'static Reactor,
+    token: Token,
+    slot: Arc<Slot>,
+}
+
+impl Registration {
+    /// Run a non-blocking operation, waiting for readiness in `direction`
+    /// each time it reports [`io::ErrorKind::WouldBlock`].
+    pub fn io<R>(
+        &self,
+        direction: Direction,
+        mut op: impl FnMut() -> io::Result<R>,
+    ) -> io::Result<R> {
+        loop {
+            // Clear before trying: an event arriving after this point is kept
+            *self.slot.state.lock().ready(direction) = false;
+            match op() {
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => self.wait(direction),
+                result => return result,
+            }
+        }
+    }
+
+    fn wait(
+        &self,
+        direction: Direction,
+    ) {
+        let mut state = self.slot.state.lock();
+        while !*state.ready(direction) {
+            match green::current() {
+                Some(task) => {
+                    state.waiters.push(task.unparker());
+                    drop(state);
+                    task.park_for_io();
+                    state = self.slot.state.lock();
+                }
+                None => self.slot.changed.wait(&mut state),
+            }
+        }
+    }
+}
+
+impl Drop for Registration {
+    fn drop(&mut self) {
+        // The OS drops the source's interest when its handle is closed
+        self.reactor.slots.lock().remove(&self.token);
+    }
+}
+
+impl std::fmt::Debug for Registration {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        f.debug_struct("Registration")
+            .field("token", &self.token)
+            .finish()
+    }
+}
+
+/// Most helper threads the blocking pool runs at once. Further calls queue
+/// until one of them is free.
+pub const MAX_BLOCKING_THREADS: usize = 16;
+
+/// How long an idle helper thread waits for work before exiting.
+const BLOCKING_KEEP_ALIVE: Duration = Duration::from_secs(10);
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// Helper threads shared by every [`blocking`] call.
+struct BlockingPool {
+    state: Mutex<PoolState>,
+    /// Signalled when a job is queued.
+    queued: Condvar,
+}
+
+struct PoolState {
+    jobs: VecDeque<Job>,
+    threads: usize,
+    idle: usize,
+}
+
+static BLOCKING_POOL: BlockingPool = BlockingPool {
+    state: Mutex::new(PoolState {
+        jobs: VecDeque::new(),
+        threads: 0,
+        idle: 0,
+    }),
+    queued: Condvar::new(),
+};
+
+impl BlockingPool {
+    /// Queue `job`, starting a helper thread if none is idle and the pool is
+    /// below its limit.
+    fn submit(
+        &'static self,
+        job: Job,
+    ) -> io::Result<()> {
+        let mut state = self.state.lock();
+        if state.idle == 0 && state.threads < MAX_BLOCKING_THREADS {
+            let spawned = std::thread::Builder::new()
+                .name("yaoxiang-blocking".to_string())
+                .spawn(move || self.work());
+            match spawned {
+                Ok(_) => state.threads += 1,
+                // Queued work still runs once a live helper is free
+                Err(e) if state.threads == 0 => return Err(e),
+                Err(_) => {}
+            }
+        }
+        state.jobs.push_back(job);
+        drop(state);
+        self.queued.notify_one();
+        Ok(())
+    }
+
+    fn work(&self) {
+        let mut state = self.state.lock();
+        loop {
+            if let Some(job) = state.jobs.pop_front() {
+                drop(state);
+                job();
+                state = self.state.lock();
+                continue;
+            }
+            state.idle += 1;
+            let timed_out = self
+                .queued
+                .wait_for(&mut state, BLOCKING_KEEP_ALIVE)
+                .timed_out();
+            state.idle -= 1;
+            if timed_out && state.jobs.is_empty() {
+                state.threads -= 1;
+                return;
+            }
+        }
+    }
+}
+
+/// Run `f`, which may block, without blocking the worker of the calling green
+/// thread: it runs on the shared blocking pool while the task is parked.
+/// Outside green threads `f` simply runs in place.
+///
+/// Fails when no helper thread can be started, or when `f` panics.
+pub fn blocking<R, F>(f: F) -> io::Result<R>
+where
+    F: FnOnce() -> R + Send + 'static,
+    R: Send + 'static,
+{
+    let Some(task) = green::current() else {
+        return Ok(f());
+    };
+    let done = Arc::new(Mutex::new(None));
+    let unparker = task.unparker();
+    let result = Arc::clone(&done);
+    BLOCKING_POOL.submit(Box::new(move || {
+        let value = panic::catch_unwind(AssertUnwindSafe(f))
+            .map_err(|_| io::Error::other("blocking operation panicked"));
+        *result.lock() = Some(value);
+        unparker.unpark();
+    }))?;
+    loop {
+        if let Some(value) = done.lock().take() {
+            return value;
+        }
+        task.park_for_io();
+    }
+}
@@ -0,0 +1,196 @@
+//! 通道测试
+//!
+//! 测试覆盖内容：
+//! - 只有一个工作线程时，recv 挂起绿色线程，发送方仍能运行
+//! - 有界通道满时 send 挂起，按发送顺序交付
+//! - 关闭后拒绝发送，排空剩余值后 recv 报告关闭
+//! - 普通线程之间收发时阻塞 OS 线程
+//! - 所有任务都在等待通道时报告死锁
+
+use crate::backends::runtime::channel::{Channel, ChannelClosed};
+use crate::backends::runtime::engine::{sv, RuntimeError, TaskMeta, TaskOutcome};
+use crate::backends::runtime::facade::{Runtime, RuntimeConfig, RuntimeFacadeError, RuntimeMode};
+use std::sync::{Arc, Mutex};
+
+fn full_runtime(workers: usize) -> Runtime {
+    Runtime::new(RuntimeConfig {
+        mode: RuntimeMode::Full,
+        workers,
+        work_stealing: false,
+    })
+    .unwrap()
+}
+
+fn ok_value(outcome: Option<TaskOutcome>) -> i64 {
+    match outcome {
+        Some(TaskOutcome::Ok(v)) => *v.downcast_ref::<i64>().expect("i64 payload"),
+        other => panic!("expected Ok outcome, got {other:?}"),
+    }
+}
+
+#[test]
+fn recv_parks_the_task_instead_of_blocking_the_worker() {
+    // 接收方先运行；recv 若阻塞线程，唯一的工作线程上发送方永远没有机会运行
+    let mut rt = full_runtime(1);
+    let ch = Arc::new(Channel::<i64>::unbounded());
+
+    let receiver = {
+        let ch = Arc::clone(&ch);
+        rt.spawn(
+            TaskMeta::default(),
+            Box::new(move |_h| Ok(sv(ch.recv().unwrap() + ch.recv().unwrap()))),
+        )
+        .unwrap()
+    };
+    let sender = {
+        let ch = Arc::clone(&ch);
+        rt.spawn(
+            TaskMeta::default(),
+            Box::new(move |_h| {
+                ch.send(20).unwrap();
+                ch.send(22).unwrap();
+                Ok(sv(0i64))
+            }),
+        )
+        .unwrap()
+    };
+
+    rt.drive_until(Some(receiver)).unwrap();
+    rt.drive_until(Some(sender)).unwrap();
+    assert_eq!(ok_value(rt.outcome(receiver)), 42);
+}
+
+#[test]
+fn full_bounded_channel_parks_the_sender() {
+    let mut rt = full_runtime(1);
+    let ch = Arc::new(Channel::<i64>::bounded(1));
+    let log = Arc::new(Mutex::new(Vec::new()));
+
+    let sender = {
+        let (ch, log) = (Arc::clone(&ch), Arc::clone(&log));
+        rt.spawn(
+            TaskMeta::default(),
+            Box::new(move |_h| {
+                for i in 0..3 {
+                    ch.send(i).unwrap();
+                    log.lock().unwrap().push(format!("sent{i}"));
+                }
+                Ok(sv(0i64))
+            }),
+        )
+        .unwrap()
+    };
+    let receiver = {
+        let (ch, log) = (Arc::clone(&ch), Arc::clone(&log));
+        rt.spawn(
+            TaskMeta::default(),
+            Box::new(move |_h| {
+                let mut total = 0;
+                for _ in 0..3 {
+                    let v = ch.recv().unwrap();
+                    log.lock().unwrap().push(format!("got{v}"));
+                    total = total * 10 + v;
+                }
+                Ok(sv(total))
+            }),
+        )
+        .unwrap()
+    };
+
+    rt.drive_until(Some(receiver)).unwrap();
+    rt.drive_until(Some(sender)).unwrap();
+    assert_eq!(ok_value(rt.outcome(receiver)), 12);
+    // 容量为 1：第二次发送必须等接收方取走第一个值
+    let log = log.lock().unwrap();
+    let got0 = log.iter().position(|e| e == "got0").unwrap();
+    let sent1 = log.iter().position(|e| e == "sent1").unwrap();
+    assert!(got0 < sent1, "sender should wait while full: {log:?}");
+}
+
+#[test]
+fn closed_channel_drains_then_reports_closed() {
+    let ch = Channel::unbounded();
+    ch.send(1).unwrap();
+    ch.close();
+
+    assert_eq!(ch.send(2), Err(ChannelClosed));
+    assert_eq!(ch.recv(), Ok(1));
+    assert_eq!(ch.recv(), Err(ChannelClosed));
+}
+
+#[test]
+fn close_wakes_a_parked_receiver() {
+    let mut rt = full_runtime(1);
+    let ch = Arc::new(Channel::<i64>::unbounded());
+
+    let receiver = {
+        let ch = Arc::clone(&ch);
+        rt.spawn(
+            TaskMeta::default(),
+            Box::new(move |_h| match ch.recv() {
+                Err(ChannelClosed) => Ok(sv(-1i64)),
+                Ok(v) => Ok(sv(v)),
+            }),
+        )
+        .unwrap()
+    };
+    let closer = {
+        let ch = Arc::clone(&ch);
+        rt.spawn(
+            TaskMeta::default(),
+            Box::new(move |_h| {
+                ch.close();
+                Ok(sv(0i64))
+            }),
+        )
+        .unwrap()
+    };
+
+    rt.drive_until(Some(closer)).unwrap();
+    rt.drive_until(Some(receiver)).unwrap();
+    assert_eq!(ok_value(rt.outcome(receiver)), -1);
+}
+
+#[test]
+fn plain_threads_block_until_a_value_arrives() {
+    let ch = Arc::new(Channel::bounded(1));
+    let producer = {
+        let ch = Arc::clone(&ch);
+        std::thread::spawn(move || {
+            for i in 0..100 {
+                ch.send(i).unwrap();
+            }
+            ch.close();
+        })
+    };
+
+    let mut received = Vec::new();
+    while let Ok(v) = ch.recv() {
+        received.push(v);
+    }
+    producer.join().unwrap();
+    assert_eq!(received, (0..100).collect::<Vec<_>>());
+}
+
+#[test]
+fn receiving_with_no_sender_reports_a_deadlock() {
+    let mut rt = full_runtime(1);
+    let ch = Arc::new(Channel::<i64>::unbounded());
+    let receiver = {
+        let ch = Arc::clone(&ch);
+        rt.spawn(
+            TaskMeta::default(),
+            Box::new(move |_h| Ok(sv(ch.recv().unwrap()))),
+        )
+        .unwrap()
+    };
+
+    assert!(matches!(
+        rt.drive_until(Some(receiver)),
+        Err(RuntimeFacadeError::Engine(RuntimeError::DeadlockOrCycle(id))) if id == receiver
+    ));
+    // 之后的发送仍能唤醒它
+    ch.send(7).unwrap();
+    rt.drive_until(Some(receiver)).unwrap();
+    assert_eq!(ok_value(rt.outcome(receiver)), 7);
+}
@@ -0,0 +1,320 @@
+//! 绿色线程测试
+//!
+//! 测试覆盖内容：
+//! - 任务让出执行权后与同一工作线程上的其他任务交替执行
+//! - 任务汇合未完成的任务时挂起，不占用工作线程
+//! - 任务数远多于工作线程时全部完成
+//! - 自我汇合报告死锁，任务 panic 不会拖垮工作线程
+//! - 协作式任务在 Full 运行时中以绿色线程运行
+//! - 开启工作窃取时，所属线程忙碌的任务由空闲线程恢复
+
+use crate::backends::runtime::engine::{sv, RuntimeError, TaskMeta, TaskOutcome, TaskPoll};
+use crate::backends::runtime::facade::{Runtime, RuntimeConfig, RuntimeMode};
+use crate::backends::runtime::green;
+use crate::backends::common::value::TaskId;
+use std::sync::{Arc, Mutex};
+
+fn full_runtime(workers: usize) -> Runtime {
+    Runtime::new(RuntimeConfig {
+        mode: RuntimeMode::Full,
+        workers,
+        work_stealing: false,
+    })
+    .unwrap()
+}
+
+fn ok_value(outcome: Option<TaskOutcome>) -> i64 {
+    match outcome {
+        Some(TaskOutcome::Ok(v)) => *v.downcast_ref::<i64>().expect("i64 payload"),
+        other => panic!("expected Ok outcome, got {other:?}"),
+    }
+}
+
+#[test]
+fn current_is_none_outside_green_threads() {
+    assert!(green::current().is_none());
+
+    let mut rt = full_runtime(1);
+    let id = rt
+        .spawn(
+            TaskMeta::default(),
+            Box::new(|_h| {
+                let task = green::current().expect("running on a green thread");
+                Ok(sv(task.id().0 as i64))
+            }),
+        )
+        .unwrap();
+    rt.drive_until(Some(id)).unwrap();
+    assert_eq!(ok_value(rt.outcome(id)), id.0 as i64);
+}
+
+#[test]
+fn yielding_tasks_interleave_on_one_worker() {
+    let mut rt = full_runtime(1);
+    let log = Arc::new(Mutex::new(Vec::new()));
+
+    let mut ids = Vec::new();
+    for name in ["a", "b"] {
+        let log = Arc::clone(&log);
+        let id = rt
+            .spawn(
+                TaskMeta::default(),
+                Box::new(move |_h| {
+                    let task = green::current().unwrap();
+                    for step in 0..3 {
+                        log.lock().unwrap().push(format!("{name}{step}"));
+                        task.yield_now();
+                    }
+                    Ok(sv(0i64))
+                }),
+            )
+            .unwrap();
+        ids.push(id);
+    }
+    rt.drive_until(None).unwrap();
+
+    assert_eq!(
+        *log.lock().unwrap(),
+        ["a0", "b0", "a1", "b1", "a2", "b2"],
+        "a yield should let the other task run"
+    );
+    for id in ids {
+        assert_eq!(ok_value(rt.outcome(id)), 0);
+    }
+}
+
+#[test]
+fn join_parks_the_waiter_instead_of_blocking_the_worker() {
+    // 只有一个工作线程：汇合若阻塞线程，子任务永远没有机会运行
+    let mut rt = full_runtime(1);
+    let parent = rt
+        .spawn(
+            TaskMeta::default(),
+            Box::new(|_h| {
+                let task = green::current().unwrap();
+                let child = task
+                    .spawn(TaskMeta::default(), Box::new(|_h| Ok(sv(20i64))))
+                    .unwrap();
+                assert!(task.outcome(child).is_none());
+                let outcome = task.join(child).unwrap();
+                let TaskOutcome::Ok(v) = outcome else {
+                    return Err(sv("child failed"));
+                };
+                Ok(sv(v.downcast_ref::<i64>().unwrap() + 1))
+            }),
+        )
+        .unwrap();
+    rt.drive_until(Some(parent)).unwrap();
+    assert_eq!(ok_value(rt.outcome(parent)), 21);
+}
+
+#[test]
+fn many_joining_tasks_share_few_workers() {
+    let mut rt = full_runtime(2);
+
+    // 每个任务先 spawn 下一个再汇合它，形成 200 层的汇合链
+    fn chain(n: i64) -> crate::backends::runtime::TaskFn {
+        Box::new(move |handle| {
+            if n == 0 {
+                return Ok(sv(0i64));
+            }
+            let next = handle.spawn(TaskMeta::default(), chain(n - 1)).unwrap();
+            match green::current().unwrap().join(next) {
+                Ok(TaskOutcome::Ok(v)) => Ok(sv(v.downcast_ref::<i64>().unwrap() + n)),
+                _ => Err(sv("link failed")),
+            }
+        })
+    }
+
+    let root = rt.spawn(TaskMeta::default(), chain(200)).unwrap();
+    rt.drive_until(Some(root)).unwrap();
+    assert_eq!(ok_value(rt.outcome(root)), 200 * 201 / 2);
+    assert_eq!(rt.stats().total_spawned, 201);
+}
+
+#[test]
+fn joining_itself_reports_a_deadlock() {
+    let mut rt = full_runtime(1);
+    let id = rt
+        .spawn(
+            TaskMeta::default(),
+            Box::new(|_h| {
+                let task = green::current().unwrap();
+                match task.join(task.id()) {
+                    Err(RuntimeError::DeadlockOrCycle(_)) => Ok(sv(1i64)),
+                    _ => Ok(sv(0i64)),
+                }
+            }),
+        )
+        .unwrap();
+    rt.drive_until(Some(id)).unwrap();
+    assert_eq!(ok_value(rt.outcome(id)), 1);
+}
+
+#[test]
+fn panicking_task_fails_and_the_worker_keeps_running() {
+    let mut rt = full_runtime(1);
+    let bad = rt
+        .spawn(TaskMeta::default(), Box::new(|_h| panic!("boom")))
+        .unwrap();
+    let good = rt
+        .spawn(TaskMeta::default(), Box::new(|_h| Ok(sv(7i64))))
+        .unwrap();
+    rt.drive_until(Some(good)).unwrap();
+
+    match rt.outcome(bad) {
+        Some(TaskOutcome::Err(e)) => assert_eq!(e.downcast_ref::<String>().unwrap(), "boom"),
+        other => panic!("expected Err outcome, got {other:?}"),
+    }
+    assert_eq!(ok_value(rt.outcome(good)), 7);
+}
+
+#[test]
+fn failed_dependency_wakes_a_parked_waiter() {
+    let mut rt = full_runtime(1);
+    // 先让出一次，等待方汇合时依赖链尚未结束
+    let failing = rt
+        .spawn(
+            TaskMeta::default(),
+            Box::new(|_h| {
+                green::current().unwrap().yield_now();
+                Err(sv("failed"))
+            }),
+        )
+        .unwrap();
+    let dependent = rt
+        .spawn(
+            TaskMeta {
+                deps: vec![failing],
+                ..TaskMeta::default()
+            },
+            Box::new(|_h| Ok(sv(0i64))),
+        )
+        .unwrap();
+    let waiter = rt
+        .spawn(
+            TaskMeta::default(),
+            Box::new(move |_h| match green::current().unwrap().join(dependent) {
+                Ok(TaskOutcome::Cancelled(_)) => Ok(sv(1i64)),
+                _ => Ok(sv(0i64)),
+            }),
+        )
+        .unwrap();
+    rt.drive_until(Some(waiter)).unwrap();
+    assert_eq!(ok_value(rt.outcome(waiter)), 1);
+}
+
+#[test]
+fn remaining_stack_shrinks_with_call_depth() {
+    fn probe(depth: usize) -> usize {
+        let padding = [0u8; 4096];
+        std::hint::black_box(&padding);
+        let remaining = green::current().unwrap().remaining_stack();
+        if depth == 0 {
+            remaining
+        } else {
+            probe(depth - 1)
+        }
+    }
+
+    let mut rt = full_runtime(1);
+    let id = rt
+        .spawn(
+            TaskMeta::default(),
+            Box::new(|_h| {
+                let shallow = probe(0);
+                let deep = probe(16);
+                Ok(sv(
+                    (shallow > deep && shallow <= green::GREEN_STACK_SIZE) as i64
+                ))
+            }),
+        )
+        .unwrap();
+    rt.drive_until(Some(id)).unwrap();
+    assert_eq!(ok_value(rt.outcome(id)), 1);
+}
+
+#[test]
+fn full_runtime_runs_coop_tasks_as_green_threads() {
+    let mut rt = full_runtime(1);
+    let log = Arc::new(Mutex::new(Vec::new()));
+
+    let mut last = TaskId(0);
+    for name in ["x", "y"] {
+        let log = Arc::clone(&log);
+        let mut step = 0;
+        last = rt
+            .spawn_coop(
+                TaskMeta::default(),
+                Box::new(move |_slice| {
+                    log.lock().unwrap().push(format!("{name}{step}"));
+                    step += 1;
+                    if step == 2 {
+                        TaskPoll::Ready(Ok(sv(0i64)))
+                    } else {
+                        TaskPoll::Pending
+                    }
+                }),
+            )
+            .unwrap();
+    }
+    rt.drive_until(Some(last)).unwrap();
+    rt.drive_until(None).unwrap();
+    assert_eq!(*log.lock().unwrap(), ["x0", "y0", "x1", "y1"]);
+}
+
+#[test]
+fn idle_worker_steals_a_task_whose_owner_is_busy() {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::time::{Duration, Instant};
+
+    let mut rt = Runtime::new(RuntimeConfig::threads(2)).unwrap();
+    let resumed = Arc::new(AtomicBool::new(false));
+    // 开始、阻塞任务、恢复时所在的线程
+    let threads = Arc::new(Mutex::new([None; 3]));
+
+    let (r, t) = (Arc::clone(&resumed), Arc::clone(&threads));
+    let task = rt
+        .spawn(
+            TaskMeta::default(),
+            Box::new(move |_h| {
+                let me = green::current().unwrap();
+                t.lock().unwrap()[0] = Some(std::thread::current().id());
+                // 占住某个工作线程，直到本任务恢复执行
+                let (r2, t2) = (Arc::clone(&r), Arc::clone(&t));
+                let blocker = me
+                    .spawn(
+                        TaskMeta::default(),
+                        Box::new(move |_h| {
+                            t2.lock().unwrap()[1] = Some(std::thread::current().id());
+                            let deadline = Instant::now() + Duration::from_secs(10);
+                            while !r2.load(Ordering::SeqCst) {
+                                if Instant::now() > deadline {
+                                    return Ok(sv(0i64));
+                                }
+                                std::hint::spin_loop();
+                            }
+                            Ok(sv(1i64))
+                        }),
+                    )
+                    .unwrap();
+                me.yield_now();
+                t.lock().unwrap()[2] = Some(std::thread::current().id());
+                r.store(true, Ordering::SeqCst);
+                match me.join(blocker) {
+                    Ok(TaskOutcome::Ok(v)) => Ok(sv(*v.downcast_ref::<i64>().unwrap())),
+                    _ => Err(sv("blocker failed")),
+                }
+            }),
+        )
+        .unwrap();
+    rt.drive_until(Some(task)).unwrap();
+
+    // 阻塞任务必须看到本任务恢复，而不是等到超时
+    assert_eq!(ok_value(rt.outcome(task)), 1);
+    let [started, blocker, resumed_on] = *threads.lock().unwrap();
+    if blocker == started {
+        assert_ne!(resumed_on, started, "the busy owner cannot have resumed it");
+        assert!(rt.stats().stolen_count >= 1);
+    }
+}
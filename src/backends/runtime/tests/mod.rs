@@ -1,8 +1,14 @@
 //! 运行时测试入口
 //!
-//! 包含 engine、facade 和 task 的测试模块。
+//! 包含 channel、engine、facade、green、reactor 和 task 的测试模块。
 
+#[cfg(not(target_arch = "wasm32"))]
+mod channel;
 mod engine;
 mod facade;
 mod facade_concurrent;
+#[cfg(not(target_arch = "wasm32"))]
+mod green;
+#[cfg(not(target_arch = "wasm32"))]
+mod reactor;
 mod task;
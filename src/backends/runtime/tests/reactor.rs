@@ -0,0 +1,214 @@
+//! I/O 反应器测试
+//!
+//! 测试覆盖内容：
+//! - 只有一个工作线程时，等待读取的任务挂起，写入方任务仍能运行
+//! - accept 在连接到来前挂起任务
+//! - 普通线程上的读取阻塞到数据到达
+//! - blocking 在辅助线程上运行，调用任务挂起期间工作线程继续运行其他任务
+//! - 大量 blocking 调用复用有上限的线程池；闭包 panic 时返回错误
+
+use std::collections::HashSet;
+use std::io::{Read, Write};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use mio::net::{TcpListener, TcpStream};
+
+use crate::backends::runtime::engine::{sv, TaskMeta, TaskOutcome};
+use crate::backends::runtime::facade::{Runtime, RuntimeConfig, RuntimeMode};
+use crate::backends::runtime::reactor::{self, Direction, Reactor, Registration};
+
+fn full_runtime(workers: usize) -> Runtime {
+    Runtime::new(RuntimeConfig {
+        mode: RuntimeMode::Full,
+        workers,
+        work_stealing: false,
+    })
+    .unwrap()
+}
+
+fn ok_value(outcome: Option<TaskOutcome>) -> i64 {
+    match outcome {
+        Some(TaskOutcome::Ok(v)) => *v.downcast_ref::<i64>().expect("i64 payload"),
+        other => panic!("expected Ok outcome, got {other:?}"),
+    }
+}
+
+fn listen() -> (TcpListener, Registration, SocketAddr) {
+    let mut listener = TcpListener::bind("127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = listener.local_addr().unwrap();
+    let registration = Reactor::global().unwrap().register(&mut listener).unwrap();
+    (listener, registration, addr)
+}
+
+fn connect(addr: SocketAddr) -> (TcpStream, Registration) {
+    let mut stream = TcpStream::connect(addr).unwrap();
+    let registration = Reactor::global().unwrap().register(&mut stream).unwrap();
+    (stream, registration)
+}
+
+#[test]
+fn waiting_reader_parks_instead_of_blocking_the_worker() {
+    // 读取方先运行；读取若阻塞线程，唯一的工作线程上写入方永远没有机会运行
+    let mut rt = full_runtime(1);
+    let (listener, listener_reg, addr) = listen();
+
+    let server = rt
+        .spawn(
+            TaskMeta::default(),
+            Box::new(move |_h| {
+                let (mut conn, _) = listener_reg
+                    .io(Direction::Read, || listener.accept())
+                    .unwrap();
+                let reg = Reactor::global().unwrap().register(&mut conn).unwrap();
+                let mut buf = [0u8; 16];
+                let n = reg.io(Direction::Read, || (&conn).read(&mut buf)).unwrap();
+                Ok(sv(String::from_utf8_lossy(&buf[..n])
+                    .parse::<i64>()
+                    .unwrap()))
+            }),
+        )
+        .unwrap();
+    let client = rt
+        .spawn(
+            TaskMeta::default(),
+            Box::new(move |_h| {
+                let (stream, reg) = connect(addr);
+                reg.io(Direction::Write, || (&stream).write(b"42")).unwrap();
+                // 保持连接直到服务端读完
+                reg.io(Direction::Read, || (&stream).read(&mut [0u8; 1]))
+                    .unwrap();
+                Ok(sv(0i64))
+            }),
+        )
+        .unwrap();
+
+    rt.drive_until(Some(server)).unwrap();
+    assert_eq!(ok_value(rt.outcome(server)), 42);
+    // 服务端结束时关闭连接，客户端读到 EOF 后结束
+    rt.drive_until(Some(client)).unwrap();
+}
+
+#[test]
+fn plain_thread_read_blocks_until_data_arrives() {
+    let (listener, listener_reg, addr) = listen();
+    let writer = std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(50));
+        let mut stream = std::net::TcpStream::connect(addr).unwrap();
+        stream.write_all(b"hello").unwrap();
+    });
+
+    let (mut conn, _) = listener_reg
+        .io(Direction::Read, || listener.accept())
+        .unwrap();
+    let reg = Reactor::global().unwrap().register(&mut conn).unwrap();
+    let mut received = Vec::new();
+    let mut buf = [0u8; 8];
+    loop {
+        let n = reg.io(Direction::Read, || (&conn).read(&mut buf)).unwrap();
+        if n == 0 {
+            break;
+        }
+        received.extend_from_slice(&buf[..n]);
+    }
+    writer.join().unwrap();
+    assert_eq!(received, b"hello");
+}
+
+#[test]
+fn blocking_work_does_not_hold_the_worker() {
+    let mut rt = full_runtime(1);
+    let log = Arc::new(Mutex::new(Vec::new()));
+
+    let slow = {
+        let log = Arc::clone(&log);
+        rt.spawn(
+            TaskMeta::default(),
+            Box::new(move |_h| {
+                let v = reactor::blocking(|| {
+                    std::thread::sleep(Duration::from_millis(100));
+                    7i64
+                })
+                .unwrap();
+                log.lock().unwrap().push("slow");
+                Ok(sv(v))
+            }),
+        )
+        .unwrap()
+    };
+    let fast = {
+        let log = Arc::clone(&log);
+        rt.spawn(
+            TaskMeta::default(),
+            Box::new(move |_h| {
+                log.lock().unwrap().push("fast");
+                Ok(sv(1i64))
+            }),
+        )
+        .unwrap()
+    };
+
+    rt.drive_until(Some(slow)).unwrap();
+    rt.drive_until(Some(fast)).unwrap();
+    assert_eq!(ok_value(rt.outcome(slow)), 7);
+    assert_eq!(*log.lock().unwrap(), ["fast", "slow"]);
+}
+
+#[test]
+fn blocking_runs_in_place_outside_green_threads() {
+    let here = std::thread::current().id();
+    assert!(reactor::blocking(move || std::thread::current().id() == here).unwrap());
+}
+
+#[test]
+fn blocking_reuses_a_bounded_set_of_threads() {
+    let mut rt = full_runtime(2);
+    let threads = Arc::new(Mutex::new(HashSet::new()));
+    let tasks: Vec<_> = (0..reactor::MAX_BLOCKING_THREADS * 4)
+        .map(|_| {
+            let threads = Arc::clone(&threads);
+            rt.spawn(
+                TaskMeta::default(),
+                Box::new(move |_h| {
+                    let id = reactor::blocking(|| {
+                        std::thread::sleep(Duration::from_millis(5));
+                        std::thread::current().id()
+                    })
+                    .unwrap();
+                    threads.lock().unwrap().insert(id);
+                    Ok(sv(1i64))
+                }),
+            )
+            .unwrap()
+        })
+        .collect();
+
+    for task in &tasks {
+        rt.drive_until(Some(*task)).unwrap();
+    }
+    for task in tasks {
+        assert_eq!(ok_value(rt.outcome(task)), 1);
+    }
+    let used = threads.lock().unwrap().len();
+    assert!(
+        (1..=reactor::MAX_BLOCKING_THREADS).contains(&used),
+        "{used} blocking threads"
+    );
+}
+
+#[test]
+fn blocking_panic_is_an_error() {
+    let mut rt = full_runtime(1);
+    let task = rt
+        .spawn(
+            TaskMeta::default(),
+            Box::new(|_h| {
+                let failed = reactor::blocking(|| -> i64 { panic!("boom") }).is_err();
+                Ok(sv(i64::from(failed)))
+            }),
+        )
+        .unwrap();
+    rt.drive_until(Some(task)).unwrap();
+    assert_eq!(ok_value(rt.outcome(task)), 1);
+}
@@ -0,0 +1,440 @@
+//! Eligibility and type inference
+//!
+//! A function is compiled only when every value it touches is an `Int`, `Float`
+//! or `Bool` whose type is known statically. Register allocation reuses a register
+//! for values of different types, so types are inferred per program point by a
+//! forward dataflow that follows the interpreter's own typing rules.
+
+use std::collections::HashMap;
+
+use crate::backends::interpreter::frames::MAX_LOCALS;
+use crate::middle::bytecode::{
+    BinaryOp, BytecodeFunction, BytecodeInstr, ConstValue, FunctionRef, Label, NumericTarget, Reg,
+    UnaryOp,
+};
+use crate::middle::core::ir::Type;
+
+/// Type of a value held in machine code
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Ty {
+    Int,
+    Float,
+    Bool,
+}
+
+impl Ty {
+    fn of_type(ty: &Type) -> Option<Ty> {
+        match NumericTarget::of_type(ty)? {
+            NumericTarget::Int(64) => Some(Ty::Int),
+            NumericTarget::Float(64) => Some(Ty::Float),
+            NumericTarget::Bool => Some(Ty::Bool),
+            _ => None,
+        }
+    }
+
+    fn of_const(value: &ConstValue) -> Option<Ty> {
+        match value {
+            ConstValue::Int(_) => Some(Ty::Int),
+            ConstValue::Float(_) => Some(Ty::Float),
+            ConstValue::Bool(_) => Some(Ty::Bool),
+            _ => None,
+        }
+    }
+
+    /// Dense index, used to number variables
+    #[cfg(feature = "native")]
+    pub fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// Parameter and return types of a compilable function
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Signature {
+    pub params: Vec<Ty>,
+    pub ret: Ty,
+}
+
+impl Signature {
+    /// Signature of `func`, if its declared types and shape allow compiling it
+    ///
+    /// Closures (functions with upvalues) and not yet loaded lazy stubs are excluded.
+    pub fn of(func: &BytecodeFunction) -> Option<Signature> {
+        if func.instructions.is_empty()
+            || func.upvalue_count > 0
+            || func.local_count > MAX_LOCALS
+            || func.params.len() > func.local_count.max(1)
+        {
+            return None;
+        }
+        Some(Signature {
+            params: func.params.iter().map(Ty::of_type).collect::<Option<_>>()?,
+            ret: Ty::of_type(&func.return_type)?,
+        })
+    }
+}
+
+/// What a register or local holds at a program point
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Slot {
+    /// Never written (the interpreter reads it as void)
+    Unset,
+    Known(Ty),
+    /// Different types on different paths
+    Mixed,
+}
+
+impl Slot {
+    fn join(
+        self,
+        other: Slot,
+    ) -> Slot {
+        if self == other {
+            self
+        } else {
+            Slot::Mixed
+        }
+    }
+}
+
+/// Result of analyzing a compilable function
+#[derive(Debug)]
+pub struct Analysis {
+    pub signature: Signature,
+    /// Number of register slots; local `i` is slot `registers + i`
+    pub registers: usize,
+    /// Slot types on entry to each instruction (`None` when unreachable)
+    pub states: Vec<Option<Vec<Slot>>>,
+    /// Names of the statically called functions (declared by the native backend)
+    #[cfg_attr(not(feature = "native"), allow(dead_code))]
+    pub callees: Vec<String>,
+}
+
+impl Analysis {
+    /// Type of register `reg` on entry to instruction `ip`
+    pub fn reg(
+        &self,
+        ip: usize,
+        reg: Reg,
+    ) -> Ty {
+        self.ty(ip, reg.0 as usize)
+    }
+
+    /// Type of local `local` on entry to instruction `ip`
+    #[cfg(feature = "native")]
+    pub fn local(
+        &self,
+        ip: usize,
+        local: u16,
+    ) -> Ty {
+        self.ty(ip, self.registers + local as usize)
+    }
+
+    fn ty(
+        &self,
+        ip: usize,
+        slot: usize,
+    ) -> Ty {
+        match self.states[ip].as_ref().map(|state| state[slot]) {
+            Some(Slot::Known(ty)) => ty,
+            other => unreachable!("slot {} at {} is {:?}", slot, ip, other),
+        }
+    }
+}
+
+/// Target of a jump at `ip` (`None` when it leaves the function)
+pub fn jump_target(
+    ip: usize,
+    label: Label,
+    len: usize,
+) -> Option<usize> {
+    let target = ip as i64 + label.0 as i32 as i64;
+    usize::try_from(target).ok().filter(|&target| target < len)
+}
+
+/// Successors of instruction `ip`; `None` stands for leaving the function
+/// other than by returning a value (a void return or running off the end)
+pub fn successors(
+    ip: usize,
+    instr: &BytecodeInstr,
+    len: usize,
+) -> Vec<Option<usize>> {
+    let next = Some(ip + 1).filter(|&next| next < len);
+    match instr {
+        BytecodeInstr::Jmp { target } => vec![jump_target(ip, *target, len)],
+        BytecodeInstr::JmpIf { target, .. } | BytecodeInstr::JmpIfNot { target, .. } => {
+            vec![jump_target(ip, *target, len), next]
+        }
+        BytecodeInstr::TableSwitch {
+            targets, default, ..
+        } => targets
+            .iter()
+            .chain(std::iter::once(default))
+            .map(|target| jump_target(ip, *target, len))
+            .collect(),
+        BytecodeInstr::ReturnValue { .. } | BytecodeInstr::TailCall { .. } => Vec::new(),
+        BytecodeInstr::Return => vec![None],
+        _ => vec![next],
+    }
+}
+
+/// Analyze `func`, assuming the functions in `signatures` are compiled too
+///
+/// Returns `None` when the function uses an instruction or a type machine
+/// code does not handle.
+pub fn analyze(
+    func: &BytecodeFunction,
+    signatures: &HashMap<String, Signature>,
+    constants: &[ConstValue],
+    callee_name: &dyn Fn(&FunctionRef) -> String,
+) -> Option<Analysis> {
+    let signature = signatures.get(&func.name)?.clone();
+    let (registers, locals) = slot_counts(func)?;
+
+    let len = func.instructions.len();
+    let mut entry = vec![Slot::Unset; registers + locals];
+    for (i, ty) in signature.params.iter().enumerate() {
+        entry[registers + i] = Slot::Known(*ty);
+    }
+    let mut flow = Flow {
+        registers,
+        signature: &signature,
+        signatures,
+        constants,
+        callee_name,
+        callees: Vec::new(),
+    };
+
+    let mut states: Vec<Option<Vec<Slot>>> = vec![None; len];
+    states[0] = Some(entry);
+    let mut worklist = vec![0];
+    while let Some(ip) = worklist.pop() {
+        let mut state = states[ip].clone()?;
+        let instr = &func.instructions[ip];
+        flow.step(instr, &mut state)?;
+        for next in successors(ip, instr, len).into_iter().flatten() {
+            let merged = match &states[next] {
+                None => state.clone(),
+                Some(old) => old.iter().zip(&state).map(|(a, b)| a.join(*b)).collect(),
+            };
+            if states[next].as_ref() != Some(&merged) {
+                states[next] = Some(merged);
+                worklist.push(next);
+            }
+        }
+    }
+
+    let mut callees = flow.callees;
+    callees.sort();
+    callees.dedup();
+    Some(Analysis {
+        signature,
+        registers,
+        states,
+        callees,
+    })
+}
+
+/// Register and local slot counts, or `None` for an unsupported instruction
+fn slot_counts(func: &BytecodeFunction) -> Option<(usize, usize)> {
+    let mut registers = 0;
+    let mut locals = func.local_count.max(1);
+    let mut reg = |reg: &Reg| registers = registers.max(reg.0 as usize + 1);
+    for instr in &func.instructions {
+        match instr {
+            BytecodeInstr::Nop
+            | BytecodeInstr::Yield
+            | BytecodeInstr::Return
+            | BytecodeInstr::Jmp { .. } => {}
+            BytecodeInstr::Drop { value: r }
+            | BytecodeInstr::Release { src: r }
+            | BytecodeInstr::ArcDrop { src: r }
+            | BytecodeInstr::ReturnValue { value: r }
+            | BytecodeInstr::JmpIf { cond: r, .. }
+            | BytecodeInstr::JmpIfNot { cond: r, .. }
+            | BytecodeInstr::TableSwitch { value: r, .. }
+            | BytecodeInstr::LoadConst { dst: r, .. } => reg(r),
+            BytecodeInstr::LoadLocal { dst: r, local_idx }
+            | BytecodeInstr::StoreLocal { src: r, local_idx }
+            | BytecodeInstr::LoadArg {
+                dst: r,
+                arg_idx: local_idx,
+            } => {
+                reg(r);
+                locals = locals.max(*local_idx as usize + 1);
+            }
+            BytecodeInstr::Mov { dst, src }
+            | BytecodeInstr::UnaryOp { dst, src, .. }
+            | BytecodeInstr::FloatNeg { dst, src }
+            | BytecodeInstr::IntToFloat { dst, src } => {
+                reg(dst);
+                reg(src);
+            }
+            BytecodeInstr::BinaryOp { dst, lhs, rhs, .. }
+            | BytecodeInstr::Compare { dst, lhs, rhs, .. }
+            | BytecodeInstr::FloatOp { dst, lhs, rhs, .. }
+            | BytecodeInstr::FloatCompare { dst, lhs, rhs, .. } => {
+                reg(dst);
+                reg(lhs);
+                reg(rhs);
+            }
+            BytecodeInstr::CallStatic { dst, args, .. } => {
+                dst.iter().chain(args).for_each(&mut reg);
+            }
+            BytecodeInstr::TailCall { args, .. } => args.iter().for_each(&mut reg),
+            _ => return None,
+        }
+    }
+    Some((registers, locals))
+}
+
+/// Transfer function of the dataflow
+struct Flow<'a> {
+    registers: usize,
+    signature: &'a Signature,
+    signatures: &'a HashMap<String, Signature>,
+    constants: &'a [ConstValue],
+    callee_name: &'a dyn Fn(&FunctionRef) -> String,
+    callees: Vec<String>,
+}
+
+impl Flow<'_> {
+    /// Apply `instr` to `state`, or `None` if it cannot be compiled
+    fn step(
+        &mut self,
+        instr: &BytecodeInstr,
+        state: &mut [Slot],
+    ) -> Option<()> {
+        let registers = self.registers;
+        let get = |state: &[Slot], reg: &Reg| match state[reg.0 as usize] {
+            Slot::Known(ty) => Some(ty),
+            _ => None,
+        };
+        let set = |state: &mut [Slot], reg: &Reg, ty: Ty| state[reg.0 as usize] = Slot::Known(ty);
+        let local = |index: u16| registers + index as usize;
+
+        match instr {
+            BytecodeInstr::Nop
+            | BytecodeInstr::Yield
+            | BytecodeInstr::Drop { .. }
+            | BytecodeInstr::Release { .. }
+            | BytecodeInstr::ArcDrop { .. }
+            | BytecodeInstr::Return
+            | BytecodeInstr::Jmp { .. } => {}
+            // The interpreter tests Bools and Ints (non-zero) for truth
+            BytecodeInstr::JmpIf { cond, .. } | BytecodeInstr::JmpIfNot { cond, .. } => {
+                (get(state, cond)? != Ty::Float).then_some(())?;
+            }
+            BytecodeInstr::TableSwitch { value, .. } => {
+                (get(state, value)? == Ty::Int).then_some(())?;
+            }
+            BytecodeInstr::ReturnValue { value } => {
+                (get(state, value)? == self.signature.ret).then_some(())?;
+            }
+            BytecodeInstr::Mov { dst, src } => {
+                let ty = get(state, src)?;
+                set(state, dst, ty);
+            }
+            BytecodeInstr::LoadConst { dst, const_idx } => {
+                let ty = Ty::of_const(self.constants.get(*const_idx as usize)?)?;
+                set(state, dst, ty);
+            }
+            BytecodeInstr::LoadLocal { dst, local_idx }
+            | BytecodeInstr::LoadArg {
+                dst,
+                arg_idx: local_idx,
+            } => match state[local(*local_idx)] {
+                Slot::Known(ty) => set(state, dst, ty),
+                _ => return None,
+            },
+            BytecodeInstr::StoreLocal { local_idx, src } => {
+                state[local(*local_idx)] = Slot::Known(get(state, src)?);
+            }
+            BytecodeInstr::BinaryOp { dst, lhs, rhs, op } => {
+                let ty = match (get(state, lhs)?, get(state, rhs)?) {
+                    (Ty::Int, Ty::Int) => Ty::Int,
+                    (Ty::Float, Ty::Float)
+                        if matches!(
+                            op,
+                            BinaryOp::Add
+                                | BinaryOp::Sub
+                                | BinaryOp::Mul
+                                | BinaryOp::Div
+                                | BinaryOp::Rem
+                        ) =>
+                    {
+                        Ty::Float
+                    }
+                    _ => return None,
+                };
+                set(state, dst, ty);
+            }
+            BytecodeInstr::UnaryOp { dst, src, op } => {
+                let ty = get(state, src)?;
+                match (op, ty) {
+                    (UnaryOp::Neg, Ty::Int | Ty::Float) | (UnaryOp::Not, Ty::Int | Ty::Bool) => {}
+                    _ => return None,
+                }
+                set(state, dst, ty);
+            }
+            BytecodeInstr::Compare { dst, lhs, rhs, .. } => {
+                match (get(state, lhs)?, get(state, rhs)?) {
+                    (Ty::Int, Ty::Int)
+                    | (Ty::Float, Ty::Float | Ty::Int)
+                    | (Ty::Int, Ty::Float) => {}
+                    _ => return None,
+                }
+                set(state, dst, Ty::Bool);
+            }
+            BytecodeInstr::FloatOp { dst, lhs, rhs, .. }
+            | BytecodeInstr::FloatCompare { dst, lhs, rhs, .. } => {
+                for reg in [lhs, rhs] {
+                    (get(state, reg)? != Ty::Bool).then_some(())?;
+                }
+                let ty = if matches!(instr, BytecodeInstr::FloatOp { .. }) {
+                    Ty::Float
+                } else {
+                    Ty::Bool
+                };
+                set(state, dst, ty);
+            }
+            BytecodeInstr::FloatNeg { dst, src } | BytecodeInstr::IntToFloat { dst, src } => {
+                (get(state, src)? != Ty::Bool).then_some(())?;
+                set(state, dst, Ty::Float);
+            }
+            BytecodeInstr::CallStatic { dst, func, args } => {
+                let ret = self.call(func, args, state)?;
+                if let Some(dst) = dst {
+                    set(state, dst, ret);
+                }
+            }
+            BytecodeInstr::TailCall { func, args } => {
+                (self.call(func, args, state)? == self.signature.ret).then_some(())?;
+            }
+            _ => return None,
+        }
+        Some(())
+    }
+
+    /// Check a static call against the callee's signature, returning its result type
+    fn call(
+        &mut self,
+        func: &FunctionRef,
+        args: &[Reg],
+        state: &[Slot],
+    ) -> Option<Ty> {
+        let name = (self.callee_name)(func);
+        let signature = self.signatures.get(&name)?;
+        let matches = signature.params.len() == args.len()
+            && args
+                .iter()
+                .zip(&signature.params)
+                .all(|(arg, ty)| state[arg.0 as usize] == Slot::Known(*ty));
+        if !matches {
+            return None;
+        }
+        let ret = signature.ret;
+        self.callees.push(name);
+        Some(ret)
+    }
+}
@@ -0,0 +1,614 @@
+//! Stencil emission
+//!
+//! Every instruction becomes a fixed sequence of stencils: load the operands
+//! from their slots, operate, store the result. Registers, locals and outgoing
+//! call arguments all live in eight-byte slots of the machine frame, so there is
+//! no register allocation; the analysis only picks the stencil variant (Int or
+//! Float, checked or wrapping). Whatever the interpreter would report an error
+//! for jumps to the function's deopt stub.
+
+use std::collections::HashMap;
+
+use super::analysis::{jump_target, successors, Analysis, Ty};
+use super::stencils::{self, Hole, Stencil};
+use super::Program;
+use crate::middle::bytecode::{
+    BinaryOp, BytecodeFunction, BytecodeInstr, CompareOp, ConstValue, Reg, UnaryOp,
+};
+
+/// Position in the code, bound once it is known
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Label(usize);
+
+/// Values for the holes of one stencil
+#[derive(Debug, Clone, Copy, Default)]
+struct Patch {
+    a: u32,
+    b: u32,
+    d: u32,
+    imm: u64,
+    target: Option<Label>,
+}
+
+impl Patch {
+    fn a(slot: u32) -> Self {
+        Self {
+            a: slot,
+            ..Self::default()
+        }
+    }
+
+    fn b(slot: u32) -> Self {
+        Self {
+            b: slot,
+            ..Self::default()
+        }
+    }
+
+    fn d(slot: u32) -> Self {
+        Self {
+            d: slot,
+            ..Self::default()
+        }
+    }
+
+    fn imm(imm: u64) -> Self {
+        Self {
+            imm,
+            ..Self::default()
+        }
+    }
+
+    fn to(target: Label) -> Self {
+        Self {
+            target: Some(target),
+            ..Self::default()
+        }
+    }
+
+    fn with_target(
+        self,
+        target: Label,
+    ) -> Self {
+        Self {
+            target: Some(target),
+            ..self
+        }
+    }
+}
+
+/// Code buffer that stencils are copied into
+#[derive(Debug, Default)]
+pub struct Assembler {
+    code: Vec<u8>,
+    labels: Vec<Option<usize>>,
+    /// `Rel32` holes and the labels they jump to
+    fixups: Vec<(usize, Label)>,
+}
+
+impl Assembler {
+    pub fn label(&mut self) -> Label {
+        self.labels.push(None);
+        Label(self.labels.len() - 1)
+    }
+
+    /// Bind `label` to the current position
+    pub fn bind(
+        &mut self,
+        label: Label,
+    ) {
+        debug_assert!(self.labels[label.0].is_none(), "label bound twice");
+        self.labels[label.0] = Some(self.code.len());
+    }
+
+    /// Offset `label` is bound to
+    pub fn offset(
+        &self,
+        label: Label,
+    ) -> usize {
+        self.labels[label.0].expect("label is bound")
+    }
+
+    fn emit(
+        &mut self,
+        stencil: &Stencil,
+        patch: Patch,
+    ) {
+        let start = self.code.len();
+        self.code.extend_from_slice(stencil.code);
+        for &(offset, hole) in stencil.holes {
+            let at = start + offset;
+            match hole {
+                Hole::A => self.patch(at, &patch.a.to_le_bytes()),
+                Hole::B => self.patch(at, &patch.b.to_le_bytes()),
+                Hole::D => self.patch(at, &patch.d.to_le_bytes()),
+                Hole::Imm32 => self.patch(at, &(patch.imm as u32).to_le_bytes()),
+                Hole::Imm64 => self.patch(at, &patch.imm.to_le_bytes()),
+                Hole::Rel32 => {
+                    let target = patch.target.expect("stencil jumps to a target");
+                    self.fixups.push((at, target));
+                }
+            }
+        }
+    }
+
+    fn patch(
+        &mut self,
+        at: usize,
+        bytes: &[u8],
+    ) {
+        self.code[at..at + bytes.len()].copy_from_slice(bytes);
+    }
+
+    /// Resolve the jumps and return the code
+    pub fn finish(mut self) -> Vec<u8> {
+        for (at, label) in std::mem::take(&mut self.fixups) {
+            let rel = self.offset(label) as i64 - (at as i64 + 4);
+            let rel = i32::try_from(rel).expect("jumps stay within 2 GiB");
+            self.patch(at, &rel.to_le_bytes());
+        }
+        self.code
+    }
+}
+
+/// Emit `func` at its label in `entries`
+pub fn function(
+    asm: &mut Assembler,
+    program: &Program<'_>,
+    entries: &HashMap<String, Label>,
+    func: &BytecodeFunction,
+    analysis: &Analysis,
+) {
+    let slots = analysis
+        .states
+        .iter()
+        .flatten()
+        .map(Vec::len)
+        .max()
+        .unwrap_or(0);
+    let outgoing = func
+        .instructions
+        .iter()
+        .map(|instr| match instr {
+            BytecodeInstr::CallStatic { args, .. } | BytecodeInstr::TailCall { args, .. } => {
+                args.len()
+            }
+            _ => 0,
+        })
+        .max()
+        .unwrap_or(0);
+    let deopt = asm.label();
+    let body = asm.label();
+    let labels = (0..func.instructions.len()).map(|_| asm.label()).collect();
+    let mut emitter = Emitter {
+        asm,
+        program,
+        entries,
+        func,
+        analysis,
+        labels,
+        deopt,
+        body,
+        outgoing: slot(slots),
+    };
+
+    // Calls need `rsp` 16-byte aligned, which a frame of whole 16 bytes keeps
+    let frame = ((slots + outgoing) * 8).next_multiple_of(16);
+    emitter.asm.bind(entries[&func.name]);
+    emitter.emit(
+        &stencils::PROLOGUE,
+        Patch::imm(frame as u64).with_target(deopt),
+    );
+    // The arguments become locals 0..n
+    for i in 0..analysis.signature.params.len() {
+        let local = slot(analysis.registers + i);
+        emitter.emit(
+            &stencils::PARAM,
+            Patch {
+                d: local,
+                ..Patch::a(slot(i))
+            },
+        );
+    }
+    emitter.body();
+}
+
+/// Emits the body of one analyzed function
+struct Emitter<'a> {
+    asm: &'a mut Assembler,
+    program: &'a Program<'a>,
+    entries: &'a HashMap<String, Label>,
+    func: &'a BytecodeFunction,
+    analysis: &'a Analysis,
+    /// Label of each instruction
+    labels: Vec<Label>,
+    deopt: Label,
+    /// After the prologue: where a self tail call starts over
+    body: Label,
+    /// Offset of the first outgoing argument slot
+    outgoing: u32,
+}
+
+impl Emitter<'_> {
+    fn emit(
+        &mut self,
+        stencil: &Stencil,
+        patch: Patch,
+    ) {
+        self.asm.emit(stencil, patch);
+    }
+
+    fn body(mut self) {
+        self.asm.bind(self.body);
+        self.poll();
+        let loop_headers = self.loop_headers();
+        for (ip, instr) in self.func.instructions.iter().enumerate() {
+            // A reachable instruction that falls through always falls through to
+            // a reachable one, so skipping the rest leaves no gaps
+            if self.analysis.states[ip].is_none() {
+                continue;
+            }
+            self.asm.bind(self.labels[ip]);
+            if loop_headers.contains(&ip) {
+                self.poll();
+            }
+            self.instruction(ip, instr);
+        }
+        // Running off the end returns void
+        self.asm.bind(self.deopt);
+        self.emit(&stencils::DEOPT, Patch::default());
+    }
+
+    /// Targets of backward jumps, where the interpreter has its loop safepoints
+    fn loop_headers(&self) -> Vec<usize> {
+        let len = self.func.instructions.len();
+        let mut headers = Vec::new();
+        for (ip, instr) in self.func.instructions.iter().enumerate() {
+            for next in successors(ip, instr, len).into_iter().flatten() {
+                if next <= ip {
+                    headers.push(next);
+                }
+            }
+        }
+        headers
+    }
+
+    /// Deopt when a signal is pending (without taking it: the interpreter handles it)
+    fn poll(&mut self) {
+        let flags = crate::std::signal::pending_flags().as_ptr() as u64;
+        self.emit(&stencils::POLL, Patch::imm(flags).with_target(self.deopt));
+    }
+
+    /// Label that jumping to the instruction at `target` enters
+    fn target(
+        &self,
+        ip: usize,
+        label: crate::middle::bytecode::Label,
+    ) -> Label {
+        jump_target(ip, label, self.func.instructions.len())
+            .map_or(self.deopt, |target| self.labels[target])
+    }
+
+    fn reg(reg: Reg) -> u32 {
+        slot(reg.0 as usize)
+    }
+
+    fn local(
+        &self,
+        local: u16,
+    ) -> u32 {
+        slot(self.analysis.registers + local as usize)
+    }
+
+    fn load(
+        &mut self,
+        slot: u32,
+    ) {
+        self.emit(&stencils::LOAD, Patch::a(slot));
+    }
+
+    fn store(
+        &mut self,
+        slot: u32,
+    ) {
+        self.emit(&stencils::STORE, Patch::d(slot));
+    }
+
+    /// Load a number into `xmm0` (`second` false) or `xmm1`, widening ints
+    fn load_float(
+        &mut self,
+        ip: usize,
+        reg: Reg,
+        second: bool,
+    ) {
+        let int = self.analysis.reg(ip, reg) == Ty::Int;
+        let slot = Self::reg(reg);
+        match (second, int) {
+            (false, false) => self.emit(&stencils::LOAD_F0, Patch::a(slot)),
+            (false, true) => self.emit(&stencils::LOAD_F0_INT, Patch::a(slot)),
+            (true, false) => self.emit(&stencils::LOAD_F1, Patch::b(slot)),
+            (true, true) => self.emit(&stencils::LOAD_F1_INT, Patch::b(slot)),
+        }
+    }
+
+    fn instruction(
+        &mut self,
+        ip: usize,
+        instr: &BytecodeInstr,
+    ) {
+        match instr {
+            BytecodeInstr::Nop
+            | BytecodeInstr::Yield
+            | BytecodeInstr::Drop { .. }
+            | BytecodeInstr::Release { .. }
+            | BytecodeInstr::ArcDrop { .. } => {}
+            BytecodeInstr::Return => self.emit(&stencils::JMP, Patch::to(self.deopt)),
+            BytecodeInstr::ReturnValue { value } => {
+                self.load(Self::reg(*value));
+                self.emit(&stencils::RETURN, Patch::default());
+            }
+            BytecodeInstr::Jmp { target } => {
+                let target = self.target(ip, *target);
+                self.emit(&stencils::JMP, Patch::to(target));
+            }
+            BytecodeInstr::JmpIf { cond, target } | BytecodeInstr::JmpIfNot { cond, target } => {
+                let target = self.target(ip, *target);
+                let stencil = if matches!(instr, BytecodeInstr::JmpIf { .. }) {
+                    &stencils::JNZ
+                } else {
+                    &stencils::JZ
+                };
+                self.emit(stencil, Patch::a(Self::reg(*cond)).with_target(target));
+            }
+            BytecodeInstr::TableSwitch {
+                value,
+                low,
+                targets,
+                default,
+            } => {
+                self.load(Self::reg(*value));
+                for (i, target) in targets.iter().enumerate() {
+                    // Keys past `Int.MAX` can never match
+                    let Some(key) = low.checked_add(i as i64) else {
+                        break;
+                    };
+                    let target = self.target(ip, *target);
+                    self.emit(&stencils::CASE, Patch::imm(key as u64).with_target(target));
+                }
+                let default = self.target(ip, *default);
+                self.emit(&stencils::JMP, Patch::to(default));
+            }
+            BytecodeInstr::Mov { dst, src } => {
+                self.load(Self::reg(*src));
+                self.store(Self::reg(*dst));
+            }
+            BytecodeInstr::LoadConst { dst, const_idx } => {
+                let bits = match &self.program.constants[*const_idx as usize] {
+                    ConstValue::Int(n) => *n as u64,
+                    ConstValue::Float(f) => f.to_bits(),
+                    ConstValue::Bool(b) => *b as u64,
+                    other => unreachable!("constant {:?} is not native", other),
+                };
+                self.emit(&stencils::LOAD_IMM, Patch::imm(bits));
+                self.store(Self::reg(*dst));
+            }
+            BytecodeInstr::LoadLocal { dst, local_idx }
+            | BytecodeInstr::LoadArg {
+                dst,
+                arg_idx: local_idx,
+            } => {
+                self.load(self.local(*local_idx));
+                self.store(Self::reg(*dst));
+            }
+            BytecodeInstr::StoreLocal { local_idx, src } => {
+                self.load(Self::reg(*src));
+                self.store(self.local(*local_idx));
+            }
+            BytecodeInstr::BinaryOp { dst, lhs, rhs, op } => {
+                if self.analysis.reg(ip, *lhs) == Ty::Int {
+                    self.load(Self::reg(*lhs));
+                    self.int_op(*op, Self::reg(*rhs));
+                    self.store(Self::reg(*dst));
+                } else {
+                    self.float_op(ip, *op, *lhs, *rhs, *dst);
+                }
+            }
+            BytecodeInstr::UnaryOp { dst, src, op } => {
+                match (op, self.analysis.reg(ip, *src)) {
+                    (UnaryOp::Neg, Ty::Float) => {
+                        self.load_float(ip, *src, false);
+                        self.emit(&stencils::FNEG, Patch::default());
+                        self.emit(&stencils::STORE_F0, Patch::d(Self::reg(*dst)));
+                        return;
+                    }
+                    (UnaryOp::Neg, _) => {
+                        self.load(Self::reg(*src));
+                        self.emit(&stencils::NEG, Patch::default());
+                        if self.program.traps_overflow {
+                            self.emit(&stencils::JO, Patch::to(self.deopt));
+                        }
+                    }
+                    (UnaryOp::Not, Ty::Int) => {
+                        self.load(Self::reg(*src));
+                        self.emit(&stencils::NOT, Patch::default());
+                    }
+                    (UnaryOp::Not, _) => {
+                        self.load(Self::reg(*src));
+                        self.emit(&stencils::NOT_BOOL, Patch::default());
+                    }
+                }
+                self.store(Self::reg(*dst));
+            }
+            BytecodeInstr::Compare { dst, lhs, rhs, cmp } => {
+                let ints = (self.analysis.reg(ip, *lhs), self.analysis.reg(ip, *rhs))
+                    == (Ty::Int, Ty::Int);
+                if ints {
+                    self.load(Self::reg(*lhs));
+                    let stencil = match cmp {
+                        CompareOp::Eq => &stencils::CMP_EQ,
+                        CompareOp::Ne => &stencils::CMP_NE,
+                        CompareOp::Lt => &stencils::CMP_LT,
+                        CompareOp::Le => &stencils::CMP_LE,
+                        CompareOp::Gt => &stencils::CMP_GT,
+                        CompareOp::Ge => &stencils::CMP_GE,
+                    };
+                    self.emit(stencil, Patch::b(Self::reg(*rhs)));
+                    self.store(Self::reg(*dst));
+                } else {
+                    self.float_compare(ip, *cmp, *lhs, *rhs, *dst);
+                }
+            }
+            BytecodeInstr::FloatOp { dst, lhs, rhs, op } => {
+                self.float_op(ip, *op, *lhs, *rhs, *dst);
+            }
+            BytecodeInstr::FloatCompare { dst, lhs, rhs, cmp } => {
+                self.float_compare(ip, *cmp, *lhs, *rhs, *dst);
+            }
+            BytecodeInstr::FloatNeg { dst, src } => {
+                self.load_float(ip, *src, false);
+                self.emit(&stencils::FNEG, Patch::default());
+                self.emit(&stencils::STORE_F0, Patch::d(Self::reg(*dst)));
+            }
+            BytecodeInstr::IntToFloat { dst, src } => {
+                self.load_float(ip, *src, false);
+                self.emit(&stencils::STORE_F0, Patch::d(Self::reg(*dst)));
+            }
+            BytecodeInstr::CallStatic { dst, func, args } => {
+                let callee = (self.program.callee_name)(func);
+                self.call(&callee, args);
+                if let Some(dst) = dst {
+                    self.store(Self::reg(*dst));
+                }
+            }
+            BytecodeInstr::TailCall { func, args } => {
+                let callee = (self.program.callee_name)(func);
+                if callee == self.func.name {
+                    // Start over in the same frame with the new arguments
+                    self.outgoing_args(args);
+                    for i in 0..args.len() {
+                        self.load(self.outgoing + slot(i));
+                        self.store(slot(self.analysis.registers + i));
+                    }
+                    self.emit(&stencils::JMP, Patch::to(self.body));
+                } else {
+                    self.call(&callee, args);
+                    self.emit(&stencils::RETURN, Patch::default());
+                }
+            }
+            other => unreachable!("{:?} is not native", other),
+        }
+    }
+
+    /// Copy the arguments of a call to the outgoing slots
+    fn outgoing_args(
+        &mut self,
+        args: &[Reg],
+    ) {
+        for (i, arg) in args.iter().enumerate() {
+            self.load(Self::reg(*arg));
+            self.store(self.outgoing + slot(i));
+        }
+    }
+
+    /// Call `callee`, leaving its result in `rax`; a deopt anywhere below
+    /// unwinds the whole machine-code call
+    fn call(
+        &mut self,
+        callee: &str,
+        args: &[Reg],
+    ) {
+        self.outgoing_args(args);
+        let entry = self.entries[callee];
+        self.emit(&stencils::CALL, Patch::a(self.outgoing).with_target(entry));
+        self.emit(&stencils::CHECK, Patch::to(self.deopt));
+    }
+
+    /// Integer arithmetic on `rax` with the interpreter's overflow and division rules
+    fn int_op(
+        &mut self,
+        op: BinaryOp,
+        rhs: u32,
+    ) {
+        let checked = self.program.traps_overflow;
+        let stencil = match op {
+            BinaryOp::Add => &stencils::ADD,
+            BinaryOp::Sub => &stencils::SUB,
+            BinaryOp::Mul => &stencils::MUL,
+            BinaryOp::And => &stencils::AND,
+            BinaryOp::Or => &stencils::OR,
+            BinaryOp::Xor => &stencils::XOR,
+            BinaryOp::Div if checked => &stencils::DIV_CHECKED,
+            BinaryOp::Div => &stencils::DIV,
+            BinaryOp::Rem if checked => &stencils::REM_CHECKED,
+            BinaryOp::Rem => &stencils::REM,
+            BinaryOp::Shl if checked => &stencils::SHL_CHECKED,
+            BinaryOp::Shl => &stencils::SHL,
+            // Both right shifts are arithmetic in the interpreter
+            BinaryOp::Sar | BinaryOp::Shr if checked => &stencils::SAR_CHECKED,
+            BinaryOp::Sar | BinaryOp::Shr => &stencils::SAR,
+        };
+        self.emit(stencil, Patch::b(rhs).with_target(self.deopt));
+        if checked && matches!(op, BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul) {
+            self.emit(&stencils::JO, Patch::to(self.deopt));
+        }
+    }
+
+    fn float_op(
+        &mut self,
+        ip: usize,
+        op: BinaryOp,
+        lhs: Reg,
+        rhs: Reg,
+        dst: Reg,
+    ) {
+        self.load_float(ip, lhs, false);
+        self.load_float(ip, rhs, true);
+        match op {
+            BinaryOp::Add => self.emit(&stencils::FADD, Patch::default()),
+            BinaryOp::Sub => self.emit(&stencils::FSUB, Patch::default()),
+            BinaryOp::Mul => self.emit(&stencils::FMUL, Patch::default()),
+            BinaryOp::Div => self.emit(&stencils::FDIV, Patch::default()),
+            _ => {
+                let rem = float_rem as extern "C" fn(f64, f64) -> f64;
+                self.emit(&stencils::FCALL, Patch::imm(rem as usize as u64));
+            }
+        }
+        self.emit(&stencils::STORE_F0, Patch::d(Self::reg(dst)));
+    }
+
+    /// IEEE comparison (only `!=` holds for NaN), widening ints
+    fn float_compare(
+        &mut self,
+        ip: usize,
+        cmp: CompareOp,
+        lhs: Reg,
+        rhs: Reg,
+        dst: Reg,
+    ) {
+        self.load_float(ip, lhs, false);
+        self.load_float(ip, rhs, true);
+        let stencil = match cmp {
+            CompareOp::Eq => &stencils::FCMP_EQ,
+            CompareOp::Ne => &stencils::FCMP_NE,
+            CompareOp::Lt => &stencils::FCMP_LT,
+            CompareOp::Le => &stencils::FCMP_LE,
+            CompareOp::Gt => &stencils::FCMP_GT,
+            CompareOp::Ge => &stencils::FCMP_GE,
+        };
+        self.emit(stencil, Patch::default());
+        self.store(Self::reg(dst));
+    }
+}
+
+/// Byte offset of slot `index` in the frame
+fn slot(index: usize) -> u32 {
+    u32::try_from(index * 8).expect("frames are small")
+}
+
+/// `Float % Float`, which has no instruction
+extern "C" fn float_rem(
+    l: f64,
+    r: f64,
+) -> f64 {
+    l % r
+}
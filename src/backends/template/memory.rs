@@ -0,0 +1,94 @@
+//! Executable memory
+//!
+//! The code is copied into fresh anonymous pages that are writable, then made
+//! read-only and executable before anything runs; pages are never writable and
+//! executable at once.
+
+use std::ffi::c_void;
+
+const PROT_READ: i32 = 1;
+const PROT_WRITE: i32 = 2;
+const PROT_EXEC: i32 = 4;
+const MAP_PRIVATE: i32 = 2;
+#[cfg(target_os = "macos")]
+const MAP_ANONYMOUS: i32 = 0x1000;
+#[cfg(not(target_os = "macos"))]
+const MAP_ANONYMOUS: i32 = 0x20;
+const MAP_FAILED: *mut c_void = !0 as *mut c_void;
+
+extern "C" {
+    fn mmap(
+        addr: *mut c_void,
+        len: usize,
+        prot: i32,
+        flags: i32,
+        fd: i32,
+        offset: i64,
+    ) -> *mut c_void;
+    fn mprotect(
+        addr: *mut c_void,
+        len: usize,
+        prot: i32,
+    ) -> i32;
+    fn munmap(
+        addr: *mut c_void,
+        len: usize,
+    ) -> i32;
+}
+
+/// Read-only, executable copy of some machine code
+#[derive(Debug)]
+pub struct ExecutableMemory {
+    ptr: *mut c_void,
+    len: usize,
+}
+
+// SAFETY: the pages are immutable once mapped and only unmapped on drop
+unsafe impl Send for ExecutableMemory {}
+unsafe impl Sync for ExecutableMemory {}
+
+impl ExecutableMemory {
+    /// Map `code` as executable, or `None` if the system refuses
+    pub fn new(code: &[u8]) -> Option<ExecutableMemory> {
+        let len = code.len().max(1);
+        // SAFETY: a fresh private anonymous mapping, written only within `len`
+        unsafe {
+            let ptr = mmap(
+                std::ptr::null_mut(),
+                len,
+                PROT_READ | PROT_WRITE,
+                MAP_PRIVATE | MAP_ANONYMOUS,
+                -1,
+                0,
+            );
+            if ptr == MAP_FAILED {
+                return None;
+            }
+            let memory = ExecutableMemory { ptr, len };
+            std::ptr::copy_nonoverlapping(code.as_ptr(), ptr.cast::<u8>(), code.len());
+            if mprotect(ptr, len, PROT_READ | PROT_EXEC) != 0 {
+                return None;
+            }
+            Some(memory)
+        }
+    }
+
+    /// Address of the byte at `offset`
+    pub fn at(
+        &self,
+        offset: usize,
+    ) -> *const u8 {
+        assert!(offset < self.len, "offset {} outside the code", offset);
+        // SAFETY: within the mapping
+        unsafe { self.ptr.cast::<u8>().add(offset) }
+    }
+}
+
+impl Drop for ExecutableMemory {
+    fn drop(&mut self) {
+        // SAFETY: mapped by `new`, and nothing runs the code once its owner is gone
+        unsafe {
+            munmap(self.ptr, self.len);
+        }
+    }
+}
@@ -0,0 +1,257 @@
+//! Template JIT
+//!
+//! Compiles type-stable functions to machine code by pasting together
+//! precompiled per-opcode stencils (see [`stencils`]) and patching their
+//! operands and jump targets. There is no IR and no optimization, so compiling
+//! costs about as much as copying the bytecode; the interpreter hands functions
+//! over as soon as they get hot.
+//!
+//! # Coverage
+//!
+//! A function is compiled when its parameters and result are `Int`, `Float` or
+//! `Bool`, every register it reads holds one of those with a statically known
+//! type, and it only calls functions that are compiled too. Closures, strings,
+//! lists and any call into the standard library keep the function interpreted.
+//! The stencils are x86-64 System V code, so on other hosts nothing is compiled.
+//!
+//! # Deoptimization
+//!
+//! Compiled functions are pure, so whenever the interpreter would do something
+//! the machine code does not — report an overflow in a debug build, a division by
+//! zero or a stack overflow, return void, or handle a pending signal — the
+//! machine code gives up and the interpreter runs the whole call again.
+
+pub(crate) mod analysis;
+#[cfg(all(target_arch = "x86_64", unix))]
+mod emit;
+#[cfg(all(target_arch = "x86_64", unix))]
+mod memory;
+#[cfg(all(target_arch = "x86_64", unix))]
+pub mod stencils;
+
+#[cfg(all(test, target_arch = "x86_64", unix))]
+mod tests;
+
+use std::collections::HashMap;
+
+use analysis::{Signature, Ty};
+
+use crate::backends::common::RuntimeValue;
+use crate::middle::bytecode::{BytecodeFunction, ConstValue, FunctionRef};
+
+/// What the interpreter knows about the program being compiled
+pub struct Program<'a> {
+    /// Candidate functions (callable by their names)
+    pub functions: Vec<&'a BytecodeFunction>,
+    /// Constant pool that `LoadConst` indexes
+    pub constants: &'a [ConstValue],
+    /// Name of a statically called function, resolved as the interpreter does
+    pub callee_name: &'a dyn Fn(&FunctionRef) -> String,
+    /// Integer overflow is an error (debug builds) rather than wrapping
+    pub traps_overflow: bool,
+}
+
+/// Result of calling a compiled function
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome {
+    Returned(RuntimeValue),
+    /// The machine code gave up; the interpreter has to run the call
+    Deopt,
+}
+
+/// State shared by the machine code of one call (layout used by the stencils)
+#[repr(C)]
+struct Context {
+    /// Set when the machine code gives up
+    status: u8,
+    /// Call levels left
+    depth: u64,
+}
+
+// The stencils address the fields by these offsets
+const _: () = assert!(std::mem::offset_of!(Context, status) == 0);
+const _: () = assert!(std::mem::offset_of!(Context, depth) == 8);
+
+/// `extern "C" fn(context, args) -> result` entry of a compiled function
+type Entry = unsafe extern "C" fn(*mut Context, *const u64) -> u64;
+
+struct TemplateFunction {
+    signature: Signature,
+    entry: Entry,
+}
+
+/// Machine code for the compilable functions of a program
+pub struct TemplateCode {
+    #[cfg(all(target_arch = "x86_64", unix))]
+    _memory: Option<memory::ExecutableMemory>,
+    functions: HashMap<String, TemplateFunction>,
+}
+
+impl std::fmt::Debug for TemplateCode {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        f.debug_struct("TemplateCode")
+            .field("functions", &self.functions.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl TemplateCode {
+    /// Compile every function of `program` that qualifies
+    ///
+    /// Compilation never fails the program: on an unsupported host, or if the
+    /// system refuses executable memory, nothing is compiled.
+    pub fn compile(program: &Program<'_>) -> TemplateCode {
+        #[cfg(all(target_arch = "x86_64", unix))]
+        {
+            let analyses = analyze(program);
+            let mut asm = emit::Assembler::default();
+            let entries: HashMap<String, emit::Label> = analyses
+                .iter()
+                .map(|(func, _)| (func.name.clone(), asm.label()))
+                .collect();
+            for (func, analysis) in &analyses {
+                emit::function(&mut asm, program, &entries, func, analysis);
+            }
+            let offsets: HashMap<&str, usize> = entries
+                .iter()
+                .map(|(name, label)| (name.as_str(), asm.offset(*label)))
+                .collect();
+            let code = asm.finish();
+            let Some(memory) = memory::ExecutableMemory::new(&code) else {
+                tracing::debug!("template compilation failed: no executable memory");
+                return TemplateCode {
+                    _memory: None,
+                    functions: HashMap::new(),
+                };
+            };
+            let functions = analyses
+                .iter()
+                .map(|(func, analysis)| {
+                    let code = memory.at(offsets[func.name.as_str()]);
+                    // SAFETY: the stencils at this offset implement the `Entry` ABI
+                    let entry = unsafe { std::mem::transmute::<*const u8, Entry>(code) };
+                    let function = TemplateFunction {
+                        signature: analysis.signature.clone(),
+                        entry,
+                    };
+                    (func.name.clone(), function)
+                })
+                .collect();
+            TemplateCode {
+                _memory: Some(memory),
+                functions,
+            }
+        }
+        #[cfg(not(all(target_arch = "x86_64", unix)))]
+        {
+            let _ = program;
+            TemplateCode {
+                functions: HashMap::new(),
+            }
+        }
+    }
+
+    /// Whether `name` runs as machine code
+    pub fn is_compiled(
+        &self,
+        name: &str,
+    ) -> bool {
+        self.functions.contains_key(name)
+    }
+
+    /// Names of the compiled functions
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.functions.keys().map(String::as_str)
+    }
+
+    /// Number of compiled functions
+    pub fn len(&self) -> usize {
+        self.functions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.functions.is_empty()
+    }
+
+    /// Call the compiled `name` with `depth` call levels left (counting its own)
+    ///
+    /// Returns `None` when `name` is not compiled or `args` do not have the
+    /// types it was compiled for.
+    pub fn call(
+        &self,
+        name: &str,
+        args: &[RuntimeValue],
+        depth: usize,
+    ) -> Option<Outcome> {
+        let function = self.functions.get(name)?;
+        if args.len() != function.signature.params.len() {
+            return None;
+        }
+        let mut raw = Vec::with_capacity(args.len());
+        for (arg, ty) in args.iter().zip(&function.signature.params) {
+            raw.push(match (arg, ty) {
+                (RuntimeValue::Int(n), Ty::Int) => *n as u64,
+                (RuntimeValue::Float(f), Ty::Float) => f.to_bits(),
+                (RuntimeValue::Bool(b), Ty::Bool) => *b as u64,
+                _ => return None,
+            });
+        }
+
+        let mut context = Context {
+            status: 0,
+            depth: depth as u64,
+        };
+        // SAFETY: the entry was compiled for exactly these argument types, and the
+        // code stays mapped as long as `self` is alive
+        let ret = unsafe { (function.entry)(&mut context, raw.as_ptr()) };
+        if context.status != 0 {
+            return Some(Outcome::Deopt);
+        }
+        Some(Outcome::Returned(match function.signature.ret {
+            Ty::Int => RuntimeValue::Int(ret as i64),
+            Ty::Float => RuntimeValue::Float(f64::from_bits(ret)),
+            Ty::Bool => RuntimeValue::Bool(ret != 0),
+        }))
+    }
+}
+
+/// Analyze the candidates until the compilable set is closed under calls
+pub(crate) fn analyze<'a>(
+    program: &Program<'a>
+) -> Vec<(&'a BytecodeFunction, analysis::Analysis)> {
+    let mut signatures: HashMap<String, Signature> = HashMap::new();
+    let mut seen = HashMap::new();
+    for func in &program.functions {
+        *seen.entry(func.name.as_str()).or_insert(0) += 1;
+    }
+    for func in &program.functions {
+        // A duplicated name calls whichever definition was loaded last
+        if seen[func.name.as_str()] == 1 {
+            if let Some(signature) = Signature::of(func) {
+                signatures.insert(func.name.clone(), signature);
+            }
+        }
+    }
+    loop {
+        let mut analyses = Vec::new();
+        let mut rejected = Vec::new();
+        for func in &program.functions {
+            if !signatures.contains_key(&func.name) {
+                continue;
+            }
+            match analysis::analyze(func, &signatures, program.constants, program.callee_name) {
+                Some(analysis) => analyses.push((*func, analysis)),
+                None => rejected.push(func.name.clone()),
+            }
+        }
+        if rejected.is_empty() {
+            return analyses;
+        }
+        for name in rejected {
+            signatures.remove(&name);
+        }
+    }
+}
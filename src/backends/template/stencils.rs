@@ -0,0 +1,759 @@
+//! Machine-code stencils (x86-64, System V)
+//!
+//! One stencil per operation, assembled ahead of time from the listing in its
+//! doc comment. The emitter copies the bytes and patches the holes; nothing is
+//! generated at run time. Throughout the compiled code `rbx` points at the
+//! frame's slots, `r12` at the call's `Context` and `rbp` is the frame pointer;
+//! `rax`, `xmm0` and `xmm1` carry values from one stencil to the next, and no
+//! value stays in a register across instructions.
+
+/// What the emitter writes into a stencil
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hole {
+    /// Byte offset of the first operand's slot (`disp32`)
+    A,
+    /// Byte offset of the second operand's slot (`disp32`)
+    B,
+    /// Byte offset of the destination slot (`disp32`)
+    D,
+    Imm32,
+    Imm64,
+    /// Jump to the emission's target, relative to the end of the hole
+    Rel32,
+}
+
+/// Machine code with holes at the given byte offsets
+#[derive(Debug)]
+pub struct Stencil {
+    pub code: &'static [u8],
+    pub holes: &'static [(usize, Hole)],
+}
+
+/// Save registers, reserve `Imm32` bytes of slots, check the call depth
+///
+/// ```text
+/// push rbp
+/// mov rbp, rsp
+/// push rbx
+/// push r12
+/// mov r12, rdi
+/// sub rsp, IMM32
+/// mov rbx, rsp
+/// cmp qword ptr [r12 + 8], 0
+/// je target
+/// dec qword ptr [r12 + 8]
+/// ```
+pub const PROLOGUE: Stencil = Stencil {
+    code: &[
+        0x55, 0x48, 0x89, 0xe5, 0x53, 0x41, 0x54, 0x49, 0x89, 0xfc, 0x48, 0x81, 0xec, 0x00, 0x00,
+        0x00, 0x00, 0x48, 0x89, 0xe3, 0x49, 0x83, 0x7c, 0x24, 0x08, 0x00, 0x0f, 0x84, 0x00, 0x00,
+        0x00, 0x00, 0x49, 0xff, 0x4c, 0x24, 0x08,
+    ],
+    holes: &[(13, Hole::Imm32), (28, Hole::Rel32)],
+};
+
+/// Copy the argument at byte `A` of the argument array to slot `D`
+///
+/// ```text
+/// mov rax, qword ptr [rsi + A]
+/// mov qword ptr [rbx + D], rax
+/// ```
+pub const PARAM: Stencil = Stencil {
+    code: &[
+        0x48, 0x8b, 0x86, 0x00, 0x00, 0x00, 0x00, 0x48, 0x89, 0x83, 0x00, 0x00, 0x00, 0x00,
+    ],
+    holes: &[(3, Hole::A), (10, Hole::D)],
+};
+
+/// Leave if a signal is pending (flags at `Imm64`)
+///
+/// ```text
+/// mov rax, IMM64
+/// cmp word ptr [rax], 0
+/// jne target
+/// ```
+pub const POLL: Stencil = Stencil {
+    code: &[
+        0x48, 0xb8, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x66, 0x83, 0x38, 0x00, 0x0f,
+        0x85, 0x00, 0x00, 0x00, 0x00,
+    ],
+    holes: &[(2, Hole::Imm64), (16, Hole::Rel32)],
+};
+
+/// `rax` = slot `A`
+///
+/// ```text
+/// mov rax, qword ptr [rbx + A]
+/// ```
+pub const LOAD: Stencil = Stencil {
+    code: &[0x48, 0x8b, 0x83, 0x00, 0x00, 0x00, 0x00],
+    holes: &[(3, Hole::A)],
+};
+
+/// Slot `D` = `rax`
+///
+/// ```text
+/// mov qword ptr [rbx + D], rax
+/// ```
+pub const STORE: Stencil = Stencil {
+    code: &[0x48, 0x89, 0x83, 0x00, 0x00, 0x00, 0x00],
+    holes: &[(3, Hole::D)],
+};
+
+/// `rax` = `Imm64`
+///
+/// ```text
+/// movabs rax, IMM64
+/// ```
+pub const LOAD_IMM: Stencil = Stencil {
+    code: &[0x48, 0xb8, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+    holes: &[(2, Hole::Imm64)],
+};
+
+/// `rax` += slot `B`
+///
+/// ```text
+/// add rax, qword ptr [rbx + B]
+/// ```
+pub const ADD: Stencil = Stencil {
+    code: &[0x48, 0x03, 0x83, 0x00, 0x00, 0x00, 0x00],
+    holes: &[(3, Hole::B)],
+};
+
+/// `rax` -= slot `B`
+///
+/// ```text
+/// sub rax, qword ptr [rbx + B]
+/// ```
+pub const SUB: Stencil = Stencil {
+    code: &[0x48, 0x2b, 0x83, 0x00, 0x00, 0x00, 0x00],
+    holes: &[(3, Hole::B)],
+};
+
+/// `rax` *= slot `B`
+///
+/// ```text
+/// imul rax, qword ptr [rbx + B]
+/// ```
+pub const MUL: Stencil = Stencil {
+    code: &[0x48, 0x0f, 0xaf, 0x83, 0x00, 0x00, 0x00, 0x00],
+    holes: &[(4, Hole::B)],
+};
+
+/// `rax` &= slot `B`
+///
+/// ```text
+/// and rax, qword ptr [rbx + B]
+/// ```
+pub const AND: Stencil = Stencil {
+    code: &[0x48, 0x23, 0x83, 0x00, 0x00, 0x00, 0x00],
+    holes: &[(3, Hole::B)],
+};
+
+/// `rax` |= slot `B`
+///
+/// ```text
+/// or rax, qword ptr [rbx + B]
+/// ```
+pub const OR: Stencil = Stencil {
+    code: &[0x48, 0x0b, 0x83, 0x00, 0x00, 0x00, 0x00],
+    holes: &[(3, Hole::B)],
+};
+
+/// `rax` ^= slot `B`
+///
+/// ```text
+/// xor rax, qword ptr [rbx + B]
+/// ```
+pub const XOR: Stencil = Stencil {
+    code: &[0x48, 0x33, 0x83, 0x00, 0x00, 0x00, 0x00],
+    holes: &[(3, Hole::B)],
+};
+
+/// Jump on signed overflow
+///
+/// ```text
+/// jo target
+/// ```
+pub const JO: Stencil = Stencil {
+    code: &[0x0f, 0x80, 0x00, 0x00, 0x00, 0x00],
+    holes: &[(2, Hole::Rel32)],
+};
+
+/// `rax` /= slot `B`, jumping on a zero divisor; `Int.MIN / -1` wraps
+///
+/// ```text
+/// mov rcx, qword ptr [rbx + B]
+/// test rcx, rcx
+/// je target
+/// cmp rcx, -1
+/// je 1f
+/// cqo
+/// idiv rcx
+/// jmp 2f
+/// 1:
+/// neg rax
+/// 2:
+/// ```
+pub const DIV: Stencil = Stencil {
+    code: &[
+        0x48, 0x8b, 0x8b, 0x00, 0x00, 0x00, 0x00, 0x48, 0x85, 0xc9, 0x0f, 0x84, 0x00, 0x00, 0x00,
+        0x00, 0x48, 0x83, 0xf9, 0xff, 0x74, 0x07, 0x48, 0x99, 0x48, 0xf7, 0xf9, 0xeb, 0x03, 0x48,
+        0xf7, 0xd8,
+    ],
+    holes: &[(3, Hole::B), (12, Hole::Rel32)],
+};
+
+/// `rax` /= slot `B`, jumping on a zero divisor or `Int.MIN / -1`
+///
+/// ```text
+/// mov rcx, qword ptr [rbx + B]
+/// test rcx, rcx
+/// je target
+/// cmp rcx, -1
+/// je 1f
+/// cqo
+/// idiv rcx
+/// jmp 2f
+/// 1:
+/// neg rax
+/// jo target
+/// 2:
+/// ```
+pub const DIV_CHECKED: Stencil = Stencil {
+    code: &[
+        0x48, 0x8b, 0x8b, 0x00, 0x00, 0x00, 0x00, 0x48, 0x85, 0xc9, 0x0f, 0x84, 0x00, 0x00, 0x00,
+        0x00, 0x48, 0x83, 0xf9, 0xff, 0x74, 0x07, 0x48, 0x99, 0x48, 0xf7, 0xf9, 0xeb, 0x09, 0x48,
+        0xf7, 0xd8, 0x0f, 0x80, 0x00, 0x00, 0x00, 0x00,
+    ],
+    holes: &[(3, Hole::B), (12, Hole::Rel32), (34, Hole::Rel32)],
+};
+
+/// `rax` %= slot `B`, jumping on a zero divisor; `Int.MIN % -1` is 0
+///
+/// ```text
+/// mov rcx, qword ptr [rbx + B]
+/// test rcx, rcx
+/// je target
+/// cmp rcx, -1
+/// je 1f
+/// cqo
+/// idiv rcx
+/// mov rax, rdx
+/// jmp 2f
+/// 1:
+/// xor eax, eax
+/// 2:
+/// ```
+pub const REM: Stencil = Stencil {
+    code: &[
+        0x48, 0x8b, 0x8b, 0x00, 0x00, 0x00, 0x00, 0x48, 0x85, 0xc9, 0x0f, 0x84, 0x00, 0x00, 0x00,
+        0x00, 0x48, 0x83, 0xf9, 0xff, 0x74, 0x0a, 0x48, 0x99, 0x48, 0xf7, 0xf9, 0x48, 0x89, 0xd0,
+        0xeb, 0x02, 0x31, 0xc0,
+    ],
+    holes: &[(3, Hole::B), (12, Hole::Rel32)],
+};
+
+/// `rax` %= slot `B`, jumping on a zero divisor or `Int.MIN % -1`
+///
+/// ```text
+/// mov rcx, qword ptr [rbx + B]
+/// test rcx, rcx
+/// je target
+/// cmp rcx, -1
+/// je 1f
+/// cqo
+/// idiv rcx
+/// mov rax, rdx
+/// jmp 2f
+/// 1:
+/// neg rax
+/// jo target
+/// xor eax, eax
+/// 2:
+/// ```
+pub const REM_CHECKED: Stencil = Stencil {
+    code: &[
+        0x48, 0x8b, 0x8b, 0x00, 0x00, 0x00, 0x00, 0x48, 0x85, 0xc9, 0x0f, 0x84, 0x00, 0x00, 0x00,
+        0x00, 0x48, 0x83, 0xf9, 0xff, 0x74, 0x0a, 0x48, 0x99, 0x48, 0xf7, 0xf9, 0x48, 0x89, 0xd0,
+        0xeb, 0x0b, 0x48, 0xf7, 0xd8, 0x0f, 0x80, 0x00, 0x00, 0x00, 0x00, 0x31, 0xc0,
+    ],
+    holes: &[(3, Hole::B), (12, Hole::Rel32), (37, Hole::Rel32)],
+};
+
+/// `rax` <<= slot `B` (mod 64)
+///
+/// ```text
+/// mov rcx, qword ptr [rbx + B]
+/// shl rax, cl
+/// ```
+pub const SHL: Stencil = Stencil {
+    code: &[0x48, 0x8b, 0x8b, 0x00, 0x00, 0x00, 0x00, 0x48, 0xd3, 0xe0],
+    holes: &[(3, Hole::B)],
+};
+
+/// `rax` <<= slot `B`, jumping unless `0 <= B < 64`
+///
+/// ```text
+/// mov rcx, qword ptr [rbx + B]
+/// cmp rcx, 63
+/// ja target
+/// shl rax, cl
+/// ```
+pub const SHL_CHECKED: Stencil = Stencil {
+    code: &[
+        0x48, 0x8b, 0x8b, 0x00, 0x00, 0x00, 0x00, 0x48, 0x83, 0xf9, 0x3f, 0x0f, 0x87, 0x00, 0x00,
+        0x00, 0x00, 0x48, 0xd3, 0xe0,
+    ],
+    holes: &[(3, Hole::B), (13, Hole::Rel32)],
+};
+
+/// `rax` >>= slot `B` (arithmetic, mod 64)
+///
+/// ```text
+/// mov rcx, qword ptr [rbx + B]
+/// sar rax, cl
+/// ```
+pub const SAR: Stencil = Stencil {
+    code: &[0x48, 0x8b, 0x8b, 0x00, 0x00, 0x00, 0x00, 0x48, 0xd3, 0xf8],
+    holes: &[(3, Hole::B)],
+};
+
+/// `rax` >>= slot `B` (arithmetic), jumping unless `0 <= B < 64`
+///
+/// ```text
+/// mov rcx, qword ptr [rbx + B]
+/// cmp rcx, 63
+/// ja target
+/// sar rax, cl
+/// ```
+pub const SAR_CHECKED: Stencil = Stencil {
+    code: &[
+        0x48, 0x8b, 0x8b, 0x00, 0x00, 0x00, 0x00, 0x48, 0x83, 0xf9, 0x3f, 0x0f, 0x87, 0x00, 0x00,
+        0x00, 0x00, 0x48, 0xd3, 0xf8,
+    ],
+    holes: &[(3, Hole::B), (13, Hole::Rel32)],
+};
+
+/// `rax` = -`rax` (flags overflow for `Int.MIN`)
+///
+/// ```text
+/// neg rax
+/// ```
+pub const NEG: Stencil = Stencil {
+    code: &[0x48, 0xf7, 0xd8],
+    holes: &[],
+};
+
+/// `rax` = !`rax`
+///
+/// ```text
+/// not rax
+/// ```
+pub const NOT: Stencil = Stencil {
+    code: &[0x48, 0xf7, 0xd0],
+    holes: &[],
+};
+
+/// Logical not of the Bool in `rax`
+///
+/// ```text
+/// xor eax, 1
+/// ```
+pub const NOT_BOOL: Stencil = Stencil {
+    code: &[0x83, 0xf0, 0x01],
+    holes: &[],
+};
+
+/// `rax` = (`rax` == slot `B`)
+///
+/// ```text
+/// cmp rax, qword ptr [rbx + B]
+/// sete al
+/// movzx eax, al
+/// ```
+pub const CMP_EQ: Stencil = Stencil {
+    code: &[
+        0x48, 0x3b, 0x83, 0x00, 0x00, 0x00, 0x00, 0x0f, 0x94, 0xc0, 0x0f, 0xb6, 0xc0,
+    ],
+    holes: &[(3, Hole::B)],
+};
+
+/// `rax` = (`rax` != slot `B`)
+///
+/// ```text
+/// cmp rax, qword ptr [rbx + B]
+/// setne al
+/// movzx eax, al
+/// ```
+pub const CMP_NE: Stencil = Stencil {
+    code: &[
+        0x48, 0x3b, 0x83, 0x00, 0x00, 0x00, 0x00, 0x0f, 0x95, 0xc0, 0x0f, 0xb6, 0xc0,
+    ],
+    holes: &[(3, Hole::B)],
+};
+
+/// `rax` = (`rax` < slot `B`)
+///
+/// ```text
+/// cmp rax, qword ptr [rbx + B]
+/// setl al
+/// movzx eax, al
+/// ```
+pub const CMP_LT: Stencil = Stencil {
+    code: &[
+        0x48, 0x3b, 0x83, 0x00, 0x00, 0x00, 0x00, 0x0f, 0x9c, 0xc0, 0x0f, 0xb6, 0xc0,
+    ],
+    holes: &[(3, Hole::B)],
+};
+
+/// `rax` = (`rax` <= slot `B`)
+///
+/// ```text
+/// cmp rax, qword ptr [rbx + B]
+/// setle al
+/// movzx eax, al
+/// ```
+pub const CMP_LE: Stencil = Stencil {
+    code: &[
+        0x48, 0x3b, 0x83, 0x00, 0x00, 0x00, 0x00, 0x0f, 0x9e, 0xc0, 0x0f, 0xb6, 0xc0,
+    ],
+    holes: &[(3, Hole::B)],
+};
+
+/// `rax` = (`rax` > slot `B`)
+///
+/// ```text
+/// cmp rax, qword ptr [rbx + B]
+/// setg al
+/// movzx eax, al
+/// ```
+pub const CMP_GT: Stencil = Stencil {
+    code: &[
+        0x48, 0x3b, 0x83, 0x00, 0x00, 0x00, 0x00, 0x0f, 0x9f, 0xc0, 0x0f, 0xb6, 0xc0,
+    ],
+    holes: &[(3, Hole::B)],
+};
+
+/// `rax` = (`rax` >= slot `B`)
+///
+/// ```text
+/// cmp rax, qword ptr [rbx + B]
+/// setge al
+/// movzx eax, al
+/// ```
+pub const CMP_GE: Stencil = Stencil {
+    code: &[
+        0x48, 0x3b, 0x83, 0x00, 0x00, 0x00, 0x00, 0x0f, 0x9d, 0xc0, 0x0f, 0xb6, 0xc0,
+    ],
+    holes: &[(3, Hole::B)],
+};
+
+/// `xmm0` = Float slot `A`
+///
+/// ```text
+/// movsd xmm0, qword ptr [rbx + A]
+/// ```
+pub const LOAD_F0: Stencil = Stencil {
+    code: &[0xf2, 0x0f, 0x10, 0x83, 0x00, 0x00, 0x00, 0x00],
+    holes: &[(4, Hole::A)],
+};
+
+/// `xmm0` = Int slot `A`, widened
+///
+/// ```text
+/// cvtsi2sd xmm0, qword ptr [rbx + A]
+/// ```
+pub const LOAD_F0_INT: Stencil = Stencil {
+    code: &[0xf2, 0x48, 0x0f, 0x2a, 0x83, 0x00, 0x00, 0x00, 0x00],
+    holes: &[(5, Hole::A)],
+};
+
+/// `xmm1` = Float slot `B`
+///
+/// ```text
+/// movsd xmm1, qword ptr [rbx + B]
+/// ```
+pub const LOAD_F1: Stencil = Stencil {
+    code: &[0xf2, 0x0f, 0x10, 0x8b, 0x00, 0x00, 0x00, 0x00],
+    holes: &[(4, Hole::B)],
+};
+
+/// `xmm1` = Int slot `B`, widened
+///
+/// ```text
+/// cvtsi2sd xmm1, qword ptr [rbx + B]
+/// ```
+pub const LOAD_F1_INT: Stencil = Stencil {
+    code: &[0xf2, 0x48, 0x0f, 0x2a, 0x8b, 0x00, 0x00, 0x00, 0x00],
+    holes: &[(5, Hole::B)],
+};
+
+/// Slot `D` = `xmm0`
+///
+/// ```text
+/// movsd qword ptr [rbx + D], xmm0
+/// ```
+pub const STORE_F0: Stencil = Stencil {
+    code: &[0xf2, 0x0f, 0x11, 0x83, 0x00, 0x00, 0x00, 0x00],
+    holes: &[(4, Hole::D)],
+};
+
+/// `xmm0` += `xmm1`
+///
+/// ```text
+/// addsd xmm0, xmm1
+/// ```
+pub const FADD: Stencil = Stencil {
+    code: &[0xf2, 0x0f, 0x58, 0xc1],
+    holes: &[],
+};
+
+/// `xmm0` -= `xmm1`
+///
+/// ```text
+/// subsd xmm0, xmm1
+/// ```
+pub const FSUB: Stencil = Stencil {
+    code: &[0xf2, 0x0f, 0x5c, 0xc1],
+    holes: &[],
+};
+
+/// `xmm0` *= `xmm1`
+///
+/// ```text
+/// mulsd xmm0, xmm1
+/// ```
+pub const FMUL: Stencil = Stencil {
+    code: &[0xf2, 0x0f, 0x59, 0xc1],
+    holes: &[],
+};
+
+/// `xmm0` /= `xmm1`
+///
+/// ```text
+/// divsd xmm0, xmm1
+/// ```
+pub const FDIV: Stencil = Stencil {
+    code: &[0xf2, 0x0f, 0x5e, 0xc1],
+    holes: &[],
+};
+
+/// `xmm0` = the `extern "C" fn(f64, f64) -> f64` at `Imm64` applied to `xmm0` and `xmm1`
+///
+/// ```text
+/// movabs rax, IMM64
+/// call rax
+/// ```
+pub const FCALL: Stencil = Stencil {
+    code: &[
+        0x48, 0xb8, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xff, 0xd0,
+    ],
+    holes: &[(2, Hole::Imm64)],
+};
+
+/// `xmm0` = -`xmm0`
+///
+/// ```text
+/// movq rax, xmm0
+/// btc rax, 63
+/// movq xmm0, rax
+/// ```
+pub const FNEG: Stencil = Stencil {
+    code: &[
+        0x66, 0x48, 0x0f, 0x7e, 0xc0, 0x48, 0x0f, 0xba, 0xf8, 0x3f, 0x66, 0x48, 0x0f, 0x6e, 0xc0,
+    ],
+    holes: &[],
+};
+
+/// `rax` = (`xmm0` == `xmm1`), false if either is NaN
+///
+/// ```text
+/// ucomisd xmm0, xmm1
+/// sete al
+/// setnp cl
+/// and al, cl
+/// movzx eax, al
+/// ```
+pub const FCMP_EQ: Stencil = Stencil {
+    code: &[
+        0x66, 0x0f, 0x2e, 0xc1, 0x0f, 0x94, 0xc0, 0x0f, 0x9b, 0xc1, 0x20, 0xc8, 0x0f, 0xb6, 0xc0,
+    ],
+    holes: &[],
+};
+
+/// `rax` = (`xmm0` != `xmm1`), true if either is NaN
+///
+/// ```text
+/// ucomisd xmm0, xmm1
+/// setne al
+/// setp cl
+/// or al, cl
+/// movzx eax, al
+/// ```
+pub const FCMP_NE: Stencil = Stencil {
+    code: &[
+        0x66, 0x0f, 0x2e, 0xc1, 0x0f, 0x95, 0xc0, 0x0f, 0x9a, 0xc1, 0x08, 0xc8, 0x0f, 0xb6, 0xc0,
+    ],
+    holes: &[],
+};
+
+/// `rax` = (`xmm0` < `xmm1`)
+///
+/// ```text
+/// ucomisd xmm1, xmm0
+/// seta al
+/// movzx eax, al
+/// ```
+pub const FCMP_LT: Stencil = Stencil {
+    code: &[0x66, 0x0f, 0x2e, 0xc8, 0x0f, 0x97, 0xc0, 0x0f, 0xb6, 0xc0],
+    holes: &[],
+};
+
+/// `rax` = (`xmm0` <= `xmm1`)
+///
+/// ```text
+/// ucomisd xmm1, xmm0
+/// setae al
+/// movzx eax, al
+/// ```
+pub const FCMP_LE: Stencil = Stencil {
+    code: &[0x66, 0x0f, 0x2e, 0xc8, 0x0f, 0x93, 0xc0, 0x0f, 0xb6, 0xc0],
+    holes: &[],
+};
+
+/// `rax` = (`xmm0` > `xmm1`)
+///
+/// ```text
+/// ucomisd xmm0, xmm1
+/// seta al
+/// movzx eax, al
+/// ```
+pub const FCMP_GT: Stencil = Stencil {
+    code: &[0x66, 0x0f, 0x2e, 0xc1, 0x0f, 0x97, 0xc0, 0x0f, 0xb6, 0xc0],
+    holes: &[],
+};
+
+/// `rax` = (`xmm0` >= `xmm1`)
+///
+/// ```text
+/// ucomisd xmm0, xmm1
+/// setae al
+/// movzx eax, al
+/// ```
+pub const FCMP_GE: Stencil = Stencil {
+    code: &[0x66, 0x0f, 0x2e, 0xc1, 0x0f, 0x93, 0xc0, 0x0f, 0xb6, 0xc0],
+    holes: &[],
+};
+
+/// Jump
+///
+/// ```text
+/// jmp target
+/// ```
+pub const JMP: Stencil = Stencil {
+    code: &[0xe9, 0x00, 0x00, 0x00, 0x00],
+    holes: &[(1, Hole::Rel32)],
+};
+
+/// Jump if slot `A` is non-zero
+///
+/// ```text
+/// cmp qword ptr [rbx + A], 0
+/// jne target
+/// ```
+pub const JNZ: Stencil = Stencil {
+    code: &[
+        0x48, 0x83, 0xbb, 0x00, 0x00, 0x00, 0x00, 0x00, 0x0f, 0x85, 0x00, 0x00, 0x00, 0x00,
+    ],
+    holes: &[(3, Hole::A), (10, Hole::Rel32)],
+};
+
+/// Jump if slot `A` is zero
+///
+/// ```text
+/// cmp qword ptr [rbx + A], 0
+/// je target
+/// ```
+pub const JZ: Stencil = Stencil {
+    code: &[
+        0x48, 0x83, 0xbb, 0x00, 0x00, 0x00, 0x00, 0x00, 0x0f, 0x84, 0x00, 0x00, 0x00, 0x00,
+    ],
+    holes: &[(3, Hole::A), (10, Hole::Rel32)],
+};
+
+/// Jump if `rax` equals `Imm64`
+///
+/// ```text
+/// movabs rcx, IMM64
+/// cmp rax, rcx
+/// je target
+/// ```
+pub const CASE: Stencil = Stencil {
+    code: &[
+        0x48, 0xb9, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x48, 0x39, 0xc8, 0x0f, 0x84,
+        0x00, 0x00, 0x00, 0x00,
+    ],
+    holes: &[(2, Hole::Imm64), (15, Hole::Rel32)],
+};
+
+/// Call the function at the target, passing the arguments stored from slot `A` on
+///
+/// ```text
+/// mov rdi, r12
+/// lea rsi, qword ptr [rbx + A]
+/// call target
+/// ```
+pub const CALL: Stencil = Stencil {
+    code: &[
+        0x4c, 0x89, 0xe7, 0x48, 0x8d, 0xb3, 0x00, 0x00, 0x00, 0x00, 0xe8, 0x00, 0x00, 0x00, 0x00,
+    ],
+    holes: &[(6, Hole::A), (11, Hole::Rel32)],
+};
+
+/// Jump if the context is flagged (a callee gave up)
+///
+/// ```text
+/// cmp byte ptr [r12], 0
+/// jne target
+/// ```
+pub const CHECK: Stencil = Stencil {
+    code: &[
+        0x41, 0x80, 0x3c, 0x24, 0x00, 0x0f, 0x85, 0x00, 0x00, 0x00, 0x00,
+    ],
+    holes: &[(7, Hole::Rel32)],
+};
+
+/// Return `rax`
+///
+/// ```text
+/// inc qword ptr [r12 + 8]
+/// lea rsp, [rbp - 16]
+/// pop r12
+/// pop rbx
+/// pop rbp
+/// ret
+/// ```
+pub const RETURN: Stencil = Stencil {
+    code: &[
+        0x49, 0xff, 0x44, 0x24, 0x08, 0x48, 0x8d, 0x65, 0xf0, 0x41, 0x5c, 0x5b, 0x5d, 0xc3,
+    ],
+    holes: &[],
+};
+
+/// Flag the context and return
+///
+/// ```text
+/// mov byte ptr [r12], 1
+/// lea rsp, [rbp - 16]
+/// pop r12
+/// pop rbx
+/// pop rbp
+/// ret
+/// ```
+pub const DEOPT: Stencil = Stencil {
+    code: &[
+        0x41, 0xc6, 0x04, 0x24, 0x01, 0x48, 0x8d, 0x65, 0xf0, 0x41, 0x5c, 0x5b, 0x5d, 0xc3,
+    ],
+    holes: &[],
+};
@@ -0,0 +1,322 @@
+//! 模板 JIT 测试
+//!
+//! 测试覆盖内容：
+//! - 模板的洞都落在机器码之内
+//! - 递归、循环、整数/浮点/布尔运算、跳转表编译为机器码，结果与解释器一致
+//! - 调试模式下溢出、除零、递归过深退回解释器，发布模式下溢出回绕
+//! - 不支持的函数及调用它们的函数不被编译
+//! - 参数类型不符时不调用机器码
+
+use super::{Outcome, Program, TemplateCode};
+use crate::backends::common::RuntimeValue;
+use crate::backends::interpreter::Interpreter;
+use crate::backends::Executor;
+use crate::frontend::{CompileConfig, Compiler};
+use crate::middle::bytecode::{BytecodeModule, ConstValue, FunctionRef};
+use crate::middle::passes::codegen::CodegenContext;
+
+const SOURCE: &str = r#"
+fib: (n: Int) -> Int = {
+    if n < 2 {
+        return n
+    }
+    return fib(n - 1) + fib(n - 2)
+}
+
+sum_to: (n: Int) -> Int = {
+    mut total = 0
+    mut i = 0
+    while i < n {
+        total = total + i * i
+        i = i + 1
+    }
+    return total
+}
+
+mul: (a: Int, b: Int) -> Int = {
+    return a * b
+}
+
+div: (a: Int, b: Int) -> Int = {
+    return a / b
+}
+
+rem: (a: Int, b: Int) -> Int = {
+    return a % b
+}
+
+neg: (a: Int) -> Int = {
+    return -a
+}
+
+poly: (x: Float, y: Float) -> Float = {
+    return x * x + y * y - x / y
+}
+
+frem: (x: Float, y: Float) -> Float = {
+    return x % y
+}
+
+less: (x: Float, y: Float) -> Bool = {
+    return x < y
+}
+
+differs: (x: Float, y: Float) -> Bool = {
+    return x != y
+}
+
+keep: (b: Bool) -> Bool = {
+    return b
+}
+
+sign: (x: Float) -> Int = {
+    if x < 0.0 {
+        return -1
+    }
+    return 1
+}
+
+classify: (n: Int) -> Int = {
+    match n {
+        10 | 11 => { return 1 },
+        12 => { return 2 },
+        13 | 14 => { return 3 },
+        _ => { return 0 }
+    }
+    return -1
+}
+
+name: () -> String = {
+    return "fib"
+}
+
+greet: (n: Int) -> Int = {
+    print(name())
+    return n
+}
+
+main: () -> Void = {
+    print(fib(10))
+    print(sum_to(3))
+    print(mul(2, 3))
+    print(div(6, 3))
+    print(rem(7, 3))
+    print(neg(1))
+    print(poly(1.0, 2.0))
+    print(frem(1.0, 2.0))
+    print(less(1.0, 2.0))
+    print(differs(1.0, 2.0))
+    print(keep(true))
+    print(sign(1.0))
+    print(classify(12))
+    print(greet(1))
+}
+"#;
+
+fn bytecode() -> BytecodeModule {
+    let module = Compiler::with_config(CompileConfig::new().with_tree_shaking(false))
+        .compile("template.yx", SOURCE)
+        .expect("source should compile");
+    let mut ctx = CodegenContext::new(module);
+    BytecodeModule::from(ctx.generate().expect("codegen should succeed"))
+}
+
+fn compile(
+    module: &BytecodeModule,
+    traps_overflow: bool,
+) -> TemplateCode {
+    let callee_name = |func: &FunctionRef| match func {
+        FunctionRef::Static { name, .. } => name.clone(),
+        FunctionRef::Index(idx) => match module.constants.get(*idx as usize) {
+            Some(ConstValue::String(s)) => s.clone(),
+            _ => format!("fn_{}", idx),
+        },
+    };
+    TemplateCode::compile(&Program {
+        functions: module.functions.iter().collect(),
+        constants: &module.constants,
+        callee_name: &callee_name,
+        traps_overflow,
+    })
+}
+
+/// Run `name` both ways and check the machine code returns what the interpreter does
+fn assert_matches_interpreter(
+    module: &BytecodeModule,
+    code: &TemplateCode,
+    name: &str,
+    args: &[RuntimeValue],
+) {
+    assert!(code.is_compiled(name), "{} should be compiled", name);
+    let mut interpreter = Interpreter::new();
+    interpreter
+        .execute_module(module)
+        .expect("module should run");
+    let func = module
+        .functions
+        .iter()
+        .find(|f| f.name == name)
+        .expect("function in module");
+    let expected = interpreter
+        .execute_function(func, args)
+        .expect("function should run");
+    match code.call(name, args, 1000) {
+        Some(Outcome::Returned(value)) => {
+            // NaN results compare unequal to themselves
+            let same = match (&value, &expected) {
+                (RuntimeValue::Float(a), RuntimeValue::Float(b)) => {
+                    a.to_bits() == b.to_bits() || (a.is_nan() && b.is_nan())
+                }
+                _ => value == expected,
+            };
+            assert!(same, "{}{:?}: {:?} != {:?}", name, args, value, expected);
+        }
+        other => panic!(
+            "{}{:?}: expected a return value, got {:?}",
+            name, args, other
+        ),
+    }
+}
+
+#[test]
+fn test_stencil_holes_fit() {
+    use super::stencils::{self, Hole};
+    for stencil in [
+        &stencils::PROLOGUE,
+        &stencils::POLL,
+        &stencils::DIV_CHECKED,
+        &stencils::REM_CHECKED,
+        &stencils::CASE,
+        &stencils::CALL,
+    ] {
+        for &(offset, hole) in stencil.holes {
+            let size = if hole == Hole::Imm64 { 8 } else { 4 };
+            assert!(offset + size <= stencil.code.len());
+        }
+    }
+    // A jump's displacement ends the stencil, so it is relative to what follows
+    for stencil in [&stencils::JMP, &stencils::CALL, &stencils::CHECK] {
+        assert_eq!(stencil.holes.last().unwrap().0 + 4, stencil.code.len());
+    }
+}
+
+#[test]
+fn test_integer_functions_match_interpreter() {
+    let module = bytecode();
+    let code = compile(&module, true);
+    let int = RuntimeValue::Int;
+    assert_matches_interpreter(&module, &code, "fib", &[int(20)]);
+    for n in [0, 1, 10, 1000] {
+        assert_matches_interpreter(&module, &code, "sum_to", &[int(n)]);
+    }
+    for (a, b) in [(7, 2), (-7, 2), (7, -2), (i64::MIN, 1), (0, 5)] {
+        assert_matches_interpreter(&module, &code, "div", &[int(a), int(b)]);
+        assert_matches_interpreter(&module, &code, "rem", &[int(a), int(b)]);
+    }
+    assert_matches_interpreter(&module, &code, "neg", &[int(5)]);
+    for n in [9, 10, 11, 12, 13, 14, 15, -3] {
+        assert_matches_interpreter(&module, &code, "classify", &[int(n)]);
+    }
+}
+
+#[test]
+fn test_float_and_bool_functions_match_interpreter() {
+    let module = bytecode();
+    let code = compile(&module, true);
+    let float = RuntimeValue::Float;
+    for (x, y) in [
+        (1.5, 2.0),
+        (-3.0, 0.5),
+        (1.0, 0.0),
+        (f64::NAN, 1.0),
+        (f64::INFINITY, 2.0),
+    ] {
+        for name in ["poly", "frem", "less", "differs"] {
+            assert_matches_interpreter(&module, &code, name, &[float(x), float(y)]);
+        }
+    }
+    for x in [-2.5, 0.0, 3.0, f64::NAN] {
+        assert_matches_interpreter(&module, &code, "sign", &[float(x)]);
+    }
+    for b in [true, false] {
+        assert_matches_interpreter(&module, &code, "keep", &[RuntimeValue::Bool(b)]);
+    }
+}
+
+#[test]
+fn test_overflow_deopts_when_trapping() {
+    let module = bytecode();
+    let int = RuntimeValue::Int;
+
+    let trapping = compile(&module, true);
+    for (name, args) in [
+        ("mul", vec![int(i64::MAX), int(2)]),
+        ("div", vec![int(i64::MIN), int(-1)]),
+        ("rem", vec![int(i64::MIN), int(-1)]),
+        ("neg", vec![int(i64::MIN)]),
+    ] {
+        assert_eq!(
+            trapping.call(name, &args, 10),
+            Some(Outcome::Deopt),
+            "{}",
+            name
+        );
+    }
+
+    let wrapping = compile(&module, false);
+    for (name, args, expected) in [
+        ("mul", vec![int(i64::MAX), int(2)], i64::MAX.wrapping_mul(2)),
+        ("div", vec![int(i64::MIN), int(-1)], i64::MIN),
+        ("rem", vec![int(i64::MIN), int(-1)], 0),
+        ("neg", vec![int(i64::MIN)], i64::MIN),
+    ] {
+        assert_eq!(
+            wrapping.call(name, &args, 10),
+            Some(Outcome::Returned(int(expected))),
+            "{}",
+            name
+        );
+    }
+}
+
+#[test]
+fn test_division_by_zero_deopts() {
+    let module = bytecode();
+    let code = compile(&module, false);
+    let zero = [RuntimeValue::Int(1), RuntimeValue::Int(0)];
+    assert_eq!(code.call("div", &zero, 10), Some(Outcome::Deopt));
+    assert_eq!(code.call("rem", &zero, 10), Some(Outcome::Deopt));
+}
+
+#[test]
+fn test_recursion_depth_deopts() {
+    let module = bytecode();
+    let code = compile(&module, true);
+    assert_eq!(
+        code.call("fib", &[RuntimeValue::Int(20)], 5),
+        Some(Outcome::Deopt)
+    );
+    // The call level budget is restored as calls return
+    assert_eq!(
+        code.call("fib", &[RuntimeValue::Int(4)], 4),
+        Some(Outcome::Returned(RuntimeValue::Int(3)))
+    );
+}
+
+#[test]
+fn test_unsupported_functions_not_compiled() {
+    let module = bytecode();
+    let code = compile(&module, true);
+    assert!(!code.is_compiled("name"));
+    assert!(!code.is_compiled("greet"));
+    assert!(!code.is_compiled("main"));
+    assert!(code.call("greet", &[RuntimeValue::Int(1)], 10).is_none());
+}
+
+#[test]
+fn test_argument_mismatch_not_called() {
+    let module = bytecode();
+    let code = compile(&module, true);
+    assert!(code.call("fib", &[RuntimeValue::Float(1.0)], 10).is_none());
+    assert!(code.call("fib", &[], 10).is_none());
+}
@@ -0,0 +1,584 @@
+//! Minimal WebAssembly binary encoder
+//!
+//! Covers exactly what the backend emits: function types, function imports, one
+//! linear memory, mutable `i32` globals, exports, code and active data segments.
+
+/// Value type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ValType {
+    I32,
+    I64,
+    F64,
+}
+
+impl ValType {
+    fn byte(self) -> u8 {
+        match self {
+            ValType::I32 => 0x7F,
+            ValType::I64 => 0x7E,
+            ValType::F64 => 0x7C,
+        }
+    }
+}
+
+/// Opcodes without immediates, emitted through [`Code::op`]
+pub mod op {
+    pub const UNREACHABLE: u8 = 0x00;
+    pub const RETURN: u8 = 0x0F;
+    pub const DROP: u8 = 0x1A;
+    pub const SELECT: u8 = 0x1B;
+
+    pub const I32_EQZ: u8 = 0x45;
+    pub const I32_EQ: u8 = 0x46;
+    pub const I32_NE: u8 = 0x47;
+    pub const I32_LT_U: u8 = 0x49;
+    pub const I32_GT_U: u8 = 0x4B;
+    pub const I32_GE_U: u8 = 0x4F;
+
+    pub const I64_EQZ: u8 = 0x50;
+    pub const I64_EQ: u8 = 0x51;
+    pub const I64_NE: u8 = 0x52;
+    pub const I64_LT_S: u8 = 0x53;
+    pub const I64_GT_S: u8 = 0x55;
+    pub const I64_LE_S: u8 = 0x57;
+    pub const I64_GE_S: u8 = 0x59;
+    pub const I64_GE_U: u8 = 0x5A;
+
+    pub const F64_EQ: u8 = 0x61;
+    pub const F64_NE: u8 = 0x62;
+    pub const F64_LT: u8 = 0x63;
+    pub const F64_GT: u8 = 0x64;
+    pub const F64_LE: u8 = 0x65;
+    pub const F64_GE: u8 = 0x66;
+
+    pub const I32_ADD: u8 = 0x6A;
+    pub const I32_SUB: u8 = 0x6B;
+    pub const I32_AND: u8 = 0x71;
+    pub const I32_OR: u8 = 0x72;
+    pub const I32_SHL: u8 = 0x74;
+    pub const I32_SHR_U: u8 = 0x76;
+
+    pub const I64_ADD: u8 = 0x7C;
+    pub const I64_SUB: u8 = 0x7D;
+    pub const I64_MUL: u8 = 0x7E;
+    pub const I64_DIV_S: u8 = 0x7F;
+    pub const I64_DIV_U: u8 = 0x80;
+    pub const I64_REM_S: u8 = 0x81;
+    pub const I64_REM_U: u8 = 0x82;
+    pub const I64_AND: u8 = 0x83;
+    pub const I64_OR: u8 = 0x84;
+    pub const I64_XOR: u8 = 0x85;
+    pub const I64_SHL: u8 = 0x86;
+    pub const I64_SHR_S: u8 = 0x87;
+
+    pub const F64_NEG: u8 = 0x9A;
+    pub const F64_TRUNC: u8 = 0x9D;
+    pub const F64_ADD: u8 = 0xA0;
+    pub const F64_SUB: u8 = 0xA1;
+    pub const F64_MUL: u8 = 0xA2;
+    pub const F64_DIV: u8 = 0xA3;
+
+    pub const I32_WRAP_I64: u8 = 0xA7;
+    pub const I64_EXTEND_I32_U: u8 = 0xAD;
+    pub const F64_CONVERT_I64_S: u8 = 0xB9;
+    pub const I64_REINTERPRET_F64: u8 = 0xBD;
+    pub const F64_REINTERPRET_I64: u8 = 0xBF;
+}
+
+/// Unsigned LEB128
+pub fn write_uleb(
+    out: &mut Vec<u8>,
+    mut value: u64,
+) {
+    loop {
+        let byte = (value & 0x7F) as u8;
+        value >>= 7;
+        if value == 0 {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+/// Signed LEB128
+pub fn write_sleb(
+    out: &mut Vec<u8>,
+    mut value: i64,
+) {
+    loop {
+        let byte = (value & 0x7F) as u8;
+        value >>= 7;
+        let done = (value == 0 && byte & 0x40 == 0) || (value == -1 && byte & 0x40 != 0);
+        if done {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+fn write_name(
+    out: &mut Vec<u8>,
+    name: &str,
+) {
+    write_uleb(out, name.len() as u64);
+    out.extend_from_slice(name.as_bytes());
+}
+
+/// Instruction sequence of one function body
+#[derive(Debug, Clone, Default)]
+pub struct Code {
+    bytes: Vec<u8>,
+}
+
+impl Code {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Opcode without immediates (see [`op`])
+    pub fn op(
+        &mut self,
+        opcode: u8,
+    ) -> &mut Self {
+        self.bytes.push(opcode);
+        self
+    }
+
+    fn with_index(
+        &mut self,
+        opcode: u8,
+        index: u32,
+    ) -> &mut Self {
+        self.bytes.push(opcode);
+        write_uleb(&mut self.bytes, index as u64);
+        self
+    }
+
+    pub fn block(&mut self) -> &mut Self {
+        self.bytes.extend_from_slice(&[0x02, 0x40]);
+        self
+    }
+
+    pub fn loop_(&mut self) -> &mut Self {
+        self.bytes.extend_from_slice(&[0x03, 0x40]);
+        self
+    }
+
+    /// `if` without results
+    pub fn if_(&mut self) -> &mut Self {
+        self.bytes.extend_from_slice(&[0x04, 0x40]);
+        self
+    }
+
+    pub fn else_(&mut self) -> &mut Self {
+        self.bytes.push(0x05);
+        self
+    }
+
+    pub fn end(&mut self) -> &mut Self {
+        self.bytes.push(0x0B);
+        self
+    }
+
+    pub fn br(
+        &mut self,
+        depth: u32,
+    ) -> &mut Self {
+        self.with_index(0x0C, depth)
+    }
+
+    pub fn br_if(
+        &mut self,
+        depth: u32,
+    ) -> &mut Self {
+        self.with_index(0x0D, depth)
+    }
+
+    pub fn br_table(
+        &mut self,
+        depths: &[u32],
+        default: u32,
+    ) -> &mut Self {
+        self.bytes.push(0x0E);
+        write_uleb(&mut self.bytes, depths.len() as u64);
+        for depth in depths {
+            write_uleb(&mut self.bytes, *depth as u64);
+        }
+        write_uleb(&mut self.bytes, default as u64);
+        self
+    }
+
+    pub fn call(
+        &mut self,
+        func: u32,
+    ) -> &mut Self {
+        self.with_index(0x10, func)
+    }
+
+    pub fn local_get(
+        &mut self,
+        local: u32,
+    ) -> &mut Self {
+        self.with_index(0x20, local)
+    }
+
+    pub fn local_set(
+        &mut self,
+        local: u32,
+    ) -> &mut Self {
+        self.with_index(0x21, local)
+    }
+
+    pub fn local_tee(
+        &mut self,
+        local: u32,
+    ) -> &mut Self {
+        self.with_index(0x22, local)
+    }
+
+    pub fn global_get(
+        &mut self,
+        global: u32,
+    ) -> &mut Self {
+        self.with_index(0x23, global)
+    }
+
+    pub fn global_set(
+        &mut self,
+        global: u32,
+    ) -> &mut Self {
+        self.with_index(0x24, global)
+    }
+
+    fn memory_access(
+        &mut self,
+        opcode: u8,
+        align: u32,
+        offset: u32,
+    ) -> &mut Self {
+        self.bytes.push(opcode);
+        write_uleb(&mut self.bytes, align as u64);
+        write_uleb(&mut self.bytes, offset as u64);
+        self
+    }
+
+    pub fn i32_load(
+        &mut self,
+        offset: u32,
+    ) -> &mut Self {
+        self.memory_access(0x28, 2, offset)
+    }
+
+    pub fn i64_load(
+        &mut self,
+        offset: u32,
+    ) -> &mut Self {
+        self.memory_access(0x29, 3, offset)
+    }
+
+    pub fn i32_load8_u(
+        &mut self,
+        offset: u32,
+    ) -> &mut Self {
+        self.memory_access(0x2D, 0, offset)
+    }
+
+    pub fn i32_store(
+        &mut self,
+        offset: u32,
+    ) -> &mut Self {
+        self.memory_access(0x36, 2, offset)
+    }
+
+    pub fn i64_store(
+        &mut self,
+        offset: u32,
+    ) -> &mut Self {
+        self.memory_access(0x37, 3, offset)
+    }
+
+    pub fn i32_store8(
+        &mut self,
+        offset: u32,
+    ) -> &mut Self {
+        self.memory_access(0x3A, 0, offset)
+    }
+
+    pub fn memory_size(&mut self) -> &mut Self {
+        self.bytes.extend_from_slice(&[0x3F, 0x00]);
+        self
+    }
+
+    pub fn memory_grow(&mut self) -> &mut Self {
+        self.bytes.extend_from_slice(&[0x40, 0x00]);
+        self
+    }
+
+    /// `memory.copy` (bulk memory): `[dst, src, len] -> []`
+    pub fn memory_copy(&mut self) -> &mut Self {
+        self.bytes.extend_from_slice(&[0xFC, 0x0A, 0x00, 0x00]);
+        self
+    }
+
+    pub fn i32_const(
+        &mut self,
+        value: i32,
+    ) -> &mut Self {
+        self.bytes.push(0x41);
+        write_sleb(&mut self.bytes, value as i64);
+        self
+    }
+
+    pub fn i64_const(
+        &mut self,
+        value: i64,
+    ) -> &mut Self {
+        self.bytes.push(0x42);
+        write_sleb(&mut self.bytes, value);
+        self
+    }
+}
+
+/// Function definition: signature, extra locals and body
+#[derive(Debug, Clone)]
+pub struct Function {
+    pub params: Vec<ValType>,
+    pub results: Vec<ValType>,
+    /// Locals after the parameters
+    pub locals: Vec<ValType>,
+    pub code: Code,
+}
+
+struct Import {
+    module: String,
+    name: String,
+    type_index: u32,
+}
+
+/// Module under construction
+///
+/// Imported functions come first in the function index space, so all imports must
+/// be added before any function is defined.
+#[derive(Default)]
+pub struct Module {
+    types: Vec<(Vec<ValType>, Vec<ValType>)>,
+    imports: Vec<Import>,
+    functions: Vec<Option<Function>>,
+    globals: Vec<i32>,
+    exports: Vec<(String, u8, u32)>,
+    data: Vec<(u32, Vec<u8>)>,
+    memory_pages: u32,
+}
+
+impl Module {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn type_index(
+        &mut self,
+        params: &[ValType],
+        results: &[ValType],
+    ) -> u32 {
+        if let Some(index) = self
+            .types
+            .iter()
+            .position(|(p, r)| p == params && r == results)
+        {
+            return index as u32;
+        }
+        self.types.push((params.to_vec(), results.to_vec()));
+        (self.types.len() - 1) as u32
+    }
+
+    /// Import a host function, returning its function index
+    pub fn import_function(
+        &mut self,
+        module: &str,
+        name: &str,
+        params: &[ValType],
+        results: &[ValType],
+    ) -> u32 {
+        debug_assert!(
+            self.functions.is_empty(),
+            "imports must precede definitions"
+        );
+        let type_index = self.type_index(params, results);
+        self.imports.push(Import {
+            module: module.to_string(),
+            name: name.to_string(),
+            type_index,
+        });
+        (self.imports.len() - 1) as u32
+    }
+
+    /// Reserve a function index to be filled in by [`Module::define`]
+    pub fn reserve_function(&mut self) -> u32 {
+        self.functions.push(None);
+        (self.imports.len() + self.functions.len() - 1) as u32
+    }
+
+    /// Give the reserved function `index` its definition
+    pub fn define(
+        &mut self,
+        index: u32,
+        function: Function,
+    ) {
+        self.functions[index as usize - self.imports.len()] = Some(function);
+    }
+
+    /// Mutable `i32` global, returning its index
+    pub fn add_global(
+        &mut self,
+        init: i32,
+    ) -> u32 {
+        self.globals.push(init);
+        (self.globals.len() - 1) as u32
+    }
+
+    pub fn set_global(
+        &mut self,
+        index: u32,
+        init: i32,
+    ) {
+        self.globals[index as usize] = init;
+    }
+
+    pub fn set_memory_pages(
+        &mut self,
+        pages: u32,
+    ) {
+        self.memory_pages = pages;
+    }
+
+    pub fn export_function(
+        &mut self,
+        name: &str,
+        index: u32,
+    ) {
+        self.exports.push((name.to_string(), 0x00, index));
+    }
+
+    pub fn export_memory(
+        &mut self,
+        name: &str,
+    ) {
+        self.exports.push((name.to_string(), 0x02, 0));
+    }
+
+    /// Active data segment placed at `offset` in memory 0
+    pub fn add_data(
+        &mut self,
+        offset: u32,
+        bytes: Vec<u8>,
+    ) {
+        self.data.push((offset, bytes));
+    }
+
+    /// Encode the module; every reserved function must have been defined
+    pub fn finish(mut self) -> Vec<u8> {
+        let functions: Vec<Function> = std::mem::take(&mut self.functions)
+            .into_iter()
+            .map(|function| function.expect("reserved wasm function left undefined"))
+            .collect();
+        let function_types: Vec<u32> = functions
+            .iter()
+            .map(|f| self.type_index(&f.params, &f.results))
+            .collect();
+
+        let mut out = b"\0asm".to_vec();
+        out.extend_from_slice(&1u32.to_le_bytes());
+
+        section(&mut out, 1, self.types.len(), |body| {
+            for (params, results) in &self.types {
+                body.push(0x60);
+                write_uleb(body, params.len() as u64);
+                body.extend(params.iter().map(|t| t.byte()));
+                write_uleb(body, results.len() as u64);
+                body.extend(results.iter().map(|t| t.byte()));
+            }
+        });
+        section(&mut out, 2, self.imports.len(), |body| {
+            for import in &self.imports {
+                write_name(body, &import.module);
+                write_name(body, &import.name);
+                body.push(0x00);
+                write_uleb(body, import.type_index as u64);
+            }
+        });
+        section(&mut out, 3, function_types.len(), |body| {
+            for type_index in &function_types {
+                write_uleb(body, *type_index as u64);
+            }
+        });
+        section(&mut out, 5, 1, |body| {
+            body.push(0x00);
+            write_uleb(body, self.memory_pages as u64);
+        });
+        section(&mut out, 6, self.globals.len(), |body| {
+            for init in &self.globals {
+                body.extend_from_slice(&[ValType::I32.byte(), 0x01, 0x41]);
+                write_sleb(body, *init as i64);
+                body.push(0x0B);
+            }
+        });
+        section(&mut out, 7, self.exports.len(), |body| {
+            for (name, kind, index) in &self.exports {
+                write_name(body, name);
+                body.push(*kind);
+                write_uleb(body, *index as u64);
+            }
+        });
+        section(&mut out, 10, functions.len(), |body| {
+            for function in &functions {
+                let mut entry = Vec::new();
+                let mut runs: Vec<(u32, ValType)> = Vec::new();
+                for ty in &function.locals {
+                    match runs.last_mut() {
+                        Some((count, last)) if last == ty => *count += 1,
+                        _ => runs.push((1, *ty)),
+                    }
+                }
+                write_uleb(&mut entry, runs.len() as u64);
+                for (count, ty) in runs {
+                    write_uleb(&mut entry, count as u64);
+                    entry.push(ty.byte());
+                }
+                entry.extend_from_slice(&function.code.bytes);
+                entry.push(0x0B);
+                write_uleb(body, entry.len() as u64);
+                body.extend_from_slice(&entry);
+            }
+        });
+        section(&mut out, 11, self.data.len(), |body| {
+            for (offset, bytes) in &self.data {
+                body.extend_from_slice(&[0x00, 0x41]);
+                write_sleb(body, *offset as i64);
+                body.push(0x0B);
+                write_uleb(body, bytes.len() as u64);
+                body.extend_from_slice(bytes);
+            }
+        });
+        out
+    }
+}
+
+/// Write a section holding `count` entries; empty sections are omitted
+fn section(
+    out: &mut Vec<u8>,
+    id: u8,
+    count: usize,
+    write_entries: impl FnOnce(&mut Vec<u8>),
+) {
+    if count == 0 {
+        return;
+    }
+    let mut body = Vec::new();
+    write_uleb(&mut body, count as u64);
+    write_entries(&mut body);
+    out.push(id);
+    write_uleb(out, body.len() as u64);
+    out.extend_from_slice(&body);
+}
@@ -0,0 +1,106 @@
+//! Static value kinds
+//!
+//! Every value is an `i64` in the generated code, so printing and string comparison
+//! need to know statically what the bits stand for. The IR's local types cannot be
+//! trusted for this (register allocation reuses a register for values of different
+//! types), so kinds are inferred per program point by a forward dataflow.
+
+use crate::frontend::core::typecheck::inference::numeric::numeric_type_named;
+use crate::frontend::core::typecheck::MonoType;
+use crate::middle::core::ir::ConstValue;
+
+/// Nesting limit for list kinds, so the dataflow terminates on self-nesting lists
+const MAX_DEPTH: usize = 8;
+
+/// What an `i64` value stands for
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Kind {
+    /// No value reaches this point (uninitialized, or the items of an empty list)
+    Nothing,
+    Int,
+    Float,
+    Bool,
+    Char,
+    Str,
+    List(Box<Kind>),
+    Void,
+    /// Conflicting or untracked kinds
+    Unknown,
+}
+
+impl Kind {
+    pub fn from_mono(ty: &MonoType) -> Kind {
+        match ty {
+            MonoType::Int(_) => Kind::Int,
+            MonoType::Float(_) => Kind::Float,
+            MonoType::Bool => Kind::Bool,
+            MonoType::Char => Kind::Char,
+            MonoType::String => Kind::Str,
+            MonoType::Void => Kind::Void,
+            MonoType::List(item) => Kind::List(Box::new(Kind::from_mono(item))),
+            // Built-in types often appear by name, e.g. `TypeRef("Int")`
+            MonoType::TypeRef(name) => match name.as_str() {
+                "Bool" => Kind::Bool,
+                "Char" => Kind::Char,
+                "String" => Kind::Str,
+                "Void" => Kind::Void,
+                _ => numeric_type_named(name).map_or(Kind::Unknown, |ty| Kind::from_mono(&ty)),
+            },
+            _ => Kind::Unknown,
+        }
+    }
+
+    pub fn of_const(value: &ConstValue) -> Kind {
+        match value {
+            ConstValue::Void => Kind::Void,
+            ConstValue::Bool(_) => Kind::Bool,
+            ConstValue::Int(_) => Kind::Int,
+            ConstValue::Float(_) => Kind::Float,
+            ConstValue::Char(_) => Kind::Char,
+            ConstValue::String(_) => Kind::Str,
+            ConstValue::List(items) => Kind::list_of(
+                items
+                    .iter()
+                    .fold(Kind::Nothing, |kind, item| kind.join(&Kind::of_const(item))),
+            ),
+            _ => Kind::Unknown,
+        }
+    }
+
+    /// List of `item`, collapsing to `Unknown` past the nesting limit
+    pub fn list_of(item: Kind) -> Kind {
+        if item.depth() >= MAX_DEPTH {
+            Kind::Unknown
+        } else {
+            Kind::List(Box::new(item))
+        }
+    }
+
+    fn depth(&self) -> usize {
+        match self {
+            Kind::List(item) => 1 + item.depth(),
+            _ => 0,
+        }
+    }
+
+    /// Item kind of a list (`Unknown` for anything else)
+    pub fn item(&self) -> Kind {
+        match self {
+            Kind::List(item) => (**item).clone(),
+            _ => Kind::Unknown,
+        }
+    }
+
+    /// Least upper bound of two kinds
+    pub fn join(
+        &self,
+        other: &Kind,
+    ) -> Kind {
+        match (self, other) {
+            (Kind::Nothing, kind) | (kind, Kind::Nothing) => kind.clone(),
+            (Kind::List(a), Kind::List(b)) => Kind::list_of(a.join(b)),
+            (a, b) if a == b => a.clone(),
+            _ => Kind::Unknown,
+        }
+    }
+}
@@ -0,0 +1,838 @@
+//! Lowering of one IR function
+//!
+//! Parameters, registers and local variable slots all become `i64` wasm locals.
+//! The flattened instruction list is cut into segments at jump targets, and a
+//! `loop` around a `br_table` on a `pc` local dispatches between them, so IR jumps
+//! need no structured control-flow recovery.
+
+use std::collections::BTreeSet;
+
+use super::encoder::{op, Code, Function, ValType};
+use super::kinds::Kind;
+use super::{unsupported, Backend};
+use crate::middle::core::ir::{ConstValue, FunctionIR, Instruction, Operand};
+use crate::middle::passes::ssa::operands::all_operands;
+use crate::util::diagnostic::Diagnostic;
+
+/// Kind of every wasm local at one program point
+type State = Vec<Kind>;
+
+pub(super) fn lower_function(
+    backend: &mut Backend<'_>,
+    func: &FunctionIR,
+) -> Result<Function, Diagnostic> {
+    let instrs: Vec<&Instruction> = func.all_instructions().collect();
+    let len = instrs.len();
+
+    let mut leaders = BTreeSet::from([0]);
+    for (ip, instr) in instrs.iter().enumerate() {
+        for target in instr.jump_targets() {
+            if target > len {
+                return Err(unsupported(&format!(
+                    "a jump past the end of `{}`",
+                    func.name
+                )));
+            }
+            leaders.insert(target);
+        }
+        if ends_segment(instr) && ip + 1 < len {
+            leaders.insert(ip + 1);
+        }
+    }
+
+    let width = instrs
+        .iter()
+        .flat_map(|instr| slot_operands(instr).into_iter().chain(all_operands(instr)))
+        .filter_map(|operand| match operand {
+            Operand::Local(i) | Operand::Temp(i) | Operand::Arg(i) => Some(i + 1),
+            _ => None,
+        })
+        .chain([func.locals.len()])
+        .max()
+        .unwrap_or(0) as u32;
+
+    let mut lowering = Lowering {
+        backend,
+        func,
+        instrs,
+        leaders: leaders.into_iter().collect(),
+        params: func.params.len() as u32,
+        width,
+        code: Code::new(),
+    };
+    lowering.lower()?;
+
+    let mut locals = vec![ValType::I64; 2 * width as usize];
+    locals.push(ValType::I32);
+    Ok(Function {
+        params: vec![ValType::I64; func.params.len()],
+        results: vec![ValType::I64],
+        locals,
+        code: lowering.code,
+    })
+}
+
+/// Whether control never falls through to the next instruction
+fn ends_segment(instr: &Instruction) -> bool {
+    matches!(
+        instr,
+        Instruction::Jmp(_)
+            | Instruction::JmpIf(..)
+            | Instruction::JmpIfNot(..)
+            | Instruction::Switch { .. }
+            | Instruction::Ret(_)
+            | Instruction::TailCall { .. }
+    )
+}
+
+/// Whether control can fall through to the next instruction
+fn falls_through(instr: &Instruction) -> bool {
+    !matches!(
+        instr,
+        Instruction::Jmp(_)
+            | Instruction::Switch { .. }
+            | Instruction::Ret(_)
+            | Instruction::TailCall { .. }
+    )
+}
+
+/// Local variable slots accessed by `Load` / `Store` (not listed by `all_operands`)
+fn slot_operands(instr: &Instruction) -> Vec<&Operand> {
+    match instr {
+        Instruction::Load { src, .. } => vec![src],
+        Instruction::Store { dst, .. } => vec![dst],
+        _ => Vec::new(),
+    }
+}
+
+/// Callee name of a direct call
+fn callee(func: &Operand) -> Option<&str> {
+    match func {
+        Operand::Const(ConstValue::String(name)) => Some(name),
+        _ => None,
+    }
+}
+
+struct Lowering<'f, 'b, 'm> {
+    backend: &'b mut Backend<'m>,
+    func: &'f FunctionIR,
+    instrs: Vec<&'f Instruction>,
+    /// First instruction of each segment, ascending
+    leaders: Vec<usize>,
+    params: u32,
+    /// Number of registers, and of local variable slots
+    width: u32,
+    code: Code,
+}
+
+impl Lowering<'_, '_, '_> {
+    fn reg(
+        &self,
+        index: usize,
+    ) -> u32 {
+        self.params + index as u32
+    }
+
+    fn slot(
+        &self,
+        index: usize,
+    ) -> u32 {
+        self.params + self.width + index as u32
+    }
+
+    fn pc(&self) -> u32 {
+        self.params + 2 * self.width
+    }
+
+    fn segment_of(
+        &self,
+        ip: usize,
+    ) -> usize {
+        self.leaders.partition_point(|&leader| leader <= ip) - 1
+    }
+
+    fn segment(
+        &self,
+        index: usize,
+    ) -> std::ops::Range<usize> {
+        let end = self
+            .leaders
+            .get(index + 1)
+            .copied()
+            .unwrap_or(self.instrs.len());
+        self.leaders[index].min(end)..end
+    }
+
+    fn lower(&mut self) -> Result<(), Diagnostic> {
+        let states = self.infer_kinds();
+        let segments = self.leaders.len();
+
+        self.code.loop_();
+        for _ in 0..segments {
+            self.code.block();
+        }
+        let depths: Vec<u32> = (0..segments as u32).collect();
+        self.code
+            .local_get(self.pc())
+            .br_table(&depths, segments as u32 - 1);
+        for (index, state) in states.into_iter().enumerate() {
+            self.code.end();
+            let mut state = state.unwrap_or_else(|| self.initial_state());
+            let depth = (segments - 1 - index) as u32;
+            for ip in self.segment(index) {
+                let instr = self.instrs[ip];
+                self.emit(instr, &state, depth)?;
+                self.transfer(&mut state, instr);
+            }
+        }
+        self.code.end();
+        self.code.i64_const(0);
+        Ok(())
+    }
+
+    fn initial_state(&self) -> State {
+        let mut state = vec![Kind::Nothing; self.pc() as usize];
+        for (i, ty) in self.func.params.iter().enumerate() {
+            state[i] = Kind::from_mono(ty);
+        }
+        state
+    }
+
+    /// Kinds at the start of every segment (`None` for unreachable segments)
+    fn infer_kinds(&self) -> Vec<Option<State>> {
+        let mut states: Vec<Option<State>> = vec![None; self.leaders.len()];
+        states[0] = Some(self.initial_state());
+        let mut work = vec![0];
+        while let Some(index) = work.pop() {
+            let Some(mut state) = states[index].clone() else {
+                continue;
+            };
+            let range = self.segment(index);
+            for ip in range.clone() {
+                self.transfer(&mut state, self.instrs[ip]);
+            }
+            let last = range.end.checked_sub(1).filter(|ip| range.contains(ip));
+            let mut successors: Vec<usize> = last
+                .map(|ip| self.instrs[ip].jump_targets())
+                .unwrap_or_default()
+                .into_iter()
+                .map(|target| self.segment_of(target))
+                .collect();
+            if last.is_none_or(|ip| falls_through(self.instrs[ip])) && index + 1 < states.len() {
+                successors.push(index + 1);
+            }
+            for successor in successors {
+                let merged = match &states[successor] {
+                    Some(old) => old.iter().zip(&state).map(|(a, b)| a.join(b)).collect(),
+                    None => state.clone(),
+                };
+                if states[successor].as_ref() != Some(&merged) {
+                    states[successor] = Some(merged);
+                    work.push(successor);
+                }
+            }
+        }
+        states
+    }
+
+    /// Local holding a register or parameter operand
+    fn value_local(
+        &self,
+        operand: &Operand,
+    ) -> Option<u32> {
+        match operand {
+            Operand::Local(i) | Operand::Temp(i) => Some(self.reg(*i)),
+            Operand::Arg(i) => Some(*i as u32),
+            _ => None,
+        }
+    }
+
+    fn kind_of(
+        &self,
+        state: &State,
+        operand: &Operand,
+    ) -> Kind {
+        match operand {
+            Operand::Const(value) => Kind::of_const(value),
+            _ => self
+                .value_local(operand)
+                .map_or(Kind::Unknown, |local| state[local as usize].clone()),
+        }
+    }
+
+    /// Kind of a call's result
+    fn call_kind(
+        &self,
+        state: &State,
+        func: &Operand,
+        args: &[Operand],
+    ) -> Kind {
+        let Some(name) = callee(func) else {
+            return Kind::Unknown;
+        };
+        if let Some((_, target)) = self.backend.functions.get(name) {
+            return Kind::from_mono(&target.return_type);
+        }
+        match name {
+            "std.io.print" | "std.io.println" => Kind::Void,
+            "std.string.len" | "std.list.len" => Kind::Int,
+            "std.list.push" if args.len() == 2 => Kind::list_of(
+                self.kind_of(state, &args[0])
+                    .item()
+                    .join(&self.kind_of(state, &args[1])),
+            ),
+            _ => Kind::Unknown,
+        }
+    }
+
+    /// Update `state` with the effect of `instr`
+    fn transfer(
+        &self,
+        state: &mut State,
+        instr: &Instruction,
+    ) {
+        let kind_of = |operand: &Operand| self.kind_of(state, operand);
+        let (dst, kind) = match instr {
+            Instruction::Move { dst, src } | Instruction::StackAlloc { dst, src } => {
+                (dst, kind_of(src))
+            }
+            Instruction::Load { dst, src } => match src {
+                Operand::Local(i) => (dst, state[self.slot(*i) as usize].clone()),
+                _ => (dst, kind_of(src)),
+            },
+            Instruction::Store {
+                dst: Operand::Local(i),
+                src,
+                ..
+            } => {
+                let kind = kind_of(src);
+                state[self.slot(*i) as usize] = kind;
+                return;
+            }
+            Instruction::Add { dst, lhs, rhs, .. } => {
+                let both_str = kind_of(lhs) == Kind::Str && kind_of(rhs) == Kind::Str;
+                (dst, if both_str { Kind::Str } else { Kind::Int })
+            }
+            Instruction::And { dst, lhs, rhs }
+            | Instruction::Or { dst, lhs, rhs }
+            | Instruction::Xor { dst, lhs, rhs } => {
+                let both_bool = kind_of(lhs) == Kind::Bool && kind_of(rhs) == Kind::Bool;
+                (dst, if both_bool { Kind::Bool } else { Kind::Int })
+            }
+            Instruction::Sub { dst, .. }
+            | Instruction::Mul { dst, .. }
+            | Instruction::Div { dst, .. }
+            | Instruction::Mod { dst, .. }
+            | Instruction::Shl { dst, .. }
+            | Instruction::Shr { dst, .. }
+            | Instruction::Sar { dst, .. }
+            | Instruction::Neg { dst, .. }
+            | Instruction::StringLength { dst, .. } => (dst, Kind::Int),
+            Instruction::Eq { dst, .. }
+            | Instruction::Ne { dst, .. }
+            | Instruction::Lt { dst, .. }
+            | Instruction::Le { dst, .. }
+            | Instruction::Gt { dst, .. }
+            | Instruction::Ge { dst, .. }
+            | Instruction::FEq { dst, .. }
+            | Instruction::FNe { dst, .. }
+            | Instruction::FLt { dst, .. }
+            | Instruction::FLe { dst, .. }
+            | Instruction::FGt { dst, .. }
+            | Instruction::FGe { dst, .. } => (dst, Kind::Bool),
+            Instruction::FAdd { dst, .. }
+            | Instruction::FSub { dst, .. }
+            | Instruction::FMul { dst, .. }
+            | Instruction::FDiv { dst, .. }
+            | Instruction::FMod { dst, .. }
+            | Instruction::FNeg { dst, .. }
+            | Instruction::IntToFloat { dst, .. } => (dst, Kind::Float),
+            Instruction::StringConcat { dst, .. }
+            | Instruction::StringFromInt { dst, .. }
+            | Instruction::StringFromFloat { dst, .. }
+            | Instruction::StringBuilderNew { dst, .. }
+            | Instruction::StringBuilderAppend { dst, .. }
+            | Instruction::StringBuilderFinish { dst, .. } => (dst, Kind::Str),
+            Instruction::AllocArray { dst, .. } => (dst, Kind::list_of(Kind::Nothing)),
+            Instruction::LoadIndex { dst, src, .. } => (dst, kind_of(src).item()),
+            Instruction::StoreIndex { dst, src, .. } => {
+                let kind = Kind::list_of(kind_of(dst).item().join(&kind_of(src)));
+                (dst, kind)
+            }
+            Instruction::Call {
+                dst: Some(dst),
+                func,
+                args,
+                ..
+            } => (dst, self.call_kind(state, func, args)),
+            _ => return,
+        };
+        if let Some(local) = self.value_local(dst) {
+            state[local as usize] = kind;
+        }
+    }
+
+    /// Push an operand's value
+    fn get(
+        &mut self,
+        operand: &Operand,
+    ) -> Result<(), Diagnostic> {
+        if let Some(local) = self.value_local(operand) {
+            self.code.local_get(local);
+            return Ok(());
+        }
+        let Operand::Const(value) = operand else {
+            return Err(unsupported(&format!("the operand `{:?}`", operand)));
+        };
+        match value {
+            ConstValue::List(_) => {
+                let template = self.backend.constant(value)?;
+                self.code
+                    .i64_const(template)
+                    .call(self.backend.runtime.list_clone);
+            }
+            _ => {
+                let bits = self.backend.constant(value)?;
+                self.code.i64_const(bits);
+            }
+        }
+        Ok(())
+    }
+
+    /// Pop the top value into a register
+    fn set(
+        &mut self,
+        dst: &Operand,
+    ) -> Result<(), Diagnostic> {
+        match dst {
+            Operand::Local(i) | Operand::Temp(i) => {
+                self.code.local_set(self.reg(*i));
+                Ok(())
+            }
+            _ => Err(unsupported(&format!("the destination `{:?}`", dst))),
+        }
+    }
+
+    fn binary(
+        &mut self,
+        dst: &Operand,
+        lhs: &Operand,
+        rhs: &Operand,
+        opcode: u8,
+    ) -> Result<(), Diagnostic> {
+        self.get(lhs)?;
+        self.get(rhs)?;
+        self.code.op(opcode);
+        self.set(dst)
+    }
+
+    /// Comparison yielding an `i32`, stored as a Bool
+    fn compare(
+        &mut self,
+        dst: &Operand,
+        lhs: &Operand,
+        rhs: &Operand,
+        opcode: u8,
+    ) -> Result<(), Diagnostic> {
+        self.get(lhs)?;
+        self.get(rhs)?;
+        self.code.op(opcode).op(op::I64_EXTEND_I32_U);
+        self.set(dst)
+    }
+
+    fn get_float(
+        &mut self,
+        operand: &Operand,
+    ) -> Result<(), Diagnostic> {
+        self.get(operand)?;
+        self.code.op(op::F64_REINTERPRET_I64);
+        Ok(())
+    }
+
+    fn float_binary(
+        &mut self,
+        dst: &Operand,
+        lhs: &Operand,
+        rhs: &Operand,
+        opcode: u8,
+    ) -> Result<(), Diagnostic> {
+        self.get_float(lhs)?;
+        self.get_float(rhs)?;
+        self.code.op(opcode).op(op::I64_REINTERPRET_F64);
+        self.set(dst)
+    }
+
+    fn float_compare(
+        &mut self,
+        dst: &Operand,
+        lhs: &Operand,
+        rhs: &Operand,
+        opcode: u8,
+    ) -> Result<(), Diagnostic> {
+        self.get_float(lhs)?;
+        self.get_float(rhs)?;
+        self.code.op(opcode).op(op::I64_EXTEND_I32_U);
+        self.set(dst)
+    }
+
+    /// Integer or float comparison, chosen by the operand kinds
+    fn ordered_compare(
+        &mut self,
+        state: &State,
+        dst: &Operand,
+        lhs: &Operand,
+        rhs: &Operand,
+        int_op: u8,
+        float_op: u8,
+    ) -> Result<(), Diagnostic> {
+        match (self.kind_of(state, lhs), self.kind_of(state, rhs)) {
+            (Kind::Float, Kind::Float) => self.float_compare(dst, lhs, rhs, float_op),
+            (Kind::Str, _) | (_, Kind::Str) => Err(unsupported("ordering comparison of strings")),
+            _ => self.compare(dst, lhs, rhs, int_op),
+        }
+    }
+
+    /// `==` / `!=`: strings compare by content
+    fn equality(
+        &mut self,
+        state: &State,
+        dst: &Operand,
+        lhs: &Operand,
+        rhs: &Operand,
+        equal: bool,
+    ) -> Result<(), Diagnostic> {
+        let (int_op, float_op) = if equal {
+            (op::I64_EQ, op::F64_EQ)
+        } else {
+            (op::I64_NE, op::F64_NE)
+        };
+        match (self.kind_of(state, lhs), self.kind_of(state, rhs)) {
+            (Kind::Float, Kind::Float) => self.float_compare(dst, lhs, rhs, float_op),
+            (Kind::Str, _) | (_, Kind::Str) => {
+                self.get(lhs)?;
+                self.get(rhs)?;
+                self.code.call(self.backend.runtime.str_eq);
+                if !equal {
+                    self.code.op(op::I64_EQZ).op(op::I64_EXTEND_I32_U);
+                }
+                self.set(dst)
+            }
+            _ => self.compare(dst, lhs, rhs, int_op),
+        }
+    }
+
+    /// Call a runtime function on the operands, storing its result
+    fn runtime_call(
+        &mut self,
+        dst: &Operand,
+        func: u32,
+        args: &[&Operand],
+    ) -> Result<(), Diagnostic> {
+        for arg in args {
+            self.get(arg)?;
+        }
+        self.code.call(func);
+        self.set(dst)
+    }
+
+    /// Continue at instruction `target`, `depth` levels below the dispatch loop
+    fn jump(
+        &mut self,
+        target: usize,
+        depth: u32,
+    ) {
+        self.set_pc(target);
+        self.code.br(depth);
+    }
+
+    fn set_pc(
+        &mut self,
+        target: usize,
+    ) {
+        let (segment, pc) = (self.segment_of(target), self.pc());
+        self.code.i32_const(segment as i32).local_set(pc);
+    }
+
+    fn emit(
+        &mut self,
+        instr: &Instruction,
+        state: &State,
+        depth: u32,
+    ) -> Result<(), Diagnostic> {
+        let runtime = &self.backend.runtime;
+        let (str_concat, str_len, str_from_int, str_from_float) = (
+            runtime.str_concat,
+            runtime.str_len,
+            runtime.str_from_int,
+            runtime.str_from_float,
+        );
+        let (list_new, list_get, list_set) = (runtime.list_new, runtime.list_get, runtime.list_set);
+        match instr {
+            Instruction::Load {
+                dst,
+                src: Operand::Local(i),
+            } => {
+                self.code.local_get(self.slot(*i));
+                self.set(dst)?;
+            }
+            Instruction::Move { dst, src }
+            | Instruction::Load { dst, src }
+            | Instruction::StackAlloc { dst, src }
+            | Instruction::StringBuilderNew { dst, src }
+            | Instruction::StringBuilderFinish { dst, src } => {
+                self.get(src)?;
+                self.set(dst)?;
+            }
+            Instruction::Store {
+                dst: Operand::Local(i),
+                src,
+                ..
+            } => {
+                self.get(src)?;
+                self.code.local_set(self.slot(*i));
+            }
+            Instruction::Add { dst, lhs, rhs, .. } => {
+                match (self.kind_of(state, lhs), self.kind_of(state, rhs)) {
+                    (Kind::Str, Kind::Str) => self.runtime_call(dst, str_concat, &[lhs, rhs])?,
+                    (Kind::Str, _) | (_, Kind::Str) => {
+                        return Err(unsupported("adding a string to a non-string value"))
+                    }
+                    _ => self.binary(dst, lhs, rhs, op::I64_ADD)?,
+                }
+            }
+            Instruction::Sub { dst, lhs, rhs, .. } => self.binary(dst, lhs, rhs, op::I64_SUB)?,
+            Instruction::Mul { dst, lhs, rhs, .. } => self.binary(dst, lhs, rhs, op::I64_MUL)?,
+            Instruction::Div { dst, lhs, rhs, .. } => self.binary(dst, lhs, rhs, op::I64_DIV_S)?,
+            Instruction::Mod { dst, lhs, rhs, .. } => self.binary(dst, lhs, rhs, op::I64_REM_S)?,
+            Instruction::And { dst, lhs, rhs } => self.binary(dst, lhs, rhs, op::I64_AND)?,
+            Instruction::Or { dst, lhs, rhs } => self.binary(dst, lhs, rhs, op::I64_OR)?,
+            Instruction::Xor { dst, lhs, rhs } => self.binary(dst, lhs, rhs, op::I64_XOR)?,
+            Instruction::Shl { dst, lhs, rhs } => self.binary(dst, lhs, rhs, op::I64_SHL)?,
+            Instruction::Shr { dst, lhs, rhs } | Instruction::Sar { dst, lhs, rhs } => {
+                self.binary(dst, lhs, rhs, op::I64_SHR_S)?
+            }
+            Instruction::Neg { dst, src } => {
+                self.code.i64_const(0);
+                self.get(src)?;
+                self.code.op(op::I64_SUB);
+                self.set(dst)?;
+            }
+            Instruction::Eq { dst, lhs, rhs } => self.equality(state, dst, lhs, rhs, true)?,
+            Instruction::Ne { dst, lhs, rhs } => self.equality(state, dst, lhs, rhs, false)?,
+            Instruction::Lt { dst, lhs, rhs } => {
+                self.ordered_compare(state, dst, lhs, rhs, op::I64_LT_S, op::F64_LT)?
+            }
+            Instruction::Le { dst, lhs, rhs } => {
+                self.ordered_compare(state, dst, lhs, rhs, op::I64_LE_S, op::F64_LE)?
+            }
+            Instruction::Gt { dst, lhs, rhs } => {
+                self.ordered_compare(state, dst, lhs, rhs, op::I64_GT_S, op::F64_GT)?
+            }
+            Instruction::Ge { dst, lhs, rhs } => {
+                self.ordered_compare(state, dst, lhs, rhs, op::I64_GE_S, op::F64_GE)?
+            }
+            Instruction::FAdd { dst, lhs, rhs } => self.float_binary(dst, lhs, rhs, op::F64_ADD)?,
+            Instruction::FSub { dst, lhs, rhs } => self.float_binary(dst, lhs, rhs, op::F64_SUB)?,
+            Instruction::FMul { dst, lhs, rhs } => self.float_binary(dst, lhs, rhs, op::F64_MUL)?,
+            Instruction::FDiv { dst, lhs, rhs } => self.float_binary(dst, lhs, rhs, op::F64_DIV)?,
+            Instruction::FMod { dst, lhs, rhs } => {
+                // lhs - trunc(lhs / rhs) * rhs
+                self.get_float(lhs)?;
+                self.get_float(lhs)?;
+                self.get_float(rhs)?;
+                self.code.op(op::F64_DIV).op(op::F64_TRUNC);
+                self.get_float(rhs)?;
+                self.code.op(op::F64_MUL).op(op::F64_SUB);
+                self.code.op(op::I64_REINTERPRET_F64);
+                self.set(dst)?;
+            }
+            Instruction::FNeg { dst, src } => {
+                self.get_float(src)?;
+                self.code.op(op::F64_NEG).op(op::I64_REINTERPRET_F64);
+                self.set(dst)?;
+            }
+            Instruction::FEq { dst, lhs, rhs } => self.float_compare(dst, lhs, rhs, op::F64_EQ)?,
+            Instruction::FNe { dst, lhs, rhs } => self.float_compare(dst, lhs, rhs, op::F64_NE)?,
+            Instruction::FLt { dst, lhs, rhs } => self.float_compare(dst, lhs, rhs, op::F64_LT)?,
+            Instruction::FLe { dst, lhs, rhs } => self.float_compare(dst, lhs, rhs, op::F64_LE)?,
+            Instruction::FGt { dst, lhs, rhs } => self.float_compare(dst, lhs, rhs, op::F64_GT)?,
+            Instruction::FGe { dst, lhs, rhs } => self.float_compare(dst, lhs, rhs, op::F64_GE)?,
+            Instruction::IntToFloat { dst, src } => {
+                self.get(src)?;
+                self.code
+                    .op(op::F64_CONVERT_I64_S)
+                    .op(op::I64_REINTERPRET_F64);
+                self.set(dst)?;
+            }
+            Instruction::Jmp(target) => self.jump(*target, depth),
+            Instruction::JmpIf(cond, target) => {
+                self.set_pc(*target);
+                self.get(cond)?;
+                self.code.op(op::I64_EQZ).op(op::I32_EQZ).br_if(depth);
+            }
+            Instruction::JmpIfNot(cond, target) => {
+                self.set_pc(*target);
+                self.get(cond)?;
+                self.code.op(op::I64_EQZ).br_if(depth);
+            }
+            Instruction::Switch {
+                value,
+                low,
+                targets,
+                default,
+            } => {
+                for (offset, target) in targets.iter().enumerate() {
+                    self.set_pc(*target);
+                    self.get(value)?;
+                    self.code
+                        .i64_const(low.wrapping_add(offset as i64))
+                        .op(op::I64_EQ)
+                        .br_if(depth);
+                }
+                self.jump(*default, depth);
+            }
+            Instruction::Call {
+                dst, func, args, ..
+            } => self.call(state, dst.as_ref(), func, args)?,
+            // wasm has no guaranteed tail calls: an ordinary call followed by a return
+            Instruction::TailCall { func, args } => {
+                self.invoke(state, func, args)?;
+                self.code.op(op::RETURN);
+            }
+            Instruction::Ret(value) => {
+                match value {
+                    Some(value) => self.get(value)?,
+                    None => {
+                        self.code.i64_const(0);
+                    }
+                }
+                self.code.op(op::RETURN);
+            }
+            Instruction::AllocArray { dst, size, .. } => {
+                self.get(size)?;
+                self.code
+                    .op(op::I32_WRAP_I64)
+                    .call(list_new)
+                    .op(op::I64_EXTEND_I32_U);
+                self.set(dst)?;
+            }
+            Instruction::LoadIndex {
+                dst, src, index, ..
+            } => {
+                if !matches!(self.kind_of(state, src), Kind::List(_)) {
+                    return Err(unsupported("indexing a value that is not a list"));
+                }
+                self.runtime_call(dst, list_get, &[src, index])?;
+            }
+            Instruction::StoreIndex {
+                dst, index, src, ..
+            } => {
+                if !matches!(self.kind_of(state, dst), Kind::List(_)) {
+                    return Err(unsupported("indexing a value that is not a list"));
+                }
+                self.get(dst)?;
+                self.get(index)?;
+                self.get(src)?;
+                self.code.call(list_set);
+            }
+            Instruction::StringLength { dst, src } => self.runtime_call(dst, str_len, &[src])?,
+            Instruction::StringConcat { dst, lhs, rhs }
+            | Instruction::StringBuilderAppend { dst, lhs, rhs } => {
+                self.runtime_call(dst, str_concat, &[lhs, rhs])?
+            }
+            Instruction::StringFromInt { dst, src } => {
+                self.runtime_call(dst, str_from_int, &[src])?
+            }
+            Instruction::StringFromFloat { dst, src } => {
+                self.runtime_call(dst, str_from_float, &[src])?
+            }
+            Instruction::Drop(_) | Instruction::UnsafeBlockStart | Instruction::UnsafeBlockEnd => {}
+            _ => {
+                let debug = format!("{:?}", instr);
+                let name: String = debug.chars().take_while(|c| c.is_alphanumeric()).collect();
+                return Err(unsupported(&format!("the `{}` instruction", name)));
+            }
+        }
+        Ok(())
+    }
+
+    fn call(
+        &mut self,
+        state: &State,
+        dst: Option<&Operand>,
+        func: &Operand,
+        args: &[Operand],
+    ) -> Result<(), Diagnostic> {
+        self.invoke(state, func, args)?;
+        match dst {
+            Some(dst) => self.set(dst),
+            None => {
+                self.code.op(op::DROP);
+                Ok(())
+            }
+        }
+    }
+
+    /// Push the result of calling `func` with `args`
+    fn invoke(
+        &mut self,
+        state: &State,
+        func: &Operand,
+        args: &[Operand],
+    ) -> Result<(), Diagnostic> {
+        let Some(name) = callee(func) else {
+            return Err(unsupported("indirect calls"));
+        };
+        let runtime = &self.backend.runtime;
+        let (print, list_len, list_push, str_len) = (
+            runtime.print,
+            runtime.list_len,
+            runtime.list_push,
+            runtime.str_len,
+        );
+        if let Some(&(index, target)) = self.backend.functions.get(name) {
+            if target.params.len() != args.len() {
+                return Err(unsupported(&format!(
+                    "calling `{}` with {} arguments",
+                    name,
+                    args.len()
+                )));
+            }
+            for arg in args {
+                self.get(arg)?;
+            }
+            self.code.call(index);
+        } else {
+            match (name, args) {
+                ("std.io.print" | "std.io.println", [] | [_]) => {
+                    if let Some(arg) = args.first() {
+                        self.get(arg)?;
+                        let kind = self.kind_of(state, arg);
+                        self.backend.display(&mut self.code, &kind)?;
+                        self.code.call(print);
+                    }
+                    if name == "std.io.println" {
+                        let newline = self.backend.data.string("\n");
+                        self.code.i64_const(newline as i64).call(print);
+                    }
+                    self.code.i64_const(0);
+                }
+                ("std.string.len", [text]) => {
+                    self.get(text)?;
+                    self.code.call(str_len);
+                }
+                ("std.list.len", [list]) => {
+                    self.get(list)?;
+                    self.code.call(list_len);
+                }
+                ("std.list.push", [list, item]) => {
+                    self.get(list)?;
+                    self.get(item)?;
+                    self.code.call(list_push);
+                }
+                _ => return Err(unsupported(&format!("calls to `{}`", name))),
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,235 @@
+//! WebAssembly backend
+//!
+//! Lowers a monomorphized [`ModuleIR`] to a standalone wasm module, so YaoXiang
+//! programs can run in a browser or any other wasm host.
+//!
+//! # Host interface
+//!
+//! The module imports two functions from `env`:
+//!
+//! - `write(ptr: i32, len: i32)`: write `len` UTF-8 bytes at `ptr` to the output
+//! - `format_f64(value: f64, buf: i32) -> i32`: format a float the way the VM prints
+//!   it (`3.0`, `0.1`) into `buf` (at most 1008 bytes), returning the byte length
+//!
+//! and exports its `memory` and `main: () -> i32`, which runs the program and
+//! returns `main`'s Int result (0 otherwise).
+//!
+//! # Coverage
+//!
+//! Ints, floats, bools, chars, strings and lists of those, direct calls between
+//! user functions, and the `std.io.print`/`println`, `std.string.len`,
+//! `std.list.len` and `std.list.push` built-ins. Integer arithmetic wraps as in
+//! release builds; division by zero and out-of-bounds indexing trap. Anything else
+//! (structs, closures, globals, other std modules) is rejected with E3020.
+
+mod encoder;
+mod kinds;
+mod lower;
+mod runtime;
+
+#[cfg(test)]
+mod tests;
+
+use std::collections::HashMap;
+
+use encoder::{op, Code, Module, ValType};
+use kinds::Kind;
+use runtime::{Data, Runtime};
+
+use crate::middle::core::ir::{ConstValue, FunctionIR, ModuleIR};
+use crate::util::diagnostic::{Diagnostic, ErrorCodeDefinition};
+
+/// Encode `module` as a wasm binary
+pub fn emit_module(module: &ModuleIR) -> Result<Vec<u8>, Diagnostic> {
+    let mut wasm = Module::new();
+    let runtime = Runtime::declare(&mut wasm);
+
+    let mut functions = HashMap::new();
+    for func in &module.functions {
+        functions.insert(func.name.as_str(), (wasm.reserve_function(), func));
+    }
+    let Some(&(main, main_ir)) = functions.get("main") else {
+        return Err(unsupported("a program without `main`"));
+    };
+
+    let mut backend = Backend {
+        wasm,
+        runtime,
+        data: Data::default(),
+        functions,
+        formatters: HashMap::new(),
+    };
+    let mut order: Vec<(u32, &FunctionIR)> = backend.functions.values().copied().collect();
+    order.sort_by_key(|(index, _)| *index);
+    for (index, func) in order {
+        let lowered = lower::lower_function(&mut backend, func)?;
+        backend.wasm.define(index, lowered);
+    }
+
+    let entry = backend.wasm.reserve_function();
+    let returns_int = Kind::from_mono(&main_ir.return_type) == Kind::Int;
+    backend.wasm.define(
+        entry,
+        runtime::function(&[], &[ValType::I32], &[], |c| {
+            c.call(main);
+            if returns_int {
+                c.op(op::I32_WRAP_I64);
+            } else {
+                c.op(op::DROP).i32_const(0);
+            }
+        }),
+    );
+    backend.wasm.export_function("main", entry);
+    backend.wasm.export_memory("memory");
+
+    let Backend {
+        mut wasm,
+        runtime,
+        data,
+        ..
+    } = backend;
+    runtime.finish(&mut wasm, data);
+    Ok(wasm.finish())
+}
+
+/// E3020 for a construct the backend cannot lower
+fn unsupported(construct: &str) -> Diagnostic {
+    ErrorCodeDefinition::wasm_unsupported(construct).build()
+}
+
+/// Module-wide lowering state shared by all functions
+struct Backend<'m> {
+    wasm: Module,
+    runtime: Runtime,
+    data: Data,
+    /// User functions by name: wasm index and IR
+    functions: HashMap<&'m str, (u32, &'m FunctionIR)>,
+    /// Generated list formatters by item kind
+    formatters: HashMap<Kind, u32>,
+}
+
+impl Backend<'_> {
+    /// `i64` representation of a constant (list constants yield their template)
+    fn constant(
+        &mut self,
+        value: &ConstValue,
+    ) -> Result<i64, Diagnostic> {
+        Ok(match value {
+            ConstValue::Void => 0,
+            ConstValue::Bool(b) => *b as i64,
+            ConstValue::Int(n) => *n as i64,
+            ConstValue::Float(f) => f.to_bits() as i64,
+            ConstValue::Char(c) => *c as i64,
+            ConstValue::String(s) => self.data.string(s) as i64,
+            ConstValue::List(items) => {
+                let items = items
+                    .iter()
+                    .map(|item| self.constant(item))
+                    .collect::<Result<Vec<_>, _>>()?;
+                self.data.list(&items) as i64
+            }
+            _ => return Err(unsupported(&format!("the constant `{:?}`", value))),
+        })
+    }
+
+    /// Replace the value on top of the stack by its display string
+    fn display(
+        &mut self,
+        code: &mut Code,
+        kind: &Kind,
+    ) -> Result<(), Diagnostic> {
+        let convert = match kind {
+            Kind::Str => return Ok(()),
+            Kind::Int => self.runtime.str_from_int,
+            Kind::Float => self.runtime.str_from_float,
+            Kind::Bool => self.runtime.str_from_bool,
+            Kind::Char => self.runtime.str_from_char,
+            Kind::List(item) => self.list_formatter(item)?,
+            Kind::Nothing | Kind::Void | Kind::Unknown => {
+                return Err(unsupported("printing a value of unknown type"))
+            }
+        };
+        code.call(convert);
+        Ok(())
+    }
+
+    /// Like [`Backend::display`], but quote strings and chars as list items print
+    fn debug(
+        &mut self,
+        code: &mut Code,
+        kind: &Kind,
+        tmp: u32,
+    ) -> Result<(), Diagnostic> {
+        let quote = match kind {
+            Kind::Str => "\"",
+            Kind::Char => "'",
+            // Items of a list that is always empty
+            Kind::Nothing => {
+                code.op(op::UNREACHABLE);
+                return Ok(());
+            }
+            _ => return self.display(code, kind),
+        };
+        self.display(code, kind)?;
+        let quote = self.data.string(quote) as i64;
+        code.local_set(tmp)
+            .i64_const(quote)
+            .local_get(tmp)
+            .call(self.runtime.str_concat)
+            .i64_const(quote)
+            .call(self.runtime.str_concat);
+        Ok(())
+    }
+
+    /// Function formatting a list of `item` as `[a, b, c]`
+    fn list_formatter(
+        &mut self,
+        item: &Kind,
+    ) -> Result<u32, Diagnostic> {
+        if let Some(&index) = self.formatters.get(item) {
+            return Ok(index);
+        }
+        let index = self.wasm.reserve_function();
+        self.formatters.insert(item.clone(), index);
+
+        const LIST: u32 = 0;
+        const OUT: u32 = 1;
+        const I: u32 = 2;
+        const N: u32 = 3;
+        const TMP: u32 = 4;
+        let open = self.data.string("[") as i64;
+        let separator = self.data.string(", ") as i64;
+        let close = self.data.string("]") as i64;
+        let concat = self.runtime.str_concat;
+
+        let mut c = Code::new();
+        c.i64_const(open).local_set(OUT);
+        c.local_get(LIST).call(self.runtime.list_len).local_set(N);
+        c.block().loop_();
+        c.local_get(I).local_get(N).op(op::I64_GE_S).br_if(1);
+        c.local_get(I).i64_const(0).op(op::I64_NE).if_();
+        c.local_get(OUT)
+            .i64_const(separator)
+            .call(concat)
+            .local_set(OUT);
+        c.end();
+        c.local_get(OUT);
+        c.local_get(LIST).local_get(I).call(self.runtime.list_get);
+        self.debug(&mut c, item, TMP)?;
+        c.call(concat).local_set(OUT);
+        c.local_get(I).i64_const(1).op(op::I64_ADD).local_set(I);
+        c.br(0).end().end();
+        c.local_get(OUT).i64_const(close).call(concat);
+
+        self.wasm.define(
+            index,
+            encoder::Function {
+                params: vec![ValType::I64],
+                results: vec![ValType::I64],
+                locals: vec![ValType::I64; 4],
+                code: c,
+            },
+        );
+        Ok(index)
+    }
+}
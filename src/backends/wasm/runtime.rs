@@ -0,0 +1,575 @@
+//! Runtime shim linked into every generated module
+//!
+//! Memory layout (all values are `i64`; heap objects are addressed by their
+//! `i32` offset, zero-extended):
+//!
+//! ```text
+//! 0 .. 16            reserved (null)
+//! 16 .. 1024         scratch buffer for number formatting
+//! 1024 ..            static data: string literals, list templates
+//! heap ..            bump-allocated objects, never freed
+//! ```
+//!
+//! A string is `[len: u32][utf-8 bytes]`; a list is `[len: u32][pad: u32][items: i64 × len]`.
+
+use std::collections::HashMap;
+
+use super::encoder::{op, Code, Function, Module, ValType};
+
+/// Scratch buffer the host writes formatted floats into
+pub const SCRATCH: u32 = 16;
+/// End of the scratch buffer (integers are formatted backwards from here)
+pub const SCRATCH_END: u32 = 1024;
+/// Start of the static data
+pub const DATA_BASE: u32 = 1024;
+
+const PAGE_SIZE: u32 = 65536;
+
+use ValType::{I32, I64};
+
+/// Static data: string literals and list templates, deduplicated
+#[derive(Default)]
+pub struct Data {
+    bytes: Vec<u8>,
+    strings: HashMap<String, u32>,
+}
+
+impl Data {
+    fn align(
+        &mut self,
+        to: usize,
+    ) {
+        while !self.bytes.len().is_multiple_of(to) {
+            self.bytes.push(0);
+        }
+    }
+
+    fn here(&self) -> u32 {
+        DATA_BASE + self.bytes.len() as u32
+    }
+
+    /// Address of the string `text`
+    pub fn string(
+        &mut self,
+        text: &str,
+    ) -> u32 {
+        if let Some(&addr) = self.strings.get(text) {
+            return addr;
+        }
+        self.align(4);
+        let addr = self.here();
+        self.bytes
+            .extend_from_slice(&(text.len() as u32).to_le_bytes());
+        self.bytes.extend_from_slice(text.as_bytes());
+        self.strings.insert(text.to_string(), addr);
+        addr
+    }
+
+    /// Address of a list template holding `items`
+    pub fn list(
+        &mut self,
+        items: &[i64],
+    ) -> u32 {
+        self.align(8);
+        let addr = self.here();
+        self.bytes
+            .extend_from_slice(&(items.len() as u32).to_le_bytes());
+        self.bytes.extend_from_slice(&[0; 4]);
+        for item in items {
+            self.bytes.extend_from_slice(&item.to_le_bytes());
+        }
+        addr
+    }
+
+    /// First free address after the data, 8-byte aligned
+    pub fn end(&self) -> u32 {
+        (self.here() + 7) & !7
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+/// Function indices of the host imports and shim functions
+pub struct Runtime {
+    /// `env.write(ptr: i32, len: i32)`: write UTF-8 bytes to the output
+    pub write: u32,
+    /// `env.format_f64(value: f64, buf: i32) -> i32`: format a float into `buf`, return its length
+    pub format_f64: u32,
+    heap: u32,
+    pub alloc: u32,
+    pub str_new: u32,
+    pub str_concat: u32,
+    pub str_eq: u32,
+    pub str_len: u32,
+    pub str_from_int: u32,
+    pub str_from_float: u32,
+    pub str_from_char: u32,
+    pub str_from_bool: u32,
+    pub list_new: u32,
+    pub list_clone: u32,
+    list_addr: u32,
+    pub list_get: u32,
+    pub list_set: u32,
+    pub list_len: u32,
+    pub list_push: u32,
+    pub print: u32,
+}
+
+impl Runtime {
+    /// Import the host functions and reserve the shim functions
+    ///
+    /// Must run before any other function is reserved.
+    pub fn declare(module: &mut Module) -> Self {
+        let write = module.import_function("env", "write", &[I32, I32], &[]);
+        let format_f64 = module.import_function("env", "format_f64", &[ValType::F64, I32], &[I32]);
+        let heap = module.add_global(0);
+        let mut reserve = || module.reserve_function();
+        Self {
+            write,
+            format_f64,
+            heap,
+            alloc: reserve(),
+            str_new: reserve(),
+            str_concat: reserve(),
+            str_eq: reserve(),
+            str_len: reserve(),
+            str_from_int: reserve(),
+            str_from_float: reserve(),
+            str_from_char: reserve(),
+            str_from_bool: reserve(),
+            list_new: reserve(),
+            list_clone: reserve(),
+            list_addr: reserve(),
+            list_get: reserve(),
+            list_set: reserve(),
+            list_len: reserve(),
+            list_push: reserve(),
+            print: reserve(),
+        }
+    }
+
+    /// Define the shim functions and lay out memory once all data is known
+    pub fn finish(
+        &self,
+        module: &mut Module,
+        mut data: Data,
+    ) {
+        let true_str = data.string("true") as i64;
+        let false_str = data.string("false") as i64;
+
+        module.define(self.alloc, self.alloc());
+        module.define(self.str_new, self.str_new());
+        module.define(self.str_concat, self.str_concat());
+        module.define(self.str_eq, self.str_eq());
+        module.define(
+            self.str_len,
+            function(&[I64], &[I64], &[], |c| {
+                c.local_get(0).op(op::I32_WRAP_I64).i32_load(0);
+                c.op(op::I64_EXTEND_I32_U);
+            }),
+        );
+        module.define(self.str_from_int, self.str_from_int());
+        module.define(self.str_from_float, self.str_from_float());
+        module.define(self.str_from_char, self.str_from_char());
+        module.define(
+            self.str_from_bool,
+            function(&[I64], &[I64], &[], |c| {
+                c.i64_const(true_str).i64_const(false_str);
+                c.local_get(0)
+                    .op(op::I64_EQZ)
+                    .op(op::I32_EQZ)
+                    .op(op::SELECT);
+            }),
+        );
+        module.define(self.list_new, self.list_new());
+        module.define(self.list_clone, self.list_clone());
+        module.define(self.list_addr, self.list_addr());
+        module.define(
+            self.list_get,
+            function(&[I64, I64], &[I64], &[], |c| {
+                c.local_get(0).local_get(1).call(self.list_addr).i64_load(0);
+            }),
+        );
+        module.define(
+            self.list_set,
+            function(&[I64, I64, I64], &[], &[], |c| {
+                c.local_get(0).local_get(1).call(self.list_addr);
+                c.local_get(2).i64_store(0);
+            }),
+        );
+        module.define(
+            self.list_len,
+            function(&[I64], &[I64], &[], |c| {
+                c.local_get(0).op(op::I32_WRAP_I64).i32_load(0);
+                c.op(op::I64_EXTEND_I32_U);
+            }),
+        );
+        module.define(self.list_push, self.list_push());
+        module.define(
+            self.print,
+            function(&[I64], &[], &[], |c| {
+                c.local_get(0)
+                    .op(op::I32_WRAP_I64)
+                    .i32_const(4)
+                    .op(op::I32_ADD);
+                c.local_get(0).op(op::I32_WRAP_I64).i32_load(0);
+                c.call(self.write);
+            }),
+        );
+
+        let heap_start = data.end();
+        module.set_global(self.heap, heap_start as i32);
+        module.set_memory_pages(heap_start.div_ceil(PAGE_SIZE) + 1);
+        module.add_data(DATA_BASE, data.into_bytes());
+    }
+
+    /// `alloc(size: i32) -> i32`: bump allocation, growing memory as needed
+    fn alloc(&self) -> Function {
+        const SIZE: u32 = 0;
+        const PTR: u32 = 1;
+        const END: u32 = 2;
+        function(&[I32], &[I32], &[I32, I32], |c| {
+            c.global_get(self.heap).local_tee(PTR);
+            c.local_get(SIZE)
+                .op(op::I32_ADD)
+                .i32_const(7)
+                .op(op::I32_ADD);
+            c.i32_const(-8).op(op::I32_AND).local_tee(END);
+            c.memory_size()
+                .i32_const(16)
+                .op(op::I32_SHL)
+                .op(op::I32_GT_U);
+            c.if_();
+            {
+                c.local_get(END);
+                c.memory_size()
+                    .i32_const(16)
+                    .op(op::I32_SHL)
+                    .op(op::I32_SUB);
+                c.i32_const(16)
+                    .op(op::I32_SHR_U)
+                    .i32_const(1)
+                    .op(op::I32_ADD);
+                c.memory_grow().i32_const(-1).op(op::I32_EQ);
+                c.if_().op(op::UNREACHABLE).end();
+            }
+            c.end();
+            c.local_get(END).global_set(self.heap);
+            c.local_get(PTR);
+        })
+    }
+
+    /// `str_new(len: i32) -> i32`: uninitialized string of `len` bytes
+    fn str_new(&self) -> Function {
+        function(&[I32], &[I32], &[I32], |c| {
+            c.local_get(0).i32_const(4).op(op::I32_ADD).call(self.alloc);
+            c.local_tee(1).local_get(0).i32_store(0);
+            c.local_get(1);
+        })
+    }
+
+    /// `str_concat(a: i64, b: i64) -> i64`
+    fn str_concat(&self) -> Function {
+        const A: u32 = 0;
+        const B: u32 = 1;
+        const A_LEN: u32 = 2;
+        const B_LEN: u32 = 3;
+        const OUT: u32 = 4;
+        function(&[I64, I64], &[I64], &[I32, I32, I32], |c| {
+            c.local_get(A)
+                .op(op::I32_WRAP_I64)
+                .i32_load(0)
+                .local_set(A_LEN);
+            c.local_get(B)
+                .op(op::I32_WRAP_I64)
+                .i32_load(0)
+                .local_set(B_LEN);
+            c.local_get(A_LEN).local_get(B_LEN).op(op::I32_ADD);
+            c.call(self.str_new).local_set(OUT);
+
+            c.local_get(OUT).i32_const(4).op(op::I32_ADD);
+            c.local_get(A)
+                .op(op::I32_WRAP_I64)
+                .i32_const(4)
+                .op(op::I32_ADD);
+            c.local_get(A_LEN).memory_copy();
+
+            c.local_get(OUT)
+                .i32_const(4)
+                .op(op::I32_ADD)
+                .local_get(A_LEN)
+                .op(op::I32_ADD);
+            c.local_get(B)
+                .op(op::I32_WRAP_I64)
+                .i32_const(4)
+                .op(op::I32_ADD);
+            c.local_get(B_LEN).memory_copy();
+
+            c.local_get(OUT).op(op::I64_EXTEND_I32_U);
+        })
+    }
+
+    /// `str_eq(a: i64, b: i64) -> i64`: byte-wise equality, 1 or 0
+    fn str_eq(&self) -> Function {
+        const A: u32 = 2;
+        const B: u32 = 3;
+        const LEN: u32 = 4;
+        const I: u32 = 5;
+        function(&[I64, I64], &[I64], &[I32, I32, I32, I32], |c| {
+            c.local_get(0).op(op::I32_WRAP_I64).local_set(A);
+            c.local_get(1).op(op::I32_WRAP_I64).local_set(B);
+            c.local_get(A).i32_load(0).local_tee(LEN);
+            c.local_get(B).i32_load(0).op(op::I32_NE);
+            c.if_().i64_const(0).op(op::RETURN).end();
+            c.block().loop_();
+            {
+                c.local_get(I).local_get(LEN).op(op::I32_GE_U).br_if(1);
+                c.local_get(A).local_get(I).op(op::I32_ADD).i32_load8_u(4);
+                c.local_get(B).local_get(I).op(op::I32_ADD).i32_load8_u(4);
+                c.op(op::I32_NE);
+                c.if_().i64_const(0).op(op::RETURN).end();
+                c.local_get(I).i32_const(1).op(op::I32_ADD).local_set(I);
+                c.br(0);
+            }
+            c.end().end();
+            c.i64_const(1);
+        })
+    }
+
+    /// Copy `len` bytes at `src` into a new string, leaving its address on the stack
+    fn copy_into_string(
+        &self,
+        c: &mut Code,
+        src: impl Fn(&mut Code),
+        len: u32,
+        out: u32,
+    ) {
+        c.local_get(len).call(self.str_new).local_set(out);
+        c.local_get(out).i32_const(4).op(op::I32_ADD);
+        src(c);
+        c.local_get(len).memory_copy();
+        c.local_get(out).op(op::I64_EXTEND_I32_U);
+    }
+
+    /// `str_from_int(value: i64) -> i64`: decimal digits, formatted backwards in scratch
+    fn str_from_int(&self) -> Function {
+        const VALUE: u32 = 0;
+        const NEG: u32 = 1;
+        const MAG: u32 = 2;
+        const POS: u32 = 3;
+        const LEN: u32 = 4;
+        const OUT: u32 = 5;
+        function(&[I64], &[I64], &[I32, I64, I32, I32, I32], |c| {
+            c.i32_const(SCRATCH_END as i32).local_set(POS);
+            c.local_get(VALUE).local_set(MAG);
+            c.local_get(VALUE)
+                .i64_const(0)
+                .op(op::I64_LT_S)
+                .local_tee(NEG);
+            c.if_()
+                .i64_const(0)
+                .local_get(VALUE)
+                .op(op::I64_SUB)
+                .local_set(MAG)
+                .end();
+            c.loop_();
+            {
+                c.local_get(POS).i32_const(1).op(op::I32_SUB).local_tee(POS);
+                c.local_get(MAG)
+                    .i64_const(10)
+                    .op(op::I64_REM_U)
+                    .op(op::I32_WRAP_I64);
+                c.i32_const(b'0' as i32).op(op::I32_ADD).i32_store8(0);
+                c.local_get(MAG)
+                    .i64_const(10)
+                    .op(op::I64_DIV_U)
+                    .local_tee(MAG);
+                c.op(op::I64_EQZ).op(op::I32_EQZ).br_if(0);
+            }
+            c.end();
+            c.local_get(NEG).if_();
+            {
+                c.local_get(POS).i32_const(1).op(op::I32_SUB).local_tee(POS);
+                c.i32_const(b'-' as i32).i32_store8(0);
+            }
+            c.end();
+            c.i32_const(SCRATCH_END as i32)
+                .local_get(POS)
+                .op(op::I32_SUB)
+                .local_set(LEN);
+            self.copy_into_string(
+                c,
+                |c| {
+                    c.local_get(POS);
+                },
+                LEN,
+                OUT,
+            );
+        })
+    }
+
+    /// `str_from_float(bits: i64) -> i64`: formatted by the host
+    fn str_from_float(&self) -> Function {
+        const LEN: u32 = 1;
+        const OUT: u32 = 2;
+        function(&[I64], &[I64], &[I32, I32], |c| {
+            c.local_get(0)
+                .op(op::F64_REINTERPRET_I64)
+                .i32_const(SCRATCH as i32);
+            c.call(self.format_f64).local_set(LEN);
+            self.copy_into_string(
+                c,
+                |c| {
+                    c.i32_const(SCRATCH as i32);
+                },
+                LEN,
+                OUT,
+            );
+        })
+    }
+
+    /// `str_from_char(code_point: i64) -> i64`: UTF-8 encoding
+    fn str_from_char(&self) -> Function {
+        const CP: u32 = 1;
+        const OUT: u32 = 2;
+        // Byte `index` of an `n`-byte encoding
+        let byte = |c: &mut Code, n: u32, index: u32| {
+            let shift = 6 * (n - 1 - index);
+            c.local_get(OUT).local_get(CP);
+            c.i32_const(shift as i32).op(op::I32_SHR_U);
+            if index == 0 {
+                let lead = [0x00, 0xC0, 0xE0, 0xF0][n as usize - 1];
+                c.i32_const(lead).op(op::I32_OR);
+            } else {
+                c.i32_const(0x3F)
+                    .op(op::I32_AND)
+                    .i32_const(0x80)
+                    .op(op::I32_OR);
+            }
+            c.i32_store8(4 + index);
+        };
+        let encode = |c: &mut Code, n: u32| {
+            c.i32_const(n as i32).call(self.str_new).local_set(OUT);
+            for index in 0..n {
+                byte(c, n, index);
+            }
+        };
+        function(&[I64], &[I64], &[I32, I32], |c| {
+            c.local_get(0).op(op::I32_WRAP_I64).local_set(CP);
+            for (n, limit) in [(1, 0x80), (2, 0x800), (3, 0x10000)] {
+                c.local_get(CP).i32_const(limit).op(op::I32_LT_U).if_();
+                encode(c, n);
+                c.else_();
+            }
+            encode(c, 4);
+            c.end().end().end();
+            c.local_get(OUT).op(op::I64_EXTEND_I32_U);
+        })
+    }
+
+    /// `list_new(len: i32) -> i32`: zero-filled list of `len` items
+    fn list_new(&self) -> Function {
+        function(&[I32], &[I32], &[I32], |c| {
+            c.local_get(0)
+                .i32_const(3)
+                .op(op::I32_SHL)
+                .i32_const(8)
+                .op(op::I32_ADD);
+            c.call(self.alloc).local_tee(1).local_get(0).i32_store(0);
+            c.local_get(1);
+        })
+    }
+
+    /// Copy the items of list `src` into list `dst` (same or greater length)
+    fn copy_items(
+        c: &mut Code,
+        dst: u32,
+        src: u32,
+        len: u32,
+    ) {
+        c.local_get(dst).i32_const(8).op(op::I32_ADD);
+        c.local_get(src).i32_const(8).op(op::I32_ADD);
+        c.local_get(len).i32_const(3).op(op::I32_SHL).memory_copy();
+    }
+
+    /// `list_clone(list: i64) -> i64`: shallow copy (list literals copy their template)
+    fn list_clone(&self) -> Function {
+        const SRC: u32 = 1;
+        const LEN: u32 = 2;
+        const OUT: u32 = 3;
+        function(&[I64], &[I64], &[I32, I32, I32], |c| {
+            c.local_get(0)
+                .op(op::I32_WRAP_I64)
+                .local_tee(SRC)
+                .i32_load(0)
+                .local_tee(LEN);
+            c.call(self.list_new).local_set(OUT);
+            Self::copy_items(c, OUT, SRC, LEN);
+            c.local_get(OUT).op(op::I64_EXTEND_I32_U);
+        })
+    }
+
+    /// `list_addr(list: i64, index: i64) -> i32`: item address, trapping when out of bounds
+    fn list_addr(&self) -> Function {
+        const LIST: u32 = 2;
+        function(&[I64, I64], &[I32], &[I32], |c| {
+            c.local_get(0).op(op::I32_WRAP_I64).local_set(LIST);
+            c.local_get(1)
+                .local_get(LIST)
+                .i32_load(0)
+                .op(op::I64_EXTEND_I32_U);
+            c.op(op::I64_GE_U).if_().op(op::UNREACHABLE).end();
+            c.local_get(LIST).i32_const(8).op(op::I32_ADD);
+            c.local_get(1)
+                .op(op::I32_WRAP_I64)
+                .i32_const(3)
+                .op(op::I32_SHL)
+                .op(op::I32_ADD);
+        })
+    }
+
+    /// `list_push(list: i64, item: i64) -> i64`: new list with `item` appended
+    fn list_push(&self) -> Function {
+        const SRC: u32 = 2;
+        const LEN: u32 = 3;
+        const OUT: u32 = 4;
+        function(&[I64, I64], &[I64], &[I32, I32, I32], |c| {
+            c.local_get(0)
+                .op(op::I32_WRAP_I64)
+                .local_tee(SRC)
+                .i32_load(0)
+                .local_tee(LEN);
+            c.i32_const(1)
+                .op(op::I32_ADD)
+                .call(self.list_new)
+                .local_set(OUT);
+            Self::copy_items(c, OUT, SRC, LEN);
+            c.local_get(OUT)
+                .local_get(LEN)
+                .i32_const(3)
+                .op(op::I32_SHL)
+                .op(op::I32_ADD);
+            c.local_get(1).i64_store(8);
+            c.local_get(OUT).op(op::I64_EXTEND_I32_U);
+        })
+    }
+}
+
+/// Build a function from its signature, extra locals and a body writer
+pub fn function(
+    params: &[ValType],
+    results: &[ValType],
+    locals: &[ValType],
+    body: impl FnOnce(&mut Code),
+) -> Function {
+    let mut code = Code::new();
+    body(&mut code);
+    Function {
+        params: params.to_vec(),
+        results: results.to_vec(),
+        locals: locals.to_vec(),
+        code,
+    }
+}
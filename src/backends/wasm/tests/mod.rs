@@ -0,0 +1,137 @@
+//! WebAssembly 后端测试
+//!
+//! 测试覆盖内容：
+//! - LEB128 编码
+//! - 模块头、段顺序与长度、导出的 `main` 与 `memory`
+//! - 不支持的构造报告 E3020
+//! - 在 wasmi 中运行生成的模块，输出与退出码与 VM 一致（`run`）
+
+mod run;
+
+use super::emit_module;
+use super::encoder::{write_sleb, write_uleb};
+use crate::frontend::Compiler;
+
+fn compile(source: &str) -> Result<Vec<u8>, String> {
+    let module = Compiler::new()
+        .compile("wasm.yx", source)
+        .map_err(|e| format!("{:?}", e))?;
+    emit_module(&module).map_err(|d| d.code)
+}
+
+fn read_uleb(
+    bytes: &[u8],
+    pos: &mut usize,
+) -> u64 {
+    let mut value = 0;
+    let mut shift = 0;
+    loop {
+        let byte = bytes[*pos];
+        *pos += 1;
+        value |= ((byte & 0x7F) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return value;
+        }
+        shift += 7;
+    }
+}
+
+/// 按段切分模块，返回 `(段 id, 段内容)`
+fn sections(bytes: &[u8]) -> Vec<(u8, &[u8])> {
+    assert_eq!(&bytes[..8], b"\0asm\x01\0\0\0");
+    let mut pos = 8;
+    let mut out = Vec::new();
+    while pos < bytes.len() {
+        let id = bytes[pos];
+        pos += 1;
+        let len = read_uleb(bytes, &mut pos) as usize;
+        out.push((id, &bytes[pos..pos + len]));
+        pos += len;
+    }
+    assert_eq!(pos, bytes.len());
+    out
+}
+
+/// 导出段中的 `(名字, 种类)`
+fn exports(bytes: &[u8]) -> Vec<(String, u8)> {
+    let (_, body) = sections(bytes)
+        .into_iter()
+        .find(|(id, _)| *id == 7)
+        .expect("export section");
+    let mut pos = 0;
+    let count = read_uleb(body, &mut pos);
+    (0..count)
+        .map(|_| {
+            let len = read_uleb(body, &mut pos) as usize;
+            let name = String::from_utf8(body[pos..pos + len].to_vec()).unwrap();
+            pos += len;
+            let kind = body[pos];
+            pos += 1;
+            read_uleb(body, &mut pos);
+            (name, kind)
+        })
+        .collect()
+}
+
+#[test]
+fn test_leb128() {
+    let uleb = |value| {
+        let mut out = Vec::new();
+        write_uleb(&mut out, value);
+        out
+    };
+    let sleb = |value| {
+        let mut out = Vec::new();
+        write_sleb(&mut out, value);
+        out
+    };
+    assert_eq!(uleb(0), [0x00]);
+    assert_eq!(uleb(127), [0x7F]);
+    assert_eq!(uleb(624485), [0xE5, 0x8E, 0x26]);
+    assert_eq!(sleb(-1), [0x7F]);
+    assert_eq!(sleb(63), [0x3F]);
+    assert_eq!(sleb(64), [0xC0, 0x00]);
+    assert_eq!(sleb(-123456), [0xC0, 0xBB, 0x78]);
+    assert_eq!(sleb(i64::MIN).len(), 10);
+}
+
+#[test]
+fn test_module_layout_and_exports() {
+    let bytes = compile(
+        "use std.io\n\n\
+         fib: (n: Int) -> Int = (n) => {\n    if n < 2 {\n        return n\n    }\n    return fib(n - 1) + fib(n - 2)\n}\n\n\
+         main = {\n    xs = [1, 2, 3]\n    io.println(fib(10))\n    io.println(\"a\" + \"b\")\n    io.println(xs)\n    io.println(1.5)\n}\n",
+    )
+    .unwrap();
+
+    let ids: Vec<u8> = sections(&bytes).iter().map(|(id, _)| *id).collect();
+    assert_eq!(ids, [1, 2, 3, 5, 6, 7, 10, 11]);
+    assert_eq!(
+        exports(&bytes),
+        [("main".to_string(), 0x00), ("memory".to_string(), 0x02)]
+    );
+}
+
+#[test]
+fn test_string_literals_land_in_data() {
+    let bytes =
+        compile("use std.io\n\nmain = {\n    io.println(\"greetings from wasm\")\n}\n").unwrap();
+    let (_, data) = sections(&bytes)
+        .into_iter()
+        .find(|(id, _)| *id == 11)
+        .expect("data section");
+    assert!(data
+        .windows(b"greetings from wasm".len())
+        .any(|w| w == b"greetings from wasm"));
+}
+
+#[test]
+fn test_unsupported_construct_reports_e3020() {
+    let code = compile(
+        "use std.io\n\n\
+         Point: Type = { x: Int, y: Int }\n\n\
+         main = {\n    p = Point(1, 2)\n    io.println(p.x)\n}\n",
+    )
+    .unwrap_err();
+    assert_eq!(code, "E3020");
+}
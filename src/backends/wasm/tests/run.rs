@@ -0,0 +1,142 @@
+//! 在 wasmi 中运行生成的模块，输出与退出码须与 VM 一致
+
+use std::sync::{Arc, Mutex};
+
+use wasmi::{Caller, Engine as WasmEngine, Extern, Linker, Module, Store};
+
+use super::super::emit_module;
+use crate::backends::Executor;
+use crate::frontend::Compiler;
+use crate::std::string::format_float_default;
+use crate::Engine;
+
+fn memory<'a>(caller: &Caller<'a, String>) -> wasmi::Memory {
+    caller
+        .get_export("memory")
+        .and_then(Extern::into_memory)
+        .expect("exported memory")
+}
+
+/// 按宿主接口运行 wasm 模块，返回输出、`main` 的结果与结束时的内存页数
+fn run_wasm(source: &str) -> (String, i32, u64) {
+    let module = Compiler::new().compile("wasm.yx", source).unwrap();
+    let bytes = emit_module(&module).unwrap();
+
+    let engine = WasmEngine::default();
+    let wasm = Module::new(&engine, &bytes[..]).unwrap();
+    let mut linker = Linker::<String>::new(&engine);
+    linker
+        .func_wrap(
+            "env",
+            "write",
+            |mut caller: Caller<'_, String>, ptr: i32, len: i32| {
+                let memory = memory(&caller);
+                let (ptr, len) = (ptr as usize, len as usize);
+                let text =
+                    String::from_utf8(memory.data(&caller)[ptr..ptr + len].to_vec()).unwrap();
+                caller.data_mut().push_str(&text);
+            },
+        )
+        .unwrap();
+    linker
+        .func_wrap(
+            "env",
+            "format_f64",
+            |mut caller: Caller<'_, String>, value: f64, buf: i32| -> i32 {
+                let memory = memory(&caller);
+                let text = format_float_default(value);
+                let buf = buf as usize;
+                memory.data_mut(&mut caller)[buf..buf + text.len()]
+                    .copy_from_slice(text.as_bytes());
+                text.len() as i32
+            },
+        )
+        .unwrap();
+
+    let mut store = Store::new(&engine, String::new());
+    let instance = linker.instantiate_and_start(&mut store, &wasm).unwrap();
+    let main = instance.get_typed_func::<(), i32>(&store, "main").unwrap();
+    let code = main.call(&mut store, ()).unwrap();
+    let pages = instance.get_memory(&store, "memory").unwrap().size(&store);
+    (store.into_data(), code, pages)
+}
+
+/// 在 VM 中运行同一段源码
+fn run_vm(source: &str) -> (String, i32) {
+    let engine = Engine::default();
+    let program = engine.compile("wasm.yx", source).unwrap();
+    let stdout = Arc::new(Mutex::new(Vec::new()));
+    let mut interpreter = engine.interpreter();
+    interpreter.set_stdout(stdout.clone());
+    interpreter.execute_module(&program).unwrap();
+    let output = String::from_utf8(stdout.lock().unwrap().clone()).unwrap();
+    (output, interpreter.state().exit_code)
+}
+
+/// 断言两端输出与退出码一致，返回输出与 wasm 内存页数
+fn assert_same(source: &str) -> (String, u64) {
+    let (output, code, pages) = run_wasm(source);
+    assert_eq!((output.clone(), code), run_vm(source));
+    (output, pages)
+}
+
+#[test]
+fn test_scalars_match_vm() {
+    let (output, _) = assert_same(
+        "use std.io\n\n\
+         main = {\n    io.println(7 / 2)\n    io.println(-7 % 3)\n    io.println(1.5 * 4.0)\n    \
+         io.println(0.1 + 0.2)\n    io.println(10 > 3)\n    io.println('x')\n    io.print(\"no newline\")\n}\n",
+    );
+    assert!(output.ends_with("no newline"));
+}
+
+#[test]
+fn test_control_flow_and_calls_match_vm() {
+    // 多个基本块与循环回边经 pc/br_table 调度器分派
+    assert_same(
+        "use std.io\n\n\
+         fib: (n: Int) -> Int = (n) => {\n    if n < 2 {\n        return n\n    }\n    return fib(n - 1) + fib(n - 2)\n}\n\n\
+         gcd: (a: Int, b: Int) -> Int = (a, b) => {\n    if b == 0 {\n        return a\n    }\n    return gcd(b, a % b)\n}\n\n\
+         classify: (n: Int) -> String = (n) => {\n    if n < 0 {\n        return \"negative\"\n    } elif n == 0 {\n        return \"zero\"\n    }\n    return \"positive\"\n}\n\n\
+         main = {\n    io.println(fib(15))\n    io.println(gcd(84, 36))\n    \
+         io.println(classify(-3))\n    io.println(classify(0))\n    io.println(classify(8))\n    \
+         mut total = 0\n    mut i = 0\n    while i < 10 {\n        if i % 3 == 0 {\n            total = total + i * i\n        } else {\n            total = total - 1\n        }\n        i = i + 1\n    }\n    io.println(total)\n}\n",
+    );
+}
+
+#[test]
+fn test_strings_and_lists_match_vm() {
+    // 局部变量的种类（Int/Float/String/List）随数据流推断，决定打印与拼接方式
+    assert_same(
+        "use std.io\nuse std.string\nuse std.list\n\n\
+         main = {\n    greeting = \"hello\" + \", \" + \"wasm\"\n    io.println(greeting)\n    \
+         io.println(string.len(greeting))\n    io.println(greeting == \"hello, wasm\")\n    \
+         xs = [3, 1, 4]\n    ys = list.push(xs, 1)\n    io.println(ys[3] + ys[0])\n    io.println(ys)\n    \
+         zs = [7, 8]\n    io.println(list.len(zs))\n    \
+         fs = [1.5, 2.0]\n    io.println(fs)\n    words = [\"a\", \"b\"]\n    io.println(words)\n}\n",
+    );
+}
+
+#[test]
+fn test_allocation_grows_memory_and_matches_vm() {
+    // 每次 push 复制整个列表：总分配远超初始页，迫使 bump 分配器执行 memory.grow
+    let (output, pages) = assert_same(
+        "use std.io\nuse std.list\n\n\
+         main = {\n    mut xs = [0]\n    mut sum = 0\n    mut i = 1\n    while i < 2000 {\n        sum = sum + xs[i - 1]\n        \
+         if i == 1999 {\n            io.println(xs[i - 1])\n        }\n        xs = list.push(xs, i)\n        i = i + 1\n    }\n    io.println(sum)\n}\n",
+    );
+    assert_eq!(output, "1998\n1997001\n");
+    // 约 2000 * 2000 / 2 个 8 字节元素
+    assert!(pages > 200, "memory did not grow: {} pages", pages);
+}
+
+#[test]
+fn test_main_result_is_exit_code() {
+    let (output, code, _) =
+        run_wasm("use std.io\n\nmain: () -> Int = {\n    io.println(\"bye\")\n    return 3\n}\n");
+    assert_eq!(code, 3);
+    assert_eq!(
+        (output, code),
+        run_vm("use std.io\n\nmain: () -> Int = {\n    io.println(\"bye\")\n    return 3\n}\n")
+    );
+}
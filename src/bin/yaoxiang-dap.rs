@@ -0,0 +1,31 @@
+//! YaoXiang Debug Adapter Protocol server
+
+use std::io::BufReader;
+use std::net::TcpListener;
+
+use anyhow::{Context, Result};
+use clap::Parser;
+
+#[derive(Parser, Debug)]
+#[command(name = "yaoxiang-dap", version, about = "YaoXiang debug adapter (DAP)")]
+struct Args {
+    /// Serve one client on this TCP port instead of stdin/stdout
+    #[arg(long, value_name = "PORT")]
+    port: Option<u16>,
+}
+
+fn main() -> Result<()> {
+    let args = Args::parse();
+    // stdout 是 DAP 通道，日志写 stderr
+    yaoxiang::util::logger::init_lsp();
+    match args.port {
+        Some(port) => {
+            let listener = TcpListener::bind(("127.0.0.1", port))
+                .with_context(|| format!("Failed to listen on port {}", port))?;
+            let (stream, _) = listener.accept().context("Failed to accept a client")?;
+            let reader = BufReader::new(stream.try_clone()?);
+            yaoxiang::dap::serve(reader, stream).context("DAP server error")
+        }
+        None => yaoxiang::dap::run_dap_server().context("DAP server error"),
+    }
+}
@@ -0,0 +1,24 @@
+//! YaoXiang 调试适配器（DAP）
+//!
+//! 通过 stdin/stdout（或 TCP）与编辑器交换 Debug Adapter Protocol 消息，基于
+//! [`vm::debug::Debugger`](crate::vm::debug::Debugger) 提供：
+//! - launch 编译并运行 `.yx` 文件，attach 接管宿主程序中的解释器
+//! - 行断点与函数断点
+//! - 继续、暂停、单步进入/越过/跳出（按行或按指令）
+//! - 调用栈、作用域与变量查看
+//!
+//! # 使用方式
+//!
+//! ```bash
+//! yaoxiang-dap              # stdin/stdout
+//! yaoxiang-dap --port 4711  # 在 TCP 端口上等待编辑器连接
+//! ```
+
+pub mod protocol;
+pub mod server;
+pub mod variables;
+
+#[cfg(test)]
+mod tests;
+
+pub use server::{listen, run_dap_server, serve, Server};
@@ -0,0 +1,167 @@
+//! DAP 消息的读写
+//!
+//! 每条消息是一个 JSON 对象，前面带 `Content-Length` 头（与 LSP 相同的分帧），
+//! 分为请求、响应与事件三类。响应与事件由 [`Output`] 统一编号后写出。
+
+use std::io::{self, BufRead, Write};
+use std::sync::{Arc, Mutex};
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+/// 调试适配器名称
+pub const ADAPTER_NAME: &str = "yaoxiang-dap";
+pub const ADAPTER_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// 客户端发来的请求
+#[derive(Debug, Clone, Deserialize)]
+pub struct Request {
+    pub seq: i64,
+    pub command: String,
+    #[serde(default)]
+    pub arguments: Value,
+}
+
+/// 读取一条消息；输入在消息开始前结束时返回 `None`
+pub fn read_message(reader: &mut impl BufRead) -> io::Result<Option<Value>> {
+    let mut length = None;
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return match length {
+                None => Ok(None),
+                Some(_) => Err(invalid_data("消息头之后输入结束")),
+            };
+        }
+        let header = line.trim_end();
+        if header.is_empty() {
+            if length.is_some() {
+                break;
+            }
+            continue;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("Content-Length") {
+                let value = value.trim();
+                length = Some(
+                    value
+                        .parse::<usize>()
+                        .map_err(|_| invalid_data(&format!("无效的 Content-Length: {}", value)))?,
+                );
+            }
+        }
+    }
+    let mut body = vec![0; length.unwrap_or(0)];
+    reader.read_exact(&mut body)?;
+    serde_json::from_slice(&body)
+        .map(Some)
+        .map_err(|e| invalid_data(&format!("无效的 JSON 消息: {}", e)))
+}
+
+/// 写出一条消息
+pub fn write_message(
+    writer: &mut impl Write,
+    message: &Value,
+) -> io::Result<()> {
+    let body = message.to_string();
+    write!(writer, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+    writer.flush()
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+/// 响应与事件的出口，可在线程间共享（克隆后指向同一个输出）
+#[derive(Clone)]
+pub struct Output {
+    inner: Arc<Mutex<(i64, Box<dyn Write + Send>)>>,
+}
+
+impl Output {
+    pub fn new(writer: impl Write + Send + 'static) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new((0, Box::new(writer)))),
+        }
+    }
+
+    /// 回复请求：`Ok` 带响应体，`Err` 带失败原因
+    pub fn respond(
+        &self,
+        request: &Request,
+        result: Result<Value, String>,
+    ) {
+        let mut message = json!({
+            "type": "response",
+            "request_seq": request.seq,
+            "command": request.command,
+            "success": result.is_ok(),
+        });
+        match result {
+            Ok(body) if !body.is_null() => message["body"] = body,
+            Ok(_) => {}
+            Err(error) => message["message"] = Value::String(error),
+        }
+        self.send(message);
+    }
+
+    /// 发出事件
+    pub fn event(
+        &self,
+        event: &str,
+        body: Value,
+    ) {
+        self.send(json!({
+            "type": "event",
+            "event": event,
+            "body": body,
+        }));
+    }
+
+    fn send(
+        &self,
+        mut message: Value,
+    ) {
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        inner.0 += 1;
+        message["seq"] = json!(inner.0);
+        // 客户端断开后无处可写，忽略错误
+        let _ = write_message(&mut inner.1, &message);
+    }
+}
+
+/// 把写入的文本作为 `output` 事件发给客户端（程序的 `print` 输出）
+pub struct OutputEvents {
+    output: Output,
+    category: &'static str,
+}
+
+impl OutputEvents {
+    pub fn new(
+        output: Output,
+        category: &'static str,
+    ) -> Self {
+        Self { output, category }
+    }
+}
+
+impl Write for OutputEvents {
+    fn write(
+        &mut self,
+        buf: &[u8],
+    ) -> io::Result<usize> {
+        self.output.event(
+            "output",
+            json!({
+                "category": self.category,
+                "output": String::from_utf8_lossy(buf),
+            }),
+        );
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
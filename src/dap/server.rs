@@ -0,0 +1,517 @@
+//! DAP 服务器核心
+//!
+//! 实现请求分发与调试会话的生命周期。
+//!
+//! 架构：
+//! ```text
+//! 客户端 → run → handle → Debugger（断点、单步）
+//!                            ↑ 钩子
+//!          运行线程：Interpreter 执行程序，输出转为 output 事件
+//!          事件线程：Debugger::wait → stopped / terminated 事件
+//! ```
+//!
+//! launch 编译并运行 `.yx` 文件；attach 接管宿主程序交给 [`Server::attachable`] 的
+//! 调试器，宿主可以用 [`listen`] 在 TCP 端口上等待编辑器附加。程序在收到
+//! `configurationDone` 之后才开始运行，之前设置的断点都已生效。
+
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader};
+use std::net::{TcpListener, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle, ThreadId};
+
+use anyhow::Result;
+use serde_json::{json, Value};
+use tracing::{debug, info};
+
+use crate::backends::{CapabilityPolicy, Executor};
+use crate::middle::bytecode::BytecodeModule;
+use crate::util::diagnostic::panic::RuntimePanic;
+use crate::vm::debug::{
+    Breakpoint, BreakpointId, DebugEvent, Debugger, Step, StepUnit, Stop, StopReason,
+};
+use crate::Engine;
+
+use super::protocol::{self, Output, OutputEvents, Request};
+use super::variables::Variables;
+
+/// 通过 stdin/stdout 启动 DAP 服务器
+pub fn run_dap_server() -> Result<()> {
+    info!("启动 YaoXiang DAP 服务器 v{}", protocol::ADAPTER_VERSION);
+    serve(io::stdin().lock(), io::stdout())
+}
+
+/// 在 `reader` / `writer` 上处理一个调试会话
+pub fn serve(
+    reader: impl BufRead,
+    writer: impl io::Write + Send + 'static,
+) -> Result<()> {
+    Server::new(Output::new(writer)).run(reader)
+}
+
+/// 在 `addr` 上等待一个客户端，让它附加到 `debugger`
+///
+/// 宿主程序把 `debugger` 挂到自己的解释器上，然后在运行线程中执行程序；客户端以
+/// attach 请求接管。返回处理会话的线程。
+pub fn listen(
+    debugger: Debugger,
+    addr: impl ToSocketAddrs,
+) -> io::Result<JoinHandle<Result<()>>> {
+    let listener = TcpListener::bind(addr)?;
+    Ok(thread::spawn(move || {
+        let (stream, peer) = listener.accept()?;
+        info!("DAP 客户端已连接: {}", peer);
+        let reader = BufReader::new(stream.try_clone()?);
+        Server::new(Output::new(stream))
+            .attachable(debugger)
+            .run(reader)
+    }))
+}
+
+/// 一个调试会话的服务器状态
+pub struct Server {
+    output: Output,
+    /// 可供 attach 的调试器
+    attachable: Option<Debugger>,
+    /// launch / attach 之后的调试目标
+    target: Option<Target>,
+    variables: Variables,
+    /// 出现过的线程；DAP 线程 id 为下标加一
+    threads: Arc<Mutex<Vec<ThreadId>>>,
+}
+
+struct Target {
+    debugger: Debugger,
+    /// 等待 configurationDone 启动的程序
+    pending: Option<Launch>,
+    /// 每个源文件的行断点
+    source_breakpoints: HashMap<String, Vec<BreakpointId>>,
+    function_breakpoints: Vec<BreakpointId>,
+    events: Option<JoinHandle<()>>,
+}
+
+struct Launch {
+    program: BytecodeModule,
+    path: PathBuf,
+    stop_on_entry: bool,
+}
+
+impl Server {
+    pub fn new(output: Output) -> Self {
+        Self {
+            output,
+            attachable: None,
+            target: None,
+            variables: Variables::default(),
+            threads: Arc::default(),
+        }
+    }
+
+    /// 允许客户端以 attach 请求接管 `debugger`
+    pub fn attachable(
+        mut self,
+        debugger: Debugger,
+    ) -> Self {
+        self.attachable = Some(debugger);
+        self
+    }
+
+    /// 处理请求，直到客户端断开或发出 `disconnect`
+    pub fn run(
+        mut self,
+        mut reader: impl BufRead,
+    ) -> Result<()> {
+        while let Some(message) = protocol::read_message(&mut reader)? {
+            let Ok(request) = serde_json::from_value::<Request>(message) else {
+                continue;
+            };
+            debug!("DAP 请求: {}", request.command);
+            let result = self.handle(&request);
+            // launch / attach 成功后才能接受断点等配置请求
+            let started = matches!(request.command.as_str(), "launch" | "attach") && result.is_ok();
+            self.output.respond(&request, result);
+            match request.command.as_str() {
+                "disconnect" => break,
+                _ if started => self.output.event("initialized", json!({})),
+                _ => {}
+            }
+        }
+        if let Some(target) = &self.target {
+            target.debugger.detach();
+        }
+        Ok(())
+    }
+
+    fn handle(
+        &mut self,
+        request: &Request,
+    ) -> Result<Value, String> {
+        let args = &request.arguments;
+        match request.command.as_str() {
+            "initialize" => Ok(json!({
+                "supportsConfigurationDoneRequest": true,
+                "supportsFunctionBreakpoints": true,
+                "supportsSteppingGranularity": true,
+            })),
+            "launch" => self.launch(args),
+            "attach" => self.attach(),
+            "setBreakpoints" => self.set_breakpoints(args),
+            "setFunctionBreakpoints" => self.set_function_breakpoints(args),
+            "setExceptionBreakpoints" => Ok(json!({ "breakpoints": [] })),
+            "configurationDone" => self.configuration_done(),
+            "threads" => Ok(self.threads()),
+            "stackTrace" => self.stack_trace(args),
+            "scopes" => self.scopes(args),
+            "variables" => self.variables(args),
+            "continue" => {
+                self.resumed()?.resume();
+                Ok(json!({ "allThreadsContinued": true }))
+            }
+            "next" => self.step(Step::Over, args),
+            "stepIn" => self.step(Step::Into, args),
+            "stepOut" => self.step(Step::Out, args),
+            "pause" => {
+                self.debugger()?.pause();
+                Ok(Value::Null)
+            }
+            "disconnect" => Ok(Value::Null),
+            other => Err(format!("不支持的请求: {}", other)),
+        }
+    }
+
+    fn launch(
+        &mut self,
+        args: &Value,
+    ) -> Result<Value, String> {
+        let program = args["program"].as_str().ok_or("launch 缺少 program 参数")?;
+        let path = std::path::absolute(program).map_err(|e| e.to_string())?;
+        let source = std::fs::read_to_string(&path)
+            .map_err(|e| format!("无法读取 {}: {}", path.display(), e))?;
+        let program = Engine::default()
+            .compile(&path.display().to_string(), &source)
+            .map_err(|e| e.to_string())?;
+        if let Some(arguments) = args["args"].as_array() {
+            let argv = std::iter::once(path.display().to_string())
+                .chain(arguments.iter().filter_map(Value::as_str).map(String::from))
+                .collect();
+            crate::std::env::set_program_args(argv);
+        }
+        self.target = Some(Target {
+            debugger: Debugger::new(&program),
+            pending: Some(Launch {
+                program,
+                path,
+                stop_on_entry: args["stopOnEntry"].as_bool().unwrap_or(false),
+            }),
+            source_breakpoints: HashMap::new(),
+            function_breakpoints: Vec::new(),
+            events: None,
+        });
+        Ok(Value::Null)
+    }
+
+    fn attach(&mut self) -> Result<Value, String> {
+        let debugger = self
+            .attachable
+            .take()
+            .ok_or("没有可以附加的程序：attach 需要宿主程序通过 dap::listen 提供调试器")?;
+        self.target = Some(Target {
+            debugger,
+            pending: None,
+            source_breakpoints: HashMap::new(),
+            function_breakpoints: Vec::new(),
+            events: None,
+        });
+        Ok(Value::Null)
+    }
+
+    fn set_breakpoints(
+        &mut self,
+        args: &Value,
+    ) -> Result<Value, String> {
+        let target = self.target.as_mut().ok_or("尚未 launch 或 attach")?;
+        let source = &args["source"];
+        let file = source["path"]
+            .as_str()
+            .or_else(|| source["name"].as_str())
+            .ok_or("setBreakpoints 缺少 source.path")?
+            .to_string();
+        for id in target.source_breakpoints.remove(&file).unwrap_or_default() {
+            target.debugger.remove_breakpoint(id);
+        }
+        let mut ids = Vec::new();
+        let breakpoints = args["breakpoints"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|requested| {
+                let line = requested["line"].as_u64().unwrap_or(0) as usize;
+                let at = Breakpoint::Line {
+                    file: file.clone(),
+                    line,
+                };
+                match target.debugger.set_breakpoint(at) {
+                    Some(id) => {
+                        ids.push(id);
+                        let line = match target.debugger.breakpoint(id) {
+                            Some(Breakpoint::Line { line, .. }) => line,
+                            _ => line,
+                        };
+                        json!({ "id": id, "verified": true, "line": line, "source": source })
+                    }
+                    None => json!({
+                        "verified": false,
+                        "line": line,
+                        "message": "程序中没有这个源文件",
+                    }),
+                }
+            })
+            .collect::<Vec<_>>();
+        target.source_breakpoints.insert(file, ids);
+        Ok(json!({ "breakpoints": breakpoints }))
+    }
+
+    fn set_function_breakpoints(
+        &mut self,
+        args: &Value,
+    ) -> Result<Value, String> {
+        let target = self.target.as_mut().ok_or("尚未 launch 或 attach")?;
+        for id in target.function_breakpoints.drain(..) {
+            target.debugger.remove_breakpoint(id);
+        }
+        let breakpoints = args["breakpoints"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|requested| {
+                let at = Breakpoint::Instruction {
+                    function: requested["name"].as_str().unwrap_or_default().to_string(),
+                    offset: 0,
+                };
+                match target.debugger.set_breakpoint(at) {
+                    Some(id) => {
+                        target.function_breakpoints.push(id);
+                        json!({ "id": id, "verified": true })
+                    }
+                    None => json!({ "verified": false, "message": "没有这个函数" }),
+                }
+            })
+            .collect::<Vec<_>>();
+        Ok(json!({ "breakpoints": breakpoints }))
+    }
+
+    /// 启动等待中的程序与事件线程
+    fn configuration_done(&mut self) -> Result<Value, String> {
+        let target = self.target.as_mut().ok_or("尚未 launch 或 attach")?;
+        if target.events.is_some() {
+            return Ok(Value::Null);
+        }
+        let stop_on_entry = target.pending.as_ref().is_some_and(|l| l.stop_on_entry);
+        if stop_on_entry {
+            target.debugger.pause();
+        }
+        if let Some(launch) = target.pending.take() {
+            spawn_program(launch, target.debugger.clone(), self.output.clone());
+        }
+        let debugger = target.debugger.clone();
+        let output = self.output.clone();
+        let threads = Arc::clone(&self.threads);
+        target.events = Some(thread::spawn(move || {
+            forward_events(debugger, output, threads, stop_on_entry)
+        }));
+        Ok(Value::Null)
+    }
+
+    fn threads(&self) -> Value {
+        let threads = self.threads.lock().unwrap_or_else(|e| e.into_inner());
+        let threads: Vec<Value> = if threads.is_empty() {
+            vec![json!({ "id": 1, "name": "main" })]
+        } else {
+            (1..=threads.len())
+                .map(|id| json!({ "id": id, "name": format!("thread {}", id) }))
+                .collect()
+        };
+        json!({ "threads": threads })
+    }
+
+    fn stack_trace(
+        &self,
+        args: &Value,
+    ) -> Result<Value, String> {
+        let stop = self.stopped()?;
+        let start = args["startFrame"].as_u64().unwrap_or(0) as usize;
+        let levels = match args["levels"].as_u64() {
+            Some(0) | None => usize::MAX,
+            Some(levels) => levels as usize,
+        };
+        let frames: Vec<Value> = stop
+            .frames
+            .iter()
+            .enumerate()
+            .skip(start)
+            .take(levels)
+            .map(|(level, frame)| {
+                let mut value = json!({
+                    "id": level,
+                    "name": frame.function,
+                    "line": 0,
+                    "column": 0,
+                    "instructionPointerReference": frame.ip.to_string(),
+                });
+                if let Some(location) = &frame.location {
+                    value["line"] = json!(location.line);
+                    value["column"] = json!(location.column);
+                    if let Some(file) = &location.file {
+                        value["source"] = source(file);
+                    }
+                }
+                value
+            })
+            .collect();
+        Ok(json!({ "stackFrames": frames, "totalFrames": stop.frames.len() }))
+    }
+
+    fn scopes(
+        &mut self,
+        args: &Value,
+    ) -> Result<Value, String> {
+        self.stopped()?;
+        let level = args["frameId"].as_u64().unwrap_or(0) as usize;
+        let reference = self.variables.scope(level);
+        Ok(json!({
+            "scopes": [{
+                "name": "Registers",
+                "presentationHint": "locals",
+                "variablesReference": reference,
+                "expensive": false,
+            }]
+        }))
+    }
+
+    fn variables(
+        &mut self,
+        args: &Value,
+    ) -> Result<Value, String> {
+        let reference = args["variablesReference"].as_i64().unwrap_or(0);
+        let debugger = self.debugger()?.clone();
+        let variables = self
+            .variables
+            .children(reference, &debugger)
+            .unwrap_or_default();
+        Ok(json!({ "variables": variables }))
+    }
+
+    fn step(
+        &mut self,
+        step: Step,
+        args: &Value,
+    ) -> Result<Value, String> {
+        let unit = match args["granularity"].as_str() {
+            Some("instruction") => StepUnit::Instruction,
+            _ => StepUnit::Line,
+        };
+        self.resumed()?.step(step, unit);
+        Ok(Value::Null)
+    }
+
+    fn debugger(&self) -> Result<&Debugger, String> {
+        self.target
+            .as_ref()
+            .map(|target| &target.debugger)
+            .ok_or_else(|| "尚未 launch 或 attach".to_string())
+    }
+
+    /// 程序即将继续运行：变量引用失效
+    fn resumed(&mut self) -> Result<&Debugger, String> {
+        self.variables.clear();
+        self.debugger()
+    }
+
+    fn stopped(&self) -> Result<Stop, String> {
+        self.debugger()?
+            .stopped()
+            .ok_or_else(|| "程序没有暂停".to_string())
+    }
+}
+
+/// DAP 的 `Source` 对象
+fn source(file: &str) -> Value {
+    let name = Path::new(file)
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| file.to_string());
+    json!({ "name": name, "path": file })
+}
+
+/// 在运行线程中执行程序：输出转为 output 事件，结束时发出 exited 事件
+fn spawn_program(
+    launch: Launch,
+    debugger: Debugger,
+    output: Output,
+) {
+    thread::spawn(move || {
+        let mut interpreter = Engine::builder()
+            .capabilities(CapabilityPolicy::allow_all())
+            .build()
+            .interpreter();
+        if let Some(dir) = launch.path.parent() {
+            interpreter.set_import_base_dir(dir);
+        }
+        interpreter.set_stdout(Arc::new(Mutex::new(OutputEvents::new(
+            output.clone(),
+            "stdout",
+        ))));
+        debugger.attach(&mut interpreter);
+        let exit_code = match interpreter.execute_module(&launch.program) {
+            Ok(()) => interpreter.state().exit_code,
+            Err(e) => {
+                let panic = RuntimePanic::new(e, &launch.program);
+                output.event(
+                    "output",
+                    json!({ "category": "stderr", "output": format!("{}\n", panic.render()) }),
+                );
+                1
+            }
+        };
+        output.event("exited", json!({ "exitCode": exit_code }));
+        debugger.finish();
+    });
+}
+
+/// 把调试器的暂停与结束转为 stopped / terminated 事件
+fn forward_events(
+    debugger: Debugger,
+    output: Output,
+    threads: Arc<Mutex<Vec<ThreadId>>>,
+    mut stop_on_entry: bool,
+) {
+    while let DebugEvent::Stopped(stop) = debugger.wait() {
+        let thread_id = {
+            let mut threads = threads.lock().unwrap_or_else(|e| e.into_inner());
+            match threads.iter().position(|t| *t == stop.thread) {
+                Some(index) => index + 1,
+                None => {
+                    threads.push(stop.thread);
+                    threads.len()
+                }
+            }
+        };
+        let mut body = json!({
+            "threadId": thread_id,
+            "allThreadsStopped": true,
+        });
+        body["reason"] = json!(match stop.reason {
+            StopReason::Breakpoint(id) => {
+                body["hitBreakpointIds"] = json!([id]);
+                "breakpoint"
+            }
+            StopReason::Step => "step",
+            StopReason::Pause if stop_on_entry => "entry",
+            StopReason::Pause => "pause",
+        });
+        stop_on_entry = false;
+        output.event("stopped", body);
+    }
+    output.event("terminated", json!({}));
+}
@@ -0,0 +1,10 @@
+//! DAP 模块测试
+//!
+//! 测试覆盖：
+//! - DAP 消息分帧与响应、事件的构建
+//! - DAP 服务器的调试会话
+//! - DAP 变量的显示与展开
+
+mod protocol;
+mod server;
+mod variables;
@@ -0,0 +1,97 @@
+//! DAP 消息读写测试
+//!
+//! 测试覆盖：
+//! - Content-Length 分帧的往返
+//! - 输入结束与无效消息
+//! - 响应与事件的编号和字段
+
+use std::io::{BufReader, Cursor, Write};
+use std::sync::{Arc, Mutex};
+
+use serde_json::{json, Value};
+
+use crate::dap::protocol::{read_message, write_message, Output, OutputEvents, Request};
+
+/// 写入共享缓冲区，供测试读回
+#[derive(Clone, Default)]
+struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Buffer {
+    fn write(
+        &mut self,
+        buf: &[u8],
+    ) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Buffer {
+    fn messages(&self) -> Vec<Value> {
+        let bytes = self.0.lock().unwrap().clone();
+        let mut reader = BufReader::new(Cursor::new(bytes));
+        std::iter::from_fn(|| read_message(&mut reader).unwrap()).collect()
+    }
+}
+
+#[test]
+fn test_message_round_trip() {
+    let mut bytes = Vec::new();
+    let first = json!({ "seq": 1, "type": "request", "command": "initialize" });
+    let second = json!({ "seq": 2, "type": "request", "command": "threads", "text": "爻象" });
+    write_message(&mut bytes, &first).unwrap();
+    write_message(&mut bytes, &second).unwrap();
+    assert!(bytes.starts_with(b"Content-Length: "));
+
+    let mut reader = BufReader::new(Cursor::new(bytes));
+    assert_eq!(read_message(&mut reader).unwrap(), Some(first));
+    assert_eq!(read_message(&mut reader).unwrap(), Some(second));
+    assert_eq!(read_message(&mut reader).unwrap(), None);
+}
+
+#[test]
+fn test_invalid_messages() {
+    let mut reader = BufReader::new(Cursor::new(b"Content-Length: x\r\n\r\n".to_vec()));
+    assert!(read_message(&mut reader).is_err());
+
+    let mut reader = BufReader::new(Cursor::new(b"Content-Length: 5\r\n\r\n{oops".to_vec()));
+    assert!(read_message(&mut reader).is_err());
+
+    let mut reader = BufReader::new(Cursor::new(b"Content-Length: 5\r\n".to_vec()));
+    assert!(read_message(&mut reader).is_err());
+}
+
+#[test]
+fn test_responses_and_events_are_numbered() {
+    let buffer = Buffer::default();
+    let output = Output::new(buffer.clone());
+    let request: Request = serde_json::from_value(json!({
+        "seq": 7,
+        "type": "request",
+        "command": "threads",
+    }))
+    .unwrap();
+    assert_eq!(request.arguments, Value::Null);
+
+    output.respond(&request, Ok(json!({ "threads": [] })));
+    output.respond(&request, Err("失败".to_string()));
+    OutputEvents::new(output.clone(), "stdout")
+        .write_all(b"hi\n")
+        .unwrap();
+
+    let messages = buffer.messages();
+    assert_eq!(messages.len(), 3);
+    assert_eq!(messages[0]["seq"], 1);
+    assert_eq!(messages[0]["request_seq"], 7);
+    assert_eq!(messages[0]["success"], true);
+    assert_eq!(messages[0]["body"]["threads"], json!([]));
+    assert_eq!(messages[1]["success"], false);
+    assert_eq!(messages[1]["message"], "失败");
+    assert_eq!(messages[2]["seq"], 3);
+    assert_eq!(messages[2]["event"], "output");
+    assert_eq!(messages[2]["body"]["output"], "hi\n");
+}
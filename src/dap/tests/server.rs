@@ -0,0 +1,251 @@
+//! DAP 服务器测试
+//!
+//! 测试覆盖：
+//! - launch 一个程序：行断点命中后查看线程、调用栈与寄存器，单步、继续，
+//!   程序输出转为 output 事件，结束时发出 exited 与 terminated
+//! - 没有可附加的调试器时 attach 失败
+//! - 不支持的请求返回失败响应
+
+use std::io::{self, BufReader, Read, Write};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread::{self, JoinHandle};
+
+use serde_json::{json, Value};
+
+use crate::dap::protocol::{read_message, write_message};
+use crate::dap::serve;
+
+const SOURCE: &str = "\
+use std.io.{println};
+
+fact: (n: Int) -> Int = (n) => {
+    if n <= 1 {
+        return 1
+    }
+    return n * fact(n - 1)
+}
+
+main: () -> Int = {
+    x = fact(3)
+    println(x)
+    return 0
+}
+";
+
+/// 字节通道的读端
+struct ChannelReader {
+    chunks: Receiver<Vec<u8>>,
+    pending: Vec<u8>,
+}
+
+impl Read for ChannelReader {
+    fn read(
+        &mut self,
+        buf: &mut [u8],
+    ) -> io::Result<usize> {
+        while self.pending.is_empty() {
+            match self.chunks.recv() {
+                Ok(chunk) => self.pending = chunk,
+                Err(_) => return Ok(0),
+            }
+        }
+        let n = buf.len().min(self.pending.len());
+        buf[..n].copy_from_slice(&self.pending[..n]);
+        self.pending.drain(..n);
+        Ok(n)
+    }
+}
+
+/// 字节通道的写端
+struct ChannelWriter(Sender<Vec<u8>>);
+
+impl Write for ChannelWriter {
+    fn write(
+        &mut self,
+        buf: &[u8],
+    ) -> io::Result<usize> {
+        self.0
+            .send(buf.to_vec())
+            .map_err(|_| io::Error::from(io::ErrorKind::BrokenPipe))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn channel() -> (ChannelWriter, BufReader<ChannelReader>) {
+    let (sender, receiver) = mpsc::channel();
+    let reader = ChannelReader {
+        chunks: receiver,
+        pending: Vec::new(),
+    };
+    (ChannelWriter(sender), BufReader::new(reader))
+}
+
+/// 在另一线程上运行服务器的测试客户端
+struct Client {
+    requests: Option<ChannelWriter>,
+    messages: BufReader<ChannelReader>,
+    events: Vec<Value>,
+    seq: i64,
+    server: Option<JoinHandle<anyhow::Result<()>>>,
+}
+
+impl Client {
+    fn start() -> Self {
+        let (requests, server_input) = channel();
+        let (server_output, messages) = channel();
+        let server = thread::spawn(move || serve(server_input, server_output));
+        Self {
+            requests: Some(requests),
+            messages,
+            events: Vec::new(),
+            seq: 0,
+            server: Some(server),
+        }
+    }
+
+    fn next_message(&mut self) -> Value {
+        read_message(&mut self.messages)
+            .unwrap()
+            .expect("服务器提前关闭了输出")
+    }
+
+    /// 发出请求并等待响应，期间收到的事件留给 [`Client::event`]
+    fn request(
+        &mut self,
+        command: &str,
+        arguments: Value,
+    ) -> Value {
+        self.seq += 1;
+        let request = json!({
+            "seq": self.seq,
+            "type": "request",
+            "command": command,
+            "arguments": arguments,
+        });
+        write_message(self.requests.as_mut().unwrap(), &request).unwrap();
+        loop {
+            let message = self.next_message();
+            if message["type"] == "response" && message["request_seq"] == self.seq {
+                assert_eq!(message["command"], command);
+                return message;
+            }
+            self.events.push(message);
+        }
+    }
+
+    /// 等待名为 `name` 的事件
+    fn event(
+        &mut self,
+        name: &str,
+    ) -> Value {
+        if let Some(index) = self.events.iter().position(|e| e["event"] == name) {
+            return self.events.remove(index);
+        }
+        loop {
+            let message = self.next_message();
+            if message["event"] == name {
+                return message;
+            }
+            self.events.push(message);
+        }
+    }
+
+    fn finish(mut self) {
+        self.requests = None;
+        self.server.take().unwrap().join().unwrap().unwrap();
+    }
+}
+
+fn success(response: &Value) -> &Value {
+    assert_eq!(response["success"], true, "{}", response);
+    &response["body"]
+}
+
+#[test]
+fn test_launch_session() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("fact.yx");
+    std::fs::write(&path, SOURCE).unwrap();
+    let path = path.display().to_string();
+
+    let mut client = Client::start();
+    let capabilities = client.request("initialize", json!({ "adapterID": "yaoxiang" }));
+    assert_eq!(
+        success(&capabilities)["supportsConfigurationDoneRequest"],
+        true
+    );
+    success(&client.request("launch", json!({ "program": path })));
+    client.event("initialized");
+
+    let breakpoints = client.request(
+        "setBreakpoints",
+        json!({ "source": { "path": path }, "breakpoints": [{ "line": 7 }] }),
+    );
+    let breakpoint = &success(&breakpoints)["breakpoints"][0];
+    assert_eq!(breakpoint["verified"], true);
+    assert_eq!(breakpoint["line"], 7);
+    success(&client.request("configurationDone", Value::Null));
+
+    let stopped = client.event("stopped");
+    assert_eq!(stopped["body"]["reason"], "breakpoint");
+    let thread_id = stopped["body"]["threadId"].clone();
+    let threads = client.request("threads", Value::Null);
+    assert_eq!(success(&threads)["threads"][0]["id"], thread_id);
+
+    let trace = client.request("stackTrace", json!({ "threadId": thread_id }));
+    let frames = success(&trace)["stackFrames"].as_array().unwrap().clone();
+    assert_eq!(frames[0]["name"], "fact");
+    assert_eq!(frames[0]["line"], 7);
+    assert_eq!(frames[0]["source"]["name"], "fact.yx");
+    assert_eq!(frames.last().unwrap()["name"], "main");
+
+    let scopes = client.request("scopes", json!({ "frameId": 0 }));
+    let reference = success(&scopes)["scopes"][0]["variablesReference"].clone();
+    let variables = client.request("variables", json!({ "variablesReference": reference }));
+    let variables = success(&variables)["variables"].as_array().unwrap();
+    assert_eq!(variables[0]["name"], "r0");
+    assert_eq!(variables[0]["value"], "3");
+
+    // 去掉断点后单步，进入递归调用再次停下
+    client.request(
+        "setBreakpoints",
+        json!({ "source": { "path": path }, "breakpoints": [] }),
+    );
+    success(&client.request("stepIn", json!({ "threadId": thread_id })));
+    assert_eq!(client.event("stopped")["body"]["reason"], "step");
+
+    success(&client.request("continue", json!({ "threadId": thread_id })));
+    let output = client.event("output");
+    assert_eq!(output["body"]["category"], "stdout");
+    assert_eq!(output["body"]["output"], "6\n");
+    assert_eq!(client.event("exited")["body"]["exitCode"], 0);
+    client.event("terminated");
+
+    success(&client.request("disconnect", Value::Null));
+    client.finish();
+}
+
+#[test]
+fn test_attach_without_debugger_fails() {
+    let mut client = Client::start();
+    let response = client.request("attach", Value::Null);
+    assert_eq!(response["success"], false);
+    assert!(client.events.is_empty());
+    client.finish();
+}
+
+#[test]
+fn test_unsupported_request() {
+    let mut client = Client::start();
+    let response = client.request("readMemory", json!({}));
+    assert_eq!(response["success"], false);
+    assert!(response["message"].as_str().unwrap().contains("readMemory"));
+
+    let response = client.request("stackTrace", json!({}));
+    assert_eq!(response["success"], false);
+    client.finish();
+}
@@ -0,0 +1,20 @@
+//! DAP 变量显示测试
+//!
+//! 测试覆盖：
+//! - 标量、unit 与复合值的显示文本
+
+use serde_json::json;
+
+use crate::dap::variables::display;
+
+#[test]
+fn test_display() {
+    assert_eq!(display(&json!(null)), "unit");
+    assert_eq!(display(&json!(42)), "42");
+    assert_eq!(display(&json!("爻")), "\"爻\"");
+    assert_eq!(display(&json!([1, 2, 3])), "[1,2,3]");
+    assert_eq!(display(&json!({ "x": 1 })), "{\"x\":1}");
+
+    let long: Vec<i64> = (0..100).collect();
+    assert_eq!(display(&json!(long)), "[100 items]");
+}
@@ -0,0 +1,147 @@
+//! 暂停期间的变量查看
+//!
+//! DAP 的 `variablesReference` 指向 [`Variables`] 表中的一项（从 1 开始）：调用栈的
+//! 一帧或一个可展开的值。帧的寄存器在被暂停的线程上读取并序列化为 JSON，列表、
+//! 结构体等复合值按 JSON 的数组与对象展开。程序继续运行后表被清空。
+
+use serde_json::{json, Value};
+
+use crate::vm::debug::Debugger;
+
+/// 折叠显示复合值的最大长度
+const MAX_INLINE_LEN: usize = 60;
+
+/// 变量引用表
+#[derive(Debug, Default)]
+pub struct Variables {
+    nodes: Vec<Node>,
+}
+
+#[derive(Debug)]
+enum Node {
+    /// 调用栈第 `level` 层（0 为最内层）的寄存器
+    Frame(usize),
+    /// 复合值
+    Value(Value),
+}
+
+impl Variables {
+    /// 程序继续运行，之前的引用全部失效
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+    }
+
+    /// 调用栈第 `level` 层的作用域引用
+    pub fn scope(
+        &mut self,
+        level: usize,
+    ) -> i64 {
+        self.push(Node::Frame(level))
+    }
+
+    /// `reference` 下的变量（DAP `Variable` 对象）；引用无效或程序已继续运行时为 `None`
+    pub fn children(
+        &mut self,
+        reference: i64,
+        debugger: &Debugger,
+    ) -> Option<Vec<Value>> {
+        let index = usize::try_from(reference).ok()?.checked_sub(1)?;
+        match self.nodes.get(index)? {
+            Node::Frame(level) => {
+                let level = *level;
+                let registers = debugger.inspect(move |vm| {
+                    let registers = match level {
+                        0 => Some(vm.registers()),
+                        level => vm.caller_registers(level),
+                    }?;
+                    Some(
+                        registers
+                            .iter()
+                            .map(|value| match serde_json::to_value(vm.value(&value.get())) {
+                                Ok(json) => (display(&json), Some(json)),
+                                Err(_) => (value.to_string(), None),
+                            })
+                            .collect::<Vec<_>>(),
+                    )
+                })??;
+                Some(
+                    registers
+                        .into_iter()
+                        .enumerate()
+                        .map(|(i, (shown, json))| self.variable(format!("r{}", i), shown, json))
+                        .collect(),
+                )
+            }
+            Node::Value(Value::Array(items)) => {
+                let items = items.clone();
+                Some(
+                    items
+                        .into_iter()
+                        .enumerate()
+                        .map(|(i, item)| self.variable(i.to_string(), display(&item), Some(item)))
+                        .collect(),
+                )
+            }
+            Node::Value(Value::Object(fields)) => {
+                let fields = fields.clone();
+                Some(
+                    fields
+                        .into_iter()
+                        .map(|(name, field)| self.variable(name, display(&field), Some(field)))
+                        .collect(),
+                )
+            }
+            Node::Value(_) => Some(Vec::new()),
+        }
+    }
+
+    fn variable(
+        &mut self,
+        name: String,
+        shown: String,
+        json: Option<Value>,
+    ) -> Value {
+        let reference = match json {
+            Some(json @ Value::Array(_)) | Some(json @ Value::Object(_)) if !is_empty(&json) => {
+                self.push(Node::Value(json))
+            }
+            _ => 0,
+        };
+        json!({
+            "name": name,
+            "value": shown,
+            "variablesReference": reference,
+        })
+    }
+
+    fn push(
+        &mut self,
+        node: Node,
+    ) -> i64 {
+        self.nodes.push(node);
+        self.nodes.len() as i64
+    }
+}
+
+fn is_empty(json: &Value) -> bool {
+    match json {
+        Value::Array(items) => items.is_empty(),
+        Value::Object(fields) => fields.is_empty(),
+        _ => true,
+    }
+}
+
+/// 变量的显示文本：`unit` 对应 JSON 的 null，过长的复合值只显示元素个数
+pub fn display(json: &Value) -> String {
+    let text = match json {
+        Value::Null => return "unit".to_string(),
+        other => other.to_string(),
+    };
+    match json {
+        Value::Array(items) if text.len() > MAX_INLINE_LEN => format!("[{} items]", items.len()),
+        Value::Object(fields) if text.len() > MAX_INLINE_LEN => {
+            format!("{{{} fields}}", fields.len())
+        }
+        _ => text,
+    }
+}
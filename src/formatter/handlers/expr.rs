@@ -13,7 +13,7 @@ pub fn format_expr(
 ) -> String {
     match expr {
         Expr::Lit(lit, _span) => format_literal(lit, ctx),
-        Expr::Var(name, _span) => name.clone(),
+        Expr::Var(name, _span) => name.to_string(),
         Expr::BinOp {
             op,
             left,
@@ -93,14 +93,33 @@ pub fn format_expr(
                 "continue".to_string()
             }
         }
+        // 字面量后缀：`42i32`、`1.0f32`
+        Expr::Cast {
+            expr: inner,
+            target_type: target_type @ (Type::Int(_) | Type::Float(_)),
+            checked: false,
+            span: _,
+        } if matches!(
+            inner.as_ref(),
+            Expr::Lit(Literal::Int(_) | Literal::Float(_), _)
+        ) =>
+        {
+            format!(
+                "{}{}",
+                format_expr(inner, ctx, source_map),
+                super::types::format_type(target_type, source_map)
+            )
+        }
         Expr::Cast {
             expr: inner,
             target_type,
+            checked,
             span: _,
         } => {
             format!(
-                "{} as {}",
+                "{} {} {}",
                 format_expr(inner, ctx, source_map),
+                if *checked { "as?" } else { "as" },
                 super::types::format_type(target_type, source_map)
             )
         }
@@ -148,6 +167,26 @@ pub fn format_expr(
             field,
             span: _,
         } => format_field_access(inner, field, ctx, source_map),
+        Expr::CompoundAssign {
+            op,
+            target,
+            value,
+            span: _,
+        } => format!(
+            "{} {}= {}",
+            format_expr(target, ctx, source_map),
+            binop_str(op),
+            format_expr(value, ctx, source_map)
+        ),
+        Expr::Pipe {
+            value,
+            func,
+            span: _,
+        } => format!(
+            "{} |> {}",
+            format_expr(value, ctx, source_map),
+            format_expr(func, ctx, source_map)
+        ),
         Expr::Try {
             expr: inner,
             span: _,
@@ -164,7 +203,10 @@ pub fn format_expr(
             format!("unsafe {}", format_block(body, ctx, source_map))
         }
         Expr::Spawn { body, .. } => {
-            format!("spawn {}", format_block(body, ctx, source_map))
+            match crate::frontend::core::spawn::analysis::spawn_call(body) {
+                Some(call) => format!("spawn {}", format_expr(call, ctx, source_map)),
+                None => format!("spawn {}", format_block(body, ctx, source_map)),
+            }
         }
         Expr::Lambda {
             params,
@@ -220,6 +262,7 @@ pub(crate) fn format_literal(
             }
         }
         Literal::Bool(b) => b.to_string(),
+        Literal::Void => "void".to_string(),
         Literal::Char(c) => {
             let escaped = match c {
                 '\'' => "\\'".to_string(),
@@ -250,15 +293,9 @@ pub(crate) fn format_literal(
     }
 }
 
-/// 格式化二元运算
-pub(crate) fn format_binop(
-    op: &BinOp,
-    left: &Expr,
-    right: &Expr,
-    ctx: &FormatContext,
-    source_map: &SourceMap,
-) -> String {
-    let op_str = match op {
+/// 二元运算符的源码写法
+fn binop_str(op: &BinOp) -> &'static str {
+    match op {
         BinOp::Add => "+",
         BinOp::Sub => "-",
         BinOp::Mul => "*",
@@ -274,7 +311,18 @@ pub(crate) fn format_binop(
         BinOp::Or => "||",
         BinOp::Range => "..",
         BinOp::Assign => "=",
-    };
+    }
+}
+
+/// 格式化二元运算
+pub(crate) fn format_binop(
+    op: &BinOp,
+    left: &Expr,
+    right: &Expr,
+    ctx: &FormatContext,
+    source_map: &SourceMap,
+) -> String {
+    let op_str = binop_str(op);
 
     let left_str = format_expr(left, ctx, source_map);
     let right_str = format_expr(right, ctx, source_map);
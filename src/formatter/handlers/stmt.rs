@@ -4,7 +4,7 @@ use crate::frontend::core::parser::ast::*;
 
 use super::super::context::FormatContext;
 use super::super::source_map::SourceMap;
-use super::expr::{format_block, format_expr, format_params};
+use super::expr::{format_block, format_expr, format_literal, format_params};
 use super::types::format_type;
 
 /// 格式化语句
@@ -39,18 +39,25 @@ pub fn format_stmt(
             params,
             body,
             is_pub,
-        } => format_binding(
-            name,
-            type_name.as_deref(),
-            method_type.as_ref(),
-            generic_params,
-            type_annotation.as_ref(),
-            params,
-            body,
-            *is_pub,
-            ctx,
-            source_map,
-        ),
+            attributes,
+        } => {
+            let binding = format_binding(
+                name,
+                type_name.as_deref(),
+                method_type.as_ref(),
+                generic_params,
+                type_annotation.as_ref(),
+                params,
+                body,
+                *is_pub,
+                ctx,
+                source_map,
+            );
+            let indent = ctx.indent_str();
+            attributes.iter().rev().fold(binding, |acc, attr| {
+                format!("{}\n{}{}", format_attribute(attr, ctx), indent, acc)
+            })
+        }
         StmtKind::Use {
             path, items, alias, ..
         } => format_use(path, items, alias),
@@ -125,6 +132,28 @@ fn format_var_decl(
     result
 }
 
+/// 格式化属性: `#[name]` / `#[name("text", key = "text")]`
+fn format_attribute(
+    attr: &Attribute,
+    ctx: &FormatContext,
+) -> String {
+    if attr.args.is_empty() {
+        return format!("#[{}]", attr.name);
+    }
+    let args: Vec<String> = attr
+        .args
+        .iter()
+        .map(|(key, value)| {
+            let literal = format_literal(&Literal::String(value.as_str().into()), ctx);
+            match key {
+                Some(key) => format!("{} = {}", key, literal),
+                None => literal,
+            }
+        })
+        .collect();
+    format!("#[{}({})]", attr.name, args.join(", "))
+}
+
 /// 格式化统一绑定语句 (函数/类型/方法)
 #[allow(clippy::too_many_arguments)]
 fn format_binding(
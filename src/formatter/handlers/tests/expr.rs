@@ -55,7 +55,7 @@ fn test_format_literal_bool() {
 
 #[test]
 fn test_format_literal_string() {
-    let lit = Literal::String("hello".to_string());
+    let lit = Literal::String("hello".into());
     let ctx = default_ctx();
     assert_eq!(format_literal(&lit, &ctx), "\"hello\"");
 }
@@ -72,7 +72,7 @@ fn test_format_binop_add() {
 #[test]
 fn test_format_binop_eq() {
     let ctx = default_ctx();
-    let left = Expr::Var("x".to_string(), Span::dummy());
+    let left = Expr::Var("x".into(), Span::dummy());
     let right = Expr::Lit(Literal::Int(0), Span::dummy());
     let result = format_binop(&BinOp::Eq, &left, &right, &ctx, &default_source_map());
     assert_eq!(result, "x == 0");
@@ -81,7 +81,7 @@ fn test_format_binop_eq() {
 #[test]
 fn test_format_call_no_args() {
     let ctx = default_ctx();
-    let func = Expr::Var("foo".to_string(), Span::dummy());
+    let func = Expr::Var("foo".into(), Span::dummy());
     let result = format_call(&func, &[], &[], &ctx, &default_source_map());
     assert_eq!(result, "foo()");
 }
@@ -89,7 +89,7 @@ fn test_format_call_no_args() {
 #[test]
 fn test_format_call_with_args() {
     let ctx = default_ctx();
-    let func = Expr::Var("add".to_string(), Span::dummy());
+    let func = Expr::Var("add".into(), Span::dummy());
     let arg1 = Expr::Lit(Literal::Int(1), Span::dummy());
     let arg2 = Expr::Lit(Literal::Int(2), Span::dummy());
     let result = format_call(&func, &[arg1, arg2], &[], &ctx, &default_source_map());
@@ -140,16 +140,30 @@ fn test_format_return_none() {
 #[test]
 fn test_format_cast() {
     let ctx = default_ctx();
-    let inner = Expr::Var("x".to_string(), Span::dummy());
+    let inner = Expr::Var("x".into(), Span::dummy());
     let expr = Expr::Cast {
         expr: Box::new(inner),
         target_type: Type::Int(64),
+        checked: false,
         span: Span::dummy(),
     };
     let result = format_expr(&expr, &ctx, &default_source_map());
     assert_eq!(result, "x as i64");
 }
 
+#[test]
+fn test_format_checked_cast() {
+    let ctx = default_ctx();
+    let expr = Expr::Cast {
+        expr: Box::new(Expr::Var("x".into(), Span::dummy())),
+        target_type: Type::Int(64),
+        checked: true,
+        span: Span::dummy(),
+    };
+    let result = format_expr(&expr, &ctx, &default_source_map());
+    assert_eq!(result, "x as? i64");
+}
+
 #[test]
 fn test_format_syntax_error_returns_error() {
     let source = "let x = 1";
@@ -163,14 +177,14 @@ fn test_format_syntax_error_returns_error() {
 
 #[test]
 fn test_format_literal_string_escapes_quotes() {
-    let lit = Literal::String("say \"hello\"".to_string());
+    let lit = Literal::String("say \"hello\"".into());
     let ctx = default_ctx();
     assert_eq!(format_literal(&lit, &ctx), r#""say \"hello\"""#);
 }
 
 #[test]
 fn test_format_literal_string_escapes_backslash() {
-    let lit = Literal::String("path\\to\\file".to_string());
+    let lit = Literal::String("path\\to\\file".into());
     let ctx = default_ctx();
     assert_eq!(format_literal(&lit, &ctx), r#""path\\to\\file""#);
 }
@@ -186,7 +200,7 @@ fn test_format_literal_char_escapes() {
 
 #[test]
 fn test_format_literal_string_single_quote() {
-    let lit = Literal::String("hello".to_string());
+    let lit = Literal::String("hello".into());
     let ctx = FormatContext::new(FormatOptions {
         single_quote: true,
         ..Default::default()
@@ -196,7 +210,7 @@ fn test_format_literal_string_single_quote() {
 
 #[test]
 fn test_format_literal_string_single_quote_with_escapes() {
-    let lit = Literal::String("say 'hello'".to_string());
+    let lit = Literal::String("say 'hello'".into());
     let ctx = FormatContext::new(FormatOptions {
         single_quote: true,
         ..Default::default()
@@ -242,7 +256,7 @@ fn test_format_single_stmt_block_multiline_when_long() {
     let long_name = "very_long_variable_name_that_exceeds_line_width";
     let block = Block {
         stmts: vec![Stmt {
-            kind: StmtKind::Expr(Box::new(Expr::Var(long_name.to_string(), Span::dummy()))),
+            kind: StmtKind::Expr(Box::new(Expr::Var(long_name.into(), Span::dummy()))),
             span: Span::dummy(),
         }],
         span: Span::dummy(),
@@ -314,14 +328,14 @@ fn test_format_params_long_wraps() {
 #[test]
 fn test_format_match_basic() {
     let match_expr = Expr::Match {
-        expr: Box::new(Expr::Var("x".to_string(), Span::dummy())),
+        expr: Box::new(Expr::Var("x".into(), Span::dummy())),
         arms: vec![
             MatchArm {
                 pattern: Pattern::Literal(Literal::Int(1)),
                 body: Block {
                     stmts: vec![Stmt {
                         kind: StmtKind::Expr(Box::new(Expr::Lit(
-                            Literal::String("one".to_string()),
+                            Literal::String("one".into()),
                             Span::dummy(),
                         ))),
                         span: Span::dummy(),
@@ -335,7 +349,7 @@ fn test_format_match_basic() {
                 body: Block {
                     stmts: vec![Stmt {
                         kind: StmtKind::Expr(Box::new(Expr::Lit(
-                            Literal::String("other".to_string()),
+                            Literal::String("other".into()),
                             Span::dummy(),
                         ))),
                         span: Span::dummy(),
@@ -365,7 +379,7 @@ fn test_format_fstring_simple() {
         segments: vec![
             FStringSegment::Text("Hello, ".to_string()),
             FStringSegment::Interpolation {
-                expr: Box::new(Expr::Var("name".to_string(), Span::dummy())),
+                expr: Box::new(Expr::Var("name".into(), Span::dummy())),
                 format_spec: None,
             },
         ],
@@ -379,7 +393,7 @@ fn test_format_fstring_simple() {
 fn test_format_fstring_with_spec() {
     let expr = Expr::FString {
         segments: vec![FStringSegment::Interpolation {
-            expr: Box::new(Expr::Var("value".to_string(), Span::dummy())),
+            expr: Box::new(Expr::Var("value".into(), Span::dummy())),
             format_spec: Some(".2f".to_string()),
         }],
         span: Span::dummy(),
@@ -394,7 +408,7 @@ fn test_format_fstring_with_spec() {
 fn test_format_try_operator() {
     let expr = Expr::Try {
         expr: Box::new(Expr::Call {
-            func: Box::new(Expr::Var("foo".to_string(), Span::dummy())),
+            func: Box::new(Expr::Var("foo".into(), Span::dummy())),
             args: vec![],
             named_args: vec![],
             span: Span::dummy(),
@@ -412,7 +426,7 @@ fn test_format_unsafe_block() {
     let expr = Expr::Unsafe {
         body: Box::new(Block {
             stmts: vec![Stmt {
-                kind: StmtKind::Expr(Box::new(Expr::Var("x".to_string(), Span::dummy()))),
+                kind: StmtKind::Expr(Box::new(Expr::Var("x".into(), Span::dummy()))),
                 span: Span::dummy(),
             }],
             span: Span::dummy(),
@@ -432,7 +446,7 @@ fn test_format_unsafe_block() {
 #[test]
 fn test_format_var_simple() {
     let ctx = default_ctx();
-    let expr = Expr::Var("my_variable".to_string(), Span::dummy());
+    let expr = Expr::Var("my_variable".into(), Span::dummy());
     let result = format_expr(&expr, &ctx, &default_source_map());
     assert_eq!(result, "my_variable");
 }
@@ -440,7 +454,7 @@ fn test_format_var_simple() {
 #[test]
 fn test_format_var_camel_case() {
     let ctx = default_ctx();
-    let expr = Expr::Var("camelCaseName".to_string(), Span::dummy());
+    let expr = Expr::Var("camelCaseName".into(), Span::dummy());
     let result = format_expr(&expr, &ctx, &default_source_map());
     assert_eq!(result, "camelCaseName");
 }
@@ -472,8 +486,8 @@ fn test_format_return_expression() {
     let expr = Expr::Return(
         Some(Box::new(Expr::BinOp {
             op: BinOp::Add,
-            left: Box::new(Expr::Var("x".to_string(), Span::dummy())),
-            right: Box::new(Expr::Var("y".to_string(), Span::dummy())),
+            left: Box::new(Expr::Var("x".into(), Span::dummy())),
+            right: Box::new(Expr::Var("y".into(), Span::dummy())),
             span: Span::dummy(),
         })),
         Span::dummy(),
@@ -526,7 +540,7 @@ fn test_format_tuple_simple() {
     let expr = Expr::Tuple(
         vec![
             Expr::Lit(Literal::Int(1), Span::dummy()),
-            Expr::Lit(Literal::String("hello".to_string()), Span::dummy()),
+            Expr::Lit(Literal::String("hello".into()), Span::dummy()),
             Expr::Lit(Literal::Bool(true), Span::dummy()),
         ],
         Span::dummy(),
@@ -560,7 +574,7 @@ fn test_format_tuple_empty() {
 fn test_format_index_simple() {
     let ctx = default_ctx();
     let expr = Expr::Index {
-        expr: Box::new(Expr::Var("arr".to_string(), Span::dummy())),
+        expr: Box::new(Expr::Var("arr".into(), Span::dummy())),
         index: Box::new(Expr::Lit(Literal::Int(0), Span::dummy())),
         span: Span::dummy(),
     };
@@ -573,11 +587,11 @@ fn test_format_index_nested() {
     let ctx = default_ctx();
     let expr = Expr::Index {
         expr: Box::new(Expr::Index {
-            expr: Box::new(Expr::Var("matrix".to_string(), Span::dummy())),
-            index: Box::new(Expr::Var("i".to_string(), Span::dummy())),
+            expr: Box::new(Expr::Var("matrix".into(), Span::dummy())),
+            index: Box::new(Expr::Var("i".into(), Span::dummy())),
             span: Span::dummy(),
         }),
-        index: Box::new(Expr::Var("j".to_string(), Span::dummy())),
+        index: Box::new(Expr::Var("j".into(), Span::dummy())),
         span: Span::dummy(),
     };
     let result = format_expr(&expr, &ctx, &default_source_map());
@@ -590,7 +604,7 @@ fn test_format_index_nested() {
 fn test_format_field_access_simple() {
     let ctx = default_ctx();
     let expr = Expr::FieldAccess {
-        expr: Box::new(Expr::Var("obj".to_string(), Span::dummy())),
+        expr: Box::new(Expr::Var("obj".into(), Span::dummy())),
         field: "field".to_string(),
         span: Span::dummy(),
     };
@@ -603,7 +617,7 @@ fn test_format_field_access_chain() {
     let ctx = default_ctx();
     let expr = Expr::FieldAccess {
         expr: Box::new(Expr::FieldAccess {
-            expr: Box::new(Expr::Var("obj".to_string(), Span::dummy())),
+            expr: Box::new(Expr::Var("obj".into(), Span::dummy())),
             field: "method1".to_string(),
             span: Span::dummy(),
         }),
@@ -620,7 +634,7 @@ fn test_format_field_access_chain() {
 fn test_format_ref_simple() {
     let ctx = default_ctx();
     let expr = Expr::Ref {
-        expr: Box::new(Expr::Var("value".to_string(), Span::dummy())),
+        expr: Box::new(Expr::Var("value".into(), Span::dummy())),
         span: Span::dummy(),
     };
     let result = format_expr(&expr, &ctx, &default_source_map());
@@ -632,7 +646,7 @@ fn test_format_ref_expression() {
     let ctx = default_ctx();
     let expr = Expr::Ref {
         expr: Box::new(Expr::Call {
-            func: Box::new(Expr::Var("foo".to_string(), Span::dummy())),
+            func: Box::new(Expr::Var("foo".into(), Span::dummy())),
             args: vec![],
             named_args: vec![],
             span: Span::dummy(),
@@ -1,3 +1,4 @@
 //! Formatter handlers 测试模块
 mod expr;
+mod stmt;
 mod types;
@@ -0,0 +1,16 @@
+//! 语句格式化处理器测试
+
+use crate::formatter::{format_source, FormatOptions};
+
+#[test]
+fn test_format_binding_keeps_attributes() {
+    let source =
+        "#[deprecated(\"old\",replacement=\"new_fn\")]\nold_fn: () -> Int = { return 1 }\n";
+    let formatted = format_source(source, &FormatOptions::default()).unwrap();
+    assert!(
+        formatted.starts_with("#[deprecated(\"old\", replacement = \"new_fn\")]\n"),
+        "{}",
+        formatted
+    );
+    assert!(formatted.contains("old_fn"));
+}
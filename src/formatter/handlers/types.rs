@@ -98,7 +98,7 @@ pub fn format_type(
             assoc_args,
             ..
         } => {
-            let base = format!("{}::{}", format_type(host_type, source_map), assoc_name);
+            let base = format!("{}.{}", format_type(host_type, source_map), assoc_name);
             if assoc_args.is_empty() {
                 base
             } else {
@@ -132,6 +132,7 @@ pub fn format_type(
             }
         }
         Type::ConstExpr(_) => "<const-expr>".to_string(),
+        Type::Newtype(inner) => format!("new {}", format_type(inner, source_map)),
     }
 }
 
@@ -172,6 +173,9 @@ fn format_struct_fields(
             if f.is_mut {
                 s.push_str("mut ");
             }
+            if f.is_assoc {
+                s.push_str("Self.");
+            }
             s.push_str(&f.name);
             s.push_str(": ");
             s.push_str(&format_type(&f.ty, source_map));
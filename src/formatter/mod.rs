@@ -100,6 +100,15 @@ pub fn format_source(
     source: &str,
     options: &FormatOptions,
 ) -> std::result::Result<String, FormatError> {
+    // 0. BOM 与换行风格在格式化前剥离，输出时按选项还原
+    let (bom, text) = match source.strip_prefix('\u{FEFF}') {
+        Some(rest) => ("\u{FEFF}", rest),
+        None => ("", source),
+    };
+    let line_ending = options.line_ending.resolve(text);
+    let normalized = normalize_line_endings(text);
+    let source = normalized.as_str();
+
     // 1. Pre-validate
     let vr = crate::frontend::validate::validate_source(source);
     if vr.diagnostics.iter().any(|d| d.severity.is_error()) {
@@ -123,7 +132,16 @@ pub fn format_source(
         }
     }
 
-    Ok(formatted)
+    let formatted = match line_ending {
+        crate::util::config::LineEnding::Crlf => formatted.replace('\n', "\r\n"),
+        _ => formatted,
+    };
+    Ok(format!("{}{}", bom, formatted))
+}
+
+/// 将 `\r\n` 与单独的 `\r` 统一为 `\n`
+fn normalize_line_endings(source: &str) -> String {
+    source.replace("\r\n", "\n").replace('\r', "\n")
 }
 
 /// 检查源代码是否已格式化
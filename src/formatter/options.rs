@@ -2,7 +2,7 @@
 //!
 //! 定义代码格式化的配置参数。
 
-use crate::util::config::FmtConfig;
+use crate::util::config::{FmtConfig, LineEnding};
 
 /// 格式化选项
 #[derive(Debug, Clone)]
@@ -19,6 +19,8 @@ pub struct FormatOptions {
     pub sort_imports: bool,
     /// 格式化后验证输出是否有效
     pub verify: bool,
+    /// 输出换行风格（默认保留输入的风格）
+    pub line_ending: LineEnding,
 }
 
 impl Default for FormatOptions {
@@ -30,6 +32,7 @@ impl Default for FormatOptions {
             single_quote: false,
             sort_imports: true,
             verify: true,
+            line_ending: LineEnding::Preserve,
         }
     }
 }
@@ -44,6 +47,7 @@ impl From<&FmtConfig> for FormatOptions {
             single_quote: config.single_quote.unwrap_or(default.single_quote),
             sort_imports: config.sort_imports.unwrap_or(default.sort_imports),
             verify: true,
+            line_ending: config.line_ending.unwrap_or(default.line_ending),
         }
     }
 }
@@ -5,12 +5,16 @@
 use crate::middle;
 use crate::util::diagnostic::Diagnostic;
 use crate::util::i18n::{t_cur, MSG};
+use crate::util::span::Span;
 use thiserror::Error;
 use tracing::debug;
 
-use super::config::CompileConfig;
+use super::config::{CompileConfig, OptLevel};
+use super::core::typecheck::semantic_db::SymbolLocation;
+use super::core::types::MonoType;
 use super::events::*;
 use super::pipeline::{Pipeline, PipelineState};
+use super::snippet::{self, SessionInput, Snippet, SnippetContext};
 
 /// 编译器
 ///
@@ -70,6 +74,12 @@ impl Compiler {
         &mut self.pipeline
     }
 
+    /// 最近一次编译中各优化遍的耗时
+    #[inline]
+    pub fn pass_timings(&self) -> &[crate::middle::passes::manager::PassTiming] {
+        self.pipeline.pass_timings()
+    }
+
     /// 订阅编译器事件
     ///
     /// 允许外部组件订阅编译器事件，用于 IDE 集成和进度显示。
@@ -129,6 +139,25 @@ impl Compiler {
         }
     }
 
+    /// 分别编译根模块与依赖模块，链接为一个模块
+    ///
+    /// `deps` 的每一项为 (模块路径, 源文件名, 源代码)；依赖模块的函数以模块路径为命名空间，
+    /// 见 [`link_modules`](middle::passes::link::link_modules)。
+    pub fn compile_linked(
+        &mut self,
+        source_name: &str,
+        source: &str,
+        deps: &[(&str, &str, &str)],
+    ) -> Result<middle::ModuleIR, CompileError> {
+        let root = self.compile_with_source(source_name, source)?;
+        let mut units = Vec::with_capacity(deps.len());
+        for &(path, dep_name, dep_source) in deps {
+            let module = self.compile_with_source(dep_name, dep_source)?;
+            units.push(middle::passes::link::LinkUnit::new(path, module));
+        }
+        middle::passes::link::link_modules(root, units).map_err(CompileError::Link)
+    }
+
     /// 只进行词法分析
     ///
     /// 对源代码进行词法分析，返回 token 列表。
@@ -160,6 +189,16 @@ impl Compiler {
         }
     }
 
+    /// 只进行脱糖
+    ///
+    /// 把 AST 中的语法糖改写为核心语法，返回非法写法的诊断；类型检查前调用。
+    pub fn desugar(
+        &mut self,
+        ast: &mut super::core::parser::Module,
+    ) -> Vec<Diagnostic> {
+        super::core::desugar::desugar_module(ast)
+    }
+
     /// 只进行类型检查
     ///
     /// 对 AST 进行类型检查，返回类型检查结果。
@@ -172,12 +211,14 @@ impl Compiler {
 
     /// 生成 IR
     ///
-    /// 根据 AST 和类型检查结果生成中间表示。
+    /// 根据 AST 和类型检查结果生成中间表示；先展开类型检查之后的语法糖（`?`、f-string），
+    /// `ast` 随之改写。
     pub fn generate_ir(
         &mut self,
-        ast: &super::core::parser::Module,
+        ast: &mut super::core::parser::Module,
         type_result: &super::core::typecheck::TypeCheckResult,
     ) -> Result<middle::ModuleIR, Vec<Diagnostic>> {
+        super::core::desugar::desugar_typed_module(ast);
         middle::generate_ir(ast, type_result)
     }
 
@@ -240,6 +281,79 @@ impl Compiler {
         self.pipeline.clear_cache();
     }
 
+    /// 查询上次编译中覆盖 `span` 的最内层表达式的类型
+    ///
+    /// 类型变量已按求解结果展开；未编译、编译在类型检查前失败或该位置无表达式时返回 `None`。
+    pub fn type_at(
+        &self,
+        span: Span,
+    ) -> Option<&MonoType> {
+        self.pipeline.source_index()?.type_at(span)
+    }
+
+    /// 查询上次编译中 `span` 处标识符的定义位置
+    ///
+    /// 位置本身就是声明时返回该声明；找不到标识符或定义时返回 `None`。
+    pub fn definition_of(
+        &self,
+        span: Span,
+    ) -> Option<SymbolLocation> {
+        self.pipeline.source_index()?.definition_of(span)
+    }
+
+    /// 上次编译中类型检查通过的模块环境，供 [`compile_snippet`](Self::compile_snippet) 使用
+    ///
+    /// 未编译或编译在类型检查阶段失败时返回 `None`。
+    pub fn snippet_context(&self) -> Option<SnippetContext> {
+        self.pipeline.snippet_context().cloned()
+    }
+
+    /// 在已有模块环境中单独编译一段代码
+    ///
+    /// 片段可以引用 `context` 中模块的函数、类型与导入以及添加的局部变量；最后一个
+    /// 表达式作为片段的值。只检查片段本身，模块中的函数体不会重新检查。
+    /// 片段要与模块链接运行时，模块应关闭死函数消除（[`CompileConfig::with_tree_shaking`]），
+    /// 否则片段调用的函数可能已被删除。
+    ///
+    /// # 示例
+    ///
+    /// ```ignore
+    /// let mut compiler = Compiler::new();
+    /// compiler.compile("main.yx", source)?;
+    /// let context = compiler.snippet_context().unwrap().with_local("n", MonoType::Int(64));
+    /// let snippet = compiler.compile_snippet(&context, "double(n) + 1")?;
+    /// ```
+    pub fn compile_snippet(
+        &mut self,
+        context: &SnippetContext,
+        source: &str,
+    ) -> Result<Snippet, CompileError> {
+        let mut snippet = snippet::compile(context, source)?;
+        if self.config.optimization_level != OptLevel::O0 {
+            middle::passes::const_fold::fold_constants(&mut snippet.function);
+        }
+        Ok(snippet)
+    }
+
+    /// 在会话环境中编译一次输入
+    ///
+    /// 输入可以定义函数与类型、给变量赋值并以最后一个表达式为值；执行成功后用
+    /// [`SnippetContext::commit`] 把它并入 `context`，之后的输入即可引用这些定义与变量。
+    /// 新会话从 `SnippetContext::default()` 开始。
+    pub fn compile_session(
+        &mut self,
+        context: &SnippetContext,
+        source: &str,
+    ) -> Result<SessionInput, CompileError> {
+        let mut input = snippet::compile_session(context, source)?;
+        if self.config.optimization_level != OptLevel::O0 {
+            for function in &mut input.module.functions {
+                middle::passes::const_fold::fold_constants(function);
+            }
+        }
+        Ok(input)
+    }
+
     /// 获取当前编译状态
     #[inline]
     pub fn state(&self) -> PipelineState {
@@ -274,6 +388,10 @@ pub enum CompileError {
     #[error("IR generation error: {0}")]
     IRError(String),
 
+    /// 链接错误
+    #[error("Link error: {0:?}")]
+    Link(Diagnostic),
+
     /// 取消编译
     #[error("Compilation cancelled")]
     Cancelled(String),
@@ -296,6 +414,7 @@ impl CompileError {
             CompileError::Parse(d) => &d.message,
             CompileError::TypeError(msg, _) => msg,
             CompileError::IRError(msg) => msg,
+            CompileError::Link(d) => &d.message,
             CompileError::Cancelled(msg) => msg,
             CompileError::Internal(msg) => msg,
         }
@@ -306,6 +425,7 @@ impl CompileError {
         match self {
             CompileError::Lex(d) => Some(d),
             CompileError::Parse(d) => Some(d),
+            CompileError::Link(d) => Some(d),
             CompileError::TypeError(_, diag) => diag.as_deref(),
             _ => None,
         }
@@ -5,6 +5,8 @@
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
+use crate::middle::passes::manager::Pass;
+
 /// 优化级别
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub enum OptLevel {
@@ -36,6 +38,25 @@ impl std::fmt::Display for OptLevel {
     }
 }
 
+impl std::str::FromStr for OptLevel {
+    type Err = String;
+
+    /// 解析 `0`..`3`（也接受 `O0`..`O3`）与 `auto`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim_start_matches(['O', 'o']) {
+            "0" => Ok(OptLevel::O0),
+            "1" => Ok(OptLevel::O1),
+            "2" => Ok(OptLevel::O2),
+            "3" => Ok(OptLevel::O3),
+            "auto" | "Auto" => Ok(OptLevel::Auto),
+            _ => Err(format!(
+                "invalid optimization level `{}` (expected 0, 1, 2, 3 or auto)",
+                s
+            )),
+        }
+    }
+}
+
 /// 诊断级别
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, Default)]
 pub enum DiagLevel {
@@ -234,6 +255,58 @@ impl Default for MonoConfig {
     }
 }
 
+/// 函数内联配置
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InlineConfig {
+    /// 是否启用内联（`-O0` 时总是跳过）
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// 可内联的被调函数的最大指令数；循环中的调用放宽到两倍
+    #[serde(default = "default_inline_threshold")]
+    pub threshold: usize,
+}
+
+fn default_inline_threshold() -> usize {
+    40
+}
+
+impl Default for InlineConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            threshold: default_inline_threshold(),
+        }
+    }
+}
+
+/// 逃逸分析配置
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EscapeConfig {
+    /// 是否启用逃逸分析（只在预设或显式遍列表包含 `escape` 时运行）
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+}
+
+impl Default for EscapeConfig {
+    fn default() -> Self {
+        Self { enabled: true }
+    }
+}
+
+/// 死函数消除配置
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TreeShakeConfig {
+    /// 是否删除不可达的函数；嵌入方按名调用任意函数时应关闭
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+}
+
+impl Default for TreeShakeConfig {
+    fn default() -> Self {
+        Self { enabled: true }
+    }
+}
+
 /// 死代码分析配置
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct DeadCodeConfig {
@@ -279,6 +352,26 @@ pub struct CompileConfig {
     #[serde(default)]
     pub mono: MonoConfig,
 
+    /// 函数内联配置
+    #[serde(default)]
+    pub inline: InlineConfig,
+
+    /// 逃逸分析配置
+    #[serde(default)]
+    pub escape: EscapeConfig,
+
+    /// 死函数消除配置
+    #[serde(default)]
+    pub tree_shake: TreeShakeConfig,
+
+    /// 显式指定的优化遍及顺序；为 `None` 时使用优化级别的预设
+    #[serde(default)]
+    pub passes: Option<Vec<Pass>>,
+
+    /// 是否记录并输出各优化遍耗时（`--timings`）
+    #[serde(default)]
+    pub timings: bool,
+
     /// 是否启用详细日志
     #[serde(default)]
     pub verbose: bool,
@@ -317,6 +410,46 @@ impl CompileConfig {
         self
     }
 
+    /// 设置优化遍及顺序，覆盖优化级别的预设
+    #[inline]
+    pub fn with_passes(
+        mut self,
+        passes: Vec<Pass>,
+    ) -> Self {
+        self.passes = Some(passes);
+        self
+    }
+
+    /// 设置是否输出各优化遍耗时
+    #[inline]
+    pub fn with_timings(
+        mut self,
+        timings: bool,
+    ) -> Self {
+        self.timings = timings;
+        self
+    }
+
+    /// 启用/禁用逃逸分析
+    #[inline]
+    pub fn with_escape_analysis(
+        mut self,
+        enabled: bool,
+    ) -> Self {
+        self.escape.enabled = enabled;
+        self
+    }
+
+    /// 启用/禁用死函数消除
+    #[inline]
+    pub fn with_tree_shaking(
+        mut self,
+        enabled: bool,
+    ) -> Self {
+        self.tree_shake.enabled = enabled;
+        self
+    }
+
     /// 设置诊断级别
     #[inline]
     pub fn with_diag_level(
@@ -347,6 +480,16 @@ impl CompileConfig {
         self
     }
 
+    /// 设置内联阈值（被调函数的最大指令数）
+    #[inline]
+    pub fn with_inline_threshold(
+        mut self,
+        threshold: usize,
+    ) -> Self {
+        self.inline.threshold = threshold;
+        self
+    }
+
     /// 启用/禁用增量编译
     #[inline]
     pub fn with_incremental(
@@ -469,6 +612,11 @@ impl ConfigAdapter for JsonConfig {
             incremental: self.incremental.clone(),
             dead_code: DeadCodeConfig::default(),
             mono: MonoConfig::default(),
+            inline: InlineConfig::default(),
+            escape: EscapeConfig::default(),
+            tree_shake: TreeShakeConfig::default(),
+            passes: None,
+            timings: false,
             verbose: false,
             source_root: None,
             import_paths: self.import_paths.clone(),
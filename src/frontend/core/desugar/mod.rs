@@ -0,0 +1,548 @@
+//! 脱糖阶段
+//!
+//! 位于语法分析与类型检查之间，把只改变写法、不改变含义的语法糖改写为核心语法：
+//! - 复合赋值 `x op= v` → `x = x op v`（变量目标改写为与 `x = ...` 相同的变量语句）
+//! - 管道 `v |> f` → `f(v)`，`v |> f(a, b)` → `f(v, a, b)`
+//!
+//! 改写出的节点一律沿用原表达式的 span：目标与操作数保留各自的位置，新的运算与调用
+//! 取运算符所在位置，诊断因此总是指向用户写下的代码。
+//!
+//! 类型检查之后再展开一次（[`desugar_typed_module`]），改写类型检查需要看到原写法的语法糖：
+//! - `expr?` → `match expr { Ok(v) => v, r => return r }`：类型检查据原写法报告
+//!   `?` 的位置与操作数类型错误，展开后 Err 原样从当前函数返回
+//! - f-string `f"a{x}b{y:.2f}"` → `std.string.format("a{0}b{1:.2f}", x, y)`：类型检查
+//!   逐个推断插值，展开后由 IR 生成像其他格式化调用一样在实参全为常量时折叠
+//!
+//! 展开引入的绑定名不是合法标识符，不会与用户代码冲突。
+//!
+//! 格式化器等需要还原源码写法的工具直接使用语法分析的结果，不经过本阶段。
+
+use crate::frontend::core::parser::ast::{
+    BinOp, BindingKind, Block, Expr, FStringSegment, Literal, MatchArm, Module, Pattern, Stmt,
+    StmtKind, StructField, Type,
+};
+use crate::frontend::core::typecheck::inference::expressions::expr_span;
+use crate::util::diagnostic::{Diagnostic, ErrorCodeDefinition};
+use crate::util::span::Span;
+
+/// 对模块脱糖，返回非法写法的诊断（如复合赋值的目标不可赋值）
+pub fn desugar_module(module: &mut Module) -> Vec<Diagnostic> {
+    let mut desugarer = Desugarer::default();
+    desugarer.stmts(&mut module.items);
+    desugarer.diagnostics
+}
+
+/// 类型检查通过后展开 `?` 与 f-string，IR 生成之前调用
+pub fn desugar_typed_module(module: &mut Module) {
+    let mut desugarer = Desugarer {
+        typed: true,
+        ..Desugarer::default()
+    };
+    desugarer.stmts(&mut module.items);
+}
+
+#[derive(Default)]
+struct Desugarer {
+    diagnostics: Vec<Diagnostic>,
+    /// 类型检查之后的一遍：只展开 `?` 与 f-string
+    typed: bool,
+    /// 已引入的绑定名个数
+    temps: usize,
+}
+
+impl Desugarer {
+    fn stmts(
+        &mut self,
+        stmts: &mut [Stmt],
+    ) {
+        for stmt in stmts {
+            self.stmt(stmt);
+        }
+    }
+
+    fn block(
+        &mut self,
+        block: &mut Block,
+    ) {
+        self.stmts(&mut block.stmts);
+    }
+
+    fn stmt(
+        &mut self,
+        stmt: &mut Stmt,
+    ) {
+        match &mut stmt.kind {
+            StmtKind::Expr(expr) => {
+                // 语句位置的 `x op= v` 与 `x = x op v` 一样写成变量语句
+                if let Expr::CompoundAssign { target, .. } = expr.as_ref() {
+                    if let Expr::Var(name, name_span) = target.as_ref() {
+                        let (name, name_span) = (name.to_string(), *name_span);
+                        let Expr::CompoundAssign {
+                            op,
+                            target,
+                            mut value,
+                            span,
+                        } = std::mem::replace(expr.as_mut(), Expr::Error(stmt.span))
+                        else {
+                            unreachable!()
+                        };
+                        self.expr(&mut value);
+                        stmt.kind = StmtKind::Var {
+                            name,
+                            name_span,
+                            type_annotation: None,
+                            initializer: Some(Box::new(Expr::BinOp {
+                                op,
+                                left: target,
+                                right: value,
+                                span,
+                            })),
+                            is_mut: false,
+                        };
+                        return;
+                    }
+                }
+                self.expr(expr);
+            }
+            StmtKind::Var {
+                type_annotation,
+                initializer,
+                ..
+            } => {
+                if let Some(ty) = type_annotation {
+                    self.ty(ty);
+                }
+                if let Some(init) = initializer {
+                    self.expr(init);
+                }
+            }
+            StmtKind::For { iterable, body, .. } => {
+                self.expr(iterable);
+                self.block(body);
+            }
+            StmtKind::Binding {
+                type_annotation,
+                body,
+                ..
+            } => {
+                if let Some(ty) = type_annotation {
+                    self.ty(ty);
+                }
+                self.stmts(body);
+            }
+            StmtKind::If {
+                condition,
+                then_branch,
+                elif_branches,
+                else_branch,
+                ..
+            } => self.if_parts(condition, then_branch, elif_branches, else_branch),
+            StmtKind::ExternalBindingStmt { binding, .. } => self.binding_kind(binding),
+            StmtKind::DestructureAssign { rhs, .. } => self.expr(rhs),
+            StmtKind::Return(value) => {
+                if let Some(value) = value {
+                    self.expr(value);
+                }
+            }
+            StmtKind::Use { .. } | StmtKind::Error(_) => {}
+        }
+    }
+
+    fn if_parts(
+        &mut self,
+        condition: &mut Expr,
+        then_branch: &mut Block,
+        elif_branches: &mut [(Box<Expr>, Box<Block>)],
+        else_branch: &mut Option<Box<Block>>,
+    ) {
+        self.expr(condition);
+        self.block(then_branch);
+        for (cond, block) in elif_branches {
+            self.expr(cond);
+            self.block(block);
+        }
+        if let Some(block) = else_branch {
+            self.block(block);
+        }
+    }
+
+    fn binding_kind(
+        &mut self,
+        binding: &mut BindingKind,
+    ) {
+        if let BindingKind::Anonymous { body, .. } = binding {
+            self.expr(body);
+        }
+    }
+
+    /// 类型中只有字段默认值含表达式
+    fn ty(
+        &mut self,
+        ty: &mut Type,
+    ) {
+        match ty {
+            Type::Struct {
+                fields, bindings, ..
+            } => {
+                self.fields(fields);
+                for binding in bindings {
+                    self.binding_kind(&mut binding.kind);
+                }
+            }
+            Type::NamedStruct { fields, .. } => self.fields(fields),
+            _ => {}
+        }
+    }
+
+    fn fields(
+        &mut self,
+        fields: &mut [StructField],
+    ) {
+        for field in fields {
+            if let Some(default) = &mut field.default {
+                self.expr(default);
+            }
+        }
+    }
+
+    fn arms(
+        &mut self,
+        arms: &mut [MatchArm],
+    ) {
+        for arm in arms {
+            self.pattern(&mut arm.pattern);
+            self.block(&mut arm.body);
+        }
+    }
+
+    fn pattern(
+        &mut self,
+        pattern: &mut Pattern,
+    ) {
+        match pattern {
+            Pattern::Guard { pattern, condition } => {
+                self.pattern(pattern);
+                self.expr(condition);
+            }
+            Pattern::Or(patterns) | Pattern::Tuple(patterns) => {
+                for pattern in patterns {
+                    self.pattern(pattern);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// 先改写子表达式，再改写自身，嵌套的语法糖一并展开
+    fn expr(
+        &mut self,
+        expr: &mut Expr,
+    ) {
+        match expr {
+            Expr::Lit(..)
+            | Expr::Var(..)
+            | Expr::Break(..)
+            | Expr::Continue(..)
+            | Expr::Error(_) => {}
+            Expr::BinOp { left, right, .. } => {
+                self.expr(left);
+                self.expr(right);
+            }
+            Expr::UnOp { expr, .. }
+            | Expr::Cast { expr, .. }
+            | Expr::FieldAccess { expr, .. }
+            | Expr::Ref { expr, .. }
+            | Expr::Borrow { expr, .. } => self.expr(expr),
+            Expr::Try {
+                expr: operand,
+                span,
+            } => {
+                self.expr(operand);
+                if self.typed {
+                    let span = *span;
+                    let Expr::Try { expr: operand, .. } =
+                        std::mem::replace(expr, Expr::Error(span))
+                    else {
+                        unreachable!()
+                    };
+                    self.temps += 1;
+                    *expr = try_match(*operand, span, self.temps);
+                }
+            }
+            Expr::Call {
+                func,
+                args,
+                named_args,
+                ..
+            } => {
+                self.expr(func);
+                for arg in args {
+                    self.expr(arg);
+                }
+                for (_, arg) in named_args {
+                    self.expr(arg);
+                }
+            }
+            Expr::FnDef { body, .. }
+            | Expr::Lambda { body, .. }
+            | Expr::Unsafe { body, .. }
+            | Expr::Spawn { body, .. } => self.block(body),
+            Expr::If {
+                condition,
+                then_branch,
+                elif_branches,
+                else_branch,
+                ..
+            } => self.if_parts(condition, then_branch, elif_branches, else_branch),
+            Expr::Match { expr, arms, .. } => {
+                self.expr(expr);
+                self.arms(arms);
+            }
+            Expr::While {
+                condition, body, ..
+            } => {
+                self.expr(condition);
+                self.block(body);
+            }
+            Expr::For { iterable, body, .. } | Expr::SpawnFor { iterable, body, .. } => {
+                self.expr(iterable);
+                self.block(body);
+            }
+            Expr::Block(block) => self.block(block),
+            Expr::Return(value, _) => {
+                if let Some(value) = value {
+                    self.expr(value);
+                }
+            }
+            Expr::Tuple(items, _) | Expr::List(items, _) => {
+                for item in items {
+                    self.expr(item);
+                }
+            }
+            Expr::ListComp {
+                element,
+                iterable,
+                condition,
+                ..
+            } => {
+                self.expr(element);
+                self.expr(iterable);
+                if let Some(condition) = condition {
+                    self.expr(condition);
+                }
+            }
+            Expr::Dict(entries, _) => {
+                for (key, value) in entries {
+                    self.expr(key);
+                    self.expr(value);
+                }
+            }
+            Expr::Index { expr, index, .. } => {
+                self.expr(expr);
+                self.expr(index);
+            }
+            Expr::FString { segments, span } => {
+                for segment in segments.iter_mut() {
+                    if let FStringSegment::Interpolation { expr, .. } = segment {
+                        self.expr(expr);
+                    }
+                }
+                if self.typed {
+                    let span = *span;
+                    let Expr::FString { segments, .. } = std::mem::replace(expr, Expr::Error(span))
+                    else {
+                        unreachable!()
+                    };
+                    *expr = format_call(segments, span);
+                }
+            }
+            Expr::CompoundAssign {
+                target,
+                value,
+                span,
+                ..
+            } => {
+                self.expr(target);
+                self.expr(value);
+                if !is_assignable(target) {
+                    self.diagnostics.push(
+                        ErrorCodeDefinition::invalid_syntax(
+                            "复合赋值的左侧必须是变量、字段或以变量、字面量为下标的元素",
+                        )
+                        .at(*span)
+                        .build(),
+                    );
+                }
+                let span = *span;
+                let Expr::CompoundAssign {
+                    op, target, value, ..
+                } = std::mem::replace(expr, Expr::Error(span))
+                else {
+                    unreachable!()
+                };
+                *expr = compound_assign(op, target, value, span);
+            }
+            Expr::Pipe {
+                value, func, span, ..
+            } => {
+                self.expr(value);
+                self.expr(func);
+                let span = *span;
+                let Expr::Pipe { value, func, .. } = std::mem::replace(expr, Expr::Error(span))
+                else {
+                    unreachable!()
+                };
+                *expr = pipe(*value, *func, span);
+            }
+        }
+    }
+}
+
+/// `target op= value` → `target = target op value`
+fn compound_assign(
+    op: BinOp,
+    target: Box<Expr>,
+    value: Box<Expr>,
+    span: Span,
+) -> Expr {
+    Expr::BinOp {
+        op: BinOp::Assign,
+        left: target.clone(),
+        right: Box::new(Expr::BinOp {
+            op,
+            left: target,
+            right: value,
+            span,
+        }),
+        span,
+    }
+}
+
+/// `value |> f(args..)` → `f(value, args..)`；`value |> f` → `f(value)`
+fn pipe(
+    value: Expr,
+    func: Expr,
+    span: Span,
+) -> Expr {
+    match func {
+        Expr::Call {
+            func,
+            mut args,
+            named_args,
+            ..
+        } => {
+            args.insert(0, value);
+            Expr::Call {
+                func,
+                args,
+                named_args,
+                span,
+            }
+        }
+        func => Expr::Call {
+            func: Box::new(func),
+            args: vec![value],
+            named_args: Vec::new(),
+            span,
+        },
+    }
+}
+
+/// `operand?` → `match operand { Ok(v) => v, r => return r }`
+///
+/// 解包出的值沿用 `?` 的位置（类型为 Ok 的负载），原样返回的值沿用操作数的位置
+/// （类型为 Result），IR 生成据此判断返回值不需要再包装为 Ok。
+fn try_match(
+    operand: Expr,
+    span: Span,
+    id: usize,
+) -> Expr {
+    let ok = format!("?ok{id}");
+    let result = format!("?result{id}");
+    let operand_span = expr_span(&operand);
+    let arm = |pattern: Pattern, body: Expr| MatchArm {
+        pattern,
+        body: Block {
+            stmts: vec![Stmt {
+                kind: StmtKind::Expr(Box::new(body)),
+                span,
+            }],
+            span,
+        },
+        span,
+    };
+    Expr::Match {
+        expr: Box::new(operand),
+        arms: vec![
+            arm(
+                Pattern::Union {
+                    name: "Ok".to_string(),
+                    variant: "Ok".to_string(),
+                    pattern: Some(Box::new(Pattern::Identifier(ok.clone()))),
+                },
+                Expr::Var(ok.as_str().into(), span),
+            ),
+            arm(
+                Pattern::Identifier(result.clone()),
+                Expr::Return(
+                    Some(Box::new(Expr::Var(result.as_str().into(), operand_span))),
+                    span,
+                ),
+            ),
+        ],
+        span,
+    }
+}
+
+/// `f"a{x}b{y:.2f}"` → `std.string.format("a{0}b{1:.2f}", x, y)`
+fn format_call(
+    segments: Vec<FStringSegment>,
+    span: Span,
+) -> Expr {
+    let mut format = String::new();
+    let mut args = Vec::new();
+    for segment in segments {
+        match segment {
+            FStringSegment::Text(text) => format.push_str(&text),
+            FStringSegment::Interpolation { expr, format_spec } => {
+                match format_spec {
+                    Some(spec) => format.push_str(&format!("{{{}:{}}}", args.len(), spec)),
+                    None => format.push_str(&format!("{{{}}}", args.len())),
+                }
+                args.push(*expr);
+            }
+        }
+    }
+    args.insert(0, Expr::Lit(Literal::String(format.as_str().into()), span));
+    let path =
+        ["string", "format"]
+            .into_iter()
+            .fold(Expr::Var("std".into(), span), |expr, field| {
+                Expr::FieldAccess {
+                    expr: Box::new(expr),
+                    field: field.to_string(),
+                    span,
+                }
+            });
+    Expr::Call {
+        func: Box::new(path),
+        args,
+        named_args: Vec::new(),
+        span,
+    }
+}
+
+/// 目标会被求值两次，只接受求值没有副作用的位置
+fn is_assignable(target: &Expr) -> bool {
+    match target {
+        Expr::Var(..) => true,
+        Expr::FieldAccess { expr, .. } => is_assignable(expr),
+        Expr::Index { expr, index, .. } => {
+            is_assignable(expr) && matches!(index.as_ref(), Expr::Var(..) | Expr::Lit(..))
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,276 @@
+//! 脱糖阶段测试
+//!
+//! 覆盖：
+//! - 复合赋值改写为赋值，变量目标改写为变量语句
+//! - 管道改写为调用，链式管道按从左到右的顺序嵌套
+//! - 改写出的节点沿用原代码的 span，诊断指向用户写下的位置
+//! - 非法的复合赋值目标
+//! - 类型检查之后：`?` 改写为按 Ok/Err 分派的 match，f-string 改写为格式化调用
+//! - 改写后的程序在虚拟机上运行
+
+use crate::backends::Executor;
+use crate::frontend::core::desugar::{desugar_module, desugar_typed_module};
+use crate::frontend::core::lexer::tokenize;
+use crate::frontend::core::parser::ast::{BinOp, Expr, Literal, Module, Pattern, StmtKind};
+use crate::frontend::core::parser::parse;
+use crate::frontend::Compiler;
+use crate::util::span::Span;
+
+fn desugared(source: &str) -> Module {
+    let tokens = tokenize(source).expect("source should lex");
+    let mut module = parse(&tokens).module;
+    let errors = desugar_module(&mut module);
+    assert!(errors.is_empty(), "unexpected desugar errors: {:?}", errors);
+    module
+}
+
+/// `main` 函数体的语句
+fn main_body(module: &Module) -> &[crate::frontend::core::parser::ast::Stmt] {
+    module
+        .items
+        .iter()
+        .find_map(|stmt| match &stmt.kind {
+            StmtKind::Binding { name, body, .. } if name == "main" => Some(body.as_slice()),
+            _ => None,
+        })
+        .expect("main should exist")
+}
+
+#[test]
+fn test_compound_assign_on_variable_becomes_var_stmt() {
+    let module = desugared("main = {\n    mut x = 1\n    x += 2\n}\n");
+    let stmt = &main_body(&module)[1];
+    let StmtKind::Var {
+        name,
+        name_span,
+        initializer: Some(init),
+        ..
+    } = &stmt.kind
+    else {
+        panic!("expected variable statement, got {:?}", stmt.kind);
+    };
+    assert_eq!(name, "x");
+    assert_eq!((name_span.start.line, name_span.start.column), (3, 5));
+    let Expr::BinOp {
+        op: BinOp::Add,
+        left,
+        span,
+        ..
+    } = init.as_ref()
+    else {
+        panic!("expected addition, got {:?}", init);
+    };
+    assert!(matches!(left.as_ref(), Expr::Var(n, _) if n.as_str() == "x"));
+    // 运算取 `+=` 的位置
+    assert_eq!((span.start.line, span.start.column), (3, 7));
+}
+
+#[test]
+fn test_compound_assign_on_field_becomes_assignment() {
+    let module = desugared("main = {\n    p.x *= 2\n}\n");
+    let StmtKind::Expr(expr) = &main_body(&module)[0].kind else {
+        panic!("expected expression statement");
+    };
+    let Expr::BinOp {
+        op: BinOp::Assign,
+        left,
+        right,
+        ..
+    } = expr.as_ref()
+    else {
+        panic!("expected assignment, got {:?}", expr);
+    };
+    assert!(matches!(left.as_ref(), Expr::FieldAccess { field, .. } if field == "x"));
+    assert!(matches!(
+        right.as_ref(),
+        Expr::BinOp { op: BinOp::Mul, left, .. }
+            if matches!(left.as_ref(), Expr::FieldAccess { .. })
+    ));
+}
+
+#[test]
+fn test_pipe_becomes_call() {
+    let module = desugared("main = {\n    r = 5 |> double |> add(1)\n}\n");
+    let StmtKind::Var {
+        initializer: Some(init),
+        ..
+    } = &main_body(&module)[0].kind
+    else {
+        panic!("expected variable statement");
+    };
+    // add(double(5), 1)
+    let Expr::Call { func, args, .. } = init.as_ref() else {
+        panic!("expected call, got {:?}", init);
+    };
+    assert!(matches!(func.as_ref(), Expr::Var(n, _) if n.as_str() == "add"));
+    assert_eq!(args.len(), 2);
+    let Expr::Call {
+        func: inner,
+        args: inner_args,
+        ..
+    } = &args[0]
+    else {
+        panic!("expected nested call, got {:?}", args[0]);
+    };
+    assert!(matches!(inner.as_ref(), Expr::Var(n, _) if n.as_str() == "double"));
+    assert!(matches!(inner_args.as_slice(), [Expr::Lit(..)]));
+    assert!(matches!(args[1], Expr::Lit(..)));
+}
+
+#[test]
+fn test_invalid_compound_target_is_reported() {
+    let tokens = tokenize("main = {\n    next() += 1\n}\n").unwrap();
+    let mut module = parse(&tokens).module;
+    let errors = desugar_module(&mut module);
+    assert_eq!(errors.len(), 1);
+    let span = errors[0].span.expect("diagnostic should have a span");
+    assert_eq!((span.start.line, span.start.column), (2, 12));
+}
+
+/// 编译失败时第一条诊断的位置
+fn error_span(source: &str) -> Span {
+    let err = Compiler::new()
+        .compile("desugar.yx", source)
+        .expect_err("source should not compile");
+    err.diagnostic()
+        .and_then(|d| d.span)
+        .expect("error should have a span")
+}
+
+#[test]
+fn test_diagnostics_point_at_original_code() {
+    // 复合赋值右侧的未定义变量：指向该变量
+    let span = error_span("main = {\n    mut s = 1\n    s += missing\n}\n");
+    assert_eq!((span.start.line, span.start.column), (3, 10));
+
+    // 管道目标未定义：指向目标名
+    let span = error_span("main = {\n    r = 5 |> missing\n}\n");
+    assert_eq!((span.start.line, span.start.column), (2, 14));
+}
+
+/// 两遍脱糖后 `main` 中第 `index` 条变量语句的初始值
+fn typed_initializer(
+    source: &str,
+    index: usize,
+) -> Expr {
+    let mut module = desugared(source);
+    desugar_typed_module(&mut module);
+    match &main_body(&module)[index].kind {
+        StmtKind::Var {
+            initializer: Some(init),
+            ..
+        } => init.as_ref().clone(),
+        other => panic!("expected variable statement, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_try_becomes_match_on_result_variants() {
+    let init = typed_initializer("main = {\n    v = half(4)?\n}\n", 0);
+    let Expr::Match { expr, arms, span } = init else {
+        panic!("expected match, got {:?}", init);
+    };
+    assert!(matches!(expr.as_ref(), Expr::Call { .. }));
+    // match 沿用 `?` 的位置
+    assert_eq!((span.start.line, span.start.column), (2, 16));
+    assert_eq!(arms.len(), 2);
+    assert!(matches!(
+        &arms[0].pattern,
+        Pattern::Union { variant, pattern: Some(_), .. } if variant == "Ok"
+    ));
+    assert!(matches!(&arms[1].pattern, Pattern::Identifier(_)));
+    let StmtKind::Expr(body) = &arms[1].body.stmts[0].kind else {
+        panic!("expected expression body");
+    };
+    assert!(matches!(body.as_ref(), Expr::Return(Some(_), _)));
+}
+
+#[test]
+fn test_fstring_becomes_format_call() {
+    let init = typed_initializer("main = {\n    s = f\"a{x}b{y:.2f}\"\n}\n", 0);
+    let Expr::Call { func, args, .. } = init else {
+        panic!("expected call, got {:?}", init);
+    };
+    assert!(matches!(
+        func.as_ref(),
+        Expr::FieldAccess { field, .. } if field == "format"
+    ));
+    assert_eq!(args.len(), 3);
+    assert!(matches!(
+        &args[0],
+        Expr::Lit(Literal::String(format), _) if format.as_str() == "a{0}b{1:.2f}"
+    ));
+    assert!(matches!(&args[1], Expr::Var(name, _) if name.as_str() == "x"));
+}
+
+#[test]
+fn test_syntax_pass_keeps_try_and_fstring() {
+    let module = desugared("main = {\n    v = half(4)?\n    s = f\"{v}\"\n}\n");
+    let initializers: Vec<&Expr> = main_body(&module)
+        .iter()
+        .filter_map(|stmt| match &stmt.kind {
+            StmtKind::Var {
+                initializer: Some(init),
+                ..
+            } => Some(init.as_ref()),
+            _ => None,
+        })
+        .collect();
+    assert!(matches!(initializers[0], Expr::Try { .. }));
+    assert!(matches!(initializers[1], Expr::FString { .. }));
+}
+
+#[test]
+fn test_desugared_program_runs_on_the_vm() {
+    let source = "\
+use std.assert
+use std.result
+
+half: (n: Int) -> Result(Int, String) = (n) => {
+    if n % 2 == 1 {
+        return err(\"odd\")
+    }
+    return ok(n / 2)
+}
+
+quarter: (n: Int) -> Result(Int, String) = (n) => {
+    h = half(n)?
+    return half(h)?
+}
+
+double: (n: Int) -> Int = (n) => {
+    return n * 2
+}
+
+add: (a: Int, b: Int) -> Int = (a, b) => {
+    return a + b
+}
+
+main = {
+    mut x = 1
+    x += 2
+    x *= 5
+    x -= 3
+    x /= 2
+    x %= 4
+    assert_eq(x, 2)
+    assert_eq(5 |> double |> add(1), 11)
+
+    assert_eq(unwrap(quarter(12)), 3)
+    assert_eq(is_err(quarter(6)), true)
+    name = \"yx\"
+    assert_eq(f\"{name}:{x}\", \"yx:2\")
+    assert_eq(f\"{1}+{1.0}\", \"1+1.0\")
+}
+";
+    let module = Compiler::new()
+        .compile("desugar.yx", source)
+        .expect("source should compile");
+    let mut ctx = crate::middle::passes::codegen::CodegenContext::new(module);
+    let bytecode = ctx.generate().expect("codegen should succeed");
+    let module = crate::middle::bytecode::BytecodeModule::from(bytecode);
+    let mut interpreter = crate::backends::interpreter::Interpreter::new();
+    interpreter
+        .execute_module(&module)
+        .expect("desugared program should run");
+}
@@ -4,6 +4,7 @@
 
 use crate::util::span::{Position, Span};
 use crate::frontend::core::lexer::tokens::*;
+use crate::util::symbol::Symbol;
 
 /// Scan a number literal (decimal, hex, octal, binary)
 pub fn scan_number(
@@ -34,12 +35,72 @@ pub fn scan_number(
         None
     };
 
-    match base {
+    let token = match base {
         Some(16) => scan_hex_number(lexer, value),
         Some(8) => scan_octal_number(lexer, value),
         Some(2) => scan_binary_number(lexer, value),
         None => scan_decimal_number(lexer, value),
         _ => unreachable!("Invalid base value"),
+    }?;
+
+    Some(apply_numeric_suffix(lexer, token))
+}
+
+/// Attach an optional width suffix (`42i32`, `1.0f32`, `2f64`) to a number token
+///
+/// Integer digits with a float suffix become a float literal; a float with an
+/// integer suffix is rejected. Text that is not a known suffix is left for the
+/// identifier scanner.
+fn apply_numeric_suffix(
+    lexer: &mut super::tokenizer::Lexer<'_>,
+    token: Token,
+) -> Token {
+    if !matches!(
+        token.kind,
+        TokenKind::IntLiteral(_) | TokenKind::FloatLiteral(_)
+    ) {
+        return token;
+    }
+
+    let mut chars = lexer.chars_clone();
+    let mut text = String::new();
+    while let Some(&c) = chars.peek() {
+        if c.is_alphanumeric() || c == '_' {
+            text.push(c);
+            chars.next();
+        } else {
+            break;
+        }
+    }
+    let Some(suffix) = NumericSuffix::parse(&text) else {
+        return token;
+    };
+    for _ in 0..text.len() {
+        lexer.advance();
+    }
+
+    match (token.kind, suffix.is_float()) {
+        (TokenKind::IntLiteral(n), false) => Token {
+            kind: TokenKind::SuffixedIntLiteral(n, suffix),
+            span: lexer.span(),
+            literal: Some(Literal::Int(n)),
+        },
+        (TokenKind::IntLiteral(n), true) => Token {
+            kind: TokenKind::SuffixedFloatLiteral(n as f64, suffix),
+            span: lexer.span(),
+            literal: Some(Literal::Float(n as f64)),
+        },
+        (TokenKind::FloatLiteral(f), true) => Token {
+            kind: TokenKind::SuffixedFloatLiteral(f, suffix),
+            span: lexer.span(),
+            literal: Some(Literal::Float(f)),
+        },
+        (_, _) => {
+            lexer.error = Some(crate::frontend::core::lexer::LexError::InvalidNumber(
+                format!("integer suffix '{}' on a float literal", suffix.as_str()),
+            ));
+            lexer.make_token(TokenKind::Error("Invalid number suffix".to_string()))
+        }
     }
 }
 
@@ -481,7 +542,7 @@ pub fn scan_string(lexer: &mut super::tokenizer::Lexer<'_>) -> Option<Token> {
             '"' => {
                 lexer.advance();
                 return Some(Token {
-                    kind: TokenKind::StringLiteral(value.clone()),
+                    kind: TokenKind::StringLiteral(Symbol::intern(&value)),
                     span: Span::new(
                         Position::with_offset(
                             lexer.start_line(),
@@ -490,7 +551,7 @@ pub fn scan_string(lexer: &mut super::tokenizer::Lexer<'_>) -> Option<Token> {
                         ),
                         lexer.position(),
                     ),
-                    literal: Some(Literal::String(value.clone())),
+                    literal: Some(Literal::String(Symbol::intern(&value))),
                 });
             }
             '\\' => {
@@ -587,7 +648,7 @@ pub fn scan_string(lexer: &mut super::tokenizer::Lexer<'_>) -> Option<Token> {
                     }
                 }
             }
-            '\n' => {
+            '\n' | '\r' => {
                 lexer.error = Some(crate::frontend::core::lexer::LexError::UnterminatedString {
                     position: format!("{}:{}", start_pos.line, start_pos.column),
                 });
@@ -637,7 +698,7 @@ fn scan_multi_line_string(lexer: &mut super::tokenizer::Lexer<'_>) -> Option<Tok
                 lexer.advance(); // consume second quote
                 lexer.advance(); // consume third quote
                 return Some(Token {
-                    kind: TokenKind::StringLiteral(value.clone()),
+                    kind: TokenKind::StringLiteral(Symbol::intern(&value)),
                     span: Span::new(
                         Position::with_offset(
                             lexer.start_line(),
@@ -646,7 +707,7 @@ fn scan_multi_line_string(lexer: &mut super::tokenizer::Lexer<'_>) -> Option<Tok
                         ),
                         lexer.position(),
                     ),
-                    literal: Some(Literal::String(value.clone())),
+                    literal: Some(Literal::String(Symbol::intern(&value))),
                 });
             } else {
                 // This is just a single quote inside the string
@@ -669,6 +730,12 @@ fn scan_multi_line_string(lexer: &mut super::tokenizer::Lexer<'_>) -> Option<Tok
                     }
                 }
             }
+        } else if c == '\r' {
+            // 多行字符串中的 `\r\n` 与单独的 `\r` 统一为 `\n`，内容不随文件换行风格变化
+            if lexer.peek_public() == Some(&'\n') {
+                lexer.advance();
+            }
+            value.push('\n');
         } else {
             value.push(c);
         }
@@ -809,7 +876,7 @@ pub fn scan_char(lexer: &mut super::tokenizer::Lexer<'_>) -> Option<Token> {
                     }
                 }
             }
-            '\n' => {
+            '\n' | '\r' => {
                 lexer.error = Some(crate::frontend::core::lexer::LexError::InvalidToken {
                     position: format!("{}:{}", start_pos.line, start_pos.column),
                     message: "Unterminated character literal".to_string(),
@@ -1019,7 +1086,7 @@ pub fn scan_fstring(lexer: &mut super::tokenizer::Lexer<'_>) -> Option<Token> {
                     }
                 }
             }
-            '\n' if brace_depth == 0 => {
+            '\n' | '\r' if brace_depth == 0 => {
                 lexer.error = Some(crate::frontend::core::lexer::LexError::UnterminatedString {
                     position: format!("{}:{}", start_pos.line, start_pos.column),
                 });
@@ -58,7 +58,7 @@ fn log_token(token: &Token) {
     use crate::util::i18n::{t_cur, MSG};
 
     let (msg, arg) = match &token.kind {
-        TokenKind::Identifier(name) => (MSG::LexTokenIdentifier, name.clone()),
+        TokenKind::Identifier(name) => (MSG::LexTokenIdentifier, name.to_string()),
         TokenKind::KwPub
         | TokenKind::KwUse
         | TokenKind::KwSpawn
@@ -77,7 +77,13 @@ fn log_token(token: &Token) {
         | TokenKind::KwAs => (MSG::LexTokenKeyword, format!("{:?}", token.kind)),
         TokenKind::IntLiteral(n) => (MSG::LexTokenNumber, n.to_string()),
         TokenKind::FloatLiteral(f) => (MSG::LexTokenNumber, f.to_string()),
-        TokenKind::StringLiteral(s) => (MSG::LexTokenString, s.clone()),
+        TokenKind::SuffixedIntLiteral(n, suffix) => {
+            (MSG::LexTokenNumber, format!("{}{}", n, suffix.as_str()))
+        }
+        TokenKind::SuffixedFloatLiteral(f, suffix) => {
+            (MSG::LexTokenNumber, format!("{}{}", f, suffix.as_str()))
+        }
+        TokenKind::StringLiteral(s) => (MSG::LexTokenString, s.to_string()),
         TokenKind::FStringLiteral(s) => (MSG::LexTokenString, format!("f\"{}\"", s)),
         TokenKind::CharLiteral(c) => (MSG::LexTokenChar, c.to_string()),
         TokenKind::Plus
@@ -85,6 +91,12 @@ fn log_token(token: &Token) {
         | TokenKind::Star
         | TokenKind::Slash
         | TokenKind::Percent
+        | TokenKind::PlusEq
+        | TokenKind::MinusEq
+        | TokenKind::StarEq
+        | TokenKind::SlashEq
+        | TokenKind::PercentEq
+        | TokenKind::PipeGt
         | TokenKind::Arrow
         | TokenKind::FatArrow
         | TokenKind::EqEq
@@ -105,3 +117,11 @@ fn log_token(token: &Token) {
 #[cfg(test)]
 #[path = "tests/fstring.rs"]
 mod fstring_tests;
+
+#[cfg(test)]
+#[path = "tests/line_endings.rs"]
+mod line_endings_tests;
+
+#[cfg(test)]
+#[path = "tests/numeric_suffix.rs"]
+mod numeric_suffix_tests;
@@ -0,0 +1,54 @@
+//! BOM 与换行风格测试
+
+use crate::frontend::core::lexer::{tokenize, TokenKind};
+
+#[test]
+fn test_bom_is_skipped_and_offsets_kept() {
+    let tokens = tokenize("\u{FEFF}x = 1").unwrap();
+    assert!(matches!(&tokens[0].kind, TokenKind::Identifier(name) if name == "x"));
+    assert_eq!(tokens[0].span.start.column, 1);
+    assert_eq!(tokens[0].span.start.offset, 3);
+}
+
+#[test]
+fn test_crlf_and_lf_give_same_positions() {
+    let lf = tokenize("a = 1\nbb = 2\n").unwrap();
+    let crlf = tokenize("a = 1\r\nbb = 2\r\n").unwrap();
+    let positions = |tokens: &[crate::frontend::core::lexer::Token]| {
+        tokens
+            .iter()
+            .map(|t| (t.span.start.line, t.span.start.column, t.span.end.column))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(positions(&lf), positions(&crlf));
+}
+
+#[test]
+fn test_lone_cr_is_a_line_break() {
+    let tokens = tokenize("a = 1\rb = 2").unwrap();
+    let b = tokens
+        .iter()
+        .find(|t| matches!(&t.kind, TokenKind::Identifier(name) if name == "b"))
+        .unwrap();
+    assert_eq!((b.span.start.line, b.span.start.column), (2, 1));
+}
+
+#[test]
+fn test_line_comment_ends_at_crlf() {
+    let tokens = tokenize("// note\r\nx").unwrap();
+    assert!(matches!(&tokens[0].kind, TokenKind::Identifier(name) if name == "x"));
+    assert_eq!(tokens[0].span.start.line, 2);
+}
+
+#[test]
+fn test_multi_line_string_normalizes_crlf() {
+    let tokens = tokenize("s = \"\"\"a\r\nb\rc\"\"\"").unwrap();
+    let value = tokens
+        .iter()
+        .find_map(|t| match &t.kind {
+            TokenKind::StringLiteral(s) => Some(s.to_string()),
+            _ => None,
+        })
+        .unwrap();
+    assert_eq!(value, "a\nb\nc");
+}
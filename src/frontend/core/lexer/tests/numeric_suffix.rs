@@ -0,0 +1,35 @@
+//! 数值字面量后缀测试（`42i32`、`1.5f32`）
+
+use crate::frontend::core::lexer::tokens::NumericSuffix;
+use crate::frontend::core::lexer::{tokenize, TokenKind};
+
+#[test]
+fn test_int_suffix() {
+    let tokens = tokenize("42i32 7i8").unwrap();
+    assert!(matches!(
+        tokens[0].kind,
+        TokenKind::SuffixedIntLiteral(42, NumericSuffix::I32)
+    ));
+    assert!(matches!(
+        tokens[1].kind,
+        TokenKind::SuffixedIntLiteral(7, NumericSuffix::I8)
+    ));
+}
+
+#[test]
+fn test_float_suffix() {
+    let tokens = tokenize("1.5f32 2f64").unwrap();
+    assert!(matches!(
+        tokens[0].kind,
+        TokenKind::SuffixedFloatLiteral(f, NumericSuffix::F32) if f == 1.5
+    ));
+    assert!(matches!(
+        tokens[1].kind,
+        TokenKind::SuffixedFloatLiteral(f, NumericSuffix::F64) if f == 2.0
+    ));
+}
+
+#[test]
+fn test_float_with_int_suffix_is_error() {
+    assert!(tokenize("1.5i32").is_err());
+}
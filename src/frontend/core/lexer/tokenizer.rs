@@ -8,6 +8,7 @@ use super::literals::{
     is_identifier_char, is_digit,
 };
 use crate::frontend::core::lexer::tokens::*;
+use crate::util::symbol::Symbol;
 use crate::util::span::{Position, Span};
 use std::iter::Peekable;
 use std::str::Chars;
@@ -28,12 +29,17 @@ pub struct Lexer<'a> {
 impl<'a> Lexer<'a> {
     /// Create a new lexer for the given source
     pub fn new(source: &'a str) -> Self {
+        // UTF-8 BOM 不参与词法分析，但保留其字节偏移，使 span 仍指向原始源码
+        let (body, offset) = match source.strip_prefix('\u{FEFF}') {
+            Some(rest) => (rest, '\u{FEFF}'.len_utf8()),
+            None => (source, 0),
+        };
         Self {
-            chars: source.chars().peekable(),
-            offset: 0,
+            chars: body.chars().peekable(),
+            offset,
             line: 1,
             column: 1,
-            start_offset: 0,
+            start_offset: offset,
             start_line: 1,
             start_column: 1,
             error: None,
@@ -65,6 +71,15 @@ impl<'a> Lexer<'a> {
                 self.column = 1;
                 Some('\n')
             }
+            Some('\r') => {
+                // `\r\n` 由其后的 `\n` 换行；单独的 `\r`（旧式 Mac 换行）自身即换行
+                self.offset += 1;
+                if self.chars.peek() != Some(&'\n') {
+                    self.line += 1;
+                    self.column = 1;
+                }
+                Some('\r')
+            }
             Some(c) => {
                 self.offset += c.len_utf8();
                 self.column += 1;
@@ -123,7 +138,7 @@ impl<'a> Lexer<'a> {
                         self.advance();
                         self.advance();
                         while let Some(&c) = self.peek() {
-                            if c == '\n' {
+                            if c == '\n' || c == '\r' {
                                 break;
                             }
                             self.advance();
@@ -182,17 +197,17 @@ impl<'a> Lexer<'a> {
             c if is_digit(c) => scan_number(self, c),
             '"' => scan_string(self),
             '\'' => scan_char(self),
-            '+' => Some(self.make_token(TokenKind::Plus)),
+            '+' => Some(self.make_compound(TokenKind::Plus, TokenKind::PlusEq)),
             '-' => {
                 if self.peek() == Some(&'>') {
                     self.advance();
                     Some(self.make_token(TokenKind::Arrow))
                 } else {
-                    Some(self.make_token(TokenKind::Minus))
+                    Some(self.make_compound(TokenKind::Minus, TokenKind::MinusEq))
                 }
             }
-            '*' => Some(self.make_token(TokenKind::Star)),
-            '%' => Some(self.make_token(TokenKind::Percent)),
+            '*' => Some(self.make_compound(TokenKind::Star, TokenKind::StarEq)),
+            '%' => Some(self.make_compound(TokenKind::Percent, TokenKind::PercentEq)),
             ',' => Some(self.make_token(TokenKind::Comma)),
             ';' => Some(self.make_token(TokenKind::Semicolon)),
             '(' => Some(self.make_token(TokenKind::LParen)),
@@ -210,6 +225,7 @@ impl<'a> Lexer<'a> {
             '{' => Some(self.make_token(TokenKind::LBrace)),
             '}' => Some(self.make_token(TokenKind::RBrace)),
             '@' => Some(self.make_token(TokenKind::At)),
+            '#' => Some(self.make_token(TokenKind::Hash)),
             '=' => {
                 if self.peek() == Some(&'>') {
                     self.advance();
@@ -271,6 +287,9 @@ impl<'a> Lexer<'a> {
                 if self.peek() == Some(&'|') {
                     self.advance();
                     Some(self.make_token(TokenKind::Or))
+                } else if self.peek() == Some(&'>') {
+                    self.advance();
+                    Some(self.make_token(TokenKind::PipeGt))
                 } else {
                     Some(self.make_token(TokenKind::Pipe))
                 }
@@ -299,7 +318,7 @@ impl<'a> Lexer<'a> {
                     Some(self.make_token(TokenKind::Dot))
                 }
             }
-            '/' => Some(self.make_token(TokenKind::Slash)),
+            '/' => Some(self.make_compound(TokenKind::Slash, TokenKind::SlashEq)),
             '?' => Some(self.make_token(TokenKind::Question)),
             c => {
                 self.error = Some(crate::frontend::core::lexer::LexError::UnexpectedChar { ch: c });
@@ -341,7 +360,7 @@ impl<'a> Lexer<'a> {
             })
         } else {
             Some(Token {
-                kind: TokenKind::Identifier(value.clone()),
+                kind: TokenKind::Identifier(Symbol::intern(&value)),
                 span: self.span(),
                 literal: None,
             })
@@ -359,4 +378,18 @@ impl<'a> Lexer<'a> {
             literal: None,
         }
     }
+
+    /// Operator token, or its compound-assignment form when followed by `=`
+    fn make_compound(
+        &mut self,
+        op: TokenKind,
+        assign: TokenKind,
+    ) -> Token {
+        if self.peek() == Some(&'=') {
+            self.advance();
+            self.make_token(assign)
+        } else {
+            self.make_token(op)
+        }
+    }
 }
@@ -2,6 +2,7 @@
 
 use crate::util::diagnostic::{Diagnostic, ErrorCodeDefinition};
 use crate::util::span::Span;
+use crate::util::symbol::Symbol;
 
 /// Lexer error
 #[derive(Debug, thiserror::Error)]
@@ -75,15 +76,19 @@ pub enum TokenKind {
     KwUnsafe,
 
     // Identifiers
-    Identifier(String),
+    Identifier(Symbol),
     Underscore,
 
     // Literals
     IntLiteral(i128),
     FloatLiteral(f64),
+    /// Integer literal with a width suffix: `42i32`
+    SuffixedIntLiteral(i128, NumericSuffix),
+    /// Float literal with a width suffix: `1.0f32`, `2f64`
+    SuffixedFloatLiteral(f64, NumericSuffix),
     BoolLiteral(bool),
     CharLiteral(char),
-    StringLiteral(String),
+    StringLiteral(Symbol),
     /// RFC-012: F-string template literal
     /// Stores the raw content of f"..." including interpolation markers
     FStringLiteral(String),
@@ -110,6 +115,14 @@ pub enum TokenKind {
     ColonColon,
     DotDotDot,
     DotDot,
+    /// Compound assignment: `+=`, `-=`, `*=`, `/=`, `%=`
+    PlusEq,
+    MinusEq,
+    StarEq,
+    SlashEq,
+    PercentEq,
+    /// Pipeline: `|>`
+    PipeGt,
 
     // Delimiters
     LParen,
@@ -119,6 +132,7 @@ pub enum TokenKind {
     LBrace,
     RBrace,
     At,
+    Hash,
     Comma,
     Colon,
     Semicolon,
@@ -133,6 +147,57 @@ pub enum TokenKind {
     Error(String),
 }
 
+/// Numeric literal width suffix
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NumericSuffix {
+    I8,
+    I16,
+    I32,
+    I64,
+    F32,
+    F64,
+}
+
+impl NumericSuffix {
+    /// Parse the suffix text following the digits (`i32`, `f64`, ...)
+    pub fn parse(text: &str) -> Option<Self> {
+        match text {
+            "i8" => Some(NumericSuffix::I8),
+            "i16" => Some(NumericSuffix::I16),
+            "i32" => Some(NumericSuffix::I32),
+            "i64" => Some(NumericSuffix::I64),
+            "f32" => Some(NumericSuffix::F32),
+            "f64" => Some(NumericSuffix::F64),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NumericSuffix::I8 => "i8",
+            NumericSuffix::I16 => "i16",
+            NumericSuffix::I32 => "i32",
+            NumericSuffix::I64 => "i64",
+            NumericSuffix::F32 => "f32",
+            NumericSuffix::F64 => "f64",
+        }
+    }
+
+    pub fn is_float(&self) -> bool {
+        matches!(self, NumericSuffix::F32 | NumericSuffix::F64)
+    }
+
+    /// Bit width of the suffixed type
+    pub fn bits(&self) -> usize {
+        match self {
+            NumericSuffix::I8 => 8,
+            NumericSuffix::I16 => 16,
+            NumericSuffix::I32 | NumericSuffix::F32 => 32,
+            NumericSuffix::I64 | NumericSuffix::F64 => 64,
+        }
+    }
+}
+
 /// Token
 #[derive(Debug, Clone, PartialEq)]
 pub struct Token {
@@ -148,7 +213,9 @@ pub enum Literal {
     Float(f64),
     Bool(bool),
     Char(char),
-    String(String),
+    String(Symbol),
+    /// `void` 字面量（Void 类型的唯一值）
+    Void,
 }
 
 impl From<TokenKind> for Token {
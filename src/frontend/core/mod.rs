@@ -1,6 +1,7 @@
 //! Core algorithm layer
 //! Contains the main compiler algorithms split into specialized modules
 
+pub mod desugar;
 pub mod lexer;
 pub mod parser;
 pub mod spawn;
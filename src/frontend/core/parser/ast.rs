@@ -2,6 +2,7 @@
 
 pub use crate::frontend::core::lexer::tokens::Literal;
 use crate::util::span::Span;
+pub use crate::util::symbol::Symbol;
 
 #[derive(Debug, Clone)]
 pub struct SpannedIdent {
@@ -24,7 +25,7 @@ pub enum BindingSemanticKind {
 #[derive(Debug, Clone)]
 pub enum Expr {
     Lit(Literal, Span),
-    Var(String, Span),
+    Var(Symbol, Span),
     BinOp {
         op: BinOp,
         left: Box<Expr>,
@@ -91,6 +92,8 @@ pub enum Expr {
     Cast {
         expr: Box<Expr>,
         target_type: Type,
+        /// `as?` 向下转换：运行时类型不符时得到 `void`，类型为 `Option(target_type)`
+        checked: bool,
         span: Span,
     },
     Tuple(Vec<Expr>, Span),
@@ -113,8 +116,28 @@ pub enum Expr {
         field: String,
         span: Span,
     },
+    /// Compound assignment: `target op= value`
+    ///
+    /// Syntax sugar, rewritten to `target = target op value` by the desugar stage.
+    CompoundAssign {
+        op: BinOp,
+        target: Box<Expr>,
+        value: Box<Expr>,
+        span: Span,
+    },
+    /// Pipeline: `value |> func`
+    ///
+    /// Syntax sugar, rewritten to `func(value)` (or `f(value, args..)` when `func` is
+    /// the call `f(args..)`) by the desugar stage.
+    Pipe {
+        value: Box<Expr>,
+        func: Box<Expr>,
+        span: Span,
+    },
     /// Error propagation operator: `expr?`
-    /// Generated when the user writes `expr?` - propagates Err/None upward
+    ///
+    /// Syntax sugar, rewritten to a `match` on `Ok`/`Err` by the desugar stage once
+    /// type checking has validated the operand.
     Try {
         expr: Box<Expr>,
         span: Span,
@@ -154,6 +177,9 @@ pub enum Expr {
     },
     /// RFC-012: F-string template literal
     /// `f"Hello {name}"` → FString { segments: [Text("Hello "), Interpolation { expr, format_spec }] }
+    ///
+    /// Syntax sugar, rewritten to a `std.string.format` call by the desugar stage after
+    /// type checking.
     FString {
         segments: Vec<FStringSegment>,
         span: Span,
@@ -258,6 +284,8 @@ pub enum StmtKind {
         body: Vec<Stmt>,
         /// Whether this binding is public
         is_pub: bool,
+        /// Attributes written before the binding: `#[deprecated("...")]`
+        attributes: Vec<Attribute>,
     },
     /// Use statement: `use module.path` or `use module.{a, b} as c, d`
     Use {
@@ -318,6 +346,8 @@ pub struct StructField {
     pub ty: Type,
     /// 可选的默认值表达式
     pub default: Option<Box<Expr>>,
+    /// 接口体中的关联项：`Self.Item: Type`、`Self.SIDES: Int`
+    pub is_assoc: bool,
 }
 
 impl StructField {
@@ -332,6 +362,18 @@ impl StructField {
             is_mut,
             ty,
             default: None,
+            is_assoc: false,
+        }
+    }
+
+    /// 创建接口关联项（`Self.NAME: Type`）
+    pub fn assoc(
+        name: String,
+        ty: Type,
+    ) -> Self {
+        Self {
+            is_assoc: true,
+            ..Self::new(name, false, ty)
         }
     }
 
@@ -347,6 +389,7 @@ impl StructField {
             is_mut,
             ty,
             default: Some(Box::new(default)),
+            is_assoc: false,
         }
     }
 }
@@ -444,7 +487,7 @@ pub enum Type {
         name_span: Span,
         args: Vec<Type>,
     },
-    /// 关联类型访问（如 T::Item）
+    /// 关联类型访问（如 T.Item）
     AssocType {
         /// 宿主类型
         host_type: Box<Type>,
@@ -489,6 +532,9 @@ pub enum Type {
     },
     /// 编译期表达式（泛型参数位置的值表达式，如 Assert(N > 0) 中的 N > 0）
     ConstExpr(Box<Expr>),
+    /// 新类型：`UserId: Type = new Int`
+    /// 运行时表示与底层类型相同，但类型检查时与底层类型互不兼容
+    Newtype(Box<Type>),
 }
 
 /// Block
@@ -500,6 +546,28 @@ pub struct Block {
     pub span: Span,
 }
 
+/// Attribute: `#[name]`, `#[name("text")]` or `#[name(key = "text", ...)]`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Attribute {
+    pub name: String,
+    /// String arguments; positional ones have no key
+    pub args: Vec<(Option<String>, String)>,
+    pub span: Span,
+}
+
+impl Attribute {
+    /// Value of the keyed argument `key`, or the first positional argument when `key` is `None`
+    pub fn arg(
+        &self,
+        key: Option<&str>,
+    ) -> Option<&str> {
+        self.args
+            .iter()
+            .find(|(k, _)| k.as_deref() == key)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
 /// Function parameter
 #[derive(Debug, Clone)]
 pub struct Param {
@@ -557,6 +625,22 @@ impl Default for Module {
     }
 }
 
+/// 位置构造器模式 `Point(0, y)` 按字段声明顺序展开后的参数模式
+///
+/// 单字段时负载本身就是参数；参数个数与 `arity` 不符时返回 None。
+pub fn constructor_args(
+    payload: Option<&Pattern>,
+    arity: usize,
+) -> Option<Vec<&Pattern>> {
+    let args = match payload {
+        None => Vec::new(),
+        Some(single) if arity == 1 => vec![single],
+        Some(Pattern::Tuple(elems)) => elems.iter().collect(),
+        Some(single) => vec![single],
+    };
+    (args.len() == arity).then_some(args)
+}
+
 /// Returns true if a type is the `Type` meta keyword form.
 pub fn is_meta_type(ty: &Type) -> bool {
     matches!(ty, Type::MetaType { .. })
@@ -571,6 +655,38 @@ pub fn type_annotation_returns_meta_type(ty: &Type) -> bool {
     }
 }
 
+/// 类型定义是否为透明别名：`Meters: Type = Float`、`Pair: Type = (Int, Int)`
+///
+/// 结构体、枚举、新类型等会引入新的类型身份，不算别名；函数类型注解可能来自
+/// 空函数体的降级形态，也不在此列。
+pub fn is_type_alias_definition(
+    name: &str,
+    definition: &Type,
+) -> bool {
+    match definition {
+        Type::Name { name: target, .. } => target != name && target != "Type",
+        Type::Generic { name: target, .. } => target != name,
+        Type::Int(_)
+        | Type::Float(_)
+        | Type::Char
+        | Type::String
+        | Type::Bytes
+        | Type::Bool
+        | Type::Tuple(_)
+        | Type::Option(_)
+        | Type::Result(_, _) => true,
+        _ => false,
+    }
+}
+
+/// Returns true if a method binding's `method_type` denotes an associated item.
+///
+/// 关联常量 (`Square.SIDES: Int = 4`) 与关联类型 (`Square.Item: Type = Int`)
+/// 复用方法绑定的形态，区别在于 `method_type` 不是函数类型。
+pub fn is_assoc_item_type(method_type: &Type) -> bool {
+    !matches!(method_type, Type::Fn { .. })
+}
+
 /// Classify a unified binding into method/type-constructor/function semantics.
 ///
 /// The parser lowers type constructors into bindings with empty params/body and
@@ -594,3 +710,50 @@ pub fn classify_binding_semantic_kind(
 
     BindingSemanticKind::Function
 }
+
+/// 函数签名中的 const 泛型参数：`scale: (N: Int) -> (x: Int) -> Int` 中的 `N`
+///
+/// 只有柯里化签名（参数列表之后仍是函数类型）里首字母大写的 const 参数才是编译期参数，
+/// 普通函数 `(n: Int) -> Int` 的形参不在此列。
+pub fn const_generic_params<'a>(
+    generic_params: &'a [GenericParam],
+    type_annotation: Option<&Type>,
+) -> Vec<&'a GenericParam> {
+    let curried = matches!(
+        type_annotation,
+        Some(Type::Fn { return_type, .. }) if matches!(return_type.as_ref(), Type::Fn { .. })
+    );
+    if !curried {
+        return Vec::new();
+    }
+    generic_params
+        .iter()
+        .filter(|p| {
+            matches!(p.kind, GenericParamKind::Const { .. })
+                && p.name.chars().next().is_some_and(|c| c.is_uppercase())
+        })
+        .collect()
+}
+
+/// 类型中是否引用了给定名称（泛型参数名）
+pub fn type_mentions_name(
+    ty: &Type,
+    names: &[&str],
+) -> bool {
+    match ty {
+        Type::Name { name, .. } => names.contains(&name.as_str()),
+        Type::Generic { args, .. } | Type::Tuple(args) => {
+            args.iter().any(|arg| type_mentions_name(arg, names))
+        }
+        Type::Option(inner) | Type::Ref { inner, .. } => type_mentions_name(inner, names),
+        Type::Result(ok, err) => type_mentions_name(ok, names) || type_mentions_name(err, names),
+        Type::Fn {
+            params,
+            return_type,
+        } => {
+            params.iter().any(|p| type_mentions_name(p, names))
+                || type_mentions_name(return_type, names)
+        }
+        _ => false,
+    }
+}
@@ -161,6 +161,7 @@ impl<'a> ParserState<'a> {
             Some(TokenKind::KwPub) => parse_identifier_stmt(self, ss),
             Some(TokenKind::Identifier(_)) => parse_identifier_stmt(self, ss),
             Some(TokenKind::LParen) => parse_paren_destructure_stmt(self, ss),
+            Some(TokenKind::Hash) => parse_attributed_stmt(self, ss),
             Some(TokenKind::Eof) | None => None,
             Some(TokenKind::At) => {
                 self.error(ErrorCodeDefinition::unexpected_token("@").at(ss).build());
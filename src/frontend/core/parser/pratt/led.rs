@@ -41,6 +41,16 @@ impl<'a> ParserState<'a> {
         match self.current().map(|t| &t.kind) {
             // Assignment
             Some(TokenKind::Eq) => Some((BP_ASSIGN, BP_ASSIGN + 1, Self::parse_assign)),
+            // Compound assignment
+            Some(
+                TokenKind::PlusEq
+                | TokenKind::MinusEq
+                | TokenKind::StarEq
+                | TokenKind::SlashEq
+                | TokenKind::PercentEq,
+            ) => Some((BP_ASSIGN, BP_ASSIGN + 1, Self::parse_compound_assign)),
+            // Pipeline
+            Some(TokenKind::PipeGt) => Some((BP_PIPE, BP_PIPE + 1, Self::parse_pipe)),
             // Range
             Some(TokenKind::DotDot) => Some((BP_RANGE, BP_RANGE + 1, Self::parse_binary)),
             // Logical OR
@@ -98,6 +108,51 @@ impl<'a> ParserState<'a> {
         })
     }
 
+    /// Parse compound assignment expression: `target op= value`
+    fn parse_compound_assign(
+        &mut self,
+        lhs: Expr,
+        _left_bp: u8,
+    ) -> Option<Expr> {
+        let span = self.span();
+        let op = match self.current().map(|t| &t.kind) {
+            Some(TokenKind::PlusEq) => BinOp::Add,
+            Some(TokenKind::MinusEq) => BinOp::Sub,
+            Some(TokenKind::StarEq) => BinOp::Mul,
+            Some(TokenKind::SlashEq) => BinOp::Div,
+            Some(TokenKind::PercentEq) => BinOp::Mod,
+            _ => return None,
+        };
+        self.bump();
+
+        let rhs = self.parse_expression(BP_ASSIGN)?;
+
+        Some(Expr::CompoundAssign {
+            op,
+            target: Box::new(lhs),
+            value: Box::new(rhs),
+            span,
+        })
+    }
+
+    /// Parse pipeline expression: `value |> func`
+    fn parse_pipe(
+        &mut self,
+        lhs: Expr,
+        right_bp: u8,
+    ) -> Option<Expr> {
+        let span = self.span();
+        self.bump(); // consume '|>'
+
+        let func = self.parse_expression(right_bp)?;
+
+        Some(Expr::Pipe {
+            value: Box::new(lhs),
+            func: Box::new(func),
+            span,
+        })
+    }
+
     /// Parse binary operator expression
     fn parse_binary(
         &mut self,
@@ -170,7 +225,7 @@ impl<'a> ParserState<'a> {
             } = arg
             {
                 if let Expr::Var(name, _) = *left {
-                    named_args.push((name, *right));
+                    named_args.push((name.to_string(), *right));
                 } else {
                     // 左侧不是简单变量，视为普通参数
                     args.push(Expr::BinOp {
@@ -218,7 +273,7 @@ impl<'a> ParserState<'a> {
             self.bump();
             Some(Expr::FieldAccess {
                 expr: Box::new(lhs),
-                field: name,
+                field: name.to_string(),
                 span,
             })
         } else {
@@ -260,11 +315,15 @@ impl<'a> ParserState<'a> {
         let span = self.span();
         self.bump(); // consume 'as'
 
+        // `as?`: checked downcast
+        let checked = self.skip(&TokenKind::Question);
+
         let ty = self.parse_type_annotation()?;
 
         Some(Expr::Cast {
             expr: Box::new(lhs),
             target_type: ty,
+            checked,
             span,
         })
     }
@@ -326,7 +385,7 @@ impl<'a> ParserState<'a> {
         match expr {
             // Single identifier: x => expr
             Expr::Var(name, span) => Some(vec![Param {
-                name: name.clone(),
+                name: name.to_string(),
                 ty: None,
                 is_mut: false,
                 span: *span,
@@ -339,7 +398,7 @@ impl<'a> ParserState<'a> {
                 for elem in elements {
                     if let Expr::Var(name, span) = elem {
                         params.push(Param {
-                            name: name.clone(),
+                            name: name.to_string(),
                             ty: None,
                             is_mut: false,
                             span: *span,
@@ -42,9 +42,13 @@ impl<'a> ParserState<'a> {
             // Literals
             Some(TokenKind::IntLiteral(_)) => Some((BP_HIGHEST, Self::parse_int_literal)),
             Some(TokenKind::FloatLiteral(_)) => Some((BP_HIGHEST, Self::parse_float_literal)),
+            Some(TokenKind::SuffixedIntLiteral(..)) | Some(TokenKind::SuffixedFloatLiteral(..)) => {
+                Some((BP_HIGHEST, Self::parse_suffixed_literal))
+            }
             Some(TokenKind::StringLiteral(_)) => Some((BP_HIGHEST, Self::parse_string_literal)),
             Some(TokenKind::CharLiteral(_)) => Some((BP_HIGHEST, Self::parse_char_literal)),
             Some(TokenKind::BoolLiteral(_)) => Some((BP_HIGHEST, Self::parse_bool_literal)),
+            Some(TokenKind::VoidLiteral) => Some((BP_HIGHEST, Self::parse_void_literal)),
             // RFC-012: F-string literal
             Some(TokenKind::FStringLiteral(_)) => Some((BP_HIGHEST, Self::parse_fstring)),
             // Identifier or path
@@ -129,15 +133,40 @@ impl<'a> ParserState<'a> {
             Some(TokenKind::KwFor) => self.parse_spawn_for(span),
             // spawn { ... }
             Some(TokenKind::LBrace) => self.parse_spawn_block(span),
+            // spawn f(x)
+            _ => self.parse_spawn_call(span),
+        }
+    }
+
+    /// Parse spawn call: `spawn f(x)`
+    ///
+    /// 单任务形式，等价于只含一个调用的 spawn 块；类型为 `Async(T)`
+    fn parse_spawn_call(
+        &mut self,
+        span: Span,
+    ) -> Option<Expr> {
+        let start_span = self.span();
+        let (call, call_span) = match self.parse_expression(BP_UNARY) {
+            Some(call @ Expr::Call { span, .. }) => (call, span),
             _ => {
                 self.error(
-                    ErrorCodeDefinition::expected_expression("block or 'for' after spawn")
-                        .at(self.span())
+                    ErrorCodeDefinition::expected_expression("block, 'for' or call after spawn")
+                        .at(start_span)
                         .build(),
                 );
-                None
+                return None;
             }
-        }
+        };
+        Some(Expr::Spawn {
+            body: Box::new(Block {
+                stmts: vec![Stmt {
+                    kind: StmtKind::Expr(Box::new(call)),
+                    span: call_span,
+                }],
+                span: call_span,
+            }),
+            span,
+        })
     }
 
     /// Parse spawn block: `spawn { ... }`
@@ -203,7 +232,7 @@ impl<'a> ParserState<'a> {
         let body = self.parse_block_expr()?;
 
         Some(Expr::SpawnFor {
-            var,
+            var: var.to_string(),
             var_mut,
             iterable: Box::new(iterable),
             body: Box::new(body),
@@ -285,7 +314,7 @@ impl<'a> ParserState<'a> {
         // Convert Expr to String label if present
         let label_str = label.and_then(|expr| {
             if let Expr::Var(name, _) = expr {
-                Some(name)
+                Some(name.to_string())
             } else {
                 None
             }
@@ -309,7 +338,7 @@ impl<'a> ParserState<'a> {
         // Convert Expr to String label if present
         let label_str = label.and_then(|expr| {
             if let Expr::Var(name, _) = expr {
-                Some(name)
+                Some(name.to_string())
             } else {
                 None
             }
@@ -334,7 +363,7 @@ impl<'a> ParserState<'a> {
     fn parse_wildcard(&mut self) -> Option<Expr> {
         let span = self.span();
         self.bump(); // consume '_'
-        Some(Expr::Var("_".to_string(), span))
+        Some(Expr::Var(Symbol::intern("_"), span))
     }
 
     /// Parse integer literal expression
@@ -361,6 +390,29 @@ impl<'a> ParserState<'a> {
         }
     }
 
+    /// Parse suffixed numeric literal: `42i32` → `42 as i32`
+    fn parse_suffixed_literal(&mut self) -> Option<Expr> {
+        let span = self.span();
+        let token = self.current().cloned()?;
+        let (literal, suffix) = match token.kind {
+            TokenKind::SuffixedIntLiteral(n, suffix) => (Literal::Int(n), suffix),
+            TokenKind::SuffixedFloatLiteral(f, suffix) => (Literal::Float(f), suffix),
+            _ => return None,
+        };
+        self.bump();
+        let target_type = if suffix.is_float() {
+            Type::Float(suffix.bits())
+        } else {
+            Type::Int(suffix.bits())
+        };
+        Some(Expr::Cast {
+            expr: Box::new(Expr::Lit(literal, span)),
+            target_type,
+            checked: false,
+            span,
+        })
+    }
+
     /// Parse string literal expression
     fn parse_string_literal(&mut self) -> Option<Expr> {
         let span = self.span();
@@ -397,6 +449,13 @@ impl<'a> ParserState<'a> {
         }
     }
 
+    /// Parse void literal
+    fn parse_void_literal(&mut self) -> Option<Expr> {
+        let span = self.span();
+        self.bump();
+        Some(Expr::Lit(Literal::Void, span))
+    }
+
     /// RFC-012: Parse f-string literal into FString AST node
     ///
     /// The FStringLiteral token contains the raw content of f"...".
@@ -598,7 +657,7 @@ impl<'a> ParserState<'a> {
         }
 
         let mut params = vec![Param {
-            name: first_name,
+            name: first_name.to_string(),
             ty: first_type,
             is_mut: first_is_mut,
             span: first_span,
@@ -637,7 +696,7 @@ impl<'a> ParserState<'a> {
             };
 
             params.push(Param {
-                name: param_name,
+                name: param_name.to_string(),
                 ty: param_type,
                 is_mut: param_is_mut,
                 span: param_span,
@@ -708,7 +767,7 @@ impl<'a> ParserState<'a> {
         Some(Expr::ListComp {
             element: Box::new(elements[0].clone()),
             var: if let Expr::Var(name, _) = pattern {
-                name
+                name.to_string()
             } else {
                 // Fallback: use a default variable name if pattern is not a simple identifier
                 "_".to_string()
@@ -871,9 +930,8 @@ impl<'a> ParserState<'a> {
         let mut arms = Vec::new();
 
         while !self.at(&TokenKind::RBrace) && !self.at_end() {
-            // Parse pattern - use BP_LAMBDA + 1 to prevent => from being parsed as lambda
-            // Lambda binding power is 11, so we use 12 to stop before =>
-            let pattern = self.parse_expression(12)?;
+            let arm_span = self.span();
+            let pattern = self.parse_match_pattern()?;
 
             self.expect(&TokenKind::FatArrow);
 
@@ -893,9 +951,9 @@ impl<'a> ParserState<'a> {
             };
 
             arms.push(MatchArm {
-                pattern: self.expr_to_pattern(&pattern),
+                pattern,
                 body,
-                span: self.span(),
+                span: arm_span,
             });
 
             // Skip comma if present
@@ -911,6 +969,30 @@ impl<'a> ParserState<'a> {
         })
     }
 
+    /// Parse a match arm pattern, including constructor patterns (`Some(x)`,
+    /// `Point(0, y)`), qualified names (`Color.red`) and `|` alternatives
+    fn parse_match_pattern(&mut self) -> Option<Pattern> {
+        let mut alternatives = Vec::new();
+        loop {
+            // Lambda binding power is 11, so we use 12 to stop before =>
+            let mut expr = self.parse_expression(12)?;
+            // Calls and field access bind tighter than =>, but still below 12
+            while self.at(&TokenKind::LParen) || self.at(&TokenKind::Dot) {
+                let (_, right_bp, parser_fn) = self.infix_info()?;
+                expr = parser_fn(self, expr, right_bp)?;
+            }
+            alternatives.push(self.expr_to_pattern(&expr));
+            if !self.skip(&TokenKind::Pipe) {
+                break;
+            }
+        }
+        Some(if alternatives.len() == 1 {
+            alternatives.remove(0)
+        } else {
+            Pattern::Or(alternatives)
+        })
+    }
+
     /// Parse while expression: `while cond { body }`
     fn parse_while(&mut self) -> Option<Expr> {
         let span = self.span();
@@ -939,7 +1021,7 @@ impl<'a> ParserState<'a> {
 
         Some(Expr::For {
             var: if let Expr::Var(name, _) = pattern {
-                name
+                name.to_string()
             } else {
                 // Fallback: use a default variable name if pattern is not a simple identifier
                 "_".to_string()
@@ -976,7 +1058,7 @@ impl<'a> ParserState<'a> {
                 if name == "_" {
                     Pattern::Wildcard
                 } else {
-                    Pattern::Identifier(name.clone())
+                    Pattern::Identifier(name.to_string())
                 }
             }
             Expr::Lit(lit, _) => Pattern::Literal(lit.clone()),
@@ -994,13 +1076,13 @@ impl<'a> ParserState<'a> {
                             // This is a struct pattern: Name { ... }
                             let fields = self.parse_struct_pattern_fields(block);
                             return Pattern::Struct {
-                                name: name.clone(),
+                                name: name.to_string(),
                                 fields,
                             };
                         }
                         Pattern::Union {
-                            name: name.clone(),
-                            variant: name.clone(),
+                            name: name.to_string(),
+                            variant: name.to_string(),
                             pattern: Some(Box::new(self.expr_to_pattern(&args[0]))),
                         }
                     } else {
@@ -1008,8 +1090,8 @@ impl<'a> ParserState<'a> {
                         let tuple_pattern =
                             Pattern::Tuple(args.iter().map(|e| self.expr_to_pattern(e)).collect());
                         Pattern::Union {
-                            name: name.clone(),
-                            variant: name.clone(),
+                            name: name.to_string(),
+                            variant: name.to_string(),
                             pattern: Some(Box::new(tuple_pattern)),
                         }
                     }
@@ -1090,7 +1172,11 @@ impl<'a> ParserState<'a> {
                     // Default pattern for bare identifier
                     Some((field_name.clone(), true, Pattern::Identifier(field_name)))
                 } else {
-                    Some((name.clone(), false, Pattern::Identifier(name.clone())))
+                    Some((
+                        name.to_string(),
+                        false,
+                        Pattern::Identifier(name.to_string()),
+                    ))
                 }
             }
             Expr::Call { func, args, .. } => {
@@ -1101,7 +1187,7 @@ impl<'a> ParserState<'a> {
                         is_mut = true;
                         func_name.trim_start_matches("mut ").trim().to_string()
                     } else {
-                        func_name.clone()
+                        func_name.to_string()
                     };
 
                     if field_name.is_empty() {
@@ -18,6 +18,7 @@ pub const BP_HIGHEST: u8 = 10;
 
 /// Additional binding power levels for infix operators
 pub const BP_RANGE: u8 = 1;
+pub const BP_PIPE: u8 = 1;
 pub const BP_OR: u8 = 2;
 pub const BP_AND: u8 = 3;
 pub const BP_EQ: u8 = 4;
@@ -0,0 +1,124 @@
+//! Attribute parsing
+//!
+//! Implements parsing for attributes written before a binding:
+//! - `#[name]`
+//! - `#[name("text")]`
+//! - `#[name(key = "text", ...)]`
+
+use crate::frontend::core::lexer::tokens::*;
+use crate::frontend::core::parser::ast::*;
+use crate::frontend::core::parser::ParserState;
+use crate::util::diagnostic::ErrorCodeDefinition;
+use crate::util::span::Span;
+
+/// Parse attributes and the binding they annotate: `#[deprecated] name = ...`
+pub fn parse_attributed_stmt(
+    state: &mut ParserState<'_>,
+    span: Span,
+) -> Option<Stmt> {
+    let mut attributes = Vec::new();
+    while state.at(&TokenKind::Hash) {
+        attributes.push(parse_attribute(state)?);
+    }
+
+    let mut stmt = state.parse_statement()?;
+    match &mut stmt.kind {
+        StmtKind::Binding {
+            attributes: slot, ..
+        } => {
+            *slot = attributes;
+            stmt.span = Span::new(span.start, stmt.span.end);
+            Some(stmt)
+        }
+        _ => {
+            state.error(
+                ErrorCodeDefinition::invalid_syntax(
+                    "Attributes can only be applied to function or type definitions",
+                )
+                .at(span)
+                .build(),
+            );
+            Some(stmt)
+        }
+    }
+}
+
+/// Parse a single attribute: `#[name(args)]`
+fn parse_attribute(state: &mut ParserState<'_>) -> Option<Attribute> {
+    let start = state.span();
+    state.bump(); // consume '#'
+
+    if !state.expect(&TokenKind::LBracket) {
+        return None;
+    }
+
+    let name = match state.current().map(|t| &t.kind) {
+        Some(TokenKind::Identifier(n)) => n.to_string(),
+        _ => {
+            let span = state.span();
+            state.error(
+                ErrorCodeDefinition::invalid_syntax("Expected attribute name after '#['")
+                    .at(span)
+                    .build(),
+            );
+            return None;
+        }
+    };
+    state.bump();
+
+    let mut args = Vec::new();
+    if state.skip(&TokenKind::LParen) {
+        while !state.at(&TokenKind::RParen) && !state.at_end() {
+            // key = "value"
+            let key = match (
+                state.current().map(|t| &t.kind),
+                state.peek().map(|t| &t.kind),
+            ) {
+                (Some(TokenKind::Identifier(k)), Some(TokenKind::Eq)) => {
+                    let key = k.to_string();
+                    state.bump();
+                    state.bump();
+                    Some(key)
+                }
+                _ => None,
+            };
+
+            match state.current().map(|t| &t.kind) {
+                Some(TokenKind::StringLiteral(s)) => {
+                    args.push((key, s.to_string()));
+                    state.bump();
+                }
+                _ => {
+                    let span = state.span();
+                    state.error(
+                        ErrorCodeDefinition::invalid_syntax(&format!(
+                            "Expected string literal in attribute '{}'",
+                            name
+                        ))
+                        .at(span)
+                        .build(),
+                    );
+                    return None;
+                }
+            }
+
+            if !state.skip(&TokenKind::Comma) {
+                break;
+            }
+        }
+        if !state.expect(&TokenKind::RParen) {
+            return None;
+        }
+    }
+
+    let end = state.span();
+    if !state.expect(&TokenKind::RBracket) {
+        return None;
+    }
+
+    Some(Attribute {
+        name,
+        args,
+        span: Span::new(start.start, end.end),
+    })
+}
@@ -59,14 +59,15 @@ pub fn parse_method_bind(
 
     Some(Stmt {
         kind: StmtKind::Binding {
-            name: method_name,
-            type_name: Some(type_name),
+            name: method_name.to_string(),
+            type_name: Some(type_name.to_string()),
             method_type: Some(method_type),
             generic_params: Vec::new(),
             type_annotation: None,
             params,
             body,
             is_pub: false,
+            attributes: Vec::new(),
         },
         span,
     })
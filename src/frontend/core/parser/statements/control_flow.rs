@@ -80,7 +80,7 @@ fn parse_loop_label(state: &mut crate::frontend::core::parser::ParserState<'_>)
         Some(TokenKind::Identifier(name)) => {
             let name = name.clone();
             state.bump();
-            Some(name)
+            Some(name.to_string())
         }
         _ => None,
     }
@@ -144,7 +144,7 @@ pub fn parse_for_stmt(
 
     Some(Stmt {
         kind: StmtKind::For {
-            var,
+            var: var.to_string(),
             var_span,
             var_mut,
             iterable,
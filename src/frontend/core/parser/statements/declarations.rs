@@ -257,7 +257,7 @@ pub fn parse_external_binding_stmt(
 ) -> Option<Stmt> {
     // Parse type name
     let type_name = match state.current().map(|t| &t.kind) {
-        Some(TokenKind::Identifier(n)) => n.clone(),
+        Some(TokenKind::Identifier(n)) => n.to_string(),
         _ => return None,
     };
     state.bump(); // consume type name
@@ -266,7 +266,7 @@ pub fn parse_external_binding_stmt(
 
     // Parse method name
     let method_name = match state.current().map(|t| &t.kind) {
-        Some(TokenKind::Identifier(n)) => n.clone(),
+        Some(TokenKind::Identifier(n)) => n.to_string(),
         _ => return None,
     };
     state.bump(); // consume method name
@@ -290,12 +290,12 @@ pub fn parse_external_binding_stmt(
     let binding = if state.at(&TokenKind::LBracket) {
         let positions = parse_binding_positions(state).ok()?;
         BindingKind::External {
-            function: func_name,
+            function: func_name.to_string(),
             positions,
         }
     } else {
         BindingKind::DefaultExternal {
-            function: func_name,
+            function: func_name.to_string(),
         }
     };
 
@@ -317,7 +317,7 @@ pub fn parse_method_bind_stmt(
 ) -> Option<Stmt> {
     // Parse type name
     let type_name = match state.current().map(|t| &t.kind) {
-        Some(TokenKind::Identifier(n)) => n.clone(),
+        Some(TokenKind::Identifier(n)) => n.to_string(),
         _ => {
             state.error(
                 ErrorCodeDefinition::unexpected_token(&format!(
@@ -342,7 +342,7 @@ pub fn parse_method_bind_stmt(
 
     // Parse method name
     let method_name = match state.current().map(|t| &t.kind) {
-        Some(TokenKind::Identifier(n)) => n.clone(),
+        Some(TokenKind::Identifier(n)) => n.to_string(),
         _ => {
             state.error(
                 ErrorCodeDefinition::unexpected_token(&format!(
@@ -365,6 +365,11 @@ pub fn parse_method_bind_stmt(
         return None;
     }
 
+    // 关联项：标注不是函数签名时，按关联常量 / 关联类型解析
+    if !state.at(&TokenKind::LParen) {
+        return parse_assoc_item_stmt(state, type_name, method_name, span);
+    }
+
     // Parse method type annotation - use parse_fn_type_with_names to preserve param names
     // This returns (Vec<Param>, Box<Type>) where Param has name and ty.
     let (method_fn_params, method_return_type) = match parse_fn_type_with_names(state) {
@@ -436,8 +441,8 @@ pub fn parse_method_bind_stmt(
 
     Some(Stmt {
         kind: StmtKind::Binding {
-            name: method_name,
-            type_name: Some(type_name),
+            name: method_name.to_string(),
+            type_name: Some(type_name.to_string()),
             method_type: Some(method_type),
             generic_params: Vec::new(),
             type_annotation: None,
@@ -445,6 +450,86 @@ pub fn parse_method_bind_stmt(
             params,
             body: body_stmts,
             is_pub: false,
+            attributes: Vec::new(),
+        },
+        span,
+    })
+}
+
+/// Parse associated item binding (the part after `Type.Name:`)
+///
+/// - 关联常量: `Square.SIDES: Int = 4`
+/// - 关联类型: `Square.Item: Type = Int`
+///
+/// 两者都降级为 `method_type` 不是函数类型的方法绑定：
+/// 关联常量的 body 为 `return <expr>`，关联类型的目标类型放在 `type_annotation` 中。
+fn parse_assoc_item_stmt(
+    state: &mut ParserState<'_>,
+    type_name: String,
+    item_name: String,
+    span: Span,
+) -> Option<Stmt> {
+    let item_type = match parse_type_annotation(state) {
+        Some(ty) => ty,
+        None => {
+            state.error(parse_msg(format!(
+                "Expected type annotation for associated item '{}.{}'",
+                type_name, item_name
+            )));
+            return None;
+        }
+    };
+
+    if !state.expect(&TokenKind::Eq) {
+        return None;
+    }
+
+    let (type_annotation, body) = if matches!(item_type, Type::MetaType { .. }) {
+        let target = match parse_type_annotation(state) {
+            Some(ty) => ty,
+            None => {
+                state.error(parse_msg(format!(
+                    "Expected type after '=' in associated type '{}.{}'",
+                    type_name, item_name
+                )));
+                return None;
+            }
+        };
+        (Some(target), Vec::new())
+    } else {
+        let value = match state.parse_expression(BP_LOWEST) {
+            Some(expr) => expr,
+            None => {
+                state.error(parse_msg(format!(
+                    "Expected value after '=' in associated constant '{}.{}'",
+                    type_name, item_name
+                )));
+                return None;
+            }
+        };
+        let value_span = state.span();
+        (
+            None,
+            vec![Stmt {
+                kind: StmtKind::Return(Some(Box::new(value))),
+                span: value_span,
+            }],
+        )
+    };
+
+    state.skip(&TokenKind::Semicolon);
+
+    Some(Stmt {
+        kind: StmtKind::Binding {
+            name: item_name,
+            type_name: Some(type_name),
+            method_type: Some(item_type),
+            generic_params: Vec::new(),
+            type_annotation,
+            params: Vec::new(),
+            body,
+            is_pub: false,
+            attributes: Vec::new(),
         },
         span,
     })
@@ -714,7 +799,7 @@ fn parse_var_stmt_with_pub(
                 state.skip(&TokenKind::Semicolon);
                 return Some(Stmt {
                     kind: StmtKind::Binding {
-                        name,
+                        name: name.to_string(),
                         type_name: None,
                         method_type: None,
                         generic_params: generic_params_for_type,
@@ -723,6 +808,7 @@ fn parse_var_stmt_with_pub(
                         params: Vec::new(),
                         body: Vec::new(),
                         is_pub: final_is_pub,
+                        attributes: Vec::new(),
                     },
                     span,
                 });
@@ -786,24 +872,53 @@ fn parse_var_stmt_with_pub(
                         }
                     }
 
+                    // 泛型函数的值级形参类型位于内层函数类型中：
+                    // `second: (T: Type) -> (a: T, b: T) -> T = (a, b) => b`
+                    let inner_param_types = match &type_annotation {
+                        Some(Type::Fn { return_type, .. })
+                            if value_params.is_empty() && !extracted_params.is_empty() =>
+                        {
+                            match return_type.as_ref() {
+                                Type::Fn { params, .. } if params.len() == lambda_params.len() => {
+                                    Some(params)
+                                }
+                                _ => None,
+                            }
+                        }
+                        _ => None,
+                    };
+
                     // 合并类型信息
-                    let mut merged = Vec::new();
-                    for (i, extracted) in extracted_params.iter().enumerate() {
-                        if let Some(lambda_p) = lambda_params.get(i) {
-                            merged.push(Param {
+                    let merged: Vec<Param> = if let Some(inner_types) = inner_param_types {
+                        lambda_params
+                            .iter()
+                            .zip(inner_types)
+                            .map(|(lambda_p, ty)| Param {
                                 name: lambda_p.name.clone(),
-                                ty: extracted.ty.clone(),
+                                ty: Some(ty.clone()),
                                 is_mut: lambda_p.is_mut,
                                 span: lambda_p.span,
-                            });
-                        } else {
-                            merged.push(extracted.clone());
-                        }
-                    }
+                            })
+                            .collect()
+                    } else {
+                        extracted_params
+                            .iter()
+                            .enumerate()
+                            .map(|(i, extracted)| match lambda_params.get(i) {
+                                Some(lambda_p) => Param {
+                                    name: lambda_p.name.clone(),
+                                    ty: extracted.ty.clone(),
+                                    is_mut: lambda_p.is_mut,
+                                    span: lambda_p.span,
+                                },
+                                None => extracted.clone(),
+                            })
+                            .collect()
+                    };
                     state.skip(&TokenKind::Semicolon);
                     return Some(Stmt {
                         kind: StmtKind::Binding {
-                            name,
+                            name: name.to_string(),
                             type_name: None,
                             method_type: None,
                             generic_params,
@@ -811,6 +926,7 @@ fn parse_var_stmt_with_pub(
                             params: merged,
                             body: body.stmts.clone(),
                             is_pub: final_is_pub,
+                            attributes: Vec::new(),
                         },
                         span,
                     });
@@ -832,7 +948,7 @@ fn parse_var_stmt_with_pub(
 
                     return Some(Stmt {
                         kind: StmtKind::Binding {
-                            name,
+                            name: name.to_string(),
                             type_name: None,
                             method_type: None,
                             generic_params,
@@ -840,6 +956,7 @@ fn parse_var_stmt_with_pub(
                             params: extracted_params.clone(),
                             body,
                             is_pub: final_is_pub,
+                            attributes: Vec::new(),
                         },
                         span,
                     });
@@ -894,6 +1011,7 @@ fn parse_var_stmt_with_pub(
                                 params: Vec::new(),
                                 body: Vec::new(),
                                 is_pub: false,
+                                attributes: Vec::new(),
                             },
                             span,
                         });
@@ -917,7 +1035,7 @@ fn parse_var_stmt_with_pub(
             state.skip(&TokenKind::Semicolon);
             return Some(Stmt {
                 kind: StmtKind::Binding {
-                    name,
+                    name: name.to_string(),
                     type_name: None,
                     method_type: None,
                     generic_params: generic_params_for_type,
@@ -926,6 +1044,7 @@ fn parse_var_stmt_with_pub(
                     params: Vec::new(),
                     body: Vec::new(),
                     is_pub: false,
+                    attributes: Vec::new(),
                 },
                 span,
             });
@@ -951,7 +1070,7 @@ fn parse_var_stmt_with_pub(
             if let Expr::Block(block) = init_expr.as_ref() {
                 return Some(Stmt {
                     kind: StmtKind::Binding {
-                        name,
+                        name: name.to_string(),
                         type_name: None,
                         method_type: None,
                         generic_params: Vec::new(),
@@ -961,6 +1080,7 @@ fn parse_var_stmt_with_pub(
                         params: Vec::new(),
                         body: block.stmts.clone(),
                         is_pub: final_is_pub,
+                        attributes: Vec::new(),
                     },
                     span,
                 });
@@ -969,7 +1089,7 @@ fn parse_var_stmt_with_pub(
 
         return Some(Stmt {
             kind: StmtKind::Var {
-                name,
+                name: name.to_string(),
                 name_span,
                 type_annotation,
                 initializer,
@@ -983,7 +1103,7 @@ fn parse_var_stmt_with_pub(
 
     Some(Stmt {
         kind: StmtKind::Var {
-            name,
+            name: name.to_string(),
             name_span,
             type_annotation,
             initializer: None,
@@ -999,6 +1119,18 @@ fn parse_var_stmt_with_pub(
 /// - Const parameters: `[N: Int]` - const generic with type annotation
 /// - Platform parameter: `[P: X86_64]` - RFC-011 platform specialization
 fn parse_type_definition(state: &mut ParserState<'_>) -> Option<Type> {
+    // `new` 是上下文关键字：`UserId: Type = new Int` 定义新类型
+    if matches!(state.current().map(|t| &t.kind), Some(TokenKind::Identifier(n)) if n == "new")
+        && matches!(
+            state.peek().map(|t| &t.kind),
+            Some(TokenKind::Identifier(_) | TokenKind::LParen | TokenKind::LBrace)
+        )
+    {
+        state.bump();
+        let underlying = parse_type_annotation(state)?;
+        return Some(Type::Newtype(Box::new(underlying)));
+    }
+
     let first_type = parse_type_annotation(state)?;
 
     // 检查是否有 | 符号（不允许使用不带花括号的枚举语法）
@@ -1085,7 +1217,7 @@ pub fn parse_identifier_stmt(
 
             // If = is followed by (, try to parse as function definition
             if state.at(&TokenKind::LParen) {
-                if let Some(stmt) = parse_fn_stmt_with_name(state, name.clone(), span, is_pub) {
+                if let Some(stmt) = parse_fn_stmt_with_name(state, name.to_string(), span, is_pub) {
                     state.skip(&TokenKind::Semicolon);
                     return Some(stmt);
                 }
@@ -1096,7 +1228,7 @@ pub fn parse_identifier_stmt(
                 let err_count2 = state.error_count();
 
                 if let Some(stmt) =
-                    parse_fn_stmt_with_name_simple(state, name.clone(), span, is_pub)
+                    parse_fn_stmt_with_name_simple(state, name.to_string(), span, is_pub)
                 {
                     state.skip(&TokenKind::Semicolon);
                     return Some(stmt);
@@ -1116,7 +1248,7 @@ pub fn parse_identifier_stmt(
             if let Expr::Block(block) = &initializer {
                 return Some(Stmt {
                     kind: StmtKind::Binding {
-                        name,
+                        name: name.to_string(),
                         type_name: None,
                         method_type: None,
                         generic_params: Vec::new(),
@@ -1125,6 +1257,7 @@ pub fn parse_identifier_stmt(
                         params: Vec::new(),
                         body: block.stmts.clone(),
                         is_pub,
+                        attributes: Vec::new(),
                     },
                     span,
                 });
@@ -1132,7 +1265,7 @@ pub fn parse_identifier_stmt(
 
             return Some(Stmt {
                 kind: StmtKind::Var {
-                    name,
+                    name: name.to_string(),
                     name_span,
                     type_annotation: None,
                     initializer: Some(Box::new(initializer)),
@@ -1159,7 +1292,7 @@ pub fn parse_identifier_stmt(
         let first_token = state.current().unwrap();
         let first_name = SpannedIdent {
             name: match &first_token.kind {
-                TokenKind::Identifier(n) => n.clone(),
+                TokenKind::Identifier(n) => n.to_string(),
                 _ => {
                     state.restore_position(saved);
                     state.truncate_errors(err_count);
@@ -1184,7 +1317,7 @@ pub fn parse_identifier_stmt(
             };
             names.push(SpannedIdent {
                 name: match tok.kind {
-                    TokenKind::Identifier(n) => n,
+                    TokenKind::Identifier(n) => n.to_string(),
                     _ => unreachable!(),
                 },
                 span: tok.span,
@@ -1260,7 +1393,7 @@ pub fn parse_constructor(state: &mut ParserState<'_>) -> Option<VariantDef> {
     };
 
     Some(VariantDef {
-        name,
+        name: name.to_string(),
         name_span,
         params,
         span: state.span(),
@@ -1285,7 +1418,7 @@ fn parse_constructor_params(state: &mut ParserState<'_>) -> Option<Vec<(Option<S
     if has_named_params {
         while !state.at(&TokenKind::RParen) && !state.at_end() {
             let name = match state.current().map(|t| &t.kind) {
-                Some(TokenKind::Identifier(n)) => n.clone(),
+                Some(TokenKind::Identifier(n)) => n.to_string(),
                 _ => break,
             };
             state.bump();
@@ -1351,7 +1484,7 @@ pub fn parse_paren_destructure_stmt(
     };
     let first_name = SpannedIdent {
         name: match &first_token.kind {
-            TokenKind::Identifier(n) => n.clone(),
+            TokenKind::Identifier(n) => n.to_string(),
             _ => unreachable!(),
         },
         span: first_token.span,
@@ -1374,7 +1507,7 @@ pub fn parse_paren_destructure_stmt(
         };
         names.push(SpannedIdent {
             name: match tok.kind {
-                TokenKind::Identifier(n) => n,
+                TokenKind::Identifier(n) => n.to_string(),
                 _ => unreachable!(),
             },
             span: tok.span,
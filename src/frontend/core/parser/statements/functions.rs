@@ -43,6 +43,7 @@ pub fn parse_fn_stmt_with_name(
             params,
             body,
             is_pub,
+            attributes: Vec::new(),
         },
         span,
     })
@@ -77,13 +78,14 @@ pub fn parse_fn_stmt_with_name_simple(
             generic_params: Vec::new(),
             type_annotation: None,
             params: vec![Param {
-                name: param_name,
+                name: param_name.to_string(),
                 ty: None,
                 is_mut: false,
                 span: param_span,
             }],
             body,
             is_pub,
+            attributes: Vec::new(),
         },
         span,
     })
@@ -158,7 +160,7 @@ pub fn parse_fn_params(state: &mut ParserState<'_>) -> Option<Vec<Param>> {
         };
 
         params.push(Param {
-            name,
+            name: name.to_string(),
             ty,
             is_mut,
             span: param_span,
@@ -26,7 +26,7 @@ pub fn parse_use_stmt(
         while !state.at(&TokenKind::RBrace) && !state.at_end() {
             match state.current().map(|t| &t.kind) {
                 Some(TokenKind::Identifier(n)) => {
-                    items.push(n.clone());
+                    items.push(n.to_string());
                     state.bump();
                     state.skip(&TokenKind::Comma);
                 }
@@ -47,7 +47,7 @@ pub fn parse_use_stmt(
     let alias = if state.skip(&TokenKind::KwAs) {
         let mut aliases = Vec::new();
         while let Some(TokenKind::Identifier(n)) = state.current().map(|t| &t.kind) {
-            aliases.push(n.clone());
+            aliases.push(n.to_string());
             state.bump();
             // 继续读取逗号分隔的下一个别名
             if !state.skip(&TokenKind::Comma) {
@@ -90,9 +90,9 @@ fn parse_use_path(state: &mut ParserState<'_>) -> Option<(String, Span, Vec<Span
             start = Some(token_span.start);
         }
         end = Some(token_span.end);
-        parts.push(n.clone());
+        parts.push(n.to_string());
         part_spans.push(SpannedIdent {
-            name: n.clone(),
+            name: n.to_string(),
             span: token_span,
         });
         state.bump();
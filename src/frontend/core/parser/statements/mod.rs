@@ -1,6 +1,7 @@
 //! Statement parsing modules
 //! Contains specialized modules for different statement types
 
+pub mod attributes;
 pub mod bindings;
 pub mod control_flow;
 pub mod declarations;
@@ -18,6 +19,7 @@ pub use functions::*;
 pub use imports::*;
 pub use control_flow::*;
 pub use bindings::*;
+pub use attributes::*;
 
 /// Statement parsing trait for RFC support
 pub trait StatementParser {
@@ -65,6 +67,8 @@ impl StatementParser for ParserState<'_> {
             Some(TokenKind::LParen) => declarations::parse_paren_destructure_stmt(self, start_span),
             // Eof - no statement to parse
             Some(TokenKind::Eof) | None => None,
+            // #[attr] 属性，作用于其后的绑定
+            Some(TokenKind::Hash) => attributes::parse_attributed_stmt(self, start_span),
             // Phase 1: @ 不再是有效的语句起始（eval block 已移除）
             Some(TokenKind::At) => {
                 self.error(
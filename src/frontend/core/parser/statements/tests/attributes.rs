@@ -0,0 +1,82 @@
+//! 属性解析测试 — `#[name]` / `#[name("text")]` / `#[name(key = "text")]`
+
+use crate::frontend::core::lexer::tokenize;
+use crate::frontend::core::parser::parse;
+use crate::frontend::core::parser::ast::{Attribute, StmtKind};
+
+fn parse_attributes(source: &str) -> Vec<Attribute> {
+    let tokens = tokenize(source).unwrap();
+    let result = parse(&tokens);
+    assert!(!result.has_errors, "{:?}", result.errors);
+    assert_eq!(result.module.items.len(), 1);
+    match result.module.items.into_iter().next().unwrap().kind {
+        StmtKind::Binding { attributes, .. } => attributes,
+        other => panic!("Expected Binding, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_attribute_without_args() {
+    let attrs = parse_attributes("#[deprecated]\nold: () -> Int = { return 1 }");
+    assert_eq!(attrs.len(), 1);
+    assert_eq!(attrs[0].name, "deprecated");
+    assert!(attrs[0].args.is_empty());
+}
+
+#[test]
+fn test_attribute_positional_and_keyed_args() {
+    let attrs = parse_attributes(
+        "#[deprecated(\"old api\", replacement = \"add\")]\nplus: (a: Int, b: Int) -> Int = (a, b) => a + b",
+    );
+    assert_eq!(attrs[0].arg(None), Some("old api"));
+    assert_eq!(attrs[0].arg(Some("replacement")), Some("add"));
+    assert_eq!(attrs[0].arg(Some("message")), None);
+}
+
+#[test]
+fn test_attribute_on_pub_function() {
+    let tokens = tokenize("#[deprecated]\npub old: () -> Int = { return 1 }").unwrap();
+    let result = parse(&tokens);
+    assert!(!result.has_errors, "{:?}", result.errors);
+    match &result.module.items[0].kind {
+        StmtKind::Binding {
+            name,
+            is_pub,
+            attributes,
+            ..
+        } => {
+            assert_eq!(name, "old");
+            assert!(*is_pub);
+            assert_eq!(attributes.len(), 1);
+        }
+        other => panic!("Expected Binding, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_multiple_attributes_span_whole_statement() {
+    let tokens = tokenize("#[a]\n#[b]\nf: () -> Int = { return 1 }").unwrap();
+    let result = parse(&tokens);
+    assert!(!result.has_errors);
+    let stmt = &result.module.items[0];
+    assert_eq!(stmt.span.start.line, 1);
+    match &stmt.kind {
+        StmtKind::Binding { attributes, .. } => {
+            let names: Vec<&str> = attributes.iter().map(|a| a.name.as_str()).collect();
+            assert_eq!(names, vec!["a", "b"]);
+        }
+        other => panic!("Expected Binding, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_attribute_on_non_binding_is_error() {
+    let tokens = tokenize("#[deprecated]\nreturn 1").unwrap();
+    assert!(parse(&tokens).has_errors);
+}
+
+#[test]
+fn test_attribute_non_string_arg_is_error() {
+    let tokens = tokenize("#[deprecated(42)]\nf: () -> Int = { return 1 }").unwrap();
+    assert!(parse(&tokens).has_errors);
+}
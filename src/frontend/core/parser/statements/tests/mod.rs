@@ -1,6 +1,7 @@
 //! Statement parsing sub-module tests
 //! Mirrors src/frontend/core/parser/statements/
 
+mod attributes;
 mod bindings;
 mod control_flow;
 mod declarations;
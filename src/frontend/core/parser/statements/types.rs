@@ -3,12 +3,13 @@
 //! Implements parsing for:
 //! - Type annotations: `name: Type`
 //! - Function types: `(Params) -> ReturnType`
-//! - Struct types: `{ field: Type }`
+//! - Struct types: `{ field: Type }`, with interface associated items `{ Self.Item: Type }`
 //! - Enum types: `{ Variant1 | Variant2 }`
 //! - Tuple types: `(T, U, V)`
 //! - Named struct types: `Name(x: Type, y: Type)`
 //! - Constructor types: `Name(Type1, Type2)` — the ONLY generic application syntax
 //! - Meta types: `Type`
+//! - Associated type access: `T.Item`
 //!
 //! Also provides `TypeStatementParser` trait so callers can use
 //! `state.parse_type_annotation()` instead of the free function.
@@ -146,10 +147,10 @@ pub fn parse_type_annotation(state: &mut ParserState<'_>) -> Option<Type> {
 
                 if has_named_fields {
                     // Parse as named struct: Name(x: Type, y: Type)
-                    return parse_named_struct_type(name, name_span, state);
+                    return parse_named_struct_type(name.to_string(), name_span, state);
                 } else {
                     // Parse as generic constructor: Name(Type1, Type2)
-                    return parse_constructor_type(name, name_span, state);
+                    return parse_constructor_type(name.to_string(), name_span, state);
                 }
             }
             // 后视检查：如果下一个 token 是比较/相等运算符，继续作为表达式解析
@@ -173,8 +174,26 @@ pub fn parse_type_annotation(state: &mut ParserState<'_>) -> Option<Type> {
                 ));
                 return None;
             }
+            // 关联类型访问: `T.Item`
+            if state.at(&TokenKind::Dot) {
+                if let Some(TokenKind::Identifier(assoc_name)) = state.peek().map(|t| &t.kind) {
+                    let assoc_name = assoc_name.to_string();
+                    state.bump(); // consume '.'
+                    let assoc_name_span = state.span();
+                    state.bump(); // consume assoc name
+                    return Some(Type::AssocType {
+                        host_type: Box::new(Type::Name {
+                            name: name.to_string(),
+                            span: name_span,
+                        }),
+                        assoc_name,
+                        assoc_name_span,
+                        assoc_args: Vec::new(),
+                    });
+                }
+            }
             Some(Type::Name {
-                name,
+                name: name.to_string(),
                 span: name_span,
             })
         }
@@ -264,6 +283,20 @@ pub fn parse_type_annotation(state: &mut ParserState<'_>) -> Option<Type> {
                 Some(Type::Tuple(param_types))
             }
         }
+        Some(TokenKind::IntLiteral(value)) => {
+            // const 泛型实参：`Vec(Float, 3)` 中的 `3`
+            let value = *value;
+            let span = state.span();
+            state.bump();
+            Some(Type::Literal {
+                name: value.to_string(),
+                name_span: span,
+                base_type: Box::new(Type::Name {
+                    name: "Int".to_string(),
+                    span,
+                }),
+            })
+        }
         Some(TokenKind::LBrace) => parse_struct_type(state),
         Some(TokenKind::LBracket) => {
             // RFC-010: `[T, U](params) -> Ret` syntax is removed.
@@ -304,7 +337,7 @@ fn parse_named_struct_type(
         }
 
         let field_type = parse_type_annotation(state)?;
-        fields.push(StructField::new(field_name, is_mut, field_type));
+        fields.push(StructField::new(field_name.to_string(), is_mut, field_type));
 
         if !state.skip(&TokenKind::Comma) {
             break;
@@ -413,7 +446,7 @@ pub fn parse_fn_type_with_names(state: &mut ParserState<'_>) -> Option<(Vec<Para
                     // extract_generic_params will unpack this into multiple constraints.
                     Some(Type::Tuple(all_types))
                 } else {
-                    Some(wrap_literal_type_if_needed(name.clone(), parsed_type))
+                    Some(wrap_literal_type_if_needed(name.to_string(), parsed_type))
                 }
             } else {
                 // 无类型标注，HM 推断
@@ -421,7 +454,7 @@ pub fn parse_fn_type_with_names(state: &mut ParserState<'_>) -> Option<(Vec<Para
             };
 
             params.push(Param {
-                name,
+                name: name.to_string(),
                 ty,
                 is_mut,
                 span: param_span,
@@ -495,6 +528,29 @@ fn parse_struct_type(state: &mut ParserState<'_>) -> Option<Type> {
             let name = name.clone();
             state.bump();
 
+            // 接口关联项: `Self.Item: Type`、`Self.SIDES: Int`
+            if name == "Self" && state.skip(&TokenKind::Dot) {
+                let item_name = match state.current().map(|t| &t.kind) {
+                    Some(TokenKind::Identifier(n)) => n.clone(),
+                    _ => {
+                        state.error(parse_msg(
+                            "Expected associated item name after 'Self.'".to_string(),
+                        ));
+                        return None;
+                    }
+                };
+                state.bump();
+                if !state.expect(&TokenKind::Colon) {
+                    return None;
+                }
+                let item_type = parse_type_annotation(state)?;
+                fields.push(StructField::assoc(item_name.to_string(), item_type));
+                if !state.skip(&TokenKind::Comma) {
+                    break;
+                }
+                continue;
+            }
+
             // 检查下一个 token 是否是 mut 或冒号
             let is_mut = state.skip(&TokenKind::KwMut);
 
@@ -513,7 +569,7 @@ fn parse_struct_type(state: &mut ParserState<'_>) -> Option<Type> {
                         let body_expr = state.parse_expression(BP_LOWEST)?;
                         let (params, return_type) = extract_fn_type_info(&field_type);
                         bindings.push(TypeBodyBinding {
-                            name,
+                            name: name.to_string(),
                             kind: BindingKind::Anonymous {
                                 params,
                                 return_type: Box::new(return_type),
@@ -525,7 +581,7 @@ fn parse_struct_type(state: &mut ParserState<'_>) -> Option<Type> {
                         // 默认值字段: name: Type = expression
                         let default_expr = state.parse_expression(BP_LOWEST)?;
                         fields.push(StructField::with_default(
-                            name,
+                            name.to_string(),
                             is_mut,
                             field_type,
                             default_expr,
@@ -533,7 +589,7 @@ fn parse_struct_type(state: &mut ParserState<'_>) -> Option<Type> {
                     }
                 } else {
                     // 普通字段: name: Type
-                    fields.push(StructField::new(name, is_mut, field_type));
+                    fields.push(StructField::new(name.to_string(), is_mut, field_type));
                 }
             } else if state.skip(&TokenKind::Eq) {
                 // 无冒号但有等号: 外部函数绑定 name = function[positions] 或默认绑定 name = function
@@ -553,18 +609,18 @@ fn parse_struct_type(state: &mut ParserState<'_>) -> Option<Type> {
                 if state.at(&TokenKind::LBracket) {
                     let positions = parse_binding_positions(state).ok()?;
                     bindings.push(TypeBodyBinding {
-                        name,
+                        name: name.to_string(),
                         kind: BindingKind::External {
-                            function: func_name,
+                            function: func_name.to_string(),
                             positions,
                         },
                     });
                 } else {
                     // 默认绑定: name = function（自动查找第一个类型匹配位置）
                     bindings.push(TypeBodyBinding {
-                        name,
+                        name: name.to_string(),
                         kind: BindingKind::DefaultExternal {
-                            function: func_name,
+                            function: func_name.to_string(),
                         },
                     });
                 }
@@ -582,7 +638,7 @@ fn parse_struct_type(state: &mut ParserState<'_>) -> Option<Type> {
                 return parse_enum_variants_in_braces(state);
             } else {
                 // 接口约束: InterfaceName
-                interfaces.push(name);
+                interfaces.push(name.to_string());
             }
 
             // 跳过逗号，如果不是逗号则结束循环
@@ -628,7 +684,7 @@ fn parse_enum_variants_in_braces(state: &mut ParserState<'_>) -> Option<Type> {
             };
 
             VariantDef {
-                name,
+                name: name.to_string(),
                 name_span,
                 params,
                 span: state.span(),
@@ -668,7 +724,7 @@ fn parse_enum_variants_in_braces(state: &mut ParserState<'_>) -> Option<Type> {
                 };
 
                 variants.push(VariantDef {
-                    name,
+                    name: name.to_string(),
                     name_span,
                     params,
                     span: state.span(),
@@ -339,6 +339,7 @@ fn test_stmtkind_binding() {
             params: vec![],
             body: vec![],
             is_pub: false,
+            attributes: Vec::new(),
         },
         span: Span::dummy(),
     };
@@ -902,6 +903,7 @@ fn test_expr_cast() {
             name: "Float".into(),
             span: Span::dummy(),
         },
+        checked: false,
         span: Span::dummy(),
     };
     assert!(matches!(expr, Expr::Cast { .. }));
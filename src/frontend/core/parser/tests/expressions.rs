@@ -2,7 +2,7 @@
 
 use crate::frontend::core::lexer::tokenize;
 use crate::frontend::core::parser::parse_expression;
-use crate::frontend::core::parser::ast::{BinOp, Expr, UnOp};
+use crate::frontend::core::parser::ast::{BinOp, Expr, Pattern, Type, UnOp};
 
 fn parse_expr(source: &str) -> Expr {
     let tokens = tokenize(source).unwrap();
@@ -259,6 +259,22 @@ fn test_if_expr() {
     assert!(matches!(expr, Expr::If { .. }));
 }
 
+#[test]
+fn test_match_constructor_and_or_patterns() {
+    let expr = parse_expr("match p { Point(0, y) => y, Some(n) => n, 1 | 2 => 3, _ => 0 }");
+    let Expr::Match { arms, .. } = expr else {
+        panic!("Expected Expr::Match");
+    };
+    assert!(matches!(
+        &arms[0].pattern,
+        Pattern::Union { variant, pattern: Some(args), .. }
+            if variant == "Point" && matches!(args.as_ref(), Pattern::Tuple(elems) if elems.len() == 2)
+    ));
+    assert!(matches!(&arms[1].pattern, Pattern::Union { variant, .. } if variant == "Some"));
+    assert!(matches!(&arms[2].pattern, Pattern::Or(alts) if alts.len() == 2));
+    assert!(matches!(arms[3].pattern, Pattern::Wildcard));
+}
+
 #[test]
 fn test_block_expr() {
     let expr = parse_expr("{ 42 }");
@@ -272,7 +288,20 @@ fn test_block_expr() {
 #[test]
 fn test_cast() {
     let expr = parse_expr("42 as Float");
-    assert!(matches!(expr, Expr::Cast { .. }));
+    assert!(matches!(expr, Expr::Cast { checked: false, .. }));
+}
+
+#[test]
+fn test_checked_cast() {
+    let expr = parse_expr("value as? String");
+    assert!(matches!(
+        expr,
+        Expr::Cast {
+            checked: true,
+            target_type: Type::Name { ref name, .. },
+            ..
+        } if name == "String"
+    ));
 }
 
 // ============================================================================
@@ -47,3 +47,118 @@ fn test_variable_and_binding() {
     assert!(!result.has_errors);
     assert_eq!(result.module.items.len(), 2);
 }
+
+#[test]
+fn test_assoc_items_lower_to_method_bindings() {
+    use crate::frontend::core::parser::ast::{StmtKind, Type};
+
+    let source = "Square.SIDES: Int = 4\nSquare.Item: Type = Int";
+    let tokens = tokenize(source).unwrap();
+    let result = parse(&tokens);
+    assert!(!result.has_errors);
+    assert_eq!(result.module.items.len(), 2);
+
+    match &result.module.items[0].kind {
+        StmtKind::Binding {
+            name,
+            type_name,
+            method_type,
+            body,
+            ..
+        } => {
+            assert_eq!(name, "SIDES");
+            assert_eq!(type_name.as_deref(), Some("Square"));
+            assert!(matches!(method_type, Some(Type::Name { name, .. }) if name == "Int"));
+            assert!(matches!(body[0].kind, StmtKind::Return(Some(_))));
+        }
+        other => panic!("Expected Binding, got {:?}", other),
+    }
+
+    match &result.module.items[1].kind {
+        StmtKind::Binding {
+            name,
+            method_type,
+            type_annotation,
+            body,
+            ..
+        } => {
+            assert_eq!(name, "Item");
+            assert!(matches!(method_type, Some(Type::MetaType { .. })));
+            assert!(matches!(type_annotation, Some(Type::Name { name, .. }) if name == "Int"));
+            assert!(body.is_empty());
+        }
+        other => panic!("Expected Binding, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_newtype_definition() {
+    use crate::frontend::core::parser::ast::{StmtKind, Type};
+
+    let source = "UserId: Type = new Int\nMeters: Type = Float";
+    let tokens = tokenize(source).unwrap();
+    let result = parse(&tokens);
+    assert!(!result.has_errors);
+    assert_eq!(result.module.items.len(), 2);
+
+    match &result.module.items[0].kind {
+        StmtKind::Binding {
+            name,
+            type_annotation: Some(Type::Newtype(inner)),
+            ..
+        } => {
+            assert_eq!(name, "UserId");
+            assert!(matches!(inner.as_ref(), Type::Name { name, .. } if name == "Int"));
+        }
+        other => panic!("Expected newtype Binding, got {:?}", other),
+    }
+
+    match &result.module.items[1].kind {
+        StmtKind::Binding {
+            type_annotation, ..
+        } => {
+            assert!(matches!(type_annotation, Some(Type::Name { name, .. }) if name == "Float"));
+        }
+        other => panic!("Expected Binding, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_interface_assoc_items_are_marked() {
+    use crate::frontend::core::parser::ast::{StmtKind, Type};
+
+    let source = "Shape: Type = { Self.SIDES: Int, Count: Int, name: () -> String }\n\
+                  first: (T: Shape) -> (x: T.Item) -> T.Item = (x) => x";
+    let tokens = tokenize(source).unwrap();
+    let result = parse(&tokens);
+    assert!(!result.has_errors, "{:?}", result.errors);
+
+    let StmtKind::Binding {
+        type_annotation: Some(Type::Struct { fields, .. }),
+        ..
+    } = &result.module.items[0].kind
+    else {
+        panic!("Expected struct type binding");
+    };
+    let marks: Vec<(&str, bool)> = fields
+        .iter()
+        .map(|f| (f.name.as_str(), f.is_assoc))
+        .collect();
+    assert_eq!(marks, [("SIDES", true), ("Count", false), ("name", false)]);
+
+    let StmtKind::Binding {
+        type_annotation: Some(Type::Fn { return_type, .. }),
+        ..
+    } = &result.module.items[1].kind
+    else {
+        panic!("Expected function binding");
+    };
+    let Type::Fn { return_type, .. } = return_type.as_ref() else {
+        panic!("Expected curried function type");
+    };
+    assert!(matches!(
+        return_type.as_ref(),
+        Type::AssocType { host_type, assoc_name, .. }
+            if assoc_name == "Item" && matches!(host_type.as_ref(), Type::Name { name, .. } if name == "T")
+    ));
+}
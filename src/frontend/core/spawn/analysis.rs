@@ -69,6 +69,19 @@ pub fn is_direct_child(stmt: &Stmt) -> bool {
     matches!(stmt.kind, StmtKind::Expr(_))
 }
 
+/// 识别单任务形式 `spawn f(x)`：spawn 块只含一个调用表达式语句
+///
+/// 返回该调用表达式；其结果即 spawn 表达式的值（类型 `Async(T)`）。
+pub fn spawn_call(body: &Block) -> Option<&Expr> {
+    match body.stmts.as_slice() {
+        [Stmt {
+            kind: StmtKind::Expr(expr),
+            ..
+        }] if matches!(expr.as_ref(), Expr::Call { .. }) => Some(expr),
+        _ => None,
+    }
+}
+
 /// 分析 spawn 块，生成执行计划
 pub fn analyze_spawn_body(
     body: &Block,
@@ -94,13 +107,13 @@ pub fn analyze_spawn_body(
                     let (reads, mut writes, mut resource_vars) =
                         analyze_reads_writes(right, trait_table, local_var_types);
                     // 赋值目标是写入变量
-                    writes.insert(name.clone());
+                    writes.insert(name.to_string());
                     if is_resource_type(name, trait_table, local_var_types) {
-                        resource_vars.insert(name.clone());
+                        resource_vars.insert(name.to_string());
                     }
                     tasks.push(TaskInfo {
                         index: i,
-                        target: Some(name.clone()),
+                        target: Some(name.to_string()),
                         expr: (**right).clone(),
                         reads,
                         writes,
@@ -405,9 +418,9 @@ fn collect_reads_writes(
     match expr {
         // 叶子节点：变量引用
         Expr::Var(name, _) => {
-            reads.insert(name.clone());
+            reads.insert(name.to_string());
             if is_resource_type(name, trait_table, local_var_types) {
-                resource_vars.insert(name.clone());
+                resource_vars.insert(name.to_string());
             }
         }
 
@@ -423,11 +436,16 @@ fn collect_reads_writes(
             left,
             right,
             ..
+        }
+        | Expr::CompoundAssign {
+            target: left,
+            value: right,
+            ..
         } => {
             if let Expr::Var(name, _) = left.as_ref() {
-                writes.insert(name.clone());
+                writes.insert(name.to_string());
                 if is_resource_type(name, trait_table, local_var_types) {
-                    resource_vars.insert(name.clone());
+                    resource_vars.insert(name.to_string());
                 }
             }
             collect_reads_writes(
@@ -441,7 +459,12 @@ fn collect_reads_writes(
         }
 
         // 其他二元运算
-        Expr::BinOp { left, right, .. } => {
+        Expr::BinOp { left, right, .. }
+        | Expr::Pipe {
+            value: left,
+            func: right,
+            ..
+        } => {
             collect_reads_writes(
                 left,
                 reads,
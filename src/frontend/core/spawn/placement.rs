@@ -95,7 +95,17 @@ impl SpawnPlacementChecker {
         match expr {
             Expr::Lit(..) | Expr::Var(..) | Expr::Break(..) | Expr::Continue(..) => {}
 
-            Expr::BinOp { left, right, .. } => {
+            Expr::BinOp { left, right, .. }
+            | Expr::CompoundAssign {
+                target: left,
+                value: right,
+                ..
+            }
+            | Expr::Pipe {
+                value: left,
+                func: right,
+                ..
+            } => {
                 self.check_expr(left);
                 self.check_expr(right);
             }
@@ -11,7 +11,7 @@ use crate::frontend::core::parser::ast::{
 };
 use crate::frontend::core::spawn::analysis::{
     analyze_reads_writes, analyze_spawn_body, analyze_spawn_for, build_execution_plan,
-    is_direct_child,
+    is_direct_child, spawn_call,
 };
 use crate::frontend::core::types::{MonoType, TraitTable};
 use crate::util::span::Span;
@@ -34,7 +34,7 @@ fn empty_var_types() -> HashMap<String, MonoType> {
 }
 
 fn var_expr(name: &str) -> Expr {
-    Expr::Var(name.to_string(), dummy_span())
+    Expr::Var(name.into(), dummy_span())
 }
 
 fn assign_stmt(
@@ -140,6 +140,43 @@ fn test_is_direct_child_with_var_decl_is_false() {
     assert!(!result, "StmtKind::Var 不应被视为直接子表达式");
 }
 
+// ============================================================================
+// spawn_call — 单任务形式 spawn f(x)
+// ============================================================================
+
+#[test]
+fn test_spawn_call_single_call_stmt() {
+    // spawn f(x) 解析为只含一个调用语句的 spawn 块
+    let body = spawn_body(vec![Stmt {
+        kind: StmtKind::Expr(Box::new(call_expr("fetch", vec![var_expr("url")]))),
+        span: dummy_span(),
+    }]);
+
+    let call = spawn_call(&body);
+
+    assert!(matches!(call, Some(Expr::Call { .. })));
+}
+
+#[test]
+fn test_spawn_call_rejects_block_forms() {
+    // 赋值任务、多任务块都不是单任务形式
+    let assign = spawn_body(vec![assign_stmt("a", call_expr("fetch", vec![]))]);
+    let multi = spawn_body(vec![
+        Stmt {
+            kind: StmtKind::Expr(Box::new(call_expr("f", vec![]))),
+            span: dummy_span(),
+        },
+        Stmt {
+            kind: StmtKind::Expr(Box::new(call_expr("g", vec![]))),
+            span: dummy_span(),
+        },
+    ]);
+
+    assert!(spawn_call(&assign).is_none());
+    assert!(spawn_call(&multi).is_none());
+    assert!(spawn_call(&spawn_body(vec![])).is_none());
+}
+
 // ============================================================================
 // analyze_reads_writes — 基础
 // ============================================================================
@@ -263,6 +300,7 @@ fn test_reads_writes_cast_expr() {
             name: "Int".to_string(),
             span: dummy_span(),
         },
+        checked: false,
         span: dummy_span(),
     };
 
@@ -116,7 +116,7 @@ fn test_spawn_in_for_body_allowed() {
                 var: "i".to_string(),
                 var_span: Span::dummy(),
                 var_mut: false,
-                iterable: Box::new(Expr::Var("items".to_string(), Span::dummy())),
+                iterable: Box::new(Expr::Var("items".into(), Span::dummy())),
                 body: Box::new(make_block(vec![spawn_stmt(vec![])])),
                 label: None,
             },
@@ -3,6 +3,7 @@
 //! 包含 TypeChecker 的完整实现
 
 use std::collections::{HashMap, HashSet};
+use std::ops::Range;
 
 use crate::frontend::core::parser::ast::Module;
 use crate::frontend::core::types::{MonoType, PolyType, TraitTable};
@@ -33,6 +34,12 @@ pub struct TypeChecker {
     body_checker: Option<inference::StatementChecker>,
     /// 语义信息收集（typecheck 阶段同时产出）
     semantic_db: semantic_db::SemanticDB,
+    /// 增量检查：跳过函数体检查的顶层项下标（诊断由调用方复用）
+    reused_items: HashSet<usize>,
+    /// 被跳过的项上次检查是否有错误
+    reused_errors: bool,
+    /// 第三遍中每个顶层项产生的错误在诊断列表中的下标范围
+    item_error_ranges: Vec<(usize, Range<usize>)>,
 }
 
 impl TypeChecker {
@@ -50,9 +57,31 @@ impl TypeChecker {
             env,
             body_checker: None,
             semantic_db: semantic_db::SemanticDB::new(),
+            reused_items: HashSet::new(),
+            reused_errors: false,
+            item_error_ranges: Vec::new(),
         }
     }
 
+    /// 增量检查：不再检查这些顶层项（`module.items` 下标）的函数体
+    ///
+    /// 只应传入签名完整标注且未改动的函数；它们的诊断由调用方从缓存复用，
+    /// `with_errors` 表示复用的诊断中是否有错误（有错误时不做未使用检查）。
+    pub fn reuse_items(
+        &mut self,
+        items: HashSet<usize>,
+        with_errors: bool,
+    ) {
+        self.reused_items = items;
+        self.reused_errors = with_errors;
+    }
+
+    /// 第三遍检查中各顶层项（`module.items` 下标）产生的错误在
+    /// `TypeCheckResult::diagnostics` 中的下标范围，用于把没有位置的错误归属到项
+    pub fn item_error_ranges(&self) -> &[(usize, Range<usize>)] {
+        &self.item_error_ranges
+    }
+
     /// 注册预定义的 const 函数
     /// 这些函数用于值依赖类型的编译期求值
     fn register_predefined_const_functions(env: &mut TypeEnvironment) {
@@ -167,30 +196,10 @@ impl TypeChecker {
 
     /// 检查整个模块
     ///
-    /// 在收集模式下，将收集所有错误后统一返回。
+    /// 单条语句或函数体内的错误不会中断检查，一次调用报告所有相互独立的错误。
     pub fn check_module(
         &mut self,
         module: &Module,
-    ) -> TypeCheckResult {
-        self.check_module_impl(module, false)
-    }
-
-    /// 检查整个模块（收集所有错误模式）
-    ///
-    /// 启用错误收集模式后，类型检查器会尽可能多地收集错误，
-    /// 而不是在第一个错误处停止。适用于 LSP 诊断场景。
-    pub fn check_module_collect_all(
-        &mut self,
-        module: &Module,
-    ) -> TypeCheckResult {
-        self.check_module_impl(module, true)
-    }
-
-    /// 检查整个模块的内部实现
-    fn check_module_impl(
-        &mut self,
-        module: &Module,
-        collect_all: bool,
     ) -> TypeCheckResult {
         // 第一遍：收集所有类型定义
         for stmt in &module.items {
@@ -254,10 +263,6 @@ impl TypeChecker {
             .map(|(name, poly)| (name.clone(), poly.body.clone()))
             .collect();
         body_checker.set_type_defs(type_defs);
-        // 如果启用收集模式，设置收集所有错误
-        if collect_all {
-            body_checker.set_collect_all_errors(true);
-        }
         *self.body_checker_mut() = body_checker;
 
         // 将环境中的变量同步到 body_checker
@@ -267,18 +272,59 @@ impl TypeChecker {
         }
 
         // 第三遍：检查所有语句（包括函数体）
-        for stmt in &module.items {
-            if let Err(e) = self.body_checker_mut().check_stmt(stmt) {
+        // 语句出错不会中断检查：函数体内收集的错误按顶层项顺序并入诊断
+        // 连续出现的独立函数并行检查函数体，结果仍按源码顺序并回
+        let independent = self.independent_items(module);
+        let mut index = 0;
+        while index < module.items.len() {
+            let start = index;
+            while index < module.items.len() && independent[index] {
+                index += 1;
+            }
+            if index - start > 1 {
+                self.check_items_parallel(module, start..index);
+                continue;
+            }
+            index = start + 1;
+            if self.reused_items.contains(&start) {
+                continue;
+            }
+            let first_error = self.errors().len();
+            let result = self.body_checker_mut().check_stmt(&module.items[start]);
+            for err in self.body_checker_mut().drain_collected_errors() {
+                self.add_error(err);
+            }
+            if let Err(e) = result {
                 self.add_error(*e);
             }
+            let end = self.errors().len();
+            self.item_error_ranges.push((start, first_error..end));
         }
 
+        // 以下逐项分析只作用于需要重新检查的项
+        let rechecked_module;
+        let checked = if self.reused_items.is_empty() {
+            module
+        } else {
+            rechecked_module = Module {
+                items: module
+                    .items
+                    .iter()
+                    .enumerate()
+                    .filter(|(index, _)| !self.reused_items.contains(index))
+                    .map(|(_, stmt)| stmt.clone())
+                    .collect(),
+                span: module.span,
+            };
+            &rechecked_module
+        };
+
         // Phase 2.5: 检查精化类型绑定
         // 遍历所有语句，对变量绑定的精化类型执行证明检查
         let mut proof_calls = Vec::new();
-        self.collect_refined_binding_checks(module, &mut proof_calls);
+        self.collect_refined_binding_checks(checked, &mut proof_calls);
 
-        // 收集 body_checker 中累积的错误（收集模式下产生的）
+        // 收集 body_checker 在精化检查中累积的错误
         if let Some(ref mut bc) = self.body_checker {
             for err in bc.drain_collected_errors() {
                 self.env.errors.add_error(err);
@@ -289,7 +335,7 @@ impl TypeChecker {
         // 分析循环和递归函数，自动证明终止性
         let term_results = {
             let mut term_checker = super::layers::termination::TerminationChecker::new();
-            term_checker.check_module(module, self.env())
+            term_checker.check_module(checked, self.env())
         };
         for result in term_results {
             match result.into_result() {
@@ -301,9 +347,22 @@ impl TypeChecker {
         // RFC-027: 所有权检查 — 在终止检查之后、约束求解之前运行
         // 分析借用令牌冲突、Move/Drop/Clone/Mut 语义（RFC-009a §系统谓词清单）
         let (release_plan, escaped_refs) = {
-            let mut ownership_checker = super::layers::ownership::OwnershipChecker::new();
+            // 局部变量类型决定赋值/传参是复制还是 Move
+            let local_types: HashMap<String, MonoType> = match self.body_checker.as_mut() {
+                Some(bc) => bc
+                    .vars()
+                    .into_iter()
+                    .map(|(name, poly)| {
+                        let ty = bc.solver().resolve_type(&poly.body);
+                        (name, ty)
+                    })
+                    .collect(),
+                None => HashMap::new(),
+            };
+            let mut ownership_checker =
+                super::layers::ownership::OwnershipChecker::new().with_local_types(local_types);
             let (ownership_results, plan, escaped_refs) =
-                ownership_checker.check_module(module, self.env());
+                ownership_checker.check_module(checked, self.env());
             for result in ownership_results {
                 match result {
                     ProofResult::Proved => {}
@@ -326,6 +385,23 @@ impl TypeChecker {
             }
         }
 
+        // 从 body_checker 收集实例化请求、定宽算术表、隐式整数转浮点与各位置的推断类型
+        let (mut instantiation_requests, sized_arith, int_to_float, expr_types) =
+            if let Some(ref bc) = self.body_checker {
+                (
+                    bc.resolved_instantiation_requests(),
+                    bc.sized_arith.clone(),
+                    bc.int_to_float.clone(),
+                    bc.resolved_expr_types(),
+                )
+            } else {
+                (Vec::new(), HashMap::new(), HashSet::new(), HashMap::new())
+            };
+
+        // RFC-011: 泛型约束检查 — 具体类型必须满足 (T: Show) 声明的接口，
+        // 约束随请求交给单态化器，在实例化时再次校验
+        let generic_bounds = self.check_generic_bounds(module, &mut instantiation_requests);
+
         // 语义收集：遍历 AST 构建 SemanticDB
         // 即便类型检查存在错误（如语法或类型错误），我们也要尽可能收集当前的语义 token，保证代码染色等功能
         self.collect_semantic_tokens(module);
@@ -333,6 +409,15 @@ impl TypeChecker {
         // 收集错误（无论有无错误都收进 result.diagnostics）
         let diagnostics = self.errors().to_vec();
 
+        // 弃用检查：引用 #[deprecated] 函数/类型只产生警告
+        let mut warnings =
+            super::passes::deprecation::DeprecationChecker::new().check_module(module);
+
+        // 未使用代码检查：有错误时跳过，避免在残缺代码上误报
+        if diagnostics.is_empty() && !self.reused_errors {
+            warnings.extend(super::passes::unused::UnusedChecker::new().check_module(module));
+        }
+
         // 构建类型检查结果
         // 合并 StatementChecker 中的局部变量类型到 bindings
         let mut bindings = self.env.vars.clone();
@@ -346,7 +431,8 @@ impl TypeChecker {
                     bindings.insert(name.clone(), poly.clone());
                 }
                 // 收集局部变量的 MonoType（用于 IR 生成器错误消息）
-                local_var_types.insert(name, poly.body);
+                // Async(T) 是编译期标记，运行时寄存器中已是汇合后的 T
+                local_var_types.insert(name, poly.body.joined());
             }
         }
 
@@ -356,7 +442,7 @@ impl TypeChecker {
             let is_function =
                 matches!(poly.body, crate::frontend::core::types::MonoType::Fn { .. });
             if !is_function && !local_var_types.contains_key(name) {
-                local_var_types.insert(name.clone(), poly.body.clone());
+                local_var_types.insert(name.clone(), poly.body.clone().joined());
             }
         }
 
@@ -365,16 +451,10 @@ impl TypeChecker {
         // 所以这里直接使用 scope 中的类型即可，不需要额外 resolve。
         // （注：如果后续需要支持更复杂的泛型推导，可能需要重新设计 solver 的共享机制）
 
-        // 从 body_checker 收集实例化请求
-        let instantiation_requests = if let Some(ref bc) = self.body_checker {
-            bc.instantiation_requests.clone()
-        } else {
-            Vec::new()
-        };
-
         TypeCheckResult {
             module_name: self.env.module_name.clone(),
             diagnostics,
+            warnings,
             bindings,
             local_var_types,
             semantic_db: std::mem::take(&mut self.semantic_db),
@@ -383,6 +463,213 @@ impl TypeChecker {
             release_plan,
             escaped_refs,
             instantiation_requests,
+            generic_bounds,
+            sized_arith,
+            int_to_float,
+            expr_types,
+        }
+    }
+
+    /// 检查泛型函数实例化是否满足类型参数约束
+    ///
+    /// 约束是接口类型（字段全为函数的记录），按结构化子类型检查：
+    /// 具体类型需要提供接口要求的全部方法。
+    /// 同时把每个泛型参数的约束附加到请求的 `GenericFunctionId` 上，并返回
+    /// 全部泛型函数的约束表，供单态化器在实例化（包括嵌套调用）时校验。
+    fn check_generic_bounds(
+        &mut self,
+        module: &Module,
+        requests: &mut [crate::middle::passes::mono::instance::InstantiationRequest],
+    ) -> HashMap<String, Vec<Vec<crate::middle::passes::mono::instance::GenericBound>>> {
+        use crate::frontend::core::parser::ast::{GenericParamKind, StmtKind, Type};
+        use crate::middle::passes::mono::instance::GenericBound;
+
+        // 函数名 -> [(泛型参数名, 约束接口名列表)]
+        let mut bounds: HashMap<&str, Vec<(&str, Vec<&str>)>> = HashMap::new();
+        for stmt in &module.items {
+            let StmtKind::Binding {
+                name,
+                generic_params,
+                ..
+            } = &stmt.kind
+            else {
+                continue;
+            };
+            let params: Vec<(&str, Vec<&str>)> = generic_params
+                .iter()
+                .filter(|p| matches!(p.kind, GenericParamKind::Type))
+                .map(|p| {
+                    let names = p
+                        .constraints
+                        .iter()
+                        .filter_map(|c| match c {
+                            Type::Name { name, .. } => Some(name.as_str()),
+                            _ => None,
+                        })
+                        .collect();
+                    (p.name.as_str(), names)
+                })
+                .collect();
+            if params.iter().any(|(_, c)| !c.is_empty()) {
+                bounds.insert(name.as_str(), params);
+            }
+        }
+
+        // 函数名 -> 各泛型参数的约束（接口约束附带要求的方法名）
+        let generic_bounds: HashMap<String, Vec<Vec<GenericBound>>> = bounds
+            .iter()
+            .map(|(name, params)| {
+                let param_bounds = params
+                    .iter()
+                    .map(|(_, constraints)| {
+                        constraints
+                            .iter()
+                            .map(|constraint| {
+                                let methods = self
+                                    .env
+                                    .types
+                                    .get(*constraint)
+                                    .filter(|def| def.body.is_constraint())
+                                    .map(|def| {
+                                        def.body
+                                            .constraint_fields()
+                                            .into_iter()
+                                            .map(|(name, _)| name)
+                                            .collect()
+                                    })
+                                    .unwrap_or_default();
+                                GenericBound::new(*constraint, methods)
+                            })
+                            .collect()
+                    })
+                    .collect();
+                (name.to_string(), param_bounds)
+            })
+            .collect();
+
+        let checker = inference::BoundsChecker::new();
+        let mut errors = Vec::new();
+        for req in requests.iter_mut() {
+            let Some(params) = bounds.get(req.generic_id().name()) else {
+                continue;
+            };
+            if let Some(param_bounds) = generic_bounds.get(req.generic_id().name()) {
+                req.generic_id = req.generic_id.clone().with_bounds(param_bounds.clone());
+            }
+            // 泛型函数体内的调用以所在函数的类型参数表示，特化所在函数时再检查
+            if req.caller.is_some() {
+                continue;
+            }
+
+            for ((_, constraints), type_arg) in params.iter().zip(req.type_args()) {
+                // 泛型函数体内的调用以接口本身代表受约束的类型参数，具体类型在实例化时检查
+                let is_interface = match type_arg {
+                    MonoType::TypeRef(name) => self
+                        .env
+                        .types
+                        .get(name.as_str())
+                        .is_some_and(|def| def.body.is_constraint()),
+                    other => other.is_constraint(),
+                };
+                if is_interface {
+                    continue;
+                }
+                for constraint in constraints {
+                    let Some(constraint_ty) = self.env.types.get(*constraint) else {
+                        continue;
+                    };
+                    if !constraint_ty.body.is_constraint() {
+                        continue;
+                    }
+                    if let Err(e) =
+                        checker.check_constraint(type_arg, &constraint_ty.body, Some(&self.env))
+                    {
+                        errors.push(
+                            ErrorCodeDefinition::trait_bound_not_satisfied(
+                                &format!("{} ({})", type_arg.type_name(), e.reason),
+                                constraint,
+                            )
+                            .at(req.source_location)
+                            .build(),
+                        );
+                    }
+                }
+            }
+        }
+        for err in errors {
+            self.add_error(err);
+        }
+        generic_bounds
+    }
+
+    /// 标记可以并行检查的顶层项
+    ///
+    /// 签名完整标注的非泛型函数，且名称在模块中唯一（重复定义只检查第一个）。
+    fn independent_items(
+        &self,
+        module: &Module,
+    ) -> Vec<bool> {
+        let mut name_counts: HashMap<&str, usize> = HashMap::new();
+        for stmt in &module.items {
+            if let crate::frontend::core::parser::ast::StmtKind::Binding { name, .. } = &stmt.kind {
+                *name_counts.entry(name.as_str()).or_default() += 1;
+            }
+        }
+        module
+            .items
+            .iter()
+            .enumerate()
+            .map(|(index, stmt)| {
+                let unique = match &stmt.kind {
+                    crate::frontend::core::parser::ast::StmtKind::Binding { name, .. } => {
+                        name_counts.get(name.as_str()) == Some(&1)
+                    }
+                    _ => false,
+                };
+                unique
+                    && !self.reused_items.contains(&index)
+                    && inference::StatementChecker::is_independent_fn(stmt)
+            })
+            .collect()
+    }
+
+    /// 并行检查一段连续的独立函数
+    ///
+    /// 每个函数在 body_checker 的独立副本上检查，再按源码顺序并回。
+    fn check_items_parallel(
+        &mut self,
+        module: &Module,
+        range: std::ops::Range<usize>,
+    ) {
+        let base = self.body_checker_mut().fork();
+        let check = |index: usize| {
+            let mut fork = base.fork();
+            let result = fork.check_stmt(&module.items[index]);
+            (fork, result)
+        };
+        #[cfg(feature = "cli")]
+        let outcomes: Vec<_> = {
+            use rayon::prelude::*;
+            range.clone().into_par_iter().map(check).collect()
+        };
+        #[cfg(not(feature = "cli"))]
+        let outcomes: Vec<_> = range.clone().map(check).collect();
+
+        for (index, (fork, result)) in range.zip(outcomes) {
+            let crate::frontend::core::parser::ast::StmtKind::Binding { name, .. } =
+                &module.items[index].kind
+            else {
+                continue;
+            };
+            let first_error = self.errors().len();
+            for err in self.body_checker_mut().merge_fork(name, fork) {
+                self.add_error(err);
+            }
+            if let Err(e) = result {
+                self.add_error(*e);
+            }
+            let end = self.errors().len();
+            self.item_error_ranges.push((index, first_error..end));
         }
     }
 
@@ -489,7 +776,7 @@ impl TypeChecker {
                                 other => other,
                             };
 
-                            self.env.add_var(name.clone(), PolyType::mono(fn_ty));
+                            self.env.add_var(name.to_string(), PolyType::mono(fn_ty));
                         }
                     }
                 }
@@ -504,6 +791,14 @@ impl TypeChecker {
                 is_pub,
                 ..
             } => {
+                // 关联常量 / 关联类型：注册为值类型的方法绑定，不生成函数签名
+                if let (Some(host), Some(item_ty)) = (type_name, method_type) {
+                    if crate::frontend::core::parser::ast::is_assoc_item_type(item_ty) {
+                        self.collect_assoc_item(host, name, item_ty, type_annotation.as_ref());
+                        return;
+                    }
+                }
+
                 // 处理统一函数语法
                 // 方法绑定使用 method_type，普通函数使用 type_annotation
                 let (param_types, return_type) = if let Some(meth_ty) = method_type {
@@ -614,8 +909,25 @@ impl TypeChecker {
 
                     // 剥离类型级参数，使用 return_type 作为实际函数类型
                     let inner_fn_ty = Self::substitute_type_refs(return_type.clone(), &subst);
+                    let const_param_types: Vec<MonoType> =
+                        crate::frontend::core::parser::ast::const_generic_params(
+                            generic_params,
+                            type_annotation.as_ref(),
+                        )
+                        .iter()
+                        .filter_map(|p| match &p.kind {
+                            crate::frontend::core::parser::ast::GenericParamKind::Const {
+                                const_type,
+                            } => Some(MonoType::from((**const_type).clone())),
+                            _ => None,
+                        })
+                        .collect();
 
                     match inner_fn_ty {
+                        // 同时带 const 参数时只剥离类型参数：`fill(3)(x)`
+                        inner @ MonoType::Fn { .. } if !const_param_types.is_empty() => {
+                            (const_param_types, inner)
+                        }
                         MonoType::Fn {
                             params: inner_params,
                             return_type: inner_ret,
@@ -784,6 +1096,28 @@ impl TypeChecker {
         }
     }
 
+    /// 注册关联项
+    ///
+    /// - 关联常量 `Square.SIDES: Int = 4`：方法绑定表记录值类型 `Int`
+    /// - 关联类型 `Square.Item: Type = Int`：额外注册类型别名 `Square.Item -> Int`
+    fn collect_assoc_item(
+        &mut self,
+        host: &str,
+        item_name: &str,
+        item_ty: &crate::frontend::core::parser::ast::Type,
+        target: Option<&crate::frontend::core::parser::ast::Type>,
+    ) {
+        if crate::frontend::core::parser::ast::is_meta_type(item_ty) {
+            if let Some(target) = target {
+                let target_ty = self.resolve_type_annotation(&MonoType::from(target.clone()));
+                self.env
+                    .add_type(format!("{}.{}", host, item_name), PolyType::mono(target_ty));
+            }
+        }
+        let value_ty = self.resolve_type_annotation(&MonoType::from(item_ty.clone()));
+        self.env.add_method_binding(host, item_name, value_ty);
+    }
+
     /// 将模块注册为 Struct 类型（包含所有导出作为字段）
     fn register_module_as_struct(
         &mut self,
@@ -806,6 +1140,7 @@ impl TypeChecker {
             field_mutability: Vec::new(),
             field_has_default: Vec::new(),
             interfaces: vec![],
+            assoc_items: Vec::new(),
         });
         self.env
             .add_var(module_alias.to_string(), PolyType::mono(module_ty));
@@ -846,6 +1181,7 @@ impl TypeChecker {
                     field_mutability: Vec::new(),
                     field_has_default: Vec::new(),
                     interfaces: vec![],
+                    assoc_items: Vec::new(),
                 });
                 self.env.add_var(register_name, PolyType::mono(module_ty));
             }
@@ -915,6 +1251,25 @@ impl TypeChecker {
             }
         }
 
+        // 新类型：环境中记为名义类型 `TypeRef(name)`，底层类型只登记在求解器里
+        if let crate::frontend::core::parser::ast::Type::Newtype(inner) = definition {
+            let underlying = MonoType::from(inner.as_ref().clone());
+            self.env.solver().add_newtype(name.to_string(), underlying);
+            self.env.add_type(
+                name.to_string(),
+                PolyType::mono(MonoType::TypeRef(name.to_string())),
+            );
+            return;
+        }
+        // 透明别名：统一时展开为目标类型
+        if generic_params.is_empty()
+            && crate::frontend::core::parser::ast::is_type_alias_definition(name, definition)
+        {
+            self.env
+                .solver()
+                .add_type_alias(name.to_string(), MonoType::from(definition.clone()));
+        }
+
         let poly = PolyType::mono(MonoType::from(definition.clone()));
         // Inject the type name into StructType if it's missing (plain Type::Struct has no name)
         let poly = PolyType::mono(match &poly.body {
@@ -926,6 +1281,7 @@ impl TypeChecker {
                     field_mutability: s.field_mutability.clone(),
                     field_has_default: s.field_has_default.clone(),
                     interfaces: s.interfaces.clone(),
+                    assoc_items: s.assoc_items.clone(),
                 })
             }
             _ => poly.body.clone(),
@@ -1206,7 +1562,8 @@ impl TypeChecker {
     /// 解析类型标注：如果是编译期谓词调用，正格化为 Refined
     ///
     /// Generic("Positive", [arg]) -> 尝试 PredicateResolver::try_resolve
-    /// 如果不是已知的编译期谓词，检查是否是证明函数
+    /// 如果不是已知的编译期谓词，检查是否是证明函数；
+    /// 其余标注中的用户泛型实例（如 `Box(Int)`）展开为实例化后的结构体
     fn resolve_type_annotation(
         &self,
         ty: &MonoType,
@@ -1234,9 +1591,9 @@ impl TypeChecker {
                         constraint,
                     };
                 }
-                ty.clone()
+                TypeEnvironment::expand_generic_instances(ty, &self.env.generic_type_defs)
             }
-            _ => ty.clone(),
+            _ => TypeEnvironment::expand_generic_instances(ty, &self.env.generic_type_defs),
         }
     }
 
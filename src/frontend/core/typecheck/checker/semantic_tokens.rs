@@ -262,6 +262,7 @@ impl TypeChecker {
                 self.collect_type_tokens(file_path, base_type);
             }
             Type::Ptr(inner) => self.collect_type_tokens(file_path, inner),
+            Type::Newtype(inner) => self.collect_type_tokens(file_path, inner),
             Type::MetaType { name_span, args } => {
                 self.semantic_db.add_token(
                     file_path,
@@ -351,9 +352,9 @@ impl TypeChecker {
                 );
             }
             Expr::Var(name, span) => {
-                let token_type = if imported_module_roots.contains(name) {
+                let token_type = if imported_module_roots.contains(name.as_str()) {
                     SemanticTokenType::Namespace
-                } else if constructor_names.contains(name) {
+                } else if constructor_names.contains(name.as_str()) {
                     SemanticTokenType::EnumMember
                 } else if self.is_struct_binding(name) {
                     SemanticTokenType::Type
@@ -372,7 +373,7 @@ impl TypeChecker {
                 self.semantic_db.add_token(
                     file_path,
                     semantic_db::SemanticToken {
-                        name: name.clone(),
+                        name: name.to_string(),
                         token_type,
                         modifiers: vec![],
                         span: *span,
@@ -397,7 +398,7 @@ impl TypeChecker {
         imported_module_roots: &HashSet<String>,
     ) -> bool {
         match expr {
-            Expr::Var(name, _) => imported_module_roots.contains(name),
+            Expr::Var(name, _) => imported_module_roots.contains(name.as_str()),
             Expr::FieldAccess { expr: inner, .. } => {
                 Self::is_module_path_expr(inner, imported_module_roots)
             }
@@ -1034,9 +1035,9 @@ impl TypeChecker {
         match expr {
             Expr::Var(name, span) => {
                 // 判断是函数引用还是变量引用
-                let token_type = if imported_module_roots.contains(name) {
+                let token_type = if imported_module_roots.contains(name.as_str()) {
                     semantic_db::SemanticTokenType::Namespace
-                } else if constructor_names.contains(name) {
+                } else if constructor_names.contains(name.as_str()) {
                     semantic_db::SemanticTokenType::EnumMember
                 } else if let Some(poly) = self.env.get_var(name) {
                     if matches!(poly.body, MonoType::Fn { .. }) {
@@ -1050,7 +1051,7 @@ impl TypeChecker {
                 self.semantic_db.add_token(
                     file_path,
                     semantic_db::SemanticToken {
-                        name: name.clone(),
+                        name: name.to_string(),
                         token_type,
                         modifiers: vec![],
                         span: *span,
@@ -1109,7 +1110,7 @@ impl TypeChecker {
             Expr::Cast {
                 expr: inner,
                 target_type,
-                span: _,
+                ..
             } => {
                 self.collect_expr_tokens(
                     file_path,
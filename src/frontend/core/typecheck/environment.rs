@@ -246,6 +246,50 @@ impl TypeEnvironment {
         Self::resolve_type_refs(&result)
     }
 
+    /// 展开类型中嵌套的用户泛型实例（静态方法）
+    ///
+    /// `List(Box(Int))` → `List(Box { value: Int })`，`Box(Box(Int))` 先展开实参再实例化。
+    /// 实例化结果的字段不再展开（字段中的泛型实例在访问时按需展开），
+    /// 因此自引用的泛型类型不会无限递归。
+    pub fn expand_generic_instances(
+        ty: &MonoType,
+        defs: &HashMap<String, GenericTypeDef>,
+    ) -> MonoType {
+        let expand = |t: &MonoType| Self::expand_generic_instances(t, defs);
+        match ty {
+            MonoType::Generic { name, args } => {
+                let args: Vec<MonoType> = args.iter().map(expand).collect();
+                defs.get(name)
+                    .and_then(|def| Self::instantiate_generic_type(def, &args))
+                    .unwrap_or_else(|| MonoType::Generic {
+                        name: name.clone(),
+                        args,
+                    })
+            }
+            MonoType::List(elem) => MonoType::List(Box::new(expand(elem))),
+            MonoType::Set(elem) => MonoType::Set(Box::new(expand(elem))),
+            MonoType::Option(elem) => MonoType::Option(Box::new(expand(elem))),
+            MonoType::Arc(elem) => MonoType::Arc(Box::new(expand(elem))),
+            MonoType::Result(ok, err) => {
+                MonoType::Result(Box::new(expand(ok)), Box::new(expand(err)))
+            }
+            MonoType::Dict(k, v) => MonoType::Dict(Box::new(expand(k)), Box::new(expand(v))),
+            MonoType::Tuple(elems) => MonoType::Tuple(elems.iter().map(expand).collect()),
+            MonoType::Fn {
+                params,
+                return_type,
+            } => MonoType::Fn {
+                params: params.iter().map(expand).collect(),
+                return_type: Box::new(expand(return_type)),
+            },
+            MonoType::Ref { mutable, inner } => MonoType::Ref {
+                mutable: *mutable,
+                inner: Box::new(expand(inner)),
+            },
+            _ => ty.clone(),
+        }
+    }
+
     /// 替换类型参数：将 TypeRef(param_name) 替换为具体的类型实参
     fn replace_type_params(
         ty: &MonoType,
@@ -280,6 +324,7 @@ impl TypeEnvironment {
                     field_mutability: s.field_mutability.clone(),
                     field_has_default: s.field_has_default.clone(),
                     interfaces: s.interfaces.clone(),
+                    assoc_items: s.assoc_items.clone(),
                 })
             }
             MonoType::List(elem) => {
@@ -359,6 +404,8 @@ impl TypeEnvironment {
             MonoType::TypeRef(name) => match name.as_str() {
                 "Int" | "int" | "Int64" | "int64" | "i64" => MonoType::Int(64),
                 "Int32" | "int32" | "i32" => MonoType::Int(32),
+                "Int16" | "int16" | "i16" => MonoType::Int(16),
+                "Int8" | "int8" | "i8" => MonoType::Int(8),
                 "Float" | "float" | "Float64" | "float64" | "f64" => MonoType::Float(64),
                 "Float32" | "float32" | "f32" => MonoType::Float(32),
                 "Bool" | "bool" => MonoType::Bool,
@@ -381,6 +428,7 @@ impl TypeEnvironment {
                     field_mutability: s.field_mutability.clone(),
                     field_has_default: s.field_has_default.clone(),
                     interfaces: s.interfaces.clone(),
+                    assoc_items: s.assoc_items.clone(),
                 })
             }
             MonoType::List(elem) => MonoType::List(Box::new(Self::resolve_type_refs(elem))),
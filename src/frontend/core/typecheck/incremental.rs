@@ -0,0 +1,485 @@
+//! 增量类型检查
+//!
+//! 为每个顶层项计算两个指纹：
+//! - 签名哈希：项对外可见的部分。签名完整标注的函数取函数体之前的源码
+//!   （名字、泛型参数、参数与类型标注）；其余项的类型依赖全文，取全文哈希
+//! - 全文哈希：项的完整源码（从项开始到下一项开始）
+//!
+//! 再次检查时若所有签名哈希都未变，说明只有函数体改动：只重新检查改动过的函数，
+//! 未改动的函数直接复用上次的错误（随位置平移）。签名改动、增删顶层项，
+//! 或上次存在无法归属到某一项的错误时，退回全量检查。
+//!
+//! 检查状态可以持久化为构建产物（[`IncrementalModule::save`] /
+//! [`IncrementalModule::load`]）：逐项保存指纹、名字、推断出的类型与诊断，
+//! 下一次编译（新进程）加载后同样只重新检查改动过的函数。
+//! 产物格式版本或编译器版本不一致时视为无缓存。
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::checker::TypeChecker;
+use crate::frontend::core::desugar::desugar_module;
+use crate::frontend::core::lexer::{tokenize, Token, TokenKind};
+use crate::frontend::core::parser::ast::{
+    classify_binding_semantic_kind, BinOp, BindingSemanticKind, Expr, Module, StmtKind, Type,
+};
+use crate::frontend::core::parser::parse;
+use crate::frontend::pipeline::compilation_cache::content_hash;
+use crate::util::diagnostic::Diagnostic;
+use crate::util::span::{Position, Span};
+
+/// 一次增量检查的结果
+#[derive(Debug, Clone)]
+pub struct IncrementalOutcome {
+    /// 所有诊断信息（词法、语法、类型检查，含警告）
+    pub diagnostics: Vec<Diagnostic>,
+    /// 模块 AST（词法/语法失败时为 `None`）
+    pub module: Option<Module>,
+    /// 重新检查的顶层项数
+    pub rechecked: usize,
+    /// 复用上次诊断的顶层项数
+    pub reused: usize,
+    /// 公开项签名的哈希：不变时依赖本模块的模块无需重新检查
+    pub export_hash: u64,
+}
+
+/// 单个源文件的增量类型检查状态
+#[derive(Debug, Default)]
+pub struct IncrementalModule {
+    previous: Option<Snapshot>,
+}
+
+/// 检查产物的格式版本，结构变化时递增
+pub const ARTIFACT_VERSION: u32 = 1;
+
+/// 持久化的检查产物
+#[derive(Debug, Serialize, Deserialize)]
+struct Artifact {
+    version: u32,
+    /// 生成产物的编译器版本
+    compiler: String,
+    snapshot: Snapshot,
+}
+
+/// 上一次检查留下的逐项指纹与错误
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Snapshot {
+    items: Vec<ItemState>,
+    /// 存在无法归属到顶层项的错误
+    module_errors: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ItemState {
+    print: Fingerprint,
+    /// 项绑定的名字
+    name: Option<String>,
+    /// 推断出的类型（签名）
+    ty: Option<String>,
+    errors: Vec<Diagnostic>,
+}
+
+/// 顶层项指纹
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Fingerprint {
+    start: Position,
+    end_offset: usize,
+    text_hash: u64,
+    signature_hash: u64,
+    /// 签名完整标注的函数：函数体可以独立检查
+    annotated: bool,
+    /// 本项引用到的、类型依赖其他项函数体的名字
+    uses_inferred: bool,
+    /// 公开项的名字
+    export: Option<String>,
+}
+
+impl IncrementalModule {
+    /// 创建空状态（首次检查为全量检查）
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 丢弃缓存，下次检查为全量检查（如依赖模块的导出变化时）
+    pub fn invalidate(&mut self) {
+        self.previous = None;
+    }
+
+    /// 从产物文件恢复检查状态；文件缺失、损坏或版本不符时返回空状态
+    pub fn load(path: &Path) -> Self {
+        let previous = std::fs::read_to_string(path)
+            .ok()
+            .and_then(|text| serde_json::from_str::<Artifact>(&text).ok())
+            .filter(|artifact| {
+                artifact.version == ARTIFACT_VERSION
+                    && artifact.compiler == env!("CARGO_PKG_VERSION")
+            })
+            .map(|artifact| artifact.snapshot);
+        Self { previous }
+    }
+
+    /// 把检查状态写入产物文件；没有可复用状态时删除旧产物
+    pub fn save(
+        &self,
+        path: &Path,
+    ) -> std::io::Result<()> {
+        let Some(snapshot) = &self.previous else {
+            return match std::fs::remove_file(path) {
+                Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(err),
+                _ => Ok(()),
+            };
+        };
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let artifact = Artifact {
+            version: ARTIFACT_VERSION,
+            compiler: env!("CARGO_PKG_VERSION").to_string(),
+            snapshot: snapshot.clone(),
+        };
+        let text = serde_json::to_string(&artifact).map_err(std::io::Error::other)?;
+        // 先写临时文件再改名，避免并发读到半个文件
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, text)?;
+        std::fs::rename(&tmp, path)
+    }
+
+    /// 上次检查得到的顶层项名字与推断类型（按源码顺序）
+    pub fn signatures(&self) -> Vec<(String, String)> {
+        self.previous
+            .iter()
+            .flat_map(|snapshot| &snapshot.items)
+            .filter_map(|item| Some((item.name.clone()?, item.ty.clone()?)))
+            .collect()
+    }
+
+    /// 检查源码：词法分析 → 语法分析 → 类型检查（尽量复用上次结果）
+    pub fn check(
+        &mut self,
+        source: &str,
+    ) -> IncrementalOutcome {
+        let tokens = match tokenize(source) {
+            Ok(tokens) => tokens,
+            Err(err) => {
+                return Self::failed(
+                    source,
+                    vec![Diagnostic::error(
+                        "E0001".to_string(),
+                        err.to_string(),
+                        String::new(),
+                        None,
+                    )],
+                );
+            }
+        };
+        let parse_result = parse(&tokens);
+        if parse_result.has_errors {
+            return Self::failed(source, parse_result.errors);
+        }
+        let mut module = parse_result.module;
+        let desugar_errors = desugar_module(&mut module);
+        if !desugar_errors.is_empty() {
+            return Self::failed(source, desugar_errors);
+        }
+
+        let prints = fingerprint(&module, source, &tokens);
+        let reuse = match (&self.previous, &prints) {
+            (Some(previous), Some(prints)) => previous.reusable(prints),
+            _ => HashSet::new(),
+        };
+        let reused_errors = reuse.iter().any(|&index| {
+            self.previous.as_ref().is_some_and(|previous| {
+                previous.items[index]
+                    .errors
+                    .iter()
+                    .any(|d| d.severity.is_error())
+            })
+        });
+
+        let mut checker = TypeChecker::new("main");
+        checker.reuse_items(reuse.clone(), reused_errors);
+        let result = checker.check_module(&module);
+        let checked_by_item = checker.item_error_ranges().to_vec();
+
+        let Some(prints) = prints else {
+            // 无法可靠划分顶层项：仅做全量检查，不保留缓存
+            self.previous = None;
+            let mut diagnostics = result.diagnostics;
+            diagnostics.extend(result.warnings);
+            return IncrementalOutcome {
+                diagnostics,
+                rechecked: module.items.len(),
+                reused: 0,
+                export_hash: content_hash(source),
+                module: Some(module),
+            };
+        };
+
+        // 新错误归属到顶层项：检查该项时产生的错误归该项，其余按位置归属
+        let mut item_errors: Vec<Vec<Diagnostic>> = vec![Vec::new(); prints.len()];
+        let mut module_errors = Vec::new();
+        for (position, diagnostic) in result.diagnostics.into_iter().enumerate() {
+            let owner = checked_by_item
+                .iter()
+                .find(|(_, range)| range.contains(&position))
+                .map(|(index, _)| *index)
+                .or_else(|| owning_item(&prints, diagnostic.span.as_ref()));
+            match owner {
+                Some(index) => item_errors[index].push(diagnostic),
+                None => module_errors.push(diagnostic),
+            }
+        }
+        if let Some(previous) = &self.previous {
+            for &index in &reuse {
+                let old = &previous.items[index];
+                item_errors[index] = old
+                    .errors
+                    .iter()
+                    .map(|d| rebase(d, &old.print, &prints[index]))
+                    .collect();
+            }
+        }
+
+        let mut diagnostics: Vec<Diagnostic> = item_errors.iter().flatten().cloned().collect();
+        diagnostics.extend(module_errors.iter().cloned());
+        diagnostics.extend(result.warnings);
+
+        let export_hash = export_hash(&prints);
+        self.previous = Some(Snapshot {
+            module_errors: !module_errors.is_empty(),
+            items: prints
+                .into_iter()
+                .zip(item_errors)
+                .zip(&module.items)
+                .map(|((print, errors), stmt)| {
+                    let name = item_name(&stmt.kind).map(str::to_string);
+                    let ty = name
+                        .as_ref()
+                        .and_then(|name| result.bindings.get(name))
+                        .map(|poly| poly.type_name());
+                    ItemState {
+                        print,
+                        name,
+                        ty,
+                        errors,
+                    }
+                })
+                .collect(),
+        });
+
+        IncrementalOutcome {
+            diagnostics,
+            rechecked: module.items.len() - reuse.len(),
+            reused: reuse.len(),
+            export_hash,
+            module: Some(module),
+        }
+    }
+
+    /// 词法/语法失败：保留上次的缓存，等源码恢复后继续复用
+    fn failed(
+        source: &str,
+        diagnostics: Vec<Diagnostic>,
+    ) -> IncrementalOutcome {
+        IncrementalOutcome {
+            diagnostics,
+            module: None,
+            rechecked: 0,
+            reused: 0,
+            export_hash: content_hash(source),
+        }
+    }
+}
+
+impl Snapshot {
+    /// 可以跳过检查的顶层项：签名全部未变时，全文未变、签名完整标注、
+    /// 且不引用推断类型名字的函数
+    fn reusable(
+        &self,
+        prints: &[Fingerprint],
+    ) -> HashSet<usize> {
+        let signatures_unchanged = !self.module_errors
+            && self.items.len() == prints.len()
+            && self
+                .items
+                .iter()
+                .zip(prints)
+                .all(|(old, new)| old.print.signature_hash == new.signature_hash);
+        if !signatures_unchanged {
+            return HashSet::new();
+        }
+
+        self.items
+            .iter()
+            .zip(prints)
+            .enumerate()
+            .filter(|(_, (old, new))| {
+                new.annotated
+                    && !new.uses_inferred
+                    && old.print.text_hash == new.text_hash
+                    && old.print.start.column == new.start.column
+            })
+            .map(|(index, _)| index)
+            .collect()
+    }
+}
+
+/// 计算所有顶层项的指纹；项的位置无法可靠确定时返回 `None`
+fn fingerprint(
+    module: &Module,
+    source: &str,
+    tokens: &[Token],
+) -> Option<Vec<Fingerprint>> {
+    let starts: Vec<Position> = module.items.iter().map(|stmt| stmt.span.start).collect();
+    if starts.iter().any(|start| start.line == 0)
+        || starts.windows(2).any(|w| w[0].offset >= w[1].offset)
+    {
+        return None;
+    }
+
+    // 类型由函数体推断的顶层名字：引用它们的函数体会约束其类型，不能跳过
+    let inferred: HashSet<&str> = module
+        .items
+        .iter()
+        .filter(|stmt| annotated_body_start(&stmt.kind).is_none())
+        .filter_map(|stmt| item_name(&stmt.kind))
+        .collect();
+
+    let mut prints = Vec::with_capacity(module.items.len());
+    for (index, stmt) in module.items.iter().enumerate() {
+        let start = starts[index];
+        let end_offset = starts
+            .get(index + 1)
+            .map_or(source.len(), |next| next.offset);
+        let text = source.get(start.offset..end_offset)?;
+        let text_hash = content_hash(text);
+
+        let body_start = annotated_body_start(&stmt.kind)
+            .filter(|&offset| offset > start.offset && offset <= end_offset);
+        let signature_hash = match body_start {
+            Some(offset) => content_hash(source.get(start.offset..offset)?),
+            None => text_hash,
+        };
+        let uses_inferred = tokens
+            .iter()
+            .filter(|t| t.span.start.offset >= start.offset && t.span.start.offset < end_offset)
+            .any(|t| matches!(&t.kind, TokenKind::Identifier(name) if inferred.contains(name.as_str())));
+        let export = match &stmt.kind {
+            StmtKind::Binding {
+                name, is_pub: true, ..
+            } => Some(name.clone()),
+            _ => None,
+        };
+
+        prints.push(Fingerprint {
+            start,
+            end_offset,
+            text_hash,
+            signature_hash,
+            annotated: body_start.is_some(),
+            uses_inferred,
+            export,
+        });
+    }
+    Some(prints)
+}
+
+/// 顶层项绑定的名字
+fn item_name(kind: &StmtKind) -> Option<&str> {
+    match kind {
+        StmtKind::Binding { name, .. } => Some(name.as_str()),
+        StmtKind::Expr(expr) => match expr.as_ref() {
+            Expr::FnDef { name, .. } => Some(name.as_str()),
+            Expr::BinOp {
+                op: BinOp::Assign,
+                left,
+                ..
+            } => match left.as_ref() {
+                Expr::Var(name, _) => Some(name.as_str()),
+                _ => None,
+            },
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// 签名完整标注的函数：返回函数体起始偏移
+fn annotated_body_start(kind: &StmtKind) -> Option<usize> {
+    let StmtKind::Binding {
+        type_name: None,
+        type_annotation: Some(annotation @ Type::Fn { .. }),
+        params,
+        body,
+        ..
+    } = kind
+    else {
+        return None;
+    };
+    let kind = classify_binding_semantic_kind(None, Some(annotation), params, body);
+    if kind != BindingSemanticKind::Function {
+        return None;
+    }
+    let first = body.first()?;
+    (first.span.start.line > 0).then_some(first.span.start.offset)
+}
+
+/// 公开项签名的哈希
+fn export_hash(prints: &[Fingerprint]) -> u64 {
+    let mut exports = String::new();
+    for print in prints {
+        if let Some(name) = &print.export {
+            exports.push_str(&format!("{}:{:x};", name, print.signature_hash));
+        }
+    }
+    content_hash(&exports)
+}
+
+/// 诊断位置所在的顶层项
+fn owning_item(
+    prints: &[Fingerprint],
+    span: Option<&Span>,
+) -> Option<usize> {
+    let span = span.filter(|span| !span.is_dummy())?;
+    let offset = span.start.offset;
+    let index = prints.partition_point(|print| print.start.offset <= offset);
+    index
+        .checked_sub(1)
+        .filter(|&index| offset < prints[index].end_offset)
+}
+
+/// 把旧项中的诊断平移到新位置（项全文未变，列号不变）
+fn rebase(
+    diagnostic: &Diagnostic,
+    old: &Fingerprint,
+    new: &Fingerprint,
+) -> Diagnostic {
+    let shift = |span: Span| -> Span {
+        if span.is_dummy()
+            || span.start.offset < old.start.offset
+            || span.start.offset >= old.end_offset
+        {
+            return span;
+        }
+        let move_pos = |pos: Position| Position {
+            line: pos.line - old.start.line + new.start.line,
+            column: pos.column,
+            offset: pos.offset - old.start.offset + new.start.offset,
+        };
+        Span::new(move_pos(span.start), move_pos(span.end))
+    };
+
+    let mut rebased = diagnostic.clone();
+    rebased.span = diagnostic.span.map(shift);
+    for related in &mut rebased.related {
+        **related = rebase(related, old, new);
+    }
+    for fix in &mut rebased.fixes {
+        for edit in &mut fix.edits {
+            edit.span = shift(edit.span);
+        }
+    }
+    rebased
+}
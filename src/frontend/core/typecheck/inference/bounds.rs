@@ -228,6 +228,13 @@ impl BoundsChecker {
             }
         }
 
+        // 关联项必须由 `Type.Item: ... = ...` 绑定提供
+        for (item_name, _) in constraint.constraint_assoc_items() {
+            if !method_bindings.iter().any(|(name, _)| name == &item_name) {
+                missing_fields.push(item_name);
+            }
+        }
+
         if !missing_fields.is_empty() || !mismatched_fields.is_empty() {
             let constraint_name = constraint.type_name();
             let type_name = ty.type_name();
@@ -0,0 +1,114 @@
+//! 未知名字的拼写建议
+//!
+//! 在局部变量、模块级定义、`use` 导入以及尚未导入的 std 函数中
+//! 寻找最接近的名字，作为 E1001 的结构化修复建议（fix-it）附加到诊断上。
+
+use std::collections::HashMap;
+
+use super::scope::ScopeManager;
+use crate::frontend::core::types::MonoType;
+use crate::util::diagnostic::{
+    Candidate, Diagnostic, ErrorCodeDefinition, Fix, FixEdit, SuggestionEngine, SymbolSource,
+};
+use crate::util::i18n::{error_lang, t, MSG};
+use crate::util::span::{Position, Span};
+
+/// 构建未知变量诊断，找到相近名字时附带 "did you mean" 修复建议
+///
+/// `arity` 为调用处的实参个数（`name(a, b)`），参数个数匹配的函数优先推荐。
+pub(crate) fn unknown_variable(
+    name: &str,
+    span: Span,
+    scope: &ScopeManager,
+    native_signatures: &HashMap<String, MonoType>,
+    arity: Option<usize>,
+) -> Diagnostic {
+    let builder = ErrorCodeDefinition::unknown_variable(name).at(span);
+    match suggest(name, span, scope, native_signatures, arity) {
+        Some(fix) => builder.fix(fix).build(),
+        None => builder.build(),
+    }
+}
+
+fn suggest(
+    name: &str,
+    span: Span,
+    scope: &ScopeManager,
+    native_signatures: &HashMap<String, MonoType>,
+    arity: Option<usize>,
+) -> Option<Fix> {
+    let mut engine = SuggestionEngine::new();
+
+    for (var, info, level) in scope.visible_vars() {
+        // 限定名（`std.io.print`、`Native.c`）不是可直接书写的标识符
+        if var.contains('.') {
+            continue;
+        }
+        let source = if info.imported {
+            SymbolSource::Import
+        } else if level == 0 {
+            SymbolSource::Module
+        } else {
+            SymbolSource::Local
+        };
+        engine.add_candidate(
+            Candidate::new(var, source).compatible(accepts_arity(&info.poly.body, arity)),
+        );
+    }
+
+    for (path, ty) in native_signatures {
+        let Some((module, short)) = path.rsplit_once('.') else {
+            continue;
+        };
+        if !module.starts_with("std.") || scope.var_in_any_scope(short) {
+            continue;
+        }
+        engine.add_candidate(
+            Candidate::new(short, SymbolSource::Std)
+                .from_module(module)
+                .compatible(accepts_arity(ty, arity)),
+        );
+    }
+
+    let best = engine.best_candidate(name)?;
+    let quoted = format!("`{}`", best.name);
+    let rename = FixEdit {
+        span,
+        replacement: best.name.clone(),
+    };
+
+    let fix = match &best.module {
+        // 未导入的 std 函数：改名的同时在文件开头插入 `use`
+        Some(module) => {
+            let start = Position::with_offset(1, 1, 0);
+            let import = FixEdit {
+                span: Span::new(start, start),
+                replacement: format!("use {}.{{{}}}\n", module, best.name),
+            };
+            Fix {
+                message: t(
+                    MSG::HelpDidYouMeanFrom,
+                    error_lang(),
+                    Some(&[&quoted, &format!("`{}`", module)]),
+                ),
+                edits: vec![import, rename],
+            }
+        }
+        None => Fix {
+            message: t(MSG::HelpDidYouMean, error_lang(), Some(&[&quoted])),
+            edits: vec![rename],
+        },
+    };
+    Some(fix)
+}
+
+/// 候选类型能否以 `arity` 个实参调用（非调用位置一律兼容）
+fn accepts_arity(
+    ty: &MonoType,
+    arity: Option<usize>,
+) -> bool {
+    match arity {
+        None => true,
+        Some(n) => matches!(ty, MonoType::Fn { params, .. } if params.len() == n),
+    }
+}
@@ -0,0 +1,783 @@
+#![allow(clippy::result_large_err)]
+
+//! 模式穷尽性与可达性检查
+//!
+//! 基于 Maranget 的“有用性”（usefulness）算法：
+//! - 某个分支相对于它之前的分支不再有用 → 该分支不可达
+//! - 通配符相对于全部分支仍然有用 → match 不穷尽，并构造一个未被覆盖的示例模式
+//!
+//! 被匹配值的构造器集合优先取自被匹配表达式的类型；类型尚未确定时，
+//! 从分支模式本身推断（例如出现 `Some(..)` 即视为 Option）。
+
+use std::collections::HashMap;
+
+use crate::frontend::core::lexer::tokens::Literal;
+use crate::frontend::core::parser::ast::{constructor_args, MatchArm, Pattern};
+use crate::frontend::core::types::MonoType;
+use crate::util::diagnostic::{ErrorCodeDefinition, Result};
+use crate::util::span::Span;
+
+/// 被匹配值的形状：决定某一列上有哪些构造器
+#[derive(Debug, Clone, PartialEq)]
+enum Shape {
+    Bool,
+    Option(Box<Shape>),
+    Result(Box<Shape>, Box<Shape>),
+    /// 枚举：变体名及其负载（若有）
+    Enum(Vec<(String, Option<Shape>)>),
+    Tuple(Vec<Shape>),
+    Struct {
+        name: String,
+        fields: Vec<(String, Shape)>,
+    },
+    /// 无限域（整数、字符串等）或未知类型，永远无法被构造器穷尽
+    Open,
+}
+
+/// 模式构造器
+#[derive(Debug, Clone, PartialEq)]
+enum Ctor {
+    /// `true` / `false`、`Some` / `None`、`Ok` / `Err` 以及枚举变体
+    Variant(String),
+    /// 无限域上的字面量，或无法与类型对应的模式（按不透明常量处理）
+    Literal(String),
+    Tuple,
+    Struct {
+        name: String,
+        fields: Vec<String>,
+    },
+}
+
+/// 归一化后的模式
+#[derive(Debug, Clone)]
+enum Pat {
+    Wild,
+    Ctor(Ctor, Vec<Pat>),
+    Or(Vec<Pat>),
+}
+
+impl std::fmt::Display for Pat {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        match self {
+            Pat::Wild => write!(f, "_"),
+            Pat::Or(alts) => write!(f, "{}", join(alts, " | ")),
+            Pat::Ctor(Ctor::Variant(name), args) => match args.as_slice() {
+                [] => write!(f, "{}", name),
+                [Pat::Ctor(Ctor::Tuple, elems)] => write!(f, "{}({})", name, join(elems, ", ")),
+                _ => write!(f, "{}({})", name, join(args, ", ")),
+            },
+            Pat::Ctor(Ctor::Literal(text), _) => write!(f, "{}", text),
+            Pat::Ctor(Ctor::Tuple, elems) => write!(f, "({})", join(elems, ", ")),
+            Pat::Ctor(Ctor::Struct { name, fields }, args) => {
+                let body = fields
+                    .iter()
+                    .zip(args)
+                    .map(|(field, arg)| format!("{}: {}", field, arg))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                if name.is_empty() {
+                    write!(f, "{{ {} }}", body)
+                } else {
+                    write!(f, "{} {{ {} }}", name, body)
+                }
+            }
+        }
+    }
+}
+
+fn join(
+    pats: &[Pat],
+    sep: &str,
+) -> String {
+    pats.iter()
+        .map(|p| p.to_string())
+        .collect::<Vec<_>>()
+        .join(sep)
+}
+
+/// match 检查结果
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MatchCheck {
+    /// 不可达分支的下标
+    pub unreachable: Vec<usize>,
+    /// 未被覆盖的示例模式（match 穷尽时为 None）
+    pub missing: Option<String>,
+}
+
+/// match 穷尽性与可达性检查器
+pub struct ExhaustivenessChecker<'a> {
+    /// 类型定义表：用于解析 TypeRef 以及 `Color.red` 这类限定变体名
+    type_defs: &'a HashMap<String, MonoType>,
+}
+
+impl<'a> ExhaustivenessChecker<'a> {
+    /// 创建检查器
+    pub fn new(type_defs: &'a HashMap<String, MonoType>) -> Self {
+        Self { type_defs }
+    }
+
+    /// 检查 match 分支，将第一个问题报告为诊断
+    ///
+    /// 不可达分支报告在分支位置（E1031），不穷尽报告在 match 位置（E1030）。
+    pub fn check_arms(
+        &self,
+        scrutinee: &MonoType,
+        arms: &[MatchArm],
+        span: Span,
+    ) -> Result<()> {
+        let patterns: Vec<&Pattern> = arms.iter().map(|arm| &arm.pattern).collect();
+        let result = self.check(scrutinee, &patterns);
+
+        if let Some(&idx) = result.unreachable.first() {
+            return Err(
+                ErrorCodeDefinition::unreachable_pattern(&render(&arms[idx].pattern))
+                    .at(arms[idx].span)
+                    .build(),
+            );
+        }
+        if let Some(missing) = result.missing {
+            return Err(ErrorCodeDefinition::pattern_non_exhaustive(&missing)
+                .at(span)
+                .build());
+        }
+        Ok(())
+    }
+
+    /// 对一组分支模式做有用性分析
+    pub fn check(
+        &self,
+        scrutinee: &MonoType,
+        patterns: &[&Pattern],
+    ) -> MatchCheck {
+        let shape = self.shape(Some(scrutinee), patterns);
+        let shapes = [shape];
+
+        let mut rows: Vec<Vec<Pat>> = Vec::new();
+        let mut result = MatchCheck::default();
+        for (idx, pattern) in patterns.iter().enumerate() {
+            let pat = self.lower(pattern, &shapes[0]);
+            if useful(&rows, std::slice::from_ref(&pat), &shapes).is_none() {
+                result.unreachable.push(idx);
+            }
+            // 带守卫的分支可能不匹配，不计入覆盖
+            if !matches!(pattern, Pattern::Guard { .. }) {
+                rows.push(vec![pat]);
+            }
+        }
+
+        result.missing = useful(&rows, &[Pat::Wild], &shapes).map(|witness| witness[0].to_string());
+        result
+    }
+
+    // ------------------------------------------------------------------
+    // 形状推断
+    // ------------------------------------------------------------------
+
+    /// 解析 TypeRef 到其定义
+    fn resolve<'t>(
+        &'t self,
+        ty: Option<&'t MonoType>,
+    ) -> Option<&'t MonoType> {
+        let mut ty = ty?;
+        // 防止别名互相引用导致死循环
+        for _ in 0..8 {
+            match ty {
+                MonoType::TypeRef(name) => ty = self.type_defs.get(name)?,
+                _ => return Some(ty),
+            }
+        }
+        None
+    }
+
+    /// 限定变体名 `Color.red` 所属的枚举类型
+    fn enum_of_qualified(
+        &self,
+        name: &str,
+    ) -> Option<&MonoType> {
+        let (type_name, _) = name.rsplit_once('.')?;
+        self.resolve(self.type_defs.get(type_name))
+            .filter(|ty| matches!(ty, MonoType::Enum(_)))
+    }
+
+    /// 根据类型与该列上的模式推断形状
+    ///
+    /// 只沿着模式实际展开的结构向下推断，因此递归类型不会无限展开。
+    fn shape(
+        &self,
+        ty: Option<&MonoType>,
+        patterns: &[&Pattern],
+    ) -> Shape {
+        let ty = self.resolve(ty);
+        let structured: Vec<&Pattern> = flatten(patterns)
+            .into_iter()
+            .filter(|p| match p {
+                Pattern::Wildcard => false,
+                Pattern::Identifier(name) => is_ctor_name(name, ty),
+                _ => true,
+            })
+            .collect();
+        if structured.is_empty() {
+            return Shape::Open;
+        }
+
+        let has_variant = |names: &[&str]| {
+            structured.iter().any(|p| match p {
+                Pattern::Union { variant, .. } => names.contains(&variant.as_str()),
+                Pattern::Identifier(name) => names.contains(&name.as_str()),
+                _ => false,
+            })
+        };
+
+        match ty {
+            Some(MonoType::Bool) => Shape::Bool,
+            Some(MonoType::Option(inner)) => Shape::Option(Box::new(self.payload_shape(
+                Some(inner),
+                &structured,
+                "Some",
+            ))),
+            Some(MonoType::Result(ok, err)) => Shape::Result(
+                Box::new(self.payload_shape(Some(ok), &structured, "Ok")),
+                Box::new(self.payload_shape(Some(err), &structured, "Err")),
+            ),
+            Some(MonoType::Enum(e)) => self.enum_shape(&e.variants, &structured),
+            Some(MonoType::Tuple(elems)) => self.tuple_shape(Some(elems), &structured),
+            Some(MonoType::Struct(s)) => self.struct_shape(
+                &s.name,
+                s.fields
+                    .iter()
+                    .map(|(name, ty)| (name.clone(), Some(ty)))
+                    .collect(),
+                &structured,
+            ),
+            Some(MonoType::Int(_) | MonoType::Float(_) | MonoType::Char | MonoType::String) => {
+                Shape::Open
+            }
+            // 类型未知：从模式推断
+            _ => {
+                if structured
+                    .iter()
+                    .any(|p| matches!(p, Pattern::Literal(Literal::Bool(_))))
+                {
+                    Shape::Bool
+                } else if has_variant(&["Some", "None"]) {
+                    Shape::Option(Box::new(self.payload_shape(None, &structured, "Some")))
+                } else if has_variant(&["Ok", "Err"]) {
+                    Shape::Result(
+                        Box::new(self.payload_shape(None, &structured, "Ok")),
+                        Box::new(self.payload_shape(None, &structured, "Err")),
+                    )
+                } else if let Some(MonoType::Enum(e)) = structured.iter().find_map(|p| match p {
+                    Pattern::Identifier(name) => self.enum_of_qualified(name),
+                    _ => None,
+                }) {
+                    self.enum_shape(&e.variants, &structured)
+                } else if structured.iter().any(|p| matches!(p, Pattern::Tuple(_))) {
+                    self.tuple_shape(None, &structured)
+                } else if let Some(Pattern::Struct { name, .. }) = structured
+                    .iter()
+                    .find(|p| matches!(p, Pattern::Struct { .. }))
+                {
+                    let mut fields: Vec<(String, Option<&MonoType>)> = Vec::new();
+                    for p in &structured {
+                        if let Pattern::Struct { fields: pf, .. } = p {
+                            for (field, _, _) in pf {
+                                if !fields.iter().any(|(f, _)| f == field) {
+                                    fields.push((field.clone(), None));
+                                }
+                            }
+                        }
+                    }
+                    self.struct_shape(name, fields, &structured)
+                } else {
+                    Shape::Open
+                }
+            }
+        }
+    }
+
+    /// 变体 `variant` 负载的形状
+    fn payload_shape(
+        &self,
+        ty: Option<&MonoType>,
+        patterns: &[&Pattern],
+        variant: &str,
+    ) -> Shape {
+        let payloads: Vec<&Pattern> = patterns
+            .iter()
+            .filter_map(|p| match p {
+                Pattern::Union {
+                    variant: v,
+                    pattern: Some(inner),
+                    ..
+                } if v == variant => Some(inner.as_ref()),
+                _ => None,
+            })
+            .collect();
+        self.shape(ty, &payloads)
+    }
+
+    fn enum_shape(
+        &self,
+        variants: &[String],
+        patterns: &[&Pattern],
+    ) -> Shape {
+        Shape::Enum(
+            variants
+                .iter()
+                .map(|v| {
+                    let has_payload = patterns.iter().any(|p| {
+                        matches!(p, Pattern::Union { variant, pattern: Some(_), .. } if variant == v)
+                    });
+                    let payload = has_payload.then(|| self.payload_shape(None, patterns, v));
+                    (v.clone(), payload)
+                })
+                .collect(),
+        )
+    }
+
+    fn tuple_shape(
+        &self,
+        elems: Option<&Vec<MonoType>>,
+        patterns: &[&Pattern],
+    ) -> Shape {
+        let arity = match elems {
+            Some(elems) => elems.len(),
+            None => patterns
+                .iter()
+                .find_map(|p| match p {
+                    Pattern::Tuple(ps) => Some(ps.len()),
+                    _ => None,
+                })
+                .unwrap_or(0),
+        };
+        Shape::Tuple(
+            (0..arity)
+                .map(|i| {
+                    let column: Vec<&Pattern> = patterns
+                        .iter()
+                        .filter_map(|p| match p {
+                            Pattern::Tuple(ps) if ps.len() == arity => Some(&ps[i]),
+                            _ => None,
+                        })
+                        .collect();
+                    self.shape(elems.map(|e| &e[i]), &column)
+                })
+                .collect(),
+        )
+    }
+
+    fn struct_shape(
+        &self,
+        name: &str,
+        fields: Vec<(String, Option<&MonoType>)>,
+        patterns: &[&Pattern],
+    ) -> Shape {
+        let arity = fields.len();
+        Shape::Struct {
+            name: name.to_string(),
+            fields: fields
+                .into_iter()
+                .enumerate()
+                .map(|(i, (field, ty))| {
+                    let column: Vec<&Pattern> = patterns
+                        .iter()
+                        .filter_map(|p| match p {
+                            Pattern::Struct { fields: pf, .. } => pf
+                                .iter()
+                                .find(|(f, _, _)| *f == field)
+                                .map(|(_, _, pat)| pat.as_ref()),
+                            Pattern::Union {
+                                variant,
+                                pattern: payload,
+                                ..
+                            } if variant == name => {
+                                constructor_args(payload.as_deref(), arity).map(|args| args[i])
+                            }
+                            _ => None,
+                        })
+                        .collect();
+                    let shape = self.shape(ty, &column);
+                    (field, shape)
+                })
+                .collect(),
+        }
+    }
+
+    // ------------------------------------------------------------------
+    // 模式归一化
+    // ------------------------------------------------------------------
+
+    /// 把 AST 模式归一化为某个形状上的构造器模式
+    fn lower(
+        &self,
+        pattern: &Pattern,
+        shape: &Shape,
+    ) -> Pat {
+        match pattern {
+            Pattern::Wildcard => Pat::Wild,
+            Pattern::Guard { pattern, .. } => self.lower(pattern, shape),
+            Pattern::Or(alts) => Pat::Or(alts.iter().map(|p| self.lower(p, shape)).collect()),
+            Pattern::Literal(Literal::Bool(b)) => Pat::Ctor(Ctor::Variant(b.to_string()), vec![]),
+            // `void` 即 Option 的 None
+            Pattern::Literal(Literal::Void) if matches!(shape, Shape::Option(_)) => {
+                Pat::Ctor(Ctor::Variant("None".to_string()), vec![])
+            }
+            Pattern::Literal(_) => opaque(pattern),
+            Pattern::Identifier(name) => {
+                let variant = name.rsplit('.').next().unwrap_or(name);
+                match shape {
+                    Shape::Option(_) if name == "None" => {
+                        Pat::Ctor(Ctor::Variant(name.clone()), vec![])
+                    }
+                    Shape::Enum(variants) => match variants.iter().find(|(v, _)| v == variant) {
+                        Some((v, payload)) => Pat::Ctor(
+                            Ctor::Variant(v.clone()),
+                            payload.iter().map(|_| Pat::Wild).collect(),
+                        ),
+                        None if name.contains('.') => opaque(pattern),
+                        None => Pat::Wild,
+                    },
+                    _ if name.contains('.') || name == "None" => opaque(pattern),
+                    // 普通标识符是绑定，匹配任意值
+                    _ => Pat::Wild,
+                }
+            }
+            Pattern::Union {
+                variant,
+                pattern: payload,
+                ..
+            } => {
+                // 位置构造器模式 `Point(0, y)`：参数按字段声明顺序对应
+                if let Shape::Struct { name, fields } = shape {
+                    if let Some(args) = (name == variant)
+                        .then(|| constructor_args(payload.as_deref(), fields.len()))
+                        .flatten()
+                    {
+                        return Pat::Ctor(
+                            Ctor::Struct {
+                                name: name.clone(),
+                                fields: fields.iter().map(|(f, _)| f.clone()).collect(),
+                            },
+                            args.into_iter()
+                                .zip(fields)
+                                .map(|(p, (_, s))| self.lower(p, s))
+                                .collect(),
+                        );
+                    }
+                }
+                let payload_shape = match shape {
+                    Shape::Option(inner) if variant == "Some" => Some(Some(inner.as_ref())),
+                    Shape::Option(_) if variant == "None" => Some(None),
+                    Shape::Result(ok, _) if variant == "Ok" => Some(Some(ok.as_ref())),
+                    Shape::Result(_, err) if variant == "Err" => Some(Some(err.as_ref())),
+                    Shape::Enum(variants) => variants
+                        .iter()
+                        .find(|(v, _)| v == variant)
+                        .map(|(_, payload)| payload.as_ref()),
+                    _ => None,
+                };
+                match (payload_shape, payload) {
+                    (Some(Some(inner)), Some(p)) => {
+                        Pat::Ctor(Ctor::Variant(variant.clone()), vec![self.lower(p, inner)])
+                    }
+                    (Some(Some(_)), None) => {
+                        Pat::Ctor(Ctor::Variant(variant.clone()), vec![Pat::Wild])
+                    }
+                    (Some(None), None) => Pat::Ctor(Ctor::Variant(variant.clone()), vec![]),
+                    _ => opaque(pattern),
+                }
+            }
+            Pattern::Tuple(elems) => match shape {
+                Shape::Tuple(shapes) if shapes.len() == elems.len() => Pat::Ctor(
+                    Ctor::Tuple,
+                    elems
+                        .iter()
+                        .zip(shapes)
+                        .map(|(p, s)| self.lower(p, s))
+                        .collect(),
+                ),
+                _ => opaque(pattern),
+            },
+            Pattern::Struct { fields, .. } => match shape {
+                Shape::Struct {
+                    name,
+                    fields: shapes,
+                } if fields
+                    .iter()
+                    .all(|(f, _, _)| shapes.iter().any(|(s, _)| s == f)) =>
+                {
+                    let args = shapes
+                        .iter()
+                        .map(|(field, s)| {
+                            fields
+                                .iter()
+                                .find(|(f, _, _)| f == field)
+                                .map_or(Pat::Wild, |(_, _, p)| self.lower(p, s))
+                        })
+                        .collect();
+                    Pat::Ctor(
+                        Ctor::Struct {
+                            name: name.clone(),
+                            fields: shapes.iter().map(|(f, _)| f.clone()).collect(),
+                        },
+                        args,
+                    )
+                }
+                _ => opaque(pattern),
+            },
+        }
+    }
+}
+
+/// 展开或模式与守卫，得到同一列上的所有子模式
+fn flatten<'p>(patterns: &[&'p Pattern]) -> Vec<&'p Pattern> {
+    let mut out = Vec::new();
+    for p in patterns {
+        match p {
+            Pattern::Or(alts) => out.extend(flatten(&alts.iter().collect::<Vec<_>>())),
+            Pattern::Guard { pattern, .. } => out.extend(flatten(&[pattern.as_ref()])),
+            _ => out.push(*p),
+        }
+    }
+    out
+}
+
+/// 标识符模式是否表示构造器（而非变量绑定）
+fn is_ctor_name(
+    name: &str,
+    ty: Option<&MonoType>,
+) -> bool {
+    name == "None"
+        || name.contains('.')
+        || matches!(ty, Some(MonoType::Enum(e)) if e.variants.iter().any(|v| v == name))
+}
+
+/// 与形状不符的模式：当作只匹配自身的常量，既不会遮蔽后续分支，也不计入穷尽
+fn opaque(pattern: &Pattern) -> Pat {
+    Pat::Ctor(Ctor::Literal(render(pattern)), vec![])
+}
+
+/// 构造器在某形状下的完整集合及各自参数的形状；无限域返回 None
+fn all_ctors(shape: &Shape) -> Option<Vec<(Ctor, Vec<Shape>)>> {
+    let variant = |name: &str, args: Vec<Shape>| (Ctor::Variant(name.to_string()), args);
+    match shape {
+        Shape::Bool => Some(vec![variant("true", vec![]), variant("false", vec![])]),
+        Shape::Option(inner) => Some(vec![
+            variant("Some", vec![inner.as_ref().clone()]),
+            variant("None", vec![]),
+        ]),
+        Shape::Result(ok, err) => Some(vec![
+            variant("Ok", vec![ok.as_ref().clone()]),
+            variant("Err", vec![err.as_ref().clone()]),
+        ]),
+        Shape::Enum(variants) => Some(
+            variants
+                .iter()
+                .map(|(name, payload)| variant(name, payload.iter().cloned().collect()))
+                .collect(),
+        ),
+        Shape::Tuple(elems) => Some(vec![(Ctor::Tuple, elems.clone())]),
+        Shape::Struct { name, fields } => Some(vec![(
+            Ctor::Struct {
+                name: name.clone(),
+                fields: fields.iter().map(|(f, _)| f.clone()).collect(),
+            },
+            fields.iter().map(|(_, s)| s.clone()).collect(),
+        )]),
+        Shape::Open => None,
+    }
+}
+
+/// 构造器参数的形状
+fn arg_shapes(
+    shape: &Shape,
+    ctor: &Ctor,
+    arity: usize,
+) -> Vec<Shape> {
+    all_ctors(shape)
+        .and_then(|ctors| ctors.into_iter().find(|(c, _)| c == ctor))
+        .map(|(_, args)| args)
+        .filter(|args| args.len() == arity)
+        .unwrap_or_else(|| vec![Shape::Open; arity])
+}
+
+/// 把首列的或模式展开成多行
+fn expand_or(rows: &[Vec<Pat>]) -> Vec<Vec<Pat>> {
+    let mut out = Vec::new();
+    for row in rows {
+        match row.split_first() {
+            Some((Pat::Or(alts), rest)) => {
+                let expanded: Vec<Vec<Pat>> = alts
+                    .iter()
+                    .map(|alt| {
+                        std::iter::once(alt.clone())
+                            .chain(rest.iter().cloned())
+                            .collect()
+                    })
+                    .collect();
+                out.extend(expand_or(&expanded));
+            }
+            _ => out.push(row.clone()),
+        }
+    }
+    out
+}
+
+/// 以构造器 `ctor` 特化矩阵
+fn specialize(
+    rows: &[Vec<Pat>],
+    ctor: &Ctor,
+    arity: usize,
+) -> Vec<Vec<Pat>> {
+    expand_or(rows)
+        .into_iter()
+        .filter_map(|row| {
+            let (head, rest) = row.split_first()?;
+            let mut args = match head {
+                Pat::Ctor(c, args) if c == ctor => args.clone(),
+                Pat::Wild => Vec::new(),
+                _ => return None,
+            };
+            args.resize(arity, Pat::Wild);
+            args.extend(rest.iter().cloned());
+            Some(args)
+        })
+        .collect()
+}
+
+/// 默认矩阵：首列为通配符的行去掉首列
+fn default_matrix(rows: &[Vec<Pat>]) -> Vec<Vec<Pat>> {
+    expand_or(rows)
+        .into_iter()
+        .filter(|row| matches!(row.first(), Some(Pat::Wild)))
+        .map(|row| row[1..].to_vec())
+        .collect()
+}
+
+/// 用构造器把见证向量的前 `arity` 项收拢为一项
+fn rebuild(
+    ctor: &Ctor,
+    arity: usize,
+    mut witness: Vec<Pat>,
+) -> Vec<Pat> {
+    let rest = witness.split_off(arity);
+    std::iter::once(Pat::Ctor(ctor.clone(), witness))
+        .chain(rest)
+        .collect()
+}
+
+/// 向量 `v` 相对于矩阵 `rows` 是否有用
+///
+/// 有用时返回一个见证：能被 `v` 匹配、但不能被 `rows` 中任何一行匹配的值。
+fn useful(
+    rows: &[Vec<Pat>],
+    v: &[Pat],
+    shapes: &[Shape],
+) -> Option<Vec<Pat>> {
+    let Some((head, rest)) = v.split_first() else {
+        return rows.is_empty().then(Vec::new);
+    };
+
+    match head {
+        Pat::Or(alts) => alts.iter().find_map(|alt| {
+            let v: Vec<Pat> = std::iter::once(alt.clone())
+                .chain(rest.iter().cloned())
+                .collect();
+            useful(rows, &v, shapes)
+        }),
+        Pat::Ctor(ctor, args) => {
+            let sub_shapes: Vec<Shape> = arg_shapes(&shapes[0], ctor, args.len())
+                .into_iter()
+                .chain(shapes[1..].iter().cloned())
+                .collect();
+            let v: Vec<Pat> = args.iter().chain(rest).cloned().collect();
+            useful(&specialize(rows, ctor, args.len()), &v, &sub_shapes)
+                .map(|w| rebuild(ctor, args.len(), w))
+        }
+        Pat::Wild => {
+            let seen: Vec<Ctor> = expand_or(rows)
+                .into_iter()
+                .filter_map(|row| match row.into_iter().next() {
+                    Some(Pat::Ctor(c, _)) => Some(c),
+                    _ => None,
+                })
+                .collect();
+            let all = all_ctors(&shapes[0]);
+
+            if let Some(all) = all
+                .as_ref()
+                .filter(|all| all.iter().all(|(c, _)| seen.contains(c)))
+            {
+                // 首列的构造器完整：逐个构造器特化
+                return all.iter().find_map(|(ctor, args)| {
+                    let v: Vec<Pat> = vec![Pat::Wild; args.len()]
+                        .into_iter()
+                        .chain(rest.iter().cloned())
+                        .collect();
+                    let sub_shapes: Vec<Shape> = args.iter().chain(&shapes[1..]).cloned().collect();
+                    useful(&specialize(rows, ctor, args.len()), &v, &sub_shapes)
+                        .map(|w| rebuild(ctor, args.len(), w))
+                });
+            }
+
+            // 构造器不完整：只需看默认矩阵
+            let witness = useful(&default_matrix(rows), rest, &shapes[1..])?;
+            let head = match all {
+                Some(all) if !seen.is_empty() => all
+                    .into_iter()
+                    .find(|(c, _)| !seen.contains(c))
+                    .map_or(Pat::Wild, |(c, args)| {
+                        Pat::Ctor(c, vec![Pat::Wild; args.len()])
+                    }),
+                _ => Pat::Wild,
+            };
+            Some(std::iter::once(head).chain(witness).collect())
+        }
+    }
+}
+
+/// 把 AST 模式渲染为源码形式
+fn render(pattern: &Pattern) -> String {
+    match pattern {
+        Pattern::Wildcard => "_".to_string(),
+        Pattern::Identifier(name) => name.clone(),
+        Pattern::Literal(lit) => match lit {
+            Literal::Int(n) => n.to_string(),
+            Literal::Float(x) => x.to_string(),
+            Literal::Bool(b) => b.to_string(),
+            Literal::Char(c) => format!("'{}'", c),
+            Literal::Void => "void".to_string(),
+            Literal::String(s) => format!("\"{}\"", s),
+        },
+        Pattern::Tuple(elems) => format!(
+            "({})",
+            elems.iter().map(render).collect::<Vec<_>>().join(", ")
+        ),
+        Pattern::Struct { name, fields } => {
+            let body = fields
+                .iter()
+                .map(|(field, is_mut, p)| {
+                    let prefix = if *is_mut { "mut " } else { "" };
+                    format!("{}{}: {}", prefix, field, render(p))
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            if name.is_empty() {
+                format!("{{ {} }}", body)
+            } else {
+                format!("{} {{ {} }}", name, body)
+            }
+        }
+        Pattern::Union {
+            variant, pattern, ..
+        } => match pattern {
+            Some(p) => format!("{}({})", variant, render(p)),
+            None => variant.clone(),
+        },
+        Pattern::Or(alts) => alts.iter().map(render).collect::<Vec<_>>().join(" | "),
+        Pattern::Guard { pattern, .. } => format!("{} if ..", render(pattern)),
+    }
+}
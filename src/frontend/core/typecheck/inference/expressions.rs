@@ -8,11 +8,16 @@
 use crate::util::diagnostic::{ErrorCodeDefinition, Result};
 use crate::frontend::core::parser::ast::{BinOp, UnOp};
 use crate::frontend::core::types::{MonoType, PolyType, TypeConstraintSolver};
+use crate::frontend::core::typecheck::layers::equivalence::{is_subtype, upcasts_to_any};
 use crate::frontend::core::typecheck::passes::overload;
 use crate::middle::passes::mono::instance::{GenericFunctionId, InstantiationRequest};
 use std::collections::{HashMap, HashSet};
 
+use super::exhaustiveness::ExhaustivenessChecker;
+use super::narrowing::{self, Narrowing};
+use super::numeric;
 use super::scope::ScopeManager;
+use crate::util::span::Span;
 
 /// 空的 Native 签名表（默认值）
 static EMPTY_SIGNATURES: std::sync::LazyLock<HashMap<String, MonoType>> =
@@ -22,6 +27,74 @@ static EMPTY_GENERIC_TYPE_DEFS: std::sync::LazyLock<
     HashMap<String, crate::frontend::core::typecheck::environment::GenericTypeDef>,
 > = std::sync::LazyLock::new(HashMap::new);
 
+/// 表达式的源码 span（没有自身 span 的节点返回 dummy）
+pub fn expr_span(expr: &crate::frontend::core::parser::ast::Expr) -> Span {
+    use crate::frontend::core::parser::ast::Expr;
+    match expr {
+        Expr::Lit(_, span)
+        | Expr::Var(_, span)
+        | Expr::Return(_, span)
+        | Expr::Break(_, span)
+        | Expr::Continue(_, span)
+        | Expr::Tuple(_, span)
+        | Expr::List(_, span)
+        | Expr::Dict(_, span)
+        | Expr::Error(span) => *span,
+        Expr::BinOp { span, .. }
+        | Expr::CompoundAssign { span, .. }
+        | Expr::Pipe { span, .. }
+        | Expr::UnOp { span, .. }
+        | Expr::Call { span, .. }
+        | Expr::FnDef { span, .. }
+        | Expr::If { span, .. }
+        | Expr::Match { span, .. }
+        | Expr::While { span, .. }
+        | Expr::For { span, .. }
+        | Expr::SpawnFor { span, .. }
+        | Expr::Cast { span, .. }
+        | Expr::ListComp { span, .. }
+        | Expr::Index { span, .. }
+        | Expr::FieldAccess { span, .. }
+        | Expr::Try { span, .. }
+        | Expr::Ref { span, .. }
+        | Expr::Borrow { span, .. }
+        | Expr::Unsafe { span, .. }
+        | Expr::Spawn { span, .. }
+        | Expr::Lambda { span, .. }
+        | Expr::FString { span, .. } => *span,
+        Expr::Block(block) => block.span,
+    }
+}
+
+/// 新类型参与的算术与比较
+///
+/// 新类型是名义类型：任一侧是新类型时两侧必须是同一个新类型，否则报 E1002。
+/// 两侧都不是新类型时返回 `None`；算术得到该新类型，比较得到 `Bool`。
+pub fn newtype_operands(
+    solver: &TypeConstraintSolver,
+    op: &BinOp,
+    left: &MonoType,
+    right: &MonoType,
+) -> Result<Option<MonoType>> {
+    let is_newtype = |ty: &MonoType| matches!(ty, MonoType::TypeRef(name) if solver.newtype_underlying(name).is_some());
+    if !is_newtype(left) && !is_newtype(right) {
+        return Ok(None);
+    }
+    let result = match op {
+        BinOp::Add | BinOp::Sub | BinOp::Mul | BinOp::Div | BinOp::Mod => left.clone(),
+        BinOp::Eq | BinOp::Neq | BinOp::Lt | BinOp::Le | BinOp::Gt | BinOp::Ge => MonoType::Bool,
+        _ => return Ok(None),
+    };
+    if left != right {
+        return Err(ErrorCodeDefinition::type_mismatch(
+            &format!("{}", left),
+            &format!("{}", right),
+        )
+        .build());
+    }
+    Ok(Some(result))
+}
+
 /// 表达式类型推断器
 ///
 /// 使用统一的 ScopeManager 管理变量作用域，
@@ -53,6 +126,12 @@ pub struct ExpressionInferrer<'a> {
         &'a HashMap<String, crate::frontend::core::typecheck::environment::GenericTypeDef>,
     /// 实例化请求（收集遇到的所有泛型函数实例化需求）
     pub instantiation_requests: Vec<InstantiationRequest>,
+    /// 定宽算术表达式的结果类型（按运算符 span 索引），IR 生成据此插入收窄指令
+    pub sized_arith: HashMap<Span, MonoType>,
+    /// 隐式转为浮点的整数表达式（按表达式 span 索引），IR 生成据此插入转换指令
+    pub int_to_float: HashSet<Span>,
+    /// 每个表达式推断出的类型（按表达式 span 索引，未展开类型变量），供位置查询使用
+    pub expr_types: HashMap<Span, MonoType>,
 }
 
 impl<'a> ExpressionInferrer<'a> {
@@ -74,6 +153,9 @@ impl<'a> ExpressionInferrer<'a> {
             type_defs: &EMPTY_SIGNATURES,
             generic_type_defs: &EMPTY_GENERIC_TYPE_DEFS,
             instantiation_requests: Vec::new(),
+            sized_arith: HashMap::new(),
+            int_to_float: HashSet::new(),
+            expr_types: HashMap::new(),
         }
     }
 
@@ -96,6 +178,9 @@ impl<'a> ExpressionInferrer<'a> {
             type_defs: &EMPTY_SIGNATURES,
             generic_type_defs: &EMPTY_GENERIC_TYPE_DEFS,
             instantiation_requests: Vec::new(),
+            sized_arith: HashMap::new(),
+            int_to_float: HashSet::new(),
+            expr_types: HashMap::new(),
         }
     }
 
@@ -119,6 +204,9 @@ impl<'a> ExpressionInferrer<'a> {
             type_defs: &EMPTY_SIGNATURES,
             generic_type_defs: &EMPTY_GENERIC_TYPE_DEFS,
             instantiation_requests: Vec::new(),
+            sized_arith: HashMap::new(),
+            int_to_float: HashSet::new(),
+            expr_types: HashMap::new(),
         }
     }
 
@@ -144,6 +232,9 @@ impl<'a> ExpressionInferrer<'a> {
             type_defs: &EMPTY_SIGNATURES,
             generic_type_defs: &EMPTY_GENERIC_TYPE_DEFS,
             instantiation_requests: Vec::new(),
+            sized_arith: HashMap::new(),
+            int_to_float: HashSet::new(),
+            expr_types: HashMap::new(),
         }
     }
 
@@ -312,6 +403,28 @@ impl<'a> ExpressionInferrer<'a> {
         self.loop_labels.contains(&label.to_string())
     }
 
+    /// 记录定宽算术（`Int8/16/32`、`Float32`）的结果类型
+    fn record_sized_arith(
+        &mut self,
+        span: Span,
+        ty: &MonoType,
+    ) {
+        if numeric::is_sized(ty) && !span.is_dummy() {
+            self.sized_arith.insert(span, ty.clone());
+        }
+    }
+
+    /// 记录隐式转为浮点的整数表达式
+    fn record_int_to_float(
+        &mut self,
+        expr: &crate::frontend::core::parser::ast::Expr,
+    ) {
+        let span = expr_span(expr);
+        if !span.is_dummy() {
+            self.int_to_float.insert(span);
+        }
+    }
+
     /// 推断字面量表达式类型
     pub fn infer_literal(
         &mut self,
@@ -323,6 +436,7 @@ impl<'a> ExpressionInferrer<'a> {
             crate::frontend::core::lexer::tokens::Literal::Bool(_) => MonoType::Bool,
             crate::frontend::core::lexer::tokens::Literal::Char(_) => MonoType::Char,
             crate::frontend::core::lexer::tokens::Literal::String(_) => MonoType::String,
+            crate::frontend::core::lexer::tokens::Literal::Void => MonoType::Void,
         };
         Ok(ty)
     }
@@ -334,12 +448,15 @@ impl<'a> ExpressionInferrer<'a> {
         left: &MonoType,
         right: &MonoType,
     ) -> Result<MonoType> {
+        if let Some(ty) = newtype_operands(self.solver, op, left, right)? {
+            return Ok(ty);
+        }
         match op {
             BinOp::Add | BinOp::Sub | BinOp::Mul | BinOp::Div => {
-                if let (MonoType::Int(_), MonoType::Int(_)) = (left, right) {
-                    Ok(left.clone())
-                } else if let (MonoType::Float(_), MonoType::Float(_)) = (left, right) {
-                    Ok(left.clone())
+                if let (MonoType::Int(a), MonoType::Int(b)) = (left, right) {
+                    Ok(MonoType::Int((*a).max(*b)))
+                } else if let (MonoType::Float(a), MonoType::Float(b)) = (left, right) {
+                    Ok(MonoType::Float((*a).max(*b)))
                 } else if let (MonoType::String, MonoType::String) = (left, right) {
                     Ok(MonoType::String)
                 } else if let (MonoType::List(left_elem), MonoType::List(right_elem)) =
@@ -354,17 +471,30 @@ impl<'a> ExpressionInferrer<'a> {
                 }
             }
             BinOp::Mod => {
-                if let (MonoType::Int(_), MonoType::Int(_)) = (left, right) {
-                    Ok(left.clone())
-                } else if let (MonoType::Float(_), MonoType::Float(_)) = (left, right) {
-                    Ok(left.clone())
+                if let (MonoType::Int(a), MonoType::Int(b)) = (left, right) {
+                    Ok(MonoType::Int((*a).max(*b)))
+                } else if let (MonoType::Float(a), MonoType::Float(b)) = (left, right) {
+                    Ok(MonoType::Float((*a).max(*b)))
                 } else {
                     let _ = self.solver.unify(left, right);
                     Ok(left.clone())
                 }
             }
             BinOp::Eq | BinOp::Neq | BinOp::Lt | BinOp::Le | BinOp::Gt | BinOp::Ge => {
-                let _ = self.solver.unify(left, right);
+                // 同类数值不同位宽、`opt != void` 之类一侧可提升为另一侧的比较同样合法
+                if numeric::widens_to(left, right) || numeric::widens_to(right, left) {
+                    return Ok(MonoType::Bool);
+                }
+                if self.solver.unify(left, right).is_err()
+                    && !is_subtype(left, right, None)
+                    && !is_subtype(right, left, None)
+                {
+                    return Err(ErrorCodeDefinition::type_mismatch(
+                        &format!("{}", left),
+                        &format!("{}", right),
+                    )
+                    .build());
+                }
                 Ok(MonoType::Bool)
             }
             BinOp::And | BinOp::Or => {
@@ -464,7 +594,9 @@ impl<'a> ExpressionInferrer<'a> {
                     Self::collect_type_var_indices(t, out);
                 }
             }
-            MonoType::Arc(t) | MonoType::Weak(t) => Self::collect_type_var_indices(t, out),
+            MonoType::Arc(t) | MonoType::Weak(t) | MonoType::Async(t) => {
+                Self::collect_type_var_indices(t, out)
+            }
             MonoType::Struct(s) => {
                 for (_, field_ty) in &s.fields {
                     Self::collect_type_var_indices(field_ty, out);
@@ -512,6 +644,7 @@ impl<'a> ExpressionInferrer<'a> {
                     field_mutability: s.field_mutability.clone(),
                     field_has_default: s.field_has_default.clone(),
                     interfaces: s.interfaces.clone(),
+                    assoc_items: s.assoc_items.clone(),
                 })
             }
             MonoType::List(elem) => {
@@ -566,6 +699,66 @@ impl<'a> ExpressionInferrer<'a> {
         Substituter::new().substitute(ty, &sub)
     }
 
+    /// 类型中是否含有关联类型访问 `T.Item`
+    fn contains_assoc_type(ty: &MonoType) -> bool {
+        match ty {
+            MonoType::AssocType { .. } => true,
+            MonoType::List(elem) | MonoType::Option(elem) | MonoType::Arc(elem) => {
+                Self::contains_assoc_type(elem)
+            }
+            MonoType::Tuple(elems) => elems.iter().any(Self::contains_assoc_type),
+            MonoType::Fn {
+                params,
+                return_type,
+            } => {
+                params.iter().any(Self::contains_assoc_type)
+                    || Self::contains_assoc_type(return_type)
+            }
+            _ => false,
+        }
+    }
+
+    /// 解析关联类型访问 `T.Item`
+    ///
+    /// 宿主类型已推断为实现类型（如 `Square`）时，替换为 `Square.Item: Type = ...`
+    /// 绑定的目标类型；宿主尚未确定或实现类型没有该绑定时原样保留。
+    fn resolve_assoc_types(
+        &self,
+        ty: &MonoType,
+    ) -> MonoType {
+        match ty {
+            MonoType::AssocType {
+                host_type,
+                assoc_name,
+                ..
+            } => {
+                let host = match self.solver.resolve_type(host_type) {
+                    MonoType::Struct(s) if !s.name.is_empty() => s.name,
+                    MonoType::TypeRef(name) => name,
+                    _ => return ty.clone(),
+                };
+                match self.type_defs.get(&format!("{}.{}", host, assoc_name)) {
+                    Some(target) => self.solver.resolve_type(target),
+                    None => ty.clone(),
+                }
+            }
+            MonoType::List(elem) => MonoType::List(Box::new(self.resolve_assoc_types(elem))),
+            MonoType::Option(elem) => MonoType::Option(Box::new(self.resolve_assoc_types(elem))),
+            MonoType::Arc(elem) => MonoType::Arc(Box::new(self.resolve_assoc_types(elem))),
+            MonoType::Tuple(elems) => {
+                MonoType::Tuple(elems.iter().map(|e| self.resolve_assoc_types(e)).collect())
+            }
+            MonoType::Fn {
+                params,
+                return_type,
+            } => MonoType::Fn {
+                params: params.iter().map(|p| self.resolve_assoc_types(p)).collect(),
+                return_type: Box::new(self.resolve_assoc_types(return_type)),
+            },
+            _ => ty.clone(),
+        }
+    }
+
     /// 单态化泛型函数类型：将泛型函数类型中的类型变量统一替换为具体类型。
     ///
     /// 当调用泛型函数（如 `fn identity[T](x: T) -> T`）时，根据实参类型
@@ -603,14 +796,28 @@ impl<'a> ExpressionInferrer<'a> {
                 .collect();
             let new_return = Self::substitute_type_vars(return_type, &subst);
 
-            // Unify 新参数与实参以推断具体类型
+            // Unify 新参数与实参以推断具体类型；
+            // 关联类型 `T.Item` 依赖 T 的推断结果，在其余参数统一之后再解析
+            if arg_types.len() == new_params.len() {
+                for (arg_ty, param_ty) in arg_types.iter().zip(new_params.iter()) {
+                    if !Self::contains_assoc_type(param_ty) {
+                        let _ = self.solver.unify(arg_ty, param_ty);
+                    }
+                }
+            }
+            let new_params: Vec<MonoType> = new_params
+                .iter()
+                .map(|p| self.resolve_assoc_types(p))
+                .collect();
             if arg_types.len() == new_params.len() {
                 for (arg_ty, param_ty) in arg_types.iter().zip(new_params.iter()) {
                     let _ = self.solver.unify(arg_ty, param_ty);
                 }
             }
 
-            let resolved_return = self.solver.resolve_type(&new_return);
+            let resolved_return = self
+                .solver
+                .resolve_type(&self.resolve_assoc_types(&new_return));
             return MonoType::Fn {
                 params: new_params,
                 return_type: Box::new(resolved_return),
@@ -680,7 +887,6 @@ impl<'a> ExpressionInferrer<'a> {
         &mut self,
         func_ty: &MonoType,
         func_expr: &crate::frontend::core::parser::ast::Expr,
-        arg_types: &[MonoType],
         mono_func_ty: &MonoType,
         call_span: crate::util::span::Span,
     ) {
@@ -715,26 +921,60 @@ impl<'a> ExpressionInferrer<'a> {
         // 获取泛型参数名称列表
         let type_params: Vec<String> = self.lookup_type_params(&fn_name);
 
-        // 从单态化后的函数类型中提取具体的类型参数
-        if let MonoType::Fn {
+        let MonoType::Fn {
             params: resolved_params,
             ..
         } = mono_func_ty
-        {
-            // 收集所有的具体类型（从已解析的参数中提取去重后的类型）
-            let type_args: Vec<MonoType> =
-                self.extract_concrete_type_args(resolved_params, arg_types);
-
-            if !type_args.is_empty() {
-                let generic_id = if type_params.is_empty() {
-                    GenericFunctionId::new(fn_name, vec![])
-                } else {
-                    GenericFunctionId::new(fn_name, type_params)
-                };
-                let request = InstantiationRequest::new(generic_id, type_args, call_span);
-                self.instantiation_requests.push(request);
+        else {
+            return;
+        };
+        let type_args = if var_indices.is_empty() {
+            // 泛型类型构造器：收集已解析的参数类型
+            self.extract_concrete_type_args(resolved_params)
+        } else {
+            match self.type_args_in_order(original_params, resolved_params, &var_indices) {
+                Some(type_args) => type_args,
+                None => return,
             }
+        };
+        if type_args.is_empty() {
+            return;
         }
+
+        let generic_id = GenericFunctionId::new(fn_name.to_string(), type_params);
+        let request = InstantiationRequest::new(generic_id, type_args, call_span);
+        self.instantiation_requests.push(request);
+    }
+
+    /// 按泛型参数的声明顺序取出本次调用的类型实参
+    ///
+    /// 泛型函数签名中的类型变量按类型参数的声明顺序创建，索引递增；把签名参数与调用处
+    /// 单态化后的参数按结构对齐，读出每个类型变量对应的类型。类型实参可能仍是类型变量，
+    /// 待整个模块求解后再展开。只出现在返回类型中的类型参数无法由实参确定，返回 `None`。
+    fn type_args_in_order(
+        &self,
+        original_params: &[MonoType],
+        resolved_params: &[MonoType],
+        var_indices: &HashSet<usize>,
+    ) -> Option<Vec<MonoType>> {
+        let resolved_params: Vec<MonoType> = resolved_params
+            .iter()
+            .map(|p| self.solver.resolve_type(p))
+            .collect();
+        let mut bindings = HashMap::new();
+        for (pattern, actual) in original_params.iter().zip(&resolved_params) {
+            super::instantiation::zip_types(pattern, actual, &mut |pattern, actual| {
+                if let MonoType::TypeVar(tv) = pattern {
+                    bindings.entry(tv.index()).or_insert_with(|| actual.clone());
+                }
+            });
+        }
+        let mut indices: Vec<usize> = var_indices.iter().copied().collect();
+        indices.sort_unstable();
+        indices
+            .into_iter()
+            .map(|index| bindings.remove(&index))
+            .collect()
     }
 
     /// 查找函数的泛型类型参数名称
@@ -771,11 +1011,10 @@ impl<'a> ExpressionInferrer<'a> {
         vec![]
     }
 
-    /// 从已解析的参数类型和实参类型中提取具体的类型参数
+    /// 从已解析的参数类型中提取去重后的具体类型（泛型类型构造器的类型参数）
     fn extract_concrete_type_args(
         &self,
         resolved_params: &[MonoType],
-        _arg_types: &[MonoType],
     ) -> Vec<MonoType> {
         let mut type_args = Vec::new();
         let mut seen = HashSet::new();
@@ -798,6 +1037,18 @@ impl<'a> ExpressionInferrer<'a> {
     pub fn infer_expr(
         &mut self,
         expr: &crate::frontend::core::parser::ast::Expr,
+    ) -> Result<MonoType> {
+        let ty = self.infer_expr_kind(expr)?;
+        let span = expr_span(expr);
+        if !span.is_dummy() {
+            self.expr_types.insert(span, ty.clone());
+        }
+        Ok(ty)
+    }
+
+    fn infer_expr_kind(
+        &mut self,
+        expr: &crate::frontend::core::parser::ast::Expr,
     ) -> Result<MonoType> {
         match expr {
             // 字面量
@@ -810,18 +1061,42 @@ impl<'a> ExpressionInferrer<'a> {
                     // 关键：直接使用 scope 中存储的类型！
                     // 因为 assign_var 已经将更新后的类型写入了 scope
                     // 不需要再通过 solver 解析（solver 不知道 scope 的更新）
+                    // Async(T) 变量在读取处隐式汇合（join）为 T
+                    if let MonoType::Async(inner) = self.solver.resolve_type(&poly.body) {
+                        return Ok(*inner);
+                    }
                     Ok(poly.body)
                 } else {
-                    Err(ErrorCodeDefinition::unknown_variable(name)
-                        .at(*span)
-                        .build())
+                    Err(super::did_you_mean::unknown_variable(
+                        name,
+                        *span,
+                        self.scope,
+                        self.native_signatures,
+                        None,
+                    ))
                 }
             }
 
             // 二元运算
             crate::frontend::core::parser::ast::Expr::BinOp {
-                op, left, right, ..
+                op,
+                left,
+                right,
+                span,
             } => {
+                // `a && b` 中 b 在 a 成立的前提下推断，`a || b` 中 b 在 a 不成立的前提下推断
+                if matches!(op, BinOp::And | BinOp::Or) {
+                    let left_ty = self.infer_expr(left)?;
+                    let narrowing = Narrowing::of_condition(left, self.scope, self.solver);
+                    let facts = if matches!(op, BinOp::And) {
+                        narrowing.when_true
+                    } else {
+                        narrowing.when_false
+                    };
+                    let right_ty = self.with_narrowed(&facts, |this| this.infer_expr(right))?;
+                    return self.infer_binary(op, &left_ty, &right_ty);
+                }
+
                 let right_ty = self.infer_expr(right)?;
 
                 if matches!(op, BinOp::Assign) {
@@ -835,12 +1110,43 @@ impl<'a> ExpressionInferrer<'a> {
                 }
 
                 let left_ty = self.infer_expr(left)?;
-                self.infer_binary(op, &left_ty, &right_ty)
+                // `Int8` 这类标注在作用域里是类型名，先解析为定宽类型
+                let (l, r) = (
+                    numeric::resolve_numeric(&self.solver.resolve_type(&left_ty)),
+                    numeric::resolve_numeric(&self.solver.resolve_type(&right_ty)),
+                );
+                let is_arith = matches!(
+                    op,
+                    BinOp::Add | BinOp::Sub | BinOp::Mul | BinOp::Div | BinOp::Mod
+                );
+                if is_arith {
+                    if let Some(ty) = numeric::arith_result(&l, left, &r, right)? {
+                        self.record_sized_arith(*span, &ty);
+                        return Ok(ty);
+                    }
+                }
+                if is_arith
+                    || matches!(
+                        op,
+                        BinOp::Eq | BinOp::Neq | BinOp::Lt | BinOp::Le | BinOp::Gt | BinOp::Ge
+                    )
+                {
+                    if let Some((int_expr, float_ty)) =
+                        numeric::int_operand_to_float(&l, left, &r, right)
+                    {
+                        self.record_int_to_float(int_expr);
+                        return Ok(if is_arith { float_ty } else { MonoType::Bool });
+                    }
+                }
+                self.infer_binary(op, &l, &r)
             }
 
             // 一元运算
-            crate::frontend::core::parser::ast::Expr::UnOp { op, expr, .. } => {
+            crate::frontend::core::parser::ast::Expr::UnOp { op, expr, span } => {
                 let expr_ty = self.infer_expr(expr)?;
+                if matches!(op, UnOp::Neg) {
+                    self.record_sized_arith(*span, &expr_ty);
+                }
                 self.infer_unary(op, &expr_ty)
             }
 
@@ -936,7 +1242,7 @@ impl<'a> ExpressionInferrer<'a> {
                 ) -> Option<String> {
                     match expr {
                         crate::frontend::core::parser::ast::Expr::Var(name, _) => {
-                            Some(name.clone())
+                            Some(name.to_string())
                         }
                         crate::frontend::core::parser::ast::Expr::FieldAccess {
                             expr,
@@ -1023,6 +1329,33 @@ impl<'a> ExpressionInferrer<'a> {
                                 .build(),
                         )
                     }
+                    // 用户泛型实例（如参数 `b: Box(Int)`）：按模板实例化后查找字段与方法
+                    MonoType::Generic { ref name, .. }
+                        if self.generic_type_defs.contains_key(name) =>
+                    {
+                        let instance =
+                            crate::frontend::core::typecheck::TypeEnvironment::expand_generic_instances(
+                                &resolved,
+                                self.generic_type_defs,
+                            );
+                        let MonoType::Struct(struct_type) = instance else {
+                            return Err(ErrorCodeDefinition::field_access_on_non_struct(&format!(
+                                "{}",
+                                obj_ty
+                            ))
+                            .build());
+                        };
+                        if let Some((_, field_ty)) =
+                            struct_type.fields.iter().find(|(n, _)| n == field)
+                        {
+                            return Ok(field_ty.clone());
+                        }
+                        let method_key = format!("{}.{}", name, field);
+                        if let Some(method_ty) = self.method_bindings.get(&method_key) {
+                            return Ok(method_ty.clone());
+                        }
+                        Err(ErrorCodeDefinition::field_not_found(field, name).build())
+                    }
                     _ => Err(ErrorCodeDefinition::field_access_on_non_struct(&format!(
                         "{}",
                         obj_ty
@@ -1035,6 +1368,20 @@ impl<'a> ExpressionInferrer<'a> {
             crate::frontend::core::parser::ast::Expr::Call {
                 func, args, span, ..
             } => {
+                // 未知函数名：按实参个数挑选拼写建议
+                if let crate::frontend::core::parser::ast::Expr::Var(name, name_span) =
+                    func.as_ref()
+                {
+                    if self.scope.get_var(name).is_none() {
+                        return Err(super::did_you_mean::unknown_variable(
+                            name,
+                            *name_span,
+                            self.scope,
+                            self.native_signatures,
+                            Some(args.len()),
+                        ));
+                    }
+                }
                 let func_ty = self.infer_expr(func)?;
 
                 // LibraryRef callable rule: when calling a LibraryRef with a string literal
@@ -1108,18 +1455,13 @@ impl<'a> ExpressionInferrer<'a> {
                 let mono_func_ty = self.monomorphize(func_ty.clone(), &arg_types);
 
                 // 收集实例化请求：检测泛型函数调用并记录
-                self.collect_instantiation_request(
-                    &func_ty,
-                    func.as_ref(),
-                    &arg_types,
-                    &mono_func_ty,
-                    *span,
-                );
+                self.collect_instantiation_request(&func_ty, func.as_ref(), &mono_func_ty, *span);
 
                 // 泛型类型构造：当函数名在 generic_type_defs 中且 func_ty 是 Struct 时，
                 // 通过 unify arg_types 与 struct fields 推断泛型参数，返回实例化后的结构体
                 if let crate::frontend::core::parser::ast::Expr::Var(ref fn_name, _) = **func {
-                    if let Some(generic_def) = self.generic_type_defs.get(fn_name).cloned() {
+                    if let Some(generic_def) = self.generic_type_defs.get(fn_name.as_str()).cloned()
+                    {
                         if let MonoType::Struct(ref s) = func_ty {
                             if !generic_def.type_param_names.is_empty() && !s.fields.is_empty() {
                                 // 使用 struct fields 的类型作为参数类型，
@@ -1177,7 +1519,9 @@ impl<'a> ExpressionInferrer<'a> {
                     } => {
                         // 值级函数调用
                         if arg_types.len() == params.len() {
-                            for (arg_ty, param_ty) in arg_types.iter().zip(params.iter()) {
+                            for ((arg_ty, param_ty), arg) in
+                                arg_types.iter().zip(params.iter()).zip(args.iter())
+                            {
                                 // 自动借用：当参数签名要求 &T 且实参是值类型时，
                                 // 编译器自动创建令牌（RFC-009 §2.8）
                                 let actual_arg = match (param_ty, arg_ty) {
@@ -1193,13 +1537,80 @@ impl<'a> ExpressionInferrer<'a> {
                                 };
                                 // Int -> Float 扩展转换是允许的
                                 if matches!(
-                                    (&actual_arg, param_ty),
+                                    (
+                                        &self.solver.resolve_type(&actual_arg),
+                                        &numeric::resolve_numeric(param_ty)
+                                    ),
                                     (MonoType::Int(_), MonoType::Float(_))
                                 ) {
+                                    self.record_int_to_float(arg);
+                                    continue;
+                                }
+                                // TypeRef 未完全解析时跳过（如用户自定义类型名）；
+                                // 新类型是名义类型，必须照常统一
+                                if matches!(param_ty, MonoType::TypeRef(name) if self.solver.newtype_underlying(name).is_none())
+                                {
                                     continue;
                                 }
-                                // TypeRef 未完全解析时跳过（如用户自定义类型名）
-                                if matches!(param_ty, MonoType::TypeRef(_)) {
+                                // 定宽数值参数：字面量按参数类型检查范围，变量只允许拓宽
+                                if numeric::is_sized(param_ty)
+                                    || numeric::is_sized(&self.solver.resolve_type(arg_ty))
+                                {
+                                    let resolved_arg = self.solver.resolve_type(arg_ty);
+                                    if numeric::literal_adapts_to(arg, param_ty)?
+                                        || numeric::widens_to(&resolved_arg, param_ty)
+                                    {
+                                        continue;
+                                    }
+                                    if matches!(
+                                        (&resolved_arg, param_ty),
+                                        (MonoType::Int(_), MonoType::Int(_))
+                                            | (MonoType::Float(_), MonoType::Float(_))
+                                    ) {
+                                        return Err(ErrorCodeDefinition::type_mismatch(
+                                            &format!("{}", param_ty),
+                                            &format!("{}", resolved_arg),
+                                        )
+                                        .at(*span)
+                                        .build());
+                                    }
+                                }
+                                // 可选类型提升：`T` 与 `void` 都可以传给 `Option(T)`
+                                if let MonoType::Option(inner) = param_ty {
+                                    let inner = match inner.as_ref() {
+                                        MonoType::TypeRef(name) => self
+                                            .type_defs
+                                            .get(name)
+                                            .cloned()
+                                            .unwrap_or_else(|| inner.as_ref().clone()),
+                                        other => other.clone(),
+                                    };
+                                    if is_subtype(
+                                        &actual_arg,
+                                        &MonoType::Option(Box::new(inner)),
+                                        None,
+                                    ) {
+                                        continue;
+                                    }
+                                }
+                                // 匿名记录参数：宽度子类型，字段更多的结构体也可传入
+                                if param_ty.is_record() {
+                                    let resolved_arg = match self.solver.resolve_type(arg_ty) {
+                                        MonoType::TypeRef(name) => self
+                                            .type_defs
+                                            .get(&name)
+                                            .cloned()
+                                            .unwrap_or(MonoType::TypeRef(name)),
+                                        other => other,
+                                    };
+                                    let resolved_arg = self.solver.resolve_type(&resolved_arg);
+                                    let record = self.solver.resolve_type(param_ty);
+                                    if is_subtype(&resolved_arg, &record, None) {
+                                        continue;
+                                    }
+                                }
+                                // Any 参数：任何实参都可上转
+                                if upcasts_to_any(&self.solver.resolve_type(arg_ty), param_ty) {
                                     continue;
                                 }
                                 // TypeVar 是泛型类型参数 —— 必须 unify 以推断具体类型
@@ -1242,31 +1653,47 @@ impl<'a> ExpressionInferrer<'a> {
                     .build());
                 }
 
-                self.scope.enter_scope();
-                let then_result = self.infer_block(then_branch, true, None);
-                self.scope.exit_scope();
-                let _then_ty = then_result?;
+                // 前面所有条件都不成立时的收窄，随 elif 链累积
+                let narrowing = Narrowing::of_condition(condition, self.scope, self.solver);
+                let mut otherwise = narrowing.when_false;
+
+                let _then_ty = self.with_narrowed(&narrowing.when_true, |this| {
+                    this.scope.enter_scope();
+                    let then_result = this.infer_block(then_branch, true, None);
+                    this.scope.exit_scope();
+                    then_result
+                })?;
 
                 for (elif_cond, elif_block) in elif_branches {
-                    let elif_cond_ty = self.infer_expr(elif_cond)?;
-                    if elif_cond_ty != MonoType::Bool {
-                        return Err(ErrorCodeDefinition::condition_type_mismatch(&format!(
-                            "{}",
-                            elif_cond_ty
-                        ))
-                        .build());
-                    }
-                    self.scope.enter_scope();
-                    let elif_result = self.infer_block(elif_block, true, None);
-                    self.scope.exit_scope();
-                    let _ = elif_result?;
+                    let elif_when_false = self.with_narrowed(&otherwise.clone(), |this| {
+                        let elif_cond_ty = this.infer_expr(elif_cond)?;
+                        if elif_cond_ty != MonoType::Bool {
+                            return Err(ErrorCodeDefinition::condition_type_mismatch(&format!(
+                                "{}",
+                                elif_cond_ty
+                            ))
+                            .build());
+                        }
+                        let elif_narrowing =
+                            Narrowing::of_condition(elif_cond, this.scope, this.solver);
+                        this.with_narrowed(&elif_narrowing.when_true, |this| {
+                            this.scope.enter_scope();
+                            let elif_result = this.infer_block(elif_block, true, None);
+                            this.scope.exit_scope();
+                            elif_result
+                        })?;
+                        Ok(elif_narrowing.when_false)
+                    })?;
+                    otherwise.extend(elif_when_false);
                 }
 
                 if let Some(else_block) = else_branch {
-                    self.scope.enter_scope();
-                    let else_result = self.infer_block(else_block, true, None);
-                    self.scope.exit_scope();
-                    else_result
+                    self.with_narrowed(&otherwise, |this| {
+                        this.scope.enter_scope();
+                        let else_result = this.infer_block(else_block, true, None);
+                        this.scope.exit_scope();
+                        else_result
+                    })
                 } else {
                     Ok(MonoType::Void)
                 }
@@ -1344,6 +1771,22 @@ impl<'a> ExpressionInferrer<'a> {
                     // If we know the expected return type, check that the return
                     // expression type matches it via unification.
                     if let Some(ref expected) = self.expected_return_type {
+                        let resolved = self.solver.resolve_type(&ret_ty);
+                        if upcasts_to_any(&resolved, expected) {
+                            return Ok(expected.clone());
+                        }
+                        // RFC-001: 返回 Result 的函数中，非 Result 的值隐式包装为 Ok
+                        let expected = match expected {
+                            MonoType::Result(ok, _)
+                                if !matches!(
+                                    resolved,
+                                    MonoType::Result(..) | MonoType::TypeVar(_)
+                                ) =>
+                            {
+                                ok.as_ref()
+                            }
+                            _ => expected,
+                        };
                         self.solver.unify(&ret_ty, expected).map_err(|_| {
                             ErrorCodeDefinition::type_mismatch(
                                 &format!("{}", expected),
@@ -1381,10 +1824,18 @@ impl<'a> ExpressionInferrer<'a> {
 
             // Cast 表达式
             crate::frontend::core::parser::ast::Expr::Cast {
-                expr, target_type, ..
+                expr,
+                target_type,
+                checked,
+                ..
             } => {
-                let _ = self.infer_expr(expr)?;
-                let target_mono: MonoType = target_type.clone().into();
+                let source_ty = self.infer_expr(expr)?;
+                let target_mono = numeric::resolve_numeric(&target_type.clone().into());
+                if *checked {
+                    // `as?`：运行时按类型标签检查，不符时得到 void
+                    return Ok(MonoType::Option(Box::new(target_mono)));
+                }
+                numeric::check_cast(&source_ty, expr, &target_mono)?;
                 Ok(target_mono)
             }
 
@@ -1510,8 +1961,57 @@ impl<'a> ExpressionInferrer<'a> {
             }
 
             // Match 表达式
-            crate::frontend::core::parser::ast::Expr::Match { expr, .. } => {
-                let _expr_ty = self.infer_expr(expr)?;
+            crate::frontend::core::parser::ast::Expr::Match { expr, arms, span } => {
+                let expr_ty = self.infer_expr(expr)?;
+                let expr_ty = self.solver.resolve_type(&expr_ty);
+                ExhaustivenessChecker::new(self.type_defs).check_arms(&expr_ty, arms, *span)?;
+
+                let scrutinee = match expr.as_ref() {
+                    crate::frontend::core::parser::ast::Expr::Var(name, _) => Some(name),
+                    _ => None,
+                };
+                // 前面分支未匹配时被匹配值剩余的类型
+                let mut remaining = expr_ty.clone();
+                for arm in arms {
+                    // 被匹配的变量在分支内收窄为模式对应的类型
+                    let narrowed = narrowing::narrow_by_pattern(&remaining, &arm.pattern)
+                        .or_else(|| (remaining != expr_ty).then(|| remaining.clone()));
+                    let facts: Vec<(String, MonoType)> = scrutinee
+                        .zip(narrowed)
+                        .map(|(name, ty)| (name.to_string(), ty))
+                        .into_iter()
+                        .collect();
+                    let mut bindings = Vec::new();
+                    let solver = &mut *self.solver;
+                    narrowing::pattern_bindings(
+                        &remaining,
+                        &arm.pattern,
+                        &mut || solver.new_var(),
+                        &mut bindings,
+                    );
+
+                    self.with_narrowed(&facts, |this| {
+                        this.scope.enter_scope();
+                        for (name, ty) in bindings {
+                            this.scope
+                                .add_var(name, PolyType::mono(ty), false, arm.span);
+                        }
+                        let result = match &arm.pattern {
+                            crate::frontend::core::parser::ast::Pattern::Guard {
+                                condition,
+                                ..
+                            } => this
+                                .infer_expr(condition)
+                                .and_then(|_| this.infer_block(&arm.body, true, None)),
+                            _ => this.infer_block(&arm.body, true, None),
+                        };
+                        this.scope.exit_scope();
+                        result
+                    })?;
+                    if let Some(rest) = narrowing::exclude_pattern(&remaining, &arm.pattern) {
+                        remaining = rest;
+                    }
+                }
                 Ok(self.solver.new_var())
             }
 
@@ -1561,6 +2061,14 @@ impl<'a> ExpressionInferrer<'a> {
 
             // spawn 块：spawn { ... }
             crate::frontend::core::parser::ast::Expr::Spawn { body, .. } => {
+                // spawn f(x)：单任务形式，结果为 Async(T)，在使用处隐式汇合
+                if let Some(call) = crate::frontend::core::spawn::analysis::spawn_call(body) {
+                    let result_ty = self.infer_expr(call)?;
+                    return Ok(match self.solver.resolve_type(&result_ty) {
+                        async_ty @ MonoType::Async(_) => async_ty,
+                        _ => MonoType::Async(Box::new(result_ty)),
+                    });
+                }
                 self.infer_block(body, true, None)
             }
 
@@ -1611,6 +2119,14 @@ impl<'a> ExpressionInferrer<'a> {
                 Ok(MonoType::String)
             }
 
+            // 语法糖由脱糖阶段改写，未经脱糖的 AST 不应进入类型检查
+            crate::frontend::core::parser::ast::Expr::CompoundAssign { span, .. }
+            | crate::frontend::core::parser::ast::Expr::Pipe { span, .. } => Err(
+                ErrorCodeDefinition::internal_error("语法糖未经脱糖即进入类型检查")
+                    .at(*span)
+                    .build(),
+            ),
+
             // 错误恢复占位符：返回新类型变量，不会导致 panic
             crate::frontend::core::parser::ast::Expr::Error(span) => {
                 Err(ErrorCodeDefinition::invalid_syntax("缺失表达式")
@@ -1708,6 +2224,8 @@ impl<'a> ExpressionInferrer<'a> {
                 _ => {}
             }
             self.infer_stmt(stmt)?;
+            let facts = narrowing::after_if(stmt, self.scope, self.solver);
+            narrowing::apply(self.scope, &facts);
         }
 
         // 块的类型 = return 的类型，没有 return 则 Void
@@ -1731,6 +2249,22 @@ impl<'a> ExpressionInferrer<'a> {
         }
     }
 
+    /// 在带有收窄变量的内层作用域中执行 `f`
+    fn with_narrowed<T>(
+        &mut self,
+        facts: &[(String, MonoType)],
+        f: impl FnOnce(&mut Self) -> Result<T>,
+    ) -> Result<T> {
+        if facts.is_empty() {
+            return f(self);
+        }
+        self.scope.enter_scope();
+        narrowing::apply(self.scope, facts);
+        let result = f(self);
+        self.scope.exit_scope();
+        result
+    }
+
     /// 推断语句的类型
     pub fn infer_stmt(
         &mut self,
@@ -1855,7 +2389,7 @@ fn extract_string_literal_from_expr(
         crate::frontend::core::parser::ast::Expr::Lit(
             crate::frontend::core::lexer::tokens::Literal::String(s),
             _,
-        ) => Some(s.clone()),
+        ) => Some(s.to_string()),
         _ => None,
     }
 }
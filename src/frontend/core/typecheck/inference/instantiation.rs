@@ -0,0 +1,54 @@
+//! 泛型实例化记录
+//!
+//! 调用点按泛型参数的声明顺序记录类型实参，交给单态化器特化。
+//! 类型实参由泛型函数签名与调用处推断出的签名按结构对齐得到；
+//! 泛型函数体内的调用改写为以所在函数的类型参数表示。
+
+use crate::frontend::core::types::MonoType;
+
+/// 类型的直接子类型（按构造器中的位置排列）
+fn children(ty: &MonoType) -> Vec<&MonoType> {
+    match ty {
+        MonoType::List(t)
+        | MonoType::Set(t)
+        | MonoType::Option(t)
+        | MonoType::Arc(t)
+        | MonoType::Weak(t)
+        | MonoType::Async(t) => vec![t],
+        MonoType::Range { elem_type } => vec![elem_type],
+        MonoType::Dict(k, v) | MonoType::Result(k, v) => vec![k, v],
+        MonoType::Tuple(types) | MonoType::Union(types) | MonoType::Intersection(types) => {
+            types.iter().collect()
+        }
+        MonoType::Fn {
+            params,
+            return_type,
+        } => params
+            .iter()
+            .chain(std::iter::once(return_type.as_ref()))
+            .collect(),
+        MonoType::Generic { args, .. } => args.iter().collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// 按结构对齐模式类型与实际类型，对每一对位置相同的子类型调用 `visit`
+///
+/// 构造器不同或子类型个数不同处停止下降。
+pub fn zip_types<'a>(
+    pattern: &'a MonoType,
+    actual: &'a MonoType,
+    visit: &mut impl FnMut(&'a MonoType, &'a MonoType),
+) {
+    visit(pattern, actual);
+    if std::mem::discriminant(pattern) != std::mem::discriminant(actual) {
+        return;
+    }
+    let (pattern_children, actual_children) = (children(pattern), children(actual));
+    if pattern_children.len() != actual_children.len() {
+        return;
+    }
+    for (p, a) in pattern_children.into_iter().zip(actual_children) {
+        zip_types(p, a, visit);
+    }
+}
@@ -12,7 +12,12 @@ pub mod types;
 // ✅ 从 checking/ 移入的模块
 pub mod assignment;
 pub mod bounds;
+pub mod did_you_mean;
 
+pub mod exhaustiveness;
+pub mod instantiation;
+pub mod narrowing;
+pub mod numeric;
 pub mod patterns;
 
 // 重新导出核心类型
@@ -24,6 +29,8 @@ pub use assignment::AssignmentChecker;
 pub use assignment::ConstraintAssignmentInfo;
 pub use bounds::BoundsChecker;
 pub use patterns::PatternInferrer;
+pub use exhaustiveness::ExhaustivenessChecker;
+pub use narrowing::Narrowing;
 
 // 向后兼容别名
 pub use expressions::ExprInferrer;
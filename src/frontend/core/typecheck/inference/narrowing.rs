@@ -0,0 +1,341 @@
+//! 流敏感类型收窄
+//!
+//! 根据条件或 match 模式推出分支内变量更精确的类型：
+//! - `x != void` 成立时 `x: Option(T)` 收窄为 `T`，不成立时收窄为 `Void`；`x == void` 相反
+//! - `not` 交换两侧，`&&` 合并成立侧，`||` 合并不成立侧
+//! - match 分支中的 `Some(..)` / `None` / 结构体模式收窄被匹配的变量，并给模式绑定赋予类型
+//! - `if x == void { return }` 之后的语句中 `x` 收窄为 `T`
+//!
+//! 收窄结果以新的内层作用域变量呈现，离开分支即失效。
+
+use crate::frontend::core::lexer::tokens::Literal;
+use crate::frontend::core::parser::ast::{
+    constructor_args, BinOp, Block, Expr, Pattern, Stmt, StmtKind, UnOp,
+};
+use crate::frontend::core::typecheck::layers::equivalence::is_subtype;
+use crate::frontend::core::types::{MonoType, PolyType, TypeConstraintSolver};
+
+use super::scope::ScopeManager;
+
+/// 条件成立/不成立时各变量的收窄类型
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Narrowing {
+    pub when_true: Vec<(String, MonoType)>,
+    pub when_false: Vec<(String, MonoType)>,
+}
+
+impl Narrowing {
+    /// 分析条件表达式
+    pub fn of_condition(
+        cond: &Expr,
+        scope: &ScopeManager,
+        solver: &TypeConstraintSolver,
+    ) -> Self {
+        match cond {
+            Expr::BinOp {
+                op: op @ (BinOp::Eq | BinOp::Neq),
+                left,
+                right,
+                ..
+            } => {
+                let name = match (left.as_ref(), right.as_ref()) {
+                    (Expr::Var(name, _), Expr::Lit(Literal::Void, _))
+                    | (Expr::Lit(Literal::Void, _), Expr::Var(name, _)) => name,
+                    _ => return Self::default(),
+                };
+                let Some(poly) = scope.get_var(name) else {
+                    return Self::default();
+                };
+                let Some(present) = without_void(&solver.resolve_type(&poly.body)) else {
+                    return Self::default();
+                };
+                let narrowing = Self {
+                    when_true: vec![(name.to_string(), present)],
+                    when_false: vec![(name.to_string(), MonoType::Void)],
+                };
+                if matches!(op, BinOp::Eq) {
+                    narrowing.negate()
+                } else {
+                    narrowing
+                }
+            }
+            Expr::UnOp {
+                op: UnOp::Not,
+                expr,
+                ..
+            } => Self::of_condition(expr, scope, solver).negate(),
+            Expr::BinOp {
+                op: BinOp::And,
+                left,
+                right,
+                ..
+            } => {
+                let mut when_true = Self::of_condition(left, scope, solver).when_true;
+                when_true.extend(Self::of_condition(right, scope, solver).when_true);
+                Self {
+                    when_true,
+                    when_false: Vec::new(),
+                }
+            }
+            Expr::BinOp {
+                op: BinOp::Or,
+                left,
+                right,
+                ..
+            } => {
+                let mut when_false = Self::of_condition(left, scope, solver).when_false;
+                when_false.extend(Self::of_condition(right, scope, solver).when_false);
+                Self {
+                    when_true: Vec::new(),
+                    when_false,
+                }
+            }
+            _ => Self::default(),
+        }
+    }
+
+    /// 条件取反
+    pub fn negate(self) -> Self {
+        Self {
+            when_true: self.when_false,
+            when_false: self.when_true,
+        }
+    }
+}
+
+/// if 语句之后的语句中成立的收窄
+///
+/// then 与各 elif 分支都以 return/break/continue 结束时，能走到 if 之后的路径上
+/// 所有条件都不成立。
+pub fn after_if(
+    stmt: &Stmt,
+    scope: &ScopeManager,
+    solver: &TypeConstraintSolver,
+) -> Vec<(String, MonoType)> {
+    let (condition, then_branch, elif_branches) = match &stmt.kind {
+        StmtKind::If {
+            condition,
+            then_branch,
+            elif_branches,
+            ..
+        } => (condition, then_branch, elif_branches),
+        StmtKind::Expr(expr) => match expr.as_ref() {
+            Expr::If {
+                condition,
+                then_branch,
+                elif_branches,
+                ..
+            } => (condition, then_branch, elif_branches),
+            _ => return Vec::new(),
+        },
+        _ => return Vec::new(),
+    };
+    if !exits(then_branch) || !elif_branches.iter().all(|(_, block)| exits(block)) {
+        return Vec::new();
+    }
+    let mut facts = Narrowing::of_condition(condition, scope, solver).when_false;
+    for (cond, _) in elif_branches {
+        facts.extend(Narrowing::of_condition(cond, scope, solver).when_false);
+    }
+    facts
+}
+
+/// 代码块是否总以跳转结束
+fn exits(block: &Block) -> bool {
+    match block.stmts.last().map(|stmt| &stmt.kind) {
+        Some(StmtKind::Return(_)) => true,
+        Some(StmtKind::Expr(expr)) => matches!(
+            expr.as_ref(),
+            Expr::Return(..) | Expr::Break(..) | Expr::Continue(..)
+        ),
+        _ => false,
+    }
+}
+
+/// 去掉类型中的 `void` 部分：`Option(T)` → `T`，`T | Void` → `T`
+///
+/// 类型不含 `void` 时返回 None。
+pub fn without_void(ty: &MonoType) -> Option<MonoType> {
+    match ty {
+        MonoType::Option(inner) => Some(inner.as_ref().clone()),
+        MonoType::Union(members) => {
+            let (voids, mut rest): (Vec<&MonoType>, Vec<&MonoType>) = members
+                .iter()
+                .partition(|m| is_subtype(m, &MonoType::Void, None));
+            if voids.is_empty() || rest.is_empty() {
+                return None;
+            }
+            Some(if rest.len() == 1 {
+                rest.remove(0).clone()
+            } else {
+                MonoType::Union(rest.into_iter().cloned().collect())
+            })
+        }
+        _ => None,
+    }
+}
+
+/// match 分支的模式成立时，被匹配值的类型
+pub fn narrow_by_pattern(
+    ty: &MonoType,
+    pattern: &Pattern,
+) -> Option<MonoType> {
+    match (ty, pattern) {
+        (_, Pattern::Guard { pattern, .. }) => narrow_by_pattern(ty, pattern),
+        (MonoType::Option(inner), Pattern::Union { variant, .. }) if variant == "Some" => {
+            Some(inner.as_ref().clone())
+        }
+        (MonoType::Option(_), Pattern::Identifier(name)) if name == "None" => Some(MonoType::Void),
+        (MonoType::Option(_) | MonoType::Union(_), Pattern::Literal(Literal::Void)) => {
+            Some(MonoType::Void)
+        }
+        (MonoType::Union(members), Pattern::Struct { name, .. }) if !name.is_empty() => members
+            .iter()
+            .find(|m| matches!(m, MonoType::Struct(s) if s.name == *name))
+            .cloned(),
+        _ => None,
+    }
+}
+
+/// match 分支的模式不成立时，被匹配值剩余的类型
+///
+/// 后续分支据此收窄：`void => ..` 之后的分支中 `Option(T)` 只剩 `T`。
+/// 带守卫的分支可能不成立，不排除任何类型。
+pub fn exclude_pattern(
+    ty: &MonoType,
+    pattern: &Pattern,
+) -> Option<MonoType> {
+    match (ty, pattern) {
+        (MonoType::Option(inner), Pattern::Identifier(name)) if name == "None" => {
+            Some(inner.as_ref().clone())
+        }
+        (MonoType::Option(_) | MonoType::Union(_), Pattern::Literal(Literal::Void)) => {
+            without_void(ty)
+        }
+        (
+            MonoType::Option(_),
+            Pattern::Union {
+                variant,
+                pattern: sub,
+                ..
+            },
+        ) if variant == "Some" && sub.as_deref().is_none_or(is_irrefutable) => Some(MonoType::Void),
+        (MonoType::Union(members), Pattern::Struct { name, fields, .. })
+            if !name.is_empty() && fields.iter().all(|(_, _, sub)| is_irrefutable(sub)) =>
+        {
+            let mut rest: Vec<MonoType> = members
+                .iter()
+                .filter(|m| !matches!(m, MonoType::Struct(s) if s.name == *name))
+                .cloned()
+                .collect();
+            match rest.len() {
+                n if n == members.len() || n == 0 => None,
+                1 => rest.pop(),
+                _ => Some(MonoType::Union(rest)),
+            }
+        }
+        _ => None,
+    }
+}
+
+/// 总能匹配的模式：通配符或变量绑定
+fn is_irrefutable(pattern: &Pattern) -> bool {
+    match pattern {
+        Pattern::Wildcard => true,
+        Pattern::Identifier(name) => name != "None" && !name.contains('.'),
+        _ => false,
+    }
+}
+
+/// 模式中的变量绑定及其类型
+///
+/// 能从被匹配值的类型推出的绑定使用该类型，其余使用 `fresh()` 生成的类型变量。
+pub fn pattern_bindings(
+    ty: &MonoType,
+    pattern: &Pattern,
+    fresh: &mut dyn FnMut() -> MonoType,
+    out: &mut Vec<(String, MonoType)>,
+) {
+    match pattern {
+        Pattern::Wildcard | Pattern::Literal(_) => {}
+        Pattern::Identifier(name) => {
+            // `None` 与 `Color.red` 是构造器而非绑定
+            let is_ctor = name == "None"
+                || name.contains('.')
+                || matches!(ty, MonoType::Enum(e) if e.variants.contains(name));
+            if !is_ctor {
+                out.push((name.clone(), ty.clone()));
+            }
+        }
+        Pattern::Guard { pattern, .. } => pattern_bindings(ty, pattern, fresh, out),
+        Pattern::Or(alts) => {
+            if let Some(first) = alts.first() {
+                pattern_bindings(ty, first, fresh, out);
+            }
+        }
+        Pattern::Tuple(elems) => {
+            for (i, elem) in elems.iter().enumerate() {
+                let elem_ty = match ty {
+                    MonoType::Tuple(types) if types.len() == elems.len() => types[i].clone(),
+                    _ => fresh(),
+                };
+                pattern_bindings(&elem_ty, elem, fresh, out);
+            }
+        }
+        Pattern::Struct { fields, .. } => {
+            let narrowed = narrow_by_pattern(ty, pattern).unwrap_or_else(|| ty.clone());
+            for (field, _, sub) in fields {
+                let field_ty = match &narrowed {
+                    MonoType::Struct(s) => s
+                        .fields
+                        .iter()
+                        .find(|(name, _)| name == field)
+                        .map(|(_, t)| t.clone()),
+                    _ => None,
+                }
+                .unwrap_or_else(&mut *fresh);
+                pattern_bindings(&field_ty, sub, fresh, out);
+            }
+        }
+        Pattern::Union {
+            variant,
+            pattern: Some(sub),
+            ..
+        } => {
+            // 位置构造器模式 `Point(0, y)`：参数按字段声明顺序绑定
+            if let MonoType::Struct(s) = ty {
+                if let Some(args) = (s.name == *variant)
+                    .then(|| constructor_args(Some(sub), s.fields.len()))
+                    .flatten()
+                {
+                    for (arg, (_, field_ty)) in args.into_iter().zip(&s.fields) {
+                        pattern_bindings(field_ty, arg, fresh, out);
+                    }
+                    return;
+                }
+            }
+            let payload_ty = match (ty, variant.as_str()) {
+                (MonoType::Option(inner), "Some") => inner.as_ref().clone(),
+                (MonoType::Result(ok, _), "Ok") => ok.as_ref().clone(),
+                (MonoType::Result(_, err), "Err") => err.as_ref().clone(),
+                _ => fresh(),
+            };
+            pattern_bindings(&payload_ty, sub, fresh, out);
+        }
+        Pattern::Union { pattern: None, .. } => {}
+    }
+}
+
+/// 在当前作用域中以收窄后的类型重新声明变量（保留可变性与定义位置）
+pub fn apply(
+    scope: &mut ScopeManager,
+    facts: &[(String, MonoType)],
+) {
+    for (name, ty) in facts {
+        let Some(info) = scope.get_var_info(name) else {
+            continue;
+        };
+        let (is_mut, span) = (info.is_mut, info.definition_span);
+        scope.add_var(name.clone(), PolyType::mono(ty.clone()), is_mut, span);
+    }
+}
@@ -0,0 +1,197 @@
+//! 定宽数值类型规则
+//!
+//! `Int8`/`Int16`/`Int32`/`Int64` 与 `Float32`/`Float64` 之间：
+//! - 同类且位宽不减的转换是隐式的（`Int8 → Int32`、`Float32 → Float64`）
+//! - 收窄必须显式写 `as`
+//! - 无后缀字面量按上下文取类型，但必须落在目标类型的范围内
+//! - 混合位宽的算术取较宽的一侧；一侧为无后缀字面量时取另一侧的类型
+//! - 浮点与整数混合的算术、比较中，整数一侧隐式转为浮点
+
+use crate::frontend::core::lexer::tokens::Literal;
+use crate::frontend::core::parser::ast::{Expr, UnOp};
+use crate::frontend::core::types::MonoType;
+use crate::util::diagnostic::{Diagnostic, ErrorCodeDefinition};
+
+/// 内置数值类型名 → 类型
+pub fn numeric_type_named(name: &str) -> Option<MonoType> {
+    match name {
+        "Int" | "int" | "Int64" | "int64" | "i64" => Some(MonoType::Int(64)),
+        "Int32" | "int32" | "i32" => Some(MonoType::Int(32)),
+        "Int16" | "int16" | "i16" => Some(MonoType::Int(16)),
+        "Int8" | "int8" | "i8" => Some(MonoType::Int(8)),
+        "Float" | "float" | "Float64" | "float64" | "f64" => Some(MonoType::Float(64)),
+        "Float32" | "float32" | "f32" => Some(MonoType::Float(32)),
+        _ => None,
+    }
+}
+
+/// 把 `TypeRef("Int32")` 之类的内置数值类型名解析为具体类型，其余原样返回
+pub fn resolve_numeric(ty: &MonoType) -> MonoType {
+    match ty {
+        MonoType::TypeRef(name) => numeric_type_named(name).unwrap_or_else(|| ty.clone()),
+        _ => ty.clone(),
+    }
+}
+
+/// 运行时需要收窄到位宽的类型（`Int8/16/32`、`Float32`）
+pub fn is_sized(ty: &MonoType) -> bool {
+    matches!(ty, MonoType::Int(bits) if *bits < 64) || matches!(ty, MonoType::Float(32))
+}
+
+/// 隐式拓宽：同类且位宽不减
+pub fn widens_to(
+    from: &MonoType,
+    to: &MonoType,
+) -> bool {
+    match (from, to) {
+        (MonoType::Int(a), MonoType::Int(b)) | (MonoType::Float(a), MonoType::Float(b)) => a <= b,
+        _ => false,
+    }
+}
+
+/// 无后缀数值字面量（允许前置负号）
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UnsuffixedLiteral {
+    Int(i128),
+    Float(f64),
+}
+
+/// 提取无后缀数值字面量；`42i32` 这类带后缀的字面量已是 `as` 表达式，不在此列
+pub fn unsuffixed_literal(expr: &Expr) -> Option<UnsuffixedLiteral> {
+    match expr {
+        Expr::Lit(Literal::Int(n), _) => Some(UnsuffixedLiteral::Int(*n)),
+        Expr::Lit(Literal::Float(f), _) => Some(UnsuffixedLiteral::Float(*f)),
+        Expr::UnOp {
+            op: UnOp::Neg,
+            expr,
+            ..
+        } => match unsuffixed_literal(expr)? {
+            UnsuffixedLiteral::Int(n) => Some(UnsuffixedLiteral::Int(-n)),
+            UnsuffixedLiteral::Float(f) => Some(UnsuffixedLiteral::Float(-f)),
+        },
+        _ => None,
+    }
+}
+
+/// 整数字面量必须落在 `Int(bits)` 的范围内
+pub fn check_literal_fits(
+    value: i128,
+    ty: &MonoType,
+) -> Result<(), Diagnostic> {
+    let MonoType::Int(bits) = ty else {
+        return Ok(());
+    };
+    let bits = (*bits).clamp(1, 64) as u32;
+    let min = -(1i128 << (bits - 1));
+    let max = (1i128 << (bits - 1)) - 1;
+    if value < min || value > max {
+        return Err(ErrorCodeDefinition::numeric_literal_out_of_range(
+            &value.to_string(),
+            &ty.to_string(),
+        )
+        .build());
+    }
+    Ok(())
+}
+
+/// 字面量按目标类型取值：整数字面量检查范围，浮点字面量只能给浮点类型
+///
+/// 返回 `Ok(true)` 表示字面量可以直接取目标类型。
+pub fn literal_adapts_to(
+    expr: &Expr,
+    target: &MonoType,
+) -> Result<bool, Diagnostic> {
+    match (unsuffixed_literal(expr), target) {
+        (Some(UnsuffixedLiteral::Int(n)), MonoType::Int(_)) => {
+            check_literal_fits(n, target)?;
+            Ok(true)
+        }
+        (Some(_), MonoType::Float(_)) => Ok(true),
+        _ => Ok(false),
+    }
+}
+
+/// 定宽算术的结果类型
+///
+/// 两侧同为整数或同为浮点时返回结果类型；其他组合返回 `None`，交给常规规则处理。
+/// `Any` 不能直接参与算术，须先用 `as?` 取回具体类型。
+pub fn arith_result(
+    left_ty: &MonoType,
+    left: &Expr,
+    right_ty: &MonoType,
+    right: &Expr,
+) -> Result<Option<MonoType>, Diagnostic> {
+    if matches!(left_ty, MonoType::Any) || matches!(right_ty, MonoType::Any) {
+        return Err(ErrorCodeDefinition::unsupported_operation("arithmetic", "Any").build());
+    }
+    let (a, b) = match (left_ty, right_ty) {
+        (MonoType::Int(a), MonoType::Int(b)) | (MonoType::Float(a), MonoType::Float(b)) => (*a, *b),
+        _ => return Ok(None),
+    };
+    if a == b {
+        return Ok(Some(left_ty.clone()));
+    }
+    if literal_adapts_to(left, right_ty)? {
+        return Ok(Some(right_ty.clone()));
+    }
+    if literal_adapts_to(right, left_ty)? {
+        return Ok(Some(left_ty.clone()));
+    }
+    Ok(Some(if a >= b {
+        left_ty.clone()
+    } else {
+        right_ty.clone()
+    }))
+}
+
+/// 浮点与整数混合的算术、比较：返回需要隐式转为浮点的整数操作数及结果浮点类型
+///
+/// 两侧不是一浮一整时返回 `None`。
+pub fn int_operand_to_float<'e>(
+    left_ty: &MonoType,
+    left: &'e Expr,
+    right_ty: &MonoType,
+    right: &'e Expr,
+) -> Option<(&'e Expr, MonoType)> {
+    match (left_ty, right_ty) {
+        (MonoType::Float(_), MonoType::Int(_)) => Some((right, left_ty.clone())),
+        (MonoType::Int(_), MonoType::Float(_)) => Some((left, right_ty.clone())),
+        _ => None,
+    }
+}
+
+/// 检查 `expr as target`：数值目标只接受数值、`Bool`、`Char` 来源，字面量还需在范围内；
+/// `Any` 来源须改用 `as?`
+pub fn check_cast(
+    source_ty: &MonoType,
+    expr: &Expr,
+    target: &MonoType,
+) -> Result<(), Diagnostic> {
+    if matches!(source_ty, MonoType::Any) && !matches!(target, MonoType::Any) {
+        return Err(ErrorCodeDefinition::invalid_numeric_cast("Any", &target.to_string()).build());
+    }
+    if !matches!(target, MonoType::Int(_) | MonoType::Float(_)) {
+        return Ok(());
+    }
+    let convertible = matches!(
+        source_ty,
+        MonoType::Int(_)
+            | MonoType::Float(_)
+            | MonoType::Bool
+            | MonoType::Char
+            | MonoType::TypeVar(_)
+            | MonoType::TypeRef(_)
+            | MonoType::Refined { .. }
+    );
+    if !convertible {
+        return Err(ErrorCodeDefinition::invalid_numeric_cast(
+            &source_ty.to_string(),
+            &target.to_string(),
+        )
+        .build());
+    }
+    if let Some(UnsuffixedLiteral::Int(n)) = unsuffixed_literal(expr) {
+        check_literal_fits(n, target)?;
+    }
+    Ok(())
+}
@@ -54,6 +54,7 @@ impl PatternInferrer {
                     crate::frontend::core::lexer::tokens::Literal::String(_) => {
                         Ok(MonoType::String)
                     }
+                    crate::frontend::core::lexer::tokens::Literal::Void => Ok(MonoType::Void),
                 }
             }
             ast::Pattern::Identifier(name) => {
@@ -86,6 +87,7 @@ impl PatternInferrer {
                     field_mutability,
                     field_has_default: Vec::new(),
                     interfaces: vec![],
+                    assoc_items: Vec::new(),
                 }))
             }
             ast::Pattern::Or(patterns) => {
@@ -16,6 +16,8 @@ pub struct VarInfo {
     pub moved: bool,
     /// 变量定义位置的 span（用于 LSP 跳转定义）
     pub definition_span: Span,
+    /// 是否由 `use` 导入
+    pub imported: bool,
 }
 
 // Need to import Span
@@ -25,6 +27,7 @@ pub struct VarInfo {
 ///
 /// 管理变量的作用域栈，支持嵌套作用域的进入与退出。
 /// 整个类型检查流程共享同一个 ScopeManager 实例。
+#[derive(Clone)]
 pub struct ScopeManager {
     scopes: Vec<HashMap<String, VarInfo>>,
 }
@@ -70,6 +73,25 @@ impl ScopeManager {
                 is_mut,
                 moved: false,
                 definition_span,
+                imported: false,
+            },
+        );
+    }
+
+    /// 添加 `use` 导入的名字到当前作用域
+    pub fn add_import(
+        &mut self,
+        name: String,
+        poly: PolyType,
+    ) {
+        self.scopes.last_mut().unwrap().insert(
+            name,
+            VarInfo {
+                poly,
+                is_mut: false,
+                moved: false,
+                definition_span: Span::default(),
+                imported: true,
             },
         );
     }
@@ -167,6 +189,7 @@ impl ScopeManager {
                 is_mut: false,
                 moved: false,
                 definition_span: Span::default(),
+                imported: false,
             },
         );
     }
@@ -209,6 +232,20 @@ impl ScopeManager {
         result
     }
 
+    /// 获取所有可见变量（内层遮蔽外层）及其所在层级，0 为模块级
+    pub fn visible_vars(&self) -> Vec<(&str, &VarInfo, usize)> {
+        let mut result: HashMap<&str, (&VarInfo, usize)> = HashMap::new();
+        for (level, scope) in self.scopes.iter().enumerate() {
+            for (name, info) in scope {
+                result.insert(name.as_str(), (info, level));
+            }
+        }
+        result
+            .into_iter()
+            .map(|(name, (info, level))| (name, info, level))
+            .collect()
+    }
+
     /// 获取当前（最内层）作用域的变量，保留可变性
     /// 用于 promote_loop_vars_to_parent_scope
     pub fn current_scope_vars(&self) -> HashMap<String, VarInfo> {
@@ -7,13 +7,16 @@
 
 use crate::util::diagnostic::{Diagnostic, ErrorCodeDefinition};
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use crate::frontend::module::{Export, ExportKind, ModuleInfo};
 use crate::frontend::module::registry::ModuleRegistry;
+use crate::frontend::core::typecheck::layers::equivalence::{is_subtype, upcasts_to_any};
 use crate::frontend::core::types::{MonoType, PolyType, TypeConstraintSolver};
 use crate::frontend::core::parser::ast::{Block, Expr, Param, Stmt};
 use crate::middle::passes::mono::instance::InstantiationRequest;
 
+use super::narrowing::{self, Narrowing};
+use super::numeric;
 use super::scope::ScopeManager;
 
 /// 语句检查器
@@ -21,13 +24,16 @@ use super::scope::ScopeManager;
 /// 负责检查函数体中的语句和表达式的类型正确性。
 /// 使用统一的 ScopeManager 实现作用域管理。
 ///
-/// ## 错误收集模式
+/// ## 错误收集
 ///
-/// 支持两种错误处理模式：
-/// - **短路模式**（默认）：遇到错误立即返回，保持向后兼容
-/// - **收集模式**：收集所有错误后统一返回，用于 LSP 支持
+/// 语句列表中某条语句出错时，诊断进入 `collected_errors`，检查继续进行，
+/// 由调用方通过 `drain_collected_errors` 取出。
 ///
-/// 通过 `set_collect_all_errors(true)` 启用收集模式。
+/// ## 并行检查
+///
+/// 签名完整标注的非泛型函数彼此独立：`fork` 出的副本可在其他线程检查函数体，
+/// 再由 `merge_fork` 按源码顺序并回。
+#[derive(Clone)]
 pub struct StatementChecker {
     /// 约束求解器
     solver: TypeConstraintSolver,
@@ -44,10 +50,8 @@ pub struct StatementChecker {
     module_registry: ModuleRegistry,
     /// 是否在顶层作用域（模块级，非函数内部）
     is_top_level: bool,
-    /// 累积的错误（收集模式下使用）
+    /// 累积的错误（语句出错后继续检查时收集）
     collected_errors: Vec<Diagnostic>,
-    /// 是否启用错误收集模式（收集所有错误而非短路返回）
-    collect_all_errors: bool,
     /// 保存函数体的变量（在退出函数作用域后保留）
     function_local_vars: HashMap<String, PolyType>,
     /// 当前函数的 Result 错误类型栈（用于 `?` 运算符约束）
@@ -66,6 +70,12 @@ pub struct StatementChecker {
     type_defs: HashMap<String, MonoType>,
     /// 实例化请求（收集所有泛型函数实例化需求）
     pub instantiation_requests: Vec<InstantiationRequest>,
+    /// 定宽算术表达式的结果类型（运算符 span → 类型）
+    pub sized_arith: HashMap<crate::util::span::Span, MonoType>,
+    /// 隐式转为浮点的整数表达式（表达式 span）
+    pub int_to_float: HashSet<crate::util::span::Span>,
+    /// 表达式与变量声明名的推断类型（span → 类型，未展开类型变量）
+    expr_types: HashMap<crate::util::span::Span, MonoType>,
 }
 
 impl StatementChecker {
@@ -80,7 +90,6 @@ impl StatementChecker {
             module_registry: ModuleRegistry::with_std(),
             is_top_level: true,
             collected_errors: Vec::new(),
-            collect_all_errors: false,
             function_local_vars: HashMap::new(),
             result_err_stack: Vec::new(),
             expected_return_type: None,
@@ -88,6 +97,9 @@ impl StatementChecker {
             method_bindings: HashMap::new(),
             type_defs: HashMap::new(),
             instantiation_requests: Vec::new(),
+            sized_arith: HashMap::new(),
+            int_to_float: HashSet::new(),
+            expr_types: HashMap::new(),
         }
     }
 
@@ -113,6 +125,8 @@ impl StatementChecker {
                 match name.as_str() {
                     "Int" | "int" | "Int64" | "int64" | "i64" => return MonoType::Int(64),
                     "Int32" | "int32" | "i32" => return MonoType::Int(32),
+                    "Int16" | "int16" | "i16" => return MonoType::Int(16),
+                    "Int8" | "int8" | "i8" => return MonoType::Int(8),
                     "Float" | "float" | "Float64" | "float64" | "f64" => {
                         return MonoType::Float(64)
                     }
@@ -156,41 +170,71 @@ impl StatementChecker {
     ///
     /// 当 type_annotation 为 `List(Int)` 时，查找 `List` 的泛型模板，
     /// 将类型参数 `T` 替换为 `Int`，返回展开后的结构体类型。
+    /// 嵌套的用户泛型实例（如 `List(Box(Int))` 中的 `Box(Int)`）一并展开。
     fn try_instantiate_generic_type(
         &self,
         type_ann: &crate::frontend::core::parser::ast::Type,
     ) -> Option<MonoType> {
+        let expanded = self.annotation_type(type_ann);
+        (expanded != MonoType::from(type_ann.clone())).then_some(expanded)
+    }
+
+    /// 类型标注对应的类型，其中的用户泛型实例已展开
+    ///
+    /// 与内置类型同名的用户泛型（如自定义的 `List`）以用户定义为准。
+    fn annotation_type(
+        &self,
+        type_ann: &crate::frontend::core::parser::ast::Type,
+    ) -> MonoType {
+        use crate::frontend::core::parser::ast::Type;
         use crate::frontend::core::typecheck::TypeEnvironment;
-        match type_ann {
-            crate::frontend::core::parser::ast::Type::Generic { name, args, .. } => {
-                let def = self.generic_type_defs.get(name)?;
+        if let Type::Generic { name, args, .. } = type_ann {
+            if let Some(def) = self.generic_type_defs.get(name) {
+                if let Some(base) = Self::proof_fn_base_type(def) {
+                    // 证明函数标注（`IsPositive(5)`）按基类型推断，约束由证明层检查
+                    return base;
+                }
                 let arg_types: Vec<MonoType> =
-                    args.iter().map(|a| MonoType::from(a.clone())).collect();
-                TypeEnvironment::instantiate_generic_type(def, &arg_types)
+                    args.iter().map(|a| self.annotation_type(a)).collect();
+                if let Some(instance) = TypeEnvironment::instantiate_generic_type(def, &arg_types) {
+                    return instance;
+                }
             }
-            _ => None,
         }
+        let ty = MonoType::from(type_ann.clone());
+        // 别名指向定宽数值时，按目标类型处理（`Byte: Type = Int8`）
+        let sized = match &ty {
+            MonoType::TypeRef(name) => self.solver.resolve_alias(name).unwrap_or(ty.clone()),
+            _ => ty.clone(),
+        };
+        let sized = numeric::resolve_numeric(&sized);
+        if numeric::is_sized(&sized) {
+            return sized;
+        }
+        TypeEnvironment::expand_generic_instances(&ty, &self.generic_type_defs)
     }
 
-    fn current_result_err(&self) -> Option<MonoType> {
-        self.result_err_stack.last().cloned().flatten()
-    }
-
-    /// 设置是否启用错误收集模式
+    /// 证明函数（`IsPositive: (x: Int) -> Type = { x > 0 }`）用作标注时的基类型
     ///
-    /// 启用后，类型检查不会在遇到第一个错误时短路返回，
-    /// 而是尽可能多地收集错误，最终统一返回。
-    /// 这对于 LSP 诊断非常重要，因为用户需要看到所有错误。
-    pub fn set_collect_all_errors(
-        &mut self,
-        collect: bool,
-    ) {
-        self.collect_all_errors = collect;
+    /// 证明函数的函数体是谓词而不是字段定义，登记出的模板是只有 const 参数的空结构体。
+    fn proof_fn_base_type(
+        def: &crate::frontend::core::typecheck::environment::GenericTypeDef
+    ) -> Option<MonoType> {
+        use crate::frontend::core::types::const_data::ConstKind;
+        match &def.poly.body {
+            MonoType::Struct(s) if s.fields.is_empty() && def.type_param_names.is_empty() => {
+                def.poly.const_binders.first().map(|b| match b.kind {
+                    ConstKind::Int(_) => MonoType::Int(64),
+                    ConstKind::Bool => MonoType::Bool,
+                    ConstKind::Float(_) => MonoType::Float(64),
+                })
+            }
+            _ => None,
+        }
     }
 
-    /// 获取是否启用了错误收集模式
-    pub fn is_collect_all_errors(&self) -> bool {
-        self.collect_all_errors
+    fn current_result_err(&self) -> Option<MonoType> {
+        self.result_err_stack.last().cloned().flatten()
     }
 
     /// 获取累积的错误
@@ -203,12 +247,96 @@ impl StatementChecker {
         std::mem::take(&mut self.collected_errors)
     }
 
+    /// 判断顶层语句是否是可独立检查的函数
+    ///
+    /// 要求签名完整标注且不含泛型：函数体不会约束签名中的类型变量，
+    /// 检查结果与其他函数体互不影响。
+    pub fn is_independent_fn(stmt: &Stmt) -> bool {
+        use crate::frontend::core::parser::ast::{
+            const_generic_params, GenericParamKind, StmtKind, Type,
+        };
+        let StmtKind::Binding {
+            type_name: None,
+            method_type: None,
+            generic_params,
+            type_annotation: Some(annotation @ Type::Fn { return_type, .. }),
+            ..
+        } = &stmt.kind
+        else {
+            return false;
+        };
+        // 带值参数的签名中，参数也以 Const 形式出现在 generic_params 里，
+        // 这里按 check_fn_stmt 的方式只识别真正的类型参数与 const 泛型参数
+        let is_generic = generic_params
+            .iter()
+            .any(|p| matches!(p.kind, GenericParamKind::Type))
+            || !const_generic_params(generic_params, Some(annotation)).is_empty();
+        !is_generic
+            && !matches!(return_type.as_ref(), Type::MetaType { .. })
+            && !crate::frontend::core::types::substitute::contains_type_vars(&MonoType::from(
+                annotation.clone(),
+            ))
+    }
+
+    /// 复制当前状态，用于在其他线程独立检查一个函数
+    ///
+    /// 副本不携带累积的错误、局部变量和实例化请求，合并时只并回新增部分。
+    pub fn fork(&self) -> Self {
+        let mut fork = self.clone();
+        fork.collected_errors.clear();
+        fork.function_local_vars.clear();
+        fork.instantiation_requests.clear();
+        fork.sized_arith.clear();
+        fork.int_to_float.clear();
+        fork.expr_types.clear();
+        fork
+    }
+
+    /// 并回 `fork` 检查函数 `name` 的结果，返回其中累积的错误
+    ///
+    /// 各副本的求解器互不相通，类型在并回前用副本自己的求解器展开。
+    pub fn merge_fork(
+        &mut self,
+        name: &str,
+        fork: Self,
+    ) -> Vec<Diagnostic> {
+        if let Some(poly) = fork.scope.get_var(name) {
+            let ty = fork.solver.resolve_type(&poly.body);
+            self.scope.add_var(
+                name.to_string(),
+                PolyType::mono(ty),
+                false,
+                crate::util::span::Span::default(),
+            );
+        }
+        self.checked_functions.insert(name.to_string(), true);
+        for (var, poly) in fork.function_local_vars {
+            let ty = fork.solver.resolve_type(&poly.body);
+            self.function_local_vars
+                .insert(var, PolyType { body: ty, ..poly });
+        }
+        for mut request in fork.instantiation_requests {
+            for arg in &mut request.type_args {
+                *arg = fork.solver.resolve_type(arg);
+            }
+            self.instantiation_requests.push(request);
+        }
+        for (span, ty) in fork.sized_arith {
+            self.sized_arith.insert(span, fork.solver.resolve_type(&ty));
+        }
+        self.int_to_float.extend(fork.int_to_float);
+        for (span, ty) in fork.expr_types {
+            self.expr_types.insert(span, fork.solver.resolve_type(&ty));
+        }
+        fork.collected_errors
+    }
+
     /// 检查是否有累积的错误
     pub fn has_collected_errors(&self) -> bool {
         !self.collected_errors.is_empty()
     }
 
-    /// 收集错误（在收集模式下添加到列表，否则忽略）
+    /// 收集错误
     fn collect_error(
         &mut self,
         error: Diagnostic,
@@ -276,6 +404,7 @@ impl StatementChecker {
             field_mutability: Vec::new(),
             field_has_default: Vec::new(),
             interfaces: vec![],
+            assoc_items: Vec::new(),
         })
     }
 
@@ -285,12 +414,8 @@ impl StatementChecker {
         export: &Export,
     ) {
         let ty = self.export_type(export);
-        self.scope.add_var(
-            binding_name.to_string(),
-            PolyType::mono(ty),
-            false,
-            crate::util::span::Span::default(),
-        );
+        self.scope
+            .add_import(binding_name.to_string(), PolyType::mono(ty));
     }
 
     fn process_use_stmt(
@@ -316,23 +441,15 @@ impl StatementChecker {
             (None, None) => {
                 let module_alias = path.split('.').next_back().unwrap_or(path);
                 let module_ty = self.module_as_struct_type(&module, module_alias);
-                self.scope.add_var(
-                    module_alias.to_string(),
-                    PolyType::mono(module_ty),
-                    false,
-                    crate::util::span::Span::default(),
-                );
+                self.scope
+                    .add_import(module_alias.to_string(), PolyType::mono(module_ty));
             }
             // use path as alias
             (None, Some(aliases)) if aliases.len() == 1 => {
                 let module_alias = &aliases[0];
                 let module_ty = self.module_as_struct_type(&module, module_alias);
-                self.scope.add_var(
-                    module_alias.to_string(),
-                    PolyType::mono(module_ty),
-                    false,
-                    crate::util::span::Span::default(),
-                );
+                self.scope
+                    .add_import(module_alias.to_string(), PolyType::mono(module_ty));
             }
             // use path.{a, b}
             (Some(item_names), None) => {
@@ -434,6 +551,42 @@ impl StatementChecker {
         self.scope.update_var(name, PolyType::mono(new_ty));
     }
 
+    /// 重新赋值后的变量类型
+    ///
+    /// 定宽数值变量保持声明的宽度：字面量和更窄的同类值可直接写入，更宽的值需显式 `as`。
+    /// 浮点变量写入整数时整数隐式转为浮点。
+    fn reassigned_type(
+        &mut self,
+        name: &str,
+        value: &Expr,
+        value_ty: MonoType,
+    ) -> Result<MonoType, Box<Diagnostic>> {
+        let Some(current) = self.scope.get_var(name).map(|poly| poly.body.clone()) else {
+            return Ok(value_ty);
+        };
+        let current = numeric::resolve_numeric(&self.solver.resolve_type(&current));
+        if matches!(
+            (&current, &self.solver.resolve_type(&value_ty)),
+            (MonoType::Float(_), MonoType::Int(_))
+        ) {
+            self.record_int_to_float(value);
+            return Ok(current);
+        }
+        if !numeric::is_sized(&current) {
+            return Ok(value_ty);
+        }
+        if numeric::literal_adapts_to(value, &current)? || numeric::widens_to(&value_ty, &current) {
+            return Ok(current);
+        }
+        if numeric::widens_to(&current, &value_ty) {
+            return Err(Box::new(
+                ErrorCodeDefinition::type_mismatch(&current.to_string(), &value_ty.to_string())
+                    .build(),
+            ));
+        }
+        Ok(value_ty)
+    }
+
     /// 进入新的作用域
     pub fn enter_scope(&mut self) {
         self.scope.enter_scope();
@@ -444,10 +597,17 @@ impl StatementChecker {
         self.scope.exit_scope();
     }
 
+    /// 退出代码块作用域，保留块内变量的类型供后续阶段（所有权检查、IR 生成）查询
+    fn exit_block_scope(&mut self) {
+        for (name, info) in self.scope.current_scope_vars() {
+            self.function_local_vars.entry(name).or_insert(info.poly);
+        }
+        self.scope.exit_scope();
+    }
+
     /// 检查函数定义
     ///
-    /// 在收集模式下，遇到错误不会短路返回，而是继续检查后续语句，
-    /// 尽可能收集所有错误。
+    /// 函数体内的错误进入 `collected_errors`，不会中断后续语句的检查。
     pub fn check_fn_def(
         &mut self,
         name: &str,
@@ -474,7 +634,7 @@ impl StatementChecker {
             let param_ty = param
                 .ty
                 .as_ref()
-                .map(|t| MonoType::from(t.clone()))
+                .map(|t| self.annotation_type(t))
                 .unwrap_or_else(|| self.solver.new_var());
             self.scope.add_var(
                 param.name.clone(),
@@ -484,55 +644,18 @@ impl StatementChecker {
             );
         }
 
-        if self.collect_all_errors {
-            // 收集模式：收集所有错误，不短路
-            let mut first_err = None;
-            for stmt in &body.stmts {
-                if let Err(e) = self.check_stmt(stmt) {
-                    if first_err.is_none() {
-                        first_err = Some(e.clone());
-                    }
-                    self.collect_error(*e);
-                }
-            }
-
-            // 退出函数作用域前，保存所有变量（解决退出作用域后变量丢失的问题）
-            for (name, poly) in self.scope.vars() {
-                self.function_local_vars.insert(name, poly);
-            }
-
-            // 退出函数作用域
-            self.scope.exit_scope();
-            self.is_top_level = was_top_level;
-
-            match first_err {
-                Some(e) => Err(e),
-                None => Ok(()),
-            }
-        } else {
-            // 短路模式：遇到第一个错误立即返回
-            let mut err = None;
-            for stmt in &body.stmts {
-                if let Err(e) = self.check_stmt(stmt) {
-                    err = Some(e);
-                    break;
-                }
-            }
+        self.check_stmt_list(&body.stmts);
 
-            // 退出函数作用域前，保存所有变量（解决退出作用域后变量丢失的问题）
-            for (name, poly) in self.scope.vars() {
-                self.function_local_vars.insert(name, poly);
-            }
+        // 退出函数作用域前，保存所有变量（解决退出作用域后变量丢失的问题）
+        for (name, poly) in self.scope.vars() {
+            self.function_local_vars.insert(name, poly);
+        }
 
-            // 退出函数作用域
-            self.scope.exit_scope();
-            self.is_top_level = was_top_level;
+        // 退出函数作用域
+        self.scope.exit_scope();
+        self.is_top_level = was_top_level;
 
-            match err {
-                Some(e) => Err(e),
-                None => Ok(()),
-            }
-        }
+        Ok(())
     }
 
     /// 检查语句
@@ -550,6 +673,7 @@ impl StatementChecker {
                 params,
                 body,
                 is_pub: _,
+                attributes: _,
                 method_type,
             } => {
                 // 根据是否有 type_name 来区分方法绑定和其他绑定
@@ -558,7 +682,28 @@ impl StatementChecker {
                     stmts: body.clone(),
                     span: stmt.span,
                 };
-                if type_name.is_some() {
+                if let Some(item_ty) = method_type
+                    .as_ref()
+                    .filter(|t| crate::frontend::core::parser::ast::is_assoc_item_type(t))
+                {
+                    // 关联类型没有函数体；关联常量按 `() -> T` 的零参方法检查其值
+                    if crate::frontend::core::parser::ast::is_meta_type(item_ty) {
+                        return Ok(());
+                    }
+                    let const_sig = crate::frontend::core::parser::ast::Type::Fn {
+                        params: Vec::new(),
+                        return_type: Box::new(item_ty.clone()),
+                    };
+                    self.check_fn_stmt(
+                        name,
+                        Some(&const_sig),
+                        generic_params,
+                        params,
+                        body,
+                        body_block,
+                        stmt.span,
+                    )
+                } else if type_name.is_some() {
                     // 方法绑定：使用 method_type 作为签名
                     // method_type 包含完整的 (params) -> ReturnType 签名
                     let type_ann = method_type.as_ref();
@@ -599,6 +744,7 @@ impl StatementChecker {
                         // 从 type_annotation 或 body 提取结构体字段
                         let mut fields = Vec::new();
                         let mut interfaces = Vec::new();
+                        let mut assoc_items = Vec::new();
 
                         // 情况 1：type_annotation 是 Type::Struct（直接结构体定义）
                         if let Some(crate::frontend::core::parser::ast::Type::Struct {
@@ -610,6 +756,9 @@ impl StatementChecker {
                             for field in ast_fields {
                                 let field_ty = MonoType::from(field.ty.clone());
                                 fields.push((field.name.clone(), field_ty));
+                                if field.is_assoc {
+                                    assoc_items.push(field.name.clone());
+                                }
                             }
                             interfaces = ast_interfaces.clone();
                         } else {
@@ -639,6 +788,7 @@ impl StatementChecker {
                                 field_mutability: vec![false; field_count],
                                 field_has_default: vec![false; field_count],
                                 interfaces,
+                                assoc_items,
                             });
                         self.type_defs.insert(name.to_string(), struct_ty.clone());
                         self.scope.add_var(
@@ -724,6 +874,24 @@ impl StatementChecker {
                         return Ok(());
                     }
 
+                    // 新类型：类型名同时是构造函数 `UserId(42)`，接受底层类型，返回新类型
+                    if let Some(crate::frontend::core::parser::ast::Type::Newtype(inner)) =
+                        type_annotation
+                    {
+                        let underlying =
+                            self.resolve_type_ref_type(&MonoType::from(inner.as_ref().clone()));
+                        self.scope.add_var(
+                            name.to_string(),
+                            PolyType::mono(MonoType::Fn {
+                                params: vec![underlying],
+                                return_type: Box::new(MonoType::TypeRef(name.to_string())),
+                            }),
+                            false,
+                            stmt.span,
+                        );
+                        return Ok(());
+                    }
+
                     // 函数绑定（包括空参数的函数）
                     // 使用 type_annotation 作为签名
                     self.check_fn_stmt(
@@ -739,17 +907,34 @@ impl StatementChecker {
             }
             crate::frontend::core::parser::ast::StmtKind::Var {
                 name,
+                name_span,
                 type_annotation,
                 initializer,
                 is_mut,
                 ..
-            } => self.check_var_stmt(
-                name,
-                type_annotation.as_ref(),
-                &[],
-                initializer.as_deref(),
-                *is_mut,
-            ),
+            } => {
+                let result = self.check_var_stmt(
+                    name,
+                    type_annotation.as_ref(),
+                    &[],
+                    initializer.as_deref(),
+                    *is_mut,
+                );
+                if let Some(ty) = self.scope.get_var(name).map(|poly| poly.body.clone()) {
+                    self.record_expr_type(*name_span, &ty);
+                }
+                // 初始化失败时仍以未知类型绑定名称，避免后续引用报连锁的未定义错误
+                if result.is_err() && !self.scope.var_in_any_scope(name) {
+                    let ty = self.solver.new_var();
+                    self.scope.add_var(
+                        name.clone(),
+                        PolyType::mono(ty),
+                        *is_mut,
+                        crate::util::span::Span::default(),
+                    );
+                }
+                result
+            }
             crate::frontend::core::parser::ast::StmtKind::For {
                 var,
                 var_mut,
@@ -827,6 +1012,11 @@ impl StatementChecker {
                     }
                 }
             }
+            // `=> expr` 形式的函数体会脱糖为 return 语句
+            crate::frontend::core::parser::ast::StmtKind::Return(Some(expr)) => {
+                self.check_expr(expr)?;
+                Ok(())
+            }
             // 错误恢复占位符：报告错误但不 panic
             crate::frontend::core::parser::ast::StmtKind::Error(span) => Err(Box::new(
                 ErrorCodeDefinition::invalid_syntax("缺失语句")
@@ -859,13 +1049,14 @@ impl StatementChecker {
                 if let Expr::Var(name, _) = left.as_ref() {
                     if self.scope.var_in_any_scope(name) {
                         // 统一变量类型并写回 scope，确保后续类型推断正确
+                        let right_ty = self.reassigned_type(name, right, right_ty)?;
                         self.assign_var(name, right_ty);
                     } else {
                         // 新变量：创建类型变量并统一
                         let ty = self.solver.new_var();
                         let _ = self.solver.unify(&ty, &right_ty);
                         self.scope.add_var(
-                            name.clone(),
+                            name.to_string(),
                             PolyType::mono(ty),
                             false,
                             crate::util::span::Span::default(),
@@ -914,6 +1105,22 @@ impl StatementChecker {
                 )
             })
             .collect();
+        // const 泛型参数（`(N: Int) -> (x: Int) -> Int` 中的 N）在调用处显式给出，
+        // 函数体内作为常量可见
+        let const_generic_params = crate::frontend::core::parser::ast::const_generic_params(
+            generic_params,
+            type_annotation,
+        );
+        let is_generic_fn = !type_generic_params.is_empty() || !const_generic_params.is_empty();
+        let const_param_types: Vec<MonoType> = const_generic_params
+            .iter()
+            .map(|p| match &p.kind {
+                crate::frontend::core::parser::ast::GenericParamKind::Const { const_type } => {
+                    self.annotation_type(const_type)
+                }
+                _ => MonoType::Int(64),
+            })
+            .collect();
 
         // 将函数自身注册到变量环境中
         if let Some(type_ann) = type_annotation {
@@ -924,9 +1131,9 @@ impl StatementChecker {
             {
                 let fn_param_types: Vec<MonoType> = param_types
                     .iter()
-                    .map(|t| MonoType::from(t.clone()))
+                    .map(|t| self.annotation_type(t))
                     .collect();
-                let fn_return_type = MonoType::from(*return_type.clone());
+                let fn_return_type = self.annotation_type(return_type);
 
                 // 泛型函数处理：剥离类型级参数，替换 TypeRef 为类型变量
                 let (final_params, final_ret) = if !type_generic_params.is_empty()
@@ -940,6 +1147,10 @@ impl StatementChecker {
 
                     let inner_fn_ty = Self::substitute_type_refs(fn_return_type.clone(), &subst);
                     match inner_fn_ty {
+                        // 同时带 const 参数时只剥离类型参数：`fill(3)(x)`
+                        inner @ MonoType::Fn { .. } if !const_param_types.is_empty() => {
+                            (const_param_types.clone(), inner)
+                        }
                         MonoType::Fn {
                             params: inner_params,
                             return_type: inner_ret,
@@ -967,7 +1178,7 @@ impl StatementChecker {
                 .iter()
                 .map(|p| {
                     p.ty.as_ref()
-                        .map(|t| MonoType::from(t.clone()))
+                        .map(|t| self.annotation_type(t))
                         .unwrap_or_else(|| self.solver.new_var())
                 })
                 .collect();
@@ -986,7 +1197,7 @@ impl StatementChecker {
 
         // 进入函数 Result 上下文（用于 `?` 运算符检查）
         // 对于泛型函数，需要从内层 return_type 提取 Result 类型
-        let fn_result_err = if !type_generic_params.is_empty() {
+        let fn_result_err = if is_generic_fn {
             // 泛型函数：从内层 Fn 的 return_type 提取
             type_annotation.and_then(|t| match t {
                 crate::frontend::core::parser::ast::Type::Fn { return_type, .. } => {
@@ -1029,7 +1240,7 @@ impl StatementChecker {
 
         // Set expected return type for return statement type checking
         // 对于泛型函数，预期返回类型是内层 Fn 的返回类型
-        let fn_expected_ret = if !type_generic_params.is_empty() {
+        let fn_expected_ret = if is_generic_fn {
             type_annotation.and_then(|t| match t {
                 crate::frontend::core::parser::ast::Type::Fn { return_type, .. } => {
                     match return_type.as_ref() {
@@ -1096,34 +1307,79 @@ impl StatementChecker {
 
         // 对于泛型函数，将类型级参数（MetaType 类型）替换为新的类型变量
         // 这些参数是泛型类型参数声明合并到值级参数的结果
+        let type_param_names: Vec<&str> = type_generic_params
+            .iter()
+            .map(|p| p.name.as_str())
+            .collect();
         let owned_value_params: Vec<Param>;
-        let value_params_slice: &[Param] = if !type_generic_params.is_empty() {
-            owned_value_params = params
-                .iter()
-                .map(|p| {
-                    let is_meta = matches!(
-                        p.ty.as_ref().map(|t| MonoType::from(t.clone())),
-                        Some(MonoType::MetaType { .. })
-                    );
-                    if is_meta {
-                        // MetaType 参数：移除类型标注，让 HM 推断
-                        Param {
-                            name: p.name.clone(),
-                            ty: None,
-                            is_mut: p.is_mut,
-                            span: p.span,
+        let value_params_slice: &[Param] = if is_generic_fn {
+            // const 参数作为函数体作用域中的常量
+            let const_params = const_generic_params.iter().map(|p| Param {
+                name: p.name.clone(),
+                ty: match &p.kind {
+                    crate::frontend::core::parser::ast::GenericParamKind::Const { const_type } => {
+                        Some((**const_type).clone())
+                    }
+                    _ => None,
+                },
+                is_mut: false,
+                span: _span,
+            });
+            let value_params = params.iter().map(|p| {
+                // 受约束类型参数（`(T: Shape) -> (shape: T)`）按约束接口检查函数体；
+                // 多重约束（`T: Shape + Clone`）取其中唯一的用户接口
+                let bound = p.ty.as_ref().and_then(|t| match t {
+                    crate::frontend::core::parser::ast::Type::Name { name, .. } => {
+                        let g = type_generic_params.iter().find(|g| &g.name == name)?;
+                        if g.constraints.len() == 1 {
+                            return Some(g.constraints[0].clone());
+                        }
+                        let mut interfaces = g.constraints.iter().filter(|c| {
+                            matches!(c, crate::frontend::core::parser::ast::Type::Name { name, .. }
+                                if self.type_defs.get(name).is_some_and(MonoType::is_constraint))
+                        });
+                        match (interfaces.next(), interfaces.next()) {
+                            (Some(only), None) => Some(only.clone()),
+                            _ => None,
                         }
-                    } else {
-                        p.clone()
                     }
-                })
-                .collect();
+                    _ => None,
+                });
+                if let Some(bound) = bound {
+                    return Param {
+                        ty: Some(bound),
+                        ..p.clone()
+                    };
+                }
+                let is_meta = matches!(
+                    p.ty.as_ref().map(|t| MonoType::from(t.clone())),
+                    Some(MonoType::MetaType { .. })
+                ) || p.ty.as_ref().is_some_and(|t| {
+                    crate::frontend::core::parser::ast::type_mentions_name(t, &type_param_names)
+                });
+                if is_meta {
+                    // MetaType 参数：移除类型标注，让 HM 推断
+                    Param {
+                        name: p.name.clone(),
+                        ty: None,
+                        is_mut: p.is_mut,
+                        span: p.span,
+                    }
+                } else {
+                    p.clone()
+                }
+            });
+            owned_value_params = const_params.chain(value_params).collect();
             &owned_value_params
         } else {
             params
         };
 
+        let first_request = self.instantiation_requests.len();
         let out = self.check_fn_def(name, value_params_slice, &body);
+        if !type_param_names.is_empty() {
+            self.abstract_body_requests(name, first_request, params, &type_param_names);
+        }
 
         // Clear expected return type after function body checking
         self.expected_return_type = None;
@@ -1134,6 +1390,73 @@ impl StatementChecker {
         out
     }
 
+    /// 把泛型函数 `name` 体内记录的实例化请求改写为以其类型参数表示
+    ///
+    /// 函数体按无标注参数推断，声明为 `x: T` 的参数在体内是一个类型变量
+    /// （受约束时是约束接口）。对齐声明类型与推断类型后，把请求中的这些类型换回
+    /// `TypeRef("T")`，由单态化器在特化所在函数时代入具体类型。
+    fn abstract_body_requests(
+        &mut self,
+        name: &str,
+        first_request: usize,
+        params: &[Param],
+        type_params: &[&str],
+    ) {
+        let mut var_params = HashMap::new();
+        let mut bound_params: Vec<(MonoType, MonoType)> = Vec::new();
+        for param in params {
+            let (Some(declared), Some(local)) =
+                (param.ty.as_ref(), self.function_local_vars.get(&param.name))
+            else {
+                continue;
+            };
+            let declared = MonoType::from(declared.clone());
+            let actual = self.solver.resolve_type(&local.body);
+            super::instantiation::zip_types(&declared, &actual, &mut |pattern, actual| {
+                let MonoType::TypeRef(type_param) = pattern else {
+                    return;
+                };
+                if !type_params.contains(&type_param.as_str()) {
+                    return;
+                }
+                match actual {
+                    MonoType::TypeVar(tv) => {
+                        var_params.insert(tv.index(), pattern.clone());
+                    }
+                    MonoType::TypeRef(bound)
+                        if self
+                            .type_defs
+                            .get(bound)
+                            .is_some_and(MonoType::is_constraint) =>
+                    {
+                        bound_params.push((actual.clone(), pattern.clone()));
+                    }
+                    other if other.is_constraint() => {
+                        bound_params.push((actual.clone(), pattern.clone()));
+                    }
+                    _ => {}
+                }
+            });
+        }
+
+        let substituter = crate::frontend::core::types::substitute::Substituter::new();
+        for request in &mut self.instantiation_requests[first_request..] {
+            if request.caller.is_some() {
+                continue;
+            }
+            for arg in &mut request.type_args {
+                let resolved =
+                    substituter.substitute_with_map(&self.solver.resolve_type(arg), &var_params);
+                *arg = bound_params
+                    .iter()
+                    .find(|(bound, _)| *bound == resolved)
+                    .map(|(_, type_param)| type_param.clone())
+                    .unwrap_or(resolved);
+            }
+            request.caller = Some(name.to_string());
+        }
+    }
+
     /// 替换 MonoType 中的 TypeRef 名称为对应的类型变量
     ///
     /// 用于泛型函数类型推断：将 TypeRef("T") 替换为 solver 中的新类型变量。
@@ -1179,6 +1502,15 @@ impl StatementChecker {
             MonoType::Range { elem_type } => MonoType::Range {
                 elem_type: Box::new(Self::substitute_type_refs(*elem_type, subst)),
             },
+            MonoType::AssocType {
+                host_type,
+                assoc_name,
+                assoc_args,
+            } => MonoType::AssocType {
+                host_type: Box::new(Self::substitute_type_refs(*host_type, subst)),
+                assoc_name,
+                assoc_args,
+            },
             other => other,
         }
     }
@@ -1251,7 +1583,13 @@ impl StatementChecker {
                     (&resolved_ann, &resolved_init),
                     (MonoType::Float(_), MonoType::Int(_))
                 );
-                if !is_int_to_float {
+                if is_int_to_float {
+                    self.record_int_to_float(init_expr);
+                }
+                // 定宽数值：无后缀字面量按标注取类型，同类拓宽隐式进行
+                let is_numeric_widening = numeric::literal_adapts_to(init_expr, &resolved_ann)?
+                    || numeric::widens_to(&resolved_init, &resolved_ann);
+                if !is_int_to_float && !is_numeric_widening {
                     let unify_result = self.solver.unify(&resolved_init, &resolved_ann);
                     if unify_result.is_err() {
                         // Unify failed — check structural subtyping (interface assignment)
@@ -1268,7 +1606,35 @@ impl StatementChecker {
                             }
                             _ => false,
                         };
-                        if !is_structural_subtype && !is_generic_constructor {
+                        // 可选类型提升：`T` 与 `void` 都可以赋给 `Option(T)`
+                        let is_optional_promotion = match &resolved_ann {
+                            MonoType::Option(inner) => is_subtype(
+                                &resolved_init,
+                                &MonoType::Option(Box::new(self.resolve_type_ref_type(inner))),
+                                None,
+                            ),
+                            _ => false,
+                        };
+                        // 匿名记录：字段更多的结构体可以赋给字段更少的记录（宽度子类型）
+                        let is_record_subtype = resolved_ann.is_record()
+                            && is_subtype(
+                                &self
+                                    .solver
+                                    .resolve_type(&self.resolve_type_ref_type(&resolved_init)),
+                                &self.solver.resolve_type(&resolved_ann),
+                                None,
+                            );
+                        // 上转为 Any：`x: Any = 1`、`xs: List(Any) = [1, "a"]`
+                        let is_any_upcast = upcasts_to_any(
+                            &self.solver.resolve_type(&resolved_init),
+                            &resolved_ann,
+                        );
+                        if !is_structural_subtype
+                            && !is_generic_constructor
+                            && !is_optional_promotion
+                            && !is_record_subtype
+                            && !is_any_upcast
+                        {
                             return Err(Box::new(
                                 ErrorCodeDefinition::type_mismatch(
                                     &format!("{}", ann_ty),
@@ -1285,11 +1651,20 @@ impl StatementChecker {
                     && matches!(resolved_init, MonoType::Struct(_))
                 {
                     resolved_init
+                } else if numeric::is_sized(&resolved_ann) {
+                    resolved_ann
                 } else {
                     ann_ty
                 }
             }
-            (Some(init_expr), None) => self.check_expr(init_expr)?,
+            (Some(init_expr), None) => {
+                let init_ty = self.check_expr(init_expr)?;
+                if self.scope.var_in_any_scope(name) {
+                    self.reassigned_type(name, init_expr, init_ty)?
+                } else {
+                    init_ty
+                }
+            }
             (None, Some(type_ann)) => self
                 .try_instantiate_generic_type(type_ann)
                 .unwrap_or_else(|| MonoType::from(type_ann.clone())),
@@ -1318,7 +1693,7 @@ impl StatementChecker {
 
     /// 检查 for 语句
     ///
-    /// 在收集模式下，循环体内的错误会被收集而非短路。
+    /// 循环体内的错误会被收集而非短路。
     fn check_for_stmt(
         &mut self,
         var: &str,
@@ -1353,35 +1728,9 @@ impl StatementChecker {
             crate::util::span::Span::default(),
         );
 
-        if self.collect_all_errors {
-            let mut first_err = None;
-            for stmt in &body.stmts {
-                if let Err(e) = self.check_stmt(stmt) {
-                    if first_err.is_none() {
-                        first_err = Some(e.clone());
-                    }
-                    self.collect_error(*e);
-                }
-            }
-            self.scope.exit_scope();
-            match first_err {
-                Some(e) => Err(e),
-                None => Ok(()),
-            }
-        } else {
-            let mut err = None;
-            for stmt in &body.stmts {
-                if let Err(e) = self.check_stmt(stmt) {
-                    err = Some(e);
-                    break;
-                }
-            }
-            self.scope.exit_scope();
-            match err {
-                Some(e) => Err(e),
-                None => Ok(()),
-            }
-        }
+        self.check_stmt_list(&body.stmts);
+        self.exit_block_scope();
+        Ok(())
     }
 
     /// 检查 if 语句
@@ -1402,10 +1751,19 @@ impl StatementChecker {
             ));
         }
 
-        self.check_block(then_branch)?;
+        let narrowing = Narrowing::of_condition(condition, &self.scope, &self.solver);
+        self.check_narrowed_block(then_branch, &narrowing.when_true)?;
 
-        for (elif_cond, _) in elif_branches {
-            let elif_cond_ty = self.check_expr(elif_cond)?;
+        // 前面所有条件都不成立时的收窄，随 elif 链累积
+        let mut otherwise = narrowing.when_false;
+        for (elif_cond, elif_block) in elif_branches {
+            self.scope.enter_scope();
+            narrowing::apply(&mut self.scope, &otherwise);
+            let elif_cond_ty = self.check_expr(elif_cond);
+            let elif_narrowing = Narrowing::of_condition(elif_cond, &self.scope, &self.solver);
+            self.scope.exit_scope();
+
+            let elif_cond_ty = elif_cond_ty?;
             if elif_cond_ty != MonoType::Bool {
                 return Err(Box::new(
                     ErrorCodeDefinition::type_mismatch("bool", &format!("{}", elif_cond_ty))
@@ -1413,59 +1771,75 @@ impl StatementChecker {
                         .build(),
                 ));
             }
-        }
 
-        for (_, elif_block) in elif_branches {
-            self.check_block(elif_block)?;
+            let mut facts = otherwise.clone();
+            facts.extend(elif_narrowing.when_true);
+            self.check_narrowed_block(elif_block, &facts)?;
+            otherwise.extend(elif_narrowing.when_false);
         }
 
         if let Some(else_block) = else_branch {
-            self.check_block(else_block)?;
+            self.check_narrowed_block(else_block, &otherwise)?;
         }
 
         Ok(())
     }
 
+    /// 在带有收窄变量的外层作用域中检查代码块
+    fn check_narrowed_block(
+        &mut self,
+        block: &Block,
+        facts: &[(String, MonoType)],
+    ) -> Result<(), Box<Diagnostic>> {
+        if facts.is_empty() {
+            return self.check_block(block);
+        }
+        self.scope.enter_scope();
+        narrowing::apply(&mut self.scope, facts);
+        let result = self.check_block(block);
+        self.scope.exit_scope();
+        result
+    }
+
     /// 检查代码块（创建独立作用域）
     ///
-    /// 在收集模式下，代码块内的错误会被收集而非短路。
+    /// 代码块内的错误会被收集而非短路。
     fn check_block(
         &mut self,
         block: &Block,
     ) -> Result<(), Box<Diagnostic>> {
         self.scope.enter_scope();
 
-        if self.collect_all_errors {
-            let mut first_err = None;
-            for stmt in &block.stmts {
-                if let Err(e) = self.check_stmt(stmt) {
-                    if first_err.is_none() {
-                        first_err = Some(e.clone());
-                    }
-                    self.collect_error(*e);
-                }
-            }
-            self.scope.exit_scope();
-            match first_err {
-                Some(e) => Err(e),
-                None => Ok(()),
-            }
-        } else {
-            let mut err = None;
-            for stmt in &block.stmts {
-                if let Err(e) = self.check_stmt(stmt) {
-                    err = Some(e);
-                    break;
-                }
-            }
-            self.scope.exit_scope();
-            match err {
-                Some(e) => Err(e),
-                None => Ok(()),
+        self.check_stmt_list(&block.stmts);
+        self.exit_block_scope();
+        Ok(())
+    }
+
+    /// 依次检查语句列表
+    ///
+    /// 语句出错时把诊断放入 `collected_errors` 并继续检查后续语句，
+    /// 使一次编译报告所有相互独立的错误。
+    fn check_stmt_list(
+        &mut self,
+        stmts: &[Stmt],
+    ) {
+        for stmt in stmts {
+            if let Err(e) = self.check_stmt(stmt) {
+                self.collect_error(*e);
             }
+            self.narrow_after(stmt);
         }
     }
 
+    /// 语句之后成立的收窄（如以 return 结束的判空 if）作用于块内后续语句
+    fn narrow_after(
+        &mut self,
+        stmt: &Stmt,
+    ) {
+        let facts = narrowing::after_if(stmt, &self.scope, &self.solver);
+        narrowing::apply(&mut self.scope, &facts);
+    }
+
     /// 检查表达式
     ///
     /// 直接使用同一个 ScopeManager 和 Solver，确保变量状态正确传递。
@@ -1474,6 +1848,63 @@ impl StatementChecker {
     pub fn check_expr(
         &mut self,
         expr: &Expr,
+    ) -> Result<MonoType, Box<Diagnostic>> {
+        let ty = self.check_expr_kind(expr)?;
+        self.record_expr_type(super::expressions::expr_span(expr), &ty);
+        Ok(ty)
+    }
+
+    /// 记录 span 处的推断类型（供 `TypeCheckResult::expr_types` 位置查询）
+    fn record_expr_type(
+        &mut self,
+        span: crate::util::span::Span,
+        ty: &MonoType,
+    ) {
+        if !span.is_dummy() {
+            self.expr_types.insert(span, ty.clone());
+        }
+    }
+
+    /// 记录隐式转为浮点的整数表达式
+    fn record_int_to_float(
+        &mut self,
+        expr: &Expr,
+    ) {
+        let span = super::expressions::expr_span(expr);
+        if !span.is_dummy() {
+            self.int_to_float.insert(span);
+        }
+    }
+
+    /// 全部已记录的类型，类型变量按当前求解结果展开
+    /// 展开类型实参后的实例化请求；类型实参仍含类型变量的请求无法特化，丢弃
+    pub fn resolved_instantiation_requests(&self) -> Vec<InstantiationRequest> {
+        self.instantiation_requests
+            .iter()
+            .filter_map(|request| {
+                let mut request = request.clone();
+                for arg in &mut request.type_args {
+                    *arg = self.solver.resolve_type(arg);
+                }
+                let unresolved = request
+                    .type_args
+                    .iter()
+                    .any(crate::frontend::core::types::substitute::contains_type_vars);
+                (!unresolved).then_some(request)
+            })
+            .collect()
+    }
+
+    pub fn resolved_expr_types(&self) -> HashMap<crate::util::span::Span, MonoType> {
+        self.expr_types
+            .iter()
+            .map(|(span, ty)| (*span, self.solver.resolve_type(ty)))
+            .collect()
+    }
+
+    fn check_expr_kind(
+        &mut self,
+        expr: &Expr,
     ) -> Result<MonoType, Box<Diagnostic>> {
         match expr {
             // 变量：直接从 scope 中读取
@@ -1482,11 +1913,13 @@ impl StatementChecker {
                     // 直接返回 scope 中的类型
                     Ok(poly.body)
                 } else {
-                    Err(Box::new(
-                        ErrorCodeDefinition::unknown_variable(name)
-                            .at(*span)
-                            .build(),
-                    ))
+                    Err(Box::new(super::did_you_mean::unknown_variable(
+                        name,
+                        *span,
+                        &self.scope,
+                        &self.native_signatures,
+                        None,
+                    )))
                 }
             }
             // 列表字面量：直接处理
@@ -1511,13 +1944,14 @@ impl StatementChecker {
                 op,
                 left,
                 right,
-                span: _,
+                span,
             } => {
                 use crate::frontend::core::parser::ast::BinOp;
                 let right_ty = self.check_expr(right)?;
 
                 if matches!(op, BinOp::Assign) {
                     if let Expr::Var(var_name, _) = left.as_ref() {
+                        let right_ty = self.reassigned_type(var_name, right, right_ty)?;
                         self.assign_var(var_name, right_ty);
                     }
                     return Ok(MonoType::Void);
@@ -1528,12 +1962,24 @@ impl StatementChecker {
 
                 match op {
                     BinOp::Add | BinOp::Sub | BinOp::Mul | BinOp::Div => {
-                        if let (MonoType::Int(_), MonoType::Int(_)) = (&left_ty, &right_ty) {
-                            Ok(left_ty)
-                        } else if let (MonoType::Float(_), MonoType::Float(_)) =
-                            (&left_ty, &right_ty)
+                        let (l, r) = (
+                            numeric::resolve_numeric(&self.solver.resolve_type(&left_ty)),
+                            numeric::resolve_numeric(&self.solver.resolve_type(&right_ty)),
+                        );
+                        if let Some(ty) =
+                            super::expressions::newtype_operands(&self.solver, op, &l, &r)?
+                        {
+                            Ok(ty)
+                        } else if let Some(ty) = numeric::arith_result(&l, left, &r, right)? {
+                            if numeric::is_sized(&ty) && !span.is_dummy() {
+                                self.sized_arith.insert(*span, ty.clone());
+                            }
+                            Ok(ty)
+                        } else if let Some((int_expr, float_ty)) =
+                            numeric::int_operand_to_float(&l, left, &r, right)
                         {
-                            Ok(left_ty)
+                            self.record_int_to_float(int_expr);
+                            Ok(float_ty)
                         } else if let (MonoType::String, MonoType::String) = (&left_ty, &right_ty) {
                             Ok(MonoType::String)
                         } else if let (MonoType::List(left_elem), MonoType::List(right_elem)) =
@@ -1574,6 +2020,9 @@ impl StatementChecker {
                         let result = inferrer.infer_expr(expr).map_err(Box::new);
                         self.instantiation_requests
                             .extend(inferrer.instantiation_requests);
+                        self.sized_arith.extend(inferrer.sized_arith);
+                        self.int_to_float.extend(inferrer.int_to_float);
+                        self.expr_types.extend(inferrer.expr_types);
                         result
                     }
                 }
@@ -1595,6 +2044,9 @@ impl StatementChecker {
                 let result = inferrer.infer_expr(expr).map_err(Box::new);
                 self.instantiation_requests
                     .extend(inferrer.instantiation_requests);
+                self.sized_arith.extend(inferrer.sized_arith);
+                self.int_to_float.extend(inferrer.int_to_float);
+                self.expr_types.extend(inferrer.expr_types);
                 result
             }
         }
@@ -98,6 +98,7 @@ fn test_check_constraint_empty_constraint() {
         field_mutability: vec![],
         field_has_default: vec![],
         interfaces: vec![],
+        assoc_items: Vec::new(),
     });
 
     // Act
@@ -141,6 +142,7 @@ fn test_check_constraint_missing_method() {
         field_mutability: vec![false],
         field_has_default: vec![false],
         interfaces: vec![],
+        assoc_items: Vec::new(),
     });
     let constraint = MonoType::Struct(StructType {
         name: "Drawable".to_string(),
@@ -155,6 +157,7 @@ fn test_check_constraint_missing_method() {
         field_mutability: vec![],
         field_has_default: vec![],
         interfaces: vec![],
+        assoc_items: Vec::new(),
     });
 
     // Act
@@ -182,6 +185,7 @@ fn test_check_constraint_signature_mismatch() {
         field_mutability: vec![false],
         field_has_default: vec![false],
         interfaces: vec![],
+        assoc_items: Vec::new(),
     });
     let constraint = MonoType::Struct(StructType {
         name: "Drawable".to_string(),
@@ -196,6 +200,7 @@ fn test_check_constraint_signature_mismatch() {
         field_mutability: vec![],
         field_has_default: vec![],
         interfaces: vec![],
+        assoc_items: Vec::new(),
     });
 
     // Act
@@ -235,6 +240,7 @@ fn test_check_constraint_with_method_binding() {
         field_mutability: vec![false],
         field_has_default: vec![false],
         interfaces: vec![],
+        assoc_items: Vec::new(),
     });
     let constraint = MonoType::Struct(StructType {
         name: "Drawable".to_string(),
@@ -249,6 +255,7 @@ fn test_check_constraint_with_method_binding() {
         field_mutability: vec![],
         field_has_default: vec![],
         interfaces: vec![],
+        assoc_items: Vec::new(),
     });
 
     // Act
@@ -287,6 +294,7 @@ fn test_check_trait_bounds_dup_struct_auto_derive() {
         field_mutability: vec![false, false],
         field_has_default: vec![false, false],
         interfaces: vec![],
+        assoc_items: Vec::new(),
     });
 
     // Act & Assert
@@ -315,6 +323,7 @@ fn test_check_trait_bounds_dup_struct_auto_derive_fails() {
         field_mutability: vec![false, false],
         field_has_default: vec![false, false],
         interfaces: vec![],
+        assoc_items: Vec::new(),
     });
 
     // Act & Assert
@@ -346,6 +355,7 @@ fn test_bounds_checker_dup_struct_passes() {
         field_mutability: vec![false, false],
         field_has_default: vec![false, false],
         interfaces: vec![],
+        assoc_items: Vec::new(),
     });
     let bounds = vec!["Dup".to_string()];
 
@@ -378,6 +388,7 @@ fn test_bounds_checker_dup_struct_fails() {
         field_mutability: vec![false, false],
         field_has_default: vec![false, false],
         interfaces: vec![],
+        assoc_items: Vec::new(),
     });
     let bounds = vec!["Dup".to_string()];
 
@@ -413,6 +424,7 @@ fn test_bounds_checker_dup_nested_struct_passes() {
         field_mutability: vec![false, false],
         field_has_default: vec![false, false],
         interfaces: vec![],
+        assoc_items: Vec::new(),
     });
 
     let conn_type = MonoType::Struct(StructType {
@@ -425,6 +437,7 @@ fn test_bounds_checker_dup_nested_struct_passes() {
         field_mutability: vec![false, false],
         field_has_default: vec![false, false],
         interfaces: vec![],
+        assoc_items: Vec::new(),
     });
     let bounds = vec!["Dup".to_string()];
 
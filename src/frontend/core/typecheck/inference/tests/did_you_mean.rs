@@ -0,0 +1,90 @@
+//! 拼写建议测试 — 未知名字的 did-you-mean 修复建议
+
+use std::collections::HashMap;
+
+use crate::frontend::core::typecheck::inference::did_you_mean::unknown_variable;
+use crate::frontend::core::typecheck::inference::scope::ScopeManager;
+use crate::frontend::core::types::{MonoType, PolyType};
+use crate::util::span::{Position, Span};
+
+fn span() -> Span {
+    Span::new(
+        Position::with_offset(3, 5, 20),
+        Position::with_offset(3, 11, 26),
+    )
+}
+
+fn fn_type(arity: usize) -> PolyType {
+    PolyType::mono(MonoType::Fn {
+        params: vec![MonoType::Int(64); arity],
+        return_type: Box::new(MonoType::Void),
+    })
+}
+
+#[test]
+fn test_suggests_local_variable_with_rename_edit() {
+    let mut scope = ScopeManager::new();
+    scope.enter_scope();
+    scope.add_var(
+        "counter".to_string(),
+        PolyType::mono(MonoType::Int(64)),
+        false,
+        Span::default(),
+    );
+
+    let diagnostic = unknown_variable("countr", span(), &scope, &HashMap::new(), None);
+
+    assert_eq!(diagnostic.code, "E1001");
+    let fix = diagnostic.fixes.first().expect("fix-it");
+    assert!(fix.message.contains("`counter`"), "{}", fix.message);
+    assert_eq!(fix.edits.len(), 1);
+    assert_eq!(fix.edits[0].span, span());
+    assert_eq!(fix.edits[0].replacement, "counter");
+}
+
+#[test]
+fn test_call_site_prefers_function_with_matching_arity() {
+    let mut scope = ScopeManager::new();
+    scope.add_var("plot".to_string(), fn_type(1), false, Span::default());
+    scope.add_var("plus".to_string(), fn_type(2), false, Span::default());
+
+    let diagnostic = unknown_variable("plos", span(), &scope, &HashMap::new(), Some(2));
+
+    assert_eq!(diagnostic.fixes[0].edits[0].replacement, "plus");
+}
+
+#[test]
+fn test_suggests_std_function_with_import_edit() {
+    let scope = ScopeManager::new();
+    let mut natives = HashMap::new();
+    natives.insert(
+        "std.time.now".to_string(),
+        MonoType::Fn {
+            params: vec![],
+            return_type: Box::new(MonoType::Int(64)),
+        },
+    );
+
+    let diagnostic = unknown_variable("noww", span(), &scope, &natives, Some(0));
+
+    let fix = diagnostic.fixes.first().expect("fix-it");
+    assert!(fix.message.contains("`std.time`"), "{}", fix.message);
+    assert_eq!(fix.edits[0].replacement, "use std.time.{now}\n");
+    assert_eq!(fix.edits[0].span.start.line, 1);
+    assert_eq!(fix.edits[1].replacement, "now");
+}
+
+#[test]
+fn test_no_fix_when_nothing_is_close() {
+    let mut scope = ScopeManager::new();
+    scope.add_var(
+        "alpha".to_string(),
+        PolyType::mono(MonoType::Int(64)),
+        false,
+        Span::default(),
+    );
+
+    let diagnostic = unknown_variable("zeta", span(), &scope, &HashMap::new(), None);
+
+    assert!(diagnostic.fixes.is_empty());
+}
@@ -0,0 +1,254 @@
+//! 模式穷尽性测试 — 基于语言规范 §4.8
+//!
+//! 覆盖：不穷尽 match 的示例模式（E1030）、不可达分支（E1031）
+
+use std::collections::HashMap;
+
+use crate::frontend::core::lexer::tokens::Literal;
+use crate::frontend::core::parser::ast::Pattern;
+use crate::frontend::core::typecheck::inference::exhaustiveness::{ExhaustivenessChecker, MatchCheck};
+use crate::frontend::core::types::{EnumType, MonoType, StructType};
+
+fn check(
+    scrutinee: &MonoType,
+    patterns: &[Pattern],
+) -> MatchCheck {
+    let type_defs = HashMap::new();
+    let refs: Vec<&Pattern> = patterns.iter().collect();
+    ExhaustivenessChecker::new(&type_defs).check(scrutinee, &refs)
+}
+
+fn int(n: i128) -> Pattern {
+    Pattern::Literal(Literal::Int(n))
+}
+
+fn boolean(b: bool) -> Pattern {
+    Pattern::Literal(Literal::Bool(b))
+}
+
+fn some(inner: Pattern) -> Pattern {
+    Pattern::Union {
+        name: "Some".to_string(),
+        variant: "Some".to_string(),
+        pattern: Some(Box::new(inner)),
+    }
+}
+
+fn none() -> Pattern {
+    Pattern::Identifier("None".to_string())
+}
+
+fn color() -> MonoType {
+    MonoType::Enum(EnumType {
+        name: "Color".to_string(),
+        variants: vec!["red".to_string(), "green".to_string(), "blue".to_string()],
+    })
+}
+
+// ===================================================================
+// 穷尽性
+// ===================================================================
+
+#[test]
+fn test_int_match_with_wildcard_is_exhaustive() {
+    // Act
+    let result = check(&MonoType::Int(64), &[int(1), int(2), Pattern::Wildcard]);
+
+    // Assert
+    assert_eq!(result, MatchCheck::default());
+}
+
+#[test]
+fn test_int_match_without_wildcard_is_missing_wildcard() {
+    // Act
+    let result = check(&MonoType::Int(64), &[int(1), int(2)]);
+
+    // Assert
+    assert_eq!(result.missing.as_deref(), Some("_"));
+}
+
+#[test]
+fn test_bool_match_reports_missing_literal() {
+    // Act
+    let both = check(&MonoType::Bool, &[boolean(true), boolean(false)]);
+    let only_true = check(&MonoType::Bool, &[boolean(true)]);
+
+    // Assert
+    assert_eq!(both.missing, None);
+    assert_eq!(only_true.missing.as_deref(), Some("false"));
+}
+
+#[test]
+fn test_enum_match_reports_missing_variant() {
+    // Act
+    let result = check(
+        &color(),
+        &[
+            Pattern::Identifier("Color.red".to_string()),
+            Pattern::Identifier("green".to_string()),
+        ],
+    );
+
+    // Assert
+    assert_eq!(result.missing.as_deref(), Some("blue"));
+    assert!(result.unreachable.is_empty());
+}
+
+#[test]
+fn test_option_witness_is_nested() {
+    // Arrange: Some(true), None 未覆盖 Some(false)
+    let scrutinee = MonoType::Option(Box::new(MonoType::Bool));
+
+    // Act
+    let result = check(&scrutinee, &[some(boolean(true)), none()]);
+
+    // Assert
+    assert_eq!(result.missing.as_deref(), Some("Some(false)"));
+}
+
+#[test]
+fn test_option_shape_is_inferred_from_patterns() {
+    // Arrange: 被匹配表达式的类型尚未确定
+    let scrutinee = MonoType::TypeVar(crate::frontend::core::types::var::TypeVar::new(0));
+
+    // Act
+    let result = check(&scrutinee, &[some(Pattern::Identifier("x".to_string()))]);
+
+    // Assert
+    assert_eq!(result.missing.as_deref(), Some("None"));
+}
+
+#[test]
+fn test_tuple_match_witness() {
+    // Arrange
+    let scrutinee = MonoType::Tuple(vec![MonoType::Bool, MonoType::Bool]);
+    let patterns = [
+        Pattern::Tuple(vec![boolean(true), Pattern::Wildcard]),
+        Pattern::Tuple(vec![Pattern::Wildcard, boolean(true)]),
+    ];
+
+    // Act
+    let result = check(&scrutinee, &patterns);
+
+    // Assert
+    assert_eq!(result.missing.as_deref(), Some("(false, false)"));
+}
+
+#[test]
+fn test_guarded_arm_does_not_count_toward_coverage() {
+    // Arrange
+    let guarded = Pattern::Guard {
+        pattern: Box::new(Pattern::Identifier("n".to_string())),
+        condition: crate::frontend::core::parser::ast::Expr::Lit(
+            Literal::Bool(true),
+            crate::util::span::Span::dummy(),
+        ),
+    };
+
+    // Act
+    let result = check(&MonoType::Int(64), &[guarded]);
+
+    // Assert
+    assert_eq!(result.missing.as_deref(), Some("_"));
+}
+
+#[test]
+fn test_positional_struct_pattern_maps_fields_in_order() {
+    // Arrange
+    let point = MonoType::Struct(StructType {
+        name: "Point".to_string(),
+        fields: vec![
+            ("x".to_string(), MonoType::Bool),
+            ("y".to_string(), MonoType::Bool),
+        ],
+        methods: HashMap::new(),
+        field_mutability: vec![false, false],
+        field_has_default: vec![false, false],
+        interfaces: vec![],
+        assoc_items: Vec::new(),
+    });
+    let positional = |x: Pattern, y: Pattern| Pattern::Union {
+        name: "Point".to_string(),
+        variant: "Point".to_string(),
+        pattern: Some(Box::new(Pattern::Tuple(vec![x, y]))),
+    };
+
+    // Act
+    let partial = check(
+        &point,
+        &[
+            positional(boolean(true), Pattern::Wildcard),
+            positional(Pattern::Wildcard, boolean(true)),
+        ],
+    );
+    let complete = check(
+        &point,
+        &[
+            positional(boolean(true), Pattern::Wildcard),
+            positional(boolean(false), Pattern::Identifier("y".to_string())),
+        ],
+    );
+
+    // Assert
+    assert_eq!(
+        partial.missing.as_deref(),
+        Some("Point { x: false, y: false }")
+    );
+    assert_eq!(complete, MatchCheck::default());
+}
+
+// ===================================================================
+// 可达性
+// ===================================================================
+
+#[test]
+fn test_arm_after_wildcard_is_unreachable() {
+    // Act
+    let result = check(&MonoType::Int(64), &[Pattern::Wildcard, int(1)]);
+
+    // Assert
+    assert_eq!(result.unreachable, vec![1]);
+    assert_eq!(result.missing, None);
+}
+
+#[test]
+fn test_binding_arm_after_binding_is_unreachable() {
+    // Act
+    let result = check(
+        &MonoType::String,
+        &[
+            Pattern::Identifier("s".to_string()),
+            Pattern::Identifier("t".to_string()),
+        ],
+    );
+
+    // Assert
+    assert_eq!(result.unreachable, vec![1]);
+}
+
+#[test]
+fn test_duplicate_literal_arm_is_unreachable() {
+    // Act
+    let result = check(&MonoType::Int(64), &[int(1), int(1), Pattern::Wildcard]);
+
+    // Assert
+    assert_eq!(result.unreachable, vec![1]);
+}
+
+#[test]
+fn test_wildcard_after_complete_variants_is_unreachable() {
+    // Act
+    let result = check(
+        &color(),
+        &[
+            Pattern::Identifier("red".to_string()),
+            Pattern::Identifier("green".to_string()),
+            Pattern::Identifier("blue".to_string()),
+            Pattern::Wildcard,
+        ],
+    );
+
+    // Assert
+    assert_eq!(result.unreachable, vec![3]);
+    assert_eq!(result.missing, None);
+}
@@ -86,7 +86,7 @@ fn test_infer_string_literal() {
     let mut ctx = TestContext::new();
     let mut inferrer = ctx.inferrer();
     let expr = Expr::Lit(
-        crate::frontend::core::lexer::tokens::Literal::String("hello".to_string()),
+        crate::frontend::core::lexer::tokens::Literal::String("hello".into()),
         Span::dummy(),
     );
 
@@ -135,7 +135,7 @@ fn test_infer_undefined_variable() {
     // Arrange
     let mut ctx = TestContext::new();
     let mut inferrer = ctx.inferrer();
-    let expr = Expr::Var("undefined_var".to_string(), Span::dummy());
+    let expr = Expr::Var("undefined_var".into(), Span::dummy());
 
     // Act
     let result = inferrer.infer_expr(&expr);
@@ -9,14 +9,20 @@
 //! - expressions: 表达式推断
 //! - statements: 语句检查
 //! - patterns: 模式匹配
+//! - exhaustiveness: 模式穷尽性与可达性
+//! - narrowing: 流敏感类型收窄
 //! - bounds: 类型边界
 //! - scope: 作用域
+//! - did_you_mean: 未知名字的拼写建议
 //! - assignment: 赋值检查
 
 mod assignment;
 mod bounds;
 mod bounds_duck;
+mod did_you_mean;
+mod exhaustiveness;
 mod expressions;
+mod narrowing;
 mod patterns;
 mod scope;
 mod statements;
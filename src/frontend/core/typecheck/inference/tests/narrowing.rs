@@ -0,0 +1,238 @@
+//! 流敏感类型收窄测试
+//!
+//! 覆盖：`!= void` / `== void` 条件、`not` / `&&` / `||` 组合、
+//! match 模式收窄与前序分支排除后的剩余类型
+
+use std::collections::HashMap;
+
+use crate::frontend::core::lexer::tokens::Literal;
+use crate::frontend::core::parser::ast::{BinOp, Block, Expr, Pattern, Stmt, StmtKind, UnOp};
+use crate::frontend::core::typecheck::inference::narrowing::{self, Narrowing};
+use crate::frontend::core::typecheck::inference::scope::ScopeManager;
+use crate::frontend::core::types::{MonoType, PolyType, StructType, TypeConstraintSolver};
+use crate::util::span::Span;
+
+fn point() -> MonoType {
+    named_struct("Point")
+}
+
+fn named_struct(name: &str) -> MonoType {
+    MonoType::Struct(StructType {
+        name: name.to_string(),
+        fields: vec![("x".to_string(), MonoType::Int(64))],
+        methods: HashMap::new(),
+        field_mutability: vec![false],
+        field_has_default: vec![false],
+        interfaces: vec![],
+        assoc_items: Vec::new(),
+    })
+}
+
+fn optional_point() -> MonoType {
+    MonoType::Option(Box::new(point()))
+}
+
+fn scope_with(
+    name: &str,
+    ty: MonoType,
+) -> ScopeManager {
+    let mut scope = ScopeManager::new();
+    scope.add_var(name.to_string(), PolyType::mono(ty), false, Span::dummy());
+    scope
+}
+
+fn compare(
+    op: BinOp,
+    name: &str,
+) -> Expr {
+    Expr::BinOp {
+        op,
+        left: Box::new(Expr::Var(name.into(), Span::dummy())),
+        right: Box::new(Expr::Lit(Literal::Void, Span::dummy())),
+        span: Span::dummy(),
+    }
+}
+
+// ===================================================================
+// 条件收窄
+// ===================================================================
+
+#[test]
+fn test_not_void_narrows_option_to_inner() {
+    // Arrange
+    let scope = scope_with("p", optional_point());
+    let solver = TypeConstraintSolver::new();
+
+    // Act
+    let facts = Narrowing::of_condition(&compare(BinOp::Neq, "p"), &scope, &solver);
+
+    // Assert
+    assert_eq!(facts.when_true, vec![("p".to_string(), point())]);
+    assert_eq!(facts.when_false, vec![("p".to_string(), MonoType::Void)]);
+}
+
+#[test]
+fn test_eq_void_and_not_swap_branches() {
+    // Arrange
+    let scope = scope_with("p", optional_point());
+    let solver = TypeConstraintSolver::new();
+    let negated = Expr::UnOp {
+        op: UnOp::Not,
+        expr: Box::new(compare(BinOp::Neq, "p")),
+        span: Span::dummy(),
+    };
+
+    // Act
+    let eq = Narrowing::of_condition(&compare(BinOp::Eq, "p"), &scope, &solver);
+    let not = Narrowing::of_condition(&negated, &scope, &solver);
+
+    // Assert
+    assert_eq!(eq.when_false, vec![("p".to_string(), point())]);
+    assert_eq!(eq, not);
+}
+
+#[test]
+fn test_and_keeps_only_true_side_facts() {
+    // Arrange
+    let mut scope = scope_with("p", optional_point());
+    scope.add_var(
+        "q".to_string(),
+        PolyType::mono(optional_point()),
+        false,
+        Span::dummy(),
+    );
+    let solver = TypeConstraintSolver::new();
+    let cond = Expr::BinOp {
+        op: BinOp::And,
+        left: Box::new(compare(BinOp::Neq, "p")),
+        right: Box::new(compare(BinOp::Neq, "q")),
+        span: Span::dummy(),
+    };
+
+    // Act
+    let facts = Narrowing::of_condition(&cond, &scope, &solver);
+
+    // Assert
+    assert_eq!(facts.when_true.len(), 2);
+    assert!(facts.when_false.is_empty());
+}
+
+#[test]
+fn test_non_optional_variable_is_not_narrowed() {
+    // Arrange
+    let scope = scope_with("n", MonoType::Int(64));
+    let solver = TypeConstraintSolver::new();
+
+    // Act
+    let facts = Narrowing::of_condition(&compare(BinOp::Neq, "n"), &scope, &solver);
+
+    // Assert
+    assert_eq!(facts, Narrowing::default());
+}
+
+#[test]
+fn test_without_void_removes_void_union_member() {
+    // Arrange
+    let union = MonoType::Union(vec![point(), MonoType::Void]);
+
+    // Act & Assert
+    assert_eq!(narrowing::without_void(&union), Some(point()));
+    assert_eq!(narrowing::without_void(&point()), None);
+}
+
+// ===================================================================
+// match 模式收窄
+// ===================================================================
+
+#[test]
+fn test_some_pattern_narrows_and_binds_inner() {
+    // Arrange
+    let pattern = Pattern::Union {
+        name: "Some".to_string(),
+        variant: "Some".to_string(),
+        pattern: Some(Box::new(Pattern::Identifier("pt".to_string()))),
+    };
+    let mut bindings = Vec::new();
+
+    // Act
+    let narrowed = narrowing::narrow_by_pattern(&optional_point(), &pattern);
+    narrowing::pattern_bindings(
+        &optional_point(),
+        &pattern,
+        &mut || MonoType::Void,
+        &mut bindings,
+    );
+
+    // Assert
+    assert_eq!(narrowed, Some(point()));
+    assert_eq!(bindings, vec![("pt".to_string(), point())]);
+}
+
+#[test]
+fn test_struct_pattern_selects_union_member() {
+    // Arrange
+    let union = MonoType::Union(vec![point(), named_struct("Line")]);
+    let pattern = Pattern::Struct {
+        name: "Line".to_string(),
+        fields: vec![],
+    };
+
+    // Act
+    let narrowed = narrowing::narrow_by_pattern(&union, &pattern);
+    let remaining = narrowing::exclude_pattern(&union, &pattern);
+
+    // Assert
+    assert_eq!(narrowed, Some(named_struct("Line")));
+    assert_eq!(remaining, Some(point()));
+}
+
+#[test]
+fn test_void_arm_leaves_inner_type_for_later_arms() {
+    // Act
+    let after_void =
+        narrowing::exclude_pattern(&optional_point(), &Pattern::Literal(Literal::Void));
+    let after_none =
+        narrowing::exclude_pattern(&optional_point(), &Pattern::Identifier("None".to_string()));
+    let after_binding =
+        narrowing::exclude_pattern(&optional_point(), &Pattern::Identifier("p".to_string()));
+
+    // Assert
+    assert_eq!(after_void, Some(point()));
+    assert_eq!(after_none, Some(point()));
+    assert_eq!(after_binding, None);
+}
+
+#[test]
+fn test_early_return_narrows_following_statements() {
+    // Arrange: if p == void { return }
+    let scope = scope_with("p", optional_point());
+    let solver = TypeConstraintSolver::new();
+    let guard = |then_stmts: Vec<Stmt>| Stmt {
+        kind: StmtKind::If {
+            condition: Box::new(compare(BinOp::Eq, "p")),
+            then_branch: Box::new(Block {
+                stmts: then_stmts,
+                span: Span::dummy(),
+            }),
+            elif_branches: vec![],
+            else_branch: None,
+            span: Span::dummy(),
+        },
+        span: Span::dummy(),
+    };
+
+    // Act
+    let returning = narrowing::after_if(
+        &guard(vec![Stmt {
+            kind: StmtKind::Return(None),
+            span: Span::dummy(),
+        }]),
+        &scope,
+        &solver,
+    );
+    let falling_through = narrowing::after_if(&guard(vec![]), &scope, &solver);
+
+    // Assert
+    assert_eq!(returning, vec![("p".to_string(), point())]);
+    assert!(falling_through.is_empty());
+}
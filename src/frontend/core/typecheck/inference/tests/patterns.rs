@@ -86,7 +86,7 @@ fn test_pattern_infer_string_literal() {
 
     // Act
     let result = inferrer
-        .infer_pattern(&Pattern::Literal(Literal::String("hello".to_string())))
+        .infer_pattern(&Pattern::Literal(Literal::String("hello".into())))
         .unwrap();
 
     // Assert
@@ -131,7 +131,7 @@ fn test_pattern_infer_tuple() {
     let mut inferrer = new_inferrer();
     let tuple_pattern = Pattern::Tuple(vec![
         Pattern::Literal(Literal::Int(1)),
-        Pattern::Literal(Literal::String("hello".to_string())),
+        Pattern::Literal(Literal::String("hello".into())),
     ]);
 
     // Act
@@ -179,6 +179,7 @@ fn test_pattern_infer_struct() {
         field_mutability: vec![false, false],
         field_has_default: vec![],
         interfaces: vec![],
+        assoc_items: Vec::new(),
     });
     assert_eq!(result, expected, "Struct 模式应正确推断各字段类型");
 }
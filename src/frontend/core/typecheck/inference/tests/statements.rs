@@ -176,7 +176,7 @@ fn test_check_expr_stmt_assignment_creates_var() {
     let mut checker = make_checker();
     let stmt = make_stmt(StmtKind::Expr(Box::new(Expr::BinOp {
         op: BinOp::Assign,
-        left: Box::new(Expr::Var("x".to_string(), Span::dummy())),
+        left: Box::new(Expr::Var("x".into(), Span::dummy())),
         right: Box::new(Expr::Lit(Literal::Int(99), Span::dummy())),
         span: Span::dummy(),
     })));
@@ -199,7 +199,7 @@ fn test_check_expr_stmt_reassignment_updates_type() {
     let mut checker = make_checker_with_var("x", MonoType::Int(32));
     let stmt = make_stmt(StmtKind::Expr(Box::new(Expr::BinOp {
         op: BinOp::Assign,
-        left: Box::new(Expr::Var("x".to_string(), Span::dummy())),
+        left: Box::new(Expr::Var("x".into(), Span::dummy())),
         right: Box::new(Expr::Lit(Literal::Int(100), Span::dummy())),
         span: Span::dummy(),
     })));
@@ -293,7 +293,7 @@ fn test_check_if_stmt_non_bool_elif_condition() {
 fn test_check_undefined_variable() {
     // Arrange
     let mut checker = make_checker();
-    let expr = Expr::Var("undefined_var".to_string(), Span::dummy());
+    let expr = Expr::Var("undefined_var".into(), Span::dummy());
 
     // Act
     let result = checker.check_expr(&expr);
@@ -511,7 +511,7 @@ fn test_check_fn_def_with_params() {
         },
     ];
     let body = make_block(vec![Stmt {
-        kind: StmtKind::Expr(Box::new(Expr::Var("a".to_string(), Span::dummy()))),
+        kind: StmtKind::Expr(Box::new(Expr::Var("a".into(), Span::dummy()))),
         span: Span::dummy(),
     }]);
 
@@ -537,7 +537,7 @@ fn test_check_fn_def_params_visible_in_body() {
     let body = make_block(vec![Stmt {
         kind: StmtKind::Expr(Box::new(Expr::BinOp {
             op: BinOp::Add,
-            left: Box::new(Expr::Var("x".to_string(), Span::dummy())),
+            left: Box::new(Expr::Var("x".into(), Span::dummy())),
             right: Box::new(Expr::Lit(Literal::Int(1), Span::dummy())),
             span: Span::dummy(),
         })),
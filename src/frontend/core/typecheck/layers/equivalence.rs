@@ -5,6 +5,7 @@
 //! ### 纯助手（无 ProofContext）
 //! - `structurally_equal`
 //! - `is_subtype`            ← 新增
+//! - `upcasts_to_any`
 //!
 //! ### 证明入口（需要 ProofContext）
 //! - `check_type_equivalence`
@@ -35,6 +36,7 @@ pub fn structurally_equal(
         (MonoType::Char, MonoType::Char) => true,
         (MonoType::String, MonoType::String) => true,
         (MonoType::Bytes, MonoType::Bytes) => true,
+        (MonoType::Any, MonoType::Any) => true,
         // 类型引用
         (MonoType::TypeRef(a), MonoType::TypeRef(b)) => a == b,
         // 函数类型
@@ -96,8 +98,12 @@ pub fn is_subtype(
 ) -> bool {
     match (sub, sup) {
         (a, b) if a == b => true,
+        // Any 是顶类型
+        (_, MonoType::Any) => true,
         // List 协变
         (MonoType::List(a), MonoType::List(b)) => is_subtype(a, b, env),
+        // Dict 键不变、值协变
+        (MonoType::Dict(ka, va), MonoType::Dict(kb, vb)) => ka == kb && is_subtype(va, vb, env),
         // 函数：参数逆变 + 返回值协变
         (
             MonoType::Fn {
@@ -121,6 +127,14 @@ pub fn is_subtype(
         (MonoType::Struct(_), MonoType::Struct(_)) if sup.is_constraint() => {
             satisfies_constraint(sub, sup, env)
         }
+        // 匿名记录：宽度子类型，sub 须含 sup 的全部字段（与顺序无关）
+        (MonoType::Struct(a), MonoType::Struct(b)) if sup.is_record() => {
+            b.fields.iter().all(|(name, ty)| {
+                a.fields
+                    .iter()
+                    .any(|(n, t)| n == name && is_subtype(t, ty, env))
+            })
+        }
         (MonoType::Struct(a), MonoType::Struct(b)) => {
             if a.name != b.name || a.fields.len() != b.fields.len() {
                 return false;
@@ -132,6 +146,32 @@ pub fn is_subtype(
         }
         // 非结构体对约束类型：尝试鸭子类型
         (_, MonoType::Struct(_)) if sup.is_constraint() => satisfies_constraint(sub, sup, env),
+        // 可选类型：`void` 与 `T` 都是 `Option(T)` 的子类型
+        (MonoType::Option(a), MonoType::Option(b)) => is_subtype(a, b, env),
+        (MonoType::Void, MonoType::Option(_)) => true,
+        (_, MonoType::Option(inner)) => is_subtype(sub, inner, env),
+        // 联合类型：属于任一成员即可
+        (_, MonoType::Union(members)) => members.iter().any(|m| is_subtype(sub, m, env)),
+        _ => false,
+    }
+}
+
+/// 检查 `sub` 能否隐式上转为含 `Any` 的 `sup`（如 `Int` → `Any`、`List(Int)` → `List(Any)`）
+///
+/// 只在 `sup` 提到 `Any` 时成立；不含 `Any` 的赋值仍走统一。
+pub fn upcasts_to_any(
+    sub: &MonoType,
+    sup: &MonoType,
+) -> bool {
+    mentions_any(sup) && is_subtype(sub, sup, None)
+}
+
+fn mentions_any(ty: &MonoType) -> bool {
+    match ty {
+        MonoType::Any => true,
+        MonoType::List(t) | MonoType::Option(t) => mentions_any(t),
+        MonoType::Dict(_, v) => mentions_any(v),
+        MonoType::Union(members) => members.iter().any(mentions_any),
         _ => false,
     }
 }
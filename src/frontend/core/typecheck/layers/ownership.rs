@@ -7,6 +7,7 @@
 //!
 //! § 品牌树: 令牌派生关系与冲突检测
 //! § 系统谓词清单: 5 种命题（borrow_conflict / use_after_move / use_after_drop / double_drop / mut_violation）
+//! § Move 语义: 原语值与 `Dup` 类型按复制传递，其余类型赋值/传参/返回/装入集合时转移所有权
 //! § 快速通道: 反向 BFS 活性分析
 //! § 慢速通道: SMT 逻辑切断
 
@@ -599,6 +600,20 @@ pub fn emit_move_predicate(
     }
 }
 
+/// 为 Move 后使用的反例附上值被移动的位置
+fn with_move_site(
+    result: ProofResult,
+    moved_at: Option<&Span>,
+) -> ProofResult {
+    match result {
+        ProofResult::Disproved(mut model) => {
+            model.predicate_span = moved_at.copied();
+            ProofResult::Disproved(model)
+        }
+        other => other,
+    }
+}
+
 /// Drop 后使用谓词：`¬dropped(v)`
 pub fn emit_drop_predicate(
     var_name: &str,
@@ -669,6 +684,8 @@ pub fn check_ownership(_ctx: &ProofContext<'_>) -> ProofResult {
 // ── OwnershipChecker：AST 遍历 ───────────────────────────
 
 use crate::frontend::core::parser::ast::{Expr, Module, Stmt, StmtKind};
+use crate::frontend::core::typecheck::inference::numeric;
+use crate::frontend::core::types::{MonoType, TraitTable};
 
 /// 函数内变量状态
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -686,6 +703,21 @@ struct PendingWrite {
     span: Span,
 }
 
+/// 循环体遍历期间的 Move 记录
+///
+/// 循环体顶层（不在分支内）移动了循环外的变量且未在本轮重新赋值时，
+/// 下一轮迭代会使用已移动的值。
+struct LoopFrame {
+    /// 进入循环体时存活的变量
+    alive_before: HashSet<String>,
+    /// 循环体开始时的分支嵌套深度
+    branch_depth: usize,
+    /// 本轮迭代中被重新赋值的变量
+    assigned: HashSet<String>,
+    /// 本轮迭代中被移动的变量及移动位置
+    moves: Vec<(String, Span)>,
+}
+
 /// 所有权检查器——遍历 AST 构建 BrandTree + CFG，执行所有权验证
 pub struct OwnershipChecker {
     brand_tree: BrandTree,
@@ -722,6 +754,14 @@ pub struct OwnershipChecker {
     current_spawn_refs: HashSet<String>,
     /// 字段赋值记录：(变量名, 字段名, 被赋值的变量名)
     field_assignments: Vec<(String, String, String)>,
+    /// 局部变量类型（类型检查结果），用于区分可复制值与需 Move 的值
+    local_types: HashMap<String, MonoType>,
+    /// 变量最近一次被移动的位置
+    moved_at: HashMap<String, Span>,
+    /// 当前 if 分支嵌套深度
+    branch_depth: usize,
+    /// 正在遍历的循环体（内层在后）
+    loop_frames: Vec<LoopFrame>,
 }
 
 impl Default for OwnershipChecker {
@@ -751,9 +791,25 @@ impl OwnershipChecker {
             spawn_ref_graph: HashMap::new(),
             current_spawn_refs: HashSet::new(),
             field_assignments: Vec::new(),
+            local_types: HashMap::new(),
+            moved_at: HashMap::new(),
+            branch_depth: 0,
+            loop_frames: Vec::new(),
         }
     }
 
+    /// 提供局部变量类型
+    ///
+    /// 类型为原语值或满足 `Dup` 的变量在赋值、传参、返回时复制而非移动；
+    /// 类型未知的变量按 Move 处理。
+    pub fn with_local_types(
+        mut self,
+        local_types: HashMap<String, MonoType>,
+    ) -> Self {
+        self.local_types = local_types;
+        self
+    }
+
     /// 重置函数级状态
     fn reset(&mut self) {
         self.brand_tree = BrandTree::new();
@@ -771,6 +827,9 @@ impl OwnershipChecker {
         self.spawn_ref_graph.clear();
         self.current_spawn_refs.clear();
         self.field_assignments.clear();
+        self.moved_at.clear();
+        self.branch_depth = 0;
+        self.loop_frames.clear();
         self.current_node = self.cfg.add_node(None); // 入口节点
         self.current_span = Span::dummy();
     }
@@ -778,7 +837,7 @@ impl OwnershipChecker {
     /// 从表达式提取变量名（用于 Borrow/FieldAccess/Move 识别）
     fn extract_var_name(expr: &Expr) -> Option<String> {
         match expr {
-            Expr::Var(name, _) => Some(name.clone()),
+            Expr::Var(name, _) => Some(name.to_string()),
             Expr::FieldAccess { expr: inner, .. } => Self::extract_var_name(inner),
             _ => None,
         }
@@ -823,11 +882,7 @@ impl OwnershipChecker {
         ownership: &ParamOwnership,
     ) {
         match ownership {
-            ParamOwnership::Move => {
-                if !self.ref_vars.contains(var_name) {
-                    self.var_state.insert(var_name.to_string(), VarState::Moved);
-                }
-            }
+            ParamOwnership::Move => self.mark_moved(var_name, self.current_span),
             ParamOwnership::ReadBorrow => {
                 let token = self.brand_tree.create_read_token(var_name.to_string());
                 self.brand_tree.add_consumer(&token, self.current_node);
@@ -909,12 +964,109 @@ impl OwnershipChecker {
         span: Span,
     ) -> ProofResult {
         match self.var_state.get(name) {
-            Some(VarState::Moved) => emit_move_predicate(name, true, span),
+            Some(VarState::Moved) => with_move_site(
+                emit_move_predicate(name, true, span),
+                self.moved_at.get(name),
+            ),
             Some(VarState::Dropped) => emit_drop_predicate(name, true, span),
             _ => ProofResult::Proved,
         }
     }
 
+    /// 变量的值是否按复制传递（原语值类型或满足 `Dup`）
+    fn is_copy(
+        &self,
+        name: &str,
+    ) -> bool {
+        let Some(ty) = self.local_types.get(name) else {
+            return false;
+        };
+        // Async(T) 读取即汇合，复制语义同 T
+        let ty = &ty.clone().joined();
+        if TraitTable::is_primitive_value_type(ty) {
+            return true;
+        }
+        let env: &crate::frontend::core::typecheck::environment::TypeEnvironment = match self.env {
+            Some(env) => unsafe { &*env },
+            None => return false,
+        };
+        // 新类型沿用底层类型的复制语义
+        if let MonoType::TypeRef(type_name) = ty {
+            if let Some(underlying) = env.solver.newtype_underlying(type_name) {
+                let underlying = numeric::resolve_numeric(underlying);
+                return TraitTable::is_primitive_value_type(&underlying)
+                    || env.trait_table.satisfies("Dup", &underlying);
+            }
+        }
+        env.trait_table.satisfies("Dup", ty)
+    }
+
+    /// 所有权转移：ref 变量与可复制值不受影响
+    fn mark_moved(
+        &mut self,
+        name: &str,
+        span: Span,
+    ) {
+        if self.ref_vars.contains(name) || self.is_copy(name) {
+            return;
+        }
+        self.var_state.insert(name.to_string(), VarState::Moved);
+        self.moved_at.insert(name.to_string(), span);
+        if let Some(frame) = self.loop_frames.last_mut() {
+            if frame.branch_depth == self.branch_depth && !frame.assigned.contains(name) {
+                frame.moves.push((name.to_string(), span));
+            }
+        }
+    }
+
+    /// 变量被（重新）赋值
+    fn mark_assigned(
+        &mut self,
+        name: &str,
+    ) {
+        self.var_state.insert(name.to_string(), VarState::Alive);
+        if let Some(frame) = self.loop_frames.last_mut() {
+            frame.assigned.insert(name.to_string());
+        }
+    }
+
+    /// 进入循环体：记录当前存活的变量
+    fn enter_loop_body(&mut self) {
+        let alive_before = self
+            .var_state
+            .iter()
+            .filter(|(_, state)| **state == VarState::Alive)
+            .map(|(name, _)| name.clone())
+            .collect();
+        self.loop_frames.push(LoopFrame {
+            alive_before,
+            branch_depth: self.branch_depth,
+            assigned: HashSet::new(),
+            moves: Vec::new(),
+        });
+    }
+
+    /// 离开循环体：循环外的变量在本轮被移动且仍未恢复，下一轮迭代即为移动后使用
+    fn exit_loop_body(&mut self) -> Vec<ProofResult> {
+        let Some(frame) = self.loop_frames.pop() else {
+            return Vec::new();
+        };
+        let mut reported = HashSet::new();
+        let mut results = Vec::new();
+        for (name, span) in frame.moves {
+            if frame.alive_before.contains(&name)
+                && self.var_state.get(&name) == Some(&VarState::Moved)
+                && reported.insert(name.clone())
+            {
+                results.push(with_move_site(
+                    emit_move_predicate(&name, true, span),
+                    Some(&span),
+                ));
+            }
+        }
+        results
+    }
+
     /// 推进 CFG 节点（创建新节点并从当前节点连 Normal 边）
     #[allow(dead_code)] // 控制流方法提取后暂未使用，保留供后续使用
     fn next_node(&mut self) -> usize {
@@ -941,6 +1093,7 @@ impl OwnershipChecker {
         let mut results = self.walk_expr(condition);
 
         let merge_node = self.cfg.add_node(None);
+        self.branch_depth += 1;
 
         // then 分支 —— 路径条件 = condition
         let then_start = self.cfg.add_node(Some(format!("{:?}", condition)));
@@ -973,6 +1126,7 @@ impl OwnershipChecker {
             self.cfg.add_edge(split_node, merge_node, EdgeKind::Normal);
         }
 
+        self.branch_depth -= 1;
         self.current_node = merge_node;
         results
     }
@@ -995,7 +1149,9 @@ impl OwnershipChecker {
         let body_start = self.cfg.add_node(None);
         self.cfg.add_edge(head_node, body_start, EdgeKind::Normal);
         self.current_node = body_start;
+        self.enter_loop_body();
         results.extend(self.walk_stmts(body));
+        results.extend(self.exit_loop_body());
 
         // 回边：body_end → head
         self.cfg
@@ -1029,7 +1185,13 @@ impl OwnershipChecker {
         let body_start = self.cfg.add_node(None);
         self.cfg.add_edge(head_node, body_start, EdgeKind::Normal);
         self.current_node = body_start;
+        self.enter_loop_body();
+        // 迭代变量每轮重新绑定
+        if let Some(frame) = self.loop_frames.last_mut() {
+            frame.alive_before.remove(var);
+        }
         results.extend(self.walk_stmts(body));
+        results.extend(self.exit_loop_body());
 
         self.cfg
             .add_edge(self.current_node, head_node, EdgeKind::BackEdge);
@@ -1053,9 +1215,9 @@ impl OwnershipChecker {
                     results.push(check);
                 }
                 // spawn 体内使用 ref 变量 → 标记逃逸
-                if self.inside_spawn && self.ref_vars.contains(name) {
-                    self.escaped_refs.insert(name.clone());
-                    self.current_spawn_refs.insert(name.clone());
+                if self.inside_spawn && self.ref_vars.contains(name.as_str()) {
+                    self.escaped_refs.insert(name.to_string());
+                    self.current_spawn_refs.insert(name.to_string());
                 }
                 self.add_consumer_for_var(name);
                 results
@@ -1133,17 +1295,19 @@ impl OwnershipChecker {
                 if *op == crate::frontend::core::parser::ast::BinOp::Assign {
                     if let Expr::Var(name, _) = left.as_ref() {
                         // 仅在变量已存在且已记录可变性时检查（重赋值场景）
-                        if let Some(&is_mut) = self.var_mutability.get(name) {
-                            let mut r = self.walk_expr(left);
-                            r.extend(self.walk_expr(right));
+                        if let Some(&is_mut) = self.var_mutability.get(name.as_str()) {
+                            // 赋值目标不是读取：已移动的变量可以重新赋值
+                            let _ = self.walk_expr(left);
+                            let mut r = self.walk_expr(right);
                             if !is_mut {
                                 r.push(emit_mut_predicate(name, false, self.current_span));
                             }
+                            self.mark_assigned(name);
                             self.add_consumer_for_var(name);
                             // ref 属性传播：x = ref_var → x 也是 ref 变量
                             if let Expr::Var(src_name, _) = right.as_ref() {
-                                if self.ref_vars.contains(src_name) {
-                                    self.ref_vars.insert(name.clone());
+                                if self.ref_vars.contains(src_name.as_str()) {
+                                    self.ref_vars.insert(name.to_string());
                                 }
                             }
                             r
@@ -1151,15 +1315,15 @@ impl OwnershipChecker {
                             // 变量未在 var_mutability 中 → 首次声明（非 StmtKind::Var 路径）
                             let mut r = self.walk_expr(right);
                             r.extend(self.walk_expr(left));
-                            self.var_state.insert(name.clone(), VarState::Alive);
-                            self.var_mutability.insert(name.clone(), false);
+                            self.mark_assigned(name);
+                            self.var_mutability.insert(name.to_string(), false);
                             if let Some(scope) = self.scope_vars.last_mut() {
-                                scope.push(name.clone());
+                                scope.push(name.to_string());
                             }
                             // ref 属性传播
                             if let Expr::Var(src_name, _) = right.as_ref() {
-                                if self.ref_vars.contains(src_name) {
-                                    self.ref_vars.insert(name.clone());
+                                if self.ref_vars.contains(src_name.as_str()) {
+                                    self.ref_vars.insert(name.to_string());
                                 }
                             }
                             r
@@ -1202,8 +1366,16 @@ impl OwnershipChecker {
                 r.extend(self.walk_expr(index));
                 r
             }
+            // 元素变量的所有权转移进集合
             Expr::Tuple(elements, _) | Expr::List(elements, _) => {
-                elements.iter().flat_map(|e| self.walk_expr(e)).collect()
+                let mut results = Vec::new();
+                for element in elements {
+                    results.extend(self.walk_expr(element));
+                    if let Expr::Var(name, span) = element {
+                        self.mark_moved(name, *span);
+                    }
+                }
+                results
             }
             Expr::Try { expr: inner, .. } => self.walk_expr(inner),
             Expr::Call { func, args, .. } => {
@@ -1234,10 +1406,8 @@ impl OwnershipChecker {
             }
             Expr::Return(Some(inner), _) => {
                 let results = self.walk_expr(inner);
-                if let Expr::Var(name, _) = inner.as_ref() {
-                    if !self.ref_vars.contains(name) {
-                        self.var_state.insert(name.clone(), VarState::Moved);
-                    }
+                if let Expr::Var(name, span) = inner.as_ref() {
+                    self.mark_moved(name, *span);
                 }
                 results
             }
@@ -1348,7 +1518,7 @@ impl OwnershipChecker {
             } => {
                 let mut results = Vec::new();
                 let is_new = !self.var_state.contains_key(name);
-                self.var_state.insert(name.clone(), VarState::Alive);
+                self.mark_assigned(name);
                 self.var_mutability.insert(name.clone(), *is_mut);
                 // 仅新声明的变量加入作用域（重赋值不重复注册，避免内层作用域错误 Drop）
                 if is_new {
@@ -1375,7 +1545,7 @@ impl OwnershipChecker {
                                     self.field_assignments.push((
                                         var_name,
                                         field.clone(),
-                                        assigned_name.clone(),
+                                        assigned_name.to_string(),
                                     ));
                                 }
                             }
@@ -1410,12 +1580,10 @@ impl OwnershipChecker {
                     results.extend(self.walk_expr(init));
                     // 只有直接传变量才标记 Move（字段访问或借用不转移所有权）
                     // ref 类型是 Dup——不 Move，可多次复制
-                    if let Expr::Var(src_name, _) = init.as_ref() {
-                        if !self.ref_vars.contains(src_name) {
-                            self.var_state.insert(src_name.clone(), VarState::Moved);
-                        }
+                    if let Expr::Var(src_name, src_span) = init.as_ref() {
+                        self.mark_moved(src_name, *src_span);
                         // ref 属性传播：alias = shared → alias 也是 ref 变量
-                        if self.ref_vars.contains(src_name) {
+                        if self.ref_vars.contains(src_name.as_str()) {
                             self.ref_vars.insert(name.clone());
                         }
                     }
@@ -1425,10 +1593,8 @@ impl OwnershipChecker {
 
             StmtKind::Return(Some(expr)) => {
                 let results = self.walk_expr(expr);
-                if let Expr::Var(name, _) = expr.as_ref() {
-                    if !self.ref_vars.contains(name) {
-                        self.var_state.insert(name.clone(), VarState::Moved);
-                    }
+                if let Expr::Var(name, span) = expr.as_ref() {
+                    self.mark_moved(name, *span);
                 }
                 results
             }
@@ -416,7 +416,7 @@ impl TerminationChecker {
             // 尝试 left = var, right = bound
             if let Expr::Var(var_name, _) = left.as_ref() {
                 if let Some(bound) = self.expr_to_bound(right) {
-                    bounds.push((var_name.clone(), (cmp_op, bound)));
+                    bounds.push((var_name.to_string(), (cmp_op, bound)));
                 }
             }
             // 尝试 right = var, left = bound (反转比较)
@@ -428,7 +428,7 @@ impl TerminationChecker {
                         BoundOp::Gt => BoundOp::Lt,
                         BoundOp::Ge => BoundOp::Le,
                     };
-                    bounds.push((var_name.clone(), (rev_op, bound)));
+                    bounds.push((var_name.to_string(), (rev_op, bound)));
                 }
             }
         }
@@ -447,7 +447,7 @@ impl TerminationChecker {
                 ast::Literal::Float(f) => Some(BoundExpr::Const(*f as i128)),
                 _ => None,
             },
-            Expr::Var(name, _) => Some(BoundExpr::Var(name.clone())),
+            Expr::Var(name, _) => Some(BoundExpr::Var(name.to_string())),
             _ => None,
         }
     }
@@ -482,7 +482,7 @@ impl TerminationChecker {
                     if let Expr::Var(var_name, _) = left.as_ref() {
                         let delta_info = self.analyze_delta(right, var_name);
                         assignments.push(LoopAssignment {
-                            var: var_name.clone(),
+                            var: var_name.to_string(),
                             delta_info,
                         });
                     }
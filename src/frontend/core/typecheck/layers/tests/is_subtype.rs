@@ -2,7 +2,9 @@
 
 use crate::frontend::core::typecheck::environment::TypeEnvironment;
 use crate::frontend::core::typecheck::layers::equivalence::is_subtype;
+use crate::frontend::core::types::mono::StructType;
 use crate::frontend::core::types::MonoType;
+use std::collections::HashMap;
 
 #[test]
 fn reflexivity_int64() {
@@ -36,3 +38,71 @@ fn env_some_accepted() {
     let sup = MonoType::Int(32);
     assert!(is_subtype(&sub, &sup, Some(&env)));
 }
+
+#[test]
+fn value_and_void_are_subtypes_of_option() {
+    let opt = MonoType::Option(Box::new(MonoType::Int(64)));
+    assert!(is_subtype(&MonoType::Int(64), &opt, None));
+    assert!(is_subtype(&MonoType::Void, &opt, None));
+    assert!(!is_subtype(&MonoType::String, &opt, None));
+}
+
+#[test]
+fn member_is_subtype_of_union() {
+    let union = MonoType::Union(vec![MonoType::Int(64), MonoType::Void]);
+    assert!(is_subtype(&MonoType::Void, &union, None));
+    assert!(!is_subtype(&MonoType::Bool, &union, None));
+}
+
+fn record(
+    name: &str,
+    fields: &[(&str, MonoType)],
+) -> MonoType {
+    MonoType::Struct(StructType {
+        name: name.to_string(),
+        fields: fields
+            .iter()
+            .map(|(n, t)| (n.to_string(), t.clone()))
+            .collect(),
+        methods: HashMap::new(),
+        field_mutability: vec![false; fields.len()],
+        field_has_default: vec![false; fields.len()],
+        interfaces: vec![],
+        assoc_items: Vec::new(),
+    })
+}
+
+#[test]
+fn wider_struct_is_subtype_of_anonymous_record() {
+    let point3 = record(
+        "Point3",
+        &[
+            ("x", MonoType::Int(64)),
+            ("y", MonoType::Int(64)),
+            ("z", MonoType::Int(64)),
+        ],
+    );
+    // 字段顺序无关
+    let yz = record("", &[("z", MonoType::Int(64)), ("y", MonoType::Int(64))]);
+    assert!(is_subtype(&point3, &yz, None));
+    assert!(!is_subtype(&yz, &point3, None));
+}
+
+#[test]
+fn record_subtyping_requires_every_field() {
+    let point2 = record(
+        "Point2",
+        &[("x", MonoType::Int(64)), ("y", MonoType::Int(64))],
+    );
+    let missing = record("", &[("x", MonoType::Int(64)), ("z", MonoType::Int(64))]);
+    let wrong_type = record("", &[("x", MonoType::String)]);
+    assert!(!is_subtype(&point2, &missing, None));
+    assert!(!is_subtype(&point2, &wrong_type, None));
+}
+
+#[test]
+fn named_structs_stay_nominal() {
+    let a = record("A", &[("x", MonoType::Int(64)), ("y", MonoType::Int(64))]);
+    let b = record("B", &[("x", MonoType::Int(64))]);
+    assert!(!is_subtype(&a, &b, None));
+}
@@ -15,7 +15,7 @@ use crate::frontend::core::typecheck::layers::ownership::{
     BrandId, BrandTree, ControlFlowGraph, EdgeKind, FastPathResult, emit_move_predicate,
     emit_drop_predicate, emit_double_drop_predicate, emit_mut_predicate, fast_path_check,
 };
-use crate::frontend::core::typecheck::proof::verdict::{DisproofKind, ProofResult};
+use crate::frontend::core::typecheck::proof::verdict::{DisproofKind, DisproofModel, ProofResult};
 use crate::util::span::Span;
 
 // ── BrandId 前缀匹配 ──────────────────────────────────
@@ -357,6 +357,7 @@ fn test_mut_allowed() {
 use crate::frontend::core::parser::ast::{BinOp, Block, Expr, Literal, Module, Param, Stmt, StmtKind};
 use crate::frontend::core::typecheck::environment::TypeEnvironment;
 use crate::frontend::core::typecheck::layers::ownership::OwnershipChecker;
+use crate::frontend::core::types::MonoType;
 
 fn make_var(name: &str) -> Expr {
     Expr::Var(name.into(), Span::default())
@@ -428,6 +429,7 @@ fn make_binding(
                 .collect(),
             body,
             is_pub: false,
+            attributes: Vec::new(),
         },
         span: Span::default(),
     }
@@ -2025,3 +2027,128 @@ fn test_e2e_spawn_accesses_outer() {
         errors
     );
 }
+
+// ── E2E Move 语义：可复制值、移动位置、循环、集合 ──────────────────
+
+/// 收集 UseAfterMove 反例
+fn use_after_move_errors(results: &[ProofResult]) -> Vec<&DisproofModel> {
+    results
+        .iter()
+        .filter_map(|r| match r {
+            ProofResult::Disproved(model) if matches!(model.kind, DisproofKind::UseAfterMove) => {
+                Some(model)
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+fn check_with_types(
+    source: &str,
+    types: &[(&str, MonoType)],
+) -> Vec<ProofResult> {
+    let module = parse_module(source);
+    let local_types = types
+        .iter()
+        .map(|(name, ty)| (name.to_string(), ty.clone()))
+        .collect();
+    let mut checker = OwnershipChecker::new().with_local_types(local_types);
+    let (results, _plan, _escaped) = checker.check_module(&module, &make_test_env());
+    results
+}
+
+#[test]
+fn test_e2e_primitive_value_copied_not_moved() {
+    // Arrange: x: Int 赋值后仍可使用
+    let source = "main = () => {\n    x = 42\n    y = x\n    z = x\n}\n";
+
+    // Act
+    let results = check_with_types(source, &[("x", MonoType::Int(64))]);
+
+    // Assert
+    assert!(
+        use_after_move_errors(&results).is_empty(),
+        "原语值按复制传递，不应报 use after move: {:?}",
+        results
+    );
+}
+
+#[test]
+fn test_e2e_use_after_move_reports_move_site() {
+    // Arrange: p 是结构体，b = p 之后读取 p
+    let source = "main = () => {\n    p = P(1)\n    b = p\n    c = p\n}\n";
+
+    // Act
+    let results = check_with_types(source, &[("p", MonoType::TypeRef("P".into()))]);
+
+    // Assert
+    let errors = use_after_move_errors(&results);
+    assert_eq!(errors.len(), 1, "应报告一次 use after move: {:?}", results);
+    let moved_at = errors[0].predicate_span.expect("应携带移动位置");
+    let used_at = errors[0].span.expect("应携带使用位置");
+    assert_eq!(moved_at.start.line, 3, "移动位置应在 b = p");
+    assert_eq!(used_at.start.line, 4, "使用位置应在 c = p");
+}
+
+#[test]
+fn test_e2e_move_into_list_literal() {
+    // Arrange: p 装入列表后再使用
+    let source = "main = () => {\n    p = P(1)\n    items = [p]\n    q = p\n}\n";
+
+    // Act
+    let results = check_with_types(source, &[("p", MonoType::TypeRef("P".into()))]);
+
+    // Assert
+    assert!(
+        !use_after_move_errors(&results).is_empty(),
+        "装入列表的值已移动，再次使用应报错"
+    );
+}
+
+#[test]
+fn test_e2e_move_in_loop_body_rejected() {
+    // Arrange: 循环外的 p 在循环体内被移动，下一轮迭代再次使用
+    let source = "main = () => {\n    p = P(1)\n    for i in [1, 2] {\n        q = p\n    }\n}\n";
+
+    // Act
+    let results = check_with_types(source, &[("p", MonoType::TypeRef("P".into()))]);
+
+    // Assert
+    assert!(
+        !use_after_move_errors(&results).is_empty(),
+        "循环体内移动循环外的值应报错"
+    );
+}
+
+#[test]
+fn test_e2e_loop_local_move_allowed() {
+    // Arrange: 每轮迭代新建并移动循环体内的值
+    let source =
+        "main = () => {\n    for i in [1, 2] {\n        p = P(i)\n        q = p\n    }\n}\n";
+
+    // Act
+    let results = check_with_types(source, &[("p", MonoType::TypeRef("P".into()))]);
+
+    // Assert
+    assert!(
+        use_after_move_errors(&results).is_empty(),
+        "循环体内的局部值每轮重新绑定，不应报错: {:?}",
+        results
+    );
+}
+
+#[test]
+fn test_e2e_reassign_after_move_allowed() {
+    // Arrange: mut p 被移动后重新赋值再使用
+    let source = "main = () => {\n    mut p = P(1)\n    b = p\n    p = P(2)\n    c = p\n}\n";
+
+    // Act
+    let results = check_with_types(source, &[("p", MonoType::TypeRef("P".into()))]);
+
+    // Assert
+    assert!(
+        use_after_move_errors(&results).is_empty(),
+        "重新赋值后变量恢复可用，不应报错: {:?}",
+        results
+    );
+}
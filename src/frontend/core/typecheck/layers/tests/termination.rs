@@ -21,10 +21,10 @@ fn make_increment(
     Stmt {
         kind: StmtKind::Expr(Box::new(Expr::BinOp {
             op: BinOp::Assign,
-            left: Box::new(Expr::Var(var.to_string(), dummy_span())),
+            left: Box::new(Expr::Var(var.into(), dummy_span())),
             right: Box::new(Expr::BinOp {
                 op: BinOp::Add,
-                left: Box::new(Expr::Var(var.to_string(), dummy_span())),
+                left: Box::new(Expr::Var(var.into(), dummy_span())),
                 right: Box::new(Expr::Lit(Literal::Int(delta), dummy_span())),
                 span: dummy_span(),
             }),
@@ -42,10 +42,10 @@ fn make_decrement(
     Stmt {
         kind: StmtKind::Expr(Box::new(Expr::BinOp {
             op: BinOp::Assign,
-            left: Box::new(Expr::Var(var.to_string(), dummy_span())),
+            left: Box::new(Expr::Var(var.into(), dummy_span())),
             right: Box::new(Expr::BinOp {
                 op: BinOp::Sub,
-                left: Box::new(Expr::Var(var.to_string(), dummy_span())),
+                left: Box::new(Expr::Var(var.into(), dummy_span())),
                 right: Box::new(Expr::Lit(Literal::Int(delta), dummy_span())),
                 span: dummy_span(),
             }),
@@ -62,8 +62,8 @@ fn make_lt_condition(
 ) -> Box<Expr> {
     Box::new(Expr::BinOp {
         op: BinOp::Lt,
-        left: Box::new(Expr::Var(var.to_string(), dummy_span())),
-        right: Box::new(Expr::Var(bound.to_string(), dummy_span())),
+        left: Box::new(Expr::Var(var.into(), dummy_span())),
+        right: Box::new(Expr::Var(bound.into(), dummy_span())),
         span: dummy_span(),
     })
 }
@@ -75,7 +75,7 @@ fn make_gt_condition(
 ) -> Box<Expr> {
     Box::new(Expr::BinOp {
         op: BinOp::Gt,
-        left: Box::new(Expr::Var(var.to_string(), dummy_span())),
+        left: Box::new(Expr::Var(var.into(), dummy_span())),
         right: Box::new(Expr::Lit(Literal::Int(bound), dummy_span())),
         span: dummy_span(),
     })
@@ -169,7 +169,7 @@ fn test_while_constant_condition_fails() {
     let body_stmt = Box::new(Stmt {
         kind: StmtKind::Expr(Box::new(Expr::BinOp {
             op: BinOp::Assign,
-            left: Box::new(Expr::Var("x".to_string(), dummy_span())),
+            left: Box::new(Expr::Var("x".into(), dummy_span())),
             right: Box::new(Expr::Lit(Literal::Int(1), dummy_span())),
             span: dummy_span(),
         })),
@@ -201,7 +201,7 @@ fn test_while_constant_condition_fails() {
 fn test_while_no_assignment_fails() {
     // while i < n { print(i) } — 循环体内没有修改 i
     let body_stmt = Stmt {
-        kind: StmtKind::Expr(Box::new(Expr::Var("i".to_string(), dummy_span()))),
+        kind: StmtKind::Expr(Box::new(Expr::Var("i".into(), dummy_span()))),
         span: dummy_span(),
     };
     let while_expr = make_while(make_lt_condition("i", "n"), vec![body_stmt]);
@@ -255,12 +255,12 @@ fn test_while_increment_by_two_terminates() {
 fn test_for_loop_trivially_terminates() {
     // for x in range { print(x) }
     let body_stmt = Stmt {
-        kind: StmtKind::Expr(Box::new(Expr::Var("x".to_string(), dummy_span()))),
+        kind: StmtKind::Expr(Box::new(Expr::Var("x".into(), dummy_span()))),
         span: dummy_span(),
     };
     let for_expr = make_for(
         "x",
-        Box::new(Expr::Var("range".to_string(), dummy_span())),
+        Box::new(Expr::Var("range".into(), dummy_span())),
         vec![body_stmt],
     );
 
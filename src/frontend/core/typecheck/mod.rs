@@ -25,6 +25,9 @@ pub mod environment;
 // 类型检查器
 pub mod checker;
 
+// 增量类型检查
+pub mod incremental;
+
 // 签名解析
 pub mod signature;
 // 类型定义
@@ -70,32 +73,12 @@ pub type TypeErrorCollector = ErrorCollector<Diagnostic>;
 // ============ 入口函数 ============
 
 /// 检查模块
-#[allow(unused_variables)]
-pub fn check_module(
-    ast: &Module,
-    env: &mut Option<environment::TypeEnvironment>,
-) -> types::TypeCheckResult {
-    check_module_inner(ast, env, false)
-}
-
-/// 检查模块（收集所有错误模式）
 ///
-/// 与 `check_module` 相同，但启用错误收集模式。
-/// 类型检查器会尽可能多地收集错误，适用于 LSP 诊断。
-#[allow(unused_variables)]
-pub fn check_module_collect_all(
-    ast: &Module,
-    env: &mut Option<environment::TypeEnvironment>,
-) -> types::TypeCheckResult {
-    check_module_inner(ast, env, true)
-}
-
-/// 模块检查内部实现
+/// 类型检查器会尽可能多地收集错误，而不是在第一个错误处停止。
 #[allow(unused_variables)]
-fn check_module_inner(
+pub fn check_module(
     ast: &Module,
     env: &mut Option<environment::TypeEnvironment>,
-    collect_all: bool,
 ) -> types::TypeCheckResult {
     // 使用 TypeChecker 进行完整的模块检查
     let mut checker = checker::TypeChecker::new("main");
@@ -111,11 +94,7 @@ fn check_module_inner(
     }
 
     // 执行模块检查
-    let result = if collect_all {
-        checker.check_module_collect_all(ast)
-    } else {
-        checker.check_module(ast)
-    };
+    let result = checker.check_module(ast);
 
     // 将 exports 和 method_bindings 导回传入的环境
     if let Some(ref mut ext_env) = env {
@@ -206,6 +185,15 @@ pub fn add_native_function_types(env: &mut environment::TypeEnvironment) {
         }
     }
 
+    // 原生扩展模块只注册完全限定名（如 sqlite.open），需先 `use sqlite`
+    for module in registry.native_modules() {
+        for export in module.exports.values() {
+            let fn_ty = signature::parse_signature(&export.signature, env);
+            env.native_signatures
+                .insert(export.full_path.clone(), fn_ty);
+        }
+    }
+
     // Register Native.c — the C ABI FFI entry point
     // Native.c: (lib: String) -> LibraryRef
     env.native_signatures.insert(
@@ -268,6 +256,7 @@ pub fn add_native_function_types(env: &mut environment::TypeEnvironment) {
             field_mutability: vec![false, false],
             field_has_default: vec![false, false],
             interfaces: Vec::new(),
+            assoc_items: Vec::new(),
         })),
     );
 
@@ -220,7 +220,7 @@ impl DeadCodeAnalyzer {
         ) {
             match expr {
                 Expr::Var(name, _) => {
-                    referenced.insert(name.clone());
+                    referenced.insert(name.to_string());
                 }
                 Expr::Call { func, args, .. } => {
                     collect_from_expr(func, referenced);
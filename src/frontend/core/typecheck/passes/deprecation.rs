@@ -0,0 +1,577 @@
+//! 弃用检查
+//!
+//! 收集带 `#[deprecated]` 属性的函数、类型与方法，
+//! 对未被局部绑定遮蔽的引用生成 W2001/W2002 警告。
+//!
+//! 支持的属性形式：
+//! - `#[deprecated]`
+//! - `#[deprecated("说明")]`
+//! - `#[deprecated(message = "说明", replacement = "新名称")]`
+
+use std::collections::{HashMap, HashSet};
+
+use crate::frontend::core::parser::ast::{
+    Attribute, Block, Expr, FStringSegment, Module, Param, Pattern, Stmt, StmtKind, Type,
+};
+use crate::util::diagnostic::{Diagnostic, ErrorCodeDefinition};
+use crate::util::span::Span;
+
+/// 弃用信息
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Deprecation {
+    /// 说明文字
+    pub note: Option<String>,
+    /// 建议替代的名称
+    pub replacement: Option<String>,
+}
+
+impl Deprecation {
+    /// 从属性列表中提取 `#[deprecated]`
+    pub fn from_attributes(attributes: &[Attribute]) -> Option<Self> {
+        let attr = attributes.iter().find(|a| a.name == "deprecated")?;
+        Some(Self {
+            note: attr
+                .arg(Some("message"))
+                .or_else(|| attr.arg(None))
+                .map(str::to_string),
+            replacement: attr.arg(Some("replacement")).map(str::to_string),
+        })
+    }
+}
+
+/// 弃用检查器
+#[derive(Default)]
+pub struct DeprecationChecker {
+    /// 已弃用的顶层符号（函数名、类型名或 `Type.method`）
+    deprecated: HashMap<String, Deprecation>,
+    /// 局部作用域栈（参数、局部变量、泛型参数等会遮蔽顶层符号）
+    scopes: Vec<HashSet<String>>,
+    /// 收集到的警告
+    warnings: Vec<Diagnostic>,
+}
+
+impl DeprecationChecker {
+    /// 创建新的弃用检查器
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 检查模块，返回警告诊断
+    pub fn check_module(
+        mut self,
+        module: &Module,
+    ) -> Vec<Diagnostic> {
+        self.collect_deprecated(module);
+        if self.deprecated.is_empty() {
+            return Vec::new();
+        }
+
+        for stmt in &module.items {
+            // 已弃用项自身的定义（及其方法）内部不再重复警告
+            if let StmtKind::Binding {
+                name,
+                type_name,
+                attributes,
+                ..
+            } = &stmt.kind
+            {
+                let owner = type_name.as_deref().unwrap_or(name);
+                if Deprecation::from_attributes(attributes).is_some()
+                    || self.deprecated.contains_key(owner)
+                {
+                    continue;
+                }
+            }
+            self.check_stmt(stmt);
+        }
+        self.warnings
+    }
+
+    /// 收集顶层的已弃用绑定
+    fn collect_deprecated(
+        &mut self,
+        module: &Module,
+    ) {
+        for stmt in &module.items {
+            if let StmtKind::Binding {
+                name,
+                type_name,
+                attributes,
+                ..
+            } = &stmt.kind
+            {
+                if let Some(deprecation) = Deprecation::from_attributes(attributes) {
+                    let key = match type_name {
+                        Some(ty) => format!("{}.{}", ty, name),
+                        None => name.clone(),
+                    };
+                    self.deprecated.insert(key, deprecation);
+                }
+            }
+        }
+    }
+
+    fn is_shadowed(
+        &self,
+        name: &str,
+    ) -> bool {
+        self.scopes.iter().any(|scope| scope.contains(name))
+    }
+
+    fn declare(
+        &mut self,
+        name: &str,
+    ) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name.to_string());
+        }
+    }
+
+    fn with_scope(
+        &mut self,
+        names: impl IntoIterator<Item = String>,
+        f: impl FnOnce(&mut Self),
+    ) {
+        self.scopes.push(names.into_iter().collect());
+        f(self);
+        self.scopes.pop();
+    }
+
+    /// 引用 `name`（全局符号键）时检查是否弃用
+    fn check_reference(
+        &mut self,
+        name: &str,
+        span: Span,
+    ) {
+        let root = name.split('.').next().unwrap_or(name);
+        if self.is_shadowed(root) {
+            return;
+        }
+        let Some(deprecation) = self.deprecated.get(name) else {
+            return;
+        };
+        let note = deprecation.note.as_deref();
+        let builder = match &deprecation.replacement {
+            Some(replacement) => {
+                ErrorCodeDefinition::deprecated_item_with_replacement(name, note, replacement)
+            }
+            None => ErrorCodeDefinition::deprecated_item(name, note),
+        };
+        self.warnings.push(builder.at(span).build());
+    }
+
+    fn check_block(
+        &mut self,
+        block: &Block,
+    ) {
+        self.check_stmts(&block.stmts);
+    }
+
+    fn check_stmts(
+        &mut self,
+        stmts: &[Stmt],
+    ) {
+        self.with_scope(Vec::new(), |this| {
+            for stmt in stmts {
+                this.check_stmt(stmt);
+            }
+        });
+    }
+
+    fn check_stmt(
+        &mut self,
+        stmt: &Stmt,
+    ) {
+        match &stmt.kind {
+            StmtKind::Expr(expr) => self.check_expr(expr),
+            StmtKind::Var {
+                name,
+                type_annotation,
+                initializer,
+                ..
+            } => {
+                if let Some(ty) = type_annotation {
+                    self.check_type(ty);
+                }
+                if let Some(init) = initializer {
+                    self.check_expr(init);
+                }
+                self.declare(name);
+            }
+            StmtKind::For {
+                var,
+                iterable,
+                body,
+                ..
+            } => {
+                self.check_expr(iterable);
+                self.with_scope([var.clone()], |this| this.check_block(body));
+            }
+            StmtKind::Binding {
+                name,
+                type_name,
+                method_type,
+                generic_params,
+                type_annotation,
+                params,
+                body,
+                ..
+            } => {
+                // 嵌套函数遮蔽同名的顶层符号
+                if type_name.is_none() && !self.scopes.is_empty() {
+                    self.declare(name);
+                }
+                let mut locals: Vec<String> =
+                    generic_params.iter().map(|g| g.name.clone()).collect();
+                locals.extend(params.iter().map(|p| p.name.clone()));
+                self.with_scope(locals, |this| {
+                    for generic in generic_params {
+                        for constraint in &generic.constraints {
+                            this.check_type(constraint);
+                        }
+                    }
+                    if let Some(ty) = method_type {
+                        this.check_type(ty);
+                    }
+                    if let Some(ty) = type_annotation {
+                        this.check_type(ty);
+                    }
+                    this.check_params(params);
+                    this.check_stmts(body);
+                });
+            }
+            StmtKind::If {
+                condition,
+                then_branch,
+                elif_branches,
+                else_branch,
+                ..
+            } => self.check_if(
+                condition,
+                then_branch,
+                elif_branches,
+                else_branch.as_deref(),
+            ),
+            StmtKind::DestructureAssign { names, rhs, .. } => {
+                self.check_expr(rhs);
+                for ident in names {
+                    self.declare(&ident.name);
+                }
+            }
+            StmtKind::Return(Some(expr)) => self.check_expr(expr),
+            StmtKind::Return(None)
+            | StmtKind::Use { .. }
+            | StmtKind::ExternalBindingStmt { .. }
+            | StmtKind::Error(_) => {}
+        }
+    }
+
+    fn check_if(
+        &mut self,
+        condition: &Expr,
+        then_branch: &Block,
+        elif_branches: &[(Box<Expr>, Box<Block>)],
+        else_branch: Option<&Block>,
+    ) {
+        self.check_expr(condition);
+        self.check_block(then_branch);
+        for (cond, block) in elif_branches {
+            self.check_expr(cond);
+            self.check_block(block);
+        }
+        if let Some(block) = else_branch {
+            self.check_block(block);
+        }
+    }
+
+    fn check_params(
+        &mut self,
+        params: &[Param],
+    ) {
+        for param in params {
+            if let Some(ty) = &param.ty {
+                self.check_type(ty);
+            }
+        }
+    }
+
+    fn check_expr(
+        &mut self,
+        expr: &Expr,
+    ) {
+        match expr {
+            Expr::Var(name, span) => self.check_reference(name, *span),
+            Expr::FieldAccess { expr, field, span } => {
+                // `Type.method` 形式的静态引用
+                if let Expr::Var(owner, _) = expr.as_ref() {
+                    self.check_reference(&format!("{}.{}", owner, field), *span);
+                }
+                self.check_expr(expr);
+            }
+            Expr::Lit(..) | Expr::Break(..) | Expr::Continue(..) | Expr::Error(_) => {}
+            Expr::BinOp { left, right, .. }
+            | Expr::CompoundAssign {
+                target: left,
+                value: right,
+                ..
+            }
+            | Expr::Pipe {
+                value: left,
+                func: right,
+                ..
+            } => {
+                self.check_expr(left);
+                self.check_expr(right);
+            }
+            Expr::UnOp { expr, .. }
+            | Expr::Try { expr, .. }
+            | Expr::Ref { expr, .. }
+            | Expr::Borrow { expr, .. } => self.check_expr(expr),
+            Expr::Call {
+                func,
+                args,
+                named_args,
+                ..
+            } => {
+                self.check_expr(func);
+                for arg in args {
+                    self.check_expr(arg);
+                }
+                for (_, arg) in named_args {
+                    self.check_expr(arg);
+                }
+            }
+            Expr::FnDef {
+                params,
+                return_type,
+                body,
+                ..
+            } => self.check_function(params, return_type.as_ref(), body),
+            Expr::Lambda { params, body, .. } => self.check_function(params, None, body),
+            Expr::If {
+                condition,
+                then_branch,
+                elif_branches,
+                else_branch,
+                ..
+            } => self.check_if(
+                condition,
+                then_branch,
+                elif_branches,
+                else_branch.as_deref(),
+            ),
+            Expr::Match { expr, arms, .. } => {
+                self.check_expr(expr);
+                for arm in arms {
+                    let mut bound = Vec::new();
+                    self.check_pattern(&arm.pattern, &mut bound);
+                    self.with_scope(bound, |this| {
+                        if let Pattern::Guard { condition, .. } = &arm.pattern {
+                            this.check_expr(condition);
+                        }
+                        this.check_block(&arm.body);
+                    });
+                }
+            }
+            Expr::While {
+                condition, body, ..
+            } => {
+                self.check_expr(condition);
+                self.check_block(body);
+            }
+            Expr::For {
+                var,
+                iterable,
+                body,
+                ..
+            }
+            | Expr::SpawnFor {
+                var,
+                iterable,
+                body,
+                ..
+            } => {
+                self.check_expr(iterable);
+                self.with_scope([var.clone()], |this| this.check_block(body));
+            }
+            Expr::Block(block) => self.check_block(block),
+            Expr::Unsafe { body, .. } | Expr::Spawn { body, .. } => self.check_block(body),
+            Expr::Return(value, _) => {
+                if let Some(value) = value {
+                    self.check_expr(value);
+                }
+            }
+            Expr::Cast {
+                expr, target_type, ..
+            } => {
+                self.check_expr(expr);
+                self.check_type(target_type);
+            }
+            Expr::Tuple(elems, _) | Expr::List(elems, _) => {
+                for elem in elems {
+                    self.check_expr(elem);
+                }
+            }
+            Expr::ListComp {
+                element,
+                var,
+                iterable,
+                condition,
+                ..
+            } => {
+                self.check_expr(iterable);
+                self.with_scope([var.clone()], |this| {
+                    this.check_expr(element);
+                    if let Some(cond) = condition {
+                        this.check_expr(cond);
+                    }
+                });
+            }
+            Expr::Dict(entries, _) => {
+                for (key, value) in entries {
+                    self.check_expr(key);
+                    self.check_expr(value);
+                }
+            }
+            Expr::Index { expr, index, .. } => {
+                self.check_expr(expr);
+                self.check_expr(index);
+            }
+            Expr::FString { segments, .. } => {
+                for segment in segments {
+                    if let FStringSegment::Interpolation { expr, .. } = segment {
+                        self.check_expr(expr);
+                    }
+                }
+            }
+        }
+    }
+
+    fn check_function(
+        &mut self,
+        params: &[Param],
+        return_type: Option<&Type>,
+        body: &Block,
+    ) {
+        self.check_params(params);
+        if let Some(ty) = return_type {
+            self.check_type(ty);
+        }
+        let names: Vec<String> = params.iter().map(|p| p.name.clone()).collect();
+        self.with_scope(names, |this| this.check_block(body));
+    }
+
+    /// 检查模式中引用的类型名，并收集模式绑定的变量
+    fn check_pattern(
+        &mut self,
+        pattern: &Pattern,
+        bound: &mut Vec<String>,
+    ) {
+        match pattern {
+            Pattern::Wildcard | Pattern::Literal(_) => {}
+            Pattern::Identifier(name) => bound.push(name.clone()),
+            Pattern::Tuple(items) | Pattern::Or(items) => {
+                for item in items {
+                    self.check_pattern(item, bound);
+                }
+            }
+            Pattern::Struct { fields, .. } => {
+                for (_, _, field) in fields {
+                    self.check_pattern(field, bound);
+                }
+            }
+            Pattern::Union { pattern, .. } => {
+                if let Some(inner) = pattern {
+                    self.check_pattern(inner, bound);
+                }
+            }
+            Pattern::Guard { pattern, .. } => self.check_pattern(pattern, bound),
+        }
+    }
+
+    fn check_type(
+        &mut self,
+        ty: &Type,
+    ) {
+        match ty {
+            Type::Name { name, span } => self.check_reference(name, *span),
+            Type::Generic {
+                name,
+                name_span,
+                args,
+            } => {
+                self.check_reference(name, *name_span);
+                self.check_types(args);
+            }
+            Type::Int(_)
+            | Type::Float(_)
+            | Type::Char
+            | Type::String
+            | Type::Bytes
+            | Type::Bool
+            | Type::Void
+            | Type::Enum(_) => {}
+            Type::Struct { fields, .. } | Type::NamedStruct { fields, .. } => {
+                for field in fields {
+                    self.check_type(&field.ty);
+                    if let Some(default) = &field.default {
+                        self.check_expr(default);
+                    }
+                }
+            }
+            Type::Union(variants) => {
+                for (_, ty) in variants {
+                    if let Some(ty) = ty {
+                        self.check_type(ty);
+                    }
+                }
+            }
+            Type::Variant(variants) => {
+                for variant in variants {
+                    for (_, ty) in &variant.params {
+                        self.check_type(ty);
+                    }
+                }
+            }
+            Type::Tuple(types) | Type::Sum(types) | Type::MetaType { args: types, .. } => {
+                self.check_types(types)
+            }
+            Type::Fn {
+                params,
+                return_type,
+            } => {
+                self.check_types(params);
+                self.check_type(return_type);
+            }
+            Type::Option(inner)
+            | Type::Ptr(inner)
+            | Type::Newtype(inner)
+            | Type::Ref { inner, .. }
+            | Type::Literal {
+                base_type: inner, ..
+            } => self.check_type(inner),
+            Type::Result(ok, err) => {
+                self.check_type(ok);
+                self.check_type(err);
+            }
+            Type::AssocType {
+                host_type,
+                assoc_args,
+                ..
+            } => {
+                self.check_type(host_type);
+                self.check_types(assoc_args);
+            }
+            Type::ConstExpr(expr) => self.check_expr(expr),
+        }
+    }
+
+    fn check_types(
+        &mut self,
+        types: &[Type],
+    ) {
+        for ty in types {
+            self.check_type(ty);
+        }
+    }
+}
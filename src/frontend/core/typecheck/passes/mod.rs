@@ -4,7 +4,9 @@
 //! 每个遍独立执行，互不依赖，不依赖 layers/。
 
 pub mod dead_code;
+pub mod deprecation;
 pub mod overload;
+pub mod unused;
 
 #[cfg(test)]
 mod tests;
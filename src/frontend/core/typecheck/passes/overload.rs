@@ -629,6 +629,7 @@ fn substitute_return_type(
             field_mutability: s.field_mutability.clone(),
             field_has_default: s.field_has_default.clone(),
             interfaces: s.interfaces.clone(),
+            assoc_items: s.assoc_items.clone(),
         }),
         MonoType::Enum(e) => MonoType::Enum(e.clone()),
         MonoType::Range { elem_type } => MonoType::Range {
@@ -651,6 +652,9 @@ fn substitute_return_type(
         MonoType::Weak(inner) => {
             MonoType::Weak(Box::new(substitute_return_type(inner, substitutions)))
         }
+        MonoType::Async(inner) => {
+            MonoType::Async(Box::new(substitute_return_type(inner, substitutions)))
+        }
         MonoType::AssocType {
             host_type,
             assoc_name,
@@ -35,6 +35,7 @@ fn make_binding(
             type_name: type_name.map(String::from),
             method_type: None,
             is_pub,
+            attributes: Vec::new(),
             params: vec![],
             body: body_stmts,
             generic_params: vec![],
@@ -53,6 +54,7 @@ fn make_type_constructor(name: &str) -> Stmt {
             type_name: None,
             method_type: None,
             is_pub: false,
+            attributes: Vec::new(),
             params: vec![],
             body: vec![],
             generic_params: vec![],
@@ -104,7 +106,7 @@ fn make_use(
 fn make_call_stmt(name: &str) -> Stmt {
     Stmt {
         kind: StmtKind::Expr(Box::new(Expr::Call {
-            func: Box::new(Expr::Var(name.to_string(), Span::dummy())),
+            func: Box::new(Expr::Var(name.into(), Span::dummy())),
             args: vec![],
             named_args: vec![],
             span: Span::dummy(),
@@ -0,0 +1,106 @@
+//! 弃用检查测试 — `#[deprecated]` 引用产生 W2001/W2002 警告
+
+use crate::frontend::core::lexer::tokenize;
+use crate::frontend::core::parser::parse;
+use crate::frontend::core::typecheck::passes::deprecation::DeprecationChecker;
+use crate::util::diagnostic::{Diagnostic, Severity};
+
+fn check(source: &str) -> Vec<Diagnostic> {
+    let tokens = tokenize(source).unwrap();
+    let result = parse(&tokens);
+    assert!(!result.has_errors, "{:?}", result.errors);
+    DeprecationChecker::new().check_module(&result.module)
+}
+
+#[test]
+fn test_deprecated_function_call_warns() {
+    let warnings = check(
+        r#"
+#[deprecated("use add")]
+old_add: (a: Int, b: Int) -> Int = (a, b) => a + b
+main = { x = old_add(1, 2) }
+"#,
+    );
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].code, "W2001");
+    assert_eq!(warnings[0].severity, Severity::Warning);
+    assert!(warnings[0].message.contains("old_add"));
+    assert!(warnings[0].message.contains("use add"));
+    assert_eq!(warnings[0].span.unwrap().start.line, 4);
+}
+
+#[test]
+fn test_replacement_uses_w2002_help() {
+    let warnings = check(
+        r#"
+#[deprecated(replacement = "add")]
+plus: (a: Int, b: Int) -> Int = (a, b) => a + b
+main = { x = plus(1, 2) }
+"#,
+    );
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].code, "W2002");
+    assert!(warnings[0].help.contains("add"));
+}
+
+#[test]
+fn test_deprecated_type_in_annotation_and_constructor() {
+    let warnings = check(
+        r#"
+#[deprecated]
+OldPoint: Type = { x: Int, y: Int }
+main = { p: OldPoint = OldPoint(1, 2) }
+"#,
+    );
+    assert_eq!(warnings.len(), 2);
+    assert!(warnings.iter().all(|w| w.code == "W2001"));
+}
+
+#[test]
+fn test_local_shadowing_suppresses_warning() {
+    let warnings = check(
+        r#"
+#[deprecated]
+old: () -> Int = { return 1 }
+shadow: (old: Int) -> Int = (old) => old + 1
+main = {
+    old = 5
+    y = old
+}
+"#,
+    );
+    assert!(warnings.is_empty(), "{:?}", warnings);
+}
+
+#[test]
+fn test_no_warning_inside_deprecated_item() {
+    let warnings = check(
+        r#"
+#[deprecated]
+OldPoint: Type = { x: Int }
+OldPoint.get: (self: &OldPoint) -> Int = { return self.x }
+#[deprecated]
+older: () -> Int = { return old() }
+#[deprecated]
+old: () -> Int = { return 1 }
+"#,
+    );
+    assert!(warnings.is_empty(), "{:?}", warnings);
+}
+
+#[test]
+fn test_deprecated_method_static_reference() {
+    let warnings = check(
+        r#"
+Point: Type = { x: Int }
+#[deprecated("use Point.value")]
+Point.get: (self: &Point) -> Int = { return self.x }
+main = {
+    p = Point(1)
+    v = Point.get(p)
+}
+"#,
+    );
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].message.contains("Point.get"));
+}
@@ -1,6 +1,8 @@
 //! Passes tests — mirrors src/frontend/core/typecheck/passes/
 //!
-//! Tests for dead_code and overload analysis passes.
+//! Tests for dead_code, deprecation and overload analysis passes.
 
 mod dead_code;
+mod deprecation;
 mod overload;
+mod unused;
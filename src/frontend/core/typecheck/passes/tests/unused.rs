@@ -0,0 +1,151 @@
+//! 未使用代码检查测试 — W1003/W1006/W1007
+
+use crate::frontend::core::lexer::tokenize;
+use crate::frontend::core::parser::parse;
+use crate::frontend::core::typecheck::passes::unused::UnusedChecker;
+use crate::util::diagnostic::{Diagnostic, Severity};
+
+fn check(source: &str) -> Vec<Diagnostic> {
+    let tokens = tokenize(source).unwrap();
+    let result = parse(&tokens);
+    assert!(!result.has_errors, "{:?}", result.errors);
+    UnusedChecker::new().check_module(&result.module)
+}
+
+fn codes(warnings: &[Diagnostic]) -> Vec<&str> {
+    warnings.iter().map(|w| w.code.as_str()).collect()
+}
+
+#[test]
+fn test_unused_local_warns_with_underscore_hint() {
+    let warnings = check(
+        r#"
+main = {
+    x = 1
+    y = 2
+    print(y)
+}
+"#,
+    );
+    assert_eq!(codes(&warnings), ["W1006"]);
+    assert_eq!(warnings[0].severity, Severity::Warning);
+    assert!(warnings[0].message.contains("'x'"));
+    assert!(warnings[0].help.contains("_x"));
+    assert_eq!(warnings[0].span.unwrap().start.line, 3);
+}
+
+#[test]
+fn test_underscore_prefix_and_params_are_not_reported() {
+    let warnings = check(
+        r#"
+add: (a: Int, b: Int) -> Int = (a, b) => 0
+main = {
+    _ignored = add(1, 2)
+    for i in [1, 2] {
+        print("loop")
+    }
+}
+"#,
+    );
+    assert!(warnings.is_empty(), "{:?}", warnings);
+}
+
+#[test]
+fn test_reassignment_counts_as_use_of_previous_value() {
+    let warnings = check(
+        r#"
+main = {
+    mut total = 0
+    total = total + 1
+    print(total)
+}
+"#,
+    );
+    assert!(warnings.is_empty(), "{:?}", warnings);
+}
+
+#[test]
+fn test_shadowed_local_is_reported_separately() {
+    let warnings = check(
+        r#"
+main = {
+    x = 1
+    mut x = 2
+    print(x)
+}
+"#,
+    );
+    assert_eq!(codes(&warnings), ["W1006"]);
+    assert_eq!(warnings[0].span.unwrap().start.line, 3);
+}
+
+#[test]
+fn test_unused_import_warns() {
+    let warnings = check(
+        r#"
+use std.io
+use std.math.{sqrt, pi}
+main = {
+    print(sqrt(4.0))
+}
+"#,
+    );
+    assert_eq!(codes(&warnings), ["W1003", "W1003"]);
+    assert!(warnings[0].message.contains("io"));
+    assert!(warnings[1].message.contains("pi"));
+}
+
+#[test]
+fn test_module_import_used_through_field_access() {
+    let warnings = check(
+        r#"
+use std.io
+use std.math as m
+main = {
+    io.println(m.sqrt(4.0))
+}
+"#,
+    );
+    assert!(warnings.is_empty(), "{:?}", warnings);
+}
+
+#[test]
+fn test_uncalled_private_function_warns() {
+    let warnings = check(
+        r#"
+helper: (x: Int) -> Int = (x) => x + 1
+unused: (x: Int) -> Int = (x) => x * 2
+pub exported: (x: Int) -> Int = (x) => x
+main = {
+    print(helper(1))
+}
+"#,
+    );
+    assert_eq!(codes(&warnings), ["W1007"]);
+    assert!(warnings[0].message.contains("unused"));
+}
+
+#[test]
+fn test_functions_reachable_only_from_each_other_are_dead() {
+    let warnings = check(
+        r#"
+ping: (n: Int) -> Int = (n) => pong(n)
+pong: (n: Int) -> Int = (n) => ping(n)
+main = {
+    print("hi")
+}
+"#,
+    );
+    assert_eq!(codes(&warnings), ["W1007", "W1007"]);
+}
+
+#[test]
+fn test_function_called_from_pub_function_is_used() {
+    let warnings = check(
+        r#"
+double: (x: Int) -> Int = (x) => x * 2
+pub quad: (x: Int) -> Int = (x) => double(double(x))
+"#,
+    );
+    assert!(warnings.is_empty(), "{:?}", warnings);
+}
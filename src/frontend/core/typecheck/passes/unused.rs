@@ -0,0 +1,713 @@
+//! 未使用代码检查
+//!
+//! 类型检查之后运行的 lint 遍，报告：
+//! - W1006 未使用的局部变量（`x = ...` 与解构绑定；参数、循环变量、模式绑定不报告）
+//! - W1003 未使用的 `use` 导入
+//! - W1007 从未被调用的私有函数（从 `main`、`pub` 项、方法、类型等根出发不可达）
+//!
+//! 以 `_` 开头的变量名与函数名不报告。报告级别由 `[lint]` 配置决定，
+//! 见 [`LintConfig::level_for`](crate::util::config::LintConfig::level_for)。
+
+use std::collections::{HashMap, HashSet};
+
+use crate::frontend::core::parser::ast::{
+    BindingKind, Block, Expr, FStringSegment, Module, Param, Pattern, Stmt, StmtKind, Type,
+};
+use crate::util::diagnostic::{Diagnostic, ErrorCodeDefinition};
+use crate::util::span::Span;
+
+/// 作用域中的局部绑定
+struct Local {
+    name: String,
+    span: Span,
+    used: bool,
+    /// 未使用时是否报告（参数、循环变量等只用于解析遮蔽）
+    report: bool,
+}
+
+/// 未使用代码检查器
+#[derive(Default)]
+pub struct UnusedChecker {
+    /// 局部作用域栈
+    scopes: Vec<Vec<Local>>,
+    /// 当前顶层项引用的全局名称
+    refs: HashSet<String>,
+    /// 收集到的警告
+    warnings: Vec<Diagnostic>,
+}
+
+impl UnusedChecker {
+    /// 创建新的检查器
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 检查模块，返回警告诊断
+    pub fn check_module(
+        mut self,
+        module: &Module,
+    ) -> Vec<Diagnostic> {
+        // 每个顶层项引用的全局名称
+        let mut item_refs: Vec<HashSet<String>> = Vec::with_capacity(module.items.len());
+        for stmt in &module.items {
+            match &stmt.kind {
+                StmtKind::Use { .. } => {}
+                StmtKind::Var {
+                    type_annotation,
+                    initializer,
+                    ..
+                } => {
+                    if let Some(ty) = type_annotation {
+                        self.visit_type(ty);
+                    }
+                    if let Some(init) = initializer {
+                        self.visit_expr(init);
+                    }
+                }
+                _ => self.visit_stmt(stmt),
+            }
+            item_refs.push(std::mem::take(&mut self.refs));
+        }
+
+        self.check_imports(module, &item_refs);
+        self.check_functions(module, &item_refs);
+
+        self.warnings.sort_by_key(|w| {
+            w.span
+                .map(|s| (s.start.line, s.start.column))
+                .unwrap_or_default()
+        });
+        self.warnings
+    }
+
+    /// W1003：导入的名称没有被任何顶层项引用
+    fn check_imports(
+        &mut self,
+        module: &Module,
+        item_refs: &[HashSet<String>],
+    ) {
+        for stmt in &module.items {
+            let StmtKind::Use {
+                path, items, alias, ..
+            } = &stmt.kind
+            else {
+                continue;
+            };
+            for name in imported_names(path, items.as_deref(), alias.as_deref()) {
+                if name.starts_with('_') || item_refs.iter().any(|refs| refs.contains(&name)) {
+                    continue;
+                }
+                self.warnings.push(
+                    ErrorCodeDefinition::unused_import(&name)
+                        .at(stmt.span)
+                        .build(),
+                );
+            }
+        }
+    }
+
+    /// W1007：从根出发不可达的私有函数
+    fn check_functions(
+        &mut self,
+        module: &Module,
+        item_refs: &[HashSet<String>],
+    ) {
+        // 同名重载共享可达性
+        let mut candidates: HashMap<&str, Vec<usize>> = HashMap::new();
+        for (index, stmt) in module.items.iter().enumerate() {
+            if let Some(name) = private_function_name(stmt) {
+                candidates.entry(name).or_default().push(index);
+            }
+        }
+        if candidates.is_empty() {
+            return;
+        }
+
+        let mut reached: HashSet<&str> = HashSet::new();
+        let mut queue: Vec<usize> = (0..module.items.len())
+            .filter(|index| private_function_name(&module.items[*index]).is_none())
+            .collect();
+        while let Some(index) = queue.pop() {
+            for name in &item_refs[index] {
+                if let Some((name, indices)) = candidates.get_key_value(name.as_str()) {
+                    if reached.insert(name) {
+                        queue.extend(indices);
+                    }
+                }
+            }
+        }
+
+        for (name, indices) in &candidates {
+            if reached.contains(name) {
+                continue;
+            }
+            for index in indices {
+                self.warnings.push(
+                    ErrorCodeDefinition::unused_private_function(name)
+                        .at(module.items[*index].span)
+                        .build(),
+                );
+            }
+        }
+    }
+
+    fn declare(
+        &mut self,
+        name: &str,
+        span: Span,
+        report: bool,
+    ) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.push(Local {
+                name: name.to_string(),
+                span,
+                used: false,
+                report: report && !name.starts_with('_'),
+            });
+        }
+    }
+
+    fn lookup(
+        &mut self,
+        name: &str,
+    ) -> Option<&mut Local> {
+        self.scopes
+            .iter_mut()
+            .rev()
+            .find_map(|scope| scope.iter_mut().rev().find(|local| local.name == name))
+    }
+
+    /// 引用名称：解析到局部绑定则标记已使用，否则记为全局引用
+    fn reference(
+        &mut self,
+        name: &str,
+    ) {
+        let root = name.split('.').next().unwrap_or(name);
+        if let Some(local) = self.lookup(root) {
+            local.used = true;
+            return;
+        }
+        self.refs.insert(root.to_string());
+        if root != name {
+            self.refs.insert(name.to_string());
+        }
+    }
+
+    /// 在新作用域中执行 `f`，离开时报告未使用的局部变量
+    fn with_scope(
+        &mut self,
+        params: &[(String, Span)],
+        f: impl FnOnce(&mut Self),
+    ) {
+        self.scopes.push(Vec::new());
+        for (name, span) in params {
+            self.declare(name, *span, false);
+        }
+        f(self);
+        if let Some(scope) = self.scopes.pop() {
+            for local in scope.into_iter().filter(|l| l.report && !l.used) {
+                self.warnings.push(
+                    ErrorCodeDefinition::unused_local(&local.name)
+                        .at(local.span)
+                        .build(),
+                );
+            }
+        }
+    }
+
+    fn visit_block(
+        &mut self,
+        block: &Block,
+    ) {
+        self.visit_stmts(&block.stmts);
+    }
+
+    fn visit_stmts(
+        &mut self,
+        stmts: &[Stmt],
+    ) {
+        self.with_scope(&[], |this| {
+            for stmt in stmts {
+                this.visit_stmt(stmt);
+            }
+        });
+    }
+
+    fn visit_stmt(
+        &mut self,
+        stmt: &Stmt,
+    ) {
+        match &stmt.kind {
+            StmtKind::Expr(expr) => self.visit_expr(expr),
+            StmtKind::Var {
+                name,
+                name_span,
+                type_annotation,
+                initializer,
+                is_mut,
+            } => {
+                if let Some(ty) = type_annotation {
+                    self.visit_type(ty);
+                }
+                if let Some(init) = initializer {
+                    self.visit_expr(init);
+                }
+                // `x = ...` 对已有局部变量是赋值，不是新绑定
+                let is_assignment =
+                    !*is_mut && type_annotation.is_none() && self.lookup(name).is_some();
+                if !is_assignment {
+                    let span = if name_span.is_dummy() {
+                        stmt.span
+                    } else {
+                        *name_span
+                    };
+                    self.declare(name, span, true);
+                }
+            }
+            StmtKind::For {
+                var,
+                var_span,
+                iterable,
+                body,
+                ..
+            } => {
+                self.visit_expr(iterable);
+                self.with_scope(&[(var.clone(), *var_span)], |this| this.visit_block(body));
+            }
+            StmtKind::Binding {
+                name,
+                type_name,
+                method_type,
+                generic_params,
+                type_annotation,
+                params,
+                body,
+                ..
+            } => {
+                // 嵌套函数是局部名称
+                if type_name.is_none() && !self.scopes.is_empty() {
+                    self.declare(name, stmt.span, false);
+                }
+                let mut locals: Vec<(String, Span)> = generic_params
+                    .iter()
+                    .map(|g| (g.name.clone(), stmt.span))
+                    .collect();
+                locals.extend(params.iter().map(|p| (p.name.clone(), p.span)));
+                self.with_scope(&locals, |this| {
+                    for generic in generic_params {
+                        for constraint in &generic.constraints {
+                            this.visit_type(constraint);
+                        }
+                    }
+                    if let Some(ty) = method_type {
+                        this.visit_type(ty);
+                    }
+                    if let Some(ty) = type_annotation {
+                        this.visit_type(ty);
+                    }
+                    this.visit_params(params);
+                    this.visit_stmts(body);
+                });
+            }
+            StmtKind::If {
+                condition,
+                then_branch,
+                elif_branches,
+                else_branch,
+                ..
+            } => self.visit_if(
+                condition,
+                then_branch,
+                elif_branches,
+                else_branch.as_deref(),
+            ),
+            StmtKind::DestructureAssign { names, rhs, .. } => {
+                self.visit_expr(rhs);
+                for ident in names {
+                    self.declare(&ident.name, ident.span, true);
+                }
+            }
+            StmtKind::ExternalBindingStmt { binding, .. } => self.visit_binding_kind(binding),
+            StmtKind::Return(Some(expr)) => self.visit_expr(expr),
+            StmtKind::Return(None) | StmtKind::Use { .. } | StmtKind::Error(_) => {}
+        }
+    }
+
+    fn visit_binding_kind(
+        &mut self,
+        binding: &BindingKind,
+    ) {
+        match binding {
+            BindingKind::External { function, .. } | BindingKind::DefaultExternal { function } => {
+                self.reference(function)
+            }
+            BindingKind::Anonymous {
+                params,
+                return_type,
+                body,
+                ..
+            } => {
+                self.visit_params(params);
+                self.visit_type(return_type);
+                let names = param_names(params);
+                self.with_scope(&names, |this| this.visit_expr(body));
+            }
+        }
+    }
+
+    fn visit_if(
+        &mut self,
+        condition: &Expr,
+        then_branch: &Block,
+        elif_branches: &[(Box<Expr>, Box<Block>)],
+        else_branch: Option<&Block>,
+    ) {
+        self.visit_expr(condition);
+        self.visit_block(then_branch);
+        for (cond, block) in elif_branches {
+            self.visit_expr(cond);
+            self.visit_block(block);
+        }
+        if let Some(block) = else_branch {
+            self.visit_block(block);
+        }
+    }
+
+    fn visit_params(
+        &mut self,
+        params: &[Param],
+    ) {
+        for param in params {
+            if let Some(ty) = &param.ty {
+                self.visit_type(ty);
+            }
+        }
+    }
+
+    fn visit_expr(
+        &mut self,
+        expr: &Expr,
+    ) {
+        match expr {
+            Expr::Var(name, _) => self.reference(name),
+            Expr::FieldAccess { expr, field, .. } => {
+                self.visit_expr(expr);
+                // `value.func()` 也可能调用同名自由函数
+                self.refs.insert(field.clone());
+            }
+            Expr::Lit(..) | Expr::Break(..) | Expr::Continue(..) | Expr::Error(_) => {}
+            Expr::BinOp { left, right, .. }
+            | Expr::CompoundAssign {
+                target: left,
+                value: right,
+                ..
+            }
+            | Expr::Pipe {
+                value: left,
+                func: right,
+                ..
+            } => {
+                self.visit_expr(left);
+                self.visit_expr(right);
+            }
+            Expr::UnOp { expr, .. }
+            | Expr::Try { expr, .. }
+            | Expr::Ref { expr, .. }
+            | Expr::Borrow { expr, .. } => self.visit_expr(expr),
+            Expr::Call {
+                func,
+                args,
+                named_args,
+                ..
+            } => {
+                self.visit_expr(func);
+                for arg in args {
+                    self.visit_expr(arg);
+                }
+                for (_, arg) in named_args {
+                    self.visit_expr(arg);
+                }
+            }
+            Expr::FnDef {
+                params,
+                return_type,
+                body,
+                ..
+            } => self.visit_function(params, return_type.as_ref(), body),
+            Expr::Lambda { params, body, .. } => self.visit_function(params, None, body),
+            Expr::If {
+                condition,
+                then_branch,
+                elif_branches,
+                else_branch,
+                ..
+            } => self.visit_if(
+                condition,
+                then_branch,
+                elif_branches,
+                else_branch.as_deref(),
+            ),
+            Expr::Match { expr, arms, .. } => {
+                self.visit_expr(expr);
+                for arm in arms {
+                    let mut bound = Vec::new();
+                    self.visit_pattern(&arm.pattern, &mut bound);
+                    let bound: Vec<(String, Span)> =
+                        bound.into_iter().map(|name| (name, arm.span)).collect();
+                    self.with_scope(&bound, |this| {
+                        if let Pattern::Guard { condition, .. } = &arm.pattern {
+                            this.visit_expr(condition);
+                        }
+                        this.visit_block(&arm.body);
+                    });
+                }
+            }
+            Expr::While {
+                condition, body, ..
+            } => {
+                self.visit_expr(condition);
+                self.visit_block(body);
+            }
+            Expr::For {
+                var,
+                iterable,
+                body,
+                span,
+                ..
+            }
+            | Expr::SpawnFor {
+                var,
+                iterable,
+                body,
+                span,
+                ..
+            } => {
+                self.visit_expr(iterable);
+                self.with_scope(&[(var.clone(), *span)], |this| this.visit_block(body));
+            }
+            Expr::Block(block) => self.visit_block(block),
+            Expr::Unsafe { body, .. } | Expr::Spawn { body, .. } => self.visit_block(body),
+            Expr::Return(value, _) => {
+                if let Some(value) = value {
+                    self.visit_expr(value);
+                }
+            }
+            Expr::Cast {
+                expr, target_type, ..
+            } => {
+                self.visit_expr(expr);
+                self.visit_type(target_type);
+            }
+            Expr::Tuple(elems, _) | Expr::List(elems, _) => {
+                for elem in elems {
+                    self.visit_expr(elem);
+                }
+            }
+            Expr::ListComp {
+                element,
+                var,
+                iterable,
+                condition,
+                span,
+            } => {
+                self.visit_expr(iterable);
+                self.with_scope(&[(var.clone(), *span)], |this| {
+                    this.visit_expr(element);
+                    if let Some(cond) = condition {
+                        this.visit_expr(cond);
+                    }
+                });
+            }
+            Expr::Dict(entries, _) => {
+                for (key, value) in entries {
+                    self.visit_expr(key);
+                    self.visit_expr(value);
+                }
+            }
+            Expr::Index { expr, index, .. } => {
+                self.visit_expr(expr);
+                self.visit_expr(index);
+            }
+            Expr::FString { segments, .. } => {
+                for segment in segments {
+                    if let FStringSegment::Interpolation { expr, .. } = segment {
+                        self.visit_expr(expr);
+                    }
+                }
+            }
+        }
+    }
+
+    fn visit_function(
+        &mut self,
+        params: &[Param],
+        return_type: Option<&Type>,
+        body: &Block,
+    ) {
+        self.visit_params(params);
+        if let Some(ty) = return_type {
+            self.visit_type(ty);
+        }
+        let names = param_names(params);
+        self.with_scope(&names, |this| this.visit_block(body));
+    }
+
+    /// 记录模式中引用的类型名，并收集模式绑定的变量
+    fn visit_pattern(
+        &mut self,
+        pattern: &Pattern,
+        bound: &mut Vec<String>,
+    ) {
+        match pattern {
+            Pattern::Wildcard | Pattern::Literal(_) => {}
+            Pattern::Identifier(name) => bound.push(name.clone()),
+            Pattern::Tuple(items) | Pattern::Or(items) => {
+                for item in items {
+                    self.visit_pattern(item, bound);
+                }
+            }
+            Pattern::Struct { name, fields } => {
+                self.reference(name);
+                for (_, _, field) in fields {
+                    self.visit_pattern(field, bound);
+                }
+            }
+            Pattern::Union { name, pattern, .. } => {
+                self.reference(name);
+                if let Some(inner) = pattern {
+                    self.visit_pattern(inner, bound);
+                }
+            }
+            Pattern::Guard { pattern, .. } => self.visit_pattern(pattern, bound),
+        }
+    }
+
+    fn visit_type(
+        &mut self,
+        ty: &Type,
+    ) {
+        match ty {
+            Type::Name { name, .. } => self.reference(name),
+            Type::Generic { name, args, .. } => {
+                self.reference(name);
+                self.visit_types(args);
+            }
+            Type::Int(_)
+            | Type::Float(_)
+            | Type::Char
+            | Type::String
+            | Type::Bytes
+            | Type::Bool
+            | Type::Void
+            | Type::Enum(_) => {}
+            Type::Struct {
+                fields, bindings, ..
+            } => {
+                for field in fields {
+                    self.visit_type(&field.ty);
+                    if let Some(default) = &field.default {
+                        self.visit_expr(default);
+                    }
+                }
+                for binding in bindings {
+                    self.visit_binding_kind(&binding.kind);
+                }
+            }
+            Type::NamedStruct { fields, .. } => {
+                for field in fields {
+                    self.visit_type(&field.ty);
+                    if let Some(default) = &field.default {
+                        self.visit_expr(default);
+                    }
+                }
+            }
+            Type::Union(variants) => {
+                for (_, ty) in variants {
+                    if let Some(ty) = ty {
+                        self.visit_type(ty);
+                    }
+                }
+            }
+            Type::Variant(variants) => {
+                for variant in variants {
+                    for (_, ty) in &variant.params {
+                        self.visit_type(ty);
+                    }
+                }
+            }
+            Type::Tuple(types) | Type::Sum(types) | Type::MetaType { args: types, .. } => {
+                self.visit_types(types)
+            }
+            Type::Fn {
+                params,
+                return_type,
+            } => {
+                self.visit_types(params);
+                self.visit_type(return_type);
+            }
+            Type::Option(inner)
+            | Type::Ptr(inner)
+            | Type::Newtype(inner)
+            | Type::Ref { inner, .. }
+            | Type::Literal {
+                base_type: inner, ..
+            } => self.visit_type(inner),
+            Type::Result(ok, err) => {
+                self.visit_type(ok);
+                self.visit_type(err);
+            }
+            Type::AssocType {
+                host_type,
+                assoc_args,
+                ..
+            } => {
+                self.visit_type(host_type);
+                self.visit_types(assoc_args);
+            }
+            Type::ConstExpr(expr) => self.visit_expr(expr),
+        }
+    }
+
+    fn visit_types(
+        &mut self,
+        types: &[Type],
+    ) {
+        for ty in types {
+            self.visit_type(ty);
+        }
+    }
+}
+
+fn param_names(params: &[Param]) -> Vec<(String, Span)> {
+    params.iter().map(|p| (p.name.clone(), p.span)).collect()
+}
+
+/// `use` 语句引入作用域的名称
+///
+/// - `use std.io` → `io`；`use std.io as s` → `s`
+/// - `use std.{a, b}` → `a`、`b`；`use std.{a, b} as x, y` → `x`、`y`
+fn imported_names(
+    path: &str,
+    items: Option<&[String]>,
+    alias: Option<&[String]>,
+) -> Vec<String> {
+    match (items, alias) {
+        (_, Some(aliases)) => aliases.to_vec(),
+        (Some(items), None) => items.to_vec(),
+        (None, None) => vec![path.rsplit('.').next().unwrap_or(path).to_string()],
+    }
+}
+
+/// 可能未被调用的私有顶层函数：非 `pub`、非 `main`、非方法、有函数体
+fn private_function_name(stmt: &Stmt) -> Option<&str> {
+    match &stmt.kind {
+        StmtKind::Binding {
+            name,
+            type_name: None,
+            is_pub: false,
+            body,
+            ..
+        } if !body.is_empty() && name != "main" && !name.starts_with('_') => Some(name),
+        _ => None,
+    }
+}
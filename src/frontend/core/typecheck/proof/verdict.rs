@@ -3,7 +3,7 @@
 //! 所有编译期检查（类型等式、所有权、终止性、精化谓词）
 //! 统一返回此类型。这是 RFC-027 Section 4.1 的核心数据类型。
 
-use crate::util::diagnostic::Diagnostic;
+use crate::util::diagnostic::{Diagnostic, Severity};
 use crate::util::diagnostic::codes::ErrorCodeDefinition;
 use crate::util::span::Span;
 
@@ -67,7 +67,7 @@ pub struct DisproofModel {
     pub constraint: String,
     /// 违反位置
     pub span: Option<Span>,
-    /// 谓词定义位置（仅 PredicateViolation 时填入）
+    /// 关联位置：PredicateViolation 时为谓词定义位置，UseAfterMove 时为值被移动的位置
     pub predicate_span: Option<Span>,
 }
 
@@ -141,6 +141,13 @@ impl DisproofModel {
                 if let Some(span) = self.span {
                     builder = builder.at(span);
                 }
+                if let Some(moved_at) = self.predicate_span {
+                    let note = ErrorCodeDefinition::value_moved_here(&name)
+                        .at(moved_at)
+                        .severity(Severity::Hint)
+                        .build();
+                    builder = builder.with_related(vec![note]);
+                }
                 builder.build()
             }
             DisproofKind::UseAfterDrop => {
@@ -71,7 +71,7 @@ pub struct SemanticToken {
 }
 
 /// 符号位置
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct SymbolLocation {
     /// 文件路径
     pub file_path: String,
@@ -2,7 +2,7 @@
 //!
 //! 解析函数签名字符串为 MonoType
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use crate::frontend::core::types::MonoType;
 
@@ -112,9 +112,48 @@ pub fn parse_signature(
     // 解析返回类型
     let return_type = Box::new(parse_type_str_with_generics(return_str, &generic_params));
 
-    MonoType::Fn {
+    let fn_ty = MonoType::Fn {
         params,
         return_type,
+    };
+    if generic_params.is_empty() {
+        return fn_ty;
+    }
+    // 泛型参数换成类型变量，调用处逐次实例化
+    let vars: HashMap<String, MonoType> = generic_params
+        .iter()
+        .map(|gp| (gp.clone(), env.solver().new_var()))
+        .collect();
+    bind_generic_params(&fn_ty, &vars)
+}
+
+/// 把泛型参数引用（`TypeRef("T")`）替换为对应的类型变量
+fn bind_generic_params(
+    ty: &MonoType,
+    vars: &HashMap<String, MonoType>,
+) -> MonoType {
+    let bind = |t: &MonoType| Box::new(bind_generic_params(t, vars));
+    match ty {
+        MonoType::TypeRef(name) => vars.get(name).cloned().unwrap_or_else(|| ty.clone()),
+        MonoType::Fn {
+            params,
+            return_type,
+        } => MonoType::Fn {
+            params: params
+                .iter()
+                .map(|p| bind_generic_params(p, vars))
+                .collect(),
+            return_type: bind(return_type),
+        },
+        MonoType::Tuple(elems) => {
+            MonoType::Tuple(elems.iter().map(|e| bind_generic_params(e, vars)).collect())
+        }
+        MonoType::List(elem) => MonoType::List(bind(elem)),
+        MonoType::Set(elem) => MonoType::Set(bind(elem)),
+        MonoType::Option(elem) => MonoType::Option(bind(elem)),
+        MonoType::Dict(k, v) => MonoType::Dict(bind(k), bind(v)),
+        MonoType::Result(ok, err) => MonoType::Result(bind(ok), bind(err)),
+        _ => ty.clone(),
     }
 }
 
@@ -285,7 +324,7 @@ fn parse_type_str_with_generics(
         }
     }
 
-    // 处理泛型类型: List(T), Dict(String, Int)
+    // 处理泛型类型: List(T), Dict(String, Int), Option(Int)
     if let Some(paren_start) = type_str.find('(') {
         let base = &type_str[..paren_start];
         let inner_start = paren_start + 1;
@@ -319,6 +358,22 @@ fn parse_type_str_with_generics(
                         return MonoType::Set(inner_type);
                     }
                 }
+                "Option" => {
+                    let inner_types = split_by_top_level_comma(inner);
+                    if inner_types.len() == 1 {
+                        let inner_type =
+                            Box::new(parse_type_str_with_generics(inner_types[0], generic_params));
+                        return MonoType::Option(inner_type);
+                    }
+                }
+                "Result" => {
+                    let parts: Vec<&str> = split_by_top_level_comma(inner);
+                    if parts.len() == 2 {
+                        let ok = Box::new(parse_type_str_with_generics(parts[0], generic_params));
+                        let err = Box::new(parse_type_str_with_generics(parts[1], generic_params));
+                        return MonoType::Result(ok, err);
+                    }
+                }
                 _ => {}
             }
         }
@@ -0,0 +1,91 @@
+//! `Any` 顶类型测试
+//!
+//! 测试点：
+//! - 任何值可隐式上转为 `Any`（标注、参数、返回值、`List(Any)`）
+//! - 取回具体类型必须写 `as?`，结果为 `Option(T)`，判空后收窄
+//! - `Any` 不能隐式下转、不能用 `as` 转换（E1057）、不能直接参与算术（E1040）
+
+use crate::frontend::core::typecheck::checker::TypeChecker;
+use crate::frontend::core::lexer::tokenize;
+use crate::frontend::core::parser::parse;
+
+/// 辅助函数：解析源代码并类型检查，返回诊断码
+fn check_codes(source: &str) -> Vec<String> {
+    let tokens = tokenize(source).expect("tokenize failed");
+    let result = parse(&tokens);
+    assert!(!result.has_errors, "parse failed: {:?}", result.errors);
+    let mut checker = TypeChecker::new("test");
+    checker
+        .check_module(&result.module)
+        .diagnostics
+        .into_iter()
+        .map(|d| d.code)
+        .collect()
+}
+
+#[test]
+fn test_upcast_to_any_passes() {
+    let source = r#"
+        Point: Type = { x: Int, y: Int }
+        describe: (v: Any) -> Int = (v) => 0
+        wrap: (n: Int) -> Any = (n) => {
+            return n
+        }
+        main = {
+            a: Any = 1
+            b: List(Any) = [1, "two", Point(1, 2)]
+            c = describe("text")
+            d = describe(Point(3, 4))
+            e = wrap(5)
+        }
+    "#;
+    assert!(check_codes(source).is_empty());
+}
+
+#[test]
+fn test_checked_downcast_narrows() {
+    let source = r#"
+        main = {
+            a: Any = 41
+            n = a as? Int
+            if n != void {
+                m: Int = n + 1
+            }
+            o: Option(String) = a as? String
+        }
+    "#;
+    assert!(check_codes(source).is_empty());
+}
+
+#[test]
+fn test_implicit_downcast_rejected() {
+    let source = r#"
+        main = {
+            a: Any = 1
+            b: Int = a
+        }
+    "#;
+    assert_eq!(check_codes(source), vec!["E1002"]);
+}
+
+#[test]
+fn test_unchecked_cast_from_any_rejected() {
+    let source = r#"
+        main = {
+            a: Any = 1
+            b = a as Int
+        }
+    "#;
+    assert_eq!(check_codes(source), vec!["E1057"]);
+}
+
+#[test]
+fn test_arithmetic_on_any_rejected() {
+    let source = r#"
+        main = {
+            a: Any = 1
+            b = a + 1
+        }
+    "#;
+    assert_eq!(check_codes(source), vec!["E1040"]);
+}
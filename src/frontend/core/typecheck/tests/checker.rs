@@ -132,7 +132,7 @@ fn test_type_checker_reports_undefined_variable() {
     let module = Module {
         items: vec![Stmt {
             kind: crate::frontend::core::parser::ast::StmtKind::Expr(Box::new(Expr::Var(
-                "undefined_var".to_string(),
+                "undefined_var".into(),
                 Span::dummy(),
             ))),
             span: Span::dummy(),
@@ -176,20 +176,21 @@ fn test_type_checker_reports_fn_param_type_mismatch() {
                     }],
                     body: vec![Stmt {
                         kind: crate::frontend::core::parser::ast::StmtKind::Expr(Box::new(
-                            Expr::Var("x".to_string(), Span::dummy()),
+                            Expr::Var("x".into(), Span::dummy()),
                         )),
                         span: Span::dummy(),
                     }],
                     is_pub: false,
+                    attributes: Vec::new(),
                 },
                 span: Span::dummy(),
             },
             // add("hello") — 传入 String 但参数期望 Int
             Stmt {
                 kind: crate::frontend::core::parser::ast::StmtKind::Expr(Box::new(Expr::Call {
-                    func: Box::new(Expr::Var("add".to_string(), Span::dummy())),
+                    func: Box::new(Expr::Var("add".into(), Span::dummy())),
                     args: vec![Expr::Lit(
-                        crate::frontend::core::lexer::tokens::Literal::String("hello".to_string()),
+                        crate::frontend::core::lexer::tokens::Literal::String("hello".into()),
                         Span::dummy(),
                     )],
                     named_args: vec![],
@@ -224,7 +225,7 @@ fn test_type_checker_with_large_module() {
         // 添加大量语句
         items.push(Stmt {
             kind: crate::frontend::core::parser::ast::StmtKind::Expr(Box::new(Expr::Var(
-                format!("var_{}", i),
+                format!("var_{}", i).into(),
                 Span::dummy(),
             ))),
             span: Span::dummy(),
@@ -269,12 +270,13 @@ fn test_type_checker_with_multiple_function_definitions() {
                 }],
                 body: vec![Stmt {
                     kind: crate::frontend::core::parser::ast::StmtKind::Expr(Box::new(Expr::Var(
-                        "x".to_string(),
+                        "x".into(),
                         Span::dummy(),
                     ))),
                     span: Span::dummy(),
                 }],
                 is_pub: false,
+                attributes: Vec::new(),
             },
             span: Span::dummy(),
         }
@@ -314,7 +316,7 @@ fn test_type_checker_with_nested_function_definition() {
         body: Box::new(crate::frontend::core::parser::ast::Block {
             stmts: vec![Stmt {
                 kind: crate::frontend::core::parser::ast::StmtKind::Expr(Box::new(Expr::Var(
-                    "y".to_string(),
+                    "y".into(),
                     Span::dummy(),
                 ))),
                 span: Span::dummy(),
@@ -350,12 +352,13 @@ fn test_type_checker_with_nested_function_definition() {
                     },
                     Stmt {
                         kind: crate::frontend::core::parser::ast::StmtKind::Expr(Box::new(
-                            Expr::Var("x".to_string(), Span::dummy()),
+                            Expr::Var("x".into(), Span::dummy()),
                         )),
                         span: Span::dummy(),
                     },
                 ],
                 is_pub: false,
+                attributes: Vec::new(),
             },
             span: Span::dummy(),
         }],
@@ -398,6 +401,7 @@ fn test_type_checker_with_generic_type_binding() {
                                 span: Span::dummy(),
                             },
                             default: None,
+                            is_assoc: false,
                         }],
                         bindings: vec![],
                         interfaces: vec![],
@@ -406,6 +410,7 @@ fn test_type_checker_with_generic_type_binding() {
                     params: vec![],
                     body: vec![],
                     is_pub: false,
+                    attributes: Vec::new(),
                 },
                 span: Span::dummy(),
             },
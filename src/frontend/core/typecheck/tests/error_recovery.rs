@@ -0,0 +1,76 @@
+//! 错误累积测试
+//!
+//! 测试点：
+//! - 同一函数体内的多个独立错误一次全部报告
+//! - 嵌套代码块中的错误不会重复报告
+//! - 初始化失败的变量不会引出连锁的未定义错误
+
+use crate::frontend::core::typecheck::checker::TypeChecker;
+use crate::frontend::core::lexer::tokenize;
+use crate::frontend::core::parser::parse;
+
+/// 辅助函数：解析源代码并类型检查，返回诊断码
+fn check_codes(source: &str) -> Vec<String> {
+    let tokens = tokenize(source).expect("tokenize failed");
+    let result = parse(&tokens);
+    assert!(!result.has_errors, "parse failed: {:?}", result.errors);
+    let mut checker = TypeChecker::new("test");
+    checker
+        .check_module(&result.module)
+        .diagnostics
+        .into_iter()
+        .map(|d| d.code)
+        .collect()
+}
+
+#[test]
+fn test_errors_in_one_function_are_all_reported() {
+    let source = r#"
+        main = {
+            a: Int = "x"
+            b = missing
+            c: Bool = 1
+        }
+    "#;
+    assert_eq!(check_codes(source), ["E1002", "E1001", "E1002"]);
+}
+
+#[test]
+fn test_errors_in_sibling_functions_are_all_reported() {
+    let source = r#"
+        f = {
+            a: Int = "x"
+        }
+        main = {
+            b: Bool = 1
+        }
+    "#;
+    assert_eq!(check_codes(source), ["E1002", "E1002"]);
+}
+
+#[test]
+fn test_nested_block_error_is_reported_once() {
+    let source = r#"
+        main = {
+            if true {
+                a: Int = "x"
+            }
+            for i in [1, 2] {
+                b: Bool = i
+            }
+        }
+    "#;
+    assert_eq!(check_codes(source), ["E1002", "E1002"]);
+}
+
+#[test]
+fn test_failed_binding_does_not_cascade() {
+    let source = r#"
+        main = {
+            a = missing
+            b = a + 1
+            print(b)
+        }
+    "#;
+    assert_eq!(check_codes(source), ["E1001"]);
+}
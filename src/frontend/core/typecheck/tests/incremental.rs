@@ -0,0 +1,174 @@
+//! 增量类型检查测试
+//!
+//! 测试点：
+//! - 只改函数体时只重新检查该函数，其余函数复用上次的诊断
+//! - 增量结果与全量检查一致
+//! - 签名改动退回全量检查，并改变导出哈希
+//! - 复用的错误随行号平移
+//! - 持久化的产物在新进程中同样可复用，版本不符时退回全量检查
+
+use crate::frontend::core::typecheck::incremental::{IncrementalModule, ARTIFACT_VERSION};
+use crate::util::diagnostic::Diagnostic;
+
+const BASE: &str = r#"pub add: (a: Int, b: Int) -> Int = {
+    return a + b
+}
+
+broken: (x: Int) -> Int = {
+    y: Bool = 1
+    return x
+}
+
+main: () -> Void = {
+    z = add(1, 2)
+}
+"#;
+
+/// 诊断的 (错误码, 行, 列)，按位置排序
+fn located(diagnostics: &[Diagnostic]) -> Vec<(String, usize, usize)> {
+    let mut out: Vec<_> = diagnostics
+        .iter()
+        .map(|d| {
+            let (line, column) = d
+                .span
+                .map_or((0, 0), |span| (span.start.line, span.start.column));
+            (d.code.clone(), line, column)
+        })
+        .collect();
+    out.sort();
+    out
+}
+
+fn full_check(source: &str) -> Vec<(String, usize, usize)> {
+    located(&IncrementalModule::new().check(source).diagnostics)
+}
+
+#[test]
+fn test_first_check_rechecks_everything() {
+    let outcome = IncrementalModule::new().check(BASE);
+    assert_eq!(outcome.rechecked, 3);
+    assert_eq!(outcome.reused, 0);
+    assert!(outcome.diagnostics.iter().any(|d| d.code == "E1002"));
+}
+
+#[test]
+fn test_body_change_rechecks_only_that_function() {
+    let mut incremental = IncrementalModule::new();
+    let first = incremental.check(BASE);
+
+    let edited = BASE.replace("return a + b", "return b + a");
+    let outcome = incremental.check(&edited);
+    assert_eq!(outcome.rechecked, 1);
+    assert_eq!(outcome.reused, 2);
+    assert_eq!(outcome.export_hash, first.export_hash);
+    assert_eq!(located(&outcome.diagnostics), full_check(&edited));
+}
+
+#[test]
+fn test_signature_change_rechecks_everything() {
+    let mut incremental = IncrementalModule::new();
+    let first = incremental.check(BASE);
+
+    let edited = BASE.replace("pub add: (a: Int, b: Int)", "pub add: (a: Int, b: Float)");
+    let outcome = incremental.check(&edited);
+    assert_eq!(outcome.reused, 0);
+    assert_ne!(outcome.export_hash, first.export_hash);
+    assert_eq!(located(&outcome.diagnostics), full_check(&edited));
+}
+
+#[test]
+fn test_reused_errors_follow_shifted_lines() {
+    let mut incremental = IncrementalModule::new();
+    incremental.check(BASE);
+
+    // 在 add 的函数体中插入两行，broken 整体下移
+    let edited = BASE.replace("    return a + b", "    c = a\n    d = b\n    return c + d");
+    let outcome = incremental.check(&edited);
+    assert!(outcome.reused >= 1, "broken should be reused");
+    assert_eq!(located(&outcome.diagnostics), full_check(&edited));
+}
+
+#[test]
+fn test_fixing_a_reused_error_clears_it() {
+    let mut incremental = IncrementalModule::new();
+    incremental.check(BASE);
+
+    let fixed = BASE.replace("y: Bool = 1", "y: Bool = true");
+    let outcome = incremental.check(&fixed);
+    assert_eq!(outcome.rechecked, 1);
+    assert_eq!(located(&outcome.diagnostics), full_check(&fixed));
+    assert!(!outcome.diagnostics.iter().any(|d| d.code == "E1002"));
+}
+
+#[test]
+fn test_parse_error_keeps_previous_state() {
+    let mut incremental = IncrementalModule::new();
+    incremental.check(BASE);
+
+    let broken = BASE.replace("return a + b", "return a +");
+    let outcome = incremental.check(&broken);
+    assert!(outcome.module.is_none());
+
+    let outcome = incremental.check(BASE);
+    assert_eq!(outcome.rechecked, 0);
+    assert_eq!(outcome.reused, 3);
+}
+
+#[test]
+fn test_saved_artifact_is_reused_after_reload() {
+    let dir = tempfile::tempdir().expect("create temp dir");
+    let artifact = dir.path().join("cache").join("main.json");
+    let mut incremental = IncrementalModule::new();
+    incremental.check(BASE);
+    incremental.save(&artifact).expect("save artifact");
+
+    // 模拟下一次编译：从产物恢复后只重新检查改动过的函数
+    let mut reloaded = IncrementalModule::load(&artifact);
+    let edited = BASE.replace("return a + b", "return b + a");
+    let outcome = reloaded.check(&edited);
+    assert_eq!(outcome.rechecked, 1);
+    assert_eq!(outcome.reused, 2);
+    assert_eq!(located(&outcome.diagnostics), full_check(&edited));
+}
+
+#[test]
+fn test_artifact_records_item_signatures() {
+    let dir = tempfile::tempdir().expect("create temp dir");
+    let artifact = dir.path().join("main.json");
+    let mut incremental = IncrementalModule::new();
+    incremental.check(BASE);
+    incremental.save(&artifact).expect("save artifact");
+
+    let signatures = IncrementalModule::load(&artifact).signatures();
+    let names: Vec<&str> = signatures.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(names, ["add", "broken", "main"]);
+    assert!(signatures[0].1.contains("Int"), "{:?}", signatures[0]);
+}
+
+#[test]
+fn test_stale_or_corrupt_artifact_is_ignored() {
+    let dir = tempfile::tempdir().expect("create temp dir");
+    let artifact = dir.path().join("main.json");
+    let mut incremental = IncrementalModule::new();
+    incremental.check(BASE);
+    incremental.save(&artifact).expect("save artifact");
+
+    let text = std::fs::read_to_string(&artifact).expect("read artifact");
+    let stale = text.replacen(
+        &format!("\"version\":{}", ARTIFACT_VERSION),
+        &format!("\"version\":{}", ARTIFACT_VERSION + 1),
+        1,
+    );
+    assert_ne!(stale, text);
+    std::fs::write(&artifact, stale).expect("write stale artifact");
+    assert_eq!(IncrementalModule::load(&artifact).check(BASE).reused, 0);
+
+    std::fs::write(&artifact, "{ not json").expect("write corrupt artifact");
+    assert_eq!(IncrementalModule::load(&artifact).check(BASE).reused, 0);
+    assert_eq!(
+        IncrementalModule::load(&dir.path().join("missing.json"))
+            .check(BASE)
+            .reused,
+        0
+    );
+}
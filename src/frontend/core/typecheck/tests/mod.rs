@@ -8,16 +8,26 @@
 //!
 //! 单文件模块测试：
 //! - checker: TypeChecker 主检查器
+//! - error_recovery: 一次检查报告多个独立错误
+//! - incremental: 按签名哈希的增量检查
+//! - parallel: 独立函数体的并行检查
 //! - environment: TypeEnvironment 类型环境
 //! - signature: 签名解析
 //! - types: 类型定义
 //!
 //! 规范测试：
+//! - any_type: `Any` 顶类型与 `as?` 向下转换
 //! - rfc010: RFC-010 统一类型语法测试
 //! - rfc011: RFC-011 泛型系统测试
+//! - sized_numbers: 定宽数值类型
+//! - type_aliases: 类型别名与新类型
 
+mod any_type;
 mod checker;
 mod environment;
+mod error_recovery;
+mod incremental;
+mod parallel;
 mod rfc010;
 mod rfc011;
 mod rfc027_phase1_integration;
@@ -28,4 +38,6 @@ mod rfc027_phase33_termination;
 mod rfc027_phase3_e2e;
 mod rfc027_phase4_e2e;
 mod signature;
+mod sized_numbers;
+mod type_aliases;
 mod types;
@@ -0,0 +1,112 @@
+//! 并行函数体检查测试
+//!
+//! 测试点：
+//! - 只有签名完整标注的非泛型函数被视为独立函数
+//! - 并行检查的诊断仍按源码顺序报告
+//! - 并行检查的局部变量类型并回检查结果
+//! - 重名函数退回顺序检查，只检查第一个定义
+
+use crate::frontend::core::lexer::tokenize;
+use crate::frontend::core::parser::ast::Module;
+use crate::frontend::core::parser::parse;
+use crate::frontend::core::typecheck::checker::TypeChecker;
+use crate::frontend::core::typecheck::inference::StatementChecker;
+use crate::frontend::core::typecheck::TypeCheckResult;
+use crate::frontend::core::types::MonoType;
+
+fn parse_module(source: &str) -> Module {
+    let tokens = tokenize(source).expect("tokenize failed");
+    let result = parse(&tokens);
+    assert!(!result.has_errors, "parse failed: {:?}", result.errors);
+    result.module
+}
+
+fn check(source: &str) -> TypeCheckResult {
+    let module = parse_module(source);
+    TypeChecker::new("test").check_module(&module)
+}
+
+#[test]
+fn test_independent_fn_requires_full_concrete_signature() {
+    let module = parse_module(
+        r#"
+        add: (a: Int, b: Int) -> Int = (a, b) => a + b
+        inc = (x) => x + 1
+        id: (T: Type) -> (x: T) -> T = (x) => x
+        main = {
+            print("hi")
+        }
+        "#,
+    );
+    let independent: Vec<bool> = module
+        .items
+        .iter()
+        .map(StatementChecker::is_independent_fn)
+        .collect();
+    assert_eq!(independent, [true, false, false, false]);
+}
+
+#[test]
+fn test_parallel_errors_keep_source_order() {
+    let result = check(
+        r#"
+        f1: (x: Int) -> Int = (x) => {
+            a: Int = "x"
+            return x
+        }
+        f2: (x: Int) -> Int = (x) => {
+            return missing
+        }
+        f3: (x: Int) -> Bool = (x) => {
+            b: Bool = 1
+            return true
+        }
+        f4: (x: Int) -> Int = (x) => x + 1
+        "#,
+    );
+    let codes: Vec<&str> = result.diagnostics.iter().map(|d| d.code.as_str()).collect();
+    assert_eq!(codes, ["E1002", "E1001", "E1002"]);
+}
+
+#[test]
+fn test_parallel_locals_are_merged() {
+    let result = check(
+        r#"
+        double: (x: Int) -> Int = (x) => {
+            doubled = x * 2
+            return doubled
+        }
+        greet: (name: String) -> String = (name) => {
+            message = "hi " + name
+            return message
+        }
+        main = {
+            print(greet("yx"))
+        }
+        "#,
+    );
+    assert!(result.diagnostics.is_empty(), "{:?}", result.diagnostics);
+    assert!(matches!(
+        result.local_var_types.get("doubled"),
+        Some(MonoType::Int(_))
+    ));
+    assert_eq!(
+        result.local_var_types.get("message"),
+        Some(&MonoType::String)
+    );
+}
+
+#[test]
+fn test_duplicate_names_are_checked_sequentially() {
+    let result = check(
+        r#"
+        f: (x: Int) -> Int = (x) => x + 1
+        g: (x: Int) -> Int = (x) => x + 2
+        f: (x: Int) -> Int = (x) => {
+            a: Int = "never checked"
+            return x
+        }
+        "#,
+    );
+    assert!(result.diagnostics.is_empty(), "{:?}", result.diagnostics);
+}
@@ -207,6 +207,37 @@ fn test_rfc011_generic_explicit_fill_required() {
     );
 }
 
+/// 规范：嵌套的用户泛型实例
+///
+/// `items: List(Box(Int))`、`nested: Box(Box(Int))`
+///
+/// 预期行为：
+/// - 注解中的嵌套泛型实例展开为具体结构体
+/// - 字段访问逐层得到实例化后的字段类型
+/// - 参数注解为泛型实例的函数接受对应构造值
+#[test]
+fn test_rfc011_nested_generic_instances() {
+    // Arrange
+    let source = r#"
+        Box: (T: Type) -> Type = { value: T }
+        unbox: (b: Box(Int)) -> Int = (b) => { return b.value }
+        items: List(Box(Int)) = [Box(1), Box(2)]
+        nested: Box(Box(Int)) = Box(Box(9))
+        inner: Int = nested.value.value
+        direct: Int = unbox(Box(5))
+    "#;
+
+    // Act
+    let result = check_source(source);
+
+    // Assert
+    assert!(
+        result.diagnostics.is_empty(),
+        "nested generic instances should type check: {:?}",
+        result.diagnostics
+    );
+}
+
 // ===================================================================
 // RFC-011 §2: 类型约束系统
 // ===================================================================
@@ -345,6 +376,63 @@ fn test_rfc011_associated_type() {
     assert!(result.diagnostics.is_empty(), "associated type should pass");
 }
 
+/// 接口关联类型经类型参数访问：`T.Item`
+const ASSOC_ITEM_SOURCE: &str = r#"
+    Shape: Type = {
+        Self.Item: Type,
+        name: () -> String
+    }
+    Square: Type = { side: Int, Shape }
+    Square.Item: Type = Int
+    Square.name: (self: &Square) -> String = { return "square" }
+    Triangle: Type = { side: Int, Shape }
+    Triangle.Item: Type = Float
+    Triangle.name: (self: &Triangle) -> String = { return "triangle" }
+    echo_item: (T: Shape) -> (shape: T, x: T.Item) -> T.Item = (shape, x) => x
+"#;
+
+/// 规范：关联类型随类型参数单态化
+///
+/// `echo_item(Square(2), 41)` 中 T = Square，`T.Item` 解析为 `Square.Item` 即 Int
+///
+/// 预期行为：
+/// - 每个调用点按实现类型解析 `T.Item`
+/// - 返回值具有实现类型绑定的关联类型
+#[test]
+fn test_rfc011_associated_type_through_type_param() {
+    let source = format!(
+        "{}\nn: Int = echo_item(Square(2), 41)\nf: Float = echo_item(Triangle(3), 1.5)\n",
+        ASSOC_ITEM_SOURCE
+    );
+
+    let result = check_source(&source);
+
+    assert!(
+        result.diagnostics.is_empty(),
+        "T.Item should resolve per call site: {:?}",
+        result.diagnostics
+    );
+}
+
+/// 规范：关联类型约束实参
+///
+/// `Triangle.Item` 是 Float，传入 String 报类型错误
+#[test]
+fn test_rfc011_associated_type_mismatch() {
+    let source = format!(
+        "{}\nbad = echo_item(Triangle(3), \"no\")\n",
+        ASSOC_ITEM_SOURCE
+    );
+
+    let result = check_source(&source);
+
+    assert!(
+        result.diagnostics.iter().any(|d| d.code == "E1002"),
+        "String is not Triangle.Item: {:?}",
+        result.diagnostics
+    );
+}
+
 /// 规范：泛型关联类型（GAT）
 ///
 /// `Container: (Item: Type) -> Type = { IteratorType: Iterator(Item), iter: (Self) -> IteratorType }`
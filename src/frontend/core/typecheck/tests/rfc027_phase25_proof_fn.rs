@@ -195,6 +195,7 @@ fn make_proof_fn_module(
                 span: Span::dummy(),
             }],
             is_pub: false,
+            attributes: Vec::new(),
         },
         span: Span::dummy(),
     };
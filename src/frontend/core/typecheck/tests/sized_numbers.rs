@@ -0,0 +1,143 @@
+//! 定宽数值类型测试
+//!
+//! 测试点：
+//! - 字面量按上下文取类型并检查范围（E1056）
+//! - 同类位宽不减的隐式拓宽，收窄必须写 `as`
+//! - `as` 只接受数值、`Bool`、`Char` 来源（E1057）
+//! - 浮点与整数混合时整数一侧隐式转为浮点
+//! - 混合位宽的算术取较宽的一侧，结果不能隐式收窄
+
+use crate::frontend::core::typecheck::checker::TypeChecker;
+use crate::frontend::core::lexer::tokenize;
+use crate::frontend::core::parser::parse;
+
+/// 辅助函数：解析源代码并类型检查，返回诊断码
+fn check_codes(source: &str) -> Vec<String> {
+    let tokens = tokenize(source).expect("tokenize failed");
+    let result = parse(&tokens);
+    assert!(!result.has_errors, "parse failed: {:?}", result.errors);
+    let mut checker = TypeChecker::new("test");
+    checker
+        .check_module(&result.module)
+        .diagnostics
+        .into_iter()
+        .map(|d| d.code)
+        .collect()
+}
+
+#[test]
+fn test_sized_literals_and_widening_pass() {
+    let source = r#"
+        main = {
+            a: Int8 = 127
+            b: Int32 = a
+            c = 40i32 + b
+            d: Float32 = 1.5
+            e: Float = d
+        }
+    "#;
+    assert!(check_codes(source).is_empty());
+}
+
+#[test]
+fn test_literal_out_of_range() {
+    let source = r#"
+        main = {
+            a: Int8 = 200
+        }
+    "#;
+    assert_eq!(check_codes(source), vec!["E1056"]);
+}
+
+#[test]
+fn test_implicit_narrowing_rejected() {
+    let source = r#"
+        main = {
+            a = 5
+            b: Int32 = a
+        }
+    "#;
+    assert_eq!(check_codes(source), vec!["E1002"]);
+}
+
+#[test]
+fn test_cast_literal_checked_and_invalid_source_rejected() {
+    assert_eq!(check_codes("main = { a = 300 as Int8 }"), vec!["E1056"]);
+    assert_eq!(check_codes(r#"main = { a = "x" as Int32 }"#), vec!["E1057"]);
+    assert!(check_codes("main = { a = 300i64 as Int8 }").is_empty());
+}
+
+#[test]
+fn test_sized_call_argument() {
+    let source = r#"
+        take8: (a: Int8) -> Int8 = (a) => a
+        main = {
+            take8(1000)
+        }
+    "#;
+    assert_eq!(check_codes(source), vec!["E1056"]);
+}
+
+#[test]
+fn test_int_operands_widen_to_float() {
+    let source = r#"
+        half: (x: Float) -> Float = {
+            return x / 2.0
+        }
+        main = {
+            n = 3
+            a = 1.5 + n
+            b = a < 2
+            c: Float = 4
+            d = half(n)
+        }
+    "#;
+    let tokens = tokenize(source).expect("tokenize failed");
+    let result = parse(&tokens);
+    assert!(!result.has_errors, "parse failed: {:?}", result.errors);
+    let mut checker = TypeChecker::new("test");
+    let result = checker.check_module(&result.module);
+    assert!(result.diagnostics.is_empty(), "{:?}", result.diagnostics);
+    // `n`、`2`、`4` 与实参 `n` 各转换一次
+    assert_eq!(result.int_to_float.len(), 4);
+}
+
+#[test]
+fn test_mixed_width_arithmetic_widens() {
+    let source = r#"
+        main = {
+            a: Int8 = 100
+            c: Int64 = 1000
+            d: Int64 = a + c
+            e: Int64 = c - a
+            f: Float32 = 1.5
+            g: Float64 = 2.5
+            h: Float64 = f * g
+            i: Float64 = g / f
+            less = a < c
+        }
+    "#;
+    assert!(check_codes(source).is_empty());
+}
+
+#[test]
+fn test_mixed_width_int_result_not_narrowed() {
+    for expr in ["c + a", "a + c"] {
+        let source = format!(
+            "main = {{\n a: Int8 = 100\n c: Int64 = 1000\n e: Int8 = {}\n }}",
+            expr
+        );
+        assert_eq!(check_codes(&source), vec!["E1002"], "{}", expr);
+    }
+}
+
+#[test]
+fn test_mixed_width_float_result_not_narrowed() {
+    for expr in ["c * a", "a * c"] {
+        let source = format!(
+            "main = {{\n a: Float32 = 1.5\n c: Float64 = 2.5\n e: Float32 = {}\n }}",
+            expr
+        );
+        assert_eq!(check_codes(&source), vec!["E1002"], "{}", expr);
+    }
+}
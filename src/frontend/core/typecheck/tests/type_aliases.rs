@@ -0,0 +1,167 @@
+//! 类型别名与新类型测试
+//!
+//! 测试点：
+//! - `Meters: Type = Float` 是透明别名，与目标类型互相赋值
+//! - `UserId: Type = new Int` 是名义类型，只能经构造函数得到
+//! - 新类型与底层类型、不同新类型之间互不兼容（E1002）
+//! - 新类型只能与同一个新类型做算术和比较
+
+use crate::frontend::core::typecheck::checker::TypeChecker;
+use crate::frontend::core::lexer::tokenize;
+use crate::frontend::core::parser::parse;
+
+/// 辅助函数：解析源代码并类型检查，返回诊断码
+fn check_codes(source: &str) -> Vec<String> {
+    let tokens = tokenize(source).expect("tokenize failed");
+    let result = parse(&tokens);
+    assert!(!result.has_errors, "parse failed: {:?}", result.errors);
+    let mut checker = TypeChecker::new("test");
+    checker
+        .check_module(&result.module)
+        .diagnostics
+        .into_iter()
+        .map(|d| d.code)
+        .collect()
+}
+
+#[test]
+fn test_alias_is_transparent() {
+    let source = r#"
+        Meters: Type = Float
+        double: (m: Meters) -> Meters = (m) => m * 2.0
+        main = {
+            d: Meters = 3.5
+            raw: Float = double(d)
+            back: Meters = raw
+        }
+    "#;
+    assert!(check_codes(source).is_empty());
+}
+
+#[test]
+fn test_alias_chain_and_sized_target() {
+    let source = r#"
+        Byte: Type = Int8
+        Octet: Type = Byte
+        main = {
+            a: Octet = 100
+        }
+    "#;
+    assert!(check_codes(source).is_empty());
+
+    let source = r#"
+        Byte: Type = Int8
+        main = {
+            a: Byte = 300
+        }
+    "#;
+    assert_eq!(check_codes(source), vec!["E1056"]);
+}
+
+#[test]
+fn test_newtype_constructor_and_unwrap() {
+    let source = r#"
+        UserId: Type = new Int
+        next: (id: UserId) -> UserId = (id) => UserId(id as Int + 1)
+        main = {
+            u = next(UserId(41))
+            raw: Int = u as Int
+        }
+    "#;
+    assert!(check_codes(source).is_empty());
+}
+
+#[test]
+fn test_newtype_rejects_underlying_value() {
+    let source = r#"
+        UserId: Type = new Int
+        main = {
+            u: UserId = 42
+        }
+    "#;
+    assert_eq!(check_codes(source), vec!["E1002"]);
+
+    let source = r#"
+        UserId: Type = new Int
+        show: (id: UserId) -> Int = (id) => id as Int
+        main = {
+            show(42)
+        }
+    "#;
+    assert_eq!(check_codes(source), vec!["E1002"]);
+}
+
+#[test]
+fn test_distinct_newtypes_do_not_mix() {
+    let source = r#"
+        UserId: Type = new Int
+        OrderId: Type = new Int
+        show: (id: UserId) -> Int = (id) => id as Int
+        main = {
+            order = OrderId(7)
+            show(order)
+        }
+    "#;
+    assert_eq!(check_codes(source), vec!["E1002"]);
+}
+
+#[test]
+fn test_newtype_constructor_checks_argument() {
+    let source = r#"
+        UserId: Type = new Int
+        main = {
+            u = UserId("alice")
+        }
+    "#;
+    assert_eq!(check_codes(source), vec!["E1002"]);
+}
+
+#[test]
+fn test_newtype_arithmetic_with_itself() {
+    let source = r#"
+        UserId: Type = new Int
+        main = {
+            u = UserId(1)
+            v: UserId = u + u
+            same = u == v
+            less = u < v
+        }
+    "#;
+    assert!(check_codes(source).is_empty());
+}
+
+#[test]
+fn test_newtype_arithmetic_with_underlying_rejected() {
+    let source = r#"
+        UserId: Type = new Int
+        main = {
+            u = UserId(1)
+            x: Int = u + 1
+        }
+    "#;
+    assert_eq!(check_codes(source), vec!["E1002"]);
+}
+
+#[test]
+fn test_distinct_newtype_arithmetic_rejected() {
+    let source = r#"
+        UserId: Type = new Int
+        OrderId: Type = new Int
+        main = {
+            u = UserId(1) + OrderId(2)
+        }
+    "#;
+    assert_eq!(check_codes(source), vec!["E1002"]);
+}
+
+#[test]
+fn test_newtype_compared_with_literal_rejected() {
+    let source = r#"
+        UserId: Type = new Int
+        main = {
+            u = UserId(1)
+            b = u == 1
+        }
+    "#;
+    assert_eq!(check_codes(source), vec!["E1002"]);
+}
@@ -17,6 +17,8 @@ pub struct TypeCheckResult {
     pub module_name: String,
     /// 诊断信息（空 = 无错误）
     pub diagnostics: Vec<crate::util::diagnostic::Diagnostic>,
+    /// 警告诊断（不阻止编译，如引用 `#[deprecated]` 符号）
+    pub warnings: Vec<crate::util::diagnostic::Diagnostic>,
     pub bindings: HashMap<String, PolyType>,
     /// 局部变量的类型信息（用于 IR 生成器显示错误消息）
     /// Key 是变量名，Value 是推断出的具体类型
@@ -33,6 +35,15 @@ pub struct TypeCheckResult {
     pub escaped_refs: HashSet<String>,
     /// 实例化请求列表（单态化器使用）
     pub instantiation_requests: Vec<crate::middle::passes::mono::instance::InstantiationRequest>,
+    /// 泛型函数的参数约束（函数名 -> 各泛型参数的约束），单态化时校验
+    pub generic_bounds:
+        HashMap<String, Vec<Vec<crate::middle::passes::mono::instance::GenericBound>>>,
+    /// 定宽算术表达式的结果类型（运算符 span → `Int8/16/32`、`Float32`），IR 生成据此收窄结果
+    pub sized_arith: HashMap<crate::util::span::Span, MonoType>,
+    /// 隐式转为浮点的整数表达式（表达式 span），IR 生成据此插入 `IntToFloat`
+    pub int_to_float: HashSet<crate::util::span::Span>,
+    /// 表达式与变量声明名的推断类型（span → 展开后的类型），供 `Compiler::type_at` 按位置查询
+    pub expr_types: HashMap<crate::util::span::Span, MonoType>,
 }
 
 /// 导入信息
@@ -285,7 +285,7 @@ pub fn convert_expr_to_const_expr(expr: &Expr) -> Option<ConstExpr> {
             Literal::Float(f) => Some(ConstExpr::Float(*f as f32)),
             _ => None,
         },
-        Expr::Var(name, _) => Some(ConstExpr::Var(name.clone())),
+        Expr::Var(name, _) => Some(ConstExpr::Var(name.to_string())),
         Expr::BinOp {
             op, left, right, ..
         } => {
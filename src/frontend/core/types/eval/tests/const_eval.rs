@@ -847,6 +847,7 @@ fn test_generic_size_struct() {
         field_mutability: vec![false, false],
         field_has_default: vec![false, false],
         interfaces: vec![],
+        assoc_items: Vec::new(),
     });
     // Struct is not supported by GenericSize
     assert!(gs.size_of(&s).is_err());
@@ -1183,7 +1184,7 @@ fn test_convert_expr_to_const_expr_var() {
     use crate::frontend::core::types::eval::const_eval::convert_expr_to_const_expr;
 
     // Act
-    let result = convert_expr_to_const_expr(&Expr::Var("N".to_string(), Span::dummy()));
+    let result = convert_expr_to_const_expr(&Expr::Var("N".into(), Span::dummy()));
     // Assert
     assert_eq!(
         result,
@@ -1202,7 +1203,7 @@ fn test_convert_expr_to_const_expr_binop_gt() {
     // Arrange
     let expr = Expr::BinOp {
         op: BinOp::Gt,
-        left: Box::new(Expr::Var("N".to_string(), Span::dummy())),
+        left: Box::new(Expr::Var("N".into(), Span::dummy())),
         right: Box::new(Expr::Lit(Literal::Int(0), Span::dummy())),
         span: Span::dummy(),
     };
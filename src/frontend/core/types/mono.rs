@@ -154,6 +154,8 @@ pub enum MonoType {
     String,
     /// 字节数组
     Bytes,
+    /// 顶类型 `Any`：任何值都可隐式上转为 `Any`，取回具体类型需 `as?` 向下转换
+    Any,
     /// 结构体类型
     Struct(StructType),
     /// 枚举类型
@@ -202,6 +204,9 @@ pub enum MonoType {
     Arc(Box<MonoType>),
     /// Weak 类型（不增加引用计数）
     Weak(Box<MonoType>),
+    /// 异步结果类型：`spawn f(x)` 的类型 `Async(T)`
+    /// 编译期标记 — 使用处隐式汇合（join）为 `T`，无运行时表示
+    Async(Box<MonoType>),
     /// 借用引用类型：`&T`（不可变）或 `&mut T`（可变）
     /// 编译期零大小类型 — 无运行时表示
     Ref {
@@ -297,14 +302,14 @@ impl MonoType {
 
     /// 判断是否是约束类型（所有字段都是函数类型）
     ///
-    /// 约束类型 = 接口，定义为所有字段都是函数类型的记录类型
+    /// 约束类型 = 接口，定义为所有字段都是函数类型的记录类型。
+    /// 接口还可以声明关联项（`Self.Item: Type`、`Self.SIDES: Int`），它们不是数据字段。
     pub fn is_constraint(&self) -> bool {
         match self {
-            // 结构体类型：检查所有字段是否都是函数类型
-            MonoType::Struct(s) => s
-                .fields
-                .iter()
-                .all(|(_, ty)| matches!(ty, MonoType::Fn { .. })),
+            // 结构体类型：检查所有字段是否都是函数类型（或关联项）
+            MonoType::Struct(s) => s.fields.iter().all(|(name, ty)| {
+                matches!(ty, MonoType::Fn { .. }) || s.assoc_items.contains(name)
+            }),
             // TypeRef 指向的可能是约束类型（需要结合类型环境判断）
             // 这里返回 false，具体判断在类型检查时结合环境确定
             MonoType::TypeRef(_) => false,
@@ -314,6 +319,21 @@ impl MonoType {
         }
     }
 
+    /// 汇合异步结果：`Async(T)` 返回 `T`，其他类型原样返回
+    pub fn joined(self) -> MonoType {
+        match self {
+            MonoType::Async(inner) => *inner,
+            ty => ty,
+        }
+    }
+
+    /// 是否为匿名记录类型（如 `{ x: Int, y: Int }`）
+    ///
+    /// 匿名记录按结构比较：字段更多的结构体可用在字段更少的记录处（宽度子类型）
+    pub fn is_record(&self) -> bool {
+        matches!(self, MonoType::Struct(s) if s.name.is_empty()) && !self.is_constraint()
+    }
+
     /// 获取约束的所有要求字段
     /// 返回字段名和类型的列表
     pub fn constraint_fields(&self) -> Vec<(String, &MonoType)> {
@@ -330,6 +350,20 @@ impl MonoType {
         }
     }
 
+    /// 获取约束声明的关联项（`Self.NAME` 字段）
+    /// 返回关联项名和声明类型的列表
+    pub fn constraint_assoc_items(&self) -> Vec<(String, &MonoType)> {
+        match self {
+            MonoType::Struct(s) if self.is_constraint() => s
+                .fields
+                .iter()
+                .filter(|(name, _)| s.assoc_items.contains(name))
+                .map(|(name, ty)| (name.clone(), ty))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
     /// 获取类型的字符串描述
     pub fn type_name(&self) -> String {
         match self {
@@ -340,6 +374,15 @@ impl MonoType {
             MonoType::Char => "char".to_string(),
             MonoType::String => "string".to_string(),
             MonoType::Bytes => "bytes".to_string(),
+            MonoType::Any => "Any".to_string(),
+            MonoType::Struct(s) if s.name.is_empty() => format!(
+                "{{ {} }}",
+                s.fields
+                    .iter()
+                    .map(|(name, ty)| format!("{}: {}", name, ty.type_name()))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
             MonoType::Struct(s) => s.name.clone(),
             MonoType::Enum(e) => e.name.clone(),
             MonoType::Tuple(types) => {
@@ -405,6 +448,7 @@ impl MonoType {
             }
             MonoType::Arc(t) => format!("Arc({})", t.type_name()),
             MonoType::Weak(t) => format!("Weak({})", t.type_name()),
+            MonoType::Async(t) => format!("Async({})", t.type_name()),
             MonoType::Ref { mutable, inner } => {
                 if *mutable {
                     format!("&mut {}", inner.type_name())
@@ -493,6 +537,7 @@ impl fmt::Display for MonoType {
 impl From<ast::Type> for MonoType {
     fn from(ast_type: ast::Type) -> Self {
         match ast_type {
+            ast::Type::Name { name, .. } if name == "Any" => MonoType::Any,
             ast::Type::Name { name, .. } => MonoType::TypeRef(name),
             ast::Type::Int(n) => MonoType::Int(n),
             ast::Type::Float(n) => MonoType::Float(n),
@@ -504,6 +549,11 @@ impl From<ast::Type> for MonoType {
             ast::Type::Struct {
                 fields, interfaces, ..
             } => {
+                let assoc_items = fields
+                    .iter()
+                    .filter(|f| f.is_assoc)
+                    .map(|f| f.name.clone())
+                    .collect();
                 let (field_names, field_types, field_mutability, field_has_default) = fields
                     .into_iter()
                     .map(|f| (f.name, MonoType::from(f.ty), f.is_mut, f.default.is_some()))
@@ -524,6 +574,7 @@ impl From<ast::Type> for MonoType {
                     field_mutability,
                     field_has_default,
                     interfaces,
+                    assoc_items,
                 })
             }
             ast::Type::Union(variants) => MonoType::Enum(EnumType {
@@ -577,6 +628,9 @@ impl From<ast::Type> for MonoType {
                 if name == "Set" && args.len() == 1 {
                     return MonoType::Set(Box::new(MonoType::from(args[0].clone())));
                 }
+                if name == "Async" && args.len() == 1 {
+                    return MonoType::Async(Box::new(MonoType::from(args[0].clone())));
+                }
                 // 泛型类型，如 Option(T), List(Int)
                 MonoType::Generic {
                     name,
@@ -614,6 +668,7 @@ impl From<ast::Type> for MonoType {
                     field_mutability,
                     field_has_default: Vec::new(),
                     interfaces: vec![],
+                    assoc_items: Vec::new(),
                 })
             }
             ast::Type::Sum(types) => {
@@ -628,12 +683,17 @@ impl From<ast::Type> for MonoType {
                 ))
             }
             ast::Type::Literal {
-                name: _, base_type, ..
-            } => {
-                // Literal type - for now, convert to the base type
-                // The actual literal value is handled during const evaluation
-                MonoType::from(*base_type)
-            }
+                name, base_type, ..
+            } => match name.parse::<i128>() {
+                // 整数字面量实参（`Vec(Float, 3)`）保留值，供 const 泛型参数校验
+                Ok(value) => MonoType::Literal {
+                    name,
+                    base_type: Box::new(MonoType::Int(64)),
+                    value: ConstValue::Int(value),
+                },
+                // 其他字面量类型按基础类型处理，值在常量求值阶段确定
+                Err(_) => MonoType::from(*base_type),
+            },
             ast::Type::Ptr(inner) => {
                 // Raw pointer type: *T
                 MonoType::TypeRef(format!("*{}", MonoType::from(*inner).type_name()))
@@ -659,6 +719,8 @@ impl From<ast::Type> for MonoType {
                 // ConstExpr 只在 Assert 参数位置出现，不应出现在类型转换中
                 MonoType::TypeRef("<const-expr>".to_string())
             }
+            // 新类型的名义身份由类型定义处登记；单独转换时取底层类型
+            ast::Type::Newtype(inner) => MonoType::from(*inner),
         }
     }
 }
@@ -721,6 +783,8 @@ pub struct StructType {
     pub field_has_default: Vec<bool>,
     /// RFC-010: 接口约束列表
     pub interfaces: Vec<String>,
+    /// 接口声明的关联项（`Self.Item: Type`、`Self.SIDES: Int`）在 fields 中的名称
+    pub assoc_items: Vec<String>,
 }
 
 impl StructType {
@@ -753,9 +817,9 @@ impl Hash for StructType {
         &self,
         state: &mut H,
     ) {
+        // 与 `PartialEq` 一致：只按名称与字段区分
         self.name.hash(state);
         self.fields.hash(state);
-        self.field_mutability.hash(state);
     }
 }
 
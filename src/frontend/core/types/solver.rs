@@ -23,6 +23,10 @@ pub struct TypeConstraintSolver {
     next_var: usize,
     /// 泛型变量集合（不应被实例化）
     generic_vars: HashMap<usize, usize>,
+    /// 透明类型别名：`Meters: Type = Float`，展开后与目标类型等价
+    type_aliases: HashMap<String, MonoType>,
+    /// 新类型：`UserId: Type = new Int`，名义类型，不与底层类型统一
+    newtypes: HashMap<String, MonoType>,
 }
 
 impl TypeConstraintSolver {
@@ -33,6 +37,8 @@ impl TypeConstraintSolver {
             constraints: Vec::new(),
             next_var: 0,
             generic_vars: HashMap::new(),
+            type_aliases: HashMap::new(),
+            newtypes: HashMap::new(),
         }
     }
 
@@ -42,6 +48,52 @@ impl TypeConstraintSolver {
         self.constraints.clear();
         self.next_var = 0;
         self.generic_vars.clear();
+        self.type_aliases.clear();
+        self.newtypes.clear();
+    }
+
+    /// 登记透明类型别名
+    pub fn add_type_alias(
+        &mut self,
+        name: String,
+        target: MonoType,
+    ) {
+        self.type_aliases.insert(name, target);
+    }
+
+    /// 登记新类型及其底层类型
+    pub fn add_newtype(
+        &mut self,
+        name: String,
+        underlying: MonoType,
+    ) {
+        self.newtypes.insert(name, underlying);
+    }
+
+    /// 新类型的底层类型；不是新类型时返回 `None`
+    pub fn newtype_underlying(
+        &self,
+        name: &str,
+    ) -> Option<&MonoType> {
+        self.newtypes.get(name)
+    }
+
+    /// 沿别名链找到最终目标类型；别名成环时返回 `None`
+    pub fn resolve_alias(
+        &self,
+        name: &str,
+    ) -> Option<MonoType> {
+        let mut target = self.type_aliases.get(name)?;
+        for _ in 0..self.type_aliases.len() {
+            match target {
+                MonoType::TypeRef(next) => match self.type_aliases.get(next) {
+                    Some(next_target) => target = next_target,
+                    None => return Some(target.clone()),
+                },
+                _ => return Some(target.clone()),
+            }
+        }
+        None
     }
 
     /// 创建新的类型变量
@@ -166,6 +218,7 @@ impl TypeConstraintSolver {
             }
             MonoType::TypeRef(name) => self
                 .resolve_builtin_type_ref(name)
+                .or_else(|| self.resolve_alias(name).map(|t| self.expand_type(&t)))
                 .unwrap_or_else(|| ty.clone()),
             MonoType::Struct(s) => MonoType::Struct(StructType {
                 name: s.name.clone(),
@@ -178,6 +231,7 @@ impl TypeConstraintSolver {
                 field_mutability: s.field_mutability.clone(),
                 field_has_default: s.field_has_default.clone(),
                 interfaces: s.interfaces.clone(),
+                assoc_items: s.assoc_items.clone(),
             }),
             MonoType::Enum(e) => MonoType::Enum(EnumType {
                 name: e.name.clone(),
@@ -196,6 +250,7 @@ impl TypeConstraintSolver {
             },
             MonoType::Arc(inner) => MonoType::Arc(Box::new(self.expand_type(inner))),
             MonoType::Weak(inner) => MonoType::Weak(Box::new(self.expand_type(inner))),
+            MonoType::Async(inner) => MonoType::Async(Box::new(self.expand_type(inner))),
             MonoType::AssocType {
                 host_type,
                 assoc_name,
@@ -334,9 +389,13 @@ impl TypeConstraintSolver {
                     MonoType::TypeVar(root)
                 }
             }
-            MonoType::TypeRef(name) => self
-                .resolve_builtin_type_ref(name)
-                .unwrap_or_else(|| ty.clone()),
+            MonoType::TypeRef(name) => match self.resolve_builtin_type_ref(name) {
+                Some(builtin) => builtin,
+                None => match self.resolve_alias(name) {
+                    Some(target) => self.expand_type_mut(&target),
+                    None => ty.clone(),
+                },
+            },
             MonoType::Struct(s) => MonoType::Struct(StructType {
                 name: s.name.clone(),
                 fields: s
@@ -348,6 +407,7 @@ impl TypeConstraintSolver {
                 field_mutability: s.field_mutability.clone(),
                 field_has_default: s.field_has_default.clone(),
                 interfaces: s.interfaces.clone(),
+                assoc_items: s.assoc_items.clone(),
             }),
             MonoType::Enum(e) => MonoType::Enum(EnumType {
                 name: e.name.clone(),
@@ -367,6 +427,7 @@ impl TypeConstraintSolver {
             },
             MonoType::Arc(inner) => MonoType::Arc(Box::new(self.expand_type_mut(inner))),
             MonoType::Weak(inner) => MonoType::Weak(Box::new(self.expand_type_mut(inner))),
+            MonoType::Async(inner) => MonoType::Async(Box::new(self.expand_type_mut(inner))),
             MonoType::AssocType {
                 host_type,
                 assoc_name,
@@ -458,6 +519,8 @@ impl TypeConstraintSolver {
             (MonoType::Char, MonoType::Char) => Ok(()),
             (MonoType::String, MonoType::String) => Ok(()),
             (MonoType::Bytes, MonoType::Bytes) => Ok(()),
+            // `Any` 只与自身统一：上转由调用处按方向检查（`upcasts_to_any`），下转须显式 `as?`
+            (MonoType::Any, MonoType::Any) => Ok(()),
 
             // 函数类型 unify
             (
@@ -646,6 +709,12 @@ impl TypeConstraintSolver {
                 self.unify(i1, i2)
             }
 
+            // 异步结果 unify：Async(T) 在使用处隐式汇合为 T
+            (MonoType::Async(a1), MonoType::Async(a2)) => self.unify(a1, a2),
+            (MonoType::Async(inner), other) | (other, MonoType::Async(inner)) => {
+                self.unify(inner, other)
+            }
+
             // 结构化子类型：Struct 声明实现了 TypeRef 接口，或类型名匹配
             (MonoType::Struct(s), MonoType::TypeRef(name))
             | (MonoType::TypeRef(name), MonoType::Struct(s)) => {
@@ -706,6 +775,7 @@ impl TypeConstraintSolver {
                 field_mutability: s.field_mutability.clone(),
                 field_has_default: s.field_has_default.clone(),
                 interfaces: s.interfaces.clone(),
+                assoc_items: s.assoc_items.clone(),
             }),
             MonoType::Enum(e) => MonoType::Enum(EnumType {
                 name: e.name.clone(),
@@ -747,6 +817,9 @@ impl TypeConstraintSolver {
             MonoType::Arc(inner) => {
                 MonoType::Arc(Box::new(self.substitute_type(inner, substitution)))
             }
+            MonoType::Async(inner) => {
+                MonoType::Async(Box::new(self.substitute_type(inner, substitution)))
+            }
             // 关联类型替换
             MonoType::AssocType {
                 host_type,
@@ -816,7 +889,7 @@ impl TypeConstraintSolver {
                     || self.contains_var(return_type, var)
             }
             MonoType::Range { elem_type } => self.contains_var(elem_type, var),
-            MonoType::Arc(inner) => self.contains_var(inner, var),
+            MonoType::Arc(inner) | MonoType::Async(inner) => self.contains_var(inner, var),
             MonoType::AssocType {
                 host_type,
                 assoc_args,
@@ -972,6 +1045,7 @@ impl TypeConstraintSolver {
             | MonoType::Set(t)
             | MonoType::Arc(t)
             | MonoType::Weak(t)
+            | MonoType::Async(t)
             | MonoType::Option(t) => self.collect_generalizable_vars(t, seen, out),
             MonoType::Ref { inner, .. } => self.collect_generalizable_vars(inner, seen, out),
             MonoType::Range { elem_type } => {
@@ -1022,6 +1096,7 @@ impl TypeConstraintSolver {
             | MonoType::Char
             | MonoType::String
             | MonoType::Bytes
+            | MonoType::Any
             | MonoType::LibraryRef { .. }
             | MonoType::ExternRef { .. } => {}
             MonoType::Generic { args, .. } => {
@@ -177,6 +177,11 @@ impl Substituter {
                 Box::new(self.substitute_internal(v, lookup)),
             ),
             MonoType::Set(t) => MonoType::Set(Box::new(self.substitute_internal(t, lookup))),
+            MonoType::Option(t) => MonoType::Option(Box::new(self.substitute_internal(t, lookup))),
+            MonoType::Result(ok, err) => MonoType::Result(
+                Box::new(self.substitute_internal(ok, lookup)),
+                Box::new(self.substitute_internal(err, lookup)),
+            ),
             MonoType::Fn {
                 params,
                 return_type,
@@ -206,6 +211,7 @@ impl Substituter {
                     field_mutability: struct_type.field_mutability.clone(),
                     field_has_default: struct_type.field_has_default.clone(),
                     interfaces: struct_type.interfaces.clone(),
+                    assoc_items: struct_type.assoc_items.clone(),
                 })
             }
             MonoType::Enum(e) => MonoType::Enum(EnumType {
@@ -229,6 +235,7 @@ impl Substituter {
             ),
             MonoType::Arc(t) => MonoType::Arc(Box::new(self.substitute_internal(t, lookup))),
             MonoType::Weak(t) => MonoType::Weak(Box::new(self.substitute_internal(t, lookup))),
+            MonoType::Async(t) => MonoType::Async(Box::new(self.substitute_internal(t, lookup))),
             MonoType::AssocType {
                 host_type,
                 assoc_name,
@@ -254,7 +261,8 @@ pub fn contains_type_vars(ty: &MonoType) -> bool {
         MonoType::List(inner) => contains_type_vars(inner),
         MonoType::Tuple(types) => types.iter().any(contains_type_vars),
         MonoType::Dict(k, v) => contains_type_vars(k) || contains_type_vars(v),
-        MonoType::Set(t) => contains_type_vars(t),
+        MonoType::Set(t) | MonoType::Option(t) => contains_type_vars(t),
+        MonoType::Result(ok, err) => contains_type_vars(ok) || contains_type_vars(err),
         MonoType::Fn {
             params,
             return_type,
@@ -270,6 +278,7 @@ pub fn contains_type_vars(ty: &MonoType) -> bool {
         }
         MonoType::Arc(t) => contains_type_vars(t),
         MonoType::Weak(t) => contains_type_vars(t),
+        MonoType::Async(t) => contains_type_vars(t),
         MonoType::AssocType {
             host_type,
             assoc_args,
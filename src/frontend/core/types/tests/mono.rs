@@ -61,6 +61,7 @@ fn test_from_ast_type_struct_with_defaults() {
             is_mut: false,
             ty: ast::Type::Float(64),
             default: Some(Box::new(ast::Expr::Lit(Literal::Float(0.0), Span::dummy()))),
+            is_assoc: false,
         }],
         bindings: vec![],
         interfaces: vec![],
@@ -266,6 +267,7 @@ fn test_struct_type_eq_same_fields() {
         field_mutability: vec![false],
         field_has_default: vec![false],
         interfaces: vec![],
+        assoc_items: Vec::new(),
     };
     let b = StructType {
         name: "P".to_string(),
@@ -274,6 +276,7 @@ fn test_struct_type_eq_same_fields() {
         field_mutability: vec![false],
         field_has_default: vec![false],
         interfaces: vec![],
+        assoc_items: Vec::new(),
     };
     assert_eq!(a, b);
 }
@@ -287,6 +290,7 @@ fn test_struct_type_eq_different_fields() {
         field_mutability: vec![false],
         field_has_default: vec![false],
         interfaces: vec![],
+        assoc_items: Vec::new(),
     };
     let b = StructType {
         name: "P".to_string(),
@@ -295,6 +299,7 @@ fn test_struct_type_eq_different_fields() {
         field_mutability: vec![false],
         field_has_default: vec![false],
         interfaces: vec![],
+        assoc_items: Vec::new(),
     };
     assert_ne!(a, b);
 }
@@ -370,6 +375,8 @@ fn test_type_name_arc_weak_assoc() {
     assert_eq!(a.type_name(), "Arc(int32)");
     let w = MonoType::Weak(Box::new(MonoType::String));
     assert_eq!(w.type_name(), "Weak(string)");
+    let asy = MonoType::Async(Box::new(MonoType::Int(64)));
+    assert_eq!(asy.type_name(), "Async(int64)");
     let at = MonoType::AssocType {
         host_type: Box::new(MonoType::TypeRef("Iter".to_string())),
         assoc_name: "Item".to_string(),
@@ -425,6 +432,7 @@ fn test_is_constraint_and_constraint_fields() {
         field_mutability: vec![false],
         field_has_default: vec![false],
         interfaces: vec![],
+        assoc_items: Vec::new(),
     });
     assert!(iface.is_constraint());
     let fields = iface.constraint_fields();
@@ -434,6 +442,43 @@ fn test_is_constraint_and_constraint_fields() {
         .is_empty());
 }
 
+#[test]
+fn test_constraint_with_assoc_items() {
+    let make = |extra: &str, assoc_items: Vec<String>| {
+        let fields = vec![
+            (extra.to_string(), MonoType::Int(64)),
+            (
+                "name".to_string(),
+                MonoType::Fn {
+                    params: vec![],
+                    return_type: Box::new(MonoType::String),
+                },
+            ),
+        ];
+        MonoType::Struct(StructType {
+            name: "Shape".to_string(),
+            fields,
+            methods: HashMap::new(),
+            field_mutability: vec![false; 2],
+            field_has_default: vec![false; 2],
+            interfaces: vec![],
+            assoc_items,
+        })
+    };
+
+    let iface = make("SIDES", vec!["SIDES".to_string()]);
+    assert!(iface.is_constraint());
+    assert_eq!(iface.constraint_fields().len(), 1);
+    let items = iface.constraint_assoc_items();
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].0, "SIDES");
+
+    // 未以 `Self.` 声明的字段是数据字段，与命名无关
+    let data = make("SIDES", vec![]);
+    assert!(!data.is_constraint());
+    assert!(data.constraint_assoc_items().is_empty());
+}
+
 #[test]
 fn test_struct_field_is_mut_found() {
     let s = StructType {
@@ -446,6 +491,7 @@ fn test_struct_field_is_mut_found() {
         field_mutability: vec![true, false],
         field_has_default: vec![false, false],
         interfaces: vec![],
+        assoc_items: Vec::new(),
     };
     assert_eq!(s.field_is_mut("a"), Some(true));
     assert_eq!(s.field_is_mut("b"), Some(false));
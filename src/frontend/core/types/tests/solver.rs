@@ -36,6 +36,7 @@ fn struct_ty(
         field_mutability: vec![false; field_count],
         field_has_default: vec![false; field_count],
         interfaces: vec![],
+        assoc_items: Vec::new(),
     })
 }
 
@@ -81,6 +82,22 @@ fn test_unify_cross_kind_mismatch() {
     assert!(solver.unify(&MonoType::Char, &MonoType::Bytes).is_err());
 }
 
+#[test]
+fn test_unify_async_joins_to_inner() {
+    // Async(T) 在使用处隐式汇合为 T
+    let mut solver = s();
+    let asy = MonoType::Async(Box::new(MonoType::Int(64)));
+    assert!(solver.unify(&asy, &MonoType::Int(64)).is_ok());
+    assert!(solver.unify(&MonoType::Int(64), &asy).is_ok());
+    assert!(solver.unify(&asy, &asy.clone()).is_ok());
+    assert!(solver.unify(&asy, &MonoType::String).is_err());
+
+    // 类型变量绑定保留 Async 标记
+    let v = solver.new_var();
+    assert!(solver.unify(&v, &asy).is_ok());
+    assert_eq!(solver.resolve_type(&v), asy);
+}
+
 // ===================================================================
 // §3.3: 结构体统一
 // ===================================================================
@@ -528,6 +545,7 @@ fn test_contains_var_nested() {
             field_mutability: vec![false],
             field_has_default: vec![false],
             interfaces: vec![],
+            assoc_items: Vec::new(),
         }),
         tv,
     ));
@@ -777,6 +795,7 @@ fn test_contains_var_in_containers() {
         field_mutability: vec![false],
         field_has_default: vec![false],
         interfaces: vec![],
+        assoc_items: Vec::new(),
     });
     assert!(solver.contains_var(&s, v));
 
@@ -1107,6 +1126,7 @@ fn test_instantiate_poly_with_struct() {
             field_mutability: vec![false],
             field_has_default: vec![false],
             interfaces: vec![],
+            assoc_items: Vec::new(),
         }),
     );
     let inst = solver.instantiate(&poly);
@@ -1162,6 +1182,7 @@ fn test_expand_mut_struct_with_bound_var() {
         field_mutability: vec![false],
         field_has_default: vec![false],
         interfaces: vec![],
+        assoc_items: Vec::new(),
     });
     let resolved = solver.resolve(&s);
     assert!(matches!(resolved, MonoType::Struct(ref ss) if ss.fields[0].1 == MonoType::Float(64)));
@@ -1220,6 +1241,7 @@ fn test_generalize_with_nested_containers() {
         field_mutability: vec![false],
         field_has_default: vec![false],
         interfaces: vec![],
+        assoc_items: Vec::new(),
     });
     let poly = solver.generalize(&body);
     assert!(!poly.is_mono());
@@ -90,6 +90,7 @@ fn test_substitute_through_struct() {
         field_mutability: vec![false],
         field_has_default: vec![false],
         interfaces: vec![],
+        assoc_items: Vec::new(),
     });
     let result = subber.substitute(&ty, &sub);
     match result {
@@ -162,11 +163,16 @@ fn test_substitute_through_option_result() {
     let subber = Substituter::new();
     let mut sub = Substitution::new();
     sub.bind(TypeVar::new(0), MonoType::String);
-    // Option/Result not explicitly in substitute_internal → falls through
     let opt = MonoType::Option(Box::new(tv(0)));
-    assert_eq!(subber.substitute(&opt, &sub), opt);
+    assert_eq!(
+        subber.substitute(&opt, &sub),
+        MonoType::Option(Box::new(MonoType::String))
+    );
     let res = MonoType::Result(Box::new(tv(0)), Box::new(MonoType::Int(32)));
-    assert_eq!(subber.substitute(&res, &sub), res);
+    assert_eq!(
+        subber.substitute(&res, &sub),
+        MonoType::Result(Box::new(MonoType::String), Box::new(MonoType::Int(32)))
+    );
 }
 
 #[test]
@@ -377,6 +383,7 @@ fn test_substitute_struct_with_nested_list() {
         field_mutability: vec![false],
         field_has_default: vec![false],
         interfaces: vec![],
+        assoc_items: Vec::new(),
     });
     let result = subber.substitute(&ty, &sub);
     match result {
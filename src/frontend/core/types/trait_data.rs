@@ -552,5 +552,22 @@ impl TraitTable {
             for_type_name: "Void".into(),
             methods: debug_fn(),
         });
+
+        // Type（运行时类型值，typeof 的结果）: Dup + Equal + Debug
+        self.add_impl(TraitImplementation {
+            trait_name: "Dup".into(),
+            for_type_name: "Type".into(),
+            methods: HashMap::new(),
+        });
+        self.add_impl(TraitImplementation {
+            trait_name: "Equal".into(),
+            for_type_name: "Type".into(),
+            methods: equal_fn(),
+        });
+        self.add_impl(TraitImplementation {
+            trait_name: "Debug".into(),
+            for_type_name: "Type".into(),
+            methods: debug_fn(),
+        });
     }
 }
@@ -8,6 +8,8 @@
 //! - [`core`] - 核心算法层（词法分析器、解析器、类型系统、类型检查）
 //! - [`config`] - 编译配置
 //! - [`pipeline`] - 编译流水线
+//! - [`query`] - 按源码位置查询类型与定义
+//! - [`snippet`] - 在已有模块环境中单独编译代码片段
 //! - [`events`] - 事件系统
 //!
 //! # 快速开始
@@ -62,6 +64,12 @@ pub mod config;
 // 编译流水线
 pub mod pipeline;
 
+// 源码位置查询（类型与定义）
+pub mod query;
+
+// 代码片段编译（REPL、调试器求值）
+pub mod snippet;
+
 // 诊断系统
 pub use crate::util::diagnostic;
 
@@ -87,6 +95,9 @@ pub use pipeline::{Pipeline, PipelineState, CompilationResult};
 // 编译结果
 pub use compiler::CompileError;
 
+// 代码片段
+pub use snippet::{SessionInput, Snippet, SnippetContext};
+
 // 事件类型
 pub use events::*;
 pub use validate::{validate_source, ValidateResult};
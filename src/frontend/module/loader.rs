@@ -155,7 +155,7 @@ impl ModuleLoader {
     /// - `pub fn_name: (...) -> ... = ...` → 公开函数
     /// - `Name: Type = ...` → 类型定义（始终导出）
     /// - `name = expr`（不可变绑定） → 常量
-    fn extract_exports(
+    pub(crate) fn extract_exports(
         module_path: &str,
         ast: &AstModule,
         source: &ModuleSource,
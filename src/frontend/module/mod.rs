@@ -67,6 +67,8 @@ pub enum ModuleSource {
     User,
     /// 来自 vendor 目录的依赖模块
     Vendor,
+    /// 包随附的原生扩展库（cdylib）注册的模块
+    Native,
 }
 
 /// 模块信息
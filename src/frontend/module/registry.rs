@@ -26,9 +26,15 @@ impl ModuleRegistry {
     }
 
     /// 创建包含 std 模块的注册表
+    ///
+    /// 已安装的原生扩展模块也会一并注册。
     pub fn with_std() -> Self {
         let mut registry = Self::new();
         registry.register_std_modules();
+        #[cfg(not(target_arch = "wasm32"))]
+        for module_info in crate::backends::interpreter::extension::module_infos() {
+            registry.register(module_info);
+        }
         registry
     }
 
@@ -132,7 +138,7 @@ impl ModuleRegistry {
     pub fn native_names(&self) -> Vec<String> {
         let mut names = Vec::new();
         for module in self.modules.values() {
-            if module.source == ModuleSource::Std {
+            if matches!(module.source, ModuleSource::Std | ModuleSource::Native) {
                 for export in module.exports.values() {
                     if export.kind == ExportKind::Function || export.kind == ExportKind::Constant {
                         names.push(export.full_path.clone());
@@ -153,7 +159,8 @@ impl ModuleRegistry {
             let module_path = &full_path[..dot_pos];
             let export_name = &full_path[dot_pos + 1..];
             if let Some(module) = self.modules.get(module_path) {
-                return module.has_export(export_name) && module.source == ModuleSource::Std;
+                return module.has_export(export_name)
+                    && matches!(module.source, ModuleSource::Std | ModuleSource::Native);
             }
         }
         false
@@ -168,6 +175,24 @@ impl ModuleRegistry {
         self.modules.contains_key(&path)
     }
 
+    /// 检查名称是否是原生扩展模块（如 sqlite）
+    pub fn is_native_module(
+        &self,
+        name: &str,
+    ) -> bool {
+        self.modules
+            .get(name)
+            .is_some_and(|module| module.source == ModuleSource::Native)
+    }
+
+    /// 获取所有原生扩展模块
+    pub fn native_modules(&self) -> Vec<&ModuleInfo> {
+        self.modules
+            .values()
+            .filter(|module| module.source == ModuleSource::Native)
+            .collect()
+    }
+
     /// 获取所有 std 子模块的名称
     pub fn std_submodule_names(&self) -> Vec<String> {
         if let Some(std_module) = self.modules.get("std") {
@@ -6,12 +6,19 @@ pub mod compilation_cache;
 pub mod incremental_scheduler;
 
 use crate::middle;
+use crate::middle::passes::manager::{PassManager, PassTiming};
 use crate::util::span::SourceFile;
 use crate::util::diagnostic::Diagnostic;
-use super::{config::CompileConfig, events::*, core::typecheck};
+use super::{
+    config::CompileConfig,
+    events::*,
+    core::{desugar, typecheck},
+};
 
 use compilation_cache::CompilationCache;
 use incremental_scheduler::IncrementalStats;
+use super::query::SourceIndex;
+use super::snippet::SnippetContext;
 
 /// 管道错误类型
 #[derive(Debug, Clone)]
@@ -198,6 +205,12 @@ pub struct Pipeline {
     compilation_cache: CompilationCache,
     /// 增量编译统计
     incremental_stats: IncrementalStats,
+    /// 最近一次编译的位置索引（类型检查完成后才有）
+    source_index: Option<SourceIndex>,
+    /// 最近一次类型检查通过的模块环境（供片段编译使用）
+    snippet_context: Option<SnippetContext>,
+    /// 最近一次编译中各优化遍的耗时
+    pass_timings: Vec<PassTiming>,
 }
 
 impl Default for Pipeline {
@@ -232,6 +245,9 @@ impl Pipeline {
             cache_dir: None,
             compilation_cache: cache,
             incremental_stats: IncrementalStats::default(),
+            source_index: None,
+            snippet_context: None,
+            pass_timings: Vec::new(),
         }
     }
 
@@ -251,6 +267,9 @@ impl Pipeline {
             cache_dir: None,
             compilation_cache: cache,
             incremental_stats: IncrementalStats::default(),
+            source_index: None,
+            snippet_context: None,
+            pass_timings: Vec::new(),
         }
     }
 
@@ -311,6 +330,10 @@ impl Pipeline {
             self.config.incremental.enabled,
         ));
 
+        self.source_index = None;
+        self.snippet_context = None;
+        self.pass_timings.clear();
+
         // 执行各阶段
         let lex_result = self.run_lexing(source_name, source, &mut phase_durations);
         if !lex_result.is_success() {
@@ -325,7 +348,8 @@ impl Pipeline {
             );
         }
 
-        let parse_result = self.run_parsing(source_name, &lex_result.tokens, &mut phase_durations);
+        let mut parse_result =
+            self.run_parsing(source_name, &lex_result.tokens, &mut phase_durations);
         if !parse_result.is_success() {
             return CompilationResult::failed(
                 parse_result
@@ -338,8 +362,23 @@ impl Pipeline {
             );
         }
 
+        // 脱糖：此后各阶段只见核心语法；诊断带有位置，与类型错误一同报告
+        let desugar_errors = desugar::desugar_module(&mut parse_result.ast);
+        if !desugar_errors.is_empty() {
+            return CompilationResult::failed(
+                desugar_errors
+                    .into_iter()
+                    .map(PipelineError::TypeCheck)
+                    .collect(),
+                phase_durations,
+                start_time.elapsed().as_millis() as u64,
+            );
+        }
+
         let typecheck_result =
             self.run_typecheck(source_name, source, &parse_result.ast, &mut phase_durations);
+        // 类型检查出错时也保留索引，编辑器仍可查询已推断的部分
+        self.source_index = Some(SourceIndex::new(source_name, &typecheck_result.type_result));
         if !typecheck_result.is_success() {
             return CompilationResult::failed(
                 typecheck_result
@@ -352,6 +391,14 @@ impl Pipeline {
             );
         }
 
+        self.snippet_context = Some(SnippetContext::new(
+            &parse_result.ast,
+            &typecheck_result.type_result,
+        ));
+
+        // 类型检查已基于原写法报告错误，此后展开 `?` 与 f-string
+        desugar::desugar_typed_module(&mut parse_result.ast);
+
         // RFC-027 Phase 2.5: 证明函数执行循环
         // 在类型检查通过后、IR 生成前，执行编译期证明函数
         if !typecheck_result.type_result.proof_calls.is_empty() {
@@ -516,12 +563,21 @@ impl Pipeline {
         let errors = std::mem::take(&mut type_result.diagnostics);
         let error_messages: Vec<String> = errors.iter().map(|e| e.message.clone()).collect();
 
+        // 类型检查阶段产生的警告（如引用 #[deprecated] 符号）
+        let mut warnings: Vec<String> = type_result
+            .warnings
+            .iter()
+            .map(|w| format!("warning [{}]: {} at {:?}", w.code, w.message, w.span))
+            .collect();
+
         // 执行死代码分析（根据配置决定是否启用）
-        let warnings = if self.config.dead_code.enabled && !has_errors {
-            self.run_dead_code_analysis(source_name, ast, &type_result.semantic_db)
-        } else {
-            Vec::new()
-        };
+        if self.config.dead_code.enabled && !has_errors {
+            warnings.extend(self.run_dead_code_analysis(
+                source_name,
+                ast,
+                &type_result.semantic_db,
+            ));
+        }
 
         let warning_count = warnings.len();
 
@@ -641,16 +697,26 @@ impl Pipeline {
         match middle::generate_ir(ast, type_result) {
             Ok(mut ir) => {
                 // 单态化（根据配置决定是否启用）
-                if self.config.mono.enabled && !type_result.instantiation_requests.is_empty() {
+                if self.config.mono.enabled
+                    && (!type_result.instantiation_requests.is_empty()
+                        || !ir.generic_types.is_empty())
+                {
                     let mut mono = middle::passes::mono::Monomorphizer::with_max_depth(
                         self.config.mono.max_depth,
-                    );
-                    match mono.monomorphize(&ir, &type_result.instantiation_requests) {
+                    )
+                    .with_trait_table(type_result.trait_table.clone())
+                    .with_generic_bounds(type_result.generic_bounds.clone());
+                    match mono
+                        .monomorphize(std::mem::take(&mut ir), &type_result.instantiation_requests)
+                    {
                         Ok(mono_ir) => ir = mono_ir,
                         Err(diag) => return IRResult::failed(vec![diag]),
                     }
                 }
 
+                // 按优化级别（或显式配置）运行优化遍
+                self.pass_timings = PassManager::from_config(&self.config).run(&mut ir);
+
                 let duration = start.elapsed().as_millis() as u64;
                 phase_durations.push((CompilationPhase::IRGeneration, duration));
 
@@ -728,6 +794,21 @@ impl Pipeline {
         &self.incremental_stats
     }
 
+    /// 最近一次编译的位置索引
+    pub fn source_index(&self) -> Option<&SourceIndex> {
+        self.source_index.as_ref()
+    }
+
+    /// 最近一次类型检查通过的模块环境
+    pub fn snippet_context(&self) -> Option<&SnippetContext> {
+        self.snippet_context.as_ref()
+    }
+
+    /// 最近一次编译中各优化遍的耗时
+    pub fn pass_timings(&self) -> &[PassTiming] {
+        &self.pass_timings
+    }
+
     /// 运行编译并缓存结果
     pub fn run_and_cache(
         &mut self,
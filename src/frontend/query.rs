@@ -0,0 +1,147 @@
+//! 源码位置查询
+//!
+//! 保留最近一次编译的类型检查产物，按 span 回答两类问题：
+//! - 该位置的表达式/变量推断出什么类型（`type_at`）
+//! - 该位置的标识符定义在哪里（`definition_of`）
+//!
+//! 编辑器工具与 REPL `:type` 以库的形式使用，不依赖 LSP 协议。
+
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+use crate::frontend::core::typecheck::semantic_db::{
+    ScopeInfo, ScopeKind, SemanticToken, SemanticTokenModifier, SymbolLocation,
+};
+use crate::frontend::core::typecheck::TypeCheckResult;
+use crate::frontend::core::types::MonoType;
+use crate::util::span::{Position, Span};
+
+/// 一次编译的位置索引
+#[derive(Debug, Clone, Default)]
+pub struct SourceIndex {
+    /// 源文件名（定义位置的 `file_path`）
+    source_name: String,
+    /// span → 推断类型
+    types: HashMap<Span, MonoType>,
+    /// 标识符 token（声明与引用）
+    tokens: Vec<SemanticToken>,
+    /// 词法作用域
+    scopes: Vec<ScopeInfo>,
+}
+
+impl SourceIndex {
+    /// 从类型检查结果建立索引（复制其中的 `expr_types`，IR 生成仍要按类型选择指令）
+    pub fn new(
+        source_name: &str,
+        result: &TypeCheckResult,
+    ) -> Self {
+        let module = result.module_name.clone();
+        let db = &result.semantic_db;
+        Self {
+            source_name: source_name.to_string(),
+            types: result.expr_types.clone(),
+            tokens: db
+                .get_tokens(&module)
+                .map(<[_]>::to_vec)
+                .unwrap_or_default(),
+            scopes: db
+                .get_scopes(&module)
+                .map(<[_]>::to_vec)
+                .unwrap_or_default(),
+        }
+    }
+
+    /// 覆盖 `span` 的最内层表达式的类型
+    ///
+    /// 标识符本身没有记录类型时（如引用函数参数），退回到其定义处的类型。
+    pub fn type_at(
+        &self,
+        span: Span,
+    ) -> Option<&MonoType> {
+        let innermost = self
+            .types
+            .iter()
+            .filter(|(recorded, _)| recorded.contains(&span))
+            .min_by_key(|(recorded, _)| nesting(recorded))
+            .map(|(_, ty)| ty);
+        innermost.or_else(|| {
+            let def = self.definition_of(span)?;
+            self.types.get(&def.span)
+        })
+    }
+
+    /// `span` 处标识符的定义位置
+    ///
+    /// 按词法作用域解析：在包含引用的作用域里找同名声明，内层优先；
+    /// 同一作用域内取引用之前最近的一次声明（函数与类型可在声明前引用）。
+    pub fn definition_of(
+        &self,
+        span: Span,
+    ) -> Option<SymbolLocation> {
+        let token = self.tokens.iter().find(|t| t.span.contains(&span))?;
+        if is_declaration(token) {
+            return Some(self.location(token.span));
+        }
+
+        self.tokens
+            .iter()
+            .filter(|t| t.name == token.name && is_declaration(t))
+            .filter_map(|decl| {
+                let scope = self.scope_of(decl.span);
+                scope
+                    .is_none_or(|s| s.span.contains(&token.span))
+                    .then(|| (scope.map(|s| nesting(&s.span)), decl))
+            })
+            .min_by_key(|(scope, decl)| {
+                // 内层作用域优先，其次是引用之前最近的声明，最后是引用之后最早的声明
+                let start = position(decl.span.start);
+                let order = if start <= position(token.span.start) {
+                    (false, Reverse(start), start)
+                } else {
+                    (true, Reverse((0, 0)), start)
+                };
+                (scope.is_none(), *scope, order)
+            })
+            .map(|(_, decl)| self.location(decl.span))
+    }
+
+    /// 包含 `span` 的最内层局部作用域
+    ///
+    /// 全局作用域不限制可见性；与声明 span 相同的是该声明自身引入的函数作用域，
+    /// 声明本身属于外层。
+    fn scope_of(
+        &self,
+        span: Span,
+    ) -> Option<&ScopeInfo> {
+        self.scopes
+            .iter()
+            .filter(|scope| scope.kind != ScopeKind::Global && scope.span != span)
+            .filter(|scope| scope.span.contains(&span))
+            .min_by_key(|scope| nesting(&scope.span))
+    }
+
+    fn location(
+        &self,
+        span: Span,
+    ) -> SymbolLocation {
+        SymbolLocation {
+            file_path: self.source_name.clone(),
+            span,
+        }
+    }
+}
+
+fn is_declaration(token: &SemanticToken) -> bool {
+    token
+        .modifiers
+        .contains(&SemanticTokenModifier::Declaration)
+}
+
+fn position(pos: Position) -> (usize, usize) {
+    (pos.line, pos.column)
+}
+
+/// 嵌套 span 的排序键：越靠内（起点越靠后、终点越靠前）越小
+fn nesting(span: &Span) -> (Reverse<(usize, usize)>, (usize, usize)) {
+    (Reverse(position(span.start)), position(span.end))
+}
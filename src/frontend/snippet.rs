@@ -0,0 +1,495 @@
+//! 单独编译代码片段
+//!
+//! REPL、调试器的栈帧内求值与笔记本单元格只需编译一小段代码，但要能引用已编译模块中的
+//! 函数、类型与导入。[`SnippetContext`] 保留模块顶层绑定的类型以及 `use` 与类型定义；
+//! 片段被包装成以可见局部变量为参数的函数 [`SNIPPET_FUNCTION`]，只对这个函数做类型检查
+//! 与 IR 生成，模块中的函数体不再重新检查。
+//!
+//! 片段对模块函数的调用按名字生成，执行时需与原模块的 IR 链接在一起。
+//!
+//! REPL 与嵌入方的会话逐次编译输入（[`SessionInput`]）：输入中的函数与类型定义同片段函数
+//! 一起生成，顶层赋值的变量随片段的值返回；执行成功后用 [`SnippetContext::commit`] 并入
+//! 环境，下一次输入即可引用。
+
+use std::collections::HashMap;
+
+use crate::frontend::core::desugar;
+use crate::frontend::core::lexer;
+use crate::frontend::core::parser::{
+    self,
+    ast::{Expr, Module, Param, Stmt, StmtKind, Type},
+};
+use crate::frontend::core::typecheck::inference::expressions::expr_span;
+use crate::frontend::core::typecheck::{self, TypeCheckResult, TypeEnvironment};
+use crate::frontend::core::types::{MonoType, PolyType};
+use crate::middle::core::ir::{FunctionIR, ModuleIR};
+use crate::util::diagnostic::ErrorCodeDefinition;
+use crate::util::span::Span;
+
+use super::compiler::CompileError;
+
+/// 片段函数名（不是合法标识符，不会与模块中的名字冲突）
+pub const SNIPPET_FUNCTION: &str = "<snippet>";
+
+/// 会话输入中收集返回值与变量的列表
+const SESSION_VALUES: &str = "<values>";
+
+/// 片段的编译环境
+#[derive(Debug, Clone, Default)]
+pub struct SnippetContext {
+    /// 模块顶层绑定的类型
+    bindings: HashMap<String, PolyType>,
+    /// `use` 语句与类型定义，随片段一起检查
+    declarations: Vec<Stmt>,
+    /// 片段可见的局部变量，按顺序成为片段函数的参数
+    locals: Vec<(String, MonoType)>,
+}
+
+impl SnippetContext {
+    /// 从模块的 AST 与类型检查结果建立环境
+    pub fn new(
+        module: &Module,
+        result: &TypeCheckResult,
+    ) -> Self {
+        let declarations = module
+            .items
+            .iter()
+            .filter(|stmt| match &stmt.kind {
+                StmtKind::Use { .. } => true,
+                StmtKind::Binding {
+                    type_name: None,
+                    type_annotation: Some(_),
+                    body,
+                    ..
+                } => body.is_empty(),
+                _ => false,
+            })
+            .cloned()
+            .collect();
+        Self {
+            bindings: result.bindings.clone(),
+            declarations,
+            locals: Vec::new(),
+        }
+    }
+
+    /// 添加一个片段可见的局部变量（如调试器当前栈帧中的变量）
+    ///
+    /// 同名局部变量遮蔽模块中的绑定。
+    pub fn with_local(
+        mut self,
+        name: impl Into<String>,
+        ty: MonoType,
+    ) -> Self {
+        self.locals.push((name.into(), ty));
+        self
+    }
+
+    /// 片段可见的局部变量，顺序即片段函数的参数顺序
+    pub fn locals(&self) -> &[(String, MonoType)] {
+        &self.locals
+    }
+
+    /// 模块中名为 `name` 的顶层绑定的类型
+    pub fn binding(
+        &self,
+        name: &str,
+    ) -> Option<&PolyType> {
+        self.bindings.get(name)
+    }
+}
+
+/// 编译后的片段
+#[derive(Debug, Clone)]
+pub struct Snippet {
+    /// 片段函数：参数依次为上下文中的局部变量，返回片段最后一个表达式的值
+    pub function: FunctionIR,
+    /// 片段的值类型；最后一条语句不是表达式时为 `Void`
+    pub result_type: MonoType,
+}
+
+/// 在会话中编译的一次输入
+///
+/// 与普通片段不同，输入顶层的函数、类型定义与 `use` 成为会话的定义，顶层赋值的变量在
+/// 之后的输入中仍然可见。定义了变量时，片段函数返回 `List(Any)`：值类型不是 `Void` 时
+/// 首元素为片段的值，其后依次为 [`variables`](Self::variables) 的值；否则只返回片段的值。
+#[derive(Debug, Clone)]
+pub struct SessionInput {
+    /// 输入编译成的模块：顶层定义的函数、类型构造器与片段函数 [`SNIPPET_FUNCTION`]
+    pub module: ModuleIR,
+    /// 片段的值类型；最后一条语句不是表达式时为 `Void`
+    pub result_type: MonoType,
+    /// 顶层赋值的变量及其类型，按首次赋值的顺序
+    pub variables: Vec<(String, MonoType)>,
+    /// 类型检查后的顶层绑定（含新定义的函数）
+    bindings: HashMap<String, PolyType>,
+    /// 输入中的 `use` 与类型定义
+    declarations: Vec<Stmt>,
+}
+
+impl SnippetContext {
+    /// 并入会话输入的定义与变量，之后的输入可以引用它们
+    ///
+    /// 应在输入执行成功后调用；重新定义的类型与变量替换旧的，变量保持原来的参数位置。
+    pub fn commit(
+        &mut self,
+        input: &SessionInput,
+    ) {
+        self.bindings.extend(input.bindings.clone());
+        for stmt in &input.declarations {
+            let name = declared_name(stmt);
+            if name.is_some() {
+                self.declarations.retain(|d| declared_name(d) != name);
+            }
+            self.declarations.push(stmt.clone());
+        }
+        for (name, ty) in &input.variables {
+            match self.locals.iter_mut().find(|(local, _)| local == name) {
+                Some(local) => local.1 = ty.clone(),
+                None => self.locals.push((name.clone(), ty.clone())),
+            }
+        }
+    }
+}
+
+/// 类型定义的名字；`use` 没有名字，总是追加
+fn declared_name(stmt: &Stmt) -> Option<&str> {
+    match &stmt.kind {
+        StmtKind::Binding { name, .. } => Some(name),
+        _ => None,
+    }
+}
+
+/// 是否为随片段一起检查的声明（`use` 与类型定义）
+fn is_declaration(stmt: &Stmt) -> bool {
+    match &stmt.kind {
+        StmtKind::Use { .. } => true,
+        StmtKind::Binding {
+            type_name: None,
+            type_annotation: Some(_),
+            body,
+            ..
+        } => body.is_empty(),
+        _ => false,
+    }
+}
+
+/// 在 `context` 中编译 `source`
+pub(crate) fn compile(
+    context: &SnippetContext,
+    source: &str,
+) -> Result<Snippet, CompileError> {
+    let snippet = parse(source)?;
+    let (body, value) = split_value(snippet.items);
+    let ast = module(context, Vec::new(), body, value, snippet.span);
+    let result = check(context, &ast)?;
+    let result_type = value_type(&ast, &result);
+    let mut module = generate(ast, &result)?;
+    let function = take_snippet_function(&mut module)?;
+    Ok(Snippet {
+        function,
+        result_type,
+    })
+}
+
+/// 在会话 `context` 中编译一次输入
+pub(crate) fn compile_session(
+    context: &SnippetContext,
+    source: &str,
+) -> Result<SessionInput, CompileError> {
+    let snippet = parse(source)?;
+    let (definitions, statements): (Vec<_>, Vec<_>) = snippet
+        .items
+        .into_iter()
+        .partition(|stmt| matches!(stmt.kind, StmtKind::Use { .. } | StmtKind::Binding { .. }));
+    let (body, value) = split_value(statements);
+    let mut ast = module(
+        context,
+        definitions.clone(),
+        body.clone(),
+        value.clone(),
+        snippet.span,
+    );
+    let mut result = check(context, &ast)?;
+    let result_type = value_type(&ast, &result);
+
+    // 顶层赋值的变量：类型取自初始值
+    let mut variables: Vec<(String, MonoType, Span)> = Vec::new();
+    for stmt in &body {
+        let StmtKind::Var {
+            name,
+            name_span,
+            initializer: Some(initializer),
+            ..
+        } = &stmt.kind
+        else {
+            continue;
+        };
+        let Some(ty) = result.expr_types.get(&expr_span(initializer)).cloned() else {
+            continue;
+        };
+        variables.retain(|(existing, _, _)| existing != name);
+        variables.push((name.clone(), ty, *name_span));
+    }
+
+    // 变量连同片段的值一起以 `List(Any)` 返回
+    if !variables.is_empty() {
+        let span = value.as_ref().map(|v| v.1).unwrap_or(Span::default());
+        let mut body = body;
+        let mut elements = Vec::new();
+        match value {
+            Some((value, _)) if result_type != MonoType::Void => elements.push(*value),
+            Some((value, span)) => body.push(Stmt {
+                kind: StmtKind::Expr(value),
+                span,
+            }),
+            None => {}
+        }
+        elements.extend(
+            variables
+                .iter()
+                .map(|(name, _, span)| Expr::Var(name.into(), *span)),
+        );
+        body.push(Stmt {
+            kind: StmtKind::Var {
+                name: SESSION_VALUES.to_string(),
+                name_span: span,
+                type_annotation: Some(Type::Generic {
+                    name: "List".to_string(),
+                    name_span: span,
+                    args: vec![Type::Name {
+                        name: "Any".to_string(),
+                        span,
+                    }],
+                }),
+                initializer: Some(Box::new(Expr::List(elements, span))),
+                is_mut: false,
+            },
+            span,
+        });
+        let values = Box::new(Expr::Var(SESSION_VALUES.into(), span));
+        ast = module(
+            context,
+            definitions.clone(),
+            body,
+            Some((values, span)),
+            snippet.span,
+        );
+        result = check(context, &ast)?;
+    }
+
+    let module = generate(ast, &result)?;
+    let bindings = result
+        .bindings
+        .into_iter()
+        .filter(|(name, _)| name != SNIPPET_FUNCTION)
+        .collect();
+    Ok(SessionInput {
+        module,
+        result_type,
+        variables: variables
+            .into_iter()
+            .map(|(name, ty, _)| (name, ty))
+            .collect(),
+        bindings,
+        declarations: definitions.into_iter().filter(is_declaration).collect(),
+    })
+}
+
+/// 解析并脱糖片段源码
+fn parse(source: &str) -> Result<Module, CompileError> {
+    let tokens = lexer::tokenize(source).map_err(|e| CompileError::Lex(e.to_diagnostic()))?;
+    let parsed = parser::parse(&tokens);
+    if parsed.has_errors {
+        return Err(CompileError::Parse(
+            parsed.errors.into_iter().next().unwrap_or_else(|| {
+                ErrorCodeDefinition::unexpected_token("unknown")
+                    .at(Span::dummy())
+                    .build()
+            }),
+        ));
+    }
+
+    let mut snippet = parsed.module;
+    let errors = desugar::desugar_module(&mut snippet);
+    if let Some(first) = errors.into_iter().next() {
+        return Err(CompileError::TypeError(
+            first.message.clone(),
+            Some(Box::new(first)),
+        ));
+    }
+    Ok(snippet)
+}
+
+/// 分出作为片段值的最后一个表达式（及其语句的位置）
+fn split_value(mut body: Vec<Stmt>) -> (Vec<Stmt>, Option<(Box<Expr>, Span)>) {
+    let value = match body.last().map(|last| &last.kind) {
+        Some(StmtKind::Expr(expr)) if !matches!(**expr, Expr::Return(..)) => body.pop(),
+        _ => None,
+    };
+    let value = value.and_then(|last| match last.kind {
+        StmtKind::Expr(expr) => Some((expr, last.span)),
+        _ => None,
+    });
+    (body, value)
+}
+
+/// 组装待检查的模块：上下文中的声明、`definitions` 与片段函数
+fn module(
+    context: &SnippetContext,
+    definitions: Vec<Stmt>,
+    mut body: Vec<Stmt>,
+    value: Option<(Box<Expr>, Span)>,
+    span: Span,
+) -> Module {
+    if let Some((value, span)) = value {
+        body.push(Stmt {
+            kind: StmtKind::Expr(Box::new(Expr::Return(Some(value), span))),
+            span,
+        });
+    }
+    let params = context
+        .locals
+        .iter()
+        .map(|(name, ty)| Param {
+            name: name.clone(),
+            ty: type_syntax(ty),
+            is_mut: false,
+            span: Span::default(),
+        })
+        .collect();
+    let mut items = context.declarations.clone();
+    items.extend(definitions);
+    items.push(Stmt {
+        kind: StmtKind::Binding {
+            name: SNIPPET_FUNCTION.to_string(),
+            type_name: None,
+            method_type: None,
+            generic_params: Vec::new(),
+            type_annotation: None,
+            params,
+            body,
+            is_pub: false,
+            attributes: Vec::new(),
+        },
+        span,
+    });
+    Module { items, span }
+}
+
+/// 以上下文中的绑定为环境检查 `ast`
+fn check(
+    context: &SnippetContext,
+    ast: &Module,
+) -> Result<TypeCheckResult, CompileError> {
+    let mut env = TypeEnvironment::new();
+    env.vars = context.bindings.clone();
+    let result = typecheck::check_module(ast, &mut Some(env));
+    if !result.diagnostics.is_empty() {
+        let message = result
+            .diagnostics
+            .iter()
+            .map(|d| d.message.clone())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let first = result.diagnostics.into_iter().next().map(Box::new);
+        return Err(CompileError::TypeError(message, first));
+    }
+    Ok(result)
+}
+
+/// 展开类型检查之后的语法糖并生成 IR
+fn generate(
+    mut ast: Module,
+    result: &TypeCheckResult,
+) -> Result<ModuleIR, CompileError> {
+    desugar::desugar_typed_module(&mut ast);
+    crate::middle::generate_ir(&ast, result).map_err(|errors| {
+        CompileError::IRError(
+            errors
+                .iter()
+                .map(|d| d.message.clone())
+                .collect::<Vec<_>>()
+                .join("\n"),
+        )
+    })
+}
+
+fn take_snippet_function(module: &mut ModuleIR) -> Result<FunctionIR, CompileError> {
+    let index = module
+        .functions
+        .iter()
+        .position(|f| f.name == SNIPPET_FUNCTION)
+        .ok_or_else(|| CompileError::Internal("snippet function was not generated".to_string()))?;
+    Ok(module.functions.swap_remove(index))
+}
+
+/// 片段函数最后返回的值的类型；没有返回值时为 `Void`
+fn value_type(
+    ast: &Module,
+    result: &TypeCheckResult,
+) -> MonoType {
+    let value = ast.items.last().and_then(|stmt| match &stmt.kind {
+        StmtKind::Binding { body, .. } => match body.last().map(|last| &last.kind) {
+            Some(StmtKind::Expr(expr)) => match &**expr {
+                Expr::Return(Some(value), _) => Some(expr_span(value)),
+                _ => None,
+            },
+            _ => None,
+        },
+        _ => None,
+    });
+    value
+        .and_then(|span| result.expr_types.get(&span))
+        .cloned()
+        .unwrap_or(MonoType::Void)
+}
+
+/// 局部变量类型的源码写法；无法写出的类型留空，由使用处推断
+fn type_syntax(ty: &MonoType) -> Option<Type> {
+    let name = |name: &str| Type::Name {
+        name: name.to_string(),
+        span: Span::default(),
+    };
+    let generic = |base: &str, args: &[&MonoType]| {
+        Some(Type::Generic {
+            name: base.to_string(),
+            name_span: Span::default(),
+            args: args
+                .iter()
+                .map(|arg| type_syntax(arg))
+                .collect::<Option<_>>()?,
+        })
+    };
+    Some(match ty {
+        MonoType::Void => Type::Void,
+        MonoType::Bool => Type::Bool,
+        MonoType::Int(n) => Type::Int(*n),
+        MonoType::Float(n) => Type::Float(*n),
+        MonoType::Char => Type::Char,
+        MonoType::String => Type::String,
+        MonoType::Bytes => Type::Bytes,
+        MonoType::Any => name("Any"),
+        MonoType::TypeRef(n) => name(n),
+        MonoType::Struct(s) if !s.name.is_empty() => name(&s.name),
+        MonoType::Enum(e) if !e.name.is_empty() => name(&e.name),
+        MonoType::Tuple(elems) => {
+            Type::Tuple(elems.iter().map(type_syntax).collect::<Option<_>>()?)
+        }
+        MonoType::List(elem) => return generic("List", &[elem]),
+        MonoType::Set(elem) => return generic("Set", &[elem]),
+        MonoType::Dict(k, v) => return generic("Dict", &[k, v]),
+        MonoType::Option(inner) => Type::Option(Box::new(type_syntax(inner)?)),
+        MonoType::Result(ok, err) => {
+            Type::Result(Box::new(type_syntax(ok)?), Box::new(type_syntax(err)?))
+        }
+        MonoType::Fn {
+            params,
+            return_type,
+        } => Type::Fn {
+            params: params.iter().map(type_syntax).collect::<Option<_>>()?,
+            return_type: Box::new(type_syntax(return_type)?),
+        },
+        _ => return None,
+    })
+}
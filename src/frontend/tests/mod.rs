@@ -3,4 +3,6 @@
 //! 包含前端编译器各模块的测试
 
 mod config;
+mod query;
+mod snippet;
 mod validate;
@@ -0,0 +1,97 @@
+//! 位置查询测试
+//!
+//! 测试 `Compiler::type_at` 与 `Compiler::definition_of`，覆盖：
+//! - 表达式、变量引用与声明名的类型
+//! - 局部变量、函数与遮蔽变量的定义位置
+//! - 未编译或位置上没有标识符
+
+use crate::frontend::Compiler;
+use crate::util::span::{Position, Span};
+
+const SOURCE: &str = "\
+add: (a: Int, b: Int) -> Int = (a, b) => {
+    total = a + b
+    return total
+}
+
+main = () => {
+    x = add(1, 2)
+    label = \"sum\"
+    x = x + 1
+    print(label)
+}
+";
+
+/// 光标（零宽 span），行列均从 1 开始
+fn at(
+    line: usize,
+    column: usize,
+) -> Span {
+    Span::new(Position::new(line, column), Position::new(line, column))
+}
+
+fn compiled() -> Compiler {
+    let mut compiler = Compiler::new();
+    compiler
+        .compile("query.yx", SOURCE)
+        .expect("source should compile");
+    compiler
+}
+
+#[test]
+fn test_type_at_expression_and_variables() {
+    let compiler = compiled();
+
+    // 被调用的函数名 `add`
+    assert_eq!(
+        compiler.type_at(at(7, 9)).unwrap().type_name(),
+        "fn(int64, int64) -> int64"
+    );
+    // `add(1, 2)` 的参数列表处取整个调用的类型
+    assert_eq!(compiler.type_at(at(7, 12)).unwrap().type_name(), "int64");
+    // 声明名 `label`
+    assert_eq!(compiler.type_at(at(8, 5)).unwrap().type_name(), "string");
+    // 引用 `label`
+    assert_eq!(compiler.type_at(at(10, 11)).unwrap().type_name(), "string");
+    // 函数体内的 `total`
+    assert_eq!(compiler.type_at(at(3, 12)).unwrap().type_name(), "int64");
+}
+
+#[test]
+fn test_definition_of_resolves_references() {
+    let compiler = compiled();
+
+    let label = compiler
+        .definition_of(at(10, 11))
+        .expect("label definition");
+    assert_eq!(label.file_path, "query.yx");
+    assert_eq!((label.span.start.line, label.span.start.column), (8, 5));
+
+    let total = compiler.definition_of(at(3, 12)).expect("total definition");
+    assert_eq!((total.span.start.line, total.span.start.column), (2, 5));
+
+    let add = compiler.definition_of(at(7, 9)).expect("add definition");
+    assert_eq!(add.span.start.line, 1);
+}
+
+#[test]
+fn test_definition_of_declaration_is_itself() {
+    let compiler = compiled();
+
+    let x = compiler.definition_of(at(7, 5)).expect("x declaration");
+    assert_eq!((x.span.start.line, x.span.start.column), (7, 5));
+    // 重新赋值后的引用仍指向首次声明
+    let reassigned = compiler.definition_of(at(9, 9)).expect("x reference");
+    assert_eq!(reassigned.span, x.span);
+}
+
+#[test]
+fn test_queries_without_result() {
+    let compiler = Compiler::new();
+    assert!(compiler.type_at(at(1, 1)).is_none());
+    assert!(compiler.definition_of(at(1, 1)).is_none());
+
+    let compiler = compiled();
+    // 空行上没有标识符
+    assert!(compiler.definition_of(at(5, 1)).is_none());
+}
@@ -0,0 +1,180 @@
+//! 代码片段编译测试
+//!
+//! 测试 `Compiler::compile_snippet` 与 `Compiler::compile_session`，覆盖：
+//! - 调用模块中的函数、访问模块定义的结构体字段
+//! - 栈帧局部变量成为片段函数的参数
+//! - 片段中的类型错误
+//! - 与模块链接后在虚拟机上求值
+//! - 会话输入返回顶层变量，提交后之后的输入可以引用
+
+use crate::backends::common::RuntimeValue;
+use crate::backends::Executor;
+use crate::frontend::core::types::MonoType;
+use crate::frontend::snippet::SNIPPET_FUNCTION;
+use crate::frontend::config::CompileConfig;
+use crate::frontend::{Compiler, SnippetContext};
+use crate::middle::core::ir::{ConstValue, Instruction, Operand};
+
+const SOURCE: &str = "\
+use std.io
+
+Point: Type = { x: Int, y: Int }
+
+double: (n: Int) -> Int = (n) => {
+    return n * 2
+}
+
+main = () => {
+    p = Point(1, 2)
+    io.println(double(p.x))
+}
+";
+
+fn compiled() -> Compiler {
+    let mut compiler = Compiler::new();
+    compiler
+        .compile("snippet.yx", SOURCE)
+        .expect("source should compile");
+    compiler
+}
+
+fn calls(
+    function: &crate::middle::core::ir::FunctionIR,
+    name: &str,
+) -> bool {
+    function.all_instructions().any(|i| {
+        matches!(
+            i,
+            Instruction::Call { func: Operand::Const(ConstValue::String(f)), .. } if f == name
+        )
+    })
+}
+
+#[test]
+fn test_snippet_calls_module_function() {
+    let mut compiler = compiled();
+    let context = compiler.snippet_context().expect("module typechecked");
+    let snippet = compiler
+        .compile_snippet(&context, "z = double(20)\nz + 2")
+        .expect("snippet should compile");
+
+    assert_eq!(snippet.function.name, SNIPPET_FUNCTION);
+    assert!(snippet.function.params.is_empty());
+    assert_eq!(snippet.result_type, MonoType::Int(64));
+    assert!(calls(&snippet.function, "double"));
+}
+
+#[test]
+fn test_snippet_uses_locals_and_module_types() {
+    let mut compiler = compiled();
+    let context = compiler
+        .snippet_context()
+        .expect("module typechecked")
+        .with_local("p", MonoType::TypeRef("Point".to_string()))
+        .with_local("k", MonoType::Int(64));
+    let snippet = compiler
+        .compile_snippet(&context, "p.y * k")
+        .expect("snippet should compile");
+
+    assert_eq!(snippet.function.params.len(), 2);
+    assert_eq!(snippet.result_type, MonoType::Int(64));
+    assert!(snippet
+        .function
+        .all_instructions()
+        .any(|i| matches!(i, Instruction::LoadField { .. })));
+}
+
+#[test]
+fn test_snippet_type_error_is_reported() {
+    let mut compiler = compiled();
+    let context = compiler.snippet_context().expect("module typechecked");
+    let err = compiler
+        .compile_snippet(&context, "k: Int = \"text\"\nk")
+        .unwrap_err();
+    assert!(err.is_type_error());
+
+    let err = compiler
+        .compile_snippet(&context, "missing(1)")
+        .unwrap_err();
+    assert!(err.is_type_error());
+}
+
+#[test]
+fn test_no_context_before_successful_typecheck() {
+    let compiler = Compiler::new();
+    assert!(compiler.snippet_context().is_none());
+
+    let mut compiler = Compiler::new();
+    let _ = compiler.compile("bad.yx", "main = () => { x: Int = \"s\" }\n");
+    assert!(compiler.snippet_context().is_none());
+}
+
+#[test]
+fn test_snippet_runs_against_module() {
+    // 片段调用的 `double` 已内联进 `main`，保留它才能链接
+    let mut compiler = Compiler::with_config(CompileConfig::new().with_tree_shaking(false));
+    let mut module = compiler
+        .compile("snippet.yx", SOURCE)
+        .expect("source should compile");
+    let context = compiler
+        .snippet_context()
+        .expect("module typechecked")
+        .with_local("n", MonoType::Int(64));
+    let snippet = compiler
+        .compile_snippet(&context, "double(n) + 1")
+        .expect("snippet should compile");
+    module.functions.push(snippet.function);
+
+    let mut ctx = crate::middle::passes::codegen::CodegenContext::new(module);
+    let bytecode = ctx.generate().expect("codegen should succeed");
+    let bytecode = crate::middle::bytecode::BytecodeModule::from(bytecode);
+    let mut interpreter = crate::backends::interpreter::Interpreter::new();
+    interpreter
+        .execute_module(&bytecode)
+        .expect("module should run");
+    let function = bytecode
+        .functions
+        .iter()
+        .find(|f| f.name == SNIPPET_FUNCTION)
+        .expect("snippet function linked");
+    let value = interpreter
+        .execute_function(function, &[RuntimeValue::Int(20)])
+        .expect("snippet should run");
+    assert_eq!(value, RuntimeValue::Int(41));
+}
+
+#[test]
+fn test_session_input_returns_variables_and_commits() {
+    let mut compiler = Compiler::new();
+    let mut context = SnippetContext::default();
+    let input = compiler
+        .compile_session(
+            &context,
+            "Point: Type = { x: Int, y: Int }\ndouble: (n: Int) -> Int = (n) => n * 2\np = Point(1, 2)",
+        )
+        .expect("input should compile");
+    assert_eq!(input.result_type, MonoType::Void);
+    assert!(matches!(
+        input.variables.as_slice(),
+        [(name, MonoType::Struct(point))] if name == "p" && point.name == "Point"
+    ));
+    assert!(input.module.functions.iter().any(|f| f.name == "double"));
+    context.commit(&input);
+    assert_eq!(context.locals().len(), 1);
+    assert!(context.binding("double").is_some());
+
+    // 之后的输入可以引用已提交的变量、函数与类型
+    let input = compiler
+        .compile_session(&context, "q = Point(p.y, double(p.x))\nq.x")
+        .expect("input should compile");
+    assert_eq!(input.result_type, MonoType::Int(64));
+    assert_eq!(input.variables.len(), 1);
+    let snippet = input
+        .module
+        .functions
+        .iter()
+        .find(|f| f.name == SNIPPET_FUNCTION)
+        .expect("snippet function generated");
+    assert_eq!(snippet.params.len(), 1);
+    assert!(calls(snippet, "double"));
+}
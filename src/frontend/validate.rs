@@ -8,6 +8,7 @@ use std::sync::Arc;
 
 use parking_lot::Mutex;
 
+use crate::frontend::core::desugar::desugar_module;
 use crate::frontend::core::lexer::tokenize;
 use crate::frontend::core::parser::parse;
 use crate::frontend::core::parser::ast::Module;
@@ -19,7 +20,7 @@ use std::sync::LazyLock;
 /// 前端验证结果
 #[derive(Debug, Clone)]
 pub struct ValidateResult {
-    /// 所有诊断信息（词法、语法、类型检查）
+    /// 所有诊断信息（词法、语法、类型检查，含警告）
     pub diagnostics: Vec<Diagnostic>,
     /// 模块 AST（解析成功时存在；词法/语法失败时为 `None`）
     pub module: Option<Module>,
@@ -77,11 +78,26 @@ pub fn validate_source(source: &str) -> ValidateResult {
             return result;
         }
 
+        // ---- 脱糖（返回的 AST 保留源码写法）----
+        let mut desugared = parse_result.module.clone();
+        let desugar_errors = desugar_module(&mut desugared);
+        if !desugar_errors.is_empty() {
+            let result = ValidateResult {
+                diagnostics: desugar_errors,
+                module: None,
+            };
+            let mut cache = VALIDATE_CACHE.lock();
+            cache.insert(hash, Arc::new(result.clone()));
+            return result;
+        }
+
         // ---- 类型检查（语法成功则始终执行）----
-        let typecheck_result = check_module(&parse_result.module, &mut None);
+        let typecheck_result = check_module(&desugared, &mut None);
+        let mut diagnostics = typecheck_result.diagnostics;
+        diagnostics.extend(typecheck_result.warnings);
 
         ValidateResult {
-            diagnostics: typecheck_result.diagnostics,
+            diagnostics,
             module: Some(parse_result.module),
         }
     };
@@ -10,26 +10,71 @@
 //! }
 //! ```
 //!
+//! # Public API
+//!
+//! External tools should depend only on the curated modules below; they follow
+//! semver and stay stable across internal refactors:
+//!
+//! - [`compile`]: source code → executable [`vm::Program`]
+//! - [`ast`]: syntax tree and [`ast::parse`]
+//! - [`diagnostics`]: structured diagnostics and rendering
+//! - [`vm`]: loading and running programs
+//! - [`Engine`]: one configured object for embedders (compiler options, VM
+//!   limits, capabilities, stdlib selection, host functions, language)
+//! - [`Session`]: successive evaluations that keep definitions and variables
+//!
+//! ```no_run
+//! let program = yaoxiang::compile("hello.yx", "main = { print(\"hi\") }")?;
+//! yaoxiang::vm::run(&program)?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! All other modules are implementation details, hidden from the docs and
+//! exempt from semver guarantees.
+//!
 //! # Crate Features
 //!
-//! - `cli`: CLI-only dependencies (REPL, LSP, hot-reload)
+//! - `cli`: CLI-only dependencies (REPL, LSP, DAP, hot-reload)
+//! - `hooks`: execution hooks (`vm::ExecutionHook`) and the breakpoint debugger
+//!   (`vm::debug`) behind the REPL's `:debug` and the `yaoxiang-dap` adapter
+//! - `wasm`: WebAssembly backend (`build --emit wasm`), which compiles programs to
+//!   standalone wasm modules that run in the browser
 
 #![doc(html_root_url = "https://docs.rs/yaoxiang")]
 #![warn(rust_2018_idioms)]
 
-// Public modules
+// Stable public API
+mod api;
+pub use api::{ast, compile, diagnostics, vm};
+pub use api::compile::compile;
+pub use api::engine::{Engine, EngineBuilder};
+pub use api::session::Session;
+
+// Internal modules (no semver guarantees)
+#[doc(hidden)]
 pub mod backends;
+#[cfg(all(not(target_arch = "wasm32"), feature = "hooks"))]
+#[doc(hidden)]
+pub mod dap;
+#[doc(hidden)]
 pub mod formatter;
+#[doc(hidden)]
 pub mod frontend;
 #[cfg(not(target_arch = "wasm32"))]
+#[doc(hidden)]
 pub mod lsp;
+#[doc(hidden)]
 pub mod middle;
 #[cfg(not(target_arch = "wasm32"))]
+#[doc(hidden)]
 pub mod package;
 #[cfg(not(target_arch = "wasm32"))]
+#[doc(hidden)]
 pub mod repl;
+#[doc(hidden)]
 pub mod std;
 
+#[doc(hidden)]
 pub mod util;
 
 // Re-exports
@@ -37,7 +82,9 @@ pub use anyhow::{Context, Result};
 pub use thiserror::Error;
 
 // Backend re-exports
-pub use backends::{Executor, DebuggableExecutor, ExecutorError, ExecutorResult, ExecutorConfig};
+pub use backends::{
+    CapabilityPolicy, DebuggableExecutor, Executor, ExecutorConfig, ExecutorError, ExecutorResult,
+};
 pub use backends::common::{RuntimeValue, Opcode, Heap, Handle, BumpAllocator};
 pub use backends::interpreter::Interpreter;
 #[cfg(not(target_arch = "wasm32"))]
@@ -55,6 +102,9 @@ pub const NAME: &str = "YaoXiang (爻象)";
 
 /// Run the interpreter on source code
 ///
+/// Returns the program's exit code: the Int returned by `main`, the code
+/// passed to `std.process.exit`, or 0.
+///
 /// # Example
 ///
 /// ```no_run
@@ -70,7 +120,7 @@ pub const NAME: &str = "YaoXiang (爻象)";
 ///     Ok(())
 /// }
 /// ```
-pub fn run(source: &str) -> Result<()> {
+pub fn run(source: &str) -> Result<i32> {
     run_with_source_name("<input>", source)
 }
 /// Evaluate YaoXiang code (eval mode: auto-wrap if no main function)
@@ -79,7 +129,50 @@ pub fn run(source: &str) -> Result<()> {
 /// - Checks if the code has a top-level `main =` binding
 /// - If yes: compiles and executes as-is
 /// - If no: wraps the code in `main = { ... }` automatically
-pub fn eval_code(source: &str) -> Result<()> {
+///
+/// Nothing is written to disk; use [`eval_code_with_cache`] to reuse compiled
+/// bytecode across calls.
+///
+/// Returns the program's exit code, like `run()`.
+pub fn eval_code(source: &str) -> Result<i32> {
+    let (compile_source, _) = eval_source(source)?;
+    run_with_source_name("<eval>", &compile_source)
+}
+
+/// Evaluate YaoXiang code like [`eval_code`], looking up and storing the compiled
+/// bytecode in `cache` (`None` disables caching) and granting the program
+/// `capabilities`
+///
+/// Code that imports non-std modules is never cached: the imported files may
+/// change without the evaluated code changing.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn eval_code_with_cache(
+    source: &str,
+    cache: Option<&util::artifact_cache::ArtifactCache>,
+    capabilities: &CapabilityPolicy,
+) -> Result<i32> {
+    let (compile_source, cacheable) = eval_source(source)?;
+    let Some(cache) = cache.filter(|_| cacheable) else {
+        let bytecode_file = compile_bytecode("<eval>", &compile_source)?;
+        return execute_bytecode("<eval>", bytecode_file, capabilities);
+    };
+
+    let key = util::artifact_cache::ArtifactCache::key(&["eval", &compile_source]);
+    let bytecode_file = match cache.load_bytecode(&key) {
+        Some(bytecode_file) => bytecode_file,
+        None => {
+            let bytecode_file = compile_bytecode("<eval>", &compile_source)?;
+            if let Err(e) = cache.store_bytecode(&key, &bytecode_file) {
+                debug!("Failed to cache eval bytecode: {}", e);
+            }
+            bytecode_file
+        }
+    };
+    execute_bytecode("<eval>", bytecode_file, capabilities)
+}
+
+/// Source actually compiled by eval mode, and whether it only imports std modules
+fn eval_source(source: &str) -> Result<(String, bool)> {
     let tokens = crate::frontend::core::tokenize(source)
         .map_err(|e| anyhow::anyhow!("Lexer error: {:?}", e))?;
     let parse_result = crate::frontend::core::parser::parse(&tokens);
@@ -90,70 +183,99 @@ pub fn eval_code(source: &str) -> Result<()> {
             if name == "main"
         )
     });
+    let std_only = parse_result
+        .module
+        .items
+        .iter()
+        .all(|stmt| match &stmt.kind {
+            crate::frontend::core::parser::ast::StmtKind::Use { path, .. } => {
+                path == "std" || path.starts_with("std.")
+            }
+            _ => true,
+        });
     let compile_source: String = if has_main {
         source.to_string()
     } else {
         format!("main = {{\n{}}}", source)
     };
-    run_with_source_name("<eval>", &compile_source)
+    Ok((compile_source, std_only))
 }
 
 fn run_with_source_name(
     source_name: &str,
     source: &str,
-) -> Result<()> {
+) -> Result<i32> {
+    let bytecode_file = compile_bytecode(source_name, source)?;
+    execute_bytecode(source_name, bytecode_file, &CapabilityPolicy::default())
+}
+
+/// Compile source code to bytecode with the default engine
+fn compile_bytecode(
+    source_name: &str,
+    source: &str,
+) -> Result<crate::middle::passes::codegen::bytecode::BytecodeFile> {
     debug!("{}", t_cur_simple(MSG::DebugRunCalled));
-    let mut compiler = frontend::Compiler::new();
     debug!("{}", t_cur_simple(MSG::CompilationStart));
-    let module = compiler.compile_with_source(source_name, source)?;
-    // Generate BytecodeModule using the new backend architecture
-    let mut ctx = crate::middle::passes::codegen::CodegenContext::new(module);
-    let bytecode_file = ctx
-        .generate()
-        .map_err(|e| anyhow::anyhow!("Codegen failed: {:?}", e))?;
+    Ok(Engine::default().compile_bytecode(source_name, source)?)
+}
 
-    // Convert BytecodeFile to BytecodeModule
+/// Run compiled bytecode with the default engine granted `capabilities`,
+/// returning the program's exit code
+fn execute_bytecode(
+    source_name: &str,
+    bytecode_file: crate::middle::passes::codegen::bytecode::BytecodeFile,
+    capabilities: &CapabilityPolicy,
+) -> Result<i32> {
     let bytecode_module = crate::middle::bytecode::BytecodeModule::from(bytecode_file);
 
-    // Use the new Interpreter backend
-    let mut interpreter = backends::interpreter::Interpreter::new();
+    let mut interpreter = Engine::builder()
+        .capabilities(capabilities.clone())
+        .build()
+        .interpreter();
+    if let Some(dir) = ::std::path::Path::new(source_name).parent() {
+        interpreter.set_import_base_dir(dir);
+    }
     debug!("{}", t_cur_simple(MSG::VmStart));
     interpreter.execute_module(&bytecode_module)?;
     debug!("{}", t_cur_simple(MSG::VmComplete));
-    Ok(())
+    Ok(interpreter.state().exit_code)
 }
 
 #[cfg(not(target_arch = "wasm32"))]
 use ::std::fs;
 #[cfg(not(target_arch = "wasm32"))]
 use ::std::path::Path;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::frontend::CompileConfig;
 
-/// Run the interpreter on a file
+/// Run the interpreter on a file, returning the program's exit code
 #[cfg(not(target_arch = "wasm32"))]
-pub fn run_file(path: &Path) -> Result<()> {
+pub fn run_file(path: &Path) -> Result<i32> {
     let path_str = path.display().to_string();
     debug!("{}", t_cur(MSG::RunFile, Some(&[&path_str])));
     let source = fs::read_to_string(path)
         .with_context(|| format!("Failed to read file: {}", path.display()))?;
     debug!("{}", t_cur(MSG::ReadingFile, Some(&[&path_str])));
+    backends::interpreter::extension::load_for_file(path, &backends::CapabilityPolicy::default())?;
     run_with_source_name(&path_str, &source)
 }
 
-/// Build bytecode file (.42)
+/// Build bytecode file (.42 / .yxbc)
 #[cfg(not(target_arch = "wasm32"))]
 pub fn build_bytecode(
     source_path: &Path,
     output_path: &Path,
 ) -> Result<()> {
-    build_bytecode_with_options(source_path, output_path, false)
+    build_bytecode_with_options(source_path, output_path, false, CompileConfig::new())
 }
 
-/// Build bytecode file (.42) with options
+/// Build bytecode file (.42 / .yxbc) with options
 #[cfg(not(target_arch = "wasm32"))]
 pub fn build_bytecode_with_options(
     source_path: &Path,
     output_path: &Path,
     debug_info: bool,
+    config: CompileConfig,
 ) -> Result<()> {
     use crate::middle::passes::codegen::CodegenContext;
 
@@ -166,8 +288,14 @@ pub fn build_bytecode_with_options(
     debug!("{}", t_cur(MSG::ReadingFile, Some(&[&source_path_str])));
 
     // Compile
-    let mut compiler = frontend::Compiler::new();
+    let mut compiler = frontend::Compiler::with_config(config);
     let module = compiler.compile_with_source(&source_path_str, &source)?;
+    if compiler.config().timings {
+        eprint!(
+            "{}",
+            crate::middle::passes::manager::format_timings(compiler.pass_timings())
+        );
+    }
 
     // Generate bytecode
     let mut ctx = CodegenContext::new(module);
@@ -198,6 +326,48 @@ pub fn build_bytecode_with_options(
     Ok(())
 }
 
+/// Compile a source file and render its optimized IR as text (`build --emit ir`)
+#[cfg(not(target_arch = "wasm32"))]
+pub fn emit_ir(
+    source_path: &Path,
+    config: CompileConfig,
+) -> Result<String> {
+    let source_path_str = source_path.display().to_string();
+    let source = fs::read_to_string(source_path)
+        .with_context(|| format!("Failed to read source: {}", source_path.display()))?;
+
+    let mut compiler = frontend::Compiler::with_config(config);
+    let module = compiler.compile_with_source(&source_path_str, &source)?;
+    if compiler.config().timings {
+        eprint!(
+            "{}",
+            crate::middle::passes::manager::format_timings(compiler.pass_timings())
+        );
+    }
+    Ok(module.to_string())
+}
+
+/// Compile a source file to a WebAssembly module (`build --emit wasm`)
+#[cfg(all(feature = "wasm", not(target_arch = "wasm32")))]
+pub fn emit_wasm(
+    source_path: &Path,
+    config: CompileConfig,
+) -> Result<Vec<u8>> {
+    let source_path_str = source_path.display().to_string();
+    let source = fs::read_to_string(source_path)
+        .with_context(|| format!("Failed to read source: {}", source_path.display()))?;
+
+    let mut compiler = frontend::Compiler::with_config(config);
+    let module = compiler.compile_with_source(&source_path_str, &source)?;
+    if compiler.config().timings {
+        eprint!(
+            "{}",
+            crate::middle::passes::manager::format_timings(compiler.pass_timings())
+        );
+    }
+    backends::wasm::emit_module(&module).map_err(|e| anyhow::anyhow!("{}", e))
+}
+
 /// Dump bytecode for debugging
 #[cfg(not(target_arch = "wasm32"))]
 pub fn dump_bytecode(path: &Path) -> Result<()> {
@@ -350,6 +520,7 @@ fn dump_type_detail(ty: &crate::frontend::core::typecheck::MonoType) -> String {
         crate::frontend::core::typecheck::MonoType::Char => "char".to_string(),
         crate::frontend::core::typecheck::MonoType::String => "String".to_string(),
         crate::frontend::core::typecheck::MonoType::Bytes => "bytes".to_string(),
+        crate::frontend::core::typecheck::MonoType::Any => "Any".to_string(),
         crate::frontend::core::typecheck::MonoType::Struct(struct_type) => {
             format!("struct {:?}", struct_type)
         }
@@ -426,6 +597,9 @@ fn dump_type_detail(ty: &crate::frontend::core::typecheck::MonoType) -> String {
         crate::frontend::core::typecheck::MonoType::Weak(inner) => {
             format!("Weak({})", dump_type_detail(inner))
         }
+        crate::frontend::core::typecheck::MonoType::Async(inner) => {
+            format!("Async({})", dump_type_detail(inner))
+        }
         crate::frontend::core::typecheck::MonoType::Ref { mutable, inner } => {
             if *mutable {
                 format!("&mut {}", dump_type_detail(inner))
@@ -510,6 +684,9 @@ fn dump_const_detail(constant: &crate::middle::core::ir::ConstValue) -> &'static
         crate::middle::core::ir::ConstValue::Char(_) => "char",
         crate::middle::core::ir::ConstValue::String(_) => "String",
         crate::middle::core::ir::ConstValue::Bytes(_) => "bytes",
+        crate::middle::core::ir::ConstValue::List(_) => "list",
+        crate::middle::core::ir::ConstValue::Dict(_) => "dict",
+        crate::middle::core::ir::ConstValue::Struct { .. } => "struct",
         crate::middle::core::ir::ConstValue::LibraryRef { .. }
         | crate::middle::core::ir::ConstValue::ExternRef { .. } => todo!(),
     }
@@ -4,7 +4,9 @@
 //! - 基于诊断的快速修复
 //! - 代码重构建议
 
-use lsp_types::{CodeAction, CodeActionKind, CodeActionParams, Range};
+use std::collections::HashMap;
+
+use lsp_types::{CodeAction, CodeActionKind, CodeActionParams, Range, WorkspaceEdit};
 use tracing::debug;
 
 use crate::lsp::handlers::diagnostics;
@@ -12,7 +14,7 @@ use crate::lsp::handlers::diagnostics;
 /// 处理 `textDocument/codeAction` 请求
 ///
 /// 返回基于当前上下文的代码操作列表：
-/// 1. 诊断相关的快速修复
+/// 1. 诊断相关的快速修复（含编译器给出的 did-you-mean 修复）
 /// 2. 代码重构建议
 pub fn handle_code_action(
     params: CodeActionParams,
@@ -26,8 +28,29 @@ pub fn handle_code_action(
 
     let mut actions: Vec<CodeAction> = Vec::new();
 
-    // 为每个诊断生成快速修复
+    // 为每个诊断生成快速修复：优先使用编译器给出的结构化修复
     for diagnostic in &diagnostic_list {
+        let fixes = diagnostics::fixes_from_data(diagnostic);
+        if !fixes.is_empty() {
+            actions.extend(fixes.into_iter().enumerate().map(|(i, fix)| {
+                #[allow(clippy::mutable_key_type)]
+                let changes = HashMap::from([(params.text_document.uri.clone(), fix.edits)]);
+                CodeAction {
+                    title: fix.title,
+                    kind: Some(CodeActionKind::QUICKFIX),
+                    diagnostics: Some(vec![diagnostic.clone()]),
+                    edit: Some(WorkspaceEdit {
+                        changes: Some(changes),
+                        ..Default::default()
+                    }),
+                    command: None,
+                    is_preferred: Some(i == 0),
+                    disabled: None,
+                    data: None,
+                }
+            }));
+            continue;
+        }
         if let Some(fix) = generate_fix_for_diagnostic(diagnostic) {
             actions.push(fix);
         }
@@ -115,3 +138,7 @@ fn generate_refactor_actions(range: &Range) -> Vec<CodeAction> {
 
     actions
 }
+
+#[cfg(test)]
+#[path = "tests/code_action.rs"]
+mod tests;
@@ -6,7 +6,7 @@
 //!
 //! 诊断管线：
 //! ```text
-//! 源代码 → tokenize → parse → check_module_collect_all
+//! 源代码 → tokenize → parse → check_module
 //!                          ↓                ↓
 //!                    ParseError[]      Diagnostic[]
 //!                          ↓                ↓
@@ -20,14 +20,17 @@
 use std::str::FromStr;
 
 use lsp_types::{
-    Diagnostic as LspDiagnostic, DiagnosticSeverity, Position, PublishDiagnosticsParams, Range, Uri,
+    Diagnostic as LspDiagnostic, DiagnosticSeverity, Position, PublishDiagnosticsParams, Range,
+    TextEdit, Uri,
 };
+use serde::{Deserialize, Serialize};
 use tracing::{debug, warn};
 
+use crate::frontend::core::desugar::desugar_module;
 use crate::frontend::core::lexer::tokenize;
 use crate::frontend::core::parser::parse;
-use crate::frontend::core::typecheck::check_module_collect_all;
-use crate::util::diagnostic::{Diagnostic, Severity};
+use crate::frontend::core::typecheck::check_module;
+use crate::util::diagnostic::{Diagnostic, Fix, Severity};
 use crate::util::span::Span;
 
 /// 将 YaoXiang Diagnostic 转换为 LSP Diagnostic
@@ -50,6 +53,12 @@ pub fn to_lsp_diagnostic(diag: &Diagnostic) -> LspDiagnostic {
         Some(lsp_types::NumberOrString::String(diag.code.clone()))
     };
 
+    // W2xxx 弃用警告：编辑器以删除线标出
+    let tags = diag
+        .code
+        .starts_with("W2")
+        .then(|| vec![lsp_types::DiagnosticTag::DEPRECATED]);
+
     LspDiagnostic {
         range,
         severity,
@@ -57,10 +66,49 @@ pub fn to_lsp_diagnostic(diag: &Diagnostic) -> LspDiagnostic {
         source: Some("yaoxiang".to_string()),
         message: diag.message.clone(),
         related_information: None,
-        tags: None,
+        tags,
         code_description: None,
-        data: None,
+        data: fixes_to_data(&diag.fixes),
+    }
+}
+
+/// 随诊断下发的修复建议（存放在 `Diagnostic.data` 中）
+///
+/// `textDocument/codeAction` 从中直接生成快速修复，无需重新分析。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FixData {
+    pub title: String,
+    pub edits: Vec<TextEdit>,
+}
+
+fn fixes_to_data(fixes: &[Fix]) -> Option<serde_json::Value> {
+    if fixes.is_empty() {
+        return None;
     }
+    let data: Vec<FixData> = fixes
+        .iter()
+        .map(|fix| FixData {
+            title: fix.message.clone(),
+            edits: fix
+                .edits
+                .iter()
+                .map(|edit| TextEdit {
+                    range: span_to_range(&edit.span),
+                    new_text: edit.replacement.clone(),
+                })
+                .collect(),
+        })
+        .collect();
+    serde_json::to_value(data).ok()
+}
+
+/// 读取诊断携带的修复建议
+pub fn fixes_from_data(diagnostic: &LspDiagnostic) -> Vec<FixData> {
+    diagnostic
+        .data
+        .clone()
+        .and_then(|data| serde_json::from_value(data).ok())
+        .unwrap_or_default()
 }
 
 /// 将 YaoXiang Span 转换为 LSP Range
@@ -88,7 +136,7 @@ pub fn to_lsp_diagnostics(diagnostics: &[Diagnostic]) -> Vec<LspDiagnostic> {
 ///
 /// 对文档内容运行完整诊断管线
 ///
-/// 流程：tokenize → parse → check_module_collect_all
+/// 流程：tokenize → parse → check_module
 ///
 /// 任何阶段的错误都会收集为 LSP 诊断返回。
 /// Lex 错误会短路（无法继续解析），但 parse 错误不影响 typecheck。
@@ -129,14 +177,20 @@ pub fn run_diagnostics(
         all_diagnostics.extend(to_lsp_diagnostics(&parse_diags));
     }
 
-    // 3. 类型检查（收集所有错误模式）
-    let type_result = check_module_collect_all(&parse_result.module, &mut None);
+    // 3. 脱糖
+    let mut module = parse_result.module;
+    let desugar_diags = desugar_module(&mut module);
+    all_diagnostics.extend(to_lsp_diagnostics(&desugar_diags));
+
+    // 4. 类型检查（收集所有错误模式）
+    let type_result = check_module(&module, &mut None);
     if !type_result.diagnostics.is_empty() {
         debug!("类型错误 ({} 个): {}", type_result.diagnostics.len(), uri);
         all_diagnostics.extend(to_lsp_diagnostics(&type_result.diagnostics));
     } else {
         debug!("类型检查通过: {}", uri);
     }
+    all_diagnostics.extend(to_lsp_diagnostics(&type_result.warnings));
 
     debug!("诊断完成: {} ({} 条诊断)", uri, all_diagnostics.len());
 
@@ -5,7 +5,7 @@
 //! - 有选区时的代码操作
 //! - 诊断相关的快速修复
 
-use lsp_types::{CodeAction, CodeActionKind, CodeActionParams, Range};
+use lsp_types::{CodeActionKind, CodeActionParams, Range};
 use lsp_types::{CodeActionContext, Position, TextDocumentIdentifier, Uri};
 use std::str::FromStr;
 
@@ -78,3 +78,24 @@ fn test_code_action_with_selection() {
     assert!(actions.iter().any(|a| a.title == "提取为变量"));
     assert!(actions.iter().any(|a| a.title == "内联变量"));
 }
+
+#[test]
+#[allow(clippy::mutable_key_type)]
+fn test_code_action_applies_did_you_mean_fix() {
+    let params = make_params("file:///test.yx");
+    let content = "main = {\n    counter = 1\n    print(countr)\n}\n";
+    let actions = handle_code_action(params, content).expect("actions");
+
+    let fix = actions
+        .iter()
+        .find(|a| a.title.contains("`counter`"))
+        .expect("did-you-mean quick fix");
+    assert_eq!(fix.kind, Some(CodeActionKind::QUICKFIX));
+    assert_eq!(fix.is_preferred, Some(true));
+
+    let changes = fix.edit.as_ref().and_then(|e| e.changes.as_ref()).unwrap();
+    let edits = changes.values().next().unwrap();
+    assert_eq!(edits.len(), 1);
+    assert_eq!(edits[0].new_text, "counter");
+    assert_eq!(edits[0].range.start, Position::new(2, 10));
+}
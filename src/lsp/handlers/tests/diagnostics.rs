@@ -17,7 +17,7 @@ use lsp_types::{
 
 use crate::frontend::core::lexer::tokenize;
 use crate::frontend::core::parser::parse;
-use crate::frontend::core::typecheck::check_module_collect_all;
+use crate::frontend::core::typecheck::check_module;
 use crate::lsp::handlers::diagnostics::{
     to_lsp_diagnostic, to_lsp_diagnostics, run_diagnostics, clear_diagnostics,
 };
@@ -38,6 +38,7 @@ fn make_diag(
         help: String::new(),
         span,
         related: vec![],
+        fixes: vec![],
     }
 }
 
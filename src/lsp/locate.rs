@@ -51,7 +51,7 @@ pub fn find_identifier_at_position(
         if after_start && before_end {
             if let TokenKind::Identifier(ref name) = token.kind {
                 return Some(IdentAtPosition {
-                    name: name.clone(),
+                    name: name.to_string(),
                     span: token.span,
                 });
             }
@@ -468,11 +468,12 @@ fn update_semantic_db(
             }
         };
 
-        let parse_result = crate::frontend::core::parser::parse(&tokens);
+        let mut module = crate::frontend::core::parser::parse(&tokens).module;
+        crate::frontend::core::desugar::desugar_module(&mut module);
 
-        // 运行 typecheck 收集语义 tokens（使用 collect_all 模式以收集更多信息）
+        // 运行 typecheck 收集语义 tokens（出错时仍会检查其余语句，尽量收集信息）
         let mut tc = crate::frontend::core::typecheck::TypeChecker::new(uri);
-        let result = tc.check_module_collect_all(&parse_result.module);
+        let result = tc.check_module(&module);
         world.update_semantic_db(result.semantic_db);
 
         debug!("已更新语义数据库: {}", uri);
@@ -166,7 +166,7 @@ impl World {
         // YaoXiang 语言的核心内置类型
         let builtin_types = [
             "Int", "Int8", "Int16", "Int32", "Int64", "Int128", "Uint", "Float", "Float32",
-            "Float64", "Bool", "String", "Char", "Bytes", "Void",
+            "Float64", "Bool", "String", "Char", "Bytes", "Void", "Any",
         ];
 
         let file_path = "builtin://types".to_string();
@@ -2,23 +2,31 @@
 
 use anyhow::{Context, Result};
 use clap::{Parser, Subcommand, ValueEnum};
-use std::io::{IsTerminal, Read};
+use std::io::{IsTerminal, Read, Write};
 use std::path::PathBuf;
 use tracing::info;
+use yaoxiang::backends::common::heap_dump::{HeapDiff, HeapDump};
+use yaoxiang::backends::interpreter::runtime::InterpreterRuntimeConfig;
+use yaoxiang::backends::runtime::RuntimeMode;
+use yaoxiang::backends::{BuildMode, CapabilityPolicy, ExecutorConfig};
+use yaoxiang::frontend::config::{CompileConfig, OptLevel};
+use yaoxiang::middle::passes::manager::Pass;
 use yaoxiang::repl::Repl;
 use yaoxiang::formatter::run_format_command;
 use yaoxiang::{dump_bytecode, NAME, VERSION};
 use yaoxiang::util::diagnostic::{
-    render_explain_output, run_check_command_once, run_check_watch_command,
-    run_file_with_diagnostics,
+    render_error_index, render_explain_output, run_check_command_summary, run_check_watch_command,
+    run_file_with_diagnostics, ExitStatus, RunOptions,
 };
+use yaoxiang::util::artifact_cache::ArtifactCache;
 use yaoxiang::util::i18n::set_lang_from_string;
-use yaoxiang::util::logger::LogLevel;
+use yaoxiang::util::logger::{LogConfig, LogLevel};
 use yaoxiang::package;
 
 /// Log level enum for CLI
 #[derive(Debug, Clone, Copy, ValueEnum)]
 enum LogLevelArg {
+    Trace,
     Debug,
     Info,
     Warn,
@@ -28,6 +36,7 @@ enum LogLevelArg {
 impl From<LogLevelArg> for LogLevel {
     fn from(level: LogLevelArg) -> Self {
         match level {
+            LogLevelArg::Trace => LogLevel::Trace,
             LogLevelArg::Debug => LogLevel::Debug,
             LogLevelArg::Info => LogLevel::Info,
             LogLevelArg::Warn => LogLevel::Warn,
@@ -41,6 +50,9 @@ impl From<LogLevelArg> for LogLevel {
 enum LangArg {
     En,
     Zh,
+    Ja,
+    Ru,
+    ZhClassical,
     ZhMiao,
 }
 
@@ -49,6 +61,9 @@ impl From<LangArg> for String {
         match lang {
             LangArg::En => "en".to_string(),
             LangArg::Zh => "zh".to_string(),
+            LangArg::Ja => "ja".to_string(),
+            LangArg::Ru => "ru".to_string(),
+            LangArg::ZhClassical => "zh-classical".to_string(),
             LangArg::ZhMiao => "zh-x-miao".to_string(),
         }
     }
@@ -62,6 +77,19 @@ enum ColorChoice {
     Never,
 }
 
+/// Output of `yaoxiang build`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+enum EmitKind {
+    /// Bytecode file (.42 or .yxbc)
+    #[default]
+    Bytecode,
+    /// Optimized IR as text
+    Ir,
+    /// WebAssembly module (.wasm)
+    #[cfg(feature = "wasm")]
+    Wasm,
+}
+
 /// A high-performance programming language with "everything is type" philosophy
 #[derive(Parser, Debug)]
 #[command(name = "yaoxiang")]
@@ -72,24 +100,32 @@ struct Args {
     #[command(subcommand)]
     command: Option<Commands>,
 
-    /// Enable verbose output
-    #[arg(short, long)]
-    verbose: bool,
+    /// Increase log verbosity (-v debug, -vv trace, -vvv trace with source locations)
+    #[arg(short, long, action = clap::ArgAction::Count)]
+    verbose: u8,
 
-    /// Set log level (debug, info, warn, error)
+    /// Set log level (trace, debug, info, warn, error)
     #[arg(short, long, value_enum)]
     log_level: Option<LogLevelArg>,
 
-    /// Set language (en, zh, zh-miao)
+    /// Per-module log filters, e.g. `frontend=debug,vm=warn`
+    #[arg(long, value_name = "SPEC")]
+    log: Option<String>,
+
+    /// Set language (en, zh, ja, ru, zh-classical, zh-miao)
     #[arg(short = 'L', long, value_enum)]
     lang: Option<LangArg>,
+
+    /// Explain an error code (same as `yaoxiang explain <CODE>`)
+    #[arg(long, value_name = "CODE")]
+    explain: Option<String>,
 }
 
 #[derive(Subcommand, Debug)]
 enum Commands {
     /// Run a YaoXiang source file
     Run {
-        /// Source file to run
+        /// Source file to run, or a bytecode file (.42 / .yxbc) from `yaoxiang build`
         #[arg(value_name = "FILE")]
         file: PathBuf,
 
@@ -104,6 +140,53 @@ enum Commands {
         /// Number of worker threads (0 = auto)
         #[arg(long, default_value = "0")]
         workers: usize,
+
+        /// Run spawned tasks on N threads that steal each other's work (selects the full runtime)
+        #[arg(long, value_name = "N")]
+        threads: Option<usize>,
+
+        /// Release mode: integer overflow wraps instead of trapping
+        #[arg(long)]
+        release: bool,
+
+        /// Optimization level (0, 1, 2); overrides `[build] opt_level` in yaoxiang.toml
+        #[arg(short = 'O', long = "opt-level", value_name = "LEVEL")]
+        opt_level: Option<OptLevel>,
+
+        /// Print the time spent in each optimization pass
+        #[arg(long)]
+        timings: bool,
+
+        /// Skip escape analysis even when the level or `[build] passes` includes it
+        #[arg(long)]
+        no_escape_analysis: bool,
+
+        /// Write a heap dump (object types, sizes, retaining paths) here when the VM exits
+        #[arg(long, value_name = "PATH")]
+        heap_dump_on_exit: Option<PathBuf>,
+
+        /// Interpret every function instead of compiling hot ones to machine code
+        #[arg(long)]
+        no_jit: bool,
+
+        /// Print which functions the JIT compiled and the time spent in machine code vs the interpreter
+        #[arg(long)]
+        jit_stats: bool,
+
+        /// Collect unreachable heap objects once the heap holds this many (0 = only on `gc.collect()`)
+        #[arg(long, value_name = "OBJECTS")]
+        gc_threshold: Option<usize>,
+
+        /// Arguments passed to the program (after `--`), read via `std.env.args()`
+        #[arg(last = true, value_name = "ARGS")]
+        args: Vec<String>,
+    },
+
+    /// Debug a YaoXiang source file (breakpoints, stepping, call stack)
+    Debug {
+        /// Source file to debug
+        #[arg(value_name = "FILE")]
+        file: PathBuf,
     },
 
     /// Evaluate YaoXiang code (use '-' to read from stdin)
@@ -111,6 +194,10 @@ enum Commands {
         /// Code to evaluate
         #[arg(value_name = "CODE")]
         code: String,
+
+        /// Always compile, bypassing the compiled bytecode cache
+        #[arg(long)]
+        no_cache: bool,
     },
 
     /// Check source file for errors (type checking)
@@ -138,6 +225,10 @@ enum Commands {
         /// Suppress progress and summary messages
         #[arg(long)]
         no_progress: bool,
+
+        /// Fail (exit code 1) when more than N warnings are reported
+        #[arg(long, value_name = "N")]
+        max_warnings: Option<usize>,
     },
 
     /// Format source file
@@ -177,6 +268,10 @@ enum Commands {
         /// Use single quotes for strings
         #[arg(long)]
         single_quote: bool,
+
+        /// Line endings of the output: preserve, lf or crlf
+        #[arg(long, value_name = "STYLE")]
+        line_ending: Option<yaoxiang::util::config::LineEnding>,
     },
 
     /// Dump bytecode for debugging
@@ -186,32 +281,59 @@ enum Commands {
         file: PathBuf,
     },
 
+    /// Compare two heap dumps written by `run --heap-dump-on-exit`
+    HeapDiff {
+        /// Earlier heap dump
+        #[arg(value_name = "BEFORE")]
+        before: PathBuf,
+
+        /// Later heap dump
+        #[arg(value_name = "AFTER")]
+        after: PathBuf,
+    },
+
     /// Build bytecode file
     Build {
         /// Source file to compile
         #[arg(value_name = "FILE")]
         file: PathBuf,
 
-        /// Output file (optional, defaults to <input>.42)
+        /// Output file (optional, defaults to <input>.42; a .yxbc name works too)
         #[arg(short, long)]
         output: Option<PathBuf>,
 
-        /// Embed debug section into .42 (sources + ip->span mapping)
+        /// Embed debug section into .42 (sources + line table)
         #[arg(long)]
         debug_info: bool,
+
+        /// Optimization level (0, 1, 2); overrides `[build] opt_level` in yaoxiang.toml
+        #[arg(short = 'O', long = "opt-level", value_name = "LEVEL")]
+        opt_level: Option<OptLevel>,
+
+        /// Print the time spent in each optimization pass
+        #[arg(long)]
+        timings: bool,
+
+        /// Skip escape analysis even when the level or `[build] passes` includes it
+        #[arg(long)]
+        no_escape_analysis: bool,
+
+        /// What to emit; `ir` prints the optimized IR to stdout unless `-o` is given
+        #[arg(long, value_enum, default_value_t = EmitKind::Bytecode)]
+        emit: EmitKind,
     },
 
-    /// Explain an error code
+    /// Explain an error code, or list all codes when none is given
     Explain {
         /// Error code to explain (e.g., E1001)
         #[arg(value_name = "CODE")]
-        code: String,
+        code: Option<String>,
 
         /// Output in JSON format
         #[arg(short, long)]
         json: bool,
 
-        /// Language for explanation (en, zh)
+        /// Language for explanation (defaults to the diagnostic language)
         #[arg(short, long, value_enum)]
         lang: Option<LangArg>,
     },
@@ -294,20 +416,106 @@ enum Commands {
     },
 }
 
-fn main() -> Result<()> {
+/// 退出码约定见 [`ExitStatus`]：0 成功，1 源码诊断，2 内部错误，101 panic
+fn main() {
+    let status = match std::panic::catch_unwind(run) {
+        Ok(Ok(())) => ExitStatus::Success,
+        Ok(Err(e)) => {
+            let status = ExitStatus::of_error(&e);
+            // 已渲染过的诊断不再重复打印
+            if e.downcast_ref::<yaoxiang::util::diagnostic::DiagnosticsReported>()
+                .is_none()
+            {
+                eprintln!("Error: {:?}", e);
+            }
+            status
+        }
+        Err(_) => ExitStatus::Panic,
+    };
+    std::process::exit(status.code());
+}
+
+/// 以程序自己请求的退出码结束进程（0 时照常返回）
+/// Load `./yaoxiang.toml`, falling back to defaults when missing or invalid
+fn load_project_config() -> yaoxiang::util::config::ProjectConfig {
+    std::fs::read_to_string("yaoxiang.toml")
+        .ok()
+        .and_then(|content| toml::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Compiler settings from the `[build]` section, with CLI flags taking precedence
+fn compile_config(
+    project: &yaoxiang::util::config::ProjectConfig,
+    opt_level: Option<OptLevel>,
+    timings: bool,
+    no_escape_analysis: bool,
+) -> Result<CompileConfig> {
+    let escape_analysis = !no_escape_analysis && project.build.escape_analysis.unwrap_or(true);
+    let mut config = CompileConfig::new()
+        .with_timings(timings)
+        .with_escape_analysis(escape_analysis)
+        .with_tree_shaking(project.build.tree_shake.unwrap_or(true));
+    let project_level = project
+        .build
+        .opt_level
+        .map(|level| level.to_string().parse::<OptLevel>())
+        .transpose()
+        .map_err(|e| anyhow::anyhow!("yaoxiang.toml: {}", e))?;
+    if let Some(level) = opt_level.or(project_level) {
+        config = config.with_opt_level(level);
+    }
+    // An explicit `-O` selects that level's preset instead of the project's pass list
+    if let (Some(passes), None) = (&project.build.passes, opt_level) {
+        let passes = passes
+            .iter()
+            .map(|name| name.parse::<Pass>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| anyhow::anyhow!("yaoxiang.toml: {}", e))?;
+        config = config.with_passes(passes);
+    }
+    Ok(config)
+}
+
+fn exit_with_program_code(code: i32) {
+    if code != 0 {
+        let _ = std::io::stdout().flush();
+        ::std::process::exit(code);
+    }
+}
+
+fn run() -> Result<()> {
     let args = Args::parse();
 
     // Set language first (before logger init)
     let lang = args.lang.map(Into::<String>::into).unwrap_or_else(|| {
         std::env::var("YAOXIANG_LANG")
             .ok()
-            .filter(|s| ["en", "zh", "zh-x-miao", "zh-miao"].contains(&s.as_str()))
+            .filter(|s| {
+                [
+                    "en",
+                    "zh",
+                    "ja",
+                    "ru",
+                    "zh-classical",
+                    "zh-x-miao",
+                    "zh-miao",
+                ]
+                .contains(&s.as_str())
+            })
             .unwrap_or_else(|| "en".to_string())
     });
     set_lang_from_string(lang);
 
-    // 如果没有提供子命令，启动 TUI REPL
-    let command = args.command.unwrap_or(Commands::Repl { tui: false });
+    // `--explain <CODE>` 等同于 explain 子命令；没有子命令时启动 TUI REPL
+    let command = match args.explain {
+        Some(code) => Commands::Explain {
+            code: Some(code),
+            json: false,
+            lang: None,
+        },
+        None => args.command.unwrap_or(Commands::Repl { tui: false }),
+    };
 
     // Initialize logger
     // LSP 模式必须写 stderr，避免污染 stdout 的 JSON-RPC 通道
@@ -320,13 +528,21 @@ fn main() -> Result<()> {
                 yaoxiang::util::logger::init_lsp();
             }
         }
-        _ => match args.log_level {
-            Some(level) => yaoxiang::util::logger::init_with_level(level.into()),
-            None => yaoxiang::util::logger::init_cli(),
-        },
+        _ => {
+            let mut config = LogConfig::from_verbosity(args.verbose);
+            if let Some(level) = args.log_level {
+                config.level = level.into();
+            }
+            if let Some(spec) = &args.log {
+                config
+                    .parse_directives(spec)
+                    .map_err(|e| anyhow::anyhow!("invalid --log value: {}", e))?;
+            }
+            yaoxiang::util::logger::init_with_config(&config);
+        }
     }
 
-    if args.verbose {
+    if args.verbose > 0 {
         info!("YaoXiang version: {}", VERSION);
         info!("Host: {}", std::env::consts::OS);
     }
@@ -337,26 +553,33 @@ fn main() -> Result<()> {
             debug_info,
             runtime,
             workers,
+            threads,
+            release,
+            opt_level,
+            timings,
+            no_escape_analysis,
+            heap_dump_on_exit,
+            no_jit,
+            jit_stats,
+            gc_threshold,
+            args: program_args,
         } => {
             // Load project config for runtime settings
-            let project_config = {
-                let config_path = std::path::PathBuf::from("yaoxiang.toml");
-                if config_path.exists() {
-                    let content = std::fs::read_to_string(&config_path).unwrap_or_default();
-                    toml::from_str::<yaoxiang::util::config::ProjectConfig>(&content)
-                        .unwrap_or_default()
-                } else {
-                    yaoxiang::util::config::ProjectConfig::default()
-                }
-            };
+            let project_config = load_project_config();
+            let compile_config =
+                compile_config(&project_config, opt_level, timings, no_escape_analysis)?;
 
             // CLI args override project config
-            let runtime_mode = if runtime != "embedded" {
+            let runtime_mode = if threads.is_some() {
+                "full".to_string()
+            } else if runtime != "embedded" {
                 runtime.clone()
             } else {
                 project_config.runtime.mode.clone()
             };
-            let workers = if workers > 0 {
+            let workers = if let Some(threads) = threads {
+                threads
+            } else if workers > 0 {
                 workers
             } else if project_config.runtime.workers > 0 {
                 project_config.runtime.workers
@@ -364,9 +587,58 @@ fn main() -> Result<()> {
                 0 // 0 = auto-detect
             };
 
-            run_file_with_diagnostics(&file, debug_info, &runtime_mode, workers)?;
+            // argv[0] is the script path, followed by everything after `--`
+            let argv = std::iter::once(file.display().to_string())
+                .chain(program_args)
+                .collect();
+            yaoxiang::std::env::set_program_args(argv);
+            // Ctrl-C/SIGTERM 在安全点中断 VM，而不是直接杀掉进程
+            yaoxiang::std::signal::install();
+
+            let defaults = ExecutorConfig::default();
+            let executor = ExecutorConfig {
+                build_mode: if release {
+                    BuildMode::Release
+                } else {
+                    BuildMode::Debug
+                },
+                heap_dump: heap_dump_on_exit,
+                native_code: !no_jit,
+                jit_stats,
+                gc_threshold: gc_threshold.unwrap_or(defaults.gc_threshold),
+                // 命令行运行的是用户自己的程序，授予全部能力
+                capabilities: CapabilityPolicy::allow_all(),
+                ..defaults
+            };
+            let mut runtime = InterpreterRuntimeConfig {
+                runtime: match runtime_mode.as_str() {
+                    "standard" => RuntimeMode::Standard,
+                    "full" => RuntimeMode::Full,
+                    _ => RuntimeMode::Embedded,
+                },
+                work_stealing: threads.is_some(),
+                ..Default::default()
+            };
+            if workers > 0 {
+                runtime.workers = workers;
+            }
+
+            let code = run_file_with_diagnostics(
+                &file,
+                RunOptions {
+                    debug_info,
+                    executor,
+                    runtime,
+                    compile: compile_config,
+                },
+            )?;
+            exit_with_program_code(code);
+        }
+        Commands::Debug { file } => {
+            let mut repl = Repl::new().context("Failed to start debugger")?;
+            repl.debug(&file).context("Debugger error")?;
         }
-        Commands::Eval { code } => {
+        Commands::Eval { code, no_cache } => {
             let source = if code == "-" {
                 let mut buf = String::new();
                 std::io::stdin()
@@ -376,7 +648,18 @@ fn main() -> Result<()> {
             } else {
                 code
             };
-            yaoxiang::eval_code(&source).context("Failed to evaluate code")?;
+            let cache = if no_cache {
+                None
+            } else {
+                ArtifactCache::user()
+            };
+            let code = yaoxiang::eval_code_with_cache(
+                &source,
+                cache.as_ref(),
+                &CapabilityPolicy::allow_all(),
+            )
+            .context("Failed to evaluate code")?;
+            exit_with_program_code(code);
         }
         Commands::Check {
             paths,
@@ -385,6 +668,7 @@ fn main() -> Result<()> {
             watch,
             color,
             no_progress,
+            max_warnings,
         } => {
             let use_colors = match color {
                 ColorChoice::Always => true,
@@ -395,19 +679,17 @@ fn main() -> Result<()> {
             if watch {
                 run_check_watch_command(paths, exclude, json, use_colors, no_progress)?;
             } else {
-                match run_check_command_once(&paths, &exclude, json, use_colors, no_progress) {
-                    Ok(error_count) => {
-                        if error_count > 0 {
-                            ::std::process::exit(1);
-                        }
-                    }
-                    Err(e) => {
-                        eprintln!("{}", e);
-                        if e.to_string().contains("No .yx files found") {
-                            ::std::process::exit(2);
-                        }
-                        return Err(e);
-                    }
+                let summary =
+                    run_check_command_summary(&paths, &exclude, json, use_colors, no_progress)?;
+                if let Some(max) = max_warnings.filter(|max| summary.warning_count > *max) {
+                    eprintln!(
+                        "Too many warnings: {} (--max-warnings {})",
+                        summary.warning_count, max
+                    );
+                }
+                let status = summary.exit_status(max_warnings);
+                if status != ExitStatus::Success {
+                    ::std::process::exit(status.code());
                 }
             }
         }
@@ -421,6 +703,7 @@ fn main() -> Result<()> {
             line_width,
             use_tabs,
             single_quote,
+            line_ending,
         } => {
             // 1. Load user config
             let user_config = yaoxiang::util::config::load_user_config().unwrap_or_default();
@@ -457,6 +740,9 @@ fn main() -> Result<()> {
             if let Some(si) = project_config.sort_imports {
                 options.sort_imports = si;
             }
+            if let Some(le) = project_config.line_ending {
+                options.line_ending = le;
+            }
 
             // 5. Override with CLI args (highest priority)
             if let Some(w) = indent {
@@ -471,49 +757,85 @@ fn main() -> Result<()> {
             if single_quote {
                 options.single_quote = true;
             }
+            if let Some(le) = line_ending {
+                options.line_ending = le;
+            }
 
             // 6. Apply CLI overrides
             if no_verify {
                 options.verify = false;
             }
 
-            let result = match run_format_command(&file, &options, dry_run, write) {
-                Ok(result) => result,
-                Err(e) => {
-                    eprintln!("{}", e);
-                    if e.to_string().contains("No .yx files found") {
-                        ::std::process::exit(2);
-                    }
-                    return Err(e);
-                }
-            };
+            let result = run_format_command(&file, &options, dry_run, write)?;
             if dry_run && result.needs_formatting {
-                ::std::process::exit(2);
+                ::std::process::exit(ExitStatus::Diagnostics.code());
             }
         }
         Commands::Dump { file } => {
             dump_bytecode(&file).with_context(|| format!("Failed to dump: {}", file.display()))?;
         }
+        Commands::HeapDiff { before, after } => {
+            let read = |path: &PathBuf| {
+                HeapDump::read(path)
+                    .with_context(|| format!("Failed to read heap dump: {}", path.display()))
+            };
+            let diff = HeapDiff::between(&read(&before)?, &read(&after)?);
+            print!("{}", diff.render());
+        }
         Commands::Build {
             file,
             output,
             debug_info,
+            opt_level,
+            timings,
+            no_escape_analysis,
+            emit,
         } => {
+            let compile_config = compile_config(
+                &load_project_config(),
+                opt_level,
+                timings,
+                no_escape_analysis,
+            )?;
+            if emit == EmitKind::Ir {
+                let ir = yaoxiang::emit_ir(&file, compile_config)
+                    .with_context(|| format!("Failed to build: {}", file.display()))?;
+                match output {
+                    Some(path) => std::fs::write(&path, ir)
+                        .with_context(|| format!("Failed to write IR: {}", path.display()))?,
+                    None => print!("{}", ir),
+                }
+                return Ok(());
+            }
+            #[cfg(feature = "wasm")]
+            if emit == EmitKind::Wasm {
+                let wasm = yaoxiang::emit_wasm(&file, compile_config)
+                    .with_context(|| format!("Failed to build: {}", file.display()))?;
+                let output_path = output.unwrap_or_else(|| file.with_extension("wasm"));
+                std::fs::write(&output_path, wasm).with_context(|| {
+                    format!("Failed to write module: {}", output_path.display())
+                })?;
+                return Ok(());
+            }
             let output_path = output.unwrap_or_else(|| {
                 let mut path = file.clone();
                 path.set_extension("42");
                 path
             });
-            yaoxiang::build_bytecode_with_options(&file, &output_path, debug_info)
+            yaoxiang::build_bytecode_with_options(&file, &output_path, debug_info, compile_config)
                 .with_context(|| format!("Failed to build: {}", file.display()))?;
         }
         Commands::Explain { code, json, lang } => {
             let lang_code = lang.map(Into::<String>::into);
+            let Some(code) = code else {
+                println!("{}", render_error_index(lang_code.as_deref()));
+                return Ok(());
+            };
             if let Some(output) = render_explain_output(&code, json, lang_code.as_deref())? {
                 println!("{}", output);
             } else {
                 eprintln!("Unknown error code: {}", code);
-                std::process::exit(1);
+                std::process::exit(ExitStatus::Internal.code());
             }
         }
         Commands::Version => {
@@ -524,7 +846,7 @@ fn main() -> Result<()> {
                 tracing::error!(
                     "TUI REPL mode is not available. Use 'yaoxiang repl' for the standard REPL."
                 );
-                std::process::exit(1);
+                std::process::exit(ExitStatus::Internal.code());
             }
             let mut repl = Repl::new().context("Failed to initialize REPL")?;
             repl.run().context("REPL exited with error")?;
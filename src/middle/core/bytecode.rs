@@ -120,6 +120,70 @@ pub enum CompareOp {
     Ge,
 }
 
+/// Numeric target of `Cast` / `Narrow`
+///
+/// Ids 0/1/2 match the Int/Float/Bool ids of `TypeCheck`; sized types start at 0x10.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NumericTarget {
+    /// Signed integer of the given bit width
+    Int(u8),
+    /// Float of the given bit width (32 or 64)
+    Float(u8),
+    Bool,
+}
+
+impl NumericTarget {
+    /// Id used when the target is not a numeric type (the value passes through)
+    pub const NONE: u16 = u16::MAX;
+
+    pub fn id(self) -> u16 {
+        match self {
+            NumericTarget::Int(8) => 0x10,
+            NumericTarget::Int(16) => 0x11,
+            NumericTarget::Int(32) => 0x12,
+            NumericTarget::Int(_) => 0,
+            NumericTarget::Float(32) => 0x13,
+            NumericTarget::Float(_) => 1,
+            NumericTarget::Bool => 2,
+        }
+    }
+
+    pub fn from_id(id: u16) -> Option<Self> {
+        match id {
+            0 => Some(NumericTarget::Int(64)),
+            1 => Some(NumericTarget::Float(64)),
+            2 => Some(NumericTarget::Bool),
+            0x10 => Some(NumericTarget::Int(8)),
+            0x11 => Some(NumericTarget::Int(16)),
+            0x12 => Some(NumericTarget::Int(32)),
+            0x13 => Some(NumericTarget::Float(32)),
+            _ => None,
+        }
+    }
+
+    /// Resolve a source-level type (`Int32`, `f32`, `Bool`, ...)
+    pub fn of_type(ty: &IrType) -> Option<Self> {
+        use crate::frontend::core::typecheck::inference::numeric::numeric_type_named;
+        use crate::frontend::core::typecheck::MonoType;
+
+        let mono = match ty {
+            IrType::Int(bits) => MonoType::Int(*bits),
+            IrType::Float(bits) => MonoType::Float(*bits),
+            IrType::Bool => return Some(NumericTarget::Bool),
+            IrType::Name { name, .. } if name == "Bool" || name == "bool" => {
+                return Some(NumericTarget::Bool)
+            }
+            IrType::Name { name, .. } => numeric_type_named(name)?,
+            _ => return None,
+        };
+        match mono {
+            MonoType::Int(bits) => Some(NumericTarget::Int(bits as u8)),
+            MonoType::Float(bits) => Some(NumericTarget::Float(bits as u8)),
+            _ => None,
+        }
+    }
+}
+
 /// Function reference
 #[derive(Debug, Clone)]
 pub enum FunctionRef {
@@ -199,6 +263,16 @@ pub enum BytecodeInstr {
         targets: Vec<(Option<Label>, Label)>,
     },
 
+    /// Jump table dispatch: `targets[value - low]` when in range, else `default`
+    ///
+    /// `value` is an `Int`, a `Char` (by code point) or an enum (by variant id).
+    TableSwitch {
+        value: Reg,
+        low: i64,
+        targets: Vec<Label>,
+        default: Label,
+    },
+
     // =====================
     // Register Operations
     // =====================
@@ -217,19 +291,19 @@ pub enum BytecodeInstr {
     /// Load local variable
     LoadLocal {
         dst: Reg,
-        local_idx: u8,
+        local_idx: u16,
     },
 
     /// Store local variable
     StoreLocal {
-        local_idx: u8,
+        local_idx: u16,
         src: Reg,
     },
 
     /// Load function argument
     LoadArg {
         dst: Reg,
-        arg_idx: u8,
+        arg_idx: u16,
     },
 
     // =====================
@@ -248,6 +322,19 @@ pub enum BytecodeInstr {
         op: UnaryOp,
     },
 
+    /// 32-bit integer arithmetic (`I32Add`..`I32Shr`); the result is narrowed to `Int32`
+    Int32Op {
+        dst: Reg,
+        lhs: Reg,
+        rhs: Reg,
+        op: BinaryOp,
+    },
+
+    Int32Neg {
+        dst: Reg,
+        src: Reg,
+    },
+
     // =====================
     // Comparison
     // =====================
@@ -258,13 +345,43 @@ pub enum BytecodeInstr {
         cmp: CompareOp,
     },
 
+    // =====================
+    // Float Operations
+    // =====================
+    /// Float arithmetic (`F64Add`..`F64Rem`); integer operands are widened first
+    FloatOp {
+        dst: Reg,
+        lhs: Reg,
+        rhs: Reg,
+        op: BinaryOp,
+    },
+
+    FloatNeg {
+        dst: Reg,
+        src: Reg,
+    },
+
+    /// Float comparison (`F64Eq`..`F64Ge`)
+    FloatCompare {
+        dst: Reg,
+        lhs: Reg,
+        rhs: Reg,
+        cmp: CompareOp,
+    },
+
+    /// Convert an integer to a float (implicit `Int` → `Float`)
+    IntToFloat {
+        dst: Reg,
+        src: Reg,
+    },
+
     // =====================
     // Memory Operations
     // =====================
-    /// Stack allocation
+    /// Move a fresh non-escaping object into the frame's stack region
     StackAlloc {
         dst: Reg,
-        size: u16,
+        src: Reg,
     },
 
     /// Heap allocation
@@ -278,17 +395,17 @@ pub enum BytecodeInstr {
         value: Reg,
     },
 
-    /// Get struct field
+    /// Get struct field at a byte offset in the struct's layout
     GetField {
         dst: Reg,
         src: Reg,
-        field_idx: u16,
+        offset: u16,
     },
 
-    /// Set struct field
+    /// Set struct field at a byte offset in the struct's layout
     SetField {
         src: Reg,
-        field_idx: u16,
+        offset: u16,
         value: Reg,
     },
 
@@ -379,6 +496,13 @@ pub enum BytecodeInstr {
         args: Vec<Reg>,
     },
 
+    /// Static tail call: the callee replaces the current frame and its
+    /// return value becomes the caller's
+    TailCall {
+        func: FunctionRef,
+        args: Vec<Reg>,
+    },
+
     /// Native function call (FFI)
     CallNative {
         dst: Option<Reg>,
@@ -405,6 +529,23 @@ pub enum BytecodeInstr {
         args: Vec<Reg>,
     },
 
+    /// Interface method call: slot `slot` of the receiver's vtable.
+    /// `name_idx` names the method for receivers that were not packed.
+    InvokeVirtual {
+        dst: Option<Reg>,
+        obj: Reg,
+        slot: u16,
+        name_idx: u16,
+        args: Vec<Reg>,
+    },
+
+    /// Pack `src` with module vtable `vtable` into an existential value
+    MakeDyn {
+        dst: Reg,
+        src: Reg,
+        vtable: u32,
+    },
+
     /// Create closure
     MakeClosure {
         dst: Reg,
@@ -459,6 +600,22 @@ pub enum BytecodeInstr {
         dst: Reg,
         src: Reg,
     },
+    /// New string builder holding the string `src`
+    StringBuilderNew {
+        dst: Reg,
+        src: Reg,
+    },
+    /// Append the string `rhs` to the builder `lhs` in place; `dst` gets the builder
+    StringBuilderAppend {
+        dst: Reg,
+        lhs: Reg,
+        rhs: Reg,
+    },
+    /// Join the builder `src` into a string (a string passes through)
+    StringBuilderFinish {
+        dst: Reg,
+        src: Reg,
+    },
 
     // =====================
     // Exception Handling
@@ -493,6 +650,21 @@ pub enum BytecodeInstr {
         target_type_id: u16,
     },
 
+    /// Checked downcast (`as?`): `dst = src` when the runtime type of `src` is
+    /// `target`, otherwise `dst = void`
+    TypeTest {
+        dst: Reg,
+        src: Reg,
+        target: String,
+    },
+
+    /// Narrow a sized arithmetic result to its declared width
+    Narrow {
+        dst: Reg,
+        src: Reg,
+        target_type_id: u16,
+    },
+
     // =====================
     // Reflection
     // =====================
@@ -500,6 +672,14 @@ pub enum BytecodeInstr {
         dst: Reg,
         src: Reg,
     },
+
+    /// Read field `field` of the struct in `src`, resolving its offset through
+    /// the struct layout at runtime (values typed as anonymous records)
+    GetRecordField {
+        dst: Reg,
+        src: Reg,
+        field: String,
+    },
 }
 
 impl BytecodeInstr {
@@ -516,6 +696,7 @@ impl BytecodeInstr {
             BytecodeInstr::JmpIf { .. } => Opcode::JmpIf,
             BytecodeInstr::JmpIfNot { .. } => Opcode::JmpIfNot,
             BytecodeInstr::Switch { .. } => Opcode::Switch,
+            BytecodeInstr::TableSwitch { .. } => Opcode::TableSwitch,
             BytecodeInstr::Mov { .. } => Opcode::Mov,
             BytecodeInstr::LoadConst { .. } => Opcode::LoadConst,
             BytecodeInstr::LoadLocal { .. } => Opcode::LoadLocal,
@@ -535,6 +716,20 @@ impl BytecodeInstr {
                 BinaryOp::Shr => Opcode::I64Shr,
             },
             BytecodeInstr::UnaryOp { .. } => Opcode::I64Neg,
+            BytecodeInstr::Int32Op { op, .. } => match op {
+                BinaryOp::Add => Opcode::I32Add,
+                BinaryOp::Sub => Opcode::I32Sub,
+                BinaryOp::Mul => Opcode::I32Mul,
+                BinaryOp::Div => Opcode::I32Div,
+                BinaryOp::Rem => Opcode::I32Rem,
+                BinaryOp::And => Opcode::I32And,
+                BinaryOp::Or => Opcode::I32Or,
+                BinaryOp::Xor => Opcode::I32Xor,
+                BinaryOp::Shl => Opcode::I32Shl,
+                BinaryOp::Sar => Opcode::I32Sar,
+                BinaryOp::Shr => Opcode::I32Shr,
+            },
+            BytecodeInstr::Int32Neg { .. } => Opcode::I32Neg,
             BytecodeInstr::Compare { cmp, .. } => match cmp {
                 CompareOp::Eq => Opcode::I64Eq,
                 CompareOp::Ne => Opcode::I64Ne,
@@ -543,6 +738,23 @@ impl BytecodeInstr {
                 CompareOp::Gt => Opcode::I64Gt,
                 CompareOp::Ge => Opcode::I64Ge,
             },
+            BytecodeInstr::FloatOp { op, .. } => match op {
+                BinaryOp::Add => Opcode::F64Add,
+                BinaryOp::Sub => Opcode::F64Sub,
+                BinaryOp::Mul => Opcode::F64Mul,
+                BinaryOp::Div => Opcode::F64Div,
+                _ => Opcode::F64Rem,
+            },
+            BytecodeInstr::FloatNeg { .. } => Opcode::F64Neg,
+            BytecodeInstr::FloatCompare { cmp, .. } => match cmp {
+                CompareOp::Eq => Opcode::F64Eq,
+                CompareOp::Ne => Opcode::F64Ne,
+                CompareOp::Lt => Opcode::F64Lt,
+                CompareOp::Le => Opcode::F64Le,
+                CompareOp::Gt => Opcode::F64Gt,
+                CompareOp::Ge => Opcode::F64Ge,
+            },
+            BytecodeInstr::IntToFloat { .. } => Opcode::I64ToF64,
             BytecodeInstr::StackAlloc { .. } => Opcode::StackAlloc,
             BytecodeInstr::HeapAlloc { .. } => Opcode::HeapAlloc,
             BytecodeInstr::Drop { .. } => Opcode::Drop,
@@ -562,9 +774,12 @@ impl BytecodeInstr {
             BytecodeInstr::Borrow { .. } => Opcode::Borrow,
             BytecodeInstr::Release { .. } => Opcode::Release,
             BytecodeInstr::CallStatic { .. } => Opcode::CallStatic,
+            BytecodeInstr::TailCall { .. } => Opcode::TailCall,
             BytecodeInstr::CallNative { .. } => Opcode::CallNative,
             BytecodeInstr::CallVirt { .. } => Opcode::CallVirt,
             BytecodeInstr::CallDyn { .. } => Opcode::CallDyn,
+            BytecodeInstr::InvokeVirtual { .. } => Opcode::InvokeVirtual,
+            BytecodeInstr::MakeDyn { .. } => Opcode::MakeDyn,
             BytecodeInstr::MakeClosure { .. } => Opcode::MakeClosure,
             BytecodeInstr::LoadUpvalue { .. } => Opcode::LoadUpvalue,
             BytecodeInstr::StoreUpvalue { .. } => Opcode::StoreUpvalue,
@@ -575,13 +790,19 @@ impl BytecodeInstr {
             BytecodeInstr::StringGetChar { .. } => Opcode::StringGetChar,
             BytecodeInstr::StringFromInt { .. } => Opcode::StringFromInt,
             BytecodeInstr::StringFromFloat { .. } => Opcode::StringFromFloat,
+            BytecodeInstr::StringBuilderNew { .. } => Opcode::StringBuilderNew,
+            BytecodeInstr::StringBuilderAppend { .. } => Opcode::StringBuilderAppend,
+            BytecodeInstr::StringBuilderFinish { .. } => Opcode::StringBuilderFinish,
             BytecodeInstr::TryBegin { .. } => Opcode::TryBegin,
             BytecodeInstr::TryEnd => Opcode::TryEnd,
             BytecodeInstr::Throw { .. } => Opcode::Throw,
             BytecodeInstr::BoundsCheck { .. } => Opcode::BoundsCheck,
             BytecodeInstr::TypeCheck { .. } => Opcode::TypeCheck,
             BytecodeInstr::Cast { .. } => Opcode::Cast,
+            BytecodeInstr::TypeTest { .. } => Opcode::TypeTest,
+            BytecodeInstr::Narrow { .. } => Opcode::Narrow,
             BytecodeInstr::TypeOf { .. } => Opcode::TypeOf,
+            BytecodeInstr::GetRecordField { .. } => Opcode::GetRecordField,
         }
     }
 
@@ -641,6 +862,7 @@ impl BytecodeInstr {
             BytecodeInstr::JmpIf { .. } => 4,
             BytecodeInstr::JmpIfNot { .. } => 4,
             BytecodeInstr::Switch { targets, .. } => 2 + targets.len() * 4,
+            BytecodeInstr::TableSwitch { targets, .. } => 16 + targets.len() * 4,
             BytecodeInstr::Mov { .. } => 4,
             BytecodeInstr::LoadConst { .. } => 4,
             BytecodeInstr::LoadLocal { .. } => 3,
@@ -648,7 +870,13 @@ impl BytecodeInstr {
             BytecodeInstr::LoadArg { .. } => 3,
             BytecodeInstr::BinaryOp { .. } => 6,
             BytecodeInstr::UnaryOp { .. } => 4,
+            BytecodeInstr::Int32Op { .. } => 6,
+            BytecodeInstr::Int32Neg { .. } => 4,
             BytecodeInstr::Compare { .. } => 6,
+            BytecodeInstr::FloatOp { .. } => 6,
+            BytecodeInstr::FloatNeg { .. } => 4,
+            BytecodeInstr::FloatCompare { .. } => 6,
+            BytecodeInstr::IntToFloat { .. } => 4,
             BytecodeInstr::StackAlloc { .. } => 4,
             BytecodeInstr::HeapAlloc { .. } => 4,
             BytecodeInstr::Drop { .. } => 2,
@@ -673,6 +901,7 @@ impl BytecodeInstr {
             BytecodeInstr::Borrow { .. } => 5, // dst(2) + src(2) + mutable(1)
             BytecodeInstr::Release { .. } => 2, // src(2)
             BytecodeInstr::CallStatic { args, .. } => 4 + args.len() * 2,
+            BytecodeInstr::TailCall { args, .. } => 4 + args.len() * 2,
             BytecodeInstr::CallNative {
                 args,
                 func_name,
@@ -688,6 +917,8 @@ impl BytecodeInstr {
             }
             BytecodeInstr::CallVirt { args, .. } => 4 + args.len() * 2,
             BytecodeInstr::CallDyn { args, .. } => 4 + args.len() * 2,
+            BytecodeInstr::InvokeVirtual { args, .. } => 6 + args.len() * 2,
+            BytecodeInstr::MakeDyn { .. } => 8,
             BytecodeInstr::MakeClosure { env, .. } => 4 + env.len() * 2,
             BytecodeInstr::LoadUpvalue { .. } => 3,
             BytecodeInstr::StoreUpvalue { .. } => 3,
@@ -698,13 +929,19 @@ impl BytecodeInstr {
             BytecodeInstr::StringGetChar { .. } => 4,
             BytecodeInstr::StringFromInt { .. } => 4,
             BytecodeInstr::StringFromFloat { .. } => 4,
+            BytecodeInstr::StringBuilderNew { .. } => 4,
+            BytecodeInstr::StringBuilderAppend { .. } => 6,
+            BytecodeInstr::StringBuilderFinish { .. } => 4,
             BytecodeInstr::TryBegin { .. } => 4,
             BytecodeInstr::TryEnd => 0,
             BytecodeInstr::Throw { .. } => 2,
             BytecodeInstr::BoundsCheck { .. } => 4,
             BytecodeInstr::TypeCheck { .. } => 4,
             BytecodeInstr::Cast { .. } => 4,
+            BytecodeInstr::Narrow { .. } => 4,
+            BytecodeInstr::TypeTest { .. } => 6,
             BytecodeInstr::TypeOf { .. } => 4,
+            BytecodeInstr::GetRecordField { .. } => 6,
         }
     }
 }
@@ -728,8 +965,10 @@ pub struct BytecodeFunction {
     pub labels: HashMap<Label, usize>,
     /// Exception handlers (try-catch blocks)
     pub exception_handlers: Vec<ExceptionHandler>,
-    /// Debug info: mapping from IP to Span
-    pub debug_map: HashMap<usize, crate::util::span::DebugSpan>,
+    /// Debug info: line table from instruction index to source span
+    pub line_table: crate::util::span::LineTable,
+    /// Fixed-size form of `instructions` that the interpreter dispatches on
+    pub decoded: crate::middle::core::decoded::DecodedCell,
 }
 
 /// Exception handler information
@@ -760,6 +999,61 @@ pub struct BytecodeModule {
     pub globals: Vec<GlobalInfo>,
     /// Entry point function index
     pub entry_point: Option<usize>,
+    /// On-demand function source; `functions` holds unloaded stubs for its entries
+    pub lazy_functions: Option<LazyFunctions>,
+    /// Interface vtables referenced by `MakeDyn`
+    pub vtables: Vec<crate::middle::core::ir::VTable>,
+    /// Struct layouts (type name and field names) used when printing values
+    pub struct_layouts: Vec<crate::middle::core::ir::StructLayout>,
+    /// Source files of the debug section, used to locate runtime errors
+    pub sources: Option<crate::util::span::SourceMap>,
+}
+
+/// Functions whose bodies are decoded from a bytecode file on first use
+///
+/// 由主解释器与并行任务解释器共享，内部的文件读取器通过互斥锁串行访问。
+#[derive(Clone)]
+pub struct LazyFunctions(
+    std::sync::Arc<std::sync::Mutex<crate::middle::passes::codegen::bytecode::LazyBytecodeFile>>,
+);
+
+impl LazyFunctions {
+    pub fn new(file: crate::middle::passes::codegen::bytecode::LazyBytecodeFile) -> Self {
+        Self(std::sync::Arc::new(std::sync::Mutex::new(file)))
+    }
+
+    /// Index of the function named `name` in the file's code section
+    pub fn index_of(
+        &self,
+        name: &str,
+    ) -> Option<usize> {
+        self.0.lock().ok()?.function_index(name)
+    }
+
+    /// Load and decode the function at `index`
+    pub fn load(
+        &self,
+        index: usize,
+    ) -> std::io::Result<BytecodeFunction> {
+        let mut file = self
+            .0
+            .lock()
+            .map_err(|_| std::io::Error::other("lazy function source poisoned"))?;
+        let func = file.load_function(index)?;
+        Ok(BytecodeFunction::decode(func, &file.const_pool))
+    }
+}
+
+impl std::fmt::Debug for LazyFunctions {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        match self.0.lock() {
+            Ok(file) => write!(f, "LazyFunctions({} functions)", file.functions.len()),
+            Err(_) => write!(f, "LazyFunctions(<poisoned>)"),
+        }
+    }
 }
 
 /// Global variable information
@@ -785,6 +1079,10 @@ impl BytecodeModule {
             type_table: Vec::new(),
             globals: Vec::new(),
             entry_point: None,
+            lazy_functions: None,
+            vtables: Vec::new(),
+            struct_layouts: Vec::new(),
+            sources: None,
         }
     }
 
@@ -809,20 +1107,44 @@ impl BytecodeModule {
     }
 }
 
-impl From<crate::middle::passes::codegen::bytecode::BytecodeFile> for BytecodeModule {
-    fn from(file: crate::middle::passes::codegen::bytecode::BytecodeFile) -> Self {
-        let name = "main".to_string(); // Default module name
+impl BytecodeFunction {
+    /// The instructions decoded into fixed-size form, decoding them on first use
+    pub fn decoded(&self) -> &crate::middle::core::decoded::DecodedCode {
+        self.decoded.get_or_decode(&self.instructions)
+    }
 
-        // Convert functions
+    /// Decode a serialized function; `const_pool` resolves name/type operands
+    pub fn decode(
+        func: crate::middle::passes::codegen::bytecode::FunctionCode,
+        const_pool: &[ConstValue],
+    ) -> Self {
+        let mut functions = BytecodeModule::decode_functions([func], const_pool);
+        functions.pop().expect("one function in, one function out")
+    }
+}
+
+impl BytecodeModule {
+    /// Decode serialized functions; `const_pool` resolves name/type operands
+    fn decode_functions(
+        code: impl IntoIterator<Item = crate::middle::passes::codegen::bytecode::FunctionCode>,
+        const_pool: &[ConstValue],
+    ) -> Vec<BytecodeFunction> {
         let mut functions = Vec::new();
-        for func in file.code_section.functions {
+        for func in code {
             // Decode instructions from BytecodeInstruction to BytecodeInstr
             let mut decoded_instructions = Vec::new();
             let mut labels = std::collections::HashMap::new();
-            let debug_map = func.debug_map;
+            let line_table = func.line_table;
+            // Encoded byte offset → decoded instruction index, for resolving jumps
+            let mut byte_to_decoded = std::collections::HashMap::new();
+            let mut pending_jumps: Vec<(usize, i64)> = Vec::new();
+            // Jump tables: absolute byte targets, default first
+            let mut pending_tables: Vec<(usize, Vec<i64>)> = Vec::new();
+            let mut byte_pos = 0usize;
             let mut ip = 0;
             while ip < func.instructions.len() {
                 let instr = &func.instructions[ip];
+                byte_to_decoded.insert(byte_pos, decoded_instructions.len());
                 // Decode the instruction based on opcode
                 match Opcode::try_from(instr.opcode) {
                     Ok(opcode) => {
@@ -838,47 +1160,75 @@ impl From<crate::middle::passes::codegen::bytecode::BytecodeFile> for BytecodeMo
                                     labels.insert(Label(label), decoded_instructions.len());
                                 }
                             }
-                            Opcode::Jmp => {
-                                if !instr.operands.is_empty() {
-                                    let target = u32::from_le_bytes([
-                                        instr.operands[0],
-                                        *instr.operands.get(1).unwrap_or(&0),
-                                        *instr.operands.get(2).unwrap_or(&0),
-                                        *instr.operands.get(3).unwrap_or(&0),
-                                    ]);
-                                    decoded_instructions.push(BytecodeInstr::Jmp {
-                                        target: Label(target),
-                                    });
-                                }
-                            }
-                            Opcode::JmpIf => {
-                                if instr.operands.len() >= 5 {
-                                    let cond = instr.operands[0] as u16;
-                                    let target = u32::from_le_bytes([
-                                        instr.operands[1],
-                                        instr.operands[2],
-                                        instr.operands[3],
-                                        instr.operands[4],
-                                    ]);
-                                    decoded_instructions.push(BytecodeInstr::JmpIf {
-                                        cond: Reg(cond),
-                                        target: Label(target),
-                                    });
+                            Opcode::Jmp | Opcode::JmpIf | Opcode::JmpIfNot => {
+                                // Operands are [cond,] followed by an i16 or i32 byte offset
+                                // relative to the jump's first byte.
+                                let cond_len = usize::from(opcode != Opcode::Jmp);
+                                let offset = match &instr.operands[..] {
+                                    [.., lo, hi] if instr.operands.len() == cond_len + 2 => {
+                                        Some(i16::from_le_bytes([*lo, *hi]) as i64)
+                                    }
+                                    [.., b0, b1, b2, b3]
+                                        if instr.operands.len() == cond_len + 4 =>
+                                    {
+                                        Some(i32::from_le_bytes([*b0, *b1, *b2, *b3]) as i64)
+                                    }
+                                    _ => None,
+                                };
+                                match offset {
+                                    Some(offset) => {
+                                        pending_jumps.push((
+                                            decoded_instructions.len(),
+                                            byte_pos as i64 + offset,
+                                        ));
+                                        let target = Label(0);
+                                        decoded_instructions.push(match opcode {
+                                            Opcode::Jmp => BytecodeInstr::Jmp { target },
+                                            Opcode::JmpIf => BytecodeInstr::JmpIf {
+                                                cond: Reg(instr.operands[0] as u16),
+                                                target,
+                                            },
+                                            _ => BytecodeInstr::JmpIfNot {
+                                                cond: Reg(instr.operands[0] as u16),
+                                                target,
+                                            },
+                                        });
+                                    }
+                                    None => decoded_instructions.push(BytecodeInstr::Nop),
                                 }
                             }
-                            Opcode::JmpIfNot => {
-                                if instr.operands.len() >= 5 {
-                                    let cond = instr.operands[0] as u16;
-                                    let target = u32::from_le_bytes([
-                                        instr.operands[1],
-                                        instr.operands[2],
-                                        instr.operands[3],
-                                        instr.operands[4],
-                                    ]);
-                                    decoded_instructions.push(BytecodeInstr::JmpIfNot {
-                                        cond: Reg(cond),
-                                        target: Label(target),
-                                    });
+                            Opcode::TableSwitch => {
+                                // Operands: value reg, i64 low, u16 count, then i32 byte
+                                // offsets (default first) relative to the first byte.
+                                let ops = &instr.operands;
+                                let count = ops
+                                    .get(9..11)
+                                    .map(|b| u16::from_le_bytes([b[0], b[1]]) as usize);
+                                match count.filter(|count| ops.len() == 15 + 4 * count) {
+                                    Some(count) => {
+                                        let low = i64::from_le_bytes(
+                                            ops[1..9].try_into().expect("length checked"),
+                                        );
+                                        let targets = (0..=count)
+                                            .map(|i| {
+                                                let at = 11 + 4 * i;
+                                                let offset = i32::from_le_bytes(
+                                                    ops[at..at + 4]
+                                                        .try_into()
+                                                        .expect("length checked"),
+                                                );
+                                                byte_pos as i64 + offset as i64
+                                            })
+                                            .collect();
+                                        pending_tables.push((decoded_instructions.len(), targets));
+                                        decoded_instructions.push(BytecodeInstr::TableSwitch {
+                                            value: Reg(ops[0] as u16),
+                                            low,
+                                            targets: vec![Label(0); count],
+                                            default: Label(0),
+                                        });
+                                    }
+                                    None => decoded_instructions.push(BytecodeInstr::Nop),
                                 }
                             }
                             Opcode::I64Add => {
@@ -1125,6 +1475,135 @@ impl From<crate::middle::passes::codegen::bytecode::BytecodeFile> for BytecodeMo
                                     });
                                 }
                             }
+                            Opcode::I32Add
+                            | Opcode::I32Sub
+                            | Opcode::I32Mul
+                            | Opcode::I32Div
+                            | Opcode::I32Rem
+                            | Opcode::I32And
+                            | Opcode::I32Or
+                            | Opcode::I32Xor
+                            | Opcode::I32Shl
+                            | Opcode::I32Sar
+                            | Opcode::I32Shr => {
+                                // 32-bit arithmetic: dst(1) + lhs(1) + rhs(1)
+                                if let [dst, lhs, rhs, ..] = instr.operands[..] {
+                                    let op = match opcode {
+                                        Opcode::I32Add => BinaryOp::Add,
+                                        Opcode::I32Sub => BinaryOp::Sub,
+                                        Opcode::I32Mul => BinaryOp::Mul,
+                                        Opcode::I32Div => BinaryOp::Div,
+                                        Opcode::I32Rem => BinaryOp::Rem,
+                                        Opcode::I32And => BinaryOp::And,
+                                        Opcode::I32Or => BinaryOp::Or,
+                                        Opcode::I32Xor => BinaryOp::Xor,
+                                        Opcode::I32Shl => BinaryOp::Shl,
+                                        Opcode::I32Sar => BinaryOp::Sar,
+                                        _ => BinaryOp::Shr,
+                                    };
+                                    decoded_instructions.push(BytecodeInstr::Int32Op {
+                                        dst: Reg(dst as u16),
+                                        lhs: Reg(lhs as u16),
+                                        rhs: Reg(rhs as u16),
+                                        op,
+                                    });
+                                }
+                            }
+                            Opcode::I32Neg => {
+                                // 32-bit negation: dst(1) + src(1)
+                                if let [dst, src, ..] = instr.operands[..] {
+                                    decoded_instructions.push(BytecodeInstr::Int32Neg {
+                                        dst: Reg(dst as u16),
+                                        src: Reg(src as u16),
+                                    });
+                                }
+                            }
+                            Opcode::F64Add
+                            | Opcode::F64Sub
+                            | Opcode::F64Mul
+                            | Opcode::F64Div
+                            | Opcode::F64Rem => {
+                                // Float arithmetic: dst(1) + lhs(1) + rhs(1)
+                                if let [dst, lhs, rhs, ..] = instr.operands[..] {
+                                    let op = match opcode {
+                                        Opcode::F64Add => BinaryOp::Add,
+                                        Opcode::F64Sub => BinaryOp::Sub,
+                                        Opcode::F64Mul => BinaryOp::Mul,
+                                        Opcode::F64Div => BinaryOp::Div,
+                                        _ => BinaryOp::Rem,
+                                    };
+                                    decoded_instructions.push(BytecodeInstr::FloatOp {
+                                        dst: Reg(dst as u16),
+                                        lhs: Reg(lhs as u16),
+                                        rhs: Reg(rhs as u16),
+                                        op,
+                                    });
+                                }
+                            }
+                            Opcode::F64Eq
+                            | Opcode::F64Ne
+                            | Opcode::F64Lt
+                            | Opcode::F64Le
+                            | Opcode::F64Gt
+                            | Opcode::F64Ge => {
+                                // Float comparison: dst(1) + lhs(1) + rhs(1)
+                                if let [dst, lhs, rhs, ..] = instr.operands[..] {
+                                    let cmp = match opcode {
+                                        Opcode::F64Eq => CompareOp::Eq,
+                                        Opcode::F64Ne => CompareOp::Ne,
+                                        Opcode::F64Lt => CompareOp::Lt,
+                                        Opcode::F64Le => CompareOp::Le,
+                                        Opcode::F64Gt => CompareOp::Gt,
+                                        _ => CompareOp::Ge,
+                                    };
+                                    decoded_instructions.push(BytecodeInstr::FloatCompare {
+                                        dst: Reg(dst as u16),
+                                        lhs: Reg(lhs as u16),
+                                        rhs: Reg(rhs as u16),
+                                        cmp,
+                                    });
+                                }
+                            }
+                            Opcode::F64Neg | Opcode::I64ToF64 => {
+                                // FloatNeg / IntToFloat: dst(1) + src(1)
+                                if let [dst, src, ..] = instr.operands[..] {
+                                    let (dst, src) = (Reg(dst as u16), Reg(src as u16));
+                                    decoded_instructions.push(if opcode == Opcode::F64Neg {
+                                        BytecodeInstr::FloatNeg { dst, src }
+                                    } else {
+                                        BytecodeInstr::IntToFloat { dst, src }
+                                    });
+                                }
+                            }
+                            Opcode::StringConcat | Opcode::StringBuilderAppend => {
+                                // dst(1) + lhs(1) + rhs(1)
+                                if let [dst, lhs, rhs, ..] = instr.operands[..] {
+                                    let (dst, lhs, rhs) =
+                                        (Reg(dst as u16), Reg(lhs as u16), Reg(rhs as u16));
+                                    decoded_instructions.push(if opcode == Opcode::StringConcat {
+                                        BytecodeInstr::StringConcat {
+                                            dst,
+                                            str1: lhs,
+                                            str2: rhs,
+                                        }
+                                    } else {
+                                        BytecodeInstr::StringBuilderAppend { dst, lhs, rhs }
+                                    });
+                                }
+                            }
+                            Opcode::StringBuilderNew | Opcode::StringBuilderFinish => {
+                                // dst(1) + src(1)
+                                if let [dst, src, ..] = instr.operands[..] {
+                                    let (dst, src) = (Reg(dst as u16), Reg(src as u16));
+                                    decoded_instructions.push(
+                                        if opcode == Opcode::StringBuilderNew {
+                                            BytecodeInstr::StringBuilderNew { dst, src }
+                                        } else {
+                                            BytecodeInstr::StringBuilderFinish { dst, src }
+                                        },
+                                    );
+                                }
+                            }
                             Opcode::CallStatic => {
                                 // CallStatic: dst(1) + func_id(4) + base_arg_reg(1) + arg_count(1) + args(2*count)
                                 if instr.operands.len() >= 7 {
@@ -1169,6 +1648,138 @@ impl From<crate::middle::passes::codegen::bytecode::BytecodeFile> for BytecodeMo
                                     decoded_instructions.push(BytecodeInstr::Nop);
                                 }
                             }
+                            Opcode::TailCall => {
+                                // TailCall: func_id(4) + base_arg_reg(1) + arg_count(1) + args(2*count)
+                                let ops = &instr.operands;
+                                let arg_count = ops.get(5).copied().unwrap_or(0) as usize;
+                                if ops.len() == 6 + arg_count * 2 {
+                                    decoded_instructions.push(BytecodeInstr::TailCall {
+                                        func: FunctionRef::Index(u32::from_le_bytes([
+                                            ops[0], ops[1], ops[2], ops[3],
+                                        ])),
+                                        args: ops[6..]
+                                            .chunks_exact(2)
+                                            .map(|pair| Reg(u16::from_le_bytes([pair[0], pair[1]])))
+                                            .collect(),
+                                    });
+                                } else {
+                                    decoded_instructions.push(BytecodeInstr::Nop);
+                                }
+                            }
+                            Opcode::CallDyn => {
+                                // CallDyn: dst(1) + func_reg(1) + name_idx(2) + args(1*count) + arg_count(1)
+                                let ops = &instr.operands;
+                                let arg_count = ops.last().copied().unwrap_or(0) as usize;
+                                if ops.len() == 5 + arg_count {
+                                    decoded_instructions.push(BytecodeInstr::CallDyn {
+                                        dst: Some(Reg(ops[0] as u16)),
+                                        obj: Reg(ops[1] as u16),
+                                        name_idx: u16::from_le_bytes([ops[2], ops[3]]),
+                                        args: ops[4..4 + arg_count]
+                                            .iter()
+                                            .map(|&r| Reg(r as u16))
+                                            .collect(),
+                                    });
+                                } else {
+                                    decoded_instructions.push(BytecodeInstr::Nop);
+                                }
+                            }
+                            Opcode::InvokeVirtual => {
+                                // InvokeVirtual: dst(1) + obj(1) + slot(2) + name_idx(2) + args(1*count) + arg_count(1)
+                                let ops = &instr.operands;
+                                let arg_count = ops.last().copied().unwrap_or(0) as usize;
+                                if ops.len() == 7 + arg_count {
+                                    decoded_instructions.push(BytecodeInstr::InvokeVirtual {
+                                        dst: Some(Reg(ops[0] as u16)),
+                                        obj: Reg(ops[1] as u16),
+                                        slot: u16::from_le_bytes([ops[2], ops[3]]),
+                                        name_idx: u16::from_le_bytes([ops[4], ops[5]]),
+                                        args: ops[6..6 + arg_count]
+                                            .iter()
+                                            .map(|&r| Reg(r as u16))
+                                            .collect(),
+                                    });
+                                } else {
+                                    decoded_instructions.push(BytecodeInstr::Nop);
+                                }
+                            }
+                            Opcode::Cast | Opcode::Narrow => {
+                                // Cast / Narrow: dst(1) + src(1) + target_type_id(2)
+                                let ops = &instr.operands;
+                                if ops.len() >= 4 {
+                                    let dst = Reg(ops[0] as u16);
+                                    let src = Reg(ops[1] as u16);
+                                    let target_type_id = u16::from_le_bytes([ops[2], ops[3]]);
+                                    decoded_instructions.push(if opcode == Opcode::Cast {
+                                        BytecodeInstr::Cast {
+                                            dst,
+                                            src,
+                                            target_type_id,
+                                        }
+                                    } else {
+                                        BytecodeInstr::Narrow {
+                                            dst,
+                                            src,
+                                            target_type_id,
+                                        }
+                                    });
+                                } else {
+                                    decoded_instructions.push(BytecodeInstr::Nop);
+                                }
+                            }
+                            Opcode::TypeTest => {
+                                // TypeTest: dst(1) + src(1) + type_name_idx(4)
+                                let ops = &instr.operands;
+                                if ops.len() >= 6 {
+                                    let name_idx =
+                                        u32::from_le_bytes([ops[2], ops[3], ops[4], ops[5]]);
+                                    let target = match const_pool.get(name_idx as usize) {
+                                        Some(ConstValue::String(s)) => s.clone(),
+                                        _ => format!("type_{}", name_idx),
+                                    };
+                                    decoded_instructions.push(BytecodeInstr::TypeTest {
+                                        dst: Reg(ops[0] as u16),
+                                        src: Reg(ops[1] as u16),
+                                        target,
+                                    });
+                                } else {
+                                    decoded_instructions.push(BytecodeInstr::Nop);
+                                }
+                            }
+                            Opcode::GetRecordField => {
+                                // GetRecordField: dst(1) + src(1) + field_name_idx(4)
+                                let ops = &instr.operands;
+                                if ops.len() >= 6 {
+                                    let name_idx =
+                                        u32::from_le_bytes([ops[2], ops[3], ops[4], ops[5]]);
+                                    let field = match const_pool.get(name_idx as usize) {
+                                        Some(ConstValue::String(s)) => s.clone(),
+                                        _ => format!("field_{}", name_idx),
+                                    };
+                                    decoded_instructions.push(BytecodeInstr::GetRecordField {
+                                        dst: Reg(ops[0] as u16),
+                                        src: Reg(ops[1] as u16),
+                                        field,
+                                    });
+                                } else {
+                                    decoded_instructions.push(BytecodeInstr::Nop);
+                                }
+                            }
+                            Opcode::MakeDyn => {
+                                // MakeDyn: dst(1) + src(1) + vtable(4)
+                                let ops = &instr.operands;
+                                if ops.len() >= 6 {
+                                    decoded_instructions.push(BytecodeInstr::MakeDyn {
+                                        dst: Reg(ops[0] as u16),
+                                        src: Reg(ops[1] as u16),
+                                        vtable: u32::from_le_bytes([
+                                            ops[2], ops[3], ops[4], ops[5],
+                                        ]),
+                                    });
+                                } else {
+                                    decoded_instructions.push(BytecodeInstr::Nop);
+                                }
+                            }
                             Opcode::CallNative => {
                                 // CallNative decode: supports old and FFI format
                                 // Old:  dst(1) + func_name_idx(4) + base(1) + count(1) + args(2*count)
@@ -1184,7 +1795,7 @@ impl From<crate::middle::passes::codegen::bytecode::BytecodeFile> for BytecodeMo
 
                                     // Resolve function name from constant pool
                                     let func_name = if let Some(ConstValue::String(s)) =
-                                        file.const_pool.get(func_name_idx as usize)
+                                        const_pool.get(func_name_idx as usize)
                                     {
                                         s.clone()
                                     } else {
@@ -1223,18 +1834,12 @@ impl From<crate::middle::passes::codegen::bytecode::BytecodeFile> for BytecodeMo
                                             instr.operands[15],
                                             instr.operands[16],
                                         ]);
-                                        let mechanism = resolve_const_string(
-                                            &file.const_pool,
-                                            mech_idx as usize,
-                                        );
-                                        let lib = resolve_const_string(
-                                            &file.const_pool,
-                                            lib_idx as usize,
-                                        );
-                                        let symbol = resolve_const_string(
-                                            &file.const_pool,
-                                            sym_idx as usize,
-                                        );
+                                        let mechanism =
+                                            resolve_const_string(const_pool, mech_idx as usize);
+                                        let lib =
+                                            resolve_const_string(const_pool, lib_idx as usize);
+                                        let symbol =
+                                            resolve_const_string(const_pool, sym_idx as usize);
                                         let _base_arg_reg = instr.operands[17];
                                         let arg_count = instr.operands[18] as usize;
                                         (mechanism, lib, symbol, _base_arg_reg, arg_count, 19)
@@ -1538,10 +2143,13 @@ impl From<crate::middle::passes::codegen::bytecode::BytecodeFile> for BytecodeMo
                                 }
                             }
                             Opcode::LoadLocal => {
-                                // LoadLocal: dst(1) + local_idx(1)
+                                // LoadLocal: dst(1) + local_idx(2), or local_idx(1) [legacy]
                                 if instr.operands.len() >= 2 {
                                     let dst = instr.operands[0] as u16;
-                                    let local_idx = instr.operands[1];
+                                    let local_idx = u16::from_le_bytes([
+                                        instr.operands[1],
+                                        *instr.operands.get(2).unwrap_or(&0),
+                                    ]);
                                     decoded_instructions.push(BytecodeInstr::LoadLocal {
                                         dst: Reg(dst),
                                         local_idx,
@@ -1551,9 +2159,17 @@ impl From<crate::middle::passes::codegen::bytecode::BytecodeFile> for BytecodeMo
                                 }
                             }
                             Opcode::StoreLocal => {
-                                // StoreLocal: local_idx(1) + src(1)
-                                if instr.operands.len() >= 2 {
-                                    let local_idx = instr.operands[0];
+                                // StoreLocal: local_idx(2) + src(1), or local_idx(1) + src(1) [legacy]
+                                if instr.operands.len() >= 3 {
+                                    let local_idx =
+                                        u16::from_le_bytes([instr.operands[0], instr.operands[1]]);
+                                    let src = instr.operands[2] as u16;
+                                    decoded_instructions.push(BytecodeInstr::StoreLocal {
+                                        local_idx,
+                                        src: Reg(src),
+                                    });
+                                } else if instr.operands.len() == 2 {
+                                    let local_idx = instr.operands[0] as u16;
                                     let src = instr.operands[1] as u16;
                                     decoded_instructions.push(BytecodeInstr::StoreLocal {
                                         local_idx,
@@ -1564,10 +2180,13 @@ impl From<crate::middle::passes::codegen::bytecode::BytecodeFile> for BytecodeMo
                                 }
                             }
                             Opcode::LoadArg => {
-                                // LoadArg: dst(1) + arg_idx(1)
+                                // LoadArg: dst(1) + arg_idx(2), or arg_idx(1) [legacy]
                                 if instr.operands.len() >= 2 {
                                     let dst = instr.operands[0] as u16;
-                                    let arg_idx = instr.operands[1];
+                                    let arg_idx = u16::from_le_bytes([
+                                        instr.operands[1],
+                                        *instr.operands.get(2).unwrap_or(&0),
+                                    ]);
                                     decoded_instructions.push(BytecodeInstr::LoadArg {
                                         dst: Reg(dst),
                                         arg_idx,
@@ -1591,6 +2210,41 @@ impl From<crate::middle::passes::codegen::bytecode::BytecodeFile> for BytecodeMo
                                     decoded_instructions.push(BytecodeInstr::Return);
                                 }
                             }
+                            Opcode::LoadUpvalue => {
+                                // LoadUpvalue: dst(1) + upvalue_idx(1)
+                                if instr.operands.len() >= 2 {
+                                    decoded_instructions.push(BytecodeInstr::LoadUpvalue {
+                                        dst: Reg(instr.operands[0] as u16),
+                                        upvalue_idx: instr.operands[1],
+                                    });
+                                } else {
+                                    decoded_instructions.push(BytecodeInstr::Nop);
+                                }
+                            }
+                            Opcode::StoreUpvalue => {
+                                // StoreUpvalue: src(1) + upvalue_idx(1)
+                                if instr.operands.len() >= 2 {
+                                    decoded_instructions.push(BytecodeInstr::StoreUpvalue {
+                                        src: Reg(instr.operands[0] as u16),
+                                        upvalue_idx: instr.operands[1],
+                                    });
+                                } else {
+                                    decoded_instructions.push(BytecodeInstr::Nop);
+                                }
+                            }
+                            Opcode::StackAlloc => {
+                                // StackAlloc: dst(1) + src(1)
+                                if instr.operands.len() >= 2 {
+                                    let dst = instr.operands[0] as u16;
+                                    let src = instr.operands[1] as u16;
+                                    decoded_instructions.push(BytecodeInstr::StackAlloc {
+                                        dst: Reg(dst),
+                                        src: Reg(src),
+                                    });
+                                } else {
+                                    decoded_instructions.push(BytecodeInstr::Nop);
+                                }
+                            }
                             Opcode::NewListWithCap => {
                                 // NewListWithCap: dst(1) + capacity(2)
                                 if instr.operands.len() >= 3 {
@@ -1634,7 +2288,7 @@ impl From<crate::middle::passes::codegen::bytecode::BytecodeFile> for BytecodeMo
 
                                     // Resolve type name from constant pool
                                     let type_name = if let Some(ConstValue::String(s)) =
-                                        file.const_pool.get(type_name_idx as usize)
+                                        const_pool.get(type_name_idx as usize)
                                     {
                                         s.clone()
                                     } else {
@@ -1781,31 +2435,31 @@ impl From<crate::middle::passes::codegen::bytecode::BytecodeFile> for BytecodeMo
                                 }
                             }
                             Opcode::GetField => {
-                                // GetField: dst(1) + src(1) + field_idx(2)
+                                // GetField: dst(1) + src(1) + offset(2)
                                 if instr.operands.len() >= 4 {
                                     let dst = instr.operands[0] as u16;
                                     let src = instr.operands[1] as u16;
-                                    let field_idx =
+                                    let offset =
                                         u16::from_le_bytes([instr.operands[2], instr.operands[3]]);
                                     decoded_instructions.push(BytecodeInstr::GetField {
                                         dst: Reg(dst),
                                         src: Reg(src),
-                                        field_idx,
+                                        offset,
                                     });
                                 } else {
                                     decoded_instructions.push(BytecodeInstr::Nop);
                                 }
                             }
                             Opcode::SetField => {
-                                // SetField: src(1) + field_idx(2) + value(1)
+                                // SetField: src(1) + offset(2) + value(1)
                                 if instr.operands.len() >= 4 {
                                     let src = instr.operands[0] as u16;
-                                    let field_idx =
+                                    let offset =
                                         u16::from_le_bytes([instr.operands[1], instr.operands[2]]);
                                     let value = instr.operands[3] as u16;
                                     decoded_instructions.push(BytecodeInstr::SetField {
                                         src: Reg(src),
-                                        field_idx,
+                                        offset,
                                         value: Reg(value),
                                     });
                                 } else {
@@ -1824,22 +2478,73 @@ impl From<crate::middle::passes::codegen::bytecode::BytecodeFile> for BytecodeMo
                         decoded_instructions.push(BytecodeInstr::Nop);
                     }
                 }
+                byte_pos += instr.encoded_size();
                 ip += 1;
             }
+            byte_to_decoded.insert(byte_pos, decoded_instructions.len());
+
+            // Rewrite byte offsets as relative offsets in decoded instructions
+            for (idx, target) in pending_jumps {
+                let resolved = usize::try_from(target)
+                    .ok()
+                    .and_then(|target| byte_to_decoded.get(&target))
+                    .map(|&target_idx| Label((target_idx as i64 - idx as i64) as i32 as u32));
+                match (resolved, &mut decoded_instructions[idx]) {
+                    (Some(label), BytecodeInstr::Jmp { target })
+                    | (Some(label), BytecodeInstr::JmpIf { target, .. })
+                    | (Some(label), BytecodeInstr::JmpIfNot { target, .. }) => *target = label,
+                    _ => decoded_instructions[idx] = BytecodeInstr::Nop,
+                }
+            }
+            for (idx, byte_targets) in pending_tables {
+                let resolved: Option<Vec<Label>> = byte_targets
+                    .into_iter()
+                    .map(|target| {
+                        usize::try_from(target)
+                            .ok()
+                            .and_then(|target| byte_to_decoded.get(&target))
+                            .map(|&target_idx| {
+                                Label((target_idx as i64 - idx as i64) as i32 as u32)
+                            })
+                    })
+                    .collect();
+                match resolved {
+                    Some(labels) => {
+                        if let BytecodeInstr::TableSwitch {
+                            targets, default, ..
+                        } = &mut decoded_instructions[idx]
+                        {
+                            *default = labels[0];
+                            *targets = labels[1..].to_vec();
+                        }
+                    }
+                    None => decoded_instructions[idx] = BytecodeInstr::Nop,
+                }
+            }
 
-            let byte_func = BytecodeFunction {
+            functions.push(BytecodeFunction {
                 name: func.name,
                 params: func.params.into_iter().map(|t| t.into()).collect(),
                 return_type: func.return_type.into(),
                 local_count: func.local_count,
-                upvalue_count: 0, // Not stored in BytecodeFile
+                upvalue_count: func.upvalue_count,
                 instructions: decoded_instructions,
                 labels,                         // Populated from Opcode::Label
                 exception_handlers: Vec::new(), // Not implemented yet
-                debug_map,
-            };
-            functions.push(byte_func);
+                line_table,
+                decoded: Default::default(),
+            });
         }
+        functions
+    }
+}
+
+impl From<crate::middle::passes::codegen::bytecode::BytecodeFile> for BytecodeModule {
+    fn from(file: crate::middle::passes::codegen::bytecode::BytecodeFile) -> Self {
+        let name = "main".to_string(); // Default module name
+
+        // Convert functions
+        let functions = Self::decode_functions(file.code_section.functions, &file.const_pool);
 
         // Determine entry point
         let entry_point = if file.header.entry_point > 0 {
@@ -1858,10 +2563,67 @@ impl From<crate::middle::passes::codegen::bytecode::BytecodeFile> for BytecodeMo
             type_table: file.type_table.into_iter().map(|t| t.into()).collect(),
             globals: Vec::new(), // Not stored in BytecodeFile yet
             entry_point,
+            lazy_functions: None,
+            vtables: file.vtables,
+            struct_layouts: file.struct_layouts,
+            sources: file.debug_section.map(|debug| debug.sources),
         }
     }
 }
 
+impl TryFrom<crate::middle::passes::codegen::bytecode::LazyBytecodeFile> for BytecodeModule {
+    type Error = std::io::Error;
+
+    /// 只解码入口函数，其余函数以空函数体占位，执行时按需加载
+    ///
+    /// 入口函数读取或解码失败时返回错误。
+    fn try_from(
+        file: crate::middle::passes::codegen::bytecode::LazyBytecodeFile
+    ) -> std::io::Result<Self> {
+        let mut functions: Vec<BytecodeFunction> = file
+            .functions
+            .iter()
+            .map(|entry| BytecodeFunction {
+                name: entry.name.clone(),
+                params: entry.params.iter().cloned().map(|t| t.into()).collect(),
+                return_type: entry.return_type.clone().into(),
+                local_count: entry.local_count,
+                upvalue_count: entry.upvalue_count,
+                instructions: Vec::new(),
+                labels: HashMap::new(),
+                exception_handlers: Vec::new(),
+                line_table: crate::util::span::LineTable::new(),
+                decoded: Default::default(),
+            })
+            .collect();
+
+        let entry_point = (!functions.is_empty()).then_some(file.header.entry_point as usize);
+        let constants = file.const_pool.clone();
+        let vtables = file.vtables.clone();
+        let struct_layouts = file.struct_layouts.clone();
+        let sources = file.sources().cloned();
+        let type_table = file.type_table.iter().cloned().map(|t| t.into()).collect();
+        let lazy = LazyFunctions::new(file);
+
+        if let Some(idx) = entry_point.filter(|idx| *idx < functions.len()) {
+            functions[idx] = lazy.load(idx)?;
+        }
+
+        Ok(BytecodeModule {
+            name: "main".to_string(),
+            constants,
+            functions,
+            type_table,
+            globals: Vec::new(),
+            entry_point,
+            lazy_functions: Some(lazy),
+            vtables,
+            struct_layouts,
+            sources,
+        })
+    }
+}
+
 /// Convert MonoType to IrType (ast::Type)
 impl From<crate::frontend::core::typecheck::MonoType> for IrType {
     fn from(ty: crate::frontend::core::typecheck::MonoType) -> Self {
@@ -1897,6 +2659,18 @@ impl From<crate::frontend::core::typecheck::MonoType> for IrType {
             | MonoType::Weak(_) => IrType::Void,
             // Ref is ZST, no runtime representation
             MonoType::Ref { .. } => IrType::Void,
+            // Async is a compile-time marker; the runtime value is the joined result
+            MonoType::Async(inner) => (*inner).into(),
+            // Named types and generic instances keep their structure in the VM type table
+            MonoType::TypeRef(name) if name != "_" => IrType::Name {
+                name,
+                span: crate::util::span::Span::dummy(),
+            },
+            MonoType::Generic { name, args } => IrType::Generic {
+                name,
+                name_span: crate::util::span::Span::dummy(),
+                args: args.into_iter().map(|t| t.into()).collect(),
+            },
             // TypeVar, TypeRef, Union, Intersection, AssocType — unresolved or no IR form
             _ => IrType::Void,
         }
@@ -0,0 +1,731 @@
+//! Decoded instructions
+//!
+//! The interpreter does not dispatch on [`BytecodeInstr`]: when a function is
+//! loaded its instructions are decoded once into [`Instr`]s — an [`Op`], three
+//! 16-bit operand fields and a 32-bit immediate, 12 bytes each — so running an
+//! instruction never walks heap-allocated operands. Operands that do not fit
+//! (argument lists, names, case tables, spawn metadata) live in side tables of
+//! [`DecodedCode`] that the immediate indexes, and jump labels are resolved to
+//! absolute instruction indices.
+//!
+//! Instruction `i` of the decoded code is instruction `i` of the function, so
+//! instruction pointers, line tables and breakpoints mean the same for both.
+//!
+//! # Operand layout
+//!
+//! Register and 16-bit fields fill `a`, `b` and `c` in the order the
+//! [`BytecodeInstr`] variant declares them, and an absent `dst` is [`NO_REG`].
+//! `imm` holds what is left:
+//!
+//! | `imm`                           | Instructions                                      |
+//! |---------------------------------|---------------------------------------------------|
+//! | jump target                     | `Jmp`, `JmpIf`, `JmpIfNot`, `TryBegin`            |
+//! | sub-operation                   | `BinaryOp`, `Int32Op`, `FloatOp`, `UnaryOp`, `Compare`, `FloatCompare` |
+//! | 32-bit operand                  | `MakeDyn` (vtable)                                |
+//! | [`DecodedCode::switches`] index | `Switch`                                          |
+//! | [`DecodedCode::tables`] index   | `TableSwitch`                                     |
+//! | [`DecodedCode::calls`] index    | `CallStatic`, `TailCall`, `MakeClosure` (env)     |
+//! | [`DecodedCode::natives`] index  | `CallNative`                                      |
+//! | [`DecodedCode::methods`] index  | `CallVirt`, `CallDyn`, `InvokeVirtual`            |
+//! | [`DecodedCode::structs`] index  | `CreateStruct`                                    |
+//! | [`DecodedCode::dicts`] index    | `NewDict`                                         |
+//! | [`DecodedCode::spawns`] index   | `Spawn`, `SpawnFromList`                          |
+//! | [`DecodedCode::names`] index    | `TypeTest`, `GetRecordField`                      |
+//!
+//! The method name of `CallVirt`, `CallDyn` and `InvokeVirtual` moves to the
+//! side table, so their fields are `dst`, `obj` (and `slot`).
+
+use std::sync::OnceLock;
+
+use crate::middle::core::bytecode::{
+    BinaryOp, BytecodeInstr, CompareOp, FunctionRef, Label, Reg, UnaryOp,
+};
+
+/// `a` of an instruction whose optional `dst` is absent
+pub const NO_REG: u16 = u16::MAX;
+
+/// Jump target of a label that points before the function
+pub const NO_TARGET: u32 = u32::MAX;
+
+/// Operation of a decoded instruction (one per [`BytecodeInstr`] variant)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Op {
+    Nop,
+    Return,
+    ReturnValue,
+    Yield,
+    Spawn,
+    SpawnFromList,
+    Jmp,
+    JmpIf,
+    JmpIfNot,
+    Switch,
+    TableSwitch,
+    Mov,
+    LoadConst,
+    LoadLocal,
+    StoreLocal,
+    LoadArg,
+    BinaryOp,
+    UnaryOp,
+    Int32Op,
+    Int32Neg,
+    Compare,
+    FloatOp,
+    FloatNeg,
+    FloatCompare,
+    IntToFloat,
+    StackAlloc,
+    HeapAlloc,
+    Drop,
+    GetField,
+    SetField,
+    LoadElement,
+    StoreElement,
+    NewListWithCap,
+    CreateStruct,
+    NewDict,
+    ArcNew,
+    RcNew,
+    ArcClone,
+    ArcDrop,
+    WeakNew,
+    WeakUpgrade,
+    Borrow,
+    Release,
+    CallStatic,
+    TailCall,
+    CallNative,
+    CallVirt,
+    CallDyn,
+    InvokeVirtual,
+    MakeDyn,
+    MakeClosure,
+    LoadUpvalue,
+    StoreUpvalue,
+    CloseUpvalue,
+    StringLength,
+    StringConcat,
+    StringEqual,
+    StringGetChar,
+    StringFromInt,
+    StringFromFloat,
+    StringBuilderNew,
+    StringBuilderAppend,
+    StringBuilderFinish,
+    TryBegin,
+    TryEnd,
+    Throw,
+    BoundsCheck,
+    TypeCheck,
+    Cast,
+    TypeTest,
+    Narrow,
+    TypeOf,
+    GetRecordField,
+}
+
+/// Fixed-size decoded instruction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Instr {
+    pub op: Op,
+    pub a: u16,
+    pub b: u16,
+    pub c: u16,
+    pub imm: u32,
+}
+
+// Keeps the dispatch loop reading small, copyable values
+const _: () = assert!(std::mem::size_of::<Instr>() == 12);
+
+/// `BinaryOp`s in declaration order, indexed by `imm`
+const BINARY_OPS: [BinaryOp; 11] = [
+    BinaryOp::Add,
+    BinaryOp::Sub,
+    BinaryOp::Mul,
+    BinaryOp::Div,
+    BinaryOp::Rem,
+    BinaryOp::And,
+    BinaryOp::Or,
+    BinaryOp::Xor,
+    BinaryOp::Shl,
+    BinaryOp::Sar,
+    BinaryOp::Shr,
+];
+
+/// `CompareOp`s in declaration order, indexed by `imm`
+const COMPARE_OPS: [CompareOp; 6] = [
+    CompareOp::Eq,
+    CompareOp::Ne,
+    CompareOp::Lt,
+    CompareOp::Le,
+    CompareOp::Gt,
+    CompareOp::Ge,
+];
+
+/// `UnaryOp`s in declaration order, indexed by `imm`
+const UNARY_OPS: [UnaryOp; 2] = [UnaryOp::Neg, UnaryOp::Not];
+
+impl Instr {
+    fn new(
+        op: Op,
+        a: u16,
+        b: u16,
+        c: u16,
+        imm: u32,
+    ) -> Self {
+        Instr { op, a, b, c, imm }
+    }
+
+    /// Register in `a`
+    pub fn a(self) -> Reg {
+        Reg(self.a)
+    }
+
+    /// Register in `b`
+    pub fn b(self) -> Reg {
+        Reg(self.b)
+    }
+
+    /// Register in `c`
+    pub fn c(self) -> Reg {
+        Reg(self.c)
+    }
+
+    /// Optional `dst` in `a`
+    pub fn dst(self) -> Option<Reg> {
+        (self.a != NO_REG).then_some(Reg(self.a))
+    }
+
+    /// Jump target in `imm`
+    pub fn target(self) -> usize {
+        self.imm as usize
+    }
+
+    /// Side-table index in `imm`
+    pub fn index(self) -> usize {
+        self.imm as usize
+    }
+
+    pub fn binary_op(self) -> BinaryOp {
+        BINARY_OPS[self.imm as usize]
+    }
+
+    pub fn compare_op(self) -> CompareOp {
+        COMPARE_OPS[self.imm as usize]
+    }
+
+    pub fn unary_op(self) -> UnaryOp {
+        UNARY_OPS[self.imm as usize]
+    }
+}
+
+/// Range of a flat side table
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Span {
+    pub start: u32,
+    pub len: u32,
+}
+
+impl Span {
+    fn range(self) -> std::ops::Range<usize> {
+        self.start as usize..(self.start + self.len) as usize
+    }
+}
+
+/// Callee and registers of `CallStatic`, `TailCall` and `MakeClosure`
+#[derive(Debug, Clone)]
+pub struct Call {
+    pub func: FunctionRef,
+    /// Arguments (captured environment for `MakeClosure`)
+    pub args: Span,
+}
+
+/// FFI metadata and arguments of `CallNative`
+#[derive(Debug, Clone)]
+pub struct NativeCall {
+    pub func_name: String,
+    pub mechanism: String,
+    pub lib: String,
+    pub symbol: String,
+    pub args: Span,
+}
+
+/// Method name and arguments of `CallVirt`, `CallDyn` and `InvokeVirtual`
+#[derive(Debug, Clone)]
+pub struct MethodCall {
+    /// Constant-pool index of the method name
+    pub name_idx: u16,
+    pub args: Span,
+}
+
+/// Operands of `CreateStruct`
+#[derive(Debug, Clone)]
+pub struct StructInit {
+    pub type_name: String,
+    pub fields: Span,
+}
+
+/// Operands of `NewDict`
+#[derive(Debug, Clone)]
+pub struct DictInit {
+    pub keys: Span,
+    pub values: Span,
+}
+
+/// Operands of `Spawn` and `SpawnFromList`
+#[derive(Debug, Clone)]
+pub struct SpawnSite {
+    /// Closure registers (empty for `SpawnFromList`)
+    pub closures: Span,
+    pub task_deps: Vec<Vec<u32>>,
+    pub task_resources: Vec<Vec<String>>,
+}
+
+/// Cases of `Switch`, tried in order
+#[derive(Debug, Clone)]
+pub struct SwitchTable {
+    /// `(case value, target)` pairs
+    pub cases: Span,
+    /// Target when no case matches; without one execution falls through
+    pub default: Option<u32>,
+}
+
+/// Jump table of `TableSwitch`
+#[derive(Debug, Clone)]
+pub struct JumpTable {
+    pub low: i64,
+    pub targets: Span,
+    pub default: u32,
+}
+
+/// Decoded instructions of a function and their side tables
+#[derive(Debug, Clone, Default)]
+pub struct DecodedCode {
+    pub instrs: Vec<Instr>,
+    pub calls: Vec<Call>,
+    pub natives: Vec<NativeCall>,
+    pub methods: Vec<MethodCall>,
+    pub structs: Vec<StructInit>,
+    pub dicts: Vec<DictInit>,
+    pub spawns: Vec<SpawnSite>,
+    pub switches: Vec<SwitchTable>,
+    pub tables: Vec<JumpTable>,
+    pub names: Vec<String>,
+    /// Register lists that `Span`s of the other tables point into
+    regs: Vec<Reg>,
+    /// `Switch` cases
+    cases: Vec<(i32, u32)>,
+    /// `TableSwitch` targets
+    targets: Vec<u32>,
+}
+
+impl DecodedCode {
+    /// Decode `instructions`, resolving their relative labels
+    pub fn decode(instructions: &[BytecodeInstr]) -> Self {
+        let mut code = DecodedCode {
+            instrs: Vec::with_capacity(instructions.len()),
+            ..Default::default()
+        };
+        for (ip, instr) in instructions.iter().enumerate() {
+            let decoded = code.decode_one(ip, instr);
+            code.instrs.push(decoded);
+        }
+        code
+    }
+
+    /// Registers of a register list
+    pub fn regs(
+        &self,
+        span: Span,
+    ) -> &[Reg] {
+        &self.regs[span.range()]
+    }
+
+    /// `(case value, target)` pairs of a `Switch`
+    pub fn cases(
+        &self,
+        span: Span,
+    ) -> &[(i32, u32)] {
+        &self.cases[span.range()]
+    }
+
+    /// Targets of a `TableSwitch`
+    pub fn targets(
+        &self,
+        span: Span,
+    ) -> &[u32] {
+        &self.targets[span.range()]
+    }
+
+    fn decode_one(
+        &mut self,
+        ip: usize,
+        instr: &BytecodeInstr,
+    ) -> Instr {
+        use BytecodeInstr as B;
+
+        let op = |op: Op, a: Reg, b: Reg, c: Reg| Instr::new(op, a.0, b.0, c.0, 0);
+        let none = Reg(0);
+        let dst = |dst: &Option<Reg>| dst.map_or(NO_REG, |r| r.0);
+        match instr {
+            B::Nop => op(Op::Nop, none, none, none),
+            B::Return => op(Op::Return, none, none, none),
+            B::ReturnValue { value } => op(Op::ReturnValue, *value, none, none),
+            B::Yield => op(Op::Yield, none, none, none),
+            B::Spawn {
+                dst,
+                closures,
+                task_deps,
+                task_resources,
+            } => {
+                let closures = self.push_regs(closures);
+                let index = self.push_spawn(closures, task_deps, task_resources);
+                Instr::new(Op::Spawn, dst.0, 0, 0, index)
+            }
+            B::SpawnFromList {
+                dst,
+                closures_list,
+                task_deps,
+                task_resources,
+            } => {
+                let index = self.push_spawn(Span::default(), task_deps, task_resources);
+                Instr::new(Op::SpawnFromList, dst.0, closures_list.0, 0, index)
+            }
+            B::Jmp { target } => Instr::new(Op::Jmp, 0, 0, 0, resolve(ip, *target)),
+            B::JmpIf { cond, target } => Instr::new(Op::JmpIf, cond.0, 0, 0, resolve(ip, *target)),
+            B::JmpIfNot { cond, target } => {
+                Instr::new(Op::JmpIfNot, cond.0, 0, 0, resolve(ip, *target))
+            }
+            B::Switch { value, targets } => {
+                let start = self.cases.len() as u32;
+                for (case, target) in targets {
+                    if let Some(case) = case {
+                        self.cases.push((offset_of(*case), resolve(ip, *target)));
+                    }
+                }
+                let cases = Span {
+                    start,
+                    len: self.cases.len() as u32 - start,
+                };
+                // Only a trailing case-less entry is the default
+                let default = match targets.last() {
+                    Some((None, target)) => Some(resolve(ip, *target)),
+                    _ => None,
+                };
+                let index = self.switches.len() as u32;
+                self.switches.push(SwitchTable { cases, default });
+                Instr::new(Op::Switch, value.0, 0, 0, index)
+            }
+            B::TableSwitch {
+                value,
+                low,
+                targets,
+                default,
+            } => {
+                let start = self.targets.len() as u32;
+                self.targets
+                    .extend(targets.iter().map(|target| resolve(ip, *target)));
+                let index = self.tables.len() as u32;
+                self.tables.push(JumpTable {
+                    low: *low,
+                    targets: Span {
+                        start,
+                        len: targets.len() as u32,
+                    },
+                    default: resolve(ip, *default),
+                });
+                Instr::new(Op::TableSwitch, value.0, 0, 0, index)
+            }
+            B::Mov { dst, src } => op(Op::Mov, *dst, *src, none),
+            B::LoadConst { dst, const_idx } => Instr::new(Op::LoadConst, dst.0, *const_idx, 0, 0),
+            B::LoadLocal { dst, local_idx } => Instr::new(Op::LoadLocal, dst.0, *local_idx, 0, 0),
+            B::StoreLocal { local_idx, src } => Instr::new(Op::StoreLocal, *local_idx, src.0, 0, 0),
+            B::LoadArg { dst, arg_idx } => Instr::new(Op::LoadArg, dst.0, *arg_idx, 0, 0),
+            B::BinaryOp { dst, lhs, rhs, op } => {
+                Instr::new(Op::BinaryOp, dst.0, lhs.0, rhs.0, *op as u32)
+            }
+            B::UnaryOp { dst, src, op } => Instr::new(Op::UnaryOp, dst.0, src.0, 0, *op as u32),
+            B::Int32Op { dst, lhs, rhs, op } => {
+                Instr::new(Op::Int32Op, dst.0, lhs.0, rhs.0, *op as u32)
+            }
+            B::Int32Neg { dst, src } => op(Op::Int32Neg, *dst, *src, none),
+            B::Compare { dst, lhs, rhs, cmp } => {
+                Instr::new(Op::Compare, dst.0, lhs.0, rhs.0, *cmp as u32)
+            }
+            B::FloatOp { dst, lhs, rhs, op } => {
+                Instr::new(Op::FloatOp, dst.0, lhs.0, rhs.0, *op as u32)
+            }
+            B::FloatNeg { dst, src } => op(Op::FloatNeg, *dst, *src, none),
+            B::FloatCompare { dst, lhs, rhs, cmp } => {
+                Instr::new(Op::FloatCompare, dst.0, lhs.0, rhs.0, *cmp as u32)
+            }
+            B::IntToFloat { dst, src } => op(Op::IntToFloat, *dst, *src, none),
+            B::StackAlloc { dst, src } => op(Op::StackAlloc, *dst, *src, none),
+            B::HeapAlloc { dst, type_id } => Instr::new(Op::HeapAlloc, dst.0, *type_id, 0, 0),
+            B::Drop { value } => op(Op::Drop, *value, none, none),
+            B::GetField { dst, src, offset } => Instr::new(Op::GetField, dst.0, src.0, *offset, 0),
+            B::SetField { src, offset, value } => {
+                Instr::new(Op::SetField, src.0, *offset, value.0, 0)
+            }
+            B::LoadElement { dst, array, index } => op(Op::LoadElement, *dst, *array, *index),
+            B::StoreElement {
+                array,
+                index,
+                value,
+            } => op(Op::StoreElement, *array, *index, *value),
+            B::NewListWithCap { dst, capacity } => {
+                Instr::new(Op::NewListWithCap, dst.0, *capacity, 0, 0)
+            }
+            B::CreateStruct {
+                dst,
+                type_name,
+                fields,
+            } => {
+                let fields = self.push_regs(fields);
+                let index = self.structs.len() as u32;
+                self.structs.push(StructInit {
+                    type_name: type_name.clone(),
+                    fields,
+                });
+                Instr::new(Op::CreateStruct, dst.0, 0, 0, index)
+            }
+            B::NewDict { dst, keys, values } => {
+                let keys = self.push_regs(keys);
+                let values = self.push_regs(values);
+                let index = self.dicts.len() as u32;
+                self.dicts.push(DictInit { keys, values });
+                Instr::new(Op::NewDict, dst.0, 0, 0, index)
+            }
+            B::ArcNew { dst, src } => op(Op::ArcNew, *dst, *src, none),
+            B::RcNew { dst, src } => op(Op::RcNew, *dst, *src, none),
+            B::ArcClone { dst, src } => op(Op::ArcClone, *dst, *src, none),
+            B::ArcDrop { src } => op(Op::ArcDrop, *src, none, none),
+            B::WeakNew { dst, src } => op(Op::WeakNew, *dst, *src, none),
+            B::WeakUpgrade { dst, src } => op(Op::WeakUpgrade, *dst, *src, none),
+            B::Borrow { dst, src, mutable } => {
+                Instr::new(Op::Borrow, dst.0, src.0, u16::from(*mutable), 0)
+            }
+            B::Release { src } => op(Op::Release, *src, none, none),
+            B::CallStatic {
+                dst: target,
+                func,
+                args,
+            } => {
+                let index = self.push_call(func, args);
+                Instr::new(Op::CallStatic, dst(target), 0, 0, index)
+            }
+            B::TailCall { func, args } => {
+                let index = self.push_call(func, args);
+                Instr::new(Op::TailCall, 0, 0, 0, index)
+            }
+            B::CallNative {
+                dst: target,
+                func_name,
+                mechanism,
+                lib,
+                symbol,
+                args,
+            } => {
+                let args = self.push_regs(args);
+                let index = self.natives.len() as u32;
+                self.natives.push(NativeCall {
+                    func_name: func_name.clone(),
+                    mechanism: mechanism.clone(),
+                    lib: lib.clone(),
+                    symbol: symbol.clone(),
+                    args,
+                });
+                Instr::new(Op::CallNative, dst(target), 0, 0, index)
+            }
+            B::CallVirt {
+                dst: target,
+                obj,
+                method_idx,
+                args,
+            } => {
+                let index = self.push_method(*method_idx, args);
+                Instr::new(Op::CallVirt, dst(target), obj.0, 0, index)
+            }
+            B::CallDyn {
+                dst: target,
+                obj,
+                name_idx,
+                args,
+            } => {
+                let index = self.push_method(*name_idx, args);
+                Instr::new(Op::CallDyn, dst(target), obj.0, 0, index)
+            }
+            B::InvokeVirtual {
+                dst: target,
+                obj,
+                slot,
+                name_idx,
+                args,
+            } => {
+                let index = self.push_method(*name_idx, args);
+                Instr::new(Op::InvokeVirtual, dst(target), obj.0, *slot, index)
+            }
+            B::MakeDyn { dst, src, vtable } => Instr::new(Op::MakeDyn, dst.0, src.0, 0, *vtable),
+            B::MakeClosure { dst, func, env } => {
+                let index = self.push_call(func, env);
+                Instr::new(Op::MakeClosure, dst.0, 0, 0, index)
+            }
+            B::LoadUpvalue { dst, upvalue_idx } => {
+                Instr::new(Op::LoadUpvalue, dst.0, u16::from(*upvalue_idx), 0, 0)
+            }
+            B::StoreUpvalue { src, upvalue_idx } => {
+                Instr::new(Op::StoreUpvalue, src.0, u16::from(*upvalue_idx), 0, 0)
+            }
+            B::CloseUpvalue { src } => op(Op::CloseUpvalue, *src, none, none),
+            B::StringLength { dst, src } => op(Op::StringLength, *dst, *src, none),
+            B::StringConcat { dst, str1, str2 } => op(Op::StringConcat, *dst, *str1, *str2),
+            B::StringEqual { dst, str1, str2 } => op(Op::StringEqual, *dst, *str1, *str2),
+            B::StringGetChar { dst, src, index } => op(Op::StringGetChar, *dst, *src, *index),
+            B::StringFromInt { dst, src } => op(Op::StringFromInt, *dst, *src, none),
+            B::StringFromFloat { dst, src } => op(Op::StringFromFloat, *dst, *src, none),
+            B::StringBuilderNew { dst, src } => op(Op::StringBuilderNew, *dst, *src, none),
+            B::StringBuilderAppend { dst, lhs, rhs } => {
+                op(Op::StringBuilderAppend, *dst, *lhs, *rhs)
+            }
+            B::StringBuilderFinish { dst, src } => op(Op::StringBuilderFinish, *dst, *src, none),
+            B::TryBegin { catch_target } => {
+                Instr::new(Op::TryBegin, 0, 0, 0, resolve(ip, *catch_target))
+            }
+            B::TryEnd => op(Op::TryEnd, none, none, none),
+            B::Throw { error } => op(Op::Throw, *error, none, none),
+            B::BoundsCheck { array, index } => op(Op::BoundsCheck, *array, *index, none),
+            B::TypeCheck { value, type_id } => Instr::new(Op::TypeCheck, value.0, *type_id, 0, 0),
+            B::Cast {
+                dst,
+                src,
+                target_type_id,
+            } => Instr::new(Op::Cast, dst.0, src.0, *target_type_id, 0),
+            B::TypeTest { dst, src, target } => {
+                let index = self.push_name(target);
+                Instr::new(Op::TypeTest, dst.0, src.0, 0, index)
+            }
+            B::Narrow {
+                dst,
+                src,
+                target_type_id,
+            } => Instr::new(Op::Narrow, dst.0, src.0, *target_type_id, 0),
+            B::TypeOf { dst, src } => op(Op::TypeOf, *dst, *src, none),
+            B::GetRecordField { dst, src, field } => {
+                let index = self.push_name(field);
+                Instr::new(Op::GetRecordField, dst.0, src.0, 0, index)
+            }
+        }
+    }
+
+    fn push_regs(
+        &mut self,
+        regs: &[Reg],
+    ) -> Span {
+        let start = self.regs.len() as u32;
+        self.regs.extend_from_slice(regs);
+        Span {
+            start,
+            len: regs.len() as u32,
+        }
+    }
+
+    fn push_call(
+        &mut self,
+        func: &FunctionRef,
+        args: &[Reg],
+    ) -> u32 {
+        let args = self.push_regs(args);
+        self.calls.push(Call {
+            func: func.clone(),
+            args,
+        });
+        self.calls.len() as u32 - 1
+    }
+
+    fn push_method(
+        &mut self,
+        name_idx: u16,
+        args: &[Reg],
+    ) -> u32 {
+        let args = self.push_regs(args);
+        self.methods.push(MethodCall { name_idx, args });
+        self.methods.len() as u32 - 1
+    }
+
+    fn push_spawn(
+        &mut self,
+        closures: Span,
+        task_deps: &[Vec<u32>],
+        task_resources: &[Vec<String>],
+    ) -> u32 {
+        self.spawns.push(SpawnSite {
+            closures,
+            task_deps: task_deps.to_vec(),
+            task_resources: task_resources.to_vec(),
+        });
+        self.spawns.len() as u32 - 1
+    }
+
+    fn push_name(
+        &mut self,
+        name: &str,
+    ) -> u32 {
+        self.names.push(name.to_string());
+        self.names.len() as u32 - 1
+    }
+}
+
+/// Signed offset stored in a relative label (also the case value of `Switch`)
+fn offset_of(label: Label) -> i32 {
+    label.0 as i32
+}
+
+/// Absolute target of the relative `label` of the instruction at `ip`
+fn resolve(
+    ip: usize,
+    label: Label,
+) -> u32 {
+    let target = ip as i64 + i64::from(offset_of(label));
+    u32::try_from(target).unwrap_or(NO_TARGET)
+}
+
+/// Lazily decoded form of a function's instructions
+///
+/// A clone starts empty: its instructions may still be rewritten (e.g. relocated
+/// on import) before it runs.
+#[derive(Default)]
+pub struct DecodedCell(OnceLock<DecodedCode>);
+
+impl DecodedCell {
+    /// The decoded `instructions`, decoding them on first use
+    pub fn get_or_decode(
+        &self,
+        instructions: &[BytecodeInstr],
+    ) -> &DecodedCode {
+        self.0.get_or_init(|| DecodedCode::decode(instructions))
+    }
+
+    /// Whether the instructions were decoded already
+    pub fn is_decoded(&self) -> bool {
+        self.0.get().is_some()
+    }
+}
+
+impl Clone for DecodedCell {
+    fn clone(&self) -> Self {
+        DecodedCell::default()
+    }
+}
+
+impl std::fmt::Debug for DecodedCell {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        f.debug_struct("DecodedCell")
+            .field("decoded", &self.is_decoded())
+            .finish()
+    }
+}
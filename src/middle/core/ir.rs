@@ -1,6 +1,7 @@
 //! Intermediate Representation
 
 pub use crate::frontend::core::parser::ast::Type;
+use crate::frontend::core::parser::ast::Attribute;
 use crate::frontend::core::typecheck::MonoType;
 use crate::util::span::Span;
 
@@ -64,16 +65,22 @@ pub enum Instruction {
         dst: Operand,
         lhs: Operand,
         rhs: Operand,
+        /// Source span for overflow reporting
+        span: Span,
     },
     Sub {
         dst: Operand,
         lhs: Operand,
         rhs: Operand,
+        /// Source span for overflow reporting
+        span: Span,
     },
     Mul {
         dst: Operand,
         lhs: Operand,
         rhs: Operand,
+        /// Source span for overflow reporting
+        span: Span,
     },
     Div {
         dst: Operand,
@@ -156,9 +163,86 @@ pub enum Instruction {
         lhs: Operand,
         rhs: Operand,
     },
+    // =====================
+    // 浮点指令（操作数类型为 Float）
+    // =====================
+    FAdd {
+        dst: Operand,
+        lhs: Operand,
+        rhs: Operand,
+    },
+    FSub {
+        dst: Operand,
+        lhs: Operand,
+        rhs: Operand,
+    },
+    FMul {
+        dst: Operand,
+        lhs: Operand,
+        rhs: Operand,
+    },
+    FDiv {
+        dst: Operand,
+        lhs: Operand,
+        rhs: Operand,
+    },
+    FMod {
+        dst: Operand,
+        lhs: Operand,
+        rhs: Operand,
+    },
+    FNeg {
+        dst: Operand,
+        src: Operand,
+    },
+    FEq {
+        dst: Operand,
+        lhs: Operand,
+        rhs: Operand,
+    },
+    FNe {
+        dst: Operand,
+        lhs: Operand,
+        rhs: Operand,
+    },
+    FLt {
+        dst: Operand,
+        lhs: Operand,
+        rhs: Operand,
+    },
+    FLe {
+        dst: Operand,
+        lhs: Operand,
+        rhs: Operand,
+    },
+    FGt {
+        dst: Operand,
+        lhs: Operand,
+        rhs: Operand,
+    },
+    FGe {
+        dst: Operand,
+        lhs: Operand,
+        rhs: Operand,
+    },
+    /// 整数转浮点：整数值隐式转为 Float 处（浮点与整数混合运算、`Float` 标注与参数）生成
+    IntToFloat {
+        dst: Operand,
+        src: Operand,
+    },
     Jmp(usize),
     JmpIf(Operand, usize),
     JmpIfNot(Operand, usize),
+    /// 跳转表：`value - low` 落在 `targets` 的下标范围内时跳到对应目标，否则跳到 `default`
+    ///
+    /// `value` 为整数、字符（按码点）或枚举（按变体编号），由稠密的整数、字符 match
+    /// 与枚举变体 match 生成
+    Switch {
+        value: Operand,
+        low: i64,
+        targets: Vec<usize>,
+        default: usize,
+    },
     Call {
         dst: Option<Operand>,
         func: Operand,
@@ -181,6 +265,26 @@ pub enum Instruction {
         /// Source span for error reporting
         span: Span,
     },
+    /// 接口方法的动态分发：按存在类型值携带的虚表调用第 slot 个方法
+    /// obj: 接收者（存在类型值）
+    /// method_name: 方法名（接收者未打包时按名称查找）
+    /// args: 包含 obj 的完整参数列表
+    InvokeVirtual {
+        dst: Option<Operand>,
+        obj: Operand,
+        slot: usize,
+        method_name: String,
+        args: Vec<Operand>,
+        /// Source span for error reporting
+        span: Span,
+    },
+    /// 打包存在类型值：具体类型的值 + 其实现接口的虚表
+    MakeDyn {
+        dst: Operand,
+        src: Operand,
+        /// 模块虚表下标
+        vtable: usize,
+    },
     /// 动态调用：直接调用寄存器中的函数值（闭包）
     CallDyn {
         dst: Option<Operand>,
@@ -210,6 +314,18 @@ pub enum Instruction {
         dst: Operand,
         src: Operand,
         field: usize,
+        /// 结构体类型名（代码生成据此查布局、换算字段偏移）
+        type_name: Option<String>,
+        /// Source span for error reporting
+        span: Span,
+    },
+    /// 按字段名读取匿名记录类型的值的字段
+    ///
+    /// 值的实际结构体类型在编译期未知（宽度子类型），偏移由运行时布局解析
+    LoadRecordField {
+        dst: Operand,
+        src: Operand,
+        field: String,
         /// Source span for error reporting
         span: Span,
     },
@@ -217,7 +333,7 @@ pub enum Instruction {
         dst: Operand,
         field: usize,
         src: Operand,
-        /// 结构体类型名（用于字段可变性检查）
+        /// 结构体类型名（用于字段可变性检查与换算字段偏移）
         type_name: Option<String>,
         /// 字段名（用于错误信息）
         field_name: Option<String>,
@@ -244,7 +360,18 @@ pub enum Instruction {
         src: Operand,
         target_type: Type,
     },
-    TypeTest(Operand, Type),
+    /// 定宽算术结果收窄到 `target_type` 的位宽（调试构建溢出报错，发布构建回绕）
+    Narrow {
+        dst: Operand,
+        src: Operand,
+        target_type: Type,
+    },
+    /// `as?` 向下转换：`src` 的运行时类型是 `target_type` 时 `dst = src`，否则 `dst = void`
+    TypeTest {
+        dst: Operand,
+        src: Operand,
+        target_type: Type,
+    },
     /// Spawn a new task (for cycle detection: track args and result)
     Spawn {
         /// 每个直接子表达式对应一个闭包
@@ -269,6 +396,13 @@ pub enum Instruction {
         dst: Operand,
         type_id: usize,
     },
+    /// 把刚由 `CreateStruct` / `AllocArray` 分配的不逃逸对象 `src` 归入当前帧的栈区，
+    /// 结果为 `dst`（逃逸分析产出）
+    /// 同一处再次执行时释放上次归入的对象，函数返回时释放全部
+    StackAlloc {
+        dst: Operand,
+        src: Operand,
+    },
     /// 创建结构体实例
     /// type_name: 结构体类型名
     /// fields: 各字段值的操作数（按字段顺序）
@@ -361,6 +495,22 @@ pub enum Instruction {
         dst: Operand,
         src: Operand,
     },
+    /// 以字符串 `src` 为初值新建字符串构建器
+    StringBuilderNew {
+        dst: Operand,
+        src: Operand,
+    },
+    /// 把字符串 `rhs` 原地追加到构建器 `lhs`，`dst` 得到同一个构建器
+    StringBuilderAppend {
+        dst: Operand,
+        lhs: Operand,
+        rhs: Operand,
+    },
+    /// 把构建器 `src` 拼成字符串；`src` 已是字符串时原样返回
+    StringBuilderFinish {
+        dst: Operand,
+        src: Operand,
+    },
     // =====================
     // 闭包 Upvalue 指令
     // =====================
@@ -375,6 +525,89 @@ pub enum Instruction {
     CloseUpvalue(Operand),
 }
 
+impl Instruction {
+    /// 跳转指令的全部目标（指令下标），不是跳转时为空
+    pub fn jump_targets(&self) -> Vec<usize> {
+        match self {
+            Instruction::Jmp(t) | Instruction::JmpIf(_, t) | Instruction::JmpIfNot(_, t) => {
+                vec![*t]
+            }
+            Instruction::Switch {
+                targets, default, ..
+            } => targets.iter().chain([default]).copied().collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// 跳转目标的可变引用，供平移、重定位跳转的遍使用
+    pub fn jump_targets_mut(&mut self) -> Vec<&mut usize> {
+        match self {
+            Instruction::Jmp(t) | Instruction::JmpIf(_, t) | Instruction::JmpIfNot(_, t) => {
+                vec![t]
+            }
+            Instruction::Switch {
+                targets, default, ..
+            } => targets.iter_mut().chain([default]).collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// 纯指令：结果只取决于操作数，执行时除运行时错误（除零、溢出）外没有副作用
+    ///
+    /// 同样操作数的两次执行得到相同结果，后一次可以复用前一次的结果；但纯指令仍可能
+    /// 报错，删除或提前执行前要另外确认不会报错。读取堆、局部变量槽或 upvalue 的指令
+    /// 不算纯指令，其结果随写入而变。
+    pub fn is_pure(&self) -> bool {
+        matches!(
+            self,
+            Instruction::Move { .. }
+                | Instruction::Load {
+                    src: Operand::Const(_),
+                    ..
+                }
+                | Instruction::Add { .. }
+                | Instruction::Sub { .. }
+                | Instruction::Mul { .. }
+                | Instruction::Div { .. }
+                | Instruction::Mod { .. }
+                | Instruction::And { .. }
+                | Instruction::Or { .. }
+                | Instruction::Xor { .. }
+                | Instruction::Shl { .. }
+                | Instruction::Shr { .. }
+                | Instruction::Sar { .. }
+                | Instruction::Neg { .. }
+                | Instruction::Eq { .. }
+                | Instruction::Ne { .. }
+                | Instruction::Lt { .. }
+                | Instruction::Le { .. }
+                | Instruction::Gt { .. }
+                | Instruction::Ge { .. }
+                | Instruction::FAdd { .. }
+                | Instruction::FSub { .. }
+                | Instruction::FMul { .. }
+                | Instruction::FDiv { .. }
+                | Instruction::FMod { .. }
+                | Instruction::FNeg { .. }
+                | Instruction::FEq { .. }
+                | Instruction::FNe { .. }
+                | Instruction::FLt { .. }
+                | Instruction::FLe { .. }
+                | Instruction::FGt { .. }
+                | Instruction::FGe { .. }
+                | Instruction::IntToFloat { .. }
+                | Instruction::Cast { .. }
+                | Instruction::Narrow { .. }
+                | Instruction::TypeTest { .. }
+                | Instruction::StringLength { .. }
+                | Instruction::StringConcat { .. }
+                | Instruction::StringGetChar { .. }
+                | Instruction::StringFromInt { .. }
+                | Instruction::StringFromFloat { .. }
+        )
+    }
+}
+
 /// Basic block
 #[derive(Debug, Clone)]
 pub struct BasicBlock {
@@ -426,6 +659,15 @@ pub enum ConstValue {
         lib: String,
         symbol: String,
     },
+    /// 复合常量：元素全部为常量的列表字面量，运行时浅拷贝为新列表
+    List(Vec<ConstValue>),
+    /// 复合常量：键值全部为常量的字典字面量，运行时浅拷贝为新字典
+    Dict(Vec<(ConstValue, ConstValue)>),
+    /// 复合常量：字段全部为常量的结构体构造，字段按声明顺序排列
+    Struct {
+        type_name: String,
+        fields: Vec<ConstValue>,
+    },
 }
 
 impl PartialEq for ConstValue {
@@ -463,6 +705,18 @@ impl PartialEq for ConstValue {
                     symbol: r2,
                 },
             ) => l0 == r0 && l1 == r1 && l2 == r2,
+            (Self::List(l0), Self::List(r0)) => l0 == r0,
+            (Self::Dict(l0), Self::Dict(r0)) => l0 == r0,
+            (
+                Self::Struct {
+                    type_name: l0,
+                    fields: l1,
+                },
+                Self::Struct {
+                    type_name: r0,
+                    fields: r1,
+                },
+            ) => l0 == r0 && l1 == r1,
             _ => false,
         }
     }
@@ -497,10 +751,47 @@ impl std::hash::Hash for ConstValue {
                 lib.hash(state);
                 symbol.hash(state);
             }
+            Self::List(items) => items.hash(state),
+            Self::Dict(entries) => entries.hash(state),
+            Self::Struct { type_name, fields } => {
+                type_name.hash(state);
+                fields.hash(state);
+            }
         }
     }
 }
 
+/// 接口虚表：具体类型实现某接口时，按接口方法声明顺序排列的方法函数名
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VTable {
+    pub interface: String,
+    pub type_name: String,
+    /// 方法函数名（如 "Circle.show"），下标即方法槽位
+    pub methods: Vec<String>,
+}
+
+/// 结构体布局：类型名与按声明顺序排列的字段名（运行时打印值时使用），
+/// 以及由 [`layout`](super::layout) 计算的字段字节偏移、总大小与对齐
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StructLayout {
+    pub name: String,
+    pub fields: Vec<String>,
+    /// 各字段的字节偏移，与 `fields` 一一对应且严格递增
+    pub offsets: Vec<u32>,
+    pub size: u32,
+    pub align: u32,
+}
+
+impl StructLayout {
+    /// 第 `index` 个字段的字节偏移
+    pub fn field_offset(
+        &self,
+        index: usize,
+    ) -> Option<u32> {
+        self.offsets.get(index).copied()
+    }
+}
+
 /// FFI 库绑定 — 编译期链接的外部库
 #[derive(Debug, Clone)]
 pub struct FfiLibBinding {
@@ -530,6 +821,8 @@ pub enum FfiBinding {
 #[derive(Debug, Clone, Default)]
 pub struct ModuleIR {
     pub types: Vec<Type>,
+    /// 泛型类型定义（类型名、类型参数、以类型参数表示的定义），由单态化按 `types` 中的实例特化
+    pub generic_types: Vec<(String, Vec<String>, Type)>,
     pub globals: Vec<(String, Type, Option<ConstValue>)>,
     pub functions: Vec<FunctionIR>,
     /// 每个函数的可变局部变量索引映射 (function_name -> set of mutable local indices)
@@ -543,4 +836,36 @@ pub struct ModuleIR {
     pub ffi_libs: Vec<FfiLibBinding>,
     /// FFI 绑定 — 不透明类型或外部函数
     pub ffi_bindings: Vec<FfiBinding>,
+    /// 接口虚表（存在类型值的动态分发）
+    pub vtables: Vec<VTable>,
+    /// 结构体布局（按类型名排序）
+    pub struct_layouts: Vec<StructLayout>,
+    /// 函数的内联提示 (function_name -> `#[inline]` / `#[noinline]`)
+    pub inline_hints: std::collections::HashMap<String, InlineHint>,
+    /// 公开（`pub`）的函数名，方法为 `Type.method`；无 `main` 时作为死函数消除的根
+    pub exports: Vec<String>,
+    /// 闭包函数的 upvalue 个数 (function_name -> 捕获变量数)，未列出的函数没有 upvalue
+    pub upvalue_counts: std::collections::HashMap<String, usize>,
+}
+
+/// 函数定义上的内联提示
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InlineHint {
+    /// `#[inline]`：不受大小阈值限制
+    Always,
+    /// `#[noinline]`：从不内联
+    Never,
+}
+
+impl InlineHint {
+    /// 从函数的属性中读取提示，`#[noinline]` 优先
+    pub fn from_attributes(attributes: &[Attribute]) -> Option<Self> {
+        if attributes.iter().any(|a| a.name == "noinline") {
+            Some(InlineHint::Never)
+        } else if attributes.iter().any(|a| a.name == "inline") {
+            Some(InlineHint::Always)
+        } else {
+            None
+        }
+    }
 }
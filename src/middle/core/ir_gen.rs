@@ -13,26 +13,40 @@ use crate::frontend::core::lexer::tokens::Literal;
 use crate::frontend::core::parser::ast::{self, Expr};
 use crate::frontend::module::registry::ModuleRegistry;
 use crate::frontend::core::typecheck::{MonoType, PolyType, TypeCheckResult};
-use crate::middle::core::ir::{BasicBlock, ConstValue, FunctionIR, Instruction, ModuleIR, Operand};
+use crate::frontend::core::typecheck::inference::numeric::resolve_numeric;
+use crate::middle::core::ir::{
+    BasicBlock, ConstValue, FunctionIR, InlineHint, Instruction, ModuleIR, Operand,
+};
+use crate::middle::core::layout::LayoutEngine;
+use crate::middle::passes::closure;
+use crate::middle::passes::const_eval::ConstEvaluator;
+use crate::middle::passes::decision_tree::{Access, Decision, JumpTable, Test, RESULT_OK};
 use crate::tlog;
 use crate::util::diagnostic::{Diagnostic, ErrorCodeDefinition};
 use crate::util::i18n::MSG;
 use crate::util::span::Span;
 use std::collections::HashMap;
 
-/// 检查是否是命名空间调用（如 std.io.println 或 io.println）
-fn is_namespace_call(expr: &ast::Expr) -> bool {
+/// 检查是否是命名空间调用（如 std.io.println、io.println 或原生扩展 sqlite.open）
+fn is_namespace_call(
+    registry: &ModuleRegistry,
+    expr: &ast::Expr,
+) -> bool {
     match expr {
         ast::Expr::Var(name, _) => {
-            name == "std" || ModuleRegistry::with_std().is_std_submodule(name)
+            if name == "std" {
+                return true;
+            }
+            registry.is_std_submodule(name) || registry.is_native_module(name)
         }
-        ast::Expr::FieldAccess { expr, .. } => is_namespace_call(expr),
+        ast::Expr::FieldAccess { expr, .. } => is_namespace_call(registry, expr),
         _ => false,
     }
 }
 
 /// 提取完整的命名空间路径（如 std.io.println 或 io.println -> std.io.println）
 fn extract_namespace_path(
+    registry: &ModuleRegistry,
     expr: &ast::Expr,
     field: &str,
 ) -> String {
@@ -40,7 +54,7 @@ fn extract_namespace_path(
         ast::Expr::Var(name, _) => {
             if name == "std" {
                 format!("std.{}", field)
-            } else if ModuleRegistry::with_std().is_std_submodule(name) {
+            } else if registry.is_std_submodule(name) {
                 format!("std.{}.{}", name, field)
             } else {
                 format!("{}.{}", name, field)
@@ -51,7 +65,7 @@ fn extract_namespace_path(
             field: sub_field,
             ..
         } => {
-            let prefix = extract_namespace_path(expr, sub_field);
+            let prefix = extract_namespace_path(registry, expr, sub_field);
             format!("{}.{}", prefix, field)
         }
         _ => field.to_string(),
@@ -102,6 +116,44 @@ fn get_type_fallback_string(mono_type: &MonoType) -> String {
 #[derive(Debug, Clone)]
 struct SymbolEntry {
     local_idx: usize,
+    /// 槽中存放 cell（按引用捕获的变量），读写经由 cell
+    boxed: bool,
+}
+
+/// 正在生成的闭包函数体
+///
+/// 闭包体只能直接访问自己的作用域；外层函数的变量经 upvalue 访问，首次引用时登记捕获。
+#[derive(Debug)]
+struct ClosureFrame {
+    /// 闭包体第一个作用域在 `symbols` 中的下标
+    scope_base: usize,
+    /// 捕获的变量，按 upvalue 下标排列
+    captures: Vec<Capture>,
+}
+
+/// 闭包捕获的一个变量
+#[derive(Debug, Clone)]
+struct Capture {
+    name: String,
+    /// 创建闭包时从外层函数的哪里取值
+    source: VarSlot,
+}
+
+/// 变量在当前函数中的位置
+#[derive(Debug, Clone, Copy)]
+enum VarSlot {
+    /// 局部变量槽
+    Local { idx: usize, boxed: bool },
+    /// 闭包环境中的 upvalue
+    Upvalue { idx: usize, boxed: bool },
+}
+
+impl VarSlot {
+    fn is_boxed(self) -> bool {
+        match self {
+            VarSlot::Local { boxed, .. } | VarSlot::Upvalue { boxed, .. } => boxed,
+        }
+    }
 }
 
 /// IR 生成器配置
@@ -122,11 +174,11 @@ impl IrGeneratorConfig {
 ///
 /// 将 AST 节点转换为 IR 指令序列。
 #[derive(Debug)]
-pub struct AstToIrGenerator {
+pub struct AstToIrGenerator<'a> {
     /// 符号表（用于变量解析）
     symbols: Vec<HashMap<String, SymbolEntry>>,
-    /// 类型检查结果（包含变量绑定信息）
-    type_result: Option<Box<TypeCheckResult>>,
+    /// 类型检查结果（包含变量绑定信息），借用而不复制
+    type_result: Option<&'a TypeCheckResult>,
     /// 下一个临时寄存器编号
     next_temp: usize,
     /// 当前函数中可变局部变量的索引集合
@@ -165,16 +217,52 @@ pub struct AstToIrGenerator {
     /// 当 `d: Drawable = Circle(1)` 时，记录 d -> "Circle"（具体类型名）
     /// 用于方法调用时选择直接调用而非 vtable 查找
     constraint_var_concrete_types: HashMap<String, String>,
+    /// 接口虚表（具体类型 × 接口），打包存在类型值时按需生成
+    vtables: Vec<crate::middle::core::ir::VTable>,
+    /// 待生成的列表字面量元素需打包为该接口的存在类型值（如 `items: List(Show) = [..]`）
+    dyn_list_interface: Option<String>,
+    /// 类型标注中出现的用户泛型实例（如 `Box(Int)`），输出为模块类型表
+    instance_types: Vec<ast::Type>,
+    /// 泛型结构体定义（类型名、类型参数、定义），交给单态化按实例特化
+    generic_type_defs: Vec<(String, Vec<String>, ast::Type)>,
+    /// 当前函数的泛型类型参数名（含这些参数的标注不是具体实例）
+    current_type_params: Vec<String>,
+    /// 泛型函数中类型为泛型参数的形参（形参名 -> 泛型参数名）
+    /// 如 `describe: (T: Show) -> (value: T) -> String` 中记录 value -> "T"，
+    /// `value.show()` 生成 `T.show`，由单态化替换为具体实现类型的方法（静态分发）
+    generic_param_vars: HashMap<String, String>,
+    /// 当前函数中类型为匿名记录的变量（形参或带标注的局部变量）
+    /// 实际传入的结构体可以有更多字段（宽度子类型），字段按名字读取
+    record_vars: std::collections::HashSet<String>,
+    /// 关联常量的限定名（如 "Square.SIDES"），访问点生成零参调用
+    assoc_consts: std::collections::HashSet<String>,
+    /// 模块顶层绑定的名字，调用时优先于标准库的同名短名称
+    module_bindings: std::collections::HashSet<String>,
     /// RFC-004: 匿名函数绑定生成的独立 FunctionIR 列表
     anon_function_irs: Vec<FunctionIR>,
     /// 函数参数类型记录（函数名 -> 参数类型列表）
     /// 用于在调用点决定是否需要发出 Borrow 指令（RFC-009 §2.8 自动借用）
     function_param_types: HashMap<String, Vec<MonoType>>,
-    /// NLL 精确释放计划（所有权检查器产出）
-    release_plan: HashMap<Span, Vec<String>>,
-    /// 待捕获的环境变量（由 spawn for 等设置，供下一个 Expr::Lambda 使用）
-    /// 在生成闭包函数体时，这些变量的当前寄存器值会被捕获到闭包环境中。
-    pending_env_vars: Vec<Operand>,
+    /// 正在生成的闭包函数体（由外向内）
+    closure_frames: Vec<ClosureFrame>,
+    /// 当前函数中按引用捕获的变量名（见 [`closure::boxed_variables`]）
+    boxed_vars: std::collections::HashSet<String>,
+    /// 闭包函数的 upvalue 个数 (function_name -> count)
+    upvalue_counts: HashMap<String, usize>,
+    /// 标准库与已安装原生扩展的模块注册表，整个模块的生成共用一份
+    std_registry: ModuleRegistry,
+    /// 标准库函数与常量的短名称 -> 完整路径（如 `println` -> `std.io.println`）
+    std_short_names: HashMap<String, String>,
+    /// 编译期常量求值器（模块级常量按定义顺序登记，供后续表达式折叠）
+    const_eval: ConstEvaluator,
+    /// const 泛型函数模板（函数名 -> 定义语句），按调用处的常量实参生成特化版本
+    const_generic_fns: HashMap<String, ast::Stmt>,
+    /// 待生成的 const 泛型特化：(特化名, 模板名, 常量实参)
+    pending_const_instances: Vec<(String, String, Vec<ConstValue>)>,
+    /// 已请求的 const 泛型特化名
+    const_instance_names: std::collections::HashSet<String>,
+    /// 当前函数声明返回 `Result`：`return` 非 Result 值时包装为 `ok(..)`（RFC-001）
+    returns_result: bool,
 }
 
 /// 绑定信息（用于 IR 生成阶段的方法调用转发）
@@ -188,23 +276,52 @@ struct BindingInfo {
     positions: Vec<i64>,
 }
 
+/// 生成嵌套函数时保存的外层函数状态
+struct NestedFunctionState {
+    mut_locals: std::collections::HashSet<usize>,
+    local_names: Vec<String>,
+    loop_binding_locals: std::collections::HashSet<usize>,
+    next_temp: usize,
+    generic_param_vars: HashMap<String, String>,
+    record_vars: std::collections::HashSet<String>,
+    type_params: Vec<String>,
+    boxed_vars: std::collections::HashSet<String>,
+    returns_result: bool,
+}
+
 /// Lambda 函数体 IR 结果
 struct LambdaBodyIR {
     instructions: Vec<Instruction>,
     locals: Vec<MonoType>,
     /// 闭包函数的可变局部变量索引集合
     mut_locals: std::collections::HashSet<usize>,
+    /// 捕获的外层变量，按 upvalue 下标排列
+    captures: Vec<Capture>,
+}
+
+/// match 决策树发射过程中的状态
+struct MatchState {
+    /// match 表达式位置（取值指令的报错位置）
+    span: Span,
+    /// 每个分支的绑定名及其专用寄存器
+    bindings: Vec<Vec<(String, usize)>>,
+    /// 跳到分支体的指令：(指令下标, 分支下标)
+    to_arm: Vec<(usize, usize)>,
+    /// 跳到 match 结束处的指令下标
+    to_end: Vec<usize>,
 }
 
-impl Default for AstToIrGenerator {
+impl Default for AstToIrGenerator<'_> {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl AstToIrGenerator {
+impl<'a> AstToIrGenerator<'a> {
     /// 创建新的 IR 生成器
     pub fn new() -> Self {
+        let std_registry = ModuleRegistry::with_std();
+        let std_short_names = std_registry.short_to_qualified_map();
         Self {
             symbols: vec![HashMap::new()],
             type_result: None,
@@ -225,18 +342,34 @@ impl AstToIrGenerator {
             closure_counter: 0,
             global_vars: Vec::new(),
             constraint_var_concrete_types: HashMap::new(),
+            vtables: Vec::new(),
+            dyn_list_interface: None,
+            instance_types: Vec::new(),
+            generic_type_defs: Vec::new(),
+            current_type_params: Vec::new(),
+            generic_param_vars: HashMap::new(),
+            record_vars: std::collections::HashSet::new(),
+            assoc_consts: std::collections::HashSet::new(),
+            module_bindings: std::collections::HashSet::new(),
             anon_function_irs: Vec::new(),
             function_param_types: HashMap::new(),
-            release_plan: HashMap::new(),
-            pending_env_vars: Vec::new(),
+            closure_frames: Vec::new(),
+            boxed_vars: std::collections::HashSet::new(),
+            upvalue_counts: HashMap::new(),
+            std_registry,
+            std_short_names,
+            const_eval: ConstEvaluator::new(),
+            const_generic_fns: HashMap::new(),
+            pending_const_instances: Vec::new(),
+            const_instance_names: std::collections::HashSet::new(),
+            returns_result: false,
         }
     }
 
     /// 创建新的 IR 生成器（带类型信息）
-    pub fn new_with_type_result(type_result: &TypeCheckResult) -> Self {
+    pub fn new_with_type_result(type_result: &'a TypeCheckResult) -> Self {
         Self {
-            type_result: Some(Box::new(type_result.clone())),
-            release_plan: type_result.release_plan.drops.clone(),
+            type_result: Some(type_result),
             ..Self::new()
         }
     }
@@ -266,7 +399,7 @@ impl AstToIrGenerator {
                 if let ast::Expr::Var(name, _) = func.as_ref() {
                     // 首字母大写的标识符通常是类型构造器
                     if name.chars().next().is_some_and(|c| c.is_uppercase()) {
-                        return Some(name.clone());
+                        return Some(name.to_string());
                     }
                 }
                 None
@@ -286,6 +419,255 @@ impl AstToIrGenerator {
         self.constraint_var_concrete_types.get(var_name)
     }
 
+    /// 记录类型标注中的用户泛型实例，内层实例先于外层（`Box(Box(Int))` 先记 `Box(Int)`）
+    fn record_instance_types(
+        &mut self,
+        ty: &ast::Type,
+    ) {
+        match ty {
+            ast::Type::Generic { name, args, .. } => {
+                for arg in args {
+                    self.record_instance_types(arg);
+                }
+                let builtin =
+                    matches!(name.as_str(), "List" | "Dict" | "Set" | "Option" | "Result");
+                if builtin || self.mentions_type_param(ty) {
+                    return;
+                }
+                let instance = Self::canonical_instance_type(ty);
+                let mono = MonoType::from(instance.clone());
+                if !self
+                    .instance_types
+                    .iter()
+                    .any(|known| MonoType::from(known.clone()) == mono)
+                {
+                    self.instance_types.push(instance);
+                }
+            }
+            ast::Type::Option(inner) | ast::Type::Ref { inner, .. } => {
+                self.record_instance_types(inner)
+            }
+            ast::Type::Result(ok, err) => {
+                self.record_instance_types(ok);
+                self.record_instance_types(err);
+            }
+            ast::Type::Tuple(elems) => {
+                for elem in elems {
+                    self.record_instance_types(elem);
+                }
+            }
+            ast::Type::Fn {
+                params,
+                return_type,
+            } => {
+                for param in params {
+                    self.record_instance_types(param);
+                }
+                self.record_instance_types(return_type);
+            }
+            _ => {}
+        }
+    }
+
+    /// 把实例中的内置类型名换成对应的基本类型（`Box(Int)` 与 `Box(Int64)` 同为 `Box(int64)`），
+    /// 使同一实例在各模块的类型表中同名
+    fn canonical_instance_type(ty: &ast::Type) -> ast::Type {
+        match ty {
+            ast::Type::Name { name, .. } => match name.as_str() {
+                "Bool" => ast::Type::Bool,
+                "Char" => ast::Type::Char,
+                "String" => ast::Type::String,
+                "Bytes" => ast::Type::Bytes,
+                "Void" => ast::Type::Void,
+                _ => {
+                    match crate::frontend::core::typecheck::inference::numeric::numeric_type_named(
+                        name,
+                    ) {
+                        Some(MonoType::Int(bits)) => ast::Type::Int(bits),
+                        Some(MonoType::Float(bits)) => ast::Type::Float(bits),
+                        _ => ty.clone(),
+                    }
+                }
+            },
+            ast::Type::Generic {
+                name,
+                name_span,
+                args,
+            } => ast::Type::Generic {
+                name: name.clone(),
+                name_span: *name_span,
+                args: args.iter().map(Self::canonical_instance_type).collect(),
+            },
+            ast::Type::Option(inner) => {
+                ast::Type::Option(Box::new(Self::canonical_instance_type(inner)))
+            }
+            ast::Type::Result(ok, err) => ast::Type::Result(
+                Box::new(Self::canonical_instance_type(ok)),
+                Box::new(Self::canonical_instance_type(err)),
+            ),
+            ast::Type::Tuple(elems) => {
+                ast::Type::Tuple(elems.iter().map(Self::canonical_instance_type).collect())
+            }
+            _ => ty.clone(),
+        }
+    }
+
+    /// 类型中是否引用了当前函数的泛型类型参数
+    fn mentions_type_param(
+        &self,
+        ty: &ast::Type,
+    ) -> bool {
+        match ty {
+            ast::Type::Name { name, .. } => self.current_type_params.contains(name),
+            ast::Type::Generic { args, .. } | ast::Type::Tuple(args) => {
+                args.iter().any(|arg| self.mentions_type_param(arg))
+            }
+            ast::Type::Option(inner) | ast::Type::Ref { inner, .. } => {
+                self.mentions_type_param(inner)
+            }
+            ast::Type::Result(ok, err) => {
+                self.mentions_type_param(ok) || self.mentions_type_param(err)
+            }
+            ast::Type::Fn {
+                params,
+                return_type,
+            } => {
+                params.iter().any(|p| self.mentions_type_param(p))
+                    || self.mentions_type_param(return_type)
+            }
+            _ => false,
+        }
+    }
+
+    /// 记录带类型参数的结构体定义（如 `Box: (T: Type) -> Type = { value: T }`）
+    fn record_generic_type_def(
+        &mut self,
+        name: &str,
+        generic_params: &[ast::GenericParam],
+        definition: &ast::Type,
+    ) {
+        let type_params: Vec<String> = generic_params
+            .iter()
+            .filter(|p| matches!(p.kind, ast::GenericParamKind::Type))
+            .map(|p| p.name.clone())
+            .collect();
+        let fields = match definition {
+            ast::Type::Struct { fields, .. } | ast::Type::NamedStruct { fields, .. } => fields,
+            _ => return,
+        };
+        if type_params.is_empty() {
+            return;
+        }
+        self.generic_type_defs.push((
+            name.to_string(),
+            type_params,
+            ast::Type::NamedStruct {
+                name: name.to_string(),
+                name_span: Span::default(),
+                fields: fields.clone(),
+            },
+        ));
+    }
+
+    /// 接口的方法名（按声明顺序）；不是接口类型时返回 None
+    ///
+    /// 接口即除关联项外全部字段都是函数类型的类型定义。
+    fn interface_methods(
+        &self,
+        name: &str,
+    ) -> Option<Vec<String>> {
+        let fields = self.struct_definitions.get(name)?;
+        let mut methods = Vec::new();
+        for field in fields {
+            if matches!(field.ty, ast::Type::Fn { .. }) {
+                methods.push(field.name.clone());
+            } else if !field.name.starts_with(|c: char| c.is_uppercase()) {
+                return None;
+            }
+        }
+        (!methods.is_empty()).then_some(methods)
+    }
+
+    /// 表达式的静态类型为接口时返回接口名
+    fn expr_interface(
+        &self,
+        expr: &ast::Expr,
+    ) -> Option<String> {
+        let type_name = match expr {
+            ast::Expr::Var(name, _) => self
+                .local_var_types
+                .get(name.as_str())
+                .cloned()
+                .unwrap_or_else(|| self.get_expr_type_name(expr)),
+            _ => self.get_expr_type_name(expr),
+        };
+        self.interface_methods(&type_name).map(|_| type_name)
+    }
+
+    /// 类型为接口时返回接口名
+    fn type_interface(
+        &self,
+        ty: &MonoType,
+    ) -> Option<String> {
+        let name = match ty {
+            MonoType::TypeRef(name) => name.as_str(),
+            MonoType::Struct(s) => s.name.as_str(),
+            _ => return None,
+        };
+        self.interface_methods(name).map(|_| name.to_string())
+    }
+
+    /// 具体类型实现接口的虚表下标（首次使用时生成）
+    fn vtable_index(
+        &mut self,
+        interface: &str,
+        type_name: &str,
+    ) -> Option<usize> {
+        if let Some(idx) = self
+            .vtables
+            .iter()
+            .position(|v| v.interface == interface && v.type_name == type_name)
+        {
+            return Some(idx);
+        }
+        let methods = self.interface_methods(interface)?;
+        self.vtables.push(crate::middle::core::ir::VTable {
+            interface: interface.to_string(),
+            type_name: type_name.to_string(),
+            methods: methods
+                .iter()
+                .map(|m| format!("{}.{}", type_name, m))
+                .collect(),
+        });
+        Some(self.vtables.len() - 1)
+    }
+
+    /// 把 `reg` 中由 `value` 求得的值原地打包为接口的存在类型值
+    ///
+    /// 只有编译期能确定具体结构体类型时才打包；其余情况保持原值，
+    /// 由 InvokeVirtual 按方法名回退查找。
+    fn pack_dyn(
+        &mut self,
+        interface: &str,
+        value: &ast::Expr,
+        reg: usize,
+        instructions: &mut Vec<Instruction>,
+    ) {
+        let Some(type_name) = self.get_expr_struct_type_name(value) else {
+            return;
+        };
+        if type_name == interface || self.interface_methods(&type_name).is_some() {
+            return;
+        }
+        if let Some(vtable) = self.vtable_index(interface, &type_name) {
+            instructions.push(Instruction::MakeDyn {
+                dst: Operand::Local(reg),
+                src: Operand::Local(reg),
+                vtable,
+            });
+        }
+    }
+
     /// 注册局部变量
     fn register_local(
         &mut self,
@@ -299,7 +681,13 @@ impl AstToIrGenerator {
             &local_idx.to_string()
         );
         if let Some(scope) = self.symbols.last_mut() {
-            scope.insert(name.to_string(), SymbolEntry { local_idx });
+            scope.insert(
+                name.to_string(),
+                SymbolEntry {
+                    local_idx,
+                    boxed: false,
+                },
+            );
         }
         // 保存变量名到当前函数的局部变量名列表
         // 确保向量长度足够（可能有空洞）
@@ -310,12 +698,26 @@ impl AstToIrGenerator {
         self.current_local_names[local_idx] = name.to_string();
     }
 
-    /// 查找局部变量
+    /// 查找当前函数的局部变量（不含外层函数中可捕获的变量）
     fn lookup_local(
         &self,
         name: &str,
     ) -> Option<usize> {
-        for scope in self.symbols.iter().rev() {
+        self.lookup_local_entry(name).map(|entry| entry.local_idx)
+    }
+
+    /// 当前函数的作用域在 `symbols` 中的起点
+    fn function_scope_base(&self) -> usize {
+        self.closure_frames
+            .last()
+            .map_or(0, |frame| frame.scope_base)
+    }
+
+    fn lookup_local_entry(
+        &self,
+        name: &str,
+    ) -> Option<&SymbolEntry> {
+        for scope in self.symbols[self.function_scope_base()..].iter().rev() {
             if let Some(entry) = scope.get(name) {
                 tlog!(
                     debug,
@@ -323,13 +725,269 @@ impl AstToIrGenerator {
                     &name.to_string(),
                     &entry.local_idx.to_string()
                 );
-                return Some(entry.local_idx);
+                return Some(entry);
             }
         }
         tlog!(debug, MSG::IrGenLookupLocalNotFound, &name.to_string());
         None
     }
 
+    /// 名字是当前函数或外层函数的局部变量
+    fn is_local_var(
+        &self,
+        name: &str,
+    ) -> bool {
+        self.symbols.iter().any(|scope| scope.contains_key(name))
+    }
+
+    /// 解析变量：当前函数的局部变量，或经 upvalue 捕获的外层函数变量
+    fn resolve_var(
+        &mut self,
+        name: &str,
+    ) -> Option<VarSlot> {
+        if let Some(entry) = self.lookup_local_entry(name) {
+            return Some(VarSlot::Local {
+                idx: entry.local_idx,
+                boxed: entry.boxed,
+            });
+        }
+        let level = self.closure_frames.len().checked_sub(1)?;
+        self.resolve_upvalue(level, name)
+    }
+
+    /// 在第 `level` 层闭包中解析外层变量，必要时逐层登记捕获
+    fn resolve_upvalue(
+        &mut self,
+        level: usize,
+        name: &str,
+    ) -> Option<VarSlot> {
+        let frame = &self.closure_frames[level];
+        if let Some(idx) = frame.captures.iter().position(|c| c.name == name) {
+            let boxed = frame.captures[idx].source.is_boxed();
+            return Some(VarSlot::Upvalue { idx, boxed });
+        }
+
+        // 直接外层函数的作用域：[外层起点, 本层起点)
+        let outer_base = match level {
+            0 => 0,
+            _ => self.closure_frames[level - 1].scope_base,
+        };
+        let outer_local = self.symbols[outer_base..frame.scope_base]
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name))
+            .map(|entry| VarSlot::Local {
+                idx: entry.local_idx,
+                boxed: entry.boxed,
+            });
+        let source = match outer_local {
+            Some(slot) => slot,
+            None if level > 0 => self.resolve_upvalue(level - 1, name)?,
+            None => return None,
+        };
+
+        let frame = &mut self.closure_frames[level];
+        frame.captures.push(Capture {
+            name: name.to_string(),
+            source,
+        });
+        Some(VarSlot::Upvalue {
+            idx: frame.captures.len() - 1,
+            boxed: source.is_boxed(),
+        })
+    }
+
+    /// 读取变量到 `dst`；按引用捕获的变量从 cell 中取值
+    fn load_var(
+        &mut self,
+        slot: VarSlot,
+        dst: usize,
+        span: Span,
+        instructions: &mut Vec<Instruction>,
+    ) {
+        let target = if slot.is_boxed() {
+            self.next_temp_reg()
+        } else {
+            dst
+        };
+        match slot {
+            VarSlot::Local { idx, .. } => instructions.push(Instruction::Load {
+                dst: Operand::Local(target),
+                src: Operand::Local(idx),
+            }),
+            VarSlot::Upvalue { idx, .. } => instructions.push(Instruction::LoadUpvalue {
+                dst: Operand::Local(target),
+                upvalue_idx: idx,
+            }),
+        }
+        if slot.is_boxed() {
+            let index = self.cell_index(instructions);
+            instructions.push(Instruction::LoadIndex {
+                dst: Operand::Local(dst),
+                src: Operand::Local(target),
+                index,
+                span,
+            });
+        }
+    }
+
+    /// 把 `src` 写入变量；按引用捕获的变量写入 cell
+    fn store_var(
+        &mut self,
+        slot: VarSlot,
+        src: usize,
+        span: Span,
+        instructions: &mut Vec<Instruction>,
+    ) {
+        if !slot.is_boxed() {
+            instructions.push(match slot {
+                VarSlot::Local { idx, .. } => Instruction::Store {
+                    dst: Operand::Local(idx),
+                    src: Operand::Local(src),
+                    span,
+                },
+                VarSlot::Upvalue { idx, .. } => Instruction::StoreUpvalue {
+                    src: Operand::Local(src),
+                    upvalue_idx: idx,
+                },
+            });
+            return;
+        }
+        let cell = self.next_temp_reg();
+        match slot {
+            VarSlot::Local { idx, .. } => instructions.push(Instruction::Load {
+                dst: Operand::Local(cell),
+                src: Operand::Local(idx),
+            }),
+            VarSlot::Upvalue { idx, .. } => instructions.push(Instruction::LoadUpvalue {
+                dst: Operand::Local(cell),
+                upvalue_idx: idx,
+            }),
+        }
+        let index = self.cell_index(instructions);
+        instructions.push(Instruction::StoreIndex {
+            dst: Operand::Local(cell),
+            index,
+            src: Operand::Local(src),
+            span,
+        });
+    }
+
+    /// cell 中值的下标
+    fn cell_index(
+        &mut self,
+        instructions: &mut Vec<Instruction>,
+    ) -> Operand {
+        let reg = self.next_temp_reg();
+        instructions.push(Instruction::Load {
+            dst: Operand::Local(reg),
+            src: Operand::Const(ConstValue::Int(0)),
+        });
+        Operand::Local(reg)
+    }
+
+    /// 变量按引用捕获时，把刚声明的局部变量装箱
+    fn box_local_if_captured(
+        &mut self,
+        name: &str,
+        idx: usize,
+        span: Span,
+        instructions: &mut Vec<Instruction>,
+    ) {
+        if self.boxed_vars.contains(name) {
+            self.box_local(name, idx, span, instructions);
+        }
+    }
+
+    /// 为被按引用捕获的参数装箱；参数 `i` 位于局部槽 `i`
+    fn box_captured_params(
+        &mut self,
+        params: &[ast::Param],
+        instructions: &mut Vec<Instruction>,
+    ) {
+        for (i, param) in params.iter().enumerate() {
+            self.box_local_if_captured(&param.name, i, Span::dummy(), instructions);
+        }
+    }
+
+    /// 把局部变量 `name` 的值装入新的 cell，槽中改存 cell
+    fn box_local(
+        &mut self,
+        name: &str,
+        idx: usize,
+        span: Span,
+        instructions: &mut Vec<Instruction>,
+    ) {
+        let cell = self.next_temp_reg();
+        instructions.push(Instruction::AllocArray {
+            dst: Operand::Local(cell),
+            size: Operand::Const(ConstValue::Int(1)),
+            elem_size: Operand::Const(ConstValue::Int(1)),
+        });
+        self.current_mut_locals.insert(cell);
+        let index = self.cell_index(instructions);
+        instructions.push(Instruction::StoreIndex {
+            dst: Operand::Local(cell),
+            index,
+            src: Operand::Local(idx),
+            span,
+        });
+        instructions.push(Instruction::Store {
+            dst: Operand::Local(idx),
+            src: Operand::Local(cell),
+            span,
+        });
+        let base = self.function_scope_base();
+        if let Some(entry) = self.symbols[base..]
+            .iter_mut()
+            .rev()
+            .find_map(|scope| scope.get_mut(name))
+        {
+            entry.boxed = true;
+        }
+    }
+
+    /// 进入闭包函数体：之后注册的变量属于闭包，外层变量经 upvalue 访问
+    fn enter_closure(&mut self) {
+        self.closure_frames.push(ClosureFrame {
+            scope_base: self.symbols.len(),
+            captures: Vec::new(),
+        });
+    }
+
+    /// 退出闭包函数体，返回其捕获的变量
+    fn exit_closure(&mut self) -> Vec<Capture> {
+        self.closure_frames
+            .pop()
+            .map(|frame| frame.captures)
+            .unwrap_or_default()
+    }
+
+    /// 在外层函数中按捕获顺序取出各变量的当前值（按引用捕获的取 cell），作为闭包环境
+    fn capture_env(
+        &mut self,
+        captures: &[Capture],
+        instructions: &mut Vec<Instruction>,
+    ) -> Vec<Operand> {
+        captures
+            .iter()
+            .map(|capture| {
+                let reg = self.next_temp_reg();
+                instructions.push(match capture.source {
+                    VarSlot::Local { idx, .. } => Instruction::Load {
+                        dst: Operand::Local(reg),
+                        src: Operand::Local(idx),
+                    },
+                    VarSlot::Upvalue { idx, .. } => Instruction::LoadUpvalue {
+                        dst: Operand::Local(reg),
+                        upvalue_idx: idx,
+                    },
+                });
+                Operand::Local(reg)
+            })
+            .collect()
+    }
+
     /// 查找全局变量
     fn lookup_global(
         &self,
@@ -348,7 +1006,7 @@ impl AstToIrGenerator {
         &self,
         name: &str,
     ) -> Option<&PolyType> {
-        if let Some(ref type_result) = self.type_result {
+        if let Some(type_result) = self.type_result {
             // 调试：打印所有绑定
             tracing::debug!("Looking for variable '{}' in bindings", name);
             tracing::debug!("All bindings: {:?}", type_result.bindings);
@@ -369,34 +1027,30 @@ impl AstToIrGenerator {
     // 原因：根据设计文档，不再需要复杂的类型名提取逻辑
     // 方法调用现在直接生成简单函数名（方法名）
 
-    /// 解析字段索引
+    /// 解析字段所属的结构体与字段索引
     ///
     /// 从类型信息和结构体定义中动态查找字段在结构体中的位置。
     /// 查找顺序：
     /// 1. 从表达式的类型推导出结构体名，再从 struct_definitions 查找字段索引
     /// 2. 遍历所有结构体定义查找匹配的字段名（兜底）
-    fn resolve_field_index(
+    fn resolve_field(
         &self,
         expr: &ast::Expr,
         field_name: &str,
-    ) -> Option<usize> {
+    ) -> Option<(String, usize)> {
         // 1. 尝试从表达式类型推导结构体名，精确查找
         if let Some(type_name) = self.get_expr_struct_type_name(expr) {
             if let Some(fields) = self.struct_definitions.get(&type_name) {
-                for (i, field) in fields.iter().enumerate() {
-                    if field.name == field_name {
-                        return Some(i);
-                    }
+                if let Some(i) = fields.iter().position(|field| field.name == field_name) {
+                    return Some((type_name, i));
                 }
             }
         }
 
         // 2. 兜底：遍历所有结构体定义查找字段名（当类型推导不可用时）
-        for fields in self.struct_definitions.values() {
-            for (i, field) in fields.iter().enumerate() {
-                if field.name == field_name {
-                    return Some(i);
-                }
+        for (type_name, fields) in &self.struct_definitions {
+            if let Some(i) = fields.iter().position(|field| field.name == field_name) {
+                return Some((type_name.clone(), i));
             }
         }
 
@@ -406,7 +1060,7 @@ impl AstToIrGenerator {
 
     /// 从表达式推导其结构体类型名称
     ///
-    /// 用于 resolve_field_index 等需要知道表达式类型的场景
+    /// 用于 resolve_field 等需要知道表达式类型的场景
     fn get_expr_struct_type_name(
         &self,
         expr: &ast::Expr,
@@ -414,8 +1068,8 @@ impl AstToIrGenerator {
         match expr {
             ast::Expr::Var(name, _) => {
                 // 从类型检查结果查找变量类型
-                if let Some(ref type_result) = self.type_result {
-                    if let Some(mono_type) = type_result.local_var_types.get(name) {
+                if let Some(type_result) = self.type_result {
+                    if let Some(mono_type) = type_result.local_var_types.get(name.as_str()) {
                         return Self::mono_type_to_struct_name(mono_type);
                     }
                 }
@@ -425,7 +1079,7 @@ impl AstToIrGenerator {
                     return Self::mono_type_to_struct_name(&mono_type);
                 }
                 // 从 IR 生成器追踪的类型查找
-                if let Some(type_name) = self.local_var_types.get(name) {
+                if let Some(type_name) = self.local_var_types.get(name.as_str()) {
                     if self.struct_definitions.contains_key(type_name) {
                         return Some(type_name.clone());
                     }
@@ -435,8 +1089,8 @@ impl AstToIrGenerator {
             ast::Expr::Call { func, .. } => {
                 // 构造器调用：Point(...) -> 类型名为 "Point"
                 if let ast::Expr::Var(name, _) = func.as_ref() {
-                    if self.struct_definitions.contains_key(name) {
-                        return Some(name.clone());
+                    if self.struct_definitions.contains_key(name.as_str()) {
+                        return Some(name.to_string());
                     }
                 }
                 None
@@ -470,8 +1124,75 @@ impl AstToIrGenerator {
         let mut errors = Vec::new();
         let mut constants = Vec::new();
 
+        // 预先收集关联常量，使定义之前的访问点也能识别
+        for stmt in &module.items {
+            if let ast::StmtKind::Binding {
+                name,
+                type_name: Some(host),
+                method_type: Some(item_ty),
+                ..
+            } = &stmt.kind
+            {
+                if ast::is_assoc_item_type(item_ty) && !ast::is_meta_type(item_ty) {
+                    self.assoc_consts.insert(format!("{}.{}", host, name));
+                }
+            }
+        }
+
+        // 预先收集顶层绑定，使定义之前的调用点也不会解析到标准库的同名函数
+        for stmt in &module.items {
+            if let ast::StmtKind::Binding {
+                name,
+                type_name: None,
+                ..
+            } = &stmt.kind
+            {
+                self.module_bindings.insert(name.clone());
+            }
+        }
+
+        // 预先收集 const 泛型函数模板，模板本身不生成代码
+        for stmt in &module.items {
+            if let ast::StmtKind::Binding {
+                name,
+                type_name: None,
+                generic_params,
+                type_annotation,
+                ..
+            } = &stmt.kind
+            {
+                if !ast::const_generic_params(generic_params, type_annotation.as_ref()).is_empty() {
+                    self.const_generic_fns.insert(name.clone(), stmt.clone());
+                }
+            }
+        }
+
+        let mut inline_hints = HashMap::new();
+        let mut exports = Vec::new();
         for stmt in &module.items {
             match self.generate_stmt_ir(stmt, &mut constants) {
+                Ok(Some(func_ir)) => {
+                    if let ast::StmtKind::Binding {
+                        attributes, is_pub, ..
+                    } = &stmt.kind
+                    {
+                        if let Some(hint) = InlineHint::from_attributes(attributes) {
+                            inline_hints.insert(func_ir.name.clone(), hint);
+                        }
+                        if *is_pub {
+                            exports.push(func_ir.name.clone());
+                        }
+                    }
+                    functions.push(func_ir)
+                }
+                Ok(None) => {}
+                Err(e) => errors.push(e),
+            }
+        }
+
+        // 生成调用处请求的 const 泛型特化（特化体内的调用可能继续请求新的特化）
+        while let Some((instance, template, values)) = self.pending_const_instances.pop() {
+            match self.generate_const_instance_ir(&instance, &template, &values, &mut constants) {
                 Ok(Some(func_ir)) => functions.push(func_ir),
                 Ok(None) => {}
                 Err(e) => errors.push(e),
@@ -489,7 +1210,8 @@ impl AstToIrGenerator {
         functions.extend(std::mem::take(&mut self.anon_function_irs));
 
         Ok(ModuleIR {
-            types: Vec::new(),
+            types: std::mem::take(&mut self.instance_types),
+            generic_types: std::mem::take(&mut self.generic_type_defs),
             globals: Vec::new(),
             functions,
             mut_locals: std::mem::take(&mut self.module_mut_locals),
@@ -497,9 +1219,34 @@ impl AstToIrGenerator {
             local_names: std::mem::take(&mut self.module_local_names),
             ffi_libs: std::mem::take(&mut self.ffi_libs),
             ffi_bindings: std::mem::take(&mut self.ffi_bindings),
+            vtables: std::mem::take(&mut self.vtables),
+            struct_layouts: self.struct_layouts(),
+            inline_hints,
+            exports,
+            upvalue_counts: std::mem::take(&mut self.upvalue_counts),
         })
     }
 
+    /// 已定义结构体的布局（按类型名排序，保证输出稳定）
+    fn struct_layouts(&self) -> Vec<crate::middle::core::ir::StructLayout> {
+        let mut engine = LayoutEngine::new();
+        for (name, fields) in &self.struct_definitions {
+            engine.define(
+                name.clone(),
+                fields
+                    .iter()
+                    .map(|field| (field.name.clone(), field.ty.clone().into()))
+                    .collect(),
+            );
+        }
+        let mut names: Vec<&String> = self.struct_definitions.keys().collect();
+        names.sort();
+        names
+            .into_iter()
+            .filter_map(|name| engine.struct_layout(name))
+            .collect()
+    }
+
     /// 生成语句的 IR
     fn generate_stmt_ir(
         &mut self,
@@ -516,10 +1263,14 @@ impl AstToIrGenerator {
                 params,
                 body,
                 is_pub: _,
+                attributes: _,
             } => {
                 // 区分函数定义、方法绑定和类型定义
-                if type_name.is_some() {
-                    // MethodBind: 有 type_name
+                if method_type.as_ref().is_some_and(ast::is_meta_type) {
+                    // 关联类型：只在类型检查期存在，不生成代码
+                    Ok(None)
+                } else if type_name.is_some() {
+                    // MethodBind: 有 type_name（关联常量也走这里，生成零参函数）
                     self.generate_method_ir(
                         type_name.as_ref().unwrap(),
                         name,
@@ -552,18 +1303,21 @@ impl AstToIrGenerator {
                     self.generate_constructor_ir(name, type_annotation.as_ref().unwrap())
                 } else if type_annotation.as_ref().is_some_and(|t| {
                     crate::frontend::core::parser::ast::type_annotation_returns_meta_type(t)
-                        || matches!(t, ast::Type::Struct { .. })
+                        || matches!(t, ast::Type::Struct { .. } | ast::Type::Newtype(_))
                 }) {
                     // TypeDef: 类型标注返回 Type 或者是 Struct 类型
+                    self.record_generic_type_def(
+                        name,
+                        generic_params,
+                        type_annotation.as_ref().unwrap(),
+                    );
                     self.generate_constructor_ir(name, type_annotation.as_ref().unwrap())
+                } else if self.const_generic_fns.contains_key(name) {
+                    // const 泛型函数模板：由调用处按常量实参生成特化
+                    Ok(None)
                 } else {
                     // Fn: 普通函数
-                    // 从 GenericParam 提取参数名字符串
-                    let generic_param_names = if generic_params.is_empty() {
-                        None
-                    } else {
-                        Some(generic_params.iter().map(|p| p.name.clone()).collect())
-                    };
+                    let generic_param_names = Self::type_param_names(generic_params);
                     self.generate_function_ir(
                         name,
                         type_annotation.as_ref(),
@@ -618,31 +1372,27 @@ impl AstToIrGenerator {
         self.current_mut_locals.clear();
         // 重置当前函数的局部变量名列表
         self.current_local_names.clear();
+        self.boxed_vars = closure::boxed_variables(params, body);
 
         // 命名空间机制：方法函数名 = Type.method
         // 例如：Point.get_x 生成函数名 "Point.get_x"
         // 调用时：p.get_x() -> Point.get_x(p)
         let func_name = format!("{}.{}", type_name, method_name);
 
-        // 注册方法到 type_bindings，使方法调用脱糖能找到绑定
-        let binding_entry = ast::TypeBodyBinding {
-            name: method_name.to_string(),
-            kind: ast::BindingKind::DefaultExternal {
-                function: func_name.clone(),
-            },
-        };
-        self.register_type_bindings(type_name, &[binding_entry]);
-
         // 解析返回类型
         let return_type = if let ast::Type::Fn { return_type, .. } = method_type {
+            // 注册方法到 type_bindings，使方法调用脱糖能找到绑定
+            let binding_entry = ast::TypeBodyBinding {
+                name: method_name.to_string(),
+                kind: ast::BindingKind::DefaultExternal {
+                    function: func_name.clone(),
+                },
+            };
+            self.register_type_bindings(type_name, &[binding_entry]);
             (**return_type).clone().into()
         } else {
-            // 非函数类型，报错
-            return Err(ErrorCodeDefinition::ir_internal_error(&format!(
-                "Method {} is not a function type",
-                method_name
-            ))
-            .build());
+            // 关联常量：`Type.NAME` 由访问点改写为零参调用，不进入方法脱糖表
+            method_type.clone().into()
         };
 
         // 进入新作用域
@@ -673,6 +1423,7 @@ impl AstToIrGenerator {
 
         // 生成指令序列
         let mut instructions = Vec::new();
+        self.box_captured_params(params, &mut instructions);
 
         // 生成语句 IR
         for stmt in body {
@@ -747,12 +1498,29 @@ impl AstToIrGenerator {
         self.current_mut_locals.clear();
         // 重置当前函数的局部变量名列表
         self.current_local_names.clear();
+        self.boxed_vars = closure::boxed_variables(params, body);
+        // 记录类型为泛型参数的形参（约束方法静态分发）
+        self.generic_param_vars = match generic_params.as_deref() {
+            Some(names) => Self::collect_generic_param_vars(type_annotation, params, names),
+            None => HashMap::new(),
+        };
+        self.current_type_params = generic_params.clone().unwrap_or_default();
+        self.record_vars = Self::collect_record_param_vars(type_annotation, params);
+        if let Some(ty) = type_annotation {
+            self.record_instance_types(ty);
+        }
+        for param in params {
+            if let Some(ty) = &param.ty {
+                self.record_instance_types(ty);
+            }
+        }
         // 阶段3修复：改进返回类型解析，更好地与类型检查集成
         let return_type = match type_annotation {
             Some(ast::Type::Fn { return_type, .. }) => (**return_type).clone().into(),
             Some(ty) => ty.clone().into(),
             None => MonoType::Void,
         };
+        self.returns_result = matches!(return_type, MonoType::Result(..));
 
         // 生成函数体指令
         let mut instructions = Vec::new();
@@ -799,6 +1567,7 @@ impl AstToIrGenerator {
                 });
             return Ok(None);
         }
+        self.box_captured_params(params, &mut instructions);
 
         // 生成语句 IR
         for stmt in body {
@@ -808,8 +1577,11 @@ impl AstToIrGenerator {
                 &self.symbols.len().to_string()
             );
             self.generate_local_stmt_ir(stmt, &mut instructions, constants)?;
-            // NLL Release: 在语句边界插入 Drop 指令
-            if let Some(vars) = self.release_plan.get(&stmt.span) {
+            // NLL Release: 按所有权检查器的释放计划在语句边界插入 Drop 指令
+            let release = self
+                .type_result
+                .and_then(|tr| tr.release_plan.drops.get(&stmt.span));
+            if let Some(vars) = release {
                 for var in vars {
                     if let Some(local_idx) = self.lookup_local(var) {
                         instructions.push(Instruction::Drop(Operand::Local(local_idx)));
@@ -872,34 +1644,236 @@ impl AstToIrGenerator {
             generic_params,
         };
 
-        // 记录函数参数类型（用于调用点发出 Borrow 指令）
-        let func_params: Vec<MonoType> = params
+        // 记录函数参数类型（用于调用点发出 Borrow 指令）
+        let func_params: Vec<MonoType> = params
+            .iter()
+            .filter_map(|p| p.ty.clone())
+            .map(|t| t.into())
+            .collect();
+        self.function_param_types
+            .insert(name.to_string(), func_params);
+
+        // 保存当前函数的可变局部变量信息到模块级别映射
+        if !self.current_mut_locals.is_empty() {
+            self.module_mut_locals
+                .insert(name.to_string(), self.current_mut_locals.clone());
+        }
+
+        // 保存当前函数的循环绑定变量信息到模块级别映射
+        if !self.current_loop_binding_locals.is_empty() {
+            self.module_loop_binding_locals
+                .insert(name.to_string(), self.current_loop_binding_locals.clone());
+        }
+
+        // 保存当前函数的局部变量名列表
+        self.module_local_names.insert(
+            name.to_string(),
+            std::mem::take(&mut self.current_local_names),
+        );
+
+        Ok(Some(func_ir))
+    }
+
+    /// 解析关联常量访问，返回要调用的零参函数名
+    fn resolve_assoc_const_access(
+        &self,
+        expr: &ast::Expr,
+        field: &str,
+    ) -> Option<String> {
+        let ast::Expr::Var(name, _) = expr else {
+            return None;
+        };
+        if let Some(type_param) = self.generic_param_vars.get(name.as_str()) {
+            return Some(format!("{}.{}", type_param, field));
+        }
+        let qualified = format!("{}.{}", name, field);
+        (!self.is_local_var(name) && self.assoc_consts.contains(&qualified)).then_some(qualified)
+    }
+
+    /// 函数的类型参数名；没有时为 `None`
+    ///
+    /// `(a: Int, b: Int) -> Int` 的形参在语法上与 const 泛型参数相同，不算作类型参数
+    /// （const 泛型函数作为模板另行特化）。
+    fn type_param_names(generic_params: &[ast::GenericParam]) -> Option<Vec<String>> {
+        let names: Vec<String> = generic_params
+            .iter()
+            .filter(|p| matches!(p.kind, ast::GenericParamKind::Type))
+            .map(|p| p.name.clone())
+            .collect();
+        (!names.is_empty()).then_some(names)
+    }
+
+    /// 收集泛型函数中类型为泛型参数的形参
+    ///
+    /// 泛型函数的类型标注形如 `(T: Show) -> (value: T) -> String`，
+    /// 值级形参类型位于内层函数类型中。
+    fn collect_generic_param_vars(
+        type_annotation: Option<&ast::Type>,
+        params: &[ast::Param],
+        generic_params: &[String],
+    ) -> HashMap<String, String> {
+        fn type_param_name(
+            ty: &ast::Type,
+            generic_params: &[String],
+        ) -> Option<String> {
+            match ty {
+                ast::Type::Name { name, .. } if generic_params.contains(name) => Some(name.clone()),
+                ast::Type::Ref { inner, .. } => type_param_name(inner, generic_params),
+                _ => None,
+            }
+        }
+
+        let value_param_types = match type_annotation {
+            Some(ast::Type::Fn { return_type, .. }) => match return_type.as_ref() {
+                ast::Type::Fn { params, .. } => params.as_slice(),
+                _ => &[],
+            },
+            _ => &[],
+        };
+
+        params
             .iter()
-            .filter_map(|p| p.ty.clone())
-            .map(|t| t.into())
-            .collect();
-        self.function_param_types
-            .insert(name.to_string(), func_params);
+            .zip(value_param_types)
+            .filter_map(|(param, ty)| {
+                type_param_name(ty, generic_params).map(|t| (param.name.clone(), t))
+            })
+            .collect()
+    }
 
-        // 保存当前函数的可变局部变量信息到模块级别映射
-        if !self.current_mut_locals.is_empty() {
-            self.module_mut_locals
-                .insert(name.to_string(), self.current_mut_locals.clone());
+    /// 收集类型为匿名记录（如 `{ x: Int, y: Int }`）的形参
+    fn collect_record_param_vars(
+        type_annotation: Option<&ast::Type>,
+        params: &[ast::Param],
+    ) -> std::collections::HashSet<String> {
+        let annotated = match type_annotation {
+            Some(ast::Type::Fn { params, .. }) => params.as_slice(),
+            _ => &[],
+        };
+        params
+            .iter()
+            .enumerate()
+            .filter(|(i, param)| {
+                param
+                    .ty
+                    .as_ref()
+                    .or_else(|| annotated.get(*i))
+                    .is_some_and(|ty| MonoType::from(ty.clone()).is_record())
+            })
+            .map(|(_, param)| param.name.clone())
+            .collect()
+    }
+
+    /// const 泛型函数应用（如 `scale(4)`）对应的特化名，首次请求时排队生成
+    ///
+    /// const 实参必须能在编译期求值。
+    fn request_const_instance(
+        &mut self,
+        func: &ast::Expr,
+    ) -> Result<Option<String>, Diagnostic> {
+        let ast::Expr::Call {
+            func: template,
+            args,
+            span,
+            ..
+        } = func
+        else {
+            return Ok(None);
+        };
+        let ast::Expr::Var(name, _) = template.as_ref() else {
+            return Ok(None);
+        };
+        if self.is_local_var(name) || !self.const_generic_fns.contains_key(name.as_str()) {
+            return Ok(None);
         }
 
-        // 保存当前函数的循环绑定变量信息到模块级别映射
-        if !self.current_loop_binding_locals.is_empty() {
-            self.module_loop_binding_locals
-                .insert(name.to_string(), self.current_loop_binding_locals.clone());
+        let mut values = Vec::with_capacity(args.len());
+        for arg in args {
+            let value = self
+                .const_eval
+                .eval_scoped(arg, &|local| self.is_local_var(local))
+                .ok_or_else(|| {
+                    ErrorCodeDefinition::const_eval_failed(&format!(
+                        "argument of const generic function '{}' is not a compile-time constant",
+                        name
+                    ))
+                    .at(*span)
+                    .build()
+                })?;
+            values.push(value);
         }
 
-        // 保存当前函数的局部变量名列表
-        self.module_local_names.insert(
-            name.to_string(),
-            std::mem::take(&mut self.current_local_names),
-        );
+        let instance = Self::const_instance_name(name, &values);
+        if self.const_instance_names.insert(instance.clone()) {
+            self.pending_const_instances
+                .push((instance.clone(), name.to_string(), values));
+        }
+        Ok(Some(instance))
+    }
 
-        Ok(Some(func_ir))
+    /// const 泛型特化名：`scale[4]`
+    fn const_instance_name(
+        name: &str,
+        values: &[ConstValue],
+    ) -> String {
+        let args: Vec<String> = values
+            .iter()
+            .map(|value| match value {
+                ConstValue::Int(n) => n.to_string(),
+                ConstValue::Bool(b) => b.to_string(),
+                ConstValue::Float(f) => f.to_string(),
+                other => format!("{:?}", other),
+            })
+            .collect();
+        format!("{}[{}]", name, args.join(", "))
+    }
+
+    /// 生成 const 泛型函数的一个特化：const 参数作为编译期常量代入函数体
+    fn generate_const_instance_ir(
+        &mut self,
+        instance: &str,
+        template: &str,
+        values: &[ConstValue],
+        constants: &mut Vec<ConstValue>,
+    ) -> Result<Option<FunctionIR>, Diagnostic> {
+        let Some(ast::StmtKind::Binding {
+            generic_params,
+            type_annotation,
+            params,
+            body,
+            ..
+        }) = self
+            .const_generic_fns
+            .get(template)
+            .map(|stmt| stmt.kind.clone())
+        else {
+            return Ok(None);
+        };
+
+        let const_names: Vec<String> =
+            ast::const_generic_params(&generic_params, type_annotation.as_ref())
+                .iter()
+                .map(|p| p.name.clone())
+                .collect();
+        // 特化后的签名是内层函数类型
+        let inner_type = match &type_annotation {
+            Some(ast::Type::Fn { return_type, .. }) => Some((**return_type).clone()),
+            _ => None,
+        };
+
+        let saved = self.const_eval.clone();
+        for (name, value) in const_names.iter().zip(values) {
+            self.const_eval.define(name.clone(), value.clone());
+        }
+        let result = self.generate_function_ir(
+            instance,
+            inner_type.as_ref(),
+            &params,
+            &body,
+            constants,
+            None,
+        );
+        self.const_eval = saved;
+        result
     }
 
     /// 尝试将表达式求值为编译时常量
@@ -913,49 +1887,23 @@ impl AstToIrGenerator {
                 ast::Literal::Int(n) => Some(ConstValue::Int(*n)),
                 ast::Literal::Float(f) => Some(ConstValue::Float(*f)),
                 ast::Literal::Bool(b) => Some(ConstValue::Bool(*b)),
-                ast::Literal::String(s) => Some(ConstValue::String(s.clone())),
+                ast::Literal::String(s) => Some(ConstValue::String(s.to_string())),
                 ast::Literal::Char(c) => Some(ConstValue::Char(*c)),
+                ast::Literal::Void => Some(ConstValue::Void),
             },
-            // RFC-012: F-string 常量求值
-            ast::Expr::FString { segments, .. } => {
-                let mut result = String::new();
-                for seg in segments {
-                    match seg {
-                        ast::FStringSegment::Text(s) => result.push_str(s),
-                        ast::FStringSegment::Interpolation { expr, format_spec } => {
-                            let val = self.eval_const_expr(expr)?;
-                            let val_str = match &val {
-                                ConstValue::Int(n) => n.to_string(),
-                                ConstValue::Float(f) => f.to_string(),
-                                ConstValue::Bool(b) => b.to_string(),
-                                ConstValue::String(s) => s.clone(),
-                                ConstValue::Char(c) => c.to_string(),
-                                ConstValue::Void => String::new(),
-                                ConstValue::Bytes(b) => format!("{:?}", b),
-                                ConstValue::LibraryRef { .. } | ConstValue::ExternRef { .. } => {
-                                    // FFI types cannot be formatted at compile time
-                                    return None;
-                                }
-                            };
-                            // 格式化说明符在常量求值中不处理，遇到则退回运行时
-                            if format_spec.is_some() {
-                                return None;
-                            }
-                            result.push_str(&val_str);
-                        }
-                    }
-                }
-                Some(ConstValue::String(result))
-            }
             // 编译期 FFI 求值：Native.c("lib") → ConstValue::LibraryRef
             // lib("sym") → ConstValue::ExternRef
-            ast::Expr::Call { func: _, args, .. } => {
+            ast::Expr::Call { func, args, .. } => {
+                // f-string 脱糖后的格式化调用
+                if let Some(text) = self.eval_const_format(expr) {
+                    return Some(ConstValue::String(text));
+                }
                 // 通过 type_result 检查调用表达式的推断类型
                 if let Some(expr_type) = self.get_expr_mono_type(expr) {
                     match expr_type {
-                        MonoType::LibraryRef { mechanism, .. } => {
+                        MonoType::LibraryRef { mechanism, .. }
                             // Native.c("lib") — 需要提取 lib 名字符串
-                            if args.len() == 1 {
+                            if args.len() == 1 => {
                                 if let Some(lib_str) = Self::extract_string_arg(args) {
                                     return Some(ConstValue::LibraryRef {
                                         mechanism,
@@ -963,7 +1911,6 @@ impl AstToIrGenerator {
                                     });
                                 }
                             }
-                        }
                         MonoType::ExternRef { mechanism, lib, .. }
                             // lib("sym") — 需要提取 symbol 名字符串
                             if args.len() == 1 => {
@@ -978,17 +1925,151 @@ impl AstToIrGenerator {
                         _ => {}
                     }
                 }
+                // 推断类型缺失时按写法识别 Native.rs("sym")
+                if let ast::Expr::FieldAccess {
+                    expr: ns, field, ..
+                } = func.as_ref()
+                {
+                    if field == "rs" && matches!(ns.as_ref(), ast::Expr::Var(n, _) if n == "Native")
+                    {
+                        return Self::extract_string_arg(args).map(|symbol| {
+                            ConstValue::ExternRef {
+                                mechanism: "rs".to_string(),
+                                lib: String::new(),
+                                symbol,
+                            }
+                        });
+                    }
+                }
                 None
             }
-            // TODO: 支持更复杂的常量表达式
-            _ => None,
+            // 复合常量：所有元素均为常量时整体求值
+            ast::Expr::List(elements, _) => elements
+                .iter()
+                .map(|e| self.eval_const_element(e))
+                .collect::<Option<Vec<_>>>()
+                .map(ConstValue::List),
+            ast::Expr::Dict(pairs, _) => pairs
+                .iter()
+                .map(|(k, v)| Some((self.eval_const_element(k)?, self.eval_const_element(v)?)))
+                .collect::<Option<Vec<_>>>()
+                .map(ConstValue::Dict),
+            // 字面量运算、已知常量、常量条件等交给常量求值器
+            _ => self.const_eval.eval(expr),
+        }
+    }
+
+    /// 实参全为常量的 `std.string.format` 调用在编译期求值
+    ///
+    /// 常量按运行时的默认格式转为文本；带格式说明符的占位符以及运行时显示方式不同的
+    /// 常量（`void`、字节串、复合常量）留给运行时。
+    fn eval_const_format(
+        &self,
+        expr: &ast::Expr,
+    ) -> Option<String> {
+        let ast::Expr::Call {
+            func,
+            args,
+            named_args,
+            ..
+        } = expr
+        else {
+            return None;
+        };
+        let ast::Expr::FieldAccess {
+            expr: namespace,
+            field,
+            ..
+        } = func.as_ref()
+        else {
+            return None;
+        };
+        if !named_args.is_empty()
+            || !is_namespace_call(&self.std_registry, namespace)
+            || extract_namespace_path(&self.std_registry, namespace, field) != "std.string.format"
+        {
+            return None;
+        }
+        let (format, values) = args.split_first()?;
+        let ast::Expr::Lit(ast::Literal::String(format), _) = format else {
+            return None;
+        };
+        let values = values
+            .iter()
+            .map(|value| match self.eval_const_expr(value)? {
+                ConstValue::Int(n) => Some(n.to_string()),
+                ConstValue::Float(f) => Some(crate::std::string::format_float_default(f)),
+                ConstValue::Bool(b) => Some(b.to_string()),
+                ConstValue::String(s) => Some(s),
+                ConstValue::Char(c) => Some(c.to_string()),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>()?;
+        let mut folded = true;
+        let text = crate::std::string::parse_format(format, |index, spec| {
+            match values.get(index).filter(|_| spec.is_empty()) {
+                Some(value) => value.clone(),
+                None => {
+                    folded = false;
+                    String::new()
+                }
+            }
+        });
+        folded.then_some(text)
+    }
+
+    /// 复合常量的元素：除 `eval_const_expr` 能求值的表达式外，
+    /// 实参全为常量的结构体构造也整体求值（只出现在复合常量内部）
+    fn eval_const_element(
+        &self,
+        expr: &ast::Expr,
+    ) -> Option<ConstValue> {
+        if let ast::Expr::Call {
+            func,
+            args,
+            named_args,
+            ..
+        } = expr
+        {
+            if let ast::Expr::Var(name, _) = func.as_ref() {
+                if let Some(fields) = self.struct_definitions.get(name.as_str()) {
+                    if self.is_local_var(name)
+                        || self.interface_methods(name).is_some()
+                        || args.len() > fields.len()
+                    {
+                        return None;
+                    }
+                    // 位置参数、命名参数、默认值依次填充，与构造调用的 IR 一致
+                    let mut values: Vec<Option<ConstValue>> = vec![None; fields.len()];
+                    for (slot, arg) in values.iter_mut().zip(args) {
+                        *slot = Some(self.eval_const_element(arg)?);
+                    }
+                    for (arg_name, arg) in named_args {
+                        let idx = fields.iter().position(|f| &f.name == arg_name)?;
+                        values[idx] = Some(self.eval_const_element(arg)?);
+                    }
+                    let fields = values
+                        .into_iter()
+                        .zip(fields)
+                        .map(|(value, field)| match value {
+                            Some(value) => Some(value),
+                            None => self.eval_const_element(field.default.as_ref()?),
+                        })
+                        .collect::<Option<Vec<_>>>()?;
+                    return Some(ConstValue::Struct {
+                        type_name: name.to_string(),
+                        fields,
+                    });
+                }
+            }
         }
+        self.eval_const_expr(expr)
     }
 
     /// 从函数调用的参数列表中提取第一个字符串字面量
     fn extract_string_arg(args: &[ast::Expr]) -> Option<String> {
         args.first().and_then(|arg| match arg {
-            ast::Expr::Lit(ast::Literal::String(s), _) => Some(s.clone()),
+            ast::Expr::Lit(ast::Literal::String(s), _) => Some(s.to_string()),
             _ => None,
         })
     }
@@ -1064,6 +2145,18 @@ impl AstToIrGenerator {
             None
         };
 
+        // 标量常量登记到求值器，后续引用处直接折叠
+        if let Some(
+            value @ (ConstValue::Int(_)
+            | ConstValue::Float(_)
+            | ConstValue::Bool(_)
+            | ConstValue::Char(_)
+            | ConstValue::String(_)),
+        ) = &init_value
+        {
+            self.const_eval.define(name, value.clone());
+        }
+
         // 注册到全局变量表
         self.global_vars
             .push((name.to_string(), var_type.clone(), init_value.clone()));
@@ -1162,6 +2255,9 @@ impl AstToIrGenerator {
 
                 constructor
             }
+            ast::Type::Newtype(underlying) => Ok(Some(
+                self.generate_newtype_constructor_ir(_name, underlying),
+            )),
             _ => {
                 // 非结构体类型，不生成构造函数
                 Ok(None)
@@ -1169,6 +2265,34 @@ impl AstToIrGenerator {
         }
     }
 
+    /// 新类型构造函数：运行时表示与底层类型相同，直接返回参数
+    fn generate_newtype_constructor_ir(
+        &self,
+        name: &str,
+        underlying: &ast::Type,
+    ) -> FunctionIR {
+        let underlying: MonoType = underlying.clone().into();
+        FunctionIR {
+            name: name.to_string(),
+            params: vec![underlying.clone()],
+            return_type: underlying.clone(),
+            locals: vec![underlying],
+            blocks: vec![BasicBlock {
+                label: 0,
+                instructions: vec![
+                    Instruction::Load {
+                        dst: Operand::Local(0),
+                        src: Operand::Arg(0),
+                    },
+                    Instruction::Ret(Some(Operand::Local(0))),
+                ],
+                successors: Vec::new(),
+            }],
+            entry: 0,
+            generic_params: None,
+        }
+    }
+
     /// RFC-004: 为匿名函数绑定生成独立的 FunctionIR
     ///
     /// 匿名函数绑定在类型定义体内以 lambda 形式定义，需要生成独立的函数 IR。
@@ -1185,6 +2309,10 @@ impl AstToIrGenerator {
         let saved_mut_locals = std::mem::take(&mut self.current_mut_locals);
         let saved_local_names = std::mem::take(&mut self.current_local_names);
         let saved_next_temp = self.next_temp;
+        let saved_boxed_vars = std::mem::replace(
+            &mut self.boxed_vars,
+            closure::boxed_variables_in_expr(params, body),
+        );
 
         let mut instructions = Vec::new();
 
@@ -1211,6 +2339,7 @@ impl AstToIrGenerator {
         // 记录局部变量起始位置
         let local_var_start = params.len();
         self.next_temp = local_var_start;
+        self.box_captured_params(params, &mut instructions);
 
         // 生成表达式体的 IR，并返回结果
         let result_reg = self.next_temp_reg();
@@ -1228,6 +2357,7 @@ impl AstToIrGenerator {
         self.current_mut_locals = saved_mut_locals;
         self.current_local_names = saved_local_names;
         self.next_temp = saved_next_temp;
+        self.boxed_vars = saved_boxed_vars;
 
         // 解析返回类型
         let ret_type: MonoType = return_type.clone().into();
@@ -1396,9 +2526,14 @@ impl AstToIrGenerator {
             } => {
                 // 记录变量的类型信息（用于错误消息）
                 if let Some(type_ann) = type_annotation {
-                    let mono: MonoType = type_ann.clone().into();
+                    self.record_instance_types(type_ann);
+                    // Async(T) 运行时即汇合后的 T
+                    let mono = MonoType::from(type_ann.clone()).joined();
                     let type_name = mono.type_name();
                     self.local_var_types.insert(name.clone(), type_name.clone());
+                    if mono.is_record() {
+                        self.record_vars.insert(name.clone());
+                    }
 
                     // 接口直接赋值优化：
                     // 当 type_annotation 是约束类型且 initializer 是具体类型构造器时，
@@ -1423,35 +2558,60 @@ impl AstToIrGenerator {
 
                 // 检查变量是否已经存在于当前或外层作用域
                 // 如果存在，这是赋值操作而不是新声明
-                let var_idx = if let Some(existing_idx) = self.lookup_local(name) {
+                let (var_idx, declared) = match self.resolve_var(name) {
                     // 变量已存在，复用其索引（这是赋值操作）
-                    existing_idx
-                } else {
-                    // 新变量声明，分配新索引
-                    let idx = self.next_temp_reg();
-                    self.register_local(name, idx);
-                    // 记录可变性信息
-                    if *is_mut {
-                        self.current_mut_locals.insert(idx);
+                    Some(VarSlot::Local { idx, boxed: false }) => (idx, false),
+                    // 按引用捕获的变量或外层变量：经 cell 或 upvalue 赋值
+                    Some(slot) => {
+                        let val_reg = self.next_temp_reg();
+                        match initializer {
+                            Some(expr) => {
+                                self.generate_expr_ir(expr, val_reg, instructions, constants)?
+                            }
+                            None => instructions.push(Instruction::Load {
+                                dst: Operand::Local(val_reg),
+                                src: Operand::Const(ConstValue::Int(0)),
+                            }),
+                        }
+                        self.store_var(slot, val_reg, stmt.span, instructions);
+                        return Ok(());
+                    }
+                    None => {
+                        // 新变量声明，分配新索引
+                        let idx = self.next_temp_reg();
+                        self.register_local(name, idx);
+                        // 记录可变性信息
+                        if *is_mut {
+                            self.current_mut_locals.insert(idx);
+                        }
+                        (idx, true)
                     }
-                    idx
                 };
 
-                if let Some(expr) = initializer {
-                    // 变量到变量的赋值生成 Move（RFC-009 所有权转移）
-                    if let ast::Expr::Var(src_name, _) = expr.as_ref() {
-                        // 直接使用源变量的寄存器
-                        if let Some(src_idx) = self.lookup_local(src_name) {
-                            instructions.push(Instruction::Move {
-                                dst: Operand::Local(var_idx),
-                                src: Operand::Local(src_idx),
-                            });
-                        } else {
-                            // 源变量不存在，回退到普通赋值
-                            self.generate_expr_ir(expr, var_idx, instructions, constants)?;
+                // 标注为接口类型的变量保存存在类型值：`s: Show = Circle(1)`、
+                // `items: List(Show) = [Point(1, 2), Circle(3)]`
+                let dyn_interface = match type_annotation {
+                    Some(ast::Type::Name { name, .. }) => {
+                        self.interface_methods(name).map(|_| name.clone())
+                    }
+                    _ => None,
+                };
+                if let (Some(ast::Type::Generic { name, args, .. }), Some(ast::Expr::List(..))) =
+                    (type_annotation, initializer.as_deref())
+                {
+                    if let [ast::Type::Name { name: elem, .. }] = args.as_slice() {
+                        if name == "List" && self.interface_methods(elem).is_some() {
+                            self.dyn_list_interface = Some(elem.clone());
                         }
-                    } else {
-                        self.generate_expr_ir(expr, var_idx, instructions, constants)?;
+                    }
+                }
+
+                if let Some(expr) = initializer {
+                    // 变量到变量的赋值也从源变量的局部槽读取：
+                    // 所有权检查已保证被移动的源变量不再使用，可复制值两侧各持一份
+                    self.generate_expr_ir(expr, var_idx, instructions, constants)?;
+                    if let Some(interface) = dyn_interface {
+                        self.pack_dyn(&interface, expr, var_idx, instructions);
                     }
                 } else {
                     // 默认初始化为 0
@@ -1460,18 +2620,14 @@ impl AstToIrGenerator {
                         src: Operand::Const(ConstValue::Int(0)),
                     });
                 }
-                // 变量到变量的 Move 已经在上面处理，不需要额外的 Store
-                // 只有非 Move 的情况才需要 Store
-                if !matches!(
-                    initializer.as_ref().map(|e| e.as_ref()),
-                    Some(ast::Expr::Var(_, _))
-                ) {
-                    // 生成 Store 指令将值存储到局部变量
-                    instructions.push(Instruction::Store {
-                        dst: Operand::Local(var_idx),
-                        src: Operand::Local(var_idx),
-                        span: stmt.span,
-                    });
+                // 生成 Store 指令将值存储到局部变量
+                instructions.push(Instruction::Store {
+                    dst: Operand::Local(var_idx),
+                    src: Operand::Local(var_idx),
+                    span: stmt.span,
+                });
+                if declared {
+                    self.box_local_if_captured(name, var_idx, stmt.span, instructions);
                 }
             }
             ast::StmtKind::Binding {
@@ -1483,28 +2639,36 @@ impl AstToIrGenerator {
                 params,
                 body,
                 is_pub: _,
+                attributes: _,
             } => {
                 // 生成嵌套函数的 IR（排除方法绑定和类型定义）
-                // 从 GenericParam 提取参数名字符串
-                let generic_param_names = if generic_params.is_empty() {
-                    None
-                } else {
-                    Some(generic_params.iter().map(|p| p.name.clone()).collect())
-                };
-                match self.generate_function_ir(
+                let generic_param_names = Self::type_param_names(generic_params);
+                let refs = closure::referenced_names(params, body);
+                if refs.iter().any(|var| self.is_local_var(var)) {
+                    // 引用了外层局部变量：转换为闭包，存入同名局部变量
+                    self.generate_local_closure_ir(
+                        name,
+                        params,
+                        body,
+                        stmt.span,
+                        instructions,
+                        constants,
+                    )?;
+                    return Ok(());
+                }
+                let saved = self.enter_nested_function();
+                let func_ir = self.generate_function_ir(
                     name,
                     type_annotation.as_ref(),
                     params,
                     body,
                     constants,
                     generic_param_names,
-                ) {
-                    Ok(Some(func_ir)) => {
-                        // 将嵌套函数添加到列表（会被提升到模块级别）
-                        self.nested_functions.push(func_ir);
-                    }
-                    Ok(None) => {} // Native 函数或其他情况
-                    Err(e) => return Err(e),
+                );
+                self.exit_nested_function(saved);
+                if let Some(func_ir) = func_ir? {
+                    // 将嵌套函数添加到列表（会被提升到模块级别）
+                    self.nested_functions.push(func_ir);
                 }
             }
             ast::StmtKind::If {
@@ -1560,6 +2724,7 @@ impl AstToIrGenerator {
                             src: Operand::Local(var_idx),
                             span: *span,
                         });
+                        self.box_local_if_captured(&name.name, var_idx, *span, instructions);
                     }
                 } else {
                     // 非字面量元组：生成 RHS，然后通过 LoadIndex 提取
@@ -1588,6 +2753,7 @@ impl AstToIrGenerator {
                             src: Operand::Local(var_idx),
                             span: *span,
                         });
+                        self.box_local_if_captured(&name.name, var_idx, *span, instructions);
                     }
                 }
             }
@@ -1595,6 +2761,7 @@ impl AstToIrGenerator {
                 Some(e) => {
                     let result_reg = self.next_temp_reg();
                     self.generate_expr_ir(e, result_reg, instructions, constants)?;
+                    self.wrap_implicit_ok(e, result_reg, instructions);
                     instructions.push(Instruction::Ret(Some(Operand::Local(result_reg))));
                 }
                 None => {
@@ -1757,26 +2924,388 @@ impl AstToIrGenerator {
             }
         }
 
-        // 7. Else 分支
-        if let Some(else_body) = else_branch {
-            let else_res = self.next_temp_reg();
-            self.generate_block_expr_ir(else_body, else_res, instructions, constants)?;
+        // 7. Else 分支
+        if let Some(else_body) = else_branch {
+            let else_res = self.next_temp_reg();
+            self.generate_block_expr_ir(else_body, else_res, instructions, constants)?;
+            instructions.push(Instruction::Move {
+                dst: Operand::Local(result_reg),
+                src: Operand::Local(else_res),
+            });
+        }
+
+        // 8. 修复所有跳转到结束的指令
+        let end_len = instructions.len();
+        for idx in jumps_to_end {
+            if let Instruction::Jmp(ref mut target) = instructions[idx] {
+                *target = end_len;
+            }
+        }
+
+        self.exit_scope();
+        Ok(())
+    }
+
+    /// 生成 match 表达式的 IR
+    ///
+    /// 分支模式先编译为决策树（见 `passes::decision_tree`），树上每个检查只发射一次比较。
+    /// 叶子把绑定值移入该分支专用的寄存器后跳到分支体，每个分支体只生成一次；
+    /// 决策树没有到达的分支不生成代码。
+    #[allow(clippy::too_many_arguments)]
+    fn generate_match_ir(
+        &mut self,
+        scrutinee: &ast::Expr,
+        arms: &[ast::MatchArm],
+        span: Span,
+        result_reg: usize,
+        instructions: &mut Vec<Instruction>,
+        constants: &mut Vec<ConstValue>,
+    ) -> Result<(), Diagnostic> {
+        let scrutinee_reg = self.next_temp_reg();
+        self.generate_expr_ir(scrutinee, scrutinee_reg, instructions, constants)?;
+
+        let patterns: Vec<&ast::Pattern> = arms.iter().map(|arm| &arm.pattern).collect();
+        let struct_definitions = &self.struct_definitions;
+        let tree = Decision::compile(&patterns, &|name| {
+            struct_definitions
+                .get(name)
+                .map(|fields| fields.iter().map(|f| f.name.clone()).collect())
+        });
+
+        let mut state = MatchState {
+            span,
+            bindings: vec![Vec::new(); arms.len()],
+            to_arm: Vec::new(),
+            to_end: Vec::new(),
+        };
+        let mut loaded = HashMap::from([(Access::Root, scrutinee_reg)]);
+        self.emit_decision(
+            &tree,
+            arms,
+            &mut loaded,
+            &mut state,
+            instructions,
+            constants,
+        )?;
+
+        // 分支体：绑定名指向叶子填好的寄存器
+        let mut arm_starts = vec![None; arms.len()];
+        for (idx, arm) in arms.iter().enumerate() {
+            if !state.to_arm.iter().any(|&(_, target)| target == idx) {
+                continue;
+            }
+            arm_starts[idx] = Some(instructions.len());
+            let arm_result_reg = self.next_temp_reg();
+            self.enter_scope();
+            for (name, reg) in &state.bindings[idx] {
+                self.register_local(name, *reg);
+            }
+            let arm_result =
+                self.generate_block_expr_ir(&arm.body, arm_result_reg, instructions, constants);
+            self.exit_scope();
+            arm_result?;
+            instructions.push(Instruction::Move {
+                dst: Operand::Local(result_reg),
+                src: Operand::Local(arm_result_reg),
+            });
+            state.to_end.push(instructions.len());
+            instructions.push(Instruction::Jmp(0)); // 占位符
+        }
+
+        // 修复跳转目标
+        let end_pos = instructions.len();
+        let targets = state
+            .to_arm
+            .iter()
+            .map(|&(idx, arm)| (idx, arm_starts[arm].unwrap_or(end_pos)))
+            .chain(state.to_end.iter().map(|&idx| (idx, end_pos)));
+        for (idx, target) in targets {
+            if let Instruction::Jmp(ref mut to)
+            | Instruction::JmpIf(_, ref mut to)
+            | Instruction::JmpIfNot(_, ref mut to) = instructions[idx]
+            {
+                *to = target;
+            }
+        }
+        Ok(())
+    }
+
+    /// 发射决策树的一个节点
+    ///
+    /// `loaded` 记录当前路径上已取出的访问路径，兄弟分支各用一份副本，
+    /// 不会读到对方路径上才赋值的寄存器。
+    fn emit_decision(
+        &mut self,
+        decision: &Decision,
+        arms: &[ast::MatchArm],
+        loaded: &mut HashMap<Access, usize>,
+        state: &mut MatchState,
+        instructions: &mut Vec<Instruction>,
+        constants: &mut Vec<ConstValue>,
+    ) -> Result<(), Diagnostic> {
+        match decision {
+            Decision::Fail => {
+                state.to_end.push(instructions.len());
+                instructions.push(Instruction::Jmp(0)); // 占位符
+            }
+            Decision::Leaf { arm, bindings } => {
+                self.bind_match_values(*arm, bindings, loaded, state, instructions);
+                state.to_arm.push((instructions.len(), *arm));
+                instructions.push(Instruction::Jmp(0)); // 占位符
+            }
+            Decision::Guard {
+                arm,
+                bindings,
+                otherwise,
+            } => {
+                self.bind_match_values(*arm, bindings, loaded, state, instructions);
+                let ast::Pattern::Guard { condition, .. } = &arms[*arm].pattern else {
+                    unreachable!("guard node comes from a guarded arm");
+                };
+                let cond_reg = self.next_temp_reg();
+                self.enter_scope();
+                for (name, reg) in &state.bindings[*arm] {
+                    self.register_local(name, *reg);
+                }
+                let cond = self.generate_expr_ir(condition, cond_reg, instructions, constants);
+                self.exit_scope();
+                cond?;
+                state.to_arm.push((instructions.len(), *arm));
+                instructions.push(Instruction::JmpIf(Operand::Local(cond_reg), 0)); // 占位符
+                self.emit_decision(otherwise, arms, loaded, state, instructions, constants)?;
+            }
+            Decision::Switch {
+                access,
+                cases,
+                default,
+            } => {
+                let value = self.load_match_access(access, loaded, state.span, instructions);
+                if let Some(table) = JumpTable::for_cases(cases) {
+                    // 变体集合完整时表外的值不会出现
+                    let default = default.as_deref().unwrap_or(&Decision::Fail);
+                    return self.emit_jump_table(
+                        value,
+                        &table,
+                        cases,
+                        default,
+                        arms,
+                        loaded,
+                        state,
+                        instructions,
+                        constants,
+                    );
+                }
+                for (i, (test, child)) in cases.iter().enumerate() {
+                    // 构造器集合完整时最后一个分支无需比较
+                    let needs_test = default.is_some() || i + 1 < cases.len();
+                    let skip = needs_test
+                        .then(|| self.emit_match_test(value, test, instructions, constants));
+                    self.emit_decision(
+                        child,
+                        arms,
+                        &mut loaded.clone(),
+                        state,
+                        instructions,
+                        constants,
+                    )?;
+                    if let Some(idx) = skip {
+                        let next = instructions.len();
+                        if let Instruction::JmpIf(_, ref mut to)
+                        | Instruction::JmpIfNot(_, ref mut to) = instructions[idx]
+                        {
+                            *to = next;
+                        }
+                    }
+                }
+                if let Some(default) = default {
+                    self.emit_decision(
+                        default,
+                        arms,
+                        &mut loaded.clone(),
+                        state,
+                        instructions,
+                        constants,
+                    )?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 按跳转表分派稠密的整数、字符检查与枚举变体检查：各分支依次排布，表中的空洞与
+    /// 越界值走默认分支
+    #[allow(clippy::too_many_arguments)]
+    fn emit_jump_table(
+        &mut self,
+        value: usize,
+        table: &JumpTable,
+        cases: &[(Test, Decision)],
+        default: &Decision,
+        arms: &[ast::MatchArm],
+        loaded: &mut HashMap<Access, usize>,
+        state: &mut MatchState,
+        instructions: &mut Vec<Instruction>,
+        constants: &mut Vec<ConstValue>,
+    ) -> Result<(), Diagnostic> {
+        let switch_idx = instructions.len();
+        instructions.push(Instruction::Switch {
+            value: Operand::Local(value),
+            low: table.low,
+            targets: Vec::new(),
+            default: 0,
+        }); // 占位符
+        let mut starts = Vec::with_capacity(cases.len());
+        for (_, child) in cases {
+            starts.push(instructions.len());
+            self.emit_decision(
+                child,
+                arms,
+                &mut loaded.clone(),
+                state,
+                instructions,
+                constants,
+            )?;
+        }
+        let default_start = instructions.len();
+        self.emit_decision(
+            default,
+            arms,
+            &mut loaded.clone(),
+            state,
+            instructions,
+            constants,
+        )?;
+        if let Instruction::Switch {
+            targets, default, ..
+        } = &mut instructions[switch_idx]
+        {
+            *targets = table
+                .entries
+                .iter()
+                .map(|entry| entry.map_or(default_start, |case| starts[case]))
+                .collect();
+            *default = default_start;
+        }
+        Ok(())
+    }
+
+    /// 把叶子上的绑定值移入分支专用的寄存器
+    fn bind_match_values(
+        &mut self,
+        arm: usize,
+        bindings: &[(String, Access)],
+        loaded: &mut HashMap<Access, usize>,
+        state: &mut MatchState,
+        instructions: &mut Vec<Instruction>,
+    ) {
+        for (name, access) in bindings {
+            let src = self.load_match_access(access, loaded, state.span, instructions);
+            let dst = match state.bindings[arm].iter().find(|(n, _)| n == name) {
+                Some(&(_, reg)) => reg,
+                None => {
+                    let reg = self.next_temp_reg();
+                    state.bindings[arm].push((name.clone(), reg));
+                    reg
+                }
+            };
             instructions.push(Instruction::Move {
-                dst: Operand::Local(result_reg),
-                src: Operand::Local(else_res),
+                dst: Operand::Local(dst),
+                src: Operand::Local(src),
+            });
+            // 具名变量按局部变量槽读取
+            instructions.push(Instruction::Store {
+                dst: Operand::Local(dst),
+                src: Operand::Local(dst),
+                span: state.span,
             });
         }
+    }
 
-        // 8. 修复所有跳转到结束的指令
-        let end_len = instructions.len();
-        for idx in jumps_to_end {
-            if let Instruction::Jmp(ref mut target) = instructions[idx] {
-                *target = end_len;
+    /// 取被匹配值内 `access` 处的值，当前路径上已取过的直接复用
+    fn load_match_access(
+        &mut self,
+        access: &Access,
+        loaded: &mut HashMap<Access, usize>,
+        span: Span,
+        instructions: &mut Vec<Instruction>,
+    ) -> usize {
+        if let Some(&reg) = loaded.get(access) {
+            return reg;
+        }
+        let reg = self.next_temp_reg();
+        match access {
+            Access::Root => unreachable!("scrutinee register is loaded up front"),
+            Access::Index(parent, index) => {
+                let parent_reg = self.load_match_access(parent, loaded, span, instructions);
+                let index_reg = self.next_temp_reg();
+                instructions.push(Instruction::Load {
+                    dst: Operand::Local(index_reg),
+                    src: Operand::Const(ConstValue::Int(*index as i128)),
+                });
+                instructions.push(Instruction::LoadIndex {
+                    dst: Operand::Local(reg),
+                    src: Operand::Local(parent_reg),
+                    index: Operand::Local(index_reg),
+                    span,
+                });
+            }
+            Access::Field(parent, field) => {
+                let parent_reg = self.load_match_access(parent, loaded, span, instructions);
+                instructions.push(Instruction::LoadRecordField {
+                    dst: Operand::Local(reg),
+                    src: Operand::Local(parent_reg),
+                    field: field.clone(),
+                    span,
+                });
+            }
+            Access::Payload(parent, variant) => {
+                // Result 是目前唯一有运行时表示的枚举：变体已由跳转表确认，解包不会失败
+                let parent_reg = self.load_match_access(parent, loaded, span, instructions);
+                let unwrap = if *variant == RESULT_OK {
+                    "std.result.unwrap"
+                } else {
+                    "std.result.unwrap_err"
+                };
+                self.push_std_call(unwrap, parent_reg, reg, span, instructions);
             }
         }
+        loaded.insert(access.clone(), reg);
+        reg
+    }
 
-        self.exit_scope();
-        Ok(())
+    /// 发射一次决策树检查，返回检查不成立时跳走的指令下标（目标待修复）
+    fn emit_match_test(
+        &mut self,
+        value: usize,
+        test: &Test,
+        instructions: &mut Vec<Instruction>,
+        constants: &mut Vec<ConstValue>,
+    ) -> usize {
+        let (expected, jump_if_equal) = match test {
+            Test::Literal(lit) => (lit.clone(), false),
+            Test::Void => (ConstValue::Void, false),
+            Test::NotVoid => (ConstValue::Void, true),
+            Test::Variant { .. } => unreachable!("variant tests dispatch through a jump table"),
+        };
+        constants.push(expected.clone());
+        let expected_reg = self.next_temp_reg();
+        instructions.push(Instruction::Load {
+            dst: Operand::Local(expected_reg),
+            src: Operand::Const(expected),
+        });
+        let eq_reg = self.next_temp_reg();
+        instructions.push(Instruction::Eq {
+            dst: Operand::Local(eq_reg),
+            lhs: Operand::Local(value),
+            rhs: Operand::Local(expected_reg),
+        });
+        let idx = instructions.len();
+        instructions.push(if jump_if_equal {
+            Instruction::JmpIf(Operand::Local(eq_reg), 0)
+        } else {
+            Instruction::JmpIfNot(Operand::Local(eq_reg), 0)
+        }); // 占位符
+        idx
     }
 
     /// 生成代码块的 IR（用于表达式）
@@ -1804,19 +3333,30 @@ impl AstToIrGenerator {
     fn generate_block_expr_ir(
         &mut self,
         block: &ast::Block,
-        _result_reg: usize,
+        result_reg: usize,
         instructions: &mut Vec<Instruction>,
         constants: &mut Vec<ConstValue>,
     ) -> Result<(), Diagnostic> {
         // 进入新的作用域
         self.enter_scope();
 
-        // 生成语句
-        for stmt in &block.stmts {
+        // 生成语句；末尾的表达式语句是代码块的值
+        let (last, init) = match block.stmts.split_last() {
+            Some((last, init)) if matches!(last.kind, ast::StmtKind::Expr(_)) => (Some(last), init),
+            _ => (None, block.stmts.as_slice()),
+        };
+        for stmt in init {
             self.generate_local_stmt_ir(stmt, instructions, constants)?;
         }
+        if let Some(ast::Stmt {
+            kind: ast::StmtKind::Expr(expr),
+            ..
+        }) = last
+        {
+            self.generate_expr_ir(expr, result_reg, instructions, constants)?;
+        }
 
-        // 如果没有 return 语句，result_reg 保持默认值（Void）
+        // 没有末尾表达式时，result_reg 保持默认值（Void）
         // 退出作用域
         self.exit_scope();
 
@@ -1954,6 +3494,7 @@ impl AstToIrGenerator {
                 dst: Operand::Local(current_reg),
                 lhs: Operand::Local(current_reg),
                 rhs: Operand::Local(one_reg),
+                span: for_span,
             });
 
             // 6. 将新的 current 值存储到循环变量的 slot
@@ -1984,12 +3525,18 @@ impl AstToIrGenerator {
 
             Ok(())
         } else if let Some(
-            _iter_ty @ (MonoType::List(_)
+            iter_ty @ (MonoType::List(_)
             | MonoType::Tuple(_)
             | MonoType::Dict(_, _)
             | MonoType::Range { .. }),
         ) = self.get_expr_mono_type(iterable)
         {
+            // 遍历 List(接口) 时循环变量是存在类型值，方法调用走虚表
+            if let MonoType::List(elem) = &iter_ty {
+                if let Some(interface) = self.type_interface(elem) {
+                    self.local_var_types.insert(var_name.to_string(), interface);
+                }
+            }
             // 使用迭代器协议的 For 循环
             self.generate_iterator_for_loop_ir(
                 var_name,
@@ -2119,6 +3666,98 @@ impl AstToIrGenerator {
     /// }
     /// Spawn { closures, plan, result }
     /// ```
+    /// 生成 `spawn f(x)` 的 IR
+    ///
+    /// 实参在 spawn 处求值并由闭包按值捕获，调用包装为单任务闭包：
+    /// ```text
+    /// __spawn_arg_i = <实参>
+    /// closure = MakeClosure(() => { return f(__spawn_arg_i...) }, env=[__spawn_arg_i...])
+    /// Spawn { closures: [closure], plan, result }
+    /// result = closure        // Spawn 完成后闭包寄存器即为调用结果（已汇合）
+    /// ```
+    fn generate_spawn_call_ir(
+        &mut self,
+        call: &ast::Expr,
+        result_reg: usize,
+        span: Span,
+        instructions: &mut Vec<Instruction>,
+        constants: &mut Vec<ConstValue>,
+    ) -> Result<(), Diagnostic> {
+        let ast::Expr::Call {
+            func,
+            args,
+            named_args,
+            span: call_span,
+        } = call
+        else {
+            return Err(
+                ErrorCodeDefinition::ir_internal_error("spawn call expects a call").build(),
+            );
+        };
+
+        // 1. 在 spawn 处对实参求值，登记为局部变量，由闭包按值捕获
+        self.enter_scope();
+        let mut arg_vars = Vec::with_capacity(args.len());
+        for (i, arg) in args.iter().enumerate() {
+            let arg_reg = self.next_temp_reg();
+            if let Err(err) = self.generate_expr_ir(arg, arg_reg, instructions, constants) {
+                self.exit_scope();
+                return Err(err);
+            }
+            instructions.push(Instruction::Store {
+                dst: Operand::Local(arg_reg),
+                src: Operand::Local(arg_reg),
+                span,
+            });
+            let name = format!("__spawn_arg{}", i);
+            self.register_local(&name, arg_reg);
+            arg_vars.push(ast::Expr::Var(
+                ast::Symbol::intern(&name),
+                Self::get_expr_span(arg),
+            ));
+        }
+
+        // 2. 包装为单任务闭包：() => { return f(args...) }
+        let closure_reg = self.next_temp_reg();
+        let task_call = ast::Expr::Call {
+            func: func.clone(),
+            args: arg_vars,
+            named_args: named_args.clone(),
+            span: *call_span,
+        };
+        let body = ast::Block {
+            stmts: vec![ast::Stmt {
+                kind: ast::StmtKind::Expr(Box::new(ast::Expr::Return(
+                    Some(Box::new(task_call)),
+                    span,
+                ))),
+                span,
+            }],
+            span,
+        };
+        let closure = self.generate_closure_ir(&[], &body, closure_reg, instructions, constants);
+        self.exit_scope();
+        closure?;
+
+        // 3. 单任务 Spawn，完成后取回结果
+        instructions.push(Instruction::Spawn {
+            closures: vec![Operand::Local(closure_reg)],
+            plan: crate::middle::core::ir::ExecutionPlan {
+                groups: vec![crate::middle::core::ir::TaskGroup {
+                    task_indices: vec![0],
+                }],
+                task_deps: vec![Vec::new()],
+                task_resources: vec![Vec::new()],
+            },
+            result: Operand::Local(result_reg),
+        });
+        instructions.push(Instruction::Move {
+            dst: Operand::Local(result_reg),
+            src: Operand::Local(closure_reg),
+        });
+        Ok(())
+    }
+
     #[allow(clippy::too_many_arguments)]
     fn generate_spawn_for_ir(
         &mut self,
@@ -2134,7 +3773,7 @@ impl AstToIrGenerator {
         use crate::frontend::core::spawn::analysis::analyze_spawn_for;
 
         // 1. 分析循环体读写集
-        let (trait_table, local_var_types) = if let Some(ref type_result) = self.type_result {
+        let (trait_table, local_var_types) = if let Some(type_result) = self.type_result {
             (&type_result.trait_table, &type_result.local_var_types)
         } else {
             static EMPTY_TRAIT_TABLE: once_cell::sync::Lazy<
@@ -2212,22 +3851,10 @@ impl AstToIrGenerator {
             span,
         });
 
-        // 11. 在循环体内创建闭包：(item) => { body }
-        //     迭代变量作为参数传入，当前值通过 env 捕获
+        // 11. 在循环体内创建闭包：() => { body }
+        //     迭代变量由闭包按值捕获，每个闭包持有本次迭代的值
         let closure_reg = self.next_temp_reg();
-        // 捕获迭代变量的当前值（element_reg 在 next() 返回后被设置）
-        self.pending_env_vars = vec![Operand::Local(element_reg)];
-        let lambda = ast::Expr::Lambda {
-            params: vec![ast::Param {
-                name: var_name.to_string(),
-                ty: None,
-                is_mut: false,
-                span,
-            }],
-            body: Box::new(body.clone()),
-            span,
-        };
-        self.generate_expr_ir(&lambda, closure_reg, instructions, constants)?;
+        self.generate_closure_ir(&[], body, closure_reg, instructions, constants)?;
 
         // 12. 将闭包推入列表: push(closures, closure)
         instructions.push(Instruction::Call {
@@ -2284,6 +3911,8 @@ impl AstToIrGenerator {
             ast::Expr::Lit(_, span) => *span,
             ast::Expr::Var(_, span) => *span,
             ast::Expr::BinOp { span, .. } => *span,
+            ast::Expr::CompoundAssign { span, .. } => *span,
+            ast::Expr::Pipe { span, .. } => *span,
             ast::Expr::UnOp { span, .. } => *span,
             ast::Expr::Call { span, .. } => *span,
             ast::Expr::FnDef { span, .. } => *span,
@@ -2325,8 +3954,8 @@ impl AstToIrGenerator {
         // 如果表达式是变量，尝试从多个来源查找其类型
         if let ast::Expr::Var(name, _) = expr {
             // 1. 从类型检查结果中的 local_var_types 查找（最准确，包含具体类型）
-            if let Some(ref type_result) = self.type_result {
-                if let Some(mono_type) = type_result.local_var_types.get(name) {
+            if let Some(type_result) = self.type_result {
+                if let Some(mono_type) = type_result.local_var_types.get(name.as_str()) {
                     return mono_type.type_name();
                 }
             }
@@ -2336,7 +3965,7 @@ impl AstToIrGenerator {
                 return mono_type.type_name();
             }
             // 3. 从 IR 生成器本地追踪的类型中查找
-            if let Some(type_name) = self.local_var_types.get(name) {
+            if let Some(type_name) = self.local_var_types.get(name.as_str()) {
                 return type_name.clone();
             }
         }
@@ -2344,8 +3973,8 @@ impl AstToIrGenerator {
         // 构造器调用：Point(1.0, 2.0) → 类型名为 "Point"
         if let ast::Expr::Call { func, .. } = expr {
             if let ast::Expr::Var(name, _) = func.as_ref() {
-                if self.struct_definitions.contains_key(name) {
-                    return name.clone();
+                if self.struct_definitions.contains_key(name.as_str()) {
+                    return name.to_string();
                 }
             }
         }
@@ -2360,15 +3989,14 @@ impl AstToIrGenerator {
         func: &ast::Expr,
     ) -> Operand {
         if let Expr::Var(name, _) = func {
-            let resolved_name = if ModuleRegistry::with_std().is_native_name(name) {
-                name.clone()
-            } else if let Some(qualified) = ModuleRegistry::with_std()
-                .short_to_qualified_map()
-                .get(name)
+            let resolved_name = if self.std_registry.is_native_name(name)
+                || self.module_bindings.contains(name.as_str())
             {
+                name.to_string()
+            } else if let Some(qualified) = self.std_short_names.get(name.as_str()) {
                 qualified.clone()
             } else {
-                name.clone()
+                name.to_string()
             };
             Operand::Const(ConstValue::String(resolved_name))
         } else {
@@ -2376,6 +4004,51 @@ impl AstToIrGenerator {
         }
     }
 
+    /// 常量折叠：整棵子表达式在编译期可求值时返回结果
+    ///
+    /// 定宽算术需要运行时收窄，不参与折叠；被局部变量遮蔽的常量名不按常量处理。
+    fn fold_const_expr(
+        &self,
+        expr: &ast::Expr,
+        span: Span,
+    ) -> Option<ConstValue> {
+        if self
+            .type_result
+            .as_ref()
+            .is_some_and(|tr| tr.sized_arith.contains_key(&span))
+        {
+            return None;
+        }
+        self.const_eval
+            .eval_scoped(expr, &|name| self.is_local_var(name))
+    }
+
+    /// 定宽算术（`Int8/16/32`、`Float32`）之后追加收窄指令
+    fn push_sized_narrow(
+        &self,
+        span: Span,
+        reg: usize,
+        instructions: &mut Vec<Instruction>,
+    ) {
+        let Some(ty) = self
+            .type_result
+            .as_ref()
+            .and_then(|tr| tr.sized_arith.get(&span))
+        else {
+            return;
+        };
+        let target_type = match ty {
+            MonoType::Int(bits) => ast::Type::Int(*bits),
+            MonoType::Float(bits) => ast::Type::Float(*bits),
+            _ => return,
+        };
+        instructions.push(Instruction::Narrow {
+            dst: Operand::Local(reg),
+            src: Operand::Local(reg),
+            target_type,
+        });
+    }
+
     /// 获取表达式的推断类型（用于 IR 生成阶段的分支）
     fn get_expr_mono_type(
         &self,
@@ -2400,9 +4073,10 @@ impl AstToIrGenerator {
                 })
             }
             ast::Expr::Var(name, _) => {
-                if let Some(ref type_result) = self.type_result {
-                    if let Some(mono_type) = type_result.local_var_types.get(name) {
-                        return Some(mono_type.clone());
+                if let Some(type_result) = self.type_result {
+                    if let Some(mono_type) = type_result.local_var_types.get(name.as_str()) {
+                        // 标注的内置数值类型（`c: Float`）记录为类型名
+                        return Some(resolve_numeric(mono_type));
                     }
                 }
 
@@ -2435,10 +4109,17 @@ impl AstToIrGenerator {
         let saved_mut_locals = std::mem::take(&mut self.current_mut_locals);
         let saved_local_names = std::mem::take(&mut self.current_local_names);
         let saved_next_temp = self.next_temp;
+        let saved_boxed_vars = std::mem::replace(
+            &mut self.boxed_vars,
+            closure::boxed_variables(params, &body.stmts),
+        );
+        // 闭包体不标注返回类型，不做 ok 包装
+        let saved_returns_result = std::mem::take(&mut self.returns_result);
 
         let mut instructions = Vec::new();
 
-        // 进入闭包函数体作用域
+        // 进入闭包函数体作用域：外层变量只能经 upvalue 访问
+        self.enter_closure();
         self.enter_scope();
 
         // 为每个参数生成 LoadArg 指令并注册
@@ -2453,55 +4134,310 @@ impl AstToIrGenerator {
                 src: Operand::Local(i),
                 span: Span::dummy(),
             });
-            self.register_local(&param.name, i);
-            // Only mut parameters are registered as mutable
-            if param.is_mut {
-                self.current_mut_locals.insert(i);
-            }
+            self.register_local(&param.name, i);
+            // Only mut parameters are registered as mutable
+            if param.is_mut {
+                self.current_mut_locals.insert(i);
+            }
+        }
+
+        // 记录局部变量起始位置
+        let local_var_start = params.len();
+        self.next_temp = local_var_start;
+
+        self.box_captured_params(params, &mut instructions);
+
+        // 处理函数体语句
+        for stmt in &body.stmts {
+            if let Err(err) = self.generate_local_stmt_ir(stmt, &mut instructions, constants) {
+                self.exit_scope();
+                self.exit_closure();
+                return Err(err);
+            }
+        }
+
+        // 如果没有遇到 Ret 指令，追加 Ret(None)
+        let has_ret = instructions
+            .iter()
+            .any(|inst| matches!(inst, Instruction::Ret(_)));
+        if !has_ret {
+            instructions.push(Instruction::Ret(None));
+        }
+
+        // 退出作用域
+        self.exit_scope();
+        let captures = self.exit_closure();
+
+        // 计算局部变量总数
+        let total_locals = self.next_temp;
+        let locals_types: Vec<MonoType> = (0..total_locals).map(|_| MonoType::Int(64)).collect();
+
+        // 保存当前闭包函数的可变局部变量信息
+        let mut_locals = std::mem::take(&mut self.current_mut_locals);
+
+        // 恢复父函数的可变局部变量和局部变量名信息
+        self.current_mut_locals = saved_mut_locals;
+        self.current_local_names = saved_local_names;
+        self.next_temp = saved_next_temp;
+        self.boxed_vars = saved_boxed_vars;
+        self.returns_result = saved_returns_result;
+
+        Ok(LambdaBodyIR {
+            instructions,
+            locals: locals_types,
+            mut_locals,
+            captures,
+        })
+    }
+
+    /// 生成引用外层变量的局部函数：`name = (params) => body` 转换为闭包并存入局部变量 `name`
+    ///
+    /// 函数体递归引用自身时，`name` 先装箱为 cell，闭包按引用捕获，创建后再写入 cell。
+    fn generate_local_closure_ir(
+        &mut self,
+        name: &str,
+        params: &[ast::Param],
+        body: &[ast::Stmt],
+        span: Span,
+        instructions: &mut Vec<Instruction>,
+        constants: &mut Vec<ConstValue>,
+    ) -> Result<(), Diagnostic> {
+        let slot = match self.resolve_var(name) {
+            Some(slot) => slot,
+            None => {
+                let idx = self.next_temp_reg();
+                self.register_local(name, idx);
+                if closure::referenced_names(params, body).contains(name) {
+                    instructions.push(Instruction::Load {
+                        dst: Operand::Local(idx),
+                        src: Operand::Const(ConstValue::Int(0)),
+                    });
+                    self.box_local(name, idx, span, instructions);
+                    VarSlot::Local { idx, boxed: true }
+                } else {
+                    VarSlot::Local { idx, boxed: false }
+                }
+            }
+        };
+        let body = ast::Block {
+            stmts: body.to_vec(),
+            span,
+        };
+        let closure_reg = self.next_temp_reg();
+        self.generate_closure_ir(params, &body, closure_reg, instructions, constants)?;
+        self.store_var(slot, closure_reg, span, instructions);
+        Ok(())
+    }
+
+    /// 进入提升为模块级函数的嵌套函数：屏蔽外层局部变量，保存外层函数的生成状态
+    fn enter_nested_function(&mut self) -> NestedFunctionState {
+        self.enter_closure();
+        NestedFunctionState {
+            mut_locals: std::mem::take(&mut self.current_mut_locals),
+            local_names: std::mem::take(&mut self.current_local_names),
+            loop_binding_locals: std::mem::take(&mut self.current_loop_binding_locals),
+            next_temp: self.next_temp,
+            generic_param_vars: std::mem::take(&mut self.generic_param_vars),
+            record_vars: std::mem::take(&mut self.record_vars),
+            type_params: std::mem::take(&mut self.current_type_params),
+            boxed_vars: std::mem::take(&mut self.boxed_vars),
+            returns_result: std::mem::take(&mut self.returns_result),
+        }
+    }
+
+    /// 退出嵌套函数，恢复外层函数的生成状态
+    fn exit_nested_function(
+        &mut self,
+        saved: NestedFunctionState,
+    ) {
+        self.exit_closure();
+        self.current_mut_locals = saved.mut_locals;
+        self.current_local_names = saved.local_names;
+        self.current_loop_binding_locals = saved.loop_binding_locals;
+        self.next_temp = saved.next_temp;
+        self.generic_param_vars = saved.generic_param_vars;
+        self.record_vars = saved.record_vars;
+        self.current_type_params = saved.type_params;
+        self.boxed_vars = saved.boxed_vars;
+        self.returns_result = saved.returns_result;
+    }
+
+    /// 把函数体提升为闭包函数，并在 `result_reg` 中创建闭包
+    ///
+    /// 闭包环境按 upvalue 下标排列被捕获变量的当前值；按引用捕获的变量传递其 cell。
+    fn generate_closure_ir(
+        &mut self,
+        params: &[ast::Param],
+        body: &ast::Block,
+        result_reg: usize,
+        instructions: &mut Vec<Instruction>,
+        constants: &mut Vec<ConstValue>,
+    ) -> Result<String, Diagnostic> {
+        // 1. 生成唯一的闭包函数名
+        let closure_name = format!("closure_{}", self.closure_counter);
+        self.closure_counter += 1;
+
+        // 2. 生成闭包函数体 IR
+        let closure_body = self.generate_lambda_body_ir(params, body, constants)?;
+
+        // 3. 创建闭包函数 IR（返回类型简化为 Void）
+        let param_types: Vec<MonoType> = params
+            .iter()
+            .filter_map(|p| p.ty.clone())
+            .map(|t| t.into())
+            .collect();
+
+        let closure_func = FunctionIR {
+            name: closure_name.clone(),
+            params: param_types,
+            return_type: MonoType::Void,
+            locals: closure_body.locals,
+            blocks: vec![BasicBlock {
+                label: 0,
+                instructions: closure_body.instructions,
+                successors: Vec::new(),
+            }],
+            entry: 0,
+            generic_params: None,
+        };
+        self.nested_functions.push(closure_func);
+
+        // 4. 保存闭包函数的可变局部变量与 upvalue 个数
+        if !closure_body.mut_locals.is_empty() {
+            self.module_mut_locals
+                .insert(closure_name.clone(), closure_body.mut_locals);
+        }
+        if !closure_body.captures.is_empty() {
+            self.upvalue_counts
+                .insert(closure_name.clone(), closure_body.captures.len());
+        }
+
+        // 5. 在外层函数中取出被捕获变量，创建闭包
+        let env = self.capture_env(&closure_body.captures, instructions);
+        instructions.push(Instruction::MakeClosure {
+            dst: Operand::Local(result_reg),
+            func: closure_name.clone(),
+            env,
+        });
+        Ok(closure_name)
+    }
+
+    /// 生成表达式 IR
+    ///
+    /// 类型检查标记为隐式转浮点的整数表达式求值后转为 Float：
+    /// 常量直接取浮点值，其余追加 `IntToFloat`。
+    fn generate_expr_ir(
+        &mut self,
+        expr: &ast::Expr,
+        result_reg: usize,
+        instructions: &mut Vec<Instruction>,
+        constants: &mut Vec<ConstValue>,
+    ) -> Result<(), Diagnostic> {
+        let span = Self::get_expr_span(expr);
+        let to_float = self
+            .type_result
+            .as_ref()
+            .is_some_and(|tr| tr.int_to_float.contains(&span));
+        if !to_float {
+            return self.generate_expr_kind_ir(expr, result_reg, instructions, constants);
+        }
+        if let Some(ConstValue::Int(n)) = self.fold_const_expr(expr, span) {
+            let value = ConstValue::Float(n as f64);
+            constants.push(value.clone());
+            instructions.push(Instruction::Load {
+                dst: Operand::Local(result_reg),
+                src: Operand::Const(value),
+            });
+            return Ok(());
         }
+        self.generate_expr_kind_ir(expr, result_reg, instructions, constants)?;
+        instructions.push(Instruction::IntToFloat {
+            dst: Operand::Local(result_reg),
+            src: Operand::Local(result_reg),
+        });
+        Ok(())
+    }
 
-        // 记录局部变量起始位置
-        let local_var_start = params.len();
-        self.next_temp = local_var_start;
-
-        // 处理函数体语句
-        for stmt in &body.stmts {
-            self.generate_local_stmt_ir(stmt, &mut instructions, constants)?;
+    /// 表达式的推断类型是字符串
+    ///
+    /// 二元运算的类型不记录在 `expr_types` 中，`a + b` 按两侧判断。
+    fn is_string_expr(
+        &self,
+        expr: &ast::Expr,
+    ) -> bool {
+        if let ast::Expr::BinOp {
+            op: ast::BinOp::Add,
+            left,
+            right,
+            ..
+        } = expr
+        {
+            return self.is_string_expr(left) && self.is_string_expr(right);
         }
+        self.type_result
+            .as_ref()
+            .and_then(|tr| tr.expr_types.get(&Self::get_expr_span(expr)))
+            .is_some_and(|ty| matches!(ty, MonoType::String))
+    }
 
-        // 如果没有遇到 Ret 指令，追加 Ret(None)
-        let has_ret = instructions
-            .iter()
-            .any(|inst| matches!(inst, Instruction::Ret(_)));
-        if !has_ret {
-            instructions.push(Instruction::Ret(None));
+    /// 返回 `Result` 的函数中，`return` 的值不是 Result 时包装为 `ok(value)`
+    ///
+    /// 推断类型未知的表达式保持原样。
+    fn wrap_implicit_ok(
+        &self,
+        expr: &ast::Expr,
+        reg: usize,
+        instructions: &mut Vec<Instruction>,
+    ) {
+        if !self.returns_result {
+            return;
         }
+        let is_plain_value = self
+            .type_result
+            .as_ref()
+            .and_then(|tr| tr.expr_types.get(&Self::get_expr_span(expr)))
+            .is_some_and(|ty| !matches!(ty, MonoType::Result(..) | MonoType::TypeVar(_)));
+        if is_plain_value {
+            self.push_std_call(
+                "std.result.ok",
+                reg,
+                reg,
+                Self::get_expr_span(expr),
+                instructions,
+            );
+        }
+    }
 
-        // 退出作用域
-        self.exit_scope();
-
-        // 计算局部变量总数
-        let total_locals = self.next_temp;
-        let locals_types: Vec<MonoType> = (0..total_locals).map(|_| MonoType::Int(64)).collect();
-
-        // 保存当前闭包函数的可变局部变量信息
-        let mut_locals = std::mem::take(&mut self.current_mut_locals);
-
-        // 恢复父函数的可变局部变量和局部变量名信息
-        self.current_mut_locals = saved_mut_locals;
-        self.current_local_names = saved_local_names;
-        self.next_temp = saved_next_temp;
+    /// 以单个参数调用标准库函数
+    fn push_std_call(
+        &self,
+        name: &str,
+        arg: usize,
+        dst: usize,
+        span: Span,
+        instructions: &mut Vec<Instruction>,
+    ) {
+        instructions.push(Instruction::Call {
+            dst: Some(Operand::Local(dst)),
+            func: Operand::Const(ConstValue::String(name.to_string())),
+            args: vec![Operand::Local(arg)],
+            span,
+        });
+    }
 
-        Ok(LambdaBodyIR {
-            instructions,
-            locals: locals_types,
-            mut_locals,
-        })
+    /// 表达式的推断类型是浮点
+    fn is_float_expr(
+        &self,
+        expr: &ast::Expr,
+    ) -> bool {
+        self.type_result
+            .as_ref()
+            .and_then(|tr| tr.expr_types.get(&Self::get_expr_span(expr)))
+            .is_some_and(|ty| matches!(ty, MonoType::Float(_)))
     }
 
-    /// 生成表达式 IR
     #[allow(clippy::only_used_in_recursion)]
-    fn generate_expr_ir(
+    fn generate_expr_kind_ir(
         &mut self,
         expr: &ast::Expr,
         result_reg: usize,
@@ -2515,8 +4451,9 @@ impl AstToIrGenerator {
                     Literal::Int(n) => ConstValue::Int(*n),
                     Literal::Float(f) => ConstValue::Float(*f),
                     Literal::Bool(b) => ConstValue::Bool(*b),
-                    Literal::String(s) => ConstValue::String(s.clone()),
+                    Literal::String(s) => ConstValue::String(s.to_string()),
                     Literal::Char(c) => ConstValue::Char(*c),
+                    Literal::Void => ConstValue::Void,
                 };
                 // 添加到常量池
                 constants.push(const_val.clone());
@@ -2527,18 +4464,22 @@ impl AstToIrGenerator {
             }
             Expr::Var(var_name, var_span) => {
                 // 变量加载 - 首先查找局部变量，然后查找全局变量
-                if let Some(local_idx) = self.lookup_local(var_name) {
-                    // 局部变量：直接加载
+                if let Some(slot) = self.resolve_var(var_name) {
+                    // 局部变量或捕获的外层变量
+                    self.load_var(slot, result_reg, *var_span, instructions);
+                } else if let Some(value) = self.const_eval.get(var_name).cloned() {
+                    // 编译期常量：直接加载值，省去一次全局取值调用
+                    constants.push(value.clone());
                     instructions.push(Instruction::Load {
                         dst: Operand::Local(result_reg),
-                        src: Operand::Local(local_idx),
+                        src: Operand::Const(value),
                     });
                 } else if self.lookup_global(var_name).is_some() {
                     // 全局变量：生成函数调用获取值
                     let func_name = var_name.clone();
                     instructions.push(Instruction::Call {
                         dst: Some(Operand::Local(result_reg)),
-                        func: Operand::Const(ConstValue::String(func_name)),
+                        func: Operand::Const(ConstValue::String(func_name.to_string())),
                         args: vec![],
                         span: *var_span,
                     });
@@ -2561,12 +4502,21 @@ impl AstToIrGenerator {
                 let instr = match op {
                     ast::BinOp::Assign => {
                         if let Expr::Var(var_name, _) = left.as_ref() {
-                            let local_idx = if let Some(idx) = self.lookup_local(var_name) {
-                                idx
-                            } else {
-                                let idx = self.next_temp_reg();
-                                self.register_local(var_name, idx);
-                                idx
+                            let (local_idx, declared) = match self.resolve_var(var_name) {
+                                Some(VarSlot::Local { idx, boxed: false }) => (idx, false),
+                                Some(slot) => {
+                                    // 经 cell 或 upvalue 赋值
+                                    let val_reg = self.next_temp_reg();
+                                    self.generate_expr_ir(right, val_reg, instructions, constants)?;
+                                    self.store_var(slot, val_reg, *span, instructions);
+                                    self.load_var(slot, result_reg, *span, instructions);
+                                    return Ok(());
+                                }
+                                None => {
+                                    let idx = self.next_temp_reg();
+                                    self.register_local(var_name, idx);
+                                    (idx, true)
+                                }
                             };
                             let val_reg = self.next_temp_reg();
                             self.generate_expr_ir(right, val_reg, instructions, constants)?;
@@ -2575,7 +4525,7 @@ impl AstToIrGenerator {
                             // 优先使用 typecheck 结果推导类型名，AST 推断仅作为兜底
                             let inferred = self.get_expr_type_name(right);
                             if inferred != "<unknown>" {
-                                self.local_var_types.insert(var_name.clone(), inferred);
+                                self.local_var_types.insert(var_name.to_string(), inferred);
                             }
 
                             // 变量到变量的赋值生成 Move，值表达式生成 Store
@@ -2595,30 +4545,85 @@ impl AstToIrGenerator {
                                 dst: Operand::Local(result_reg),
                                 src: Operand::Local(local_idx),
                             });
+                            if declared {
+                                self.box_local_if_captured(
+                                    var_name,
+                                    local_idx,
+                                    *span,
+                                    instructions,
+                                );
+                            }
                         }
                         return Ok(());
                     }
                     _ => {
+                        if let Some(value) = self.fold_const_expr(expr, *span) {
+                            constants.push(value.clone());
+                            instructions.push(Instruction::Load {
+                                dst: Operand::Local(result_reg),
+                                src: Operand::Const(value),
+                            });
+                            return Ok(());
+                        }
                         let left_reg = self.next_temp_reg();
                         let right_reg = self.next_temp_reg();
                         self.generate_expr_ir(left, left_reg, instructions, constants)?;
                         self.generate_expr_ir(right, right_reg, instructions, constants)?;
 
+                        if self.is_float_expr(left) || self.is_float_expr(right) {
+                            let (dst, lhs, rhs) = (
+                                Operand::Local(result_reg),
+                                Operand::Local(left_reg),
+                                Operand::Local(right_reg),
+                            );
+                            let float_instr = match op {
+                                ast::BinOp::Add => Some(Instruction::FAdd { dst, lhs, rhs }),
+                                ast::BinOp::Sub => Some(Instruction::FSub { dst, lhs, rhs }),
+                                ast::BinOp::Mul => Some(Instruction::FMul { dst, lhs, rhs }),
+                                ast::BinOp::Div => Some(Instruction::FDiv { dst, lhs, rhs }),
+                                ast::BinOp::Mod => Some(Instruction::FMod { dst, lhs, rhs }),
+                                ast::BinOp::Eq => Some(Instruction::FEq { dst, lhs, rhs }),
+                                ast::BinOp::Neq => Some(Instruction::FNe { dst, lhs, rhs }),
+                                ast::BinOp::Lt => Some(Instruction::FLt { dst, lhs, rhs }),
+                                ast::BinOp::Le => Some(Instruction::FLe { dst, lhs, rhs }),
+                                ast::BinOp::Gt => Some(Instruction::FGt { dst, lhs, rhs }),
+                                ast::BinOp::Ge => Some(Instruction::FGe { dst, lhs, rhs }),
+                                _ => None,
+                            };
+                            if let Some(float_instr) = float_instr {
+                                instructions.push(float_instr);
+                                self.push_sized_narrow(*span, result_reg, instructions);
+                                return Ok(());
+                            }
+                        }
+
                         match op {
+                            ast::BinOp::Add
+                                if self.is_string_expr(left) && self.is_string_expr(right) =>
+                            {
+                                Instruction::StringConcat {
+                                    dst: Operand::Local(result_reg),
+                                    lhs: Operand::Local(left_reg),
+                                    rhs: Operand::Local(right_reg),
+                                }
+                            }
                             ast::BinOp::Add => Instruction::Add {
                                 dst: Operand::Local(result_reg),
                                 lhs: Operand::Local(left_reg),
                                 rhs: Operand::Local(right_reg),
+                                span: *span,
                             },
                             ast::BinOp::Sub => Instruction::Sub {
                                 dst: Operand::Local(result_reg),
                                 lhs: Operand::Local(left_reg),
                                 rhs: Operand::Local(right_reg),
+                                span: *span,
                             },
                             ast::BinOp::Mul => Instruction::Mul {
                                 dst: Operand::Local(result_reg),
                                 lhs: Operand::Local(left_reg),
                                 rhs: Operand::Local(right_reg),
+                                span: *span,
                             },
                             ast::BinOp::Div => Instruction::Div {
                                 dst: Operand::Local(result_reg),
@@ -2672,6 +4677,34 @@ impl AstToIrGenerator {
                     }
                 };
                 instructions.push(instr);
+                self.push_sized_narrow(*span, result_reg, instructions);
+            }
+            Expr::Cast {
+                expr,
+                target_type,
+                checked,
+                ..
+            } => {
+                let src_reg = self.next_temp_reg();
+                self.generate_expr_ir(expr, src_reg, instructions, constants)?;
+                let (dst, src, target_type) = (
+                    Operand::Local(result_reg),
+                    Operand::Local(src_reg),
+                    target_type.clone(),
+                );
+                instructions.push(if *checked {
+                    Instruction::TypeTest {
+                        dst,
+                        src,
+                        target_type,
+                    }
+                } else {
+                    Instruction::Cast {
+                        dst,
+                        src,
+                        target_type,
+                    }
+                });
             }
             Expr::Call {
                 func,
@@ -2679,6 +4712,15 @@ impl AstToIrGenerator {
                 named_args,
                 span,
             } => {
+                if let Some(text) = self.eval_const_format(expr) {
+                    let value = ConstValue::String(text);
+                    constants.push(value.clone());
+                    instructions.push(Instruction::Load {
+                        dst: Operand::Local(result_reg),
+                        src: Operand::Const(value),
+                    });
+                    return Ok(());
+                }
                 // 检查是否是方法调用：func 是 FieldAccess
                 if let Expr::FieldAccess { expr, field, .. } = func.as_ref() {
                     // 方法调用 - 转换为普通函数调用
@@ -2686,7 +4728,7 @@ impl AstToIrGenerator {
 
                     // 只有非命名空间调用才需要添加 self 参数
                     // 命名空间调用（如 std.io.println）不需要隐式参数
-                    if is_namespace_call(expr) {
+                    if is_namespace_call(&self.std_registry, expr) {
                         // 命名空间调用：不需要隐式参数
                         let mut arg_regs = Vec::new();
                         for arg in args.iter() {
@@ -2694,7 +4736,8 @@ impl AstToIrGenerator {
                             self.generate_expr_ir(arg, arg_reg, instructions, constants)?;
                             arg_regs.push(Operand::Local(arg_reg));
                         }
-                        let method_function_name = extract_namespace_path(expr, field);
+                        let method_function_name =
+                            extract_namespace_path(&self.std_registry, expr, field);
                         instructions.push(Instruction::Call {
                             dst: Some(Operand::Local(result_reg)),
                             func: Operand::Const(ConstValue::String(
@@ -2746,9 +4789,8 @@ impl AstToIrGenerator {
                             }
 
                             // 解析函数名
-                            let func_name = if let Some(qualified) = ModuleRegistry::with_std()
-                                .short_to_qualified_map()
-                                .get(&binding.function)
+                            let func_name = if let Some(qualified) =
+                                self.std_short_names.get(&binding.function)
                             {
                                 qualified.clone()
                             } else {
@@ -2788,8 +4830,23 @@ impl AstToIrGenerator {
                             let concrete_type = var_name.as_ref().and_then(|name| {
                                 self.get_constraint_var_concrete_type(name).cloned()
                             });
+                            let generic_receiver = var_name.as_ref().and_then(|name| {
+                                self.generic_param_vars.get(name.as_str()).cloned()
+                            });
 
-                            if let Some(concrete_type_name) = concrete_type {
+                            if let Some(type_param) = generic_receiver {
+                                // 接收者类型为泛型参数：生成 T.method，
+                                // 单态化时替换为具体实现类型的方法（静态分发）
+                                instructions.push(Instruction::Call {
+                                    dst: Some(Operand::Local(result_reg)),
+                                    func: Operand::Const(ConstValue::String(format!(
+                                        "{}.{}",
+                                        type_param, field
+                                    ))),
+                                    args: arg_regs,
+                                    span: *span,
+                                });
+                            } else if let Some(concrete_type_name) = concrete_type {
                                 // 编译期可确定具体类型 → 直接调用（零开销）
                                 // d.draw(screen) → ConcreteType.draw(d, screen)
                                 let qualified_name = format!("{}.{}", concrete_type_name, field);
@@ -2802,17 +4859,33 @@ impl AstToIrGenerator {
                                     args: final_args,
                                     span: *span,
                                 });
+                            } else if let Some(slot) =
+                                self.expr_interface(expr).and_then(|interface| {
+                                    self.interface_methods(&interface)?
+                                        .iter()
+                                        .position(|m| m == field)
+                                })
+                            {
+                                // 接收者静态类型为接口：按存在类型值的虚表动态分发
+                                instructions.push(Instruction::InvokeVirtual {
+                                    dst: Some(Operand::Local(result_reg)),
+                                    obj: Operand::Local(obj_reg),
+                                    slot,
+                                    method_name: field.to_string(),
+                                    args: arg_regs,
+                                    span: *span,
+                                });
                             } else if var_name.as_ref().is_some_and(|name| {
                                 // 检查变量的类型标注是否是约束类型（但具体类型未知）
                                 self.local_var_types
-                                    .get(name)
+                                    .get(name.as_str())
                                     .and_then(|type_name| {
                                         // 如果变量类型是约束类型且不在 constraint_var_concrete_types 中
                                         // 说明具体类型无法在编译期确定，需要 vtable 调用
                                         if !self.struct_definitions.contains_key(type_name)
                                             && !self
                                                 .constraint_var_concrete_types
-                                                .contains_key(name)
+                                                .contains_key(name.as_str())
                                         {
                                             // 简单启发式：如果变量类型不是已知结构体，可能是约束类型
                                             Some(true)
@@ -2836,13 +4909,14 @@ impl AstToIrGenerator {
                                 // 例如：a.is_greater(b) 中 a 的类型是 Node
                                 // → 函数名应为 "Node.is_greater" 而非 "a.is_greater"
                                 let func_name = if let Expr::Var(name, _) = expr.as_ref() {
-                                    if let Some(type_name) = self.local_var_types.get(name) {
+                                    if let Some(type_name) = self.local_var_types.get(name.as_str())
+                                    {
                                         format!("{}.{}", type_name, field)
                                     } else {
-                                        extract_namespace_path(expr, field)
+                                        extract_namespace_path(&self.std_registry, expr, field)
                                     }
                                 } else {
-                                    extract_namespace_path(expr, field)
+                                    extract_namespace_path(&self.std_registry, expr, field)
                                 };
 
                                 let final_args: Vec<Operand> = arg_regs.clone();
@@ -2868,7 +4942,9 @@ impl AstToIrGenerator {
                     // RFC-010: 处理命名参数构造 `Point(x=1, y=2)`
                     if !named_args.is_empty() {
                         if let Expr::Var(name, _) = func.as_ref() {
-                            if let Some(fields) = self.struct_definitions.get(name).cloned() {
+                            if let Some(fields) =
+                                self.struct_definitions.get(name.as_str()).cloned()
+                            {
                                 // 生成命名参数的 IR
                                 let mut named_regs: Vec<(String, Operand)> = Vec::new();
                                 for (arg_name, arg_expr) in named_args.iter() {
@@ -2927,7 +5003,7 @@ impl AstToIrGenerator {
 
                     // 检查是否是结构体构造器调用，需要填充默认值
                     if let Expr::Var(name, _) = func.as_ref() {
-                        if let Some(fields) = self.struct_definitions.get(name).cloned() {
+                        if let Some(fields) = self.struct_definitions.get(name.as_str()).cloned() {
                             // 这是一个结构体构造器调用
                             // 如果提供的参数数少于字段数，用默认值填充
                             if arg_regs.len() < fields.len() {
@@ -2954,12 +5030,41 @@ impl AstToIrGenerator {
                         }
                     }
 
+                    // 接口类型的形参接收存在类型值
+                    if let Expr::Var(name, _) = func.as_ref() {
+                        let param_types = self
+                            .function_param_types
+                            .get(name.as_str())
+                            .cloned()
+                            .unwrap_or_default();
+                        for ((param_ty, arg), arg_reg) in
+                            param_types.iter().zip(args.iter()).zip(arg_regs.iter())
+                        {
+                            if let (Some(interface), Operand::Local(reg)) =
+                                (self.type_interface(param_ty), arg_reg)
+                            {
+                                self.pack_dyn(&interface, arg, *reg, instructions);
+                            }
+                        }
+                    }
+
                     // 命名空间解析：将短名称解析为完整名称
                     // 例如：print -> std.io.print (当 print 是通过 use std.io.{print} 导入时)
-                    // 检查是否是闭包调用（函数表达式不是简单的变量名）
-                    let is_closure_call = !matches!(func.as_ref(), Expr::Var(_, _));
+                    // 检查是否是闭包调用（函数表达式不是简单的变量名，或是保存函数值的局部变量）
+                    let is_closure_call = match func.as_ref() {
+                        Expr::Var(name, _) => self.is_local_var(name),
+                        _ => true,
+                    };
 
-                    if is_closure_call {
+                    if let Some(instance) = self.request_const_instance(func)? {
+                        // const 泛型函数应用：`scale(4)(x)` 直接调用特化版本 `scale[4]`
+                        instructions.push(Instruction::Call {
+                            dst: Some(Operand::Local(result_reg)),
+                            func: Operand::Const(ConstValue::String(instance)),
+                            args: arg_regs,
+                            span: *span,
+                        });
+                    } else if is_closure_call {
                         // 闭包调用：先加载函数值，然后使用 CallDyn
                         let func_reg = self.next_temp_reg();
                         self.generate_expr_ir(func, func_reg, instructions, constants)?;
@@ -3023,16 +5128,15 @@ impl AstToIrGenerator {
                                     let print_func_name = if let Expr::Var(name, _) = func.as_ref()
                                     {
                                         if name == "print" || name == "println" {
-                                            if let Some(qualified) = ModuleRegistry::with_std()
-                                                .short_to_qualified_map()
-                                                .get(name)
+                                            if let Some(qualified) =
+                                                self.std_short_names.get(name.as_str())
                                             {
                                                 qualified.clone()
                                             } else {
                                                 format!("std.io.{}", name)
                                             }
                                         } else {
-                                            name.clone()
+                                            name.to_string()
                                         }
                                     } else {
                                         "std.io.print".to_string()
@@ -3076,16 +5180,15 @@ impl AstToIrGenerator {
                                     let print_func_name = if let Expr::Var(name, _) = func.as_ref()
                                     {
                                         if name == "print" || name == "println" {
-                                            if let Some(qualified) = ModuleRegistry::with_std()
-                                                .short_to_qualified_map()
-                                                .get(name)
+                                            if let Some(qualified) =
+                                                self.std_short_names.get(name.as_str())
                                             {
                                                 qualified.clone()
                                             } else {
                                                 format!("std.io.{}", name)
                                             }
                                         } else {
-                                            name.clone()
+                                            name.to_string()
                                         }
                                     } else {
                                         "std.io.print".to_string()
@@ -3125,11 +5228,23 @@ impl AstToIrGenerator {
                 }
             }
             Expr::FieldAccess { expr, field, span } => {
+                // 关联常量访问：`Square.SIDES` 直接调用 "Square.SIDES"；
+                // 泛型形参上的 `value.SIDES` 生成 "T.SIDES"，由单态化替换为实现类型
+                if let Some(const_fn) = self.resolve_assoc_const_access(expr, field) {
+                    instructions.push(Instruction::Call {
+                        dst: Some(Operand::Local(result_reg)),
+                        func: Operand::Const(ConstValue::String(const_fn)),
+                        args: vec![],
+                        span: *span,
+                    });
+                    return Ok(());
+                }
+
                 // 首先检查是否是模块变量的字段访问（如 io.println）
                 // io 是通过 use std.{io} 导入的模块变量
                 if let Expr::Var(module_name, _) = expr.as_ref() {
                     if let Some(full_path) = {
-                        let reg = ModuleRegistry::with_std();
+                        let reg = &self.std_registry;
                         if reg.is_std_submodule(module_name) {
                             let path = format!("std.{}", field);
                             if reg.is_native_name(&path) {
@@ -3151,24 +5266,38 @@ impl AstToIrGenerator {
                             args: vec![],
                             span: *span,
                         });
+                    } else if self.record_vars.contains(module_name.as_str()) {
+                        // 匿名记录类型的变量：实际结构体布局未知，按字段名读取
+                        let obj_reg = self.next_temp_reg();
+                        self.generate_expr_ir(expr, obj_reg, instructions, constants)?;
+                        instructions.push(Instruction::LoadRecordField {
+                            dst: Operand::Local(result_reg),
+                            src: Operand::Local(obj_reg),
+                            field: field.clone(),
+                            span: *span,
+                        });
                     } else {
                         // 普通字段访问
                         let obj_reg = self.next_temp_reg();
                         self.generate_expr_ir(expr, obj_reg, instructions, constants)?;
-                        let field_index = self.resolve_field_index(expr, field).unwrap_or(0);
+                        let (type_name, field_index) = match self.resolve_field(expr, field) {
+                            Some((type_name, index)) => (Some(type_name), index),
+                            None => (None, 0),
+                        };
                         instructions.push(Instruction::LoadField {
                             dst: Operand::Local(result_reg),
                             src: Operand::Local(obj_reg),
                             field: field_index,
+                            type_name,
                             span: *span,
                         });
                     }
                 } else {
                     // 提取完整的命名空间路径（如 std.math.PI）
-                    let full_path = extract_namespace_path(expr, field);
+                    let full_path = extract_namespace_path(&self.std_registry, expr, field);
 
                     // 检查是否是命名空间常量访问
-                    if ModuleRegistry::with_std().is_native_name(&full_path) {
+                    if self.std_registry.is_native_name(&full_path) {
                         // 命名空间常量访问：生成零参数函数调用
                         instructions.push(Instruction::Call {
                             dst: Some(Operand::Local(result_reg)),
@@ -3180,11 +5309,15 @@ impl AstToIrGenerator {
                         // 普通字段访问
                         let obj_reg = self.next_temp_reg();
                         self.generate_expr_ir(expr, obj_reg, instructions, constants)?;
-                        let field_index = self.resolve_field_index(expr, field).unwrap_or(0);
+                        let (type_name, field_index) = match self.resolve_field(expr, field) {
+                            Some((type_name, index)) => (Some(type_name), index),
+                            None => (None, 0),
+                        };
                         instructions.push(Instruction::LoadField {
                             dst: Operand::Local(result_reg),
                             src: Operand::Local(obj_reg),
                             field: field_index,
+                            type_name,
                             span: *span,
                         });
                     }
@@ -3317,6 +5450,16 @@ impl AstToIrGenerator {
                 }
             }
             Expr::List(elements, span) => {
+                let dyn_interface = self.dyn_list_interface.take();
+                // 全常量列表：整体放入常量池，运行时浅拷贝，避免逐元素重建
+                if let Some(value) = self.eval_const_expr(expr).filter(|_| !elements.is_empty()) {
+                    instructions.push(Instruction::Load {
+                        dst: Operand::Local(result_reg),
+                        src: Operand::Const(value),
+                    });
+                    return Ok(());
+                }
+
                 // 列表字面量：先创建空列表，再按索引写入元素
                 instructions.push(Instruction::AllocArray {
                     dst: Operand::Local(result_reg),
@@ -3330,6 +5473,9 @@ impl AstToIrGenerator {
                 for (idx, element) in elements.iter().enumerate() {
                     let element_reg = self.next_temp_reg();
                     self.generate_expr_ir(element, element_reg, instructions, constants)?;
+                    if let Some(interface) = &dyn_interface {
+                        self.pack_dyn(interface, element, element_reg, instructions);
+                    }
 
                     let index_reg = self.next_temp_reg();
                     instructions.push(Instruction::Load {
@@ -3346,6 +5492,15 @@ impl AstToIrGenerator {
                 }
             }
             Expr::Dict(pairs, _span) => {
+                // 全常量字典：同列表，整体作为复合常量加载
+                if let Some(value) = self.eval_const_expr(expr).filter(|_| !pairs.is_empty()) {
+                    instructions.push(Instruction::Load {
+                        dst: Operand::Local(result_reg),
+                        src: Operand::Const(value),
+                    });
+                    return Ok(());
+                }
+
                 // 字典字面量：使用 NewDict 指令一次性创建
                 let mut keys = Vec::new();
                 let mut values = Vec::new();
@@ -3381,15 +5536,19 @@ impl AstToIrGenerator {
                 // 生成返回指令
                 if let Some(e) = expr {
                     self.generate_expr_ir(e, result_reg, instructions, constants)?;
+                    self.wrap_implicit_ok(e, result_reg, instructions);
                     instructions.push(Instruction::Ret(Some(Operand::Local(result_reg))));
                 } else {
                     instructions.push(Instruction::Ret(None));
                 }
             }
-            Expr::Try { expr, span: _ } => {
-                // `expr?`：当前阶段仅作为错误传播标记，运行时等价于 `expr`。
-                // 错误的传播由解释器/Runtime 的错误通道处理（RFC-001）。
-                self.generate_expr_ir(expr, result_reg, instructions, constants)?;
+            // `?` 与 f-string 由类型检查之后的脱糖展开
+            Expr::Try { span, .. } | Expr::FString { span, .. } => {
+                return Err(
+                    ErrorCodeDefinition::ir_internal_error("语法糖未经脱糖即进入 IR 生成")
+                        .at(*span)
+                        .build(),
+                );
             }
             Expr::If {
                 condition,
@@ -3449,7 +5608,7 @@ impl AstToIrGenerator {
                 let use_arc = var_name.as_ref().is_some_and(|n| {
                     self.type_result
                         .as_ref()
-                        .is_some_and(|tr| tr.escaped_refs.contains(n))
+                        .is_some_and(|tr| tr.escaped_refs.contains(n.as_str()))
                 });
 
                 if use_arc {
@@ -3500,13 +5659,19 @@ impl AstToIrGenerator {
                     constants,
                 )?;
             }
+            Expr::Spawn { body, span }
+                if crate::frontend::core::spawn::analysis::spawn_call(body).is_some() =>
+            {
+                // Spawn call: spawn f(x)
+                let call = crate::frontend::core::spawn::analysis::spawn_call(body).unwrap();
+                self.generate_spawn_call_ir(call, result_reg, *span, instructions, constants)?;
+            }
             Expr::Spawn { body, span } => {
                 // Spawn block: spawn { ... }
                 // RFC-024: DAG 分析识别直接子表达式，为每个生成独立闭包
 
                 // 1. DAG 分析：识别直接子表达式，生成执行计划
-                let (trait_table, local_var_types) = if let Some(ref type_result) = self.type_result
-                {
+                let (trait_table, local_var_types) = if let Some(type_result) = self.type_result {
                     (&type_result.trait_table, &type_result.local_var_types)
                 } else {
                     // 无类型信息时使用空表（向后兼容）
@@ -3596,7 +5761,17 @@ impl AstToIrGenerator {
                 // 7. 退出 spawn 作用域
                 self.exit_scope();
             }
-            Expr::UnOp { op, expr, span: _ } => {
+            unop @ Expr::UnOp { op, expr, span } => {
+                if !matches!(op, ast::UnOp::Deref) {
+                    if let Some(value) = self.fold_const_expr(unop, *span) {
+                        constants.push(value.clone());
+                        instructions.push(Instruction::Load {
+                            dst: Operand::Local(result_reg),
+                            src: Operand::Const(value),
+                        });
+                        return Ok(());
+                    }
+                }
                 // 一元运算符
                 match op {
                     ast::UnOp::Deref => {
@@ -3615,10 +5790,13 @@ impl AstToIrGenerator {
                         // 负号：-x
                         let src_reg = self.next_temp_reg();
                         self.generate_expr_ir(expr, src_reg, instructions, constants)?;
-                        instructions.push(Instruction::Neg {
-                            dst: Operand::Local(result_reg),
-                            src: Operand::Local(src_reg),
+                        let (dst, src) = (Operand::Local(result_reg), Operand::Local(src_reg));
+                        instructions.push(if self.is_float_expr(expr) {
+                            Instruction::FNeg { dst, src }
+                        } else {
+                            Instruction::Neg { dst, src }
                         });
+                        self.push_sized_narrow(*span, result_reg, instructions);
                     }
                     ast::UnOp::Pos => {
                         // 正号：+x（无操作）
@@ -3643,62 +5821,7 @@ impl AstToIrGenerator {
             } => {
                 // Lambda 表达式 IR 生成
                 // 例如: (x, y) => x + y
-
-                // 1. 生成唯一的闭包函数名
-                let closure_name = format!("closure_{}", self.closure_counter);
-                self.closure_counter += 1;
-
-                // 2. 获取闭包的返回类型（简化处理：使用 Void）
-                // TODO: 可以通过类型检查结果获取更精确的返回类型
-                let return_type = MonoType::Void;
-
-                // 3. 为闭包参数分配寄存器索引
-                let _param_regs: Vec<usize> = (0..params.len()).collect();
-
-                let env_vars = std::mem::take(&mut self.pending_env_vars);
-
-                // 5. 生成闭包函数体 IR
-                // 类似于 generate_function_ir 的逻辑，但针对 Lambda
-                let closure_body =
-                    self.generate_lambda_body_ir(params, body.as_ref(), constants)?;
-
-                // 6. 创建闭包函数 IR
-                let param_types: Vec<MonoType> = params
-                    .iter()
-                    .filter_map(|p| p.ty.clone())
-                    .map(|t| t.into())
-                    .collect();
-
-                let closure_func = FunctionIR {
-                    name: closure_name.clone(),
-                    params: param_types,
-                    return_type,
-                    locals: closure_body.locals.clone(),
-                    blocks: vec![BasicBlock {
-                        label: 0,
-                        instructions: closure_body.instructions,
-                        successors: Vec::new(),
-                    }],
-                    entry: 0,
-                    generic_params: None,
-                };
-
-                // 7. 将闭包函数添加到嵌套函数列表
-                self.nested_functions.push(closure_func);
-
-                // 8. 保存闭包函数的可变局部变量信息
-                if !closure_body.mut_locals.is_empty() {
-                    self.module_mut_locals
-                        .insert(closure_name.clone(), closure_body.mut_locals);
-                }
-
-                // 9. 创建 MakeClosure 指令
-                // env 包含被捕获的外部变量的 Operand
-                instructions.push(Instruction::MakeClosure {
-                    dst: Operand::Local(result_reg),
-                    func: closure_name,
-                    env: env_vars,
-                });
+                self.generate_closure_ir(params, body, result_reg, instructions, constants)?;
             }
             Expr::Borrow {
                 mutable: _,
@@ -3719,176 +5842,16 @@ impl AstToIrGenerator {
             Expr::Match {
                 expr: match_expr,
                 arms,
-                span: _,
+                span,
             } => {
-                // Match 表达式 IR 生成
-                // 模式: match scrutinee { pat1 => body1, pat2 => body2, _ => bodyN }
-                //
-                // IR 结构:
-                //   1. 评估 scrutinee
-                //   2. 对每个 arm:
-                //      a. 如果模式是 Literal: 比较 scrutinee == literal, JmpIfNot 到下一个 arm
-                //      b. 如果模式是 Wildcard: 始终匹配
-                //      c. 生成 arm body, Move 结果到 result_reg, Jmp 到 end
-                //   3. 修复所有跳转目标
-
-                // 1. 评估 scrutinee
-                let scrutinee_reg = self.next_temp_reg();
-                self.generate_expr_ir(match_expr, scrutinee_reg, instructions, constants)?;
-
-                let mut jumps_to_end: Vec<usize> = Vec::new();
-
-                for arm in arms {
-                    // 检查模式是否匹配
-                    let needs_condition = matches!(arm.pattern, ast::Pattern::Wildcard);
-
-                    let jump_to_next_idx = if needs_condition {
-                        // Wildcard: 始终匹配，不需条件跳转
-                        None
-                    } else {
-                        // 生成条件: 比较 scrutinee 和模式值
-                        let cmp_reg = self.next_temp_reg();
-
-                        match &arm.pattern {
-                            ast::Pattern::Literal(lit) => {
-                                let const_val = match lit {
-                                    ast::Literal::Int(n) => ConstValue::Int(*n),
-                                    ast::Literal::Bool(b) => ConstValue::Bool(*b),
-                                    ast::Literal::Float(f) => ConstValue::Float(*f),
-                                    ast::Literal::String(s) => ConstValue::String(s.clone()),
-                                    ast::Literal::Char(c) => ConstValue::Char(*c),
-                                };
-                                constants.push(const_val.clone());
-                                instructions.push(Instruction::Load {
-                                    dst: Operand::Local(cmp_reg),
-                                    src: Operand::Const(const_val),
-                                });
-                            }
-                            _ => {
-                                // 不支持的 pattern: 加载 0，总会跳到下一个 arm
-                                instructions.push(Instruction::Load {
-                                    dst: Operand::Local(cmp_reg),
-                                    src: Operand::Const(ConstValue::Int(0)),
-                                });
-                            }
-                        }
-
-                        // 比较: scrutinee == pattern_value
-                        let eq_reg = self.next_temp_reg();
-                        instructions.push(Instruction::Eq {
-                            dst: Operand::Local(eq_reg),
-                            lhs: Operand::Local(scrutinee_reg),
-                            rhs: Operand::Local(cmp_reg),
-                        });
-
-                        // 如果不相等，跳到下一个 arm
-                        let jmp_idx = instructions.len();
-                        instructions.push(Instruction::JmpIfNot(
-                            Operand::Local(eq_reg),
-                            0, // 占位符
-                        ));
-                        Some(jmp_idx)
-                    };
-
-                    // 生成 arm body，结果放入 result_reg
-                    let arm_result_reg = self.next_temp_reg();
-                    self.generate_block_expr_ir(
-                        &arm.body,
-                        arm_result_reg,
-                        instructions,
-                        constants,
-                    )?;
-                    instructions.push(Instruction::Move {
-                        dst: Operand::Local(result_reg),
-                        src: Operand::Local(arm_result_reg),
-                    });
-
-                    // 跳转到 match 结束
-                    let jmp_end_idx = instructions.len();
-                    instructions.push(Instruction::Jmp(0)); // 占位符
-                    jumps_to_end.push(jmp_end_idx);
-
-                    // 修复条件跳转目标（指向当前 arm 之后的代码）
-                    if let Some(jmp_idx) = jump_to_next_idx {
-                        let current_pos = instructions.len();
-                        if let Instruction::JmpIfNot(_, ref mut target) = instructions[jmp_idx] {
-                            *target = current_pos;
-                        }
-                    }
-                }
-
-                // 修复所有跳转到结束的指令
-                let end_pos = instructions.len();
-                for idx in jumps_to_end {
-                    if let Instruction::Jmp(ref mut target) = instructions[idx] {
-                        *target = end_pos;
-                    }
-                }
-            }
-            // RFC-012: F-string 代码生成
-            Expr::FString { segments, span } => {
-                // 1. 尝试常量求值
-                if let Some(const_val) = self.eval_const_expr(expr) {
-                    constants.push(const_val.clone());
-                    instructions.push(Instruction::Load {
-                        dst: Operand::Local(result_reg),
-                        src: Operand::Const(const_val),
-                    });
-                    return Ok(());
-                }
-
-                // 2. 转换为 format() 调用
-                // 构建 format_str: "Hello {} is {} years old"
-                // 构建 args: [name, age]
-                let mut format_str = String::new();
-                let mut arg_regs = Vec::new();
-                let mut arg_index = 0usize;
-
-                for segment in segments {
-                    match segment {
-                        ast::FStringSegment::Text(text) => {
-                            format_str.push_str(text);
-                        }
-                        ast::FStringSegment::Interpolation {
-                            expr: interp_expr,
-                            format_spec,
-                        } => {
-                            // Build format placeholder: {0}, {1}, or {0:.2f}
-                            if let Some(spec) = format_spec {
-                                format_str.push_str(&format!("{{{0}:{1}}}", arg_index, spec));
-                            } else {
-                                format_str.push_str(&format!("{{{}}}", arg_index));
-                            }
-                            arg_index += 1;
-
-                            // Generate IR for the interpolation expression
-                            let arg_reg = self.next_temp_reg();
-                            self.generate_expr_ir(interp_expr, arg_reg, instructions, constants)?;
-                            arg_regs.push(Operand::Local(arg_reg));
-                        }
-                    }
-                }
-
-                // Load format string constant
-                let fmt_reg = self.next_temp_reg();
-                let fmt_const = ConstValue::String(format_str);
-                constants.push(fmt_const.clone());
-                instructions.push(Instruction::Load {
-                    dst: Operand::Local(fmt_reg),
-                    src: Operand::Const(fmt_const),
-                });
-
-                // Build args: [format_str, arg0, arg1, ...]
-                let mut call_args = vec![Operand::Local(fmt_reg)];
-                call_args.extend(arg_regs);
-
-                // Generate Call to std.string.format
-                instructions.push(Instruction::Call {
-                    dst: Some(Operand::Local(result_reg)),
-                    func: Operand::Const(ConstValue::String("std.string.format".to_string())),
-                    args: call_args,
-                    span: *span,
-                });
+                self.generate_match_ir(
+                    match_expr,
+                    arms,
+                    *span,
+                    result_reg,
+                    instructions,
+                    constants,
+                )?;
             }
             _ => {
                 // 默认返回 0
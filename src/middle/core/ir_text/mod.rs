@@ -0,0 +1,38 @@
+//! 文本 IR
+//!
+//! [`ModuleIR`](super::ir::ModuleIR) 的可读文本形式：`Display` 输出，[`parse_module`] 读回。
+//! 用于 `yaoxiang build --emit ir` 查看优化后的 IR，也便于直接手写 IR 测试优化遍。
+//!
+//! ```text
+//! global "limit": int64 = 10
+//! struct "Point" size 16 align 8 ["x": 0, "y": 8]
+//!
+//! fn "main"() -> void {
+//!   locals int64
+//!   var %l0 "i" mut
+//! bb0:
+//!      0: %l0 = move 0
+//!      1: %t0 = lt %l0, 10
+//!      2: jmp_if_not %t0, @5
+//!      3: %l0 = add %l0, 1
+//!      4: jmp @1
+//!      5: ret
+//! }
+//! ```
+//!
+//! 约定：
+//! - 操作数：`%lN` 局部变量、`%aN` 参数、`%tN` 临时值、`%gN` 全局、`%rN` 寄存器，常量按字面量书写
+//! - 跳转目标 `@N` 是函数内跨基本块的全局指令下标，与 IR 一致；指令前的 `N:` 仅作参考，可省略
+//! - 类型是有损的：具名结构体、枚举读回为类型引用；无法命名的类型用反引号包裹
+//! - `export "name"` 列出公开函数
+//! - 闭包函数体首行 `upvalues N` 标出捕获的变量数
+//! - 源码位置不输出，读回时为 `Span::dummy()`
+//! - `;` 之后为注释
+
+mod parser;
+mod printer;
+
+pub use parser::{parse_module, IrParseError};
+
+#[cfg(test)]
+mod tests;
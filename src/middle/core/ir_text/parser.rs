@@ -0,0 +1,1332 @@
+//! 文本 IR 解析
+
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::frontend::core::typecheck::MonoType;
+use crate::middle::core::ir::{
+    BasicBlock, ConstValue, ExecutionPlan, FfiBinding, FfiLibBinding, FunctionIR, InlineHint,
+    Instruction, ModuleIR, Operand, StructLayout, TaskGroup, Type, VTable,
+};
+use crate::util::span::Span;
+
+/// 文本 IR 解析错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IrParseError {
+    /// 出错的行号（从 1 开始）
+    pub line: usize,
+    /// 错误描述
+    pub message: String,
+}
+
+impl fmt::Display for IrParseError {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for IrParseError {}
+
+type Result<T> = std::result::Result<T, IrParseError>;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Int(i128),
+    Float(f64),
+    Str(String),
+    Char(char),
+    /// `%t0`、`%l1` 等寄存器/槽位
+    Var(String, usize),
+    /// 跳转目标 `@n`
+    Target(usize),
+    /// 反引号中的类型名
+    Quoted(String),
+    Arrow,
+    Punct(char),
+}
+
+impl fmt::Display for Token {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        match self {
+            Token::Ident(s) => write!(f, "`{}`", s),
+            Token::Int(i) => write!(f, "`{}`", i),
+            Token::Float(x) => write!(f, "`{:?}`", x),
+            Token::Str(s) => write!(f, "{:?}", s),
+            Token::Char(c) => write!(f, "{:?}", c),
+            Token::Var(kind, i) => write!(f, "`%{}{}`", kind, i),
+            Token::Target(i) => write!(f, "`@{}`", i),
+            Token::Quoted(s) => write!(f, "`{}`", s),
+            Token::Arrow => f.write_str("`->`"),
+            Token::Punct(c) => write!(f, "`{}`", c),
+        }
+    }
+}
+
+/// 一行的词法单元
+struct Line {
+    no: usize,
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+fn tokenize(
+    no: usize,
+    text: &str,
+) -> Result<Vec<Token>> {
+    let err = |message: String| IrParseError { line: no, message };
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c == ';' {
+            break;
+        } else if c == '-' && chars.get(i + 1) == Some(&'>') {
+            tokens.push(Token::Arrow);
+            i += 2;
+        } else if c.is_ascii_digit()
+            || (c == '-' && chars.get(i + 1).is_some_and(|d| d.is_ascii_digit()))
+        {
+            let start = i;
+            i += 1;
+            let mut float = false;
+            while i < chars.len() {
+                let d = chars[i];
+                if d.is_ascii_digit() {
+                    i += 1;
+                } else if d == '.' && chars.get(i + 1).is_some_and(|n| n.is_ascii_digit()) {
+                    float = true;
+                    i += 1;
+                } else if (d == 'e' || d == 'E')
+                    && chars
+                        .get(i + 1)
+                        .is_some_and(|n| n.is_ascii_digit() || *n == '-' || *n == '+')
+                {
+                    float = true;
+                    i += 2;
+                } else {
+                    break;
+                }
+            }
+            let text: String = chars[start..i].iter().collect();
+            tokens.push(if float {
+                Token::Float(
+                    text.parse()
+                        .map_err(|_| err(format!("invalid float `{}`", text)))?,
+                )
+            } else {
+                Token::Int(
+                    text.parse()
+                        .map_err(|_| err(format!("invalid integer `{}`", text)))?,
+                )
+            });
+        } else if c == '-' && chars[i + 1..].starts_with(&['i', 'n', 'f']) {
+            tokens.push(Token::Float(f64::NEG_INFINITY));
+            i += 4;
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len()
+                && (chars[i].is_alphanumeric() || chars[i] == '_' || chars[i] == '.')
+            {
+                i += 1;
+            }
+            tokens.push(Token::Ident(chars[start..i].iter().collect()));
+        } else if c == '%' || c == '@' {
+            let start = i + 1;
+            i += 1;
+            while i < chars.len() && chars[i].is_alphabetic() {
+                i += 1;
+            }
+            let kind: String = chars[start..i].iter().collect();
+            let digits = i;
+            while i < chars.len() && chars[i].is_ascii_digit() {
+                i += 1;
+            }
+            let index: usize = chars[digits..i]
+                .iter()
+                .collect::<String>()
+                .parse()
+                .map_err(|_| err(format!("expected a number after `{}{}`", c, kind)))?;
+            tokens.push(match (c, kind.is_empty()) {
+                ('@', true) => Token::Target(index),
+                ('%', false) => Token::Var(kind, index),
+                _ => return Err(err(format!("invalid `{}{}{}`", c, kind, index))),
+            });
+        } else if c == '"' || c == '\'' {
+            let (text, end) = unescape(&chars, i + 1, c).map_err(err)?;
+            i = end;
+            if c == '"' {
+                tokens.push(Token::Str(text));
+            } else {
+                let mut it = text.chars();
+                match (it.next(), it.next()) {
+                    (Some(ch), None) => tokens.push(Token::Char(ch)),
+                    _ => return Err(err(format!("invalid char literal '{}'", text))),
+                }
+            }
+        } else if c == '`' {
+            let start = i + 1;
+            let end = chars[start..]
+                .iter()
+                .position(|&ch| ch == '`')
+                .ok_or_else(|| err("unterminated `".to_string()))?;
+            tokens.push(Token::Quoted(chars[start..start + end].iter().collect()));
+            i = start + end + 1;
+        } else if "()[]{},:=#".contains(c) {
+            tokens.push(Token::Punct(c));
+            i += 1;
+        } else {
+            return Err(err(format!("unexpected character `{}`", c)));
+        }
+    }
+    Ok(tokens)
+}
+
+/// 读取引号内的内容（Rust 风格转义），返回内容与结束引号之后的位置
+fn unescape(
+    chars: &[char],
+    mut i: usize,
+    quote: char,
+) -> std::result::Result<(String, usize), String> {
+    let mut out = String::new();
+    while i < chars.len() {
+        match chars[i] {
+            c if c == quote => return Ok((out, i + 1)),
+            '\\' => {
+                let escaped = chars.get(i + 1).ok_or("unterminated escape")?;
+                i += 2;
+                match escaped {
+                    'n' => out.push('\n'),
+                    'r' => out.push('\r'),
+                    't' => out.push('\t'),
+                    '0' => out.push('\0'),
+                    '\\' | '"' | '\'' => out.push(*escaped),
+                    'u' => {
+                        if chars.get(i) != Some(&'{') {
+                            return Err("expected `{` after \\u".to_string());
+                        }
+                        let end = chars[i..]
+                            .iter()
+                            .position(|&c| c == '}')
+                            .ok_or("unterminated \\u{...}")?;
+                        let hex: String = chars[i + 1..i + end].iter().collect();
+                        let ch = u32::from_str_radix(&hex, 16)
+                            .ok()
+                            .and_then(char::from_u32)
+                            .ok_or_else(|| format!("invalid escape \\u{{{}}}", hex))?;
+                        out.push(ch);
+                        i += end + 1;
+                    }
+                    other => return Err(format!("unknown escape \\{}", other)),
+                }
+            }
+            c => {
+                out.push(c);
+                i += 1;
+            }
+        }
+    }
+    Err(format!("unterminated {}", quote))
+}
+
+impl Line {
+    fn err<T>(
+        &self,
+        message: impl Into<String>,
+    ) -> Result<T> {
+        Err(IrParseError {
+            line: self.no,
+            message: message.into(),
+        })
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn peek_at(
+        &self,
+        offset: usize,
+    ) -> Option<&Token> {
+        self.tokens.get(self.pos + offset)
+    }
+
+    fn next(&mut self) -> Result<Token> {
+        match self.tokens.get(self.pos) {
+            Some(token) => {
+                self.pos += 1;
+                Ok(token.clone())
+            }
+            None => self.err("unexpected end of line"),
+        }
+    }
+
+    fn unexpected<T>(
+        &self,
+        token: &Token,
+        expected: &str,
+    ) -> Result<T> {
+        self.err(format!("expected {}, found {}", expected, token))
+    }
+
+    fn is_punct(
+        &self,
+        c: char,
+    ) -> bool {
+        self.peek() == Some(&Token::Punct(c))
+    }
+
+    fn eat_punct(
+        &mut self,
+        c: char,
+    ) -> bool {
+        let found = self.is_punct(c);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn punct(
+        &mut self,
+        c: char,
+    ) -> Result<()> {
+        let token = self.next()?;
+        if token == Token::Punct(c) {
+            Ok(())
+        } else {
+            self.unexpected(&token, &format!("`{}`", c))
+        }
+    }
+
+    fn is_ident(
+        &self,
+        word: &str,
+    ) -> bool {
+        matches!(self.peek(), Some(Token::Ident(s)) if s == word)
+    }
+
+    fn eat_ident(
+        &mut self,
+        word: &str,
+    ) -> bool {
+        let found = self.is_ident(word);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn keyword(
+        &mut self,
+        word: &str,
+    ) -> Result<()> {
+        let token = self.next()?;
+        match &token {
+            Token::Ident(s) if s == word => Ok(()),
+            _ => self.unexpected(&token, &format!("`{}`", word)),
+        }
+    }
+
+    fn ident(&mut self) -> Result<String> {
+        match self.next()? {
+            Token::Ident(s) => Ok(s),
+            token => self.unexpected(&token, "a name"),
+        }
+    }
+
+    fn string(&mut self) -> Result<String> {
+        match self.next()? {
+            Token::Str(s) => Ok(s),
+            token => self.unexpected(&token, "a string"),
+        }
+    }
+
+    fn usize(&mut self) -> Result<usize> {
+        match self.next()? {
+            Token::Int(i) if i >= 0 => usize::try_from(i).or_else(|_| self.err("number too large")),
+            token => self.unexpected(&token, "a non-negative integer"),
+        }
+    }
+
+    fn target(&mut self) -> Result<usize> {
+        match self.next()? {
+            Token::Target(i) => Ok(i),
+            token => self.unexpected(&token, "a jump target `@n`"),
+        }
+    }
+
+    /// `bbN` 中的 N
+    fn block_label(&mut self) -> Result<usize> {
+        let name = self.ident()?;
+        match name.strip_prefix("bb").and_then(|n| n.parse().ok()) {
+            Some(label) => Ok(label),
+            None => self.err(format!("expected a block label `bbN`, found `{}`", name)),
+        }
+    }
+
+    fn end(&self) -> Result<()> {
+        match self.peek() {
+            None => Ok(()),
+            Some(token) => self.unexpected(token, "end of line"),
+        }
+    }
+
+    /// 逗号分隔的列表，直到 `close`（不含）
+    fn list<T>(
+        &mut self,
+        close: char,
+        mut item: impl FnMut(&mut Self) -> Result<T>,
+    ) -> Result<Vec<T>> {
+        let mut items = Vec::new();
+        if self.is_punct(close) {
+            return Ok(items);
+        }
+        loop {
+            items.push(item(self)?);
+            if !self.eat_punct(',') {
+                return Ok(items);
+            }
+        }
+    }
+
+    /// `open item, ... close`
+    fn delimited<T>(
+        &mut self,
+        open: char,
+        close: char,
+        item: impl FnMut(&mut Self) -> Result<T>,
+    ) -> Result<Vec<T>> {
+        self.punct(open)?;
+        let items = self.list(close, item)?;
+        self.punct(close)?;
+        Ok(items)
+    }
+
+    fn operand(&mut self) -> Result<Operand> {
+        if let Some(Token::Var(kind, index)) = self.peek() {
+            let (kind, index) = (kind.clone(), *index);
+            self.pos += 1;
+            return match kind.as_str() {
+                "t" => Ok(Operand::Temp(index)),
+                "l" => Ok(Operand::Local(index)),
+                "a" => Ok(Operand::Arg(index)),
+                "g" => Ok(Operand::Global(index)),
+                "label" => Ok(Operand::Label(index)),
+                "r" => match u8::try_from(index) {
+                    Ok(reg) => Ok(Operand::Register(reg)),
+                    Err(_) => self.err(format!("register %r{} out of range", index)),
+                },
+                _ => self.err(format!("unknown operand kind `%{}`", kind)),
+            };
+        }
+        self.constant().map(Operand::Const)
+    }
+
+    fn constant(&mut self) -> Result<ConstValue> {
+        let token = self.next()?;
+        match token {
+            Token::Int(i) => Ok(ConstValue::Int(i)),
+            Token::Float(x) => Ok(ConstValue::Float(x)),
+            Token::Str(s) => Ok(ConstValue::String(s)),
+            Token::Char(c) => Ok(ConstValue::Char(c)),
+            Token::Punct('[') => {
+                let items = self.list(']', Self::constant)?;
+                self.punct(']')?;
+                Ok(ConstValue::List(items))
+            }
+            Token::Punct('{') => {
+                let entries = self.list('}', |line| {
+                    let key = line.constant()?;
+                    line.punct(':')?;
+                    Ok((key, line.constant()?))
+                })?;
+                self.punct('}')?;
+                Ok(ConstValue::Dict(entries))
+            }
+            Token::Ident(word) => match word.as_str() {
+                "void" => Ok(ConstValue::Void),
+                "true" => Ok(ConstValue::Bool(true)),
+                "false" => Ok(ConstValue::Bool(false)),
+                "NaN" => Ok(ConstValue::Float(f64::NAN)),
+                "inf" => Ok(ConstValue::Float(f64::INFINITY)),
+                "bytes" => {
+                    let bytes = self.delimited('[', ']', |line| match line.next()? {
+                        Token::Int(b) if (0..=255).contains(&b) => Ok(b as u8),
+                        token => line.unexpected(&token, "a byte"),
+                    })?;
+                    Ok(ConstValue::Bytes(bytes))
+                }
+                "library" => {
+                    self.punct('(')?;
+                    let mechanism = self.string()?;
+                    self.punct(',')?;
+                    let lib = self.string()?;
+                    self.punct(')')?;
+                    Ok(ConstValue::LibraryRef { mechanism, lib })
+                }
+                "extern" => {
+                    self.punct('(')?;
+                    let mechanism = self.string()?;
+                    self.punct(',')?;
+                    let lib = self.string()?;
+                    self.punct(',')?;
+                    let symbol = self.string()?;
+                    self.punct(')')?;
+                    Ok(ConstValue::ExternRef {
+                        mechanism,
+                        lib,
+                        symbol,
+                    })
+                }
+                "struct" => {
+                    self.punct('(')?;
+                    let type_name = self.string()?;
+                    self.punct(',')?;
+                    let fields = self.delimited('[', ']', |line| line.constant())?;
+                    self.punct(')')?;
+                    Ok(ConstValue::Struct { type_name, fields })
+                }
+                _ => self.err(format!("expected an operand, found `{}`", word)),
+            },
+            token => self.unexpected(&token, "an operand"),
+        }
+    }
+
+    fn mono_type(&mut self) -> Result<MonoType> {
+        let token = self.next()?;
+        let name = match token {
+            Token::Quoted(name) => return Ok(MonoType::TypeRef(name)),
+            Token::Punct('(') => {
+                let items = self.list(')', Self::mono_type)?;
+                self.punct(')')?;
+                return Ok(MonoType::Tuple(items));
+            }
+            Token::Ident(name) => name,
+            token => return self.unexpected(&token, "a type"),
+        };
+        if name == "fn" {
+            let params = self.delimited('(', ')', Self::mono_type)?;
+            match self.next()? {
+                Token::Arrow => {}
+                token => return self.unexpected(&token, "`->`"),
+            }
+            return Ok(MonoType::Fn {
+                params,
+                return_type: Box::new(self.mono_type()?),
+            });
+        }
+        if self.is_punct('(') {
+            let mut args = self.delimited('(', ')', Self::mono_type)?;
+            let boxed = |args: &mut Vec<MonoType>| Box::new(args.remove(0));
+            return Ok(match (name.as_str(), args.len()) {
+                ("List", 1) => MonoType::List(boxed(&mut args)),
+                ("Set", 1) => MonoType::Set(boxed(&mut args)),
+                ("Option", 1) => MonoType::Option(boxed(&mut args)),
+                ("Arc", 1) => MonoType::Arc(boxed(&mut args)),
+                ("Weak", 1) => MonoType::Weak(boxed(&mut args)),
+                ("Async", 1) => MonoType::Async(boxed(&mut args)),
+                ("Range", 1) => MonoType::Range {
+                    elem_type: boxed(&mut args),
+                },
+                ("Dict", 2) => {
+                    let key = boxed(&mut args);
+                    MonoType::Dict(key, boxed(&mut args))
+                }
+                ("Result", 2) => {
+                    let ok = boxed(&mut args);
+                    MonoType::Result(ok, boxed(&mut args))
+                }
+                _ => MonoType::Generic { name, args },
+            });
+        }
+        let width = |prefix: &str| name.strip_prefix(prefix).and_then(|n| n.parse().ok());
+        Ok(match name.as_str() {
+            "void" => MonoType::Void,
+            "bool" => MonoType::Bool,
+            "char" => MonoType::Char,
+            "string" => MonoType::String,
+            "bytes" => MonoType::Bytes,
+            "Any" => MonoType::Any,
+            _ => match (width("int"), width("float")) {
+                (Some(n), _) => MonoType::Int(n),
+                (_, Some(n)) => MonoType::Float(n),
+                _ => MonoType::TypeRef(name),
+            },
+        })
+    }
+
+    fn ast_type(&mut self) -> Result<Type> {
+        Ok(to_ast_type(self.mono_type()?))
+    }
+
+    fn args(&mut self) -> Result<Vec<Operand>> {
+        self.delimited('(', ')', Self::operand)
+    }
+
+    fn plan(&mut self) -> Result<ExecutionPlan> {
+        self.keyword("groups")?;
+        let groups = self
+            .delimited('[', ']', |line| line.delimited('[', ']', Self::usize))?
+            .into_iter()
+            .map(|task_indices| TaskGroup { task_indices })
+            .collect();
+        self.keyword("deps")?;
+        let task_deps = self.delimited('[', ']', |line| line.delimited('[', ']', Self::usize))?;
+        self.keyword("resources")?;
+        let task_resources =
+            self.delimited('[', ']', |line| line.delimited('[', ']', Self::string))?;
+        Ok(ExecutionPlan {
+            groups,
+            task_deps,
+            task_resources,
+        })
+    }
+
+    fn opt_name(&mut self) -> Result<Option<String>> {
+        if self.eat_ident("_") {
+            Ok(None)
+        } else {
+            self.string().map(Some)
+        }
+    }
+}
+
+/// 单态类型转回语法树类型（用于 `cast` 等指令与全局变量）
+fn to_ast_type(ty: MonoType) -> Type {
+    let span = Span::dummy();
+    let generic = |name: &str, args: Vec<MonoType>| Type::Generic {
+        name: name.to_string(),
+        name_span: span,
+        args: args.into_iter().map(to_ast_type).collect(),
+    };
+    match ty {
+        MonoType::Void => Type::Void,
+        MonoType::Bool => Type::Bool,
+        MonoType::Int(n) => Type::Int(n),
+        MonoType::Float(n) => Type::Float(n),
+        MonoType::Char => Type::Char,
+        MonoType::String => Type::String,
+        MonoType::Bytes => Type::Bytes,
+        MonoType::Any => Type::Name {
+            name: "Any".to_string(),
+            span,
+        },
+        MonoType::Tuple(items) => Type::Tuple(items.into_iter().map(to_ast_type).collect()),
+        MonoType::Fn {
+            params,
+            return_type,
+        } => Type::Fn {
+            params: params.into_iter().map(to_ast_type).collect(),
+            return_type: Box::new(to_ast_type(*return_type)),
+        },
+        MonoType::Option(t) => Type::Option(Box::new(to_ast_type(*t))),
+        MonoType::Result(ok, err) => {
+            Type::Result(Box::new(to_ast_type(*ok)), Box::new(to_ast_type(*err)))
+        }
+        MonoType::List(t) => generic("List", vec![*t]),
+        MonoType::Set(t) => generic("Set", vec![*t]),
+        MonoType::Arc(t) => generic("Arc", vec![*t]),
+        MonoType::Weak(t) => generic("Weak", vec![*t]),
+        MonoType::Async(t) => generic("Async", vec![*t]),
+        MonoType::Range { elem_type } => generic("Range", vec![*elem_type]),
+        MonoType::Dict(k, v) => generic("Dict", vec![*k, *v]),
+        MonoType::Generic { name, args } => generic(&name, args),
+        other => Type::Name {
+            name: other.type_name(),
+            span,
+        },
+    }
+}
+
+/// 解析一条指令（不含下标前缀）
+fn instruction(line: &mut Line) -> Result<Instruction> {
+    let dst = match (line.peek(), line.peek_at(1)) {
+        (Some(Token::Var(..)), Some(Token::Punct('='))) => {
+            let dst = line.operand()?;
+            line.pos += 1;
+            Some(dst)
+        }
+        _ => None,
+    };
+    let mnemonic = line.ident()?;
+    let span = Span::dummy();
+    let need_dst = |line: &Line| -> Result<Operand> {
+        match &dst {
+            Some(dst) => Ok(dst.clone()),
+            None => line.err(format!("`{}` needs a destination `%x = ...`", mnemonic)),
+        }
+    };
+    let no_dst = |line: &Line| -> Result<()> {
+        match &dst {
+            Some(_) => line.err(format!("`{}` has no destination", mnemonic)),
+            None => Ok(()),
+        }
+    };
+
+    macro_rules! binary {
+        ($variant:ident $(, $field:ident: $value:expr)*) => {{
+            let dst = need_dst(line)?;
+            let lhs = line.operand()?;
+            line.punct(',')?;
+            let rhs = line.operand()?;
+            Instruction::$variant { dst, lhs, rhs $(, $field: $value)* }
+        }};
+    }
+    macro_rules! unary {
+        ($variant:ident) => {{
+            let dst = need_dst(line)?;
+            let src = line.operand()?;
+            Instruction::$variant { dst, src }
+        }};
+    }
+    macro_rules! single {
+        ($variant:ident) => {{
+            no_dst(line)?;
+            Instruction::$variant(line.operand()?)
+        }};
+    }
+    macro_rules! typed {
+        ($variant:ident) => {{
+            let dst = need_dst(line)?;
+            let src = line.operand()?;
+            line.keyword("to")?;
+            let target_type = line.ast_type()?;
+            Instruction::$variant {
+                dst,
+                src,
+                target_type,
+            }
+        }};
+    }
+
+    let instr = match mnemonic.as_str() {
+        "move" => unary!(Move),
+        "load" => unary!(Load),
+        "neg" => unary!(Neg),
+        "fneg" => unary!(FNeg),
+        "int_to_float" => unary!(IntToFloat),
+        "arc_new" => unary!(ArcNew),
+        "rc_new" => unary!(RcNew),
+        "arc_clone" => unary!(ArcClone),
+        "ptr_from_ref" => unary!(PtrFromRef),
+        "ptr_deref" => unary!(PtrDeref),
+        "ptr_load" => unary!(PtrLoad),
+        "string_length" => unary!(StringLength),
+        "string_from_int" => unary!(StringFromInt),
+        "string_from_float" => unary!(StringFromFloat),
+        "string_builder_new" => unary!(StringBuilderNew),
+        "string_builder_finish" => unary!(StringBuilderFinish),
+        "stack_alloc" => unary!(StackAlloc),
+        "add" => binary!(Add, span: span),
+        "sub" => binary!(Sub, span: span),
+        "mul" => binary!(Mul, span: span),
+        "div" => binary!(Div, span: span),
+        "mod" => binary!(Mod, span: span),
+        "and" => binary!(And),
+        "or" => binary!(Or),
+        "xor" => binary!(Xor),
+        "shl" => binary!(Shl),
+        "shr" => binary!(Shr),
+        "sar" => binary!(Sar),
+        "eq" => binary!(Eq),
+        "ne" => binary!(Ne),
+        "lt" => binary!(Lt),
+        "le" => binary!(Le),
+        "gt" => binary!(Gt),
+        "ge" => binary!(Ge),
+        "fadd" => binary!(FAdd),
+        "fsub" => binary!(FSub),
+        "fmul" => binary!(FMul),
+        "fdiv" => binary!(FDiv),
+        "fmod" => binary!(FMod),
+        "feq" => binary!(FEq),
+        "fne" => binary!(FNe),
+        "flt" => binary!(FLt),
+        "fle" => binary!(FLe),
+        "fgt" => binary!(FGt),
+        "fge" => binary!(FGe),
+        "string_concat" => binary!(StringConcat),
+        "string_builder_append" => binary!(StringBuilderAppend),
+        "string_get_char" => {
+            let dst = need_dst(line)?;
+            let src = line.operand()?;
+            line.punct(',')?;
+            let index = line.operand()?;
+            Instruction::StringGetChar { dst, src, index }
+        }
+        "load_index" => {
+            let dst = need_dst(line)?;
+            let src = line.operand()?;
+            line.punct(',')?;
+            let index = line.operand()?;
+            Instruction::LoadIndex {
+                dst,
+                src,
+                index,
+                span,
+            }
+        }
+        "alloc" => {
+            let dst = need_dst(line)?;
+            let size = line.operand()?;
+            Instruction::Alloc { dst, size }
+        }
+        "alloc_array" => {
+            let dst = need_dst(line)?;
+            let size = line.operand()?;
+            line.punct(',')?;
+            let elem_size = line.operand()?;
+            Instruction::AllocArray {
+                dst,
+                size,
+                elem_size,
+            }
+        }
+        "store" | "ptr_store" | "store_index" => {
+            no_dst(line)?;
+            let dst = line.operand()?;
+            line.punct(',')?;
+            let first = line.operand()?;
+            match mnemonic.as_str() {
+                "store" => Instruction::Store {
+                    dst,
+                    src: first,
+                    span,
+                },
+                "ptr_store" => Instruction::PtrStore { dst, src: first },
+                _ => {
+                    line.punct(',')?;
+                    Instruction::StoreIndex {
+                        dst,
+                        index: first,
+                        src: line.operand()?,
+                        span,
+                    }
+                }
+            }
+        }
+        "push" => single!(Push),
+        "pop" => single!(Pop),
+        "free" => single!(Free),
+        "drop" => single!(Drop),
+        "arc_drop" => single!(ArcDrop),
+        "close_upvalue" => single!(CloseUpvalue),
+        "dup" | "swap" | "yield" | "unsafe_begin" | "unsafe_end" => {
+            no_dst(line)?;
+            match mnemonic.as_str() {
+                "dup" => Instruction::Dup,
+                "swap" => Instruction::Swap,
+                "yield" => Instruction::Yield,
+                "unsafe_begin" => Instruction::UnsafeBlockStart,
+                _ => Instruction::UnsafeBlockEnd,
+            }
+        }
+        "ret" => {
+            no_dst(line)?;
+            Instruction::Ret(match line.peek() {
+                None => None,
+                Some(_) => Some(line.operand()?),
+            })
+        }
+        "jmp" => {
+            no_dst(line)?;
+            Instruction::Jmp(line.target()?)
+        }
+        "jmp_if" | "jmp_if_not" => {
+            no_dst(line)?;
+            let cond = line.operand()?;
+            line.punct(',')?;
+            let target = line.target()?;
+            if mnemonic == "jmp_if" {
+                Instruction::JmpIf(cond, target)
+            } else {
+                Instruction::JmpIfNot(cond, target)
+            }
+        }
+        "switch" => {
+            no_dst(line)?;
+            let value = line.operand()?;
+            line.punct(',')?;
+            let low = match line.next()? {
+                Token::Int(low) => i64::try_from(low).or_else(|_| line.err("number too large"))?,
+                token => return line.unexpected(&token, "an integer"),
+            };
+            line.punct(',')?;
+            let targets = line.delimited('[', ']', Line::target)?;
+            line.punct(',')?;
+            Instruction::Switch {
+                value,
+                low,
+                targets,
+                default: line.target()?,
+            }
+        }
+        "call" => {
+            let func = line.operand()?;
+            Instruction::Call {
+                dst,
+                func,
+                args: line.args()?,
+                span,
+            }
+        }
+        "call_dyn" => {
+            let func = line.operand()?;
+            Instruction::CallDyn {
+                dst,
+                func,
+                args: line.args()?,
+                span,
+            }
+        }
+        "tail_call" => {
+            no_dst(line)?;
+            let func = line.operand()?;
+            Instruction::TailCall {
+                func,
+                args: line.args()?,
+            }
+        }
+        "call_virt" => {
+            let obj = line.operand()?;
+            line.punct(',')?;
+            let method_name = line.string()?;
+            Instruction::CallVirt {
+                dst,
+                obj,
+                method_name,
+                args: line.args()?,
+                span,
+            }
+        }
+        "invoke_virtual" => {
+            let obj = line.operand()?;
+            line.punct(',')?;
+            let slot = line.usize()?;
+            line.punct(',')?;
+            let method_name = line.string()?;
+            Instruction::InvokeVirtual {
+                dst,
+                obj,
+                slot,
+                method_name,
+                args: line.args()?,
+                span,
+            }
+        }
+        "make_dyn" => {
+            let dst = need_dst(line)?;
+            let src = line.operand()?;
+            line.punct(',')?;
+            let vtable = line.usize()?;
+            Instruction::MakeDyn { dst, src, vtable }
+        }
+        "load_field" => {
+            let dst = need_dst(line)?;
+            let src = line.operand()?;
+            line.punct(',')?;
+            let field = line.usize()?;
+            line.punct(',')?;
+            let type_name = line.opt_name()?;
+            Instruction::LoadField {
+                dst,
+                src,
+                field,
+                type_name,
+                span,
+            }
+        }
+        "load_record_field" => {
+            let dst = need_dst(line)?;
+            let src = line.operand()?;
+            line.punct(',')?;
+            let field = line.string()?;
+            Instruction::LoadRecordField {
+                dst,
+                src,
+                field,
+                span,
+            }
+        }
+        "store_field" => {
+            no_dst(line)?;
+            let dst = line.operand()?;
+            line.punct(',')?;
+            let field = line.usize()?;
+            line.punct(',')?;
+            let src = line.operand()?;
+            line.punct(',')?;
+            let type_name = line.opt_name()?;
+            line.punct(',')?;
+            let field_name = line.opt_name()?;
+            Instruction::StoreField {
+                dst,
+                field,
+                src,
+                type_name,
+                field_name,
+                span,
+            }
+        }
+        "cast" => typed!(Cast),
+        "narrow" => typed!(Narrow),
+        "type_test" => typed!(TypeTest),
+        "spawn" => {
+            let result = need_dst(line)?;
+            let closures = line.delimited('[', ']', Line::operand)?;
+            Instruction::Spawn {
+                closures,
+                plan: line.plan()?,
+                result,
+            }
+        }
+        "spawn_from_list" => {
+            let result = need_dst(line)?;
+            let closures_list = line.operand()?;
+            Instruction::SpawnFromList {
+                closures_list,
+                plan: line.plan()?,
+                result,
+            }
+        }
+        "heap_alloc" => {
+            let dst = need_dst(line)?;
+            Instruction::HeapAlloc {
+                dst,
+                type_id: line.usize()?,
+            }
+        }
+        "create_struct" => {
+            let dst = need_dst(line)?;
+            let type_name = line.string()?;
+            Instruction::CreateStruct {
+                dst,
+                type_name,
+                fields: line.args()?,
+            }
+        }
+        "new_dict" => {
+            let dst = need_dst(line)?;
+            let entries = line.delimited('{', '}', |line| {
+                let key = line.operand()?;
+                line.punct(':')?;
+                Ok((key, line.operand()?))
+            })?;
+            let (keys, values) = entries.into_iter().unzip();
+            Instruction::NewDict { dst, keys, values }
+        }
+        "make_closure" => {
+            let dst = need_dst(line)?;
+            let func = line.string()?;
+            Instruction::MakeClosure {
+                dst,
+                func,
+                env: line.args()?,
+            }
+        }
+        "load_upvalue" => {
+            let dst = need_dst(line)?;
+            Instruction::LoadUpvalue {
+                dst,
+                upvalue_idx: line.usize()?,
+            }
+        }
+        "store_upvalue" => {
+            no_dst(line)?;
+            let upvalue_idx = line.usize()?;
+            line.punct(',')?;
+            Instruction::StoreUpvalue {
+                src: line.operand()?,
+                upvalue_idx,
+            }
+        }
+        other => return line.err(format!("unknown instruction `{}`", other)),
+    };
+    line.end()?;
+    Ok(instr)
+}
+
+/// 解析文本 IR
+///
+/// 与 [`ModuleIR`] 的 `Display` 输出互逆（源码位置除外）。指令前的下标 `n:` 可以省略；
+/// 函数体在第一个块标签之前出现的指令归入 `bb0`。
+pub fn parse_module(text: &str) -> Result<ModuleIR> {
+    let mut lines = Vec::new();
+    for (i, raw) in text.lines().enumerate() {
+        let tokens = tokenize(i + 1, raw)?;
+        if !tokens.is_empty() {
+            lines.push(Line {
+                no: i + 1,
+                tokens,
+                pos: 0,
+            });
+        }
+    }
+
+    let mut module = ModuleIR::default();
+    let mut hint = None;
+    let mut lines = lines.into_iter();
+    while let Some(mut line) = lines.next() {
+        let Some(Token::Ident(word)) = line.peek().cloned() else {
+            if line.eat_punct('#') {
+                line.punct('[')?;
+                hint = Some(match line.ident()?.as_str() {
+                    "inline" => InlineHint::Always,
+                    "noinline" => InlineHint::Never,
+                    other => return line.err(format!("unknown attribute `{}`", other)),
+                });
+                line.punct(']')?;
+                line.end()?;
+                continue;
+            }
+            let token = line.next()?;
+            return line.unexpected(&token, "a declaration");
+        };
+        line.pos += 1;
+        match word.as_str() {
+            "global" => {
+                let name = line.string()?;
+                line.punct(':')?;
+                let ty = line.ast_type()?;
+                let value = if line.eat_punct('=') {
+                    Some(line.constant()?)
+                } else {
+                    None
+                };
+                module.globals.push((name, ty, value));
+            }
+            "struct" => {
+                let name = line.string()?;
+                line.keyword("size")?;
+                let size = line.usize()? as u32;
+                line.keyword("align")?;
+                let align = line.usize()? as u32;
+                let entries = line.delimited('[', ']', |line| {
+                    let field = line.string()?;
+                    line.punct(':')?;
+                    Ok((field, line.usize()? as u32))
+                })?;
+                let (fields, offsets) = entries.into_iter().unzip();
+                module.struct_layouts.push(StructLayout {
+                    name,
+                    fields,
+                    offsets,
+                    size,
+                    align,
+                });
+            }
+            "vtable" => {
+                let interface = line.string()?;
+                line.keyword("for")?;
+                let type_name = line.string()?;
+                let methods = line.delimited('[', ']', Line::string)?;
+                module.vtables.push(VTable {
+                    interface,
+                    type_name,
+                    methods,
+                });
+            }
+            "ffi_lib" => {
+                let id = line.usize()?;
+                let mechanism = line.string()?;
+                let lib_name = line.string()?;
+                module.ffi_libs.push(FfiLibBinding {
+                    id,
+                    mechanism,
+                    lib_name,
+                });
+            }
+            "ffi_type" | "ffi_fn" => {
+                let name = line.string()?;
+                line.punct('=')?;
+                let lib_id = line.usize()?;
+                let symbol = line.string()?;
+                module.ffi_bindings.push(if word == "ffi_type" {
+                    FfiBinding::TypeBinding {
+                        type_name: name,
+                        lib_id,
+                        symbol,
+                    }
+                } else {
+                    FfiBinding::FuncBinding {
+                        func_name: name,
+                        lib_id,
+                        symbol,
+                    }
+                });
+            }
+            "export" => module.exports.push(line.string()?),
+            "fn" => {
+                let func = function(&mut line, &mut lines, &mut module)?;
+                if let Some(hint) = hint.take() {
+                    module.inline_hints.insert(func.name.clone(), hint);
+                }
+                module.functions.push(func);
+                continue;
+            }
+            other => return line.err(format!("unknown declaration `{}`", other)),
+        }
+        line.end()?;
+    }
+    Ok(module)
+}
+
+/// 解析函数：`header` 是 `fn` 之后的首行，其余行从 `lines` 读取直到 `}`
+fn function(
+    header: &mut Line,
+    lines: &mut impl Iterator<Item = Line>,
+    module: &mut ModuleIR,
+) -> Result<FunctionIR> {
+    let name = header.string()?;
+    let generic_params = if header.is_punct('[') {
+        Some(header.delimited('[', ']', Line::ident)?)
+    } else {
+        None
+    };
+    let params = header.delimited('(', ')', Line::mono_type)?;
+    match header.next()? {
+        Token::Arrow => {}
+        token => return header.unexpected(&token, "`->`"),
+    }
+    let return_type = header.mono_type()?;
+    header.punct('{')?;
+    header.end()?;
+
+    let mut func = FunctionIR {
+        name,
+        params,
+        return_type,
+        locals: Vec::new(),
+        blocks: Vec::new(),
+        entry: 0,
+        generic_params,
+    };
+    let mut names: Vec<String> = Vec::new();
+    let mut muts = HashSet::new();
+    let mut loops = HashSet::new();
+    let mut closed = false;
+    let mut last_line = header.no;
+
+    for mut line in lines.by_ref() {
+        last_line = line.no;
+        if line.eat_punct('}') {
+            line.end()?;
+            closed = true;
+            break;
+        }
+        // 块标签：`bb0:` / `bb0 -> bb1, bb2:`
+        let is_block = matches!(line.peek(), Some(Token::Ident(s)) if s.starts_with("bb"))
+            && matches!(
+                line.peek_at(1),
+                Some(Token::Punct(':')) | Some(Token::Arrow)
+            );
+        if is_block {
+            let label = line.block_label()?;
+            let mut successors = Vec::new();
+            if line.peek() == Some(&Token::Arrow) {
+                line.pos += 1;
+                successors = line.list(':', Line::block_label)?;
+            }
+            line.punct(':')?;
+            line.end()?;
+            func.blocks.push(BasicBlock {
+                label,
+                instructions: Vec::new(),
+                successors,
+            });
+            continue;
+        }
+        if line.eat_ident("entry") {
+            func.entry = line.block_label()?;
+            line.end()?;
+            continue;
+        }
+        if line.eat_ident("upvalues") {
+            let count = line.usize()?;
+            line.end()?;
+            module.upvalue_counts.insert(func.name.clone(), count);
+            continue;
+        }
+        if line.eat_ident("locals") {
+            func.locals = line.list('\0', Line::mono_type)?;
+            line.end()?;
+            continue;
+        }
+        if line.eat_ident("var") {
+            let index = match line.operand()? {
+                Operand::Local(index) => index,
+                _ => return line.err("expected a local `%lN`"),
+            };
+            if let Some(Token::Str(name)) = line.peek().cloned() {
+                line.pos += 1;
+                if names.len() <= index {
+                    names.resize(index + 1, String::new());
+                }
+                names[index] = name;
+            }
+            while let Some(token) = line.peek().cloned() {
+                match token {
+                    Token::Ident(flag) if flag == "mut" => muts.insert(index),
+                    Token::Ident(flag) if flag == "loop" => loops.insert(index),
+                    token => return line.unexpected(&token, "`mut` or `loop`"),
+                };
+                line.pos += 1;
+            }
+            continue;
+        }
+
+        // 指令：可选的下标前缀 `n:`
+        if matches!(
+            (line.peek(), line.peek_at(1)),
+            (Some(Token::Int(_)), Some(Token::Punct(':')))
+        ) {
+            line.pos += 2;
+        }
+        let instr = instruction(&mut line)?;
+        if func.blocks.is_empty() {
+            func.blocks.push(BasicBlock {
+                label: 0,
+                instructions: Vec::new(),
+                successors: Vec::new(),
+            });
+        }
+        if let Some(block) = func.blocks.last_mut() {
+            block.instructions.push(instr);
+        }
+    }
+    if !closed {
+        return Err(IrParseError {
+            line: last_line,
+            message: format!("function `{}` is missing its closing `}}`", func.name),
+        });
+    }
+
+    if !names.is_empty() {
+        module.local_names.insert(func.name.clone(), names);
+    }
+    if !muts.is_empty() {
+        module.mut_locals.insert(func.name.clone(), muts);
+    }
+    if !loops.is_empty() {
+        module.loop_binding_locals.insert(func.name.clone(), loops);
+    }
+    Ok(func)
+}
+
+impl std::str::FromStr for ModuleIR {
+    type Err = IrParseError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        parse_module(s)
+    }
+}
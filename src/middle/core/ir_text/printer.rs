@@ -0,0 +1,664 @@
+//! 文本 IR 输出
+
+use std::collections::BTreeSet;
+use std::fmt::{self, Display, Formatter, Write};
+
+use crate::frontend::core::typecheck::MonoType;
+use crate::middle::core::ir::{
+    BasicBlock, ConstValue, ExecutionPlan, FfiBinding, FunctionIR, InlineHint, Instruction,
+    ModuleIR, Operand, Type,
+};
+
+/// IR 中的类型
+struct TypeText<'a>(&'a MonoType);
+
+impl Display for TypeText<'_> {
+    fn fmt(
+        &self,
+        f: &mut Formatter<'_>,
+    ) -> fmt::Result {
+        match self.0 {
+            MonoType::Void => f.write_str("void"),
+            MonoType::Bool => f.write_str("bool"),
+            MonoType::Int(n) => write!(f, "int{}", n),
+            MonoType::Float(n) => write!(f, "float{}", n),
+            MonoType::Char => f.write_str("char"),
+            MonoType::String => f.write_str("string"),
+            MonoType::Bytes => f.write_str("bytes"),
+            MonoType::Any => f.write_str("Any"),
+            MonoType::Struct(s) if is_name(&s.name) => f.write_str(&s.name),
+            MonoType::Enum(e) if is_name(&e.name) => f.write_str(&e.name),
+            MonoType::TypeRef(name) if is_name(name) => f.write_str(name),
+            MonoType::Tuple(items) => {
+                f.write_str("(")?;
+                write_list(f, items.iter().map(TypeText))?;
+                f.write_str(")")
+            }
+            MonoType::Fn {
+                params,
+                return_type,
+            } => {
+                f.write_str("fn(")?;
+                write_list(f, params.iter().map(TypeText))?;
+                write!(f, ") -> {}", TypeText(return_type))
+            }
+            MonoType::List(t) => write!(f, "List({})", TypeText(t)),
+            MonoType::Set(t) => write!(f, "Set({})", TypeText(t)),
+            MonoType::Option(t) => write!(f, "Option({})", TypeText(t)),
+            MonoType::Arc(t) => write!(f, "Arc({})", TypeText(t)),
+            MonoType::Weak(t) => write!(f, "Weak({})", TypeText(t)),
+            MonoType::Async(t) => write!(f, "Async({})", TypeText(t)),
+            MonoType::Range { elem_type } => write!(f, "Range({})", TypeText(elem_type)),
+            MonoType::Dict(k, v) => write!(f, "Dict({}, {})", TypeText(k), TypeText(v)),
+            MonoType::Result(ok, err) => write!(f, "Result({}, {})", TypeText(ok), TypeText(err)),
+            MonoType::Generic { name, args } if is_name(name) => {
+                write!(f, "{}(", name)?;
+                write_list(f, args.iter().map(TypeText))?;
+                f.write_str(")")
+            }
+            // 其余类型（匿名结构体、类型变量、联合类型等）只保留名字
+            other => write!(f, "`{}`", other.type_name().replace('`', "'")),
+        }
+    }
+}
+
+/// 语法树类型按其单态类型输出
+fn ast_type_text(ty: &Type) -> String {
+    TypeText(&MonoType::from(ty.clone())).to_string()
+}
+
+/// 能直接作为标识符输出的类型名
+fn is_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || c == '_' || c == '.')
+        && name != "fn"
+}
+
+fn write_list<T: Display>(
+    f: &mut Formatter<'_>,
+    items: impl IntoIterator<Item = T>,
+) -> fmt::Result {
+    for (i, item) in items.into_iter().enumerate() {
+        if i > 0 {
+            f.write_str(", ")?;
+        }
+        write!(f, "{}", item)?;
+    }
+    Ok(())
+}
+
+/// 常量
+struct ConstText<'a>(&'a ConstValue);
+
+impl Display for ConstText<'_> {
+    fn fmt(
+        &self,
+        f: &mut Formatter<'_>,
+    ) -> fmt::Result {
+        match self.0 {
+            ConstValue::Void => f.write_str("void"),
+            ConstValue::Bool(b) => write!(f, "{}", b),
+            ConstValue::Int(i) => write!(f, "{}", i),
+            ConstValue::Float(x) => write!(f, "{:?}", x),
+            ConstValue::Char(c) => write!(f, "{:?}", c),
+            ConstValue::String(s) => write!(f, "{:?}", s),
+            ConstValue::Bytes(bytes) => {
+                f.write_str("bytes[")?;
+                write_list(f, bytes)?;
+                f.write_str("]")
+            }
+            ConstValue::LibraryRef { mechanism, lib } => {
+                write!(f, "library({:?}, {:?})", mechanism, lib)
+            }
+            ConstValue::ExternRef {
+                mechanism,
+                lib,
+                symbol,
+            } => write!(f, "extern({:?}, {:?}, {:?})", mechanism, lib, symbol),
+            ConstValue::List(items) => {
+                f.write_str("[")?;
+                write_list(f, items.iter().map(ConstText))?;
+                f.write_str("]")
+            }
+            ConstValue::Dict(entries) => {
+                f.write_str("{")?;
+                write_list(
+                    f,
+                    entries
+                        .iter()
+                        .map(|(k, v)| format!("{}: {}", ConstText(k), ConstText(v))),
+                )?;
+                f.write_str("}")
+            }
+            ConstValue::Struct { type_name, fields } => {
+                write!(f, "struct({:?}, [", type_name)?;
+                write_list(f, fields.iter().map(ConstText))?;
+                f.write_str("])")
+            }
+        }
+    }
+}
+
+impl Display for Operand {
+    fn fmt(
+        &self,
+        f: &mut Formatter<'_>,
+    ) -> fmt::Result {
+        match self {
+            Operand::Const(value) => write!(f, "{}", ConstText(value)),
+            Operand::Local(i) => write!(f, "%l{}", i),
+            Operand::Arg(i) => write!(f, "%a{}", i),
+            Operand::Temp(i) => write!(f, "%t{}", i),
+            Operand::Global(i) => write!(f, "%g{}", i),
+            Operand::Label(i) => write!(f, "%label{}", i),
+            Operand::Register(r) => write!(f, "%r{}", r),
+        }
+    }
+}
+
+/// 可选的目标操作数：`%t0 = ` 或空
+struct Dst<'a>(&'a Option<Operand>);
+
+impl Display for Dst<'_> {
+    fn fmt(
+        &self,
+        f: &mut Formatter<'_>,
+    ) -> fmt::Result {
+        match self.0 {
+            Some(dst) => write!(f, "{} = ", dst),
+            None => Ok(()),
+        }
+    }
+}
+
+/// 调用参数：`(%t0, %t1)`
+struct Args<'a>(&'a [Operand]);
+
+impl Display for Args<'_> {
+    fn fmt(
+        &self,
+        f: &mut Formatter<'_>,
+    ) -> fmt::Result {
+        f.write_str("(")?;
+        write_list(f, self.0)?;
+        f.write_str(")")
+    }
+}
+
+/// 可选的名字：`"name"` 或 `_`
+struct OptName<'a>(&'a Option<String>);
+
+impl Display for OptName<'_> {
+    fn fmt(
+        &self,
+        f: &mut Formatter<'_>,
+    ) -> fmt::Result {
+        match self.0 {
+            Some(name) => write!(f, "{:?}", name),
+            None => f.write_str("_"),
+        }
+    }
+}
+
+/// spawn 的执行计划：`groups [[0, 1]] deps [[], []] resources [["x"], []]`
+struct PlanText<'a>(&'a ExecutionPlan);
+
+impl Display for PlanText<'_> {
+    fn fmt(
+        &self,
+        f: &mut Formatter<'_>,
+    ) -> fmt::Result {
+        let plan = self.0;
+        f.write_str("groups [")?;
+        write_list(
+            f,
+            plan.groups
+                .iter()
+                .map(|group| index_list(&group.task_indices)),
+        )?;
+        f.write_str("] deps [")?;
+        write_list(f, plan.task_deps.iter().map(|deps| index_list(deps)))?;
+        f.write_str("] resources [")?;
+        write_list(
+            f,
+            plan.task_resources.iter().map(|names| {
+                let names: Vec<String> = names.iter().map(|n| format!("{:?}", n)).collect();
+                format!("[{}]", names.join(", "))
+            }),
+        )?;
+        f.write_str("]")
+    }
+}
+
+fn index_list(indices: &[usize]) -> String {
+    let items: Vec<String> = indices.iter().map(usize::to_string).collect();
+    format!("[{}]", items.join(", "))
+}
+
+impl Display for Instruction {
+    fn fmt(
+        &self,
+        f: &mut Formatter<'_>,
+    ) -> fmt::Result {
+        use Instruction::*;
+        match self {
+            Move { dst, src } => write!(f, "{} = move {}", dst, src),
+            Load { dst, src } => write!(f, "{} = load {}", dst, src),
+            Store { dst, src, .. } => write!(f, "store {}, {}", dst, src),
+            Push(x) => write!(f, "push {}", x),
+            Pop(x) => write!(f, "pop {}", x),
+            Dup => f.write_str("dup"),
+            Swap => f.write_str("swap"),
+            Add { dst, lhs, rhs, .. } => write!(f, "{} = add {}, {}", dst, lhs, rhs),
+            Sub { dst, lhs, rhs, .. } => write!(f, "{} = sub {}, {}", dst, lhs, rhs),
+            Mul { dst, lhs, rhs, .. } => write!(f, "{} = mul {}, {}", dst, lhs, rhs),
+            Div { dst, lhs, rhs, .. } => write!(f, "{} = div {}, {}", dst, lhs, rhs),
+            Mod { dst, lhs, rhs, .. } => write!(f, "{} = mod {}, {}", dst, lhs, rhs),
+            And { dst, lhs, rhs } => write!(f, "{} = and {}, {}", dst, lhs, rhs),
+            Or { dst, lhs, rhs } => write!(f, "{} = or {}, {}", dst, lhs, rhs),
+            Xor { dst, lhs, rhs } => write!(f, "{} = xor {}, {}", dst, lhs, rhs),
+            Shl { dst, lhs, rhs } => write!(f, "{} = shl {}, {}", dst, lhs, rhs),
+            Shr { dst, lhs, rhs } => write!(f, "{} = shr {}, {}", dst, lhs, rhs),
+            Sar { dst, lhs, rhs } => write!(f, "{} = sar {}, {}", dst, lhs, rhs),
+            Neg { dst, src } => write!(f, "{} = neg {}", dst, src),
+            Eq { dst, lhs, rhs } => write!(f, "{} = eq {}, {}", dst, lhs, rhs),
+            Ne { dst, lhs, rhs } => write!(f, "{} = ne {}, {}", dst, lhs, rhs),
+            Lt { dst, lhs, rhs } => write!(f, "{} = lt {}, {}", dst, lhs, rhs),
+            Le { dst, lhs, rhs } => write!(f, "{} = le {}, {}", dst, lhs, rhs),
+            Gt { dst, lhs, rhs } => write!(f, "{} = gt {}, {}", dst, lhs, rhs),
+            Ge { dst, lhs, rhs } => write!(f, "{} = ge {}, {}", dst, lhs, rhs),
+            FAdd { dst, lhs, rhs } => write!(f, "{} = fadd {}, {}", dst, lhs, rhs),
+            FSub { dst, lhs, rhs } => write!(f, "{} = fsub {}, {}", dst, lhs, rhs),
+            FMul { dst, lhs, rhs } => write!(f, "{} = fmul {}, {}", dst, lhs, rhs),
+            FDiv { dst, lhs, rhs } => write!(f, "{} = fdiv {}, {}", dst, lhs, rhs),
+            FMod { dst, lhs, rhs } => write!(f, "{} = fmod {}, {}", dst, lhs, rhs),
+            FNeg { dst, src } => write!(f, "{} = fneg {}", dst, src),
+            FEq { dst, lhs, rhs } => write!(f, "{} = feq {}, {}", dst, lhs, rhs),
+            FNe { dst, lhs, rhs } => write!(f, "{} = fne {}, {}", dst, lhs, rhs),
+            FLt { dst, lhs, rhs } => write!(f, "{} = flt {}, {}", dst, lhs, rhs),
+            FLe { dst, lhs, rhs } => write!(f, "{} = fle {}, {}", dst, lhs, rhs),
+            FGt { dst, lhs, rhs } => write!(f, "{} = fgt {}, {}", dst, lhs, rhs),
+            FGe { dst, lhs, rhs } => write!(f, "{} = fge {}, {}", dst, lhs, rhs),
+            IntToFloat { dst, src } => write!(f, "{} = int_to_float {}", dst, src),
+            Jmp(target) => write!(f, "jmp @{}", target),
+            JmpIf(cond, target) => write!(f, "jmp_if {}, @{}", cond, target),
+            JmpIfNot(cond, target) => write!(f, "jmp_if_not {}, @{}", cond, target),
+            Switch {
+                value,
+                low,
+                targets,
+                default,
+            } => {
+                write!(f, "switch {}, {}, [", value, low)?;
+                for (i, target) in targets.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "@{}", target)?;
+                }
+                write!(f, "], @{}", default)
+            }
+            Call {
+                dst, func, args, ..
+            } => {
+                write!(f, "{}call {}{}", Dst(dst), func, Args(args))
+            }
+            CallVirt {
+                dst,
+                obj,
+                method_name,
+                args,
+                ..
+            } => write!(
+                f,
+                "{}call_virt {}, {:?}{}",
+                Dst(dst),
+                obj,
+                method_name,
+                Args(args)
+            ),
+            InvokeVirtual {
+                dst,
+                obj,
+                slot,
+                method_name,
+                args,
+                ..
+            } => write!(
+                f,
+                "{}invoke_virtual {}, {}, {:?}{}",
+                Dst(dst),
+                obj,
+                slot,
+                method_name,
+                Args(args)
+            ),
+            MakeDyn { dst, src, vtable } => write!(f, "{} = make_dyn {}, {}", dst, src, vtable),
+            CallDyn {
+                dst, func, args, ..
+            } => {
+                write!(f, "{}call_dyn {}{}", Dst(dst), func, Args(args))
+            }
+            TailCall { func, args } => write!(f, "tail_call {}{}", func, Args(args)),
+            Ret(None) => f.write_str("ret"),
+            Ret(Some(value)) => write!(f, "ret {}", value),
+            Alloc { dst, size } => write!(f, "{} = alloc {}", dst, size),
+            Free(x) => write!(f, "free {}", x),
+            AllocArray {
+                dst,
+                size,
+                elem_size,
+            } => write!(f, "{} = alloc_array {}, {}", dst, size, elem_size),
+            LoadField {
+                dst,
+                src,
+                field,
+                type_name,
+                ..
+            } => write!(
+                f,
+                "{} = load_field {}, {}, {}",
+                dst,
+                src,
+                field,
+                OptName(type_name)
+            ),
+            LoadRecordField {
+                dst, src, field, ..
+            } => write!(f, "{} = load_record_field {}, {:?}", dst, src, field),
+            StoreField {
+                dst,
+                field,
+                src,
+                type_name,
+                field_name,
+                ..
+            } => write!(
+                f,
+                "store_field {}, {}, {}, {}, {}",
+                dst,
+                field,
+                src,
+                OptName(type_name),
+                OptName(field_name)
+            ),
+            LoadIndex {
+                dst, src, index, ..
+            } => write!(f, "{} = load_index {}, {}", dst, src, index),
+            StoreIndex {
+                dst, index, src, ..
+            } => write!(f, "store_index {}, {}, {}", dst, index, src),
+            Cast {
+                dst,
+                src,
+                target_type,
+            } => write!(
+                f,
+                "{} = cast {} to {}",
+                dst,
+                src,
+                ast_type_text(target_type)
+            ),
+            Narrow {
+                dst,
+                src,
+                target_type,
+            } => write!(
+                f,
+                "{} = narrow {} to {}",
+                dst,
+                src,
+                ast_type_text(target_type)
+            ),
+            TypeTest {
+                dst,
+                src,
+                target_type,
+            } => write!(
+                f,
+                "{} = type_test {} to {}",
+                dst,
+                src,
+                ast_type_text(target_type)
+            ),
+            Spawn {
+                closures,
+                plan,
+                result,
+            } => {
+                write!(f, "{} = spawn [", result)?;
+                write_list(f, closures)?;
+                write!(f, "] {}", PlanText(plan))
+            }
+            SpawnFromList {
+                closures_list,
+                plan,
+                result,
+            } => write!(
+                f,
+                "{} = spawn_from_list {} {}",
+                result,
+                closures_list,
+                PlanText(plan)
+            ),
+            Yield => f.write_str("yield"),
+            HeapAlloc { dst, type_id } => write!(f, "{} = heap_alloc {}", dst, type_id),
+            StackAlloc { dst, src } => write!(f, "{} = stack_alloc {}", dst, src),
+            CreateStruct {
+                dst,
+                type_name,
+                fields,
+            } => write!(f, "{} = create_struct {:?}{}", dst, type_name, Args(fields)),
+            NewDict { dst, keys, values } => {
+                write!(f, "{} = new_dict {{", dst)?;
+                write_list(
+                    f,
+                    keys.iter()
+                        .zip(values)
+                        .map(|(k, v)| format!("{}: {}", k, v)),
+                )?;
+                f.write_str("}")
+            }
+            MakeClosure { dst, func, env } => {
+                write!(f, "{} = make_closure {:?}{}", dst, func, Args(env))
+            }
+            Drop(x) => write!(f, "drop {}", x),
+            ArcNew { dst, src } => write!(f, "{} = arc_new {}", dst, src),
+            RcNew { dst, src } => write!(f, "{} = rc_new {}", dst, src),
+            ArcClone { dst, src } => write!(f, "{} = arc_clone {}", dst, src),
+            ArcDrop(x) => write!(f, "arc_drop {}", x),
+            UnsafeBlockStart => f.write_str("unsafe_begin"),
+            UnsafeBlockEnd => f.write_str("unsafe_end"),
+            PtrFromRef { dst, src } => write!(f, "{} = ptr_from_ref {}", dst, src),
+            PtrDeref { dst, src } => write!(f, "{} = ptr_deref {}", dst, src),
+            PtrStore { dst, src } => write!(f, "ptr_store {}, {}", dst, src),
+            PtrLoad { dst, src } => write!(f, "{} = ptr_load {}", dst, src),
+            StringLength { dst, src } => write!(f, "{} = string_length {}", dst, src),
+            StringConcat { dst, lhs, rhs } => {
+                write!(f, "{} = string_concat {}, {}", dst, lhs, rhs)
+            }
+            StringGetChar { dst, src, index } => {
+                write!(f, "{} = string_get_char {}, {}", dst, src, index)
+            }
+            StringFromInt { dst, src } => write!(f, "{} = string_from_int {}", dst, src),
+            StringFromFloat { dst, src } => write!(f, "{} = string_from_float {}", dst, src),
+            StringBuilderNew { dst, src } => write!(f, "{} = string_builder_new {}", dst, src),
+            StringBuilderAppend { dst, lhs, rhs } => {
+                write!(f, "{} = string_builder_append {}, {}", dst, lhs, rhs)
+            }
+            StringBuilderFinish { dst, src } => {
+                write!(f, "{} = string_builder_finish {}", dst, src)
+            }
+            LoadUpvalue { dst, upvalue_idx } => {
+                write!(f, "{} = load_upvalue {}", dst, upvalue_idx)
+            }
+            StoreUpvalue { src, upvalue_idx } => {
+                write!(f, "store_upvalue {}, {}", upvalue_idx, src)
+            }
+            CloseUpvalue(x) => write!(f, "close_upvalue {}", x),
+        }
+    }
+}
+
+impl Display for FunctionIR {
+    fn fmt(
+        &self,
+        f: &mut Formatter<'_>,
+    ) -> fmt::Result {
+        write_function(f, self, &ModuleIR::default())
+    }
+}
+
+/// 输出函数；`module` 提供局部变量名、可变性与内联提示
+fn write_function(
+    f: &mut Formatter<'_>,
+    func: &FunctionIR,
+    module: &ModuleIR,
+) -> fmt::Result {
+    match module.inline_hints.get(&func.name) {
+        Some(InlineHint::Always) => writeln!(f, "#[inline]")?,
+        Some(InlineHint::Never) => writeln!(f, "#[noinline]")?,
+        None => {}
+    }
+    write!(f, "fn {:?}", func.name)?;
+    if let Some(generics) = &func.generic_params {
+        f.write_str("[")?;
+        write_list(f, generics)?;
+        f.write_str("]")?;
+    }
+    f.write_str("(")?;
+    write_list(f, func.params.iter().map(TypeText))?;
+    writeln!(f, ") -> {} {{", TypeText(&func.return_type))?;
+
+    if func.entry != 0 {
+        writeln!(f, "  entry bb{}", func.entry)?;
+    }
+    if let Some(count) = module.upvalue_counts.get(&func.name) {
+        writeln!(f, "  upvalues {}", count)?;
+    }
+    if !func.locals.is_empty() {
+        f.write_str("  locals ")?;
+        write_list(f, func.locals.iter().map(TypeText))?;
+        writeln!(f)?;
+    }
+
+    // 局部变量名与标记：`var %l0 "x" mut`
+    let names = module.local_names.get(&func.name);
+    let muts = module.mut_locals.get(&func.name);
+    let loops = module.loop_binding_locals.get(&func.name);
+    let mut vars: BTreeSet<usize> = names.map(|n| (0..n.len()).collect()).unwrap_or_default();
+    vars.extend(muts.into_iter().flatten());
+    vars.extend(loops.into_iter().flatten());
+    for var in vars {
+        write!(f, "  var %l{}", var)?;
+        if let Some(name) = names.and_then(|n| n.get(var)) {
+            write!(f, " {:?}", name)?;
+        }
+        if muts.is_some_and(|m| m.contains(&var)) {
+            f.write_str(" mut")?;
+        }
+        if loops.is_some_and(|l| l.contains(&var)) {
+            f.write_str(" loop")?;
+        }
+        writeln!(f)?;
+    }
+
+    // 指令前标出全局下标，跳转目标 `@n` 指向该下标
+    let mut index = 0;
+    for block in &func.blocks {
+        write_block_header(f, block)?;
+        for instr in &block.instructions {
+            writeln!(f, "  {:>4}: {}", index, instr)?;
+            index += 1;
+        }
+    }
+    writeln!(f, "}}")
+}
+
+fn write_block_header(
+    f: &mut Formatter<'_>,
+    block: &BasicBlock,
+) -> fmt::Result {
+    write!(f, "bb{}", block.label)?;
+    if !block.successors.is_empty() {
+        f.write_str(" -> ")?;
+        write_list(f, block.successors.iter().map(|s| format!("bb{}", s)))?;
+    }
+    writeln!(f, ":")
+}
+
+impl Display for ModuleIR {
+    fn fmt(
+        &self,
+        f: &mut Formatter<'_>,
+    ) -> fmt::Result {
+        let mut header = String::new();
+        for (name, ty, value) in &self.globals {
+            write!(header, "global {:?}: {}", name, ast_type_text(ty))?;
+            if let Some(value) = value {
+                write!(header, " = {}", ConstText(value))?;
+            }
+            header.push('\n');
+        }
+        for layout in &self.struct_layouts {
+            let fields: Vec<String> = layout
+                .fields
+                .iter()
+                .zip(&layout.offsets)
+                .map(|(name, offset)| format!("{:?}: {}", name, offset))
+                .collect();
+            writeln!(
+                header,
+                "struct {:?} size {} align {} [{}]",
+                layout.name,
+                layout.size,
+                layout.align,
+                fields.join(", ")
+            )?;
+        }
+        for vtable in &self.vtables {
+            let methods: Vec<String> = vtable.methods.iter().map(|n| format!("{:?}", n)).collect();
+            writeln!(
+                header,
+                "vtable {:?} for {:?} [{}]",
+                vtable.interface,
+                vtable.type_name,
+                methods.join(", ")
+            )?;
+        }
+        for lib in &self.ffi_libs {
+            writeln!(
+                header,
+                "ffi_lib {} {:?} {:?}",
+                lib.id, lib.mechanism, lib.lib_name
+            )?;
+        }
+        for binding in &self.ffi_bindings {
+            match binding {
+                FfiBinding::TypeBinding {
+                    type_name,
+                    lib_id,
+                    symbol,
+                } => writeln!(header, "ffi_type {:?} = {} {:?}", type_name, lib_id, symbol)?,
+                FfiBinding::FuncBinding {
+                    func_name,
+                    lib_id,
+                    symbol,
+                } => writeln!(header, "ffi_fn {:?} = {} {:?}", func_name, lib_id, symbol)?,
+            }
+        }
+        for name in &self.exports {
+            writeln!(header, "export {:?}", name)?;
+        }
+        f.write_str(&header)?;
+
+        for (i, func) in self.functions.iter().enumerate() {
+            if i > 0 || !header.is_empty() {
+                writeln!(f)?;
+            }
+            write_function(f, func, self)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,160 @@
+//! 文本 IR 测试
+//!
+//! 覆盖：
+//! - 编译产物输出后读回，再输出与原文一致
+//! - 手写 IR 经常量折叠后的结果
+//! - 字符串转义、特殊浮点数与各类常量
+//! - 解析错误带行号
+//! - 读回的模块可以生成字节码并运行
+
+use super::{parse_module, IrParseError};
+use crate::backends::Executor;
+use crate::frontend::Compiler;
+use crate::middle::core::ir::{ConstValue, InlineHint, Instruction, ModuleIR, Operand};
+use crate::middle::passes::const_fold::fold_module;
+
+const SOURCE: &str = "\
+Point: Type = { x: Int, y: Int }
+
+sum_to: (n: Int) -> Int = (n) => {
+    mut total = 0
+    mut i = 0
+    while i < n {
+        total = total + i
+        i = i + 1
+    }
+    return total
+}
+
+main = {
+    p = Point(3, 4)
+    print(p.x + sum_to(5))
+    print(\"line\\n\\\"quoted\\\"\")
+}
+";
+
+fn compile(source: &str) -> ModuleIR {
+    Compiler::new()
+        .compile("ir_text.yx", source)
+        .expect("source should compile")
+}
+
+fn run(module: ModuleIR) {
+    let mut ctx = crate::middle::passes::codegen::CodegenContext::new(module);
+    let bytecode = ctx.generate().expect("codegen should succeed");
+    let module = crate::middle::bytecode::BytecodeModule::from(bytecode);
+    let mut interpreter = crate::backends::interpreter::Interpreter::new();
+    interpreter
+        .execute_module(&module)
+        .expect("parsed program should run");
+}
+
+#[test]
+fn test_round_trip_compiled_module() {
+    let module = compile(SOURCE);
+    let text = module.to_string();
+    let parsed = parse_module(&text).unwrap_or_else(|e| panic!("{}\n{}", e, text));
+    assert_eq!(parsed.to_string(), text);
+    assert_eq!(parsed.functions.len(), module.functions.len());
+    assert_eq!(parsed.local_names, module.local_names);
+    assert_eq!(parsed.mut_locals, module.mut_locals);
+}
+
+#[test]
+fn test_parsed_module_runs() {
+    let text = compile(SOURCE).to_string();
+    run(text.parse().expect("emitted IR should parse"));
+}
+
+#[test]
+fn test_hand_written_ir_through_const_fold() {
+    let text = "\
+#[noinline]
+fn \"calc\"() -> int64 {
+  locals int64
+  var %l0 \"x\"
+bb0:
+  %l0 = move 6
+  %t0 = mul %l0, 7
+  ret %t0
+}
+";
+    let mut module = parse_module(text).expect("hand-written IR should parse");
+    assert_eq!(module.inline_hints.get("calc"), Some(&InlineHint::Never));
+    fold_module(&mut module);
+
+    let func = &module.functions[0];
+    assert!(func
+        .all_instructions()
+        .all(|i| !matches!(i, Instruction::Mul { .. })));
+    assert!(module.to_string().contains("%t0 = load 42\n"), "{}", module);
+}
+
+#[test]
+fn test_constants_round_trip() {
+    let text = "\
+fn \"consts\"() -> void {
+bb0:
+  %t0 = move \"tab\\t\\\"q\\\" \\u{7f}\"
+  %t1 = move '\\''
+  %t2 = move NaN
+  %t3 = move -inf
+  %t4 = move 1.5e300
+  %t5 = move -17
+  %t6 = move bytes[0, 255]
+  %t7 = move [1, \"a\", {true: void}]
+  %t8 = move extern(\"c\", \"libm\", \"cos\")
+  ret
+}
+";
+    let module = parse_module(text).expect("constants should parse");
+    let consts: Vec<ConstValue> = module.functions[0]
+        .all_instructions()
+        .filter_map(|i| match i {
+            Instruction::Move {
+                src: Operand::Const(c),
+                ..
+            } => Some(c.clone()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        consts[0],
+        ConstValue::String("tab\t\"q\" \u{7f}".to_string())
+    );
+    assert_eq!(consts[1], ConstValue::Char('\''));
+    assert!(matches!(consts[2], ConstValue::Float(x) if x.is_nan()));
+    assert_eq!(consts[3], ConstValue::Float(f64::NEG_INFINITY));
+    assert_eq!(consts[4], ConstValue::Float(1.5e300));
+    assert_eq!(consts[5], ConstValue::Int(-17));
+    assert_eq!(consts[6], ConstValue::Bytes(vec![0, 255]));
+    assert_eq!(
+        consts[7],
+        ConstValue::List(vec![
+            ConstValue::Int(1),
+            ConstValue::String("a".to_string()),
+            ConstValue::Dict(vec![(ConstValue::Bool(true), ConstValue::Void)]),
+        ])
+    );
+
+    let printed = module.to_string();
+    assert_eq!(
+        parse_module(&printed)
+            .expect("printed IR should parse")
+            .to_string(),
+        printed
+    );
+}
+
+#[test]
+fn test_errors_report_line() {
+    let err = parse_module("fn \"f\"() -> void {\nbb0:\n  %t0 = frobnicate 1\n}\n").unwrap_err();
+    assert_eq!(err.line, 3);
+    assert!(err.message.contains("frobnicate"), "{}", err);
+
+    let err = parse_module("fn \"f\"() -> void {\n  ret\n").unwrap_err();
+    assert!(err.message.contains("closing"), "{}", err);
+
+    let err: IrParseError = parse_module("\n\n  jmp @1\n").unwrap_err();
+    assert_eq!(err.to_string(), "line 3: unknown declaration `jmp`");
+}
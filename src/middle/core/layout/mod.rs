@@ -0,0 +1,261 @@
+//! 类型布局
+//!
+//! 为单态化后的结构体与枚举类型计算大小、对齐与字段偏移。结果记录在
+//! [`StructLayout`] 中：代码生成按字段偏移发出 `GetField`/`SetField`，
+//! 虚拟机按同一份布局由偏移找回字段所在的槽位。
+//!
+//! 规则与 C 的默认布局一致：
+//! - 字段按声明顺序排列，各自对齐到自身的对齐值；整体大小向上取整到最大对齐
+//! - 整数、浮点按位宽取大小，`Bool` 占 1 字节，`Char` 占 4 字节
+//! - 字符串、容器、函数等堆上的值以一个指针表示
+//! - 具名结构体、元组按值内联；引用自身的字段（直接或间接）退化为指针
+//! - 枚举只有标签；`Option`、`Result` 与联合类型为标签加最大的载荷
+//! - 零大小类型（`Void`、借用）作字段时仍占 1 字节，保证偏移与字段一一对应
+
+use std::collections::{HashMap, HashSet};
+
+use crate::frontend::core::typecheck::inference::numeric::numeric_type_named;
+use crate::frontend::core::typecheck::MonoType;
+use crate::middle::core::ir::StructLayout;
+
+/// 类型的大小与对齐（字节）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TypeLayout {
+    pub size: u32,
+    pub align: u32,
+}
+
+impl TypeLayout {
+    /// 堆上的值：一个指针
+    pub const POINTER: TypeLayout = TypeLayout { size: 8, align: 8 };
+    /// 零大小类型
+    pub const ZERO: TypeLayout = TypeLayout { size: 0, align: 1 };
+
+    fn scalar(size: u32) -> Self {
+        TypeLayout { size, align: size }
+    }
+}
+
+/// 将 `offset` 向上取整到 `align` 的倍数
+fn align_to(
+    offset: u32,
+    align: u32,
+) -> u32 {
+    offset.div_ceil(align) * align
+}
+
+/// 按顺序排列成员，返回整体布局与各成员的偏移
+fn sequence(members: &[TypeLayout]) -> (TypeLayout, Vec<u32>) {
+    let mut offset = 0;
+    let mut align = 1;
+    let mut offsets = Vec::with_capacity(members.len());
+    for member in members {
+        offset = align_to(offset, member.align);
+        offsets.push(offset);
+        offset += member.size;
+        align = align.max(member.align);
+    }
+    let size = align_to(offset, align);
+    (TypeLayout { size, align }, offsets)
+}
+
+/// 区分 `variants` 个变体所需的标签
+fn tag(variants: usize) -> TypeLayout {
+    match variants {
+        0..=0x100 => TypeLayout::scalar(1),
+        0x101..=0x10000 => TypeLayout::scalar(2),
+        _ => TypeLayout::scalar(4),
+    }
+}
+
+/// 标签后接载荷，载荷取各候选中最大的大小与对齐
+fn tagged(
+    variants: usize,
+    payloads: &[TypeLayout],
+) -> TypeLayout {
+    let payload = payloads
+        .iter()
+        .fold(TypeLayout::ZERO, |acc, layout| TypeLayout {
+            size: acc.size.max(layout.size),
+            align: acc.align.max(layout.align),
+        });
+    sequence(&[tag(variants), payload]).0
+}
+
+/// 内置类型名对应的基本类型（字段类型常以 `TypeRef("Int")` 的形式出现）
+fn builtin_type_named(name: &str) -> Option<MonoType> {
+    match name {
+        "Bool" => Some(MonoType::Bool),
+        "Char" => Some(MonoType::Char),
+        "String" => Some(MonoType::String),
+        "Bytes" => Some(MonoType::Bytes),
+        "Void" => Some(MonoType::Void),
+        _ => numeric_type_named(name),
+    }
+}
+
+/// 布局计算器
+///
+/// 登记具名结构体的字段类型后按需计算布局，已算出的结果按类型名缓存，
+/// 嵌套引用同一结构体时直接复用。
+#[derive(Debug, Default)]
+pub struct LayoutEngine {
+    /// 具名结构体的字段（按声明顺序）
+    definitions: HashMap<String, Vec<(String, MonoType)>>,
+    /// 已计算的具名结构体布局
+    known: HashMap<String, TypeLayout>,
+    /// 正在计算的结构体，用于识别递归引用
+    in_progress: HashSet<String>,
+}
+
+impl LayoutEngine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 登记具名结构体的字段
+    pub fn define(
+        &mut self,
+        name: impl Into<String>,
+        fields: Vec<(String, MonoType)>,
+    ) {
+        self.definitions.insert(name.into(), fields);
+    }
+
+    /// 登记已算好的结构体布局，其大小与对齐用于内联到其他类型中
+    pub fn add_layout(
+        &mut self,
+        layout: &StructLayout,
+    ) {
+        self.known.insert(
+            layout.name.clone(),
+            TypeLayout {
+                size: layout.size,
+                align: layout.align,
+            },
+        );
+    }
+
+    /// 计算已登记结构体的布局
+    pub fn struct_layout(
+        &mut self,
+        name: &str,
+    ) -> Option<StructLayout> {
+        let fields = self.definitions.get(name)?.clone();
+        Some(self.layout_fields(name, &fields))
+    }
+
+    /// 按给定字段计算结构体布局
+    pub fn layout_fields(
+        &mut self,
+        name: &str,
+        fields: &[(String, MonoType)],
+    ) -> StructLayout {
+        self.in_progress.insert(name.to_string());
+        let members: Vec<TypeLayout> = fields
+            .iter()
+            .map(|(_, ty)| {
+                let layout = self.type_layout(ty);
+                TypeLayout {
+                    size: layout.size.max(1),
+                    align: layout.align,
+                }
+            })
+            .collect();
+        self.in_progress.remove(name);
+        let (layout, offsets) = sequence(&members);
+        self.known.insert(name.to_string(), layout);
+        StructLayout {
+            name: name.to_string(),
+            fields: fields.iter().map(|(field, _)| field.clone()).collect(),
+            offsets,
+            size: layout.size,
+            align: layout.align,
+        }
+    }
+
+    /// 类型按值存放时的大小与对齐
+    pub fn type_layout(
+        &mut self,
+        ty: &MonoType,
+    ) -> TypeLayout {
+        match ty {
+            MonoType::Void | MonoType::Ref { .. } => TypeLayout::ZERO,
+            MonoType::Bool => TypeLayout::scalar(1),
+            MonoType::Int(bits) | MonoType::Float(bits) => {
+                TypeLayout::scalar((*bits as u32 / 8).clamp(1, 16).next_power_of_two())
+            }
+            MonoType::Char => TypeLayout::scalar(4),
+            MonoType::Async(inner) => self.type_layout(inner),
+            MonoType::Literal { base_type, .. } => self.type_layout(base_type),
+            MonoType::Refined { base, .. } => self.type_layout(base),
+            MonoType::TypeRef(name) => match builtin_type_named(name) {
+                Some(builtin) => self.type_layout(&builtin),
+                None => self.named(name),
+            },
+            MonoType::Struct(st) if !st.name.is_empty() && self.is_named(&st.name) => {
+                self.named(&st.name)
+            }
+            MonoType::Struct(st) => {
+                let members: Vec<TypeLayout> = st
+                    .fields
+                    .iter()
+                    .map(|(_, ty)| self.type_layout(ty))
+                    .collect();
+                sequence(&members).0
+            }
+            MonoType::Tuple(items) => {
+                let members: Vec<TypeLayout> =
+                    items.iter().map(|ty| self.type_layout(ty)).collect();
+                sequence(&members).0
+            }
+            MonoType::Enum(e) => tag(e.variants.len()),
+            MonoType::Option(inner) => tagged(2, &[self.type_layout(inner)]),
+            MonoType::Result(ok, err) => tagged(2, &[self.type_layout(ok), self.type_layout(err)]),
+            MonoType::Generic { name, args } => match (name.as_str(), args.as_slice()) {
+                ("Option", [inner]) => tagged(2, &[self.type_layout(inner)]),
+                ("Result", [ok, err]) => tagged(2, &[self.type_layout(ok), self.type_layout(err)]),
+                _ => TypeLayout::POINTER,
+            },
+            MonoType::Union(items) => {
+                let payloads: Vec<TypeLayout> =
+                    items.iter().map(|ty| self.type_layout(ty)).collect();
+                tagged(items.len(), &payloads)
+            }
+            _ => TypeLayout::POINTER,
+        }
+    }
+
+    fn is_named(
+        &self,
+        name: &str,
+    ) -> bool {
+        self.known.contains_key(name) || self.definitions.contains_key(name)
+    }
+
+    /// 具名类型的布局；未知的类型与递归引用按指针处理
+    fn named(
+        &mut self,
+        name: &str,
+    ) -> TypeLayout {
+        if let Some(layout) = self.known.get(name) {
+            return *layout;
+        }
+        if self.in_progress.contains(name) {
+            return TypeLayout::POINTER;
+        }
+        match self.definitions.get(name).cloned() {
+            Some(fields) => {
+                let layout = self.layout_fields(name, &fields);
+                TypeLayout {
+                    size: layout.size,
+                    align: layout.align,
+                }
+            }
+            None => TypeLayout::POINTER,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
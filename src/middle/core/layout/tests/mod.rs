@@ -0,0 +1,175 @@
+//! 类型布局测试
+//!
+//! 覆盖：
+//! - 标量字段按自身对齐排列，整体大小取整到最大对齐
+//! - 具名结构体按值内联，递归引用退化为指针
+//! - 枚举、`Option` 的标签与零大小字段
+//! - 编译产物的布局与字节码中的字段偏移一致，程序按偏移读写字段
+
+use super::{LayoutEngine, TypeLayout};
+use crate::backends::common::Opcode;
+use crate::backends::interpreter::Interpreter;
+use crate::backends::Executor;
+use crate::frontend::config::{CompileConfig, OptLevel};
+use crate::frontend::core::typecheck::{EnumType, MonoType};
+use crate::frontend::Compiler;
+use crate::middle::bytecode::BytecodeModule;
+use crate::middle::core::ir::ModuleIR;
+use crate::middle::passes::codegen::CodegenContext;
+
+fn named(name: &str) -> MonoType {
+    MonoType::TypeRef(name.to_string())
+}
+
+fn fields(items: &[(&str, MonoType)]) -> Vec<(String, MonoType)> {
+    items
+        .iter()
+        .map(|(name, ty)| (name.to_string(), ty.clone()))
+        .collect()
+}
+
+#[test]
+fn test_scalar_fields_are_aligned() {
+    let mut engine = LayoutEngine::new();
+    let layout = engine.layout_fields(
+        "Mixed",
+        &fields(&[
+            ("flag", named("Bool")),
+            ("x", named("Int")),
+            ("c", MonoType::Char),
+            ("b", MonoType::Bool),
+        ]),
+    );
+    assert_eq!(layout.offsets, [0, 8, 16, 20]);
+    assert_eq!((layout.size, layout.align), (24, 8));
+}
+
+#[test]
+fn test_sized_integers_pack_by_width() {
+    let mut engine = LayoutEngine::new();
+    let layout = engine.layout_fields(
+        "Packed",
+        &fields(&[
+            ("a", named("Int32")),
+            ("b", named("Int8")),
+            ("c", MonoType::Int(16)),
+        ]),
+    );
+    assert_eq!(layout.offsets, [0, 4, 6]);
+    assert_eq!((layout.size, layout.align), (8, 4));
+}
+
+#[test]
+fn test_nested_struct_is_inlined() {
+    let mut engine = LayoutEngine::new();
+    engine.define("Point", fields(&[("x", named("Int")), ("y", named("Int"))]));
+    engine.define(
+        "Labeled",
+        fields(&[
+            ("visible", named("Bool")),
+            ("at", named("Point")),
+            ("name", MonoType::String),
+        ]),
+    );
+    let layout = engine.struct_layout("Labeled").expect("Labeled is defined");
+    assert_eq!(layout.offsets, [0, 8, 24]);
+    assert_eq!(layout.size, 32);
+}
+
+#[test]
+fn test_recursive_reference_is_a_pointer() {
+    let mut engine = LayoutEngine::new();
+    engine.define(
+        "Node",
+        fields(&[
+            ("value", named("Int")),
+            ("next", MonoType::Option(Box::new(named("Node")))),
+        ]),
+    );
+    let layout = engine.struct_layout("Node").expect("Node is defined");
+    // `next` 为 1 字节标签加指针载荷
+    assert_eq!(layout.offsets, [0, 8]);
+    assert_eq!(layout.size, 24);
+}
+
+#[test]
+fn test_enum_tag_and_zero_sized_fields() {
+    let mut engine = LayoutEngine::new();
+    let color = MonoType::Enum(EnumType {
+        name: "Color".to_string(),
+        variants: vec!["red".to_string(), "green".to_string(), "blue".to_string()],
+    });
+    assert_eq!(engine.type_layout(&color), TypeLayout { size: 1, align: 1 });
+    assert_eq!(engine.type_layout(&MonoType::Void), TypeLayout::ZERO);
+
+    let layout = engine.layout_fields(
+        "Tagged",
+        &fields(&[
+            ("color", color),
+            ("unit", MonoType::Void),
+            ("n", named("Int16")),
+        ]),
+    );
+    // 零大小字段仍占 1 字节，偏移与字段一一对应
+    assert_eq!(layout.offsets, [0, 1, 2]);
+    assert_eq!((layout.size, layout.align), (4, 2));
+}
+
+const SOURCE: &str = "\
+use std.assert
+
+Particle: Type = { alive: Bool, mass: Float, id: Int32, tag: Char }
+
+main = {
+    p = Particle(true, 2.5, 7, 'q')
+    assert_eq(p.alive, true)
+    assert_eq(p.mass, 2.5)
+    assert_eq(p.id, 7)
+    assert_eq(p.tag, 'q')
+}
+";
+
+fn compile(level: OptLevel) -> ModuleIR {
+    Compiler::with_config(CompileConfig::new().with_opt_level(level))
+        .compile("layout.yx", SOURCE)
+        .expect("source should compile")
+}
+
+#[test]
+fn test_compiled_layout_drives_field_offsets() {
+    let module = compile(OptLevel::O0);
+    let layout = module
+        .struct_layouts
+        .iter()
+        .find(|layout| layout.name == "Particle")
+        .expect("Particle should have a layout")
+        .clone();
+    assert_eq!(layout.offsets, [0, 8, 16, 20]);
+    assert_eq!((layout.size, layout.align), (24, 8));
+
+    let bytecode = CodegenContext::new(module)
+        .generate()
+        .expect("codegen should succeed");
+    let mut offsets: Vec<u32> = bytecode
+        .code_section
+        .functions
+        .iter()
+        .flat_map(|func| func.instructions.iter())
+        .filter(|instr| instr.opcode == Opcode::GetField as u8)
+        .map(|instr| u32::from(u16::from_le_bytes([instr.operands[2], instr.operands[3]])))
+        .collect();
+    offsets.sort_unstable();
+    assert_eq!(offsets, layout.offsets);
+}
+
+#[test]
+fn test_fields_read_by_offset_at_all_levels() {
+    for level in [OptLevel::O0, OptLevel::O1, OptLevel::O2] {
+        let bytecode = CodegenContext::new(compile(level))
+            .generate()
+            .expect("codegen should succeed");
+        Interpreter::new()
+            .execute_module(&BytecodeModule::from(bytecode))
+            .expect("program should run");
+    }
+}
@@ -4,8 +4,11 @@
 //! 这是整个middle层的基石，所有其他模块都依赖于此。
 
 pub mod bytecode;
+pub mod decoded;
 pub mod ir;
 pub mod ir_gen;
+pub mod ir_text;
+pub mod layout;
 
 pub use ir::*;
 pub use bytecode::*;
@@ -180,7 +180,8 @@ fn build_and_decode(instrs: Vec<BytecodeInstruction>) -> BytecodeModule {
         return_type: crate::frontend::core::typecheck::MonoType::Void,
         instructions: instrs,
         local_count: 0,
-        debug_map: std::collections::HashMap::new(),
+        upvalue_count: 0,
+        line_table: crate::util::span::LineTable::new(),
     };
     let file = bcfile::BytecodeFile {
         header: bcfile::FileHeader::default(),
@@ -189,6 +190,7 @@ fn build_and_decode(instrs: Vec<BytecodeInstruction>) -> BytecodeModule {
         code_section: bcfile::CodeSection {
             functions: vec![func],
         },
+        vtables: vec![],
         debug_section: None,
     };
     BytecodeModule::from(file)
@@ -13,6 +13,10 @@
 //! 2. **passes/**: 编译器各个阶段
 //!    - lifetime/: 生命周期检查
 //!    - mono/: 泛型单态化
+//!    - const_eval/: 编译期常量求值
+//!    - const_fold/: IR 常量折叠与传播
+//!    - decision_tree/: match 的决策树编译
+//!    - ssa/: SSA 构造与析构
 //!    - module/: 模块系统
 //!    - codegen/: 代码生成
 //!    - tests/: 统一测试套件
@@ -30,7 +34,9 @@ pub mod passes;
 // 对外导出
 pub use core::ir::*;
 pub use core::bytecode;
+pub use core::decoded;
 pub use core::ir_gen::*;
+pub use core::ir_text;
 pub use passes::mono::*;
 pub use passes::module::*;
 pub use passes::codegen;
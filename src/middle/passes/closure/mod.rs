@@ -0,0 +1,418 @@
+//! 闭包转换：捕获分析
+//!
+//! IR 生成把 lambda 与捕获了外层变量的局部函数提升为独立函数，外层变量经 `MakeClosure`
+//! 的环境传入，函数体用 `LoadUpvalue` / `StoreUpvalue` 访问。运行时调用闭包时，环境按
+//! 顺序成为被调函数帧的 upvalue，实参从第 0 个参数槽开始。
+//!
+//! 本模块在生成函数体之前分析源码，决定每个变量的捕获方式：
+//! - 按值捕获：变量声明之后不再赋值，创建闭包时复制当前值
+//! - 按引用捕获：变量被内层函数引用，且除声明之外还有赋值（在外层函数或任一闭包中）。
+//!   这样的变量在声明处装箱为 cell（堆上共享的单元素列表），声明它的函数与各层闭包
+//!   都经由 cell 读写，彼此看到对方的修改
+//!
+//! 内层函数包括 lambda、局部函数定义，以及 IR 生成包装为闭包的 `spawn` 块与 `spawn for`
+//! 循环体。分析按名字进行且偏保守：同名的不同变量一起装箱只影响性能，不影响结果。
+
+use std::collections::{HashMap, HashSet};
+
+use crate::frontend::core::parser::ast::{
+    BinOp, Block, Expr, FStringSegment, Param, Pattern, Stmt, StmtKind,
+};
+
+/// 函数中需要按引用捕获（装箱为 cell）的变量名，含参数与内层函数的局部变量
+pub fn boxed_variables(
+    params: &[Param],
+    body: &[Stmt],
+) -> HashSet<String> {
+    let mut analyzer = Analyzer::new(params);
+    analyzer.visit_stmts(body);
+    analyzer.boxed()
+}
+
+/// 同 [`boxed_variables`]，函数体为单个表达式
+pub fn boxed_variables_in_expr(
+    params: &[Param],
+    body: &Expr,
+) -> HashSet<String> {
+    let mut analyzer = Analyzer::new(params);
+    analyzer.visit_expr(body);
+    analyzer.boxed()
+}
+
+/// 函数体（含内层函数）引用或赋值的名字，不含函数自己的参数
+///
+/// 结果是自由变量的上界：函数体内声明的局部变量同样在内。
+pub fn referenced_names(
+    params: &[Param],
+    body: &[Stmt],
+) -> HashSet<String> {
+    let mut analyzer = Analyzer::new(params);
+    analyzer.visit_stmts(body);
+    let params: HashSet<&str> = params.iter().map(|p| p.name.as_str()).collect();
+    analyzer
+        .referenced
+        .into_iter()
+        .filter(|name| !params.contains(name.as_str()))
+        .collect()
+}
+
+/// 捕获分析的遍历状态
+struct Analyzer {
+    /// 由外向内每层函数已声明的名字
+    frames: Vec<HashSet<String>>,
+    /// 每个名字的赋值次数，声明计一次
+    writes: HashMap<String, usize>,
+    /// 被内层函数引用的外层变量名
+    captured: HashSet<String>,
+    /// 出现过的全部名字
+    referenced: HashSet<String>,
+}
+
+impl Analyzer {
+    fn new(params: &[Param]) -> Self {
+        let mut analyzer = Self {
+            frames: vec![HashSet::new()],
+            writes: HashMap::new(),
+            captured: HashSet::new(),
+            referenced: HashSet::new(),
+        };
+        for param in params {
+            analyzer.declare(&param.name);
+        }
+        analyzer
+    }
+
+    /// 被捕获且不止一次赋值的变量
+    fn boxed(self) -> HashSet<String> {
+        let writes = self.writes;
+        self.captured
+            .into_iter()
+            .filter(|name| writes.get(name).copied().unwrap_or(0) > 1)
+            .collect()
+    }
+
+    /// 在当前函数中声明变量
+    fn declare(
+        &mut self,
+        name: &str,
+    ) {
+        if let Some(frame) = self.frames.last_mut() {
+            frame.insert(name.to_string());
+        }
+        *self.writes.entry(name.to_string()).or_insert(0) += 1;
+    }
+
+    /// 引用名字：在外层函数中声明的记为被捕获
+    fn reference(
+        &mut self,
+        name: &str,
+    ) {
+        self.referenced.insert(name.to_string());
+        let Some((current, outer)) = self.frames.split_last() else {
+            return;
+        };
+        if !current.contains(name) && outer.iter().any(|frame| frame.contains(name)) {
+            self.captured.insert(name.to_string());
+        }
+    }
+
+    /// `name = ...`：名字已在当前或外层函数中声明时是赋值，否则声明新变量
+    fn assign(
+        &mut self,
+        name: &str,
+    ) {
+        if self.frames.iter().any(|frame| frame.contains(name)) {
+            self.reference(name);
+            *self.writes.entry(name.to_string()).or_insert(0) += 1;
+        } else {
+            self.referenced.insert(name.to_string());
+            self.declare(name);
+        }
+    }
+
+    /// 进入内层函数
+    fn function(
+        &mut self,
+        params: &[Param],
+        visit: impl FnOnce(&mut Self),
+    ) {
+        self.frames.push(HashSet::new());
+        for param in params {
+            self.declare(&param.name);
+        }
+        visit(self);
+        self.frames.pop();
+    }
+
+    fn visit_stmts(
+        &mut self,
+        stmts: &[Stmt],
+    ) {
+        for stmt in stmts {
+            self.visit_stmt(stmt);
+        }
+    }
+
+    fn visit_block(
+        &mut self,
+        block: &Block,
+    ) {
+        self.visit_stmts(&block.stmts);
+    }
+
+    fn visit_stmt(
+        &mut self,
+        stmt: &Stmt,
+    ) {
+        match &stmt.kind {
+            StmtKind::Expr(expr) | StmtKind::Return(Some(expr)) => self.visit_expr(expr),
+            StmtKind::Var {
+                name, initializer, ..
+            } => {
+                if let Some(init) = initializer {
+                    self.visit_expr(init);
+                }
+                self.assign(name);
+            }
+            StmtKind::For {
+                var,
+                iterable,
+                body,
+                ..
+            } => {
+                self.visit_expr(iterable);
+                self.declare(var);
+                self.visit_block(body);
+            }
+            StmtKind::Binding {
+                name,
+                type_name,
+                params,
+                body,
+                ..
+            } => {
+                if type_name.is_none() {
+                    self.declare(name);
+                }
+                self.function(params, |this| this.visit_stmts(body));
+            }
+            StmtKind::If {
+                condition,
+                then_branch,
+                elif_branches,
+                else_branch,
+                ..
+            } => {
+                self.visit_expr(condition);
+                self.visit_block(then_branch);
+                for (cond, block) in elif_branches {
+                    self.visit_expr(cond);
+                    self.visit_block(block);
+                }
+                if let Some(block) = else_branch {
+                    self.visit_block(block);
+                }
+            }
+            StmtKind::DestructureAssign { names, rhs, .. } => {
+                self.visit_expr(rhs);
+                for ident in names {
+                    self.assign(&ident.name);
+                }
+            }
+            StmtKind::Return(None)
+            | StmtKind::Use { .. }
+            | StmtKind::ExternalBindingStmt { .. }
+            | StmtKind::Error(_) => {}
+        }
+    }
+
+    fn visit_expr(
+        &mut self,
+        expr: &Expr,
+    ) {
+        match expr {
+            Expr::Var(name, _) => self.reference(name.as_str()),
+            Expr::BinOp {
+                op: BinOp::Assign,
+                left,
+                right,
+                ..
+            } => {
+                self.visit_expr(right);
+                match left.as_ref() {
+                    Expr::Var(name, _) => self.assign(name.as_str()),
+                    target => self.visit_expr(target),
+                }
+            }
+            Expr::CompoundAssign { target, value, .. } => {
+                self.visit_expr(value);
+                self.visit_expr(target);
+                if let Expr::Var(name, _) = target.as_ref() {
+                    self.assign(name.as_str());
+                }
+            }
+            Expr::BinOp { left, right, .. }
+            | Expr::Pipe {
+                value: left,
+                func: right,
+                ..
+            }
+            | Expr::Index {
+                expr: left,
+                index: right,
+                ..
+            } => {
+                self.visit_expr(left);
+                self.visit_expr(right);
+            }
+            Expr::UnOp { expr, .. }
+            | Expr::Try { expr, .. }
+            | Expr::Ref { expr, .. }
+            | Expr::Borrow { expr, .. }
+            | Expr::Cast { expr, .. }
+            | Expr::FieldAccess { expr, .. } => self.visit_expr(expr),
+            Expr::Call {
+                func,
+                args,
+                named_args,
+                ..
+            } => {
+                self.visit_expr(func);
+                for arg in args {
+                    self.visit_expr(arg);
+                }
+                for (_, arg) in named_args {
+                    self.visit_expr(arg);
+                }
+            }
+            Expr::FnDef { params, body, .. } | Expr::Lambda { params, body, .. } => {
+                self.function(params, |this| this.visit_block(body));
+            }
+            Expr::If {
+                condition,
+                then_branch,
+                elif_branches,
+                else_branch,
+                ..
+            } => {
+                self.visit_expr(condition);
+                self.visit_block(then_branch);
+                for (cond, block) in elif_branches {
+                    self.visit_expr(cond);
+                    self.visit_block(block);
+                }
+                if let Some(block) = else_branch {
+                    self.visit_block(block);
+                }
+            }
+            Expr::Match { expr, arms, .. } => {
+                self.visit_expr(expr);
+                for arm in arms {
+                    self.visit_pattern(&arm.pattern);
+                    self.visit_block(&arm.body);
+                }
+            }
+            Expr::While {
+                condition, body, ..
+            } => {
+                self.visit_expr(condition);
+                self.visit_block(body);
+            }
+            Expr::For {
+                var,
+                iterable,
+                body,
+                ..
+            } => {
+                self.visit_expr(iterable);
+                self.declare(var);
+                self.visit_block(body);
+            }
+            // 循环体包装为闭包，循环变量在外层按值捕获
+            Expr::SpawnFor {
+                var,
+                iterable,
+                body,
+                ..
+            } => {
+                self.visit_expr(iterable);
+                self.declare(var);
+                self.function(&[], |this| this.visit_block(body));
+            }
+            Expr::Spawn { body, .. } => self.function(&[], |this| this.visit_block(body)),
+            Expr::Block(block) => self.visit_block(block),
+            Expr::Unsafe { body, .. } => self.visit_block(body),
+            Expr::Return(value, _) => {
+                if let Some(value) = value {
+                    self.visit_expr(value);
+                }
+            }
+            Expr::Tuple(items, _) | Expr::List(items, _) => {
+                for item in items {
+                    self.visit_expr(item);
+                }
+            }
+            Expr::ListComp {
+                element,
+                var,
+                iterable,
+                condition,
+                ..
+            } => {
+                self.visit_expr(iterable);
+                self.declare(var);
+                self.visit_expr(element);
+                if let Some(condition) = condition {
+                    self.visit_expr(condition);
+                }
+            }
+            Expr::Dict(entries, _) => {
+                for (key, value) in entries {
+                    self.visit_expr(key);
+                    self.visit_expr(value);
+                }
+            }
+            Expr::FString { segments, .. } => {
+                for segment in segments {
+                    if let FStringSegment::Interpolation { expr, .. } = segment {
+                        self.visit_expr(expr);
+                    }
+                }
+            }
+            Expr::Lit(..) | Expr::Break(..) | Expr::Continue(..) | Expr::Error(_) => {}
+        }
+    }
+
+    /// 模式中的标识符声明新变量
+    fn visit_pattern(
+        &mut self,
+        pattern: &Pattern,
+    ) {
+        match pattern {
+            Pattern::Identifier(name) => self.declare(name),
+            Pattern::Tuple(items) | Pattern::Or(items) => {
+                for item in items {
+                    self.visit_pattern(item);
+                }
+            }
+            Pattern::Struct { fields, .. } => {
+                for (_, _, field) in fields {
+                    self.visit_pattern(field);
+                }
+            }
+            Pattern::Union { pattern, .. } => {
+                if let Some(inner) = pattern {
+                    self.visit_pattern(inner);
+                }
+            }
+            Pattern::Guard { pattern, condition } => {
+                self.visit_pattern(pattern);
+                self.visit_expr(condition);
+            }
+            Pattern::Wildcard | Pattern::Literal(_) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
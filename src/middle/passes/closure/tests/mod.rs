@@ -0,0 +1,262 @@
+//! 闭包转换测试
+//!
+//! 覆盖：
+//! - 捕获分析：只读捕获按值，被重新赋值的捕获变量装箱
+//! - 闭包函数记录 upvalue 个数，环境按捕获顺序传入
+//! - 按值捕获、按引用捕获、嵌套闭包与递归局部函数在各优化级别下的运行结果
+
+use std::collections::HashSet;
+
+use crate::backends::interpreter::Interpreter;
+use crate::backends::Executor;
+use crate::frontend::config::{CompileConfig, OptLevel};
+use crate::frontend::core::lexer::tokenize;
+use crate::frontend::core::parser::ast::{Param, Stmt, StmtKind};
+use crate::frontend::core::parser::parse;
+use crate::frontend::Compiler;
+use crate::middle::bytecode::BytecodeModule;
+use crate::middle::core::ir::{Instruction, ModuleIR};
+use crate::middle::passes::closure::{boxed_variables, referenced_names};
+use crate::middle::passes::codegen::CodegenContext;
+
+/// 解析源码并取出第一个函数绑定的参数与函数体
+fn parse_function(source: &str) -> (Vec<Param>, Vec<Stmt>) {
+    let tokens = tokenize(source).expect("tokenize failed");
+    let result = parse(&tokens);
+    assert!(!result.has_errors, "parse failed: {:?}", result.errors);
+    match &result.module.items[0].kind {
+        StmtKind::Binding { params, body, .. } => (params.clone(), body.clone()),
+        other => panic!("Expected Binding, got {:?}", other),
+    }
+}
+
+fn boxed(source: &str) -> HashSet<String> {
+    let (params, body) = parse_function(source);
+    boxed_variables(&params, &body)
+}
+
+fn names(items: &[&str]) -> HashSet<String> {
+    items.iter().map(|s| s.to_string()).collect()
+}
+
+#[test]
+fn test_read_only_capture_is_by_value() {
+    let source = "\
+f = (n) => {
+    k = 10
+    g = (x) => x + k + n
+    return g(1)
+}
+";
+    assert!(boxed(source).is_empty());
+}
+
+#[test]
+fn test_captured_and_reassigned_is_boxed() {
+    let source = "\
+f = (n) => {
+    mut count = 0
+    inc = () => {
+        count = count + 1
+    }
+    inc()
+    n = n + 1
+    return count
+}
+";
+    // `n` 被重新赋值但未被捕获，无需装箱
+    assert_eq!(boxed(source), names(&["count"]));
+}
+
+#[test]
+fn test_outer_write_after_capture_is_boxed() {
+    let source = "\
+f = () => {
+    mut total = 1
+    get = () => total
+    total = 2
+    return get()
+}
+";
+    assert_eq!(boxed(source), names(&["total"]));
+}
+
+#[test]
+fn test_inner_locals_are_not_captures() {
+    let source = "\
+f = () => {
+    g = () => {
+        mut i = 0
+        i = i + 1
+        return i
+    }
+    return g()
+}
+";
+    assert!(boxed(source).is_empty());
+}
+
+#[test]
+fn test_referenced_names_exclude_params() {
+    let (params, body) = parse_function("f = (x) => x + k\n");
+    assert_eq!(referenced_names(&params, &body), names(&["k"]));
+}
+
+fn compile(
+    source: &str,
+    level: OptLevel,
+) -> ModuleIR {
+    Compiler::with_config(CompileConfig::new().with_opt_level(level))
+        .compile("closure.yx", source)
+        .expect("source should compile")
+}
+
+fn run(module: ModuleIR) {
+    let mut ctx = CodegenContext::new(module);
+    let bytecode = ctx.generate().expect("codegen should succeed");
+    let module = BytecodeModule::from(bytecode);
+    Interpreter::new()
+        .execute_module(&module)
+        .expect("program should run");
+}
+
+/// 在各优化级别下编译并运行
+fn run_all_levels(source: &str) {
+    for level in [OptLevel::O0, OptLevel::O1, OptLevel::O2] {
+        run(compile(source, level));
+    }
+}
+
+#[test]
+fn test_make_closure_env_matches_upvalue_count() {
+    let source = "\
+use std.assert
+
+main = {
+    a = 1
+    b = 2
+    f = (x) => { return x + a + b }
+    assert_eq(f(3), 6)
+}
+";
+    let module = compile(source, OptLevel::O0);
+    let closure = module
+        .functions
+        .iter()
+        .find(|f| f.name.starts_with("closure_"))
+        .expect("lambda should be lifted");
+    assert_eq!(module.upvalue_counts.get(&closure.name), Some(&2));
+    let env_len = module
+        .functions
+        .iter()
+        .flat_map(|f| f.blocks.iter().flat_map(|b| b.instructions.iter()))
+        .find_map(|instr| match instr {
+            Instruction::MakeClosure { func, env, .. } if *func == closure.name => Some(env.len()),
+            _ => None,
+        });
+    assert_eq!(env_len, Some(2));
+}
+
+#[test]
+fn test_capture_by_value() {
+    run_all_levels(
+        "\
+use std.{assert, list}
+
+main = {
+    k = 10
+    add_k = (x) => { return x + k }
+    assert_eq(add_k(5), 15)
+    assert_eq(list.map([1, 2, 3], x => x + k), [11, 12, 13])
+}
+",
+    );
+}
+
+#[test]
+fn test_capture_by_reference() {
+    run_all_levels(
+        "\
+use std.assert
+
+main = {
+    mut count = 0
+    inc = () => {
+        count = count + 1
+    }
+    inc()
+    inc()
+    assert_eq(count, 2)
+    count = 10
+    inc()
+    assert_eq(count, 11)
+}
+",
+    );
+}
+
+#[test]
+fn test_nested_closures() {
+    run_all_levels(
+        "\
+use std.assert
+
+make_adder: (n: Int) -> (Int) -> Int = (n) => {
+    return (x) => x + n
+}
+
+main = {
+    a = 1
+    outer = (x) => {
+        inner = (y) => { return x + y + a }
+        return inner(100)
+    }
+    assert_eq(outer(10), 111)
+    add3 = make_adder(3)
+    assert_eq(add3(4), 7)
+}
+",
+    );
+}
+
+#[test]
+fn test_nested_closure_shares_cell() {
+    run_all_levels(
+        "\
+use std.assert
+
+main = {
+    mut total = 0
+    outer = () => {
+        add = (n) => {
+            total = total + n
+        }
+        add(2)
+        add(3)
+    }
+    outer()
+    assert_eq(total, 5)
+}
+",
+    );
+}
+
+#[test]
+fn test_recursive_local_function() {
+    run_all_levels(
+        "\
+use std.assert
+
+main = {
+    base = 1
+    fact = (n) => {
+        if n <= 1 {
+            return base
+        }
+        return n * fact(n - 1)
+    }
+    assert_eq(fact(5), 120)
+}
+",
+    );
+}
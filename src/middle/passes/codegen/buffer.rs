@@ -1,30 +1,41 @@
 //! 字节码缓冲区管理
 //!
-//! 管理常量池和字节码生成缓冲区。
+//! 管理常量池和字节码生成缓冲区，并把函数体汇编为带字节偏移跳转的指令序列。
 
+use std::collections::HashMap;
+
+use crate::backends::common::Opcode;
 use crate::middle::core::ir::ConstValue;
+use crate::middle::passes::codegen::BytecodeInstruction;
+use crate::util::diagnostic::{Diagnostic, ErrorCodeDefinition};
 
 /// 常量池
+///
+/// 相同的常量只存一份，链接后的多个模块共用同一个常量池。
 #[derive(Debug, Default, Clone)]
 pub struct ConstantPool {
     /// 常量列表
     constants: Vec<ConstValue>,
+    /// 常量 -> 索引
+    index: HashMap<ConstValue, usize>,
 }
 
 impl ConstantPool {
     /// 创建新常量池
     pub fn new() -> Self {
-        ConstantPool {
-            constants: Vec::new(),
-        }
+        ConstantPool::default()
     }
 
-    /// 添加常量并返回索引
+    /// 添加常量并返回索引，已有相同的常量时返回其索引
     pub fn add(
         &mut self,
         value: ConstValue,
     ) -> usize {
-        self.constants.push(value);
+        if let Some(&index) = self.index.get(&value) {
+            return index;
+        }
+        self.constants.push(value.clone());
+        self.index.insert(value, self.constants.len() - 1);
         self.constants.len() - 1
     }
 
@@ -52,24 +63,186 @@ impl ConstantPool {
     }
 }
 
+/// 待修补的跳转
+#[derive(Debug, Clone, Copy)]
+struct Fixup {
+    /// 跳转指令的下标
+    instr: usize,
+    /// 跳转目标标签
+    label: usize,
+    /// 是否使用 4 字节偏移
+    long: bool,
+}
+
+/// 待修补的跳转表：每项为 4 字节偏移，大小固定，不参与长短跳转的排布
+#[derive(Debug, Clone)]
+struct TableFixup {
+    /// 跳转表指令的下标
+    instr: usize,
+    /// 默认目标在前、各表项在后的标签
+    labels: Vec<usize>,
+}
+
 /// 字节码缓冲区
 ///
 /// 管理常量池和字节码生成的缓冲区。
+///
+/// 函数体按指令追加，跳转指向标签；[`BytecodeBuffer::assemble`] 分两遍把标签解析为
+/// 相对字节偏移：先按短跳转（2 字节偏移）排布，放不下的改为长跳转（4 字节偏移）并重新
+/// 排布直到稳定，再回填偏移。偏移从跳转指令的首字节算起。
 #[derive(Debug, Default)]
 pub struct BytecodeBuffer {
     /// 常量池
     constant_pool: ConstantPool,
     /// 字节码缓冲区
     bytecode: Vec<u8>,
+    /// 当前函数的指令
+    instructions: Vec<BytecodeInstruction>,
+    /// 标签 → 绑定处的指令下标
+    labels: HashMap<usize, usize>,
+    /// 待修补的跳转
+    fixups: Vec<Fixup>,
+    /// 待修补的跳转表
+    tables: Vec<TableFixup>,
 }
 
 impl BytecodeBuffer {
     /// 创建新的字节码缓冲区
     pub fn new() -> Self {
-        BytecodeBuffer {
-            constant_pool: ConstantPool::new(),
-            bytecode: Vec::new(),
+        BytecodeBuffer::default()
+    }
+
+    /// 追加一条指令，返回其下标
+    pub fn push_instruction(
+        &mut self,
+        instr: BytecodeInstruction,
+    ) -> usize {
+        self.instructions.push(instr);
+        self.instructions.len() - 1
+    }
+
+    /// 追加跳到 `label` 的跳转（`Jmp` / `JmpIf` / `JmpIfNot`），偏移在汇编时回填
+    pub fn push_jump(
+        &mut self,
+        jump: BytecodeInstruction,
+        label: usize,
+    ) -> usize {
+        let instr = self.push_instruction(jump);
+        self.fixups.push(Fixup {
+            instr,
+            label,
+            long: false,
+        });
+        instr
+    }
+
+    /// 追加跳转表（`TableSwitch`），默认目标与各表项的偏移在汇编时回填
+    ///
+    /// `labels` 中默认目标在前，依次对应操作数中从 [`TABLE_OFFSETS_START`] 起的 4 字节槽位。
+    pub fn push_table(
+        &mut self,
+        table: BytecodeInstruction,
+        labels: Vec<usize>,
+    ) -> usize {
+        let instr = self.push_instruction(table);
+        self.tables.push(TableFixup { instr, labels });
+        instr
+    }
+
+    /// 把标签绑定到下一条指令
+    pub fn bind_label(
+        &mut self,
+        label: usize,
+    ) {
+        self.labels.insert(label, self.instructions.len());
+    }
+
+    /// 当前函数已追加的指令数
+    pub fn instruction_count(&self) -> usize {
+        self.instructions.len()
+    }
+
+    /// 解析跳转并取出当前函数的指令，缓冲区随后可用于下一个函数
+    pub fn assemble(&mut self) -> Result<Vec<BytecodeInstruction>, Diagnostic> {
+        let mut instructions = std::mem::take(&mut self.instructions);
+        let labels = std::mem::take(&mut self.labels);
+        let mut fixups = std::mem::take(&mut self.fixups);
+        let tables = std::mem::take(&mut self.tables);
+
+        let resolve = |label: usize| {
+            labels.get(&label).copied().ok_or_else(|| {
+                ErrorCodeDefinition::codegen_invalid_operand(&format!(
+                    "unbound jump label {}",
+                    label
+                ))
+                .build()
+            })
+        };
+        let targets = fixups
+            .iter()
+            .map(|fixup| resolve(fixup.label))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // 第一遍：按当前长短排布，放不下 2 字节偏移的跳转改为长跳转，直到不再变化
+        let offsets = loop {
+            let mut offsets = Vec::with_capacity(instructions.len() + 1);
+            let mut pos = 0usize;
+            let mut jump = fixups.iter().peekable();
+            for (idx, instr) in instructions.iter().enumerate() {
+                offsets.push(pos);
+                let mut size = instr.encoded_size();
+                if let Some(fixup) = jump.next_if(|f| f.instr == idx) {
+                    size = 3 + jump_operand_len(instr.opcode, fixup.long);
+                }
+                pos += size;
+            }
+            offsets.push(pos);
+
+            let mut widened = false;
+            for (fixup, &target) in fixups.iter_mut().zip(&targets) {
+                let delta = offsets[target] as i64 - offsets[fixup.instr] as i64;
+                if !fixup.long && i16::try_from(delta).is_err() {
+                    fixup.long = true;
+                    widened = true;
+                }
+            }
+            if !widened {
+                break offsets;
+            }
+        };
+
+        // 第二遍：回填字节偏移
+        for (fixup, &target) in fixups.iter().zip(&targets) {
+            let delta = offsets[target] as i64 - offsets[fixup.instr] as i64;
+            let instr = &mut instructions[fixup.instr];
+            instr
+                .operands
+                .truncate(usize::from(instr.opcode != Opcode::Jmp as u8));
+            if fixup.long {
+                let delta = i32::try_from(delta).map_err(|_| {
+                    ErrorCodeDefinition::codegen_invalid_operand("jump offset out of range").build()
+                })?;
+                instr.operands.extend_from_slice(&delta.to_le_bytes());
+            } else {
+                instr
+                    .operands
+                    .extend_from_slice(&(delta as i16).to_le_bytes());
+            }
         }
+        for table in tables {
+            let base = offsets[table.instr] as i64;
+            let instr = &mut instructions[table.instr];
+            for (i, &label) in table.labels.iter().enumerate() {
+                let delta =
+                    i32::try_from(offsets[resolve(label)?] as i64 - base).map_err(|_| {
+                        ErrorCodeDefinition::codegen_invalid_operand("jump offset out of range")
+                            .build()
+                    })?;
+                let at = TABLE_OFFSETS_START + 4 * i;
+                instr.operands[at..at + 4].copy_from_slice(&delta.to_le_bytes());
+            }
+        }
+        Ok(instructions)
     }
 
     /// 添加常量并返回索引
@@ -123,6 +296,18 @@ impl BytecodeBuffer {
 
     /// 获取常量池数据（获取所有权并清空）
     pub fn take_constant_pool(&mut self) -> Vec<ConstValue> {
-        std::mem::take(&mut self.constant_pool.constants)
+        std::mem::take(&mut self.constant_pool).build()
     }
 }
+
+/// `TableSwitch` 操作数中偏移槽位的起点：分派值寄存器（1）、下界（8）、表项数（2）之后
+pub const TABLE_OFFSETS_START: usize = 11;
+
+/// 跳转指令的操作数长度：条件寄存器（`JmpIf` / `JmpIfNot`）加 2 或 4 字节偏移
+fn jump_operand_len(
+    opcode: u8,
+    long: bool,
+) -> usize {
+    let cond = usize::from(opcode != Opcode::Jmp as u8);
+    cond + if long { 4 } else { 2 }
+}
@@ -1,12 +1,15 @@
 //! 字节码序列化
 //!
-//! 定义 .yx (.42) 字节码文件格式并实现序列化。
+//! 定义 .yx (.42 / .yxbc) 字节码文件格式并实现序列化。
+//!
+//! 文件头记录整个文件的长度和除函数体外其余内容的 CRC-32 校验和，打开时校验；
+//! 每个函数体的校验和记在代码段索引表中，在解码该函数体时校验。
+//! 这样按需加载时不必为了校验读取全部函数体，截断或损坏的内容仍在解码前被拒绝。
 
 use crate::frontend::core::typecheck::MonoType;
-use crate::middle::core::ir::ConstValue;
-use crate::util::span::{DebugSpan, FileId, Position, SourceMap, Span};
+use crate::middle::core::ir::{ConstValue, StructLayout, VTable};
+use crate::util::span::{DebugSpan, FileId, LineTable, Position, SourceMap, Span};
 use crate::backends::common::Opcode;
-use std::collections::HashMap;
 use std::io::{self, Read, Seek, SeekFrom, Write};
 
 /// 字节码文件头魔数 (YaoXiang ByteCode: YXBC)
@@ -14,12 +17,24 @@ use std::io::{self, Read, Seek, SeekFrom, Write};
 /// 文件格式采用混合端序：魔数大端序（方便调试），其他数据小端序（性能优化）
 const MAGIC: u32 = 0x59584243;
 /// 版本号
-const VERSION: u32 = 3;
+const VERSION: u32 = 17;
+/// 文件头字节数：魔数、版本、标志、入口、段数、文件长度、校验和
+const HEADER_SIZE: u64 = 26;
 
 const FLAG_DEBUG_INFO: u32 = 0x02;
 
 const DEBUG_SECTION_MAGIC: u32 = 0x59584442; // 'Y' 'X' 'D' 'B'
-const DEBUG_SECTION_VERSION: u32 = 1;
+const DEBUG_SECTION_VERSION: u32 = 2;
+
+/// 字节码文件的扩展名：`yaoxiang build` 默认输出 `.42`，也接受 `.yxbc`
+pub const BYTECODE_EXTENSIONS: [&str; 2] = ["42", "yxbc"];
+
+/// 按扩展名判断 `path` 是否为字节码文件
+pub fn is_bytecode_path(path: &std::path::Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| BYTECODE_EXTENSIONS.contains(&ext))
+}
 
 /// 字节码文件结构
 #[derive(Debug, Clone)]
@@ -32,6 +47,10 @@ pub struct BytecodeFile {
     pub const_pool: Vec<ConstValue>,
     /// 代码段
     pub code_section: CodeSection,
+    /// 接口虚表段
+    pub vtables: Vec<VTable>,
+    /// 结构体布局段
+    pub struct_layouts: Vec<StructLayout>,
 
     /// 可选调试信息段（用于离线 .42 调试/定位）
     pub debug_section: Option<DebugSection>,
@@ -63,16 +82,38 @@ impl Default for FileHeader {
     }
 }
 
+/// 代码段
+///
+/// 序列化布局：先写出全部函数的索引表（签名 + 函数体偏移），再写出连续的函数体。
+/// 加载器可以只读索引表，按需解码函数体（见 [`LazyBytecodeFile`]）。
 #[derive(Debug, Clone)]
 pub struct CodeSection {
     pub functions: Vec<FunctionCode>,
 }
 
-/// Debug section (sources + per-function ip mapping)
+/// 代码段索引表中的一项
+#[derive(Debug, Clone)]
+pub struct FunctionEntry {
+    pub name: String,
+    pub params: Vec<MonoType>,
+    pub return_type: MonoType,
+    pub local_count: usize,
+    /// 闭包捕获的变量数，调用时环境的前若干项成为 upvalue
+    pub upvalue_count: usize,
+    pub instr_count: usize,
+    /// 函数体相对代码体起点的字节偏移
+    pub offset: u32,
+    /// 函数体字节长度
+    pub len: u32,
+    /// 函数体的 CRC-32 校验和
+    pub checksum: u32,
+}
+
+/// Debug section (sources + per-function line tables)
 #[derive(Debug, Clone)]
 pub struct DebugSection {
     pub sources: SourceMap,
-    pub function_debug_maps: Vec<HashMap<usize, DebugSpan>>,
+    pub function_line_tables: Vec<LineTable>,
 }
 
 impl DebugSection {
@@ -80,13 +121,15 @@ impl DebugSection {
         sources: SourceMap,
         functions: &[FunctionCode],
     ) -> Self {
-        let function_debug_maps = functions.iter().map(|f| f.debug_map.clone()).collect();
+        let function_line_tables = functions.iter().map(|f| f.line_table.clone()).collect();
         Self {
             sources,
-            function_debug_maps,
+            function_line_tables,
         }
     }
 
+    /// 行号表按行增量编码：指令下标与位置都相对上一行，用变长整数存储，
+    /// 连续语句通常每行只占几个字节
     fn encode(&self) -> io::Result<Vec<u8>> {
         let mut out = Vec::new();
 
@@ -98,17 +141,23 @@ impl DebugSection {
             write_string(&mut out, &file.content)?;
         }
 
-        out.write_all(&(self.function_debug_maps.len() as u32).to_le_bytes())?;
-        for map in &self.function_debug_maps {
-            let mut entries: Vec<(usize, DebugSpan)> = map.iter().map(|(k, v)| (*k, *v)).collect();
-            entries.sort_by_key(|(ip, _)| *ip);
-
-            out.write_all(&(entries.len() as u32).to_le_bytes())?;
-            for (ip, ds) in entries {
-                out.write_all(&(ip as u32).to_le_bytes())?;
-                out.write_all(&ds.file_id.to_le_bytes())?;
-                write_position(&mut out, ds.span.start)?;
-                write_position(&mut out, ds.span.end)?;
+        out.write_all(&(self.function_line_tables.len() as u32).to_le_bytes())?;
+        for table in &self.function_line_tables {
+            write_varint(&mut out, table.len() as u64)?;
+            let mut prev_ip = 0;
+            let mut prev = Position::dummy();
+            for &(ip, ds) in table.rows() {
+                let Span { start, end } = ds.span;
+                write_varint(&mut out, (ip - prev_ip) as u64)?;
+                write_varint(&mut out, ds.file_id as u64)?;
+                write_svarint(&mut out, start.line as i64 - prev.line as i64)?;
+                write_varint(&mut out, start.column as u64)?;
+                write_svarint(&mut out, start.offset as i64 - prev.offset as i64)?;
+                write_svarint(&mut out, end.line as i64 - start.line as i64)?;
+                write_varint(&mut out, end.column as u64)?;
+                write_svarint(&mut out, end.offset as i64 - start.offset as i64)?;
+                prev_ip = ip;
+                prev = start;
             }
         }
 
@@ -135,23 +184,32 @@ impl DebugSection {
         }
 
         let func_count = read_u32(&mut cursor)? as usize;
-        let mut function_debug_maps = Vec::with_capacity(func_count);
+        let mut function_line_tables = Vec::with_capacity(func_count);
         for _ in 0..func_count {
-            let entry_count = read_u32(&mut cursor)? as usize;
-            let mut map = HashMap::with_capacity(entry_count);
-            for _ in 0..entry_count {
-                let ip = read_u32(&mut cursor)? as usize;
-                let file_id = read_u32(&mut cursor)? as FileId;
-                let start = read_position(&mut cursor)?;
-                let end = read_position(&mut cursor)?;
-                map.insert(ip, DebugSpan::new(file_id, Span::new(start, end)));
+            let row_count = read_varint(&mut cursor)?;
+            let mut table = LineTable::new();
+            let mut ip = 0usize;
+            let mut prev = Position::dummy();
+            for _ in 0..row_count {
+                ip += read_varint(&mut cursor)? as usize;
+                let file_id = read_varint(&mut cursor)? as FileId;
+                let line = offset_by(prev.line, read_svarint(&mut cursor)?)?;
+                let column = read_varint(&mut cursor)? as usize;
+                let offset = offset_by(prev.offset, read_svarint(&mut cursor)?)?;
+                let start = Position::with_offset(line, column, offset);
+                let end_line = offset_by(line, read_svarint(&mut cursor)?)?;
+                let end_column = read_varint(&mut cursor)? as usize;
+                let end_offset = offset_by(offset, read_svarint(&mut cursor)?)?;
+                let end = Position::with_offset(end_line, end_column, end_offset);
+                table.push(ip, DebugSpan::new(file_id, Span::new(start, end)));
+                prev = start;
             }
-            function_debug_maps.push(map);
+            function_line_tables.push(table);
         }
 
         Ok(Self {
             sources,
-            function_debug_maps,
+            function_line_tables,
         })
     }
 
@@ -193,8 +251,10 @@ pub struct FunctionCode {
     pub return_type: MonoType,
     pub instructions: Vec<BytecodeInstruction>,
     pub local_count: usize,
-    /// Debug info: mapping from IP to source Span
-    pub debug_map: HashMap<usize, DebugSpan>,
+    /// 闭包捕获的变量数
+    pub upvalue_count: usize,
+    /// Debug info: line table from instruction index to source span
+    pub line_table: LineTable,
 }
 
 #[derive(Debug, Clone)]
@@ -214,6 +274,45 @@ impl BytecodeInstruction {
         }
     }
 
+    /// 按 `remap`（旧索引 -> 新索引）改写操作数中的常量池索引
+    ///
+    /// 索引的位置取自操作码的操作数布局（[`Opcode::const_operands`]）。
+    /// 新索引放不进原有宽度时报错，不截断。
+    pub fn remap_consts(
+        &mut self,
+        remap: &[usize],
+    ) -> Result<(), String> {
+        let Ok(opcode) = Opcode::try_from(self.opcode) else {
+            return Ok(());
+        };
+        for (offset, width) in opcode.const_operands(&self.operands) {
+            let bytes = self
+                .operands
+                .get_mut(offset..offset + width)
+                .ok_or_else(|| format!("{} operands too short for a constant index", opcode))?;
+            let old = if width == 2 {
+                u16::from_le_bytes([bytes[0], bytes[1]]) as usize
+            } else {
+                u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize
+            };
+            let new = *remap
+                .get(old)
+                .ok_or_else(|| format!("{} refers to missing constant {}", opcode, old))?;
+            if width == 2 {
+                let new = u16::try_from(new).map_err(|_| {
+                    format!("{} constant index {} exceeds {}", opcode, new, u16::MAX)
+                })?;
+                bytes.copy_from_slice(&new.to_le_bytes());
+            } else {
+                let new = u32::try_from(new).map_err(|_| {
+                    format!("{} constant index {} exceeds {}", opcode, new, u32::MAX)
+                })?;
+                bytes.copy_from_slice(&new.to_le_bytes());
+            }
+        }
+        Ok(())
+    }
+
     /// 编码为字节序列
     pub fn encode(&self) -> Vec<u8> {
         let mut bytes = vec![self.opcode];
@@ -253,6 +352,18 @@ impl BytecodeFile {
             header.section_count = 5;
         }
 
+        // 先写出各段，文件长度与校验和随后填入文件头
+        let mut sections = Vec::new();
+        let bodies = self.write_sections(header.flags, &mut sections)?;
+        header.file_size = u32::try_from(HEADER_SIZE as usize + sections.len()).map_err(|_| {
+            io::Error::new(io::ErrorKind::InvalidInput, "bytecode file exceeds 4 GiB")
+        })?;
+        // 函数体各有校验和，不计入文件头的校验和
+        let mut crc = Crc32::new();
+        crc.update(&sections[..bodies.start]);
+        crc.update(&sections[bodies.end..]);
+        header.checksum = crc.finish();
+
         // 文件头：魔数大端序，其他小端序
         writer.write_all(&header.magic.to_be_bytes())?; // YXBC 方便调试
         writer.write_all(&header.version.to_le_bytes())?;
@@ -261,64 +372,65 @@ impl BytecodeFile {
         writer.write_all(&header.section_count.to_le_bytes())?;
         writer.write_all(&header.file_size.to_le_bytes())?;
         writer.write_all(&header.checksum.to_le_bytes())?;
+        writer.write_all(&sections)
+    }
 
+    /// 文件头之后的各段：类型表、常量池、代码段、虚表、结构体布局与可选的调试段
+    ///
+    /// 返回函数体区域在 `writer` 中的范围。
+    fn write_sections(
+        &self,
+        flags: u32,
+        writer: &mut Vec<u8>,
+    ) -> io::Result<std::ops::Range<usize>> {
         // 类型表 (小端序，性能优化)
         writer.write_all(&(self.type_table.len() as u32).to_le_bytes())?;
         for ty in &self.type_table {
-            writer.write_all(&ty.to_type_id().to_le_bytes())?;
+            write_type(writer, ty)?;
         }
 
         // 常量池 (小端序，性能优化)
         writer.write_all(&(self.const_pool.len() as u32).to_le_bytes())?;
         for const_val in &self.const_pool {
-            match const_val {
-                ConstValue::Void => writer.write_all(&[0])?,
-                ConstValue::Bool(b) => writer.write_all(&[1, if *b { 1 } else { 0 }])?,
-                ConstValue::Int(n) => {
-                    writer.write_all(&[2])?;
-                    writer.write_all(&n.to_le_bytes())?;
-                }
-                ConstValue::Float(f) => {
-                    writer.write_all(&[3])?;
-                    writer.write_all(&f.to_le_bytes())?;
-                }
-                ConstValue::Char(c) => {
-                    writer.write_all(&[4])?;
-                    writer.write_all(&(*c as u32).to_le_bytes())?;
-                }
-                ConstValue::String(s) => {
-                    writer.write_all(&[5])?;
-                    writer.write_all(&(s.len() as u32).to_le_bytes())?;
-                    writer.write_all(s.as_bytes())?;
-                }
-                ConstValue::Bytes(bytes) => {
-                    writer.write_all(&[6])?;
-                    writer.write_all(&(bytes.len() as u32).to_le_bytes())?;
-                    writer.write_all(bytes)?;
-                }
-                ConstValue::LibraryRef { .. } | ConstValue::ExternRef { .. } => todo!(),
-            }
+            write_const(writer, const_val)?;
         }
 
-        // 代码段 (小端序，性能优化)
+        // 代码段 (小端序，性能优化)：索引表 + 函数体
+        let bodies: Vec<Vec<u8>> = self
+            .code_section
+            .functions
+            .iter()
+            .map(|func| func.encode_all())
+            .collect();
         writer.write_all(&(self.code_section.functions.len() as u32).to_le_bytes())?;
-        for func in &self.code_section.functions {
-            writer.write_all(&(func.name.len() as u32).to_le_bytes())?;
-            writer.write_all(func.name.as_bytes())?;
+        let mut offset = 0u32;
+        for (func, body) in self.code_section.functions.iter().zip(&bodies) {
+            write_string(writer, &func.name)?;
             writer.write_all(&(func.params.len() as u32).to_le_bytes())?;
-            writer.write_all(&func.return_type.to_type_id().to_le_bytes())?;
+            for param in &func.params {
+                write_type(writer, param)?;
+            }
+            write_type(writer, &func.return_type)?;
             writer.write_all(&(func.local_count as u32).to_le_bytes())?;
+            writer.write_all(&(func.upvalue_count as u32).to_le_bytes())?;
             writer.write_all(&(func.instructions.len() as u32).to_le_bytes())?;
-            for instr in &func.instructions {
-                writer.write_all(&[instr.opcode])?;
-                writer.write_all(&(instr.operands.len() as u16).to_le_bytes())?;
-                writer.write_all(&instr.operands)?;
-            }
+            writer.write_all(&offset.to_le_bytes())?;
+            writer.write_all(&(body.len() as u32).to_le_bytes())?;
+            writer.write_all(&crc32(body).to_le_bytes())?;
+            offset += body.len() as u32;
+        }
+        writer.write_all(&offset.to_le_bytes())?;
+        let code_start = writer.len();
+        for body in &bodies {
+            writer.write_all(body)?;
         }
+        let code = code_start..writer.len();
 
-        writer.write_all(&[0u8; 4])?; // 跳转表
+        // 虚表段（位于原跳转表占位处，数量为 0 时与旧文件布局一致）
+        write_vtables(writer, &self.vtables)?;
+        write_struct_layouts(writer, &self.struct_layouts)?;
 
-        if (header.flags & FLAG_DEBUG_INFO) != 0 {
+        if (flags & FLAG_DEBUG_INFO) != 0 {
             let Some(debug) = &self.debug_section else {
                 return Err(io::Error::new(
                     io::ErrorKind::InvalidInput,
@@ -326,7 +438,7 @@ impl BytecodeFile {
                 ));
             };
 
-            if debug.function_debug_maps.len() != self.code_section.functions.len() {
+            if debug.function_line_tables.len() != self.code_section.functions.len() {
                 return Err(io::Error::new(
                     io::ErrorKind::InvalidInput,
                     "debug_section function count mismatch",
@@ -339,7 +451,7 @@ impl BytecodeFile {
             writer.write_all(&(payload.len() as u32).to_le_bytes())?;
         }
 
-        Ok(())
+        Ok(code)
     }
 }
 
@@ -347,193 +459,756 @@ impl BytecodeFile {
     ///
     /// 格式与 `write_to` 对称。支持通过文件尾的 YXDB 魔数检测可选的调试段。
     pub fn read_from<R: Read + Seek>(reader: &mut R) -> io::Result<Self> {
-        // 读取文件头
-        let mut buf32 = [0u8; 4];
-        reader.read_exact(&mut buf32)?;
-        let magic = u32::from_be_bytes(buf32);
-        if magic != MAGIC {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                format!("invalid magic: expected YXBC (0x{MAGIC:08X}), got 0x{magic:08X}"),
-            ));
+        let (header, type_table, const_pool) = read_prelude(reader)?;
+
+        // 读取代码段：索引表后是连续的函数体
+        let (entries, code_len) = read_code_index(reader)?;
+        let code_start = reader.stream_position()?;
+        let mut code = vec![0u8; code_len];
+        reader.read_exact(&mut code)?;
+        verify_checksum(reader, &header, code_start..code_start + code_len as u64)?;
+
+        let mut functions = Vec::with_capacity(entries.len());
+        for entry in entries {
+            let start = entry.offset as usize;
+            let end = start + entry.len as usize;
+            let body = code.get(start..end).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("function '{}' body out of code section", entry.name),
+                )
+            })?;
+            let instructions = entry.decode_body(body)?;
+            functions.push(entry.into_function(instructions));
         }
 
-        let version = read_u32(reader)?;
-        if version != VERSION {
+        let vtables = read_vtables(reader)?;
+        let struct_layouts = read_struct_layouts(reader)?;
+
+        // 可选的调试段（从文件尾向后读取）
+        let debug_section = DebugSection::read_from_end(reader)?;
+
+        Ok(Self {
+            header,
+            type_table,
+            const_pool,
+            code_section: CodeSection { functions },
+            vtables,
+            struct_layouts,
+            debug_section,
+        })
+    }
+
+    /// 从文件路径加载字节码文件
+    pub fn load<P: AsRef<std::path::Path>>(path: P) -> io::Result<Self> {
+        let file = std::fs::File::open(path.as_ref())?;
+        let mut reader = std::io::BufReader::new(file);
+        Self::read_from(&mut reader)
+    }
+}
+
+impl FunctionEntry {
+    /// 校验并解码函数体
+    fn decode_body(
+        &self,
+        body: &[u8],
+    ) -> io::Result<Vec<BytecodeInstruction>> {
+        let actual = crc32(body);
+        if actual != self.checksum {
             return Err(io::Error::new(
                 io::ErrorKind::InvalidData,
-                format!("unsupported bytecode version {version}, expected {VERSION}"),
+                format!(
+                    "function '{}' body checksum mismatch: expected 0x{:08X}, got 0x{actual:08X}",
+                    self.name, self.checksum
+                ),
             ));
         }
+        decode_instructions(body, self.instr_count)
+    }
 
-        let flags = read_u32(reader)?;
-        let entry_point = read_u32(reader)?;
+    fn into_function(
+        self,
+        instructions: Vec<BytecodeInstruction>,
+    ) -> FunctionCode {
+        FunctionCode {
+            name: self.name,
+            params: self.params,
+            return_type: self.return_type,
+            instructions,
+            local_count: self.local_count,
+            upvalue_count: self.upvalue_count,
+            line_table: LineTable::new(),
+        }
+    }
+}
 
-        let mut buf16 = [0u8; 2];
-        reader.read_exact(&mut buf16)?;
-        let section_count = u16::from_le_bytes(buf16);
+/// 可随机读取的字节源
+pub trait ReadSeek: Read + Seek + Send {}
 
-        let _file_size = read_u32(reader)?;
-        let _checksum = read_u32(reader)?;
+impl<T: Read + Seek + Send> ReadSeek for T {}
 
-        let header = FileHeader {
-            magic,
-            version,
-            flags,
-            entry_point,
-            section_count,
-            file_size: _file_size,
-            checksum: _checksum,
+/// 按需加载函数体的字节码文件
+///
+/// 打开时校验文件长度与除函数体外其余内容的校验和，只解码文件头、类型表、常量池和代码段索引表；
+/// 函数体在第一次需要时才从文件中定位、校验并解码，大程序启动时不必读取全部函数。
+pub struct LazyBytecodeFile {
+    pub header: FileHeader,
+    pub type_table: Vec<MonoType>,
+    pub const_pool: Vec<ConstValue>,
+    /// 代码段索引表（与函数下标一一对应）
+    pub functions: Vec<FunctionEntry>,
+    /// 接口虚表段
+    pub vtables: Vec<VTable>,
+    /// 结构体布局段
+    pub struct_layouts: Vec<StructLayout>,
+    /// 调试段中每个函数的行号表（无调试段时为空）
+    line_tables: Vec<LineTable>,
+    /// 调试段中的源文件（无调试段时为 `None`）
+    sources: Option<SourceMap>,
+    reader: Box<dyn ReadSeek>,
+    /// 函数体区域在文件中的起始位置
+    code_start: u64,
+}
+
+impl std::fmt::Debug for LazyBytecodeFile {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        f.debug_struct("LazyBytecodeFile")
+            .field("header", &self.header)
+            .field("functions", &self.functions.len())
+            .field("code_start", &self.code_start)
+            .finish()
+    }
+}
+
+impl LazyBytecodeFile {
+    /// 从文件路径打开字节码文件（不解码函数体）
+    pub fn open<P: AsRef<std::path::Path>>(path: P) -> io::Result<Self> {
+        let file = std::fs::File::open(path.as_ref())?;
+        Self::from_reader(std::io::BufReader::new(file))
+    }
+
+    /// 从任意可随机读取的字节源打开
+    pub fn from_reader<R: ReadSeek + 'static>(mut reader: R) -> io::Result<Self> {
+        let (header, type_table, const_pool) = read_prelude(&mut reader)?;
+        let (functions, code_len) = read_code_index(&mut reader)?;
+        let code_start = reader.stream_position()?;
+        verify_checksum(
+            &mut reader,
+            &header,
+            code_start..code_start + code_len as u64,
+        )?;
+        let vtables = read_vtables(&mut reader)?;
+        let struct_layouts = read_struct_layouts(&mut reader)?;
+
+        let (sources, line_tables) = match DebugSection::read_from_end(&mut reader)? {
+            Some(debug) => (Some(debug.sources), debug.function_line_tables),
+            None => (None, Vec::new()),
         };
 
-        // 读取类型表
-        let type_count = read_u32(reader)? as usize;
-        let mut type_table = Vec::with_capacity(type_count);
-        for _ in 0..type_count {
-            let type_id = read_u32(reader)?;
-            type_table.push(type_id_to_monotype(type_id));
-        }
-
-        // 读取常量池
-        let const_count = read_u32(reader)? as usize;
-        let mut const_pool = Vec::with_capacity(const_count);
-        for _ in 0..const_count {
-            let mut tag_buf = [0u8; 1];
-            reader.read_exact(&mut tag_buf)?;
-            let tag = tag_buf[0];
-
-            let const_val = match tag {
-                0 => ConstValue::Void,
-                1 => {
-                    let mut b = [0u8; 1];
-                    reader.read_exact(&mut b)?;
-                    ConstValue::Bool(b[0] != 0)
-                }
-                2 => {
-                    let mut n = [0u8; 16];
-                    reader.read_exact(&mut n)?;
-                    ConstValue::Int(i128::from_le_bytes(n))
-                }
-                3 => {
-                    let mut f = [0u8; 8];
-                    reader.read_exact(&mut f)?;
-                    ConstValue::Float(f64::from_le_bytes(f))
-                }
-                4 => {
-                    let mut c = [0u8; 4];
-                    reader.read_exact(&mut c)?;
-                    let code = u32::from_le_bytes(c);
-                    ConstValue::Char(char::from_u32(code).unwrap_or('\u{FFFD}'))
-                }
-                5 => {
-                    let s = read_string(reader)?;
-                    ConstValue::String(s)
-                }
-                6 => {
-                    let len = read_u32(reader)? as usize;
-                    let mut bytes = vec![0u8; len];
-                    reader.read_exact(&mut bytes)?;
-                    ConstValue::Bytes(bytes)
-                }
-                _ => {
-                    return Err(io::Error::new(
-                        io::ErrorKind::InvalidData,
-                        format!("unknown const tag: {tag}"),
-                    ));
-                }
-            };
-            const_pool.push(const_val);
+        Ok(Self {
+            header,
+            type_table,
+            const_pool,
+            functions,
+            vtables,
+            struct_layouts,
+            line_tables,
+            sources,
+            reader: Box::new(reader),
+            code_start,
+        })
+    }
+
+    /// 调试段中的源文件，运行时错误据此显示 `文件:行:列`
+    pub fn sources(&self) -> Option<&SourceMap> {
+        self.sources.as_ref()
+    }
+
+    /// 按名称查找函数下标
+    pub fn function_index(
+        &self,
+        name: &str,
+    ) -> Option<usize> {
+        self.functions.iter().position(|f| f.name == name)
+    }
+
+    /// 读取并解码第 `index` 个函数的函数体
+    pub fn load_function(
+        &mut self,
+        index: usize,
+    ) -> io::Result<FunctionCode> {
+        let entry = self.functions.get(index).cloned().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("function index {index} out of range"),
+            )
+        })?;
+
+        self.reader
+            .seek(SeekFrom::Start(self.code_start + entry.offset as u64))?;
+        let mut body = vec![0u8; entry.len as usize];
+        self.reader.read_exact(&mut body)?;
+        let instructions = entry.decode_body(&body)?;
+
+        let mut func = entry.into_function(instructions);
+        if let Some(line_table) = self.line_tables.get_mut(index) {
+            func.line_table = std::mem::take(line_table);
         }
+        Ok(func)
+    }
+}
 
-        // 读取代码段
-        let func_count = read_u32(reader)? as usize;
-        let mut functions = Vec::with_capacity(func_count);
-        for _ in 0..func_count {
-            let name = read_string(reader)?;
+/// 读取文件头、类型表与常量池
+fn read_prelude<R: Read + Seek>(
+    reader: &mut R
+) -> io::Result<(FileHeader, Vec<MonoType>, Vec<ConstValue>)> {
+    // 读取文件头
+    let mut buf32 = [0u8; 4];
+    reader.read_exact(&mut buf32)?;
+    let magic = u32::from_be_bytes(buf32);
+    if magic != MAGIC {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("invalid magic: expected YXBC (0x{MAGIC:08X}), got 0x{magic:08X}"),
+        ));
+    }
 
-            let param_count = read_u32(reader)? as usize;
-            let mut params = Vec::with_capacity(param_count);
-            for _ in 0..param_count {
-                let type_id = read_u32(reader)?;
-                params.push(type_id_to_monotype(type_id));
-            }
+    let version = read_u32(reader)?;
+    if version != VERSION {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("unsupported bytecode version {version}, expected {VERSION}"),
+        ));
+    }
 
-            let return_type_id = read_u32(reader)?;
-            let return_type = type_id_to_monotype(return_type_id);
+    let flags = read_u32(reader)?;
+    let entry_point = read_u32(reader)?;
+
+    let mut buf16 = [0u8; 2];
+    reader.read_exact(&mut buf16)?;
+    let section_count = u16::from_le_bytes(buf16);
+
+    let file_size = read_u32(reader)?;
+    let checksum = read_u32(reader)?;
+
+    let header = FileHeader {
+        magic,
+        version,
+        flags,
+        entry_point,
+        section_count,
+        file_size,
+        checksum,
+    };
+    let file_end = verify_file_size(reader, &header)?;
+
+    // 读取类型表
+    let type_count = read_len(reader, file_end, 1)?;
+    let mut type_table = Vec::with_capacity(type_count);
+    for _ in 0..type_count {
+        type_table.push(read_type(reader)?);
+    }
 
-            let local_count = read_u32(reader)? as usize;
-            let instr_count = read_u32(reader)? as usize;
+    // 读取常量池
+    let const_count = read_len(reader, file_end, 1)?;
+    let mut const_pool = Vec::with_capacity(const_count);
+    for _ in 0..const_count {
+        const_pool.push(read_const(reader, file_end, 0)?);
+    }
 
-            let mut instructions = Vec::with_capacity(instr_count);
-            for _ in 0..instr_count {
-                let mut opcode_buf = [0u8; 1];
-                reader.read_exact(&mut opcode_buf)?;
-                let opcode = opcode_buf[0];
+    Ok((header, type_table, const_pool))
+}
+
+/// 校验文件长度，完成后回到原位置，返回文件末尾位置
+fn verify_file_size<R: Read + Seek>(
+    reader: &mut R,
+    header: &FileHeader,
+) -> io::Result<u64> {
+    let start = reader.stream_position()?;
+    let file_end = reader.seek(SeekFrom::End(0))?;
+    if file_end - start + HEADER_SIZE != u64::from(header.file_size) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "bytecode file size mismatch: header says {} bytes, file has {}",
+                header.file_size,
+                file_end - start + HEADER_SIZE
+            ),
+        ));
+    }
+    reader.seek(SeekFrom::Start(start))?;
+    Ok(file_end)
+}
 
-                let mut len_buf = [0u8; 2];
-                reader.read_exact(&mut len_buf)?;
-                let operand_len = u16::from_le_bytes(len_buf) as usize;
+/// 校验文件头之后、函数体区域 `code` 以外全部内容的校验和，完成后停在函数体区域之后
+///
+/// 须在 [`verify_file_size`] 之后调用。
+fn verify_checksum<R: Read + Seek>(
+    reader: &mut R,
+    header: &FileHeader,
+    code: std::ops::Range<u64>,
+) -> io::Result<()> {
+    let file_end = reader.seek(SeekFrom::End(0))?;
+    let start = file_end + HEADER_SIZE - u64::from(header.file_size);
+    let mut crc = Crc32::new();
+    reader.seek(SeekFrom::Start(start))?;
+    hash_range(reader, code.start - start, &mut crc)?;
+    reader.seek(SeekFrom::Start(code.end))?;
+    hash_range(reader, file_end.saturating_sub(code.end), &mut crc)?;
+    let actual = crc.finish();
+    if actual != header.checksum {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "bytecode checksum mismatch: expected 0x{:08X}, got 0x{actual:08X}",
+                header.checksum
+            ),
+        ));
+    }
+    reader.seek(SeekFrom::Start(code.end))?;
+    Ok(())
+}
 
-                let mut operands = vec![0u8; operand_len];
-                if operand_len > 0 {
-                    reader.read_exact(&mut operands)?;
-                }
+/// 从当前位置读取 `len` 字节计入校验和
+fn hash_range<R: Read>(
+    reader: &mut R,
+    len: u64,
+    crc: &mut Crc32,
+) -> io::Result<()> {
+    let mut chunk = [0u8; 64 * 1024];
+    let mut remaining = len;
+    while remaining > 0 {
+        let n = chunk.len().min(remaining as usize);
+        reader.read_exact(&mut chunk[..n])?;
+        crc.update(&chunk[..n]);
+        remaining -= n as u64;
+    }
+    Ok(())
+}
 
-                instructions.push(BytecodeInstruction { opcode, operands });
+/// CRC-32（IEEE 802.3，与 zlib 相同）
+struct Crc32(u32);
+
+impl Crc32 {
+    const TABLE: [u32; 256] = {
+        let mut table = [0u32; 256];
+        let mut i = 0;
+        while i < 256 {
+            let mut crc = i as u32;
+            let mut bit = 0;
+            while bit < 8 {
+                crc = if crc & 1 != 0 {
+                    0xEDB8_8320 ^ (crc >> 1)
+                } else {
+                    crc >> 1
+                };
+                bit += 1;
             }
+            table[i] = crc;
+            i += 1;
+        }
+        table
+    };
 
-            functions.push(FunctionCode {
-                name,
-                params,
-                return_type,
-                instructions,
-                local_count,
-                debug_map: HashMap::new(),
-            });
+    fn new() -> Self {
+        Self(!0)
+    }
+
+    fn update(
+        &mut self,
+        bytes: &[u8],
+    ) {
+        for &byte in bytes {
+            self.0 = Self::TABLE[((self.0 ^ u32::from(byte)) & 0xff) as usize] ^ (self.0 >> 8);
         }
+    }
 
-        // 跳转表（4 字节填充）
-        let mut jump_table = [0u8; 4];
-        reader.read_exact(&mut jump_table)?;
+    fn finish(&self) -> u32 {
+        !self.0
+    }
+}
 
-        // 可选的调试段（从文件尾向后读取）
-        let debug_section = DebugSection::read_from_end(reader)?;
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = Crc32::new();
+    crc.update(bytes);
+    crc.finish()
+}
 
-        Ok(Self {
-            header,
-            type_table,
-            const_pool,
-            code_section: CodeSection { functions },
-            debug_section,
-        })
+/// 读取代码段索引表，返回索引项和函数体区域总长度
+fn read_code_index<R: Read>(reader: &mut R) -> io::Result<(Vec<FunctionEntry>, usize)> {
+    let func_count = read_u32(reader)? as usize;
+    let mut entries = Vec::with_capacity(func_count);
+    for _ in 0..func_count {
+        let name = read_string(reader)?;
+
+        let param_count = read_u32(reader)? as usize;
+        let mut params = Vec::with_capacity(param_count);
+        for _ in 0..param_count {
+            params.push(read_type(reader)?);
+        }
+
+        let return_type = read_type(reader)?;
+        let local_count = read_u32(reader)? as usize;
+        let upvalue_count = read_u32(reader)? as usize;
+        let instr_count = read_u32(reader)? as usize;
+        let offset = read_u32(reader)?;
+        let len = read_u32(reader)?;
+        let checksum = read_u32(reader)?;
+
+        entries.push(FunctionEntry {
+            name,
+            params,
+            return_type,
+            local_count,
+            upvalue_count,
+            instr_count,
+            offset,
+            len,
+            checksum,
+        });
     }
+    let code_len = read_u32(reader)? as usize;
+    Ok((entries, code_len))
+}
 
-    /// 从文件路径加载字节码文件
-    pub fn load<P: AsRef<std::path::Path>>(path: P) -> io::Result<Self> {
-        let file = std::fs::File::open(path.as_ref())?;
-        let mut reader = std::io::BufReader::new(file);
-        Self::read_from(&mut reader)
+/// 写出虚表段：数量 + 每个虚表的接口名、类型名与方法函数名
+fn write_vtables<W: Write>(
+    writer: &mut W,
+    vtables: &[VTable],
+) -> io::Result<()> {
+    writer.write_all(&(vtables.len() as u32).to_le_bytes())?;
+    for vtable in vtables {
+        write_string(writer, &vtable.interface)?;
+        write_string(writer, &vtable.type_name)?;
+        writer.write_all(&(vtable.methods.len() as u32).to_le_bytes())?;
+        for method in &vtable.methods {
+            write_string(writer, method)?;
+        }
     }
+    Ok(())
+}
+
+/// 读取虚表段
+fn read_vtables<R: Read>(reader: &mut R) -> io::Result<Vec<VTable>> {
+    let count = read_u32(reader)? as usize;
+    let mut vtables = Vec::with_capacity(count);
+    for _ in 0..count {
+        let interface = read_string(reader)?;
+        let type_name = read_string(reader)?;
+        let method_count = read_u32(reader)? as usize;
+        let mut methods = Vec::with_capacity(method_count);
+        for _ in 0..method_count {
+            methods.push(read_string(reader)?);
+        }
+        vtables.push(VTable {
+            interface,
+            type_name,
+            methods,
+        });
+    }
+    Ok(vtables)
+}
+
+/// 写出结构体布局段：数量 + 每个结构体的类型名、大小、对齐与字段名和偏移
+fn write_struct_layouts<W: Write>(
+    writer: &mut W,
+    layouts: &[StructLayout],
+) -> io::Result<()> {
+    writer.write_all(&(layouts.len() as u32).to_le_bytes())?;
+    for layout in layouts {
+        write_string(writer, &layout.name)?;
+        writer.write_all(&layout.size.to_le_bytes())?;
+        writer.write_all(&layout.align.to_le_bytes())?;
+        writer.write_all(&(layout.fields.len() as u32).to_le_bytes())?;
+        for (field, offset) in layout.fields.iter().zip(&layout.offsets) {
+            write_string(writer, field)?;
+            writer.write_all(&offset.to_le_bytes())?;
+        }
+    }
+    Ok(())
+}
+
+/// 读取结构体布局段
+fn read_struct_layouts<R: Read>(reader: &mut R) -> io::Result<Vec<StructLayout>> {
+    let count = read_u32(reader)? as usize;
+    let mut layouts = Vec::with_capacity(count);
+    for _ in 0..count {
+        let name = read_string(reader)?;
+        let size = read_u32(reader)?;
+        let align = read_u32(reader)?;
+        let field_count = read_u32(reader)? as usize;
+        let mut fields = Vec::with_capacity(field_count);
+        let mut offsets = Vec::with_capacity(field_count);
+        for _ in 0..field_count {
+            fields.push(read_string(reader)?);
+            offsets.push(read_u32(reader)?);
+        }
+        layouts.push(StructLayout {
+            name,
+            fields,
+            offsets,
+            size,
+            align,
+        });
+    }
+    Ok(layouts)
+}
+
+/// 解码一个函数体中的 `count` 条指令
+fn decode_instructions(
+    body: &[u8],
+    count: usize,
+) -> io::Result<Vec<BytecodeInstruction>> {
+    let mut cursor = io::Cursor::new(body);
+    let mut instructions = Vec::with_capacity(count);
+    for _ in 0..count {
+        let mut opcode_buf = [0u8; 1];
+        cursor.read_exact(&mut opcode_buf)?;
+        let opcode = opcode_buf[0];
+
+        let mut len_buf = [0u8; 2];
+        cursor.read_exact(&mut len_buf)?;
+        let operand_len = u16::from_le_bytes(len_buf) as usize;
+
+        let mut operands = vec![0u8; operand_len];
+        if operand_len > 0 {
+            cursor.read_exact(&mut operands)?;
+        }
+
+        instructions.push(BytecodeInstruction { opcode, operands });
+    }
+    Ok(instructions)
 }
 
 /// 将 type_id (u32) 转换为相应的 MonoType。
 ///
 /// 序列化是 lossy 的（复杂类型如 Struct/Enum 只存储一个 id），
-/// 因此复杂类型会 fallback 到 `TypeRef("_")`。
-/// 简单类型（Void/Bool/Int/Float/Char/String/Bytes）可以精确重建。
+/// 因此复杂类型会 fallback 到 `TypeRef("_")`；带名称的 TypeRef 与泛型实例由 `read_type` 重建。
+/// 简单类型（Void/Bool/Int/Float/Char/String/Bytes/Any）可以精确重建。
 fn type_id_to_monotype(id: u32) -> MonoType {
     match id {
         0 => MonoType::Void,
         1 => MonoType::Bool,
-        2..=5 => MonoType::Int(((id - 2) * 8 + 8) as usize),
-        6..=9 => MonoType::Float(((id - 6) * 8 + 8) as usize),
+        2..=5 => MonoType::Int(8 << (id - 2)),
+        6..=9 => MonoType::Float(8 << (id - 6)),
         10 => MonoType::Char,
         11 => MonoType::String,
         12 => MonoType::Bytes,
+        13 => MonoType::Any,
         _ => MonoType::TypeRef("_".to_string()),
     }
 }
 
+/// 写入类型：类型 ID，命名类型引用后随类型名，泛型实例后随类型名与各实参
+fn write_type<W: Write>(
+    writer: &mut W,
+    ty: &MonoType,
+) -> io::Result<()> {
+    if let MonoType::Refined { base, .. } = ty {
+        return write_type(writer, base);
+    }
+    writer.write_all(&ty.to_type_id().to_le_bytes())?;
+    match ty {
+        MonoType::TypeRef(name) => write_string(writer, name),
+        MonoType::Generic { name, args } => {
+            write_string(writer, name)?;
+            writer.write_all(&(args.len() as u32).to_le_bytes())?;
+            for arg in args {
+                write_type(writer, arg)?;
+            }
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// 读取 `write_type` 写入的类型
+fn read_type<R: Read>(reader: &mut R) -> io::Result<MonoType> {
+    match read_u32(reader)? {
+        40 => Ok(MonoType::TypeRef(read_string(reader)?)),
+        51 => {
+            let name = read_string(reader)?;
+            let arg_count = read_u32(reader)? as usize;
+            let mut args = Vec::with_capacity(arg_count);
+            for _ in 0..arg_count {
+                args.push(read_type(reader)?);
+            }
+            Ok(MonoType::Generic { name, args })
+        }
+        id => Ok(type_id_to_monotype(id)),
+    }
+}
+
+/// 写入单个常量（复合常量递归写入元素）
+fn write_const<W: Write>(
+    writer: &mut W,
+    const_val: &ConstValue,
+) -> io::Result<()> {
+    match const_val {
+        ConstValue::Void => writer.write_all(&[0])?,
+        ConstValue::Bool(b) => writer.write_all(&[1, if *b { 1 } else { 0 }])?,
+        ConstValue::Int(n) => {
+            writer.write_all(&[2])?;
+            writer.write_all(&n.to_le_bytes())?;
+        }
+        ConstValue::Float(f) => {
+            writer.write_all(&[3])?;
+            writer.write_all(&f.to_le_bytes())?;
+        }
+        ConstValue::Char(c) => {
+            writer.write_all(&[4])?;
+            writer.write_all(&(*c as u32).to_le_bytes())?;
+        }
+        ConstValue::String(s) => {
+            writer.write_all(&[5])?;
+            writer.write_all(&(s.len() as u32).to_le_bytes())?;
+            writer.write_all(s.as_bytes())?;
+        }
+        ConstValue::Bytes(bytes) => {
+            writer.write_all(&[6])?;
+            writer.write_all(&(bytes.len() as u32).to_le_bytes())?;
+            writer.write_all(bytes)?;
+        }
+        ConstValue::List(items) => {
+            writer.write_all(&[7])?;
+            writer.write_all(&(items.len() as u32).to_le_bytes())?;
+            for item in items {
+                write_const(writer, item)?;
+            }
+        }
+        ConstValue::Dict(entries) => {
+            writer.write_all(&[8])?;
+            writer.write_all(&(entries.len() as u32).to_le_bytes())?;
+            for (key, value) in entries {
+                write_const(writer, key)?;
+                write_const(writer, value)?;
+            }
+        }
+        ConstValue::Struct { type_name, fields } => {
+            writer.write_all(&[9])?;
+            write_string(writer, type_name)?;
+            writer.write_all(&(fields.len() as u32).to_le_bytes())?;
+            for field in fields {
+                write_const(writer, field)?;
+            }
+        }
+        ConstValue::LibraryRef { .. } | ConstValue::ExternRef { .. } => todo!(),
+    }
+    Ok(())
+}
+
+/// 常量嵌套层数上限，防止损坏的文件耗尽栈
+const MAX_CONST_DEPTH: usize = 64;
+
+/// 读取元素个数
+///
+/// 每个元素至少占 `min_size` 字节，个数超出 `file_end` 之前的剩余字节即为损坏，
+/// 以免按损坏的长度预分配内存。
+fn read_len<R: Read + Seek>(
+    reader: &mut R,
+    file_end: u64,
+    min_size: u64,
+) -> io::Result<usize> {
+    let len = read_u32(reader)?;
+    let remaining = file_end.saturating_sub(reader.stream_position()?);
+    if u64::from(len) * min_size > remaining {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("length {len} exceeds the {remaining} bytes left in the file"),
+        ));
+    }
+    Ok(len as usize)
+}
+
+/// 读取单个常量（与 [`write_const`] 对应），`depth` 为所在的嵌套层数
+fn read_const<R: Read + Seek>(
+    reader: &mut R,
+    file_end: u64,
+    depth: usize,
+) -> io::Result<ConstValue> {
+    let mut tag_buf = [0u8; 1];
+    reader.read_exact(&mut tag_buf)?;
+    let tag = tag_buf[0];
+
+    let const_val = match tag {
+        0 => ConstValue::Void,
+        1 => {
+            let mut b = [0u8; 1];
+            reader.read_exact(&mut b)?;
+            ConstValue::Bool(b[0] != 0)
+        }
+        2 => {
+            let mut n = [0u8; 16];
+            reader.read_exact(&mut n)?;
+            ConstValue::Int(i128::from_le_bytes(n))
+        }
+        3 => {
+            let mut f = [0u8; 8];
+            reader.read_exact(&mut f)?;
+            ConstValue::Float(f64::from_le_bytes(f))
+        }
+        4 => {
+            let mut c = [0u8; 4];
+            reader.read_exact(&mut c)?;
+            let code = u32::from_le_bytes(c);
+            ConstValue::Char(char::from_u32(code).unwrap_or('\u{FFFD}'))
+        }
+        5 => {
+            let s = read_string(reader)?;
+            ConstValue::String(s)
+        }
+        6 => {
+            let len = read_len(reader, file_end, 1)?;
+            let mut bytes = vec![0u8; len];
+            reader.read_exact(&mut bytes)?;
+            ConstValue::Bytes(bytes)
+        }
+        7..=9 if depth >= MAX_CONST_DEPTH => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("constants nested deeper than {MAX_CONST_DEPTH} levels"),
+            ));
+        }
+        7 => {
+            let len = read_len(reader, file_end, 1)?;
+            let mut items = Vec::with_capacity(len);
+            for _ in 0..len {
+                items.push(read_const(reader, file_end, depth + 1)?);
+            }
+            ConstValue::List(items)
+        }
+        8 => {
+            let len = read_len(reader, file_end, 2)?;
+            let mut entries = Vec::with_capacity(len);
+            for _ in 0..len {
+                let key = read_const(reader, file_end, depth + 1)?;
+                let value = read_const(reader, file_end, depth + 1)?;
+                entries.push((key, value));
+            }
+            ConstValue::Dict(entries)
+        }
+        9 => {
+            let type_name = read_string(reader)?;
+            let len = read_len(reader, file_end, 1)?;
+            let mut fields = Vec::with_capacity(len);
+            for _ in 0..len {
+                fields.push(read_const(reader, file_end, depth + 1)?);
+            }
+            ConstValue::Struct { type_name, fields }
+        }
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unknown const tag: {tag}"),
+            ));
+        }
+    };
+    Ok(const_val)
+}
+
 fn write_string<W: Write>(
     writer: &mut W,
     s: &str,
@@ -550,21 +1225,57 @@ fn read_string<R: Read>(reader: &mut R) -> io::Result<String> {
     String::from_utf8(buf).map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Invalid utf-8"))
 }
 
-fn write_position<W: Write>(
+/// 无符号 LEB128 变长整数
+fn write_varint<W: Write>(
     writer: &mut W,
-    pos: Position,
+    mut value: u64,
 ) -> io::Result<()> {
-    writer.write_all(&(pos.line as u32).to_le_bytes())?;
-    writer.write_all(&(pos.column as u32).to_le_bytes())?;
-    writer.write_all(&(pos.offset as u32).to_le_bytes())?;
-    Ok(())
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            return writer.write_all(&[byte]);
+        }
+        writer.write_all(&[byte | 0x80])?;
+    }
+}
+
+fn read_varint<R: Read>(reader: &mut R) -> io::Result<u64> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let mut byte = [0u8; 1];
+        reader.read_exact(&mut byte)?;
+        value |= u64::from(byte[0] & 0x7f) << shift;
+        if byte[0] & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(io::Error::new(
+        io::ErrorKind::InvalidData,
+        "varint too long",
+    ))
+}
+
+/// 有符号变长整数（zigzag 编码后按无符号存储）
+fn write_svarint<W: Write>(
+    writer: &mut W,
+    value: i64,
+) -> io::Result<()> {
+    write_varint(writer, ((value << 1) ^ (value >> 63)) as u64)
+}
+
+fn read_svarint<R: Read>(reader: &mut R) -> io::Result<i64> {
+    let value = read_varint(reader)?;
+    Ok((value >> 1) as i64 ^ -((value & 1) as i64))
 }
 
-fn read_position<R: Read>(reader: &mut R) -> io::Result<Position> {
-    let line = read_u32(reader)? as usize;
-    let column = read_u32(reader)? as usize;
-    let offset = read_u32(reader)? as usize;
-    Ok(Position::with_offset(line, column, offset))
+/// `base + delta`，结果为负时数据损坏
+fn offset_by(
+    base: usize,
+    delta: i64,
+) -> io::Result<usize> {
+    usize::try_from(base as i64 + delta)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Invalid line table position"))
 }
 
 fn read_u32<R: Read>(reader: &mut R) -> io::Result<u32> {
@@ -582,11 +1293,13 @@ impl MonoTypeExt for MonoType {
         match self {
             MonoType::Void => 0,
             MonoType::Bool => 1,
-            MonoType::Int(n) => 2 + (*n as u32 / 8 - 1),
-            MonoType::Float(n) => 6 + (*n as u32 / 8 - 1),
+            // 位宽 8/16/32/64 依次占 4 个 ID
+            MonoType::Int(n) => 2 + (*n as u32 / 8).trailing_zeros(),
+            MonoType::Float(n) => 6 + (*n as u32 / 8).trailing_zeros(),
             MonoType::Char => 10,
             MonoType::String => 11,
             MonoType::Bytes => 12,
+            MonoType::Any => 13,
             MonoType::Struct(_) => 20,
             MonoType::Enum(_) => 21,
             MonoType::Option(_) => 21,
@@ -596,19 +1309,20 @@ impl MonoTypeExt for MonoType {
             MonoType::Dict(_, _) => 24,
             MonoType::Set(_) => 25,
             MonoType::Fn { .. } => 30,
-            MonoType::TypeRef(_) => 40,
+            MonoType::TypeRef(_) => 40, // 后随类型名
             MonoType::TypeVar(_) => 50,
             MonoType::Range { .. } => 26,
-            // 联合类型和交集类型暂时使用 TypeRef 的 ID
-            MonoType::Union(_) => 40,
-            MonoType::Intersection(_) => 40,
+            MonoType::Union(_) => 41,
+            MonoType::Intersection(_) => 41,
             MonoType::Arc(_) => 45,
             MonoType::Weak(_) => 46,
+            // Async 是编译期标记，运行时即内部类型
+            MonoType::Async(inner) => inner.to_type_id(),
             MonoType::Ref { .. } => 49,       // 借用引用类型
             MonoType::AssocType { .. } => 47, // 使用新的类型ID
             MonoType::Literal { .. } => 48,   // 字面量类型
             MonoType::MetaType { .. } => 0,   // 元类型无运行时表示
-            MonoType::Generic { .. } => 47,   // 泛型实例化，使用结构体类型ID
+            MonoType::Generic { .. } => 51,   // 泛型实例，后随类型名与实参
             MonoType::Refined { base, .. } => base.to_type_id(),
             MonoType::DepFn { .. } => 30, // 依赖函数类型，与普通函数同ID
             MonoType::LibraryRef { .. } | MonoType::ExternRef { .. } => todo!(),
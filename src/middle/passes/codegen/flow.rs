@@ -3,7 +3,10 @@
 //! 整合寄存器分配、标签生成、跳转表管理和符号表/作用域管理。
 
 use crate::frontend::core::typecheck::MonoType;
-use std::collections::{HashMap, HashSet};
+use crate::middle::core::ir::{FunctionIR, Instruction, Operand};
+use crate::middle::passes::ssa::operands::{self, register};
+use crate::util::diagnostic::{Diagnostic, ErrorCodeDefinition};
+use std::collections::{BTreeSet, HashMap, HashSet};
 
 // ===== 跳转表和流程控制 =====
 
@@ -74,6 +77,408 @@ impl RegisterAllocator {
     }
 }
 
+// ===== 线性扫描寄存器分配 =====
+
+/// 字节码以一个字节编码寄存器
+pub const PHYSICAL_REGISTERS: usize = 256;
+
+/// 寄存器文件末尾留给溢出值的临时寄存器数，即单条指令最多可引用的溢出值个数
+pub const SCRATCH_REGISTERS: usize = 16;
+
+/// 虚拟寄存器（IR 中的 `Local` / `Temp` 编号）被分配到的位置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Location {
+    /// 物理寄存器
+    Register(u8),
+    /// 溢出到局部变量槽，使用前经 `LoadLocal` 装入临时寄存器，定值后经 `StoreLocal` 写回
+    Spill(u16),
+}
+
+/// 虚拟寄存器的活跃区间：按函数内指令下标，两端都包含
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LiveInterval {
+    pub vreg: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// 单条指令的溢出代码：(临时寄存器, 槽)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SpillCode {
+    /// 指令前装入的使用值
+    pub reloads: Vec<(u8, u16)>,
+    /// 指令后写回的定值
+    pub stores: Vec<(u8, u16)>,
+}
+
+/// 一个函数的寄存器分配结果
+#[derive(Debug, Clone, Default)]
+pub struct RegisterAllocation {
+    locations: HashMap<usize, Location>,
+    /// 第一个临时寄存器
+    scratch_base: usize,
+    /// 局部变量槽总数（含溢出槽）
+    slot_count: usize,
+    spill_slots: usize,
+}
+
+impl RegisterAllocation {
+    /// 虚拟寄存器的位置
+    pub fn location(
+        &self,
+        vreg: usize,
+    ) -> Option<Location> {
+        self.locations.get(&vreg).copied()
+    }
+
+    /// 局部变量槽总数：函数自身的槽加上溢出槽
+    pub fn slot_count(&self) -> usize {
+        self.slot_count
+    }
+
+    /// 溢出槽个数
+    pub fn spill_slots(&self) -> usize {
+        self.spill_slots
+    }
+
+    /// 被溢出的虚拟寄存器个数
+    pub fn spilled_count(&self) -> usize {
+        self.locations
+            .values()
+            .filter(|loc| matches!(loc, Location::Spill(_)))
+            .count()
+    }
+
+    /// 用到的物理寄存器个数（不含临时寄存器）
+    pub fn register_count(&self) -> usize {
+        self.locations
+            .values()
+            .filter_map(|loc| match loc {
+                Location::Register(r) => Some(*r as usize + 1),
+                Location::Spill(_) => None,
+            })
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// 为指令引用的溢出值依次指定临时寄存器，返回溢出值 → 临时寄存器与需要插入的溢出代码
+    pub fn spill_code(
+        &self,
+        instr: &Instruction,
+    ) -> Result<(HashMap<usize, u8>, SpillCode), Diagnostic> {
+        let (defs, uses) = operands::operands(instr);
+        let mut scratch = HashMap::new();
+        let mut code = SpillCode::default();
+        for (operand, is_def) in uses
+            .into_iter()
+            .map(|op| (op, false))
+            .chain(defs.into_iter().map(|op| (op, true)))
+        {
+            let Some(vreg) = register(operand) else {
+                continue;
+            };
+            let Some(Location::Spill(slot)) = self.location(vreg) else {
+                continue;
+            };
+            let reg = match scratch.get(&vreg) {
+                Some(&reg) => reg,
+                None if scratch.len() < SCRATCH_REGISTERS => {
+                    let reg = (self.scratch_base + scratch.len()) as u8;
+                    scratch.insert(vreg, reg);
+                    reg
+                }
+                None => {
+                    return Err(ErrorCodeDefinition::register_overflow(
+                        &(scratch.len() + 1).to_string(),
+                        &SCRATCH_REGISTERS.to_string(),
+                    )
+                    .build())
+                }
+            };
+            let list = if is_def {
+                &mut code.stores
+            } else {
+                &mut code.reloads
+            };
+            if !list.contains(&(reg, slot)) {
+                list.push((reg, slot));
+            }
+        }
+        Ok((scratch, code))
+    }
+}
+
+/// 线性扫描寄存器分配器
+///
+/// 把函数的虚拟寄存器映射到有限的物理寄存器：按区间起点依次扫描，区间结束即归还
+/// 寄存器；寄存器用尽时溢出结束最晚的区间。溢出值放在函数已有局部变量槽之后，
+/// 互不重叠的溢出值共用一个槽。
+#[derive(Debug, Clone)]
+pub struct LinearScanAllocator {
+    /// 可分配的物理寄存器个数，其后紧跟临时寄存器
+    registers: usize,
+}
+
+impl Default for LinearScanAllocator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LinearScanAllocator {
+    pub fn new() -> Self {
+        Self::with_registers(PHYSICAL_REGISTERS - SCRATCH_REGISTERS)
+    }
+
+    /// 只使用前 `registers` 个物理寄存器（测试中用来触发溢出）
+    pub fn with_registers(registers: usize) -> Self {
+        let registers = registers.clamp(2, PHYSICAL_REGISTERS - SCRATCH_REGISTERS);
+        LinearScanAllocator { registers }
+    }
+
+    /// 分配函数的寄存器
+    pub fn allocate(
+        &self,
+        func: &FunctionIR,
+    ) -> Result<RegisterAllocation, Diagnostic> {
+        let code: Vec<&Instruction> = func.all_instructions().collect();
+        let mut intervals = live_intervals(&code);
+        intervals.sort_by_key(|iv| (iv.start, iv.vreg));
+
+        // 不带 `dst` 的调用由虚拟机写入寄存器 0，存在时不分配寄存器 0
+        let first = usize::from(code.iter().any(|i| clobbers_register_zero(i)));
+        let mut free: BTreeSet<usize> = (first..self.registers).collect();
+        let mut active: Vec<LiveInterval> = Vec::new();
+        let mut assigned: HashMap<usize, usize> = HashMap::new();
+        let mut spilled: Vec<LiveInterval> = Vec::new();
+
+        for &interval in &intervals {
+            active.retain(|other| {
+                let expired = other.end < interval.start;
+                if expired {
+                    free.insert(assigned[&other.vreg]);
+                }
+                !expired
+            });
+            if let Some(reg) = free.pop_first() {
+                assigned.insert(interval.vreg, reg);
+                active.push(interval);
+                continue;
+            }
+            // 溢出结束最晚的区间，它占用寄存器的时间最长
+            let (idx, furthest) = active
+                .iter()
+                .copied()
+                .enumerate()
+                .max_by_key(|(_, iv)| (iv.end, iv.vreg))
+                .expect("no free register implies an active interval");
+            if furthest.end > interval.end {
+                let reg = assigned.remove(&furthest.vreg).unwrap();
+                assigned.insert(interval.vreg, reg);
+                active[idx] = interval;
+                spilled.push(furthest);
+            } else {
+                spilled.push(interval);
+            }
+        }
+
+        // 溢出槽排在函数已有的局部变量槽之后，区间不重叠的溢出值共用槽
+        let slot_base = slot_count(func, &code);
+        spilled.sort_by_key(|iv| (iv.start, iv.vreg));
+        let mut slot_of: HashMap<usize, usize> = HashMap::new();
+        let mut live_slots: Vec<(usize, usize)> = Vec::new();
+        let mut free_slots: BTreeSet<usize> = BTreeSet::new();
+        let mut spill_slots = 0;
+        for interval in &spilled {
+            live_slots.retain(|&(end, slot)| {
+                let expired = end < interval.start;
+                if expired {
+                    free_slots.insert(slot);
+                }
+                !expired
+            });
+            let slot = free_slots.pop_first().unwrap_or_else(|| {
+                spill_slots += 1;
+                spill_slots - 1
+            });
+            slot_of.insert(interval.vreg, slot);
+            live_slots.push((interval.end, slot));
+        }
+        let slot_count = slot_base + spill_slots;
+        if slot_count > u16::MAX as usize + 1 {
+            return Err(ErrorCodeDefinition::register_overflow(
+                &slot_count.to_string(),
+                &(u16::MAX as usize + 1).to_string(),
+            )
+            .build());
+        }
+
+        let mut locations = HashMap::new();
+        for (vreg, reg) in assigned {
+            locations.insert(vreg, Location::Register(reg as u8));
+        }
+        for (vreg, slot) in slot_of {
+            locations.insert(vreg, Location::Spill((slot_base + slot) as u16));
+        }
+        Ok(RegisterAllocation {
+            locations,
+            scratch_base: self.registers,
+            slot_count,
+            spill_slots,
+        })
+    }
+}
+
+/// 不带 `dst` 的调用把结果写入寄存器 0
+fn clobbers_register_zero(instr: &Instruction) -> bool {
+    matches!(
+        instr,
+        Instruction::Call { dst: None, .. }
+            | Instruction::CallDyn { dst: None, .. }
+            | Instruction::CallVirt { dst: None, .. }
+            | Instruction::InvokeVirtual { dst: None, .. }
+    )
+}
+
+/// 函数自身用到的局部变量槽个数：声明的局部变量、参数与 `Load` / `Store` 访问的槽
+fn slot_count(
+    func: &FunctionIR,
+    code: &[&Instruction],
+) -> usize {
+    let accessed = code.iter().filter_map(|instr| match instr {
+        Instruction::Load {
+            src: Operand::Local(slot) | Operand::Arg(slot),
+            ..
+        }
+        | Instruction::Store {
+            dst: Operand::Local(slot),
+            ..
+        } => Some(slot + 1),
+        _ => None,
+    });
+    accessed
+        .chain([func.locals.len(), func.params.len()])
+        .max()
+        .unwrap_or(0)
+}
+
+/// 指令的后继下标
+fn successors(
+    code: &[&Instruction],
+    pc: usize,
+) -> Vec<usize> {
+    let next = pc + 1;
+    let targets = match code[pc] {
+        Instruction::Jmp(target) => vec![*target],
+        Instruction::JmpIf(_, target) | Instruction::JmpIfNot(_, target) => vec![*target, next],
+        Instruction::Switch { .. } => code[pc].jump_targets(),
+        Instruction::Ret(_) | Instruction::TailCall { .. } => Vec::new(),
+        _ => vec![next],
+    };
+    targets.into_iter().filter(|t| *t < code.len()).collect()
+}
+
+/// 计算虚拟寄存器的活跃区间
+///
+/// 先按基本块求活跃性，再把每个虚拟寄存器出现过的位置与活跃的块边界合并成一个
+/// 连续区间；区间可能比实际活跃范围宽，但不会漏掉活跃的位置。
+pub fn live_intervals(code: &[&Instruction]) -> Vec<LiveInterval> {
+    if code.is_empty() {
+        return Vec::new();
+    }
+
+    // 基本块首指令：入口、跳转目标、跳转与返回之后的指令
+    let mut leaders: BTreeSet<usize> = BTreeSet::from([0]);
+    for (pc, instr) in code.iter().enumerate() {
+        match instr {
+            Instruction::Jmp(_)
+            | Instruction::JmpIf(..)
+            | Instruction::JmpIfNot(..)
+            | Instruction::Switch { .. } => {
+                leaders.extend(instr.jump_targets());
+                leaders.insert(pc + 1);
+            }
+            Instruction::Ret(_) | Instruction::TailCall { .. } => {
+                leaders.insert(pc + 1);
+            }
+            _ => {}
+        }
+    }
+    let starts: Vec<usize> = leaders.into_iter().filter(|&l| l < code.len()).collect();
+    let ranges: Vec<(usize, usize)> = starts
+        .iter()
+        .enumerate()
+        .map(|(b, &s)| (s, starts.get(b + 1).map_or(code.len(), |&n| n) - 1))
+        .collect();
+    let block_of = |pc: usize| starts.partition_point(|&s| s <= pc) - 1;
+    let succs: Vec<Vec<usize>> = ranges
+        .iter()
+        .map(|&(_, end)| successors(code, end).into_iter().map(block_of).collect())
+        .collect();
+
+    // 块内向上暴露的使用与定值
+    let mut gen: Vec<HashSet<usize>> = vec![HashSet::new(); ranges.len()];
+    let mut kill: Vec<HashSet<usize>> = vec![HashSet::new(); ranges.len()];
+    for (b, &(start, end)) in ranges.iter().enumerate() {
+        for instr in &code[start..=end] {
+            for used in operands::uses(instr) {
+                if !kill[b].contains(&used) {
+                    gen[b].insert(used);
+                }
+            }
+            kill[b].extend(operands::defs(instr));
+        }
+    }
+
+    // 活跃性：逆序迭代到不动点
+    let mut live_in: Vec<HashSet<usize>> = gen.clone();
+    let mut live_out: Vec<HashSet<usize>> = vec![HashSet::new(); ranges.len()];
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for b in (0..ranges.len()).rev() {
+            let out: HashSet<usize> = succs[b]
+                .iter()
+                .flat_map(|s| live_in[*s].iter().copied())
+                .collect();
+            if out.len() == live_out[b].len() {
+                continue;
+            }
+            let mut live = gen[b].clone();
+            live.extend(out.iter().filter(|v| !kill[b].contains(v)));
+            live_in[b] = live;
+            live_out[b] = out;
+            changed = true;
+        }
+    }
+
+    let mut bounds: HashMap<usize, (usize, usize)> = HashMap::new();
+    let mut extend = |vreg: usize, pc: usize| {
+        let bound = bounds.entry(vreg).or_insert((pc, pc));
+        bound.0 = bound.0.min(pc);
+        bound.1 = bound.1.max(pc);
+    };
+    for (b, &(start, end)) in ranges.iter().enumerate() {
+        for &vreg in &live_in[b] {
+            extend(vreg, start);
+        }
+        for &vreg in &live_out[b] {
+            extend(vreg, end);
+        }
+    }
+    for (pc, instr) in code.iter().enumerate() {
+        let (defs, uses) = operands::operands(instr);
+        for vreg in defs.into_iter().chain(uses).filter_map(register) {
+            extend(vreg, pc);
+        }
+    }
+
+    bounds
+        .into_iter()
+        .map(|(vreg, (start, end))| LiveInterval { vreg, start, end })
+        .collect()
+}
+
 /// 跳转表
 #[derive(Debug, Clone)]
 pub struct JumpTable {
@@ -10,7 +10,7 @@
 //! - `operand.rs`: 操作数解析
 //! - `buffer.rs`: 常量池 + 字节码缓冲区
 //! - `bytecode.rs`: 字节码格式定义 + 序列化
-//! - `flow.rs`: 寄存器分配 + 标签生成 + 符号表
+//! - `flow.rs`: 线性扫描寄存器分配 + 标签生成 + 符号表
 
 pub mod buffer;
 pub mod bytecode;
@@ -167,6 +167,8 @@ impl CodegenContext {
             type_table,
             const_pool,
             code_section: output.code_section,
+            vtables: self.module.vtables.clone(),
+            struct_layouts: self.module.struct_layouts.clone(),
             debug_section: None,
         })
     }
@@ -233,14 +235,11 @@ impl CodegenContext {
                 Box::new(self.type_from_ast(ok)),
                 Box::new(self.type_from_ast(err)),
             ),
-            Type::Generic { name, args, .. } => MonoType::TypeRef(format!(
-                "{}({})",
-                name,
-                args.iter()
-                    .map(|t| self.type_from_ast(t).type_name())
-                    .collect::<Vec<_>>()
-                    .join(", ")
-            )),
+            // 泛型实例保留结构（类型名 + 实参），不折叠为字符串
+            Type::Generic { name, args, .. } => MonoType::Generic {
+                name: name.clone(),
+                args: args.iter().map(|t| self.type_from_ast(t)).collect(),
+            },
             _ => MonoType::Void,
         }
     }
@@ -264,7 +263,12 @@ pub use bytecode::BytecodeInstruction;
 pub use bytecode::CodeSection;
 pub use bytecode::FileHeader as BytecodeHeader;
 pub use bytecode::FunctionCode;
+pub use bytecode::LazyBytecodeFile;
+pub use bytecode::is_bytecode_path;
 
 /// 常量定义
 pub const YAOXIANG_MAGIC: u32 = 0x59584243;
-pub const BYTECODE_VERSION: u32 = 3;
+pub const BYTECODE_VERSION: u32 = 17;
+
+#[cfg(test)]
+mod tests;
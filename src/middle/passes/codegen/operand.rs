@@ -2,7 +2,10 @@
 //!
 //! 将 IR 操作数转换为寄存器编号。
 
-use crate::middle::core::ir::Operand;
+use std::collections::HashMap;
+
+use crate::middle::core::ir::{Instruction, Operand};
+use crate::middle::passes::codegen::flow::{Location, RegisterAllocation, SpillCode};
 use crate::util::diagnostic::{Diagnostic, ErrorCodeDefinition};
 
 /// 操作数解析结果
@@ -13,13 +16,48 @@ pub type OperandResult = Result<u8, Diagnostic>;
 /// 职责：
 /// - 将 Operand 转换为寄存器编号
 /// - 验证操作数的有效性
+///
+/// 设置了寄存器分配结果时，虚拟寄存器按分配结果映射到物理寄存器；否则寄存器编号
+/// 即虚拟寄存器编号。
 #[derive(Debug, Default)]
-pub struct OperandResolver;
+pub struct OperandResolver {
+    /// 当前函数的寄存器分配
+    allocation: Option<RegisterAllocation>,
+    /// 当前指令中溢出值所在的临时寄存器
+    scratch: HashMap<usize, u8>,
+}
 
 impl OperandResolver {
     /// 创建新的操作数解析器
     pub fn new() -> Self {
-        OperandResolver
+        OperandResolver::default()
+    }
+
+    /// 切换到新函数的寄存器分配
+    pub fn set_allocation(
+        &mut self,
+        allocation: RegisterAllocation,
+    ) {
+        self.allocation = Some(allocation);
+        self.scratch.clear();
+    }
+
+    /// 当前函数的寄存器分配
+    pub fn allocation(&self) -> Option<&RegisterAllocation> {
+        self.allocation.as_ref()
+    }
+
+    /// 为指令引用的溢出值指定临时寄存器，返回需要在指令前后插入的溢出代码
+    pub fn bind_spills(
+        &mut self,
+        instr: &Instruction,
+    ) -> Result<SpillCode, Diagnostic> {
+        let Some(allocation) = &self.allocation else {
+            return Ok(SpillCode::default());
+        };
+        let (scratch, code) = allocation.spill_code(instr)?;
+        self.scratch = scratch;
+        Ok(code)
     }
 
     /// 将操作数转换为寄存器编号
@@ -27,6 +65,30 @@ impl OperandResolver {
         &self,
         operand: &Operand,
     ) -> OperandResult {
+        if let Some(allocation) = &self.allocation {
+            return match operand {
+                Operand::Local(id) | Operand::Temp(id) | Operand::Arg(id) => {
+                    match allocation.location(*id) {
+                        Some(Location::Register(reg)) => Ok(reg),
+                        Some(Location::Spill(_)) => {
+                            self.scratch.get(id).copied().ok_or_else(|| {
+                                ErrorCodeDefinition::codegen_invalid_operand(
+                                    "spilled register not reloaded",
+                                )
+                                .build()
+                            })
+                        }
+                        None => Err(ErrorCodeDefinition::codegen_invalid_operand(
+                            "unallocated register",
+                        )
+                        .build()),
+                    }
+                }
+                _ => Err(
+                    ErrorCodeDefinition::codegen_invalid_operand("invalid operand type").build(),
+                ),
+            };
+        }
         match operand {
             Operand::Local(id) => {
                 if *id > 255 {
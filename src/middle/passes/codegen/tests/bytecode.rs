@@ -1,14 +1,15 @@
 //! 字节码序列化单元测试
 //!
-//! 测试 DebugSection 的序列化和反序列化（round-trip）功能。
+//! 测试 DebugSection、常量池、类型表与虚表段的序列化和反序列化（round-trip）功能，
+//! 以及相同常量在 `.yxc` 中只占一个常量池条目、损坏的常量长度与过深嵌套被拒绝。
 
 use crate::frontend::core::typecheck::MonoType;
 use crate::middle::passes::codegen::bytecode::{
     BytecodeFile, BytecodeInstruction, CodeSection, DebugSection, FileHeader, FunctionCode,
 };
 use crate::backends::common::Opcode;
-use crate::util::span::{DebugSpan, Position, SourceMap, Span};
-use std::collections::HashMap;
+use crate::middle::core::ir::{ConstValue, VTable};
+use crate::util::span::{DebugSpan, LineTable, Position, SourceMap, Span};
 use std::io;
 
 #[test]
@@ -28,7 +29,12 @@ fn test_debug_section_round_trip() {
         return_type: MonoType::Void,
         instructions: vec![BytecodeInstruction::new(Opcode::Nop, vec![])],
         local_count: 0,
-        debug_map: HashMap::from([(0usize, debug_span)]),
+        upvalue_count: 0,
+        line_table: {
+            let mut table = LineTable::new();
+            table.push(0, debug_span);
+            table
+        },
     };
 
     let code_section = CodeSection {
@@ -42,6 +48,8 @@ fn test_debug_section_round_trip() {
         type_table: Vec::new(),
         const_pool: Vec::new(),
         code_section,
+        vtables: Vec::new(),
+        struct_layouts: Vec::new(),
         debug_section: Some(debug_section),
     };
 
@@ -56,9 +64,194 @@ fn test_debug_section_round_trip() {
     assert_eq!(decoded.sources.files().len(), 1);
     assert_eq!(decoded.sources.files()[0].name, "main.yx");
     assert_eq!(decoded.sources.files()[0].content, "main = () => { 1 / 0 }");
-    assert_eq!(decoded.function_debug_maps.len(), 1);
-    assert_eq!(
-        decoded.function_debug_maps[0].get(&0).copied(),
-        Some(debug_span)
-    );
+    assert_eq!(decoded.function_line_tables.len(), 1);
+    assert_eq!(decoded.function_line_tables[0].lookup(0), Some(debug_span));
+}
+
+#[test]
+fn test_composite_const_pool_round_trip() {
+    let list = ConstValue::List(vec![
+        ConstValue::Int(1),
+        ConstValue::String("two".to_string()),
+        ConstValue::List(vec![ConstValue::Bool(true)]),
+        ConstValue::Struct {
+            type_name: "Point".to_string(),
+            fields: vec![ConstValue::Int(3), ConstValue::Float(0.5)],
+        },
+    ]);
+    let dict = ConstValue::Dict(vec![
+        (ConstValue::String("a".to_string()), ConstValue::Float(1.5)),
+        (ConstValue::String("b".to_string()), list.clone()),
+    ]);
+    let file = BytecodeFile {
+        header: FileHeader::default(),
+        type_table: Vec::new(),
+        const_pool: vec![list.clone(), dict.clone()],
+        code_section: CodeSection {
+            functions: Vec::new(),
+        },
+        vtables: Vec::new(),
+        struct_layouts: Vec::new(),
+        debug_section: None,
+    };
+
+    let mut bytes = Vec::new();
+    file.write_to(&mut bytes).expect("write bytecode");
+
+    let decoded = BytecodeFile::read_from(&mut io::Cursor::new(bytes)).expect("read bytecode");
+    assert_eq!(decoded.const_pool, vec![list, dict]);
+}
+
+/// 只含常量池的字节码文件
+fn const_pool_file(const_pool: Vec<ConstValue>) -> Vec<u8> {
+    let file = BytecodeFile {
+        header: FileHeader::default(),
+        type_table: Vec::new(),
+        const_pool,
+        code_section: CodeSection {
+            functions: Vec::new(),
+        },
+        vtables: Vec::new(),
+        struct_layouts: Vec::new(),
+        debug_section: None,
+    };
+    let mut bytes = Vec::new();
+    file.write_to(&mut bytes).expect("write bytecode");
+    bytes
+}
+
+#[test]
+fn test_corrupted_const_length_rejected() {
+    let mut bytes = const_pool_file(vec![ConstValue::List(vec![
+        ConstValue::Int(1),
+        ConstValue::Int(2),
+    ])]);
+    // 列表标签 7 之后是元素个数 2
+    let at = bytes
+        .windows(6)
+        .position(|w| w == [7, 2, 0, 0, 0, 2])
+        .expect("encoded list")
+        + 1;
+    bytes[at..at + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+
+    let err = BytecodeFile::read_from(&mut io::Cursor::new(bytes)).expect_err("corrupted length");
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn test_deeply_nested_consts_rejected() {
+    let mut nested = ConstValue::Int(0);
+    for _ in 0..100 {
+        nested = ConstValue::List(vec![nested]);
+    }
+    let bytes = const_pool_file(vec![nested]);
+
+    let err = BytecodeFile::read_from(&mut io::Cursor::new(bytes)).expect_err("too deep");
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn test_vtable_section_round_trip() {
+    let vtable = VTable {
+        interface: "Show".to_string(),
+        type_name: "Point".to_string(),
+        methods: vec!["Point.show".to_string(), "Point.name".to_string()],
+    };
+    let file = BytecodeFile {
+        header: FileHeader::default(),
+        type_table: Vec::new(),
+        const_pool: Vec::new(),
+        code_section: CodeSection {
+            functions: Vec::new(),
+        },
+        vtables: vec![vtable.clone()],
+        struct_layouts: Vec::new(),
+        debug_section: None,
+    };
+
+    let mut bytes = Vec::new();
+    file.write_to(&mut bytes).expect("write bytecode");
+
+    let decoded = BytecodeFile::read_from(&mut io::Cursor::new(bytes)).expect("read bytecode");
+    assert_eq!(decoded.vtables, vec![vtable]);
+}
+
+#[test]
+fn test_type_table_keeps_generic_instances() {
+    let boxed_int = MonoType::Generic {
+        name: "Box".to_string(),
+        args: vec![MonoType::Int(64)],
+    };
+    let type_table = vec![
+        MonoType::Generic {
+            name: "Box".to_string(),
+            args: vec![boxed_int.clone()],
+        },
+        MonoType::Generic {
+            name: "Pair".to_string(),
+            args: vec![boxed_int, MonoType::TypeRef("Point".to_string())],
+        },
+    ];
+    let file = BytecodeFile {
+        header: FileHeader::default(),
+        type_table: type_table.clone(),
+        const_pool: Vec::new(),
+        code_section: CodeSection {
+            functions: Vec::new(),
+        },
+        vtables: Vec::new(),
+        struct_layouts: Vec::new(),
+        debug_section: None,
+    };
+
+    let mut bytes = Vec::new();
+    file.write_to(&mut bytes).expect("write bytecode");
+
+    let decoded = BytecodeFile::read_from(&mut io::Cursor::new(bytes)).expect("read bytecode");
+    assert_eq!(decoded.type_table, type_table);
+}
+
+#[test]
+fn test_duplicate_strings_share_one_pool_entry() {
+    // 两个函数与 main 各自使用同一字符串字面量
+    let source = "use std.io\n\n\
+                  greet: () -> Void = {\n    io.println(\"shared\")\n}\n\n\
+                  wave: () -> Void = {\n    io.println(\"shared\")\n}\n\n\
+                  main = {\n    greet()\n    wave()\n    io.println(\"shared\")\n}\n";
+    let file = crate::Engine::default()
+        .compile_bytecode("dedupe.yx", source)
+        .expect("compile");
+
+    let mut bytes = Vec::new();
+    file.write_to(&mut bytes).expect("write bytecode");
+
+    let decoded = BytecodeFile::read_from(&mut io::Cursor::new(bytes)).expect("read bytecode");
+    let shared = decoded
+        .const_pool
+        .iter()
+        .filter(|value| matches!(value, ConstValue::String(s) if s == "shared"))
+        .count();
+    assert_eq!(shared, 1);
+}
+
+#[test]
+fn test_remap_consts_follows_opcode_layout() {
+    // LoadConst: dst(1) + const_idx(2)
+    let mut load = BytecodeInstruction::new(Opcode::LoadConst, vec![3, 1, 0]);
+    load.remap_consts(&[7, 9]).unwrap();
+    assert_eq!(load.operands, vec![3, 9, 0]);
+
+    // CallStatic: dst(1) + func_name_idx(4) + base(1) + count(1) + args(2*count)
+    let mut call = BytecodeInstruction::new(Opcode::CallStatic, vec![0, 0, 0, 0, 0, 2, 1, 2, 0]);
+    call.remap_consts(&[70_000]).unwrap();
+    assert_eq!(&call.operands[1..5], &70_000u32.to_le_bytes());
+    assert_eq!(&call.operands[5..], &[2, 1, 2, 0]);
+}
+
+#[test]
+fn test_remap_consts_rejects_index_too_wide_for_operand() {
+    let mut load = BytecodeInstruction::new(Opcode::LoadConst, vec![0, 0, 0]);
+    let err = load.remap_consts(&[u16::MAX as usize + 1]).unwrap_err();
+    assert!(err.contains("65535"), "{err}");
+    assert_eq!(load.operands, vec![0, 0, 0]);
 }
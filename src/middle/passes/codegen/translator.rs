@@ -4,13 +4,16 @@
 
 use crate::backends::common::Opcode;
 use crate::middle::core::ir::{ConstValue, FunctionIR, Instruction, ModuleIR, Operand};
-use crate::middle::core::Reg;
+use crate::middle::core::{NumericTarget, Reg};
 use crate::middle::passes::codegen::emitter::Emitter;
+use crate::middle::passes::codegen::buffer::{ConstantPool, TABLE_OFFSETS_START};
+use crate::middle::passes::codegen::flow::LinearScanAllocator;
 use crate::middle::passes::codegen::operand::OperandResolver;
 use crate::middle::passes::codegen::{BytecodeInstruction};
 use crate::util::diagnostic::{Diagnostic, ErrorCodeDefinition};
-use crate::util::span::{DebugSpan, FileId, Span};
+use crate::util::span::{DebugSpan, FileId, LineTable, Span};
 use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 
 /// FFI 函数元数据 — 机制/库/符号
 #[derive(Debug, Clone)]
@@ -32,16 +35,16 @@ pub struct Translator {
     emitter: Emitter,
     /// 操作数解析器
     operand_resolver: OperandResolver,
-    /// 当前函数
-    current_function: Option<FunctionIR>,
     /// 已注册的 native 函数名集合
-    native_functions: HashSet<String>,
+    native_functions: Arc<HashSet<String>>,
     /// 闭包函数的索引偏移量（用于计算闭包函数在模块中的正确索引）
     closure_function_offset: Option<usize>,
     /// 函数名到索引的映射
-    function_name_to_idx: Option<HashMap<String, usize>>,
+    function_name_to_idx: Option<Arc<HashMap<String, usize>>>,
     /// FFI 函数元数据缓存: func_name → mechanism/lib/symbol
-    ffi_func_meta: HashMap<String, FfiFuncMeta>,
+    ffi_func_meta: Arc<HashMap<String, FfiFuncMeta>>,
+    /// 结构体字段偏移: type_name → 按声明顺序的字段字节偏移
+    struct_offsets: Arc<HashMap<String, Vec<u32>>>,
 
     /// 是否生成运行时调试信息（IP -> Span）
     generate_debug_info: bool,
@@ -53,14 +56,12 @@ pub struct Translator {
 impl Translator {
     /// 创建新的翻译器
     pub fn new() -> Self {
-        let native_functions = HashSet::new();
-
         Translator {
             emitter: Emitter::new(),
             operand_resolver: OperandResolver::new(),
-            current_function: None,
-            native_functions,
-            ffi_func_meta: HashMap::new(),
+            native_functions: Arc::default(),
+            ffi_func_meta: Arc::default(),
+            struct_offsets: Arc::default(),
             closure_function_offset: None,
             function_name_to_idx: None,
             generate_debug_info: false,
@@ -87,7 +88,7 @@ impl Translator {
         &mut self,
         name: &str,
     ) {
-        self.native_functions.insert(name.to_string());
+        Arc::make_mut(&mut self.native_functions).insert(name.to_string());
     }
 
     /// 检查函数是否是 native 函数
@@ -121,7 +122,7 @@ impl Translator {
             {
                 self.register_native(func_name);
                 if let Some(lib) = module.ffi_libs.get(*lib_id) {
-                    self.ffi_func_meta.insert(
+                    Arc::make_mut(&mut self.ffi_func_meta).insert(
                         func_name.clone(),
                         FfiFuncMeta {
                             mechanism: lib.mechanism.clone(),
@@ -133,6 +134,15 @@ impl Translator {
             }
         }
 
+        // 字段访问按结构体布局换算字节偏移
+        self.struct_offsets = Arc::new(
+            module
+                .struct_layouts
+                .iter()
+                .map(|layout| (layout.name.clone(), layout.offsets.clone()))
+                .collect(),
+        );
+
         // 建立函数名到索引的映射
         let mut function_name_to_idx: HashMap<String, usize> = HashMap::new();
         for (idx, func) in module.functions.iter().enumerate() {
@@ -143,78 +153,162 @@ impl Translator {
         // 这样 translate_make_closure 就可以正确计算闭包函数的索引
         let closure_offset = module.functions.len();
         self.closure_function_offset = Some(closure_offset);
-        self.function_name_to_idx = Some(function_name_to_idx);
+        self.function_name_to_idx = Some(Arc::new(function_name_to_idx));
 
-        let mut code_section = super::CodeSection {
-            functions: Vec::new(),
+        // 各函数用独立的缓冲区与常量池翻译，可以并行；结果按函数顺序合并
+        let translate = |translator: &mut Translator, func: &FunctionIR| {
+            let upvalue_count = module.upvalue_counts.get(&func.name).copied().unwrap_or(0);
+            let code = translator.translate_function(func, upvalue_count)?;
+            Ok((code, translator.emitter.take_constant_pool()))
+        };
+        #[cfg(feature = "cli")]
+        let outputs: Vec<Result<_, Diagnostic>> = {
+            use rayon::prelude::*;
+            module
+                .functions
+                .par_iter()
+                .map_init(|| self.fork(), translate)
+                .collect()
+        };
+        #[cfg(not(feature = "cli"))]
+        let outputs: Vec<Result<_, Diagnostic>> = {
+            let mut translator = self.fork();
+            module
+                .functions
+                .iter()
+                .map(|func| translate(&mut translator, func))
+                .collect()
         };
 
-        for func in &module.functions {
-            let func_code = self.translate_function(func)?;
+        // 依次并入模块常量池并改写各函数的常量索引，结果与逐个函数串行翻译相同
+        let mut const_pool = ConstantPool::new();
+        for value in self.emitter.take_constant_pool() {
+            const_pool.add(value);
+        }
+        let mut code_section = super::CodeSection {
+            functions: Vec::with_capacity(outputs.len()),
+        };
+        for output in outputs {
+            let (mut func_code, constants) = output?;
+            let remap: Vec<usize> = constants
+                .into_iter()
+                .map(|value| const_pool.add(value))
+                .collect();
+            for instr in &mut func_code.instructions {
+                instr
+                    .remap_consts(&remap)
+                    .map_err(|message| ErrorCodeDefinition::translation_error(&message).build())?;
+            }
             code_section.functions.push(func_code);
         }
 
-        let const_pool = self.emitter.take_constant_pool();
-
         Ok(TranslatorOutput {
             code_section,
-            const_pool,
+            const_pool: const_pool.build(),
         })
     }
 
+    /// 翻译单个函数用的副本：共享模块级的表，缓冲区、常量池与寄存器分配各自独立
+    fn fork(&self) -> Translator {
+        Translator {
+            emitter: Emitter::new(),
+            operand_resolver: OperandResolver::new(),
+            native_functions: self.native_functions.clone(),
+            closure_function_offset: self.closure_function_offset,
+            function_name_to_idx: self.function_name_to_idx.clone(),
+            ffi_func_meta: self.ffi_func_meta.clone(),
+            struct_offsets: self.struct_offsets.clone(),
+            generate_debug_info: self.generate_debug_info,
+            source_file_id: self.source_file_id,
+        }
+    }
+
     /// 翻译单个函数
+    ///
+    /// 每条 IR 指令以其下标为标签绑定到对应的第一条字节码，跳转指向目标下标的标签，
+    /// 由缓冲区汇编时解析为字节偏移。
     fn translate_function(
         &mut self,
         func: &FunctionIR,
+        upvalue_count: usize,
     ) -> Result<super::FunctionCode, Diagnostic> {
-        self.current_function = Some(func.clone());
-
-        let mut instructions = Vec::new();
-        let mut debug_map = HashMap::new();
-        let mut ir_to_bytecode_map = HashMap::new();
-        let mut pending_jumps: Vec<(usize, usize, Opcode)> = Vec::new(); // (bytecode_idx, target_ir_idx, opcode)
+        let mut line_table = LineTable::new();
         let mut global_ir_index = 0;
 
+        let allocation = LinearScanAllocator::new().allocate(func)?;
+        let local_count = allocation.slot_count().max(func.locals.len());
+        self.operand_resolver.set_allocation(allocation);
+
         for block in func.blocks.iter() {
-            for instr in &block.instructions {
-                ir_to_bytecode_map.insert(global_ir_index, instructions.len());
-                let current_bytecode_idx = instructions.len();
+            let mut narrowed = false;
+            for (idx, instr) in block.instructions.iter().enumerate() {
+                self.emitter.buffer_mut().bind_label(global_ir_index);
+                global_ir_index += 1;
+
+                // 已并入上一条 `I32*` 指令的收窄
+                if std::mem::take(&mut narrowed) {
+                    continue;
+                }
+
+                // 溢出值：指令前装入临时寄存器，定值后写回槽
+                let spill_code = self.operand_resolver.bind_spills(instr)?;
+                for &(reg, slot) in &spill_code.reloads {
+                    self.emitter
+                        .buffer_mut()
+                        .push_instruction(Self::load_local(reg, slot));
+                }
+
+                let bytecode_instr =
+                    match self.translate_int32(instr, block.instructions.get(idx + 1))? {
+                        Some(fused) => {
+                            narrowed = true;
+                            fused
+                        }
+                        None => self.translate_instruction(instr)?,
+                    };
+                let buffer = self.emitter.buffer_mut();
+                let current_bytecode_idx = match (instr, Self::get_jump_target(instr)) {
+                    (
+                        Instruction::Switch {
+                            targets, default, ..
+                        },
+                        _,
+                    ) => buffer.push_table(
+                        bytecode_instr,
+                        std::iter::once(default).chain(targets).copied().collect(),
+                    ),
+                    (_, Some(target)) => buffer.push_jump(bytecode_instr, target),
+                    (_, None) => buffer.push_instruction(bytecode_instr),
+                };
+                for &(reg, slot) in &spill_code.stores {
+                    buffer.push_instruction(Self::store_local(slot, reg));
+                }
 
                 if self.generate_debug_info {
                     if let Some(span) = Self::extract_span(instr) {
                         if !span.is_dummy() {
-                            debug_map.insert(
+                            line_table.push(
                                 current_bytecode_idx,
                                 DebugSpan::new(self.source_file_id, span),
                             );
                         }
                     }
                 }
-
-                // 检查是否是跳转指令，记录待回填信息
-                if let Some((target, opcode)) = Self::get_jump_target(instr) {
-                    pending_jumps.push((current_bytecode_idx, target, opcode));
-                }
-
-                global_ir_index += 1;
-
-                let bytecode_instr = self.translate_instruction(instr)?;
-                instructions.push(bytecode_instr);
             }
         }
 
-        ir_to_bytecode_map.insert(global_ir_index, instructions.len());
-
-        // 回填跳转偏移
-        Self::backfill_jumps_impl(&mut instructions, &ir_to_bytecode_map, &pending_jumps);
+        // 跳到函数末尾
+        self.emitter.buffer_mut().bind_label(global_ir_index);
+        let instructions = self.emitter.buffer_mut().assemble()?;
 
         Ok(super::FunctionCode {
             name: func.name.clone(),
             params: func.params.clone(),
             return_type: func.return_type.clone(),
             instructions,
-            local_count: func.locals.len(),
-            debug_map,
+            local_count,
+            upvalue_count,
+            line_table,
         })
     }
 
@@ -223,61 +317,32 @@ impl Translator {
             Instruction::Call { span, .. } => Some(*span),
             Instruction::CallVirt { span, .. } => Some(*span),
             Instruction::CallDyn { span, .. } => Some(*span),
+            Instruction::InvokeVirtual { span, .. } => Some(*span),
             Instruction::Store { span, .. } => Some(*span),
             Instruction::StoreField { span, .. } => Some(*span),
             Instruction::StoreIndex { span, .. } => Some(*span),
+            Instruction::Add { span, .. } => Some(*span),
+            Instruction::Sub { span, .. } => Some(*span),
+            Instruction::Mul { span, .. } => Some(*span),
             Instruction::Div { span, .. } => Some(*span),
             Instruction::Mod { span, .. } => Some(*span),
             Instruction::LoadField { span, .. } => Some(*span),
+            Instruction::LoadRecordField { span, .. } => Some(*span),
             Instruction::LoadIndex { span, .. } => Some(*span),
             _ => None,
         }
     }
 
     /// 从指令中提取跳转目标（如果是跳转指令）
-    fn get_jump_target(instr: &Instruction) -> Option<(usize, Opcode)> {
+    fn get_jump_target(instr: &Instruction) -> Option<usize> {
         match instr {
-            Instruction::Jmp(target) => Some((*target, Opcode::Jmp)),
-            Instruction::JmpIf(_, target) => Some((*target, Opcode::JmpIf)),
-            Instruction::JmpIfNot(_, target) => Some((*target, Opcode::JmpIfNot)),
+            Instruction::Jmp(target)
+            | Instruction::JmpIf(_, target)
+            | Instruction::JmpIfNot(_, target) => Some(*target),
             _ => None,
         }
     }
 
-    /// 回填跳转偏移（实际实现）
-    fn backfill_jumps_impl(
-        instructions: &mut [BytecodeInstruction],
-        ir_to_bytecode_map: &HashMap<usize, usize>,
-        pending_jumps: &[(usize, usize, Opcode)],
-    ) {
-        for (bytecode_idx, target_ir_idx, opcode) in pending_jumps {
-            if let Some(&target_bytecode_idx) = ir_to_bytecode_map.get(target_ir_idx) {
-                // 计算相对偏移: target - current
-                let offset = (target_bytecode_idx as i32) - (*bytecode_idx as i32);
-                let bytes = offset.to_le_bytes();
-
-                let instr = &mut instructions[*bytecode_idx];
-                match opcode {
-                    Opcode::Jmp => {
-                        // Jmp 操作数: [offset: i32]
-                        instr.operands[0] = bytes[0];
-                        instr.operands[1] = bytes[1];
-                        instr.operands[2] = bytes[2];
-                        instr.operands[3] = bytes[3];
-                    }
-                    Opcode::JmpIf | Opcode::JmpIfNot => {
-                        // JmpIf/JmpIfNot 操作数: [cond_reg: u8, offset: i32]
-                        instr.operands[1] = bytes[0];
-                        instr.operands[2] = bytes[1];
-                        instr.operands[3] = bytes[2];
-                        instr.operands[4] = bytes[3];
-                    }
-                    _ => {}
-                }
-            }
-        }
-    }
-
     /// 翻译单条 IR 指令
     fn translate_instruction(
         &mut self,
@@ -290,9 +355,9 @@ impl Translator {
             Load { dst, src } => self.translate_load(dst, src),
             Store { dst, src, .. } => self.translate_store(dst, src),
 
-            Add { dst, lhs, rhs } => self.translate_binary_op(Opcode::I64Add, dst, lhs, rhs),
-            Sub { dst, lhs, rhs } => self.translate_binary_op(Opcode::I64Sub, dst, lhs, rhs),
-            Mul { dst, lhs, rhs } => self.translate_binary_op(Opcode::I64Mul, dst, lhs, rhs),
+            Add { dst, lhs, rhs, .. } => self.translate_binary_op(Opcode::I64Add, dst, lhs, rhs),
+            Sub { dst, lhs, rhs, .. } => self.translate_binary_op(Opcode::I64Sub, dst, lhs, rhs),
+            Mul { dst, lhs, rhs, .. } => self.translate_binary_op(Opcode::I64Mul, dst, lhs, rhs),
             Div { dst, lhs, rhs, .. } => self.translate_binary_op(Opcode::I64Div, dst, lhs, rhs),
             Mod { dst, lhs, rhs, .. } => self.translate_binary_op(Opcode::I64Rem, dst, lhs, rhs),
 
@@ -315,9 +380,29 @@ impl Translator {
             Gt { dst, lhs, rhs } => self.translate_binary_op(Opcode::I64Gt, dst, lhs, rhs),
             Ge { dst, lhs, rhs } => self.translate_binary_op(Opcode::I64Ge, dst, lhs, rhs),
 
+            FAdd { dst, lhs, rhs } => self.translate_binary_op(Opcode::F64Add, dst, lhs, rhs),
+            FSub { dst, lhs, rhs } => self.translate_binary_op(Opcode::F64Sub, dst, lhs, rhs),
+            FMul { dst, lhs, rhs } => self.translate_binary_op(Opcode::F64Mul, dst, lhs, rhs),
+            FDiv { dst, lhs, rhs } => self.translate_binary_op(Opcode::F64Div, dst, lhs, rhs),
+            FMod { dst, lhs, rhs } => self.translate_binary_op(Opcode::F64Rem, dst, lhs, rhs),
+            FNeg { dst, src } => self.translate_unary_op(Opcode::F64Neg, dst, src),
+            FEq { dst, lhs, rhs } => self.translate_binary_op(Opcode::F64Eq, dst, lhs, rhs),
+            FNe { dst, lhs, rhs } => self.translate_binary_op(Opcode::F64Ne, dst, lhs, rhs),
+            FLt { dst, lhs, rhs } => self.translate_binary_op(Opcode::F64Lt, dst, lhs, rhs),
+            FLe { dst, lhs, rhs } => self.translate_binary_op(Opcode::F64Le, dst, lhs, rhs),
+            FGt { dst, lhs, rhs } => self.translate_binary_op(Opcode::F64Gt, dst, lhs, rhs),
+            FGe { dst, lhs, rhs } => self.translate_binary_op(Opcode::F64Ge, dst, lhs, rhs),
+            IntToFloat { dst, src } => self.translate_unary_op(Opcode::I64ToF64, dst, src),
+
             Jmp(target) => self.translate_jmp(*target),
             JmpIf(cond, target) => self.translate_jmp_if(cond, *target),
             JmpIfNot(cond, target) => self.translate_jmp_if_not(cond, *target),
+            Switch {
+                value,
+                low,
+                targets,
+                ..
+            } => self.translate_switch(value, *low, targets.len()),
             Ret(value) => self.translate_ret(value),
 
             Call {
@@ -333,6 +418,15 @@ impl Translator {
             CallDyn {
                 dst, func, args, ..
             } => self.translate_call_dyn(dst, func, args),
+            InvokeVirtual {
+                dst,
+                obj,
+                slot,
+                method_name,
+                args,
+                ..
+            } => self.translate_invoke_virtual(dst, obj, *slot, method_name, args),
+            MakeDyn { dst, src, vtable } => self.translate_make_dyn(dst, src, *vtable),
             TailCall { func, args } => self.translate_tail_call(func, args),
 
             Alloc { dst, .. } => self.translate_alloc(dst),
@@ -340,11 +434,22 @@ impl Translator {
             AllocArray { dst, .. } => self.translate_alloc_array(dst),
 
             LoadField {
+                dst,
+                src,
+                field,
+                type_name,
+                ..
+            } => self.translate_load_field(dst, src, *field, type_name.as_deref()),
+            LoadRecordField {
                 dst, src, field, ..
-            } => self.translate_load_field(dst, src, *field),
+            } => self.translate_load_record_field(dst, src, field),
             StoreField {
-                dst, field, src, ..
-            } => self.translate_store_field(dst, *field, src),
+                dst,
+                field,
+                src,
+                type_name,
+                ..
+            } => self.translate_store_field(dst, *field, src, type_name.as_deref()),
             LoadIndex {
                 dst, src, index, ..
             } => self.translate_load_index(dst, src, index),
@@ -352,8 +457,21 @@ impl Translator {
                 dst, index, src, ..
             } => self.translate_store_index(dst, index, src),
 
-            Cast { dst, src, .. } => self.translate_cast(dst, src),
-            TypeTest(_, _) => Ok(BytecodeInstruction::new(Opcode::TypeCheck, vec![0, 0, 0])),
+            Cast {
+                dst,
+                src,
+                target_type,
+            } => self.translate_cast(Opcode::Cast, dst, src, target_type),
+            Narrow {
+                dst,
+                src,
+                target_type,
+            } => self.translate_cast(Opcode::Narrow, dst, src, target_type),
+            TypeTest {
+                dst,
+                src,
+                target_type,
+            } => self.translate_type_test(dst, src, target_type),
 
             Spawn {
                 closures,
@@ -363,6 +481,7 @@ impl Translator {
             Yield => Ok(BytecodeInstruction::new(Opcode::Yield, vec![])),
 
             HeapAlloc { dst, .. } => self.translate_heap_alloc(dst),
+            StackAlloc { dst, src } => self.translate_stack_alloc(dst, src),
             CreateStruct {
                 dst,
                 type_name,
@@ -387,6 +506,15 @@ impl Translator {
             StringGetChar { dst, src, index } => self.translate_string_get_char(dst, src, index),
             StringFromInt { dst, src } => self.translate_string_from_int(dst, src),
             StringFromFloat { dst, src } => self.translate_string_from_float(dst, src),
+            StringBuilderNew { dst, src } => {
+                self.translate_unary_op(Opcode::StringBuilderNew, dst, src)
+            }
+            StringBuilderAppend { dst, lhs, rhs } => {
+                self.translate_binary_op(Opcode::StringBuilderAppend, dst, lhs, rhs)
+            }
+            StringBuilderFinish { dst, src } => {
+                self.translate_unary_op(Opcode::StringBuilderFinish, dst, src)
+            }
 
             LoadUpvalue { dst, upvalue_idx } => self.translate_load_upvalue(dst, *upvalue_idx),
             StoreUpvalue { src, upvalue_idx } => self.translate_store_upvalue(src, *upvalue_idx),
@@ -437,14 +565,14 @@ impl Translator {
                     vec![dst_reg, (const_idx as u16) as u8, (const_idx >> 8) as u8],
                 ))
             }
-            Operand::Local(local_idx) => Ok(BytecodeInstruction::new(
-                Opcode::LoadLocal,
-                vec![dst_reg, *local_idx as u8],
-            )),
-            Operand::Arg(arg_idx) => Ok(BytecodeInstruction::new(
-                Opcode::LoadArg,
-                vec![dst_reg, *arg_idx as u8],
-            )),
+            Operand::Local(local_idx) => Ok(Self::load_local(dst_reg, Self::slot(*local_idx)?)),
+            Operand::Arg(arg_idx) => {
+                let [lo, hi] = Self::slot(*arg_idx)?.to_le_bytes();
+                Ok(BytecodeInstruction::new(
+                    Opcode::LoadArg,
+                    vec![dst_reg, lo, hi],
+                ))
+            }
             _ => {
                 let src_reg = self.operand_resolver.to_reg(src)?;
                 Ok(BytecodeInstruction::new(
@@ -462,15 +590,37 @@ impl Translator {
     ) -> Result<BytecodeInstruction, Diagnostic> {
         if let Operand::Local(local_idx) = dst {
             let src_reg = self.operand_resolver.to_reg(src)?;
-            Ok(BytecodeInstruction::new(
-                Opcode::StoreLocal,
-                vec![*local_idx as u8, src_reg],
-            ))
+            Ok(Self::store_local(Self::slot(*local_idx)?, src_reg))
         } else {
             Err(ErrorCodeDefinition::codegen_invalid_operand("invalid operand").build())
         }
     }
 
+    /// 局部变量槽编号（两字节编码）
+    fn slot(local_idx: usize) -> Result<u16, Diagnostic> {
+        u16::try_from(local_idx).map_err(|_| {
+            ErrorCodeDefinition::register_overflow(&local_idx.to_string(), "65535").build()
+        })
+    }
+
+    /// LoadLocal: dst(1) + local_idx(2)
+    fn load_local(
+        dst_reg: u8,
+        slot: u16,
+    ) -> BytecodeInstruction {
+        let [lo, hi] = slot.to_le_bytes();
+        BytecodeInstruction::new(Opcode::LoadLocal, vec![dst_reg, lo, hi])
+    }
+
+    /// StoreLocal: local_idx(2) + src(1)
+    fn store_local(
+        slot: u16,
+        src_reg: u8,
+    ) -> BytecodeInstruction {
+        let [lo, hi] = slot.to_le_bytes();
+        BytecodeInstruction::new(Opcode::StoreLocal, vec![lo, hi, src_reg])
+    }
+
     fn translate_binary_op(
         &mut self,
         opcode: Opcode,
@@ -495,6 +645,52 @@ impl Translator {
         }
     }
 
+    /// 定宽 32 位算术
+    ///
+    /// `instr` 的结果紧接着被 `next` 原地收窄到 `Int32` 时合成一条 `I32*` 指令，
+    /// 收窄不再单独生成；否则返回 `None`，按 64 位指令翻译。
+    fn translate_int32(
+        &mut self,
+        instr: &Instruction,
+        next: Option<&Instruction>,
+    ) -> Result<Option<BytecodeInstruction>, Diagnostic> {
+        use Instruction::*;
+
+        let Some(Narrow {
+            dst: narrow_dst,
+            src: narrow_src,
+            target_type,
+        }) = next
+        else {
+            return Ok(None);
+        };
+        if NumericTarget::of_type(target_type) != Some(NumericTarget::Int(32)) {
+            return Ok(None);
+        }
+        let (opcode, dst, lhs, rhs) = match instr {
+            Add { dst, lhs, rhs, .. } => (Opcode::I32Add, dst, lhs, Some(rhs)),
+            Sub { dst, lhs, rhs, .. } => (Opcode::I32Sub, dst, lhs, Some(rhs)),
+            Mul { dst, lhs, rhs, .. } => (Opcode::I32Mul, dst, lhs, Some(rhs)),
+            Div { dst, lhs, rhs, .. } => (Opcode::I32Div, dst, lhs, Some(rhs)),
+            Mod { dst, lhs, rhs, .. } => (Opcode::I32Rem, dst, lhs, Some(rhs)),
+            And { dst, lhs, rhs } => (Opcode::I32And, dst, lhs, Some(rhs)),
+            Or { dst, lhs, rhs } => (Opcode::I32Or, dst, lhs, Some(rhs)),
+            Xor { dst, lhs, rhs } => (Opcode::I32Xor, dst, lhs, Some(rhs)),
+            Shl { dst, lhs, rhs } => (Opcode::I32Shl, dst, lhs, Some(rhs)),
+            Sar { dst, lhs, rhs } => (Opcode::I32Sar, dst, lhs, Some(rhs)),
+            Shr { dst, lhs, rhs } => (Opcode::I32Shr, dst, lhs, Some(rhs)),
+            Neg { dst, src } => (Opcode::I32Neg, dst, src, None),
+            _ => return Ok(None),
+        };
+        if narrow_dst != dst || narrow_src != dst {
+            return Ok(None);
+        }
+        match rhs {
+            Some(rhs) => self.translate_binary_op(opcode, dst, lhs, rhs).map(Some),
+            None => self.translate_unary_op(opcode, dst, lhs).map(Some),
+        }
+    }
+
     /// 翻译比较操作，统一使用整数比较指令
     /// 注意：实际类型检查在运行时通过 executor.rs 的 exec_compare 完成
     fn translate_compare(
@@ -520,11 +716,12 @@ impl Translator {
         Ok(BytecodeInstruction::new(opcode, vec![dst_reg, src_reg]))
     }
 
+    /// Jmp: offset(2 或 4)，偏移由缓冲区汇编时回填
     fn translate_jmp(
         &mut self,
         _target: usize,
     ) -> Result<BytecodeInstruction, Diagnostic> {
-        Ok(BytecodeInstruction::new(Opcode::Jmp, vec![0, 0, 0, 0]))
+        Ok(BytecodeInstruction::new(Opcode::Jmp, vec![0, 0]))
     }
 
     fn translate_jmp_if(
@@ -535,7 +732,7 @@ impl Translator {
         let cond_reg = self.operand_resolver.to_reg(cond)?;
         Ok(BytecodeInstruction::new(
             Opcode::JmpIf,
-            vec![cond_reg, 0, 0, 0, 0],
+            vec![cond_reg, 0, 0],
         ))
     }
 
@@ -547,10 +744,28 @@ impl Translator {
         let cond_reg = self.operand_resolver.to_reg(cond)?;
         Ok(BytecodeInstruction::new(
             Opcode::JmpIfNot,
-            vec![cond_reg, 0, 0, 0, 0],
+            vec![cond_reg, 0, 0],
         ))
     }
 
+    /// 跳转表：分派值寄存器、下界、表项数，其后的偏移槽位（默认目标在前）由缓冲区回填
+    fn translate_switch(
+        &mut self,
+        value: &Operand,
+        low: i64,
+        len: usize,
+    ) -> Result<BytecodeInstruction, Diagnostic> {
+        let value_reg = self.operand_resolver.to_reg(value)?;
+        let count = u16::try_from(len).map_err(|_| {
+            ErrorCodeDefinition::codegen_invalid_operand("jump table too large").build()
+        })?;
+        let mut operands = vec![value_reg];
+        operands.extend_from_slice(&low.to_le_bytes());
+        operands.extend_from_slice(&count.to_le_bytes());
+        operands.resize(TABLE_OFFSETS_START + 4 * (len + 1), 0);
+        Ok(BytecodeInstruction::new(Opcode::TableSwitch, operands))
+    }
+
     fn translate_ret(
         &mut self,
         value: &Option<Operand>,
@@ -589,14 +804,7 @@ impl Translator {
             .map(|n| self.is_native(n))
             .unwrap_or(false);
 
-        let func_id = match func {
-            Operand::Const(ConstValue::Int(i)) => *i as u32,
-            Operand::Const(ConstValue::String(name)) => {
-                let const_idx = self.emitter.add_constant(ConstValue::String(name.clone()));
-                const_idx as u32
-            }
-            _ => 0,
-        };
+        let func_id = self.static_callee(func)?;
         let base_arg_reg = if let Some(first_arg) = args.first() {
             self.operand_resolver.to_reg(first_arg)?
         } else {
@@ -604,7 +812,6 @@ impl Translator {
         };
         let mut operands = vec![dst_reg];
         operands.extend_from_slice(&func_id.to_le_bytes());
-        operands.push(base_arg_reg);
         // 对 FFI 函数，在 func_name_idx 后追加 mechanism/lib/symbol 的常量池索引
         if let Some(meta) = func_name.as_ref().and_then(|n| self.ffi_func_meta.get(n)) {
             let mech_idx = self
@@ -616,10 +823,11 @@ impl Translator {
             let sym_idx = self
                 .emitter
                 .add_constant(ConstValue::String(meta.symbol.clone()));
-            operands.extend_from_slice(&mech_idx.to_le_bytes()); // 4 bytes
-            operands.extend_from_slice(&lib_idx.to_le_bytes()); // 4 bytes
-            operands.extend_from_slice(&sym_idx.to_le_bytes()); // 4 bytes
+            operands.extend_from_slice(&(mech_idx as u32).to_le_bytes()); // 4 bytes
+            operands.extend_from_slice(&(lib_idx as u32).to_le_bytes()); // 4 bytes
+            operands.extend_from_slice(&(sym_idx as u32).to_le_bytes()); // 4 bytes
         }
+        operands.push(base_arg_reg);
         operands.push(args.len() as u8);
         for arg in args {
             let arg_reg = self.operand_resolver.to_reg(arg)?;
@@ -794,15 +1002,59 @@ impl Translator {
         Ok(BytecodeInstruction::new(Opcode::CallDyn, operands))
     }
 
+    /// InvokeVirtual: dst(1) + obj(1) + slot(2) + name_idx(2) + args(1*count) + arg_count(1)
+    fn translate_invoke_virtual(
+        &mut self,
+        dst: &Option<Operand>,
+        obj: &Operand,
+        slot: usize,
+        method_name: &str,
+        args: &[Operand],
+    ) -> Result<BytecodeInstruction, Diagnostic> {
+        let dst_reg = if let Some(d) = dst {
+            self.operand_resolver.to_reg(d)?
+        } else {
+            0
+        };
+        let obj_reg = self.operand_resolver.to_reg(obj)?;
+        let name_idx = self
+            .emitter
+            .add_constant(ConstValue::String(method_name.to_owned())) as u16;
+
+        let mut operands = vec![dst_reg, obj_reg];
+        operands.extend_from_slice(&(slot as u16).to_le_bytes());
+        operands.extend_from_slice(&name_idx.to_le_bytes());
+        for arg in args {
+            operands.push(self.operand_resolver.to_reg(arg)?);
+        }
+        operands.push(args.len() as u8);
+        Ok(BytecodeInstruction::new(Opcode::InvokeVirtual, operands))
+    }
+
+    /// MakeDyn: dst(1) + src(1) + vtable(4)
+    fn translate_make_dyn(
+        &mut self,
+        dst: &Operand,
+        src: &Operand,
+        vtable: usize,
+    ) -> Result<BytecodeInstruction, Diagnostic> {
+        let mut operands = vec![
+            self.operand_resolver.to_reg(dst)?,
+            self.operand_resolver.to_reg(src)?,
+        ];
+        operands.extend_from_slice(&(vtable as u32).to_le_bytes());
+        Ok(BytecodeInstruction::new(Opcode::MakeDyn, operands))
+    }
+
+    /// TailCall: func_id(4) + base_arg_reg(1) + arg_count(1) + args(2*count)
+    ///
+    /// 与 CallStatic 相同的布局，只是没有 dst：被调函数的返回值即当前函数的返回值。
     fn translate_tail_call(
         &mut self,
         func: &Operand,
         args: &[Operand],
     ) -> Result<BytecodeInstruction, Diagnostic> {
-        let func_id = match func {
-            Operand::Const(ConstValue::Int(i)) => *i as u32,
-            _ => 0,
-        };
+        let func_id = self.static_callee(func)?;
         let base_arg_reg = if let Some(first_arg) = args.first() {
             self.operand_resolver.to_reg(first_arg)?
         } else {
@@ -812,9 +1064,29 @@ impl Translator {
         operands.extend_from_slice(&func_id.to_le_bytes());
         operands.push(base_arg_reg);
         operands.push(args.len() as u8);
+        for arg in args {
+            let arg_reg = self.operand_resolver.to_reg(arg)?;
+            operands.extend_from_slice(&(arg_reg as u16).to_le_bytes());
+        }
         Ok(BytecodeInstruction::new(Opcode::TailCall, operands))
     }
 
+    /// 静态调用的目标：函数名存入常量池，操作数记录其索引
+    fn static_callee(
+        &mut self,
+        func: &Operand,
+    ) -> Result<u32, Diagnostic> {
+        match func {
+            Operand::Const(ConstValue::String(name)) => {
+                Ok(self.emitter.add_constant(ConstValue::String(name.clone())) as u32)
+            }
+            _ => Err(ErrorCodeDefinition::codegen_invalid_operand(
+                "static call target must be a function name",
+            )
+            .build()),
+        }
+    }
+
     fn translate_alloc(
         &mut self,
         dst: &Operand,
@@ -834,15 +1106,36 @@ impl Translator {
         ))
     }
 
+    /// 字段的字节偏移
+    ///
+    /// 按结构体布局查找；限定名（`geo.Point`）与实例名（`Box(int64)`）回退到基础名，
+    /// 与运行时按 `CreateStruct` 类型名登记的布局一致。找不到布局时按字段下标处理。
+    fn field_offset(
+        &self,
+        type_name: Option<&str>,
+        field: usize,
+    ) -> u16 {
+        let offset = type_name.and_then(|type_name| {
+            let base = type_name.split(['[', '(']).next().unwrap_or(type_name);
+            let short = base.rsplit('.').next().unwrap_or(base);
+            [type_name, base, short]
+                .iter()
+                .find_map(|name| self.struct_offsets.get(*name))
+                .and_then(|offsets| offsets.get(field).copied())
+        });
+        offset.map_or(field as u16, |offset| offset as u16)
+    }
+
     fn translate_load_field(
         &mut self,
         dst: &Operand,
         src: &Operand,
         field: usize,
+        type_name: Option<&str>,
     ) -> Result<BytecodeInstruction, Diagnostic> {
         let dst_reg = self.operand_resolver.to_reg(dst)?;
         let src_reg = self.operand_resolver.to_reg(src)?;
-        let field_offset = field as u16;
+        let field_offset = self.field_offset(type_name, field);
         Ok(BytecodeInstruction::new(
             Opcode::GetField,
             vec![
@@ -854,15 +1147,34 @@ impl Translator {
         ))
     }
 
+    /// 翻译 LoadRecordField 指令
+    /// 格式: dst(1) + src(1) + field_name_idx(4)
+    fn translate_load_record_field(
+        &mut self,
+        dst: &Operand,
+        src: &Operand,
+        field: &str,
+    ) -> Result<BytecodeInstruction, Diagnostic> {
+        let dst_reg = self.operand_resolver.to_reg(dst)?;
+        let src_reg = self.operand_resolver.to_reg(src)?;
+        let name_idx = self
+            .emitter
+            .add_constant(ConstValue::String(field.to_string())) as u32;
+        let mut operands = vec![dst_reg, src_reg];
+        operands.extend_from_slice(&name_idx.to_le_bytes());
+        Ok(BytecodeInstruction::new(Opcode::GetRecordField, operands))
+    }
+
     fn translate_store_field(
         &mut self,
         dst: &Operand,
         field: usize,
         src: &Operand,
+        type_name: Option<&str>,
     ) -> Result<BytecodeInstruction, Diagnostic> {
         let dst_reg = self.operand_resolver.to_reg(dst)?;
         let src_reg = self.operand_resolver.to_reg(src)?;
-        let field_offset = field as u16;
+        let field_offset = self.field_offset(type_name, field);
         Ok(BytecodeInstruction::new(
             Opcode::SetField,
             vec![
@@ -904,17 +1216,40 @@ impl Translator {
         ))
     }
 
+    /// Cast / Narrow: dst(1) + src(1) + target_type_id(2)
     fn translate_cast(
         &mut self,
+        opcode: Opcode,
         dst: &Operand,
         src: &Operand,
+        target_type: &crate::middle::core::ir::Type,
     ) -> Result<BytecodeInstruction, Diagnostic> {
         let dst_reg = self.operand_resolver.to_reg(dst)?;
         let src_reg = self.operand_resolver.to_reg(src)?;
-        Ok(BytecodeInstruction::new(
-            Opcode::Cast,
-            vec![dst_reg, src_reg, 0, 0],
-        ))
+        let type_id = NumericTarget::of_type(target_type).map_or(NumericTarget::NONE, |t| t.id());
+        let mut operands = vec![dst_reg, src_reg];
+        operands.extend_from_slice(&type_id.to_le_bytes());
+        Ok(BytecodeInstruction::new(opcode, operands))
+    }
+
+    /// TypeTest: dst(1) + src(1) + type_name_idx(4)
+    ///
+    /// 目标类型以运行时类型标签名（`Int`、`String`、`List`、结构体名……）存入常量池
+    fn translate_type_test(
+        &mut self,
+        dst: &Operand,
+        src: &Operand,
+        target_type: &crate::middle::core::ir::Type,
+    ) -> Result<BytecodeInstruction, Diagnostic> {
+        let dst_reg = self.operand_resolver.to_reg(dst)?;
+        let src_reg = self.operand_resolver.to_reg(src)?;
+        let name_idx = self
+            .emitter
+            .add_constant(ConstValue::String(runtime_type_name(target_type)))
+            as u32;
+        let mut operands = vec![dst_reg, src_reg];
+        operands.extend_from_slice(&name_idx.to_le_bytes());
+        Ok(BytecodeInstruction::new(Opcode::TypeTest, operands))
     }
 
     fn translate_heap_alloc(
@@ -928,6 +1263,19 @@ impl Translator {
         ))
     }
 
+    fn translate_stack_alloc(
+        &mut self,
+        dst: &Operand,
+        src: &Operand,
+    ) -> Result<BytecodeInstruction, Diagnostic> {
+        let dst_reg = self.operand_resolver.to_reg(dst)?;
+        let src_reg = self.operand_resolver.to_reg(src)?;
+        Ok(BytecodeInstruction::new(
+            Opcode::StackAlloc,
+            vec![dst_reg, src_reg],
+        ))
+    }
+
     /// 翻译 CreateStruct 指令
     /// 格式: dst(1) + type_name_idx(4) + field_count(1) + fields(2*count)
     fn translate_create_struct(
@@ -1184,3 +1532,38 @@ pub struct TranslatorOutput {
     pub code_section: super::CodeSection,
     pub const_pool: Vec<ConstValue>,
 }
+
+/// 源码类型对应的运行时类型标签名（与 `ValueType::name` 及结构体布局名一致）
+///
+/// 容器只比较外层标签：`List(Int)` 与 `List(String)` 都是 `List`。
+fn runtime_type_name(ty: &crate::middle::core::ir::Type) -> String {
+    use crate::frontend::core::typecheck::inference::numeric::resolve_numeric;
+    use crate::frontend::core::typecheck::MonoType;
+
+    match resolve_numeric(&MonoType::from(ty.clone())) {
+        MonoType::Int(64) => "Int".to_string(),
+        MonoType::Int(bits) => format!("Int{}", bits),
+        MonoType::Float(64) => "Float".to_string(),
+        MonoType::Float(bits) => format!("Float{}", bits),
+        MonoType::Bool => "Bool".to_string(),
+        MonoType::Char => "Char".to_string(),
+        MonoType::String => "String".to_string(),
+        MonoType::Bytes => "Bytes".to_string(),
+        MonoType::Void => "Void".to_string(),
+        MonoType::Any => "Any".to_string(),
+        MonoType::List(_) => "List".to_string(),
+        MonoType::Dict(_, _) => "Dict".to_string(),
+        MonoType::Fn { .. } => "Function".to_string(),
+        MonoType::TypeRef(name) => match name.as_str() {
+            "bool" => "Bool",
+            "char" => "Char",
+            "string" | "str" => "String",
+            "bytes" => "Bytes",
+            "void" => "Void",
+            _ => name.as_str(),
+        }
+        .to_string(),
+        MonoType::Generic { name, .. } => name,
+        other => other.type_name(),
+    }
+}
@@ -0,0 +1,219 @@
+//! 编译期常量求值
+//!
+//! 在 IR 生成阶段折叠只由字面量和已知常量组成的表达式：
+//! - 整数、浮点的四则运算、取余与比较
+//! - 字符串拼接与比较、布尔逻辑
+//! - 条件为常量、分支只有一个表达式的 `if`
+//!
+//! 整数按 `Int` 的 64 位范围计算。除零、溢出、类型不一致或引用了非常量时
+//! 放弃折叠，交给运行时按原语义处理（例如调试构建下的溢出报错）。
+
+use std::collections::HashMap;
+
+use crate::frontend::core::parser::ast::{self, BinOp, Expr, Literal, StmtKind, UnOp};
+use crate::middle::core::ir::ConstValue;
+
+/// 常量求值器
+///
+/// 记录已求值的模块级常量，后续表达式可以按名字引用它们。
+#[derive(Debug, Default, Clone)]
+pub struct ConstEvaluator {
+    constants: HashMap<String, ConstValue>,
+}
+
+impl ConstEvaluator {
+    /// 创建空的求值器
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 登记一个已知常量
+    pub fn define(
+        &mut self,
+        name: impl Into<String>,
+        value: ConstValue,
+    ) {
+        self.constants.insert(name.into(), value);
+    }
+
+    /// 查询已登记的常量
+    pub fn get(
+        &self,
+        name: &str,
+    ) -> Option<&ConstValue> {
+        self.constants.get(name)
+    }
+
+    /// 求值表达式，无法在编译期确定时返回 `None`
+    pub fn eval(
+        &self,
+        expr: &Expr,
+    ) -> Option<ConstValue> {
+        self.eval_scoped(expr, &|_| false)
+    }
+
+    /// 求值表达式；`shadowed` 返回 true 的名字被局部变量遮蔽，不按常量处理
+    pub fn eval_scoped(
+        &self,
+        expr: &Expr,
+        shadowed: &dyn Fn(&str) -> bool,
+    ) -> Option<ConstValue> {
+        match expr {
+            Expr::Lit(literal, _) => Some(match literal {
+                Literal::Int(n) => ConstValue::Int(*n),
+                Literal::Float(f) => ConstValue::Float(*f),
+                Literal::Bool(b) => ConstValue::Bool(*b),
+                Literal::String(s) => ConstValue::String(s.to_string()),
+                Literal::Char(c) => ConstValue::Char(*c),
+                Literal::Void => ConstValue::Void,
+            }),
+            Expr::Var(name, _) if !shadowed(name) => self.constants.get(name.as_str()).cloned(),
+            Expr::UnOp { op, expr, .. } => {
+                let value = self.eval_scoped(expr, shadowed)?;
+                eval_unop(*op, value)
+            }
+            Expr::BinOp {
+                op, left, right, ..
+            } => {
+                let lhs = self.eval_scoped(left, shadowed)?;
+                // 逻辑运算短路：左侧已决定结果时不要求右侧是常量
+                match (op, &lhs) {
+                    (BinOp::And, ConstValue::Bool(false)) => return Some(ConstValue::Bool(false)),
+                    (BinOp::Or, ConstValue::Bool(true)) => return Some(ConstValue::Bool(true)),
+                    _ => {}
+                }
+                let rhs = self.eval_scoped(right, shadowed)?;
+                eval_binop(*op, lhs, rhs)
+            }
+            Expr::If {
+                condition,
+                then_branch,
+                elif_branches,
+                else_branch,
+                ..
+            } => {
+                let branches = std::iter::once((condition.as_ref(), then_branch.as_ref())).chain(
+                    elif_branches
+                        .iter()
+                        .map(|(cond, body)| (cond.as_ref(), body.as_ref())),
+                );
+                for (cond, body) in branches {
+                    match self.eval_scoped(cond, shadowed)? {
+                        ConstValue::Bool(true) => return self.eval_block(body, shadowed),
+                        ConstValue::Bool(false) => {}
+                        _ => return None,
+                    }
+                }
+                self.eval_block(else_branch.as_deref()?, shadowed)
+            }
+            _ => None,
+        }
+    }
+
+    /// 只有一个表达式（或 `return 表达式`）的代码块
+    fn eval_block(
+        &self,
+        block: &ast::Block,
+        shadowed: &dyn Fn(&str) -> bool,
+    ) -> Option<ConstValue> {
+        match block.stmts.as_slice() {
+            [stmt] => match &stmt.kind {
+                StmtKind::Expr(expr) | StmtKind::Return(Some(expr)) => {
+                    self.eval_scoped(expr, shadowed)
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+/// 整数结果必须落在 `Int` 的范围内
+fn int_result(value: Option<i64>) -> Option<ConstValue> {
+    value.map(|n| ConstValue::Int(n as i128))
+}
+
+fn eval_unop(
+    op: UnOp,
+    value: ConstValue,
+) -> Option<ConstValue> {
+    match (op, value) {
+        (UnOp::Neg, ConstValue::Int(n)) => int_result(i64::try_from(n).ok()?.checked_neg()),
+        (UnOp::Neg, ConstValue::Float(f)) => Some(ConstValue::Float(-f)),
+        (UnOp::Pos, value @ (ConstValue::Int(_) | ConstValue::Float(_))) => Some(value),
+        (UnOp::Not, ConstValue::Bool(b)) => Some(ConstValue::Bool(!b)),
+        _ => None,
+    }
+}
+
+fn eval_binop(
+    op: BinOp,
+    lhs: ConstValue,
+    rhs: ConstValue,
+) -> Option<ConstValue> {
+    let ordering = match (&lhs, &rhs) {
+        (ConstValue::Int(a), ConstValue::Int(b)) => {
+            let (a, b) = (i64::try_from(*a).ok()?, i64::try_from(*b).ok()?);
+            let value = match op {
+                BinOp::Add => a.checked_add(b),
+                BinOp::Sub => a.checked_sub(b),
+                BinOp::Mul => a.checked_mul(b),
+                BinOp::Div => a.checked_div(b),
+                BinOp::Mod => a.checked_rem(b),
+                _ => return compare(op, a.cmp(&b)),
+            };
+            return int_result(value);
+        }
+        (ConstValue::Float(a), ConstValue::Float(b)) => {
+            // 浮点除零交给运行时报错
+            if matches!(op, BinOp::Div | BinOp::Mod) && *b == 0.0 {
+                return None;
+            }
+            let value = match op {
+                BinOp::Add => a + b,
+                BinOp::Sub => a - b,
+                BinOp::Mul => a * b,
+                BinOp::Div => a / b,
+                BinOp::Mod => a % b,
+                _ => return compare(op, a.partial_cmp(b)?),
+            };
+            return Some(ConstValue::Float(value));
+        }
+        (ConstValue::String(a), ConstValue::String(b)) => {
+            if op == BinOp::Add {
+                return Some(ConstValue::String(format!("{}{}", a, b)));
+            }
+            a.cmp(b)
+        }
+        (ConstValue::Char(a), ConstValue::Char(b)) => a.cmp(b),
+        (ConstValue::Bool(a), ConstValue::Bool(b)) => match op {
+            BinOp::And => return Some(ConstValue::Bool(*a && *b)),
+            BinOp::Or => return Some(ConstValue::Bool(*a || *b)),
+            BinOp::Eq | BinOp::Neq => a.cmp(b),
+            _ => return None,
+        },
+        _ => return None,
+    };
+    compare(op, ordering)
+}
+
+fn compare(
+    op: BinOp,
+    ordering: std::cmp::Ordering,
+) -> Option<ConstValue> {
+    use std::cmp::Ordering;
+
+    let result = match op {
+        BinOp::Eq => ordering == Ordering::Equal,
+        BinOp::Neq => ordering != Ordering::Equal,
+        BinOp::Lt => ordering == Ordering::Less,
+        BinOp::Le => ordering != Ordering::Greater,
+        BinOp::Gt => ordering == Ordering::Greater,
+        BinOp::Ge => ordering != Ordering::Less,
+        _ => return None,
+    };
+    Some(ConstValue::Bool(result))
+}
+
+#[cfg(test)]
+mod tests;
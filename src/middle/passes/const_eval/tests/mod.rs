@@ -0,0 +1,69 @@
+//! 常量求值器测试
+
+use crate::frontend::core::lexer::tokenize;
+use crate::frontend::core::parser::ast::{Expr, StmtKind};
+use crate::frontend::core::parser::parse;
+use crate::middle::core::ir::ConstValue;
+use crate::middle::passes::const_eval::ConstEvaluator;
+
+/// 解析 `x = <expr>` 并取出右侧表达式
+fn parse_expr(source: &str) -> Expr {
+    let tokens = tokenize(&format!("x = {}", source)).expect("tokenize failed");
+    let result = parse(&tokens);
+    assert!(!result.has_errors, "parse failed: {:?}", result.errors);
+    match &result.module.items[0].kind {
+        StmtKind::Var {
+            initializer: Some(init),
+            ..
+        } => init.as_ref().clone(),
+        other => panic!("Expected Var, got {:?}", other),
+    }
+}
+
+fn eval(source: &str) -> Option<ConstValue> {
+    ConstEvaluator::new().eval(&parse_expr(source))
+}
+
+#[test]
+fn test_folds_arithmetic() {
+    assert_eq!(eval("60 * 60 * 24"), Some(ConstValue::Int(86400)));
+    assert_eq!(eval("-7 / 2"), Some(ConstValue::Int(-3)));
+    assert_eq!(eval("-7 % 3"), Some(ConstValue::Int(-1)));
+    assert_eq!(eval("1.5 * 2.0"), Some(ConstValue::Float(3.0)));
+}
+
+#[test]
+fn test_folds_strings_and_comparisons() {
+    assert_eq!(
+        eval("\"hello, \" + \"world\""),
+        Some(ConstValue::String("hello, world".to_string()))
+    );
+    assert_eq!(eval("3 > 2 && \"a\" < \"b\""), Some(ConstValue::Bool(true)));
+    assert_eq!(eval("!(1 == 1)"), Some(ConstValue::Bool(false)));
+}
+
+#[test]
+fn test_folds_simple_conditionals() {
+    assert_eq!(eval("if 2 > 1 { 20 }"), Some(ConstValue::Int(20)));
+    // 条件不成立且没有 else 分支时没有值
+    assert_eq!(eval("if false { 1 }"), None);
+    assert_eq!(eval("if 1 { 1 }"), None);
+}
+
+#[test]
+fn test_leaves_runtime_errors_to_runtime() {
+    assert_eq!(eval("1 / 0"), None);
+    assert_eq!(eval("1.0 / 0.0"), None);
+    assert_eq!(eval("9223372036854775807 + 1"), None);
+    assert_eq!(eval("1 + 2.0"), None);
+}
+
+#[test]
+fn test_named_constants_and_shadowing() {
+    let mut evaluator = ConstEvaluator::new();
+    evaluator.define("DAY", ConstValue::Int(86400));
+    let expr = parse_expr("DAY * 7");
+    assert_eq!(evaluator.eval(&expr), Some(ConstValue::Int(604800)));
+    assert_eq!(evaluator.eval_scoped(&expr, &|name| name == "DAY"), None);
+    assert_eq!(evaluator.eval(&parse_expr("unknown + 1")), None);
+}
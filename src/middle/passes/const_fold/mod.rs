@@ -0,0 +1,522 @@
+//! IR 常量折叠与传播
+//!
+//! `const_eval` 只能折叠字面量组成的表达式，`x = 2; y = x * 3` 这类经过局部变量的
+//! 常量仍在运行时计算。本遍在 SSA 形式上做块级的条件常量传播：
+//! - 值与局部变量槽各自维护格值（未知 / 常量 / 变化），按逆后序迭代到不动点
+//! - 只沿可执行的边传播，条件为常量的分支只有一条出边可执行
+//! - 折叠整数、浮点与字符串的算术和比较，语义与虚拟机一致；除零、溢出、类型不一致
+//!   时放弃折叠，交给运行时按原语义处理（例如调试构建下的溢出报错）
+//!
+//! 改写：结果为常量的纯指令换成 `Load` 常量，常量条件的分支换成跳转，删去不可达块，
+//! 再删去结果不再被使用的 `Load` / `Move`。函数没有任何改写时保持原样。
+
+use std::collections::{HashMap, HashSet};
+
+use crate::middle::core::ir::{ConstValue, FunctionIR, Instruction, ModuleIR, Operand};
+use crate::middle::passes::ssa::operands::{self, register};
+use crate::middle::passes::ssa::{SsaFunction, Terminator};
+
+/// 对模块中的每个函数做常量折叠与传播
+pub fn fold_module(module: &mut ModuleIR) {
+    for func in &mut module.functions {
+        fold_constants(func);
+    }
+}
+
+/// 对函数做常量折叠与传播，返回是否有改写
+pub fn fold_constants(func: &mut FunctionIR) -> bool {
+    let mut ssa = SsaFunction::construct(func);
+    let lattice = Lattice::solve(&ssa);
+    if !lattice.rewrite(&mut ssa) {
+        return false;
+    }
+    ssa.remove_unreachable_blocks();
+    remove_dead_copies(&mut ssa);
+    *func = ssa.destruct();
+    true
+}
+
+/// 格值
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    /// 尚未求出（所在位置还不可执行）
+    Unknown,
+    Const(ConstValue),
+    /// 运行时才能确定
+    Varying,
+}
+
+impl Value {
+    fn meet(
+        &self,
+        other: &Value,
+    ) -> Value {
+        match (self, other) {
+            (Value::Unknown, v) | (v, Value::Unknown) => v.clone(),
+            (Value::Const(a), Value::Const(b)) if a == b => self.clone(),
+            _ => Value::Varying,
+        }
+    }
+
+    /// 可以在编译期复制的常量；列表、字典常量每次加载都创建新对象，不参与传播
+    fn constant(value: &ConstValue) -> Value {
+        match value {
+            ConstValue::Void
+            | ConstValue::Bool(_)
+            | ConstValue::Int(_)
+            | ConstValue::Float(_)
+            | ConstValue::Char(_)
+            | ConstValue::String(_) => Value::Const(value.clone()),
+            _ => Value::Varying,
+        }
+    }
+}
+
+/// 局部变量槽的格值；不在表中的槽为变化
+type Slots = HashMap<usize, ConstValue>;
+
+/// 不动点上的格值
+struct Lattice {
+    values: Vec<Value>,
+    executable: Vec<bool>,
+    edges: HashSet<(usize, usize)>,
+}
+
+impl Lattice {
+    fn solve(ssa: &SsaFunction) -> Self {
+        let mut lattice = Lattice {
+            values: vec![Value::Unknown; ssa.value_count],
+            executable: vec![false; ssa.blocks.len()],
+            edges: HashSet::new(),
+        };
+        lattice.executable[0] = true;
+        let order = ssa.dominators().reverse_postorder().to_vec();
+        let mut slots_out: Vec<Option<Slots>> = vec![None; ssa.blocks.len()];
+
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for &b in &order {
+                if !lattice.executable[b] {
+                    continue;
+                }
+                let block = &ssa.blocks[b];
+
+                // 入口状态：可执行前驱出口状态的交
+                let mut slots: Option<Slots> = None;
+                for &pred in &block.preds {
+                    let Some(out) = slots_out[pred].as_ref() else {
+                        continue;
+                    };
+                    if !lattice.edges.contains(&(pred, b)) {
+                        continue;
+                    }
+                    slots = Some(match slots {
+                        None => out.clone(),
+                        Some(mut slots) => {
+                            slots.retain(|slot, value| out.get(slot) == Some(value));
+                            slots
+                        }
+                    });
+                }
+                let mut slots = slots.unwrap_or_default();
+
+                for phi in &block.phis {
+                    let value = phi
+                        .args
+                        .iter()
+                        .filter(|(from, _)| lattice.edges.contains(&(*from, b)))
+                        .fold(Value::Unknown, |acc, (_, arg)| {
+                            acc.meet(&lattice.operand(arg))
+                        });
+                    changed |= lattice.update(phi.dst, value);
+                }
+                for instr in &block.instructions {
+                    if let Instruction::Store {
+                        dst: Operand::Local(slot),
+                        src,
+                        ..
+                    } = instr
+                    {
+                        match lattice.operand(src) {
+                            Value::Const(c) => slots.insert(*slot, c),
+                            _ => slots.remove(slot),
+                        };
+                        continue;
+                    }
+                    let value = lattice.evaluate(instr, &slots);
+                    for def in operands::defs(instr) {
+                        changed |= lattice.update(def, value.clone());
+                    }
+                }
+
+                let taken = match &block.terminator {
+                    Terminator::Branch {
+                        cond,
+                        then_block,
+                        else_block,
+                    } => match lattice.operand(cond) {
+                        Value::Const(ConstValue::Bool(true)) => vec![*then_block],
+                        Value::Const(ConstValue::Bool(false)) => vec![*else_block],
+                        _ => vec![*then_block, *else_block],
+                    },
+                    Terminator::Switch {
+                        value,
+                        low,
+                        targets,
+                        default,
+                    } => {
+                        let key = match lattice.operand(value) {
+                            Value::Const(ConstValue::Int(n)) => i64::try_from(n).ok(),
+                            Value::Const(ConstValue::Char(c)) => Some(i64::from(u32::from(c))),
+                            _ => None,
+                        };
+                        match key {
+                            Some(key) => {
+                                let index =
+                                    key.checked_sub(*low).and_then(|i| usize::try_from(i).ok());
+                                vec![*index.and_then(|i| targets.get(i)).unwrap_or(default)]
+                            }
+                            None => block.successors(),
+                        }
+                    }
+                    _ => block.successors(),
+                };
+                for succ in taken {
+                    changed |= lattice.edges.insert((b, succ));
+                    changed |= !std::mem::replace(&mut lattice.executable[succ], true);
+                }
+                if slots_out[b].as_ref() != Some(&slots) {
+                    slots_out[b] = Some(slots);
+                    changed = true;
+                }
+            }
+        }
+        lattice
+    }
+
+    /// 与旧值取交，保证格值只下降
+    fn update(
+        &mut self,
+        value: usize,
+        new: Value,
+    ) -> bool {
+        let merged = self.values[value].meet(&new);
+        if merged == self.values[value] {
+            return false;
+        }
+        self.values[value] = merged;
+        true
+    }
+
+    fn operand(
+        &self,
+        operand: &Operand,
+    ) -> Value {
+        match operand {
+            Operand::Const(value) => Value::constant(value),
+            _ => match register(operand) {
+                Some(value) => self.values[value].clone(),
+                None => Value::Varying,
+            },
+        }
+    }
+
+    /// 指令结果的格值
+    fn evaluate(
+        &self,
+        instr: &Instruction,
+        slots: &Slots,
+    ) -> Value {
+        let binary = |lhs: &Operand, rhs: &Operand| match (self.operand(lhs), self.operand(rhs)) {
+            (Value::Const(l), Value::Const(r)) => {
+                fold_binary(instr, &l, &r).map_or(Value::Varying, Value::Const)
+            }
+            (Value::Unknown, _) | (_, Value::Unknown) => Value::Unknown,
+            _ => Value::Varying,
+        };
+        match instr {
+            Instruction::Load {
+                src: Operand::Local(slot),
+                ..
+            } => slots
+                .get(slot)
+                .map_or(Value::Varying, |c| Value::Const(c.clone())),
+            Instruction::Load { src, .. } | Instruction::Move { src, .. } => self.operand(src),
+            Instruction::Add { lhs, rhs, .. }
+            | Instruction::Sub { lhs, rhs, .. }
+            | Instruction::Mul { lhs, rhs, .. }
+            | Instruction::Div { lhs, rhs, .. }
+            | Instruction::Mod { lhs, rhs, .. }
+            | Instruction::And { lhs, rhs, .. }
+            | Instruction::Or { lhs, rhs, .. }
+            | Instruction::Xor { lhs, rhs, .. }
+            | Instruction::Shl { lhs, rhs, .. }
+            | Instruction::Shr { lhs, rhs, .. }
+            | Instruction::Sar { lhs, rhs, .. }
+            | Instruction::Eq { lhs, rhs, .. }
+            | Instruction::Ne { lhs, rhs, .. }
+            | Instruction::Lt { lhs, rhs, .. }
+            | Instruction::Le { lhs, rhs, .. }
+            | Instruction::Gt { lhs, rhs, .. }
+            | Instruction::Ge { lhs, rhs, .. }
+            | Instruction::FAdd { lhs, rhs, .. }
+            | Instruction::FSub { lhs, rhs, .. }
+            | Instruction::FMul { lhs, rhs, .. }
+            | Instruction::FDiv { lhs, rhs, .. }
+            | Instruction::FMod { lhs, rhs, .. }
+            | Instruction::FEq { lhs, rhs, .. }
+            | Instruction::FNe { lhs, rhs, .. }
+            | Instruction::FLt { lhs, rhs, .. }
+            | Instruction::FLe { lhs, rhs, .. }
+            | Instruction::FGt { lhs, rhs, .. }
+            | Instruction::FGe { lhs, rhs, .. }
+            | Instruction::StringConcat { lhs, rhs, .. } => binary(lhs, rhs),
+            Instruction::FNeg { src, .. } => match self.operand(src) {
+                Value::Const(ConstValue::Float(f)) => Value::Const(ConstValue::Float(-f)),
+                Value::Unknown => Value::Unknown,
+                _ => Value::Varying,
+            },
+            Instruction::IntToFloat { src, .. } => match self.operand(src) {
+                Value::Const(ConstValue::Int(n)) => Value::Const(ConstValue::Float(n as f64)),
+                Value::Unknown => Value::Unknown,
+                _ => Value::Varying,
+            },
+            Instruction::Neg { src, .. } => match self.operand(src) {
+                Value::Const(ConstValue::Int(n)) => i64::try_from(n)
+                    .ok()
+                    .and_then(i64::checked_neg)
+                    .map_or(Value::Varying, |n| Value::Const(ConstValue::Int(n as i128))),
+                Value::Const(ConstValue::Float(f)) => Value::Const(ConstValue::Float(-f)),
+                Value::Unknown => Value::Unknown,
+                _ => Value::Varying,
+            },
+            _ => Value::Varying,
+        }
+    }
+
+    /// 按格值改写，返回是否有改写
+    fn rewrite(
+        &self,
+        ssa: &mut SsaFunction,
+    ) -> bool {
+        let mut changed = false;
+        for (b, block) in ssa.blocks.iter_mut().enumerate() {
+            if !self.executable[b] {
+                continue;
+            }
+            let mut loads = Vec::new();
+            block.phis.retain(|phi| match &self.values[phi.dst] {
+                Value::Const(c) => {
+                    loads.push(Instruction::Load {
+                        dst: Operand::Local(phi.dst),
+                        src: Operand::Const(c.clone()),
+                    });
+                    false
+                }
+                _ => true,
+            });
+            changed |= !loads.is_empty();
+            block.instructions.splice(0..0, loads);
+
+            for instr in &mut block.instructions {
+                if matches!(
+                    instr,
+                    Instruction::Load {
+                        src: Operand::Const(_),
+                        ..
+                    } | Instruction::Store { .. }
+                ) {
+                    continue;
+                }
+                let Some(dst) = operands::operands(instr)
+                    .0
+                    .first()
+                    .map(|dst| (*dst).clone())
+                else {
+                    continue;
+                };
+                // 只有 `evaluate` 认识的纯指令会得到常量
+                let Some(Value::Const(c)) = register(&dst).map(|v| &self.values[v]) else {
+                    continue;
+                };
+                *instr = Instruction::Load {
+                    dst,
+                    src: Operand::Const(c.clone()),
+                };
+                changed = true;
+            }
+
+            if let Terminator::Branch {
+                then_block,
+                else_block,
+                ..
+            } = block.terminator
+            {
+                let then_taken = self.edges.contains(&(b, then_block));
+                let else_taken = self.edges.contains(&(b, else_block));
+                if then_taken != else_taken {
+                    block.terminator =
+                        Terminator::Jump(if then_taken { then_block } else { else_block });
+                    changed = true;
+                }
+            } else if let Terminator::Switch { .. } = block.terminator {
+                let taken: Vec<usize> = block
+                    .successors()
+                    .into_iter()
+                    .filter(|succ| self.edges.contains(&(b, *succ)))
+                    .collect();
+                if let [target] = taken[..] {
+                    block.terminator = Terminator::Jump(target);
+                    changed = true;
+                }
+            }
+        }
+        changed
+    }
+}
+
+/// 删去结果不再被使用的 `Load` / `Move`
+fn remove_dead_copies(ssa: &mut SsaFunction) {
+    loop {
+        let mut used = HashSet::new();
+        for block in &ssa.blocks {
+            for phi in &block.phis {
+                used.extend(phi.args.iter().filter_map(|(_, arg)| register(arg)));
+            }
+            for instr in &block.instructions {
+                used.extend(operands::uses(instr));
+            }
+            match &block.terminator {
+                Terminator::Branch { cond, .. } | Terminator::Switch { value: cond, .. } => {
+                    used.extend(register(cond))
+                }
+                Terminator::Exit(instr) => used.extend(operands::uses(instr)),
+                Terminator::Jump(_) => {}
+            }
+        }
+        let mut removed = false;
+        for block in &mut ssa.blocks {
+            let before = block.instructions.len();
+            block.instructions.retain(|instr| match instr {
+                Instruction::Load { dst, .. } | Instruction::Move { dst, .. } => {
+                    register(dst).is_none_or(|v| used.contains(&v))
+                }
+                _ => true,
+            });
+            removed |= block.instructions.len() != before;
+        }
+        if !removed {
+            return;
+        }
+    }
+}
+
+/// 折叠二元运算；与虚拟机不一致或运行时会报错的情形返回 `None`
+fn fold_binary(
+    instr: &Instruction,
+    lhs: &ConstValue,
+    rhs: &ConstValue,
+) -> Option<ConstValue> {
+    if let (ConstValue::Float(a), ConstValue::Float(b)) = (lhs, rhs) {
+        if let Some(value) = fold_float(instr, *a, *b) {
+            return Some(value);
+        }
+    }
+    match (lhs, rhs) {
+        (ConstValue::Int(a), ConstValue::Int(b)) => {
+            let (a, b) = (i64::try_from(*a).ok()?, i64::try_from(*b).ok()?);
+            let value = match instr {
+                Instruction::Add { .. } => a.checked_add(b),
+                Instruction::Sub { .. } => a.checked_sub(b),
+                Instruction::Mul { .. } => a.checked_mul(b),
+                Instruction::Div { .. } => a.checked_div(b),
+                Instruction::Mod { .. } => a.checked_rem(b),
+                Instruction::And { .. } => Some(a & b),
+                Instruction::Or { .. } => Some(a | b),
+                Instruction::Xor { .. } => Some(a ^ b),
+                Instruction::Shl { .. } => u32::try_from(b).ok().and_then(|s| a.checked_shl(s)),
+                Instruction::Shr { .. } | Instruction::Sar { .. } => {
+                    u32::try_from(b).ok().and_then(|s| a.checked_shr(s))
+                }
+                _ => return compare(instr, a.cmp(&b)),
+            };
+            value.map(|n| ConstValue::Int(n as i128))
+        }
+        (ConstValue::Float(a), ConstValue::Float(b)) => {
+            // 浮点除零交给运行时处理
+            if matches!(instr, Instruction::Div { .. } | Instruction::Mod { .. }) && *b == 0.0 {
+                return None;
+            }
+            let value = match instr {
+                Instruction::Add { .. } => a + b,
+                Instruction::Sub { .. } => a - b,
+                Instruction::Mul { .. } => a * b,
+                Instruction::Div { .. } => a / b,
+                Instruction::Mod { .. } => a % b,
+                _ => return None,
+            };
+            Some(ConstValue::Float(value))
+        }
+        (ConstValue::String(a), ConstValue::String(b)) => match instr {
+            Instruction::Add { .. } | Instruction::StringConcat { .. } => {
+                Some(ConstValue::String(format!("{}{}", a, b)))
+            }
+            _ => compare(instr, a.cmp(b)),
+        },
+        // void 只等于 void
+        (ConstValue::Void, other) | (other, ConstValue::Void) => {
+            let is_void = matches!(other, ConstValue::Void);
+            match instr {
+                Instruction::Eq { .. } => Some(ConstValue::Bool(is_void)),
+                Instruction::Ne { .. } => Some(ConstValue::Bool(!is_void)),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// 折叠浮点指令；除数为零交给运行时处理
+fn fold_float(
+    instr: &Instruction,
+    a: f64,
+    b: f64,
+) -> Option<ConstValue> {
+    let value = match instr {
+        Instruction::FAdd { .. } => ConstValue::Float(a + b),
+        Instruction::FSub { .. } => ConstValue::Float(a - b),
+        Instruction::FMul { .. } => ConstValue::Float(a * b),
+        Instruction::FDiv { .. } if b != 0.0 => ConstValue::Float(a / b),
+        Instruction::FMod { .. } if b != 0.0 => ConstValue::Float(a % b),
+        Instruction::FEq { .. } => ConstValue::Bool(a == b),
+        Instruction::FNe { .. } => ConstValue::Bool(a != b),
+        Instruction::FLt { .. } => ConstValue::Bool(a < b),
+        Instruction::FLe { .. } => ConstValue::Bool(a <= b),
+        Instruction::FGt { .. } => ConstValue::Bool(a > b),
+        Instruction::FGe { .. } => ConstValue::Bool(a >= b),
+        _ => return None,
+    };
+    Some(value)
+}
+
+fn compare(
+    instr: &Instruction,
+    ordering: std::cmp::Ordering,
+) -> Option<ConstValue> {
+    use std::cmp::Ordering;
+
+    let result = match instr {
+        Instruction::Eq { .. } => ordering == Ordering::Equal,
+        Instruction::Ne { .. } => ordering != Ordering::Equal,
+        Instruction::Lt { .. } => ordering == Ordering::Less,
+        Instruction::Le { .. } => ordering != Ordering::Greater,
+        Instruction::Gt { .. } => ordering == Ordering::Greater,
+        Instruction::Ge { .. } => ordering != Ordering::Less,
+        _ => return None,
+    };
+    Some(ConstValue::Bool(result))
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,280 @@
+//! 常量折叠与传播测试
+//!
+//! 覆盖：
+//! - 经局部变量传播的常量折叠，常量条件的分支随之消去
+//! - 循环变量、除零与溢出不折叠
+//! - 浮点指令与整数转浮点
+//! - `-O0` 不运行本遍
+//! - 折叠后的程序在虚拟机上运行结果不变
+
+use crate::backends::Executor;
+use crate::frontend::config::{CompileConfig, OptLevel};
+use crate::frontend::Compiler;
+use crate::middle::core::ir::{ConstValue, FunctionIR, Instruction, Operand};
+use crate::middle::passes::const_fold::fold_constants;
+
+/// 以 `-O0` 编译并取出函数
+fn unoptimized(
+    source: &str,
+    name: &str,
+) -> FunctionIR {
+    let config = CompileConfig::new().with_opt_level(OptLevel::O0);
+    Compiler::with_config(config)
+        .compile("fold.yx", source)
+        .expect("source should compile")
+        .functions
+        .into_iter()
+        .find(|f| f.name == name)
+        .expect("function should exist")
+}
+
+fn count(
+    func: &FunctionIR,
+    pred: impl Fn(&Instruction) -> bool,
+) -> usize {
+    func.all_instructions().filter(|i| pred(i)).count()
+}
+
+fn is_branch(instr: &Instruction) -> bool {
+    matches!(instr, Instruction::JmpIf(..) | Instruction::JmpIfNot(..))
+}
+
+#[test]
+fn test_propagates_through_locals() {
+    let source = "\
+calc: (n: Int) -> Int = (n) => {
+    x = 2
+    mut y = x * 3
+    if y > 4 {
+        y = y + n
+    }
+    return y + 1
+}
+
+main = { calc(1) }
+";
+    let mut func = unoptimized(source, "calc");
+    let before = func.all_instructions().count();
+    assert!(fold_constants(&mut func));
+
+    assert_eq!(count(&func, |i| matches!(i, Instruction::Mul { .. })), 0);
+    assert_eq!(count(&func, |i| matches!(i, Instruction::Gt { .. })), 0);
+    assert_eq!(count(&func, is_branch), 0);
+    // `y + n` 与 `y + 1` 依赖参数，保留
+    assert_eq!(count(&func, |i| matches!(i, Instruction::Add { .. })), 2);
+    assert!(func.all_instructions().count() < before);
+    assert!(func.all_instructions().any(|i| matches!(
+        i,
+        Instruction::Load {
+            src: Operand::Const(ConstValue::Int(6)),
+            ..
+        }
+    )));
+}
+
+#[test]
+fn test_folds_float_instructions() {
+    let source = "\
+area: () -> Float = () => {
+    r = 1.5
+    n = 2
+    a = r * r * n
+    if a > 4 {
+        return a
+    }
+    return -a
+}
+
+main = { area() }
+";
+    let mut func = unoptimized(source, "area");
+    assert_eq!(count(&func, |i| matches!(i, Instruction::FMul { .. })), 2);
+    assert_eq!(
+        count(&func, |i| matches!(i, Instruction::IntToFloat { .. })),
+        1
+    );
+    assert!(fold_constants(&mut func));
+
+    assert_eq!(
+        count(&func, |i| matches!(
+            i,
+            Instruction::FMul { .. } | Instruction::FGt { .. } | Instruction::IntToFloat { .. }
+        )),
+        0
+    );
+    assert_eq!(count(&func, is_branch), 0);
+    assert!(func.all_instructions().any(|i| matches!(
+        i,
+        Instruction::Load {
+            src: Operand::Const(ConstValue::Float(f)),
+            ..
+        } if *f == 4.5
+    )));
+}
+
+#[test]
+fn test_folds_fully_constant_function() {
+    let source = "\
+answer: () -> Int = () => {
+    a = 6
+    b = a * 7
+    if b == 42 {
+        return b
+    }
+    return 0
+}
+
+main = { answer() }
+";
+    let mut func = unoptimized(source, "answer");
+    assert!(fold_constants(&mut func));
+    assert_eq!(count(&func, is_branch), 0);
+    assert!(matches!(
+        func.all_instructions()
+            .find(|i| matches!(i, Instruction::Ret(_))),
+        Some(Instruction::Ret(Some(_)))
+    ));
+    assert!(func.all_instructions().any(|i| matches!(
+        i,
+        Instruction::Load {
+            src: Operand::Const(ConstValue::Int(42)),
+            ..
+        }
+    )));
+    assert_eq!(count(&func, |i| matches!(i, Instruction::Mul { .. })), 0);
+}
+
+#[test]
+fn test_loop_variable_is_not_folded() {
+    let source = "\
+sum_to: (n: Int) -> Int = (n) => {
+    mut i = 0
+    mut total = 0
+    while i < 10 {
+        total = total + i
+        i = i + 1
+    }
+    return total
+}
+
+main = { sum_to(1) }
+";
+    let mut func = unoptimized(source, "sum_to");
+    fold_constants(&mut func);
+    assert_eq!(count(&func, |i| matches!(i, Instruction::Lt { .. })), 1);
+    assert_eq!(count(&func, is_branch), 1);
+    assert_eq!(count(&func, |i| matches!(i, Instruction::Add { .. })), 2);
+}
+
+#[test]
+fn test_trapping_operations_are_kept() {
+    let source = "\
+divide: () -> Int = () => {
+    a = 1
+    b = 0
+    return a / b
+}
+
+overflow: () -> Int = () => {
+    a = 9223372036854775807
+    b = 1
+    return a + b
+}
+
+main = { divide() }
+";
+    let mut func = unoptimized(source, "divide");
+    fold_constants(&mut func);
+    assert_eq!(count(&func, |i| matches!(i, Instruction::Div { .. })), 1);
+
+    let mut func = unoptimized(source, "overflow");
+    fold_constants(&mut func);
+    assert_eq!(count(&func, |i| matches!(i, Instruction::Add { .. })), 1);
+}
+
+#[test]
+fn test_unchanged_function_is_left_alone() {
+    let source = "\
+twice: (n: Int) -> Int = (n) => {
+    return n + n
+}
+
+main = { twice(1) }
+";
+    let mut func = unoptimized(source, "twice");
+    let before: Vec<String> = func
+        .all_instructions()
+        .map(|i| format!("{:?}", i))
+        .collect();
+    assert!(!fold_constants(&mut func));
+    let after: Vec<String> = func
+        .all_instructions()
+        .map(|i| format!("{:?}", i))
+        .collect();
+    assert_eq!(before, after);
+}
+
+#[test]
+fn test_opt_level_controls_pass() {
+    let source = "\
+six: () -> Int = () => {
+    x = 2
+    return x * 3
+}
+
+main = { six() }
+";
+    let func = unoptimized(source, "six");
+    assert_eq!(count(&func, |i| matches!(i, Instruction::Mul { .. })), 1);
+
+    let module = Compiler::with_config(CompileConfig::new().with_tree_shaking(false))
+        .compile("fold.yx", source)
+        .expect("source should compile");
+    let func = module
+        .functions
+        .iter()
+        .find(|f| f.name == "six")
+        .expect("function should exist");
+    assert_eq!(count(func, |i| matches!(i, Instruction::Mul { .. })), 0);
+}
+
+#[test]
+fn test_folded_program_runs_on_the_vm() {
+    let source = "\
+use std.assert
+
+calc: (n: Int) -> Int = (n) => {
+    x = 2
+    mut y = x * 3
+    if y > 4 {
+        y = y + n
+    }
+    return y + 1
+}
+
+label: () -> String = () => {
+    prefix = \"v\"
+    mut name = prefix + \"1\"
+    if name == \"v1\" {
+        name = name + \".0\"
+    }
+    return name
+}
+
+main = {
+    assert_eq(calc(1), 8)
+    assert_eq(calc(-10), -3)
+    assert_eq(label(), \"v1.0\")
+}
+";
+    let module = Compiler::new()
+        .compile("fold.yx", source)
+        .expect("source should compile");
+    let mut ctx = crate::middle::passes::codegen::CodegenContext::new(module);
+    let bytecode = ctx.generate().expect("codegen should succeed");
+    let module = crate::middle::bytecode::BytecodeModule::from(bytecode);
+    let mut interpreter = crate::backends::interpreter::Interpreter::new();
+    interpreter
+        .execute_module(&module)
+        .expect("folded program should run");
+}
@@ -0,0 +1,305 @@
+//! 公共子表达式消除
+//!
+//! 同一函数中操作码与操作数都相同的纯指令（见 [`Instruction::is_pure`]）只需计算一次。
+//! 本遍在 SSA 形式上做值编号：
+//! - 块内：按指令顺序记录 (操作码, 操作数) → 结果值，再次遇到同样的键时复用先前的值
+//! - 跨块：沿支配树先序遍历，表项对被支配的块可见，离开子树时撤销，保证复用的值
+//!   支配所有使用
+//! - 局部变量槽：块内读取复用本块先前写入或读出的值；整个函数只写入一次的槽，读取被
+//!   这次写入支配时直接使用写入的值。槽只由本帧的 `Store` 写入（闭包捕获的是值的副本）
+//! - 交换律运算（`*`、`&`、`|`、`^`、`==`、`!=`）的操作数排序后再比较；`+` 也用于字符串
+//!   拼接，不视为可交换
+//!
+//! 重复的指令被删去，其结果的所有使用改为先前的值。纯指令可能报错，但先前的同一
+//! 计算支配重复处，报错时重复处本就执行不到，所以复用不改变语义。
+
+use std::collections::HashMap;
+use std::mem::Discriminant;
+
+use crate::middle::core::ir::{FunctionIR, Instruction, ModuleIR, Operand};
+use crate::middle::passes::ssa::operands::{self, register, set_register};
+use crate::middle::passes::ssa::{SsaFunction, Terminator};
+
+/// 对模块中的每个函数做公共子表达式消除
+pub fn cse_module(module: &mut ModuleIR) {
+    for func in &mut module.functions {
+        eliminate_common_subexpressions(func);
+    }
+}
+
+/// 对函数做公共子表达式消除，返回是否有改写
+pub fn eliminate_common_subexpressions(func: &mut FunctionIR) -> bool {
+    let mut ssa = SsaFunction::construct(func);
+    let replaced = number_values(&mut ssa);
+    if replaced.iter().all(Option::is_none) {
+        return false;
+    }
+    substitute(&mut ssa, &replaced);
+    *func = ssa.destruct();
+    true
+}
+
+/// 值编号的键
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Key {
+    opcode: Discriminant<Instruction>,
+    operands: Vec<Operand>,
+    /// 目标类型；`Type` 没有实现 `Eq`，以调试格式区分
+    target_type: Option<String>,
+}
+
+/// 沿支配树做值编号，删去重复指令，返回每个值被替换成的值
+fn number_values(ssa: &mut SsaFunction) -> Vec<Option<usize>> {
+    let dom = ssa.dominators();
+    let single_stores = single_stores(ssa);
+    let mut replaced: Vec<Option<usize>> = vec![None; ssa.value_count];
+    let mut table: HashMap<Key, usize> = HashMap::new();
+    // (块, 是否正在离开)；离开时撤销该块加入的表项
+    let mut stack = vec![(0, false)];
+    let mut scopes: Vec<Vec<Key>> = Vec::new();
+    while let Some((b, leaving)) = stack.pop() {
+        if leaving {
+            for key in scopes.pop().unwrap_or_default() {
+                table.remove(&key);
+            }
+            continue;
+        }
+        let mut inserted = Vec::new();
+        // 本块内已知的槽值：槽 → 最近写入或读出的值
+        let mut slots: HashMap<usize, usize> = HashMap::new();
+        ssa.blocks[b].instructions.retain_mut(|instr| {
+            rename_uses(instr, &replaced);
+            match instr {
+                Instruction::Store {
+                    dst: Operand::Local(slot),
+                    src,
+                    ..
+                } => {
+                    match register(src) {
+                        Some(value) => slots.insert(*slot, value),
+                        None => slots.remove(slot),
+                    };
+                    return true;
+                }
+                Instruction::Load {
+                    dst,
+                    src: Operand::Local(slot),
+                } => {
+                    let Some(value) = register(dst) else {
+                        return true;
+                    };
+                    let known = slots.get(slot).copied().or_else(|| {
+                        single_stores
+                            .get(slot)
+                            .filter(|(store_block, _)| {
+                                *store_block != b && dom.dominates(*store_block, b)
+                            })
+                            .map(|&(_, stored)| replaced[stored].unwrap_or(stored))
+                    });
+                    return match known {
+                        Some(known) => {
+                            replaced[value] = Some(known);
+                            false
+                        }
+                        None => {
+                            slots.insert(*slot, value);
+                            true
+                        }
+                    };
+                }
+                _ => {}
+            }
+            let (Some(key), &[value]) = (key(instr), &operands::defs(instr)[..]) else {
+                return true;
+            };
+            match table.get(&key) {
+                Some(&first) => {
+                    replaced[value] = Some(first);
+                    false
+                }
+                None => {
+                    inserted.push(key.clone());
+                    table.insert(key, value);
+                    true
+                }
+            }
+        });
+        scopes.push(inserted);
+        stack.push((b, true));
+        stack.extend(dom.children(b).iter().rev().map(|&child| (child, false)));
+    }
+    replaced
+}
+
+/// 整个函数中只写入一次、且写入的是寄存器的槽：槽 → (写入所在块, 写入的值)
+///
+/// 唯一的写入支配某次读取时，读取看到的必定是这次写入的值（循环中即最近一次执行）。
+fn single_stores(ssa: &SsaFunction) -> HashMap<usize, (usize, usize)> {
+    let mut stores: HashMap<usize, Option<(usize, usize)>> = HashMap::new();
+    for (b, block) in ssa.blocks.iter().enumerate() {
+        for instr in &block.instructions {
+            if let Instruction::Store {
+                dst: Operand::Local(slot),
+                src,
+                ..
+            } = instr
+            {
+                let site = register(src).map(|value| (b, value));
+                stores
+                    .entry(*slot)
+                    .and_modify(|s| *s = None)
+                    .or_insert(site);
+            }
+        }
+    }
+    stores
+        .into_iter()
+        .filter_map(|(slot, site)| Some((slot, site?)))
+        .collect()
+}
+
+/// 纯指令的键；非纯指令与寄存器复制返回 `None`
+fn key(instr: &Instruction) -> Option<Key> {
+    if !instr.is_pure() {
+        return None;
+    }
+    let mut target_type = None;
+    let mut operands = match instr {
+        Instruction::Add { lhs, rhs, .. }
+        | Instruction::Sub { lhs, rhs, .. }
+        | Instruction::Mul { lhs, rhs, .. }
+        | Instruction::Div { lhs, rhs, .. }
+        | Instruction::Mod { lhs, rhs, .. }
+        | Instruction::And { lhs, rhs, .. }
+        | Instruction::Or { lhs, rhs, .. }
+        | Instruction::Xor { lhs, rhs, .. }
+        | Instruction::Shl { lhs, rhs, .. }
+        | Instruction::Shr { lhs, rhs, .. }
+        | Instruction::Sar { lhs, rhs, .. }
+        | Instruction::Eq { lhs, rhs, .. }
+        | Instruction::Ne { lhs, rhs, .. }
+        | Instruction::Lt { lhs, rhs, .. }
+        | Instruction::Le { lhs, rhs, .. }
+        | Instruction::Gt { lhs, rhs, .. }
+        | Instruction::Ge { lhs, rhs, .. }
+        | Instruction::FAdd { lhs, rhs, .. }
+        | Instruction::FSub { lhs, rhs, .. }
+        | Instruction::FMul { lhs, rhs, .. }
+        | Instruction::FDiv { lhs, rhs, .. }
+        | Instruction::FMod { lhs, rhs, .. }
+        | Instruction::FEq { lhs, rhs, .. }
+        | Instruction::FNe { lhs, rhs, .. }
+        | Instruction::FLt { lhs, rhs, .. }
+        | Instruction::FLe { lhs, rhs, .. }
+        | Instruction::FGt { lhs, rhs, .. }
+        | Instruction::FGe { lhs, rhs, .. }
+        | Instruction::StringConcat { lhs, rhs, .. } => vec![lhs, rhs],
+        Instruction::StringGetChar { src, index, .. } => vec![src, index],
+        Instruction::Neg { src, .. }
+        | Instruction::FNeg { src, .. }
+        | Instruction::IntToFloat { src, .. }
+        | Instruction::StringLength { src, .. }
+        | Instruction::StringFromInt { src, .. }
+        | Instruction::StringFromFloat { src, .. } => vec![src],
+        Instruction::Load {
+            src: src @ Operand::Const(_),
+            ..
+        } => vec![src],
+        Instruction::Cast {
+            src,
+            target_type: t,
+            ..
+        }
+        | Instruction::Narrow {
+            src,
+            target_type: t,
+            ..
+        }
+        | Instruction::TypeTest {
+            src,
+            target_type: t,
+            ..
+        } => {
+            target_type = Some(format!("{:?}", t));
+            vec![src]
+        }
+        // 寄存器之间的复制不产生新的计算
+        _ => return None,
+    }
+    .into_iter()
+    .map(normalize)
+    .collect::<Vec<_>>();
+    if matches!(
+        instr,
+        Instruction::Mul { .. }
+            | Instruction::And { .. }
+            | Instruction::Or { .. }
+            | Instruction::Xor { .. }
+            | Instruction::Eq { .. }
+            | Instruction::Ne { .. }
+            | Instruction::FAdd { .. }
+            | Instruction::FMul { .. }
+            | Instruction::FEq { .. }
+            | Instruction::FNe { .. }
+    ) {
+        operands.sort_by_key(|operand| format!("{:?}", operand));
+    }
+    Some(Key {
+        opcode: std::mem::discriminant(instr),
+        operands,
+        target_type,
+    })
+}
+
+/// `Local` 与 `Temp` 是同一组寄存器，键中统一为 `Local`
+fn normalize(operand: &Operand) -> Operand {
+    match register(operand) {
+        Some(r) => Operand::Local(r),
+        None => operand.clone(),
+    }
+}
+
+fn rename_uses(
+    instr: &mut Instruction,
+    replaced: &[Option<usize>],
+) {
+    for operand in operands::operands_mut(instr).1 {
+        rename(operand, replaced);
+    }
+}
+
+fn rename(
+    operand: &mut Operand,
+    replaced: &[Option<usize>],
+) {
+    if let Some(new) = register(operand).and_then(|v| replaced[v]) {
+        set_register(operand, new);
+    }
+}
+
+/// 把所有使用改为替换后的值（φ 参数可能来自回边，遍历时尚未改写）
+fn substitute(
+    ssa: &mut SsaFunction,
+    replaced: &[Option<usize>],
+) {
+    for block in &mut ssa.blocks {
+        for phi in &mut block.phis {
+            for (_, arg) in &mut phi.args {
+                rename(arg, replaced);
+            }
+        }
+        for instr in &mut block.instructions {
+            rename_uses(instr, replaced);
+        }
+        match &mut block.terminator {
+            Terminator::Branch { cond, .. } | Terminator::Switch { value: cond, .. } => {
+                rename(cond, replaced)
+            }
+            Terminator::Exit(instr) => rename_uses(instr, replaced),
+            Terminator::Jump(_) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,195 @@
+//! 公共子表达式消除测试
+//!
+//! 覆盖：
+//! - 块内重复的纯计算只保留一次，交换律运算的操作数顺序不影响匹配
+//! - 支配的块中的计算可被后继块复用，兄弟分支之间不复用
+//! - 调用等非纯指令不消除
+//! - 消除后的程序在虚拟机上运行结果不变
+
+use crate::backends::Executor;
+use crate::frontend::config::{CompileConfig, OptLevel};
+use crate::frontend::Compiler;
+use crate::middle::core::ir::{FunctionIR, Instruction};
+use crate::middle::passes::cse::eliminate_common_subexpressions;
+
+/// 以 `-O0` 编译并取出函数
+fn unoptimized(
+    source: &str,
+    name: &str,
+) -> FunctionIR {
+    let config = CompileConfig::new().with_opt_level(OptLevel::O0);
+    Compiler::with_config(config)
+        .compile("cse.yx", source)
+        .expect("source should compile")
+        .functions
+        .into_iter()
+        .find(|f| f.name == name)
+        .expect("function should exist")
+}
+
+fn count(
+    func: &FunctionIR,
+    pred: impl Fn(&Instruction) -> bool,
+) -> usize {
+    func.all_instructions().filter(|i| pred(i)).count()
+}
+
+#[test]
+fn test_pure_instructions() {
+    use crate::middle::core::ir::{ConstValue, Operand};
+    use crate::util::span::Span;
+
+    let add = Instruction::Add {
+        dst: Operand::Temp(0),
+        lhs: Operand::Arg(0),
+        rhs: Operand::Arg(1),
+        span: Span::dummy(),
+    };
+    assert!(add.is_pure());
+    let load_slot = Instruction::Load {
+        dst: Operand::Temp(0),
+        src: Operand::Local(0),
+    };
+    assert!(!load_slot.is_pure());
+    let load_const = Instruction::Load {
+        dst: Operand::Temp(0),
+        src: Operand::Const(ConstValue::Int(1)),
+    };
+    assert!(load_const.is_pure());
+    let call = Instruction::Call {
+        dst: Some(Operand::Temp(0)),
+        func: Operand::Global(0),
+        args: Vec::new(),
+        span: Span::default(),
+    };
+    assert!(!call.is_pure());
+}
+
+#[test]
+fn test_eliminates_within_block() {
+    let source = "\
+calc: (a: Int, b: Int) -> Int = (a, b) => {
+    x = a * b + 1
+    y = b * a + 1
+    return x + y
+}
+
+main = { calc(2, 3) }
+";
+    let mut func = unoptimized(source, "calc");
+    assert_eq!(count(&func, |i| matches!(i, Instruction::Mul { .. })), 2);
+    assert!(eliminate_common_subexpressions(&mut func));
+    assert_eq!(count(&func, |i| matches!(i, Instruction::Mul { .. })), 1);
+    // `a * b + 1` 也只算一次，剩下与 `x + y` 共两次加法
+    assert_eq!(count(&func, |i| matches!(i, Instruction::Add { .. })), 2);
+}
+
+#[test]
+fn test_reuses_dominating_computation() {
+    let source = "\
+pick: (a: Int, b: Int) -> Int = (a, b) => {
+    d = a - b
+    if a > b {
+        return a - b
+    }
+    if b > a {
+        return (a - b) * 2
+    }
+    return d
+}
+
+main = { pick(2, 3) }
+";
+    let mut func = unoptimized(source, "pick");
+    assert_eq!(count(&func, |i| matches!(i, Instruction::Sub { .. })), 3);
+    assert!(eliminate_common_subexpressions(&mut func));
+    assert_eq!(count(&func, |i| matches!(i, Instruction::Sub { .. })), 1);
+}
+
+#[test]
+fn test_sibling_branches_are_not_shared() {
+    let source = "\
+pick: (a: Int, b: Int) -> Int = (a, b) => {
+    if a > b {
+        return a - b
+    }
+    return (a - b) * 2
+}
+
+main = { pick(2, 3) }
+";
+    let mut func = unoptimized(source, "pick");
+    eliminate_common_subexpressions(&mut func);
+    assert_eq!(count(&func, |i| matches!(i, Instruction::Sub { .. })), 2);
+}
+
+#[test]
+fn test_calls_are_kept() {
+    let source = "\
+use std.io
+
+tick: () -> Int = () => {
+    print(\"tick\")
+    return 1
+}
+
+twice: () -> Int = () => {
+    return tick() + tick()
+}
+
+main = { twice() }
+";
+    let mut func = unoptimized(source, "twice");
+    let calls = count(&func, |i| matches!(i, Instruction::Call { .. }));
+    assert!(!eliminate_common_subexpressions(&mut func));
+    assert_eq!(
+        count(&func, |i| matches!(i, Instruction::Call { .. })),
+        calls
+    );
+}
+
+#[test]
+fn test_cse_program_runs_on_the_vm() {
+    let source = "\
+use std.assert
+
+calc: (a: Int, b: Int) -> Int = (a, b) => {
+    x = a * b + 1
+    y = b * a + 1
+    return x + y
+}
+
+pick: (a: Int, b: Int) -> Int = (a, b) => {
+    d = a - b
+    if a > b {
+        return a - b
+    }
+    if b > a {
+        return (a - b) * 2
+    }
+    return d
+}
+
+greet: (name: String) -> String = (name) => {
+    return \"hi \" + name + \", \" + name
+}
+
+main = {
+    assert_eq(calc(2, 3), 14)
+    assert_eq(pick(5, 3), 2)
+    assert_eq(pick(3, 5), -4)
+    assert_eq(pick(4, 4), 0)
+    assert_eq(greet(\"yx\"), \"hi yx, yx\")
+}
+";
+    let module = Compiler::new()
+        .compile("cse.yx", source)
+        .expect("source should compile");
+    let mut ctx = crate::middle::passes::codegen::CodegenContext::new(module);
+    let bytecode = ctx.generate().expect("codegen should succeed");
+    let module = crate::middle::bytecode::BytecodeModule::from(bytecode);
+    let mut interpreter = crate::backends::interpreter::Interpreter::new();
+    interpreter
+        .execute_module(&module)
+        .expect("program should run after CSE");
+}
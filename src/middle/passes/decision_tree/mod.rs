@@ -0,0 +1,502 @@
+//! match 的决策树编译
+//!
+//! 把 match 各分支的模式矩阵编译为决策树，IR 生成按树发射比较与跳转，
+//! 而不是逐个分支从头比较：
+//! - 列选择：在第一行尚待检查的位置中，取被最多行检查的那个
+//! - 按构造器分派：同一位置上每个不同的构造器只比较一次；构造器集合完整时
+//!   （`true`/`false`、`void`/非 `void`、`Ok`/`Err`）最后一个分支无需比较
+//! - 绑定提取：绑定名记录为从被匹配值出发的访问路径，到达叶子时才取值
+//! - 跳转表：同一位置上稠密的整数、字符检查（见 [`JumpTable`]）由 IR 生成发射为
+//!   一条 `Switch`，不再逐个比较
+//! - 枚举变体：`Ok(p)` / `Err(p)` 按变体编号检查，总是经跳转表分派；负载模式作用于
+//!   变体负载（[`Access::Payload`]）
+//!
+//! 元组与结构体模式不需要运行时检查，直接展开为各元素、字段上的列。
+//! 用户定义的枚举还没有运行时表示，其限定名变体模式视为永不匹配。
+
+use std::cmp::Reverse;
+use std::collections::VecDeque;
+
+use crate::frontend::core::parser::ast::{constructor_args, Literal, Pattern};
+use crate::middle::core::ir::ConstValue;
+
+/// 从被匹配值出发的访问路径
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Access {
+    /// 被匹配值本身
+    Root,
+    /// 元组的第 i 个元素
+    Index(Box<Access>, usize),
+    /// 结构体字段
+    Field(Box<Access>, String),
+    /// 枚举值在确认为第 i 个变体后的负载
+    Payload(Box<Access>, u32),
+}
+
+/// 某个位置上的运行时检查
+#[derive(Debug, Clone, PartialEq)]
+pub enum Test {
+    /// 等于字面量（含 `true` / `false`）
+    Literal(ConstValue),
+    /// 值为 `void`（`void` / `None`）
+    Void,
+    /// 值不为 `void`（`Some(p)`，负载就是值本身）
+    NotVoid,
+    /// 枚举值是第 `id` 个变体，该枚举共 `count` 个变体
+    Variant { id: u32, count: u32 },
+}
+
+/// `Result` 的变体编号，与运行时 `RuntimeValue::Enum` 的 `variant_id` 一致
+pub const RESULT_OK: u32 = 0;
+pub const RESULT_ERR: u32 = 1;
+
+/// 同一位置上可以作为一组分支的检查
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TestKind {
+    Literal,
+    Nullness,
+    Variant,
+}
+
+impl Test {
+    fn kind(&self) -> TestKind {
+        match self {
+            Test::Literal(_) => TestKind::Literal,
+            Test::Void | Test::NotVoid => TestKind::Nullness,
+            Test::Variant { .. } => TestKind::Variant,
+        }
+    }
+
+    /// 检查成立后，负载模式作用的位置
+    fn payload_access(
+        &self,
+        access: &Access,
+    ) -> Access {
+        match self {
+            Test::Variant { id, .. } => Access::Payload(Box::new(access.clone()), *id),
+            _ => access.clone(),
+        }
+    }
+
+    /// `self` 成立时 `other` 必然成立
+    fn implies(
+        &self,
+        other: &Test,
+    ) -> bool {
+        self == other || matches!((self, other), (Test::Literal(_), Test::NotVoid))
+    }
+
+    /// 两个检查不可能同时成立
+    fn disjoint(
+        &self,
+        other: &Test,
+    ) -> bool {
+        match (self, other) {
+            (Test::Literal(a), Test::Literal(b)) => a != b,
+            (Test::Variant { id: a, .. }, Test::Variant { id: b, .. }) => a != b,
+            (Test::Void, Test::Void) | (Test::NotVoid, Test::NotVoid) => false,
+            (Test::Void, _) | (_, Test::Void) => true,
+            _ => false,
+        }
+    }
+}
+
+/// 决策树
+#[derive(Debug, Clone, PartialEq)]
+pub enum Decision {
+    /// 没有分支匹配
+    Fail,
+    /// 匹配第 `arm` 个分支，`bindings` 给出绑定名对应的访问路径
+    Leaf {
+        arm: usize,
+        bindings: Vec<(String, Access)>,
+    },
+    /// 第 `arm` 个分支的模式已匹配，守卫不成立时继续走 `otherwise`
+    Guard {
+        arm: usize,
+        bindings: Vec<(String, Access)>,
+        otherwise: Box<Decision>,
+    },
+    /// 按 `access` 处的值依次检查 `cases`，都不成立时走 `default`
+    ///
+    /// `default` 为 None 表示构造器集合完整，最后一个分支无需检查。
+    Switch {
+        access: Access,
+        cases: Vec<(Test, Decision)>,
+        default: Option<Box<Decision>>,
+    },
+}
+
+impl Decision {
+    /// 把 match 各分支的模式编译为决策树
+    ///
+    /// `struct_fields` 按类型名给出结构体的字段名（声明顺序），用于展开位置构造器模式
+    /// `Point(0, y)`；不是结构体时返回 None。
+    pub fn compile(
+        patterns: &[&Pattern],
+        struct_fields: &dyn Fn(&str) -> Option<Vec<String>>,
+    ) -> Decision {
+        let mut rows = Vec::new();
+        for (arm, pattern) in patterns.iter().enumerate() {
+            let (pattern, guarded) = match pattern {
+                Pattern::Guard { pattern, .. } => (pattern.as_ref(), true),
+                _ => (*pattern, false),
+            };
+            let row = Row {
+                columns: vec![(Access::Root, lower(pattern, struct_fields))],
+                bindings: Vec::new(),
+                arm,
+                guarded,
+            };
+            normalize(row, &mut rows);
+        }
+        compile_rows(rows)
+    }
+}
+
+/// 改用跳转表至少需要的分支数：分支更少时比较链并不更慢
+const MIN_TABLE_CASES: usize = 4;
+/// 跳转表的最大长度
+const MAX_TABLE_LEN: usize = 1024;
+
+/// 按跳转表分派的 [`Decision::Switch`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JumpTable {
+    /// 第一个表项对应的值
+    pub low: i64,
+    /// 每个表项对应的分支下标，空洞为 None（走默认分支）
+    pub entries: Vec<Option<usize>>,
+}
+
+impl JumpTable {
+    /// 稠密的整数或字符检查、以及枚举变体检查改用跳转表
+    ///
+    /// 字面量要求检查全是同一类、分支数不少于 [`MIN_TABLE_CASES`]，且取值范围内至少
+    /// 一半的值有对应分支；字符按码点排布。变体检查没有可比较的字面量，按变体编号
+    /// 排布后总是使用跳转表。
+    pub fn for_cases(cases: &[(Test, Decision)]) -> Option<JumpTable> {
+        let variants = matches!(cases.first()?.0, Test::Variant { .. });
+        if cases.len() < MIN_TABLE_CASES && !variants {
+            return None;
+        }
+        let keys: Vec<i64> = match &cases[0].0 {
+            Test::Variant { .. } => cases
+                .iter()
+                .map(|(test, _)| match test {
+                    Test::Variant { id, .. } => Some(i64::from(*id)),
+                    _ => None,
+                })
+                .collect::<Option<_>>()?,
+            Test::Literal(ConstValue::Int(_)) => cases
+                .iter()
+                .map(|(test, _)| match test {
+                    Test::Literal(ConstValue::Int(n)) => i64::try_from(*n).ok(),
+                    _ => None,
+                })
+                .collect::<Option<_>>()?,
+            Test::Literal(ConstValue::Char(_)) => cases
+                .iter()
+                .map(|(test, _)| match test {
+                    Test::Literal(ConstValue::Char(c)) => Some(i64::from(u32::from(*c))),
+                    _ => None,
+                })
+                .collect::<Option<_>>()?,
+            _ => return None,
+        };
+        let low = *keys.iter().min()?;
+        let high = *keys.iter().max()?;
+        let len = usize::try_from(high.checked_sub(low)?)
+            .ok()?
+            .checked_add(1)?;
+        if len > MAX_TABLE_LEN || (len > cases.len() * 2 && !variants) {
+            return None;
+        }
+        let mut entries = vec![None; len];
+        for (case, key) in keys.iter().enumerate() {
+            entries[(key - low) as usize] = Some(case);
+        }
+        Some(JumpTable { low, entries })
+    }
+}
+
+/// 归一化后的模式
+#[derive(Debug, Clone)]
+enum Pat {
+    /// 通配或绑定
+    Any(Option<String>),
+    /// 需要运行时检查；负载模式作用于 [`Test::payload_access`] 给出的位置
+    Test(Test, Option<Box<Pat>>),
+    Tuple(Vec<Pat>),
+    Struct(Vec<(String, Pat)>),
+    Or(Vec<Pat>),
+    /// 运行时无法匹配
+    Never,
+}
+
+/// 模式矩阵的一行
+#[derive(Debug, Clone)]
+struct Row {
+    /// 尚待检查的位置及其模式
+    columns: Vec<(Access, Pat)>,
+    bindings: Vec<(String, Access)>,
+    arm: usize,
+    guarded: bool,
+}
+
+fn lower(
+    pattern: &Pattern,
+    struct_fields: &dyn Fn(&str) -> Option<Vec<String>>,
+) -> Pat {
+    let lower_all = |patterns: &[Pattern]| {
+        patterns
+            .iter()
+            .map(|p| lower(p, struct_fields))
+            .collect::<Vec<_>>()
+    };
+    match pattern {
+        Pattern::Wildcard => Pat::Any(None),
+        Pattern::Identifier(name) if name == "None" => Pat::Test(Test::Void, None),
+        // 限定名是枚举变体，运行时无法区分
+        Pattern::Identifier(name) if name.contains('.') => Pat::Never,
+        Pattern::Identifier(name) => Pat::Any(Some(name.clone())),
+        Pattern::Literal(lit) => Pat::Test(
+            match lit {
+                Literal::Int(n) => Test::Literal(ConstValue::Int(*n)),
+                Literal::Bool(b) => Test::Literal(ConstValue::Bool(*b)),
+                Literal::Float(f) => Test::Literal(ConstValue::Float(*f)),
+                Literal::String(s) => Test::Literal(ConstValue::String(s.to_string())),
+                Literal::Char(c) => Test::Literal(ConstValue::Char(*c)),
+                Literal::Void => Test::Void,
+            },
+            None,
+        ),
+        Pattern::Tuple(elems) => Pat::Tuple(lower_all(elems)),
+        Pattern::Struct { fields, .. } => Pat::Struct(
+            fields
+                .iter()
+                .map(|(name, _, p)| (name.clone(), lower(p, struct_fields)))
+                .collect(),
+        ),
+        Pattern::Union {
+            variant, pattern, ..
+        } => match variant.as_str() {
+            "Some" => Pat::Test(
+                Test::NotVoid,
+                pattern
+                    .as_deref()
+                    .map(|p| Box::new(lower(p, struct_fields))),
+            ),
+            "None" => Pat::Test(Test::Void, None),
+            "Ok" | "ok" | "Err" | "err" => Pat::Test(
+                Test::Variant {
+                    id: if variant.eq_ignore_ascii_case("ok") {
+                        RESULT_OK
+                    } else {
+                        RESULT_ERR
+                    },
+                    count: 2,
+                },
+                pattern
+                    .as_deref()
+                    .map(|p| Box::new(lower(p, struct_fields))),
+            ),
+            _ => struct_fields(variant)
+                .and_then(|fields| {
+                    let args = constructor_args(pattern.as_deref(), fields.len())?;
+                    Some(Pat::Struct(
+                        fields
+                            .into_iter()
+                            .zip(args)
+                            .map(|(field, p)| (field, lower(p, struct_fields)))
+                            .collect(),
+                    ))
+                })
+                .unwrap_or(Pat::Never),
+        },
+        Pattern::Or(alts) => Pat::Or(lower_all(alts)),
+        Pattern::Guard { pattern, .. } => lower(pattern, struct_fields),
+    }
+}
+
+/// 把行内的绑定、元组与结构体展开，只留下需要运行时检查的列；或模式拆成多行
+fn normalize(
+    row: Row,
+    out: &mut Vec<Row>,
+) {
+    let Row {
+        columns: pending,
+        mut bindings,
+        arm,
+        guarded,
+    } = row;
+    let mut pending: VecDeque<(Access, Pat)> = pending.into();
+    let mut columns = Vec::new();
+    while let Some((access, pat)) = pending.pop_front() {
+        match pat {
+            Pat::Any(None) => {}
+            Pat::Any(Some(name)) => bindings.push((name, access)),
+            Pat::Tuple(elems) => {
+                for (i, elem) in elems.into_iter().enumerate().rev() {
+                    pending.push_front((Access::Index(Box::new(access.clone()), i), elem));
+                }
+            }
+            Pat::Struct(fields) => {
+                for (field, sub) in fields.into_iter().rev() {
+                    pending.push_front((Access::Field(Box::new(access.clone()), field), sub));
+                }
+            }
+            Pat::Test(..) => columns.push((access, pat)),
+            Pat::Never => return,
+            Pat::Or(alts) => {
+                for alt in alts {
+                    let mut rest = columns.clone();
+                    rest.push((access.clone(), alt));
+                    rest.extend(pending.iter().cloned());
+                    let row = Row {
+                        columns: rest,
+                        bindings: bindings.clone(),
+                        arm,
+                        guarded,
+                    };
+                    normalize(row, out);
+                }
+                return;
+            }
+        }
+    }
+    out.push(Row {
+        columns,
+        bindings,
+        arm,
+        guarded,
+    });
+}
+
+/// 行在 `access` 处的检查
+fn test_at<'r>(
+    row: &'r Row,
+    access: &Access,
+) -> Option<&'r Test> {
+    row.columns.iter().find_map(|(a, pat)| match pat {
+        Pat::Test(test, _) if a == access => Some(test),
+        _ => None,
+    })
+}
+
+/// `access` 处的值通过检查 `test` 后，这一行剩下的部分
+fn specialize(
+    row: &Row,
+    access: &Access,
+    test: &Test,
+    out: &mut Vec<Row>,
+) {
+    let Some(pos) = row.columns.iter().position(|(a, _)| a == access) else {
+        out.push(row.clone());
+        return;
+    };
+    let Pat::Test(own, payload) = &row.columns[pos].1 else {
+        unreachable!("normalized rows only keep test columns");
+    };
+    if test.implies(own) {
+        let mut rest = row.clone();
+        rest.columns.remove(pos);
+        let Some(payload) = payload else {
+            out.push(rest);
+            return;
+        };
+        // `Some(p)` 的负载模式作用于同一个值，可能还要按同一检查继续化简
+        rest.columns
+            .insert(pos, (test.payload_access(access), payload.as_ref().clone()));
+        let mut normalized = Vec::new();
+        normalize(rest, &mut normalized);
+        for row in &normalized {
+            specialize(row, access, test, out);
+        }
+    } else if !test.disjoint(own) {
+        out.push(row.clone());
+    }
+}
+
+fn compile_rows(rows: Vec<Row>) -> Decision {
+    let Some(first) = rows.first() else {
+        return Decision::Fail;
+    };
+    if first.columns.is_empty() {
+        let (arm, bindings) = (first.arm, first.bindings.clone());
+        return if first.guarded {
+            Decision::Guard {
+                arm,
+                bindings,
+                otherwise: Box::new(compile_rows(rows[1..].to_vec())),
+            }
+        } else {
+            Decision::Leaf { arm, bindings }
+        };
+    }
+
+    // 第一行必须检查的位置中，被最多行检查的那个
+    let (access, first_test) = first
+        .columns
+        .iter()
+        .enumerate()
+        .filter_map(|(i, (access, pat))| match pat {
+            Pat::Test(test, _) => Some((i, access, test)),
+            _ => None,
+        })
+        .max_by_key(|(i, access, _)| {
+            let uses = rows.iter().filter(|r| test_at(r, access).is_some()).count();
+            // 并列时取靠前的列
+            (uses, Reverse(*i))
+        })
+        .map(|(_, access, test)| (access.clone(), test.clone()))
+        .expect("non-empty normalized row has a test column");
+
+    // 与第一行同类的检查作为分支：`void`/非 `void` 一组，字面量一组，变体一组
+    let kind = first_test.kind();
+    let mut tests: Vec<Test> = Vec::new();
+    for row in &rows {
+        if let Some(test) = test_at(row, &access) {
+            if test.kind() == kind && !tests.contains(test) {
+                tests.push(test.clone());
+            }
+        }
+    }
+    let complete = match (kind, &first_test) {
+        (TestKind::Nullness, _) => tests.len() == 2,
+        (TestKind::Variant, Test::Variant { count, .. }) => tests.len() == *count as usize,
+        _ => [true, false]
+            .iter()
+            .all(|b| tests.contains(&Test::Literal(ConstValue::Bool(*b)))),
+    };
+
+    let cases = tests
+        .iter()
+        .map(|test| {
+            let mut specialized = Vec::new();
+            for row in &rows {
+                specialize(row, &access, test, &mut specialized);
+            }
+            (test.clone(), compile_rows(specialized))
+        })
+        .collect();
+    let default = (!complete).then(|| {
+        // 所有分支都不成立：检查蕴含某个分支的行也不可能成立
+        let rest = rows
+            .iter()
+            .filter(|row| {
+                test_at(row, &access).is_none_or(|own| !tests.iter().any(|t| own.implies(t)))
+            })
+            .cloned()
+            .collect();
+        Box::new(compile_rows(rest))
+    });
+
+    Decision::Switch {
+        access,
+        cases,
+        default,
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,407 @@
+//! 决策树编译测试
+//!
+//! 覆盖：
+//! - 字面量、布尔与 `void`/`Some` 的分派及默认分支
+//! - 或模式、守卫与运行时无法匹配的模式
+//! - 位置构造器模式的列选择与绑定路径
+//! - IR 生成中比较次数少于逐分支比较
+//! - 稠密的整数、字符检查改用跳转表
+//! - `Ok`/`Err` 按变体编号经跳转表分派，负载模式作用于变体负载
+
+use crate::frontend::core::lexer::tokenize;
+use crate::frontend::core::parser::ast::{Expr, Literal, Pattern, StmtKind};
+use crate::frontend::core::parser::parse;
+use crate::frontend::Compiler;
+use crate::middle::core::ir::{ConstValue, Instruction};
+use crate::middle::passes::decision_tree::{Access, Decision, JumpTable, Test};
+use crate::util::span::Span;
+
+/// 解析 `x = match v { ... }` 并取出各分支的模式
+fn parse_patterns(source: &str) -> Vec<Pattern> {
+    let tokens = tokenize(&format!("x = {}", source)).expect("tokenize failed");
+    let result = parse(&tokens);
+    assert!(!result.has_errors, "parse failed: {:?}", result.errors);
+    match &result.module.items[0].kind {
+        StmtKind::Var {
+            initializer: Some(init),
+            ..
+        } => match init.as_ref() {
+            Expr::Match { arms, .. } => arms.iter().map(|arm| arm.pattern.clone()).collect(),
+            other => panic!("Expected Match, got {:?}", other),
+        },
+        other => panic!("Expected Var, got {:?}", other),
+    }
+}
+
+fn point_fields(name: &str) -> Option<Vec<String>> {
+    (name == "Point").then(|| vec!["x".to_string(), "y".to_string()])
+}
+
+fn compile(source: &str) -> Decision {
+    let patterns = parse_patterns(source);
+    let refs: Vec<&Pattern> = patterns.iter().collect();
+    Decision::compile(&refs, &point_fields)
+}
+
+fn leaf(arm: usize) -> Decision {
+    Decision::Leaf {
+        arm,
+        bindings: Vec::new(),
+    }
+}
+
+fn int(n: i128) -> Test {
+    Test::Literal(ConstValue::Int(n))
+}
+
+fn field(name: &str) -> Access {
+    Access::Field(Box::new(Access::Root), name.to_string())
+}
+
+#[test]
+fn test_literal_switch_with_default() {
+    assert_eq!(
+        compile("match v { 1 => 10, 2 => 20, _ => 0 }"),
+        Decision::Switch {
+            access: Access::Root,
+            cases: vec![(int(1), leaf(0)), (int(2), leaf(1))],
+            default: Some(Box::new(leaf(2))),
+        }
+    );
+}
+
+#[test]
+fn test_complete_constructor_sets_have_no_default() {
+    assert_eq!(
+        compile("match b { true => 1, false => 0 }"),
+        Decision::Switch {
+            access: Access::Root,
+            cases: vec![
+                (Test::Literal(ConstValue::Bool(true)), leaf(0)),
+                (Test::Literal(ConstValue::Bool(false)), leaf(1)),
+            ],
+            default: None,
+        }
+    );
+    assert_eq!(
+        compile("match v { void => 0, Some(n) => n }"),
+        Decision::Switch {
+            access: Access::Root,
+            cases: vec![
+                (Test::Void, leaf(0)),
+                (
+                    Test::NotVoid,
+                    Decision::Leaf {
+                        arm: 1,
+                        bindings: vec![("n".to_string(), Access::Root)],
+                    }
+                ),
+            ],
+            default: None,
+        }
+    );
+}
+
+#[test]
+fn test_or_pattern_shares_arm() {
+    assert_eq!(
+        compile("match v { 1 | 2 => 0, _ => 1 }"),
+        Decision::Switch {
+            access: Access::Root,
+            cases: vec![(int(1), leaf(0)), (int(2), leaf(0))],
+            default: Some(Box::new(leaf(1))),
+        }
+    );
+}
+
+#[test]
+fn test_literal_implies_not_void() {
+    // `Some(n)` 分支下仍要区分 1，但 void 分支不会再检查字面量
+    assert_eq!(
+        compile("match v { void => 0, 1 => 1, Some(n) => n }"),
+        Decision::Switch {
+            access: Access::Root,
+            cases: vec![
+                (Test::Void, leaf(0)),
+                (
+                    Test::NotVoid,
+                    Decision::Switch {
+                        access: Access::Root,
+                        cases: vec![(int(1), leaf(1))],
+                        default: Some(Box::new(Decision::Leaf {
+                            arm: 2,
+                            bindings: vec![("n".to_string(), Access::Root)],
+                        })),
+                    }
+                ),
+            ],
+            default: None,
+        }
+    );
+}
+
+#[test]
+fn test_guard_falls_through_to_later_arms() {
+    let guarded = Pattern::Guard {
+        pattern: Box::new(Pattern::Identifier("n".to_string())),
+        condition: Expr::Lit(Literal::Bool(true), Span::default()),
+    };
+    let wildcard = Pattern::Wildcard;
+    assert_eq!(
+        Decision::compile(&[&guarded, &wildcard], &point_fields),
+        Decision::Guard {
+            arm: 0,
+            bindings: vec![("n".to_string(), Access::Root)],
+            otherwise: Box::new(leaf(1)),
+        }
+    );
+}
+
+#[test]
+fn test_enum_variants_never_match() {
+    assert_eq!(compile("match c { Color.red => 0, _ => 1 }"), leaf(1));
+    assert_eq!(compile("match c { Color.red => 0 }"), Decision::Fail);
+}
+
+fn variant(id: u32) -> Test {
+    Test::Variant { id, count: 2 }
+}
+
+#[test]
+fn test_result_variants_switch_on_variant_id() {
+    let payload = |id| Access::Payload(Box::new(Access::Root), id);
+    assert_eq!(
+        compile("match r { Ok(0) => 0, Ok(n) => n, Err(e) => 1 }"),
+        Decision::Switch {
+            access: Access::Root,
+            cases: vec![
+                (
+                    variant(0),
+                    Decision::Switch {
+                        access: payload(0),
+                        cases: vec![(int(0), leaf(0))],
+                        default: Some(Box::new(Decision::Leaf {
+                            arm: 1,
+                            bindings: vec![("n".to_string(), payload(0))],
+                        })),
+                    }
+                ),
+                (
+                    variant(1),
+                    Decision::Leaf {
+                        arm: 2,
+                        bindings: vec![("e".to_string(), payload(1))],
+                    }
+                ),
+            ],
+            default: None,
+        }
+    );
+    // 小写的构造器名同样按变体分派；缺少的变体落入默认分支
+    assert_eq!(
+        compile("match r { err(_) => 1, _ => 0 }"),
+        Decision::Switch {
+            access: Access::Root,
+            cases: vec![(variant(1), leaf(0))],
+            default: Some(Box::new(leaf(1))),
+        }
+    );
+}
+
+#[test]
+fn test_positional_struct_pattern_tests_each_field_once() {
+    let tree = compile("match p { Point(0, 0) => 0, Point(0, y) => y, Point(_, 0) => 2, _ => 3 }");
+    let Decision::Switch {
+        access,
+        cases,
+        default: Some(default),
+    } = tree
+    else {
+        panic!("Expected Switch, got {:?}", tree);
+    };
+    // 第一列 x 被三行检查，先于 y
+    assert_eq!(access, field("x"));
+    assert_eq!(cases.len(), 1);
+    assert_eq!(
+        cases[0].1,
+        Decision::Switch {
+            access: field("y"),
+            cases: vec![(int(0), leaf(0))],
+            default: Some(Box::new(Decision::Leaf {
+                arm: 1,
+                bindings: vec![("y".to_string(), field("y"))],
+            })),
+        }
+    );
+    assert_eq!(
+        *default,
+        Decision::Switch {
+            access: field("y"),
+            cases: vec![(int(0), leaf(2))],
+            default: Some(Box::new(leaf(3))),
+        }
+    );
+}
+
+/// 编译源码并统计函数 `name` 中满足条件的指令数
+fn count_instructions(
+    source: &str,
+    name: &str,
+    pred: impl Fn(&Instruction) -> bool,
+) -> usize {
+    let module = Compiler::new()
+        .compile("match.yx", source)
+        .expect("source should compile");
+    let function = module
+        .functions
+        .iter()
+        .find(|f| f.name == name)
+        .expect("function should exist");
+    function.all_instructions().filter(|i| pred(i)).count()
+}
+
+#[test]
+fn test_ir_shares_field_comparisons() {
+    let source = "\
+Point: Type = { x: Int, y: Int }
+pick: (p: Point) -> Int = (p) => {
+    match p {
+        Point(1, 1) => { return 1 },
+        Point(1, 2) => { return 2 },
+        Point(1, 3) => { return 3 },
+        Point(2, _) => { return 4 },
+        _ => { return 0 }
+    }
+    return -1
+}
+";
+    // 逐分支比较需要 2 + 2 + 2 + 1 = 7 次；决策树对 x 只比较 1 和 2 各一次
+    let eqs = count_instructions(source, "pick", |i| matches!(i, Instruction::Eq { .. }));
+    assert_eq!(eqs, 5);
+    // 每个字段只读取一次
+    let loads = count_instructions(source, "pick", |i| {
+        matches!(i, Instruction::LoadRecordField { .. })
+    });
+    assert_eq!(loads, 2);
+}
+
+#[test]
+fn test_ir_complete_bool_match_compares_once() {
+    let source = "\
+flag: (b: Bool) -> Int = (b) => {
+    match b {
+        true => { return 1 },
+        false => { return 0 }
+    }
+    return -1
+}
+";
+    let eqs = count_instructions(source, "flag", |i| matches!(i, Instruction::Eq { .. }));
+    assert_eq!(eqs, 1);
+}
+
+fn cases(tests: Vec<Test>) -> Vec<(Test, Decision)> {
+    tests
+        .into_iter()
+        .enumerate()
+        .map(|(arm, test)| (test, leaf(arm)))
+        .collect()
+}
+
+#[test]
+fn test_jump_table_for_dense_ints() {
+    let table = JumpTable::for_cases(&cases(vec![int(3), int(1), int(2), int(5)]));
+    assert_eq!(
+        table,
+        Some(JumpTable {
+            low: 1,
+            entries: vec![Some(1), Some(2), Some(0), None, Some(3)],
+        })
+    );
+}
+
+#[test]
+fn test_jump_table_for_chars() {
+    let chars = ['a', 'b', 'd', 'c'].map(|c| Test::Literal(ConstValue::Char(c)));
+    let table = JumpTable::for_cases(&cases(chars.to_vec())).expect("chars are dense");
+    assert_eq!(table.low, 'a' as i64);
+    assert_eq!(table.entries, [Some(0), Some(1), Some(3), Some(2)]);
+}
+
+#[test]
+fn test_jump_table_rejects_sparse_or_few_cases() {
+    // 取值范围内不到一半的值有分支
+    assert_eq!(
+        JumpTable::for_cases(&cases(vec![int(0), int(1), int(2), int(100)])),
+        None
+    );
+    // 分支太少
+    assert_eq!(
+        JumpTable::for_cases(&cases(vec![int(0), int(1), int(2)])),
+        None
+    );
+    // 混合了非整数检查
+    assert_eq!(
+        JumpTable::for_cases(&cases(vec![int(0), int(1), int(2), Test::Void])),
+        None
+    );
+}
+
+#[test]
+fn test_jump_table_for_variants_regardless_of_case_count() {
+    assert_eq!(
+        JumpTable::for_cases(&cases(vec![variant(1)])),
+        Some(JumpTable {
+            low: 1,
+            entries: vec![Some(0)],
+        })
+    );
+    assert_eq!(
+        JumpTable::for_cases(&cases(vec![variant(1), variant(0)])),
+        Some(JumpTable {
+            low: 0,
+            entries: vec![Some(1), Some(0)],
+        })
+    );
+}
+
+#[test]
+fn test_ir_dense_match_uses_switch() {
+    let source = "\
+digit: (n: Int) -> Int = (n) => {
+    match n {
+        0 => { return 10 },
+        1 => { return 11 },
+        2 => { return 12 },
+        4 => { return 14 },
+        _ => { return 0 }
+    }
+    return -1
+}
+";
+    let switches = count_instructions(source, "digit", |i| matches!(i, Instruction::Switch { .. }));
+    assert_eq!(switches, 1);
+    let eqs = count_instructions(source, "digit", |i| matches!(i, Instruction::Eq { .. }));
+    assert_eq!(eqs, 0);
+}
+
+#[test]
+fn test_ir_result_match_uses_switch() {
+    let source = "\
+use std.result
+
+describe: (r: Result(Int, String)) -> Int = (r) => {
+    match r {
+        Ok(n) => { return n },
+        Err(_) => { return -1 }
+    }
+    return -100
+}
+";
+    let switches = count_instructions(source, "describe", |i| {
+        matches!(i, Instruction::Switch { .. })
+    });
+    assert_eq!(switches, 1);
+    let eqs = count_instructions(source, "describe", |i| matches!(i, Instruction::Eq { .. }));
+    assert_eq!(eqs, 0);
+}
@@ -0,0 +1,178 @@
+//! 逃逸分析
+//!
+//! 证明结构体与列表字面量不会逃出所在函数的本次执行，把它们归入帧的栈区（`StackAlloc`），
+//! 不再长期占用堆。本遍在 SSA 形式上进行：`CreateStruct` / `AllocArray` 定值的对象只有
+//! 下列使用时不逃逸：
+//! - 读写其字段或元素：`LoadField`、`LoadRecordField`、`LoadIndex` 的源，`StoreField`、
+//!   `StoreIndex` 的目标
+//! - 比较（`==`、`<` 等），结果是布尔值
+//! - 列表的 `+`：拼接复制元素，结果是新列表
+//! - 写入函数中从不读取的局部变量槽
+//!
+//! 其余使用（实参、返回值、活跃 φ 的参数、存入其他对象、复制到别的寄存器等）都视为逃逸。
+//!
+//! 不逃逸的 `v = <分配>` 改写为 `t = <分配>; v = stack_alloc t`。运行时每处 `StackAlloc`
+//! 在帧中占一个栈槽：同一处再次执行时释放上次的对象（SSA 下 `v` 只指本次分配，旧对象
+//! 已无从到达），帧退出时释放全部栈槽。
+
+use std::collections::{HashMap, HashSet};
+
+use crate::middle::core::ir::{FunctionIR, Instruction, ModuleIR, Operand};
+use crate::middle::passes::ssa::operands::{self, register, set_register};
+use crate::middle::passes::ssa::{Phi, SsaFunction, Terminator};
+
+/// 分配的对象种类
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Object {
+    Struct,
+    List,
+}
+
+/// 对模块中的每个函数做逃逸分析
+pub fn escape_module(module: &mut ModuleIR) {
+    for func in &mut module.functions {
+        stack_allocate(func);
+    }
+}
+
+/// 把函数中不逃逸的分配归入栈区，返回是否有改写
+pub fn stack_allocate(func: &mut FunctionIR) -> bool {
+    let mut ssa = SsaFunction::construct(func);
+    let candidates = non_escaping(&ssa);
+    if candidates.is_empty() {
+        return false;
+    }
+    for b in 0..ssa.blocks.len() {
+        let instructions = std::mem::take(&mut ssa.blocks[b].instructions);
+        let mut rewritten = Vec::with_capacity(instructions.len());
+        for mut instr in instructions {
+            let Some(value) = allocation(&instr)
+                .map(|(value, _)| value)
+                .filter(|value| candidates.contains(value))
+            else {
+                rewritten.push(instr);
+                continue;
+            };
+            let fresh = ssa.new_value();
+            for dst in operands::operands_mut(&mut instr).0 {
+                set_register(dst, fresh);
+            }
+            rewritten.push(instr);
+            rewritten.push(Instruction::StackAlloc {
+                dst: Operand::Local(value),
+                src: Operand::Local(fresh),
+            });
+        }
+        ssa.blocks[b].instructions = rewritten;
+    }
+    *func = ssa.destruct();
+    true
+}
+
+/// 分配指令定值的 (值, 对象种类)
+fn allocation(instr: &Instruction) -> Option<(usize, Object)> {
+    match instr {
+        Instruction::CreateStruct { dst, .. } => register(dst).map(|v| (v, Object::Struct)),
+        Instruction::AllocArray { dst, .. } => register(dst).map(|v| (v, Object::List)),
+        _ => None,
+    }
+}
+
+/// 不逃逸的分配值
+fn non_escaping(ssa: &SsaFunction) -> HashSet<usize> {
+    let instructions = || ssa.blocks.iter().flat_map(|block| &block.instructions);
+    let objects: HashMap<usize, Object> = instructions().filter_map(allocation).collect();
+    if objects.is_empty() {
+        return HashSet::new();
+    }
+    let loaded_slots: HashSet<usize> = instructions()
+        .filter_map(|instr| match instr {
+            Instruction::Load {
+                src: Operand::Local(slot),
+                ..
+            } => Some(*slot),
+            _ => None,
+        })
+        .collect();
+
+    let mut escaped = HashSet::new();
+    let mut used = HashSet::new();
+    for instr in instructions() {
+        let mut contained = contained_uses(instr, &objects, &loaded_slots);
+        for value in operands::uses(instr) {
+            used.insert(value);
+            if let Some(i) = contained.iter().position(|&v| v == value) {
+                contained.swap_remove(i);
+            } else {
+                escaped.insert(value);
+            }
+        }
+    }
+    for block in &ssa.blocks {
+        let exits = match &block.terminator {
+            Terminator::Jump(_) => Vec::new(),
+            Terminator::Branch { cond, .. } | Terminator::Switch { value: cond, .. } => {
+                register(cond).into_iter().collect()
+            }
+            Terminator::Exit(instr) => operands::uses(instr),
+        };
+        used.extend(&exits);
+        escaped.extend(exits);
+    }
+
+    // φ 的参数只在 φ 活跃时才算使用：半剪枝构造会在循环头为已不活跃的寄存器插入 φ
+    let phis: Vec<&Phi> = ssa.blocks.iter().flat_map(|block| &block.phis).collect();
+    let mut live = vec![false; phis.len()];
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for (phi, live) in phis.iter().zip(&mut live) {
+            if !*live && used.contains(&phi.dst) {
+                *live = true;
+                changed = true;
+                for arg in phi.args.iter().filter_map(|(_, arg)| register(arg)) {
+                    used.insert(arg);
+                    escaped.insert(arg);
+                }
+            }
+        }
+    }
+    objects
+        .into_keys()
+        .filter(|value| !escaped.contains(value))
+        .collect()
+}
+
+/// 指令中不会让对象逃逸的寄存器使用
+fn contained_uses(
+    instr: &Instruction,
+    objects: &HashMap<usize, Object>,
+    loaded_slots: &HashSet<usize>,
+) -> Vec<usize> {
+    let contained: Vec<&Operand> = match instr {
+        Instruction::LoadField { src, .. }
+        | Instruction::LoadRecordField { src, .. }
+        | Instruction::LoadIndex { src, .. } => vec![src],
+        Instruction::StoreField { dst, .. } | Instruction::StoreIndex { dst, .. } => vec![dst],
+        Instruction::Eq { lhs, rhs, .. }
+        | Instruction::Ne { lhs, rhs, .. }
+        | Instruction::Lt { lhs, rhs, .. }
+        | Instruction::Le { lhs, rhs, .. }
+        | Instruction::Gt { lhs, rhs, .. }
+        | Instruction::Ge { lhs, rhs, .. } => vec![lhs, rhs],
+        Instruction::Add { lhs, rhs, .. } => [lhs, rhs]
+            .into_iter()
+            .filter(|op| register(op).and_then(|v| objects.get(&v)) == Some(&Object::List))
+            .collect(),
+        Instruction::Store {
+            dst: Operand::Local(slot),
+            src,
+            ..
+        } if !loaded_slots.contains(slot) => vec![src],
+        _ => Vec::new(),
+    };
+    contained.into_iter().filter_map(register).collect()
+}
+
+#[cfg(test)]
+mod tests;
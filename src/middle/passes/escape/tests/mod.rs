@@ -0,0 +1,153 @@
+//! 逃逸分析测试
+//!
+//! 覆盖：
+//! - 只读写字段、元素的结构体与列表改为栈上分配
+//! - 返回、作为实参、存入其他对象的分配保持在堆上
+//! - 只在 `-O2` 预设中运行，可由配置禁用
+//! - 栈上分配的对象在同一处再次执行与函数返回时释放，程序结果不变
+
+use crate::backends::interpreter::Interpreter;
+use crate::backends::Executor;
+use crate::frontend::config::{CompileConfig, OptLevel};
+use crate::frontend::Compiler;
+use crate::middle::bytecode::BytecodeModule;
+use crate::middle::core::ir::{FunctionIR, Instruction, ModuleIR};
+use crate::middle::passes::codegen::CodegenContext;
+use crate::middle::passes::escape::stack_allocate;
+use crate::middle::passes::manager::Pass;
+
+const SOURCE: &str = "\
+use std.assert
+
+Point: Type = { x: Int, y: Int }
+
+sum: (n: Int) -> Int = (n) => {
+    mut total = 0
+    mut i = 0
+    while i < n {
+        p = Point(i, i + 1)
+        xs = [i, i * 2]
+        total = total + p.x + p.y + xs[1]
+        i = i + 1
+    }
+    return total
+}
+
+collect: (n: Int) -> List(Int) = (n) => {
+    mut nums = []
+    mut j = 0
+    while j < n {
+        nums = nums + [j]
+        j = j + 1
+    }
+    return nums
+}
+
+origin: () -> Point = () => {
+    return Point(0, 0)
+}
+
+main = {
+    assert_eq(sum(10), 190)
+    nums = collect(10)
+    assert_eq(nums[3], 3)
+    assert_eq(origin().x, 0)
+}
+";
+
+fn compile(config: CompileConfig) -> ModuleIR {
+    // 被测函数内联进 `main` 后仍要检查
+    Compiler::with_config(config.with_tree_shaking(false))
+        .compile("escape.yx", SOURCE)
+        .expect("source should compile")
+}
+
+fn function<'a>(
+    module: &'a ModuleIR,
+    name: &str,
+) -> &'a FunctionIR {
+    module
+        .functions
+        .iter()
+        .find(|f| f.name == name)
+        .expect("function should exist")
+}
+
+fn stack_allocs(func: &FunctionIR) -> usize {
+    func.all_instructions()
+        .filter(|i| matches!(i, Instruction::StackAlloc { .. }))
+        .count()
+}
+
+/// 运行模块，返回结束时堆上的对象数
+fn run(module: ModuleIR) -> usize {
+    let mut ctx = CodegenContext::new(module);
+    let bytecode = ctx.generate().expect("codegen should succeed");
+    let module = BytecodeModule::from(bytecode);
+    let mut interpreter = Interpreter::new();
+    interpreter
+        .execute_module(&module)
+        .expect("program should run");
+    interpreter.heap_dump().objects.len()
+}
+
+#[test]
+fn test_local_records_are_stack_allocated() {
+    // `-O1` 不做逃逸分析；公共子表达式消除后绑定的读取直接使用寄存器中的值
+    let mut module = compile(CompileConfig::new());
+    let func = module
+        .functions
+        .iter_mut()
+        .find(|f| f.name == "sum")
+        .expect("function should exist");
+    assert_eq!(stack_allocs(func), 0);
+    assert!(stack_allocate(func));
+    // 内联后的 `Point(...)` 与列表字面量
+    assert_eq!(stack_allocs(func), 2);
+
+    let module = compile(CompileConfig::new().with_opt_level(OptLevel::O2));
+    assert_eq!(stack_allocs(function(&module, "sum")), 2);
+}
+
+#[test]
+fn test_escaping_allocations_stay_on_heap() {
+    let module = compile(CompileConfig::new().with_opt_level(OptLevel::O2));
+    // 返回的 `Point(0, 0)` 逃逸
+    assert_eq!(stack_allocs(function(&module, "origin")), 0);
+    // `[]` 经 φ 流向返回值，逃逸；`[j]` 只参与拼接
+    assert_eq!(stack_allocs(function(&module, "collect")), 1);
+}
+
+#[test]
+fn test_escape_is_opt_in() {
+    let module = compile(CompileConfig::new());
+    assert_eq!(stack_allocs(function(&module, "sum")), 0);
+
+    let config = CompileConfig::new()
+        .with_opt_level(OptLevel::O2)
+        .with_escape_analysis(false);
+    let module = compile(config);
+    assert_eq!(stack_allocs(function(&module, "sum")), 0);
+
+    // 不内联时 `Point(...)` 是调用，只有列表字面量在本函数中分配
+    let config = CompileConfig::new().with_passes(vec![Pass::Cse, Pass::Escape]);
+    let module = compile(config);
+    assert_eq!(stack_allocs(function(&module, "sum")), 1);
+}
+
+#[test]
+fn test_stack_allocations_are_released() {
+    let heap = run(compile(
+        CompileConfig::new()
+            .with_opt_level(OptLevel::O2)
+            .with_escape_analysis(false),
+    ));
+    let stack = run(compile(CompileConfig::new().with_opt_level(OptLevel::O2)));
+    // 每轮循环的 `Point`、`[i, i * 2]` 与 `[j]` 都已释放
+    assert!(
+        heap >= stack + 30,
+        "heap objects: {} without escape analysis, {} with",
+        heap,
+        stack
+    );
+}
@@ -0,0 +1,387 @@
+//! 函数内联
+//!
+//! 解释器中一次调用要建立栈帧、复制参数，小函数的调用开销往往超过函数体本身。本遍把
+//! 模块内对小函数的直接调用（`Call` 的目标是函数名常量）替换为被调函数体的副本：
+//! - 被调函数的寄存器与局部变量槽整体平移到调用者已用范围之后；参数先存入平移后的
+//!   参数槽，`Ret` 改为写入调用结果并跳到调用之后
+//! - 指令数不超过阈值的函数才内联，位于循环中的调用阈值放宽到两倍；`#[inline]` 不受
+//!   阈值限制，`#[noinline]` 从不内联
+//! - 递归（在调用图的环上）、闭包体（访问 upvalue）以及含尾调用、spawn 的函数不内联；
+//!   平移后寄存器或槽超出字节码的 256 个时放弃该调用点
+//!
+//! 本遍在单态化之后运行，调用已指向特化后的函数。
+//!
+//! 按调用图后序处理函数，被调函数在内联进调用者之前已完成自身的内联。内联不删除被调
+//! 函数，按名字的其他引用（闭包、导出）仍然有效。
+
+use std::collections::{HashMap, HashSet};
+
+use crate::frontend::core::typecheck::MonoType;
+use crate::middle::core::ir::{
+    BasicBlock, ConstValue, FunctionIR, InlineHint, Instruction, ModuleIR, Operand,
+};
+use crate::middle::passes::ssa::operands::{self, register};
+use crate::util::span::Span;
+
+/// 字节码寄存器与局部变量槽的个数上限
+const MAX_SLOTS: usize = 256;
+
+/// 对模块做函数内联，返回内联的调用点个数
+pub fn inline_module(
+    module: &mut ModuleIR,
+    threshold: usize,
+) -> usize {
+    let index: HashMap<String, usize> = module
+        .functions
+        .iter()
+        .enumerate()
+        .map(|(i, func)| (func.name.clone(), i))
+        .collect();
+    let callees: Vec<Vec<usize>> = module
+        .functions
+        .iter()
+        .map(|func| {
+            func.all_instructions()
+                .filter_map(|instr| direct_callee(instr).and_then(|name| index.get(name)))
+                .copied()
+                .collect()
+        })
+        .collect();
+    let recursive = recursive_functions(&callees);
+
+    let mut inlined = 0;
+    for caller in postorder(&callees) {
+        let inliner = Inliner {
+            functions: &module.functions,
+            index: &index,
+            recursive: &recursive,
+            hints: &module.inline_hints,
+            threshold,
+        };
+        if let Some((func, count)) = inliner.inline_calls(&module.functions[caller]) {
+            module.functions[caller] = func;
+            inlined += count;
+        }
+    }
+    inlined
+}
+
+/// 直接调用的函数名
+fn direct_callee(instr: &Instruction) -> Option<&str> {
+    match instr {
+        Instruction::Call {
+            func: Operand::Const(ConstValue::String(name)),
+            ..
+        } => Some(name),
+        _ => None,
+    }
+}
+
+/// 在调用图的环上（能经由调用回到自身）的函数
+fn recursive_functions(callees: &[Vec<usize>]) -> HashSet<usize> {
+    (0..callees.len())
+        .filter(|&start| {
+            let mut seen = vec![false; callees.len()];
+            let mut stack = callees[start].clone();
+            while let Some(f) = stack.pop() {
+                if f == start {
+                    return true;
+                }
+                if !std::mem::replace(&mut seen[f], true) {
+                    stack.extend(&callees[f]);
+                }
+            }
+            false
+        })
+        .collect()
+}
+
+/// 调用图后序：被调函数排在调用者之前（环上的顺序任意）
+fn postorder(callees: &[Vec<usize>]) -> Vec<usize> {
+    let mut order = Vec::with_capacity(callees.len());
+    let mut visited = vec![false; callees.len()];
+    for root in 0..callees.len() {
+        if visited[root] {
+            continue;
+        }
+        visited[root] = true;
+        // (函数, 下一个要访问的被调函数下标)
+        let mut stack = vec![(root, 0)];
+        while let Some((f, next)) = stack.last_mut() {
+            if let Some(&callee) = callees[*f].get(*next) {
+                *next += 1;
+                if !visited[callee] {
+                    visited[callee] = true;
+                    stack.push((callee, 0));
+                }
+            } else {
+                order.push(*f);
+                stack.pop();
+            }
+        }
+    }
+    order
+}
+
+/// 函数占用的寄存器与槽的范围：`locals` 的长度与实际用到的最大编号取大者
+fn extent(func: &FunctionIR) -> usize {
+    let mut extent = func.locals.len();
+    for instr in func.all_instructions() {
+        let (defs, uses) = operands::operands(instr);
+        for r in defs.into_iter().chain(uses).filter_map(register) {
+            extent = extent.max(r + 1);
+        }
+        match instr {
+            Instruction::Load {
+                src: Operand::Local(slot),
+                ..
+            }
+            | Instruction::Store {
+                dst: Operand::Local(slot),
+                ..
+            } => extent = extent.max(slot + 1),
+            _ => {}
+        }
+    }
+    extent
+}
+
+struct Inliner<'a> {
+    functions: &'a [FunctionIR],
+    index: &'a HashMap<String, usize>,
+    recursive: &'a HashSet<usize>,
+    hints: &'a HashMap<String, InlineHint>,
+    threshold: usize,
+}
+
+impl Inliner<'_> {
+    /// 被调函数能否内联到位于（`hot` 表示循环中的）调用点
+    fn can_inline(
+        &self,
+        callee: usize,
+        arg_count: usize,
+        hot: bool,
+    ) -> bool {
+        let func = &self.functions[callee];
+        if self.recursive.contains(&callee) || func.params.len() != arg_count {
+            return false;
+        }
+        let size_ok = || {
+            let limit = if hot {
+                self.threshold * 2
+            } else {
+                self.threshold
+            };
+            func.all_instructions().count() <= limit
+        };
+        let allowed = match self.hints.get(&func.name) {
+            Some(InlineHint::Never) => false,
+            Some(InlineHint::Always) => true,
+            None => size_ok(),
+        };
+        allowed && func.all_instructions().all(is_relocatable)
+    }
+
+    /// 内联 `caller` 中可内联的调用点；没有可内联的调用时返回 `None`
+    fn inline_calls(
+        &self,
+        caller: &FunctionIR,
+    ) -> Option<(FunctionIR, usize)> {
+        let code: Vec<Instruction> = caller.all_instructions().cloned().collect();
+        let hot = hot_positions(&code);
+        let mut base = extent(caller);
+        let mut locals = caller.locals.clone();
+
+        let mut out = Vec::with_capacity(code.len());
+        // 原指令下标 -> 新下标（含末尾）
+        let mut new_pos = Vec::with_capacity(code.len() + 1);
+        // 调用者自身的跳转在 `out` 中的位置，目标最后按 `new_pos` 改写
+        let mut caller_jumps = Vec::new();
+        let mut count = 0;
+
+        for (i, instr) in code.into_iter().enumerate() {
+            new_pos.push(out.len());
+            if let Instruction::Call { dst, args, .. } = &instr {
+                let callee = direct_callee(&instr).and_then(|name| self.index.get(name));
+                if let Some(&callee) = callee {
+                    if self.can_inline(callee, args.len(), hot[i]) {
+                        let func = &self.functions[callee];
+                        let width = extent(func) + args.len();
+                        if base + width <= MAX_SLOTS {
+                            splice(&mut out, func, dst.as_ref(), args, base);
+                            locals.resize(base, MonoType::Void);
+                            locals.extend(func.locals.iter().cloned());
+                            base += width;
+                            locals.resize(base, MonoType::Void);
+                            count += 1;
+                            continue;
+                        }
+                    }
+                }
+            }
+            if !instr.jump_targets().is_empty() {
+                caller_jumps.push(out.len());
+            }
+            out.push(instr);
+        }
+        if count == 0 {
+            return None;
+        }
+        new_pos.push(out.len());
+        for at in caller_jumps {
+            for target in out[at].jump_targets_mut() {
+                *target = new_pos[(*target).min(new_pos.len() - 1)];
+            }
+        }
+
+        Some((
+            FunctionIR {
+                name: caller.name.clone(),
+                params: caller.params.clone(),
+                return_type: caller.return_type.clone(),
+                locals,
+                blocks: vec![BasicBlock {
+                    label: 0,
+                    instructions: out,
+                    successors: Vec::new(),
+                }],
+                entry: 0,
+                generic_params: caller.generic_params.clone(),
+            },
+            count,
+        ))
+    }
+}
+
+/// 指令能否原样搬进另一个函数的栈帧
+fn is_relocatable(instr: &Instruction) -> bool {
+    !matches!(
+        instr,
+        Instruction::LoadUpvalue { .. }
+            | Instruction::StoreUpvalue { .. }
+            | Instruction::CloseUpvalue(_)
+            | Instruction::TailCall { .. }
+            | Instruction::Spawn { .. }
+            | Instruction::SpawnFromList { .. }
+            | Instruction::Yield
+    )
+}
+
+/// 每条指令是否位于循环中：存在跨过它的向后跳转
+fn hot_positions(code: &[Instruction]) -> Vec<bool> {
+    let mut hot = vec![false; code.len()];
+    for (j, instr) in code.iter().enumerate() {
+        for target in instr.jump_targets() {
+            if target <= j {
+                for flag in &mut hot[target..=j] {
+                    *flag = true;
+                }
+            }
+        }
+    }
+    hot
+}
+
+/// 把 `func` 的函数体平移 `base` 后接到 `out` 末尾，替代 `dst = func(args)`
+///
+/// 寄存器 `base + extent(func)` 起存放常量实参。
+fn splice(
+    out: &mut Vec<Instruction>,
+    func: &FunctionIR,
+    dst: Option<&Operand>,
+    args: &[Operand],
+    base: usize,
+) {
+    let scratch = base + extent(func);
+    // 实参存入平移后的参数槽（与 `Frame::with_args` 一致）
+    for (i, arg) in args.iter().enumerate() {
+        let src = if register(arg).is_some() {
+            arg.clone()
+        } else {
+            out.push(Instruction::Load {
+                dst: Operand::Temp(scratch + i),
+                src: arg.clone(),
+            });
+            Operand::Temp(scratch + i)
+        };
+        out.push(Instruction::Store {
+            dst: Operand::Local(base + i),
+            src,
+            span: Span::dummy(),
+        });
+    }
+
+    let mut body: Vec<Instruction> = func.all_instructions().cloned().collect();
+    // 落出末尾等价于 `Ret(None)`
+    if !matches!(body.last(), Some(Instruction::Ret(_) | Instruction::Jmp(_))) {
+        body.push(Instruction::Ret(None));
+    }
+    // 被调函数的每条指令在 `out` 中的位置：`Ret` 展开为两条
+    let start = out.len();
+    let mut offsets = Vec::with_capacity(body.len() + 1);
+    let mut len = 0;
+    for instr in &body {
+        offsets.push(start + len);
+        len += match instr {
+            Instruction::Ret(_) if dst.is_some() => 2,
+            _ => 1,
+        };
+    }
+    let end = start + len;
+    offsets.push(end);
+
+    for mut instr in body {
+        match &mut instr {
+            Instruction::Ret(value) => {
+                if let Some(dst) = dst {
+                    let value = value.take().map(|v| shift(v, base));
+                    out.push(match value {
+                        Some(src) if register(&src).is_some() => Instruction::Move {
+                            dst: dst.clone(),
+                            src,
+                        },
+                        src => Instruction::Load {
+                            dst: dst.clone(),
+                            src: src.unwrap_or(Operand::Const(ConstValue::Void)),
+                        },
+                    });
+                }
+                out.push(Instruction::Jmp(end));
+                continue;
+            }
+            Instruction::Load { src, .. } => match src {
+                Operand::Local(slot) => *slot += base,
+                Operand::Arg(i) => *src = Operand::Local(base + *i),
+                _ => {}
+            },
+            Instruction::Store {
+                dst: Operand::Local(slot),
+                ..
+            } => *slot += base,
+            _ => {}
+        }
+        for target in instr.jump_targets_mut() {
+            *target = offsets[(*target).min(offsets.len() - 1)];
+        }
+        let (defs, uses) = operands::operands_mut(&mut instr);
+        for op in defs.into_iter().chain(uses) {
+            *op = shift(op.clone(), base);
+        }
+        out.push(instr);
+    }
+}
+
+/// 寄存器操作数平移 `base`，其他操作数不变
+fn shift(
+    operand: Operand,
+    base: usize,
+) -> Operand {
+    match operand {
+        Operand::Local(r) => Operand::Local(r + base),
+        Operand::Temp(r) => Operand::Temp(r + base),
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests;
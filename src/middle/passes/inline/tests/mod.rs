@@ -0,0 +1,230 @@
+//! 函数内联测试
+//!
+//! 覆盖：
+//! - 小函数的调用被替换为函数体，常量实参随后折叠
+//! - `#[inline]` / `#[noinline]` 覆盖大小阈值
+//! - 递归函数不内联
+//! - 循环中的调用阈值放宽
+//! - 内联后的程序（多个返回点、可变参数、循环）在虚拟机上运行结果不变
+
+use crate::backends::Executor;
+use crate::frontend::config::{CompileConfig, OptLevel};
+use crate::frontend::Compiler;
+use crate::middle::core::ir::{ConstValue, FunctionIR, Instruction, ModuleIR, Operand};
+
+fn compile(
+    source: &str,
+    config: CompileConfig,
+) -> ModuleIR {
+    Compiler::with_config(config)
+        .compile("inline.yx", source)
+        .expect("source should compile")
+}
+
+fn function<'a>(
+    module: &'a ModuleIR,
+    name: &str,
+) -> &'a FunctionIR {
+    module
+        .functions
+        .iter()
+        .find(|f| f.name == name)
+        .expect("function should exist")
+}
+
+/// 函数中对 `callee` 的直接调用次数
+fn calls(
+    func: &FunctionIR,
+    callee: &str,
+) -> usize {
+    func.all_instructions()
+        .filter(|i| {
+            matches!(
+                i,
+                Instruction::Call { func: Operand::Const(ConstValue::String(f)), .. } if f == callee
+            )
+        })
+        .count()
+}
+
+fn run(module: ModuleIR) {
+    let mut ctx = crate::middle::passes::codegen::CodegenContext::new(module);
+    let bytecode = ctx.generate().expect("codegen should succeed");
+    let module = crate::middle::bytecode::BytecodeModule::from(bytecode);
+    let mut interpreter = crate::backends::interpreter::Interpreter::new();
+    interpreter
+        .execute_module(&module)
+        .expect("inlined program should run");
+}
+
+#[test]
+fn test_small_function_is_inlined_and_folded() {
+    let source = "\
+double: (n: Int) -> Int = (n) => {
+    return n * 2
+}
+
+main = {
+    x = double(21)
+}
+";
+    let module = compile(source, CompileConfig::new().with_opt_level(OptLevel::O0));
+    assert_eq!(calls(function(&module, "main"), "double"), 1);
+
+    let module = compile(source, CompileConfig::new().with_tree_shaking(false));
+    let main = function(&module, "main");
+    assert_eq!(calls(main, "double"), 0);
+    // 内联后常量实参传入函数体，乘法在编译期完成
+    assert!(main.all_instructions().any(|i| matches!(
+        i,
+        Instruction::Load {
+            src: Operand::Const(ConstValue::Int(42)),
+            ..
+        }
+    )));
+    // 被调函数本身保留，只在死函数消除时删除
+    assert!(module.functions.iter().any(|f| f.name == "double"));
+    let module = compile(source, CompileConfig::new());
+    assert!(!module.functions.iter().any(|f| f.name == "double"));
+}
+
+#[test]
+fn test_attributes_override_threshold() {
+    let source = "\
+#[inline]
+forced: (n: Int) -> Int = (n) => {
+    return n + 1
+}
+
+#[noinline]
+kept: (n: Int) -> Int = (n) => {
+    return n + 1
+}
+
+plain: (n: Int) -> Int = (n) => {
+    return n + 1
+}
+
+main = {
+    a = forced(1)
+    b = kept(a)
+    c = plain(b)
+}
+";
+    let module = compile(source, CompileConfig::new().with_inline_threshold(1));
+    let main = function(&module, "main");
+    assert_eq!(calls(main, "forced"), 0);
+    assert_eq!(calls(main, "kept"), 1);
+    assert_eq!(calls(main, "plain"), 1);
+
+    let module = compile(source, CompileConfig::new());
+    let main = function(&module, "main");
+    assert_eq!(calls(main, "plain"), 0);
+    assert_eq!(calls(main, "kept"), 1);
+}
+
+#[test]
+fn test_recursive_functions_are_not_inlined() {
+    let source = "\
+#[inline]
+fact: (n: Int) -> Int = (n) => {
+    if n <= 1 {
+        return 1
+    }
+    return n * fact(n - 1)
+}
+
+is_even: (n: Int) -> Bool = (n) => {
+    if n == 0 {
+        return true
+    }
+    return is_odd(n - 1)
+}
+
+is_odd: (n: Int) -> Bool = (n) => {
+    if n == 0 {
+        return false
+    }
+    return is_even(n - 1)
+}
+
+main = {
+    a = fact(5)
+    b = is_even(4)
+}
+";
+    let module = compile(source, CompileConfig::new());
+    assert_eq!(calls(function(&module, "fact"), "fact"), 1);
+    let main = function(&module, "main");
+    assert_eq!(calls(main, "fact"), 1);
+    assert_eq!(calls(main, "is_even"), 1);
+}
+
+#[test]
+fn test_calls_in_loops_use_larger_threshold() {
+    let source = "\
+step: (n: Int) -> Int = (n) => {
+    return n + 3
+}
+
+main = {
+    mut total = 0
+    mut i = 0
+    while i < 10 {
+        total = total + step(i)
+        i = i + 1
+    }
+    j = step(total)
+}
+";
+    let unoptimized = compile(source, CompileConfig::new().with_opt_level(OptLevel::O0));
+    let size = function(&unoptimized, "step").all_instructions().count();
+
+    let module = compile(source, CompileConfig::new().with_inline_threshold(size - 1));
+    // 只剩循环外的调用
+    assert_eq!(calls(function(&module, "main"), "step"), 1);
+
+    let module = compile(
+        source,
+        CompileConfig::new().with_inline_threshold(size / 2 - 1),
+    );
+    assert_eq!(calls(function(&module, "main"), "step"), 2);
+}
+
+#[test]
+fn test_inlined_program_runs_on_the_vm() {
+    let source = "\
+use std.assert
+
+clamp: (x: Int, lo: Int, hi: Int) -> Int = (x, lo, hi) => {
+    if x < lo {
+        return lo
+    }
+    if x > hi {
+        return hi
+    }
+    return x
+}
+
+bump: (mut n: Int) -> Int = (n) => {
+    n = n + 1
+    return n * 2
+}
+
+main = {
+    mut total = 0
+    mut i = 0
+    while i < 10 {
+        total = total + clamp(i, 2, 7)
+        i = i + 1
+    }
+    assert_eq(total, 45)
+    assert_eq(bump(total), 92)
+    assert_eq(clamp(bump(1), 0, 3), 3)
+}
+";
+    let module = compile(source, CompileConfig::new());
+    assert_eq!(calls(function(&module, "main"), "clamp"), 0);
+    assert_eq!(calls(function(&module, "main"), "bump"), 0);
+    run(module);
+}
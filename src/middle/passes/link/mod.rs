@@ -0,0 +1,282 @@
+//! 跨模块 IR 链接
+//!
+//! 各源文件分别编译为 `ModuleIR` 后，在代码生成前合并为一个模块，生成单个字节码文件。
+//! 根模块的符号保持原名，程序入口 `main` 取自根模块；依赖模块以模块路径（如 `util.math`）
+//! 为命名空间：
+//! - 自由函数与闭包改名为 `路径.名字`，模块内对它们的引用（调用、作为值传递的函数名、
+//!   `MakeClosure`、虚表、全局量初值）随之改写
+//! - 其他模块以 `路径.名字` 引用依赖模块的函数，只能引用公开函数
+//! - 方法（`Type.method`）与构造函数（`Type_constructor`）属于类型，运行时按类型名查找，
+//!   不加前缀；链接后的程序中类型共用一个命名空间，同名方法重复定义是错误
+//! - 全局量、接口虚表与 FFI 库按下标引用，合并时整体平移
+//! - 结构体布局按类型名合并，同名类型的布局必须一致
+//!
+//! 常量内联在操作数中，常量池由代码生成为整个程序统一建立，各模块相同的常量只存一份。
+
+use std::collections::{HashMap, HashSet};
+
+use crate::middle::core::ir::{ConstValue, FfiBinding, Instruction, ModuleIR, Operand};
+use crate::middle::passes::ssa::operands;
+use crate::util::diagnostic::{Diagnostic, ErrorCodeDefinition};
+
+/// 参与链接的依赖模块
+#[derive(Debug, Clone)]
+pub struct LinkUnit {
+    /// 模块路径，也是模块内函数的命名空间
+    pub path: String,
+    pub module: ModuleIR,
+}
+
+impl LinkUnit {
+    pub fn new(
+        path: impl Into<String>,
+        module: ModuleIR,
+    ) -> Self {
+        Self {
+            path: path.into(),
+            module,
+        }
+    }
+}
+
+/// 把根模块与依赖模块链接为一个模块
+pub fn link_modules(
+    root: ModuleIR,
+    deps: Vec<LinkUnit>,
+) -> Result<ModuleIR, Diagnostic> {
+    // 依赖模块的公开函数（链接后的名字）
+    let mut exports = HashMap::new();
+    for unit in &deps {
+        let local = local_symbols(&unit.module);
+        let public = unit
+            .module
+            .exports
+            .iter()
+            .map(|name| qualify(&unit.path, &local, name))
+            .collect();
+        if unit.path.is_empty() || exports.insert(unit.path.clone(), public).is_some() {
+            return Err(ErrorCodeDefinition::duplicate_import(&unit.path).build());
+        }
+    }
+    let resolver = Resolver { exports };
+
+    let mut linked = root;
+    resolver.check(&linked)?;
+    let mut defined: HashSet<String> = linked.functions.iter().map(|f| f.name.clone()).collect();
+    for unit in deps {
+        let module = relocate(unit.path.as_str(), unit.module, &linked);
+        resolver.check(&module)?;
+        for func in &module.functions {
+            if !defined.insert(func.name.clone()) {
+                return Err(ErrorCodeDefinition::duplicate_definition(&func.name).build());
+            }
+        }
+        merge(&mut linked, module)?;
+    }
+    Ok(linked)
+}
+
+/// 模块内需要加命名空间的函数：自由函数与闭包
+fn local_symbols(module: &ModuleIR) -> HashSet<String> {
+    module
+        .functions
+        .iter()
+        .map(|f| f.name.clone())
+        .filter(|name| !name.contains('.') && !name.ends_with("_constructor"))
+        .collect()
+}
+
+fn qualify(
+    path: &str,
+    local: &HashSet<String>,
+    name: &str,
+) -> String {
+    if local.contains(name) {
+        format!("{}.{}", path, name)
+    } else {
+        name.to_string()
+    }
+}
+
+/// 给依赖模块的函数加命名空间，并把按下标的引用平移到 `linked` 之后
+fn relocate(
+    path: &str,
+    mut module: ModuleIR,
+    linked: &ModuleIR,
+) -> ModuleIR {
+    let local = local_symbols(&module);
+    let rename = |name: &mut String| {
+        if local.contains(name.as_str()) {
+            *name = format!("{}.{}", path, name);
+        }
+    };
+    let global_base = linked.globals.len();
+    let vtable_base = linked.vtables.len();
+    let lib_base = linked
+        .ffi_libs
+        .iter()
+        .map(|lib| lib.id + 1)
+        .max()
+        .unwrap_or(0);
+
+    for func in &mut module.functions {
+        rename(&mut func.name);
+        for instr in func
+            .blocks
+            .iter_mut()
+            .flat_map(|block| block.instructions.iter_mut())
+        {
+            match instr {
+                Instruction::MakeClosure { func, .. } => rename(func),
+                Instruction::MakeDyn { vtable, .. } => *vtable += vtable_base,
+                Instruction::Store {
+                    dst: Operand::Global(index),
+                    ..
+                } => *index += global_base,
+                _ => {}
+            }
+            for operand in operands::all_operands_mut(instr) {
+                match operand {
+                    Operand::Const(ConstValue::String(name)) => rename(name),
+                    Operand::Global(index) => *index += global_base,
+                    _ => {}
+                }
+            }
+        }
+    }
+    for (name, _, init) in &mut module.globals {
+        *name = format!("{}.{}", path, name);
+        if let Some(ConstValue::String(value)) = init {
+            rename(value);
+        }
+    }
+    for vtable in &mut module.vtables {
+        vtable.methods.iter_mut().for_each(rename);
+    }
+    for lib in &mut module.ffi_libs {
+        lib.id += lib_base;
+    }
+    for binding in &mut module.ffi_bindings {
+        match binding {
+            FfiBinding::TypeBinding { lib_id, .. } | FfiBinding::FuncBinding { lib_id, .. } => {
+                *lib_id += lib_base
+            }
+        }
+    }
+    module.exports.iter_mut().for_each(rename);
+
+    rekey(&mut module.mut_locals, &rename);
+    rekey(&mut module.loop_binding_locals, &rename);
+    rekey(&mut module.local_names, &rename);
+    rekey(&mut module.inline_hints, &rename);
+    rekey(&mut module.upvalue_counts, &rename);
+    module
+}
+
+/// 按函数名索引的表随函数改名
+fn rekey<V>(
+    map: &mut HashMap<String, V>,
+    rename: &impl Fn(&mut String),
+) {
+    *map = std::mem::take(map)
+        .into_iter()
+        .map(|(mut name, value)| {
+            rename(&mut name);
+            (name, value)
+        })
+        .collect();
+}
+
+/// 调用依赖模块命名空间中的函数时，目标必须是该模块的公开函数
+struct Resolver {
+    exports: HashMap<String, HashSet<String>>,
+}
+
+impl Resolver {
+    fn check(
+        &self,
+        module: &ModuleIR,
+    ) -> Result<(), Diagnostic> {
+        let defined: HashSet<&str> = module.functions.iter().map(|f| f.name.as_str()).collect();
+        for instr in module.functions.iter().flat_map(|f| f.all_instructions()) {
+            let target = match instr {
+                Instruction::Call {
+                    func: Operand::Const(ConstValue::String(name)),
+                    ..
+                }
+                | Instruction::TailCall {
+                    func: Operand::Const(ConstValue::String(name)),
+                    ..
+                }
+                | Instruction::MakeClosure { func: name, .. } => name,
+                _ => continue,
+            };
+            if !defined.contains(target.as_str()) {
+                self.resolve(target)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 按最长的模块路径前缀找到 `name` 所在的依赖模块；不属于任何依赖模块的名字
+    /// （标准库、FFI 函数）留给代码生成
+    fn resolve(
+        &self,
+        name: &str,
+    ) -> Result<(), Diagnostic> {
+        let owner = self
+            .exports
+            .iter()
+            .filter_map(|(path, public)| {
+                let symbol = name.strip_prefix(path)?.strip_prefix('.')?;
+                Some((path, symbol, public))
+            })
+            .max_by_key(|(path, ..)| path.len());
+        match owner {
+            Some((path, symbol, public)) if !public.contains(name) => {
+                Err(ErrorCodeDefinition::export_not_found(symbol, path).build())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// 把平移后的依赖模块并入 `linked`
+fn merge(
+    linked: &mut ModuleIR,
+    module: ModuleIR,
+) -> Result<(), Diagnostic> {
+    for layout in module.struct_layouts {
+        match linked.struct_layouts.iter().find(|l| l.name == layout.name) {
+            Some(existing) if *existing != layout => {
+                return Err(ErrorCodeDefinition::duplicate_definition(&layout.name).build());
+            }
+            Some(_) => {}
+            None => linked.struct_layouts.push(layout),
+        }
+    }
+    linked.struct_layouts.sort_by(|a, b| a.name.cmp(&b.name));
+    linked.types.extend(module.types);
+    for generic in module.generic_types {
+        if !linked.generic_types.iter().any(|g| g.0 == generic.0) {
+            linked.generic_types.push(generic);
+        }
+    }
+    linked.globals.extend(module.globals);
+    linked.functions.extend(module.functions);
+    linked.vtables.extend(module.vtables);
+    linked.ffi_libs.extend(module.ffi_libs);
+    linked.ffi_bindings.extend(module.ffi_bindings);
+    linked.exports.extend(module.exports);
+    linked.mut_locals.extend(module.mut_locals);
+    linked
+        .loop_binding_locals
+        .extend(module.loop_binding_locals);
+    linked.local_names.extend(module.local_names);
+    linked.inline_hints.extend(module.inline_hints);
+    linked.upvalue_counts.extend(module.upvalue_counts);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,261 @@
+//! 跨模块链接测试
+//!
+//! 覆盖：
+//! - 依赖模块的函数加上模块路径前缀，模块内的调用随之改写；根模块按 `路径.名字` 调用
+//! - 调用依赖模块的私有函数、重复的模块路径与重复定义的方法报错
+//! - 全局量的下标平移、依赖模块的全局量加前缀
+//! - 链接后的模块生成单个字节码文件，常量池共用并在虚拟机上运行
+//! - 各函数并行翻译后合并，多次生成的字节码逐字节相同
+
+use crate::backends::interpreter::Interpreter;
+use crate::backends::Executor;
+use crate::frontend::Compiler;
+use crate::middle::bytecode::BytecodeModule;
+use crate::middle::core::ir::{ConstValue, Instruction, ModuleIR, Operand};
+use crate::middle::core::ir_text::parse_module;
+use crate::middle::passes::codegen::CodegenContext;
+use crate::middle::passes::link::{link_modules, LinkUnit};
+
+const UTIL: &str = r#"
+export "double"
+
+fn "helper"[n](int64) -> int64 {
+bb0:
+  %t0 = load %a0
+  %t1 = load 2
+  %t2 = mul %t0, %t1
+  ret %t2
+}
+
+fn "double"[n](int64) -> int64 {
+bb0:
+  %t0 = load %a0
+  %t1 = call "helper"(%t0)
+  ret %t1
+}
+"#;
+
+const MAIN: &str = r#"
+fn "helper"() -> int64 {
+bb0:
+  %t0 = load 1
+  ret %t0
+}
+
+fn "main"() -> void {
+bb0:
+  %t0 = load 21
+  %t1 = call "util.double"(%t0)
+  %t2 = call "helper"()
+  %t3 = add %t1, %t2
+  %t4 = load 43
+  %t5 = call "std.assert.assert_eq"(%t3, %t4)
+  ret
+}
+"#;
+
+fn parse(text: &str) -> ModuleIR {
+    parse_module(text).unwrap_or_else(|e| panic!("{}\n{}", e, text))
+}
+
+fn names(module: &ModuleIR) -> Vec<&str> {
+    module.functions.iter().map(|f| f.name.as_str()).collect()
+}
+
+fn calls<'a>(
+    module: &'a ModuleIR,
+    name: &str,
+) -> Vec<&'a str> {
+    module
+        .functions
+        .iter()
+        .find(|f| f.name == name)
+        .expect("function should exist")
+        .all_instructions()
+        .filter_map(|instr| match instr {
+            Instruction::Call {
+                func: Operand::Const(ConstValue::String(target)),
+                ..
+            } => Some(target.as_str()),
+            _ => None,
+        })
+        .collect()
+}
+
+fn run(module: ModuleIR) {
+    let mut ctx = CodegenContext::new(module);
+    let bytecode = ctx.generate().expect("codegen should succeed");
+    let module = BytecodeModule::from(bytecode);
+    Interpreter::new()
+        .execute_module(&module)
+        .expect("linked program should run");
+}
+
+#[test]
+fn test_dependency_functions_are_namespaced() {
+    let linked = link_modules(parse(MAIN), vec![LinkUnit::new("util", parse(UTIL))])
+        .expect("modules should link");
+    assert_eq!(
+        names(&linked),
+        ["helper", "main", "util.helper", "util.double"]
+    );
+    assert_eq!(calls(&linked, "util.double"), ["util.helper"]);
+    assert_eq!(
+        calls(&linked, "main"),
+        ["util.double", "helper", "std.assert.assert_eq"]
+    );
+    assert_eq!(linked.exports, ["util.double"]);
+    run(linked);
+}
+
+#[test]
+fn test_private_function_is_not_linked() {
+    let main = parse(
+        r#"
+fn "main"() -> void {
+bb0:
+  %t0 = call "util.helper"(1)
+  ret
+}
+"#,
+    );
+    let err = link_modules(main, vec![LinkUnit::new("util", parse(UTIL))])
+        .expect_err("private function should not resolve");
+    assert_eq!(err.code, "E5003");
+}
+
+#[test]
+fn test_duplicate_symbols_are_rejected() {
+    let err = link_modules(
+        parse(MAIN),
+        vec![
+            LinkUnit::new("util", parse(UTIL)),
+            LinkUnit::new("util", parse(UTIL)),
+        ],
+    )
+    .expect_err("duplicate module path should fail");
+    assert_eq!(err.code, "E5006");
+
+    let method = r#"
+fn "Point.sum"[self](int64) -> int64 {
+bb0:
+  ret %a0
+}
+"#;
+    let err = link_modules(
+        parse(MAIN),
+        vec![
+            LinkUnit::new("a", parse(method)),
+            LinkUnit::new("b", parse(method)),
+        ],
+    )
+    .expect_err("methods share the type namespace");
+    assert_eq!(err.code, "E2002");
+}
+
+#[test]
+fn test_indices_are_relocated() {
+    let root = parse(
+        r#"
+global "limit": int64 = 10
+
+fn "main"() -> void {
+bb0:
+  %t0 = load %g0
+  ret
+}
+"#,
+    );
+    let dep = parse(
+        r#"
+global "limit": int64 = 20
+
+fn "get"() -> int64 {
+bb0:
+  %t0 = load %g0
+  ret %t0
+}
+"#,
+    );
+    let linked =
+        link_modules(root, vec![LinkUnit::new("config", dep)]).expect("modules should link");
+    let globals: Vec<&str> = linked.globals.iter().map(|g| g.0.as_str()).collect();
+    assert_eq!(globals, ["limit", "config.limit"]);
+    let load = linked
+        .functions
+        .iter()
+        .find(|f| f.name == "config.get")
+        .and_then(|f| f.all_instructions().next());
+    assert!(matches!(
+        load,
+        Some(Instruction::Load {
+            src: Operand::Global(1),
+            ..
+        })
+    ));
+}
+
+#[test]
+fn test_compiled_modules_share_constant_pool() {
+    let mut compiler = Compiler::new();
+    let linked = compiler
+        .compile_linked(
+            "main.yx",
+            "main = {\n    print(\"shared\")\n}\n",
+            &[(
+                "greet",
+                "greet.yx",
+                "pub hello: () -> Void = {\n    print(\"shared\")\n}\n",
+            )],
+        )
+        .expect("modules should compile and link");
+    assert!(names(&linked).contains(&"greet.hello"));
+    let bytecode = CodegenContext::new(linked)
+        .generate()
+        .expect("codegen should succeed");
+    let shared = bytecode
+        .const_pool
+        .iter()
+        .filter(|c| **c == ConstValue::String("shared".to_string()))
+        .count();
+    assert_eq!(shared, 1);
+}
+
+#[test]
+fn test_codegen_of_many_functions_is_deterministic() {
+    let mut source = String::from("use std.assert\n\n");
+    for i in 0..64 {
+        source.push_str(&format!(
+            "word_{i}: () -> String = {{\n    return \"w{}\" + \"shared\"\n}}\n\n",
+            i % 8
+        ));
+    }
+    source.push_str("main = {\n");
+    for i in 0..64 {
+        source.push_str(&format!(
+            "    assert_eq(word_{i}(), \"w{}shared\")\n",
+            i % 8
+        ));
+    }
+    source.push_str("}\n");
+    let module = Compiler::new()
+        .compile("many.yx", &source)
+        .expect("source should compile");
+
+    let encode = |module: ModuleIR| {
+        let bytecode = CodegenContext::new(module)
+            .generate()
+            .expect("codegen should succeed");
+        let mut bytes = std::io::Cursor::new(Vec::new());
+        bytecode
+            .write_to(&mut bytes)
+            .expect("bytecode should serialize");
+        (bytecode, bytes.into_inner())
+    };
+    let (bytecode, first) = encode(module.clone());
+    let (_, second) = encode(module.clone());
+    assert_eq!(first, second);
+    let distinct: std::collections::HashSet<&ConstValue> = bytecode.const_pool.iter().collect();
+    assert_eq!(distinct.len(), bytecode.const_pool.len());
+    run(module);
+}
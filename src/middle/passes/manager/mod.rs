@@ -0,0 +1,245 @@
+//! 优化遍管理器
+//!
+//! 把 IR 优化遍组织成有序的流水线。每个遍有固定的名字（`string-builder`、`inline`、
+//! `const-fold`、`cse`、`escape`、`tail-call`、`tree-shake`），可以按优化级别取预设顺序，也可以由配置（`yaoxiang.toml` 的
+//! `[build] passes`）显式给出。运行时记录每个遍的耗时，供 `--timings` 输出。
+//!
+//! 预设：
+//! - `-O0`：只做尾调用降级（尾递归能否运行不应取决于优化级别）
+//! - `-O1`：循环内拼接改用字符串构建器、内联、常量折叠与传播、公共子表达式消除、尾调用降级、
+//!   死函数消除
+//! - `-O2` 及以上：同 `-O1`，内联阈值加倍，并在尾调用降级前做逃逸分析
+//!
+//! 字符串构建器改写依赖 IR 生成的形态，排在最前；内联在其后，使常量实参传入函数体后再折叠；逃逸分析在内联之后，被内联的构造函数的结果
+//! 才能留在调用者的栈区；尾调用降级在内联之后，避免内联看到 `TailCall`；死函数消除在最后，
+//! 删除被完全内联的函数。
+
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::frontend::config::{CompileConfig, OptLevel};
+use crate::middle::core::ir::ModuleIR;
+use crate::util::time_compat::Instant;
+
+/// 优化遍
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Pass {
+    /// 循环内反复拼接的字符串变量改用构建器
+    StringBuilder,
+    /// 函数内联
+    Inline,
+    /// 常量折叠与传播
+    ConstFold,
+    /// 公共子表达式消除
+    Cse,
+    /// 逃逸分析：不逃逸的结构体与列表改为栈上分配
+    Escape,
+    /// 尾调用降级
+    TailCall,
+    /// 死函数消除：删除从 `main` 或公开函数不可达的函数
+    TreeShake,
+}
+
+impl Pass {
+    /// 所有遍
+    pub const ALL: [Pass; 7] = [
+        Pass::StringBuilder,
+        Pass::Inline,
+        Pass::ConstFold,
+        Pass::Cse,
+        Pass::Escape,
+        Pass::TailCall,
+        Pass::TreeShake,
+    ];
+
+    /// 遍的名字
+    pub fn name(self) -> &'static str {
+        match self {
+            Pass::StringBuilder => "string-builder",
+            Pass::Inline => "inline",
+            Pass::ConstFold => "const-fold",
+            Pass::Cse => "cse",
+            Pass::Escape => "escape",
+            Pass::TailCall => "tail-call",
+            Pass::TreeShake => "tree-shake",
+        }
+    }
+}
+
+impl fmt::Display for Pass {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Pass {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Pass::ALL
+            .into_iter()
+            .find(|pass| pass.name() == s)
+            .ok_or_else(|| {
+                let names: Vec<&str> = Pass::ALL.iter().map(|pass| pass.name()).collect();
+                format!(
+                    "unknown pass `{}` (expected one of: {})",
+                    s,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+/// 单个遍的耗时
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PassTiming {
+    /// 遍
+    pub pass: Pass,
+    /// 耗时
+    pub duration: Duration,
+}
+
+/// 优化遍管理器
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PassManager {
+    /// 按顺序运行的遍
+    passes: Vec<Pass>,
+    /// 内联阈值（被调函数的最大指令数）
+    inline_threshold: usize,
+}
+
+impl PassManager {
+    /// 按给定顺序运行 `passes`
+    pub fn new(
+        passes: Vec<Pass>,
+        inline_threshold: usize,
+    ) -> Self {
+        Self {
+            passes,
+            inline_threshold,
+        }
+    }
+
+    /// 优化级别的预设
+    pub fn for_level(
+        level: OptLevel,
+        inline_threshold: usize,
+    ) -> Self {
+        match level {
+            OptLevel::O0 => Self::new(vec![Pass::TailCall], inline_threshold),
+            OptLevel::O1 => Self::new(
+                vec![
+                    Pass::StringBuilder,
+                    Pass::Inline,
+                    Pass::ConstFold,
+                    Pass::Cse,
+                    Pass::TailCall,
+                    Pass::TreeShake,
+                ],
+                inline_threshold,
+            ),
+            OptLevel::O2 | OptLevel::O3 | OptLevel::Auto => {
+                Self::new(Pass::ALL.to_vec(), inline_threshold * 2)
+            }
+        }
+    }
+
+    /// 编译配置对应的流水线：显式给出的 `passes` 优先于优化级别的预设，
+    /// 禁用内联、逃逸分析或死函数消除时去掉对应的遍
+    pub fn from_config(config: &CompileConfig) -> Self {
+        let mut manager = match &config.passes {
+            Some(passes) => Self::new(passes.clone(), config.inline.threshold),
+            None => Self::for_level(config.optimization_level, config.inline.threshold),
+        };
+        if !config.inline.enabled {
+            manager.passes.retain(|&pass| pass != Pass::Inline);
+        }
+        if !config.escape.enabled {
+            manager.passes.retain(|&pass| pass != Pass::Escape);
+        }
+        if !config.tree_shake.enabled {
+            manager.passes.retain(|&pass| pass != Pass::TreeShake);
+        }
+        manager
+    }
+
+    /// 按顺序运行的遍
+    pub fn passes(&self) -> &[Pass] {
+        &self.passes
+    }
+
+    /// 依次运行各遍，返回每个遍的耗时
+    pub fn run(
+        &self,
+        module: &mut ModuleIR,
+    ) -> Vec<PassTiming> {
+        self.passes
+            .iter()
+            .map(|&pass| {
+                let start = Instant::now();
+                self.run_pass(pass, module);
+                PassTiming {
+                    pass,
+                    duration: start.elapsed(),
+                }
+            })
+            .collect()
+    }
+
+    fn run_pass(
+        &self,
+        pass: Pass,
+        module: &mut ModuleIR,
+    ) {
+        match pass {
+            Pass::StringBuilder => {
+                super::string_builder::rewrite_module(module);
+            }
+            Pass::Inline => {
+                super::inline::inline_module(module, self.inline_threshold);
+            }
+            Pass::ConstFold => super::const_fold::fold_module(module),
+            Pass::Cse => super::cse::cse_module(module),
+            Pass::Escape => super::escape::escape_module(module),
+            Pass::TailCall => super::tail_call::lower_module(module),
+            Pass::TreeShake => {
+                super::tree_shake::shake_module(module);
+            }
+        }
+    }
+}
+
+/// 把各遍耗时排成表格，末行为合计
+pub fn format_timings(timings: &[PassTiming]) -> String {
+    let width = timings
+        .iter()
+        .map(|t| t.pass.name().len())
+        .chain(["total".len()])
+        .max()
+        .unwrap_or(0);
+    let mut out = String::new();
+    for timing in timings {
+        out.push_str(&format!(
+            "{:<width$}  {:>10.3}ms\n",
+            timing.pass.name(),
+            timing.duration.as_secs_f64() * 1000.0,
+        ));
+    }
+    let total: Duration = timings.iter().map(|t| t.duration).sum();
+    out.push_str(&format!(
+        "{:<width$}  {:>10.3}ms\n",
+        "total",
+        total.as_secs_f64() * 1000.0,
+    ));
+    out
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,143 @@
+//! 优化遍管理器测试
+//!
+//! 覆盖：
+//! - 遍名解析与未知遍名的报错
+//! - 各优化级别的预设，显式遍列表与禁用内联、逃逸分析、死函数消除
+//! - 编译时按预设运行并记录每个遍的耗时
+//! - 耗时表格
+
+use std::time::Duration;
+
+use super::{format_timings, Pass, PassManager, PassTiming};
+use crate::frontend::config::{CompileConfig, OptLevel};
+use crate::frontend::Compiler;
+
+const SOURCE: &str = "
+double: (x: Int) -> Int = (x) => x * 2
+
+main = {
+    print(double(21))
+}
+";
+
+fn names(manager: &PassManager) -> Vec<&'static str> {
+    manager.passes().iter().map(|pass| pass.name()).collect()
+}
+
+#[test]
+fn test_pass_names_round_trip() {
+    for pass in Pass::ALL {
+        assert_eq!(pass.name().parse::<Pass>(), Ok(pass));
+    }
+    let err = "dce".parse::<Pass>().unwrap_err();
+    assert!(err.contains("unknown pass `dce`"), "{}", err);
+    assert!(err.contains("const-fold"), "{}", err);
+}
+
+#[test]
+fn test_opt_level_parse() {
+    assert_eq!("0".parse::<OptLevel>(), Ok(OptLevel::O0));
+    assert_eq!("O2".parse::<OptLevel>(), Ok(OptLevel::O2));
+    assert!("4".parse::<OptLevel>().is_err());
+}
+
+#[test]
+fn test_level_presets() {
+    assert_eq!(
+        names(&PassManager::for_level(OptLevel::O0, 40)),
+        ["tail-call"]
+    );
+    assert_eq!(
+        names(&PassManager::for_level(OptLevel::O1, 40)),
+        [
+            "string-builder",
+            "inline",
+            "const-fold",
+            "cse",
+            "tail-call",
+            "tree-shake"
+        ]
+    );
+    // -O2 内联阈值加倍
+    assert_eq!(
+        PassManager::for_level(OptLevel::O2, 40),
+        PassManager::new(Pass::ALL.to_vec(), 80)
+    );
+}
+
+#[test]
+fn test_config_overrides_preset() {
+    let config = CompileConfig::new().with_passes(vec![Pass::Cse, Pass::ConstFold]);
+    assert_eq!(
+        names(&PassManager::from_config(&config)),
+        ["cse", "const-fold"]
+    );
+
+    let mut config = CompileConfig::new();
+    config.inline.enabled = false;
+    assert_eq!(
+        names(&PassManager::from_config(&config)),
+        [
+            "string-builder",
+            "const-fold",
+            "cse",
+            "tail-call",
+            "tree-shake"
+        ]
+    );
+
+    let config = CompileConfig::new()
+        .with_opt_level(OptLevel::O2)
+        .with_escape_analysis(false)
+        .with_tree_shaking(false);
+    assert_eq!(
+        names(&PassManager::from_config(&config)),
+        ["string-builder", "inline", "const-fold", "cse", "tail-call"]
+    );
+}
+
+#[test]
+fn test_compiler_records_pass_timings() {
+    let mut compiler = Compiler::with_config(CompileConfig::new().with_opt_level(OptLevel::O0));
+    compiler
+        .compile("o0.yx", SOURCE)
+        .expect("source should compile");
+    let passes: Vec<Pass> = compiler.pass_timings().iter().map(|t| t.pass).collect();
+    assert_eq!(passes, [Pass::TailCall]);
+
+    let mut compiler = Compiler::new();
+    compiler
+        .compile("o1.yx", SOURCE)
+        .expect("source should compile");
+    let passes: Vec<Pass> = compiler.pass_timings().iter().map(|t| t.pass).collect();
+    assert_eq!(
+        passes,
+        [
+            Pass::StringBuilder,
+            Pass::Inline,
+            Pass::ConstFold,
+            Pass::Cse,
+            Pass::TailCall,
+            Pass::TreeShake
+        ]
+    );
+}
+
+#[test]
+fn test_format_timings() {
+    let report = format_timings(&[
+        PassTiming {
+            pass: Pass::Inline,
+            duration: Duration::from_micros(1500),
+        },
+        PassTiming {
+            pass: Pass::TailCall,
+            duration: Duration::from_micros(500),
+        },
+    ]);
+    let lines: Vec<&str> = report.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].starts_with("inline") && lines[0].ends_with("1.500ms"));
+    assert!(lines[1].starts_with("tail-call") && lines[1].ends_with("0.500ms"));
+    assert!(lines[2].starts_with("total") && lines[2].ends_with("2.000ms"));
+}
@@ -2,9 +2,22 @@
 //!
 //! 包含中间层的各个编译阶段。
 
+pub mod closure;
 pub mod codegen;
+pub mod const_eval;
+pub mod const_fold;
+pub mod cse;
+pub mod decision_tree;
+pub mod escape;
+pub mod inline;
+pub mod link;
+pub mod manager;
 pub mod module;
 pub mod mono;
+pub mod ssa;
+pub mod string_builder;
+pub mod tail_call;
+pub mod tree_shake;
 
 // IR生成器实际在core模块中，直接re-export
 pub use crate::middle::core::ir_gen::*;
@@ -4,10 +4,10 @@
 
 use crate::frontend::core::parser::ast::Type as AstType;
 use crate::frontend::core::typecheck::MonoType;
-use crate::middle::core::ir::{BasicBlock, ConstValue, FunctionIR, Instruction, ModuleIR, Operand};
+use crate::middle::core::ir::{BasicBlock, FunctionIR, Instruction, ModuleIR};
 use crate::middle::passes::mono::instance::{FunctionId, GenericFunctionId, InstantiationRequest};
 use crate::util::diagnostic::Diagnostic;
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
 
 /// 函数单态化相关trait
 pub trait FunctionMonomorphizer {
@@ -29,42 +29,6 @@ pub trait FunctionMonomorphizer {
         func: &FunctionIR,
     ) -> Vec<String>;
 
-    /// 收集所有实例化请求
-    fn collect_instantiation_requests(
-        &mut self,
-        module: &ModuleIR,
-    );
-
-    /// 从指令中收集函数调用类型
-    fn collect_instruction_types(
-        &self,
-        instr: &Instruction,
-        all_call_type_names: &mut HashSet<String>,
-        all_generic_calls: &mut Vec<(String, Vec<MonoType>)>,
-    );
-
-    /// 将类型列表转换为唯一键字符串
-    fn types_to_key(types: &[MonoType]) -> String;
-
-    /// 从类型名字符串解析回MonoType列表
-    fn parse_type_names(key: &str) -> Vec<MonoType>;
-
-    /// 将类型名转换为MonoType
-    fn type_name_to_mono_type(name: &str) -> Option<MonoType>;
-
-    /// 将操作数转换为类型
-    fn operand_to_type(
-        &self,
-        operand: &Operand,
-    ) -> Option<MonoType>;
-
-    /// 根据收集到的类型参数为泛型函数排队实例化请求
-    fn queue_instantiations_for_types(
-        &mut self,
-        type_args: &[MonoType],
-        generic_calls: &[(String, Vec<MonoType>)],
-    );
-
     /// 添加实例化请求
     fn add_instantiation_request(
         &mut self,
@@ -171,167 +135,6 @@ impl FunctionMonomorphizer for super::Monomorphizer {
         func.generic_params.clone().unwrap_or_default()
     }
 
-    fn collect_instantiation_requests(
-        &mut self,
-        module: &ModuleIR,
-    ) {
-        let mut all_call_type_names: HashSet<String> = HashSet::new();
-        let mut all_generic_calls: Vec<(String, Vec<MonoType>)> = Vec::new();
-
-        for func in &module.functions {
-            for block in &func.blocks {
-                for instr in &block.instructions {
-                    self.collect_instruction_types(
-                        instr,
-                        &mut all_call_type_names,
-                        &mut all_generic_calls,
-                    );
-                }
-            }
-        }
-
-        for type_names in &all_call_type_names {
-            let type_args = Self::parse_type_names(type_names);
-            self.queue_instantiations_for_types(&type_args, &all_generic_calls);
-        }
-    }
-
-    fn collect_instruction_types(
-        &self,
-        instr: &Instruction,
-        all_call_type_names: &mut HashSet<String>,
-        all_generic_calls: &mut Vec<(String, Vec<MonoType>)>,
-    ) {
-        match instr {
-            Instruction::Call { func, args, .. } => {
-                let arg_types: Vec<MonoType> = args
-                    .iter()
-                    .filter_map(|a| self.operand_to_type(a))
-                    .collect();
-
-                if !arg_types.is_empty() {
-                    let type_key = Self::types_to_key(&arg_types);
-                    all_call_type_names.insert(type_key);
-
-                    if let Operand::Global(func_idx) = func {
-                        let func_name = format!("func_{}", func_idx);
-                        all_generic_calls.push((func_name, arg_types));
-                    } else if let Operand::Const(ConstValue::String(name)) = func {
-                        all_generic_calls.push((name.clone(), arg_types));
-                    }
-                }
-            }
-
-            Instruction::TailCall { func: _, args } => {
-                let arg_types: Vec<MonoType> = args
-                    .iter()
-                    .filter_map(|a| self.operand_to_type(a))
-                    .collect();
-                if !arg_types.is_empty() {
-                    let type_key = Self::types_to_key(&arg_types);
-                    all_call_type_names.insert(type_key);
-                }
-            }
-
-            Instruction::Ret(Some(operand)) => {
-                if let Some(ty) = self.operand_to_type(operand) {
-                    let type_key = Self::types_to_key(&[ty]);
-                    all_call_type_names.insert(type_key);
-                }
-            }
-            Instruction::Ret(None) => {}
-
-            Instruction::Move { dst, src } => {
-                if let (Some(dst_ty), Some(src_ty)) =
-                    (self.operand_to_type(dst), self.operand_to_type(src))
-                {
-                    if dst_ty != src_ty {
-                        let type_key = Self::types_to_key(&[dst_ty]);
-                        all_call_type_names.insert(type_key);
-                    }
-                }
-            }
-
-            Instruction::Load { dst, .. } => {
-                if let Some(ty) = self.operand_to_type(dst) {
-                    let type_key = Self::types_to_key(&[ty]);
-                    all_call_type_names.insert(type_key);
-                }
-            }
-
-            Instruction::Alloc { dst, .. } => {
-                if let Some(ty) = self.operand_to_type(dst) {
-                    let type_key = Self::types_to_key(&[ty]);
-                    all_call_type_names.insert(type_key);
-                }
-            }
-
-            _ => {}
-        }
-    }
-
-    fn types_to_key(types: &[MonoType]) -> String {
-        types
-            .iter()
-            .map(|t| t.type_name())
-            .collect::<Vec<_>>()
-            .join(",")
-    }
-
-    fn parse_type_names(key: &str) -> Vec<MonoType> {
-        if key.is_empty() {
-            return Vec::new();
-        }
-        key.split(',')
-            .filter_map(Self::type_name_to_mono_type)
-            .collect()
-    }
-
-    fn type_name_to_mono_type(name: &str) -> Option<MonoType> {
-        match name {
-            "Void" => Some(MonoType::Void),
-            "Bool" => Some(MonoType::Bool),
-            "Int64" => Some(MonoType::Int(64)),
-            "Int32" => Some(MonoType::Int(32)),
-            "Int16" => Some(MonoType::Int(16)),
-            "Int8" => Some(MonoType::Int(8)),
-            "Float64" => Some(MonoType::Float(64)),
-            "Float32" => Some(MonoType::Float(32)),
-            "Char" => Some(MonoType::Char),
-            "String" => Some(MonoType::String),
-            "Bytes" => Some(MonoType::Bytes),
-            _ => None,
-        }
-    }
-
-    fn operand_to_type(
-        &self,
-        operand: &Operand,
-    ) -> Option<MonoType> {
-        match operand {
-            Operand::Local(_id) => Some(MonoType::Int(64)),
-            Operand::Temp(_id) => Some(MonoType::Int(64)),
-            Operand::Arg(_id) => Some(MonoType::Int(64)),
-            Operand::Global(_id) => Some(MonoType::Int(64)),
-            Operand::Const(ConstValue::Int(_)) => Some(MonoType::Int(64)),
-            Operand::Const(ConstValue::Float(_)) => Some(MonoType::Float(64)),
-            Operand::Const(ConstValue::Bool(_)) => Some(MonoType::Bool),
-            Operand::Const(ConstValue::String(_)) => Some(MonoType::String),
-            Operand::Const(ConstValue::Char(_)) => Some(MonoType::Char),
-            Operand::Const(ConstValue::Void) => Some(MonoType::Void),
-            _ => None,
-        }
-    }
-
-    fn queue_instantiations_for_types(
-        &mut self,
-        _type_args: &[MonoType],
-        _generic_calls: &[(String, Vec<MonoType>)],
-    ) {
-        // 在新的队列驱动结构中，这个方法主要用于兼容旧接口
-        // 实际的队列操作在 monomorphize 方法中完成
-    }
-
     fn add_instantiation_request(
         &mut self,
         generic_id: GenericFunctionId,
@@ -525,9 +328,17 @@ impl FunctionMonomorphizer for super::Monomorphizer {
                     target_type: new_target,
                 }
             }
-            Instruction::TypeTest(operand, test_type) => {
-                let new_test_type = self.substitute_type_ast(test_type, type_map);
-                Instruction::TypeTest(operand.clone(), new_test_type)
+            Instruction::TypeTest {
+                dst,
+                src,
+                target_type,
+            } => {
+                let new_target = self.substitute_type_ast(target_type, type_map);
+                Instruction::TypeTest {
+                    dst: dst.clone(),
+                    src: src.clone(),
+                    target_type: new_target,
+                }
             }
             _ => instr.clone(),
         }
@@ -563,6 +374,7 @@ impl FunctionMonomorphizer for super::Monomorphizer {
                         is_mut: f.is_mut,
                         ty: self.substitute_type_ast(&f.ty, type_map),
                         default: f.default.clone(),
+                        is_assoc: f.is_assoc,
                     })
                     .collect(),
                 bindings: bindings.clone(),
@@ -584,6 +396,7 @@ impl FunctionMonomorphizer for super::Monomorphizer {
                         is_mut: f.is_mut,
                         ty: self.substitute_type_ast(&f.ty, type_map),
                         default: f.default.clone(),
+                        is_assoc: f.is_assoc,
                     })
                     .collect(),
             },
@@ -700,6 +513,9 @@ impl FunctionMonomorphizer for super::Monomorphizer {
             AstType::Ptr(inner) => {
                 AstType::Ptr(Box::new(self.substitute_type_ast(inner, type_map)))
             }
+            AstType::Newtype(inner) => {
+                AstType::Newtype(Box::new(self.substitute_type_ast(inner, type_map)))
+            }
 
             // 元类型：直接返回
             AstType::MetaType { .. } => ty.clone(),
@@ -727,6 +543,7 @@ impl FunctionMonomorphizer for super::Monomorphizer {
         }
         ModuleIR {
             types: original_module.types.clone(),
+            generic_types: original_module.generic_types.clone(),
             globals: original_module.globals.clone(),
             functions: output_funcs,
             mut_locals: original_module.mut_locals.clone(),
@@ -734,6 +551,11 @@ impl FunctionMonomorphizer for super::Monomorphizer {
             local_names: original_module.local_names.clone(),
             ffi_libs: original_module.ffi_libs.clone(),
             ffi_bindings: original_module.ffi_bindings.clone(),
+            vtables: original_module.vtables.clone(),
+            struct_layouts: original_module.struct_layouts.clone(),
+            inline_hints: original_module.inline_hints.clone(),
+            exports: original_module.exports.clone(),
+            upvalue_counts: original_module.upvalue_counts.clone(),
         }
     }
 }
@@ -2,10 +2,11 @@
 //!
 //! 单态化过程中的工作单元定义
 
+use crate::frontend::core::typecheck::inference::numeric::numeric_type_named;
 use crate::frontend::core::typecheck::MonoType;
 use crate::util::span::Span;
 use std::fmt;
-use std::hash::{Hash, Hasher};
+use std::hash::Hash;
 use std::sync::Arc;
 
 /// 实例化请求
@@ -19,8 +20,14 @@ pub struct InstantiationRequest {
     /// 类型参数列表
     pub type_args: Vec<MonoType>,
 
-    /// 实例化来源（用于调试和追踪）
+    /// 实例化来源：调用点位置，单态化器据此改写对应的调用指令
     pub source_location: Span,
+
+    /// 调用点所在的泛型函数
+    ///
+    /// 泛型函数体内的调用以所在函数的类型参数（`TypeRef("T")`）表示类型实参，
+    /// 在所在函数被特化时代入具体类型；顶层调用为 `None`。
+    pub caller: Option<String>,
 }
 
 impl InstantiationRequest {
@@ -34,9 +41,19 @@ impl InstantiationRequest {
             generic_id,
             type_args,
             source_location,
+            caller: None,
         }
     }
 
+    /// 标记请求位于泛型函数 `caller` 的函数体内
+    pub fn with_caller(
+        mut self,
+        caller: impl Into<String>,
+    ) -> Self {
+        self.caller = Some(caller.into());
+        self
+    }
+
     /// 获取泛型函数ID
     pub fn generic_id(&self) -> &GenericFunctionId {
         &self.generic_id
@@ -55,8 +72,9 @@ impl InstantiationRequest {
 
 /// 特化缓存键
 ///
-/// 用于在缓存中唯一标识一个特化版本
-#[derive(Debug, Clone)]
+/// 用于在缓存中唯一标识一个特化版本；类型保存为规范形式（见 [`canonical_type`]），
+/// 相等与哈希按结构进行。
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct SpecializationKey {
     /// 函数/类型名称
     pub name: String,
@@ -75,7 +93,7 @@ impl SpecializationKey {
         SpecializationKey {
             name,
             param_types: Vec::new(),
-            type_args,
+            type_args: canonical_types(&type_args),
         }
     }
 
@@ -87,8 +105,8 @@ impl SpecializationKey {
     ) -> Self {
         SpecializationKey {
             name,
-            param_types,
-            type_args,
+            param_types: canonical_types(&param_types),
+            type_args: canonical_types(&type_args),
         }
     }
 
@@ -121,203 +139,110 @@ impl fmt::Display for SpecializationKey {
     }
 }
 
-impl PartialEq for SpecializationKey {
-    fn eq(
-        &self,
-        other: &Self,
-    ) -> bool {
-        self.name == other.name
-            && self.param_types == other.param_types
-            && self.type_args == other.type_args
+// ==================== 辅助函数 ====================
+
+/// 缓存键使用的类型规范形式
+///
+/// - 内置类型名归一为基本类型：`TypeRef("Int")` 与 `Int(64)` 相同
+/// - 具名结构体、枚举只按名称区分，与同名 `TypeRef` 相同
+/// - `Generic { List, [T] }` 等内置容器写法归一为对应构造器
+/// - 精化类型取基础类型（约束不影响生成的代码）
+///
+/// 其余类型逐层规范化。缓存键在规范形式上直接派生 `Hash`/`Eq`，
+/// 嵌套泛型按结构区分，不会像拼接类型名那样碰撞，哈希时也不再分配字符串。
+pub fn canonical_type(ty: &MonoType) -> MonoType {
+    let boxed = |t: &MonoType| Box::new(canonical_type(t));
+    match ty {
+        MonoType::TypeRef(name) => builtin_type_named(name).unwrap_or_else(|| ty.clone()),
+        MonoType::Struct(s) if !s.name.is_empty() => MonoType::TypeRef(s.name.clone()),
+        MonoType::Enum(e) => MonoType::TypeRef(e.name.clone()),
+        MonoType::Refined { base, .. } => canonical_type(base),
+        MonoType::Generic { name, args } => match (name.as_str(), args.as_slice()) {
+            ("List", [t]) => MonoType::List(boxed(t)),
+            ("Set", [t]) => MonoType::Set(boxed(t)),
+            ("Option", [t]) => MonoType::Option(boxed(t)),
+            ("Dict", [k, v]) => MonoType::Dict(boxed(k), boxed(v)),
+            ("Result", [ok, err]) => MonoType::Result(boxed(ok), boxed(err)),
+            _ => MonoType::Generic {
+                name: name.clone(),
+                args: canonical_types(args),
+            },
+        },
+        MonoType::List(t) => MonoType::List(boxed(t)),
+        MonoType::Set(t) => MonoType::Set(boxed(t)),
+        MonoType::Option(t) => MonoType::Option(boxed(t)),
+        MonoType::Arc(t) => MonoType::Arc(boxed(t)),
+        MonoType::Weak(t) => MonoType::Weak(boxed(t)),
+        MonoType::Async(t) => MonoType::Async(boxed(t)),
+        MonoType::Range { elem_type } => MonoType::Range {
+            elem_type: boxed(elem_type),
+        },
+        MonoType::Ref { mutable, inner } => MonoType::Ref {
+            mutable: *mutable,
+            inner: boxed(inner),
+        },
+        MonoType::Dict(k, v) => MonoType::Dict(boxed(k), boxed(v)),
+        MonoType::Result(ok, err) => MonoType::Result(boxed(ok), boxed(err)),
+        MonoType::Tuple(types) => MonoType::Tuple(canonical_types(types)),
+        MonoType::Union(types) => MonoType::Union(canonical_types(types)),
+        MonoType::Intersection(types) => MonoType::Intersection(canonical_types(types)),
+        MonoType::Fn {
+            params,
+            return_type,
+        } => MonoType::Fn {
+            params: canonical_types(params),
+            return_type: boxed(return_type),
+        },
+        MonoType::AssocType {
+            host_type,
+            assoc_name,
+            assoc_args,
+        } => MonoType::AssocType {
+            host_type: boxed(host_type),
+            assoc_name: assoc_name.clone(),
+            assoc_args: canonical_types(assoc_args),
+        },
+        _ => ty.clone(),
     }
 }
 
-impl Eq for SpecializationKey {}
+fn canonical_types(types: &[MonoType]) -> Vec<MonoType> {
+    types.iter().map(canonical_type).collect()
+}
 
-impl Hash for SpecializationKey {
-    fn hash<H: Hasher>(
-        &self,
-        state: &mut H,
-    ) {
-        self.name.hash(state);
-        for ty in &self.param_types {
-            type_name_hash(ty, state);
-        }
-        for ty in &self.type_args {
-            type_name_hash(ty, state);
-        }
+/// 内置类型名对应的基本类型
+fn builtin_type_named(name: &str) -> Option<MonoType> {
+    match name {
+        "Bool" => Some(MonoType::Bool),
+        "Char" => Some(MonoType::Char),
+        "String" => Some(MonoType::String),
+        "Bytes" => Some(MonoType::Bytes),
+        "Void" => Some(MonoType::Void),
+        _ => numeric_type_named(name),
     }
 }
 
-// ==================== 辅助函数 ====================
+/// 泛型参数约束：`(T: Show + Clone)` 中的一项
+///
+/// `methods` 是用户接口要求的方法名；标准库 trait（Clone、Equal 等）为空，
+/// 单态化时按 `TraitTable` 判定。
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GenericBound {
+    /// 约束名称
+    pub name: String,
+    /// 接口要求的方法/关联项名称
+    pub methods: Vec<String>,
+}
 
-/// 计算 MonoType 的哈希值
-#[allow(clippy::only_used_in_recursion)]
-fn type_name_hash<H: Hasher>(
-    ty: &MonoType,
-    state: &mut H,
-) {
-    match ty {
-        MonoType::Void => "void".hash(state),
-        MonoType::Bool => "bool".hash(state),
-        MonoType::Int(n) => format!("int{}", n).hash(state),
-        MonoType::Float(n) => format!("float{}", n).hash(state),
-        MonoType::Char => "char".hash(state),
-        MonoType::String => "string".hash(state),
-        MonoType::Bytes => "bytes".hash(state),
-        MonoType::Struct(s) => s.name.hash(state),
-        MonoType::Enum(e) => e.name.hash(state),
-        MonoType::Tuple(ts) => {
-            "tuple".hash(state);
-            for t in ts {
-                type_name_hash(t, state);
-            }
-        }
-        MonoType::List(t) => {
-            "list".hash(state);
-            type_name_hash(t, state);
-        }
-        MonoType::Dict(k, v) => {
-            "dict".hash(state);
-            type_name_hash(k, state);
-            type_name_hash(v, state);
-        }
-        MonoType::Set(t) => {
-            "set".hash(state);
-            type_name_hash(t, state);
-        }
-        MonoType::Option(t) => {
-            "option".hash(state);
-            type_name_hash(t, state);
-        }
-        MonoType::Result(ok, err) => {
-            "result".hash(state);
-            type_name_hash(ok, state);
-            type_name_hash(err, state);
-        }
-        MonoType::Fn { .. } => "fn".hash(state),
-        MonoType::Range { elem_type } => {
-            "range".hash(state);
-            type_name_hash(elem_type, state);
-        }
-        MonoType::TypeVar(v) => format!("var{}", v.index()).hash(state),
-        MonoType::TypeRef(n) => n.hash(state),
-        // 联合类型和交集类型使用 TypeRef 的哈希方式
-        MonoType::Union(types) => {
-            "union".hash(state);
-            for t in types {
-                type_name_hash(t, state);
-            }
-        }
-        MonoType::Intersection(types) => {
-            "intersection".hash(state);
-            for t in types {
-                type_name_hash(t, state);
-            }
-        }
-        MonoType::Arc(t) => {
-            "arc".hash(state);
-            type_name_hash(t, state);
-        }
-        MonoType::Weak(t) => {
-            "weak".hash(state);
-            type_name_hash(t, state);
-        }
-        MonoType::Ref { mutable, inner } => {
-            "ref".hash(state);
-            mutable.hash(state);
-            type_name_hash(inner, state);
-        }
-        MonoType::AssocType {
-            host_type,
-            assoc_name,
-            assoc_args,
-        } => {
-            "assoc_type".hash(state);
-            type_name_hash(host_type, state);
-            assoc_name.hash(state);
-            for t in assoc_args {
-                type_name_hash(t, state);
-            }
-        }
-        MonoType::Literal {
-            name,
-            base_type,
-            value,
-        } => {
-            "literal".hash(state);
-            name.hash(state);
-            type_name_hash(base_type, state);
-            // 哈希常量值
-            match value {
-                crate::frontend::core::types::ConstValue::Int(n) => {
-                    format!("int:{}", n).hash(state);
-                }
-                crate::frontend::core::types::ConstValue::Bool(b) => {
-                    format!("bool:{}", b).hash(state);
-                }
-                crate::frontend::core::types::ConstValue::Float(f) => {
-                    format!("float:{}", f.to_bits()).hash(state);
-                }
-                crate::frontend::core::types::ConstValue::LibraryRef { mechanism, lib } => {
-                    format!("library_ref:{mechanism}:{lib}").hash(state);
-                }
-                crate::frontend::core::types::ConstValue::ExternRef {
-                    mechanism,
-                    lib,
-                    symbol,
-                } => {
-                    format!("extern_ref:{mechanism}:{lib}:{symbol}").hash(state);
-                }
-            }
-        }
-        MonoType::MetaType {
-            universe_level,
-            type_params,
-        } => {
-            "meta_type".hash(state);
-            universe_level.hash(state);
-            for p in type_params {
-                p.hash(state);
-            }
-        }
-        MonoType::Generic { name, args } => {
-            "generic".hash(state);
-            name.hash(state);
-            for arg in args {
-                type_name_hash(arg, state);
-            }
-        }
-        MonoType::Refined { base, constraint } => {
-            "refined".hash(state);
-            type_name_hash(base, state);
-            constraint.hash(state);
-        }
-        MonoType::DepFn {
-            params,
-            return_type,
-        } => {
-            "depfn".hash(state);
-            for p in params {
-                p.name.hash(state);
-                type_name_hash(&p.ty, state);
-            }
-            type_name_hash(return_type, state);
-        }
-        MonoType::LibraryRef { mechanism, lib } => {
-            "library_ref".hash(state);
-            mechanism.hash(state);
-            lib.hash(state);
-        }
-        MonoType::ExternRef {
-            mechanism,
-            lib,
-            symbol,
-        } => {
-            "extern_ref".hash(state);
-            mechanism.hash(state);
-            lib.hash(state);
-            symbol.hash(state);
+impl GenericBound {
+    /// 创建新的约束
+    pub fn new(
+        name: impl Into<String>,
+        methods: Vec<String>,
+    ) -> Self {
+        GenericBound {
+            name: name.into(),
+            methods,
         }
     }
 }
@@ -334,6 +259,8 @@ pub struct GenericFunctionId {
     param_types: Vec<MonoType>,
     /// 泛型参数列表（用于泛型实例化）
     type_params: Vec<String>,
+    /// 各泛型参数的约束（与 type_params 按下标对应，空表示无约束）
+    bounds: Vec<Vec<GenericBound>>,
 }
 
 impl GenericFunctionId {
@@ -346,6 +273,7 @@ impl GenericFunctionId {
             name,
             param_types: Vec::new(),
             type_params,
+            bounds: Vec::new(),
         }
     }
 
@@ -359,9 +287,19 @@ impl GenericFunctionId {
             name,
             param_types,
             type_params,
+            bounds: Vec::new(),
         }
     }
 
+    /// 附加泛型参数约束
+    pub fn with_bounds(
+        mut self,
+        bounds: Vec<Vec<GenericBound>>,
+    ) -> Self {
+        self.bounds = bounds;
+        self
+    }
+
     /// 获取函数名称
     pub fn name(&self) -> &str {
         &self.name
@@ -382,6 +320,19 @@ impl GenericFunctionId {
         &self.type_params
     }
 
+    /// 获取各泛型参数的约束
+    pub fn bounds(&self) -> &[Vec<GenericBound>] {
+        &self.bounds
+    }
+
+    /// 获取第 `index` 个泛型参数的约束
+    pub fn bounds_of(
+        &self,
+        index: usize,
+    ) -> &[GenericBound] {
+        self.bounds.get(index).map(Vec::as_slice).unwrap_or(&[])
+    }
+
     /// 获取完整的签名
     pub fn signature(&self) -> String {
         let param_str = if self.param_types.is_empty() {
@@ -400,7 +351,21 @@ impl GenericFunctionId {
         let generic_str = if self.type_params.is_empty() {
             String::new()
         } else {
-            format!("({})", self.type_params.join(", "))
+            let params: Vec<String> = self
+                .type_params
+                .iter()
+                .enumerate()
+                .map(|(i, p)| {
+                    let bounds = self.bounds_of(i);
+                    if bounds.is_empty() {
+                        p.clone()
+                    } else {
+                        let names: Vec<&str> = bounds.iter().map(|b| b.name.as_str()).collect();
+                        format!("{}: {}", p, names.join(" + "))
+                    }
+                })
+                .collect();
+            format!("({})", params.join(", "))
         };
 
         format!("{}{}{}", self.name, param_str, generic_str)
@@ -533,7 +498,7 @@ impl FunctionInstance {
 /// 函数ID
 ///
 /// 用于唯一标识一个已特化的函数
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct FunctionId {
     /// 函数名称
     name: String,
@@ -547,7 +512,10 @@ impl FunctionId {
         name: String,
         type_args: Vec<MonoType>,
     ) -> Self {
-        FunctionId { name, type_args }
+        FunctionId {
+            name,
+            type_args: canonical_types(&type_args),
+        }
     }
 
     /// 获取函数名称
@@ -576,18 +544,6 @@ impl FunctionId {
     }
 }
 
-impl std::hash::Hash for FunctionId {
-    fn hash<H: std::hash::Hasher>(
-        &self,
-        state: &mut H,
-    ) {
-        self.name.hash(state);
-        for ty in &self.type_args {
-            ty.type_name().hash(state);
-        }
-    }
-}
-
 impl fmt::Display for FunctionId {
     fn fmt(
         &self,
@@ -600,7 +556,7 @@ impl fmt::Display for FunctionId {
 /// 类型ID
 ///
 /// 用于唯一标识一个已特化的类型
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct TypeId {
     /// 类型名称
     name: String,
@@ -614,7 +570,10 @@ impl TypeId {
         name: String,
         type_args: Vec<MonoType>,
     ) -> Self {
-        TypeId { name, type_args }
+        TypeId {
+            name,
+            type_args: canonical_types(&type_args),
+        }
     }
 
     /// 获取类型名称
@@ -652,18 +611,6 @@ impl fmt::Display for TypeId {
     }
 }
 
-impl std::hash::Hash for TypeId {
-    fn hash<H: std::hash::Hasher>(
-        &self,
-        state: &mut H,
-    ) {
-        self.name.hash(state);
-        for ty in &self.type_args {
-            ty.type_name().hash(state);
-        }
-    }
-}
-
 /// 类型实例
 ///
 /// 表示一个泛型类型被特化后的具体类型
@@ -812,7 +759,7 @@ impl fmt::Display for GenericClosureId {
 /// 闭包ID
 ///
 /// 用于唯一标识一个已特化的闭包
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct ClosureId {
     /// 闭包名称
     name: String,
@@ -831,8 +778,8 @@ impl ClosureId {
     ) -> Self {
         ClosureId {
             name,
-            type_args,
-            capture_types,
+            type_args: canonical_types(&type_args),
+            capture_types: canonical_types(&capture_types),
         }
     }
 
@@ -881,21 +828,6 @@ impl ClosureId {
     }
 }
 
-impl std::hash::Hash for ClosureId {
-    fn hash<H: std::hash::Hasher>(
-        &self,
-        state: &mut H,
-    ) {
-        self.name.hash(state);
-        for ty in &self.type_args {
-            ty.type_name().hash(state);
-        }
-        for ty in &self.capture_types {
-            ty.type_name().hash(state);
-        }
-    }
-}
-
 impl fmt::Display for ClosureId {
     fn fmt(
         &self,
@@ -944,7 +876,7 @@ impl ClosureInstance {
 /// 闭包特化缓存键
 ///
 /// 用于在缓存中唯一标识一个闭包特化版本
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct ClosureSpecializationKey {
     /// 闭包名称
     pub name: String,
@@ -963,8 +895,8 @@ impl ClosureSpecializationKey {
     ) -> Self {
         ClosureSpecializationKey {
             name,
-            type_args,
-            capture_types,
+            type_args: canonical_types(&type_args),
+            capture_types: canonical_types(&capture_types),
         }
     }
 
@@ -998,21 +930,6 @@ impl ClosureSpecializationKey {
     }
 }
 
-impl Hash for ClosureSpecializationKey {
-    fn hash<H: Hasher>(
-        &self,
-        state: &mut H,
-    ) {
-        self.name.hash(state);
-        for ty in &self.type_args {
-            type_name_hash(ty, state);
-        }
-        for ty in &self.capture_types {
-            type_name_hash(ty, state);
-        }
-    }
-}
-
 impl fmt::Display for ClosureSpecializationKey {
     fn fmt(
         &self,
@@ -6,15 +6,17 @@
 //! 2. 队列驱动：BFS 处理实例化请求，自动处理嵌套泛型调用
 
 use std::collections::{HashMap, HashSet, VecDeque};
-use crate::util::diagnostic::Diagnostic;
+use crate::util::diagnostic::{Diagnostic, ErrorCodeDefinition};
+use crate::util::span::Span;
 
 pub mod function;
 pub mod instance;
 pub mod type_mono;
 
 use function::FunctionMonomorphizer;
-use instance::{GenericFunctionId, InstantiationRequest, SpecializationKey, TypeId};
+use instance::{GenericBound, InstantiationRequest, SpecializationKey, TypeId};
 use crate::frontend::core::typecheck::MonoType;
+use crate::frontend::core::types::TraitTable;
 use crate::middle::core::ir::{BasicBlock, ConstValue, FunctionIR, Instruction, ModuleIR, Operand};
 
 /// 单态化器
@@ -34,6 +36,14 @@ pub struct Monomorphizer {
     /// 已单态化的类型：TypeId -> MonoType
     #[allow(dead_code)]
     monomorphized_types: HashMap<TypeId, MonoType>,
+    /// 泛型函数的参数约束：函数名 -> 各泛型参数的约束（从实例化请求收集）
+    generic_bounds: HashMap<String, Vec<Vec<GenericBound>>>,
+    /// 泛型函数体内的实例化请求：所在函数名 -> 请求（类型实参以所在函数的类型参数表示）
+    body_requests: HashMap<String, Vec<InstantiationRequest>>,
+    /// 模块中已定义的函数名（用于判定接口方法 `Type.method` 是否存在）
+    known_functions: HashSet<String>,
+    /// trait 表（判定标准库 trait 约束）
+    trait_table: TraitTable,
 }
 
 impl Monomorphizer {
@@ -46,6 +56,10 @@ impl Monomorphizer {
             max_depth: 100,
             generic_types: HashMap::new(),
             monomorphized_types: HashMap::new(),
+            generic_bounds: HashMap::new(),
+            body_requests: HashMap::new(),
+            known_functions: HashSet::new(),
+            trait_table: TraitTable::with_std(),
         }
     }
 
@@ -56,51 +70,203 @@ impl Monomorphizer {
         }
     }
 
+    /// 设置泛型函数的参数约束表（类型检查阶段产出）
+    pub fn with_generic_bounds(
+        mut self,
+        generic_bounds: HashMap<String, Vec<Vec<GenericBound>>>,
+    ) -> Self {
+        self.generic_bounds = generic_bounds;
+        self
+    }
+
+    /// 使用类型检查阶段的 trait 表判定约束
+    pub fn with_trait_table(
+        mut self,
+        trait_table: TraitTable,
+    ) -> Self {
+        self.trait_table = trait_table;
+        self
+    }
+
     /// 核心入口：单态化 ModuleIR
     ///
+    /// 消费传入的模块：泛型函数移入模板表，非泛型函数原样留在输出模块中，
+    /// 函数体不做整体复制。
+    ///
     /// # Errors
     /// 当单态化实例化深度超过 `max_depth` 时返回 `Diagnostic` 错误，
-    /// 表明可能存在无限泛型递归（例如泛型函数无限递归调用自身）。
+    /// 表明可能存在无限泛型递归（例如泛型函数无限递归调用自身）；
+    /// 当类型实参不满足泛型参数约束时返回 E4001，位置为实例化调用点。
     pub fn monomorphize(
         &mut self,
-        module: &ModuleIR,
+        mut module: ModuleIR,
         requests: &[InstantiationRequest],
     ) -> Result<ModuleIR, Diagnostic> {
         // 1. 收集泛型函数定义
-        self.collect_generic_functions(module);
+        let functions = std::mem::take(&mut module.functions);
+        self.known_functions = functions.iter().map(|f| f.name.clone()).collect();
+        module.functions = self.collect_generic_functions(functions);
+        self.collect_generic_bounds(requests);
 
-        // 2. 收集泛型类型定义
-        self.collect_generic_types(module);
+        // 2. 收集泛型类型定义，按类型表中的实例特化
+        self.collect_generic_types(&module);
+        self.specialize_types(&mut module);
 
-        // 3. 初始化队列
+        // 3. 初始化队列：顶层调用直接入队，泛型函数体内的调用留到特化所在函数时
+        let mut top_level = Vec::new();
         for req in requests {
-            self.pending_queue.push_back(req.clone());
+            match &req.caller {
+                Some(caller) => self
+                    .body_requests
+                    .entry(caller.clone())
+                    .or_default()
+                    .push(req.clone()),
+                None => {
+                    self.pending_queue.push_back(req.clone());
+                    top_level.push(req.clone());
+                }
+            }
         }
 
         // 4. 队列循环（BFS）
         self.process_queue()?;
 
         // 5. 构建输出
-        let mut output = self.build_output(module);
+        self.build_output(&mut module);
 
         // 6. 替换调用点
-        self.replace_call_sites(&mut output, requests);
+        self.replace_call_sites(&mut module, &top_level);
 
-        Ok(output)
+        Ok(module)
     }
 
+    /// 把泛型函数移入模板表，返回剩下的非泛型函数
     fn collect_generic_functions(
         &mut self,
-        module: &ModuleIR,
+        functions: Vec<FunctionIR>,
+    ) -> Vec<FunctionIR> {
+        let (generic, concrete): (Vec<_>, Vec<_>) = functions
+            .into_iter()
+            .partition(|f| f.generic_params.is_some());
+        for func in generic {
+            self.generic_functions.insert(func.name.clone(), func);
+        }
+        concrete
+    }
+
+    /// 记录请求携带的泛型参数约束，供嵌套实例化复用
+    fn collect_generic_bounds(
+        &mut self,
+        requests: &[InstantiationRequest],
     ) {
-        for func in &module.functions {
-            if func.generic_params.is_some() {
+        for req in requests {
+            let id = req.generic_id();
+            if id.bounds().iter().any(|b| !b.is_empty()) {
+                self.generic_bounds
+                    .entry(id.name().to_string())
+                    .or_insert_with(|| id.bounds().to_vec());
+            }
+        }
+    }
+
+    /// 校验实例化请求的类型实参满足泛型参数约束
+    fn check_bounds(
+        &self,
+        req: &InstantiationRequest,
+    ) -> Result<(), Diagnostic> {
+        let id = req.generic_id();
+        let bounds = if id.bounds().is_empty() {
+            match self.generic_bounds.get(id.name()) {
+                Some(bounds) => bounds.as_slice(),
+                None => return Ok(()),
+            }
+        } else {
+            id.bounds()
+        };
+
+        for (index, (param_bounds, type_arg)) in bounds.iter().zip(req.type_args()).enumerate() {
+            // 泛型函数体内的请求可能仍带类型变量、类型参数名或以接口代表类型参数，
+            // 留给具体实例化时检查
+            if self.is_abstract(type_arg) {
+                continue;
+            }
+            for bound in param_bounds {
+                if self.satisfies_bound(type_arg, bound) {
+                    continue;
+                }
+                let param = id
+                    .type_params()
+                    .get(index)
+                    .map(String::as_str)
+                    .unwrap_or("T");
+                return Err(ErrorCodeDefinition::trait_bound_not_satisfied(
+                    &format!(
+                        "{} (required by `{}` for `{}: {}`)",
+                        type_arg.type_name(),
+                        id.name(),
+                        param,
+                        bound.name
+                    ),
+                    &bound.name,
+                )
+                .at(req.source_location)
+                .build());
+            }
+        }
+        Ok(())
+    }
+
+    /// 类型实参是否仍是抽象的（类型变量、泛型参数名、约束接口本身）
+    fn is_abstract(
+        &self,
+        ty: &MonoType,
+    ) -> bool {
+        match ty {
+            MonoType::TypeVar(_) => true,
+            MonoType::TypeRef(name) => {
                 self.generic_functions
-                    .insert(func.name.clone(), func.clone());
+                    .values()
+                    .filter_map(|f| f.generic_params.as_ref())
+                    .any(|params| params.contains(name))
+                    || self.is_interface_name(name)
             }
+            other => other.is_constraint(),
         }
     }
 
+    fn is_interface_name(
+        &self,
+        name: &str,
+    ) -> bool {
+        self.generic_bounds
+            .values()
+            .flatten()
+            .flatten()
+            .any(|bound| !bound.methods.is_empty() && bound.name == name)
+    }
+
+    /// 判定类型是否满足单个约束
+    ///
+    /// 标准库 trait 查 trait 表（结构体可自动派生）；用户接口要求每个方法
+    /// 都有 `Type.method` 实现或同名函数字段；无法判定的约束视为满足。
+    fn satisfies_bound(
+        &self,
+        ty: &MonoType,
+        bound: &GenericBound,
+    ) -> bool {
+        if self.trait_table.has_trait(&bound.name) {
+            return self.trait_table.satisfies(&bound.name, ty)
+                || matches!(ty, MonoType::Struct(s)
+                    if self.trait_table.can_auto_derive_for_monotype(&bound.name, s));
+        }
+        let type_name = ty.type_name();
+        bound.methods.iter().all(|method| {
+            let qualified = format!("{}.{}", type_name, method);
+            self.known_functions.contains(&qualified)
+                || matches!(ty, MonoType::Struct(s) if s.fields.iter().any(|(name, _)| name == method))
+        })
+    }
+
     fn process_queue(&mut self) -> Result<(), Diagnostic> {
         let mut depth: usize = 0;
         while let Some(req) = self.pending_queue.pop_front() {
@@ -121,11 +287,12 @@ impl Monomorphizer {
             if self.processed.contains(&key) {
                 continue;
             }
+            self.check_bounds(&req)?;
             self.processed.insert(key);
             depth += 1;
 
-            if let Some(specialized) = self.specialize_function(&req) {
-                self.scan_for_new_calls(&specialized);
+            if let Some(mut specialized) = self.specialize_function(&req) {
+                self.scan_for_new_calls(&mut specialized, &req);
                 self.specialized_functions
                     .insert(specialized.name.clone(), specialized);
             }
@@ -133,25 +300,18 @@ impl Monomorphizer {
         Ok(())
     }
 
+    /// 把特化函数按名称顺序追加到输出模块（移出，不复制），保证每次输出一致
     fn build_output(
-        &self,
-        module: &ModuleIR,
-    ) -> ModuleIR {
-        let mut functions: Vec<FunctionIR> = module
-            .functions
-            .iter()
-            .filter(|f| f.generic_params.is_none())
-            .cloned()
+        &mut self,
+        module: &mut ModuleIR,
+    ) {
+        let mut specialized: Vec<FunctionIR> = self
+            .specialized_functions
+            .drain()
+            .map(|(_, func)| func)
             .collect();
-
-        for func in self.specialized_functions.values() {
-            functions.push(func.clone());
-        }
-
-        ModuleIR {
-            functions,
-            ..module.clone()
-        }
+        specialized.sort_by(|a, b| a.name.cmp(&b.name));
+        module.functions.extend(specialized);
     }
 
     /// 特化单个函数：将泛型函数按类型参数替换为具体函数
@@ -174,41 +334,38 @@ impl Monomorphizer {
             .map(|i| (i, type_args[i].clone()))
             .collect();
 
+        // IR 生成可能直接以类型参数名（TypeRef("T")）标注类型
+        let substitute = |ty: &MonoType| match ty {
+            MonoType::TypeRef(name) => type_params
+                .iter()
+                .position(|p| p == name)
+                .map(|i| type_args[i].clone())
+                .unwrap_or_else(|| ty.clone()),
+            _ => self.substitute_single_type(ty, &type_map),
+        };
+
         // 替换参数类型
-        let new_params: Vec<MonoType> = generic
-            .params
-            .iter()
-            .map(|ty| self.substitute_single_type(ty, &type_map))
-            .collect();
+        let new_params: Vec<MonoType> = generic.params.iter().map(substitute).collect();
 
         // 替换返回类型
-        let new_return_type = self.substitute_single_type(&generic.return_type, &type_map);
+        let new_return_type = substitute(&generic.return_type);
 
         // 替换局部变量类型
-        let new_locals: Vec<MonoType> = generic
-            .locals
-            .iter()
-            .map(|ty| self.substitute_single_type(ty, &type_map))
-            .collect();
+        let new_locals: Vec<MonoType> = generic.locals.iter().map(substitute).collect();
 
         // 替换指令中的类型
-        let new_blocks: Vec<BasicBlock> = generic
+        let mut new_blocks: Vec<BasicBlock> = generic
             .blocks
             .iter()
             .map(|block| self.substitute_block(block, &type_map))
             .collect();
 
-        // 生成特化后的函数名: identity → identity(Int)
-        let type_args_str = type_args
-            .iter()
-            .map(|t| t.type_name())
-            .collect::<Vec<_>>()
-            .join(", ");
-        let specialized_name = format!("{}({})", generic.name, type_args_str);
+        // 约束方法静态分发：T.method → 具体实现类型.method
+        Self::resolve_bound_method_calls(&mut new_blocks, type_params, type_args);
 
         // 构建特化函数
         Some(FunctionIR {
-            name: specialized_name,
+            name: specialized_name(&generic.name, type_args),
             params: new_params,
             return_type: new_return_type,
             locals: new_locals,
@@ -218,93 +375,99 @@ impl Monomorphizer {
         })
     }
 
-    /// 扫描特化函数体中的泛型调用，将新发现的实例化请求加入队列
-    fn scan_for_new_calls(
-        &mut self,
-        func: &FunctionIR,
+    /// 将泛型参数上的方法调用（`T.method`）替换为具体类型的方法（`Point.method`）
+    fn resolve_bound_method_calls(
+        blocks: &mut [BasicBlock],
+        type_params: &[String],
+        type_args: &[MonoType],
     ) {
-        for instr in func.all_instructions() {
-            if let crate::middle::core::ir::Instruction::Call {
-                func: callee, args, ..
+        for instr in blocks.iter_mut().flat_map(|b| b.instructions.iter_mut()) {
+            let Instruction::Call {
+                func: Operand::Const(ConstValue::String(name)),
+                ..
             } = instr
-            {
-                // 从调用操作数提取被调用函数名
-                let callee_name = match callee {
-                    Operand::Const(ConstValue::String(name)) => name.clone(),
-                    _ => continue,
-                };
-
-                // 检查被调用函数是否是已知的泛型函数
-                if !self.generic_functions.contains_key(&callee_name) {
-                    continue;
-                }
-
-                let generic_func = match self.generic_functions.get(&callee_name) {
-                    Some(f) => f,
-                    None => continue,
-                };
-
-                let type_params = match &generic_func.generic_params {
-                    Some(p) => p,
-                    None => continue,
-                };
-
-                // 从 args 中尝试推断类型参数
-                let arg_types: Vec<MonoType> = args
-                    .iter()
-                    .filter_map(|op| self.operand_to_type_hint(op, func))
-                    .collect();
+            else {
+                continue;
+            };
+            let Some((receiver, method)) = name.split_once('.') else {
+                continue;
+            };
+            if let Some(idx) = type_params.iter().position(|p| p == receiver) {
+                *name = format!("{}.{}", type_args[idx].type_name(), method);
+            }
+        }
+    }
 
-                // 如果无法推断任何参数类型，跳过
-                if arg_types.is_empty() {
-                    continue;
+    /// 把所在泛型函数体内记录的实例化请求代入本次特化的类型实参后入队，
+    /// 并把对应调用点改写为特化函数名
+    fn scan_for_new_calls(
+        &mut self,
+        func: &mut FunctionIR,
+        req: &InstantiationRequest,
+    ) {
+        let caller = req.generic_id().name();
+        let Some(body_requests) = self.body_requests.get(caller) else {
+            return;
+        };
+        let Some(type_params) = self
+            .generic_functions
+            .get(caller)
+            .and_then(|f| f.generic_params.as_ref())
+        else {
+            return;
+        };
+
+        let mut renames = HashMap::new();
+        let mut nested = Vec::new();
+        for body_req in body_requests {
+            let callee = body_req.generic_id().name();
+            if !self.generic_functions.contains_key(callee) {
+                continue;
+            }
+            let type_args: Vec<MonoType> = body_req
+                .type_args()
+                .iter()
+                .map(|ty| substitute_type_params(ty, type_params, req.type_args()))
+                .collect();
+            renames.insert(
+                (callee.to_string(), body_req.source_location),
+                specialized_name(callee, &type_args),
+            );
+
+            let mut generic_id = body_req.generic_id().clone();
+            if generic_id.bounds().is_empty() {
+                if let Some(bounds) = self.generic_bounds.get(callee) {
+                    generic_id = generic_id.with_bounds(bounds.clone());
                 }
+            }
+            nested.push(InstantiationRequest::new(
+                generic_id,
+                type_args,
+                body_req.source_location,
+            ));
+        }
 
-                // 使用推断的参数类型创建实例化请求
-                // 简单启发式：使用第一个参数的类型作为泛型参数
-                if type_params.len() == 1 {
-                    let type_arg = arg_types[0].clone();
-                    let key = SpecializationKey::new(callee_name.clone(), vec![type_arg.clone()]);
-
-                    if !self.processed.contains(&key) {
-                        let req = InstantiationRequest::new(
-                            GenericFunctionId::new(callee_name.clone(), type_params.clone()),
-                            vec![type_arg],
-                            crate::util::span::Span::default(),
-                        );
-                        self.pending_queue.push_back(req);
-                    }
+        for instr in func
+            .blocks
+            .iter_mut()
+            .flat_map(|b| b.instructions.iter_mut())
+        {
+            if let Instruction::Call {
+                func: Operand::Const(ConstValue::String(name)),
+                span,
+                ..
+            } = instr
+            {
+                if let Some(specialized) = renames.get(&(name.clone(), *span)) {
+                    *name = specialized.clone();
                 }
             }
         }
-    }
 
-    /// 从特化函数中获取操作数对应的类型提示
-    fn operand_to_type_hint(
-        &self,
-        op: &Operand,
-        func: &FunctionIR,
-    ) -> Option<MonoType> {
-        match op {
-            Operand::Local(idx) => func.locals.get(*idx).cloned(),
-            Operand::Arg(idx) => {
-                if *idx < func.params.len() {
-                    Some(func.params[*idx].clone())
-                } else {
-                    None
-                }
+        for nested_req in nested {
+            if !self.processed.contains(&nested_req.specialization_key()) {
+                self.pending_queue.push_back(nested_req);
             }
-            Operand::Const(cv) => match cv {
-                ConstValue::Int(_) => Some(MonoType::Int(64)),
-                ConstValue::Float(_) => Some(MonoType::Float(64)),
-                ConstValue::Bool(_) => Some(MonoType::Bool),
-                ConstValue::String(_) => Some(MonoType::String),
-                ConstValue::Char(_) => Some(MonoType::Char),
-                ConstValue::Void => Some(MonoType::Void),
-                _ => None,
-            },
-            Operand::Temp(idx) => func.locals.get(*idx).cloned(),
-            _ => None,
         }
     }
 
@@ -316,15 +479,33 @@ impl Monomorphizer {
     ) {
         // 构建调用点映射：generic_name -> specialized_name
         let call_site_map = self.build_call_site_map(requests);
+        // 同一泛型函数的不同实例化按调用位置区分：(generic_name, span) -> specialized_name
+        let call_span_map = self.build_call_span_map(requests);
 
         // 遍历所有非泛型函数，替换调用点
         for func in &mut module.functions {
             if func.generic_params.is_none() {
-                self.replace_calls_in_function(func, &call_site_map);
+                self.replace_calls_in_function(func, &call_site_map, &call_span_map);
             }
         }
     }
 
+    /// 构建按调用位置区分的特化函数名映射
+    fn build_call_span_map(
+        &self,
+        requests: &[InstantiationRequest],
+    ) -> HashMap<(String, Span), String> {
+        requests
+            .iter()
+            .filter(|req| self.generic_functions.contains_key(req.generic_id().name()))
+            .map(|req| {
+                let generic_name = req.generic_id().name().to_string();
+                let specialized = specialized_name(&generic_name, req.type_args());
+                ((generic_name, req.source_location), specialized)
+            })
+            .collect()
+    }
+
     /// 构建泛型函数名到特化函数名的映射
     fn build_call_site_map(
         &self,
@@ -339,14 +520,8 @@ impl Monomorphizer {
                 continue;
             }
 
-            let type_args_str = req
-                .type_args()
-                .iter()
-                .map(|t| t.type_name())
-                .collect::<Vec<_>>()
-                .join(", ");
-            let specialized_name = format!("{}({})", generic_name, type_args_str);
-            map.insert(generic_name, specialized_name);
+            let specialized = specialized_name(&generic_name, req.type_args());
+            map.insert(generic_name, specialized);
         }
         map
     }
@@ -356,16 +531,21 @@ impl Monomorphizer {
         &self,
         func: &mut FunctionIR,
         call_site_map: &HashMap<String, String>,
+        call_span_map: &HashMap<(String, Span), String>,
     ) {
         for block in &mut func.blocks {
             for instr in &mut block.instructions {
                 if let Instruction::Call {
                     func: ref mut callee,
+                    span,
                     ..
                 } = instr
                 {
                     if let Operand::Const(ConstValue::String(name)) = callee {
-                        if let Some(specialized_name) = call_site_map.get(name) {
+                        if let Some(specialized_name) = call_span_map
+                            .get(&(name.clone(), *span))
+                            .or_else(|| call_site_map.get(name))
+                        {
                             *callee = Operand::Const(ConstValue::String(specialized_name.clone()));
                         }
                     }
@@ -375,6 +555,62 @@ impl Monomorphizer {
     }
 }
 
+/// 特化函数名：`identity` 以 `Int` 实例化为 `identity(int64)`
+///
+/// 按规范形式命名，特化键相同的请求得到同一个名字。
+fn specialized_name(
+    generic_name: &str,
+    type_args: &[MonoType],
+) -> String {
+    let type_args: Vec<String> = type_args
+        .iter()
+        .map(|t| instance::canonical_type(t).type_name())
+        .collect();
+    format!("{}({})", generic_name, type_args.join(", "))
+}
+
+/// 把类型中的类型参数名（`TypeRef("T")`）替换为对应的类型实参
+fn substitute_type_params(
+    ty: &MonoType,
+    type_params: &[String],
+    type_args: &[MonoType],
+) -> MonoType {
+    let sub = |t: &MonoType| Box::new(substitute_type_params(t, type_params, type_args));
+    let sub_all = |ts: &[MonoType]| {
+        ts.iter()
+            .map(|t| substitute_type_params(t, type_params, type_args))
+            .collect()
+    };
+    match ty {
+        MonoType::TypeRef(name) => type_params
+            .iter()
+            .position(|p| p == name)
+            .and_then(|i| type_args.get(i).cloned())
+            .unwrap_or_else(|| ty.clone()),
+        MonoType::List(t) => MonoType::List(sub(t)),
+        MonoType::Set(t) => MonoType::Set(sub(t)),
+        MonoType::Option(t) => MonoType::Option(sub(t)),
+        MonoType::Arc(t) => MonoType::Arc(sub(t)),
+        MonoType::Weak(t) => MonoType::Weak(sub(t)),
+        MonoType::Async(t) => MonoType::Async(sub(t)),
+        MonoType::Dict(k, v) => MonoType::Dict(sub(k), sub(v)),
+        MonoType::Result(ok, err) => MonoType::Result(sub(ok), sub(err)),
+        MonoType::Tuple(types) => MonoType::Tuple(sub_all(types)),
+        MonoType::Fn {
+            params,
+            return_type,
+        } => MonoType::Fn {
+            params: sub_all(params),
+            return_type: sub(return_type),
+        },
+        MonoType::Generic { name, args } => MonoType::Generic {
+            name: name.clone(),
+            args: sub_all(args),
+        },
+        _ => ty.clone(),
+    }
+}
+
 impl Default for Monomorphizer {
     fn default() -> Self {
         Self::new()
@@ -5,7 +5,7 @@
 
 use crate::frontend::core::typecheck::MonoType;
 use crate::middle::passes::mono::instance::{
-    GenericFunctionId, InstantiationRequest, SpecializationKey,
+    GenericBound, GenericFunctionId, InstantiationRequest, SpecializationKey,
 };
 use crate::util::span::Span;
 
@@ -33,6 +33,24 @@ fn test_generic_function_id_name_accessor() {
     assert_eq!(id.type_params(), &["T".to_string()]);
 }
 
+#[test]
+fn test_generic_function_id_with_bounds() {
+    // Arrange
+    let id =
+        GenericFunctionId::new("describe".to_string(), vec!["T".to_string()]).with_bounds(vec![
+            vec![
+                GenericBound::new("Show", vec!["show".to_string()]),
+                GenericBound::new("Clone", vec![]),
+            ],
+        ]);
+
+    // Assert
+    assert_eq!(id.bounds_of(0).len(), 2);
+    assert_eq!(id.bounds_of(0)[0].name, "Show");
+    assert!(id.bounds_of(1).is_empty(), "越界下标应视为无约束");
+    assert_eq!(id.signature(), "describe(T: Show + Clone)");
+}
+
 #[test]
 fn test_instantiation_request_specialization_key_generation() {
     // Arrange
@@ -49,3 +67,64 @@ fn test_instantiation_request_specialization_key_generation() {
     assert_eq!(key.name, "identity");
     assert_eq!(key.type_args, vec![MonoType::Int(64)]);
 }
+
+#[test]
+fn test_specialization_key_uses_canonical_types() {
+    // Arrange: 内置类型名与基本类型、具名结构体与同名引用是同一类型
+    let point = MonoType::Struct(crate::frontend::core::types::StructType {
+        name: "Point".to_string(),
+        fields: vec![("x".to_string(), MonoType::Int(64))],
+        methods: Default::default(),
+        field_mutability: vec![true],
+        field_has_default: vec![false],
+        interfaces: vec![],
+        assoc_items: Vec::new(),
+    });
+    let by_name = SpecializationKey::new(
+        "pick".to_string(),
+        vec![
+            MonoType::TypeRef("Int".to_string()),
+            MonoType::TypeRef("Point".to_string()),
+        ],
+    );
+    let by_type = SpecializationKey::new("pick".to_string(), vec![MonoType::Int(64), point]);
+
+    // Assert
+    assert_eq!(by_name, by_type);
+    let keys: std::collections::HashSet<_> = [by_name, by_type].into_iter().collect();
+    assert_eq!(keys.len(), 1, "相等的键哈希值也应相同");
+}
+
+#[test]
+fn test_specialization_key_distinguishes_nested_generics() {
+    // Arrange: 拼接类型名时容易混淆的几组实参
+    let boxed = |args: Vec<MonoType>| MonoType::Generic {
+        name: "Box".to_string(),
+        args,
+    };
+    let fn_of = |param: MonoType| MonoType::Fn {
+        params: vec![param],
+        return_type: Box::new(MonoType::Int(64)),
+    };
+    let type_args = vec![
+        vec![boxed(vec![MonoType::Int(64), MonoType::String])],
+        vec![boxed(vec![MonoType::Int(64)]), MonoType::String],
+        vec![boxed(vec![boxed(vec![MonoType::Int(64)])])],
+        vec![MonoType::List(Box::new(MonoType::Int(64)))],
+        vec![MonoType::Generic {
+            name: "List".to_string(),
+            args: vec![MonoType::Int(64)],
+        }],
+        vec![fn_of(MonoType::Int(64))],
+        vec![fn_of(MonoType::String)],
+    ];
+
+    // Act
+    let keys: std::collections::HashSet<_> = type_args
+        .into_iter()
+        .map(|args| SpecializationKey::new("f".to_string(), args))
+        .collect();
+
+    // Assert: `Generic { List, .. }` 与 `List(..)` 合并，其余各不相同
+    assert_eq!(keys.len(), 6);
+}
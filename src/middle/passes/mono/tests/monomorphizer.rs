@@ -7,10 +7,11 @@ use crate::frontend::core::typecheck::MonoType;
 use crate::frontend::core::types::var::TypeVar;
 use crate::middle::core::ir::{BasicBlock, ConstValue, FunctionIR, Instruction, ModuleIR, Operand};
 use crate::middle::passes::mono::instance::{
-    GenericFunctionId, InstantiationRequest, SpecializationKey,
+    GenericBound, GenericFunctionId, InstantiationRequest, SpecializationKey,
 };
 use crate::middle::passes::mono::Monomorphizer;
 use crate::util::span::Span;
+use std::collections::HashMap;
 
 // ==================== 辅助函数 ====================
 
@@ -270,167 +271,205 @@ fn test_specialize_with_generic_type_args_replaces_inner_types() {
     assert_eq!(func.locals[0], MonoType::List(Box::new(MonoType::String)));
 }
 
-// ==================== scan_for_new_calls 测试 ====================
-
 #[test]
-fn test_scan_for_new_calls_no_generic_calls_leaves_queue_empty() {
+fn test_specialize_resolves_bound_method_call() {
+    // describe: (T: Show) -> (value: T) -> String = value.show()
     // Arrange
-    let mut mono = Monomorphizer::new();
-    let func = FunctionIR {
-        name: "simple".to_string(),
-        params: vec![],
-        return_type: MonoType::Void,
-        locals: vec![],
+    let t = MonoType::TypeVar(TypeVar::new(0));
+    let generic = FunctionIR {
+        name: "describe".to_string(),
+        params: vec![t.clone()],
+        return_type: MonoType::String,
+        locals: vec![t.clone(), MonoType::String],
         blocks: vec![BasicBlock {
             label: 0,
-            instructions: vec![Instruction::Ret(None)],
+            instructions: vec![
+                Instruction::Call {
+                    dst: Some(Operand::Local(1)),
+                    func: Operand::Const(ConstValue::String("T.show".to_string())),
+                    args: vec![Operand::Arg(0)],
+                    span: Span::default(),
+                },
+                Instruction::Ret(Some(Operand::Local(1))),
+            ],
             successors: Vec::new(),
         }],
         entry: 0,
-        generic_params: None,
+        generic_params: Some(vec!["T".to_string()]),
     };
+    let mut mono = Monomorphizer::new();
+    mono.generic_functions
+        .insert("describe".to_string(), generic);
+
+    let req = InstantiationRequest::new(
+        GenericFunctionId::new("describe".to_string(), vec!["T".to_string()]),
+        vec![MonoType::TypeRef("Point".to_string())],
+        Span::default(),
+    );
 
     // Act
-    mono.scan_for_new_calls(&func);
+    let func = mono.specialize_function(&req).expect("特化应该成功");
 
     // Assert
-    assert!(mono.pending_queue.is_empty(), "无泛型调用时队列应为空");
+    assert!(matches!(
+        &func.blocks[0].instructions[0],
+        Instruction::Call {
+            func: Operand::Const(ConstValue::String(name)),
+            ..
+        } if name == "Point.show"
+    ));
 }
 
-#[test]
-fn test_scan_for_new_calls_with_generic_call_enqueues_request() {
-    // Arrange
-    let mut mono = Monomorphizer::new();
-    mono.generic_functions
-        .insert("identity".to_string(), make_identity_ir());
+// ==================== scan_for_new_calls 测试 ====================
 
-    let func = FunctionIR {
-        name: "wrapper(Int)".to_string(),
-        params: vec![MonoType::Int(64)],
-        return_type: MonoType::Int(64),
-        locals: vec![MonoType::Int(64)],
+/// 泛型函数 wrapper(T) 的函数体：以 `span` 处的调用把参数交给 identity
+fn make_wrapper_ir(span: Span) -> FunctionIR {
+    let t = MonoType::TypeRef("T".to_string());
+    FunctionIR {
+        name: "wrapper".to_string(),
+        params: vec![t.clone()],
+        return_type: t,
+        // 局部变量类型是 IR 生成时的占位类型
+        locals: vec![MonoType::Int(64), MonoType::Int(64)],
         blocks: vec![BasicBlock {
             label: 0,
             instructions: vec![
+                Instruction::Load {
+                    dst: Operand::Local(0),
+                    src: Operand::Arg(0),
+                },
                 Instruction::Call {
-                    dst: Some(Operand::Local(0)),
+                    dst: Some(Operand::Local(1)),
                     func: Operand::Const(ConstValue::String("identity".to_string())),
-                    args: vec![Operand::Arg(0)],
-                    span: Span::default(),
+                    args: vec![Operand::Local(0)],
+                    span,
                 },
-                Instruction::Ret(Some(Operand::Local(0))),
+                Instruction::Ret(Some(Operand::Local(1))),
             ],
             successors: Vec::new(),
         }],
         entry: 0,
-        generic_params: None,
-    };
+        generic_params: Some(vec!["T".to_string()]),
+    }
+}
+
+fn call_span() -> Span {
+    Span::new(
+        crate::util::span::Position::new(3, 5),
+        crate::util::span::Position::new(3, 9),
+    )
+}
+
+/// wrapper 体内 `identity(x)` 的实例化记录：类型实参是 wrapper 的类型参数 T
+fn wrapper_body_request(span: Span) -> InstantiationRequest {
+    InstantiationRequest::new(
+        GenericFunctionId::new("identity".to_string(), vec!["T".to_string()]),
+        vec![MonoType::TypeRef("T".to_string())],
+        span,
+    )
+    .with_caller("wrapper")
+}
+
+/// 以 `type_arg` 特化 wrapper，返回特化后的函数与对应请求
+fn specialize_wrapper(
+    mono: &mut Monomorphizer,
+    type_arg: MonoType,
+) -> (FunctionIR, InstantiationRequest) {
+    mono.generic_functions
+        .insert("identity".to_string(), make_identity_ir());
+    mono.generic_functions
+        .insert("wrapper".to_string(), make_wrapper_ir(call_span()));
+    let req = InstantiationRequest::new(
+        GenericFunctionId::new("wrapper".to_string(), vec!["T".to_string()]),
+        vec![type_arg],
+        Span::default(),
+    );
+    let func = mono
+        .specialize_function(&req)
+        .expect("wrapper should specialize");
+    (func, req)
+}
+
+#[test]
+fn test_scan_for_new_calls_without_body_requests_leaves_queue_empty() {
+    // Arrange: 没有 wrapper 体内的实例化记录
+    let mut mono = Monomorphizer::new();
+    let (mut func, req) = specialize_wrapper(&mut mono, MonoType::Float(64));
 
     // Act
-    mono.scan_for_new_calls(&func);
+    mono.scan_for_new_calls(&mut func, &req);
 
     // Assert
+    assert!(mono.pending_queue.is_empty(), "无实例化记录时队列应为空");
+}
+
+#[test]
+fn test_scan_for_new_calls_substitutes_caller_type_args() {
+    // Arrange: wrapper(Float) 体内调用 identity(x)，局部变量的占位类型是 Int
+    let mut mono = Monomorphizer::new();
+    mono.body_requests.insert(
+        "wrapper".to_string(),
+        vec![wrapper_body_request(call_span())],
+    );
+    let (mut func, req) = specialize_wrapper(&mut mono, MonoType::Float(64));
+
+    // Act
+    mono.scan_for_new_calls(&mut func, &req);
+
+    // Assert: 按记录代入 wrapper 的类型实参，而不是按占位类型猜测
     assert_eq!(mono.pending_queue.len(), 1, "应该有一个新的实例化请求");
     let pending = &mono.pending_queue[0];
     assert_eq!(pending.generic_id().name(), "identity");
-    assert_eq!(pending.type_args().len(), 1);
-    assert_eq!(pending.type_args()[0], MonoType::Int(64));
+    assert_eq!(pending.type_args(), &[MonoType::Float(64)]);
+    assert!(pending.caller.is_none());
 }
 
 #[test]
 fn test_scan_for_new_calls_duplicate_prevented_by_processed_set() {
     // Arrange
     let mut mono = Monomorphizer::new();
-    mono.generic_functions
-        .insert("identity".to_string(), make_identity_ir());
-
+    mono.body_requests.insert(
+        "wrapper".to_string(),
+        vec![wrapper_body_request(call_span())],
+    );
     mono.processed.insert(SpecializationKey::new(
         "identity".to_string(),
-        vec![MonoType::Int(64)],
+        vec![MonoType::String],
     ));
-
-    let func = FunctionIR {
-        name: "dup_check".to_string(),
-        params: vec![MonoType::Int(64)],
-        return_type: MonoType::Int(64),
-        locals: vec![MonoType::Int(64)],
-        blocks: vec![BasicBlock {
-            label: 0,
-            instructions: vec![
-                Instruction::Call {
-                    dst: Some(Operand::Local(0)),
-                    func: Operand::Const(ConstValue::String("identity".to_string())),
-                    args: vec![Operand::Arg(0)],
-                    span: Span::default(),
-                },
-                Instruction::Ret(Some(Operand::Local(0))),
-            ],
-            successors: Vec::new(),
-        }],
-        entry: 0,
-        generic_params: None,
-    };
+    let (mut func, req) = specialize_wrapper(&mut mono, MonoType::String);
 
     // Act
-    mono.scan_for_new_calls(&func);
+    mono.scan_for_new_calls(&mut func, &req);
 
-    // Assert
+    // Assert: 不重复入队，但调用点仍改写
     assert!(
         mono.pending_queue.is_empty(),
         "已处理的请求不应重复加入队列"
     );
+    assert!(matches!(
+        &func.blocks[0].instructions[1],
+        Instruction::Call { func: Operand::Const(ConstValue::String(name)), .. }
+        if name == "identity(string)"
+    ));
 }
 
-// ==================== operand_to_type_hint 测试 ====================
-
 #[test]
-fn test_operand_to_type_hint_resolves_arg_local_and_const() {
-    // Arrange
-    let mono = Monomorphizer::new();
-    let func = FunctionIR {
-        name: "test".to_string(),
-        params: vec![MonoType::Int(64), MonoType::String],
-        return_type: MonoType::Void,
-        locals: vec![MonoType::Bool, MonoType::Float(64)],
-        blocks: vec![],
-        entry: 0,
-        generic_params: None,
-    };
-
-    // Assert: Arg(0) -> Int(64)
-    assert_eq!(
-        mono.operand_to_type_hint(&Operand::Arg(0), &func),
-        Some(MonoType::Int(64))
-    );
-
-    // Assert: Arg(1) -> String
-    assert_eq!(
-        mono.operand_to_type_hint(&Operand::Arg(1), &func),
-        Some(MonoType::String)
-    );
-
-    // Assert: Arg(99) -> None (越界)
-    assert_eq!(mono.operand_to_type_hint(&Operand::Arg(99), &func), None);
-
-    // Assert: Local(0) -> Bool
-    assert_eq!(
-        mono.operand_to_type_hint(&Operand::Local(0), &func),
-        Some(MonoType::Bool)
-    );
+fn test_scan_for_new_calls_substitutes_nested_type_params() {
+    // Arrange: wrapper 体内以 List(T) 实例化 identity
+    let mut mono = Monomorphizer::new();
+    let mut body_req = wrapper_body_request(call_span());
+    body_req.type_args = vec![MonoType::List(Box::new(MonoType::TypeRef("T".to_string())))];
+    mono.body_requests
+        .insert("wrapper".to_string(), vec![body_req]);
+    let (mut func, req) = specialize_wrapper(&mut mono, MonoType::String);
 
-    // Assert: Const(Int) -> Int(64)
-    assert_eq!(
-        mono.operand_to_type_hint(&Operand::Const(ConstValue::Int(42)), &func),
-        Some(MonoType::Int(64))
-    );
+    // Act
+    mono.scan_for_new_calls(&mut func, &req);
 
-    // Assert: Const(String) -> String
+    // Assert
     assert_eq!(
-        mono.operand_to_type_hint(
-            &Operand::Const(ConstValue::String("hello".to_string())),
-            &func,
-        ),
-        Some(MonoType::String)
+        mono.pending_queue[0].type_args(),
+        &[MonoType::List(Box::new(MonoType::String))]
     );
 }
 
@@ -636,7 +675,7 @@ fn test_monomorphize_end_to_end_specializes_and_replaces_calls() {
     )];
 
     // Act
-    let result = mono.monomorphize(&module, &requests).unwrap();
+    let result = mono.monomorphize(module, &requests).unwrap();
 
     // Assert: 应有 2 个函数：main（调用已替换）+ identity(int64)
     assert_eq!(result.functions.len(), 2);
@@ -663,3 +702,253 @@ fn test_monomorphize_end_to_end_specializes_and_replaces_calls() {
         "特化函数的泛型标记应已清除"
     );
 }
+
+// ==================== 泛型约束校验测试 ====================
+
+/// 调用 identity(arg) 的 main 函数
+fn make_main_calling_identity(arg: Operand) -> FunctionIR {
+    FunctionIR {
+        name: "main".to_string(),
+        params: vec![],
+        return_type: MonoType::Void,
+        locals: vec![MonoType::Void],
+        blocks: vec![BasicBlock {
+            label: 0,
+            instructions: vec![
+                Instruction::Call {
+                    dst: Some(Operand::Local(0)),
+                    func: Operand::Const(ConstValue::String("identity".to_string())),
+                    args: vec![arg],
+                    span: Span::default(),
+                },
+                Instruction::Ret(None),
+            ],
+            successors: Vec::new(),
+        }],
+        entry: 0,
+        generic_params: None,
+    }
+}
+
+fn bounded_identity_request(
+    bound: GenericBound,
+    type_arg: MonoType,
+) -> InstantiationRequest {
+    InstantiationRequest::new(
+        GenericFunctionId::new("identity".to_string(), vec!["T".to_string()])
+            .with_bounds(vec![vec![bound]]),
+        vec![type_arg],
+        Span::default(),
+    )
+}
+
+#[test]
+fn test_monomorphize_std_trait_bound_satisfied() {
+    // Arrange: identity(T: Clone) 以 Int 实例化
+    let module = ModuleIR {
+        functions: vec![
+            make_identity_ir(),
+            make_main_calling_identity(Operand::Const(ConstValue::Int(1))),
+        ],
+        ..Default::default()
+    };
+    let requests = vec![bounded_identity_request(
+        GenericBound::new("Clone", vec![]),
+        MonoType::Int(64),
+    )];
+
+    // Act
+    let result = Monomorphizer::new().monomorphize(module, &requests);
+
+    // Assert
+    assert!(result.is_ok(), "Int 满足 Clone，单态化应成功");
+}
+
+#[test]
+fn test_monomorphize_unsatisfied_std_trait_bound_reports_e4001() {
+    // Arrange: identity(T: Clone) 以 List(Int) 实例化
+    let list = MonoType::List(Box::new(MonoType::Int(64)));
+    let module = ModuleIR {
+        functions: vec![
+            make_identity_ir(),
+            make_main_calling_identity(Operand::Local(0)),
+        ],
+        ..Default::default()
+    };
+    let requests = vec![bounded_identity_request(
+        GenericBound::new("Clone", vec![]),
+        list,
+    )];
+
+    // Act
+    let err = Monomorphizer::new()
+        .monomorphize(module, &requests)
+        .unwrap_err();
+
+    // Assert: 诊断指明约束与泛型函数
+    assert_eq!(err.code, "E4001");
+    assert!(
+        err.message.contains("Clone"),
+        "应指明未满足的约束: {}",
+        err.message
+    );
+    assert!(
+        err.message.contains("identity"),
+        "应指明泛型函数: {}",
+        err.message
+    );
+}
+
+#[test]
+fn test_monomorphize_interface_bound_requires_method_impl() {
+    // Arrange: identity(T: Show)，Point.show 存在而 Other.show 不存在
+    let point_show = FunctionIR {
+        name: "Point.show".to_string(),
+        params: vec![MonoType::TypeRef("Point".to_string())],
+        return_type: MonoType::String,
+        locals: vec![],
+        blocks: vec![BasicBlock {
+            label: 0,
+            instructions: vec![Instruction::Ret(None)],
+            successors: Vec::new(),
+        }],
+        entry: 0,
+        generic_params: None,
+    };
+    let show = || GenericBound::new("Show", vec!["show".to_string()]);
+    let module = || ModuleIR {
+        functions: vec![
+            make_identity_ir(),
+            point_show.clone(),
+            make_main_calling_identity(Operand::Local(0)),
+        ],
+        ..Default::default()
+    };
+
+    // Act
+    let ok = Monomorphizer::new().monomorphize(
+        module(),
+        &[bounded_identity_request(
+            show(),
+            MonoType::TypeRef("Point".to_string()),
+        )],
+    );
+    let err = Monomorphizer::new().monomorphize(
+        module(),
+        &[bounded_identity_request(
+            show(),
+            MonoType::TypeRef("Other".to_string()),
+        )],
+    );
+
+    // Assert
+    assert!(ok.is_ok(), "Point 实现了 show，应满足 Show");
+    assert_eq!(err.unwrap_err().code, "E4001");
+}
+
+#[test]
+fn test_scan_for_new_calls_renames_nested_call_and_carries_bounds() {
+    // Arrange: wrapper(Point) 调用受约束的 identity
+    let mut mono = Monomorphizer::new().with_generic_bounds(HashMap::from([(
+        "identity".to_string(),
+        vec![vec![GenericBound::new("Clone", vec![])]],
+    )]));
+    mono.body_requests.insert(
+        "wrapper".to_string(),
+        vec![wrapper_body_request(call_span())],
+    );
+    let point = MonoType::TypeRef("Point".to_string());
+    let (mut func, req) = specialize_wrapper(&mut mono, point.clone());
+
+    // Act
+    mono.scan_for_new_calls(&mut func, &req);
+
+    // Assert: 携带约束与调用点位置
+    let pending = &mono.pending_queue[0];
+    assert_eq!(pending.type_args(), &[point]);
+    assert_eq!(pending.generic_id().bounds_of(0)[0].name, "Clone");
+    assert_eq!(pending.source_location, call_span());
+    // Assert: 调用点改写为特化函数名
+    assert!(matches!(
+        &func.blocks[0].instructions[1],
+        Instruction::Call { func: Operand::Const(ConstValue::String(name)), .. }
+        if name == "identity(Point)"
+    ));
+}
+
+// ==================== 类型检查产出的实例化记录 ====================
+
+#[test]
+fn test_compiled_generic_calls_use_typechecker_records() {
+    // Arrange: wrap 体内的 identity(y) 与 pick(n, ...) 的类型实参取决于 wrap 的特化
+    let source = "
+identity: (T: Type) -> ((x: T) -> T) = (x) => x
+
+pick: (A: Type, B: Type) -> ((a: A, b: B) -> B) = (a, b) => b
+
+wrap: (T: Type) -> ((x: T) -> T) = (x) => {
+    n = identity(7)
+    y = identity(x)
+    return pick(n, identity(y))
+}
+
+main = {
+    print(pick(1.5, 2.5))
+    print(wrap(3.5))
+    print(wrap(\"w\"))
+}
+";
+    let config = crate::frontend::config::CompileConfig::new()
+        .with_opt_level(crate::frontend::config::OptLevel::O0);
+
+    // Act
+    let module = crate::frontend::Compiler::with_config(config)
+        .compile("generic.yx", source)
+        .expect("source should compile");
+
+    // Assert: 每个类型参数各有一个类型实参
+    let names: std::collections::HashSet<&str> =
+        module.functions.iter().map(|f| f.name.as_str()).collect();
+    for expected in [
+        "pick(float64, float64)",
+        "wrap(float64)",
+        "wrap(string)",
+        "identity(int64)",
+        "identity(float64)",
+        "identity(string)",
+        "pick(int64, float64)",
+        "pick(int64, string)",
+    ] {
+        assert!(names.contains(expected), "缺少 {}: {:?}", expected, names);
+    }
+
+    // Assert: wrap(string) 体内的调用改写为对应特化
+    let wrap = module
+        .functions
+        .iter()
+        .find(|f| f.name == "wrap(string)")
+        .unwrap();
+    let callees: Vec<&str> = wrap
+        .all_instructions()
+        .filter_map(|i| match i {
+            Instruction::Call {
+                func: Operand::Const(ConstValue::String(name)),
+                ..
+            }
+            | Instruction::TailCall {
+                func: Operand::Const(ConstValue::String(name)),
+                ..
+            } => Some(name.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        callees,
+        [
+            "identity(int64)",
+            "identity(string)",
+            "identity(string)",
+            "pick(int64, string)"
+        ]
+    );
+}
@@ -7,10 +7,11 @@
 //! - collect_generic_types: 从 ModuleIR 中识别泛型类型定义
 //! - monomorphize_type: 泛型类型实例化
 //! - 缓存去重
+//! - specialize_types: 按类型表中的实例生成结构体布局
 
 use crate::frontend::core::parser::ast::{StructField, Type as AstType};
 use crate::frontend::core::typecheck::MonoType;
-use crate::middle::core::ir::ModuleIR;
+use crate::middle::core::ir::{ModuleIR, StructLayout};
 use crate::middle::passes::mono::instance::GenericTypeId;
 use crate::middle::passes::mono::Monomorphizer;
 use crate::util::span::Span;
@@ -23,29 +24,37 @@ use crate::util::span::Span;
 /// RFC-011 §1.1: 泛型类型参数 (T: Type)
 fn make_module_with_generic_list() -> ModuleIR {
     ModuleIR {
-        types: vec![AstType::NamedStruct {
-            name: "List".to_string(),
-            name_span: Span::default(),
-            fields: vec![
-                StructField::new(
-                    "data".to_string(),
-                    false,
-                    AstType::Generic {
-                        name: "Array".to_string(),
-                        name_span: Span::default(),
-                        args: vec![AstType::Name {
-                            name: "T".to_string(),
-                            span: Span::default(),
-                        }],
-                    },
-                ),
-                StructField::new("length".to_string(), false, AstType::Int(64)),
-            ],
-        }],
+        generic_types: vec![(
+            "List".to_string(),
+            vec!["T".to_string()],
+            AstType::NamedStruct {
+                name: "List".to_string(),
+                name_span: Span::default(),
+                fields: vec![
+                    StructField::new(
+                        "data".to_string(),
+                        false,
+                        AstType::Generic {
+                            name: "Array".to_string(),
+                            name_span: Span::default(),
+                            args: vec![type_param("T")],
+                        },
+                    ),
+                    StructField::new("length".to_string(), false, AstType::Int(64)),
+                ],
+            },
+        )],
         ..ModuleIR::default()
     }
 }
 
+fn type_param(name: &str) -> AstType {
+    AstType::Name {
+        name: name.to_string(),
+        span: Span::default(),
+    }
+}
+
 /// 创建不包含泛型类型（仅具体类型）的 ModuleIR
 fn make_module_with_concrete_types() -> ModuleIR {
     ModuleIR {
@@ -129,8 +138,8 @@ fn test_monomorphize_type_returns_mono_type_for_known_generic() {
     assert!(result.is_some(), "已知泛型类型 List(Int64) 应成功单态化");
     let mono_type = result.unwrap();
     assert!(
-        matches!(&mono_type, MonoType::Struct(s) if s.name == "List_int64"),
-        "单态化后类型名应为 List_int64，实际得到 {:?}",
+        matches!(&mono_type, MonoType::Struct(s) if s.name == "List(int64)"),
+        "单态化后类型名应为 List(int64)，实际得到 {:?}",
         mono_type.type_name()
     );
 }
@@ -165,8 +174,8 @@ fn test_monomorphize_type_with_string_arg() {
     assert!(result.is_some(), "List(String) 应成功单态化");
     let mono_type = result.unwrap();
     assert!(
-        matches!(&mono_type, MonoType::Struct(s) if s.name == "List_string"),
-        "单态化后类型名应为 List_string，实际得到 {:?}",
+        matches!(&mono_type, MonoType::Struct(s) if s.name == "List(string)"),
+        "单态化后类型名应为 List(string)，实际得到 {:?}",
         mono_type.type_name()
     );
 }
@@ -214,3 +223,163 @@ fn test_monomorphize_type_different_args_produce_different_types() {
         "不同类型参数 Int64 和 String 应产生不同的单态化结果"
     );
 }
+
+// ── specialize_types 测试 ──────────────────────────────────────
+
+/// Pair: (A: Type, B: Type) -> Type = { first: A, second: B }，类型表含给定实例
+fn make_module_with_pair_instances(instances: Vec<Vec<AstType>>) -> ModuleIR {
+    ModuleIR {
+        types: instances
+            .into_iter()
+            .map(|args| AstType::Generic {
+                name: "Pair".to_string(),
+                name_span: Span::default(),
+                args,
+            })
+            .collect(),
+        generic_types: vec![(
+            "Pair".to_string(),
+            vec!["A".to_string(), "B".to_string()],
+            AstType::NamedStruct {
+                name: "Pair".to_string(),
+                name_span: Span::default(),
+                fields: vec![
+                    StructField::new("first".to_string(), false, type_param("A")),
+                    StructField::new("second".to_string(), false, type_param("B")),
+                ],
+            },
+        )],
+        struct_layouts: vec![StructLayout {
+            name: "Pair".to_string(),
+            fields: vec!["first".to_string(), "second".to_string()],
+            offsets: vec![0, 8],
+            size: 16,
+            align: 8,
+        }],
+        ..ModuleIR::default()
+    }
+}
+
+#[test]
+fn test_specialize_types_adds_layout_per_instance() {
+    // Arrange
+    let mut module = make_module_with_pair_instances(vec![
+        vec![AstType::Int(64), AstType::String],
+        vec![AstType::Bool, AstType::Int(64)],
+    ]);
+    let mut mono = Monomorphizer::new();
+    mono.collect_generic_types(&module);
+
+    // Act
+    mono.specialize_types(&mut module);
+
+    // Assert: 每个实例一份布局，按类型名排序；泛型定义已被消费
+    let names: Vec<&str> = module
+        .struct_layouts
+        .iter()
+        .map(|l| l.name.as_str())
+        .collect();
+    assert_eq!(names, ["Pair", "Pair(bool, int64)", "Pair(int64, string)"]);
+    assert!(module.generic_types.is_empty());
+
+    // Assert: 偏移按代入后的字段类型计算，Int 对齐到 8 字节
+    let layout = &module.struct_layouts[1];
+    assert_eq!(layout.offsets, [0, 8]);
+    assert_eq!((layout.size, layout.align), (16, 8));
+
+    // Assert: 字段类型按类型参数位置代入
+    let instance = mono
+        .monomorphized_types
+        .values()
+        .find(|ty| ty.type_name() == "Pair(int64, string)")
+        .expect("Pair(int64, string) 应已登记");
+    let MonoType::Struct(instance) = instance else {
+        panic!("实例应为结构体: {:?}", instance);
+    };
+    assert_eq!(
+        instance.fields,
+        vec![
+            ("first".to_string(), MonoType::Int(64)),
+            ("second".to_string(), MonoType::String),
+        ]
+    );
+}
+
+#[test]
+fn test_specialize_types_deduplicates_by_name() {
+    // Arrange: 同一实例出现两次
+    let mut module = make_module_with_pair_instances(vec![
+        vec![AstType::Int(64), AstType::Int(64)],
+        vec![AstType::Int(64), AstType::Int(64)],
+    ]);
+    let mut mono = Monomorphizer::new();
+    mono.collect_generic_types(&module);
+
+    // Act
+    mono.specialize_types(&mut module);
+
+    // Assert
+    let count = module
+        .struct_layouts
+        .iter()
+        .filter(|l| l.name == "Pair(int64, int64)")
+        .count();
+    assert_eq!(count, 1);
+    assert_eq!(mono.monomorphized_types.len(), 1);
+}
+
+#[test]
+fn test_compiled_instances_share_name_with_type_table() {
+    // Arrange: Int 与 Int64 是同一类型，实例只应出现一次
+    let source = "
+Box: (T: Type) -> Type = { value: T }
+
+main = {
+    a: Box(Int) = Box(1)
+    b: Box(Int64) = Box(2)
+    c: Box(String) = Box(\"s\")
+    print(a.value + b.value)
+    print(c.value)
+}
+";
+    let module = crate::frontend::Compiler::new()
+        .compile("instances.yx", source)
+        .expect("source should compile");
+
+    // Act
+    let layouts: Vec<StructLayout> = module.struct_layouts.clone();
+    let bytecode = crate::middle::passes::codegen::CodegenContext::new(module)
+        .generate()
+        .expect("codegen should succeed");
+
+    // Assert: 类型表中每个实例都有同名的布局
+    let instance_names: Vec<String> = bytecode
+        .type_table
+        .iter()
+        .map(|ty| ty.type_name())
+        .collect();
+    assert_eq!(instance_names, ["Box(int64)", "Box(string)"]);
+    for name in &instance_names {
+        let layout = layouts
+            .iter()
+            .find(|l| &l.name == name)
+            .unwrap_or_else(|| panic!("缺少 {} 的布局: {:?}", name, layouts));
+        assert_eq!(layout.fields, ["value"]);
+    }
+}
+
+#[test]
+fn test_monomorphize_type_shares_cache_entry_for_builtin_names() {
+    // Arrange
+    let module = make_module_with_generic_list();
+    let mut mono = Monomorphizer::new();
+    mono.collect_generic_types(&module);
+    let generic_id = GenericTypeId::new("List".to_string(), vec!["T".to_string()]);
+
+    // Act: `Int` 与 `Int(64)` 是同一个实例
+    mono.monomorphize_type(&generic_id, &[MonoType::TypeRef("Int".to_string())]);
+    mono.monomorphize_type(&generic_id, &[MonoType::Int(64)]);
+
+    // Assert
+    assert_eq!(mono.monomorphized_types.len(), 1);
+}
@@ -5,7 +5,8 @@
 use crate::frontend::core::parser::ast::Type as AstType;
 use crate::frontend::core::typecheck::{EnumType, MonoType, StructType};
 use crate::middle::core::ir::ModuleIR;
-use crate::middle::passes::mono::instance::{GenericTypeId, TypeId};
+use crate::middle::core::layout::LayoutEngine;
+use crate::middle::passes::mono::instance::{canonical_type, GenericTypeId, TypeId};
 use std::collections::HashMap;
 use super::Monomorphizer;
 
@@ -16,17 +17,52 @@ impl Monomorphizer {
         &mut self,
         module: &ModuleIR,
     ) {
+        for (name, _, ty) in &module.generic_types {
+            let mono_ty = self.type_to_mono_type(ty);
+            self.generic_types.insert(name.clone(), mono_ty);
+        }
+    }
+
+    /// 按模块类型表中的泛型实例特化泛型结构体，每个实例追加一份结构体布局
+    ///
+    /// 实例名取类型表条目的类型名（如 `Box(int64)`），与函数特化名同一格式，
+    /// 各模块特化出的同一实例同名，运行时按名注册即去重。字段偏移按代入后的字段类型
+    /// 由 [`LayoutEngine`] 计算，字段为已有结构体时按其布局内联。
+    pub(super) fn specialize_types(
+        &mut self,
+        module: &mut ModuleIR,
+    ) {
+        let type_params: HashMap<String, Vec<String>> = std::mem::take(&mut module.generic_types)
+            .into_iter()
+            .map(|(name, params, _)| (name, params))
+            .collect();
+        let mut engine = LayoutEngine::new();
+        for layout in &module.struct_layouts {
+            engine.add_layout(layout);
+        }
         for ty in &module.types {
-            // 只处理包含类型变量的泛型类型定义
-            if self.contains_type_var_type(ty) {
-                let mono_ty = self.type_to_mono_type(ty);
-                let name = match ty {
-                    AstType::NamedStruct { name, .. } => name.clone(),
-                    _ => mono_ty.type_name(),
-                };
-                self.generic_types.insert(name, mono_ty);
+            let AstType::Generic { name, args, .. } = ty else {
+                continue;
+            };
+            let Some(params) = type_params.get(name) else {
+                continue;
+            };
+            let type_args: Vec<MonoType> = args.iter().map(|arg| arg.clone().into()).collect();
+            let generic_id = GenericTypeId::new(name.clone(), params.clone());
+            let Some(MonoType::Struct(instance)) = self.monomorphize_type(&generic_id, &type_args)
+            else {
+                continue;
+            };
+            if module
+                .struct_layouts
+                .iter()
+                .all(|layout| layout.name != instance.name)
+            {
+                let layout = engine.layout_fields(&instance.name, &instance.fields);
+                module.struct_layouts.push(layout);
             }
         }
+        module.struct_layouts.sort_by(|a, b| a.name.cmp(&b.name));
     }
 
     fn type_to_mono_type(
@@ -55,6 +91,7 @@ impl Monomorphizer {
                 field_mutability: fields.iter().map(|f| f.is_mut).collect(),
                 field_has_default: fields.iter().map(|f| f.default.is_some()).collect(),
                 interfaces: vec![],
+                assoc_items: Vec::new(),
             }),
             AstType::NamedStruct { name, fields, .. } => MonoType::Struct(StructType {
                 name: name.clone(),
@@ -66,6 +103,7 @@ impl Monomorphizer {
                 field_mutability: fields.iter().map(|f| f.is_mut).collect(),
                 field_has_default: fields.iter().map(|f| f.default.is_some()).collect(),
                 interfaces: vec![],
+                assoc_items: Vec::new(),
             }),
             AstType::Union(variants) => MonoType::Union(
                 variants
@@ -165,6 +203,7 @@ impl Monomorphizer {
             },
             // ConstExpr 只在 Assert 参数中出现，不应到这里
             AstType::ConstExpr(_) => MonoType::TypeRef("<const-expr>".to_string()),
+            AstType::Newtype(inner) => self.type_to_mono_type(inner),
         }
     }
 
@@ -216,6 +255,7 @@ impl Monomorphizer {
             AstType::MetaType { .. } => "MetaType".to_string(),
             AstType::Ref { inner, .. } => format!("&{}", Self::get_type_name(inner)),
             AstType::ConstExpr(_) => "<const-expr>".to_string(),
+            AstType::Newtype(inner) => format!("new {}", Self::get_type_name(inner)),
         }
     }
 
@@ -268,6 +308,7 @@ impl Monomorphizer {
             }
             AstType::Literal { .. } => false,
             AstType::Ptr(inner) => self.contains_type_var_type(inner),
+            AstType::Newtype(inner) => self.contains_type_var_type(inner),
             AstType::Ref { inner, .. } => self.contains_type_var_type(inner),
             AstType::MetaType { .. } => false,
             AstType::ConstExpr(_) => false,
@@ -347,6 +388,7 @@ impl Monomorphizer {
             }
             AstType::Literal { .. } => {}
             AstType::Ptr(inner) => self.collect_type_vars_from_type(inner, type_params, seen),
+            AstType::Newtype(inner) => self.collect_type_vars_from_type(inner, type_params, seen),
             AstType::Ref { inner, .. } => {
                 self.collect_type_vars_from_type(inner, type_params, seen)
             }
@@ -400,6 +442,7 @@ impl Monomorphizer {
                     field_mutability: struct_type.field_mutability.clone(),
                     field_has_default: struct_type.field_has_default.clone(),
                     interfaces: struct_type.interfaces.clone(),
+                    assoc_items: struct_type.assoc_items.clone(),
                 }))
             }
             MonoType::Enum(enum_type) => Some(MonoType::Enum(EnumType {
@@ -478,6 +521,11 @@ impl Monomorphizer {
                     ty.clone()
                 }
             }
+            MonoType::TypeRef(name) => type_params
+                .iter()
+                .position(|p| p == name)
+                .and_then(|idx| type_args.get(idx).cloned())
+                .unwrap_or_else(|| ty.clone()),
             MonoType::Struct(struct_type) => MonoType::Struct(StructType {
                 name: struct_type.name.clone(),
                 fields: struct_type
@@ -494,6 +542,7 @@ impl Monomorphizer {
                 field_mutability: struct_type.field_mutability.clone(),
                 field_has_default: struct_type.field_has_default.clone(),
                 interfaces: struct_type.interfaces.clone(),
+                assoc_items: struct_type.assoc_items.clone(),
             }),
             MonoType::List(elem) => MonoType::List(Box::new(self.substitute_type_args(
                 elem,
@@ -578,12 +627,11 @@ impl Monomorphizer {
         if type_args.is_empty() {
             generic_id.name().to_string()
         } else {
-            let args_str = type_args
-                .iter()
-                .map(|t| t.type_name())
-                .collect::<Vec<_>>()
-                .join("_");
-            format!("{}_{}", generic_id.name(), args_str)
+            MonoType::Generic {
+                name: generic_id.name().to_string(),
+                args: type_args.iter().map(canonical_type).collect(),
+            }
+            .type_name()
         }
     }
 
@@ -665,14 +713,15 @@ impl Monomorphizer {
             | MonoType::Float(_)
             | MonoType::Char
             | MonoType::String
-            | MonoType::Bytes => {}
+            | MonoType::Bytes
+            | MonoType::Any => {}
             MonoType::Union(types) | MonoType::Intersection(types) => {
                 types
                     .iter()
                     .for_each(|t| self.collect_type_vars_from_mono_type(t, type_params, seen));
             }
             MonoType::Arc(inner) => self.collect_type_vars_from_mono_type(inner, type_params, seen),
-            MonoType::Weak(inner) => {
+            MonoType::Weak(inner) | MonoType::Async(inner) => {
                 self.collect_type_vars_from_mono_type(inner, type_params, seen)
             }
             MonoType::Ref { inner, .. } => {
@@ -0,0 +1,331 @@
+//! SSA 构造
+//!
+//! 1. 以跳转目标和跳转、返回之后的指令为首切分基本块，丢弃不可达块
+//! 2. 只为在某块中先使用后定值的寄存器（跨块活跃）在迭代支配边界上插入 φ
+//! 3. 沿支配树先序遍历重命名：每次定值分配新值，使用取当前最近的定值
+//!
+//! 读取从未写入的寄存器得到 unit，构造时在入口块以 `Load void` 显式给出该值。
+
+use std::collections::{BTreeSet, HashMap};
+
+use super::operands::{self, register, set_register};
+use super::{Phi, SsaBlock, SsaFunction, Terminator};
+use crate::middle::core::ir::{ConstValue, FunctionIR, Instruction, Operand};
+
+pub(super) fn construct(func: &FunctionIR) -> SsaFunction {
+    let mut ssa = SsaFunction {
+        name: func.name.clone(),
+        params: func.params.clone(),
+        return_type: func.return_type.clone(),
+        locals: func.locals.clone(),
+        generic_params: func.generic_params.clone(),
+        blocks: split_blocks(func.all_instructions().cloned().collect()),
+        value_count: 0,
+    };
+    ssa.remove_unreachable_blocks();
+    let phi_vars = insert_phis(&mut ssa);
+    Renamer::new(&ssa).run(&mut ssa, &phi_vars);
+    ssa
+}
+
+/// 切分基本块，块 0 为入口且没有前驱；前驱留待删除不可达块时填写
+fn split_blocks(code: Vec<Instruction>) -> Vec<SsaBlock> {
+    let len = code.len();
+    let targets_of = |instr: &Instruction| -> Vec<usize> {
+        instr
+            .jump_targets()
+            .into_iter()
+            .map(|t| t.min(len))
+            .collect()
+    };
+    let ends_block = |instr: &Instruction| {
+        !instr.jump_targets().is_empty()
+            || matches!(instr, Instruction::Ret(_) | Instruction::TailCall { .. })
+    };
+
+    let mut leaders = BTreeSet::from([0]);
+    for (i, instr) in code.iter().enumerate() {
+        leaders.extend(targets_of(instr));
+        if ends_block(instr) {
+            leaders.insert(i + 1);
+        }
+    }
+    // 落出末尾等价于 `Ret(None)`，需要一个位于末尾的块
+    if code.last().is_none_or(|last| !ends_block(last)) {
+        leaders.insert(len);
+    }
+    let leaders: Vec<usize> = leaders.into_iter().collect();
+    // 入口被跳回时在前面补一个空块，使入口没有前驱
+    let entry_is_target = code.iter().any(|instr| targets_of(instr).contains(&0));
+    let offset = usize::from(entry_is_target);
+    let block_at = |i: usize| offset + leaders.binary_search(&i).expect("jump target is a leader");
+
+    let mut blocks = Vec::new();
+    if entry_is_target {
+        blocks.push(SsaBlock {
+            phis: Vec::new(),
+            instructions: Vec::new(),
+            terminator: Terminator::Jump(1),
+            preds: Vec::new(),
+        });
+    }
+    let mut code = code.into_iter();
+    for (k, &start) in leaders.iter().enumerate() {
+        let end = leaders.get(k + 1).copied().unwrap_or(len);
+        let next = offset + k + 1;
+        let mut instructions: Vec<Instruction> = code.by_ref().take(end - start).collect();
+        let terminator = if start == len {
+            Terminator::Exit(Instruction::Ret(None))
+        } else if instructions.last().is_some_and(ends_block) {
+            match instructions.pop().expect("checked above") {
+                Instruction::Jmp(t) => Terminator::Jump(block_at(t.min(len))),
+                Instruction::JmpIf(cond, t) => Terminator::Branch {
+                    cond,
+                    then_block: block_at(t.min(len)),
+                    else_block: next,
+                },
+                Instruction::JmpIfNot(cond, t) => Terminator::Branch {
+                    cond,
+                    then_block: next,
+                    else_block: block_at(t.min(len)),
+                },
+                Instruction::Switch {
+                    value,
+                    low,
+                    targets,
+                    default,
+                } => Terminator::Switch {
+                    value,
+                    low,
+                    targets: targets.iter().map(|&t| block_at(t.min(len))).collect(),
+                    default: block_at(default.min(len)),
+                },
+                exit => Terminator::Exit(exit),
+            }
+        } else {
+            Terminator::Jump(next)
+        };
+        // 两个出口相同时条件无关紧要
+        let terminator = match terminator {
+            Terminator::Branch {
+                then_block,
+                else_block,
+                ..
+            } if then_block == else_block => Terminator::Jump(then_block),
+            Terminator::Switch {
+                targets, default, ..
+            } if targets.iter().all(|&t| t == default) => Terminator::Jump(default),
+            other => other,
+        };
+        blocks.push(SsaBlock {
+            phis: Vec::new(),
+            instructions,
+            terminator,
+            preds: Vec::new(),
+        });
+    }
+
+    blocks
+}
+
+/// 在迭代支配边界上插入 φ，返回每个 φ 对应的原寄存器
+fn insert_phis(ssa: &mut SsaFunction) -> Vec<Vec<usize>> {
+    let dom = ssa.dominators();
+    let n = ssa.blocks.len();
+
+    // 每个寄存器的定值块；以及在某块中先使用后定值的寄存器
+    let mut def_blocks: HashMap<usize, Vec<usize>> = HashMap::new();
+    let mut non_local = BTreeSet::new();
+    for (b, block) in ssa.blocks.iter().enumerate() {
+        let mut defined = BTreeSet::new();
+        let exit_uses = exit_uses(&block.terminator);
+        for instr in &block.instructions {
+            for reg in operands::uses(instr) {
+                if !defined.contains(&reg) {
+                    non_local.insert(reg);
+                }
+            }
+            defined.extend(operands::defs(instr));
+        }
+        non_local.extend(exit_uses.into_iter().filter(|r| !defined.contains(r)));
+        for reg in defined {
+            def_blocks.entry(reg).or_default().push(b);
+        }
+    }
+
+    let mut phi_vars = vec![Vec::new(); n];
+    for reg in non_local {
+        let Some(defs) = def_blocks.get(&reg) else {
+            continue;
+        };
+        let mut has_phi = vec![false; n];
+        let mut worklist = defs.clone();
+        let mut queued = vec![false; n];
+        for &b in defs {
+            queued[b] = true;
+        }
+        while let Some(b) = worklist.pop() {
+            for &f in dom.frontier(b) {
+                if has_phi[f] {
+                    continue;
+                }
+                has_phi[f] = true;
+                ssa.blocks[f].phis.push(Phi {
+                    dst: reg,
+                    args: Vec::new(),
+                });
+                phi_vars[f].push(reg);
+                if !queued[f] {
+                    queued[f] = true;
+                    worklist.push(f);
+                }
+            }
+        }
+    }
+    phi_vars
+}
+
+/// 出口使用的寄存器
+fn exit_uses(terminator: &Terminator) -> Vec<usize> {
+    match terminator {
+        Terminator::Exit(instr) => operands::uses(instr),
+        terminator => terminator
+            .condition()
+            .and_then(register)
+            .into_iter()
+            .collect(),
+    }
+}
+
+/// 沿支配树重命名
+struct Renamer {
+    /// 每个寄存器当前可见的值
+    stacks: HashMap<usize, Vec<usize>>,
+    /// 从未写入就被读取的寄存器对应的值
+    undef: HashMap<usize, usize>,
+    children: Vec<Vec<usize>>,
+}
+
+impl Renamer {
+    fn new(ssa: &SsaFunction) -> Self {
+        let dom = ssa.dominators();
+        Renamer {
+            stacks: HashMap::new(),
+            undef: HashMap::new(),
+            children: (0..ssa.blocks.len())
+                .map(|b| dom.children(b).to_vec())
+                .collect(),
+        }
+    }
+
+    fn run(
+        mut self,
+        ssa: &mut SsaFunction,
+        phi_vars: &[Vec<usize>],
+    ) {
+        // (块, 是否为离开事件)；离开时弹出本块压入的值
+        let mut pushed: Vec<Vec<usize>> = vec![Vec::new(); ssa.blocks.len()];
+        let mut work = vec![(0, false)];
+        while let Some((b, leaving)) = work.pop() {
+            if leaving {
+                for reg in pushed[b].drain(..) {
+                    self.stacks.get_mut(&reg).map(Vec::pop);
+                }
+                continue;
+            }
+            self.rename_block(ssa, b, phi_vars, &mut pushed[b]);
+            work.push((b, true));
+            for &child in self.children[b].iter().rev() {
+                work.push((child, false));
+            }
+        }
+
+        // 入口处显式给出未定义的值
+        let mut undef: Vec<(usize, usize)> = self.undef.into_iter().collect();
+        undef.sort_unstable();
+        let loads = undef.into_iter().map(|(_, value)| Instruction::Load {
+            dst: Operand::Local(value),
+            src: Operand::Const(ConstValue::Void),
+        });
+        ssa.blocks[0].instructions.splice(0..0, loads);
+    }
+
+    fn rename_block(
+        &mut self,
+        ssa: &mut SsaFunction,
+        b: usize,
+        phi_vars: &[Vec<usize>],
+        pushed: &mut Vec<usize>,
+    ) {
+        let first = ssa.value_count;
+        ssa.value_count += phi_vars[b].len();
+        for (value, (phi, &reg)) in (first..).zip(ssa.blocks[b].phis.iter_mut().zip(&phi_vars[b])) {
+            self.define(reg, value, pushed);
+            phi.dst = value;
+        }
+
+        let mut instructions = std::mem::take(&mut ssa.blocks[b].instructions);
+        for instr in &mut instructions {
+            let (defs, uses) = operands::operands_mut(instr);
+            for operand in uses {
+                let value = self.current(ssa, register(operand).expect("register operand"));
+                set_register(operand, value);
+            }
+            for operand in defs {
+                let value = ssa.new_value();
+                self.define(register(operand).expect("register operand"), value, pushed);
+                set_register(operand, value);
+            }
+        }
+        ssa.blocks[b].instructions = instructions;
+
+        let mut terminator = std::mem::replace(&mut ssa.blocks[b].terminator, Terminator::Jump(0));
+        match &mut terminator {
+            Terminator::Branch { cond, .. } | Terminator::Switch { value: cond, .. } => {
+                if let Some(reg) = register(cond) {
+                    let value = self.current(ssa, reg);
+                    set_register(cond, value);
+                }
+            }
+            Terminator::Exit(instr) => {
+                for operand in operands::operands_mut(instr).1 {
+                    let value = self.current(ssa, register(operand).expect("register operand"));
+                    set_register(operand, value);
+                }
+            }
+            Terminator::Jump(_) => {}
+        }
+        ssa.blocks[b].terminator = terminator;
+
+        for succ in ssa.blocks[b].successors() {
+            for (i, &reg) in phi_vars[succ].iter().enumerate() {
+                let value = self.current(ssa, reg);
+                ssa.blocks[succ].phis[i]
+                    .args
+                    .push((b, Operand::Local(value)));
+            }
+        }
+    }
+
+    fn define(
+        &mut self,
+        reg: usize,
+        value: usize,
+        pushed: &mut Vec<usize>,
+    ) {
+        self.stacks.entry(reg).or_default().push(value);
+        pushed.push(reg);
+    }
+
+    /// 寄存器当前的值；从未写入时为 unit
+    fn current(
+        &mut self,
+        ssa: &mut SsaFunction,
+        reg: usize,
+    ) -> usize {
+        if let Some(&value) = self.stacks.get(&reg).and_then(|s| s.last()) {
+            return value;
+        }
+        *self.undef.entry(reg).or_insert_with(|| ssa.new_value())
+    }
+}
@@ -0,0 +1,339 @@
+//! SSA 析构
+//!
+//! 1. 拆分关键边：多出口块通往含 φ 块的边上插入只含跳转的新块，复制才不会在另一条
+//!    出边上执行；跳转表中通往同一块的各项视为同一条边
+//! 2. φ 化为前驱末尾的并行复制，按依赖顺序串行化，环借助临时值打断
+//! 3. 按活跃性构造冲突图，贪心着色重新分配寄存器；复制两端尽量同色，同色的复制随之消去
+//! 4. 按块顺序排布，省略跳到下一块的跳转
+
+use std::collections::HashSet;
+
+use super::operands::{self, register, set_register};
+use super::{SsaBlock, SsaFunction, Terminator};
+use crate::middle::core::ir::{BasicBlock, FunctionIR, Instruction, Operand};
+
+pub(super) fn destruct(mut ssa: SsaFunction) -> FunctionIR {
+    split_critical_edges(&mut ssa);
+    lower_phis(&mut ssa);
+    allocate_registers(&mut ssa);
+    FunctionIR {
+        name: ssa.name,
+        params: ssa.params,
+        return_type: ssa.return_type,
+        locals: ssa.locals,
+        blocks: vec![BasicBlock {
+            label: 0,
+            instructions: linearize(&ssa.blocks),
+            successors: Vec::new(),
+        }],
+        entry: 0,
+        generic_params: ssa.generic_params,
+    }
+}
+
+fn split_critical_edges(ssa: &mut SsaFunction) {
+    for b in 0..ssa.blocks.len() {
+        let succs = ssa.blocks[b].successors();
+        if succs.len() < 2 {
+            continue;
+        }
+        for target in succs {
+            if ssa.blocks[target].phis.is_empty() {
+                continue;
+            }
+            let middle = ssa.blocks.len();
+            ssa.blocks.push(SsaBlock {
+                phis: Vec::new(),
+                instructions: Vec::new(),
+                terminator: Terminator::Jump(target),
+                preds: vec![b],
+            });
+            // 跳转表中通往同一目标的各项共用这一条边
+            for to in ssa.blocks[b].terminator.targets_mut() {
+                if *to == target {
+                    *to = middle;
+                }
+            }
+            let target = &mut ssa.blocks[target];
+            if let Some(pred) = target.preds.iter_mut().find(|p| **p == b) {
+                *pred = middle;
+            }
+            for phi in &mut target.phis {
+                if let Some((from, _)) = phi.args.iter_mut().find(|(from, _)| *from == b) {
+                    *from = middle;
+                }
+            }
+        }
+    }
+}
+
+/// 每个前驱末尾追加 φ 的复制；关键边已拆分，前驱只有这一个后继
+fn lower_phis(ssa: &mut SsaFunction) {
+    for s in 0..ssa.blocks.len() {
+        let phis = std::mem::take(&mut ssa.blocks[s].phis);
+        if phis.is_empty() {
+            continue;
+        }
+        for pred in ssa.blocks[s].preds.clone() {
+            let copies = phis
+                .iter()
+                .filter_map(|phi| {
+                    let (_, arg) = phi.args.iter().find(|(from, _)| *from == pred)?;
+                    Some((phi.dst, arg.clone()))
+                })
+                .collect();
+            let moves = sequentialize(copies, ssa);
+            ssa.blocks[pred].instructions.extend(moves);
+        }
+    }
+}
+
+/// 把并行复制排成等价的串行复制
+fn sequentialize(
+    copies: Vec<(usize, Operand)>,
+    ssa: &mut SsaFunction,
+) -> Vec<Instruction> {
+    let mut pending: Vec<(usize, Operand)> = copies
+        .into_iter()
+        .filter(|(dst, src)| register(src) != Some(*dst))
+        .collect();
+    let mut out = Vec::new();
+    while !pending.is_empty() {
+        // 目标不再被其余复制读取的复制可以先做
+        let ready = pending
+            .iter()
+            .position(|(dst, _)| !pending.iter().any(|(_, src)| register(src) == Some(*dst)));
+        if let Some(i) = ready {
+            let (dst, src) = pending.remove(i);
+            out.push(copy(dst, src));
+            continue;
+        }
+        // 只剩环：先把一个目标的旧值移到临时值
+        let dst = pending[0].0;
+        let temp = ssa.new_value();
+        out.push(copy(temp, Operand::Local(dst)));
+        for (_, src) in &mut pending {
+            if register(src) == Some(dst) {
+                *src = Operand::Local(temp);
+            }
+        }
+    }
+    out
+}
+
+fn copy(
+    dst: usize,
+    src: Operand,
+) -> Instruction {
+    match src {
+        Operand::Const(_) => Instruction::Load {
+            dst: Operand::Local(dst),
+            src,
+        },
+        _ => Instruction::Move {
+            dst: Operand::Local(dst),
+            src,
+        },
+    }
+}
+
+/// 出口使用的值
+fn exit_uses(terminator: &Terminator) -> Vec<usize> {
+    match terminator {
+        Terminator::Exit(instr) => operands::uses(instr),
+        terminator => terminator
+            .condition()
+            .and_then(register)
+            .into_iter()
+            .collect(),
+    }
+}
+
+/// 不带 `dst` 的调用由虚拟机写入寄存器 0，存在时不分配寄存器 0
+fn clobbers_register_zero(instr: &Instruction) -> bool {
+    matches!(
+        instr,
+        Instruction::Call { dst: None, .. }
+            | Instruction::CallDyn { dst: None, .. }
+            | Instruction::CallVirt { dst: None, .. }
+            | Instruction::InvokeVirtual { dst: None, .. }
+    )
+}
+
+fn allocate_registers(ssa: &mut SsaFunction) {
+    let n = ssa.value_count;
+    let blocks = &ssa.blocks;
+
+    // 活跃性：逆序迭代到不动点
+    let mut live_in: Vec<HashSet<usize>> = vec![HashSet::new(); blocks.len()];
+    let mut live_out: Vec<HashSet<usize>> = vec![HashSet::new(); blocks.len()];
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for b in (0..blocks.len()).rev() {
+            let out: HashSet<usize> = blocks[b]
+                .successors()
+                .iter()
+                .flat_map(|s| live_in[*s].iter().copied())
+                .collect();
+            let mut live = out.clone();
+            live.extend(exit_uses(&blocks[b].terminator));
+            for instr in blocks[b].instructions.iter().rev() {
+                for def in operands::defs(instr) {
+                    live.remove(&def);
+                }
+                live.extend(operands::uses(instr));
+            }
+            if live != live_in[b] || out != live_out[b] {
+                live_in[b] = live;
+                live_out[b] = out;
+                changed = true;
+            }
+        }
+    }
+
+    // 冲突图：定值与其后仍活跃的值冲突；复制的两端不因复制本身冲突
+    let mut interferes: Vec<HashSet<usize>> = vec![HashSet::new(); n];
+    let mut copy_partners: Vec<Vec<usize>> = vec![Vec::new(); n];
+    for (b, block) in blocks.iter().enumerate() {
+        let mut live = live_out[b].clone();
+        live.extend(exit_uses(&block.terminator));
+        for instr in block.instructions.iter().rev() {
+            let copied = match instr {
+                Instruction::Move { dst, src } => {
+                    let pair = register(dst).zip(register(src));
+                    if let Some((d, s)) = pair {
+                        copy_partners[d].push(s);
+                        copy_partners[s].push(d);
+                    }
+                    pair.map(|(_, s)| s)
+                }
+                _ => None,
+            };
+            for def in operands::defs(instr) {
+                for &other in &live {
+                    if other != def && Some(other) != copied {
+                        interferes[def].insert(other);
+                        interferes[other].insert(def);
+                    }
+                }
+            }
+            for def in operands::defs(instr) {
+                live.remove(&def);
+            }
+            live.extend(operands::uses(instr));
+        }
+    }
+
+    // 按首次定值的顺序着色
+    let reserved = usize::from(
+        blocks
+            .iter()
+            .flat_map(|b| b.instructions.iter())
+            .any(clobbers_register_zero),
+    );
+    let mut color: Vec<Option<usize>> = vec![None; n];
+    let order = blocks.iter().flat_map(|b| {
+        b.instructions
+            .iter()
+            .flat_map(operands::defs)
+            .collect::<Vec<_>>()
+    });
+    for value in order {
+        if color[value].is_some() {
+            continue;
+        }
+        let taken: HashSet<usize> = interferes[value]
+            .iter()
+            .filter_map(|other| color[*other])
+            .collect();
+        let preferred = copy_partners[value]
+            .iter()
+            .filter_map(|partner| color[*partner])
+            .find(|c| !taken.contains(c));
+        color[value] =
+            Some(preferred.unwrap_or_else(|| (reserved..).find(|c| !taken.contains(c)).unwrap()));
+    }
+
+    let recolor = |operand: &mut Operand| {
+        if let Some(value) = register(operand) {
+            set_register(operand, color[value].unwrap_or(value));
+        }
+    };
+    for block in &mut ssa.blocks {
+        for instr in &mut block.instructions {
+            let (defs, uses) = operands::operands_mut(instr);
+            defs.into_iter().chain(uses).for_each(recolor);
+        }
+        block
+            .instructions
+            .retain(|instr| !matches!(instr, Instruction::Move { dst, src } if dst == src));
+        match &mut block.terminator {
+            Terminator::Branch { cond, .. } | Terminator::Switch { value: cond, .. } => {
+                recolor(cond)
+            }
+            Terminator::Exit(instr) => operands::operands_mut(instr)
+                .1
+                .into_iter()
+                .for_each(recolor),
+            Terminator::Jump(_) => {}
+        }
+    }
+}
+
+/// 按块顺序排成平坦指令序列，跳转目标为指令下标
+fn linearize(blocks: &[SsaBlock]) -> Vec<Instruction> {
+    let exit_len = |b: usize| match &blocks[b].terminator {
+        Terminator::Jump(target) => usize::from(*target != b + 1),
+        Terminator::Branch {
+            then_block,
+            else_block,
+            ..
+        } if *then_block == b + 1 || *else_block == b + 1 => 1,
+        Terminator::Branch { .. } => 2,
+        Terminator::Switch { .. } | Terminator::Exit(_) => 1,
+    };
+    let mut starts = Vec::with_capacity(blocks.len());
+    let mut pos = 0;
+    for (b, block) in blocks.iter().enumerate() {
+        starts.push(pos);
+        pos += block.instructions.len() + exit_len(b);
+    }
+
+    let mut out = Vec::with_capacity(pos);
+    for (b, block) in blocks.iter().enumerate() {
+        out.extend(block.instructions.iter().cloned());
+        let next = b + 1;
+        match &block.terminator {
+            Terminator::Jump(target) if *target == next => {}
+            Terminator::Jump(target) => out.push(Instruction::Jmp(starts[*target])),
+            Terminator::Branch {
+                cond,
+                then_block,
+                else_block,
+            } => {
+                if *then_block == next {
+                    out.push(Instruction::JmpIfNot(cond.clone(), starts[*else_block]));
+                } else {
+                    out.push(Instruction::JmpIf(cond.clone(), starts[*then_block]));
+                    if *else_block != next {
+                        out.push(Instruction::Jmp(starts[*else_block]));
+                    }
+                }
+            }
+            Terminator::Switch {
+                value,
+                low,
+                targets,
+                default,
+            } => out.push(Instruction::Switch {
+                value: value.clone(),
+                low: *low,
+                targets: targets.iter().map(|t| starts[*t]).collect(),
+                default: starts[*default],
+            }),
+            Terminator::Exit(instr) => out.push(instr.clone()),
+        }
+    }
+    out
+}
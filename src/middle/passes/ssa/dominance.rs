@@ -0,0 +1,150 @@
+//! 支配树与支配边界
+//!
+//! 采用 Cooper–Harvey–Kennedy 的迭代算法：按逆后序反复求直接支配者直到不动点。
+
+use super::SsaFunction;
+
+/// 支配树
+#[derive(Debug, Clone)]
+pub struct DominatorTree {
+    /// 直接支配者；入口块的直接支配者是它自己
+    idom: Vec<usize>,
+    /// 逆后序中的位置
+    order: Vec<usize>,
+    reverse_postorder: Vec<usize>,
+    children: Vec<Vec<usize>>,
+    frontiers: Vec<Vec<usize>>,
+}
+
+impl DominatorTree {
+    /// 计算函数的支配树；所有块都须从入口可达
+    pub fn compute(func: &SsaFunction) -> Self {
+        let n = func.blocks.len();
+        let succs: Vec<Vec<usize>> = func.blocks.iter().map(|b| b.successors()).collect();
+
+        // 迭代深度优先求后序
+        let mut postorder = Vec::with_capacity(n);
+        let mut visited = vec![false; n];
+        let mut stack = vec![(0usize, 0usize)];
+        visited[0] = true;
+        while let Some((block, next)) = stack.last_mut() {
+            if let Some(&succ) = succs[*block].get(*next) {
+                *next += 1;
+                if !visited[succ] {
+                    visited[succ] = true;
+                    stack.push((succ, 0));
+                }
+            } else {
+                postorder.push(*block);
+                stack.pop();
+            }
+        }
+        let reverse_postorder: Vec<usize> = postorder.into_iter().rev().collect();
+        let mut order = vec![usize::MAX; n];
+        for (i, &b) in reverse_postorder.iter().enumerate() {
+            order[b] = i;
+        }
+
+        const UNDEFINED: usize = usize::MAX;
+        let mut idom = vec![UNDEFINED; n];
+        idom[0] = 0;
+        let intersect = |idom: &[usize], mut a: usize, mut b: usize| {
+            while a != b {
+                while order[a] > order[b] {
+                    a = idom[a];
+                }
+                while order[b] > order[a] {
+                    b = idom[b];
+                }
+            }
+            a
+        };
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for &b in reverse_postorder.iter().skip(1) {
+                let new_idom = func.blocks[b]
+                    .preds
+                    .iter()
+                    .copied()
+                    .filter(|&p| idom[p] != UNDEFINED)
+                    .reduce(|a, p| intersect(&idom, a, p))
+                    .expect("reachable block has a processed pred");
+                if idom[b] != new_idom {
+                    idom[b] = new_idom;
+                    changed = true;
+                }
+            }
+        }
+
+        let mut children = vec![Vec::new(); n];
+        for &b in reverse_postorder.iter().skip(1) {
+            children[idom[b]].push(b);
+        }
+
+        let mut frontiers: Vec<Vec<usize>> = vec![Vec::new(); n];
+        for (b, block) in func.blocks.iter().enumerate() {
+            if block.preds.len() < 2 {
+                continue;
+            }
+            for &pred in &block.preds {
+                let mut runner = pred;
+                while runner != idom[b] {
+                    if !frontiers[runner].contains(&b) {
+                        frontiers[runner].push(b);
+                    }
+                    runner = idom[runner];
+                }
+            }
+        }
+
+        DominatorTree {
+            idom,
+            order,
+            reverse_postorder,
+            children,
+            frontiers,
+        }
+    }
+
+    /// 直接支配者；入口块没有
+    pub fn idom(
+        &self,
+        block: usize,
+    ) -> Option<usize> {
+        (block != 0).then(|| self.idom[block])
+    }
+
+    /// `a` 支配 `b`（含相等）
+    pub fn dominates(
+        &self,
+        a: usize,
+        mut b: usize,
+    ) -> bool {
+        while self.order[b] > self.order[a] {
+            b = self.idom[b];
+        }
+        a == b
+    }
+
+    /// 支配树中的子节点
+    pub fn children(
+        &self,
+        block: usize,
+    ) -> &[usize] {
+        &self.children[block]
+    }
+
+    /// 支配边界：`block` 支配某个前驱、但不严格支配的块
+    pub fn frontier(
+        &self,
+        block: usize,
+    ) -> &[usize] {
+        &self.frontiers[block]
+    }
+
+    /// 逆后序
+    pub fn reverse_postorder(&self) -> &[usize] {
+        &self.reverse_postorder
+    }
+}
@@ -0,0 +1,294 @@
+//! SSA 形式的中层 IR
+//!
+//! `FunctionIR` 是可变寄存器上的平坦指令序列，跳转目标为指令下标，同一寄存器可被多次
+//! 赋值，优化遍难以判断某次使用看到的是哪一次赋值。本模块提供一对转换，让优化遍在
+//! 每个值只定值一次的形式上进行：
+//! - 构造：切分基本块，按支配边界插入 φ（半剪枝：只为跨块活跃的寄存器插入），再沿
+//!   支配树重命名
+//! - 析构：拆分关键边，把 φ 化为前驱末尾的并行复制，再按活跃区间重新分配寄存器
+//!
+//! 只有寄存器参与重命名；局部变量槽（`Load` / `Store` 的 `Operand::Local`）、堆与全局量
+//! 仍按内存访问处理。
+
+mod construct;
+mod destruct;
+mod dominance;
+pub mod operands;
+
+pub use dominance::DominatorTree;
+
+use crate::frontend::core::typecheck::MonoType;
+use crate::middle::core::ir::{FunctionIR, Instruction, Operand};
+
+/// φ 节点：`dst` 取控制流来源前驱对应的值
+#[derive(Debug, Clone)]
+pub struct Phi {
+    pub dst: usize,
+    /// (前驱块, 值)，与所在块的 `preds` 一一对应
+    pub args: Vec<(usize, Operand)>,
+}
+
+/// 基本块的出口
+#[derive(Debug, Clone)]
+#[allow(clippy::large_enum_variant)]
+pub enum Terminator {
+    /// 无条件跳转
+    Jump(usize),
+    /// `cond` 为真时跳到 `then_block`，否则跳到 `else_block`
+    Branch {
+        cond: Operand,
+        then_block: usize,
+        else_block: usize,
+    },
+    /// 跳转表：`value - low` 为 `targets` 的下标时跳到对应块，否则跳到 `default`
+    Switch {
+        value: Operand,
+        low: i64,
+        targets: Vec<usize>,
+        default: usize,
+    },
+    /// 离开函数：`Ret` 或 `TailCall`
+    Exit(Instruction),
+}
+
+impl Terminator {
+    /// 出口读取的操作数：分支条件或跳转表的分派值
+    pub fn condition(&self) -> Option<&Operand> {
+        match self {
+            Terminator::Branch { cond, .. } | Terminator::Switch { value: cond, .. } => Some(cond),
+            Terminator::Jump(_) | Terminator::Exit(_) => None,
+        }
+    }
+
+    /// 所有跳转目标（含重复），可就地改写
+    pub fn targets_mut(&mut self) -> Vec<&mut usize> {
+        match self {
+            Terminator::Jump(target) => vec![target],
+            Terminator::Branch {
+                then_block,
+                else_block,
+                ..
+            } => vec![then_block, else_block],
+            Terminator::Switch {
+                targets, default, ..
+            } => targets.iter_mut().chain(std::iter::once(default)).collect(),
+            Terminator::Exit(_) => Vec::new(),
+        }
+    }
+}
+
+/// SSA 基本块
+#[derive(Debug, Clone)]
+pub struct SsaBlock {
+    pub phis: Vec<Phi>,
+    /// 不含跳转与返回的指令
+    pub instructions: Vec<Instruction>,
+    pub terminator: Terminator,
+    pub preds: Vec<usize>,
+}
+
+impl SsaBlock {
+    /// 后继块
+    pub fn successors(&self) -> Vec<usize> {
+        match &self.terminator {
+            Terminator::Jump(target) => vec![*target],
+            Terminator::Branch {
+                then_block,
+                else_block,
+                ..
+            } => vec![*then_block, *else_block],
+            Terminator::Switch {
+                targets, default, ..
+            } => {
+                // 同一目标只算一条边
+                let mut succs: Vec<usize> = Vec::new();
+                for &target in targets.iter().chain(std::iter::once(default)) {
+                    if !succs.contains(&target) {
+                        succs.push(target);
+                    }
+                }
+                succs
+            }
+            Terminator::Exit(_) => Vec::new(),
+        }
+    }
+}
+
+/// SSA 形式的函数
+///
+/// 值以 `Operand::Local(值编号)` 出现在指令中；块 0 是入口，且所有块都从入口可达。
+#[derive(Debug, Clone)]
+pub struct SsaFunction {
+    pub name: String,
+    pub params: Vec<MonoType>,
+    pub return_type: MonoType,
+    pub locals: Vec<MonoType>,
+    pub generic_params: Option<Vec<String>>,
+    pub blocks: Vec<SsaBlock>,
+    /// 已分配的值个数，新值编号从这里开始
+    pub value_count: usize,
+}
+
+impl SsaFunction {
+    /// 把函数转换为 SSA 形式
+    pub fn construct(func: &FunctionIR) -> Self {
+        construct::construct(func)
+    }
+
+    /// 转换回平坦的寄存器 IR
+    pub fn destruct(self) -> FunctionIR {
+        destruct::destruct(self)
+    }
+
+    /// 分配一个新值
+    pub fn new_value(&mut self) -> usize {
+        self.value_count += 1;
+        self.value_count - 1
+    }
+
+    /// 支配树
+    pub fn dominators(&self) -> DominatorTree {
+        DominatorTree::compute(self)
+    }
+
+    /// 删除从入口不可达的块并重新编号，按出口重新填写前驱
+    ///
+    /// 改写出口后调用：φ 中来自已不存在的边的参数随之去掉。
+    pub fn remove_unreachable_blocks(&mut self) {
+        let n = self.blocks.len();
+        let mut reachable = vec![false; n];
+        let mut stack = vec![0];
+        reachable[0] = true;
+        while let Some(b) = stack.pop() {
+            for succ in self.blocks[b].successors() {
+                if !reachable[succ] {
+                    reachable[succ] = true;
+                    stack.push(succ);
+                }
+            }
+        }
+        let mut new_index = vec![usize::MAX; n];
+        let mut next = 0;
+        for (b, &keep) in reachable.iter().enumerate() {
+            if keep {
+                new_index[b] = next;
+                next += 1;
+            }
+        }
+
+        let blocks = std::mem::take(&mut self.blocks);
+        self.blocks = blocks
+            .into_iter()
+            .zip(&reachable)
+            .filter(|(_, &keep)| keep)
+            .map(|(mut block, _)| {
+                for target in block.terminator.targets_mut() {
+                    *target = new_index[*target];
+                }
+                block.preds.clear();
+                block
+            })
+            .collect();
+        for b in 0..self.blocks.len() {
+            for succ in self.blocks[b].successors() {
+                self.blocks[succ].preds.push(b);
+            }
+        }
+        for block in &mut self.blocks {
+            for phi in &mut block.phis {
+                phi.args.retain_mut(|(from, _)| {
+                    *from = new_index[*from];
+                    block.preds.contains(from)
+                });
+            }
+        }
+    }
+
+    /// 检查 SSA 性质：前驱与后继一致，φ 每个前驱恰有一个参数，每个值只定值一次，
+    /// 定值支配所有使用
+    pub fn verify(&self) -> Result<(), String> {
+        for (b, block) in self.blocks.iter().enumerate() {
+            for succ in block.successors() {
+                let count = |list: &[usize], x: usize| list.iter().filter(|&&y| y == x).count();
+                if count(&self.blocks[succ].preds, b) != count(&block.successors(), succ) {
+                    return Err(format!("block {} is not listed as a pred of {}", b, succ));
+                }
+            }
+            for phi in &block.phis {
+                let mut froms: Vec<usize> = phi.args.iter().map(|(from, _)| *from).collect();
+                let mut preds = block.preds.clone();
+                froms.sort_unstable();
+                preds.sort_unstable();
+                if froms != preds {
+                    return Err(format!(
+                        "phi v{} in block {} does not match its preds",
+                        phi.dst, b
+                    ));
+                }
+            }
+        }
+
+        // 定值位置：(块, 序号)，φ 为 0，第 i 条指令为 i + 1
+        let mut def_site = vec![None; self.value_count];
+        for (b, block) in self.blocks.iter().enumerate() {
+            let phi_defs = block.phis.iter().map(|phi| (phi.dst, 0));
+            let instr_defs = block
+                .instructions
+                .iter()
+                .enumerate()
+                .flat_map(|(i, instr)| operands::defs(instr).into_iter().map(move |v| (v, i + 1)));
+            for (value, pos) in phi_defs.chain(instr_defs) {
+                match def_site.get_mut(value) {
+                    Some(site @ None) => *site = Some((b, pos)),
+                    Some(Some(_)) => return Err(format!("v{} is defined more than once", value)),
+                    None => return Err(format!("v{} is out of range", value)),
+                }
+            }
+        }
+
+        let dom = self.dominators();
+        let check = |value: usize, block: usize, pos: usize| match def_site.get(value) {
+            Some(Some((def_block, def_pos))) if *def_block == block && *def_pos < pos => Ok(()),
+            Some(Some((def_block, _)))
+                if *def_block != block && dom.dominates(*def_block, block) =>
+            {
+                Ok(())
+            }
+            Some(Some(_)) => Err(format!(
+                "v{} does not dominate its use in block {}",
+                value, block
+            )),
+            _ => Err(format!("v{} is used but never defined", value)),
+        };
+        for (b, block) in self.blocks.iter().enumerate() {
+            for phi in &block.phis {
+                for (from, arg) in &phi.args {
+                    if let Some(value) = operands::register(arg) {
+                        // φ 参数在前驱末尾使用
+                        check(value, *from, usize::MAX)?;
+                    }
+                }
+            }
+            for (i, instr) in block.instructions.iter().enumerate() {
+                for value in operands::uses(instr) {
+                    check(value, b, i + 1)?;
+                }
+            }
+            let exit_uses = match &block.terminator {
+                Terminator::Exit(instr) => operands::uses(instr),
+                terminator => terminator
+                    .condition()
+                    .and_then(operands::register)
+                    .into_iter()
+                    .collect(),
+            };
+            for value in exit_uses {
+                check(value, b, usize::MAX)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,237 @@
+//! 指令的定值与使用
+//!
+//! 除 [`all_operands`] 外只列出寄存器操作数：常量、参数、全局量不参与重命名；`Load` 的源与 `Store` 的
+//! 目标是 `Operand::Local` 时指局部变量槽（`LoadLocal` / `StoreLocal`），属于内存访问。
+
+use crate::middle::core::ir::{Instruction, Operand};
+
+/// 寄存器操作数的编号（`Local` 与 `Temp` 共用同一组寄存器）
+pub fn register(operand: &Operand) -> Option<usize> {
+    match operand {
+        Operand::Local(r) | Operand::Temp(r) => Some(*r),
+        _ => None,
+    }
+}
+
+/// 把寄存器操作数改为寄存器 `r`，保留 `Local` / `Temp` 的区别
+///
+/// `Load` 的源按变体区分槽与寄存器，改写时不能把 `Temp` 变成 `Local`。
+pub fn set_register(
+    operand: &mut Operand,
+    r: usize,
+) {
+    match operand {
+        Operand::Temp(_) => *operand = Operand::Temp(r),
+        _ => *operand = Operand::Local(r),
+    }
+}
+
+/// 按指令形态收集 (定值, 使用)；`$iter` 为 `iter` 或 `iter_mut`，`&` 与 `&mut` 两个版本共用
+macro_rules! collect_operands {
+    ($instr:expr, $iter:ident) => {{
+        let mut defs = Vec::new();
+        let mut uses = Vec::new();
+        match $instr {
+            Instruction::Move { dst, src } => {
+                defs.push(dst);
+                uses.push(src);
+            }
+            Instruction::Load { dst, src } => {
+                defs.push(dst);
+                if !matches!(src, Operand::Local(_)) {
+                    uses.push(src);
+                }
+            }
+            Instruction::Store { src, .. } => uses.push(src),
+            Instruction::Push(src) => uses.push(src),
+            Instruction::Pop(dst) => defs.push(dst),
+            Instruction::Add { dst, lhs, rhs, .. }
+            | Instruction::Sub { dst, lhs, rhs, .. }
+            | Instruction::Mul { dst, lhs, rhs, .. }
+            | Instruction::Div { dst, lhs, rhs, .. }
+            | Instruction::Mod { dst, lhs, rhs, .. }
+            | Instruction::And { dst, lhs, rhs }
+            | Instruction::Or { dst, lhs, rhs }
+            | Instruction::Xor { dst, lhs, rhs }
+            | Instruction::Shl { dst, lhs, rhs }
+            | Instruction::Shr { dst, lhs, rhs }
+            | Instruction::Sar { dst, lhs, rhs }
+            | Instruction::Eq { dst, lhs, rhs }
+            | Instruction::Ne { dst, lhs, rhs }
+            | Instruction::Lt { dst, lhs, rhs }
+            | Instruction::Le { dst, lhs, rhs }
+            | Instruction::Gt { dst, lhs, rhs }
+            | Instruction::Ge { dst, lhs, rhs }
+            | Instruction::FAdd { dst, lhs, rhs }
+            | Instruction::FSub { dst, lhs, rhs }
+            | Instruction::FMul { dst, lhs, rhs }
+            | Instruction::FDiv { dst, lhs, rhs }
+            | Instruction::FMod { dst, lhs, rhs }
+            | Instruction::FEq { dst, lhs, rhs }
+            | Instruction::FNe { dst, lhs, rhs }
+            | Instruction::FLt { dst, lhs, rhs }
+            | Instruction::FLe { dst, lhs, rhs }
+            | Instruction::FGt { dst, lhs, rhs }
+            | Instruction::FGe { dst, lhs, rhs }
+            | Instruction::StringConcat { dst, lhs, rhs }
+            | Instruction::StringBuilderAppend { dst, lhs, rhs } => {
+                defs.push(dst);
+                uses.push(lhs);
+                uses.push(rhs);
+            }
+            Instruction::Neg { dst, src }
+            | Instruction::FNeg { dst, src }
+            | Instruction::IntToFloat { dst, src }
+            | Instruction::MakeDyn { dst, src, .. }
+            | Instruction::LoadField { dst, src, .. }
+            | Instruction::LoadRecordField { dst, src, .. }
+            | Instruction::Cast { dst, src, .. }
+            | Instruction::Narrow { dst, src, .. }
+            | Instruction::TypeTest { dst, src, .. }
+            | Instruction::ArcNew { dst, src }
+            | Instruction::RcNew { dst, src }
+            | Instruction::ArcClone { dst, src }
+            | Instruction::PtrFromRef { dst, src }
+            | Instruction::PtrDeref { dst, src }
+            | Instruction::PtrLoad { dst, src }
+            | Instruction::StringLength { dst, src }
+            | Instruction::StringFromInt { dst, src }
+            | Instruction::StringFromFloat { dst, src }
+            | Instruction::StringBuilderNew { dst, src }
+            | Instruction::StringBuilderFinish { dst, src }
+            | Instruction::StackAlloc { dst, src }
+            | Instruction::Alloc { dst, size: src } => {
+                defs.push(dst);
+                uses.push(src);
+            }
+            Instruction::LoadIndex {
+                dst, src, index, ..
+            }
+            | Instruction::StringGetChar { dst, src, index }
+            | Instruction::AllocArray {
+                dst,
+                size: src,
+                elem_size: index,
+            } => {
+                defs.push(dst);
+                uses.push(src);
+                uses.push(index);
+            }
+            Instruction::StoreField { dst, src, .. } | Instruction::PtrStore { dst, src } => {
+                uses.push(dst);
+                uses.push(src);
+            }
+            Instruction::StoreIndex {
+                dst, index, src, ..
+            } => {
+                uses.push(dst);
+                uses.push(index);
+                uses.push(src);
+            }
+            Instruction::JmpIf(cond, _)
+            | Instruction::JmpIfNot(cond, _)
+            | Instruction::Switch { value: cond, .. } => uses.push(cond),
+            Instruction::Call {
+                dst, func, args, ..
+            }
+            | Instruction::CallDyn {
+                dst, func, args, ..
+            } => {
+                defs.extend(dst.$iter());
+                uses.push(func);
+                uses.extend(args.$iter());
+            }
+            Instruction::CallVirt { dst, obj, args, .. }
+            | Instruction::InvokeVirtual { dst, obj, args, .. } => {
+                defs.extend(dst.$iter());
+                uses.push(obj);
+                uses.extend(args.$iter());
+            }
+            Instruction::TailCall { func, args } => {
+                uses.push(func);
+                uses.extend(args.$iter());
+            }
+            Instruction::Ret(value) => uses.extend(value.$iter()),
+            Instruction::Free(src)
+            | Instruction::Drop(src)
+            | Instruction::ArcDrop(src)
+            | Instruction::CloseUpvalue(src)
+            | Instruction::StoreUpvalue { src, .. } => uses.push(src),
+            Instruction::Spawn {
+                closures, result, ..
+            } => {
+                defs.push(result);
+                uses.extend(closures.$iter());
+            }
+            Instruction::SpawnFromList {
+                closures_list,
+                result,
+                ..
+            } => {
+                defs.push(result);
+                uses.push(closures_list);
+            }
+            Instruction::CreateStruct { dst, fields, .. } => {
+                defs.push(dst);
+                uses.extend(fields.$iter());
+            }
+            Instruction::NewDict { dst, keys, values } => {
+                defs.push(dst);
+                uses.extend(keys.$iter());
+                uses.extend(values.$iter());
+            }
+            Instruction::MakeClosure { dst, env, .. } => {
+                defs.push(dst);
+                uses.extend(env.$iter());
+            }
+            Instruction::HeapAlloc { dst, .. } | Instruction::LoadUpvalue { dst, .. } => {
+                defs.push(dst)
+            }
+            Instruction::Dup
+            | Instruction::Swap
+            | Instruction::Jmp(_)
+            | Instruction::Yield
+            | Instruction::UnsafeBlockStart
+            | Instruction::UnsafeBlockEnd => {}
+        }
+        (defs, uses)
+    }};
+}
+
+/// 指令定值与使用的寄存器操作数
+pub fn operands(instr: &Instruction) -> (Vec<&Operand>, Vec<&Operand>) {
+    let (mut defs, mut uses) = collect_operands!(instr, iter);
+    defs.retain(|op| register(op).is_some());
+    uses.retain(|op| register(op).is_some());
+    (defs, uses)
+}
+
+/// 同 [`operands`]，可就地改写
+pub fn operands_mut(instr: &mut Instruction) -> (Vec<&mut Operand>, Vec<&mut Operand>) {
+    let (mut defs, mut uses) = collect_operands!(instr, iter_mut);
+    defs.retain(|op| register(op).is_some());
+    uses.retain(|op| register(op).is_some());
+    (defs, uses)
+}
+
+/// 指令的全部操作数，包括常量、参数与全局量
+pub fn all_operands(instr: &Instruction) -> Vec<&Operand> {
+    let (defs, uses) = collect_operands!(instr, iter);
+    defs.into_iter().chain(uses).collect()
+}
+
+/// 同 [`all_operands`]，可就地改写
+pub fn all_operands_mut(instr: &mut Instruction) -> Vec<&mut Operand> {
+    let (defs, uses) = collect_operands!(instr, iter_mut);
+    defs.into_iter().chain(uses).collect()
+}
+
+/// 指令定值的寄存器
+pub fn defs(instr: &Instruction) -> Vec<usize> {
+    operands(instr).0.into_iter().filter_map(register).collect()
+}
+
+/// 指令使用的寄存器
+pub fn uses(instr: &Instruction) -> Vec<usize> {
+    operands(instr).1.into_iter().filter_map(register).collect()
+}
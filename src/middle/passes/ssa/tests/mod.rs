@@ -0,0 +1,450 @@
+//! SSA 构造与析构测试
+//!
+//! 覆盖：
+//! - φ 只插在汇合点与循环头，块内临时寄存器不插 φ
+//! - 未写入的寄存器读作 unit，verify 拒绝重复定值
+//! - 析构：关键边拆分、交换型并行复制、寄存器压缩
+//! - 往返前后语义一致（小型 IR 求值器与真实虚拟机）
+
+use std::collections::HashMap;
+
+use crate::backends::Executor;
+use crate::frontend::core::typecheck::MonoType;
+use crate::frontend::Compiler;
+use crate::middle::core::ir::{BasicBlock, ConstValue, FunctionIR, Instruction, Operand};
+use crate::middle::passes::ssa::operands::{self, register};
+use crate::middle::passes::ssa::{Phi, SsaBlock, SsaFunction, Terminator};
+use crate::util::span::Span;
+
+fn r(n: usize) -> Operand {
+    Operand::Local(n)
+}
+
+fn int(
+    dst: usize,
+    n: i128,
+) -> Instruction {
+    Instruction::Load {
+        dst: r(dst),
+        src: Operand::Const(ConstValue::Int(n)),
+    }
+}
+
+fn add(
+    dst: usize,
+    lhs: usize,
+    rhs: usize,
+) -> Instruction {
+    Instruction::Add {
+        dst: r(dst),
+        lhs: r(lhs),
+        rhs: r(rhs),
+        span: Span::dummy(),
+    }
+}
+
+fn lt(
+    dst: usize,
+    lhs: usize,
+    rhs: usize,
+) -> Instruction {
+    Instruction::Lt {
+        dst: r(dst),
+        lhs: r(lhs),
+        rhs: r(rhs),
+    }
+}
+
+fn ret(src: usize) -> Instruction {
+    Instruction::Ret(Some(r(src)))
+}
+
+fn function(code: Vec<Instruction>) -> FunctionIR {
+    FunctionIR {
+        name: "f".to_string(),
+        params: Vec::new(),
+        return_type: MonoType::Int(64),
+        locals: Vec::new(),
+        blocks: vec![BasicBlock {
+            label: 0,
+            instructions: code,
+            successors: Vec::new(),
+        }],
+        entry: 0,
+        generic_params: None,
+    }
+}
+
+/// 求值 Load / Move / Store / 算术 / 比较 / 跳转 / Ret 构成的平坦 IR
+fn eval(func: &FunctionIR) -> ConstValue {
+    let code: Vec<&Instruction> = func.all_instructions().collect();
+    let mut regs: HashMap<usize, ConstValue> = HashMap::new();
+    let mut slots: HashMap<usize, ConstValue> = HashMap::new();
+    let read = |regs: &HashMap<usize, ConstValue>, op: &Operand| {
+        regs.get(&register(op).expect("register operand"))
+            .cloned()
+            .unwrap_or(ConstValue::Void)
+    };
+    let as_int = |value: ConstValue| match value {
+        ConstValue::Int(n) => n,
+        other => panic!("expected Int, got {:?}", other),
+    };
+    let mut pc = 0;
+    for _ in 0..100_000 {
+        let Some(instr) = code.get(pc) else {
+            return ConstValue::Void;
+        };
+        pc += 1;
+        match instr {
+            Instruction::Load { dst, src } => {
+                let value = match src {
+                    Operand::Const(c) => c.clone(),
+                    Operand::Local(slot) => slots.get(slot).cloned().unwrap_or(ConstValue::Void),
+                    other => read(&regs, other),
+                };
+                regs.insert(register(dst).unwrap(), value);
+            }
+            Instruction::Move { dst, src } => {
+                let value = read(&regs, src);
+                regs.insert(register(dst).unwrap(), value);
+            }
+            Instruction::Store {
+                dst: Operand::Local(slot),
+                src,
+                ..
+            } => {
+                slots.insert(*slot, read(&regs, src));
+            }
+            Instruction::Add { dst, lhs, rhs, .. }
+            | Instruction::Sub { dst, lhs, rhs, .. }
+            | Instruction::Mul { dst, lhs, rhs, .. }
+            | Instruction::Lt { dst, lhs, rhs } => {
+                let (a, b) = (as_int(read(&regs, lhs)), as_int(read(&regs, rhs)));
+                let value = match instr {
+                    Instruction::Add { .. } => ConstValue::Int(a + b),
+                    Instruction::Sub { .. } => ConstValue::Int(a - b),
+                    Instruction::Mul { .. } => ConstValue::Int(a * b),
+                    _ => ConstValue::Bool(a < b),
+                };
+                regs.insert(register(dst).unwrap(), value);
+            }
+            Instruction::Jmp(t) => pc = *t,
+            Instruction::JmpIf(cond, t) => {
+                if read(&regs, cond) == ConstValue::Bool(true) {
+                    pc = *t;
+                }
+            }
+            Instruction::JmpIfNot(cond, t) => {
+                if read(&regs, cond) != ConstValue::Bool(true) {
+                    pc = *t;
+                }
+            }
+            Instruction::Ret(value) => {
+                return value
+                    .as_ref()
+                    .map_or(ConstValue::Void, |op| read(&regs, op));
+            }
+            other => panic!("unsupported instruction {:?}", other),
+        }
+    }
+    panic!("evaluation did not terminate")
+}
+
+fn round_trip(func: &FunctionIR) -> FunctionIR {
+    let ssa = SsaFunction::construct(func);
+    ssa.verify()
+        .expect("constructed function should be valid SSA");
+    ssa.destruct()
+}
+
+/// if/else 给 r0 赋不同值后返回
+fn diamond(cond: bool) -> FunctionIR {
+    function(vec![
+        Instruction::Load {
+            dst: r(1),
+            src: Operand::Const(ConstValue::Bool(cond)),
+        },
+        Instruction::JmpIfNot(r(1), 4),
+        int(0, 1),
+        Instruction::Jmp(5),
+        int(0, 2),
+        ret(0),
+    ])
+}
+
+/// r0 从 0 加到 10，返回 r0
+fn counting_loop() -> FunctionIR {
+    function(vec![
+        int(0, 0),
+        int(1, 10),
+        lt(2, 0, 1),
+        Instruction::JmpIfNot(r(2), 7),
+        int(3, 1),
+        add(0, 0, 3),
+        Instruction::Jmp(2),
+        ret(0),
+    ])
+}
+
+#[test]
+fn test_straight_line_reassignment_gets_fresh_values() {
+    let ssa = SsaFunction::construct(&function(vec![
+        int(0, 1),
+        add(0, 0, 0),
+        add(0, 0, 0),
+        ret(0),
+    ]));
+    assert!(ssa.verify().is_ok());
+    assert_eq!(ssa.blocks.len(), 1);
+    let defs: Vec<usize> = ssa.blocks[0]
+        .instructions
+        .iter()
+        .flat_map(operands::defs)
+        .collect();
+    assert_eq!(defs, vec![0, 1, 2]);
+    assert!(matches!(
+        &ssa.blocks[0].terminator,
+        Terminator::Exit(Instruction::Ret(Some(Operand::Local(2))))
+    ));
+}
+
+#[test]
+fn test_phis_only_at_joins_for_non_local_registers() {
+    let ssa = SsaFunction::construct(&diamond(true));
+    assert!(ssa.verify().is_ok());
+    assert_eq!(ssa.blocks.len(), 4);
+    let phis: Vec<&Phi> = ssa.blocks.iter().flat_map(|b| &b.phis).collect();
+    assert_eq!(phis.len(), 1);
+    assert_eq!(phis[0].args.len(), 2);
+
+    // 循环里只有被回边带回的 r0 需要 φ，条件与增量是块内临时值
+    let ssa = SsaFunction::construct(&counting_loop());
+    assert!(ssa.verify().is_ok());
+    let header = ssa
+        .blocks
+        .iter()
+        .position(|b| !b.phis.is_empty())
+        .expect("loop header has a phi");
+    assert_eq!(ssa.blocks[header].phis.len(), 1);
+    assert_eq!(ssa.blocks[header].preds.len(), 2);
+    assert!(ssa.dominators().dominates(header, ssa.blocks.len() - 1));
+}
+
+#[test]
+fn test_unwritten_register_reads_unit() {
+    let ssa = SsaFunction::construct(&function(vec![ret(5)]));
+    assert!(ssa.verify().is_ok());
+    assert!(matches!(
+        &ssa.blocks[0].instructions[..],
+        [Instruction::Load {
+            src: Operand::Const(ConstValue::Void),
+            ..
+        }]
+    ));
+    assert_eq!(eval(&ssa.destruct()), ConstValue::Void);
+}
+
+#[test]
+fn test_verify_rejects_invalid_ssa() {
+    let mut ssa = SsaFunction::construct(&function(vec![int(0, 1), add(1, 0, 0), ret(1)]));
+    ssa.blocks[0].instructions.push(int(0, 2));
+    assert!(ssa.verify().unwrap_err().contains("defined more than once"));
+
+    let mut ssa = SsaFunction::construct(&diamond(true));
+    ssa.blocks[3].phis[0].args.pop();
+    assert!(ssa
+        .verify()
+        .unwrap_err()
+        .contains("does not match its preds"));
+}
+
+#[test]
+fn test_round_trip_preserves_semantics() {
+    for func in [diamond(true), diamond(false), counting_loop()] {
+        assert_eq!(eval(&round_trip(&func)), eval(&func));
+    }
+    assert_eq!(eval(&round_trip(&counting_loop())), ConstValue::Int(10));
+
+    // 局部变量槽是内存，不参与重命名
+    let slots = function(vec![
+        int(0, 7),
+        Instruction::Store {
+            dst: r(0),
+            src: r(0),
+            span: Default::default(),
+        },
+        int(0, 1),
+        Instruction::Load {
+            dst: r(1),
+            src: r(0),
+        },
+        add(2, 1, 0),
+        ret(2),
+    ]);
+    assert_eq!(eval(&round_trip(&slots)), ConstValue::Int(8));
+}
+
+#[test]
+fn test_critical_edge_to_loop_header_is_split() {
+    // do-while：回边出自条件跳转，复制不能落在退出路径上
+    let func = function(vec![
+        int(0, 0),
+        int(1, 0),
+        add(1, 1, 0),
+        int(2, 1),
+        add(0, 0, 2),
+        int(3, 5),
+        lt(4, 0, 3),
+        Instruction::JmpIf(r(4), 2),
+        ret(1),
+    ]);
+    assert_eq!(eval(&func), ConstValue::Int(10));
+    assert_eq!(eval(&round_trip(&func)), ConstValue::Int(10));
+}
+
+#[test]
+fn test_swapping_phis_are_sequentialized() {
+    let block = |phis, instructions, terminator, preds| SsaBlock {
+        phis,
+        instructions,
+        terminator,
+        preds,
+    };
+    // 每轮交换 (a, b)，共三轮后返回 a * 10 + b
+    let ssa = SsaFunction {
+        name: "swap".to_string(),
+        params: Vec::new(),
+        return_type: MonoType::Int(64),
+        locals: Vec::new(),
+        generic_params: None,
+        blocks: vec![
+            block(
+                Vec::new(),
+                vec![int(0, 1), int(1, 2), int(2, 0)],
+                Terminator::Jump(1),
+                Vec::new(),
+            ),
+            block(
+                vec![
+                    Phi {
+                        dst: 3,
+                        args: vec![(0, r(0)), (2, r(4))],
+                    },
+                    Phi {
+                        dst: 4,
+                        args: vec![(0, r(1)), (2, r(3))],
+                    },
+                    Phi {
+                        dst: 5,
+                        args: vec![(0, r(2)), (2, r(7))],
+                    },
+                ],
+                vec![int(8, 3), lt(9, 5, 8)],
+                Terminator::Branch {
+                    cond: r(9),
+                    then_block: 2,
+                    else_block: 3,
+                },
+                vec![0, 2],
+            ),
+            block(
+                Vec::new(),
+                vec![int(10, 1), add(7, 5, 10)],
+                Terminator::Jump(1),
+                vec![1],
+            ),
+            block(
+                Vec::new(),
+                vec![
+                    int(11, 10),
+                    Instruction::Mul {
+                        dst: r(12),
+                        lhs: r(3),
+                        rhs: r(11),
+                        span: Span::dummy(),
+                    },
+                    add(13, 12, 4),
+                ],
+                Terminator::Exit(ret(13)),
+                vec![1],
+            ),
+        ],
+        value_count: 14,
+    };
+    assert!(ssa.verify().is_ok());
+    assert_eq!(eval(&ssa.destruct()), ConstValue::Int(21));
+}
+
+#[test]
+fn test_destruct_reuses_registers() {
+    // 599 个寄存器依次相加，超出字节码的 256 个寄存器
+    let mut code = vec![int(0, 0)];
+    for i in 1..300 {
+        code.push(int(2 * i - 1, 1));
+        code.push(add(2 * i, 2 * i - 2, 2 * i - 1));
+    }
+    code.push(ret(598));
+    let func = round_trip(&function(code));
+    let max_register = func
+        .all_instructions()
+        .flat_map(|instr| {
+            operands::defs(instr)
+                .into_iter()
+                .chain(operands::uses(instr))
+        })
+        .max()
+        .unwrap();
+    assert!(max_register < 4, "uses register {}", max_register);
+    assert_eq!(eval(&func), ConstValue::Int(299));
+}
+
+#[test]
+fn test_round_trip_runs_on_the_vm() {
+    let source = "\
+use std.assert
+
+odd_sum: (n: Int) -> Int = (n) => {
+    mut i = n
+    mut sum = 0
+    while i > 0 {
+        if i % 2 == 1 {
+            sum = sum + i
+        } else {
+            sum = sum - 1
+        }
+        i = i - 1
+    }
+    return sum
+}
+
+grade: (n: Int) -> String = (n) => {
+    match n {
+        1 | 2 => { return \"low\" },
+        3 => { return \"mid\" },
+        _ => { return \"high\" }
+    }
+    return \"?\"
+}
+
+main = {
+    assert_eq(odd_sum(6), 6)
+    assert_eq(odd_sum(7), 13)
+    assert_eq(grade(2), \"low\")
+    assert_eq(grade(3), \"mid\")
+    assert_eq(grade(9), \"high\")
+}
+";
+    let mut module = Compiler::new()
+        .compile("ssa.yx", source)
+        .expect("source should compile");
+    for func in &mut module.functions {
+        *func = round_trip(func);
+    }
+    let mut ctx = crate::middle::passes::codegen::CodegenContext::new(module);
+    let bytecode = ctx.generate().expect("codegen should succeed");
+    let module = crate::middle::bytecode::BytecodeModule::from(bytecode);
+    let mut interpreter = crate::backends::interpreter::Interpreter::new();
+    interpreter
+        .execute_module(&module)
+        .expect("round-tripped program should run");
+}
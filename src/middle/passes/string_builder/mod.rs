@@ -0,0 +1,244 @@
+//! 循环内字符串拼接改用构建器
+//!
+//! 字符串不可变，`s = s + x` 每次都复制整个 `s`，在循环中反复拼接因此是平方复杂度。本遍找出
+//! 循环中只以这种形式出现的字符串变量，在循环前把变量换成构建器（`StringBuilderNew`），
+//! 循环内的拼接改为原地追加（`StringBuilderAppend`），在循环出口拼成字符串
+//! （`StringBuilderFinish`），整个循环只复制一次。
+//!
+//! 变量（局部变量槽 `v`）可改写的条件：
+//! - 循环内每次读取 `t = load v` 都只用作拼接链 `t + x1 + ... + xn` 的最左端，链的结果写入
+//!   寄存器 `v` 后紧接 `store v, v`；循环内没有其他指令读写槽 `v` 或寄存器 `v`
+//! - 循环只经由跳到循环之后第一条指令（条件不成立、`break`）或 `Ret` 离开，外部只从循环头进入
+//!
+//! `StringConcat` 只在两侧都是字符串时生成，链上的值一定是字符串。本遍依赖 IR 生成的形态
+//! （变量读写经由局部变量槽、临时寄存器只定值一次），在其他优化之前运行。
+
+use crate::middle::core::ir::{BasicBlock, FunctionIR, Instruction, ModuleIR, Operand};
+use crate::middle::passes::ssa::operands::{self, register};
+use crate::util::span::Span;
+
+/// 改写模块中的每个函数，返回改写的循环变量个数
+pub fn rewrite_module(module: &mut ModuleIR) -> usize {
+    module.functions.iter_mut().map(rewrite_function).sum()
+}
+
+/// 改写函数中循环内反复拼接的字符串变量，返回改写的个数
+///
+/// 有改写时函数体合并为一个基本块，跳转目标是指令下标。
+pub fn rewrite_function(func: &mut FunctionIR) -> usize {
+    let mut code: Vec<Instruction> = func.all_instructions().cloned().collect();
+    let mut count = 0;
+    while let Some((start, end, var, chain)) = find_candidate(&code) {
+        code = rewrite(code, start, end, var, &chain);
+        count += 1;
+    }
+    if count > 0 {
+        func.blocks = vec![BasicBlock {
+            label: 0,
+            instructions: code,
+            successors: Vec::new(),
+        }];
+        func.entry = 0;
+    }
+    count
+}
+
+/// 找出一个可改写的 (循环头, 回跳指令, 变量, 拼接链上的指令)，外层循环优先
+fn find_candidate(code: &[Instruction]) -> Option<(usize, usize, usize, Vec<usize>)> {
+    let mut loops: Vec<(usize, usize)> = code
+        .iter()
+        .enumerate()
+        .filter_map(|(j, instr)| match instr {
+            Instruction::Jmp(t) if *t <= j => Some((*t, j)),
+            _ => None,
+        })
+        .collect();
+    loops.sort_by_key(|&(t, j)| std::cmp::Reverse(j - t));
+
+    let mut def_count = Vec::new();
+    let mut use_count = Vec::new();
+    for instr in code {
+        for (counts, regs) in [
+            (&mut def_count, operands::defs(instr)),
+            (&mut use_count, operands::uses(instr)),
+        ] {
+            for r in regs {
+                if counts.len() <= r {
+                    counts.resize(r + 1, 0);
+                }
+                counts[r] += 1;
+            }
+        }
+    }
+    let single =
+        |r: usize| def_count.get(r).copied() == Some(1) && use_count.get(r).copied() == Some(1);
+
+    for (start, end) in loops {
+        if !is_single_entry_exit(code, start, end) {
+            continue;
+        }
+        let mut vars: Vec<usize> = code[start..=end]
+            .iter()
+            .filter_map(|instr| match instr {
+                Instruction::StringConcat { dst, .. } => register(dst),
+                _ => None,
+            })
+            .collect();
+        vars.sort_unstable();
+        vars.dedup();
+        for var in vars {
+            if let Some(chain) = appends(code, start, end, var, &single) {
+                return Some((start, end, var, chain));
+            }
+        }
+    }
+    None
+}
+
+/// 循环 `[start, end]` 只从循环头进入，只跳到 `end + 1` 离开
+fn is_single_entry_exit(
+    code: &[Instruction],
+    start: usize,
+    end: usize,
+) -> bool {
+    code.iter().enumerate().all(|(i, instr)| {
+        instr.jump_targets().into_iter().all(|target| {
+            if (start..=end).contains(&i) {
+                (start..=end + 1).contains(&target)
+            } else {
+                target <= start || target > end
+            }
+        })
+    })
+}
+
+/// 变量 `var` 在循环中的全部出现都构成拼接链时，返回链上 `StringConcat` 的下标
+fn appends(
+    code: &[Instruction],
+    start: usize,
+    end: usize,
+    var: usize,
+    single: &impl Fn(usize) -> bool,
+) -> Option<Vec<usize>> {
+    let mut accepted = Vec::new();
+    let mut chain = Vec::new();
+    for i in start..=end {
+        let Instruction::Load {
+            dst,
+            src: Operand::Local(slot),
+        } = &code[i]
+        else {
+            continue;
+        };
+        if *slot != var {
+            continue;
+        }
+        // 沿唯一的使用者走到写回 `var` 的拼接
+        let mut value = register(dst)?;
+        let mut at = i;
+        loop {
+            if !single(value) {
+                return None;
+            }
+            at = (at + 1..=end).find(|&k| operands::uses(&code[k]).contains(&value))?;
+            let Instruction::StringConcat { dst, lhs, .. } = &code[at] else {
+                return None;
+            };
+            if register(lhs) != Some(value) {
+                return None;
+            }
+            chain.push(at);
+            value = register(dst)?;
+            if value == var {
+                break;
+            }
+        }
+        let store_back = matches!(
+            code.get(at + 1),
+            Some(Instruction::Store { dst: Operand::Local(slot), src, .. })
+                if *slot == var && register(src) == Some(var)
+        );
+        if !store_back || at + 1 > end {
+            return None;
+        }
+        accepted.extend([i, at + 1]);
+    }
+    if chain.is_empty() {
+        return None;
+    }
+    accepted.extend(&chain);
+
+    // 其余指令不得碰槽 `var` 与寄存器 `var`
+    let touches = |instr: &Instruction| {
+        operands::defs(instr).contains(&var)
+            || operands::uses(instr).contains(&var)
+            || matches!(
+                instr,
+                Instruction::Load { src: Operand::Local(slot), .. }
+                    | Instruction::Store { dst: Operand::Local(slot), .. } if *slot == var
+            )
+    };
+    let clean = (start..=end).all(|i| accepted.contains(&i) || !touches(&code[i]));
+    clean.then_some(chain)
+}
+
+/// 在循环前后插入构建器的创建与拼合，链上的拼接改为追加
+fn rewrite(
+    code: Vec<Instruction>,
+    start: usize,
+    end: usize,
+    var: usize,
+    chain: &[usize],
+) -> Vec<Instruction> {
+    let v = || Operand::Local(var);
+    let around = |instr: Instruction| {
+        [
+            Instruction::Load { dst: v(), src: v() },
+            instr,
+            Instruction::Store {
+                dst: v(),
+                src: v(),
+                span: Span::dummy(),
+            },
+        ]
+    };
+    let enter = around(Instruction::StringBuilderNew { dst: v(), src: v() });
+    let exit = around(Instruction::StringBuilderFinish { dst: v(), src: v() });
+    let (enter_len, exit_len) = (enter.len(), exit.len());
+    // 原下标 k 的指令在改写后的位置
+    let position =
+        |k: usize| k + if k >= start { enter_len } else { 0 } + if k > end { exit_len } else { 0 };
+
+    let len = code.len();
+    let mut out = Vec::with_capacity(len + enter_len + exit_len);
+    for (i, mut instr) in code.into_iter().enumerate() {
+        if i == start {
+            out.extend(enter.clone());
+        }
+        if i == end + 1 {
+            out.extend(exit.clone());
+        }
+        let inside = (start..=end).contains(&i);
+        for target in instr.jump_targets_mut() {
+            *target = match *target {
+                t if t == start && !inside => position(t) - enter_len,
+                t if t == end + 1 && inside => position(t) - exit_len,
+                t => position(t),
+            };
+        }
+        if chain.contains(&i) {
+            if let Instruction::StringConcat { dst, lhs, rhs } = instr {
+                instr = Instruction::StringBuilderAppend { dst, lhs, rhs };
+            }
+        }
+        out.push(instr);
+    }
+    // 循环是函数的最后一段
+    if end + 1 == len {
+        out.extend(exit);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests;
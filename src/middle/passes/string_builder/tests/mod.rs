@@ -0,0 +1,171 @@
+//! 字符串构建器改写测试
+//!
+//! 覆盖：
+//! - 循环中只以 `s = s + ...` 出现的变量改用构建器，链上的拼接全部改为追加
+//! - 循环中另有读取的变量不改写
+//! - 改写后的程序（含循环中提前返回、嵌套循环、零次迭代）在虚拟机上运行结果不变
+
+use crate::backends::Executor;
+use crate::frontend::config::{CompileConfig, OptLevel};
+use crate::frontend::Compiler;
+use crate::middle::core::ir::{FunctionIR, Instruction};
+use crate::middle::passes::string_builder::rewrite_function;
+
+/// 以 `-O0` 编译并取出函数
+fn unoptimized(
+    source: &str,
+    name: &str,
+) -> FunctionIR {
+    let config = CompileConfig::new().with_opt_level(OptLevel::O0);
+    Compiler::with_config(config)
+        .compile("string_builder.yx", source)
+        .expect("source should compile")
+        .functions
+        .into_iter()
+        .find(|f| f.name == name)
+        .expect("function should exist")
+}
+
+fn count(
+    func: &FunctionIR,
+    pred: impl Fn(&Instruction) -> bool,
+) -> usize {
+    func.all_instructions().filter(|i| pred(i)).count()
+}
+
+#[test]
+fn test_loop_concatenation_uses_builder() {
+    let mut func = unoptimized(
+        "\
+main: () -> Int = {
+    mut result = \"\";
+    mut i = 0;
+    while i < 500 {
+        result = result + \"Hello\";
+        result = result + \" \" + \"World\";
+        result = result + \"!\";
+        i = i + 1;
+    };
+    return 0
+}
+",
+        "main",
+    );
+    assert_eq!(rewrite_function(&mut func), 1);
+    assert_eq!(
+        count(&func, |i| matches!(i, Instruction::StringBuilderNew { .. })),
+        1
+    );
+    assert_eq!(
+        count(&func, |i| matches!(
+            i,
+            Instruction::StringBuilderAppend { .. }
+        )),
+        4
+    );
+    assert_eq!(
+        count(&func, |i| matches!(
+            i,
+            Instruction::StringBuilderFinish { .. }
+        )),
+        1
+    );
+    assert_eq!(
+        count(&func, |i| matches!(i, Instruction::StringConcat { .. })),
+        0
+    );
+}
+
+#[test]
+fn test_variable_read_in_loop_is_kept() {
+    let mut func = unoptimized(
+        "\
+use std.io
+
+main = {
+    mut s = \"\"
+    mut i = 0
+    while i < 3 {
+        s = s + \"x\"
+        io.println(s)
+        i = i + 1
+    }
+}
+",
+        "main",
+    );
+    assert_eq!(rewrite_function(&mut func), 0);
+    assert_eq!(
+        count(&func, |i| matches!(i, Instruction::StringConcat { .. })),
+        1
+    );
+}
+
+#[test]
+fn test_builder_program_runs_on_the_vm() {
+    let source = "\
+use std.assert
+
+repeat_word: (word: String, n: Int) -> String = (word, n) => {
+    mut s = \"\"
+    mut i = 0
+    while i < n {
+        s = s + word + \",\"
+        i = i + 1
+    }
+    return s
+}
+
+until_three: () -> String = {
+    mut s = \"<\"
+    mut i = 0
+    while i < 10 {
+        if i == 3 {
+            return s + \">\"
+        }
+        s = s + \"ab\"
+        i = i + 1
+    }
+    return s
+}
+
+grid: () -> String = {
+    mut s = \"\"
+    mut i = 0
+    while i < 2 {
+        mut j = 0
+        while j < 2 {
+            s = s + \"x\"
+            j = j + 1
+        }
+        s = s + \"|\"
+        i = i + 1
+    }
+    return s
+}
+
+main = {
+    assert_eq(repeat_word(\"a\", 3), \"a,a,a,\")
+    assert_eq(repeat_word(\"a\", 0), \"\")
+    assert_eq(until_three(), \"<ababab>\")
+    assert_eq(grid(), \"xx|xx|\")
+}
+";
+    let module = Compiler::new()
+        .compile("string_builder.yx", source)
+        .expect("source should compile");
+    let appends = module
+        .functions
+        .iter()
+        .flat_map(|f| f.all_instructions())
+        .filter(|i| matches!(i, Instruction::StringBuilderAppend { .. }))
+        .count();
+    assert_eq!(appends, 4);
+    let mut ctx = crate::middle::passes::codegen::CodegenContext::new(module);
+    let bytecode = ctx.generate().expect("codegen should succeed");
+    let module = crate::middle::bytecode::BytecodeModule::from(bytecode);
+    let mut interpreter = crate::backends::interpreter::Interpreter::new();
+    interpreter
+        .execute_module(&module)
+        .expect("program should run with string builders");
+}
@@ -0,0 +1,124 @@
+//! 尾调用降级
+//!
+//! 返回位置上的调用 `return f(...)` 不必保留当前栈帧：被调函数的返回值就是当前函数的
+//! 返回值。本遍把这类 `Call` 改写为 `TailCall`，虚拟机执行时由被调函数复用当前栈帧，
+//! 尾递归因此不受调用栈深度限制。
+//!
+//! 判定：从调用之后开始，只经过寄存器复制（`Move`）与无条件跳转就到达 `Ret`，且返回的
+//! 正是调用结果；被调函数返回 void 时也可以是 `Ret(None)`。只改写
+//! 对本模块函数的静态调用，native / FFI 函数、闭包与虚调用保持原样。
+//!
+//! 改写后调用之后的指令不再可达，但保留在原处，其余指令的下标（跳转目标）不变。
+
+use std::collections::{HashMap, HashSet};
+
+use crate::frontend::core::typecheck::MonoType;
+use crate::middle::core::ir::{ConstValue, FfiBinding, FunctionIR, Instruction, ModuleIR, Operand};
+use crate::middle::passes::ssa::operands::register;
+
+/// 对模块中的每个函数降级尾调用
+pub fn lower_module(module: &mut ModuleIR) {
+    let natives: HashSet<&str> = module
+        .ffi_bindings
+        .iter()
+        .filter_map(|binding| match binding {
+            FfiBinding::FuncBinding { func_name, .. } => Some(func_name.as_str()),
+            _ => None,
+        })
+        .collect();
+    let targets: HashMap<String, bool> = module
+        .functions
+        .iter()
+        .filter(|f| !natives.contains(f.name.as_str()))
+        .map(|f| (f.name.clone(), matches!(f.return_type, MonoType::Void)))
+        .collect();
+    for func in &mut module.functions {
+        lower_tail_calls(func, &targets);
+    }
+}
+
+/// 降级函数中的尾调用，返回是否有改写
+///
+/// `targets` 为可尾调用的函数名 → 是否返回 void。
+pub fn lower_tail_calls(
+    func: &mut FunctionIR,
+    targets: &HashMap<String, bool>,
+) -> bool {
+    let code: Vec<&Instruction> = func.all_instructions().collect();
+    let mut tail_calls = HashSet::new();
+    for (i, instr) in code.iter().enumerate() {
+        let Instruction::Call {
+            dst,
+            func: Operand::Const(ConstValue::String(name)),
+            ..
+        } = instr
+        else {
+            continue;
+        };
+        let Some(&returns_void) = targets.get(name) else {
+            continue;
+        };
+        let result = dst.as_ref().and_then(register);
+        if returns_result(&code, i + 1, result, returns_void) {
+            tail_calls.insert(i);
+        }
+    }
+    if tail_calls.is_empty() {
+        return false;
+    }
+
+    let mut index = 0;
+    for block in &mut func.blocks {
+        for instr in &mut block.instructions {
+            if tail_calls.contains(&index) {
+                if let Instruction::Call { func, args, .. } = instr {
+                    *instr = Instruction::TailCall {
+                        func: std::mem::replace(func, Operand::Const(ConstValue::Void)),
+                        args: std::mem::take(args),
+                    };
+                }
+            }
+            index += 1;
+        }
+    }
+    true
+}
+
+/// 从 `pc` 开始只经过复制与跳转就返回调用结果 `result`；被调函数返回 void 时
+/// `Ret(None)` 也算
+fn returns_result(
+    code: &[&Instruction],
+    mut pc: usize,
+    result: Option<usize>,
+    returns_void: bool,
+) -> bool {
+    // 持有调用结果的寄存器
+    let mut holding: HashSet<usize> = result.into_iter().collect();
+    // 每条指令至多经过一次，避免空循环
+    let mut seen = HashSet::new();
+    while seen.insert(pc) {
+        match code.get(pc) {
+            Some(Instruction::Move { dst, src }) => {
+                let Some(dst) = register(dst) else {
+                    return false;
+                };
+                if register(src).is_some_and(|src| holding.contains(&src)) {
+                    holding.insert(dst);
+                } else {
+                    holding.remove(&dst);
+                }
+                pc += 1;
+            }
+            Some(Instruction::Jmp(target)) => pc = *target,
+            Some(Instruction::Ret(Some(value))) => {
+                return register(value).is_some_and(|v| holding.contains(&v))
+            }
+            Some(Instruction::Ret(None)) => return returns_void,
+            _ => return false,
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests;
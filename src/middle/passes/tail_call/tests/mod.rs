@@ -0,0 +1,173 @@
+//! 尾调用降级测试
+//!
+//! 覆盖：
+//! - `return f(...)` 改写为 `TailCall`，返回前还要计算的调用保持原样
+//! - 不在候选集合中的函数（native / FFI）不改写
+//! - 尾递归与相互尾递归超过调用栈深度限制仍能运行，各优化级别一致
+
+use std::collections::HashMap;
+
+use crate::backends::Executor;
+use crate::frontend::config::{CompileConfig, OptLevel};
+use crate::frontend::Compiler;
+use crate::frontend::core::typecheck::MonoType;
+use crate::middle::core::ir::{BasicBlock, ConstValue, FunctionIR, Instruction, ModuleIR, Operand};
+use crate::util::span::Span;
+use crate::middle::passes::tail_call::lower_tail_calls;
+
+const SUM: &str = "\
+sum: (n: Int, acc: Int) -> Int = (n, acc) => {
+    if n == 0 {
+        return acc
+    }
+    return sum(n - 1, acc + n)
+}
+
+total: (n: Int) -> Int = (n) => {
+    if n == 0 {
+        return 0
+    }
+    return n + total(n - 1)
+}
+
+main = { sum(3, 0) }
+";
+
+fn compile(
+    source: &str,
+    opt: OptLevel,
+) -> ModuleIR {
+    Compiler::with_config(CompileConfig::new().with_opt_level(opt))
+        .compile("tail.yx", source)
+        .expect("source should compile")
+}
+
+fn function<'a>(
+    module: &'a ModuleIR,
+    name: &str,
+) -> &'a FunctionIR {
+    module
+        .functions
+        .iter()
+        .find(|f| f.name == name)
+        .expect("function should exist")
+}
+
+fn count(
+    func: &FunctionIR,
+    pred: impl Fn(&Instruction) -> bool,
+) -> usize {
+    func.all_instructions().filter(|i| pred(i)).count()
+}
+
+fn is_tail_call(instr: &Instruction) -> bool {
+    matches!(instr, Instruction::TailCall { .. })
+}
+
+fn is_call(instr: &Instruction) -> bool {
+    matches!(instr, Instruction::Call { .. })
+}
+
+#[test]
+fn test_return_call_becomes_tail_call() {
+    for opt in [OptLevel::O0, OptLevel::O2] {
+        let module = compile(SUM, opt);
+        let sum = function(&module, "sum");
+        assert_eq!(count(sum, is_tail_call), 1, "{:?}", opt);
+        assert_eq!(count(sum, is_call), 0, "{:?}", opt);
+    }
+}
+
+#[test]
+fn test_call_used_before_return_is_kept() {
+    let module = compile(SUM, OptLevel::O0);
+    let total = function(&module, "total");
+    assert_eq!(count(total, is_tail_call), 0);
+    assert_eq!(count(total, is_call), 1);
+}
+
+/// `f` 的调用结果经一次复制后返回
+fn call_then_return(callee: &str) -> FunctionIR {
+    FunctionIR {
+        name: "caller".to_string(),
+        params: Vec::new(),
+        return_type: MonoType::Int(64),
+        locals: Vec::new(),
+        blocks: vec![BasicBlock {
+            label: 0,
+            instructions: vec![
+                Instruction::Call {
+                    dst: Some(Operand::Temp(0)),
+                    func: Operand::Const(ConstValue::String(callee.to_string())),
+                    args: vec![Operand::Arg(0)],
+                    span: Span::default(),
+                },
+                Instruction::Move {
+                    dst: Operand::Local(1),
+                    src: Operand::Temp(0),
+                },
+                Instruction::Ret(Some(Operand::Local(1))),
+            ],
+            successors: Vec::new(),
+        }],
+        entry: 0,
+        generic_params: None,
+    }
+}
+
+#[test]
+fn test_only_listed_targets_are_lowered() {
+    let mut func = call_then_return("f");
+    assert!(!lower_tail_calls(&mut func, &HashMap::new()));
+    assert_eq!(count(&func, is_call), 1);
+
+    let targets = HashMap::from([("f".to_string(), false)]);
+    assert!(lower_tail_calls(&mut func, &targets));
+    assert_eq!(count(&func, is_tail_call), 1);
+    // 改写不增删指令，跳转目标不变
+    assert_eq!(func.all_instructions().count(), 3);
+}
+
+#[test]
+fn test_deep_tail_recursion_runs_on_the_vm() {
+    let source = "\
+use std.assert
+
+sum: (n: Int, acc: Int) -> Int = (n, acc) => {
+    if n == 0 {
+        return acc
+    }
+    return sum(n - 1, acc + n)
+}
+
+is_even: (n: Int) -> Bool = (n) => {
+    if n == 0 {
+        return true
+    }
+    return is_odd(n - 1)
+}
+
+is_odd: (n: Int) -> Bool = (n) => {
+    if n == 0 {
+        return false
+    }
+    return is_even(n - 1)
+}
+
+main = {
+    assert_eq(sum(20000, 0), 200010000)
+    assert_eq(is_even(10001), false)
+    assert_eq(is_odd(10001), true)
+}
+";
+    for opt in [OptLevel::O0, OptLevel::O1, OptLevel::O2] {
+        let module = compile(source, opt);
+        let mut ctx = crate::middle::passes::codegen::CodegenContext::new(module);
+        let bytecode = ctx.generate().expect("codegen should succeed");
+        let module = crate::middle::bytecode::BytecodeModule::from(bytecode);
+        let mut interpreter = crate::backends::interpreter::Interpreter::new();
+        interpreter
+            .execute_module(&module)
+            .unwrap_or_else(|e| panic!("tail recursion should not overflow at {:?}: {:?}", opt, e));
+    }
+}
@@ -0,0 +1,181 @@
+//! 死函数消除（tree shaking）
+//!
+//! 单态化与内联之后，删除从根出发不可达的函数，缩小字节码。根是 `main` 与公开（`pub`）
+//! 函数：后者可能被 `std.module.import` 按名链接。两者都没有时（嵌入方按名调用的片段）
+//! 保留全部函数。
+//!
+//! 函数之间的引用都是按名字的，下列引用使目标可达：
+//! - 字符串常量操作数等于函数名（`call "f"`、作为值传递的函数），或等于去掉
+//!   `_constructor` 后缀的构造函数名（运行时按名查找的回退）
+//! - `MakeClosure` 的目标函数
+//! - `CreateStruct` 与结构体常量创建的类型的全部方法（运行时按 `Type.` 前缀构建虚表）
+//! - `CallVirt` / `InvokeVirtual` 调用的方法名，匹配任意类型的同名方法
+//! - `MakeDyn` 引用的接口虚表中的方法
+//! - 全局量初值中的字符串常量
+
+use std::collections::HashSet;
+
+use crate::middle::core::ir::{ConstValue, FunctionIR, Instruction, ModuleIR, Operand};
+use crate::middle::passes::ssa::operands;
+
+/// 删除模块中不可达的函数，返回删除的函数数
+pub fn shake_module(module: &mut ModuleIR) -> usize {
+    let Some(reachable) = reachable(module) else {
+        return 0;
+    };
+    let before = module.functions.len();
+    module
+        .functions
+        .retain(|func| reachable.contains(func.name.as_str()));
+    let removed = before - module.functions.len();
+    if removed > 0 {
+        let live = |name: &String| reachable.contains(name.as_str());
+        module.mut_locals.retain(|name, _| live(name));
+        module.loop_binding_locals.retain(|name, _| live(name));
+        module.local_names.retain(|name, _| live(name));
+        module.inline_hints.retain(|name, _| live(name));
+        module.upvalue_counts.retain(|name, _| live(name));
+    }
+    removed
+}
+
+/// 从根出发可达的函数名；模块没有根时返回 `None`
+fn reachable(module: &ModuleIR) -> Option<HashSet<String>> {
+    let names: HashSet<&str> = module.functions.iter().map(|f| f.name.as_str()).collect();
+    let mut roots: Vec<&str> = module
+        .exports
+        .iter()
+        .map(String::as_str)
+        .filter(|name| names.contains(name))
+        .collect();
+    if names.contains("main") {
+        roots.push("main");
+    }
+    if roots.is_empty() {
+        return None;
+    }
+
+    let mut marker = Marker {
+        module,
+        names,
+        reachable: HashSet::new(),
+        worklist: Vec::new(),
+    };
+    for root in roots {
+        marker.mark(root);
+    }
+    for (_, _, value) in &module.globals {
+        if let Some(value) = value {
+            marker.mark_const(value);
+        }
+    }
+    while let Some(func) = marker.worklist.pop() {
+        for instr in func.all_instructions() {
+            marker.scan(instr);
+        }
+    }
+    Some(marker.reachable.into_iter().map(str::to_string).collect())
+}
+
+/// 可达性标记的工作状态
+struct Marker<'a> {
+    module: &'a ModuleIR,
+    names: HashSet<&'a str>,
+    reachable: HashSet<&'a str>,
+    worklist: Vec<&'a FunctionIR>,
+}
+
+impl<'a> Marker<'a> {
+    /// 标记名为 `name` 的函数；首次标记时加入工作表
+    fn mark(
+        &mut self,
+        name: &str,
+    ) {
+        let Some(&name) = self.names.get(name) else {
+            return;
+        };
+        if self.reachable.insert(name) {
+            self.worklist
+                .extend(self.module.functions.iter().filter(|f| f.name == name));
+        }
+    }
+
+    /// 标记名字满足 `pred` 的全部函数
+    fn mark_matching(
+        &mut self,
+        pred: impl Fn(&str) -> bool,
+    ) {
+        let matched: Vec<&'a str> = self.names.iter().copied().filter(|n| pred(n)).collect();
+        for name in matched {
+            self.mark(name);
+        }
+    }
+
+    /// 常量中可能引用函数的字符串
+    fn mark_const(
+        &mut self,
+        value: &ConstValue,
+    ) {
+        match value {
+            ConstValue::String(name) => {
+                self.mark(name);
+                self.mark(&format!("{}_constructor", name));
+            }
+            ConstValue::List(items) => {
+                for item in items {
+                    self.mark_const(item);
+                }
+            }
+            ConstValue::Dict(entries) => {
+                for (key, value) in entries {
+                    self.mark_const(key);
+                    self.mark_const(value);
+                }
+            }
+            // 加载结构体常量时按类型名构建虚表，方法须保留
+            ConstValue::Struct { type_name, fields } => {
+                let prefix = format!("{}.", type_name);
+                self.mark_matching(|name| name.starts_with(&prefix));
+                for field in fields {
+                    self.mark_const(field);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// 标记一条指令引用的函数
+    fn scan(
+        &mut self,
+        instr: &Instruction,
+    ) {
+        for operand in operands::all_operands(instr) {
+            if let Operand::Const(value) = operand {
+                self.mark_const(value);
+            }
+        }
+        match instr {
+            Instruction::MakeClosure { func, .. } => self.mark(func),
+            Instruction::CreateStruct { type_name, .. } => {
+                let prefix = format!("{}.", type_name);
+                self.mark_matching(|name| name.starts_with(&prefix));
+            }
+            Instruction::CallVirt { method_name, .. }
+            | Instruction::InvokeVirtual { method_name, .. } => {
+                let suffix = format!(".{}", method_name);
+                self.mark_matching(|name| name.ends_with(&suffix));
+            }
+            Instruction::MakeDyn { vtable, .. } => {
+                if let Some(vtable) = self.module.vtables.get(*vtable) {
+                    for method in &vtable.methods {
+                        self.mark(method);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,156 @@
+//! 死函数消除测试
+//!
+//! 覆盖：
+//! - 删除从 `main` 不可达的函数，保留调用链与已创建类型的方法
+//! - 公开函数作为根；既无 `main` 也无公开函数时保留全部
+//! - 文本 IR 中 `export` 列出的函数
+//! - 默认在 `-O1` 运行，可由配置禁用；删除后程序结果不变
+
+use crate::backends::interpreter::Interpreter;
+use crate::backends::Executor;
+use crate::frontend::config::CompileConfig;
+use crate::frontend::Compiler;
+use crate::middle::bytecode::BytecodeModule;
+use crate::middle::core::ir::ModuleIR;
+use crate::middle::core::ir_text::parse_module;
+use crate::middle::passes::codegen::CodegenContext;
+use crate::middle::passes::manager::Pass;
+use crate::middle::passes::tree_shake::shake_module;
+
+const SOURCE: &str = "\
+use std.assert
+
+Point: Type = { x: Int, y: Int }
+
+Point.sum: (self: Point) -> Int = (self) => {
+    return self.x + self.y
+}
+
+Unused: Type = { a: Int }
+
+Unused.get: (self: Unused) -> Int = (self) => {
+    return self.a
+}
+
+double: (n: Int) -> Int = (n) => n * 2
+
+quadruple: (n: Int) -> Int = (n) => double(double(n))
+
+unused: (n: Int) -> Int = (n) => n + 1
+
+pub exported: (n: Int) -> Int = (n) => n - 1
+
+main = {
+    p = Point(1, 2)
+    assert_eq(p.sum(), 3)
+    assert_eq(quadruple(3), 12)
+}
+";
+
+fn compile(
+    source: &str,
+    config: CompileConfig,
+) -> ModuleIR {
+    Compiler::with_config(config)
+        .compile("tree_shake.yx", source)
+        .expect("source should compile")
+}
+
+/// 不内联，保留函数之间的调用
+fn shake_only() -> CompileConfig {
+    CompileConfig::new().with_passes(vec![Pass::TreeShake])
+}
+
+fn has(
+    module: &ModuleIR,
+    name: &str,
+) -> bool {
+    module.functions.iter().any(|f| f.name == name)
+}
+
+fn run(module: ModuleIR) {
+    let mut ctx = CodegenContext::new(module);
+    let bytecode = ctx.generate().expect("codegen should succeed");
+    let module = BytecodeModule::from(bytecode);
+    Interpreter::new()
+        .execute_module(&module)
+        .expect("program should run");
+}
+
+#[test]
+fn test_unreachable_functions_are_removed() {
+    let module = compile(SOURCE, shake_only());
+    for name in ["main", "quadruple", "double", "Point.sum", "exported"] {
+        assert!(has(&module, name), "`{}` should be kept", name);
+    }
+    assert!(!has(&module, "unused"));
+    // `Unused` 从未创建，构造函数与方法都不可达
+    assert!(!has(&module, "Unused.get"));
+    assert!(module.local_names.keys().all(|name| has(&module, name)));
+    run(module);
+}
+
+#[test]
+fn test_exports_are_roots_without_main() {
+    let library = "\
+double: (n: Int) -> Int = (n) => n * 2
+
+unused: (n: Int) -> Int = (n) => n + 1
+
+pub quadruple: (n: Int) -> Int = (n) => double(double(n))
+";
+    let module = compile(library, shake_only());
+    assert_eq!(module.exports, vec!["quadruple".to_string()]);
+    assert!(has(&module, "quadruple"));
+    assert!(has(&module, "double"));
+    assert!(!has(&module, "unused"));
+
+    // 没有根：嵌入方可能按名调用任意函数
+    let snippet = "\
+double: (n: Int) -> Int = (n) => n * 2
+
+unused: (n: Int) -> Int = (n) => n + 1
+";
+    let module = compile(snippet, shake_only());
+    assert!(has(&module, "double"));
+    assert!(has(&module, "unused"));
+}
+
+#[test]
+fn test_hand_written_ir_exports() {
+    let mut module = parse_module(
+        r#"
+export "api"
+
+fn "api"() -> int64 {
+bb0:
+  %l0 = call "helper"()
+  ret %l0
+}
+
+fn "helper"() -> int64 {
+bb0:
+  ret 1
+}
+
+fn "dead"() -> int64 {
+bb0:
+  ret 2
+}
+"#,
+    )
+    .expect("IR should parse");
+    assert_eq!(shake_module(&mut module), 1);
+    assert!(!has(&module, "dead"));
+    assert!(module.to_string().starts_with("export \"api\"\n"));
+}
+
+#[test]
+fn test_tree_shake_runs_by_default_and_can_be_disabled() {
+    let module = compile(SOURCE, CompileConfig::new());
+    assert!(!has(&module, "unused"));
+
+    let module = compile(SOURCE, CompileConfig::new().with_tree_shaking(false));
+    assert!(has(&module, "unused"));
+    assert!(has(&module, "Unused.get"));
+}
@@ -2,7 +2,7 @@
 
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use crate::package::error::{PackageError, PackageResult};
 use crate::util::config::I18nConfig;
@@ -28,6 +28,39 @@ pub struct PackageInfo {
     pub license: Option<String>,
 }
 
+/// Represents the `[native]` section of yaoxiang.toml
+///
+/// A package that ships a companion cdylib declares it here; the runtime loads
+/// it as a native extension when the package is vendored into a project.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NativeConfig {
+    /// Path of the shared library, relative to the package root
+    ///
+    /// A path without an extension names the library portably: the loader adds
+    /// the platform's prefix and suffix, so `target/release/yaoxiang_sqlite`
+    /// loads `libyaoxiang_sqlite.so` on Linux, `libyaoxiang_sqlite.dylib` on
+    /// macOS and `yaoxiang_sqlite.dll` on Windows.
+    pub library: String,
+}
+
+impl NativeConfig {
+    /// The library file of the package at `package_dir` on this platform
+    pub fn library_path(
+        &self,
+        package_dir: &Path,
+    ) -> PathBuf {
+        let path = package_dir.join(&self.library);
+        match path.file_name().and_then(|name| name.to_str()) {
+            Some(name) if path.extension().is_none() => path.with_file_name(format!(
+                "{}{name}{}",
+                std::env::consts::DLL_PREFIX,
+                std::env::consts::DLL_SUFFIX
+            )),
+            _ => path,
+        }
+    }
+}
+
 /// Represents the complete yaoxiang.toml manifest
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PackageManifest {
@@ -46,6 +79,9 @@ pub struct PackageManifest {
     /// I18n configuration (project-level overrides user-level)
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub i18n: Option<I18nConfig>,
+    /// Native extension library shipped with the package
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub native: Option<NativeConfig>,
 }
 
 impl PackageManifest {
@@ -62,6 +98,7 @@ impl PackageManifest {
             dependencies: BTreeMap::new(),
             dev_dependencies: BTreeMap::new(),
             i18n: None,
+            native: None,
         }
     }
 
@@ -6,9 +6,12 @@
 //! - 从非项目目录加载返回错误
 //! - 添加/移除/查询依赖
 //! - 含 table 形式依赖和空依赖的 TOML 解析
+//! - `[native]` 库路径不带扩展名时按平台补全文件名
+
+use std::path::Path;
 
 use crate::package::error::PackageError;
-use crate::package::manifest::PackageManifest;
+use crate::package::manifest::{NativeConfig, PackageManifest};
 
 #[test]
 fn test_new_manifest() {
@@ -121,3 +124,41 @@ version = "0.1.0"
     assert!(manifest.dependencies.is_empty());
     assert!(manifest.dev_dependencies.is_empty());
 }
+
+#[test]
+fn test_parse_native_section() {
+    let toml_str = r#"
+[package]
+name = "sqlite"
+version = "0.1.0"
+
+[native]
+library = "lib/libyx_sqlite.so"
+"#;
+    let manifest: PackageManifest = toml::from_str(toml_str).unwrap();
+    let native = manifest.native.expect("native section");
+    assert_eq!(native.library, "lib/libyx_sqlite.so");
+    assert!(PackageManifest::new("plain").native.is_none());
+}
+
+#[test]
+fn test_native_library_path_resolves_platform_name() {
+    let dir = Path::new("pkg");
+    let explicit = NativeConfig {
+        library: "lib/libyx_sqlite.so".to_string(),
+    };
+    assert_eq!(explicit.library_path(dir), dir.join("lib/libyx_sqlite.so"));
+
+    let portable = NativeConfig {
+        library: "target/release/yx_sqlite".to_string(),
+    };
+    let file = format!(
+        "{}yx_sqlite{}",
+        std::env::consts::DLL_PREFIX,
+        std::env::consts::DLL_SUFFIX
+    );
+    assert_eq!(
+        portable.library_path(dir),
+        dir.join("target/release").join(file)
+    );
+}
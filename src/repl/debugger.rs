@@ -0,0 +1,241 @@
+//! Interactive debugger of the REPL (`:debug <file>`)
+//!
+//! The program runs on its own thread under a [`Debugger`]; the REPL reads
+//! debugger commands whenever it stops.
+
+use std::io;
+use std::path::Path;
+
+use rustyline::error::ReadlineError;
+use rustyline::history::FileHistory;
+use rustyline::Editor;
+
+use crate::backends::{CapabilityPolicy, Executor};
+use crate::util::diagnostic::panic::RuntimePanic;
+use crate::vm::debug::{Breakpoint, DebugEvent, Debugger, Step, StepUnit, Stop, StopReason};
+use crate::Engine;
+
+use super::ReplCompleter;
+
+/// Prompt shown while the program is stopped
+const DEBUG_PROMPT: &str = "(debug) ";
+
+/// Parse `<function> <offset>` or `<file>:<line>`
+pub fn parse_breakpoint(args: &[&str]) -> Option<Breakpoint> {
+    match args {
+        [function, offset] => Some(Breakpoint::Instruction {
+            function: function.to_string(),
+            offset: offset.parse().ok()?,
+        }),
+        [location] => {
+            let (file, line) = location.rsplit_once(':')?;
+            Some(Breakpoint::Line {
+                file: file.to_string(),
+                line: line.parse().ok()?,
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Describe a breakpoint the way `:break` takes it
+pub fn describe_breakpoint(at: &Breakpoint) -> String {
+    match at {
+        Breakpoint::Instruction { function, offset } => format!("{} {}", function, offset),
+        Breakpoint::Line { file, line } => format!("{}:{}", file, line),
+    }
+}
+
+/// Run the program in `path` under the debugger, stopping at `breakpoints`
+pub fn debug_file(
+    editor: &mut Editor<ReplCompleter, FileHistory>,
+    path: &Path,
+    breakpoints: &[Breakpoint],
+) -> io::Result<()> {
+    let source = std::fs::read_to_string(path)?;
+    let name = path.display().to_string();
+    let program = match Engine::default().compile(&name, &source) {
+        Ok(program) => program,
+        Err(e) => {
+            println!("Error: {}", e);
+            return Ok(());
+        }
+    };
+
+    let debugger = Debugger::new(&program);
+    for at in breakpoints {
+        if debugger.set_breakpoint(at.clone()).is_none() {
+            println!("No code at breakpoint {}", describe_breakpoint(at));
+        }
+    }
+    if breakpoints.is_empty() {
+        debugger.pause();
+    }
+
+    let vm = debugger.clone();
+    let base_dir = path.parent().map(Path::to_path_buf);
+    let runner = std::thread::spawn(move || {
+        let mut interpreter = Engine::builder()
+            .capabilities(CapabilityPolicy::allow_all())
+            .build()
+            .interpreter();
+        if let Some(dir) = base_dir {
+            interpreter.set_import_base_dir(dir);
+        }
+        vm.attach(&mut interpreter);
+        let result = interpreter
+            .execute_module(&program)
+            .map_err(|e| RuntimePanic::new(e, &program).render());
+        vm.finish();
+        result
+    });
+
+    let lines: Vec<&str> = source.lines().collect();
+    while let DebugEvent::Stopped(stop) = debugger.wait() {
+        print_stop(&stop, &lines);
+        if !read_commands(editor, &debugger)? {
+            debugger.detach();
+        }
+    }
+
+    match runner.join() {
+        Ok(Ok(())) => println!("Program exited"),
+        Ok(Err(rendered)) => println!("{}", rendered),
+        Err(_) => println!("Program panicked"),
+    }
+    Ok(())
+}
+
+/// Print where the program stopped, with the source line when known
+fn print_stop(
+    stop: &Stop,
+    lines: &[&str],
+) {
+    let frame = &stop.frames[0];
+    let reason = match stop.reason {
+        StopReason::Breakpoint(id) => format!("Breakpoint {}", id),
+        StopReason::Step => "Step".to_string(),
+        StopReason::Pause => "Paused".to_string(),
+    };
+    match &frame.location {
+        Some(location) => {
+            println!("{} in {} at {}", reason, frame.function, location);
+            if let Some(line) = lines.get(location.line.wrapping_sub(1)) {
+                println!("{:>5} | {}", location.line, line);
+            }
+        }
+        None => println!(
+            "{} in {} at instruction {}",
+            reason, frame.function, frame.ip
+        ),
+    }
+}
+
+/// Read commands until one resumes the program; `false` when debugging ends
+fn read_commands(
+    editor: &mut Editor<ReplCompleter, FileHistory>,
+    debugger: &Debugger,
+) -> io::Result<bool> {
+    loop {
+        let line = match editor.readline(DEBUG_PROMPT) {
+            Ok(line) => line,
+            Err(ReadlineError::Eof) | Err(ReadlineError::Interrupted) => return Ok(false),
+            Err(e) => return Err(io::Error::other(e.to_string())),
+        };
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        let Some((command, args)) = parts.split_first() else {
+            continue;
+        };
+        let step = match *command {
+            "continue" | "c" => {
+                debugger.resume();
+                return Ok(true);
+            }
+            "step" | "s" => (Step::Into, StepUnit::Line),
+            "next" | "n" => (Step::Over, StepUnit::Line),
+            "finish" | "out" => (Step::Out, StepUnit::Line),
+            "stepi" | "si" => (Step::Into, StepUnit::Instruction),
+            "nexti" | "ni" => (Step::Over, StepUnit::Instruction),
+            "backtrace" | "bt" | "where" => {
+                if let Some(stop) = debugger.stopped() {
+                    for (i, frame) in stop.frames.iter().enumerate() {
+                        match &frame.location {
+                            Some(location) => {
+                                println!("#{} {} at {}", i, frame.function, location)
+                            }
+                            None => println!("#{} {} @{}", i, frame.function, frame.ip),
+                        }
+                    }
+                }
+                continue;
+            }
+            "registers" | "regs" => {
+                let registers = debugger.inspect(|vm| {
+                    vm.registers()
+                        .iter()
+                        .map(ToString::to_string)
+                        .collect::<Vec<_>>()
+                });
+                for (i, value) in registers.into_iter().flatten().enumerate() {
+                    println!("  r{} = {}", i, value);
+                }
+                continue;
+            }
+            "break" | "b" => {
+                match parse_breakpoint(args) {
+                    Some(at) => match debugger.set_breakpoint(at.clone()) {
+                        Some(id) => {
+                            let at = debugger.breakpoint(id).unwrap_or(at);
+                            println!("Breakpoint {} at {}", id, describe_breakpoint(&at));
+                        }
+                        None => println!("No code at breakpoint {}", describe_breakpoint(&at)),
+                    },
+                    None => println!("Usage: break <function> <offset> | break <file>:<line>"),
+                }
+                continue;
+            }
+            "delete" | "d" => {
+                match args.first().and_then(|id| id.parse().ok()) {
+                    Some(id) if debugger.remove_breakpoint(id) => {
+                        println!("Deleted breakpoint {}", id)
+                    }
+                    _ => println!("Usage: delete <breakpoint id>"),
+                }
+                continue;
+            }
+            "breakpoints" | "bp" => {
+                for (id, at) in debugger.breakpoints() {
+                    println!("  {}: {}", id, describe_breakpoint(&at));
+                }
+                continue;
+            }
+            "quit" | "q" => return Ok(false),
+            "help" | "h" => {
+                print_help();
+                continue;
+            }
+            _ => {
+                println!("Unknown debugger command: {} (try `help`)", command);
+                continue;
+            }
+        };
+        debugger.step(step.0, step.1);
+        return Ok(true);
+    }
+}
+
+fn print_help() {
+    println!("Debugger commands:");
+    println!("  continue, c                  - Run to the next breakpoint");
+    println!("  step, s                      - Step to the next line, entering calls");
+    println!("  next, n                      - Step to the next line, over calls");
+    println!("  finish, out                  - Run until the current function returns");
+    println!("  stepi, si / nexti, ni        - Step one instruction (into / over calls)");
+    println!("  backtrace, bt, where         - Show the call stack");
+    println!("  registers, regs              - Show the registers of the current frame");
+    println!("  break, b <fn> <off>          - Set a breakpoint at an instruction");
+    println!("  break, b <file>:<line>       - Set a breakpoint at a source line");
+    println!("  delete, d <id>               - Remove a breakpoint");
+    println!("  breakpoints, bp              - List breakpoints");
+    println!("  quit, q                      - Stop debugging and let the program finish");
+}
@@ -3,14 +3,16 @@
 //! Core engine for compiling and executing REPL input.
 
 use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
 use std::time::{Duration, Instant};
 
 use crate::backends::common::RuntimeValue;
-use crate::backends::interpreter::Interpreter;
-use crate::backends::Executor;
-use crate::frontend::Compiler;
-use crate::middle::bytecode::BytecodeModule;
-use crate::middle::passes::codegen::CodegenContext;
+use crate::backends::{CapabilityPolicy, ExecutorError};
+use crate::frontend::core::types::MonoType;
+use crate::frontend::CompileError;
+use crate::util::artifact_cache::ArtifactCache;
+use crate::{Engine, Session};
 
 use super::backend::{EvalResult, ExecutionStats, REPLBackend, SymbolInfo};
 
@@ -176,6 +178,21 @@ impl REPLContext {
     }
 }
 
+// =============================================================================
+// Session Snapshot
+// =============================================================================
+
+/// Persisted REPL session: the inputs evaluated so far and the symbols they defined
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SessionSnapshot {
+    /// Successfully evaluated inputs, oldest first
+    pub inputs: Vec<String>,
+    /// Defined functions: (name, signature, return type)
+    pub functions: Vec<(String, String, String)>,
+    /// Defined variables: (name, type)
+    pub variables: Vec<(String, String)>,
+}
+
 // =============================================================================
 // Evaluator
 // =============================================================================
@@ -183,15 +200,18 @@ impl REPLContext {
 /// Evaluation Engine
 ///
 /// The core engine that compiles and executes REPL input.
-/// It wraps code in a main function, compiles it, and executes it.
+/// Each input runs in the same [`Session`], so functions, types and
+/// variables defined by one input are visible to the following ones.
 #[derive(Debug)]
 pub struct Evaluator {
-    /// Compiler instance
-    compiler: Compiler,
-    /// Interpreter for execution
-    interpreter: Interpreter,
+    /// Session holding definitions and variables across inputs
+    session: Session,
     /// Execution context
     context: REPLContext,
+    /// Compiled bytecode cache (`None` disables caching)
+    cache: Option<ArtifactCache>,
+    /// Successfully evaluated inputs
+    inputs: Vec<String>,
 }
 
 impl Default for Evaluator {
@@ -200,16 +220,101 @@ impl Default for Evaluator {
     }
 }
 
+/// A session for the REPL, which runs the user's own input and so gets every capability
+fn repl_session() -> Session {
+    Engine::builder()
+        .capabilities(CapabilityPolicy::allow_all())
+        .build()
+        .session()
+}
+
 impl Evaluator {
     /// Create a new evaluator
     pub fn new() -> Self {
         Self {
-            compiler: Compiler::new(),
-            interpreter: Interpreter::new(),
+            session: repl_session(),
             context: REPLContext::new(),
+            cache: None,
+            inputs: Vec::new(),
+        }
+    }
+
+    /// Look up and store compiled inputs in `cache`
+    pub fn with_cache(
+        mut self,
+        cache: ArtifactCache,
+    ) -> Self {
+        self.session = self.session.with_cache(cache.clone());
+        self.cache = Some(cache);
+        self
+    }
+
+    /// The bytecode cache, if enabled
+    pub fn cache(&self) -> Option<&ArtifactCache> {
+        self.cache.as_ref()
+    }
+
+    /// Capture the session so it can be restored later
+    pub fn snapshot(&self) -> SessionSnapshot {
+        let mut functions: Vec<_> = self
+            .context
+            .functions
+            .iter()
+            .map(|(name, info)| {
+                (
+                    name.clone(),
+                    info.signature.clone(),
+                    info.return_type.clone(),
+                )
+            })
+            .collect();
+        functions.sort();
+        let mut variables: Vec<_> = self
+            .context
+            .variables
+            .keys()
+            .filter_map(|name| Some((name.clone(), self.context.get_var_type(name)?)))
+            .collect();
+        variables.sort();
+        SessionSnapshot {
+            inputs: self.inputs.clone(),
+            functions,
+            variables,
         }
     }
 
+    /// Restore the inputs and symbols of a previous session
+    ///
+    /// Restored variables only carry their types; their values are not re-evaluated.
+    pub fn restore(
+        &mut self,
+        snapshot: SessionSnapshot,
+    ) {
+        for (name, signature, return_type) in snapshot.functions {
+            self.context.define_function(name, signature, return_type);
+        }
+        for (name, type_signature) in snapshot.variables {
+            self.context.define_variable(name, type_signature);
+        }
+        self.inputs = snapshot.inputs;
+    }
+
+    /// Successfully evaluated inputs, oldest first
+    pub fn inputs(&self) -> &[String] {
+        &self.inputs
+    }
+
+    /// Clear definitions, variables and inputs
+    pub fn clear(&mut self) {
+        let session = repl_session();
+        self.session = match &self.cache {
+            Some(cache) => session.with_cache(cache.clone()),
+            None => session,
+        };
+        self.context.clear();
+        self.inputs.clear();
+    }
+
     /// Evaluate code
     pub fn evaluate(
         &mut self,
@@ -227,35 +332,31 @@ impl Evaluator {
             return EvalResult::Incomplete;
         }
 
-        // Wrap code for REPL evaluation
-        let wrapped = self.wrap_code(trimmed);
-
-        // Compile
-        match self.compiler.compile("<repl>", &wrapped) {
-            Ok(module_ir) => match CodegenContext::new(module_ir).generate() {
-                Ok(bytecode_file) => {
-                    let bytecode_module = BytecodeModule::from(bytecode_file);
-
-                    match self.interpreter.execute_module(&bytecode_module) {
-                        Ok(_) => {
-                            self.context.increment_eval(start.elapsed());
-                            self.extract_definitions(&bytecode_module);
-                            EvalResult::Ok
-                        }
-                        Err(e) => EvalResult::Error(format!("Runtime error: {:?}", e)),
-                    }
-                }
-                Err(e) => EvalResult::Error(format!("Codegen error: {:?}", e)),
-            },
-            Err(e) => {
-                let error_msg = format!("{}", e);
-                let lines: Vec<&str> = error_msg.lines().collect();
-                if lines.len() > 2 {
-                    EvalResult::Error(lines[lines.len() - 2..].join("\n").to_string())
-                } else {
-                    EvalResult::Error(error_msg)
+        match self.session.eval(trimmed) {
+            Ok(value) => {
+                self.context.increment_eval(start.elapsed());
+                self.extract_definitions();
+                self.inputs.push(trimmed.to_string());
+                match value {
+                    RuntimeValue::Unit => EvalResult::Ok,
+                    value => EvalResult::Value(value),
                 }
             }
+            Err(e) => EvalResult::Error(match e.downcast_ref::<CompileError>() {
+                Some(e) => {
+                    let error_msg = format!("{}", e);
+                    let lines: Vec<&str> = error_msg.lines().collect();
+                    if lines.len() > 2 {
+                        lines[lines.len() - 2..].join("\n").to_string()
+                    } else {
+                        error_msg
+                    }
+                }
+                None => match e.downcast_ref::<ExecutorError>() {
+                    Some(e) => format!("Runtime error: {:?}", e),
+                    None => e.to_string(),
+                },
+            }),
         }
     }
 
@@ -307,79 +408,31 @@ impl Evaluator {
         braces == 0 && brackets == 0 && parens == 0 && !in_string && !escaped
     }
 
-    /// Wrap code for REPL evaluation
-    fn wrap_code(
-        &self,
-        code: &str,
-    ) -> String {
-        let trimmed = code.trim();
-
-        if self.is_statement(trimmed) {
-            format!("main() -> () = () => {{ {} }}", code)
-        } else {
-            format!("main() -> _ = () => {{ {} }}", code)
-        }
-    }
-
-    /// Check if code is a statement
-    fn is_statement(
-        &self,
-        code: &str,
-    ) -> bool {
-        let first_word = code.split_whitespace().next();
-        matches!(
-            first_word,
-            Some("let")
-                | Some("if")
-                | Some("match")
-                | Some("for")
-                | Some("while")
-                | Some("fn")
-                | Some("struct")
-                | Some("enum")
-                | Some("trait")
-                | Some("impl")
-                | Some("return")
-                | Some("break")
-                | Some("continue")
-        )
-    }
-
-    /// Extract defined variables and functions to context
-    fn extract_definitions(
-        &mut self,
-        module: &BytecodeModule,
-    ) {
-        for func in &module.functions {
-            if func.name == "main" {
-                continue;
+    /// Copy the session's variables and functions into the context
+    fn extract_definitions(&mut self) {
+        for (name, _) in self.session.variables() {
+            if let Some(value) = self.session.get(name) {
+                self.context.define_var(name.clone(), value.clone());
             }
-
-            let params: Vec<String> = func.params.iter().map(|p| format!("{:?}", p)).collect();
-            let signature = format!("fn({}) -> {:?}", params.join(", "), func.return_type);
-
-            self.context.define_function(
-                func.name.clone(),
-                signature,
-                format!("{:?}", func.return_type),
-            );
         }
-
-        for global in &module.globals {
-            if global.name.starts_with('$') {
-                continue;
-            }
-
-            let type_str = if (global.type_id as usize) < module.type_table.len() {
-                format!("{:?}", module.type_table[global.type_id as usize])
-            } else {
-                "unknown".to_string()
+        for (name, ty) in self.session.functions() {
+            let return_type = match &ty.body {
+                MonoType::Fn { return_type, .. } => return_type.to_string(),
+                other => other.to_string(),
             };
-
-            self.context.define_variable(global.name.clone(), type_str);
+            self.context
+                .define_function(name.clone(), ty.to_string(), return_type);
         }
     }
 
+    /// Format a value for display, as `show(value)` would
+    pub fn show(
+        &mut self,
+        value: &RuntimeValue,
+    ) -> String {
+        self.session.show(value)
+    }
+
     /// Get context reference
     pub fn context(&self) -> &REPLContext {
         &self.context
@@ -424,7 +477,7 @@ impl REPLBackend for Evaluator {
     }
 
     fn clear(&mut self) {
-        self.context.clear();
+        Evaluator::clear(self);
     }
 
     fn stats(&self) -> ExecutionStats {
@@ -13,10 +13,10 @@
 
 pub mod backend;
 pub mod completer;
+pub mod debugger;
 pub mod eval;
 
 use std::cell::RefCell;
-use std::collections::HashSet;
 use std::io;
 use std::path::PathBuf;
 use std::rc::Rc;
@@ -25,11 +25,12 @@ use rustyline::config::Config;
 use rustyline::error::ReadlineError;
 use rustyline::{CompletionType, EditMode, Editor};
 
+use crate::util::artifact_cache::ArtifactCache;
+use crate::vm::debug::Breakpoint;
+
 pub use backend::{EvalResult, ExecutionStats, REPLBackend, SymbolInfo};
 pub use completer::ReplCompleter;
-pub use eval::{Evaluator, REPLContext};
-
-use crate::backends::common::RuntimeValue;
+pub use eval::{Evaluator, REPLContext, SessionSnapshot};
 
 // =============================================================================
 // Configuration
@@ -50,6 +51,8 @@ pub struct ReplConfig {
     pub history_size: usize,
     /// Show execution time for :run
     pub show_timing: bool,
+    /// Compiled bytecode and session cache (`None` disables caching)
+    pub cache: Option<ArtifactCache>,
 }
 
 impl Default for ReplConfig {
@@ -65,10 +68,14 @@ impl Default for ReplConfig {
             history_file,
             history_size: 1000,
             show_timing: true,
+            cache: ArtifactCache::user(),
         }
     }
 }
 
+/// Name of the persisted REPL session in the artifact cache
+const SESSION_NAME: &str = "repl";
+
 // =============================================================================
 // Command Result
 // =============================================================================
@@ -99,8 +106,8 @@ pub struct Repl {
     evaluator: Rc<RefCell<Evaluator>>,
     /// Current working directory
     cwd: PathBuf,
-    /// Breakpoints: (function_name, instruction_offset)
-    breakpoints: HashSet<(String, usize)>,
+    /// Breakpoints for `:debug`, by function and offset or by file and line
+    breakpoints: Vec<Breakpoint>,
 }
 
 impl Repl {
@@ -121,7 +128,15 @@ impl Repl {
             })
             .build();
 
-        let evaluator = Rc::new(RefCell::new(Evaluator::new()));
+        let mut evaluator = Evaluator::new();
+        if let Some(ref cache) = config.cache {
+            evaluator = evaluator.with_cache(cache.clone());
+            // Restore the previous session's definitions
+            if let Some(snapshot) = cache.load_session::<SessionSnapshot>(SESSION_NAME) {
+                evaluator.restore(snapshot);
+            }
+        }
+        let evaluator = Rc::new(RefCell::new(evaluator));
         let completer = ReplCompleter::new(Rc::clone(&evaluator));
 
         let mut editor = Editor::with_config(rl_config)
@@ -140,7 +155,7 @@ impl Repl {
             editor,
             evaluator,
             cwd: std::env::current_dir().unwrap_or_default(),
-            breakpoints: HashSet::new(),
+            breakpoints: Vec::new(),
         })
     }
 
@@ -187,7 +202,7 @@ impl Repl {
 
                     match eval_result {
                         EvalResult::Value(v) => {
-                            println!("{}", Self::format_value(&v));
+                            println!("{}", self.evaluator.borrow_mut().show(&v));
                             buffer.clear();
                             in_continuation = false;
                         }
@@ -228,6 +243,11 @@ impl Repl {
             let _ = self.editor.save_history(history_file);
         }
 
+        // Save session
+        if let Some(ref cache) = self.config.cache {
+            let _ = cache.store_session(SESSION_NAME, &self.evaluator.borrow().snapshot());
+        }
+
         Ok(())
     }
 
@@ -264,6 +284,19 @@ impl Repl {
                 CommandResult::Continue
             }
 
+            // Bytecode cache
+            "cache" => match (&self.config.cache, parts.get(1).copied()) {
+                (None, _) => CommandResult::Output("Cache disabled".to_string()),
+                (Some(cache), None) => {
+                    CommandResult::Output(format!("Cache directory: {}", cache.root().display()))
+                }
+                (Some(cache), Some("clear")) => match cache.clear() {
+                    Ok(()) => CommandResult::Output("Cache cleared".to_string()),
+                    Err(e) => CommandResult::Output(format!("Error clearing cache: {}", e)),
+                },
+                (Some(_), Some(_)) => CommandResult::Output("Usage: :cache [clear]".to_string()),
+            },
+
             // Show type of symbol
             "type" | "t" => {
                 if let Some(name) = parts.get(1) {
@@ -322,27 +355,26 @@ impl Repl {
             }
 
             // Set breakpoint
-            "break" | "b" => {
-                if parts.len() > 2 {
-                    if let Ok(offset) = parts[2].parse() {
-                        self.breakpoints.insert((parts[1].to_string(), offset));
-                        println!("Breakpoint set at {}:{}", parts[1], offset);
-                        CommandResult::Continue
-                    } else {
-                        CommandResult::Output("Invalid offset".to_string())
+            "break" | "b" => match debugger::parse_breakpoint(&parts[1..]) {
+                Some(at) => {
+                    println!("Breakpoint set at {}", debugger::describe_breakpoint(&at));
+                    if !self.breakpoints.contains(&at) {
+                        self.breakpoints.push(at);
                     }
-                } else {
-                    CommandResult::Output("Usage: :break <function> <offset>".to_string())
+                    CommandResult::Continue
                 }
-            }
+                None => CommandResult::Output(
+                    "Usage: :break <function> <offset> | :break <file>:<line>".to_string(),
+                ),
+            },
 
             // List breakpoints
             "breakpoints" | "bp" => {
                 if self.breakpoints.is_empty() {
                     println!("No breakpoints set");
                 } else {
-                    for (func, offset) in &self.breakpoints {
-                        println!("  {}:{}", func, offset);
+                    for at in &self.breakpoints {
+                        println!("  {}", debugger::describe_breakpoint(at));
                     }
                 }
                 CommandResult::Continue
@@ -469,9 +501,23 @@ impl Repl {
             return CommandResult::Output(format!("File not found: {}", file_path.display()));
         }
 
-        println!("Debugging {}...", file_path.display());
-        println!("Use :break <function> <offset> to set breakpoints");
-        CommandResult::Continue
+        match self.debug(file_path) {
+            Ok(()) => CommandResult::Continue,
+            Err(e) => CommandResult::Output(format!("IO error: {}", e)),
+        }
+    }
+
+    /// Run a file under the debugger, stopping at the breakpoints set with
+    /// `:break` (or at its first instruction when there are none)
+    pub fn debug(
+        &mut self,
+        path: &std::path::Path,
+    ) -> io::Result<()> {
+        println!(
+            "Debugging {} - type `help` at the (debug) prompt",
+            path.display()
+        );
+        debugger::debug_file(&mut self.editor, path, &self.breakpoints)
     }
 
     // =========================================================================
@@ -484,13 +530,15 @@ impl Repl {
         println!("  :quit, :q, :exit       - Exit the REPL");
         println!("  :help, :h              - Show this help");
         println!("  :clear, :c             - Clear all state");
+        println!("  :cache [clear]         - Show or clear the bytecode cache");
         println!("  :type, :t <name>       - Show type of symbol");
         println!("  :symbols, :info, :i    - List all symbols");
         println!("  :stats                 - Show execution statistics");
         println!("  :run <file>            - Run a file");
         println!("  :load <file>           - Load a file");
         println!("  :debug <file>          - Debug a file");
-        println!("  :break, :b <fn> <off>  - Set breakpoint");
+        println!("  :break, :b <fn> <off>  - Set breakpoint at an instruction");
+        println!("  :break, :b <file>:<ln> - Set breakpoint at a source line");
         println!("  :breakpoints, :bp      - List breakpoints");
         println!("  :cd <dir>              - Change directory");
         println!("  :pwd                   - Print working directory");
@@ -498,18 +546,6 @@ impl Repl {
         println!("  :history, :hist        - Show command history");
     }
 
-    /// Format a value for display
-    fn format_value(value: &RuntimeValue) -> String {
-        match value {
-            RuntimeValue::Unit => "()".to_string(),
-            RuntimeValue::Bool(b) => b.to_string(),
-            RuntimeValue::Int(i) => i.to_string(),
-            RuntimeValue::Float(f) => format!("{}", f),
-            RuntimeValue::String(s) => format!("{:?}", s),
-            _ => format!("{}", value),
-        }
-    }
-
     /// Get the evaluator reference
     pub fn evaluator(&self) -> std::cell::Ref<'_, Evaluator> {
         self.evaluator.borrow()
@@ -526,7 +562,7 @@ impl Repl {
     }
 
     /// Get breakpoints
-    pub fn breakpoints(&self) -> &HashSet<(String, usize)> {
+    pub fn breakpoints(&self) -> &[Breakpoint] {
         &self.breakpoints
     }
 }
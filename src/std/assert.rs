@@ -0,0 +1,466 @@
+//! Standard Assert library (YaoXiang)
+//!
+//! Runtime equality assertions. A failed `assert_eq` reports both operands using
+//! the canonical printer (`std::show`); when either side spans several lines the
+//! message contains a line diff instead.
+//!
+//! Float results rarely match exactly, so `assert_approx_eq` and
+//! `assert_all_close` accept an absolute tolerance `eps`; the latter walks lists,
+//! tuples and arrays element-wise (a matrix is a list of rows) and reports the
+//! index path of the first mismatch.
+
+use crate::backends::common::{Handle, Heap, HeapValue, RuntimeValue, Value};
+use crate::backends::ExecutorError;
+use crate::std::show::show;
+use crate::std::string::format_float_default;
+use crate::std::{NativeContext, NativeExport, StdModule};
+
+// ============================================================================
+// AssertModule - StdModule Implementation
+// ============================================================================
+
+/// Assert module implementation.
+pub struct AssertModule;
+
+impl Default for AssertModule {
+    fn default() -> Self {
+        Self
+    }
+}
+
+impl StdModule for AssertModule {
+    fn module_path(&self) -> &str {
+        "std.assert"
+    }
+
+    fn exports(&self) -> Vec<NativeExport> {
+        vec![
+            NativeExport::new(
+                "assert_eq",
+                "std.assert.assert_eq",
+                "(left, right) -> Void",
+                native_assert_eq,
+            ),
+            NativeExport::new(
+                "assert_ne",
+                "std.assert.assert_ne",
+                "(left, right) -> Void",
+                native_assert_ne,
+            ),
+            NativeExport::new(
+                "assert_approx_eq",
+                "std.assert.assert_approx_eq",
+                "(left: Float, right: Float, eps: Float) -> Void",
+                native_assert_approx_eq,
+            ),
+            NativeExport::new(
+                "assert_all_close",
+                "std.assert.assert_all_close",
+                "(left: List, right: List, eps: Float) -> Void",
+                native_assert_all_close,
+            ),
+        ]
+    }
+}
+
+/// Singleton instance for std.assert module.
+pub const ASSERT_MODULE: AssertModule = AssertModule;
+
+// ============================================================================
+// Native Function Implementations
+// ============================================================================
+
+/// Native implementation: assert_eq
+fn native_assert_eq(
+    args: &[RuntimeValue],
+    ctx: &mut NativeContext<'_>,
+) -> Result<RuntimeValue, ExecutorError> {
+    let (left, right) = operands(args, "assert_eq")?;
+    if values_equal(left, right, ctx.heap, &mut Vec::new()) {
+        return Ok(RuntimeValue::Unit);
+    }
+    let left = show(left, ctx)?;
+    let right = show(right, ctx)?;
+    Err(ExecutorError::runtime_only(failure_message(
+        "left == right",
+        &left,
+        &right,
+    )))
+}
+
+/// Native implementation: assert_ne
+fn native_assert_ne(
+    args: &[RuntimeValue],
+    ctx: &mut NativeContext<'_>,
+) -> Result<RuntimeValue, ExecutorError> {
+    let (left, right) = operands(args, "assert_ne")?;
+    if !values_equal(left, right, ctx.heap, &mut Vec::new()) {
+        return Ok(RuntimeValue::Unit);
+    }
+    let value = show(left, ctx)?;
+    Err(ExecutorError::runtime_only(format!(
+        "assertion failed: left != right\n  both: {}",
+        value
+    )))
+}
+
+/// Native implementation: assert_approx_eq
+fn native_assert_approx_eq(
+    args: &[RuntimeValue],
+    _ctx: &mut NativeContext<'_>,
+) -> Result<RuntimeValue, ExecutorError> {
+    let (left, right) = operands(args, "assert_approx_eq")?;
+    let eps = tolerance(args, "assert_approx_eq")?;
+    let (Some(a), Some(b)) = (as_float(left), as_float(right)) else {
+        return Err(ExecutorError::runtime_only(
+            "assert_approx_eq expects numeric operands",
+        ));
+    };
+    if close(a, b, eps) {
+        return Ok(RuntimeValue::Unit);
+    }
+    Err(ExecutorError::runtime_only(approx_message(
+        "|left - right| <= eps",
+        a,
+        b,
+        eps,
+    )))
+}
+
+/// Native implementation: assert_all_close
+fn native_assert_all_close(
+    args: &[RuntimeValue],
+    ctx: &mut NativeContext<'_>,
+) -> Result<RuntimeValue, ExecutorError> {
+    let (left, right) = operands(args, "assert_all_close")?;
+    let eps = tolerance(args, "assert_all_close")?;
+    let Some(mismatch) = first_mismatch(left, right, eps, ctx.heap, &mut String::new()) else {
+        return Ok(RuntimeValue::Unit);
+    };
+    let message = match mismatch {
+        Mismatch::Value { path, left, right } => approx_message(
+            &format!("left{0} ≈ right{0}", path),
+            left,
+            right,
+            eps,
+        ),
+        Mismatch::Length { path, left, right } => format!(
+            "assertion failed: len(left{0}) == len(right{0})\n  left: {1} elements\n right: {2} elements",
+            path, left, right
+        ),
+        Mismatch::Shape { path, left, right } => {
+            let left = show(&left, ctx)?;
+            let right = show(&right, ctx)?;
+            format!(
+                "assertion failed: left{0} and right{0} are not comparable numbers or sequences\n  left: {1}\n right: {2}",
+                path, left, right
+            )
+        }
+    };
+    Err(ExecutorError::runtime_only(message))
+}
+
+fn operands<'v>(
+    args: &'v [RuntimeValue],
+    name: &str,
+) -> Result<(&'v RuntimeValue, &'v RuntimeValue), ExecutorError> {
+    match args {
+        [left, right, ..] => Ok((left, right)),
+        _ => Err(ExecutorError::runtime_only(format!(
+            "{} expects 2 arguments, got {}",
+            name,
+            args.len()
+        ))),
+    }
+}
+
+/// The `eps` argument: a non-negative number
+fn tolerance(
+    args: &[RuntimeValue],
+    name: &str,
+) -> Result<f64, ExecutorError> {
+    match args.get(2).and_then(as_float) {
+        Some(eps) if eps >= 0.0 => Ok(eps),
+        Some(eps) => Err(ExecutorError::runtime_only(format!(
+            "{}: eps must be non-negative, got {}",
+            name,
+            format_float_default(eps)
+        ))),
+        None => Err(ExecutorError::runtime_only(format!(
+            "{} expects a numeric eps as its third argument",
+            name
+        ))),
+    }
+}
+
+// ============================================================================
+// Approximate equality
+// ============================================================================
+
+/// First position where two numeric values or sequences differ by more than eps
+enum Mismatch {
+    /// Numbers further apart than eps
+    Value { path: String, left: f64, right: f64 },
+    /// Sequences of different lengths
+    Length {
+        path: String,
+        left: usize,
+        right: usize,
+    },
+    /// Neither two numbers nor two sequences
+    Shape {
+        path: String,
+        left: RuntimeValue,
+        right: RuntimeValue,
+    },
+}
+
+fn as_float(value: &RuntimeValue) -> Option<f64> {
+    match value {
+        RuntimeValue::Float(f) => Some(*f),
+        RuntimeValue::Int(n) => Some(*n as f64),
+        _ => None,
+    }
+}
+
+/// Infinities compare equal to themselves; NaN is never close to anything
+fn close(
+    a: f64,
+    b: f64,
+    eps: f64,
+) -> bool {
+    a == b || (a - b).abs() <= eps
+}
+
+/// Elements of a list, tuple or array
+fn sequence<'h>(
+    value: &RuntimeValue,
+    heap: &'h Heap,
+) -> Option<&'h [Value]> {
+    match value {
+        RuntimeValue::Tuple(h) | RuntimeValue::Array(h) | RuntimeValue::List(h) => {
+            match heap.get(*h)? {
+                HeapValue::Tuple(items) | HeapValue::Array(items) | HeapValue::List(items) => {
+                    Some(items)
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Walk both values in lockstep; `path` accumulates the index path (`[1][2]`)
+fn first_mismatch(
+    left: &RuntimeValue,
+    right: &RuntimeValue,
+    eps: f64,
+    heap: &Heap,
+    path: &mut String,
+) -> Option<Mismatch> {
+    if let (Some(a), Some(b)) = (as_float(left), as_float(right)) {
+        return (!close(a, b, eps)).then(|| Mismatch::Value {
+            path: path.clone(),
+            left: a,
+            right: b,
+        });
+    }
+    let (Some(xs), Some(ys)) = (sequence(left, heap), sequence(right, heap)) else {
+        return Some(Mismatch::Shape {
+            path: path.clone(),
+            left: left.clone(),
+            right: right.clone(),
+        });
+    };
+    if xs.len() != ys.len() {
+        return Some(Mismatch::Length {
+            path: path.clone(),
+            left: xs.len(),
+            right: ys.len(),
+        });
+    }
+    for (i, (x, y)) in xs.iter().zip(ys).enumerate() {
+        let len = path.len();
+        path.push_str(&format!("[{}]", i));
+        let mismatch = first_mismatch(&x.view(), &y.view(), eps, heap, path);
+        path.truncate(len);
+        if mismatch.is_some() {
+            return mismatch;
+        }
+    }
+    None
+}
+
+fn approx_message(
+    condition: &str,
+    left: f64,
+    right: f64,
+    eps: f64,
+) -> String {
+    format!(
+        "assertion failed: {}\n  left: {}\n right: {}\n  diff: {}\n   eps: {}",
+        condition,
+        format_float_default(left),
+        format_float_default(right),
+        format_float_default((left - right).abs()),
+        format_float_default(eps)
+    )
+}
+
+// ============================================================================
+// Structural equality
+// ============================================================================
+
+/// Compare two values by content, following heap references
+///
+/// `visiting` holds handle pairs under comparison, so self-referencing values
+/// terminate (a pair met again is assumed equal).
+fn values_equal(
+    left: &RuntimeValue,
+    right: &RuntimeValue,
+    heap: &Heap,
+    visiting: &mut Vec<(Handle, Handle)>,
+) -> bool {
+    match (left, right) {
+        (RuntimeValue::Tuple(a), RuntimeValue::Tuple(b))
+        | (RuntimeValue::Array(a), RuntimeValue::Array(b))
+        | (RuntimeValue::List(a), RuntimeValue::List(b))
+        | (RuntimeValue::Dict(a), RuntimeValue::Dict(b)) => handles_equal(*a, *b, heap, visiting),
+        (
+            RuntimeValue::Struct {
+                type_id: t1,
+                fields: f1,
+                ..
+            },
+            RuntimeValue::Struct {
+                type_id: t2,
+                fields: f2,
+                ..
+            },
+        ) => t1 == t2 && handles_equal(*f1, *f2, heap, visiting),
+        (
+            RuntimeValue::Enum {
+                type_id: t1,
+                variant_id: v1,
+                payload: p1,
+            },
+            RuntimeValue::Enum {
+                type_id: t2,
+                variant_id: v2,
+                payload: p2,
+            },
+        ) => t1 == t2 && v1 == v2 && values_equal(p1, p2, heap, visiting),
+        (RuntimeValue::Arc(a), RuntimeValue::Arc(b)) => values_equal(a, b, heap, visiting),
+        (RuntimeValue::Dyn { value: a, .. }, b) | (b, RuntimeValue::Dyn { value: a, .. }) => {
+            values_equal(a, b, heap, visiting)
+        }
+        _ => left == right,
+    }
+}
+
+fn handles_equal(
+    a: Handle,
+    b: Handle,
+    heap: &Heap,
+    visiting: &mut Vec<(Handle, Handle)>,
+) -> bool {
+    if a == b || visiting.contains(&(a, b)) {
+        return true;
+    }
+    visiting.push((a, b));
+    let equal = match (heap.get(a), heap.get(b)) {
+        (Some(HeapValue::Dict(x)), Some(HeapValue::Dict(y))) => {
+            x.len() == y.len()
+                && x.iter().all(|(key, value)| {
+                    y.get(key)
+                        .is_some_and(|other| values_equal(value, other, heap, visiting))
+                })
+        }
+        (
+            Some(
+                HeapValue::Tuple(x)
+                | HeapValue::Array(x)
+                | HeapValue::List(x)
+                | HeapValue::Struct(x),
+            ),
+            Some(
+                HeapValue::Tuple(y)
+                | HeapValue::Array(y)
+                | HeapValue::List(y)
+                | HeapValue::Struct(y),
+            ),
+        ) => {
+            x.len() == y.len()
+                && x.iter()
+                    .zip(y)
+                    .all(|(l, r)| values_equal(&l.view(), &r.view(), heap, visiting))
+        }
+        _ => false,
+    };
+    visiting.pop();
+    equal
+}
+
+// ============================================================================
+// Failure messages
+// ============================================================================
+
+/// Message for a failed comparison; multi-line operands are shown as a line diff
+fn failure_message(
+    condition: &str,
+    left: &str,
+    right: &str,
+) -> String {
+    if !left.contains('\n') && !right.contains('\n') {
+        return format!(
+            "assertion failed: {}\n  left: {}\n right: {}",
+            condition, left, right
+        );
+    }
+    let mut message = format!("assertion failed: {}\ndiff (- left, + right):", condition);
+    for line in line_diff(left, right) {
+        message.push('\n');
+        message.push_str(&line);
+    }
+    message
+}
+
+/// Longest-common-subsequence line diff: `-` only in left, `+` only in right
+fn line_diff(
+    left: &str,
+    right: &str,
+) -> Vec<String> {
+    let a: Vec<&str> = left.lines().collect();
+    let b: Vec<&str> = right.lines().collect();
+
+    // lcs[i][j]: LCS length of a[i..] and b[j..]
+    let mut lcs = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if a[i] == b[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut out = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        if a[i] == b[j] {
+            out.push(format!("  {}", a[i]));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            out.push(format!("- {}", a[i]));
+            i += 1;
+        } else {
+            out.push(format!("+ {}", b[j]));
+            j += 1;
+        }
+    }
+    out.extend(a[i..].iter().map(|line| format!("- {}", line)));
+    out.extend(b[j..].iter().map(|line| format!("+ {}", line)));
+    out
+}
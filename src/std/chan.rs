@@ -0,0 +1,203 @@
+//! Standard Channel library (YaoXiang)
+//!
+//! This module provides channels for passing values between spawned tasks.
+//!
+//! Channels are opaque `Int` handles shared by every task of the program.
+//! Values are copied into the channel on `send` and out of it on `recv`, so
+//! the receiving task owns what it gets (see
+//! [`SendValue`](crate::backends::common::SendValue)).
+//!
+//! `recv` on an empty channel and `send` on a full bounded one wait. Inside a
+//! green thread (Full runtime) only the task waits: its worker goes on running
+//! other tasks. Elsewhere the calling OS thread blocks.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{Arc, LazyLock, Mutex};
+
+use crate::backends::common::{RuntimeValue, SendValue};
+use crate::backends::runtime::channel::Channel;
+use crate::backends::ExecutorError;
+use crate::std::{NativeContext, NativeExport, StdModule};
+
+// ============================================================================
+// ChanModule - StdModule Implementation
+// ============================================================================
+
+/// Channel module implementation.
+pub struct ChanModule;
+
+impl Default for ChanModule {
+    fn default() -> Self {
+        Self
+    }
+}
+
+impl StdModule for ChanModule {
+    fn module_path(&self) -> &str {
+        "std.chan"
+    }
+
+    fn exports(&self) -> Vec<NativeExport> {
+        vec![
+            NativeExport::new("new", "std.chan.new", "() -> Int", native_new),
+            NativeExport::new(
+                "bounded",
+                "std.chan.bounded",
+                "(capacity: Int) -> Int",
+                native_bounded,
+            ),
+            NativeExport::new(
+                "send",
+                "std.chan.send",
+                "(T: Type)(ch: Int, value: T) -> Void",
+                native_send,
+            ),
+            NativeExport::new(
+                "recv",
+                "std.chan.recv",
+                "(T: Type)(ch: Int) -> T",
+                native_recv,
+            ),
+            NativeExport::new("close", "std.chan.close", "(ch: Int) -> Void", native_close),
+            NativeExport::new("len", "std.chan.len", "(ch: Int) -> Int", native_len),
+        ]
+    }
+}
+
+/// Singleton instance for std.chan module.
+pub const CHAN_MODULE: ChanModule = ChanModule;
+
+// ============================================================================
+// Channel Table
+// ============================================================================
+
+/// Global channel storage, shared by the interpreters of all tasks.
+static CHANNELS: LazyLock<Mutex<HashMap<i64, Arc<Channel<SendValue>>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Global counter for generating unique channel handles.
+static NEXT_CHANNEL: AtomicI64 = AtomicI64::new(1);
+
+fn register(channel: Channel<SendValue>) -> Result<RuntimeValue, ExecutorError> {
+    let handle = NEXT_CHANNEL.fetch_add(1, Ordering::Relaxed);
+    CHANNELS
+        .lock()
+        .map_err(|_| ExecutorError::runtime_only("Failed to lock channel table".to_string()))?
+        .insert(handle, Arc::new(channel));
+    Ok(RuntimeValue::Int(handle))
+}
+
+/// Looks up the channel behind the first argument. The table lock is released
+/// before the caller waits on the channel.
+fn channel_arg(
+    args: &[RuntimeValue],
+    func: &str,
+) -> Result<(i64, Arc<Channel<SendValue>>), ExecutorError> {
+    let Some(RuntimeValue::Int(handle)) = args.first() else {
+        return Err(ExecutorError::type_only(format!(
+            "{} expects a channel (Int) as its first argument",
+            func
+        )));
+    };
+    let channel = CHANNELS
+        .lock()
+        .map_err(|_| ExecutorError::runtime_only("Failed to lock channel table".to_string()))?
+        .get(handle)
+        .cloned()
+        .ok_or_else(|| ExecutorError::runtime_only(format!("Invalid channel: {}", handle)))?;
+    Ok((*handle, channel))
+}
+
+/// Drops a closed channel from the table once nothing is left to receive.
+fn release_if_drained(
+    handle: i64,
+    channel: &Channel<SendValue>,
+) {
+    if channel.is_closed() && channel.is_empty() {
+        if let Ok(mut channels) = CHANNELS.lock() {
+            channels.remove(&handle);
+        }
+    }
+}
+
+// ============================================================================
+// Native Function Implementations
+// ============================================================================
+
+/// Native implementation: new
+fn native_new(
+    _args: &[RuntimeValue],
+    _ctx: &mut NativeContext<'_>,
+) -> Result<RuntimeValue, ExecutorError> {
+    register(Channel::unbounded())
+}
+
+/// Native implementation: bounded
+fn native_bounded(
+    args: &[RuntimeValue],
+    _ctx: &mut NativeContext<'_>,
+) -> Result<RuntimeValue, ExecutorError> {
+    match args.first() {
+        Some(RuntimeValue::Int(capacity)) if *capacity > 0 => {
+            register(Channel::bounded(*capacity as usize))
+        }
+        Some(RuntimeValue::Int(capacity)) => Err(ExecutorError::runtime_only(format!(
+            "Channel capacity must be positive, got {}",
+            capacity
+        ))),
+        _ => Err(ExecutorError::type_only(
+            "bounded expects 1 argument (capacity: Int)".to_string(),
+        )),
+    }
+}
+
+/// Native implementation: send
+fn native_send(
+    args: &[RuntimeValue],
+    ctx: &mut NativeContext<'_>,
+) -> Result<RuntimeValue, ExecutorError> {
+    let (handle, channel) = channel_arg(args, "send")?;
+    let value = args.get(1).ok_or_else(|| {
+        ExecutorError::type_only("send expects 2 arguments (ch: Int, value: Any)".to_string())
+    })?;
+    let value = SendValue::detach(value, ctx.heap)
+        .map_err(|e| ExecutorError::type_only(format!("Cannot send value: {}", e)))?;
+    channel
+        .send(value)
+        .map_err(|e| ExecutorError::runtime_only(format!("send on channel {}: {}", handle, e)))?;
+    Ok(RuntimeValue::Unit)
+}
+
+/// Native implementation: recv
+fn native_recv(
+    args: &[RuntimeValue],
+    ctx: &mut NativeContext<'_>,
+) -> Result<RuntimeValue, ExecutorError> {
+    let (handle, channel) = channel_arg(args, "recv")?;
+    let received = channel.recv();
+    release_if_drained(handle, &channel);
+    let value = received
+        .map_err(|e| ExecutorError::runtime_only(format!("recv on channel {}: {}", handle, e)))?;
+    Ok(value.attach(ctx.heap))
+}
+
+/// Native implementation: close
+fn native_close(
+    args: &[RuntimeValue],
+    _ctx: &mut NativeContext<'_>,
+) -> Result<RuntimeValue, ExecutorError> {
+    let (handle, channel) = channel_arg(args, "close")?;
+    channel.close();
+    release_if_drained(handle, &channel);
+    Ok(RuntimeValue::Unit)
+}
+
+/// Native implementation: len
+fn native_len(
+    args: &[RuntimeValue],
+    _ctx: &mut NativeContext<'_>,
+) -> Result<RuntimeValue, ExecutorError> {
+    let (_, channel) = channel_arg(args, "len")?;
+    Ok(RuntimeValue::Int(channel.len() as i64))
+}
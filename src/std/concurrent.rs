@@ -76,10 +76,18 @@ fn native_thread_id(
 }
 
 /// Native implementation: yield_now
+///
+/// Inside a spawned task (Full runtime) this suspends the green thread so the
+/// other tasks on its worker can run; elsewhere it yields the OS thread.
 fn native_yield_now(
     _args: &[RuntimeValue],
     _ctx: &mut NativeContext<'_>,
 ) -> Result<RuntimeValue, ExecutorError> {
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(task) = crate::backends::runtime::green::current() {
+        task.yield_now();
+        return Ok(RuntimeValue::Unit);
+    }
     std::thread::yield_now();
     Ok(RuntimeValue::Unit)
 }
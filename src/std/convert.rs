@@ -5,7 +5,7 @@
 
 use crate::backends::common::RuntimeValue;
 use crate::backends::ExecutorError;
-use crate::std::io::format_value_with_prefix;
+use crate::std::show::show_display;
 use crate::std::{NativeContext, NativeExport, StdModule};
 
 // ============================================================================
@@ -132,18 +132,7 @@ fn native_to_string(
         return Ok(RuntimeValue::String("()".into()));
     }
 
-    let arg = &args[0];
-    let result = format_value_with_stringable(arg, ctx.heap);
+    let result = show_display(&args[0], ctx)?;
 
     Ok(RuntimeValue::String(result.into()))
 }
-
-/// Format a value, trying Stringable first, then falling back to type info
-/// 直接复用 io 模块的公共函数
-fn format_value_with_stringable(
-    val: &RuntimeValue,
-    heap: &crate::backends::common::Heap,
-) -> String {
-    // 直接使用 io 模块的公共格式化函数，prefix 为空
-    format_value_with_prefix(val, heap, "")
-}
@@ -139,7 +139,7 @@ fn native_set(
         _ => return Err(ExecutorError::runtime_only("Invalid dict handle")),
     };
     map.insert(key, value);
-    let new_handle = ctx.heap.allocate(HeapValue::Dict(map));
+    let new_handle = ctx.heap.try_allocate(HeapValue::Dict(map))?;
     Ok(RuntimeValue::Dict(new_handle))
 }
 
@@ -178,7 +178,7 @@ fn native_values(
         Some(HeapValue::Dict(map)) => map.values().cloned().collect(),
         _ => Vec::new(),
     };
-    let list_handle = ctx.heap.allocate(HeapValue::List(values));
+    let list_handle = ctx.heap.try_allocate(HeapValue::list(values))?;
     Ok(RuntimeValue::List(list_handle))
 }
 
@@ -200,7 +200,7 @@ fn native_keys(
         Some(HeapValue::Dict(map)) => map.keys().cloned().collect(),
         _ => Vec::new(),
     };
-    let list_handle = ctx.heap.allocate(HeapValue::List(keys));
+    let list_handle = ctx.heap.try_allocate(HeapValue::list(keys))?;
     Ok(RuntimeValue::List(list_handle))
 }
 
@@ -222,7 +222,7 @@ fn native_entries(
         Some(HeapValue::Dict(map)) => map.clone(),
         _ => {
             return Ok(RuntimeValue::List(
-                ctx.heap.allocate(HeapValue::List(Vec::new())),
+                ctx.heap.try_allocate(HeapValue::List(Vec::new()))?,
             ))
         }
     };
@@ -230,14 +230,12 @@ fn native_entries(
     let entries: Vec<RuntimeValue> = map
         .iter()
         .map(|(k, v)| {
-            let tuple_handle = ctx
-                .heap
-                .allocate(HeapValue::Tuple(vec![k.clone(), v.clone()]));
+            let tuple_handle = ctx.heap.allocate(HeapValue::tuple([k.clone(), v.clone()]));
             RuntimeValue::Tuple(tuple_handle)
         })
         .collect();
 
-    let list_handle = ctx.heap.allocate(HeapValue::List(entries));
+    let list_handle = ctx.heap.try_allocate(HeapValue::list(entries))?;
     Ok(RuntimeValue::List(list_handle))
 }
 
@@ -261,7 +259,7 @@ fn native_delete(
         _ => return Err(ExecutorError::runtime_only("Invalid dict handle")),
     };
     map.remove(&key);
-    let new_handle = ctx.heap.allocate(HeapValue::Dict(map));
+    let new_handle = ctx.heap.try_allocate(HeapValue::Dict(map))?;
     Ok(RuntimeValue::Dict(new_handle))
 }
 
@@ -330,6 +328,6 @@ fn native_merge(
 
     let mut merged = map_a;
     merged.extend(map_b);
-    let new_handle = ctx.heap.allocate(HeapValue::Dict(merged));
+    let new_handle = ctx.heap.try_allocate(HeapValue::Dict(merged))?;
     Ok(RuntimeValue::Dict(new_handle))
 }
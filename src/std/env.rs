@@ -0,0 +1,82 @@
+//! Standard Env library (YaoXiang)
+//!
+//! This module exposes the command-line arguments of the running program.
+//! `yaoxiang run script.yx -- a b` yields `["script.yx", "a", "b"]`: argv[0]
+//! is the script path, followed by everything after `--`.
+
+use std::sync::{LazyLock, RwLock};
+
+use crate::backends::common::{HeapValue, RuntimeValue};
+use crate::backends::ExecutorError;
+use crate::std::{NativeContext, NativeExport, StdModule};
+
+// ============================================================================
+// EnvModule - StdModule Implementation
+// ============================================================================
+
+/// Env module implementation.
+pub struct EnvModule;
+
+impl Default for EnvModule {
+    fn default() -> Self {
+        Self
+    }
+}
+
+impl StdModule for EnvModule {
+    fn module_path(&self) -> &str {
+        "std.env"
+    }
+
+    fn exports(&self) -> Vec<NativeExport> {
+        vec![NativeExport::new(
+            "args",
+            "std.env.args",
+            "() -> List",
+            native_args,
+        )]
+    }
+}
+
+/// Singleton instance for std.env module.
+pub const ENV_MODULE: EnvModule = EnvModule;
+
+// ============================================================================
+// Program Arguments
+// ============================================================================
+
+/// Arguments set by the host (CLI); `None` means "use the process arguments"
+static PROGRAM_ARGS: LazyLock<RwLock<Option<Vec<String>>>> = LazyLock::new(|| RwLock::new(None));
+
+/// Set the argv seen by YaoXiang programs (argv[0] is the script path)
+pub fn set_program_args(args: Vec<String>) {
+    if let Ok(mut slot) = PROGRAM_ARGS.write() {
+        *slot = Some(args);
+    }
+}
+
+/// argv of the running program, falling back to the process arguments
+pub(crate) fn program_args() -> Vec<String> {
+    PROGRAM_ARGS
+        .read()
+        .ok()
+        .and_then(|slot| slot.clone())
+        .unwrap_or_else(|| std::env::args().collect())
+}
+
+// ============================================================================
+// Native Implementations
+// ============================================================================
+
+/// Native implementation: args - argv as a list of strings
+fn native_args(
+    _args: &[RuntimeValue],
+    ctx: &mut NativeContext<'_>,
+) -> Result<RuntimeValue, ExecutorError> {
+    let items: Vec<RuntimeValue> = program_args()
+        .into_iter()
+        .map(|arg| RuntimeValue::String(arg.into()))
+        .collect();
+    let list_handle = ctx.heap.try_allocate(HeapValue::list(items))?;
+    Ok(RuntimeValue::List(list_handle))
+}
@@ -0,0 +1,56 @@
+//! Standard GC library (YaoXiang)
+//!
+//! The VM frees unreachable heap objects, reference cycles included, on its
+//! own once the heap has grown past a threshold (`--gc-threshold`). `collect`
+//! runs a collection right away, e.g. before measuring memory use.
+
+use crate::backends::common::RuntimeValue;
+use crate::backends::ExecutorError;
+use crate::std::{NativeContext, NativeExport, StdModule};
+
+// ============================================================================
+// GcModule - StdModule Implementation
+// ============================================================================
+
+/// GC module implementation.
+pub struct GcModule;
+
+impl Default for GcModule {
+    fn default() -> Self {
+        Self
+    }
+}
+
+impl StdModule for GcModule {
+    fn module_path(&self) -> &str {
+        "std.gc"
+    }
+
+    fn exports(&self) -> Vec<NativeExport> {
+        vec![NativeExport::new(
+            "collect",
+            "std.gc.collect",
+            "() -> Int",
+            native_collect,
+        )]
+    }
+}
+
+/// Singleton instance for std.gc module.
+pub const GC_MODULE: GcModule = GcModule;
+
+// ============================================================================
+// Native Implementations
+// ============================================================================
+
+/// Native implementation: collect - free unreachable objects, returning how many
+///
+/// Frees nothing when called from a callback of another native function
+/// (e.g. inside `list.map`), whose intermediate values the VM cannot see.
+fn native_collect(
+    _args: &[RuntimeValue],
+    ctx: &mut NativeContext<'_>,
+) -> Result<RuntimeValue, ExecutorError> {
+    let freed = ctx.collect_garbage()?;
+    Ok(RuntimeValue::Int(freed as i64))
+}
@@ -40,15 +40,24 @@ fn generate_interface_content(module: &dyn StdModule) -> String {
 /// 返回 `(module_name, content)` 列表
 pub fn generate_all_interfaces() -> Vec<(String, String)> {
     let modules: Vec<Box<dyn StdModule>> = vec![
+        Box::new(crate::std::assert::AssertModule),
         Box::new(crate::std::convert::ConvertModule),
         Box::new(crate::std::dict::DictModule),
+        Box::new(crate::std::env::EnvModule),
+        Box::new(crate::std::gc::GcModule),
         Box::new(crate::std::io::IoModule),
         Box::new(crate::std::list::ListModule),
+        Box::new(crate::std::locale::LocaleModule),
         Box::new(crate::std::math::MathModule),
         #[cfg(not(target_arch = "wasm32"))]
+        Box::new(crate::std::module::ModuleModule),
+        #[cfg(not(target_arch = "wasm32"))]
         Box::new(crate::std::net::NetModule),
         #[cfg(not(target_arch = "wasm32"))]
         Box::new(crate::std::concurrent::ConcurrentModule),
+        Box::new(crate::std::process::ProcessModule),
+        Box::new(crate::std::reflect::ReflectModule),
+        Box::new(crate::std::signal::SignalModule),
         Box::new(crate::std::string::StringModule),
         Box::new(crate::std::time::TimeModule),
         #[cfg(not(target_arch = "wasm32"))]
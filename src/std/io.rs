@@ -7,8 +7,12 @@
 #[cfg(not(target_arch = "wasm32"))]
 use std::io::BufRead;
 
-use crate::backends::common::{RuntimeValue, HeapValue};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::backends::runtime::reactor::blocking;
+
+use crate::backends::common::RuntimeValue;
 use crate::backends::ExecutorError;
+use crate::std::show::{show, show_display};
 use crate::std::{NativeContext, NativeExport, StdModule};
 
 // ============================================================================
@@ -68,6 +72,7 @@ impl StdModule for IoModule {
                 "(...args) -> ()",
                 native_println,
             ),
+            NativeExport::new("show", "std.io.show", "(value) -> String", native_show),
             #[cfg(not(target_arch = "wasm32"))]
             NativeExport::new(
                 "read_line",
@@ -120,8 +125,8 @@ fn native_print(
 ) -> Result<RuntimeValue, ExecutorError> {
     let output = args
         .iter()
-        .map(|arg| format_runtime_value(arg, ctx.heap))
-        .collect::<Vec<String>>()
+        .map(|arg| show_display(arg, ctx))
+        .collect::<Result<Vec<String>, ExecutorError>>()?
         .join(" ");
     #[cfg(target_arch = "wasm32")]
     {
@@ -129,7 +134,7 @@ fn native_print(
     }
     #[cfg(not(target_arch = "wasm32"))]
     {
-        print!("{}", output);
+        ctx.write_stdout(&output);
     }
     Ok(RuntimeValue::Unit)
 }
@@ -141,8 +146,8 @@ fn native_println(
 ) -> Result<RuntimeValue, ExecutorError> {
     let output = args
         .iter()
-        .map(|arg| format_runtime_value(arg, ctx.heap))
-        .collect::<Vec<String>>()
+        .map(|arg| show_display(arg, ctx))
+        .collect::<Result<Vec<String>, ExecutorError>>()?
         .join(" ");
     #[cfg(target_arch = "wasm32")]
     {
@@ -151,130 +156,27 @@ fn native_println(
     }
     #[cfg(not(target_arch = "wasm32"))]
     {
-        println!("{}", output);
+        ctx.write_stdout(&format!("{}\n", output));
     }
     Ok(RuntimeValue::Unit)
 }
 
-/// Format a runtime value, resolving heap references for List/Dict/Tuple
-fn format_runtime_value(
-    val: &RuntimeValue,
-    heap: &crate::backends::common::Heap,
-) -> String {
-    // 使用公共格式化函数，prefix 为空
-    format_value_with_prefix(val, heap, "")
-}
-
-/// 公共格式化函数（模块内共享）：格式化值，可选添加类型前缀
-/// 如果 prefix 为空，返回基础表示（如 "123"）
-/// 如果 prefix 不为空，返回带前缀的表示（如 "int(123)"）
-pub(crate) fn format_value_with_prefix(
-    val: &RuntimeValue,
-    heap: &crate::backends::common::Heap,
-    prefix: &str,
-) -> String {
-    let prefix_fn = |s: &str| {
-        if prefix.is_empty() {
-            s.to_string()
-        } else {
-            format!("{}({})", prefix, s)
-        }
+/// Native implementation: show
+/// Canonical representation of any value (strings quoted), see `std::show`
+fn native_show(
+    args: &[RuntimeValue],
+    ctx: &mut NativeContext<'_>,
+) -> Result<RuntimeValue, ExecutorError> {
+    let result = match args.first() {
+        Some(value) => show(value, ctx)?,
+        None => "unit".to_string(),
     };
-
-    match val {
-        RuntimeValue::Unit => prefix_fn("unit"),
-        RuntimeValue::Bool(b) => prefix_fn(&b.to_string()),
-        RuntimeValue::Int(i) => prefix_fn(&i.to_string()),
-        RuntimeValue::Float(f) => {
-            let s = if f.fract() == 0.0 {
-                format!("{:.1}", f)
-            } else {
-                f.to_string()
-            };
-            prefix_fn(&s)
-        }
-        RuntimeValue::Char(c) => {
-            let s = char::from_u32(*c)
-                .map(|ch| ch.to_string())
-                .unwrap_or_else(|| format!("U+{:04X}", c));
-            prefix_fn(&s)
-        }
-        RuntimeValue::String(s) => {
-            // String 类型不添加前缀，直接返回内容
-            s.to_string()
-        }
-        RuntimeValue::Bytes(b) => prefix_fn(&format!("bytes[{}]", b.len())),
-        RuntimeValue::Tuple(handle) => {
-            if let Some(HeapValue::Tuple(items)) = heap.get(*handle) {
-                let items_str: Vec<String> = items
-                    .iter()
-                    .map(|item| format_value_with_prefix(item, heap, ""))
-                    .collect();
-                let s = format!("({})", items_str.join(", "));
-                prefix_fn(&s)
-            } else {
-                prefix_fn(&format!("tuple@{}", handle.raw()))
-            }
-        }
-        RuntimeValue::Array(handle) => {
-            if let Some(HeapValue::Array(arr)) = heap.get(*handle) {
-                let items_str: Vec<String> = arr
-                    .iter()
-                    .map(|item| format_value_with_prefix(item, heap, ""))
-                    .collect();
-                let s = format!("[{}]", items_str.join(", "));
-                prefix_fn(&s)
-            } else {
-                prefix_fn(&format!("array@{}", handle.raw()))
-            }
-        }
-        RuntimeValue::List(handle) => {
-            if let Some(HeapValue::List(items)) = heap.get(*handle) {
-                let items_str: Vec<String> = items
-                    .iter()
-                    .map(|item| format_value_with_prefix(item, heap, ""))
-                    .collect();
-                let s = format!("[{}]", items_str.join(", "));
-                prefix_fn(&s)
-            } else {
-                prefix_fn(&format!("list@{}", handle.raw()))
-            }
-        }
-        RuntimeValue::Dict(handle) => {
-            if let Some(HeapValue::Dict(entries)) = heap.get(*handle) {
-                let entries_str: Vec<String> = entries
-                    .iter()
-                    .map(|(k, v)| {
-                        format!(
-                            "{}: {}",
-                            format_value_with_prefix(k, heap, ""),
-                            format_value_with_prefix(v, heap, "")
-                        )
-                    })
-                    .collect();
-                let s = format!("{{{}}}", entries_str.join(", "));
-                prefix_fn(&s)
-            } else {
-                prefix_fn(&format!("dict@{}", handle.raw()))
-            }
-        }
-        RuntimeValue::Struct { fields, .. } => prefix_fn(&format!("struct@{}", fields.raw())),
-        RuntimeValue::Enum { variant_id, .. } => prefix_fn(&format!("enum::v{}", variant_id)),
-        RuntimeValue::Function(_) => prefix_fn("function"),
-        RuntimeValue::Arc(inner) => prefix_fn(&format!("arc({})", inner)),
-        RuntimeValue::Weak(_) => prefix_fn("weak(...)"),
-        RuntimeValue::Async(_) => prefix_fn("async"),
-        RuntimeValue::Ptr { kind, address, .. } => {
-            prefix_fn(&format!("ptr({:?}, {:#x})", kind, address))
-        }
-        RuntimeValue::OpaqueHandle { type_name, .. } => {
-            prefix_fn(&format!("opaque<{}>", type_name))
-        }
-    }
+    Ok(RuntimeValue::String(result.into()))
 }
 
 /// Native implementation: format_fallback
-/// Formats a value using the fallback type info (used when type doesn't implement Stringable)
+/// Formats a value whose type doesn't implement Stringable, prefixed with its type name.
+/// Structs already carry their name (`Point(x: 1.0)`), so they are not prefixed again.
 fn native_format_fallback(
     args: &[RuntimeValue],
     ctx: &mut NativeContext<'_>,
@@ -286,12 +188,16 @@ fn native_format_fallback(
     let type_name = args
         .get(1)
         .and_then(|v| match v {
-            RuntimeValue::String(s) => Some(s.as_ref()),
+            RuntimeValue::String(s) => Some(s.to_string()),
             _ => None,
         })
-        .unwrap_or("unknown");
+        .unwrap_or_else(|| "unknown".to_string());
 
-    let formatted = format_value_with_prefix(&args[0], ctx.heap, type_name);
+    let shown = show_display(&args[0], ctx)?;
+    let formatted = match &args[0] {
+        RuntimeValue::String(_) | RuntimeValue::Struct { .. } => shown,
+        _ => format!("{}({})", type_name, shown),
+    };
 
     Ok(RuntimeValue::String(formatted.into()))
 }
@@ -338,7 +244,12 @@ fn native_read_file(
             )));
         }
     };
-    match std::fs::read_to_string(&path) {
+    // File I/O cannot be polled: run it off the worker so only this task waits
+    let read = {
+        let path = path.clone();
+        blocking(move || std::fs::read_to_string(path)).and_then(|read| read)
+    };
+    match read {
         Ok(content) => Ok(RuntimeValue::String(content.into())),
         Err(e) => Err(ExecutorError::runtime_only(format!(
             "Failed to read file '{}': {}",
@@ -376,7 +287,11 @@ fn native_write_file(
             )));
         }
     };
-    match std::fs::write(&path, &content) {
+    let written = {
+        let path = path.clone();
+        blocking(move || std::fs::write(path, content)).and_then(|written| written)
+    };
+    match written {
         Ok(()) => Ok(RuntimeValue::Bool(true)),
         Err(e) => Err(ExecutorError::runtime_only(format!(
             "Failed to write file '{}': {}",
@@ -416,21 +331,18 @@ fn native_append_file(
             )));
         }
     };
-    match std::fs::OpenOptions::new()
-        .append(true)
-        .create(true)
-        .open(&path)
-    {
-        Ok(mut file) => match file.write_all(content.as_bytes()) {
-            Ok(()) => Ok(RuntimeValue::Bool(true)),
-            Err(e) => Err(ExecutorError::runtime_only(format!(
-                "Failed to append to file '{}': {}",
-                path, e
-            ))),
-        },
-        Err(e) => Err(ExecutorError::runtime_only(format!(
-            "Failed to open file '{}' for appending: {}",
-            path, e
-        ))),
-    }
+    let target = path.clone();
+    blocking(move || {
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .create(true)
+            .open(&target)
+            .map_err(|e| format!("Failed to open file '{}' for appending: {}", target, e))?;
+        file.write_all(content.as_bytes())
+            .map_err(|e| format!("Failed to append to file '{}': {}", target, e))
+    })
+    .map_err(|e| format!("Failed to append to file '{}': {}", path, e))
+    .and_then(|appended| appended)
+    .map(|()| RuntimeValue::Bool(true))
+    .map_err(ExecutorError::runtime_only)
 }
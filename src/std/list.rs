@@ -2,7 +2,7 @@
 //!
 //! This module provides list manipulation functions for YaoXiang programs.
 
-use crate::backends::common::{RuntimeValue, HeapValue};
+use crate::backends::common::{RuntimeValue, HeapValue, Value};
 use crate::backends::ExecutorError;
 use crate::std::{NativeContext, NativeExport, StdModule, NativeHandler};
 
@@ -194,8 +194,8 @@ fn native_push(
             ))
         }
     };
-    items.push(item);
-    let new_handle = ctx.heap.allocate(HeapValue::List(items));
+    items.push(item.into());
+    let new_handle = ctx.heap.try_allocate(HeapValue::List(items))?;
     Ok(RuntimeValue::List(new_handle))
 }
 
@@ -226,7 +226,7 @@ fn native_pop(
         Some(val) => {
             // Update the list in-place (write back without the last element)
             let _ = ctx.heap.write(list_handle, HeapValue::List(items));
-            Ok(val)
+            Ok(val.into_inner())
         }
         None => Ok(RuntimeValue::Unit),
     }
@@ -263,8 +263,8 @@ fn native_prepend(
             ))
         }
     };
-    items.insert(0, item);
-    let new_handle = ctx.heap.allocate(HeapValue::List(items));
+    items.insert(0, item.into());
+    let new_handle = ctx.heap.try_allocate(HeapValue::List(items))?;
     Ok(RuntimeValue::List(new_handle))
 }
 
@@ -295,7 +295,7 @@ fn native_remove_at(
     if index < items.len() {
         let removed = items.remove(index);
         let _ = ctx.heap.write(list_handle, HeapValue::List(items));
-        Ok(removed)
+        Ok(removed.into_inner())
     } else {
         Err(ExecutorError::runtime_only(format!(
             "Index {} out of bounds for list of length {}",
@@ -328,7 +328,7 @@ fn native_reverse(
         }
     };
     items.reverse();
-    let new_handle = ctx.heap.allocate(HeapValue::List(items));
+    let new_handle = ctx.heap.try_allocate(HeapValue::List(items))?;
     Ok(RuntimeValue::List(new_handle))
 }
 
@@ -373,7 +373,7 @@ fn native_concat(
 
     let mut merged = items_a;
     merged.extend(items_b);
-    let new_handle = ctx.heap.allocate(HeapValue::List(merged));
+    let new_handle = ctx.heap.try_allocate(HeapValue::List(merged))?;
     Ok(RuntimeValue::List(new_handle))
 }
 
@@ -405,11 +405,11 @@ fn native_map(
 
     let mut result_items = Vec::with_capacity(items.len());
     for item in items {
-        let mapped = ctx.call_function(&func_value, &[item])?;
-        result_items.push(mapped);
+        let mapped = ctx.call_function(&func_value, &[item.into_inner()])?;
+        result_items.push(mapped.into());
     }
 
-    let new_handle = ctx.heap.allocate(HeapValue::List(result_items));
+    let new_handle = ctx.heap.try_allocate(HeapValue::List(result_items))?;
     Ok(RuntimeValue::List(new_handle))
 }
 
@@ -441,13 +441,13 @@ fn native_filter(
 
     let mut result_items = Vec::new();
     for item in items {
-        let result = ctx.call_function(&func_value, std::slice::from_ref(&item))?;
+        let result = ctx.call_function(&func_value, &[item.get()])?;
         if result.to_bool().unwrap_or(false) {
             result_items.push(item);
         }
     }
 
-    let new_handle = ctx.heap.allocate(HeapValue::List(result_items));
+    let new_handle = ctx.heap.try_allocate(HeapValue::List(result_items))?;
     Ok(RuntimeValue::List(new_handle))
 }
 
@@ -479,7 +479,7 @@ fn native_reduce(
     };
 
     for item in items {
-        accumulator = ctx.call_function(&func_value, &[accumulator, item])?;
+        accumulator = ctx.call_function(&func_value, &[accumulator, item.into_inner()])?;
     }
 
     Ok(accumulator)
@@ -533,7 +533,7 @@ fn native_get(
     let index = args.get(1).and_then(|v| v.to_int()).unwrap_or(0) as usize;
 
     match ctx.heap.get(list_handle) {
-        Some(HeapValue::List(items)) => Ok(items.get(index).cloned().unwrap_or(RuntimeValue::Unit)),
+        Some(HeapValue::List(items)) => Ok(items.get(index).map_or(RuntimeValue::Unit, Value::get)),
         _ => Ok(RuntimeValue::Unit),
     }
 }
@@ -564,9 +564,9 @@ fn native_set(
     };
 
     if index < items.len() {
-        items[index] = value;
+        items[index] = value.into();
     }
-    let new_handle = ctx.heap.allocate(HeapValue::List(items));
+    let new_handle = ctx.heap.try_allocate(HeapValue::List(items))?;
     Ok(RuntimeValue::List(new_handle))
 }
 
@@ -585,7 +585,7 @@ fn native_first(
     };
 
     match ctx.heap.get(list_handle) {
-        Some(HeapValue::List(items)) => Ok(items.first().cloned().unwrap_or(RuntimeValue::Unit)),
+        Some(HeapValue::List(items)) => Ok(items.first().map_or(RuntimeValue::Unit, Value::get)),
         _ => Ok(RuntimeValue::Unit),
     }
 }
@@ -605,7 +605,7 @@ fn native_last(
     };
 
     match ctx.heap.get(list_handle) {
-        Some(HeapValue::List(items)) => Ok(items.last().cloned().unwrap_or(RuntimeValue::Unit)),
+        Some(HeapValue::List(items)) => Ok(items.last().map_or(RuntimeValue::Unit, Value::get)),
         _ => Ok(RuntimeValue::Unit),
     }
 }
@@ -638,7 +638,7 @@ fn native_slice(
     let end = end.min(items.len());
     let start = start.min(end);
     let sliced = items[start..end].to_vec();
-    let new_handle = ctx.heap.allocate(HeapValue::List(sliced));
+    let new_handle = ctx.heap.try_allocate(HeapValue::List(sliced))?;
     Ok(RuntimeValue::List(new_handle))
 }
 
@@ -654,7 +654,9 @@ fn native_contains(
     let target = args.get(1).cloned().unwrap_or(RuntimeValue::Unit);
 
     match ctx.heap.get(list_handle) {
-        Some(HeapValue::List(items)) => Ok(RuntimeValue::Bool(items.contains(&target))),
+        Some(HeapValue::List(items)) => {
+            Ok(RuntimeValue::Bool(items.iter().any(|item| *item == target)))
+        }
         _ => Ok(RuntimeValue::Bool(false)),
     }
 }
@@ -671,7 +673,7 @@ fn native_find_index(
     let target = args.get(1).cloned().unwrap_or(RuntimeValue::Unit);
 
     match ctx.heap.get(list_handle) {
-        Some(HeapValue::List(items)) => match items.iter().position(|item| item == &target) {
+        Some(HeapValue::List(items)) => match items.iter().position(|item| *item == target) {
             Some(idx) => Ok(RuntimeValue::Int(idx as i64)),
             None => Ok(RuntimeValue::Int(-1)),
         },
@@ -700,8 +702,8 @@ fn native_iter(
     };
 
     // 创建一个 Tuple 存储迭代器状态 (原始列表, 索引 0)
-    let iterator_items = vec![RuntimeValue::List(list_handle), RuntimeValue::Int(0)];
-    let iterator_handle = ctx.heap.allocate(HeapValue::Tuple(iterator_items));
+    let iterator_items = vec![Value::from(RuntimeValue::List(list_handle)), Value::int(0)];
+    let iterator_handle = ctx.heap.try_allocate(HeapValue::Tuple(iterator_items))?;
     Ok(RuntimeValue::Tuple(iterator_handle))
 }
 
@@ -723,25 +725,25 @@ fn native_next(
     };
 
     // 获取原始列表和当前索引
-    let list_handle = match iterator_items.first() {
-        Some(RuntimeValue::List(h)) => *h,
+    let list_handle = match iterator_items.first().map(Value::get) {
+        Some(RuntimeValue::List(h)) => h,
         _ => return Ok(RuntimeValue::Unit),
     };
-    let current_idx = match iterator_items.get(1) {
-        Some(RuntimeValue::Int(idx)) => *idx as usize,
+    let current_idx = match iterator_items.get(1).and_then(Value::as_int) {
+        Some(idx) => idx as usize,
         _ => return Ok(RuntimeValue::Unit),
     };
 
     // 获取元素
     let element = match ctx.heap.get(list_handle) {
-        Some(HeapValue::List(items)) if current_idx < items.len() => items[current_idx].clone(),
+        Some(HeapValue::List(items)) if current_idx < items.len() => items[current_idx].get(),
         _ => RuntimeValue::Unit,
     };
 
     // 更新索引
     let new_idx = current_idx + 1;
     let mut new_iterator_items = iterator_items;
-    new_iterator_items[1] = RuntimeValue::Int(new_idx as i64);
+    new_iterator_items[1] = Value::int(new_idx as i64);
     let _ = ctx
         .heap
         .write(iter_handle, HeapValue::Tuple(new_iterator_items));
@@ -765,12 +767,12 @@ fn native_has_next(
     };
 
     // 获取原始列表和当前索引
-    let list_handle = match iterator_items.first() {
-        Some(RuntimeValue::List(h)) => *h,
+    let list_handle = match iterator_items.first().map(Value::get) {
+        Some(RuntimeValue::List(h)) => h,
         _ => return Ok(RuntimeValue::Bool(false)),
     };
-    let current_idx = match iterator_items.get(1) {
-        Some(RuntimeValue::Int(idx)) => *idx as usize,
+    let current_idx = match iterator_items.get(1).and_then(Value::as_int) {
+        Some(idx) => idx as usize,
         _ => return Ok(RuntimeValue::Bool(false)),
     };
 
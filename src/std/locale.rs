@@ -0,0 +1,236 @@
+//! Standard Locale library (YaoXiang)
+//!
+//! This module provides locale-aware number formatting for YaoXiang programs.
+//! `print` and `std.string.format` never consult the process locale; programs
+//! that want localized output ask for it explicitly here.
+
+use crate::backends::common::RuntimeValue;
+use crate::backends::ExecutorError;
+use crate::std::string::{format_float_default, group_digits};
+use crate::std::{NativeContext, NativeExport, StdModule};
+
+// ============================================================================
+// LocaleModule - StdModule Implementation
+// ============================================================================
+
+/// Locale module implementation.
+pub struct LocaleModule;
+
+impl Default for LocaleModule {
+    fn default() -> Self {
+        Self
+    }
+}
+
+impl StdModule for LocaleModule {
+    fn module_path(&self) -> &str {
+        "std.locale"
+    }
+
+    fn exports(&self) -> Vec<NativeExport> {
+        vec![
+            NativeExport::new(
+                "current",
+                "std.locale.current",
+                "() -> String",
+                native_current,
+            ),
+            NativeExport::new(
+                "format_int",
+                "std.locale.format_int",
+                "(value: Int, locale: String) -> String",
+                native_format_int,
+            ),
+            NativeExport::new(
+                "format_float",
+                "std.locale.format_float",
+                "(value: Float, precision: Int, locale: String) -> String",
+                native_format_float,
+            ),
+            NativeExport::new(
+                "decimal_separator",
+                "std.locale.decimal_separator",
+                "(locale: String) -> String",
+                native_decimal_separator,
+            ),
+            NativeExport::new(
+                "group_separator",
+                "std.locale.group_separator",
+                "(locale: String) -> String",
+                native_group_separator,
+            ),
+        ]
+    }
+}
+
+/// Singleton instance for std.locale module.
+pub const LOCALE_MODULE: LocaleModule = LocaleModule;
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// Number separators of a locale
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct NumberSymbols {
+    pub decimal: &'static str,
+    pub group: &'static str,
+}
+
+/// Separators for a BCP 47 style tag (`de-DE`, `fr`, `en_US.UTF-8`).
+///
+/// `C`/`POSIX` use no grouping; unknown languages fall back to English.
+pub(crate) fn number_symbols(locale: &str) -> NumberSymbols {
+    let tag = normalize_tag(locale);
+    let (language, region) = tag.split_once('-').unwrap_or((tag.as_str(), ""));
+    let (group, decimal) = match (language, region) {
+        ("C" | "POSIX", _) => ("", "."),
+        ("de", "CH") | ("it", "CH") => ("\u{2019}", "."),
+        ("es", "MX") | ("pt", "MO") => (",", "."),
+        ("fr", _) => ("\u{202F}", ","),
+        (
+            "ru" | "uk" | "pl" | "cs" | "sk" | "sv" | "fi" | "nb" | "no" | "hu" | "bg" | "et"
+            | "lt" | "lv",
+            _,
+        ) => ("\u{A0}", ","),
+        ("de" | "es" | "it" | "nl" | "pt" | "id" | "tr" | "da" | "el" | "ro" | "hr" | "sl", _) => {
+            (".", ",")
+        }
+        _ => (",", "."),
+    };
+    NumberSymbols { decimal, group }
+}
+
+/// `en_US.UTF-8` / `en-us` -> `en-US`; empty, `C.UTF-8` and `POSIX` -> `C`/`POSIX`
+fn normalize_tag(locale: &str) -> String {
+    let base = locale.split(['.', '@']).next().unwrap_or("").trim();
+    if base.is_empty() || base == "C" {
+        return "C".to_string();
+    }
+    if base == "POSIX" {
+        return base.to_string();
+    }
+    let mut parts = base.split(['_', '-']);
+    let language = parts.next().unwrap_or("").to_ascii_lowercase();
+    match parts.next() {
+        Some(region) if !region.is_empty() => {
+            format!("{}-{}", language, region.to_ascii_uppercase())
+        }
+        _ => language,
+    }
+}
+
+/// Locale of the process environment (`LC_ALL` > `LC_NUMERIC` > `LANG`)
+fn environment_locale() -> String {
+    ["LC_ALL", "LC_NUMERIC", "LANG"]
+        .iter()
+        .filter_map(|key| std::env::var(key).ok())
+        .find(|value| !value.is_empty())
+        .map(|value| normalize_tag(&value))
+        .unwrap_or_else(|| "C".to_string())
+}
+
+/// Localize plain `[-]digits[.digits]` text
+pub(crate) fn localize_number(
+    text: &str,
+    symbols: NumberSymbols,
+) -> String {
+    let (sign, rest) = match text.strip_prefix('-') {
+        Some(rest) => ("-", rest),
+        None => ("", text),
+    };
+    if !rest.starts_with(|c: char| c.is_ascii_digit()) {
+        // inf / NaN
+        return text.to_string();
+    }
+    let (int_part, frac_part) = rest.split_once('.').unwrap_or((rest, ""));
+    let mut out = format!("{}{}", sign, group_digits(int_part, symbols.group));
+    if !frac_part.is_empty() {
+        out.push_str(symbols.decimal);
+        out.push_str(frac_part);
+    }
+    out
+}
+
+fn string_arg(
+    args: &[RuntimeValue],
+    index: usize,
+) -> String {
+    match args.get(index) {
+        Some(RuntimeValue::String(s)) => s.to_string(),
+        _ => String::new(),
+    }
+}
+
+// ============================================================================
+// Native Implementations
+// ============================================================================
+
+/// Native implementation: current - locale tag of the process environment
+fn native_current(
+    _args: &[RuntimeValue],
+    _ctx: &mut NativeContext<'_>,
+) -> Result<RuntimeValue, ExecutorError> {
+    Ok(RuntimeValue::String(environment_locale().into()))
+}
+
+/// Native implementation: format_int - integer with locale group separators
+fn native_format_int(
+    args: &[RuntimeValue],
+    _ctx: &mut NativeContext<'_>,
+) -> Result<RuntimeValue, ExecutorError> {
+    let value = match args.first() {
+        Some(RuntimeValue::Int(n)) => *n,
+        _ => {
+            return Err(ExecutorError::type_only(
+                "format_int expects an Int value".to_string(),
+            ))
+        }
+    };
+    let symbols = number_symbols(&string_arg(args, 1));
+    Ok(RuntimeValue::String(
+        localize_number(&value.to_string(), symbols).into(),
+    ))
+}
+
+/// Native implementation: format_float - float with fixed precision and locale separators
+///
+/// A negative precision keeps the default (shortest) representation.
+fn native_format_float(
+    args: &[RuntimeValue],
+    _ctx: &mut NativeContext<'_>,
+) -> Result<RuntimeValue, ExecutorError> {
+    let value = match args.first() {
+        Some(RuntimeValue::Float(f)) => *f,
+        Some(RuntimeValue::Int(n)) => *n as f64,
+        _ => {
+            return Err(ExecutorError::type_only(
+                "format_float expects a Float value".to_string(),
+            ))
+        }
+    };
+    let text = match args.get(1) {
+        Some(RuntimeValue::Int(p)) if *p >= 0 => format!("{:.*}", *p as usize, value),
+        _ => format_float_default(value),
+    };
+    let symbols = number_symbols(&string_arg(args, 2));
+    Ok(RuntimeValue::String(localize_number(&text, symbols).into()))
+}
+
+/// Native implementation: decimal_separator
+fn native_decimal_separator(
+    args: &[RuntimeValue],
+    _ctx: &mut NativeContext<'_>,
+) -> Result<RuntimeValue, ExecutorError> {
+    let symbols = number_symbols(&string_arg(args, 0));
+    Ok(RuntimeValue::String(symbols.decimal.into()))
+}
+
+/// Native implementation: group_separator
+fn native_group_separator(
+    args: &[RuntimeValue],
+    _ctx: &mut NativeContext<'_>,
+) -> Result<RuntimeValue, ExecutorError> {
+    let symbols = number_symbols(&string_arg(args, 0));
+    Ok(RuntimeValue::String(symbols.group.into()))
+}
@@ -51,6 +51,43 @@ impl StdModule for MathModule {
                 "(value: Int, min: Int, max: Int) -> Int",
                 native_clamp as NativeHandler,
             ),
+            // Overflow-aware arithmetic
+            NativeExport::new(
+                "checked_add",
+                "std.math.checked_add",
+                "(a: Int, b: Int) -> Option(Int)",
+                native_checked_add as NativeHandler,
+            ),
+            NativeExport::new(
+                "checked_sub",
+                "std.math.checked_sub",
+                "(a: Int, b: Int) -> Option(Int)",
+                native_checked_sub as NativeHandler,
+            ),
+            NativeExport::new(
+                "checked_mul",
+                "std.math.checked_mul",
+                "(a: Int, b: Int) -> Option(Int)",
+                native_checked_mul as NativeHandler,
+            ),
+            NativeExport::new(
+                "wrapping_add",
+                "std.math.wrapping_add",
+                "(a: Int, b: Int) -> Int",
+                native_wrapping_add as NativeHandler,
+            ),
+            NativeExport::new(
+                "wrapping_sub",
+                "std.math.wrapping_sub",
+                "(a: Int, b: Int) -> Int",
+                native_wrapping_sub as NativeHandler,
+            ),
+            NativeExport::new(
+                "wrapping_mul",
+                "std.math.wrapping_mul",
+                "(a: Int, b: Int) -> Int",
+                native_wrapping_mul as NativeHandler,
+            ),
             // Float functions
             NativeExport::new(
                 "fabs",
@@ -174,6 +211,72 @@ fn native_clamp(
     Ok(RuntimeValue::Int(value.clamp(min, max)))
 }
 
+/// 取两个整数参数（缺省为 0）
+fn int_pair(args: &[RuntimeValue]) -> (i64, i64) {
+    let a = args.first().and_then(|v| v.to_int()).unwrap_or(0);
+    let b = args.get(1).and_then(|v| v.to_int()).unwrap_or(0);
+    (a, b)
+}
+
+/// 溢出时返回 void，否则返回结果
+fn checked_result(result: Option<i64>) -> Result<RuntimeValue, ExecutorError> {
+    Ok(result.map_or(RuntimeValue::Unit, RuntimeValue::Int))
+}
+
+/// Native implementation: checked_add (void on overflow)
+fn native_checked_add(
+    args: &[RuntimeValue],
+    _ctx: &mut NativeContext<'_>,
+) -> Result<RuntimeValue, ExecutorError> {
+    let (a, b) = int_pair(args);
+    checked_result(a.checked_add(b))
+}
+
+/// Native implementation: checked_sub (void on overflow)
+fn native_checked_sub(
+    args: &[RuntimeValue],
+    _ctx: &mut NativeContext<'_>,
+) -> Result<RuntimeValue, ExecutorError> {
+    let (a, b) = int_pair(args);
+    checked_result(a.checked_sub(b))
+}
+
+/// Native implementation: checked_mul (void on overflow)
+fn native_checked_mul(
+    args: &[RuntimeValue],
+    _ctx: &mut NativeContext<'_>,
+) -> Result<RuntimeValue, ExecutorError> {
+    let (a, b) = int_pair(args);
+    checked_result(a.checked_mul(b))
+}
+
+/// Native implementation: wrapping_add (two's complement wrap-around)
+fn native_wrapping_add(
+    args: &[RuntimeValue],
+    _ctx: &mut NativeContext<'_>,
+) -> Result<RuntimeValue, ExecutorError> {
+    let (a, b) = int_pair(args);
+    Ok(RuntimeValue::Int(a.wrapping_add(b)))
+}
+
+/// Native implementation: wrapping_sub (two's complement wrap-around)
+fn native_wrapping_sub(
+    args: &[RuntimeValue],
+    _ctx: &mut NativeContext<'_>,
+) -> Result<RuntimeValue, ExecutorError> {
+    let (a, b) = int_pair(args);
+    Ok(RuntimeValue::Int(a.wrapping_sub(b)))
+}
+
+/// Native implementation: wrapping_mul (two's complement wrap-around)
+fn native_wrapping_mul(
+    args: &[RuntimeValue],
+    _ctx: &mut NativeContext<'_>,
+) -> Result<RuntimeValue, ExecutorError> {
+    let (a, b) = int_pair(args);
+    Ok(RuntimeValue::Int(a.wrapping_mul(b)))
+}
+
 /// Native implementation: fabs (float absolute value)
 fn native_fabs(
     args: &[RuntimeValue],
@@ -2,26 +2,43 @@
 //!
 //! This module contains built-in functions and types.
 
+pub mod assert;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod chan;
 #[cfg(not(target_arch = "wasm32"))]
 pub mod concurrent;
 pub mod convert;
 pub mod dict;
+pub mod env;
+pub mod gc;
 pub mod gen_interfaces;
 pub mod io;
 pub mod list;
+pub mod locale;
 pub mod math;
 #[cfg(not(target_arch = "wasm32"))]
+pub mod module;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod net;
 #[cfg(not(target_arch = "wasm32"))]
 pub mod os;
+pub mod process;
+pub mod reflect;
 pub mod result;
+pub mod show;
+pub mod signal;
 pub mod string;
 pub mod time;
 #[cfg(not(target_arch = "wasm32"))]
 pub mod weak;
 
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
 use crate::backends::interpreter::ffi::FfiRegistry;
-use crate::backends::common::{RuntimeValue, Heap, HeapValue};
+use crate::backends::common::{RuntimeValue, Heap, HeapValue, StructTypes};
 use crate::backends::ExecutorError;
 use crate::frontend::module::{Export, ExportKind, ModuleInfo, ModuleSource};
 
@@ -29,6 +46,46 @@ use crate::frontend::module::{Export, ExportKind, ModuleInfo, ModuleSource};
 /// Simplifies complex type definitions
 type CallFn = dyn FnMut(&RuntimeValue, &[RuntimeValue]) -> Result<RuntimeValue, ExecutorError>;
 
+/// Type alias for the module import callback (path -> namespace value)
+type ImportFn = dyn FnMut(&str) -> Result<RuntimeValue, ExecutorError>;
+
+/// Type alias for the collection callback (-> number of objects freed)
+type CollectFn = dyn FnMut() -> usize;
+
+/// Per-VM state of native modules, such as open database connections.
+///
+/// A VM and the task interpreters it spawns share one; the state is dropped
+/// with the VM, so a module can release whatever the program left open.
+#[derive(Default)]
+pub struct NativeResources {
+    slots: Mutex<HashMap<TypeId, Arc<dyn Any + Send + Sync>>>,
+}
+
+impl NativeResources {
+    /// This VM's `T`, created on first use.
+    pub fn get<T: Default + Send + Sync + 'static>(&self) -> Arc<T> {
+        let mut slots = self.slots.lock().unwrap_or_else(|e| e.into_inner());
+        let slot = slots
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| Arc::new(T::default()));
+        Arc::clone(slot)
+            .downcast::<T>()
+            .unwrap_or_else(|_| unreachable!("resource slots are keyed by their type"))
+    }
+}
+
+impl std::fmt::Debug for NativeResources {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        let slots = self.slots.lock().unwrap_or_else(|e| e.into_inner());
+        f.debug_struct("NativeResources")
+            .field("slots", &slots.len())
+            .finish()
+    }
+}
+
 /// Execution context passed to native functions.
 ///
 /// This gives native functions access to the heap (for allocating/reading
@@ -41,6 +98,19 @@ pub struct NativeContext<'a> {
     /// The closure takes (function_value, args) and returns a RuntimeValue.
     /// Use `call_function()` instead of accessing this directly.
     call_fn: Option<&'a mut CallFn>,
+    /// Callback to compile and load another module into the running VM.
+    /// Use `import_module()` instead of accessing this directly.
+    import_fn: Option<&'a mut ImportFn>,
+    /// Callback to free unreachable heap objects with the VM's roots.
+    /// Use `collect_garbage()` instead of accessing this directly.
+    collect_fn: Option<&'a mut CollectFn>,
+    /// Struct layouts of the running program (type and field names), if available.
+    pub struct_types: Option<&'a StructTypes>,
+    /// Where `print` writes; the process's standard output when `None`.
+    pub stdout: Option<&'a Mutex<dyn Write + Send>>,
+    /// State native modules keep for the running VM.
+    /// Use `resource()` instead of accessing this directly.
+    resources: Option<&'a NativeResources>,
 }
 
 impl<'a> NativeContext<'a> {
@@ -49,6 +119,11 @@ impl<'a> NativeContext<'a> {
         Self {
             heap,
             call_fn: None,
+            import_fn: None,
+            collect_fn: None,
+            struct_types: None,
+            stdout: None,
+            resources: None,
         }
     }
 
@@ -60,6 +135,82 @@ impl<'a> NativeContext<'a> {
         Self {
             heap,
             call_fn: Some(call_fn),
+            import_fn: None,
+            collect_fn: None,
+            struct_types: None,
+            stdout: None,
+            resources: None,
+        }
+    }
+
+    /// Attach a module import callback to this context.
+    pub fn with_import_fn(
+        mut self,
+        import_fn: &'a mut ImportFn,
+    ) -> Self {
+        self.import_fn = Some(import_fn);
+        self
+    }
+
+    /// Attach a collection callback to this context.
+    pub fn with_collect_fn(
+        mut self,
+        collect_fn: &'a mut CollectFn,
+    ) -> Self {
+        self.collect_fn = Some(collect_fn);
+        self
+    }
+
+    /// Attach the struct type registry so values can be printed with field names.
+    pub fn with_struct_types(
+        mut self,
+        struct_types: &'a StructTypes,
+    ) -> Self {
+        self.struct_types = Some(struct_types);
+        self
+    }
+
+    /// Redirect program output (`print`) to `stdout`.
+    pub fn with_stdout(
+        mut self,
+        stdout: Option<&'a Mutex<dyn Write + Send>>,
+    ) -> Self {
+        self.stdout = stdout;
+        self
+    }
+
+    /// Attach the running VM's native module state.
+    pub fn with_resources(
+        mut self,
+        resources: &'a NativeResources,
+    ) -> Self {
+        self.resources = Some(resources);
+        self
+    }
+
+    /// The running VM's `T` (see [`NativeResources::get`]).
+    ///
+    /// Returns an error if this context is not attached to a VM.
+    pub fn resource<T: Default + Send + Sync + 'static>(&self) -> Result<Arc<T>, ExecutorError> {
+        self.resources.map(NativeResources::get).ok_or_else(|| {
+            ExecutorError::runtime_only(
+                "No VM state is available in this native context".to_string(),
+            )
+        })
+    }
+
+    /// Write program output to the redirect, or to the process's standard output.
+    pub fn write_stdout(
+        &self,
+        text: &str,
+    ) {
+        match self.stdout {
+            Some(stdout) => {
+                let mut stdout = stdout.lock().unwrap_or_else(|e| e.into_inner());
+                let _ = stdout.write_all(text.as_bytes());
+                let _ = stdout.flush();
+            }
+            None => print!("{}", text),
         }
     }
 
@@ -79,6 +230,36 @@ impl<'a> NativeContext<'a> {
             ))
         }
     }
+
+    /// Compile and load the module at `path`, returning its public namespace.
+    ///
+    /// Returns an error if no import callback is available.
+    pub fn import_module(
+        &mut self,
+        path: &str,
+    ) -> Result<RuntimeValue, ExecutorError> {
+        if let Some(ref mut callback) = self.import_fn {
+            callback(path)
+        } else {
+            Err(ExecutorError::runtime_only(
+                "Cannot import modules from this native context".to_string(),
+            ))
+        }
+    }
+
+    /// Free the heap objects the running program can no longer reach,
+    /// returning how many were freed.
+    ///
+    /// Returns an error if no collection callback is available.
+    pub fn collect_garbage(&mut self) -> Result<usize, ExecutorError> {
+        if let Some(ref mut callback) = self.collect_fn {
+            Ok(callback())
+        } else {
+            Err(ExecutorError::runtime_only(
+                "Cannot collect garbage from this native context".to_string(),
+            ))
+        }
+    }
 }
 
 /// Type alias for native function handlers.
@@ -233,7 +414,7 @@ fn builtin_dict_keys(
             ));
         }
     };
-    let list_handle = ctx.heap.allocate(HeapValue::List(keys));
+    let list_handle = ctx.heap.try_allocate(HeapValue::list(keys))?;
     Ok(RuntimeValue::List(list_handle))
 }
 
@@ -242,15 +423,26 @@ fn builtin_dict_keys(
 /// This is the single entry point that ffi.rs should call.
 /// New std modules only need to be added to this function.
 pub fn register_all(registry: &mut FfiRegistry) {
+    assert::AssertModule.register_ffi(registry);
+    #[cfg(not(target_arch = "wasm32"))]
+    chan::ChanModule.register_ffi(registry);
     #[cfg(not(target_arch = "wasm32"))]
     concurrent::ConcurrentModule.register_ffi(registry);
     convert::ConvertModule.register_ffi(registry);
+    env::EnvModule.register_ffi(registry);
+    gc::GcModule.register_ffi(registry);
     io::IoModule.register_ffi(registry);
     list::ListModule.register_ffi(registry);
+    locale::LocaleModule.register_ffi(registry);
     math::MathModule.register_ffi(registry);
     #[cfg(not(target_arch = "wasm32"))]
+    module::ModuleModule.register_ffi(registry);
+    #[cfg(not(target_arch = "wasm32"))]
     net::NetModule.register_ffi(registry);
+    process::ProcessModule.register_ffi(registry);
+    reflect::ReflectModule.register_ffi(registry);
     result::RESULT_MODULE.register_ffi(registry);
+    signal::SignalModule.register_ffi(registry);
     string::StringModule.register_ffi(registry);
     time::TimeModule.register_ffi(registry);
     #[cfg(not(target_arch = "wasm32"))]
@@ -278,14 +470,25 @@ pub fn register_all(registry: &mut FfiRegistry) {
 /// This is used by the frontend module system.
 pub fn all_module_infos() -> Vec<ModuleInfo> {
     vec![
+        assert::AssertModule.to_module_info(),
+        #[cfg(not(target_arch = "wasm32"))]
+        chan::ChanModule.to_module_info(),
         #[cfg(not(target_arch = "wasm32"))]
         concurrent::ConcurrentModule.to_module_info(),
         dict::DictModule.to_module_info(),
+        env::EnvModule.to_module_info(),
+        gc::GcModule.to_module_info(),
         io::IoModule.to_module_info(),
         list::ListModule.to_module_info(),
+        locale::LocaleModule.to_module_info(),
         math::MathModule.to_module_info(),
         #[cfg(not(target_arch = "wasm32"))]
+        module::ModuleModule.to_module_info(),
+        #[cfg(not(target_arch = "wasm32"))]
         net::NetModule.to_module_info(),
+        process::ProcessModule.to_module_info(),
+        reflect::ReflectModule.to_module_info(),
+        signal::SignalModule.to_module_info(),
         string::StringModule.to_module_info(),
         result::ResultModule.to_module_info(),
         time::TimeModule.to_module_info(),
@@ -0,0 +1,69 @@
+//! Standard Module library (YaoXiang)
+//!
+//! This module provides runtime module loading for YaoXiang programs,
+//! e.g. plugin architectures that discover their modules at run time.
+
+use crate::backends::common::RuntimeValue;
+use crate::backends::ExecutorError;
+use crate::std::{NativeContext, NativeExport, StdModule};
+
+// ============================================================================
+// ModuleModule - StdModule Implementation
+// ============================================================================
+
+/// Module module implementation.
+pub struct ModuleModule;
+
+impl Default for ModuleModule {
+    fn default() -> Self {
+        Self
+    }
+}
+
+impl StdModule for ModuleModule {
+    fn module_path(&self) -> &str {
+        "std.module"
+    }
+
+    fn exports(&self) -> Vec<NativeExport> {
+        vec![NativeExport::new(
+            "import",
+            "std.module.import",
+            "(path: String) -> Dict",
+            native_import,
+        )]
+    }
+}
+
+/// Singleton instance for std.module module.
+pub const MODULE_MODULE: ModuleModule = ModuleModule;
+
+// ============================================================================
+// Native Function Implementations
+// ============================================================================
+
+/// Native implementation: import
+///
+/// 编译并加载 `path` 指向的模块，返回由其公开函数组成的命名空间（名称 -> 函数）。
+/// 是否允许加载由解释器的能力策略决定。
+fn native_import(
+    args: &[RuntimeValue],
+    ctx: &mut NativeContext<'_>,
+) -> Result<RuntimeValue, ExecutorError> {
+    let path = match args.first() {
+        Some(RuntimeValue::String(s)) => s.to_string(),
+        Some(other) => {
+            return Err(ExecutorError::type_only(format!(
+                "import expects String path, got {:?}",
+                other.value_type(None)
+            )))
+        }
+        None => {
+            return Err(ExecutorError::runtime_only(
+                "import expects 1 argument (path: String)".to_string(),
+            ))
+        }
+    };
+
+    ctx.import_module(&path)
+}
@@ -1,8 +1,22 @@
 //! Standard Network library (YaoXiang)
 //!
 //! This module provides network-related functionality for YaoXiang programs.
+//!
+//! TCP sockets are opaque `Int` handles. They are non-blocking underneath and
+//! registered with the I/O [`Reactor`]: an `accept`, `read` or `write` that
+//! cannot make progress suspends only the calling task in the Full runtime, so
+//! one worker can serve many connections. Elsewhere the call blocks as usual.
+
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{Arc, LazyLock, Mutex};
+
+use mio::net::{TcpListener, TcpStream};
 
 use crate::backends::common::RuntimeValue;
+use crate::backends::runtime::reactor::{Direction, Reactor, Registration};
 use crate::backends::ExecutorError;
 use crate::std::{NativeContext, NativeExport, StdModule};
 
@@ -50,6 +64,48 @@ impl StdModule for NetModule {
                 "(s: String) -> String",
                 native_url_decode,
             ),
+            NativeExport::new(
+                "tcp_listen",
+                "std.net.tcp_listen",
+                "(addr: String) -> Int",
+                native_tcp_listen,
+            ),
+            NativeExport::new(
+                "tcp_accept",
+                "std.net.tcp_accept",
+                "(listener: Int) -> Int",
+                native_tcp_accept,
+            ),
+            NativeExport::new(
+                "tcp_connect",
+                "std.net.tcp_connect",
+                "(addr: String) -> Int",
+                native_tcp_connect,
+            ),
+            NativeExport::new(
+                "tcp_read",
+                "std.net.tcp_read",
+                "(conn: Int, max: Int) -> String",
+                native_tcp_read,
+            ),
+            NativeExport::new(
+                "tcp_write",
+                "std.net.tcp_write",
+                "(conn: Int, data: String) -> Int",
+                native_tcp_write,
+            ),
+            NativeExport::new(
+                "tcp_port",
+                "std.net.tcp_port",
+                "(sock: Int) -> Int",
+                native_tcp_port,
+            ),
+            NativeExport::new(
+                "tcp_close",
+                "std.net.tcp_close",
+                "(sock: Int) -> Void",
+                native_tcp_close,
+            ),
         ]
     }
 }
@@ -175,3 +231,234 @@ fn native_url_decode(
         ))),
     }
 }
+
+// ============================================================================
+// TCP Sockets
+// ============================================================================
+
+/// A socket and its reactor registration.
+enum Socket {
+    Listener(TcpListener, Registration),
+    Stream(TcpStream, Registration),
+}
+
+/// Global socket storage, shared by the interpreters of all tasks.
+static SOCKETS: LazyLock<Mutex<HashMap<i64, Arc<Socket>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Global counter for generating unique socket handles.
+static NEXT_SOCKET: AtomicI64 = AtomicI64::new(1);
+
+fn io_error(
+    func: &str,
+    e: std::io::Error,
+) -> ExecutorError {
+    ExecutorError::runtime_only(format!("{} failed: {}", func, e))
+}
+
+fn string_arg(
+    args: &[RuntimeValue],
+    index: usize,
+    func: &str,
+    expected: &str,
+) -> Result<String, ExecutorError> {
+    match args.get(index) {
+        Some(RuntimeValue::String(s)) => Ok(s.to_string()),
+        _ => Err(ExecutorError::type_only(format!(
+            "{} expects {}",
+            func, expected
+        ))),
+    }
+}
+
+fn insert_socket(socket: Socket) -> Result<RuntimeValue, ExecutorError> {
+    let handle = NEXT_SOCKET.fetch_add(1, Ordering::Relaxed);
+    SOCKETS
+        .lock()
+        .map_err(|_| ExecutorError::runtime_only("Failed to lock socket table".to_string()))?
+        .insert(handle, Arc::new(socket));
+    Ok(RuntimeValue::Int(handle))
+}
+
+/// Looks up the socket behind the first argument. The table lock is released
+/// before the caller waits on the socket.
+fn socket_arg(
+    args: &[RuntimeValue],
+    func: &str,
+) -> Result<Arc<Socket>, ExecutorError> {
+    let Some(RuntimeValue::Int(handle)) = args.first() else {
+        return Err(ExecutorError::type_only(format!(
+            "{} expects a socket (Int) as its first argument",
+            func
+        )));
+    };
+    SOCKETS
+        .lock()
+        .map_err(|_| ExecutorError::runtime_only("Failed to lock socket table".to_string()))?
+        .get(handle)
+        .cloned()
+        .ok_or_else(|| ExecutorError::runtime_only(format!("Invalid socket: {}", handle)))
+}
+
+fn resolve(
+    addr: &str,
+    func: &str,
+) -> Result<SocketAddr, ExecutorError> {
+    addr.to_socket_addrs()
+        .map_err(|e| io_error(func, e))?
+        .next()
+        .ok_or_else(|| ExecutorError::runtime_only(format!("{}: no address for '{}'", func, addr)))
+}
+
+/// Native implementation: tcp_listen
+fn native_tcp_listen(
+    args: &[RuntimeValue],
+    _ctx: &mut NativeContext<'_>,
+) -> Result<RuntimeValue, ExecutorError> {
+    let addr = string_arg(args, 0, "tcp_listen", "1 argument (addr: String)")?;
+    let addr = resolve(&addr, "tcp_listen")?;
+    let mut listener = TcpListener::bind(addr).map_err(|e| io_error("tcp_listen", e))?;
+    let registration = reactor()?
+        .register(&mut listener)
+        .map_err(|e| io_error("tcp_listen", e))?;
+    insert_socket(Socket::Listener(listener, registration))
+}
+
+/// Native implementation: tcp_accept
+fn native_tcp_accept(
+    args: &[RuntimeValue],
+    _ctx: &mut NativeContext<'_>,
+) -> Result<RuntimeValue, ExecutorError> {
+    let Socket::Listener(listener, registration) = &*socket_arg(args, "tcp_accept")? else {
+        return Err(ExecutorError::type_only(
+            "tcp_accept expects a listener".to_string(),
+        ));
+    };
+    let (mut stream, _) = registration
+        .io(Direction::Read, || listener.accept())
+        .map_err(|e| io_error("tcp_accept", e))?;
+    let registration = reactor()?
+        .register(&mut stream)
+        .map_err(|e| io_error("tcp_accept", e))?;
+    insert_socket(Socket::Stream(stream, registration))
+}
+
+/// Native implementation: tcp_connect
+fn native_tcp_connect(
+    args: &[RuntimeValue],
+    _ctx: &mut NativeContext<'_>,
+) -> Result<RuntimeValue, ExecutorError> {
+    let addr = string_arg(args, 0, "tcp_connect", "1 argument (addr: String)")?;
+    let addr = resolve(&addr, "tcp_connect")?;
+    let mut stream = TcpStream::connect(addr).map_err(|e| io_error("tcp_connect", e))?;
+    let registration = reactor()?
+        .register(&mut stream)
+        .map_err(|e| io_error("tcp_connect", e))?;
+    // A non-blocking connect finishes once the socket turns writable
+    registration
+        .io(Direction::Write, || match stream.take_error()? {
+            Some(e) => Err(e),
+            None => match stream.peer_addr() {
+                Err(e) if e.kind() == std::io::ErrorKind::NotConnected => {
+                    Err(std::io::ErrorKind::WouldBlock.into())
+                }
+                other => other,
+            },
+        })
+        .map_err(|e| io_error("tcp_connect", e))?;
+    insert_socket(Socket::Stream(stream, registration))
+}
+
+/// Native implementation: tcp_read
+///
+/// Returns at most `max` bytes, decoded as UTF-8 (lossily). An empty string
+/// means the peer closed the connection.
+fn native_tcp_read(
+    args: &[RuntimeValue],
+    _ctx: &mut NativeContext<'_>,
+) -> Result<RuntimeValue, ExecutorError> {
+    let Socket::Stream(stream, registration) = &*socket_arg(args, "tcp_read")? else {
+        return Err(ExecutorError::type_only(
+            "tcp_read expects a connection".to_string(),
+        ));
+    };
+    let max = match args.get(1) {
+        Some(RuntimeValue::Int(n)) if *n > 0 => *n as usize,
+        _ => {
+            return Err(ExecutorError::type_only(
+                "tcp_read expects 2 arguments (conn: Int, max: Int > 0)".to_string(),
+            ))
+        }
+    };
+    let mut buf = vec![0u8; max];
+    let n = registration
+        .io(Direction::Read, || (&*stream).read(&mut buf))
+        .map_err(|e| io_error("tcp_read", e))?;
+    buf.truncate(n);
+    Ok(RuntimeValue::String(
+        String::from_utf8_lossy(&buf).into_owned().into(),
+    ))
+}
+
+/// Native implementation: tcp_write
+fn native_tcp_write(
+    args: &[RuntimeValue],
+    _ctx: &mut NativeContext<'_>,
+) -> Result<RuntimeValue, ExecutorError> {
+    let Socket::Stream(stream, registration) = &*socket_arg(args, "tcp_write")? else {
+        return Err(ExecutorError::type_only(
+            "tcp_write expects a connection".to_string(),
+        ));
+    };
+    let data = string_arg(
+        args,
+        1,
+        "tcp_write",
+        "2 arguments (conn: Int, data: String)",
+    )?;
+    let mut written = 0;
+    while written < data.len() {
+        written += registration
+            .io(Direction::Write, || {
+                (&*stream).write(&data.as_bytes()[written..])
+            })
+            .map_err(|e| io_error("tcp_write", e))?;
+    }
+    Ok(RuntimeValue::Int(written as i64))
+}
+
+/// Native implementation: tcp_port
+fn native_tcp_port(
+    args: &[RuntimeValue],
+    _ctx: &mut NativeContext<'_>,
+) -> Result<RuntimeValue, ExecutorError> {
+    let addr = match &*socket_arg(args, "tcp_port")? {
+        Socket::Listener(listener, _) => listener.local_addr(),
+        Socket::Stream(stream, _) => stream.local_addr(),
+    };
+    let addr = addr.map_err(|e| io_error("tcp_port", e))?;
+    Ok(RuntimeValue::Int(addr.port() as i64))
+}
+
+/// Native implementation: tcp_close
+fn native_tcp_close(
+    args: &[RuntimeValue],
+    _ctx: &mut NativeContext<'_>,
+) -> Result<RuntimeValue, ExecutorError> {
+    let Some(RuntimeValue::Int(handle)) = args.first() else {
+        return Err(ExecutorError::type_only(
+            "tcp_close expects 1 argument (sock: Int)".to_string(),
+        ));
+    };
+    // Tasks still using the socket keep it open until they finish
+    SOCKETS
+        .lock()
+        .map_err(|_| ExecutorError::runtime_only("Failed to lock socket table".to_string()))?
+        .remove(handle)
+        .ok_or_else(|| ExecutorError::runtime_only(format!("Invalid socket: {}", handle)))?;
+    Ok(RuntimeValue::Unit)
+}
+
+fn reactor() -> Result<&'static Reactor, ExecutorError> {
+    Reactor::global().map_err(|e| io_error("I/O reactor", e))
+}
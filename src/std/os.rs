@@ -919,7 +919,7 @@ fn native_args(
     _args: &[RuntimeValue],
     _ctx: &mut NativeContext<'_>,
 ) -> Result<RuntimeValue, ExecutorError> {
-    let args: Vec<String> = std::env::args().collect();
+    let args = crate::std::env::program_args();
     Ok(RuntimeValue::String(args.join("\n").into()))
 }
 
@@ -0,0 +1,57 @@
+//! Standard Process library (YaoXiang)
+//!
+//! This module lets YaoXiang programs control their exit code. `exit(n)`
+//! unwinds the VM instead of killing the host process, so embedders (REPL,
+//! LSP, tests) keep running and `run_file` reports `n` to its caller.
+
+use crate::backends::common::RuntimeValue;
+use crate::backends::ExecutorError;
+use crate::std::{NativeContext, NativeExport, StdModule};
+
+// ============================================================================
+// ProcessModule - StdModule Implementation
+// ============================================================================
+
+/// Process module implementation.
+pub struct ProcessModule;
+
+impl Default for ProcessModule {
+    fn default() -> Self {
+        Self
+    }
+}
+
+impl StdModule for ProcessModule {
+    fn module_path(&self) -> &str {
+        "std.process"
+    }
+
+    fn exports(&self) -> Vec<NativeExport> {
+        vec![NativeExport::new(
+            "exit",
+            "std.process.exit",
+            "(code: Int) -> Void",
+            native_exit,
+        )]
+    }
+}
+
+/// Singleton instance for std.process module.
+pub const PROCESS_MODULE: ProcessModule = ProcessModule;
+
+// ============================================================================
+// Native Implementations
+// ============================================================================
+
+/// Native implementation: exit - stop the program with the given exit code
+fn native_exit(
+    args: &[RuntimeValue],
+    _ctx: &mut NativeContext<'_>,
+) -> Result<RuntimeValue, ExecutorError> {
+    match args.first() {
+        Some(RuntimeValue::Int(code)) => Err(ExecutorError::Exit(*code as i32)),
+        _ => Err(ExecutorError::type_only(
+            "exit expects an Int exit code".to_string(),
+        )),
+    }
+}
@@ -0,0 +1,130 @@
+//! Standard Reflect library (YaoXiang)
+//!
+//! Types are first-class runtime values: `typeof(x)` returns a `Type` value that
+//! compares with `==`, and `type_name` / `type_fields` query it. Struct names and
+//! field names come from the struct layouts stored in the bytecode file.
+
+use crate::backends::common::value::ValueType;
+use crate::backends::common::{HeapValue, RuntimeValue, StructTypes};
+use crate::backends::ExecutorError;
+use crate::std::{NativeContext, NativeExport, StdModule};
+
+// ============================================================================
+// ReflectModule - StdModule Implementation
+// ============================================================================
+
+/// Reflect module implementation.
+pub struct ReflectModule;
+
+impl Default for ReflectModule {
+    fn default() -> Self {
+        Self
+    }
+}
+
+impl StdModule for ReflectModule {
+    fn module_path(&self) -> &str {
+        "std.reflect"
+    }
+
+    fn exports(&self) -> Vec<NativeExport> {
+        vec![
+            NativeExport::new(
+                "typeof",
+                "std.reflect.typeof",
+                "(value) -> Type",
+                native_typeof,
+            ),
+            NativeExport::new(
+                "type_name",
+                "std.reflect.type_name",
+                "(t: Type) -> String",
+                native_type_name,
+            ),
+            NativeExport::new(
+                "type_fields",
+                "std.reflect.type_fields",
+                "(t: Type) -> List(String)",
+                native_type_fields,
+            ),
+        ]
+    }
+}
+
+/// Singleton instance for std.reflect module.
+pub const REFLECT_MODULE: ReflectModule = ReflectModule;
+
+/// Name of a runtime type, resolving struct names through the program's layouts
+pub fn type_name(
+    ty: &ValueType,
+    struct_types: Option<&StructTypes>,
+) -> String {
+    match ty {
+        ValueType::Struct(id) => struct_types
+            .and_then(|types| types.get(*id))
+            .map_or_else(|| ty.name(), |info| info.name.clone()),
+        _ => ty.name(),
+    }
+}
+
+// ============================================================================
+// Native Function Implementations
+// ============================================================================
+
+/// Native implementation: typeof
+fn native_typeof(
+    args: &[RuntimeValue],
+    ctx: &mut NativeContext<'_>,
+) -> Result<RuntimeValue, ExecutorError> {
+    let value = args.first().unwrap_or(&RuntimeValue::Unit);
+    Ok(RuntimeValue::Type(Box::new(
+        value.value_type(Some(ctx.heap)),
+    )))
+}
+
+/// Native implementation: type_name
+fn native_type_name(
+    args: &[RuntimeValue],
+    ctx: &mut NativeContext<'_>,
+) -> Result<RuntimeValue, ExecutorError> {
+    let ty = expect_type(args, "type_name")?;
+    Ok(RuntimeValue::String(type_name(ty, ctx.struct_types).into()))
+}
+
+/// Native implementation: type_fields
+/// Declared field names of a struct type; empty for every other type
+fn native_type_fields(
+    args: &[RuntimeValue],
+    ctx: &mut NativeContext<'_>,
+) -> Result<RuntimeValue, ExecutorError> {
+    let ty = expect_type(args, "type_fields")?;
+    let fields = match ty {
+        ValueType::Struct(id) => ctx
+            .struct_types
+            .and_then(|types| types.get(*id))
+            .map(|info| info.fields.clone())
+            .unwrap_or_default(),
+        _ => Vec::new(),
+    };
+    let items: Vec<RuntimeValue> = fields
+        .into_iter()
+        .map(|field| RuntimeValue::String(field.into()))
+        .collect();
+    Ok(RuntimeValue::List(
+        ctx.heap.try_allocate(HeapValue::list(items))?,
+    ))
+}
+
+fn expect_type<'v>(
+    args: &'v [RuntimeValue],
+    name: &str,
+) -> Result<&'v ValueType, ExecutorError> {
+    match args.first() {
+        Some(RuntimeValue::Type(ty)) => Ok(ty),
+        other => Err(ExecutorError::type_only(format!(
+            "{} expects a Type value (from typeof), got {}",
+            name,
+            other.map_or_else(|| "nothing".to_string(), |v| v.value_type(None).name())
+        ))),
+    }
+}
@@ -2,13 +2,14 @@
 //!
 //! 提供 `Result(T, E)` 类型的构造函数和实用方法，
 //! 以及 `Error` 类型（作为 Result 的 Err 载体）。
+//! `recover(f)` 在调用边界上捕获 `f` 执行中的运行时 panic，转为 `err(Error)`。
 //!
 //! 运行时表示：
 //! - Result.ok(value): RuntimeValue::Enum { type_id: ENUM, variant_id: 0, payload: value }
 //! - Result.err(error): RuntimeValue::Enum { type_id: ENUM, variant_id: 1, payload: error }
 //! - Error(msg): RuntimeValue::Struct { type_id: STRUCT, fields: [msg], vtable: [] }
 
-use crate::backends::common::value::TypeId;
+use crate::backends::common::value::{MethodTable, TypeId};
 use crate::backends::common::{HeapValue, RuntimeValue};
 use crate::backends::ExecutorError;
 use crate::std::{NativeContext, NativeExport, StdModule};
@@ -28,30 +29,54 @@ impl StdModule for ResultModule {
 
     fn exports(&self) -> Vec<NativeExport> {
         vec![
+            NativeExport::new(
+                "ok",
+                "std.result.ok",
+                "(T: Type, E: Type)(value: T) -> Result(T, E)",
+                native_result_ok,
+            ),
+            NativeExport::new(
+                "err",
+                "std.result.err",
+                "(T: Type, E: Type)(error: E) -> Result(T, E)",
+                native_result_err,
+            ),
             NativeExport::new(
                 "is_ok",
                 "std.result.is_ok",
-                "(self: Result(T, E)) -> Bool",
+                "(T: Type, E: Type)(self: Result(T, E)) -> Bool",
                 native_result_is_ok,
             ),
             NativeExport::new(
                 "is_err",
                 "std.result.is_err",
-                "(self: Result(T, E)) -> Bool",
+                "(T: Type, E: Type)(self: Result(T, E)) -> Bool",
                 native_result_is_err,
             ),
             NativeExport::new(
                 "unwrap",
                 "std.result.unwrap",
-                "(self: Result(T, E)) -> T",
+                "(T: Type, E: Type)(self: Result(T, E)) -> T",
                 native_result_unwrap,
             ),
             NativeExport::new(
                 "unwrap_or",
                 "std.result.unwrap_or",
-                "(self: Result(T, E), default: T) -> T",
+                "(T: Type, E: Type)(self: Result(T, E), default: T) -> T",
                 native_result_unwrap_or,
             ),
+            NativeExport::new(
+                "unwrap_err",
+                "std.result.unwrap_err",
+                "(T: Type, E: Type)(self: Result(T, E)) -> E",
+                native_result_unwrap_err,
+            ),
+            NativeExport::new(
+                "recover",
+                "std.result.recover",
+                "(T: Type)(f: () -> T) -> Result(T, Error)",
+                native_result_recover,
+            ),
         ]
     }
 }
@@ -86,11 +111,11 @@ pub fn error_new(
     ctx: &mut NativeContext<'_>,
 ) -> RuntimeValue {
     let field_values = vec![RuntimeValue::String(message.into())];
-    let handle = ctx.heap.allocate(HeapValue::Tuple(field_values));
+    let handle = ctx.heap.allocate(HeapValue::tuple(field_values));
     RuntimeValue::Struct {
         type_id: TypeId::STRUCT,
         fields: handle,
-        vtable: vec![],
+        vtable: MethodTable::default(),
     }
 }
 
@@ -98,6 +123,24 @@ pub fn error_new(
 // Result 方法 native 实现
 // ============================================================================
 
+pub(crate) fn native_result_ok(
+    args: &[RuntimeValue],
+    _ctx: &mut NativeContext<'_>,
+) -> Result<RuntimeValue, ExecutorError> {
+    Ok(result_ok(
+        args.first().cloned().unwrap_or(RuntimeValue::Unit),
+    ))
+}
+
+pub(crate) fn native_result_err(
+    args: &[RuntimeValue],
+    _ctx: &mut NativeContext<'_>,
+) -> Result<RuntimeValue, ExecutorError> {
+    Ok(result_err(
+        args.first().cloned().unwrap_or(RuntimeValue::Unit),
+    ))
+}
+
 pub(crate) fn native_result_is_ok(
     args: &[RuntimeValue],
     _ctx: &mut NativeContext<'_>,
@@ -145,3 +188,40 @@ pub(crate) fn native_result_unwrap_or(
         _ => Ok(args.get(1).cloned().unwrap_or(RuntimeValue::Unit)),
     }
 }
+
+pub(crate) fn native_result_unwrap_err(
+    args: &[RuntimeValue],
+    _ctx: &mut NativeContext<'_>,
+) -> Result<RuntimeValue, ExecutorError> {
+    match args.first() {
+        Some(RuntimeValue::Enum {
+            variant_id: 1,
+            payload,
+            ..
+        }) => Ok((**payload).clone()),
+        _ => Err(ExecutorError::runtime_only("unwrap_err called on Ok value")),
+    }
+}
+
+/// 调用 `f()`；正常返回时为 `ok(value)`，panic 时为 `err(Error(message))`
+///
+/// 退出请求与宿主中断不是 panic，照常向外传播。
+pub(crate) fn native_result_recover(
+    args: &[RuntimeValue],
+    ctx: &mut NativeContext<'_>,
+) -> Result<RuntimeValue, ExecutorError> {
+    let func = args
+        .first()
+        .ok_or_else(|| ExecutorError::runtime_only("recover expects a function"))?;
+    match ctx.call_function(func, &[]) {
+        Ok(value) => Ok(result_ok(value)),
+        Err(e) if e.is_panic() => {
+            let message = match &e {
+                ExecutorError::Runtime(msg, _) => msg.clone(),
+                other => other.message(),
+            };
+            Ok(result_err(error_new(&message, ctx)))
+        }
+        Err(e) => Err(e),
+    }
+}
@@ -0,0 +1,401 @@
+//! Canonical value printer (YaoXiang)
+//!
+//! `show(value)` is the single readable representation of a runtime value used by
+//! `print`, `to_string`, string formatting, the REPL and assertion messages:
+//!
+//! - structs print as `Point(x: 1.0, y: 2.0)` using the declared field names
+//! - nested strings and chars are quoted; dict entries are sorted by key
+//! - collections longer than [`ShowOptions::max_items`] end with `... N more`
+//! - nesting deeper than [`ShowOptions::max_depth`] is elided as `[...]`
+//! - a collection that contains itself prints `<cycle>` instead of recursing
+//! - output wider than [`ShowOptions::width`] breaks one item per line
+//!
+//! A type overrides its representation with a `show` (or `to_string`) binding
+//! returning `String`, e.g. `Point.show: (self: Point) -> String = { ... }`.
+
+use std::sync::Arc;
+
+use crate::backends::common::value::TypeId;
+use crate::backends::common::{Handle, HeapValue, RuntimeValue, Value};
+use crate::backends::ExecutorError;
+use crate::std::reflect::type_name;
+use crate::std::NativeContext;
+
+/// Limits applied by the printer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShowOptions {
+    /// Containers nested deeper than this are elided
+    pub max_depth: usize,
+    /// Elements shown per collection before `... N more`
+    pub max_items: usize,
+    /// Line width before a collection breaks onto multiple lines
+    pub width: usize,
+}
+
+impl Default for ShowOptions {
+    fn default() -> Self {
+        Self {
+            max_depth: 8,
+            max_items: 100,
+            width: 80,
+        }
+    }
+}
+
+/// Canonical representation of a value; strings are quoted
+pub fn show(
+    value: &RuntimeValue,
+    ctx: &mut NativeContext<'_>,
+) -> Result<String, ExecutorError> {
+    show_with(value, ctx, &ShowOptions::default())
+}
+
+/// Representation used by `print` and `to_string`: a top-level string or char is
+/// printed as-is, everything else as [`show`]
+pub fn show_display(
+    value: &RuntimeValue,
+    ctx: &mut NativeContext<'_>,
+) -> Result<String, ExecutorError> {
+    match value {
+        RuntimeValue::String(s) => Ok(s.to_string()),
+        RuntimeValue::Char(c) => Ok(char_text(*c)),
+        RuntimeValue::Dyn { value, .. } => show_display(value, ctx),
+        other => show(other, ctx),
+    }
+}
+
+/// [`show`] with explicit limits
+pub fn show_with(
+    value: &RuntimeValue,
+    ctx: &mut NativeContext<'_>,
+    options: &ShowOptions,
+) -> Result<String, ExecutorError> {
+    let mut printer = Printer {
+        ctx,
+        options,
+        active: Vec::new(),
+        overriding: Vec::new(),
+    };
+    let doc = printer.doc(value, 0)?;
+    let mut out = String::new();
+    doc.render(0, 0, options.width, &mut out);
+    Ok(out)
+}
+
+// ============================================================================
+// Layout
+// ============================================================================
+
+/// Intermediate layout tree: rendered flat when it fits, broken otherwise
+enum Doc {
+    Text(String),
+    Group {
+        open: String,
+        close: &'static str,
+        items: Vec<Doc>,
+    },
+    Entry {
+        key: String,
+        value: Box<Doc>,
+    },
+}
+
+impl Doc {
+    fn flat(&self) -> String {
+        match self {
+            Doc::Text(text) => text.clone(),
+            Doc::Group { open, close, items } => {
+                let items: Vec<String> = items.iter().map(Doc::flat).collect();
+                format!("{}{}{}", open, items.join(", "), close)
+            }
+            Doc::Entry { key, value } => format!("{}: {}", key, value.flat()),
+        }
+    }
+
+    /// Render starting at `column`; broken items are indented from `indent`
+    fn render(
+        &self,
+        column: usize,
+        indent: usize,
+        width: usize,
+        out: &mut String,
+    ) {
+        let flat = self.flat();
+        if column + flat.chars().count() <= width {
+            out.push_str(&flat);
+            return;
+        }
+        match self {
+            Doc::Text(text) => out.push_str(text),
+            Doc::Group { open, close, items } if !items.is_empty() => {
+                let inner = indent + 2;
+                out.push_str(open);
+                out.push('\n');
+                if items.iter().all(|item| matches!(item, Doc::Text(_))) {
+                    // 标量元素按行填充，而不是每行一个
+                    let mut line = String::new();
+                    for item in items {
+                        let text = format!("{},", item.flat());
+                        if !line.is_empty()
+                            && inner + line.chars().count() + 1 + text.chars().count() > width
+                        {
+                            out.push_str(&format!("{}{}\n", " ".repeat(inner), line));
+                            line.clear();
+                        }
+                        if !line.is_empty() {
+                            line.push(' ');
+                        }
+                        line.push_str(&text);
+                    }
+                    out.push_str(&format!("{}{}\n", " ".repeat(inner), line));
+                } else {
+                    for item in items {
+                        out.push_str(&" ".repeat(inner));
+                        item.render(inner, inner, width, out);
+                        out.push_str(",\n");
+                    }
+                }
+                out.push_str(&" ".repeat(indent));
+                out.push_str(close);
+            }
+            Doc::Group { .. } => out.push_str(&flat),
+            Doc::Entry { key, value } => {
+                out.push_str(key);
+                out.push_str(": ");
+                value.render(column + key.chars().count() + 2, indent, width, out);
+            }
+        }
+    }
+}
+
+// ============================================================================
+// Printer
+// ============================================================================
+
+struct Printer<'p, 'c> {
+    ctx: &'p mut NativeContext<'c>,
+    options: &'p ShowOptions,
+    /// Heap handles currently being printed (cycle detection)
+    active: Vec<Handle>,
+    /// Structs whose override is currently running; printed structurally inside it
+    overriding: Vec<Handle>,
+}
+
+impl Printer<'_, '_> {
+    fn doc(
+        &mut self,
+        value: &RuntimeValue,
+        depth: usize,
+    ) -> Result<Doc, ExecutorError> {
+        let text = match value {
+            RuntimeValue::Unit => "unit".to_string(),
+            RuntimeValue::Bool(b) => b.to_string(),
+            RuntimeValue::Int(i) => i.to_string(),
+            // 数字输出与进程 locale 无关，本地化格式见 std.locale
+            RuntimeValue::Float(f) => crate::std::string::format_float_default(*f),
+            RuntimeValue::Char(c) => match char::from_u32(*c) {
+                Some(ch) => format!("{:?}", ch),
+                None => char_text(*c),
+            },
+            RuntimeValue::String(s) => format!("{:?}", s.as_ref()),
+            RuntimeValue::Bytes(b) => format!("bytes[{}]", b.len()),
+            RuntimeValue::Tuple(handle) => return self.sequence(*handle, "(", ")", depth),
+            RuntimeValue::Array(handle) | RuntimeValue::List(handle) => {
+                return self.sequence(*handle, "[", "]", depth)
+            }
+            RuntimeValue::Dict(handle) => return self.dict(*handle, depth),
+            RuntimeValue::Struct { .. } => return self.structure(value, depth),
+            RuntimeValue::Enum {
+                type_id,
+                variant_id,
+                payload,
+            } => {
+                let name = if *type_id == TypeId::ENUM {
+                    match variant_id {
+                        0 => "ok".to_string(),
+                        _ => "err".to_string(),
+                    }
+                } else {
+                    format!("enum::v{}", variant_id)
+                };
+                if matches!(payload.as_ref(), RuntimeValue::Unit) {
+                    name
+                } else if depth >= self.options.max_depth {
+                    format!("{}(...)", name)
+                } else {
+                    let payload = self.doc(payload, depth + 1)?;
+                    return Ok(Doc::Group {
+                        open: format!("{}(", name),
+                        close: ")",
+                        items: vec![payload],
+                    });
+                }
+            }
+            RuntimeValue::Function(_) => "function".to_string(),
+            RuntimeValue::Arc(inner) => {
+                let inner = self.doc(inner, depth)?;
+                return Ok(Doc::Group {
+                    open: "arc(".to_string(),
+                    close: ")",
+                    items: vec![inner],
+                });
+            }
+            RuntimeValue::Weak(_) => "weak(...)".to_string(),
+            RuntimeValue::Async(_) => "async".to_string(),
+            RuntimeValue::Ptr { kind, address, .. } => format!("ptr({:?}, {:#x})", kind, address),
+            RuntimeValue::OpaqueHandle(opaque) => format!("opaque<{}>", opaque.type_name),
+            RuntimeValue::Dyn { value, .. } => return self.doc(value, depth),
+            RuntimeValue::Type(ty) => format!("<type {}>", type_name(ty, self.ctx.struct_types)),
+        };
+        Ok(Doc::Text(text))
+    }
+
+    /// Clone a collection's elements out of the heap
+    fn elements(
+        &self,
+        handle: Handle,
+    ) -> Option<Vec<RuntimeValue>> {
+        match self.ctx.heap.get(handle)? {
+            HeapValue::Tuple(items)
+            | HeapValue::Array(items)
+            | HeapValue::List(items)
+            | HeapValue::Struct(items) => Some(items.iter().map(Value::get).collect()),
+            HeapValue::Dict(_) => None,
+        }
+    }
+
+    fn sequence(
+        &mut self,
+        handle: Handle,
+        open: &str,
+        close: &'static str,
+        depth: usize,
+    ) -> Result<Doc, ExecutorError> {
+        let Some(items) = self.elements(handle) else {
+            return Ok(Doc::Text(format!("{}?{}", open, close)));
+        };
+        self.group(handle, open.to_string(), close, depth, |printer| {
+            let mut docs = Vec::new();
+            for item in items.iter().take(printer.options.max_items) {
+                docs.push(printer.doc(item, depth + 1)?);
+            }
+            Ok((docs, items.len()))
+        })
+    }
+
+    fn dict(
+        &mut self,
+        handle: Handle,
+        depth: usize,
+    ) -> Result<Doc, ExecutorError> {
+        let entries: Vec<(RuntimeValue, RuntimeValue)> = match self.ctx.heap.get(handle) {
+            Some(HeapValue::Dict(entries)) => entries
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect(),
+            _ => return Ok(Doc::Text("{?}".to_string())),
+        };
+        self.group(handle, "{".to_string(), "}", depth, |printer| {
+            // 字典无序，按键的表示排序以保证输出稳定
+            let mut keyed = Vec::with_capacity(entries.len());
+            for (key, value) in &entries {
+                keyed.push((printer.doc(key, depth + 1)?.flat(), value));
+            }
+            keyed.sort_by(|a, b| a.0.cmp(&b.0));
+            let mut docs = Vec::new();
+            for (key, value) in keyed.into_iter().take(printer.options.max_items) {
+                let value = Box::new(printer.doc(value, depth + 1)?);
+                docs.push(Doc::Entry { key, value });
+            }
+            Ok((docs, entries.len()))
+        })
+    }
+
+    fn structure(
+        &mut self,
+        value: &RuntimeValue,
+        depth: usize,
+    ) -> Result<Doc, ExecutorError> {
+        let RuntimeValue::Struct {
+            type_id,
+            fields,
+            vtable,
+        } = value
+        else {
+            unreachable!("structure() is only called with struct values");
+        };
+
+        if !self.overriding.contains(fields) {
+            let method = ["show", "to_string"]
+                .iter()
+                .find_map(|name| vtable.iter().find(|(method, _)| method == name));
+            if let Some((_, func)) = method {
+                self.overriding.push(*fields);
+                let result = self.ctx.call_function(
+                    &RuntimeValue::Function(Arc::clone(func)),
+                    std::slice::from_ref(value),
+                );
+                self.overriding.pop();
+                if let RuntimeValue::String(s) = result? {
+                    return Ok(Doc::Text(s.to_string()));
+                }
+            }
+        }
+
+        let (name, names) = match self.ctx.struct_types.and_then(|types| types.get(*type_id)) {
+            Some(info) => (info.name.clone(), info.fields.clone()),
+            None if *type_id == TypeId::STRUCT => {
+                ("Error".to_string(), vec!["message".to_string()])
+            }
+            None => ("struct".to_string(), Vec::new()),
+        };
+        let Some(values) = self.elements(*fields) else {
+            return Ok(Doc::Text(format!("{}(?)", name)));
+        };
+        self.group(*fields, format!("{}(", name), ")", depth, |printer| {
+            let mut docs = Vec::new();
+            for (index, value) in values.iter().enumerate().take(printer.options.max_items) {
+                let doc = printer.doc(value, depth + 1)?;
+                docs.push(match names.get(index) {
+                    Some(key) => Doc::Entry {
+                        key: key.clone(),
+                        value: Box::new(doc),
+                    },
+                    None => doc,
+                });
+            }
+            Ok((docs, values.len()))
+        })
+    }
+
+    /// Shared handling for containers: depth limit, cycle detection and truncation
+    fn group(
+        &mut self,
+        handle: Handle,
+        open: String,
+        close: &'static str,
+        depth: usize,
+        build: impl FnOnce(&mut Self) -> Result<(Vec<Doc>, usize), ExecutorError>,
+    ) -> Result<Doc, ExecutorError> {
+        if self.active.contains(&handle) {
+            return Ok(Doc::Text("<cycle>".to_string()));
+        }
+        if depth >= self.options.max_depth {
+            return Ok(Doc::Text(format!("{}...{}", open, close)));
+        }
+        self.active.push(handle);
+        let built = build(self);
+        self.active.pop();
+        let (mut items, total) = built?;
+        if total > items.len() {
+            items.push(Doc::Text(format!("... {} more", total - items.len())));
+        }
+        Ok(Doc::Group { open, close, items })
+    }
+}
+
+fn char_text(c: u32) -> String {
+    char::from_u32(c)
+        .map(|ch| ch.to_string())
+        .unwrap_or_else(|| format!("U+{:04X}", c))
+}
@@ -0,0 +1,213 @@
+//! Standard Signal library (YaoXiang)
+//!
+//! This module lets programs react to SIGINT (Ctrl-C) and SIGTERM. The host
+//! only records that a signal arrived; the interpreter notices it at the next
+//! safepoint (loop back-edge or function entry) and either runs the handler
+//! registered with `on` as a scheduler task, or stops with a stack trace
+//! (E6009) and exit code 128 + signal number.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, LazyLock, Mutex};
+
+use crate::backends::common::value::FunctionValue;
+use crate::backends::common::RuntimeValue;
+use crate::backends::ExecutorError;
+use crate::std::{NativeContext, NativeExport, StdModule};
+
+// ============================================================================
+// SignalModule - StdModule Implementation
+// ============================================================================
+
+/// Signal module implementation.
+pub struct SignalModule;
+
+impl Default for SignalModule {
+    fn default() -> Self {
+        Self
+    }
+}
+
+impl StdModule for SignalModule {
+    fn module_path(&self) -> &str {
+        "std.signal"
+    }
+
+    fn exports(&self) -> Vec<NativeExport> {
+        vec![NativeExport::new(
+            "on",
+            "std.signal.on",
+            "(signal: String, handler: (signal: String) -> Void) -> Void",
+            native_on,
+        )]
+    }
+}
+
+/// Singleton instance for std.signal module.
+pub const SIGNAL_MODULE: SignalModule = SignalModule;
+
+// ============================================================================
+// Signals
+// ============================================================================
+
+/// Host signals a program can handle
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Signal {
+    /// SIGINT (Ctrl-C)
+    Interrupt,
+    /// SIGTERM
+    Terminate,
+}
+
+impl Signal {
+    /// All handled signals
+    pub const ALL: [Signal; 2] = [Signal::Interrupt, Signal::Terminate];
+
+    /// Signal name as written in `signal.on("SIGINT", ...)`
+    pub fn name(self) -> &'static str {
+        match self {
+            Signal::Interrupt => "SIGINT",
+            Signal::Terminate => "SIGTERM",
+        }
+    }
+
+    /// Parse a signal name (`"SIGINT"` or `"SIGTERM"`)
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|s| s.name() == name)
+    }
+
+    /// Conventional exit code of a process killed by this signal (128 + signo)
+    pub fn exit_code(self) -> i32 {
+        match self {
+            Signal::Interrupt => 130,
+            Signal::Terminate => 143,
+        }
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// Signals received but not yet seen by a safepoint
+static PENDING: [AtomicBool; 2] = [AtomicBool::new(false), AtomicBool::new(false)];
+
+/// Handlers registered with `signal.on`
+static HANDLERS: LazyLock<Mutex<[Option<Arc<FunctionValue>>; 2]>> =
+    LazyLock::new(|| Mutex::new([None, None]));
+
+/// Record that `signal` arrived; the interpreter reacts at its next safepoint
+///
+/// A second unhandled signal while the first is still pending means the VM is
+/// stuck outside a safepoint (e.g. a blocking native call), so the host exits.
+pub fn raise(signal: Signal) {
+    let already_pending = PENDING[signal.index()].swap(true, Ordering::AcqRel);
+    if already_pending && handler(signal).is_none() {
+        std::process::exit(signal.exit_code());
+    }
+}
+
+/// Take the next pending signal, if any
+pub(crate) fn take_pending() -> Option<Signal> {
+    Signal::ALL.into_iter().find(|s| {
+        let flag = &PENDING[s.index()];
+        flag.load(Ordering::Relaxed) && flag.swap(false, Ordering::AcqRel)
+    })
+}
+
+/// The pending flags themselves, polled by machine code at its safepoints
+pub(crate) fn pending_flags() -> &'static [AtomicBool; 2] {
+    &PENDING
+}
+
+/// Handler registered for `signal`
+pub(crate) fn handler(signal: Signal) -> Option<Arc<FunctionValue>> {
+    HANDLERS
+        .lock()
+        .ok()
+        .and_then(|handlers| handlers[signal.index()].clone())
+}
+
+/// Forward SIGINT/SIGTERM of the host process to [`raise`]
+///
+/// Called by `yaoxiang run`; without it signals keep their default behavior.
+#[cfg(all(feature = "cli", not(target_arch = "wasm32")))]
+pub fn install() {
+    static INSTALLED: std::sync::Once = std::sync::Once::new();
+    INSTALLED.call_once(|| {
+        let runtime = match tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+        {
+            Ok(runtime) => runtime,
+            Err(e) => {
+                tracing::warn!("signal handling disabled: {}", e);
+                return;
+            }
+        };
+        let spawned = std::thread::Builder::new()
+            .name("yaoxiang-signal".to_string())
+            .spawn(move || runtime.block_on(forward_signals()));
+        if let Err(e) = spawned {
+            tracing::warn!("signal handling disabled: {}", e);
+        }
+    });
+}
+
+#[cfg(all(feature = "cli", not(target_arch = "wasm32")))]
+async fn forward_signals() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        let Ok(mut terminate) = signal(SignalKind::terminate()) else {
+            return;
+        };
+        loop {
+            tokio::select! {
+                result = tokio::signal::ctrl_c() => {
+                    if result.is_err() {
+                        return;
+                    }
+                    raise(Signal::Interrupt);
+                }
+                _ = terminate.recv() => raise(Signal::Terminate),
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    while tokio::signal::ctrl_c().await.is_ok() {
+        raise(Signal::Interrupt);
+    }
+}
+
+// ============================================================================
+// Native Implementations
+// ============================================================================
+
+/// Native implementation: on - register a handler for a signal
+fn native_on(
+    args: &[RuntimeValue],
+    _ctx: &mut NativeContext<'_>,
+) -> Result<RuntimeValue, ExecutorError> {
+    let signal = match args.first() {
+        Some(RuntimeValue::String(name)) => Signal::from_name(name).ok_or_else(|| {
+            ExecutorError::runtime_only(format!(
+                "unknown signal '{}' (expected SIGINT or SIGTERM)",
+                name
+            ))
+        })?,
+        _ => {
+            return Err(ExecutorError::type_only(
+                "on expects a signal name string".to_string(),
+            ))
+        }
+    };
+    let Some(RuntimeValue::Function(handler)) = args.get(1) else {
+        return Err(ExecutorError::type_only(
+            "on expects a handler function".to_string(),
+        ));
+    };
+    if let Ok(mut handlers) = HANDLERS.lock() {
+        handlers[signal.index()] = Some(handler.clone());
+    }
+    Ok(RuntimeValue::Unit)
+}
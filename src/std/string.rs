@@ -4,7 +4,7 @@
 
 use crate::backends::common::RuntimeValue;
 use crate::backends::ExecutorError;
-use crate::std::io::format_value_with_prefix;
+use crate::std::show::show_display;
 use crate::std::{NativeContext, NativeExport, StdModule, NativeHandler};
 use crate::std::result::{error_new, result_err, result_ok};
 
@@ -192,7 +192,7 @@ fn native_split(
 
     let handle = ctx
         .heap
-        .allocate(crate::backends::common::HeapValue::List(parts));
+        .allocate(crate::backends::common::HeapValue::list(parts));
     Ok(RuntimeValue::List(handle))
 }
 
@@ -339,7 +339,7 @@ fn native_chars(
         .collect();
     let handle = ctx
         .heap
-        .allocate(crate::backends::common::HeapValue::List(chars));
+        .allocate(crate::backends::common::HeapValue::list(chars));
     Ok(RuntimeValue::List(handle))
 }
 
@@ -358,11 +358,12 @@ fn native_concat(
 /// Native implementation: repeat - repeat string n times
 fn native_repeat(
     args: &[RuntimeValue],
-    _ctx: &mut NativeContext<'_>,
+    ctx: &mut NativeContext<'_>,
 ) -> Result<RuntimeValue, ExecutorError> {
     let s = args.first().map(extract_string).unwrap_or_default();
     let n = args.get(1).map(extract_int).unwrap_or(0) as usize;
 
+    ctx.heap.check(s.len().saturating_mul(n))?;
     let result = s.repeat(n);
     Ok(RuntimeValue::String(result.into()))
 }
@@ -378,30 +379,38 @@ fn native_reverse(
 }
 
 /// Native implementation: format - Python-style string formatting
-/// Supports {0}, {1}, {2}... placeholders and {:03}, {:>3}, {:<3} format specifiers
+/// Supports {0}, {1}, {2}... placeholders and `[[fill]align][0][width][,|_][.precision][type]`
+/// format specifiers, e.g. {:03}, {:>3}, {:,}, {:.2f}
+///
+/// Output never depends on the process locale; locale-aware variants live in `std.locale`.
 fn native_format(
     args: &[RuntimeValue],
     ctx: &mut NativeContext<'_>,
 ) -> Result<RuntimeValue, ExecutorError> {
     let format_str = args.first().map(extract_string).unwrap_or_default();
-    let format_args = &args[1..];
-
-    // Convert all args to strings upfront
-    let arg_strings: Vec<String> = format_args
-        .iter()
-        .map(|arg| format_value_with_prefix(arg, ctx.heap, ""))
-        .collect();
-
-    // Parse and replace placeholders
-    let result = parse_format(&format_str, &arg_strings);
+    let format_args = args.get(1..).unwrap_or_default();
+
+    let mut error = None;
+    let result = parse_format(&format_str, |index, spec| {
+        let Some(arg) = format_args.get(index) else {
+            return String::new();
+        };
+        format_arg(arg, spec, ctx).unwrap_or_else(|e| {
+            error.get_or_insert(e);
+            String::new()
+        })
+    });
+    if let Some(error) = error {
+        return Err(error);
+    }
 
     Ok(RuntimeValue::String(result.into()))
 }
 
-/// Parse format string and replace placeholders with argument values
-fn parse_format(
+/// Parse format string and replace placeholders with formatted arguments
+pub(crate) fn parse_format(
     format_str: &str,
-    args: &[String],
+    mut format_arg: impl FnMut(usize, &str) -> String,
 ) -> String {
     let mut result = String::new();
     let mut chars = format_str.chars().peekable();
@@ -421,32 +430,16 @@ fn parse_format(
             }
 
             // Parse placeholder: {index} or {index:format}
-            if let Some((index_str, format_spec)) = placeholder.split_once(':') {
-                // Has format specifier: {0:03}
-                let index: usize = index_str.parse().unwrap_or(0);
-                let formatted = apply_format_spec(
-                    args.get(index).map(|s| s.as_str()).unwrap_or(""),
-                    format_spec,
-                );
-                result.push_str(&formatted);
-            } else {
-                // Simple placeholder: {0}
-                let index: usize = placeholder.parse().unwrap_or(0);
-                result.push_str(args.get(index).map(|s| s.as_str()).unwrap_or(""));
-            }
+            let (index_str, format_spec) =
+                placeholder.split_once(':').unwrap_or((&placeholder, ""));
+            let index: usize = index_str.parse().unwrap_or(0);
+            result.push_str(&format_arg(index, format_spec));
         } else if c == '}' {
             // Escape }} as }
-            if let Some(&next) = chars.peek() {
-                if next == '}' {
-                    chars.next();
-                    result.push('}');
-                } else {
-                    // Unmatched }, treat as literal
-                    result.push('}');
-                }
-            } else {
-                result.push('}');
+            if chars.peek() == Some(&'}') {
+                chars.next();
             }
+            result.push('}');
         } else {
             result.push(c);
         }
@@ -455,83 +448,215 @@ fn parse_format(
     result
 }
 
-/// Apply format specifier to a value
-/// Supported: {:03} (zero-pad), {:>3} (right-align), {:<3} (left-align), {:^3} (center)
-fn apply_format_spec(
-    value: &str,
-    spec: &str,
-) -> String {
-    // Parse format spec: [align][width]
-    // align: <, >, ^
-    // width: number
-    // fill (optional): character before align
+/// Parsed format specifier: `[[fill]align][0][width][,|_][.precision][type]`
+#[derive(Debug, Default, PartialEq)]
+struct FormatSpec {
+    fill: Option<char>,
+    align: Option<char>,
+    /// `0` flag: pad numbers with zeros after the sign
+    zero: bool,
+    width: usize,
+    /// Thousands separator (`,` or `_`)
+    grouping: Option<char>,
+    precision: Option<usize>,
+    /// Presentation type: f, e, d, x, X, b, o, %
+    ty: Option<char>,
+}
 
-    if spec.is_empty() {
-        return value.to_string();
-    }
+impl FormatSpec {
+    fn parse(spec: &str) -> FormatSpec {
+        let chars: Vec<char> = spec.chars().collect();
+        let mut parsed = FormatSpec::default();
+        let mut i = 0;
+
+        let is_align = |c: char| matches!(c, '<' | '>' | '^');
+        if chars.len() >= 2 && is_align(chars[1]) {
+            parsed.fill = Some(chars[0]);
+            parsed.align = Some(chars[1]);
+            i = 2;
+        } else if chars.first().copied().is_some_and(is_align) {
+            parsed.align = Some(chars[0]);
+            i = 1;
+        }
 
-    let mut fill_char = ' ';
-    let mut align = '>'; // Default right-align for numbers
+        if chars.get(i) == Some(&'0') {
+            parsed.zero = true;
+            i += 1;
+        }
 
-    let spec_chars: Vec<char> = spec.chars().collect();
+        let width_start = i;
+        while chars.get(i).is_some_and(|c| c.is_ascii_digit()) {
+            i += 1;
+        }
+        parsed.width = chars[width_start..i]
+            .iter()
+            .collect::<String>()
+            .parse()
+            .unwrap_or(0);
+
+        if let Some(&sep @ (',' | '_')) = chars.get(i) {
+            parsed.grouping = Some(sep);
+            i += 1;
+        }
 
-    // Check for fill character (before align)
-    let width_str = if spec_chars.len() >= 2 {
-        match spec_chars[0] {
-            '<' | '>' | '^' => {
-                align = spec_chars[0];
-                &spec[1..]
+        if chars.get(i) == Some(&'.') {
+            i += 1;
+            let precision_start = i;
+            while chars.get(i).is_some_and(|c| c.is_ascii_digit()) {
+                i += 1;
             }
-            _ if spec_chars[0].is_ascii_digit() => {
-                fill_char = spec_chars[0];
-                if spec_chars.len() >= 2 {
-                    match spec_chars[1] {
-                        '<' | '>' | '^' => {
-                            align = spec_chars[1];
-                            &spec[2..]
-                        }
-                        _ => spec,
-                    }
-                } else {
-                    spec
-                }
+            parsed.precision = chars[precision_start..i]
+                .iter()
+                .collect::<String>()
+                .parse()
+                .ok();
+        }
+
+        parsed.ty = chars.get(i).copied();
+        parsed
+    }
+}
+
+/// Format one argument according to its specifier
+fn format_arg(
+    value: &RuntimeValue,
+    spec: &str,
+    ctx: &mut NativeContext<'_>,
+) -> Result<String, ExecutorError> {
+    let spec = FormatSpec::parse(spec);
+    let (sign, body) = match value {
+        RuntimeValue::Int(n) => split_sign(format_int(*n, &spec)),
+        RuntimeValue::Float(f) => split_sign(format_float(*f, &spec)),
+        other => {
+            let mut text = show_display(other, ctx)?;
+            if let Some(precision) = spec.precision {
+                text = text.chars().take(precision).collect();
             }
-            _ => spec,
+            // Strings keep the historical right alignment; `{:03}` pads with zeros
+            return Ok(pad(&text, &spec, '>'));
         }
-    } else {
-        spec
     };
 
-    // Parse width
-    let width: usize = width_str.parse().unwrap_or(0);
+    if spec.zero && spec.align.is_none() {
+        // Zeros go between the sign and the digits: `-0042`
+        let digits = spec.width.saturating_sub(sign.len() + body.chars().count());
+        return Ok(format!("{}{}{}", sign, "0".repeat(digits), body));
+    }
+    Ok(pad(&format!("{}{}", sign, body), &spec, '>'))
+}
 
-    if width == 0 {
-        return value.to_string();
+fn split_sign(text: String) -> (&'static str, String) {
+    match text.strip_prefix('-') {
+        Some(rest) => ("-", rest.to_string()),
+        None => ("", text),
     }
+}
 
-    let len = value.len();
+fn format_int(
+    n: i64,
+    spec: &FormatSpec,
+) -> String {
+    let magnitude = n.unsigned_abs();
+    let sign = if n < 0 { "-" } else { "" };
+    let digits = match spec.ty {
+        Some('x') => format!("{:x}", magnitude),
+        Some('X') => format!("{:X}", magnitude),
+        Some('b') => format!("{:b}", magnitude),
+        Some('o') => format!("{:o}", magnitude),
+        Some('f' | 'e' | '%') => return format_float(n as f64, spec),
+        _ if spec.precision.is_some() => return format_float(n as f64, spec),
+        _ => magnitude.to_string(),
+    };
+    let digits = match spec.grouping {
+        Some(sep) => group_digits(&digits, &sep.to_string()),
+        None => digits,
+    };
+    format!("{}{}", sign, digits)
+}
 
-    if len >= width {
-        return value.to_string();
+fn format_float(
+    f: f64,
+    spec: &FormatSpec,
+) -> String {
+    if !f.is_finite() {
+        return format_float_default(f);
     }
+    let text = match (spec.ty, spec.precision) {
+        (Some('e'), Some(p)) => format!("{:.*e}", p, f),
+        (Some('e'), None) => format!("{:e}", f),
+        (Some('%'), p) => format!("{:.*}%", p.unwrap_or(6), f * 100.0),
+        (Some('f'), None) => format!("{:.6}", f),
+        (_, Some(p)) => format!("{:.*}", p, f),
+        _ => format_float_default(f),
+    };
+    match spec.grouping {
+        Some(sep) => {
+            let (sign, rest) = split_sign(text);
+            let split = rest
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(rest.len());
+            format!(
+                "{}{}{}",
+                sign,
+                group_digits(&rest[..split], &sep.to_string()),
+                &rest[split..]
+            )
+        }
+        None => text,
+    }
+}
+
+/// Default Float text, as printed by `print`: integral values keep a `.0`
+pub(crate) fn format_float_default(f: f64) -> String {
+    if f.is_finite() && f.fract() == 0.0 {
+        format!("{:.1}", f)
+    } else {
+        f.to_string()
+    }
+}
 
-    let padding_len = width - len;
-    let padding: String = fill_char.to_string().repeat(padding_len);
+/// Insert `sep` between groups of three digits: `1234567` -> `1,234,567`
+pub(crate) fn group_digits(
+    digits: &str,
+    sep: &str,
+) -> String {
+    let len = digits.len();
+    let mut out = String::with_capacity(len + len / 3 * sep.len());
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (len - i).is_multiple_of(3) {
+            out.push_str(sep);
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Pad `value` to the spec width
+fn pad(
+    value: &str,
+    spec: &FormatSpec,
+    default_align: char,
+) -> String {
+    let len = value.chars().count();
+    if len >= spec.width {
+        return value.to_string();
+    }
+    let fill = spec.fill.unwrap_or(if spec.zero { '0' } else { ' ' });
+    let padding_len = spec.width - len;
+    let padding = |n: usize| fill.to_string().repeat(n);
 
-    match align {
-        '<' => format!("{}{}", value, padding), // Left align
+    match spec.align.unwrap_or(default_align) {
+        '<' => format!("{}{}", value, padding(padding_len)),
         '^' => {
-            // Center align
             let left_pad = padding_len / 2;
-            let right_pad = padding_len - left_pad;
             format!(
                 "{}{}{}",
-                padding.repeat(left_pad),
+                padding(left_pad),
                 value,
-                padding.repeat(right_pad)
+                padding(padding_len - left_pad)
             )
         }
-        _ => format!("{}{}", padding, value), // Right align (default)
+        _ => format!("{}{}", padding(padding_len), value),
     }
 }
 
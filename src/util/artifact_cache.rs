@@ -0,0 +1,157 @@
+//! 编译产物缓存
+//!
+//! 把编译好的字节码与 REPL 会话快照保存在用户缓存目录（见
+//! [`get_cache_dir`](crate::util::config::get_cache_dir)）中，使 `yaoxiang eval` 与 REPL
+//! 再次遇到相同的源码时跳过词法分析到代码生成的全部阶段。
+//!
+//! # 布局
+//!
+//! ```text
+//! <cache>/bytecode/<key>.42     编译产物（与 `yaoxiang build` 的输出格式相同）
+//! <cache>/sessions/<name>.json  REPL 会话快照
+//! ```
+//!
+//! 键是编译器版本、字节码格式版本与源码的 SHA-256，编译器升级后旧条目自然失效。
+//! 写入先写临时文件再改名，并发的进程不会读到写了一半的条目；读不出的条目视为未命中
+//! 并删除。缓存只是加速手段，所有读写失败都不影响编译本身。
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::middle::passes::codegen::bytecode::{BytecodeFile, FileHeader};
+use crate::package::vendor::cache::Sha256;
+
+/// 编译产物缓存
+#[derive(Debug, Clone)]
+pub struct ArtifactCache {
+    root: PathBuf,
+}
+
+impl ArtifactCache {
+    /// 以 `root` 为缓存目录（不存在时在首次写入时创建）
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// 用户缓存目录下的缓存；无法确定用户目录时为 `None`
+    pub fn user() -> Option<Self> {
+        crate::util::config::get_cache_dir().map(Self::new)
+    }
+
+    /// 缓存目录
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// 由若干部分（如用途与源码）计算缓存键
+    pub fn key(parts: &[&str]) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(crate::VERSION.as_bytes());
+        hasher.update(b"\0");
+        hasher.update(&FileHeader::default().version.to_le_bytes());
+        for part in parts {
+            hasher.update(b"\0");
+            hasher.update(part.as_bytes());
+        }
+        hasher.finalize_hex()
+    }
+
+    /// 读取 `key` 对应的字节码
+    pub fn load_bytecode(
+        &self,
+        key: &str,
+    ) -> Option<BytecodeFile> {
+        let path = self.bytecode_path(key);
+        if !path.exists() {
+            return None;
+        }
+        match BytecodeFile::load(&path) {
+            Ok(file) => Some(file),
+            Err(_) => {
+                let _ = fs::remove_file(&path);
+                None
+            }
+        }
+    }
+
+    /// 保存 `key` 对应的字节码
+    pub fn store_bytecode(
+        &self,
+        key: &str,
+        file: &BytecodeFile,
+    ) -> io::Result<()> {
+        let mut bytes = Vec::new();
+        file.write_to(&mut bytes)?;
+        write_atomic(&self.bytecode_path(key), &bytes)
+    }
+
+    /// 读取名为 `name` 的会话快照
+    pub fn load_session<T: DeserializeOwned>(
+        &self,
+        name: &str,
+    ) -> Option<T> {
+        let path = self.session_path(name);
+        let text = fs::read_to_string(&path).ok()?;
+        match serde_json::from_str(&text) {
+            Ok(snapshot) => Some(snapshot),
+            Err(_) => {
+                let _ = fs::remove_file(&path);
+                None
+            }
+        }
+    }
+
+    /// 保存名为 `name` 的会话快照
+    pub fn store_session<T: Serialize>(
+        &self,
+        name: &str,
+        snapshot: &T,
+    ) -> io::Result<()> {
+        let text = serde_json::to_string(snapshot).map_err(io::Error::other)?;
+        write_atomic(&self.session_path(name), text.as_bytes())
+    }
+
+    /// 删除全部缓存条目
+    pub fn clear(&self) -> io::Result<()> {
+        for dir in ["bytecode", "sessions"] {
+            let dir = self.root.join(dir);
+            if dir.exists() {
+                fs::remove_dir_all(dir)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn bytecode_path(
+        &self,
+        key: &str,
+    ) -> PathBuf {
+        self.root.join("bytecode").join(format!("{}.42", key))
+    }
+
+    fn session_path(
+        &self,
+        name: &str,
+    ) -> PathBuf {
+        self.root.join("sessions").join(format!("{}.json", name))
+    }
+}
+
+/// 先写同目录下的临时文件再改名
+fn write_atomic(
+    path: &Path,
+    bytes: &[u8],
+) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let tmp = path.with_extension(format!("tmp{}", std::process::id()));
+    fs::write(&tmp, bytes)?;
+    fs::rename(&tmp, path).inspect_err(|_| {
+        let _ = fs::remove_file(&tmp);
+    })
+}
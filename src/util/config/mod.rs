@@ -140,6 +140,62 @@ pub struct FmtConfig {
     /// Sort import statements
     #[serde(default)]
     pub sort_imports: Option<bool>,
+    /// Line endings of formatted output
+    #[serde(default)]
+    pub line_ending: Option<LineEnding>,
+}
+
+/// Line ending style of formatted output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum LineEnding {
+    /// Keep the style of the input (by its first line break)
+    #[default]
+    Preserve,
+    /// Unix `\n`
+    Lf,
+    /// Windows `\r\n`
+    Crlf,
+}
+
+impl LineEnding {
+    /// Line ending used for the given input
+    pub fn resolve(
+        self,
+        source: &str,
+    ) -> LineEnding {
+        match self {
+            LineEnding::Preserve => match source.find('\n') {
+                Some(i) if source[..i].ends_with('\r') => LineEnding::Crlf,
+                _ => LineEnding::Lf,
+            },
+            other => other,
+        }
+    }
+
+    /// Line terminator text
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LineEnding::Crlf => "\r\n",
+            LineEnding::Lf | LineEnding::Preserve => "\n",
+        }
+    }
+}
+
+impl std::str::FromStr for LineEnding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "preserve" => Ok(LineEnding::Preserve),
+            "lf" => Ok(LineEnding::Lf),
+            "crlf" => Ok(LineEnding::Crlf),
+            other => Err(format!(
+                "unknown line ending '{}' (expected preserve, lf or crlf)",
+                other
+            )),
+        }
+    }
 }
 
 /// Warning level for lints
@@ -174,9 +230,30 @@ pub struct LintConfig {
     /// Strict mode
     #[serde(default)]
     pub strict: bool,
-    /// Dead code analysis level
-    #[serde(default)]
+    /// Dead code analysis level (W1001/W1002/W1004/W1005/W1007)
+    #[serde(default, alias = "dead-code")]
     pub dead_code: WarningLevel,
+    /// Unused local variable level (W1006)
+    #[serde(default, alias = "unused-variables")]
+    pub unused_variables: WarningLevel,
+    /// Unused import level (W1003)
+    #[serde(default, alias = "unused-imports")]
+    pub unused_imports: WarningLevel,
+}
+
+impl LintConfig {
+    /// Level configured for a warning code; codes without a setting stay warnings
+    pub fn level_for(
+        &self,
+        code: &str,
+    ) -> WarningLevel {
+        match code {
+            "W1003" => self.unused_imports,
+            "W1006" => self.unused_variables,
+            "W1001" | "W1002" | "W1004" | "W1005" | "W1007" => self.dead_code,
+            _ => WarningLevel::Warn,
+        }
+    }
 }
 
 fn default_lint_rules() -> Vec<String> {
@@ -189,6 +266,8 @@ impl Default for LintConfig {
             rules: vec!["recommended".to_string()],
             strict: false,
             dead_code: WarningLevel::default(),
+            unused_variables: WarningLevel::default(),
+            unused_imports: WarningLevel::default(),
         }
     }
 }
@@ -210,6 +289,29 @@ pub struct ProjectConfig {
     /// Runtime configuration
     #[serde(default)]
     pub runtime: RuntimeConfig,
+    /// Lint configuration (overrides the user-level `[lint]` section)
+    #[serde(default)]
+    pub lint: Option<LintConfig>,
+    /// Build configuration
+    #[serde(default)]
+    pub build: BuildConfig,
+}
+
+/// Build configuration (`[build]` in yaoxiang.toml)
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct BuildConfig {
+    /// Optimization level: 0, 1, 2 or 3
+    #[serde(default)]
+    pub opt_level: Option<u8>,
+    /// Optimization passes to run, in order; overrides the level's preset
+    #[serde(default)]
+    pub passes: Option<Vec<String>>,
+    /// Set to `false` to skip escape analysis
+    #[serde(default)]
+    pub escape_analysis: Option<bool>,
+    /// Set to `false` to keep functions unreachable from `main` and `pub` exports
+    #[serde(default)]
+    pub tree_shake: Option<bool>,
 }
 
 /// Runtime configuration
@@ -256,6 +358,38 @@ pub fn get_config_dir() -> Option<PathBuf> {
     None
 }
 
+/// Get the user cache directory for compiled artifacts
+///
+/// `YAOXIANG_CACHE_DIR` overrides the platform default
+/// (`$XDG_CACHE_HOME/yaoxiang`, `~/Library/Caches/yaoxiang`, `~/.cache/yaoxiang`
+/// or `%LOCALAPPDATA%\yaoxiang\cache`).
+pub fn get_cache_dir() -> Option<PathBuf> {
+    if let Ok(dir) = std::env::var("YAOXIANG_CACHE_DIR") {
+        return Some(PathBuf::from(dir));
+    }
+
+    if let Ok(xdg_cache) = std::env::var("XDG_CACHE_HOME") {
+        return Some(PathBuf::from(xdg_cache).join("yaoxiang"));
+    }
+
+    if cfg!(windows) {
+        if let Ok(local) = std::env::var("LOCALAPPDATA") {
+            return Some(PathBuf::from(local).join("yaoxiang").join("cache"));
+        }
+    }
+
+    if let Ok(home) = std::env::var("HOME") {
+        let home = PathBuf::from(home);
+        return Some(if cfg!(target_os = "macos") {
+            home.join("Library").join("Caches").join("yaoxiang")
+        } else {
+            home.join(".cache").join("yaoxiang")
+        });
+    }
+
+    None
+}
+
 /// Get the user config file path (~/.config/yaoxiang/config.toml)
 pub fn get_config_path() -> Option<PathBuf> {
     get_config_dir().map(|dir| dir.join("config.toml"))
@@ -283,6 +417,17 @@ pub fn load_user_config() -> Result<UserConfig, ConfigError> {
     toml::from_str(&content).map_err(ConfigError::ParseError)
 }
 
+/// Load the effective lint configuration
+///
+/// A `[lint]` section in `./yaoxiang.toml` takes precedence over the user config.
+pub fn load_lint_config() -> LintConfig {
+    let project_lint = fs::read_to_string("yaoxiang.toml")
+        .ok()
+        .and_then(|content| toml::from_str::<ProjectConfig>(&content).ok())
+        .and_then(|config| config.lint);
+    project_lint.unwrap_or_else(|| load_user_config().unwrap_or_default().lint)
+}
+
 /// Load user-level config, creating default if not exists
 pub fn load_or_create_user_config() -> Result<UserConfig, ConfigError> {
     let path = match get_config_path() {
@@ -3,7 +3,7 @@
 //! 支持模板参数化的错误消息构建器，替代 trait-per-error 设计
 
 use crate::util::span::Span;
-use crate::util::diagnostic::{Diagnostic, Severity};
+use crate::util::diagnostic::{Diagnostic, Fix, Severity};
 use crate::util::i18n::error_lang;
 use std::collections::HashMap;
 
@@ -14,6 +14,7 @@ pub struct DiagnosticBuilder {
     params: Vec<(&'static str, String)>,
     span: Option<Span>,
     related: Vec<Diagnostic>,
+    fixes: Vec<Fix>,
     severity: Option<Severity>,
 }
 
@@ -25,6 +26,7 @@ impl DiagnosticBuilder {
             params: Vec::new(),
             span: None,
             related: Vec::new(),
+            fixes: Vec::new(),
             severity: None,
         }
     }
@@ -59,6 +61,16 @@ impl DiagnosticBuilder {
         self
     }
 
+    /// 附加结构化修复建议
+    #[inline]
+    pub fn fix(
+        mut self,
+        fix: Fix,
+    ) -> Self {
+        self.fixes.push(fix);
+        self
+    }
+
     /// 设置严重级别（默认 Error）
     #[inline]
     pub fn severity(
@@ -132,6 +144,9 @@ impl DiagnosticBuilder {
         if !self.related.is_empty() {
             diagnostic = diagnostic.with_related(self.related.clone());
         }
+        if !self.fixes.is_empty() {
+            diagnostic = diagnostic.with_fixes(self.fixes.clone());
+        }
 
         diagnostic
     }
@@ -186,6 +201,8 @@ impl DiagnosticBuilder {
 #[derive(Debug, Clone)]
 pub struct ErrorInfo<'a> {
     pub title: &'a str,
+    /// 长篇说明（`explain` 使用）
+    pub description: &'a str,
     pub help: &'a str,
     pub example: Option<&'a str>,
     pub error_output: Option<&'a str>,
@@ -198,6 +215,8 @@ pub struct I18nRegistry {
     templates: HashMap<&'static str, &'static str>,
     /// 标题
     titles: HashMap<&'static str, &'static str>,
+    /// 长篇说明
+    descriptions: HashMap<&'static str, &'static str>,
     /// 帮助信息
     helps: HashMap<&'static str, &'static str>,
     /// 示例代码
@@ -214,6 +233,8 @@ struct ErrorInfoJson {
     #[serde(default)]
     title: Option<String>,
     #[serde(default)]
+    message: Option<String>,
+    #[serde(default)]
     template: Option<String>,
     #[serde(default)]
     help: Option<String>,
@@ -234,6 +255,7 @@ fn load_i18n_data(json: &str) -> I18nRegistry {
 
     let mut templates = HashMap::new();
     let mut titles = HashMap::new();
+    let mut descriptions = HashMap::new();
     let mut helps = HashMap::new();
     let mut examples = HashMap::new();
     let mut error_outputs = HashMap::new();
@@ -249,6 +271,9 @@ fn load_i18n_data(json: &str) -> I18nRegistry {
             templates.insert(code_static, to_static_string(tmpl));
         }
         titles.insert(code_static, to_static_string(title));
+        if let Some(message) = info.message {
+            descriptions.insert(code_static, to_static_string(message));
+        }
         helps.insert(code_static, to_static_string(info.help.unwrap_or_default()));
 
         if let Some(ex) = info.example {
@@ -265,6 +290,7 @@ fn load_i18n_data(json: &str) -> I18nRegistry {
     I18nRegistry {
         templates,
         titles,
+        descriptions,
         helps,
         examples,
         error_outputs,
@@ -309,6 +335,7 @@ impl I18nRegistry {
     ) -> Option<ErrorInfo<'_>> {
         Some(ErrorInfo {
             title: self.titles.get(code)?,
+            description: self.descriptions.get(code).copied().unwrap_or(""),
             help: self.helps.get(code).copied().unwrap_or(""),
             example: self.examples.get(code).copied(),
             error_output: self.error_outputs.get(code).copied(),
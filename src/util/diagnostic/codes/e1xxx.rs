@@ -87,6 +87,15 @@ pub static E1XXX: &[ErrorCodeDefinition] = &[
         code: "E1055",
         category: ErrorCategory::TypeCheck,
     },
+    // === 定宽数值 ===
+    ErrorCodeDefinition {
+        code: "E1056",
+        category: ErrorCategory::TypeCheck,
+    },
+    ErrorCodeDefinition {
+        code: "E1057",
+        category: ErrorCategory::TypeCheck,
+    },
     // === 泛型实例化 ===
     ErrorCodeDefinition {
         code: "E1060",
@@ -285,6 +294,24 @@ impl ErrorCodeDefinition {
         def.builder().param("type", type_)
     }
 
+    /// E1056 数值字面量超出目标类型的范围
+    pub fn numeric_literal_out_of_range(
+        literal: &str,
+        type_: &str,
+    ) -> DiagnosticBuilder {
+        let def = Self::find("E1056").unwrap();
+        def.builder().param("literal", literal).param("type", type_)
+    }
+
+    /// E1057 无效的数值转换
+    pub fn invalid_numeric_cast(
+        from: &str,
+        to: &str,
+    ) -> DiagnosticBuilder {
+        let def = Self::find("E1057").unwrap();
+        def.builder().param("from", from).param("to", to)
+    }
+
     /// E1060 类型参数数量不匹配
     pub fn type_argument_count_mismatch(
         expected: usize,
@@ -109,6 +109,16 @@ pub static E2XXX: &[ErrorCodeDefinition] = &[
         code: "E2028",
         category: ErrorCategory::Semantic,
     },
+    // E2029: spawn 内 ref 循环
+    ErrorCodeDefinition {
+        code: "E2029",
+        category: ErrorCategory::Semantic,
+    },
+    // E2030: 值在此处被移动（E2014 的附注）
+    ErrorCodeDefinition {
+        code: "E2030",
+        category: ErrorCategory::Semantic,
+    },
     // E209x: 函数签名解析错误
     ErrorCodeDefinition {
         code: "E2090",
@@ -284,6 +294,12 @@ impl ErrorCodeDefinition {
         def.builder().param("cycle", cycle)
     }
 
+    /// E2030 值在此处被移动（附注）
+    pub fn value_moved_here(name: &str) -> DiagnosticBuilder {
+        let def = Self::find("E2030").unwrap();
+        def.builder().param("name", name)
+    }
+
     /// E2090 签名解析失败（通用）
     pub fn invalid_signature(reason: &str) -> DiagnosticBuilder {
         let def = Self::find("E2090").unwrap();
@@ -4,6 +4,7 @@
 //!
 //! E3001-E3009: IR 生成（ir_gen）
 //! E3010-E3019: 字节码生成（codegen）
+//! E3020-E3029: WebAssembly 生成
 
 use super::{ErrorCategory, ErrorCodeDefinition, DiagnosticBuilder};
 
@@ -67,6 +68,11 @@ pub static E3XXX: &[ErrorCodeDefinition] = &[
         code: "E3018",
         category: ErrorCategory::Codegen,
     },
+    // === E3020-E3029: WebAssembly 生成 ===
+    ErrorCodeDefinition {
+        code: "E3020",
+        category: ErrorCategory::Codegen,
+    },
 ];
 
 // E3xxx 快捷方法
@@ -166,4 +172,12 @@ impl ErrorCodeDefinition {
         let def = Self::find("E3018").unwrap();
         def.builder().param("message", message)
     }
+
+    // === WebAssembly 生成 ===
+
+    /// E3020 WebAssembly 后端不支持的构造
+    pub fn wasm_unsupported(construct: &str) -> DiagnosticBuilder {
+        let def = Self::find("E3020").unwrap();
+        def.builder().param("construct", construct)
+    }
 }
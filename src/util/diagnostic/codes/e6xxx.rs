@@ -34,6 +34,14 @@ pub static E6XXX: &[ErrorCodeDefinition] = &[
         code: "E6007",
         category: ErrorCategory::Runtime,
     },
+    ErrorCodeDefinition {
+        code: "E6008",
+        category: ErrorCategory::Runtime,
+    },
+    ErrorCodeDefinition {
+        code: "E6009",
+        category: ErrorCategory::Runtime,
+    },
 ];
 
 // E6xxx 快捷方法
@@ -84,4 +92,16 @@ impl ErrorCodeDefinition {
         let def = Self::find("E6007").unwrap();
         def.builder().param("message", message)
     }
+
+    /// E6008 整数溢出（debug 构建）
+    pub fn integer_overflow(expr: &str) -> DiagnosticBuilder {
+        let def = Self::find("E6008").unwrap();
+        def.builder().param("expr", expr)
+    }
+
+    /// E6009 程序被信号中断（SIGINT/SIGTERM）
+    pub fn interrupted(signal: &str) -> DiagnosticBuilder {
+        let def = Self::find("E6009").unwrap();
+        def.builder().param("signal", signal)
+    }
 }
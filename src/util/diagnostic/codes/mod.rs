@@ -12,6 +12,7 @@ pub mod e6xxx;
 pub mod e7xxx;
 pub mod e8xxx;
 pub mod w1xxx;
+pub mod w2xxx;
 
 pub use e0xxx::*;
 pub use e1xxx::*;
@@ -23,6 +24,7 @@ pub use e6xxx::*;
 pub use e7xxx::*;
 pub use e8xxx::*;
 pub use w1xxx::*;
+pub use w2xxx::*;
 
 pub mod builder;
 pub use builder::{DiagnosticBuilder, I18nRegistry, ErrorInfo};
@@ -42,7 +44,7 @@ pub enum ErrorCategory {
     Runtime,   // E6xxx: 运行时错误
     Io,        // E7xxx: I/O与系统错误
     Internal,  // E8xxx: 内部编译器错误
-    Warning,   // W1xxx: 警告（死代码等）
+    Warning,   // W1xxx/W2xxx: 警告（死代码、弃用等）
 }
 
 impl std::fmt::Display for ErrorCategory {
@@ -101,6 +103,8 @@ static ERROR_CODES: Lazy<Vec<ErrorCodeDefinition>> = Lazy::new(|| {
     codes.extend_from_slice(e8xxx::E8XXX);
     // W1xxx: 警告（死代码等）
     codes.extend_from_slice(w1xxx::W1XXX);
+    // W2xxx: 警告（弃用）
+    codes.extend_from_slice(w2xxx::W2XXX);
 
     codes
 });
@@ -109,5 +109,16 @@ fn test_i18n_consistency() {
             "Error code {} missing Chinese title in i18n JSON",
             def.code
         );
+
+        // 4. `yaoxiang explain` 需要长篇说明
+        for (lang, registry) in [("en", en), ("zh", zh)] {
+            let info = registry.get_info(def.code).unwrap();
+            assert!(
+                !info.description.is_empty(),
+                "Error code {} missing {} description (\"message\") in i18n JSON",
+                def.code,
+                lang
+            );
+        }
     }
 }
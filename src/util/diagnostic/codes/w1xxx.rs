@@ -3,6 +3,7 @@
 //! W1xxx: 死代码相关警告
 
 use super::{ErrorCategory, ErrorCodeDefinition, DiagnosticBuilder};
+use crate::util::diagnostic::Severity;
 
 /// W1xxx 警告码列表
 pub static W1XXX: &[ErrorCodeDefinition] = &[
@@ -26,6 +27,14 @@ pub static W1XXX: &[ErrorCodeDefinition] = &[
         code: "W1005",
         category: ErrorCategory::Warning,
     },
+    ErrorCodeDefinition {
+        code: "W1006",
+        category: ErrorCategory::Warning,
+    },
+    ErrorCodeDefinition {
+        code: "W1007",
+        category: ErrorCategory::Warning,
+    },
 ];
 
 // 快捷方法实现
@@ -33,30 +42,56 @@ impl ErrorCodeDefinition {
     /// W1001 未使用的导出函数
     pub fn unused_function(name: &str) -> DiagnosticBuilder {
         let def = Self::find("W1001").unwrap();
-        def.builder().param("name", name)
+        def.builder()
+            .param("name", name)
+            .severity(Severity::Warning)
     }
 
     /// W1002 未使用的导出类型
     pub fn unused_type(name: &str) -> DiagnosticBuilder {
         let def = Self::find("W1002").unwrap();
-        def.builder().param("name", name)
+        def.builder()
+            .param("name", name)
+            .severity(Severity::Warning)
     }
 
     /// W1003 未使用的导入
     pub fn unused_import(name: &str) -> DiagnosticBuilder {
         let def = Self::find("W1003").unwrap();
-        def.builder().param("name", name)
+        def.builder()
+            .param("name", name)
+            .severity(Severity::Warning)
     }
 
     /// W1004 未使用的导出变量
     pub fn unused_variable(name: &str) -> DiagnosticBuilder {
         let def = Self::find("W1004").unwrap();
-        def.builder().param("name", name)
+        def.builder()
+            .param("name", name)
+            .severity(Severity::Warning)
     }
 
     /// W1005 未使用的导出方法
     pub fn unused_method(name: &str) -> DiagnosticBuilder {
         let def = Self::find("W1005").unwrap();
-        def.builder().param("name", name)
+        def.builder()
+            .param("name", name)
+            .severity(Severity::Warning)
+    }
+
+    /// W1006 未使用的局部变量（以 `_` 开头的名称不报告）
+    pub fn unused_local(name: &str) -> DiagnosticBuilder {
+        let def = Self::find("W1006").unwrap();
+        def.builder()
+            .param("name", name)
+            .severity(Severity::Warning)
+    }
+
+    /// W1007 从未被调用的私有函数
+    pub fn unused_private_function(name: &str) -> DiagnosticBuilder {
+        let def = Self::find("W1007").unwrap();
+        def.builder()
+            .param("name", name)
+            .severity(Severity::Warning)
     }
 }
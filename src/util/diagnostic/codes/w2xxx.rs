@@ -0,0 +1,57 @@
+//! 警告码定义
+//!
+//! W2xxx: 弃用相关警告
+
+use super::{ErrorCategory, ErrorCodeDefinition, DiagnosticBuilder};
+use crate::util::diagnostic::Severity;
+
+/// W2xxx 警告码列表
+pub static W2XXX: &[ErrorCodeDefinition] = &[
+    ErrorCodeDefinition {
+        code: "W2001",
+        category: ErrorCategory::Warning,
+    },
+    ErrorCodeDefinition {
+        code: "W2002",
+        category: ErrorCategory::Warning,
+    },
+];
+
+// 快捷方法实现
+impl ErrorCodeDefinition {
+    /// W2001 使用了已弃用的函数或类型
+    ///
+    /// `note` 为 `#[deprecated("...")]` 中的说明，可为空
+    pub fn deprecated_item(
+        name: &str,
+        note: Option<&str>,
+    ) -> DiagnosticBuilder {
+        let def = Self::find("W2001").unwrap();
+        def.builder()
+            .param("name", name)
+            .param("note", deprecation_note(note))
+            .severity(Severity::Warning)
+    }
+
+    /// W2002 使用了已弃用的函数或类型（附替代建议）
+    pub fn deprecated_item_with_replacement(
+        name: &str,
+        note: Option<&str>,
+        replacement: &str,
+    ) -> DiagnosticBuilder {
+        let def = Self::find("W2002").unwrap();
+        def.builder()
+            .param("name", name)
+            .param("note", deprecation_note(note))
+            .param("replacement", replacement)
+            .severity(Severity::Warning)
+    }
+}
+
+/// 说明文字拼接为 `: <note>`，无说明时为空
+fn deprecation_note(note: Option<&str>) -> String {
+    match note {
+        Some(note) if !note.is_empty() => format!(": {}", note),
+        _ => String::new(),
+    }
+}
@@ -22,7 +22,9 @@ use super::{ErrorCodeDefinition, ErrorInfo, I18nRegistry, JsonEmitter};
 #[cfg(feature = "cli")]
 use super::{CheckResult, EmitterConfig, TextEmitter};
 #[cfg(feature = "cli")]
-use super::check_files_with_diagnostics;
+use super::{check_files_with_diagnostics, CheckSession};
+#[cfg(feature = "cli")]
+use crate::util::config::load_lint_config;
 
 #[cfg(feature = "cli")]
 pub fn run_check_command_once(
@@ -32,6 +34,62 @@ pub fn run_check_command_once(
     use_colors: bool,
     no_progress: bool,
 ) -> Result<usize> {
+    run_check_command_summary(paths, excludes, json, use_colors, no_progress)
+        .map(|summary| summary.error_count)
+}
+
+/// 类似 rustc 的提示：列出本次出现的错误码并指向 `yaoxiang explain`
+#[cfg(feature = "cli")]
+fn explain_hint(result: &CheckResult) -> Option<String> {
+    let mut codes: Vec<&str> = result
+        .diagnostics
+        .iter()
+        .filter(|entry| entry.diagnostic.severity.is_error())
+        .map(|entry| entry.diagnostic.code.as_str())
+        .filter(|code| ErrorCodeDefinition::find(code).is_some())
+        .collect();
+    codes.sort_unstable();
+    codes.dedup();
+    match codes.as_slice() {
+        [] => None,
+        [code] => Some(format!(
+            "For more information about this error, try `yaoxiang explain {}`.",
+            code
+        )),
+        [first, ..] => Some(format!(
+            "Some errors have detailed explanations: {}.\nFor more information about an error, try `yaoxiang explain {}`.",
+            codes.join(", "),
+            first
+        )),
+    }
+}
+
+/// 一次 check 的错误与警告计数
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CheckSummary {
+    pub error_count: usize,
+    pub warning_count: usize,
+}
+
+impl CheckSummary {
+    /// 按 `--max-warnings` 上限判定退出状态
+    pub fn exit_status(
+        &self,
+        max_warnings: Option<usize>,
+    ) -> super::ExitStatus {
+        super::ExitStatus::from_counts(self.error_count, self.warning_count, max_warnings)
+    }
+}
+
+/// 运行一次 check，返回错误与警告计数
+#[cfg(feature = "cli")]
+pub fn run_check_command_summary(
+    paths: &[PathBuf],
+    excludes: &[PathBuf],
+    json: bool,
+    use_colors: bool,
+    no_progress: bool,
+) -> Result<CheckSummary> {
     let paths = normalize_check_paths(paths)?;
     let files = collect_yx_files_from_paths(&paths, excludes)?;
     if files.is_empty() {
@@ -43,6 +101,19 @@ pub fn run_check_command_once(
     }
 
     let result = check_files_with_diagnostics(&files)?;
+    report_check_result(result, files.len(), json, use_colors, no_progress)
+}
+
+/// 应用 lint 配置并输出一次 check 的诊断与汇总
+#[cfg(feature = "cli")]
+fn report_check_result(
+    mut result: CheckResult,
+    file_count: usize,
+    json: bool,
+    use_colors: bool,
+    no_progress: bool,
+) -> Result<CheckSummary> {
+    result.apply_lint_levels(&load_lint_config());
 
     if json {
         output_check_json(&result)?;
@@ -56,10 +127,13 @@ pub fn run_check_command_once(
             let output = emitter.render_with_source(&entry.diagnostic, source_file);
             eprintln!("\n{}", output);
         }
+        if let Some(hint) = explain_hint(&result) {
+            eprintln!("{}", hint);
+        }
 
         if !no_progress {
             if result.error_count == 0 {
-                eprintln!("Type check passed ({} file(s))", files.len());
+                eprintln!("Type check passed ({} file(s))", file_count);
             }
             eprintln!(
                 "Summary: {} error(s), {} warning(s)",
@@ -68,7 +142,33 @@ pub fn run_check_command_once(
         }
     }
 
-    Ok(result.error_count)
+    Ok(CheckSummary {
+        error_count: result.error_count,
+        warning_count: result.warning_count,
+    })
+}
+
+/// watch 模式下的一次检查：复用会话中未受影响的文件与函数
+#[cfg(feature = "cli")]
+fn run_watch_check(
+    session: &mut CheckSession,
+    paths: &[PathBuf],
+    excludes: &[PathBuf],
+    json: bool,
+    use_colors: bool,
+    no_progress: bool,
+) -> Result<CheckSummary> {
+    let files = collect_yx_files_from_paths(paths, excludes)?;
+    if files.is_empty() {
+        return Err(anyhow::anyhow!("No .yx files found in provided paths"));
+    }
+
+    if !json && !no_progress {
+        eprintln!("Checking {} file(s)...", files.len());
+    }
+
+    let result = session.check(&files)?;
+    report_check_result(result, files.len(), json, use_colors, no_progress)
 }
 
 #[cfg(feature = "cli")]
@@ -86,7 +186,18 @@ pub fn run_check_watch_command(
     let paths = normalize_check_paths(&paths)?;
     let excludes = normalize_exclude_paths(&excludes)?;
 
-    run_check_command_once(&paths, &excludes, json, use_colors, no_progress)?;
+    let mut session = CheckSession::new();
+    if let Some(dir) = artifact_dir_for(&paths) {
+        session = session.with_artifact_dir(dir);
+    }
+    run_watch_check(
+        &mut session,
+        &paths,
+        &excludes,
+        json,
+        use_colors,
+        no_progress,
+    )?;
 
     if !no_progress {
         eprintln!("Watching for changes... press Ctrl+C to stop");
@@ -149,33 +260,71 @@ pub fn run_check_watch_command(
             eprint!("\x1B[2J\x1B[H");
         }
 
-        let error_count = run_check_command_once(&paths, &excludes, json, use_colors, no_progress)?;
-        if !no_progress {
-            eprintln!("Last run: {} error(s)", error_count);
+        let started = Instant::now();
+        let outcome = run_watch_check(
+            &mut session,
+            &paths,
+            &excludes,
+            json,
+            use_colors,
+            no_progress,
+        );
+        let elapsed = started.elapsed().as_millis();
+        match outcome {
+            Ok(summary) if !no_progress => {
+                let stats = session.stats();
+                eprintln!(
+                    "Last run: {} error(s) in {} ms (re-checked {} of {} item(s))",
+                    summary.error_count,
+                    elapsed,
+                    stats.items_rechecked,
+                    stats.items_rechecked + stats.items_reused
+                );
+            }
+            Ok(_) => {}
+            Err(err) => eprintln!("error: {:#}", err),
         }
     }
 
     Ok(())
 }
 
+/// 渲染 `yaoxiang explain <CODE>` 的输出
+///
+/// 错误码不区分大小写；`lang_code` 缺省时使用当前诊断语言。
+/// 所选语言缺少的字段（说明、示例等）回落到英文。未知错误码返回 `None`。
 pub fn render_explain_output(
     code: &str,
     json: bool,
     lang_code: Option<&str>,
 ) -> Result<Option<String>> {
-    let Some(definition) = ErrorCodeDefinition::find(code) else {
+    let code = code.trim().to_ascii_uppercase();
+    let Some(definition) = ErrorCodeDefinition::find(&code) else {
         return Ok(None);
     };
 
-    let lang_code = lang_code.unwrap_or("zh");
+    let lang_code = lang_code.unwrap_or_else(|| crate::util::i18n::error_lang());
     let i18n = I18nRegistry::new(lang_code);
-    let info = i18n.get_info(code).unwrap_or(ErrorInfo {
+    let en = I18nRegistry::new("en");
+    let empty = ErrorInfo {
         title: "",
+        description: "",
         help: "",
         example: None,
         error_output: None,
-    });
-    let template = i18n.get_template(code).unwrap_or("");
+    };
+    let fallback = en.get_info(definition.code).unwrap_or(empty.clone());
+    let info = i18n.get_info(definition.code).unwrap_or(fallback.clone());
+    let non_empty = |s: &'static str, en: &'static str| if s.is_empty() { en } else { s };
+    let title = non_empty(info.title, fallback.title);
+    let description = non_empty(info.description, fallback.description);
+    let help = non_empty(info.help, fallback.help);
+    let example = info.example.or(fallback.example);
+    let error_output = info.error_output.or(fallback.error_output);
+    let template = i18n
+        .get_template(definition.code)
+        .or_else(|| en.get_template(definition.code))
+        .unwrap_or("");
 
     if json {
         #[derive(Serialize)]
@@ -183,6 +332,7 @@ pub fn render_explain_output(
             code: &'static str,
             category: String,
             title: &'a str,
+            description: &'a str,
             template: &'a str,
             help: &'a str,
             example: Option<&'a str>,
@@ -192,34 +342,76 @@ pub fn render_explain_output(
         let output = ExplainOutput {
             code: definition.code,
             category: definition.category.to_string(),
-            title: info.title,
+            title,
+            description,
             template,
-            help: info.help,
-            example: info.example,
-            error_output: info.error_output,
+            help,
+            example,
+            error_output,
         };
 
         Ok(Some(serde_json::to_string_pretty(&output)?))
     } else {
+        let kind = if definition.code.starts_with('W') {
+            "Warning"
+        } else {
+            "Error"
+        };
         let mut lines = vec![
-            format!("Error {}", definition.code),
+            format!("{} {}: {}", kind, definition.code, title),
             format!("Category: {}", definition.category),
-            format!("Title: {}", info.title),
-            format!("Message Template: {}", template),
         ];
-        if !info.help.is_empty() {
-            lines.push(format!("Help: {}", info.help));
+        if !description.is_empty() {
+            lines.push(format!("\n{}", description));
         }
-        if let Some(example) = info.example {
-            lines.push(format!("\nExample:\n{}", example));
+        lines.push(format!("\nMessage Template: {}", template));
+        if !help.is_empty() {
+            lines.push(format!("Help: {}", help));
         }
-        if let Some(output) = info.error_output {
-            lines.push(format!("\nExpected Output:\n{}", output));
+        if let Some(example) = example {
+            lines.push(format!("\nExample:\n{}", indent_block(example)));
+        }
+        if let Some(output) = error_output {
+            lines.push(format!("\nExpected Output:\n{}", indent_block(output)));
         }
         Ok(Some(lines.join("\n")))
     }
 }
 
+/// 渲染错误码索引：按类别列出全部错误码及标题
+pub fn render_error_index(lang_code: Option<&str>) -> String {
+    let lang_code = lang_code.unwrap_or_else(|| crate::util::i18n::error_lang());
+    let i18n = I18nRegistry::new(lang_code);
+    let en = I18nRegistry::new("en");
+
+    let mut out = String::new();
+    let mut current = None;
+    for definition in ErrorCodeDefinition::all() {
+        if current != Some(definition.category) {
+            current = Some(definition.category);
+            if !out.is_empty() {
+                out.push('\n');
+            }
+            out.push_str(&format!("{}:\n", definition.category));
+        }
+        let title = i18n
+            .get_info(definition.code)
+            .or_else(|| en.get_info(definition.code))
+            .map_or("", |info| info.title);
+        out.push_str(&format!("  {}  {}\n", definition.code, title));
+    }
+    out.push_str("\nRun `yaoxiang explain <CODE>` for details.");
+    out
+}
+
+/// 示例代码缩进四格，便于与说明文字区分
+fn indent_block(text: &str) -> String {
+    text.lines()
+        .map(|line| format!("    {}", line))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 fn collect_yx_files_from_paths(
     paths: &[PathBuf],
     excludes: &[PathBuf],
@@ -291,6 +483,16 @@ fn default_check_path() -> Result<PathBuf> {
     Ok(cwd)
 }
 
+/// 增量检查产物目录：所在项目的 `.yaoxiang/cache/typecheck`，项目外不持久化
+#[cfg(feature = "cli")]
+fn artifact_dir_for(paths: &[PathBuf]) -> Option<PathBuf> {
+    let first = paths.first()?;
+    first
+        .ancestors()
+        .find(|dir| dir.join(crate::package::manifest::MANIFEST_FILE).exists())
+        .map(|root| root.join(".yaoxiang").join("cache").join("typecheck"))
+}
+
 #[cfg(feature = "cli")]
 fn normalize_check_paths(paths: &[PathBuf]) -> Result<Vec<PathBuf>> {
     if paths.is_empty() {
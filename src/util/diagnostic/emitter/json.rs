@@ -56,7 +56,7 @@ pub struct LspRelatedDiagnosticInformation {
 pub struct LspCodeAction {
     pub title: String,
     pub kind: Option<String>,
-    pub edit: Option<LspTextEdit>,
+    pub edits: Vec<LspTextEdit>,
     pub command: Option<LspCommand>,
     pub is_preferred: bool,
 }
@@ -142,10 +142,37 @@ impl JsonEmitter {
             message: diagnostic.message.clone(),
             related_information: None,
             tags: None,
-            code_actions: None,
+            code_actions: Self::code_actions(diagnostic),
         }
     }
 
+    /// 将结构化修复建议转换为快速修复
+    fn code_actions(diagnostic: &Diagnostic) -> Option<Vec<LspCodeAction>> {
+        if diagnostic.fixes.is_empty() {
+            return None;
+        }
+        let actions = diagnostic
+            .fixes
+            .iter()
+            .enumerate()
+            .map(|(i, fix)| LspCodeAction {
+                title: fix.message.clone(),
+                kind: Some("quickfix".to_string()),
+                edits: fix
+                    .edits
+                    .iter()
+                    .map(|edit| LspTextEdit {
+                        range: Self::span_to_range(Some(&edit.span)),
+                        new_text: edit.replacement.clone(),
+                    })
+                    .collect(),
+                command: None,
+                is_preferred: i == 0,
+            })
+            .collect();
+        Some(actions)
+    }
+
     /// 转换 Span 到 LSP Range
     fn span_to_range(span: Option<&Span>) -> LspRange {
         if let Some(s) = span {
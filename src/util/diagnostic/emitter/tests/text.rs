@@ -3,9 +3,11 @@
 //! §4.1: Emitter 合并（RichEmitter → TextEmitter）
 
 use crate::util::diagnostic::emitter::{TextEmitter, EmitterConfig};
+use crate::util::diagnostic::emitter::text::elide_middle;
 use crate::util::diagnostic::emitter::ansi::strip_ansi;
 use crate::util::diagnostic::codes::ErrorCodeDefinition;
-use crate::util::span::Span;
+use crate::util::diagnostic::{Fix, FixEdit};
+use crate::util::span::{Position, SourceFile, Span};
 
 #[test]
 fn test_text_emitter_render_basic_error() {
@@ -51,3 +53,109 @@ fn test_text_emitter_config_disables_colors() {
 
     assert!(!output.contains("\x1b[31m"), "colors should be disabled");
 }
+
+#[test]
+fn test_text_emitter_clips_long_line_around_span() {
+    // 压缩代码：一行 10000 个字符，错误位于中间
+    let source = format!("{}bad{}", "a".repeat(5000), "b".repeat(4997));
+    let source_file = SourceFile::new("min.yx".to_string(), source);
+    let diagnostic = ErrorCodeDefinition::unknown_variable("bad")
+        .at(Span::new(
+            Position::with_offset(1, 5001, 5000),
+            Position::with_offset(1, 5004, 5003),
+        ))
+        .build();
+
+    let config = EmitterConfig {
+        use_colors: false,
+        max_line_width: 40,
+        ..Default::default()
+    };
+    let output =
+        TextEmitter::with_config(config).render_with_source(&diagnostic, Some(&source_file));
+
+    let lines: Vec<&str> = output.lines().collect();
+    let snippet = lines
+        .iter()
+        .find(|l| l.contains("│") && l.contains("bad"))
+        .expect("snippet line");
+    assert!(snippet.chars().count() < 60, "{}", snippet);
+    assert!(snippet.contains("…a"), "{}", snippet);
+    assert!(snippet.ends_with("b…"), "{}", snippet);
+
+    // 指示符与窗口中的 `bad` 对齐
+    let indicator = lines
+        .iter()
+        .find(|l| l.contains("^^^"))
+        .expect("indicator line");
+    let bad_col = snippet.chars().position(|c| c == 'd').unwrap() - 2;
+    let caret_col = indicator.chars().position(|c| c == '^').unwrap();
+    assert_eq!(bad_col, caret_col, "\n{}\n{}", snippet, indicator);
+}
+
+#[test]
+fn test_text_emitter_keeps_short_lines_intact() {
+    let source_file = SourceFile::new("ok.yx".to_string(), "x = y + 1".to_string());
+    let diagnostic = ErrorCodeDefinition::unknown_variable("y")
+        .at(Span::new(
+            Position::with_offset(1, 5, 4),
+            Position::with_offset(1, 6, 5),
+        ))
+        .build();
+
+    let emitter = TextEmitter::with_config(EmitterConfig {
+        use_colors: false,
+        ..Default::default()
+    });
+    let output = emitter.render_with_source(&diagnostic, Some(&source_file));
+
+    assert!(output.contains("x = y + 1\n"), "{}", output);
+    assert!(!output.contains('…'), "{}", output);
+}
+
+#[test]
+fn test_elide_middle_keeps_head_and_tail() {
+    assert_eq!(elide_middle("short", 10), "short");
+    let long = format!("{}{}", "a".repeat(50), "z".repeat(50));
+    assert_eq!(elide_middle(&long, 11), "aaaaa…zzzzz");
+}
+
+#[test]
+fn test_text_emitter_renders_fix_with_patched_line() {
+    let source_file = SourceFile::new("fix.yx".to_string(), "y = noww()".to_string());
+    let span = Span::new(
+        Position::with_offset(1, 5, 4),
+        Position::with_offset(1, 9, 8),
+    );
+    let start = Position::with_offset(1, 1, 0);
+    let diagnostic = ErrorCodeDefinition::unknown_variable("noww")
+        .at(span)
+        .fix(Fix {
+            message: "did you mean `now` from `std.time`?".to_string(),
+            edits: vec![
+                FixEdit {
+                    span: Span::new(start, start),
+                    replacement: "use std.time.{now}\n".to_string(),
+                },
+                FixEdit {
+                    span,
+                    replacement: "now".to_string(),
+                },
+            ],
+        })
+        .build();
+
+    let emitter = TextEmitter::with_config(EmitterConfig {
+        use_colors: false,
+        ..Default::default()
+    });
+    let output = emitter.render_with_source(&diagnostic, Some(&source_file));
+
+    assert!(
+        output.contains("help: did you mean `now` from `std.time`?\n"),
+        "{}",
+        output
+    );
+    assert!(output.contains("+ │ use std.time.{now}\n"), "{}", output);
+    assert!(output.contains("1 │ y = now()\n"), "{}", output);
+}
@@ -1,7 +1,9 @@
 //! 诊断渲染器
 
+use std::borrow::Cow;
+
 use crate::util::span::SourceFile;
-use crate::util::diagnostic::Diagnostic;
+use crate::util::diagnostic::{Diagnostic, Fix};
 use crate::util::diagnostic::Severity;
 
 /// 渲染器配置
@@ -25,6 +27,8 @@ pub struct EmitterConfig {
     pub indicator: char,
     /// 最大显示行数
     pub max_lines: usize,
+    /// 源码行最大显示宽度（字符数），超出部分以省略号代替；0 表示不限制
+    pub max_line_width: usize,
 }
 
 impl Default for EmitterConfig {
@@ -39,6 +43,7 @@ impl Default for EmitterConfig {
             symbols: false,
             indicator: '^',
             max_lines: 6,
+            max_line_width: 120,
         }
     }
 }
@@ -108,6 +113,9 @@ impl TextEmitter {
                 output.push_str(&help);
                 output.push('\n');
             }
+            for fix in &diagnostic.fixes {
+                output.push_str(&self.render_fix(fix, source_file));
+            }
         }
 
         // 5. 渲染相关诊断
@@ -167,15 +175,6 @@ impl TextEmitter {
         }
     }
 
-    /// 获取源码行
-    fn get_source_line(
-        source_file: &SourceFile,
-        line_num: usize,
-    ) -> Option<String> {
-        let lines: Vec<&str> = source_file.content.lines().collect();
-        lines.get(line_num - 1).map(|s| s.to_string())
-    }
-
     /// 渲染源码片段
     fn render_source_snippet(
         &self,
@@ -197,23 +196,27 @@ impl TextEmitter {
 
         for i in 0..lines_to_show {
             let line_num = start_line + i;
-            if let Some(line) = Self::get_source_line(source_file, line_num) {
+            // 借用源码行，只为可见窗口分配内存
+            if let Some(line) = source_file.line_text(line_num) {
+                let focus = if i == 0 { span.start.column - 1 } else { 0 };
+                let focus_len = if i == 0 && start_line == end_line {
+                    (span.end.column - span.start.column).max(1)
+                } else {
+                    line.chars().count().saturating_sub(focus).max(1)
+                };
+                let (text, column, indicator_len) = self.clip_line(line, focus, focus_len);
+
                 if self.config.show_line_numbers {
                     output.push_str(&format!("{:>4} {} ", line_num, self.vbar()));
                 } else {
                     output.push_str(&format!("     {} ", self.vbar()));
                 }
-                output.push_str(&line);
+                output.push_str(&text);
                 output.push('\n');
 
                 // 如果是第一行，添加错误指示
                 if i == 0 {
-                    let spaces = " ".repeat(span.start.column - 1);
-                    let indicator_len = if start_line == end_line {
-                        (span.end.column - span.start.column).max(1)
-                    } else {
-                        line.len().saturating_sub(span.start.column - 1).max(1)
-                    };
+                    let spaces = " ".repeat(column);
                     let indicators = self.config.indicator.to_string().repeat(indicator_len);
 
                     output.push_str(&format!("     {} {}{}\n", self.vbar(), spaces, indicators));
@@ -224,6 +227,41 @@ impl TextEmitter {
         Some(output)
     }
 
+    /// 截取超长行中以 `focus`（从 0 开始的字符列）为中心的窗口
+    ///
+    /// 返回窗口文本、`focus` 在窗口中的列以及截断后的指示符长度。
+    /// 压缩代码、生成文件等单行输入由此保持片段宽度有界。
+    fn clip_line<'a>(
+        &self,
+        line: &'a str,
+        focus: usize,
+        focus_len: usize,
+    ) -> (Cow<'a, str>, usize, usize) {
+        let width = self.config.max_line_width;
+        let total = line.chars().count();
+        if width == 0 || total <= width {
+            return (Cow::Borrowed(line), focus, focus_len);
+        }
+
+        // 指示位置之前保留四分之一宽度作为上下文
+        let start = focus.saturating_sub(width / 4).min(total - width);
+        let end = start + width;
+        let ellipsis = self.ellipsis();
+
+        let mut text = String::with_capacity(width + 2 * ellipsis.len());
+        let mut column = focus - start;
+        if start > 0 {
+            text.push_str(ellipsis);
+            column += ellipsis.chars().count();
+        }
+        text.extend(line.chars().skip(start).take(width));
+        if end < total {
+            text.push_str(ellipsis);
+        }
+        let indicator_len = focus_len.min(end.saturating_sub(focus)).max(1);
+        (Cow::Owned(text), column, indicator_len)
+    }
+
     /// 渲染帮助信息
     fn render_help(
         &self,
@@ -236,6 +274,54 @@ impl TextEmitter {
         Some(diagnostic.help.clone())
     }
 
+    /// 渲染修复建议：说明 + 应用编辑后的源码行
+    ///
+    /// 插入的整行以 `+` 标出，行内替换显示替换后的行。
+    fn render_fix(
+        &self,
+        fix: &Fix,
+        source_file: Option<&SourceFile>,
+    ) -> String {
+        let mut output = format!("help: {}\n", fix.message);
+        let Some(source_file) = source_file.filter(|_| self.config.show_source) else {
+            return output;
+        };
+
+        for edit in &fix.edits {
+            let span = &edit.span;
+            if span.is_dummy() || span.start.line != span.end.line {
+                continue;
+            }
+            if span.start.column == span.end.column && edit.replacement.ends_with('\n') {
+                for line in edit.replacement.lines() {
+                    output.push_str(&format!("   + {} {}\n", self.vbar(), line));
+                }
+                continue;
+            }
+            let Some(line) = source_file.line_text(span.start.line) else {
+                continue;
+            };
+            let start = span.start.column - 1;
+            let end = span.end.column - 1;
+            let patched: String = line
+                .chars()
+                .take(start)
+                .chain(edit.replacement.chars())
+                .chain(line.chars().skip(end))
+                .collect();
+            let (text, _, _) =
+                self.clip_line(&patched, start, edit.replacement.chars().count().max(1));
+            if self.config.show_line_numbers {
+                output.push_str(&format!("{:>4} {} ", span.start.line, self.vbar()));
+            } else {
+                output.push_str(&format!("     {} ", self.vbar()));
+            }
+            output.push_str(&text);
+            output.push('\n');
+        }
+        output
+    }
+
     /// 简单的颜色渲染
     fn color(
         &self,
@@ -256,12 +342,40 @@ impl TextEmitter {
         }
     }
 
+    fn ellipsis(&self) -> &'static str {
+        if self.config.unicode {
+            "…"
+        } else {
+            "..."
+        }
+    }
+
     #[allow(dead_code)]
     fn hint_prefix(&self) -> String {
         self.color("hint", "hint: ")
     }
 }
 
+/// 超过 `max_chars` 个字符时保留首尾、以 `…` 省略中间部分
+///
+/// 用于把源码片段嵌入诊断消息，避免压缩代码中的长表达式撑满整条消息。
+pub fn elide_middle(
+    text: &str,
+    max_chars: usize,
+) -> Cow<'_, str> {
+    let total = text.chars().count();
+    if total <= max_chars {
+        return Cow::Borrowed(text);
+    }
+    let keep = max_chars.saturating_sub(1);
+    let head = keep - keep / 2;
+    let tail = keep / 2;
+    let mut out: String = text.chars().take(head).collect();
+    out.push('…');
+    out.extend(text.chars().skip(total - tail));
+    Cow::Owned(out)
+}
+
 impl Default for TextEmitter {
     fn default() -> Self {
         Self::new()
@@ -28,10 +28,12 @@
 //!     .build(&i18n);
 //! ```
 
+use serde::{Deserialize, Serialize};
+
 use crate::util::span::Span;
 
 /// 诊断严重级别
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Severity {
     Error,
     Warning,
@@ -88,7 +90,7 @@ impl std::fmt::Display for Severity {
 ///
 /// **不可直接构造**。必须通过 `DiagnosticBuilder::build()` 创建，
 /// 确保所有错误码都经过注册表验证。
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Diagnostic {
     /// 严重级别
     pub severity: Severity,
@@ -102,6 +104,26 @@ pub struct Diagnostic {
     pub span: Option<Span>,
     /// 相关诊断
     pub related: Vec<Box<Diagnostic>>,
+    /// 结构化修复建议（fix-it），编辑器与 `check --format json` 可直接应用
+    pub fixes: Vec<Fix>,
+}
+
+/// 结构化修复建议
+///
+/// 一条建议由若干源码编辑组成，需整体应用（如同时改名并插入 `use`）。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Fix {
+    /// 建议说明（已渲染），如 "did you mean `count`?"
+    pub message: String,
+    /// 源码编辑
+    pub edits: Vec<FixEdit>,
+}
+
+/// 单个源码编辑：用 `replacement` 替换 `span` 覆盖的文本（空 span 表示插入）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FixEdit {
+    pub span: Span,
+    pub replacement: String,
 }
 
 impl Diagnostic {
@@ -122,6 +144,7 @@ impl Diagnostic {
             help,
             span,
             related: Vec::new(),
+            fixes: Vec::new(),
         }
     }
 
@@ -141,6 +164,7 @@ impl Diagnostic {
             help,
             span,
             related: Vec::new(),
+            fixes: Vec::new(),
         }
     }
 
@@ -160,6 +184,7 @@ impl Diagnostic {
             help,
             span,
             related: Vec::new(),
+            fixes: Vec::new(),
         }
     }
 
@@ -179,6 +204,7 @@ impl Diagnostic {
             help,
             span,
             related: Vec::new(),
+            fixes: Vec::new(),
         }
     }
 
@@ -190,6 +216,15 @@ impl Diagnostic {
         self.related = related.into_iter().map(Box::new).collect();
         self
     }
+
+    /// 添加修复建议
+    pub(crate) fn with_fixes(
+        mut self,
+        fixes: Vec<Fix>,
+    ) -> Self {
+        self.fixes = fixes;
+        self
+    }
 }
 
 impl crate::util::span::SpannedError for Diagnostic {
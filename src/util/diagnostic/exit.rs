@@ -0,0 +1,67 @@
+//! CLI 退出码
+//!
+//! 所有子命令共用同一套约定，CI 可据此区分"代码有问题"与"工具出了问题"：
+//!
+//! | 退出码 | 含义 |
+//! |--------|------|
+//! | 0 | 成功 |
+//! | 1 | 源码诊断：编译/运行时错误、警告数超过 `--max-warnings`、`format --dry-run` 发现未格式化文件 |
+//! | 2 | 内部错误：参数、IO、找不到输入文件等工具自身的失败 |
+//! | 101 | 编译器 panic |
+
+use crate::backends::ExecutorError;
+use crate::frontend::CompileError;
+
+/// 进程退出状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitStatus {
+    Success,
+    Diagnostics,
+    Internal,
+    Panic,
+}
+
+impl ExitStatus {
+    /// 进程退出码
+    pub fn code(self) -> i32 {
+        match self {
+            ExitStatus::Success => 0,
+            ExitStatus::Diagnostics => 1,
+            ExitStatus::Internal => 2,
+            ExitStatus::Panic => 101,
+        }
+    }
+
+    /// 由诊断计数判定状态；`max_warnings` 为 None 时警告不影响结果
+    pub fn from_counts(
+        error_count: usize,
+        warning_count: usize,
+        max_warnings: Option<usize>,
+    ) -> Self {
+        let too_many_warnings = max_warnings.is_some_and(|max| warning_count > max);
+        if error_count > 0 || too_many_warnings {
+            ExitStatus::Diagnostics
+        } else {
+            ExitStatus::Success
+        }
+    }
+
+    /// 子命令返回的错误对应的状态
+    ///
+    /// 源码本身的编译错误、运行时错误归为诊断，其余都是内部错误。
+    pub fn of_error(error: &anyhow::Error) -> Self {
+        if error.downcast_ref::<DiagnosticsReported>().is_some()
+            || error.downcast_ref::<CompileError>().is_some()
+            || error.downcast_ref::<ExecutorError>().is_some()
+        {
+            ExitStatus::Diagnostics
+        } else {
+            ExitStatus::Internal
+        }
+    }
+}
+
+/// 诊断已经渲染给用户，上层只需以 [`ExitStatus::Diagnostics`] 退出，不再重复打印
+#[derive(Debug, thiserror::Error)]
+#[error("{0}")]
+pub struct DiagnosticsReported(pub &'static str);
@@ -10,6 +10,8 @@
 //! - suggest - 智能建议引擎
 //! - collect - 错误收集器
 //! - result - 统一 Result 类型
+//! - exit - CLI 退出码约定
+//! - panic - 运行时错误对象（类别、消息与源码调用栈）
 //!
 //! # 示例
 //!
@@ -26,6 +28,8 @@ pub mod collect;
 pub mod command;
 pub mod emitter;
 pub mod error;
+pub mod exit;
+pub mod panic;
 #[macro_use]
 pub mod error_macro;
 pub mod result;
@@ -35,17 +39,20 @@ pub mod suggest;
 // 重新导出
 pub use codes::{ErrorCategory, ErrorCodeDefinition, I18nRegistry, DiagnosticBuilder, ErrorInfo};
 pub use collect::{ErrorCollector, Warning, ErrorFormatter};
-pub use command::render_explain_output;
+pub use command::{render_error_index, render_explain_output, CheckSummary};
 #[cfg(feature = "cli")]
-pub use command::{run_check_command_once, run_check_watch_command};
+pub use command::{run_check_command_once, run_check_command_summary, run_check_watch_command};
 pub use emitter::{TextEmitter, JsonEmitter, EmitterConfig};
-pub use error::{Diagnostic, Severity};
+pub use error::{Diagnostic, Fix, FixEdit, Severity};
+pub use exit::{DiagnosticsReported, ExitStatus};
+pub use panic::{PanicKind, RuntimePanic, SourceLocation, TraceFrame};
 pub use result::{Result, ResultExt};
 pub use session::CheckSession;
-pub use suggest::SuggestionEngine;
+pub use suggest::{Candidate, SuggestionEngine, SymbolSource};
 
 // 渲染器
 use crate::util::span::{DebugSpan, SourceFile, SourceMap};
+use std::borrow::Cow;
 use std::collections::HashMap;
 
 /// 单个检查诊断（包含所属文件）
@@ -64,6 +71,35 @@ pub struct CheckResult {
     pub warning_count: usize,
 }
 
+impl CheckResult {
+    /// 按 `[lint]` 配置调整警告：`off` 丢弃，`deny` 升级为错误，并重新计数
+    pub fn apply_lint_levels(
+        &mut self,
+        lint: &crate::util::config::LintConfig,
+    ) {
+        self.diagnostics.retain_mut(|entry| {
+            if entry.diagnostic.severity != Severity::Warning {
+                return true;
+            }
+            let level = lint.level_for(&entry.diagnostic.code);
+            if level.is_deny() {
+                entry.diagnostic.severity = Severity::Error;
+            }
+            level.is_enabled()
+        });
+        self.error_count = self
+            .diagnostics
+            .iter()
+            .filter(|entry| entry.diagnostic.severity.is_error())
+            .count();
+        self.warning_count = self
+            .diagnostics
+            .iter()
+            .filter(|entry| !entry.diagnostic.severity.is_error())
+            .count();
+    }
+}
+
 /// 渲染编译错误
 ///
 /// 从错误消息解析并渲染为 Rust 风格的诊断输出
@@ -94,36 +130,7 @@ pub fn render_runtime_error(
     module: &crate::middle::bytecode::BytecodeModule,
     sources: Option<&SourceMap>,
 ) -> String {
-    let emitter = TextEmitter::new();
-
-    let primary_span = error
-        .stack_trace()
-        .and_then(|stack| stack.first())
-        .and_then(|frame| resolve_runtime_span(module, frame))
-        .filter(|span| !span.is_dummy());
-
-    let primary_source = primary_span.and_then(|ds| sources.and_then(|sm| sm.get(ds.file_id)));
-    let diagnostic = build_runtime_diagnostic(error, primary_span, primary_source);
-
-    let mut output = emitter.render_with_source(&diagnostic, primary_source);
-    let stack_text = format_runtime_stack_trace(error, module, sources);
-    if !stack_text.is_empty() {
-        output.push('\n');
-        output.push_str(&stack_text);
-    }
-
-    output
-}
-
-fn resolve_runtime_span(
-    module: &crate::middle::bytecode::BytecodeModule,
-    frame: &crate::backends::StackFrame,
-) -> Option<DebugSpan> {
-    module
-        .functions
-        .iter()
-        .find(|f| f.name == frame.function_name)
-        .and_then(|f| f.debug_map.get(&frame.ip).copied())
+    RuntimePanic::with_sources(error.clone(), module, sources).render()
 }
 
 fn build_runtime_diagnostic(
@@ -137,20 +144,19 @@ fn build_runtime_diagnostic(
         ExecutorError::FunctionNotFound(name, _) => {
             ErrorCodeDefinition::runtime_function_not_found(name.as_str())
         }
-        ExecutorError::DivisionByZero(_) => {
-            let expr = primary_span
-                .and_then(|ds| {
-                    source_file
-                        .and_then(|sf| sf.source_text(ds.span))
-                        .map(|s| s.trim())
-                })
-                .filter(|s| !s.is_empty())
-                .unwrap_or("<unknown>");
-            ErrorCodeDefinition::division_by_zero(expr)
+        ExecutorError::DivisionByZero(_) => ErrorCodeDefinition::division_by_zero(&span_text(
+            primary_span,
+            source_file,
+            "<unknown>",
+        )),
+        // 运算数比源码片段更有用，源码位置由下方的标注给出
+        ExecutorError::IntegerOverflow(operation, _) => {
+            ErrorCodeDefinition::integer_overflow(operation)
         }
         ExecutorError::Runtime(message, _) => ErrorCodeDefinition::runtime_error(message.as_str()),
         ExecutorError::Type(message, _) => ErrorCodeDefinition::runtime_error(message.as_str()),
-        ExecutorError::StackOverflow(_) => ErrorCodeDefinition::stack_overflow(0),
+        ExecutorError::StackOverflow(limit, _) => ErrorCodeDefinition::stack_overflow(*limit),
+        ExecutorError::Interrupted(signal, _) => ErrorCodeDefinition::interrupted(signal),
         other => ErrorCodeDefinition::runtime_error(&other.to_string()),
     };
 
@@ -161,93 +167,108 @@ fn build_runtime_diagnostic(
     builder.build()
 }
 
-fn format_runtime_stack_trace(
-    error: &crate::backends::ExecutorError,
-    module: &crate::middle::bytecode::BytecodeModule,
-    sources: Option<&SourceMap>,
-) -> String {
-    let Some(stack) = error.stack_trace() else {
-        return String::new();
-    };
-
-    let mut out = String::from("stack trace:\n");
-    for frame in stack {
-        if let Some(ds) = resolve_runtime_span(module, frame).filter(|s| !s.is_dummy()) {
-            let loc = match sources.and_then(|sm| sm.get(ds.file_id)) {
-                Some(sf) => format!(
-                    "{}:{}:{}",
-                    sf.name, ds.span.start.line, ds.span.start.column
-                ),
-                None => format!("{}:{}", ds.span.start.line, ds.span.start.column),
-            };
-            out.push_str(&format!(
-                "  at {} ({}) (ip: {})\n",
-                frame.function_name, loc, frame.ip
-            ));
-        } else {
-            out.push_str(&format!(
-                "  at {} (ip: {})\n",
-                frame.function_name, frame.ip
-            ));
-        }
+/// 被信号中断时程序的退出码（128 + 信号编号）
+fn interrupted_exit_code(error: &crate::backends::ExecutorError) -> Option<i32> {
+    match error {
+        crate::backends::ExecutorError::Interrupted(signal, _) => Some(
+            crate::std::signal::Signal::from_name(signal)
+                .map(|s| s.exit_code())
+                .unwrap_or(130),
+        ),
+        _ => None,
     }
-    out
+}
+
+/// 嵌入诊断消息的源码片段的最大字符数
+const MAX_SNIPPET_CHARS: usize = 80;
+
+/// 运行时错误位置对应的源码片段，取不到时使用 `fallback`
+///
+/// 过长的片段（如压缩代码中的整条表达式）省略中间部分。
+fn span_text<'a>(
+    primary_span: Option<DebugSpan>,
+    source_file: Option<&'a SourceFile>,
+    fallback: &'a str,
+) -> Cow<'a, str> {
+    let text = primary_span
+        .and_then(|ds| {
+            source_file
+                .and_then(|sf| sf.source_text(ds.span))
+                .map(|s| s.trim())
+        })
+        .filter(|s| !s.is_empty())
+        .unwrap_or(fallback);
+    emitter::text::elide_middle(text, MAX_SNIPPET_CHARS)
+}
+
+/// [`run_file_with_diagnostics`] 的选项
+#[cfg(feature = "cli")]
+#[derive(Debug, Clone, Default)]
+pub struct RunOptions {
+    /// 为源文件生成行号表，运行时错误定位到源码
+    pub debug_info: bool,
+    /// 执行器配置：构建模式、堆转储、JIT、GC 阈值与能力策略
+    pub executor: crate::backends::ExecutorConfig,
+    /// 运行时层级、工作线程数与工作窃取
+    pub runtime: crate::backends::interpreter::runtime::InterpreterRuntimeConfig,
+    /// 编译配置
+    pub compile: crate::frontend::CompileConfig,
 }
 
 /// 运行文件并美化错误输出
 ///
-/// # 参数
-/// - `file`: 源文件路径
+/// `.42` / `.yxbc` 字节码文件跳过编译直接执行，其余文件按 `options.compile` 编译后执行。
 ///
 /// # 返回
-/// 成功返回 `()`，失败返回错误
+/// 成功返回程序的退出码（`main` 返回的 Int 或 `exit(n)`，默认 0；
+/// 被 SIGINT/SIGTERM 中断时为 130/143），失败返回错误
 #[cfg(feature = "cli")]
 pub fn run_file_with_diagnostics(
     file: &std::path::PathBuf,
-    debug_info: bool,
-    runtime_mode: &str,
-    workers: usize,
-) -> anyhow::Result<()> {
+    options: RunOptions,
+) -> anyhow::Result<i32> {
     use crate::frontend::Compiler;
     use crate::middle::passes::codegen::CodegenContext;
     use crate::Executor;
     use crate::Interpreter;
 
-    // 检测 .42 字节码文件，跳过编译直接执行
-    if file.extension().map(|e| e == "42").unwrap_or(false) {
-        let bytecode_file = crate::middle::passes::codegen::BytecodeFile::load(file)
+    let RunOptions {
+        debug_info,
+        executor: executor_config,
+        runtime: runtime_config,
+        compile: compile_config,
+    } = options;
+
+    // 安装项目依赖随附的原生扩展（须在编译前完成，模块注册表会读取它们）
+    crate::backends::interpreter::extension::load_for_file(file, &executor_config.capabilities)
+        .map_err(|e| anyhow::anyhow!("{}", e))?;
+
+    // 检测 .42 / .yxbc 字节码文件，跳过编译直接执行
+    if crate::middle::passes::codegen::is_bytecode_path(file) {
+        // 函数体按需解码，启动时只读取索引表与入口函数
+        let bytecode_file = crate::middle::passes::codegen::LazyBytecodeFile::open(file)
             .map_err(|e| anyhow::anyhow!("Failed to load bytecode file: {}", e))?;
-        let bytecode_module = crate::middle::bytecode::BytecodeModule::from(bytecode_file);
-
-        let mut interp = crate::backends::interpreter::Interpreter::new();
-        let rt_mode = match runtime_mode {
-            "standard" => crate::backends::runtime::RuntimeMode::Standard,
-            "full" => crate::backends::runtime::RuntimeMode::Full,
-            _ => crate::backends::runtime::RuntimeMode::Embedded,
-        };
-        let effective_workers = if workers > 0 {
-            workers
-        } else {
-            std::thread::available_parallelism()
-                .map(|n| n.get())
-                .unwrap_or(4)
-        };
-        interp.set_runtime_config(
-            crate::backends::interpreter::runtime::InterpreterRuntimeConfig {
-                runtime: rt_mode,
-                workers: effective_workers,
-                work_stealing: false,
-            },
-        );
+        // 带调试段的字节码文件自带源文件，运行时错误可以定位到源码
+        let sources = bytecode_file.sources().cloned();
+        let bytecode_module = crate::middle::bytecode::BytecodeModule::try_from(bytecode_file)
+            .map_err(|e| anyhow::anyhow!("Failed to load bytecode file: {}", e))?;
+
+        let mut interp = Interpreter::with_config(executor_config);
+        if let Some(dir) = file.parent() {
+            interp.set_import_base_dir(dir);
+        }
+        interp.set_runtime_config(runtime_config);
         let mut executor: Box<dyn crate::backends::Executor> = Box::new(interp);
         if let Err(e) = executor.execute_module(&bytecode_module) {
             eprintln!();
-            // 字节码加载模式下无 SourceMap，传入 None
-            let output = render_runtime_error(&e, &bytecode_module, None);
-            eprintln!("{}", output);
-            return Err(anyhow::anyhow!("Runtime error"));
+            let panic = RuntimePanic::with_sources(e, &bytecode_module, sources.as_ref());
+            eprintln!("{}", panic.render());
+            if let Some(code) = interrupted_exit_code(panic.error()) {
+                return Ok(code);
+            }
+            return Err(DiagnosticsReported("Runtime error").into());
         }
-        return Ok(());
+        return Ok(executor.state().exit_code);
     }
 
     let source = match std::fs::read_to_string(file) {
@@ -268,9 +289,16 @@ pub fn run_file_with_diagnostics(
         .get(entry_file_id)
         .ok_or_else(|| anyhow::anyhow!("Failed to load source file"))?;
 
-    let mut compiler = Compiler::new();
+    let mut compiler = Compiler::with_config(compile_config);
     match compiler.compile(&source_file.name, &source_file.content) {
         Ok(module) => {
+            if compiler.config().timings {
+                eprint!(
+                    "{}",
+                    crate::middle::passes::manager::format_timings(compiler.pass_timings())
+                );
+            }
+
             // Generate bytecode
             let mut ctx = CodegenContext::new(module);
             ctx.set_generate_debug_info(debug_info);
@@ -280,50 +308,38 @@ pub fn run_file_with_diagnostics(
             let bytecode_module = crate::middle::bytecode::BytecodeModule::from(bytecode_file);
 
             // Execute
-            let mut interp = Interpreter::new();
-            let rt_mode = match runtime_mode {
-                "standard" => crate::backends::runtime::RuntimeMode::Standard,
-                "full" => crate::backends::runtime::RuntimeMode::Full,
-                _ => crate::backends::runtime::RuntimeMode::Embedded,
-            };
-            let effective_workers = if workers > 0 {
-                workers
-            } else {
-                std::thread::available_parallelism()
-                    .map(|n| n.get())
-                    .unwrap_or(4)
-            };
-            interp.set_runtime_config(
-                crate::backends::interpreter::runtime::InterpreterRuntimeConfig {
-                    runtime: rt_mode,
-                    workers: effective_workers,
-                    work_stealing: false,
-                },
-            );
+            let mut interp = Interpreter::with_config(executor_config);
+            if let Some(dir) = file.parent() {
+                interp.set_import_base_dir(dir);
+            }
+            interp.set_runtime_config(runtime_config);
             let mut executor: Box<dyn Executor> = Box::new(interp);
             if let Err(e) = executor.execute_module(&bytecode_module) {
                 eprintln!();
-                let output = render_runtime_error(&e, &bytecode_module, Some(&sources));
-                eprintln!("{}", output);
-                return Err(anyhow::anyhow!("Runtime error"));
+                let panic = RuntimePanic::with_sources(e, &bytecode_module, Some(&sources));
+                eprintln!("{}", panic.render());
+                if let Some(code) = interrupted_exit_code(panic.error()) {
+                    return Ok(code);
+                }
+                return Err(DiagnosticsReported("Runtime error").into());
             }
+            Ok(executor.state().exit_code)
         }
         Err(e) => {
             // 使用渲染器输出美化后的错误
             eprintln!();
             let output = render_compile_error(e.message(), source_file, e.diagnostic());
             eprintln!("{}", output);
-            return Err(anyhow::anyhow!("Compilation failed"));
+            Err(DiagnosticsReported("Compilation failed").into())
         }
     }
-
-    Ok(())
 }
 
 /// 只进行类型检查，不执行代码
 ///
 /// # 参数
 /// - `file`: 源文件路径
+/// - `release`: release 模式下整数溢出回绕，否则报 E6008
 ///
 /// # 返回
 /// 检查成功返回 `()`，失败返回错误
@@ -525,6 +541,13 @@ fn check_modules_with_shared_env(files: &[std::path::PathBuf]) -> anyhow::Result
 /// 使用依赖图进行拓扑排序，按依赖顺序检查，支持循环依赖检测。
 #[cfg(feature = "cli")]
 pub fn check_files_with_diagnostics(files: &[std::path::PathBuf]) -> anyhow::Result<CheckResult> {
+    if let Some(first) = files.first() {
+        crate::backends::interpreter::extension::load_for_file(
+            first,
+            &crate::backends::CapabilityPolicy::allow_all(),
+        )
+        .map_err(|e| anyhow::anyhow!("{}", e))?;
+    }
     check_modules_with_shared_env(files)
 }
 
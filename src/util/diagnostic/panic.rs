@@ -0,0 +1,310 @@
+//! 运行时 panic
+//!
+//! [`RuntimePanic`] 把执行器返回的 [`ExecutorError`] 与程序的行号表、源码结合起来：
+//! 错误类别、消息与解析到源码位置的调用栈。CLI 以 rustc 风格打印它，嵌入方可以直接用
+//! `?` 把它转换为 `anyhow::Error`：
+//!
+//! ```ignore
+//! let program = engine.compile("lib.yx", source)?;
+//! engine
+//!     .run(&program)
+//!     .map_err(|e| RuntimePanic::new(e, &program))?;
+//! ```
+
+use std::fmt;
+
+use crate::backends::{ExecutorError, StackFrame};
+use crate::middle::bytecode::BytecodeModule;
+use crate::util::span::{DebugSpan, SourceFile, SourceMap};
+
+use super::emitter::{EmitterConfig, TextEmitter};
+use super::Diagnostic;
+
+/// 渲染调用栈时最多显示的帧数
+const MAX_RENDERED_FRAMES: usize = 24;
+
+/// 省略中间帧时保留的最外层帧数
+const TAIL_RENDERED_FRAMES: usize = 4;
+
+/// 运行时错误的类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PanicKind {
+    /// 一般运行时错误（断言失败、标准库函数报错等）
+    Runtime,
+    /// 运行时类型错误
+    Type,
+    /// 调用深度超过上限
+    StackOverflow,
+    /// 除以零
+    DivisionByZero,
+    /// 索引越界
+    IndexOutOfBounds,
+    /// 整数溢出（debug 构建）
+    IntegerOverflow,
+    /// 字段不存在
+    FieldNotFound,
+    /// 函数不存在
+    FunctionNotFound,
+    /// 堆耗尽
+    HeapExhausted,
+    /// 字节码损坏（非法操作码或句柄）
+    InvalidBytecode,
+    /// 被宿主信号中断
+    Interrupted,
+    /// 执行完指令预算（燃料）
+    FuelExhausted,
+    /// 程序调用 `std.process.exit` 退出
+    Exit,
+}
+
+impl PanicKind {
+    /// 执行器错误所属的类别
+    pub fn of(error: &ExecutorError) -> Self {
+        match error {
+            ExecutorError::Runtime(..) => PanicKind::Runtime,
+            ExecutorError::Type(..) => PanicKind::Type,
+            ExecutorError::StackOverflow(..) => PanicKind::StackOverflow,
+            ExecutorError::DivisionByZero(_) => PanicKind::DivisionByZero,
+            ExecutorError::IndexOutOfBounds(_) => PanicKind::IndexOutOfBounds,
+            ExecutorError::IntegerOverflow(..) => PanicKind::IntegerOverflow,
+            ExecutorError::FieldNotFound(..) => PanicKind::FieldNotFound,
+            ExecutorError::FunctionNotFound(..) => PanicKind::FunctionNotFound,
+            ExecutorError::HeapExhausted(..) => PanicKind::HeapExhausted,
+            ExecutorError::InvalidOpcode(_) | ExecutorError::InvalidHandle(_) => {
+                PanicKind::InvalidBytecode
+            }
+            ExecutorError::Interrupted(..) => PanicKind::Interrupted,
+            ExecutorError::FuelExhausted(..) => PanicKind::FuelExhausted,
+            ExecutorError::Exit(_) => PanicKind::Exit,
+        }
+    }
+}
+
+/// 源码位置（行列从 1 开始）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceLocation {
+    /// 源文件名；程序不带源码时为 `None`
+    pub file: Option<String>,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl fmt::Display for SourceLocation {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        if let Some(file) = &self.file {
+            write!(f, "{}:", file)?;
+        }
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+/// 调用栈中的一帧，最内层在前
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceFrame {
+    /// 函数名
+    pub function: String,
+    /// 出错或正在调用时的指令位置
+    pub ip: usize,
+    /// 行号表中的源码位置；程序没有行号表时为 `None`
+    pub location: Option<SourceLocation>,
+}
+
+impl fmt::Display for TraceFrame {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        match &self.location {
+            Some(location) => write!(f, "at {} ({}) (ip: {})", self.function, location, self.ip),
+            None => write!(f, "at {} (ip: {})", self.function, self.ip),
+        }
+    }
+}
+
+/// 带类别、消息与源码调用栈的运行时错误
+///
+/// `Display` 输出不带颜色的 rustc 风格文本（诊断、源码片段与调用栈），
+/// [`render`](Self::render) 输出带颜色的版本。
+#[derive(Debug, Clone)]
+pub struct RuntimePanic {
+    kind: PanicKind,
+    trace: Vec<TraceFrame>,
+    diagnostic: Diagnostic,
+    /// 出错位置所在的源文件，渲染源码片段用
+    source: Option<SourceFile>,
+    error: ExecutorError,
+}
+
+impl RuntimePanic {
+    /// 用程序自带的源码（调试段）解析错误的调用栈
+    pub fn new(
+        error: ExecutorError,
+        program: &BytecodeModule,
+    ) -> Self {
+        Self::with_sources(error, program, program.sources.as_ref())
+    }
+
+    /// 用给定的源码解析错误的调用栈
+    pub fn with_sources(
+        error: ExecutorError,
+        program: &BytecodeModule,
+        sources: Option<&SourceMap>,
+    ) -> Self {
+        let spans: Vec<Option<DebugSpan>> = error
+            .stack_trace()
+            .map(|stack| {
+                stack
+                    .iter()
+                    .map(|frame| resolve_span(program, frame).filter(|s| !s.is_dummy()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let trace = error
+            .stack_trace()
+            .into_iter()
+            .flatten()
+            .zip(&spans)
+            .map(|(frame, span)| TraceFrame {
+                function: frame.function_name.clone(),
+                ip: frame.ip,
+                location: span.map(|ds| SourceLocation {
+                    file: sources
+                        .and_then(|sm| sm.get(ds.file_id))
+                        .map(|sf| sf.name.clone()),
+                    line: ds.span.start.line,
+                    column: ds.span.start.column,
+                }),
+            })
+            .collect();
+
+        let primary_span = spans.first().copied().flatten();
+        let source = primary_span.and_then(|ds| sources.and_then(|sm| sm.get(ds.file_id)));
+        let diagnostic = super::build_runtime_diagnostic(&error, primary_span, source);
+        Self {
+            kind: PanicKind::of(&error),
+            trace,
+            diagnostic,
+            source: source.cloned(),
+            error,
+        }
+    }
+
+    /// 错误类别
+    pub fn kind(&self) -> PanicKind {
+        self.kind
+    }
+
+    /// 错误码（如 `E6004`）
+    pub fn code(&self) -> &str {
+        &self.diagnostic.code
+    }
+
+    /// 错误消息（按当前界面语言渲染）
+    pub fn message(&self) -> &str {
+        &self.diagnostic.message
+    }
+
+    /// 调用栈，最内层在前
+    pub fn trace(&self) -> &[TraceFrame] {
+        &self.trace
+    }
+
+    /// 错误在最内层帧中的源码位置
+    pub fn location(&self) -> Option<&SourceLocation> {
+        self.trace.first()?.location.as_ref()
+    }
+
+    /// 结构化诊断
+    pub fn diagnostic(&self) -> &Diagnostic {
+        &self.diagnostic
+    }
+
+    /// 执行器返回的原始错误
+    pub fn error(&self) -> &ExecutorError {
+        &self.error
+    }
+
+    /// 取回执行器返回的原始错误
+    pub fn into_error(self) -> ExecutorError {
+        self.error
+    }
+
+    /// 以默认文本格式（带颜色）渲染
+    pub fn render(&self) -> String {
+        self.render_with(&TextEmitter::new())
+    }
+
+    /// 用指定的渲染器渲染诊断，后接调用栈
+    pub fn render_with(
+        &self,
+        emitter: &TextEmitter,
+    ) -> String {
+        let mut output = emitter.render_with_source(&self.diagnostic, self.source.as_ref());
+        if self.trace.is_empty() {
+            return output;
+        }
+        output.push_str("\nstack trace:\n");
+        // 深递归（如栈溢出）只显示两端的帧
+        let shown = if self.trace.len() > MAX_RENDERED_FRAMES {
+            MAX_RENDERED_FRAMES - TAIL_RENDERED_FRAMES
+        } else {
+            self.trace.len()
+        };
+        for frame in &self.trace[..shown] {
+            output.push_str(&format!("  {}\n", frame));
+        }
+        if shown < self.trace.len() {
+            let tail = self.trace.len() - TAIL_RENDERED_FRAMES;
+            output.push_str(&format!("  ... {} frames omitted\n", tail - shown));
+            for frame in &self.trace[tail..] {
+                output.push_str(&format!("  {}\n", frame));
+            }
+        }
+        output
+    }
+}
+
+impl fmt::Display for RuntimePanic {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        let emitter = TextEmitter::with_config(EmitterConfig {
+            use_colors: false,
+            ..EmitterConfig::default()
+        });
+        f.write_str(self.render_with(&emitter).trim_end())
+    }
+}
+
+impl std::error::Error for RuntimePanic {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+impl From<RuntimePanic> for ExecutorError {
+    fn from(panic: RuntimePanic) -> Self {
+        panic.error
+    }
+}
+
+/// 按行号表查找栈帧所在的源码位置；按需加载的函数在模块中只有占位，从字节码文件中取
+fn resolve_span(
+    module: &BytecodeModule,
+    frame: &StackFrame,
+) -> Option<DebugSpan> {
+    let func = module
+        .functions
+        .iter()
+        .find(|f| f.name == frame.function_name)?;
+    if !func.instructions.is_empty() {
+        return func.line_table.lookup(frame.ip);
+    }
+    let lazy = module.lazy_functions.as_ref()?;
+    let func = lazy.load(lazy.index_of(&func.name)?).ok()?;
+    func.line_table.lookup(frame.ip)
+}
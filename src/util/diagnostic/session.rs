@@ -1,22 +1,61 @@
 // E:/git/YaoXiang/src/util/diagnostic/session.rs
 
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 #[cfg(feature = "cli")]
-use anyhow::Result;
+use anyhow::{Context, Result};
 
-use crate::frontend::module::dep_graph::ModuleDependencyGraph;
+use crate::frontend::core::parser::ast::Module;
+use crate::frontend::core::typecheck::incremental::IncrementalModule;
+use crate::frontend::module::dep_graph::{ModuleDependencyGraph, ModuleId};
 #[cfg(feature = "cli")]
 use crate::frontend::module::cache::{ModuleCache, CacheMode};
 #[cfg(feature = "cli")]
-use super::CheckResult;
+use crate::frontend::pipeline::compilation_cache::content_hash;
+use crate::util::diagnostic::Diagnostic;
+use crate::util::span::SourceFile;
+#[cfg(feature = "cli")]
+use super::{CheckDiagnostic, CheckResult};
 
 /// 检查会话 — 管理增量检查状态
+///
+/// 多次检查之间（如 `check --watch`）保留每个文件的增量类型检查状态：
+/// 内容未变且所依赖模块的导出签名未变的文件直接复用上次的诊断；
+/// 内容变化的文件只重新检查改动过的函数（见 [`IncrementalModule`]）。
 pub struct CheckSession {
     dep_graph: ModuleDependencyGraph,
     #[cfg(feature = "cli")]
     #[allow(dead_code)]
     cache: ModuleCache,
     all_files: Vec<PathBuf>,
+    files: HashMap<PathBuf, SessionFile>,
+    stats: CheckStats,
+    /// 持久化增量检查产物的目录（`None` = 仅在内存中保留）
+    artifact_dir: Option<PathBuf>,
+}
+
+/// 会话中单个文件的状态
+struct SessionFile {
+    module_id: ModuleId,
+    content_hash: u64,
+    export_hash: u64,
+    source: SourceFile,
+    module: Option<Module>,
+    typecheck: IncrementalModule,
+    diagnostics: Vec<Diagnostic>,
+}
+
+/// 最近一次检查的复用统计
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CheckStats {
+    /// 重新检查的文件数
+    pub files_checked: usize,
+    /// 直接复用诊断的文件数
+    pub files_reused: usize,
+    /// 重新检查的顶层项数
+    pub items_rechecked: usize,
+    /// 复用诊断的顶层项数
+    pub items_reused: usize,
 }
 
 impl Default for CheckSession {
@@ -32,48 +71,188 @@ impl CheckSession {
             #[cfg(feature = "cli")]
             cache: ModuleCache::new(CacheMode::Development),
             all_files: Vec::new(),
+            files: HashMap::new(),
+            stats: CheckStats::default(),
+            artifact_dir: None,
         }
     }
 
+    /// 在 `dir` 下持久化每个文件的增量检查产物
+    ///
+    /// 新会话首次检查时加载上次留下的产物，未改动的函数无需重新检查。
+    pub fn with_artifact_dir(
+        mut self,
+        dir: PathBuf,
+    ) -> Self {
+        self.artifact_dir = Some(dir);
+        self
+    }
+
+    /// 文件对应的产物路径（按文件路径哈希命名）
+    #[cfg(feature = "cli")]
+    fn artifact_path(
+        &self,
+        path: &std::path::Path,
+    ) -> Option<PathBuf> {
+        let name = format!("{:016x}.json", content_hash(&path.display().to_string()));
+        self.artifact_dir.as_ref().map(|dir| dir.join(name))
+    }
+
     /// 全量检查
     #[cfg(feature = "cli")]
     pub fn check_all(
         &mut self,
         files: &[PathBuf],
     ) -> Result<CheckResult> {
-        self.all_files = files.to_vec();
-
-        // Parse all files and build dependency graph
-        let parsed = super::parse_files_parallel(files)?;
-        for (_, module_id, ast) in &parsed {
-            self.dep_graph.build_from_ast(module_id, ast);
-        }
-
-        super::check_files_with_diagnostics(files)
+        self.check(files)
     }
 
-    /// 增量检查 — 只检查受影响的模块
+    /// 增量检查 — 只重新检查受影响的文件与函数
+    ///
+    /// 变更按内容哈希检测；`changed_files` 中尚未加入会话的文件会被加入。
     #[cfg(feature = "cli")]
     pub fn check_incremental(
         &mut self,
         changed_files: &[PathBuf],
     ) -> Result<CheckResult> {
-        let affected = self.dep_graph.affected_modules(changed_files);
+        let mut files = self.all_files.clone();
+        for file in changed_files {
+            if !files.contains(file) {
+                files.push(file.clone());
+            }
+        }
+        self.check(&files)
+    }
 
-        if affected.is_empty() {
-            return Ok(CheckResult::default());
+    /// 检查给定文件集合，尽量复用上次检查的结果
+    #[cfg(feature = "cli")]
+    pub fn check(
+        &mut self,
+        files: &[PathBuf],
+    ) -> Result<CheckResult> {
+        self.all_files = files.to_vec();
+        self.files.retain(|path, _| files.contains(path));
+        let mut stats = CheckStats::default();
+
+        // 1. 重新检查内容变化的文件，记录导出签名变化的模块
+        let mut checked = HashSet::new();
+        let mut exports_changed = HashSet::new();
+        for path in files {
+            let source = std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            let hash = content_hash(&source);
+            if self
+                .files
+                .get(path)
+                .is_some_and(|file| file.content_hash == hash)
+            {
+                continue;
+            }
+
+            let artifact = self.artifact_path(path);
+            let file = self.files.entry(path.clone()).or_insert_with(|| {
+                let mut file = SessionFile::new(path);
+                if let Some(artifact) = &artifact {
+                    file.typecheck = IncrementalModule::load(artifact);
+                }
+                file
+            });
+            let old_export = (file.content_hash != 0).then_some(file.export_hash);
+            file.content_hash = hash;
+            file.run(&source, &mut stats);
+            if let Some(artifact) = &artifact {
+                // 产物只是缓存：写入失败不影响检查结果
+                let _ = file.typecheck.save(artifact);
+            }
+            file.source = SourceFile::new(path.display().to_string(), source);
+            if old_export != Some(file.export_hash) {
+                exports_changed.insert(file.module_id.name.clone());
+            }
+            checked.insert(path.clone());
         }
 
-        let files_to_check: Vec<PathBuf> = affected
-            .iter()
-            .filter_map(|module_id| module_id.path.clone())
-            .collect();
+        // 2. 重建依赖图
+        self.dep_graph = ModuleDependencyGraph::new();
+        for file in self.files.values() {
+            match &file.module {
+                Some(module) => self.dep_graph.build_from_ast(&file.module_id, module),
+                None => self.dep_graph.add_module(file.module_id.clone()),
+            }
+        }
+        let cycles = self.dep_graph.detect_cycles();
+        if !cycles.is_empty() {
+            let cycle_str = cycles
+                .iter()
+                .map(|c| {
+                    c.iter()
+                        .map(|m| m.name.as_str())
+                        .collect::<Vec<_>>()
+                        .join(" -> ")
+                })
+                .collect::<Vec<_>>()
+                .join("; ");
+            return Err(anyhow::anyhow!("Cyclic dependency detected: {}", cycle_str));
+        }
 
-        if files_to_check.is_empty() {
-            return Ok(CheckResult::default());
+        // 3. 依赖模块导出签名变化的文件需全量重新检查
+        for path in files {
+            if checked.contains(path) {
+                continue;
+            }
+            let artifact = self.artifact_path(path);
+            let Some(file) = self.files.get_mut(path) else {
+                continue;
+            };
+            let deps_changed = self
+                .dep_graph
+                .get_dependencies(&file.module_id)
+                .iter()
+                .any(|edge| exports_changed.contains(&edge.target.name));
+            if deps_changed {
+                file.typecheck.invalidate();
+                let source = file.source.content.clone();
+                file.run(&source, &mut stats);
+                if let Some(artifact) = &artifact {
+                    let _ = file.typecheck.save(artifact);
+                }
+                checked.insert(path.clone());
+            } else {
+                stats.files_reused += 1;
+            }
         }
+        stats.files_checked = checked.len();
+        self.stats = stats;
 
-        super::check_files_with_diagnostics(&files_to_check)
+        // 4. 按依赖顺序汇总诊断
+        let mut order: Vec<PathBuf> = match self.dep_graph.topological_sort() {
+            Ok(sorted) => sorted.into_iter().filter_map(|m| m.path).collect(),
+            Err(_) => Vec::new(),
+        };
+        for path in files {
+            if !order.contains(path) {
+                order.push(path.clone());
+            }
+        }
+        let mut result = CheckResult::default();
+        for path in order.iter().filter(|path| files.contains(path)) {
+            let Some(file) = self.files.get(path) else {
+                continue;
+            };
+            let name = path.display().to_string();
+            for diagnostic in &file.diagnostics {
+                if diagnostic.severity.is_error() {
+                    result.error_count += 1;
+                } else {
+                    result.warning_count += 1;
+                }
+                result.diagnostics.push(CheckDiagnostic {
+                    file: name.clone(),
+                    diagnostic: diagnostic.clone(),
+                });
+            }
+            result.source_files.insert(name, file.source.clone());
+        }
+        Ok(result)
     }
 
     /// 获取依赖图引用
@@ -85,4 +264,44 @@ impl CheckSession {
     pub fn all_files(&self) -> &[PathBuf] {
         &self.all_files
     }
+
+    /// 最近一次检查的复用统计
+    pub fn stats(&self) -> CheckStats {
+        self.stats
+    }
+}
+
+impl SessionFile {
+    #[cfg(feature = "cli")]
+    fn new(path: &std::path::Path) -> Self {
+        let name = path
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+        Self {
+            module_id: ModuleId::new(name, path),
+            content_hash: 0,
+            export_hash: 0,
+            source: SourceFile::new(path.display().to_string(), String::new()),
+            module: None,
+            typecheck: IncrementalModule::new(),
+            diagnostics: Vec::new(),
+        }
+    }
+
+    /// 对当前源码运行增量类型检查
+    #[cfg(feature = "cli")]
+    fn run(
+        &mut self,
+        source: &str,
+        stats: &mut CheckStats,
+    ) {
+        let outcome = self.typecheck.check(source);
+        stats.items_rechecked += outcome.rechecked;
+        stats.items_reused += outcome.reused;
+        self.export_hash = outcome.export_hash;
+        self.module = outcome.module;
+        self.diagnostics = outcome.diagnostics;
+    }
 }
@@ -37,6 +37,65 @@ pub enum Suggestion {
     Generic { message: String },
 }
 
+/// 候选名字的来源（靠前者优先）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SymbolSource {
+    /// 函数体/块内的局部变量
+    Local,
+    /// 模块级定义
+    Module,
+    /// `use` 导入的名字
+    Import,
+    /// 尚未导入的标准库函数
+    Std,
+}
+
+/// 跨作用域拼写建议的候选
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Candidate {
+    /// 候选名字
+    pub name: String,
+    /// 来源
+    pub source: SymbolSource,
+    /// 类型是否与使用处兼容（无从判断时为 true）
+    pub type_compatible: bool,
+    /// 所属模块（仅 `SymbolSource::Std`，如 `std.math`），使用前需导入
+    pub module: Option<String>,
+}
+
+impl Candidate {
+    /// 创建候选
+    pub fn new(
+        name: impl Into<String>,
+        source: SymbolSource,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            source,
+            type_compatible: true,
+            module: None,
+        }
+    }
+
+    /// 设置类型兼容性
+    pub fn compatible(
+        mut self,
+        compatible: bool,
+    ) -> Self {
+        self.type_compatible = compatible;
+        self
+    }
+
+    /// 设置所属模块
+    pub fn from_module(
+        mut self,
+        module: impl Into<String>,
+    ) -> Self {
+        self.module = Some(module.into());
+        self
+    }
+}
+
 /// 智能建议引擎
 pub struct SuggestionEngine {
     /// 已定义的名字（变量、函数等）- 存储Owned字符串
@@ -45,6 +104,8 @@ pub struct SuggestionEngine {
     pub(crate) similarity_cache: RwLock<HashMap<String, Vec<(String, f64)>>>,
     /// 名称到类型的映射（用于更精确的建议）
     pub(crate) name_to_types: HashMap<String, String>,
+    /// 跨作用域候选（局部、模块级、导入、std）
+    candidates: Vec<Candidate>,
 }
 
 impl SuggestionEngine {
@@ -54,6 +115,7 @@ impl SuggestionEngine {
             defined_names: HashSet::new(),
             similarity_cache: RwLock::new(HashMap::new()),
             name_to_types: HashMap::new(),
+            candidates: Vec::new(),
         }
     }
 
@@ -83,6 +145,52 @@ impl SuggestionEngine {
         self.name_to_types.insert(name.to_string(), ty.to_string());
     }
 
+    /// 添加跨作用域候选
+    pub fn add_candidate(
+        &mut self,
+        candidate: Candidate,
+    ) {
+        self.candidates.push(candidate);
+    }
+
+    /// 按 Damerau-Levenshtein 距离、类型兼容性、来源依次排序，返回足够接近的候选
+    ///
+    /// 允许的最大距离为名字长度的三分之一（至少 1）；同名候选只保留排序最靠前的一个。
+    pub fn rank_candidates(
+        &self,
+        typo: &str,
+    ) -> Vec<&Candidate> {
+        let max_distance = typo.chars().count().max(3) / 3;
+        let mut ranked: Vec<(usize, &Candidate)> = self
+            .candidates
+            .iter()
+            .filter(|c| c.name != typo)
+            .map(|c| (damerau_levenshtein(typo, &c.name), c))
+            .filter(|(dist, _)| *dist <= max_distance)
+            .collect();
+        ranked.sort_by(|(da, a), (db, b)| {
+            da.cmp(db)
+                .then(b.type_compatible.cmp(&a.type_compatible))
+                .then(a.source.cmp(&b.source))
+                .then(a.name.cmp(&b.name))
+        });
+
+        let mut seen = HashSet::new();
+        ranked
+            .into_iter()
+            .map(|(_, c)| c)
+            .filter(|c| seen.insert(c.name.as_str()))
+            .collect()
+    }
+
+    /// 最佳候选
+    pub fn best_candidate(
+        &self,
+        typo: &str,
+    ) -> Option<&Candidate> {
+        self.rank_candidates(typo).into_iter().next()
+    }
+
     /// 查找相似的名字
     pub fn find_similar(
         &self,
@@ -227,6 +335,38 @@ impl SuggestionEngine {
     }
 }
 
+/// Damerau-Levenshtein 编辑距离（限制换位版本）
+///
+/// 相邻字符换位（`lenght` → `length`）计为一次编辑。
+pub fn damerau_levenshtein(
+    a: &str,
+    b: &str,
+) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+
+    // 保留三行：i-2、i-1、i
+    let mut prev2: Vec<usize> = vec![0; b.len() + 1];
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut curr: Vec<usize> = vec![0; b.len() + 1];
+
+    for i in 1..=a.len() {
+        curr[0] = i;
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut value = (prev[j] + 1).min(curr[j - 1] + 1).min(prev[j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                value = value.min(prev2[j - 2] + 1);
+            }
+            curr[j] = value;
+        }
+        std::mem::swap(&mut prev2, &mut prev);
+        std::mem::swap(&mut prev, &mut curr);
+    }
+
+    prev[b.len()]
+}
+
 impl Default for SuggestionEngine {
     fn default() -> Self {
         Self::new()
@@ -5,9 +5,10 @@
 
 use crate::util::diagnostic::{
     parse_compile_error, check_files_with_diagnostics, render_runtime_error, ErrorCodeDefinition,
-    TextEmitter,
+    PanicKind, RuntimePanic, TextEmitter,
 };
-use crate::util::span::{DebugSpan, SourceFile, SourceMap, Span, Position};
+use crate::util::config::{LintConfig, WarningLevel};
+use crate::util::span::{DebugSpan, LineTable, SourceFile, SourceMap, Span, Position};
 use crate::backends::{ExecutorError, StackFrame};
 use crate::middle::bytecode::{BytecodeModule, BytecodeFunction, BytecodeInstr};
 use std::collections::HashMap;
@@ -15,6 +16,15 @@ use std::fs;
 use tempfile::tempdir;
 use crate::util::diagnostic::emitter::ansi::strip_ansi;
 
+fn line_table_at(
+    ip: usize,
+    span: DebugSpan,
+) -> LineTable {
+    let mut table = LineTable::new();
+    table.push(ip, span);
+    table
+}
+
 #[test]
 fn test_render_unknown_variable_with_source() {
     let source = r#"use std.io
@@ -49,6 +59,27 @@ main = () => {
     assert!(clean_output.contains("^"), "{}", clean_output);
 }
 
+#[test]
+fn test_render_crlf_source_with_bom() {
+    let source = "\u{FEFF}use std.io\r\n\r\nmain = {\r\n  print(a)\r\n}\r\n";
+    let source_file = SourceFile::new("crlf.yx".to_string(), source.to_string());
+
+    // 偏移与行列换算与词法分析器一致：BOM 不占列，`\r\n` 只算一次换行
+    let offset = source.find("a)").unwrap();
+    let pos = source_file.position_from_offset(offset);
+    assert_eq!((pos.line, pos.column), (4, 9));
+    assert_eq!(source_file.line_text(1), Some("use std.io"));
+    assert_eq!(source_file.line_text(4), Some("  print(a)"));
+
+    let diagnostic = ErrorCodeDefinition::unknown_variable("a")
+        .at(Span::new(pos, source_file.position_from_offset(offset + 1)))
+        .build();
+    let output =
+        strip_ansi(&TextEmitter::new().render_with_source(&diagnostic, Some(&source_file)));
+    assert!(output.contains("crlf.yx:4:9"), "{}", output);
+    assert!(!output.contains('\r'), "{:?}", output);
+}
+
 #[test]
 fn test_render_error_without_source_file() {
     let diagnostic = ErrorCodeDefinition::find("E0001")
@@ -124,7 +155,8 @@ fn test_render_runtime_function_not_found_with_span() {
         instructions: vec![BytecodeInstr::Nop],
         labels: HashMap::new(),
         exception_handlers: vec![],
-        debug_map: HashMap::from([(0usize, debug_span)]),
+        line_table: line_table_at(0, debug_span),
+        decoded: Default::default(),
     });
 
     let err = ExecutorError::function_not_found(
@@ -154,6 +186,80 @@ fn test_render_runtime_function_not_found_with_span() {
     );
 }
 
+#[test]
+fn test_render_runtime_stack_overflow_reports_limit() {
+    let source = "main = () => {\n  main()\n}";
+    let mut sources = SourceMap::new();
+    let file_id = sources.add_file("deep.yx".to_string(), source.to_string());
+    let span = Span::new(
+        Position::with_offset(2, 3, 0),
+        Position::with_offset(2, 9, 0),
+    );
+
+    let mut module = BytecodeModule::new("test".to_string());
+    module.add_function(BytecodeFunction {
+        name: "main".to_string(),
+        params: vec![],
+        return_type: crate::middle::core::ir::Type::Void,
+        local_count: 0,
+        upvalue_count: 0,
+        instructions: vec![BytecodeInstr::Nop],
+        labels: HashMap::new(),
+        exception_handlers: vec![],
+        line_table: line_table_at(0, DebugSpan::new(file_id, span)),
+        decoded: Default::default(),
+    });
+
+    let err = ExecutorError::stack_overflow(
+        32,
+        vec![StackFrame {
+            function_name: "main".to_string(),
+            ip: 0,
+        }],
+    );
+    let output = strip_ansi(&render_runtime_error(&err, &module, Some(&sources)));
+
+    assert!(output.contains("error [E6004]"), "{}", output);
+    assert!(output.contains("exceeds limit 32"), "{}", output);
+    assert!(output.contains("deep.yx:2:3"), "{}", output);
+}
+
+#[test]
+fn test_runtime_panic_elides_deep_stack_trace() {
+    let mut module = BytecodeModule::new("test".to_string());
+    module.add_function(BytecodeFunction {
+        name: "main".to_string(),
+        params: vec![],
+        return_type: crate::middle::core::ir::Type::Void,
+        local_count: 0,
+        upvalue_count: 0,
+        instructions: vec![BytecodeInstr::Nop],
+        labels: HashMap::new(),
+        exception_handlers: vec![],
+        line_table: LineTable::new(),
+        decoded: Default::default(),
+    });
+    let stack = (0..100)
+        .map(|ip| StackFrame {
+            function_name: "main".to_string(),
+            ip,
+        })
+        .collect();
+    let panic = RuntimePanic::new(ExecutorError::stack_overflow(100, stack), &module);
+
+    assert_eq!(panic.kind(), PanicKind::StackOverflow);
+    assert_eq!(panic.code(), "E6004");
+    assert_eq!(panic.trace().len(), 100);
+    assert!(panic.location().is_none());
+
+    let text = panic.to_string();
+    assert!(!text.contains('\x1b'), "{}", text);
+    assert!(text.contains("at main (ip: 19)"), "{}", text);
+    assert!(!text.contains("at main (ip: 20)"), "{}", text);
+    assert!(text.contains("... 76 frames omitted"), "{}", text);
+    assert!(text.ends_with("at main (ip: 99)"), "{}", text);
+}
+
 #[test]
 fn test_check_files_with_diagnostics_ok() {
     let dir = tempdir().expect("create temp dir");
@@ -163,7 +269,7 @@ fn test_check_files_with_diagnostics_ok() {
         r#"use std.io
 
 main: () -> Void = {
-  print("ok")
+  io.println("ok")
 }
 "#,
     )
@@ -175,6 +281,37 @@ main: () -> Void = {
     assert!(result.diagnostics.is_empty());
 }
 
+#[test]
+fn test_apply_lint_levels_drops_and_denies_warnings() {
+    let dir = tempdir().expect("create temp dir");
+    let file = dir.path().join("unused.yx");
+    fs::write(
+        &file,
+        r#"use std.io
+
+main: () -> Void = {
+  x = 1
+  print("ok")
+}
+"#,
+    )
+    .expect("write yx file");
+
+    let mut result = check_files_with_diagnostics(&[file]).expect("run check");
+    assert_eq!(result.warning_count, 2);
+
+    let lint = LintConfig {
+        unused_imports: WarningLevel::Off,
+        unused_variables: WarningLevel::Deny,
+        ..LintConfig::default()
+    };
+    result.apply_lint_levels(&lint);
+    assert_eq!(result.error_count, 1);
+    assert_eq!(result.warning_count, 0);
+    assert_eq!(result.diagnostics.len(), 1);
+    assert_eq!(result.diagnostics[0].diagnostic.code, "W1006");
+}
+
 #[test]
 fn test_check_files_with_diagnostics_error() {
     let dir = tempdir().expect("create temp dir");
@@ -283,3 +420,171 @@ main: () -> Void = {
     let result = check_files_with_diagnostics(&[file]).expect("run check");
     assert_eq!(result.error_count, 0);
 }
+
+// ============================================================================
+// 退出码
+// ============================================================================
+
+#[test]
+fn test_exit_status_codes() {
+    use crate::util::diagnostic::ExitStatus;
+    assert_eq!(ExitStatus::Success.code(), 0);
+    assert_eq!(ExitStatus::Diagnostics.code(), 1);
+    assert_eq!(ExitStatus::Internal.code(), 2);
+    assert_eq!(ExitStatus::Panic.code(), 101);
+}
+
+#[test]
+fn test_exit_status_respects_max_warnings() {
+    use crate::util::diagnostic::ExitStatus;
+    assert_eq!(ExitStatus::from_counts(0, 3, None), ExitStatus::Success);
+    assert_eq!(ExitStatus::from_counts(0, 3, Some(3)), ExitStatus::Success);
+    assert_eq!(
+        ExitStatus::from_counts(0, 4, Some(3)),
+        ExitStatus::Diagnostics
+    );
+    assert_eq!(
+        ExitStatus::from_counts(1, 0, Some(10)),
+        ExitStatus::Diagnostics
+    );
+}
+
+#[test]
+fn test_exit_status_classifies_errors() {
+    use crate::util::diagnostic::{DiagnosticsReported, ExitStatus};
+    let reported = anyhow::Error::new(DiagnosticsReported("Compilation failed"));
+    assert_eq!(ExitStatus::of_error(&reported), ExitStatus::Diagnostics);
+    let compile = anyhow::Error::new(crate::frontend::CompileError::Internal("x".into()))
+        .context("Failed to evaluate code");
+    assert_eq!(ExitStatus::of_error(&compile), ExitStatus::Diagnostics);
+    let io = anyhow::anyhow!("No .yx files found at: .");
+    assert_eq!(ExitStatus::of_error(&io), ExitStatus::Internal);
+}
+
+#[test]
+fn test_explain_prints_long_form_explanation() {
+    use crate::util::diagnostic::render_explain_output;
+    let output = render_explain_output("e1001", false, Some("en"))
+        .unwrap()
+        .expect("E1001 should be explainable");
+    assert!(
+        output.starts_with("Error E1001: Unknown variable"),
+        "{}",
+        output
+    );
+    assert!(
+        output.contains("Referenced variable is undefined"),
+        "{}",
+        output
+    );
+    assert!(output.contains("Example:\n    x = y + 1;"), "{}", output);
+
+    assert!(render_explain_output("E9999", false, Some("en"))
+        .unwrap()
+        .is_none());
+}
+
+#[test]
+fn test_explain_falls_back_to_english_for_missing_entries() {
+    use crate::util::diagnostic::render_explain_output;
+    // zh-x-miao 没有 E0018，ja 没有 E6009 的说明
+    let miao = render_explain_output("E0018", false, Some("zh-x-miao"))
+        .unwrap()
+        .unwrap();
+    assert!(miao.contains("Keyword as name"), "{}", miao);
+
+    let json = render_explain_output("E6009", true, Some("ja"))
+        .unwrap()
+        .unwrap();
+    let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(value["code"], "E6009");
+    assert!(value["description"].as_str().unwrap().contains("SIGINT"));
+}
+
+#[test]
+fn test_error_index_lists_every_code() {
+    use crate::util::diagnostic::render_error_index;
+    let index = render_error_index(Some("en"));
+    for def in ErrorCodeDefinition::all() {
+        assert!(index.contains(def.code), "{} missing from index", def.code);
+    }
+    assert!(index.contains("  E1001  Unknown variable"), "{}", index);
+}
+
+#[test]
+fn test_line_table_rows_cover_following_instructions() {
+    let at = |line, column| {
+        DebugSpan::new(
+            0,
+            Span::new(Position::new(line, column), Position::new(line, column + 1)),
+        )
+    };
+    let mut table = LineTable::new();
+    table.push(2, at(3, 5));
+    table.push(4, at(3, 5));
+    table.push(6, at(4, 1));
+    table.push(6, at(4, 9));
+    assert_eq!(table.len(), 2);
+    assert_eq!(table.lookup(0), None);
+    assert_eq!(table.lookup(2), Some(at(3, 5)));
+    assert_eq!(table.lookup(5), Some(at(3, 5)));
+    assert_eq!(table.lookup(6), Some(at(4, 9)));
+    assert_eq!(table.lookup(100), Some(at(4, 9)));
+}
+
+#[test]
+fn test_render_runtime_overflow_from_bytecode_file() {
+    use crate::backends::interpreter::Interpreter;
+    use crate::backends::Executor;
+    use crate::frontend::Compiler;
+    use crate::middle::passes::codegen::bytecode::DebugSection;
+    use crate::middle::passes::codegen::{CodegenContext, LazyBytecodeFile};
+
+    let source = "\
+use std.io
+
+grow: (a: Int) -> Int = (a) => {
+    io.println(\"growing\")
+    b = a * a
+    return b
+}
+
+main = {
+    io.println(grow(9223372036854775807))
+}
+";
+    let module = Compiler::new()
+        .compile("overflow.yx", source)
+        .expect("source should compile");
+    let mut ctx = CodegenContext::new(module);
+    ctx.set_generate_debug_info(true);
+    let mut file = ctx.generate().expect("codegen should succeed");
+    let mut sources = SourceMap::new();
+    sources.add_file("overflow.yx".to_string(), source.to_string());
+    file.debug_section = Some(DebugSection::from_sources_and_functions(
+        sources,
+        &file.code_section.functions,
+    ));
+    let mut bytes = std::io::Cursor::new(Vec::new());
+    file.write_to(&mut bytes)
+        .expect("bytecode should serialize");
+    bytes.set_position(0);
+
+    let lazy = LazyBytecodeFile::from_reader(bytes).expect("bytecode should load");
+    let sources = lazy.sources().cloned();
+    assert!(sources.is_some());
+    let module = BytecodeModule::try_from(lazy).expect("entry function should decode");
+    let err = Interpreter::new()
+        .execute_module(&module)
+        .expect_err("multiplication should overflow");
+
+    let output = strip_ansi(&render_runtime_error(&err, &module, sources.as_ref()));
+    assert!(output.contains("error [E6008]"), "{}", output);
+    assert!(
+        output.contains("9223372036854775807 * 9223372036854775807"),
+        "{}",
+        output
+    );
+    assert!(output.contains("overflow.yx:5:"), "{}", output);
+    assert!(output.contains("b = a * a"), "{}", output);
+}
@@ -1,6 +1,6 @@
 //! CheckSession 测试 — 基于 check-improvement 设计规范
 //!
-//! §6.1: CheckSession 增量检查
+//! §6.1: CheckSession 增量检查（含持久化的检查产物）
 
 use crate::util::diagnostic::session::CheckSession;
 use std::fs;
@@ -84,3 +84,128 @@ main: () -> Void = {
         "valid modified file should have no errors"
     );
 }
+
+#[test]
+fn test_session_reuses_unchanged_files_and_functions() {
+    let dir = tempfile::tempdir().expect("create temp dir");
+    let lib = dir.path().join("lib.yx");
+    let app = dir.path().join("app.yx");
+    fs::write(
+        &lib,
+        r#"pub double: (x: Int) -> Int = {
+    return x * 2
+}
+
+pub triple: (x: Int) -> Int = {
+    return x * 3
+}
+"#,
+    )
+    .expect("write lib");
+    fs::write(
+        &app,
+        r#"main: () -> Void = {
+    print("hello")
+}
+"#,
+    )
+    .expect("write app");
+    let files = vec![lib.clone(), app.clone()];
+
+    let mut session = CheckSession::new();
+    session.check(&files).expect("initial check");
+    assert_eq!(session.stats().files_checked, 2);
+
+    // 未改动：全部复用
+    session.check(&files).expect("unchanged check");
+    assert_eq!(session.stats().files_checked, 0);
+    assert_eq!(session.stats().files_reused, 2);
+
+    // 只改函数体：只重新检查该函数
+    fs::write(
+        &lib,
+        r#"pub double: (x: Int) -> Int = {
+    return x + x
+}
+
+pub triple: (x: Int) -> Int = {
+    return x * 3
+}
+"#,
+    )
+    .expect("rewrite lib");
+    let result = session.check(&files).expect("body change check");
+    assert_eq!(result.error_count, 0);
+    let stats = session.stats();
+    assert_eq!(stats.files_checked, 1);
+    assert_eq!(stats.files_reused, 1);
+    assert_eq!(stats.items_rechecked, 1);
+    assert_eq!(stats.items_reused, 1);
+}
+
+#[test]
+fn test_session_reports_errors_from_reused_files() {
+    let dir = tempfile::tempdir().expect("create temp dir");
+    let file = dir.path().join("bad.yx");
+    fs::write(
+        &file,
+        r#"main: () -> Void = {
+    x: Int = "text"
+}
+"#,
+    )
+    .expect("write file");
+
+    let mut session = CheckSession::new();
+    let first = session
+        .check_all(std::slice::from_ref(&file))
+        .expect("initial check");
+    let second = session.check_incremental(&[]).expect("incremental check");
+    assert!(first.error_count > 0);
+    assert_eq!(second.error_count, first.error_count);
+    assert_eq!(session.stats().files_reused, 1);
+}
+
+#[test]
+fn test_session_reuses_persisted_artifacts_across_sessions() {
+    let dir = tempfile::tempdir().expect("create temp dir");
+    let lib = dir.path().join("lib.yx");
+    let cache = dir.path().join(".yaoxiang").join("cache").join("typecheck");
+    fs::write(
+        &lib,
+        r#"pub double: (x: Int) -> Int = {
+    return x * 2
+}
+
+pub triple: (x: Int) -> Int = {
+    return x * 3
+}
+"#,
+    )
+    .expect("write lib");
+    let files = vec![lib.clone()];
+
+    let mut session = CheckSession::new().with_artifact_dir(cache.clone());
+    session.check(&files).expect("initial check");
+    assert_eq!(session.stats().items_rechecked, 2);
+    assert!(fs::read_dir(&cache).expect("artifact dir").count() > 0);
+
+    // 新会话（如重启 `check --watch`）：未改动的函数直接复用
+    fs::write(
+        &lib,
+        r#"pub double: (x: Int) -> Int = {
+    return x + x
+}
+
+pub triple: (x: Int) -> Int = {
+    return x * 3
+}
+"#,
+    )
+    .expect("rewrite lib");
+    let mut session = CheckSession::new().with_artifact_dir(cache);
+    let result = session.check(&files).expect("reloaded check");
+    assert_eq!(result.error_count, 0);
+    assert_eq!(session.stats().items_rechecked, 1);
+    assert_eq!(session.stats().items_reused, 1);
+}
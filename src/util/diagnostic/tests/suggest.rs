@@ -3,7 +3,9 @@
 //! §4.5: SuggestionEngine 集成
 //! X1: get_suggestions() 接入
 
-use crate::util::diagnostic::suggest::{SuggestionEngine, Suggestion};
+use crate::util::diagnostic::suggest::{
+    damerau_levenshtein, Candidate, SuggestionEngine, Suggestion, SymbolSource,
+};
 
 #[test]
 fn test_similarity_identical_strings() {
@@ -104,3 +106,43 @@ fn test_clear_cache_empties_cache() {
         "cache should be empty after clear"
     );
 }
+
+#[test]
+fn test_damerau_levenshtein_counts_transposition_once() {
+    assert_eq!(damerau_levenshtein("lenght", "length"), 1);
+    assert_eq!(damerau_levenshtein("counter", "countr"), 1);
+    assert_eq!(damerau_levenshtein("", "abc"), 3);
+    assert_eq!(damerau_levenshtein("abc", "abc"), 0);
+}
+
+#[test]
+fn test_rank_candidates_prefers_distance_then_type_then_source() {
+    let mut engine = SuggestionEngine::new();
+    engine.add_candidate(Candidate::new("totals", SymbolSource::Local).compatible(false));
+    engine.add_candidate(Candidate::new("totl", SymbolSource::Std).from_module("std.math"));
+    engine.add_candidate(Candidate::new("total", SymbolSource::Import));
+    engine.add_candidate(Candidate::new("total", SymbolSource::Module));
+    engine.add_candidate(Candidate::new("unrelated", SymbolSource::Local));
+
+    let ranked: Vec<(&str, SymbolSource)> = engine
+        .rank_candidates("totla")
+        .into_iter()
+        .map(|c| (c.name.as_str(), c.source))
+        .collect();
+
+    // "total" 为换位（距离 1），同名只保留来源更近的模块级定义
+    assert_eq!(
+        ranked,
+        vec![("total", SymbolSource::Module), ("totl", SymbolSource::Std),]
+    );
+}
+
+#[test]
+fn test_rank_candidates_breaks_ties_by_type_compatibility() {
+    let mut engine = SuggestionEngine::new();
+    engine.add_candidate(Candidate::new("mapp", SymbolSource::Local).compatible(false));
+    engine.add_candidate(Candidate::new("mop", SymbolSource::Std).compatible(true));
+
+    let best = engine.best_candidate("map").expect("suggestion");
+    assert_eq!(best.name, "mop");
+}
@@ -554,6 +554,7 @@ pub enum MSG {
     ErrorInferenceFailed,
     ErrorCannotInferParamType,
     HelpDidYouMean,
+    HelpDidYouMeanFrom,
     HelpSimilarVariables,
     HelpInScope,
 
@@ -743,6 +744,7 @@ impl MSG {
             MSG::ErrorInferenceFailed => "error_inference_failed",
             MSG::ErrorCannotInferParamType => "error_cannot_infer_param_type",
             MSG::HelpDidYouMean => "help_did_you_mean",
+            MSG::HelpDidYouMeanFrom => "help_did_you_mean_from",
             MSG::HelpSimilarVariables => "help_similar_variables",
             MSG::HelpInScope => "help_in_scope",
 
@@ -1,6 +1,9 @@
 //! Logger module for YaoXiang
 //!
 //! Go-style simple logging: `[LEVEL] message`
+//!
+//! The CLI builds a [`LogConfig`] from `-v`/`-vv`/`-vvv` and `--log`
+//! (per-module filters such as `frontend=debug,vm=warn`).
 
 use std::sync::atomic::{AtomicU8, Ordering};
 
@@ -32,6 +35,7 @@ pub fn get_lang() -> &'static str {
 /// Log level
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum LogLevel {
+    Trace,
     Debug,
     Info,
     Warn,
@@ -41,6 +45,7 @@ pub enum LogLevel {
 impl From<LogLevel> for tracing::Level {
     fn from(level: LogLevel) -> Self {
         match level {
+            LogLevel::Trace => tracing::Level::TRACE,
             LogLevel::Debug => tracing::Level::DEBUG,
             LogLevel::Info => tracing::Level::INFO,
             LogLevel::Warn => tracing::Level::WARN,
@@ -49,6 +54,126 @@ impl From<LogLevel> for tracing::Level {
     }
 }
 
+impl std::str::FromStr for LogLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "trace" => Ok(LogLevel::Trace),
+            "debug" => Ok(LogLevel::Debug),
+            "info" => Ok(LogLevel::Info),
+            "warn" | "warning" => Ok(LogLevel::Warn),
+            "error" => Ok(LogLevel::Error),
+            other => Err(format!(
+                "invalid log level '{}' (expected trace, debug, info, warn or error)",
+                other
+            )),
+        }
+    }
+}
+
+/// Short module names accepted by `--log`, mapped to tracing targets
+const MODULE_ALIASES: &[(&str, &str)] = &[
+    ("vm", "yaoxiang::backends"),
+    ("interpreter", "yaoxiang::backends::interpreter"),
+    ("runtime", "yaoxiang::backends::runtime"),
+    ("typecheck", "yaoxiang::frontend::core::typecheck"),
+    ("parser", "yaoxiang::frontend::core::parser"),
+    ("lexer", "yaoxiang::frontend::core::lexer"),
+    ("codegen", "yaoxiang::middle::passes::codegen"),
+];
+
+/// Logging configuration assembled from CLI flags
+///
+/// `-v` / `-vv` / `-vvv` pick the default level, `--log` adds per-module
+/// directives such as `frontend=debug,vm=warn`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogConfig {
+    /// Level for targets without a more specific directive
+    pub level: LogLevel,
+    /// Per-target levels (`yaoxiang::frontend` → Debug), in the order given
+    pub directives: Vec<(String, LogLevel)>,
+    /// Prefix each line with its module target and source location
+    pub show_source: bool,
+    /// Apply `level` to dependency crates too (otherwise they log at most at info)
+    pub dependencies: bool,
+}
+
+impl LogConfig {
+    /// Configuration with a single default level
+    pub fn new(level: LogLevel) -> Self {
+        Self {
+            level,
+            directives: Vec::new(),
+            show_source: false,
+            dependencies: false,
+        }
+    }
+
+    /// Map the number of `-v` flags to a configuration
+    ///
+    /// 0 → info, 1 → debug, 2 → trace, 3+ → trace for dependencies as well,
+    /// with targets and source locations.
+    pub fn from_verbosity(verbosity: u8) -> Self {
+        let level = match verbosity {
+            0 => LogLevel::Info,
+            1 => LogLevel::Debug,
+            _ => LogLevel::Trace,
+        };
+        Self {
+            show_source: verbosity >= 3,
+            dependencies: verbosity >= 3,
+            ..Self::new(level)
+        }
+    }
+
+    /// Level applied to targets outside this crate
+    pub fn dependency_level(&self) -> LogLevel {
+        match self.level {
+            LogLevel::Trace | LogLevel::Debug if !self.dependencies => LogLevel::Info,
+            level => level,
+        }
+    }
+
+    /// Add directives from a `--log` spec: comma-separated `module=level`
+    /// entries, where a bare `level` sets the default level.
+    ///
+    /// Module names are paths inside the crate (`frontend`, `std.io`,
+    /// `backends::runtime`) or one of the short aliases such as `vm`.
+    pub fn parse_directives(
+        &mut self,
+        spec: &str,
+    ) -> Result<(), String> {
+        for part in spec.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            match part.split_once('=') {
+                Some((module, level)) => {
+                    let module = module.trim();
+                    if module.is_empty() {
+                        return Err(format!("missing module name in '{}'", part));
+                    }
+                    self.directives
+                        .push((module_target(module), level.parse()?));
+                }
+                None => self.level = part.parse()?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Resolve a `--log` module name to a tracing target
+pub fn module_target(module: &str) -> String {
+    if let Some((_, target)) = MODULE_ALIASES.iter().find(|(alias, _)| *alias == module) {
+        return target.to_string();
+    }
+    let path = module.replace('.', "::");
+    if path == "yaoxiang" || path.starts_with("yaoxiang::") {
+        path
+    } else {
+        format!("yaoxiang::{}", path)
+    }
+}
+
 /// Initialize logger with default configuration (INFO level)
 #[cfg(feature = "cli")]
 pub fn init() {
@@ -58,13 +183,41 @@ pub fn init() {
 /// Initialize logger with custom level (Go style: `[LEVEL] message`)
 #[cfg(feature = "cli")]
 pub fn init_with_level(level: LogLevel) {
-    use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, Layer, Registry};
+    init_with_config(&LogConfig::new(level));
+}
 
-    let filter = tracing_subscriber::filter::LevelFilter::from_level(level.into());
+/// Initialize logger from a full configuration (default level plus per-module filters)
+#[cfg(feature = "cli")]
+pub fn init_with_config(config: &LogConfig) {
+    use tracing_subscriber::{
+        filter::{LevelFilter, Targets},
+        layer::SubscriberExt,
+        util::SubscriberInitExt,
+        Layer, Registry,
+    };
+
+    let filter = Targets::new()
+        .with_default(LevelFilter::from_level(config.dependency_level().into()))
+        .with_target("yaoxiang", LevelFilter::from_level(config.level.into()))
+        .with_targets(config.directives.iter().map(|(target, level)| {
+            (
+                target.clone(),
+                LevelFilter::from_level(tracing::Level::from(*level)),
+            )
+        }));
+    // Cranelift reports every function it compiles at info level
+    #[cfg(feature = "native")]
+    let filter = if config.dependencies {
+        filter
+    } else {
+        filter.with_target("cranelift_jit", LevelFilter::WARN)
+    };
 
     let layer = tracing_subscriber::fmt::layer()
         .without_time()
-        .with_target(false)
+        .with_target(config.show_source)
+        .with_file(config.show_source)
+        .with_line_number(config.show_source)
         .with_level(true)
         .with_ansi(true)
         .with_filter(filter);
@@ -1,13 +1,19 @@
 //! Utility types and functions
 
+#[cfg(not(target_arch = "wasm32"))]
+pub mod artifact_cache;
 pub mod cache;
 pub mod config;
 pub mod diagnostic;
 pub mod i18n;
 pub mod logger;
 pub mod span;
+pub mod symbol;
 pub mod time_compat;
 
+#[cfg(test)]
+mod tests;
+
 /// Spanned value wrapper
 #[derive(Debug, Clone, Copy)]
 pub struct Spanned<T> {
@@ -2,8 +2,10 @@
 
 use std::fmt;
 
+use serde::{Deserialize, Serialize};
+
 /// Source position (line, column, and byte offset)
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
 pub struct Position {
     /// Line number (1-indexed)
     pub line: usize,
@@ -62,7 +64,7 @@ impl fmt::Display for Position {
 }
 
 /// Source span (start position to end position)
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
 pub struct Span {
     /// Start position (inclusive)
     pub start: Position,
@@ -106,6 +108,16 @@ impl Span {
     pub fn is_empty(&self) -> bool {
         self.start.offset == self.end.offset
     }
+
+    /// Check whether `other` lies within this span (by line and column)
+    #[inline]
+    pub fn contains(
+        &self,
+        other: &Span,
+    ) -> bool {
+        (self.start.line, self.start.column) <= (other.start.line, other.start.column)
+            && (other.end.line, other.end.column) <= (self.end.line, self.end.column)
+    }
 }
 
 impl fmt::Display for Span {
@@ -150,6 +162,66 @@ impl DebugSpan {
     }
 }
 
+/// Per-function line table mapping instruction indices to source spans.
+///
+/// Each row marks where a source location starts; it covers every instruction up to
+/// the next row, so instructions without a span of their own (register reloads, moves)
+/// resolve to the statement they belong to.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LineTable {
+    rows: Vec<(usize, DebugSpan)>,
+}
+
+impl LineTable {
+    #[inline]
+    pub fn new() -> Self {
+        Self { rows: Vec::new() }
+    }
+
+    /// Record that instructions from `ip` on belong to `span`
+    ///
+    /// Rows must be added in instruction order. A row repeating the previous span is
+    /// dropped, and a second row at the same `ip` replaces the first.
+    pub fn push(
+        &mut self,
+        ip: usize,
+        span: DebugSpan,
+    ) {
+        match self.rows.last_mut() {
+            Some((_, last)) if *last == span => {}
+            Some((last_ip, last)) if *last_ip == ip => *last = span,
+            _ => {
+                debug_assert!(self.rows.last().is_none_or(|(last_ip, _)| *last_ip < ip));
+                self.rows.push((ip, span));
+            }
+        }
+    }
+
+    /// Source span of the instruction at `ip`
+    pub fn lookup(
+        &self,
+        ip: usize,
+    ) -> Option<DebugSpan> {
+        let row = self.rows.partition_point(|(start, _)| *start <= ip);
+        row.checked_sub(1).map(|row| self.rows[row].1)
+    }
+
+    /// Rows as `(first instruction index, span)`, in instruction order
+    pub fn rows(&self) -> &[(usize, DebugSpan)] {
+        &self.rows
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+}
+
 /// Source file information
 #[derive(Debug, Clone)]
 pub struct SourceFile {
@@ -163,13 +235,17 @@ pub struct SourceFile {
 
 impl SourceFile {
     /// Create a new source file
+    ///
+    /// `\n`, `\r\n` and a lone `\r` all end a line, matching the lexer.
     pub fn new(
         name: String,
         content: String,
     ) -> Self {
         let mut line_offsets = vec![0];
-        for (i, _) in content.char_indices() {
-            if content[i..].starts_with('\n') {
+        let bytes = content.as_bytes();
+        for (i, &b) in bytes.iter().enumerate() {
+            let line_break = b == b'\n' || (b == b'\r' && bytes.get(i + 1) != Some(&b'\n'));
+            if line_break {
                 line_offsets.push(i + 1);
             }
         }
@@ -182,13 +258,46 @@ impl SourceFile {
         }
     }
 
+    /// Byte offset where the text of `line` starts (a leading BOM is skipped)
+    fn line_start(
+        &self,
+        line: usize,
+    ) -> usize {
+        let start = self.line_offsets[line.saturating_sub(1).min(self.line_offsets.len() - 1)];
+        if start == 0 && self.content.starts_with('\u{FEFF}') {
+            '\u{FEFF}'.len_utf8()
+        } else {
+            start
+        }
+    }
+
+    /// Text of a line (1-indexed) without its line terminator
+    pub fn line_text(
+        &self,
+        line: usize,
+    ) -> Option<&str> {
+        if line == 0 || line >= self.line_offsets.len() {
+            return None;
+        }
+        let start = self.line_start(line);
+        let end = self.line_offsets[line].max(start);
+        let text = self.content.get(start..end)?;
+        Some(text.trim_end_matches(['\n', '\r']))
+    }
+
     /// Get position from byte offset
+    ///
+    /// Columns count characters, as the lexer does.
     pub fn position_from_offset(
         &self,
         offset: usize,
     ) -> Position {
-        let line = self.line_offsets.partition_point(|&o| o <= offset);
-        let column = offset.saturating_sub(self.line_offsets[line.saturating_sub(1)]);
+        let line = self.line_offsets.partition_point(|&o| o <= offset).max(1);
+        let start = self.line_start(line).min(offset);
+        let column = self
+            .content
+            .get(start..offset)
+            .map_or(offset - start, |text| text.chars().count());
         Position::with_offset(line, column + 1, offset)
     }
 
@@ -204,23 +313,27 @@ impl SourceFile {
         }
     }
 
+    /// Byte offset of a line/column position, clamped to the end of its line
+    fn offset_of(
+        &self,
+        pos: Position,
+    ) -> usize {
+        let start = self.line_start(pos.line);
+        let text = self.line_text(pos.line).unwrap_or("");
+        start
+            + text
+                .char_indices()
+                .nth(pos.column.saturating_sub(1))
+                .map_or(text.len(), |(i, _)| i)
+    }
+
     /// Get source text for a span
     pub fn source_text(
         &self,
         span: Span,
     ) -> Option<&str> {
-        let start = self.line_offsets[span.start.line.saturating_sub(1)]
-            + (span.start.column - 1).min(
-                self.line_offsets[span.start.line.saturating_sub(1) + 1]
-                    .saturating_sub(self.line_offsets[span.start.line.saturating_sub(1)])
-                    - 1,
-            );
-        let end = self.line_offsets[span.end.line.saturating_sub(1)]
-            + (span.end.column - 1).min(
-                self.line_offsets[span.end.line.saturating_sub(1) + 1]
-                    .saturating_sub(self.line_offsets[span.end.line.saturating_sub(1)])
-                    - 1,
-            );
+        let start = self.offset_of(span.start);
+        let end = self.offset_of(span.end);
         self.content.get(start..end)
     }
 }
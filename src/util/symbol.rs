@@ -0,0 +1,236 @@
+//! Interned strings
+//!
+//! 标识符与字符串字面量在源码中大量重复出现。词法分析时把文本放进驻留表，
+//! token 与语法树只保存指向驻留文本的 [`Symbol`]，相同文本只分配一次，比较与哈希都按地址进行。
+//!
+//! 驻留表与各个 `Symbol` 共同持有文本的引用计数。表的大小翻倍时清理一次，
+//! 释放已经没有 `Symbol` 引用的文本，长时间运行的进程（语言服务器、REPL）
+//! 不会因为编辑过程中出现过的标识符而持续增长。
+
+use std::collections::HashSet;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+use std::sync::Arc;
+
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+
+/// 清理前驻留表至少容纳的文本数
+const MIN_SWEEP: usize = 1024;
+
+/// 全局驻留表
+static INTERNER: Lazy<RwLock<Interner>> = Lazy::new(|| {
+    RwLock::new(Interner {
+        texts: HashSet::new(),
+        sweep_at: MIN_SWEEP,
+    })
+});
+
+struct Interner {
+    texts: HashSet<Arc<str>>,
+    /// 表增长到这个大小时清理未被引用的文本
+    sweep_at: usize,
+}
+
+impl Interner {
+    fn insert(
+        &mut self,
+        text: &str,
+    ) -> Arc<str> {
+        if self.texts.len() >= self.sweep_at {
+            // 只剩表自身持有的文本已没有 `Symbol` 引用，也无法再被取得
+            self.texts.retain(|text| Arc::strong_count(text) > 1);
+            self.sweep_at = (self.texts.len() * 2).max(MIN_SWEEP);
+        }
+        let interned: Arc<str> = Arc::from(text);
+        self.texts.insert(Arc::clone(&interned));
+        interned
+    }
+}
+
+/// 驻留字符串的句柄
+///
+/// 同一文本在被引用期间只有一份驻留副本，因此相等性与哈希只看地址。
+#[derive(Clone)]
+pub struct Symbol(Arc<str>);
+
+impl Symbol {
+    /// 驻留文本；相同文本总是得到同一个 `Symbol`
+    pub fn intern(text: &str) -> Self {
+        if let Some(interned) = INTERNER.read().texts.get(text) {
+            return Symbol(Arc::clone(interned));
+        }
+        let mut interner = INTERNER.write();
+        // 持有写锁前可能已有其他线程驻留了同一文本
+        if let Some(interned) = interner.texts.get(text) {
+            return Symbol(Arc::clone(interned));
+        }
+        Symbol(interner.insert(text))
+    }
+
+    /// 驻留的文本
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// 驻留表中是否还保存着这段文本（含已无引用、等待清理的）
+    pub fn is_interned(text: &str) -> bool {
+        INTERNER.read().texts.contains(text)
+    }
+}
+
+impl PartialEq for Symbol {
+    fn eq(
+        &self,
+        other: &Self,
+    ) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for Symbol {}
+
+impl Hash for Symbol {
+    fn hash<H: Hasher>(
+        &self,
+        state: &mut H,
+    ) {
+        std::ptr::hash(Arc::as_ptr(&self.0), state)
+    }
+}
+
+impl Deref for Symbol {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl AsRef<str> for Symbol {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl Default for Symbol {
+    fn default() -> Self {
+        Symbol::intern("")
+    }
+}
+
+impl fmt::Debug for Symbol {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        fmt::Display::fmt(self.as_str(), f)
+    }
+}
+
+impl PartialOrd for Symbol {
+    fn partial_cmp(
+        &self,
+        other: &Self,
+    ) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// 按文本排序，使输出顺序与驻留先后无关
+impl Ord for Symbol {
+    fn cmp(
+        &self,
+        other: &Self,
+    ) -> std::cmp::Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+impl From<&str> for Symbol {
+    fn from(text: &str) -> Self {
+        Symbol::intern(text)
+    }
+}
+
+impl From<&String> for Symbol {
+    fn from(text: &String) -> Self {
+        Symbol::intern(text)
+    }
+}
+
+impl From<String> for Symbol {
+    fn from(text: String) -> Self {
+        Symbol::intern(&text)
+    }
+}
+
+impl From<Symbol> for String {
+    fn from(symbol: Symbol) -> Self {
+        symbol.as_str().to_owned()
+    }
+}
+
+impl PartialEq<str> for Symbol {
+    fn eq(
+        &self,
+        other: &str,
+    ) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for Symbol {
+    fn eq(
+        &self,
+        other: &&str,
+    ) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl PartialEq<String> for Symbol {
+    fn eq(
+        &self,
+        other: &String,
+    ) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl PartialEq<Symbol> for str {
+    fn eq(
+        &self,
+        other: &Symbol,
+    ) -> bool {
+        self == other.as_str()
+    }
+}
+
+impl PartialEq<Symbol> for &str {
+    fn eq(
+        &self,
+        other: &Symbol,
+    ) -> bool {
+        *self == other.as_str()
+    }
+}
+
+impl PartialEq<Symbol> for String {
+    fn eq(
+        &self,
+        other: &Symbol,
+    ) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
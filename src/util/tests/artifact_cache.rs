@@ -0,0 +1,145 @@
+//! `util::artifact_cache` 模块的单元测试
+//!
+//! 覆盖字节码与会话快照的存取、损坏条目的处理、缓存键，以及 `eval` 与 REPL 对缓存的使用。
+
+use crate::backends::CapabilityPolicy;
+use crate::frontend::Compiler;
+use crate::middle::passes::codegen::CodegenContext;
+use crate::repl::{EvalResult, Evaluator, REPLBackend, SessionSnapshot};
+use crate::util::artifact_cache::ArtifactCache;
+
+fn bytecode_entries(cache: &ArtifactCache) -> usize {
+    std::fs::read_dir(cache.root().join("bytecode"))
+        .map(|dir| dir.count())
+        .unwrap_or(0)
+}
+
+#[test]
+fn test_bytecode_roundtrip() {
+    let dir = tempfile::tempdir().unwrap();
+    let cache = ArtifactCache::new(dir.path());
+    let module = Compiler::new()
+        .compile("cache.yx", "main = {\n    x = 1 + 2\n}\n")
+        .unwrap();
+    let file = CodegenContext::new(module).generate().unwrap();
+
+    let key = ArtifactCache::key(&["test", "main"]);
+    assert!(cache.load_bytecode(&key).is_none());
+    cache.store_bytecode(&key, &file).unwrap();
+
+    let loaded = cache.load_bytecode(&key).expect("entry should load");
+    assert_eq!(
+        loaded.code_section.functions.len(),
+        file.code_section.functions.len()
+    );
+}
+
+#[test]
+fn test_corrupt_entry_is_a_miss_and_removed() {
+    let dir = tempfile::tempdir().unwrap();
+    let cache = ArtifactCache::new(dir.path());
+    let key = ArtifactCache::key(&["corrupt"]);
+    let path = dir.path().join("bytecode").join(format!("{}.42", key));
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(&path, b"not bytecode").unwrap();
+
+    assert!(cache.load_bytecode(&key).is_none());
+    assert!(!path.exists());
+}
+
+#[test]
+fn test_keys_depend_on_every_part() {
+    let a = ArtifactCache::key(&["eval", "x = 1"]);
+    assert_eq!(a, ArtifactCache::key(&["eval", "x = 1"]));
+    assert_ne!(a, ArtifactCache::key(&["eval", "x = 2"]));
+    assert_ne!(a, ArtifactCache::key(&["repl", "x = 1"]));
+    // 分隔符防止拼接歧义
+    assert_ne!(
+        ArtifactCache::key(&["ab", "c"]),
+        ArtifactCache::key(&["a", "bc"])
+    );
+}
+
+#[test]
+fn test_session_roundtrip_and_clear() {
+    let dir = tempfile::tempdir().unwrap();
+    let cache = ArtifactCache::new(dir.path());
+    let snapshot = SessionSnapshot {
+        inputs: vec!["x = 1".to_string()],
+        functions: vec![("f".to_string(), "() -> Int".to_string(), "Int".to_string())],
+        variables: vec![("x".to_string(), "Int".to_string())],
+    };
+    cache.store_session("repl", &snapshot).unwrap();
+    assert_eq!(
+        cache.load_session::<SessionSnapshot>("repl"),
+        Some(snapshot)
+    );
+
+    cache.clear().unwrap();
+    assert_eq!(cache.load_session::<SessionSnapshot>("repl"), None);
+}
+
+#[test]
+fn test_eval_populates_and_reuses_cache() {
+    let dir = tempfile::tempdir().unwrap();
+    let cache = ArtifactCache::new(dir.path());
+    let source = "use std.assert\n\nmain = {\n    assert_eq(6 * 7, 42)\n}\n";
+
+    assert_eq!(
+        crate::eval_code_with_cache(source, Some(&cache), &CapabilityPolicy::default()).unwrap(),
+        0
+    );
+    assert_eq!(bytecode_entries(&cache), 1);
+    // 第二次命中缓存，结果相同
+    assert_eq!(
+        crate::eval_code_with_cache(source, Some(&cache), &CapabilityPolicy::default()).unwrap(),
+        0
+    );
+    assert_eq!(bytecode_entries(&cache), 1);
+
+    // 编译失败不写入缓存
+    assert!(crate::eval_code_with_cache(
+        "main = {\n    y = missing\n}\n",
+        Some(&cache),
+        &CapabilityPolicy::default()
+    )
+    .is_err());
+    assert_eq!(bytecode_entries(&cache), 1);
+}
+
+#[test]
+fn test_eval_does_not_cache_non_std_imports() {
+    let dir = tempfile::tempdir().unwrap();
+    let cache = ArtifactCache::new(dir.path());
+    // 依赖工作目录中文件的程序不缓存，导入失败也不影响缓存目录
+    let _ = crate::eval_code_with_cache(
+        "use local_module\n\nmain = {\n    x = 1\n}\n",
+        Some(&cache),
+        &CapabilityPolicy::default(),
+    );
+    assert_eq!(bytecode_entries(&cache), 0);
+}
+
+#[test]
+fn test_repl_uses_cache_and_restores_session() {
+    let dir = tempfile::tempdir().unwrap();
+    let cache = ArtifactCache::new(dir.path());
+
+    let mut evaluator = Evaluator::new().with_cache(cache.clone());
+    assert!(matches!(evaluator.eval("x = 1 + 2\n"), EvalResult::Ok));
+    assert_eq!(bytecode_entries(&cache), 1);
+    cache.store_session("repl", &evaluator.snapshot()).unwrap();
+
+    let snapshot: SessionSnapshot = cache.load_session("repl").unwrap();
+    let mut restored = Evaluator::new().with_cache(cache.clone());
+    restored.restore(snapshot);
+    assert_eq!(restored.inputs(), ["x = 1 + 2"]);
+    assert_eq!(restored.snapshot(), evaluator.snapshot());
+
+    // 相同的输入命中缓存
+    assert!(matches!(restored.eval("x = 1 + 2\n"), EvalResult::Ok));
+    assert_eq!(bytecode_entries(&cache), 1);
+
+    restored.clear();
+    assert!(restored.inputs().is_empty());
+}
@@ -0,0 +1,76 @@
+//! 日志配置测试：`-v` 计数与 `--log` 模块过滤
+
+use crate::util::logger::{module_target, LogConfig, LogLevel};
+
+#[test]
+fn test_verbosity_maps_to_levels() {
+    assert_eq!(LogConfig::from_verbosity(0).level, LogLevel::Info);
+    assert_eq!(LogConfig::from_verbosity(1).level, LogLevel::Debug);
+    assert_eq!(LogConfig::from_verbosity(2).level, LogLevel::Trace);
+    assert!(!LogConfig::from_verbosity(2).show_source);
+    assert!(LogConfig::from_verbosity(3).show_source);
+}
+
+#[test]
+fn test_dependencies_stay_quiet_below_vvv() {
+    assert_eq!(
+        LogConfig::from_verbosity(2).dependency_level(),
+        LogLevel::Info
+    );
+    assert_eq!(
+        LogConfig::from_verbosity(3).dependency_level(),
+        LogLevel::Trace
+    );
+    assert_eq!(
+        LogConfig::new(LogLevel::Warn).dependency_level(),
+        LogLevel::Warn
+    );
+}
+
+#[test]
+fn test_parse_module_directives() {
+    let mut config = LogConfig::from_verbosity(0);
+    config
+        .parse_directives("frontend=debug, vm=warn,std.io=trace")
+        .unwrap();
+    assert_eq!(config.level, LogLevel::Info);
+    assert_eq!(
+        config.directives,
+        vec![
+            ("yaoxiang::frontend".to_string(), LogLevel::Debug),
+            ("yaoxiang::backends".to_string(), LogLevel::Warn),
+            ("yaoxiang::std::io".to_string(), LogLevel::Trace),
+        ]
+    );
+}
+
+#[test]
+fn test_bare_level_sets_default() {
+    let mut config = LogConfig::from_verbosity(2);
+    config.parse_directives("warn,lexer=TRACE").unwrap();
+    assert_eq!(config.level, LogLevel::Warn);
+    assert_eq!(
+        config.directives,
+        vec![(
+            "yaoxiang::frontend::core::lexer".to_string(),
+            LogLevel::Trace
+        )]
+    );
+}
+
+#[test]
+fn test_invalid_directives_are_rejected() {
+    let mut config = LogConfig::new(LogLevel::Info);
+    assert!(config.parse_directives("frontend=loud").is_err());
+    assert!(config.parse_directives("=debug").is_err());
+    assert!(config.parse_directives("verbose").is_err());
+}
+
+#[test]
+fn test_module_target_keeps_crate_paths() {
+    assert_eq!(module_target("yaoxiang::middle"), "yaoxiang::middle");
+    assert_eq!(
+        module_target("backends::runtime"),
+        "yaoxiang::backends::runtime"
+    );
+}
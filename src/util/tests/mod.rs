@@ -1,3 +1,6 @@
 //! 工具模块测试
 
+mod artifact_cache;
 mod cache;
+mod logger;
+mod symbol;
@@ -0,0 +1,39 @@
+//! Symbol 驻留测试
+
+use crate::util::symbol::Symbol;
+
+#[test]
+fn test_intern_deduplicates() {
+    let a = Symbol::intern("counter");
+    let b = Symbol::from(String::from("counter"));
+    assert_eq!(a, b);
+    assert_ne!(a, Symbol::intern("count"));
+    assert_eq!(a.as_str(), "counter");
+    assert!(a == "counter");
+    assert_eq!(format!("{} {:?}", a, a), "counter \"counter\"");
+}
+
+#[test]
+fn test_ordering_follows_text() {
+    let z = Symbol::intern("zeta_ordering");
+    let a = Symbol::intern("alpha_ordering");
+    assert!(a < z);
+}
+
+#[test]
+fn test_unreferenced_text_is_released() {
+    let probe = Symbol::intern("released_probe");
+    let same = probe.clone();
+    assert_eq!(probe, same);
+    drop((probe, same));
+
+    // 清理阈值不超过当前表大小的两倍，逐个驻留并丢弃新文本必然触发一次清理
+    let mut filler = 0;
+    while Symbol::is_interned("released_probe") {
+        drop(Symbol::intern(&format!("released_filler_{filler}")));
+        filler += 1;
+        assert!(filler <= 1 << 20, "驻留表从未清理");
+    }
+    // 被释放的文本可以重新驻留
+    assert_eq!(Symbol::intern("released_probe"), "released_probe");
+}
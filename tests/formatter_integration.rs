@@ -606,3 +606,47 @@ fn test_format_no_verify() {
     let result = format_source("x = 1", &opts);
     assert!(result.is_ok(), "no-verify 模式下合法代码应正常通过");
 }
+
+// === 换行风格与 BOM ===
+
+#[test]
+fn test_format_preserves_crlf() {
+    let result = format_source("x=1\r\ny=2\r\n", &default_options()).unwrap();
+    assert_eq!(result, "x = 1\r\ny = 2\r\n");
+}
+
+#[test]
+fn test_format_normalizes_to_lf() {
+    let options = FormatOptions {
+        line_ending: yaoxiang::util::config::LineEnding::Lf,
+        ..Default::default()
+    };
+    let result = format_source("x=1\r\ny=2\ry=3\n", &options).unwrap();
+    assert_eq!(result, "x = 1\ny = 2\ny = 3\n");
+}
+
+#[test]
+fn test_format_crlf_output_is_idempotent() {
+    let options = FormatOptions {
+        line_ending: yaoxiang::util::config::LineEnding::Crlf,
+        ..Default::default()
+    };
+    let formatted = format_source("x=1\ny=2\n", &options).unwrap();
+    assert_eq!(formatted, "x = 1\r\ny = 2\r\n");
+    assert_eq!(format_source(&formatted, &options).unwrap(), formatted);
+}
+
+#[test]
+fn test_format_keeps_bom() {
+    let result = format_source("\u{FEFF}x=1\n", &default_options()).unwrap();
+    assert_eq!(result, "\u{FEFF}x = 1\n");
+}
+
+#[test]
+fn test_format_spawn_call() {
+    // spawn f(x) 单任务形式保持原样
+    assert_format_eq(
+        "f = (x) => x + 1\nr = spawn f(1)",
+        "f = (x) => { x + 1 }\nr = spawn f(1)\n",
+    );
+}
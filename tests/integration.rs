@@ -19,6 +19,10 @@ mod interpreter;
 #[path = "integration/token_system.rs"]
 mod token_system;
 
+/// 稳定公共 API（`compile` / `ast` / `diagnostics` / `vm`）
+#[path = "integration/public_api.rs"]
+mod public_api;
+
 /// `yaoxiang` CLI 子命令集成测试
 #[path = "integration/cli.rs"]
 mod cli;
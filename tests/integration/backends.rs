@@ -3,7 +3,7 @@
 //! Tests for the new backend architecture including interpreter,
 //! common components, and executor functionality.
 
-use yaoxiang::backends::common::{RuntimeValue, Heap, Handle};
+use yaoxiang::backends::common::{RuntimeValue, Heap, Handle, Value};
 use yaoxiang::backends::{ExecutorConfig, ExecutionState};
 use yaoxiang::middle::bytecode::{BytecodeModule, BytecodeFunction};
 use yaoxiang::middle::{ConstValue, Type};
@@ -17,24 +17,42 @@ fn test_executor_config_default() {
     assert_eq!(config.max_heap_size, 64 * 1024 * 1024);
     assert!(config.enable_checks);
     assert!(config.enable_debug);
+    assert!(!config.capabilities.dynamic_import);
+    assert!(!config.capabilities.native_extensions);
 }
 
 #[test]
 fn test_executor_config_custom() {
     let config = ExecutorConfig {
         max_stack_depth: 2048,
+        stack_segment_size: 4 * 1024 * 1024,
+        frame_pool_size: 0,
         initial_heap_size: 128 * 1024,
         max_heap_size: 128 * 1024 * 1024,
         build_mode: yaoxiang::backends::BuildMode::Release,
         enable_checks: false,
         enable_debug: false,
+        capabilities: yaoxiang::backends::CapabilityPolicy::deny_all(),
+        heap_dump: None,
+        inline_cache: true,
+        dispatch: yaoxiang::backends::DispatchMode::Step,
+        native_code: false,
+        jit_threshold: 1000,
+        jit_stats: false,
+        gc_threshold: 0,
+        fuel: Some(1_000),
+        deterministic: true,
     };
 
     assert_eq!(config.max_stack_depth, 2048);
+    assert_eq!(config.stack_segment_size, 4 * 1024 * 1024);
     assert_eq!(config.initial_heap_size, 128 * 1024);
     assert_eq!(config.max_heap_size, 128 * 1024 * 1024);
     assert!(!config.enable_checks);
     assert!(!config.enable_debug);
+    assert!(!config.capabilities.dynamic_import);
+    assert_eq!(config.fuel, Some(1_000));
+    assert!(config.deterministic);
 }
 
 #[test]
@@ -65,6 +83,18 @@ fn test_runtime_value_types() {
     assert_ne!(val1, val3);
 }
 
+#[test]
+fn test_runtime_value_is_three_words() {
+    // Dict entries hold unpacked values
+    assert_eq!(std::mem::size_of::<RuntimeValue>(), 24);
+}
+
+#[test]
+fn test_value_is_one_word() {
+    // Registers, locals and sequence elements hold NaN-boxed values
+    assert_eq!(std::mem::size_of::<Value>(), 8);
+}
+
 #[test]
 fn test_bytecode_module_creation() {
     let module = BytecodeModule::new("test".to_string());
@@ -102,7 +132,8 @@ fn test_bytecode_module_add_function() {
         instructions: vec![],
         labels: std::collections::HashMap::new(),
         exception_handlers: vec![],
-        debug_map: std::collections::HashMap::new(),
+        line_table: yaoxiang::util::span::LineTable::new(),
+        decoded: Default::default(),
     };
 
     let idx = module.add_function(func.clone());
@@ -18,7 +18,8 @@ use yaoxiang::package::commands::{add, rm, install, list};
 use yaoxiang::package::manifest::PackageManifest;
 use yaoxiang::package::error::PackageError;
 use yaoxiang::formatter::{format_source, FormatOptions, run_format_command};
-use yaoxiang::{run, build_bytecode, build_bytecode_with_options, eval_code};
+use yaoxiang::frontend::CompileConfig;
+use yaoxiang::{run, build_bytecode, build_bytecode_with_options, emit_ir, eval_code};
 
 // ============================================================================
 // 辅助函数
@@ -152,6 +153,126 @@ fn test_run_nonexistent_file_returns_error() {
     assert!(result.is_err(), "run_file on nonexistent path should error");
 }
 
+/// 启动 `yaoxiang run`，等程序打印 ready 后向其发送信号
+#[cfg(unix)]
+fn run_and_signal(
+    source: &str,
+    signal: &str,
+) -> std::process::Output {
+    use std::io::{BufRead, BufReader, Read};
+    use std::process::{Command, Stdio};
+
+    let dir = temp_dir();
+    let path = write_yx_file(dir.path(), "spin.yx", source);
+    let mut child = Command::new(env!("CARGO_BIN_EXE_yaoxiang"))
+        .arg("run")
+        .arg(&path)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to spawn yaoxiang");
+
+    let mut stdout = BufReader::new(child.stdout.take().unwrap());
+    let mut line = String::new();
+    stdout.read_line(&mut line).unwrap();
+    assert_eq!(line.trim(), "ready");
+
+    let status = Command::new("kill")
+        .arg(format!("-{}", signal))
+        .arg(child.id().to_string())
+        .status()
+        .expect("Failed to run kill");
+    assert!(status.success());
+
+    let mut rest = String::new();
+    stdout.read_to_string(&mut rest).unwrap();
+    let mut output = child.wait_with_output().unwrap();
+    output.stdout = (line + &rest).into_bytes();
+    output
+}
+
+#[cfg(unix)]
+const SPIN_LOOP: &str = r#"
+    mut i = 0
+    while i < 1000000000 {
+        i = i + 1
+    }
+"#;
+
+#[cfg(unix)]
+#[test]
+fn test_run_sigint_dispatches_registered_handler() {
+    // Arrange
+    let source = format!(
+        r#"use std.io
+use std.signal
+use std.process
+
+main: () -> Void = () => {{
+    signal.on("SIGINT", (name) => {{
+        io.println("caught " + name)
+        process.exit(3)
+    }})
+    io.println("ready")
+{SPIN_LOOP}}}
+"#
+    );
+    // Act
+    let output = run_and_signal(&source, "INT");
+    // Assert
+    assert_eq!(output.status.code(), Some(3));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("caught SIGINT"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_run_unhandled_sigint_stops_at_safepoint_with_stack_trace() {
+    // Arrange
+    let source = format!(
+        r#"use std.io
+
+spin: () -> Void = () => {{
+{SPIN_LOOP}}}
+
+main: () -> Void = () => {{
+    io.println("ready")
+    spin()
+}}
+"#
+    );
+    // Act
+    let output = run_and_signal(&source, "INT");
+    // Assert
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(130), "stderr: {}", stderr);
+    assert!(stderr.contains("E6009"), "stderr: {}", stderr);
+    // 信号可能落在 spin 入口或循环回边，两者都是安全点
+    assert!(stderr.contains("stack trace:"), "stderr: {}", stderr);
+    assert!(
+        stderr.contains("at spin") || stderr.contains("at main"),
+        "stderr: {}",
+        stderr
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_run_unhandled_sigterm_exits_with_143() {
+    // Arrange
+    let source = format!(
+        r#"use std.io
+
+main: () -> Void = () => {{
+    io.println("ready")
+{SPIN_LOOP}}}
+"#
+    );
+    // Act
+    let output = run_and_signal(&source, "TERM");
+    // Assert
+    assert_eq!(output.status.code(), Some(143));
+}
+
 // ============================================================================
 // build 命令 — 字节码编译
 // ============================================================================
@@ -179,7 +300,7 @@ fn test_build_bytecode_with_debug_info_succeeds() {
     let src = write_yx_file(tmp.path(), "debug.yx", "main = { print(1) }");
     let output = tmp.path().join("debug.42");
     // Act
-    let result = build_bytecode_with_options(&src, &output, true);
+    let result = build_bytecode_with_options(&src, &output, true, CompileConfig::new());
     // Assert
     assert!(
         result.is_ok(),
@@ -201,6 +322,38 @@ fn test_build_nonexistent_source_returns_error() {
     assert!(result.is_err(), "build on nonexistent source should fail");
 }
 
+#[test]
+fn test_emit_ir_parses_back() {
+    // Arrange
+    let tmp = temp_dir();
+    let src = write_yx_file(tmp.path(), "ir.yx", "main = { print(1 + 2) }");
+    // Act
+    let ir = emit_ir(&src, CompileConfig::new())
+        .unwrap_or_else(|e| panic!("Failed to emit IR: {:?}", e));
+    // Assert
+    assert!(
+        ir.contains("fn \"main\""),
+        "IR should contain main:\n{}",
+        ir
+    );
+    let module = yaoxiang::middle::ir_text::parse_module(&ir)
+        .unwrap_or_else(|e| panic!("emitted IR should parse: {}\n{}", e, ir));
+    assert_eq!(module.to_string(), ir);
+}
+
+#[cfg(feature = "wasm")]
+#[test]
+fn test_emit_wasm_module() {
+    // Arrange
+    let tmp = temp_dir();
+    let src = write_yx_file(tmp.path(), "wasm.yx", "main = { print(1 + 2) }");
+    // Act
+    let wasm = yaoxiang::emit_wasm(&src, CompileConfig::new())
+        .unwrap_or_else(|e| panic!("Failed to emit wasm: {:?}", e));
+    // Assert
+    assert_eq!(&wasm[..8], b"\0asm\x01\0\0\0");
+}
+
 // ============================================================================
 // eval 命令 — 代码求值
 // ============================================================================
@@ -1,5 +1,6 @@
 #![allow(unused_imports)]
 use yaoxiang::middle::codegen::bytecode::BytecodeFile;
+use yaoxiang::middle::codegen::LazyBytecodeFile;
 use yaoxiang::middle::codegen::CodegenContext;
 use yaoxiang::middle::ModuleIR;
 
@@ -22,6 +23,82 @@ fn test_bytecode_serialization() {
     assert_eq!(buffer[3], 0x43); // C
 }
 
+#[test]
+fn test_bytecode_file_round_trip_checks_size_and_checksum() {
+    let dir = tempfile::tempdir().unwrap();
+    let source = dir.path().join("answer.yx");
+    let output = dir.path().join("answer.yxbc");
+    std::fs::write(
+        &source,
+        "use std.assert\n\nmain = {\n    assert_eq(6 * 7, 42)\n}\n",
+    )
+    .unwrap();
+    yaoxiang::build_bytecode(&source, &output).expect("build should succeed");
+    assert!(yaoxiang::middle::codegen::is_bytecode_path(&output));
+
+    // 文件头：魔数、版本、标志、入口、段数（u16）之后是文件长度与校验和
+    let bytes = std::fs::read(&output).unwrap();
+    let file_size = u32::from_le_bytes(bytes[18..22].try_into().unwrap());
+    let checksum = u32::from_le_bytes(bytes[22..26].try_into().unwrap());
+    assert_eq!(file_size as usize, bytes.len());
+    assert_ne!(checksum, 0);
+
+    let loaded = BytecodeFile::read_from(&mut std::io::Cursor::new(&bytes)).expect("should load");
+    assert_eq!(loaded.header.file_size, file_size);
+    let program = yaoxiang::vm::load(&output).expect("should load lazily");
+    yaoxiang::vm::run(&program).expect("loaded program should run");
+
+    let mut corrupt = bytes.clone();
+    *corrupt.last_mut().unwrap() ^= 0xff;
+    let err = BytecodeFile::read_from(&mut std::io::Cursor::new(&corrupt))
+        .expect_err("corrupt file should be rejected");
+    assert!(err.to_string().contains("checksum"), "{}", err);
+    let err = BytecodeFile::read_from(&mut std::io::Cursor::new(&bytes[..bytes.len() - 1]))
+        .expect_err("truncated file should be rejected");
+    assert!(err.to_string().contains("size"), "{}", err);
+}
+
+#[test]
+fn test_lazy_open_checks_function_bodies_on_load() {
+    let dir = tempfile::tempdir().unwrap();
+    let source = dir.path().join("answer.yx");
+    let output = dir.path().join("answer.yxbc");
+    std::fs::write(
+        &source,
+        "use std.assert\n\nmain = {\n    assert_eq(6 * 7, 42)\n}\n",
+    )
+    .unwrap();
+    yaoxiang::build_bytecode(&source, &output).expect("build should succeed");
+    let bytes = std::fs::read(&output).unwrap();
+
+    // 在文件中定位 main 的函数体并改动其中一个字节
+    let file = BytecodeFile::read_from(&mut std::io::Cursor::new(&bytes)).unwrap();
+    let main = file
+        .code_section
+        .functions
+        .iter()
+        .position(|f| f.name == "main")
+        .expect("main should be compiled");
+    let body = file.code_section.functions[main].encode_all();
+    let at = bytes
+        .windows(body.len())
+        .position(|w| w == body.as_slice())
+        .expect("main body should be in the file");
+    let mut corrupt = bytes.clone();
+    corrupt[at] ^= 0xff;
+
+    // 打开时不读取函数体，损坏只在加载该函数时发现
+    let mut lazy = LazyBytecodeFile::from_reader(std::io::Cursor::new(corrupt.clone()))
+        .expect("open should not read function bodies");
+    let err = lazy
+        .load_function(main)
+        .expect_err("corrupt body should be rejected");
+    assert!(err.to_string().contains("'main' body checksum"), "{}", err);
+    let err = BytecodeFile::read_from(&mut std::io::Cursor::new(&corrupt))
+        .expect_err("eager load checks every body");
+    assert!(err.to_string().contains("'main' body checksum"), "{}", err);
+}
+
 #[test]
 fn test_switch_generation() {
     // TODO: Construct AST for switch and test generation
@@ -31,3 +108,199 @@ fn test_switch_generation() {
 fn test_loop_optimization() {
     // TODO: Construct AST for range loop and test optimization
 }
+
+// ============================================================================
+// 线性扫描寄存器分配
+// ============================================================================
+
+use yaoxiang::middle::codegen::flow::{LinearScanAllocator, Location};
+use yaoxiang::middle::{BasicBlock, ConstValue, FunctionIR, Instruction, Operand};
+use yaoxiang::frontend::core::typecheck::MonoType;
+use yaoxiang::util::span::Span;
+
+fn function(instructions: Vec<Instruction>) -> FunctionIR {
+    FunctionIR {
+        name: "f".to_string(),
+        params: Vec::new(),
+        return_type: MonoType::Int(64),
+        locals: Vec::new(),
+        blocks: vec![BasicBlock {
+            label: 0,
+            instructions,
+            successors: Vec::new(),
+        }],
+        entry: 0,
+        generic_params: None,
+    }
+}
+
+fn load_int(
+    dst: usize,
+    value: i128,
+) -> Instruction {
+    Instruction::Load {
+        dst: Operand::Temp(dst),
+        src: Operand::Const(ConstValue::Int(value)),
+    }
+}
+
+/// `count` 个同时活跃的值，最后依次相加
+fn all_live(count: usize) -> FunctionIR {
+    let mut code: Vec<Instruction> = (0..count).map(|v| load_int(v, v as i128)).collect();
+    for v in 1..count {
+        code.push(Instruction::Add {
+            dst: Operand::Temp(0),
+            lhs: Operand::Temp(0),
+            rhs: Operand::Temp(v),
+            span: Span::dummy(),
+        });
+    }
+    code.push(Instruction::Ret(Some(Operand::Temp(0))));
+    function(code)
+}
+
+#[test]
+fn test_linear_scan_reuses_dead_registers() {
+    // 每个值只活到下一条指令，区间互不重叠的值共用寄存器
+    let mut code = vec![load_int(0, 1)];
+    for v in 1..300 {
+        code.push(Instruction::Add {
+            dst: Operand::Temp(v),
+            lhs: Operand::Temp(v - 1),
+            rhs: Operand::Temp(v - 1),
+            span: Span::dummy(),
+        });
+    }
+    code.push(Instruction::Ret(Some(Operand::Temp(299))));
+    let allocation = LinearScanAllocator::new()
+        .allocate(&function(code))
+        .expect("allocation should succeed");
+    assert_eq!(allocation.spilled_count(), 0);
+    assert!(allocation.register_count() <= 2);
+}
+
+#[test]
+fn test_linear_scan_spills_when_registers_run_out() {
+    let func = all_live(10);
+    let allocation = LinearScanAllocator::with_registers(4)
+        .allocate(&func)
+        .expect("allocation should succeed");
+    assert!(allocation.spilled_count() > 0);
+    assert!(allocation.register_count() <= 4);
+    for v in 0..10 {
+        match allocation.location(v).expect("every value has a location") {
+            Location::Register(r) => assert!(r < 4),
+            Location::Spill(slot) => assert!((slot as usize) < allocation.slot_count()),
+        }
+    }
+}
+
+#[test]
+fn test_linear_scan_keeps_loop_carried_values_live() {
+    // t0 在循环体之前定值、循环体中使用，跨回边一直活跃
+    let func = function(vec![
+        load_int(0, 7),
+        load_int(1, 0),
+        Instruction::Lt {
+            dst: Operand::Temp(2),
+            lhs: Operand::Temp(1),
+            rhs: Operand::Temp(0),
+        },
+        Instruction::JmpIfNot(Operand::Temp(2), 7),
+        load_int(3, 1),
+        Instruction::Add {
+            dst: Operand::Temp(1),
+            lhs: Operand::Temp(1),
+            rhs: Operand::Temp(3),
+            span: Span::dummy(),
+        },
+        Instruction::Jmp(2),
+        Instruction::Ret(Some(Operand::Temp(1))),
+    ]);
+    let allocation = LinearScanAllocator::new()
+        .allocate(&func)
+        .expect("allocation should succeed");
+    // 循环体内定值的 t3 不能占用 t0 的寄存器
+    assert_ne!(allocation.location(0), allocation.location(3));
+    assert_ne!(allocation.location(1), allocation.location(3));
+}
+
+#[test]
+fn test_spilled_values_are_reloaded_through_locals() {
+    let func = all_live(300);
+    let mut ctx = CodegenContext::new(ModuleIR {
+        functions: vec![func],
+        ..Default::default()
+    });
+    let bytecode = ctx
+        .generate()
+        .expect("codegen should spill instead of failing");
+    let code = &bytecode.code_section.functions[0];
+    assert!(code.local_count > 0);
+    assert!(code
+        .instructions
+        .iter()
+        .any(|i| i.opcode == yaoxiang::backends::common::Opcode::StoreLocal as u8));
+}
+
+fn jump_offset(instr: &yaoxiang::middle::codegen::BytecodeInstruction) -> i64 {
+    let cond = usize::from(instr.opcode != yaoxiang::backends::common::Opcode::Jmp as u8);
+    match &instr.operands[cond..] {
+        [lo, hi] => i16::from_le_bytes([*lo, *hi]) as i64,
+        [b0, b1, b2, b3] => i32::from_le_bytes([*b0, *b1, *b2, *b3]) as i64,
+        other => panic!("unexpected jump operands {other:?}"),
+    }
+}
+
+/// 跳过 `skipped` 条指令后返回的函数：`jmp end; <skipped>; end: ret`
+fn forward_jump(skipped: usize) -> FunctionIR {
+    let mut code = vec![Instruction::Jmp(skipped + 1)];
+    code.extend((0..skipped).map(|_| load_int(0, 1)));
+    code.push(Instruction::Ret(None));
+    function(code)
+}
+
+#[test]
+fn test_short_jump_patches_byte_offset() {
+    let mut ctx = CodegenContext::new(ModuleIR {
+        functions: vec![forward_jump(3)],
+        ..Default::default()
+    });
+    let bytecode = ctx.generate().unwrap();
+    let code = &bytecode.code_section.functions[0];
+    let jmp = &code.instructions[0];
+    assert_eq!(jmp.operands.len(), 2, "short jump uses a 2-byte offset");
+    let skipped: usize = code.instructions[..4]
+        .iter()
+        .map(|i| i.encoded_size())
+        .sum();
+    assert_eq!(jump_offset(jmp), skipped as i64);
+}
+
+#[test]
+fn test_long_jump_over_large_body() {
+    // 足够多的指令让前向偏移超过 i16
+    let mut ctx = CodegenContext::new(ModuleIR {
+        functions: vec![forward_jump(8000)],
+        ..Default::default()
+    });
+    let bytecode = ctx.generate().unwrap();
+    let code = &bytecode.code_section.functions[0];
+    let jmp = &code.instructions[0];
+    assert_eq!(jmp.operands.len(), 4, "jump widened to a 4-byte offset");
+    let skipped: usize = code.instructions[..code.instructions.len() - 1]
+        .iter()
+        .map(|i| i.encoded_size())
+        .sum();
+    assert!(skipped > i16::MAX as usize);
+    assert_eq!(jump_offset(jmp), skipped as i64);
+
+    // 解码后跳转落在 ret 上
+    let decoded = yaoxiang::middle::bytecode::BytecodeFunction::decode(code.clone(), &[]);
+    match decoded.instructions[0] {
+        yaoxiang::middle::bytecode::BytecodeInstr::Jmp { target } => {
+            assert_eq!(target.0 as i32 as usize, decoded.instructions.len() - 1);
+        }
+        ref other => panic!("expected Jmp, got {other:?}"),
+    }
+}
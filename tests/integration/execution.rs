@@ -83,6 +83,32 @@ fn test_counter_loop() {
     );
 }
 
+#[test]
+fn test_nested_loops_with_branches() {
+    run_ok(
+        r#"
+        use std.assert
+        main = {
+            mut total = 0
+            mut i = 0
+            while i < 10 {
+                mut j = 0
+                while j < i {
+                    if j % 2 == 0 {
+                        total = total + j
+                    } else {
+                        total = total - 1
+                    }
+                    j = j + 1
+                }
+                i = i + 1
+            }
+            assert_eq(total, 40)
+        }
+        "#,
+    );
+}
+
 #[test]
 fn test_match_simple() {
     run_ok(
@@ -113,3 +139,87 @@ fn test_list_operations() {
         "#,
     );
 }
+
+// ============================================================================
+// 退出码
+// ============================================================================
+
+fn exit_code(source: &str) -> i32 {
+    run(source).unwrap_or_else(|e| panic!("Execution failed:\n{:?}", e))
+}
+
+#[test]
+fn test_exit_code_defaults_to_zero() {
+    assert_eq!(exit_code("main = { print(1) }"), 0);
+}
+
+#[test]
+fn test_exit_code_from_main_return_value() {
+    assert_eq!(
+        exit_code(
+            r#"
+            main: () -> Int = () => {
+                return 7
+            }
+            "#,
+        ),
+        7
+    );
+}
+
+#[test]
+fn test_exit_code_from_process_exit_unwinds_nested_calls() {
+    assert_eq!(
+        exit_code(
+            r#"
+            use std.process
+            helper: () -> Void = () => {
+                process.exit(3)
+                print("unreachable")
+            }
+            main = {
+                helper()
+                process.exit(9)
+            }
+            "#,
+        ),
+        3
+    );
+}
+
+#[test]
+fn test_function_with_more_than_256_values() {
+    // 300 个局部变量，嵌套加法让 300 个中间值同时活跃，超出寄存器数时溢出到局部变量槽
+    let count = 300;
+    let decls: String = (0..count)
+        .map(|i| format!("    v{i} = n + {i}\n"))
+        .collect();
+    let sum = (1..count).fold("v0".to_string(), |acc, i| format!("{acc} + (v{i}"))
+        + &")".repeat(count - 1);
+    let expected = count + count * (count - 1) / 2;
+    run_ok(&format!(
+        "use std.assert\n\nwide: (n: Int) -> Int = (n) => {{\n{decls}    return {sum}\n}}\n\nmain = {{\n    assert_eq(wide(1), {expected})\n}}\n"
+    ));
+}
+
+#[test]
+fn test_generic_calls_specialize_for_float_and_string() {
+    // 泛型函数体内的调用按所在函数的类型实参特化，多个类型参数分别推断
+    run_ok(
+        r#"
+        use std.assert
+        identity: (T: Type) -> ((x: T) -> T) = (x) => x
+        pick: (A: Type, B: Type) -> ((a: A, b: B) -> B) = (a, b) => b
+        wrap: (T: Type) -> ((x: T) -> T) = (x) => {
+            y = identity(x)
+            return pick(1, identity(y))
+        }
+        main = {
+            assert_eq(pick(1, 2.5), 2.5)
+            assert_eq(wrap(3.5), 3.5)
+            assert_eq(wrap("w"), "w")
+            assert_eq(pick("a", "b"), "b")
+        }
+        "#,
+    );
+}
@@ -0,0 +1,387 @@
+//! 稳定公共 API 集成测试
+//!
+//! 只通过 `yaoxiang::{compile, ast, diagnostics, vm, Engine, Session}` 访问编译器，
+//! 内部模块重构不应让这里的代码失效。
+
+use yaoxiang::ast::{self, StmtKind};
+use yaoxiang::compile::{CompileError, OptLevel};
+use yaoxiang::diagnostics::{self, Severity};
+use yaoxiang::vm::{
+    self, BuildMode, Executor, ExecutorConfig, ExecutorError, NativeContext, PanicKind,
+    RuntimePanic, RuntimeValue,
+};
+use yaoxiang::{CapabilityPolicy, Engine};
+
+#[test]
+fn test_compile_and_run() {
+    let program = yaoxiang::compile("ok.yx", "main = { x = 1 + 2 }").expect("应能编译");
+    assert!(vm::run(&program).is_ok());
+}
+
+#[test]
+fn test_run_with_release_config() {
+    let program = yaoxiang::compile::compile("ok.yx", "main = { x = 1 }").unwrap();
+    let config = ExecutorConfig {
+        build_mode: BuildMode::Release,
+        ..Default::default()
+    };
+    assert!(vm::run_with_config(&program, config).is_ok());
+}
+
+#[test]
+fn test_parse_returns_module() {
+    let module = ast::parse("main = { x = 1 }").expect("应能解析");
+    assert!(module
+        .items
+        .iter()
+        .any(|stmt| matches!(&stmt.kind, StmtKind::Binding { name, .. } if name == "main")));
+}
+
+#[test]
+fn test_parse_error_has_diagnostic() {
+    let err = ast::parse("main = {").unwrap_err();
+    assert!(matches!(err, CompileError::Parse(_)));
+    assert_eq!(err.diagnostic().unwrap().severity, Severity::Error);
+}
+
+#[test]
+fn test_type_error_renders_with_source() {
+    let source = "main = { x: Int = \"s\" }";
+    let err = yaoxiang::compile("bad.yx", source).unwrap_err();
+    let diagnostic = err.diagnostic().expect("类型错误应带诊断");
+    let rendered = diagnostics::render(diagnostic, "bad.yx", source);
+    assert!(rendered.contains(&diagnostic.code), "{}", rendered);
+}
+
+#[test]
+fn test_engine_compile_and_call() {
+    let engine = Engine::builder()
+        .max_stack_depth(256)
+        .capabilities(CapabilityPolicy::deny_all())
+        .build();
+    assert_eq!(engine.executor_config().max_stack_depth, 256);
+    assert!(!engine.executor_config().capabilities.dynamic_import);
+
+    let program = engine
+        .compile("lib.yx", "add: (a: Int, b: Int) -> Int = (a, b) => a + b\n")
+        .unwrap();
+    let sum = engine
+        .call(
+            &program,
+            "add",
+            &[RuntimeValue::Int(1), RuntimeValue::Int(2)],
+        )
+        .unwrap();
+    assert_eq!(sum, RuntimeValue::Int(3));
+    assert!(matches!(
+        engine.call(&program, "missing", &[]),
+        Err(ExecutorError::FunctionNotFound(..))
+    ));
+}
+
+#[test]
+fn test_engine_eval_wraps_main() {
+    let engine = Engine::default();
+    assert_eq!(engine.eval("x = 1 + 2\n").unwrap(), 0);
+    assert_eq!(
+        engine
+            .eval("use std.process\n\nmain = {\n    process.exit(7)\n}\n")
+            .unwrap(),
+        7
+    );
+    assert!(engine.eval("x: Int = \"s\"\n").is_err());
+}
+
+#[test]
+fn test_session_keeps_variables_and_definitions() {
+    let mut session = Engine::default().session();
+    assert_eq!(session.eval("x = 40").unwrap(), RuntimeValue::Unit);
+    assert_eq!(session.get("x"), Some(&RuntimeValue::Int(40)));
+    session
+        .eval("double: (n: Int) -> Int = (n) => n * 2")
+        .unwrap();
+    session.eval("Point: Type = { x: Int, y: Int }").unwrap();
+    assert_eq!(
+        session.eval("double(x) + 2").unwrap(),
+        RuntimeValue::Int(82)
+    );
+    assert_eq!(
+        session.eval("p = Point(1, x)\np.y + double(p.x)").unwrap(),
+        RuntimeValue::Int(42)
+    );
+    // 重新赋值替换旧值，之后的输入看到新值
+    session.eval("x = x + 1").unwrap();
+    assert_eq!(session.eval("x").unwrap(), RuntimeValue::Int(41));
+    let names: Vec<_> = session
+        .variables()
+        .iter()
+        .map(|(n, _)| n.as_str())
+        .collect();
+    assert_eq!(names, ["x", "p"]);
+    assert!(session.functions().iter().any(|(n, _)| n == "double"));
+}
+
+#[test]
+fn test_session_failed_input_leaves_session_unchanged() {
+    let mut session = Engine::default().session();
+    session.eval("x = 1").unwrap();
+    assert!(session.eval("y = x + \"s\"").is_err());
+    assert!(session.eval("y").is_err());
+    assert!(session.eval("z = 1 / 0").is_err());
+    assert!(session.get("z").is_none());
+    assert_eq!(session.eval("x").unwrap(), RuntimeValue::Int(1));
+}
+
+#[test]
+fn test_engine_enforces_stack_limit() {
+    let source = "\
+depth: (n: Int) -> Int = (n) => {
+    if n == 0 {
+        return 0
+    }
+    return 1 + depth(n - 1)
+}
+";
+    let program = Engine::default().compile("deep.yx", source).unwrap();
+    let arg = [RuntimeValue::Int(50)];
+    assert_eq!(
+        Engine::default().call(&program, "depth", &arg).unwrap(),
+        RuntimeValue::Int(50)
+    );
+    let shallow = Engine::builder().max_stack_depth(8).build();
+    assert!(matches!(
+        shallow.call(&program, "depth", &arg),
+        Err(ExecutorError::StackOverflow(..))
+    ));
+}
+
+#[test]
+fn test_runtime_panic_traces_source_lines() {
+    let source = "\
+ratio: (a: Int, b: Int) -> Int = (a, b) => {
+    return a / b
+}
+
+scaled: (n: Int) -> Int = (n) => {
+    return 10 * ratio(n, 0)
+}
+";
+    // 不内联，保留两层调用
+    let engine = Engine::builder().opt_level(OptLevel::O0).build();
+    let program = engine.compile("ratio.yx", source).unwrap();
+    let err = engine
+        .call(&program, "scaled", &[RuntimeValue::Int(3)])
+        .unwrap_err();
+    let panic = RuntimePanic::new(err, &program);
+
+    assert_eq!(panic.kind(), PanicKind::DivisionByZero);
+    let names: Vec<&str> = panic.trace().iter().map(|f| f.function.as_str()).collect();
+    assert_eq!(names, ["ratio", "scaled"]);
+    let location = panic.location().expect("line table");
+    assert_eq!(location.file.as_deref(), Some("ratio.yx"));
+    assert_eq!(location.line, 2);
+    assert_eq!(panic.trace()[1].location.as_ref().map(|l| l.line), Some(6));
+
+    let text = panic.to_string();
+    assert!(!text.contains('\x1b'), "{}", text);
+    assert!(text.contains("ratio.yx:2"), "{}", text);
+    assert!(text.contains("at scaled (ratio.yx:6:"), "{}", text);
+
+    let run = || -> yaoxiang::Result<RuntimeValue> {
+        let value = engine
+            .call(&program, "scaled", &[RuntimeValue::Int(3)])
+            .map_err(|e| RuntimePanic::new(e, &program))?;
+        Ok(value)
+    };
+    let error = run().unwrap_err();
+    let panic = error.downcast_ref::<RuntimePanic>().expect("RuntimePanic");
+    assert!(matches!(panic.error(), ExecutorError::DivisionByZero(_)));
+}
+
+#[test]
+fn test_try_and_recover() {
+    let source = "\
+use std.result
+use std.process
+
+half: (n: Int) -> Result(Int, String) = (n) => {
+    if n % 2 == 1 {
+        return err(\"odd\")
+    }
+    return n / 2
+}
+
+quarter: (n: Int) -> Result(Int, String) = (n) => {
+    h = half(n)?
+    return half(h)?
+}
+
+divide: (a: Int, b: Int) -> Int = (a, b) => a / b
+
+safe_divide: (a: Int, b: Int) -> Result(Int, Error) = (a, b) => recover(() => divide(a, b))
+
+leave: () -> Result(Int, Error) = () => recover(() => process.exit(3))
+";
+    let engine = Engine::default();
+    let program = engine.compile("result.yx", source).unwrap();
+    let call = |name: &str, args: &[i64]| {
+        let args: Vec<RuntimeValue> = args.iter().map(|&n| RuntimeValue::Int(n)).collect();
+        engine.call(&program, name, &args)
+    };
+    let variant = |value: RuntimeValue| match value {
+        RuntimeValue::Enum {
+            variant_id,
+            payload,
+            ..
+        } => (variant_id, *payload),
+        other => panic!("应返回 Result，实际: {other:?}"),
+    };
+
+    assert_eq!(
+        variant(call("quarter", &[12]).unwrap()),
+        (0, RuntimeValue::Int(3))
+    );
+    // 第二次 `?` 遇到 Err，原样返回
+    assert_eq!(
+        variant(call("quarter", &[6]).unwrap()),
+        (1, RuntimeValue::String("odd".into()))
+    );
+    assert_eq!(
+        variant(call("safe_divide", &[6, 3]).unwrap()),
+        (0, RuntimeValue::Int(2))
+    );
+    assert_eq!(variant(call("safe_divide", &[1, 0]).unwrap()).0, 1);
+    // 退出请求不是 panic，不被 recover 捕获
+    assert!(matches!(call("leave", &[]), Err(ExecutorError::Exit(3))));
+}
+
+#[test]
+fn test_engine_std_module_selection() {
+    let engine = Engine::builder().std_modules(["math", "assert"]).build();
+    assert!(engine
+        .compile("ok.yx", "use std.math\n\nmain = {\n    x = 1\n}\n")
+        .is_ok());
+
+    let source = "use std.math\nuse std.io\n\nmain = {\n    x = 1\n}\n";
+    let err = engine.compile("io.yx", source).unwrap_err();
+    let diagnostic = err.diagnostic().expect("应带诊断");
+    assert_eq!(diagnostic.code, "E5002");
+    let span = diagnostic.span.expect("应指向导入语句");
+    assert_eq!((span.start.line, span.start.column), (2, 5));
+
+    // 未选择的模块在虚拟机中也不可用
+    let interpreter = engine.interpreter();
+    assert!(interpreter.ffi_registry().has("std.math.abs"));
+    assert!(!interpreter.ffi_registry().has("std.io.println"));
+    assert!(Engine::default()
+        .interpreter()
+        .ffi_registry()
+        .has("std.io.println"));
+}
+
+fn host_triple(
+    args: &[RuntimeValue],
+    _ctx: &mut NativeContext<'_>,
+) -> Result<RuntimeValue, ExecutorError> {
+    match args {
+        [RuntimeValue::Int(n)] => Ok(RuntimeValue::Int(n * 3)),
+        _ => Err(ExecutorError::type_only("host_triple expects an Int")),
+    }
+}
+
+#[test]
+fn test_engine_host_functions() {
+    let engine = Engine::builder()
+        .host_function("host.triple", host_triple)
+        .build();
+    let source = "\
+triple: (n: Int) -> Int = Native.rs(\"host.triple\")
+
+nine: () -> Int = () => triple(3)
+";
+    let program = engine.compile("host.yx", source).unwrap();
+    assert_eq!(
+        engine.call(&program, "nine", &[]).unwrap(),
+        RuntimeValue::Int(9)
+    );
+    // 未注册宿主函数的引擎无法调用
+    assert!(Engine::default().call(&program, "nine", &[]).is_err());
+}
+
+#[test]
+fn test_engine_registers_host_closures() {
+    use std::sync::atomic::{AtomicI64, Ordering};
+    use std::sync::Arc;
+
+    let calls = Arc::new(AtomicI64::new(0));
+    let counted = calls.clone();
+    let engine = Engine::builder()
+        .register_native("app.tick", move |_args| {
+            Ok(RuntimeValue::Int(
+                counted.fetch_add(1, Ordering::SeqCst) + 1,
+            ))
+        })
+        .register_fn("app.scale", |x: f64, factor: i64| x * factor as f64)
+        .build();
+    let source = "\
+tick: () -> Int = Native.rs(\"app.tick\")
+scale: (x: Float, factor: Int) -> Float = Native.rs(\"app.scale\")
+
+twice: () -> Int = () => tick() + tick()
+half: () -> Float = () => scale(0.25, 2)
+";
+    let program = engine.compile("app.yx", source).unwrap();
+    assert_eq!(
+        engine.call(&program, "twice", &[]).unwrap(),
+        RuntimeValue::Int(3)
+    );
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+    assert_eq!(
+        engine.call(&program, "half", &[]).unwrap(),
+        RuntimeValue::Float(0.5)
+    );
+}
+
+#[test]
+fn test_typed_call_into_script() {
+    let source = "\
+area: (w: Float, h: Int) -> Float = (w, h) => {
+    if h > 1 {
+        return w * 4.0
+    }
+    return w
+}
+greet: (name: String) -> String = (name) => name + \"!\"
+count: () -> Int = () => 3
+";
+    let engine = Engine::default();
+    let program = engine.compile("typed.yx", source).unwrap();
+    let area: f64 = engine.call_typed(&program, "area", (2.5, 4_i64)).unwrap();
+    assert_eq!(area, 10.0);
+    let greeting: String = engine.call_typed(&program, "greet", ("yao",)).unwrap();
+    assert_eq!(greeting, "yao!");
+
+    let mut interpreter = engine.interpreter();
+    let mut loaded = program.clone();
+    loaded.entry_point = None;
+    interpreter.execute_module(&loaded).unwrap();
+    assert_eq!(interpreter.call::<_, i64>("count", ()).unwrap(), 3);
+
+    let message = |err: ExecutorError| match err {
+        ExecutorError::Type(msg, _) => msg,
+        other => panic!("应为类型错误，实际: {other:?}"),
+    };
+    let err = interpreter.call::<_, f64>("area", (2.5,)).unwrap_err();
+    assert_eq!(message(err), "area expects 2 argument(s), got 1");
+    let err = interpreter.call::<_, f64>("area", (2.5, "4")).unwrap_err();
+    assert_eq!(
+        message(err),
+        "argument 2 of area: expected Int, found String"
+    );
+    let err = interpreter.call::<_, String>("count", ()).unwrap_err();
+    assert_eq!(message(err), "result of count: expected String, found Int");
+    assert!(matches!(
+        interpreter.call::<_, i64>("missing", ()),
+        Err(ExecutorError::FunctionNotFound(..))
+    ));
+}
@@ -8,7 +8,7 @@ use yaoxiang::run;
 fn run_yx_file(path: &str) -> Result<(), String> {
     let source =
         std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
-    run(&source).map_err(|e| format!("{:?}", e))
+    run(&source).map(|_| ()).map_err(|e| format!("{:?}", e))
 }
 
 #[test]
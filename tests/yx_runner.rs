@@ -146,6 +146,32 @@ fn test_all_yx_files_pass() {
     }
 }
 
+/// Run the concurrency tests again on the work-stealing executor.
+#[test]
+fn test_concurrency_files_pass_with_threads() {
+    let files: Vec<PathBuf> = discover_yx_tests()
+        .into_iter()
+        .filter(|f| f.components().any(|c| c.as_os_str() == "04-concurrency"))
+        .collect();
+    assert!(!files.is_empty(), "No concurrency .yx test files found!");
+
+    let binary = binary_name();
+    for file in &files {
+        let output = Command::new(&binary)
+            .args(["run", "--threads", "2"])
+            .arg(file)
+            .output()
+            .unwrap_or_else(|e| panic!("Failed to run {binary} for {file:?}: {e}"));
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(
+            stdout.contains("ALL TESTS PASSED"),
+            "{file:?} failed with --threads 2\nSTDOUT:\n{stdout}\nSTDERR:\n{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+}
+
 /// Verify that each `.yx` file contains the required metadata header.
 #[test]
 fn test_yx_file_headers() {